@@ -0,0 +1,255 @@
+//! `glowback backtest`: run a backtest manifest and report on saved results.
+
+use chrono::{DateTime, Utc};
+use clap::{Args, Subcommand};
+use gb_engine::ipc::{write_equity_curve_ipc, write_trade_log_ipc};
+use gb_engine::{uses_explicit_sample_data_source, BacktestEngine};
+use gb_optimizer::builtin_strategy;
+use gb_types::{
+    BacktestConfig, BacktestEvent, BacktestId, BacktestResult, BacktestStatus, PerformanceMetrics,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::error::{CliError, CliResult};
+use crate::{progress, GlobalOptions};
+
+/// File in a results directory that `backtest report` reads.
+pub const SUMMARY_FILE: &str = "summary.json";
+
+#[derive(Debug, Subcommand)]
+pub enum BacktestCommand {
+    /// Run the backtest described by a TOML or YAML manifest.
+    Run(RunArgs),
+    /// Print the metrics of a saved result.
+    Report(ReportArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Backtest manifest (`.toml`, `.yaml` or `.yml`).
+    #[arg(long)]
+    pub manifest: PathBuf,
+
+    /// Directory to write the summary, manifest, equity curve and trade log
+    /// to.
+    #[arg(long, default_value = "results")]
+    pub out: PathBuf,
+}
+
+#[derive(Debug, Args)]
+pub struct ReportArgs {
+    /// Results directory written by `backtest run`, or its summary file.
+    pub result: PathBuf,
+}
+
+/// What `backtest run` records about a finished backtest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunSummary {
+    pub backtest_id: BacktestId,
+    pub name: String,
+    pub strategy_id: String,
+    pub status: BacktestStatus,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub initial_capital: Decimal,
+    pub final_value: Option<Decimal>,
+    pub duration_seconds: Option<u64>,
+    pub equity_points: usize,
+    pub trades: usize,
+    pub performance_metrics: Option<PerformanceMetrics>,
+    pub error: Option<String>,
+}
+
+impl From<&BacktestResult> for RunSummary {
+    fn from(result: &BacktestResult) -> Self {
+        Self {
+            backtest_id: result.id,
+            name: result.config.name.clone(),
+            strategy_id: result.config.strategy_config.strategy_id.clone(),
+            status: result.status,
+            start_date: result.config.start_date,
+            end_date: result.config.end_date,
+            initial_capital: result.config.initial_capital,
+            final_value: result
+                .equity_curve
+                .last()
+                .map(|point| point.portfolio_value),
+            duration_seconds: result.duration_seconds,
+            equity_points: result.equity_curve.len(),
+            trades: result.trade_log.len(),
+            performance_metrics: result.performance_metrics.clone(),
+            error: result.error_message.clone(),
+        }
+    }
+}
+
+pub async fn run(command: BacktestCommand, options: &GlobalOptions) -> CliResult {
+    match command {
+        BacktestCommand::Run(args) => run_backtest(args, options).await,
+        BacktestCommand::Report(args) => report(&args.result),
+    }
+}
+
+async fn run_backtest(args: RunArgs, options: &GlobalOptions) -> CliResult {
+    let config = BacktestConfig::from_manifest_file(&args.manifest).map_err(|e| {
+        CliError::config(format!("invalid manifest {}: {e}", args.manifest.display()))
+    })?;
+    let strategy = builtin_strategy(&config.strategy_config).map_err(CliError::config)?;
+
+    let mut engine = if uses_explicit_sample_data_source(&config) {
+        BacktestEngine::new(config)
+            .await
+            .map_err(|e| CliError::runtime(format!("failed to start engine: {e}")))?
+    } else {
+        BacktestEngine::with_data_manager(config, options.data_manager().await?)
+    };
+
+    let bar = progress::percent("Backtesting", options.quiet);
+    let mut events = engine.subscribe();
+    let progress_bar = bar.clone();
+    let progress = tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(BacktestEvent::Progress {
+                    progress_pct,
+                    current_date,
+                    ..
+                }) => {
+                    progress_bar.set_position(progress_pct.clamp(0.0, 100.0) as u64);
+                    progress_bar.set_message(current_date.date_naive().to_string());
+                }
+                Ok(BacktestEvent::TradeExecuted { trade, .. }) => {
+                    progress_bar.set_message(format!("{} {:?}", trade.symbol, trade.side));
+                }
+                Ok(BacktestEvent::Completed { .. } | BacktestEvent::Failed { .. })
+                | Err(RecvError::Closed) => break,
+                Ok(_) | Err(RecvError::Lagged(_)) => {}
+            }
+        }
+    });
+
+    let outcome = engine.run_with_strategy(strategy).await;
+    // Dropping the engine closes the event channel, ending the progress
+    // task even when the run failed before emitting any event.
+    drop(engine);
+    let _ = progress.await;
+    bar.finish_and_clear();
+    let result = outcome.map_err(|e| CliError::runtime(format!("backtest failed: {e}")))?;
+
+    let summary = RunSummary::from(&result);
+    write_results(&args.out, &result, &summary)?;
+    print_summary(&summary);
+    println!();
+    println!("Results written to {}", args.out.display());
+
+    match &result.error_message {
+        Some(error) => Err(CliError::runtime(format!("backtest failed: {error}"))),
+        None => Ok(()),
+    }
+}
+
+fn write_results(out: &Path, result: &BacktestResult, summary: &RunSummary) -> CliResult {
+    let failed = |e: &dyn std::fmt::Display| {
+        CliError::runtime(format!("failed to write results to {}: {e}", out.display()))
+    };
+    std::fs::create_dir_all(out).map_err(|e| failed(&e))?;
+
+    let json = serde_json::to_string_pretty(summary).map_err(|e| failed(&e))?;
+    std::fs::write(out.join(SUMMARY_FILE), json).map_err(|e| failed(&e))?;
+    result
+        .write_config_manifest(out.join("config.toml"))
+        .map_err(|e| failed(&e))?;
+    let equity = write_equity_curve_ipc(&result.equity_curve).map_err(|e| failed(&e))?;
+    std::fs::write(out.join("equity_curve.arrow"), equity).map_err(|e| failed(&e))?;
+    let trades = write_trade_log_ipc(&result.trade_log).map_err(|e| failed(&e))?;
+    std::fs::write(out.join("trades.arrow"), trades).map_err(|e| failed(&e))?;
+    Ok(())
+}
+
+fn report(path: &Path) -> CliResult {
+    let file = if path.is_dir() {
+        path.join(SUMMARY_FILE)
+    } else {
+        path.to_path_buf()
+    };
+    let text = std::fs::read_to_string(&file)
+        .map_err(|e| CliError::config(format!("cannot read {}: {e}", file.display())))?;
+    let summary: RunSummary = serde_json::from_str(&text)
+        .map_err(|e| CliError::config(format!("invalid result {}: {e}", file.display())))?;
+    print_summary(&summary);
+    Ok(())
+}
+
+fn print_summary(summary: &RunSummary) {
+    let mut rows = vec![
+        (
+            "Backtest",
+            format!("{} ({})", summary.name, summary.backtest_id),
+        ),
+        ("Strategy", summary.strategy_id.clone()),
+        (
+            "Period",
+            format!(
+                "{} to {}",
+                summary.start_date.date_naive(),
+                summary.end_date.date_naive()
+            ),
+        ),
+        ("Status", format!("{:?}", summary.status)),
+        (
+            "Initial capital",
+            summary.initial_capital.round_dp(2).to_string(),
+        ),
+        (
+            "Final value",
+            optional(summary.final_value.map(|v| v.round_dp(2))),
+        ),
+        ("Trades", summary.trades.to_string()),
+    ];
+    if let Some(metrics) = &summary.performance_metrics {
+        rows.extend([
+            ("Total return", percent(Some(metrics.total_return))),
+            (
+                "Annualized return",
+                percent(Some(metrics.annualized_return)),
+            ),
+            ("Volatility", percent(Some(metrics.volatility))),
+            ("Sharpe ratio", ratio(metrics.sharpe_ratio)),
+            ("Sortino ratio", ratio(metrics.sortino_ratio)),
+            ("Calmar ratio", ratio(metrics.calmar_ratio)),
+            ("Max drawdown", percent(Some(metrics.max_drawdown))),
+            (
+                "Max drawdown days",
+                optional(metrics.max_drawdown_duration_days),
+            ),
+            ("Win rate", percent(Some(metrics.win_rate))),
+            ("Profit factor", ratio(metrics.profit_factor)),
+            (
+                "Commissions",
+                metrics.total_commissions.round_dp(2).to_string(),
+            ),
+        ]);
+    }
+    if let Some(error) = &summary.error {
+        rows.push(("Error", error.clone()));
+    }
+
+    for (label, value) in rows {
+        println!("{label:<20} {value}");
+    }
+}
+
+fn percent(value: Option<Decimal>) -> String {
+    optional(value.map(|v| format!("{}%", (v * Decimal::ONE_HUNDRED).round_dp(2))))
+}
+
+fn ratio(value: Option<Decimal>) -> String {
+    optional(value.map(|v| v.round_dp(3)))
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| v.to_string())
+}
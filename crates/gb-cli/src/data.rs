@@ -0,0 +1,288 @@
+//! `glowback data`: ingest CSV files, prefetch a universe from a provider
+//! and summarize what is stored.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use clap::{Args, Subcommand, ValueEnum};
+use gb_data::{CsvDataProvider, DataProvider, SampleDataProvider};
+use gb_types::{parse_asset_class, parse_symbol_shorthand, AssetClass, Resolution, Symbol};
+use std::path::{Path, PathBuf};
+
+use crate::error::{CliError, CliResult};
+use crate::{parse_resolution, progress, GlobalOptions};
+
+#[derive(Debug, Subcommand)]
+pub enum DataCommand {
+    /// Store every `TICKER_RESOLUTION.csv` file in a directory, e.g.
+    /// `AAPL_1d.csv`; files without a resolution suffix are daily bars.
+    Ingest(IngestArgs),
+    /// Fetch bars for a list of symbols from a provider into local storage.
+    Prefetch(PrefetchArgs),
+    /// Summarize the local catalog and storage.
+    Stats,
+}
+
+#[derive(Debug, Args)]
+pub struct IngestArgs {
+    /// Directory of CSV files with date, open, high, low, close and volume
+    /// columns.
+    pub dir: PathBuf,
+
+    /// Asset class of the ingested symbols.
+    #[arg(long, default_value = "equity", value_parser = parse_asset_class)]
+    pub asset_class: AssetClass,
+
+    /// Exchange of the ingested symbols; defaults to the asset class's
+    /// default exchange.
+    #[arg(long)]
+    pub exchange: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct PrefetchArgs {
+    /// Comma-separated symbols in manifest shorthand,
+    /// `TICKER[@EXCHANGE][:asset_class]`.
+    #[arg(long, required = true, value_delimiter = ',', value_parser = parse_symbol_shorthand)]
+    pub universe: Vec<Symbol>,
+
+    /// First day to fetch (`YYYY-MM-DD` or RFC 3339); defaults to a year
+    /// before `--end`.
+    #[arg(long, value_parser = parse_date)]
+    pub start: Option<DateTime<Utc>>,
+
+    /// Last day to fetch; defaults to now.
+    #[arg(long, value_parser = parse_date)]
+    pub end: Option<DateTime<Utc>>,
+
+    #[arg(long, default_value = "1d", value_parser = parse_resolution)]
+    pub resolution: Resolution,
+
+    /// Provider to fetch bars from.
+    #[arg(long, value_enum, default_value_t = Source::Sample)]
+    pub source: Source,
+
+    /// CSV directory for `--source csv`.
+    #[arg(long, required_if_eq("source", "csv"))]
+    pub csv_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Source {
+    /// Synthetic sample bars.
+    Sample,
+    /// `TICKER_RESOLUTION.csv` files in `--csv-dir`.
+    Csv,
+}
+
+pub async fn run(command: DataCommand, options: &GlobalOptions) -> CliResult {
+    match command {
+        DataCommand::Ingest(args) => ingest(args, options).await,
+        DataCommand::Prefetch(args) => prefetch(args, options).await,
+        DataCommand::Stats => stats(options).await,
+    }
+}
+
+/// Parse a `YYYY-MM-DD` date as midnight UTC, or an RFC 3339 timestamp.
+pub fn parse_date(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|_| format!("invalid date '{value}'; expected YYYY-MM-DD or RFC 3339"))
+}
+
+async fn ingest(args: IngestArgs, options: &GlobalOptions) -> CliResult {
+    let files = csv_files(&args.dir)?;
+    if files.is_empty() {
+        return Err(CliError::config(format!(
+            "no CSV files found in {}",
+            args.dir.display()
+        )));
+    }
+    let exchange = args
+        .exchange
+        .unwrap_or_else(|| args.asset_class.default_exchange().to_string());
+
+    let mut manager = options.data_manager().await?;
+    let bar = progress::counter(files.len() as u64, "Ingesting", options.quiet);
+    let mut rows = Vec::new();
+    for (ticker, resolution, file_name) in files {
+        bar.set_message(file_name.clone());
+        let symbol = Symbol::new(&ticker, &exchange, args.asset_class);
+        let mut provider = CsvDataProvider::new(&args.dir).with_pattern(&file_name);
+        let bars = provider
+            .fetch_bars(
+                &symbol,
+                DateTime::<Utc>::MIN_UTC,
+                DateTime::<Utc>::MAX_UTC,
+                resolution,
+            )
+            .await
+            .map_err(|e| CliError::runtime(format!("failed to read {file_name}: {e}")))?;
+        let stored = manager
+            .ingest_bars(
+                &symbol,
+                &bars,
+                resolution,
+                provider.dataset_kind(),
+                provider.price_adjustment_mode(),
+            )
+            .await
+            .map_err(|e| CliError::runtime(format!("failed to store {symbol}: {e}")))?;
+        rows.push((symbol, resolution, bars.len(), stored));
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    println!(
+        "{:<20} {:>6} {:>10} {:>10}",
+        "SYMBOL", "RES", "READ", "STORED"
+    );
+    for (symbol, resolution, read, stored) in rows {
+        println!(
+            "{:<20} {:>6} {:>10} {:>10}",
+            symbol.to_string(),
+            resolution.to_string(),
+            read,
+            stored
+        );
+    }
+    Ok(())
+}
+
+/// `(ticker, resolution, file name)` for each CSV file in `dir`, by name.
+fn csv_files(dir: &Path) -> CliResult<Vec<(String, Resolution, String)>> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| CliError::config(format!("cannot read {}: {e}", dir.display())))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| CliError::runtime(format!("cannot read {}: {e}", dir.display())))?
+            .path();
+        let is_csv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+        let (Some(stem), Some(file_name)) = (
+            path.file_stem().and_then(|stem| stem.to_str()),
+            path.file_name().and_then(|name| name.to_str()),
+        ) else {
+            continue;
+        };
+        if !is_csv || !path.is_file() {
+            continue;
+        }
+        let (ticker, resolution) = match stem.rsplit_once('_') {
+            Some((ticker, suffix)) => match parse_resolution(suffix) {
+                Ok(resolution) => (ticker, resolution),
+                Err(_) => (stem, Resolution::Day),
+            },
+            None => (stem, Resolution::Day),
+        };
+        files.push((ticker.to_string(), resolution, file_name.to_string()));
+    }
+    files.sort_by(|a, b| a.2.cmp(&b.2));
+    Ok(files)
+}
+
+async fn prefetch(args: PrefetchArgs, options: &GlobalOptions) -> CliResult {
+    let end = args.end.unwrap_or_else(Utc::now);
+    let start = args.start.unwrap_or(end - Duration::days(365));
+    if start > end {
+        return Err(CliError::config(format!(
+            "--start {} is after --end {}",
+            start.date_naive(),
+            end.date_naive()
+        )));
+    }
+
+    let mut manager = options.data_manager().await?;
+    match args.source {
+        Source::Sample => manager.add_provider(Box::new(SampleDataProvider::new())),
+        Source::Csv => {
+            let dir = args.csv_dir.unwrap_or_default();
+            if !dir.is_dir() {
+                return Err(CliError::config(format!(
+                    "{} is not a directory",
+                    dir.display()
+                )));
+            }
+            manager.add_provider(Box::new(CsvDataProvider::new(dir)));
+        }
+    }
+
+    let bar = progress::counter(args.universe.len() as u64, "Fetching", options.quiet);
+    let mut rows = Vec::new();
+    let mut failures = 0;
+    for symbol in &args.universe {
+        bar.set_message(symbol.to_string());
+        let outcome = manager.load_data(symbol, start, end, args.resolution).await;
+        if outcome.is_err() {
+            failures += 1;
+        }
+        rows.push((symbol, outcome));
+        bar.inc(1);
+    }
+    bar.finish_and_clear();
+
+    println!("{:<20} {:>10}  STATUS", "SYMBOL", "BARS");
+    for (symbol, outcome) in rows {
+        match outcome {
+            Ok(bars) => println!("{:<20} {:>10}  ok", symbol.to_string(), bars.len()),
+            Err(e) => println!("{:<20} {:>10}  {e}", symbol.to_string(), "-"),
+        }
+    }
+    if failures > 0 {
+        return Err(CliError::runtime(format!(
+            "{failures} of {} symbols could not be fetched",
+            args.universe.len()
+        )));
+    }
+    Ok(())
+}
+
+async fn stats(options: &GlobalOptions) -> CliResult {
+    let manager = options.data_manager().await?;
+    let failed = |e: gb_types::GbError| CliError::runtime(format!("failed to read catalog: {e}"));
+    let catalog = manager.catalog.get_catalog_stats().await.map_err(failed)?;
+    let symbols = manager.catalog.list_symbol_data().await.map_err(failed)?;
+    let storage = manager
+        .storage
+        .get_stats()
+        .map_err(|e| CliError::runtime(format!("failed to read storage: {e}")))?;
+
+    println!("Data directory  {}", storage.data_root.display());
+    println!("Symbols         {}", catalog.total_symbols);
+    println!("Records         {}", catalog.total_records);
+    println!(
+        "Storage         {} files, {:.2} MB",
+        storage.total_files,
+        storage.total_size_mb()
+    );
+    if let (Some(earliest), Some(latest)) = (catalog.earliest_date, catalog.latest_date) {
+        println!(
+            "Range           {} to {}",
+            earliest.date_naive(),
+            latest.date_naive()
+        );
+    }
+    if symbols.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "{:<20} {:>6} {:>10} {:>12} {:>12}",
+        "SYMBOL", "RES", "BARS", "FIRST", "LAST"
+    );
+    for info in symbols {
+        println!(
+            "{:<20} {:>6} {:>10} {:>12} {:>12}",
+            info.symbol.to_string(),
+            info.resolution.to_string(),
+            info.record_count,
+            info.first_date.date_naive().to_string(),
+            info.last_date.date_naive().to_string()
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,41 @@
+//! Command failures and the exit codes they map to.
+
+use std::fmt::Display;
+use std::process::ExitCode;
+
+/// Exit code for a run, ingestion or fetch that failed while executing.
+pub const EXIT_RUNTIME: u8 = 1;
+
+/// Exit code for an invalid command line, config file or input path;
+/// matches the code clap uses for usage errors.
+pub const EXIT_CONFIG: u8 = 2;
+
+/// Why a command failed. Config errors are detected before any work starts,
+/// so rerunning with a fixed input is safe; runtime errors may leave partial
+/// output behind.
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    #[error("configuration error: {0}")]
+    Config(String),
+    #[error("{0}")]
+    Runtime(String),
+}
+
+impl CliError {
+    pub fn config(message: impl Display) -> Self {
+        Self::Config(message.to_string())
+    }
+
+    pub fn runtime(message: impl Display) -> Self {
+        Self::Runtime(message.to_string())
+    }
+
+    pub fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::Config(_) => ExitCode::from(EXIT_CONFIG),
+            Self::Runtime(_) => ExitCode::from(EXIT_RUNTIME),
+        }
+    }
+}
+
+pub type CliResult<T = ()> = Result<T, CliError>;
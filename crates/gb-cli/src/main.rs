@@ -0,0 +1,133 @@
+//! `glowback`: ingest and inspect market data, run backtests from manifests
+//! and run parameter optimizations from the command line.
+//!
+//! Exits with [`EXIT_RUNTIME`](error::EXIT_RUNTIME) when a run fails and
+//! [`EXIT_CONFIG`](error::EXIT_CONFIG) when the command line, a config file
+//! or an input path is invalid.
+
+mod backtest;
+mod data;
+mod error;
+mod optimize;
+mod progress;
+
+use clap::{Parser, Subcommand};
+use gb_data::DataManager;
+use gb_types::Resolution;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use crate::error::{CliError, CliResult};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "glowback",
+    version,
+    about = "GlowBack backtesting from the command line"
+)]
+struct Cli {
+    /// Directory holding the local data catalog and stored bars; defaults
+    /// to the platform data directory.
+    #[arg(long, global = true, env = "GLOWBACK_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+
+    /// Hide progress bars.
+    #[arg(long, short, global = true)]
+    quiet: bool,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Ingest, fetch and inspect local market data.
+    #[command(subcommand)]
+    Data(data::DataCommand),
+    /// Run backtests and report on their results.
+    #[command(subcommand)]
+    Backtest(backtest::BacktestCommand),
+    /// Run parameter optimizations.
+    #[command(subcommand)]
+    Optimize(optimize::OptimizeCommand),
+}
+
+/// Options shared by every subcommand.
+#[derive(Debug, Clone)]
+pub struct GlobalOptions {
+    pub data_dir: Option<PathBuf>,
+    pub quiet: bool,
+}
+
+impl GlobalOptions {
+    /// The data manager for `--data-dir`, without providers.
+    pub async fn data_manager(&self) -> CliResult<DataManager> {
+        let manager = match &self.data_dir {
+            Some(dir) => DataManager::new_with_data_dir(dir).await,
+            None => DataManager::new().await,
+        };
+        manager.map_err(|e| CliError::runtime(format!("failed to open data directory: {e}")))
+    }
+}
+
+/// Parse a bar resolution in its display form (`1d`, `1h`, `5m`, ...) or by
+/// name (`day`, `hour`, `minute`).
+pub fn parse_resolution(value: &str) -> Result<Resolution, String> {
+    let resolution = match value.trim() {
+        "1M" => Resolution::Month,
+        other => match other.to_ascii_lowercase().as_str() {
+            "tick" => Resolution::Tick,
+            "1s" | "second" => Resolution::Second,
+            "1m" | "minute" => Resolution::Minute,
+            "5m" => Resolution::FiveMinute,
+            "15m" => Resolution::FifteenMinute,
+            "1h" | "hour" => Resolution::Hour,
+            "4h" => Resolution::FourHour,
+            "1d" | "day" => Resolution::Day,
+            "1w" | "week" => Resolution::Week,
+            "month" => Resolution::Month,
+            _ => return Err(format!("unknown resolution '{value}'")),
+        },
+    };
+    Ok(resolution)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_env("GLOWBACK_LOG")
+                .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("warn")),
+        )
+        .with_writer(std::io::stderr)
+        .init();
+
+    let options = GlobalOptions {
+        data_dir: cli.data_dir,
+        quiet: cli.quiet,
+    };
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("glowback: failed to start runtime: {e}");
+            return ExitCode::from(error::EXIT_RUNTIME);
+        }
+    };
+    // The data manager is not `Send`, so commands run on this thread via
+    // `block_on`; progress consumers are spawned onto the worker threads.
+    let outcome = runtime.block_on(async {
+        match cli.command {
+            Command::Data(command) => data::run(command, &options).await,
+            Command::Backtest(command) => backtest::run(command, &options).await,
+            Command::Optimize(command) => optimize::run(command, &options).await,
+        }
+    });
+
+    match outcome {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("glowback: {e}");
+            e.exit_code()
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! `glowback optimize`: run a parameter optimization from a TOML config.
+//!
+//! The config holds [`OptimizationConfig`] fields, with defaults for any
+//! left out, plus a `manifest` key naming the backtest manifest trials start
+//! from, relative to the config file:
+//!
+//! ```toml
+//! name = "MA crossover sweep"
+//! manifest = "config.toml"
+//! strategy = "grid"
+//! objective_metric = "total_return"
+//!
+//! [[search_space.parameters]]
+//! name = "short_period"
+//! kind = { IntRange = { low = 5, high = 15 } }
+//! ```
+
+use clap::{Args, Subcommand};
+use gb_optimizer::{
+    export_trials, search_strategy, ExportFormat, OptimizationConfig, OptimizationRunner,
+    ParameterValue, SearchSpace, Trial,
+};
+use gb_types::BacktestConfig;
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+
+use crate::error::{CliError, CliResult};
+use crate::{progress, GlobalOptions};
+
+#[derive(Debug, Subcommand)]
+pub enum OptimizeCommand {
+    /// Run the optimization described by a TOML config.
+    Run(RunArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct RunArgs {
+    /// Optimization config (`.toml`).
+    #[arg(long)]
+    pub config: PathBuf,
+
+    /// Directory to write the trial table (`trials.csv`) to.
+    #[arg(long)]
+    pub out: Option<PathBuf>,
+}
+
+pub async fn run(command: OptimizeCommand, options: &GlobalOptions) -> CliResult {
+    match command {
+        OptimizeCommand::Run(args) => run_optimization(args, options).await,
+    }
+}
+
+/// Read an optimization config, resolving its `manifest` into the base
+/// backtest.
+pub fn load_config(path: &Path) -> CliResult<OptimizationConfig> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        CliError::config(format!(
+            "invalid optimization config {}: {e}",
+            path.display()
+        ))
+    };
+    let text = std::fs::read_to_string(path)
+        .map_err(|e| CliError::config(format!("cannot read {}: {e}", path.display())))?;
+    let mut table: toml::Table = toml::from_str(&text).map_err(|e| invalid(&e))?;
+
+    let base_backtest = match table.remove("manifest") {
+        Some(toml::Value::String(manifest)) => {
+            let manifest = path.parent().unwrap_or(Path::new(".")).join(manifest);
+            let backtest = BacktestConfig::from_manifest_file(&manifest).map_err(|e| {
+                CliError::config(format!("invalid manifest {}: {e}", manifest.display()))
+            })?;
+            Some(serde_json::to_value(backtest).map_err(|e| invalid(&e))?)
+        }
+        Some(_) => return Err(invalid(&"`manifest` must be a path")),
+        None => None,
+    };
+
+    let defaults = OptimizationConfig::new(
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        SearchSpace::new(),
+        "random",
+    );
+    let mut merged = serde_json::to_value(defaults).map_err(|e| invalid(&e))?;
+    let overrides = serde_json::to_value(table).map_err(|e| invalid(&e))?;
+    if let (Some(merged), Some(overrides)) = (merged.as_object_mut(), overrides.as_object()) {
+        for (key, value) in overrides {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    let mut config: OptimizationConfig = serde_json::from_value(merged).map_err(|e| invalid(&e))?;
+    if let Some(base_backtest) = base_backtest {
+        config.base_backtest = base_backtest;
+    }
+    if config.search_space.parameters.is_empty() {
+        return Err(invalid(&"the search space has no parameters"));
+    }
+    Ok(config)
+}
+
+async fn run_optimization(args: RunArgs, options: &GlobalOptions) -> CliResult {
+    let config = load_config(&args.config)?;
+    search_strategy(&config, 0).map_err(CliError::config)?;
+    let max_trials = config.max_trials;
+
+    let bar = progress::counter(max_trials as u64, "Optimizing", options.quiet);
+    let (sender, mut finished) = mpsc::unbounded_channel::<(usize, usize)>();
+    let progress_bar = bar.clone();
+    let progress = tokio::spawn(async move {
+        while let Some((completed, failed)) = finished.recv().await {
+            progress_bar.set_position((completed + failed) as u64);
+            progress_bar.set_message(format!("{completed} ok, {failed} failed"));
+        }
+    });
+
+    let mut runner = OptimizationRunner::new().with_trial_observer(move |_, status| {
+        let _ = sender.send((status.trials_completed, status.trials_failed));
+    });
+    let outcome = runner.run(config).await;
+    let status = runner.status().cloned();
+    // Dropping the runner drops the observer's sender, ending the progress
+    // task.
+    drop(runner);
+    let _ = progress.await;
+    bar.finish_and_clear();
+
+    let trials = outcome.map_err(|e| CliError::runtime(format!("optimization failed: {e}")))?;
+    let status = status.ok_or_else(|| CliError::runtime("optimization did not start"))?;
+    if let Some(out) = &args.out {
+        let failed = |e: &dyn std::fmt::Display| {
+            CliError::runtime(format!("failed to write trials to {}: {e}", out.display()))
+        };
+        std::fs::create_dir_all(out).map_err(|e| failed(&e))?;
+        export_trials(&status, &trials, out.join("trials.csv"), ExportFormat::Csv)
+            .map_err(|e| failed(&e))?;
+    }
+
+    print_trials(&status.config, &trials);
+    match &status.best_trial {
+        Some(best) => {
+            println!();
+            println!(
+                "Best {} = {:.6} with {}",
+                status.config.objective_metric,
+                best.objective,
+                format_parameters(&best.parameters)
+            );
+            Ok(())
+        }
+        None => Err(CliError::runtime(format!(
+            "none of {} trials completed",
+            trials.len()
+        ))),
+    }
+}
+
+fn print_trials(config: &OptimizationConfig, trials: &[Trial]) {
+    println!(
+        "{:>5}  {:<10} {:>14}  PARAMETERS",
+        "TRIAL", "STATUS", config.objective_metric
+    );
+    for trial in trials {
+        let objective = trial.result.as_ref().map_or_else(
+            || "-".to_string(),
+            |result| format!("{:.6}", result.objective),
+        );
+        println!(
+            "{:>5}  {:<10} {:>14}  {}",
+            trial.trial_number,
+            format!("{:?}", trial.status),
+            objective,
+            format_parameters(&trial.parameters)
+        );
+    }
+}
+
+fn format_parameters(parameters: &std::collections::HashMap<String, ParameterValue>) -> String {
+    let mut names: Vec<_> = parameters.keys().collect();
+    names.sort();
+    names
+        .into_iter()
+        .map(|name| format!("{name}={}", parameters[name]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
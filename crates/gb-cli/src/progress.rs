@@ -0,0 +1,35 @@
+//! Progress bars on stderr, hidden with `--quiet` or when stderr is not a
+//! terminal.
+
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+
+/// Bar counting `total` items, e.g. files ingested or trials finished.
+pub fn counter(total: u64, prefix: &str, quiet: bool) -> ProgressBar {
+    bar(
+        total,
+        prefix,
+        quiet,
+        "{prefix:>12} [{bar:30}] {pos}/{len} {msg}",
+    )
+}
+
+/// Bar for a backtest's percentage complete.
+pub fn percent(prefix: &str, quiet: bool) -> ProgressBar {
+    bar(
+        100,
+        prefix,
+        quiet,
+        "{prefix:>12} [{bar:30}] {pos:>3}% {msg}",
+    )
+}
+
+fn bar(total: u64, prefix: &str, quiet: bool, template: &str) -> ProgressBar {
+    let bar = ProgressBar::new(total).with_prefix(prefix.to_string());
+    if quiet {
+        bar.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    if let Ok(style) = ProgressStyle::with_template(template) {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar
+}
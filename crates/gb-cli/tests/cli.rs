@@ -0,0 +1,261 @@
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::path::Path;
+use tempfile::TempDir;
+
+const CONFIG_ERROR: i32 = 2;
+const RUNTIME_ERROR: i32 = 1;
+
+fn glowback(data_dir: &Path) -> Command {
+    let mut command = Command::cargo_bin("glowback").unwrap();
+    command.arg("--quiet").arg("--data-dir").arg(data_dir);
+    command
+}
+
+fn write_manifest(dir: &Path, data_source: &str, strategy_id: &str) -> std::path::PathBuf {
+    let path = dir.join("config.toml");
+    std::fs::write(
+        &path,
+        format!(
+            r#"
+schema_version = 1
+name = "CLI smoke test"
+start_date = "2024-01-01T00:00:00Z"
+end_date = "2024-04-30T00:00:00Z"
+initial_capital = 100000
+symbols = ["AAPL"]
+
+[strategy]
+strategy_id = "{strategy_id}"
+name = "Strategy under test"
+
+[strategy.parameters]
+short_period = 5
+long_period = 20
+
+[data]
+data_source = "{data_source}"
+"#
+        ),
+    )
+    .unwrap();
+    path
+}
+
+#[test]
+fn ingest_then_stats_lists_the_stored_bars() {
+    let (data_dir, csv_dir) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    std::fs::write(
+        csv_dir.path().join("AAPL_1d.csv"),
+        "date,open,high,low,close,volume\n\
+         2024-01-02,100,101,99,100.5,1000\n\
+         2024-01-03,100.5,102,100,101.5,1200\n\
+         2024-01-04,101.5,103,101,102.5,900\n",
+    )
+    .unwrap();
+    std::fs::write(
+        csv_dir.path().join("MSFT.csv"),
+        "date,open,high,low,close,volume\n2024-01-02,300,301,299,300.5,500\n",
+    )
+    .unwrap();
+    std::fs::write(csv_dir.path().join("notes.txt"), "not market data").unwrap();
+
+    glowback(data_dir.path())
+        .args(["data", "ingest"])
+        .arg(csv_dir.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"NASDAQ:AAPL\s+1d\s+3\s+3").unwrap())
+        .stdout(predicate::str::is_match(r"NASDAQ:MSFT\s+1d\s+1\s+1").unwrap());
+
+    glowback(data_dir.path())
+        .args(["data", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Symbols\s+2").unwrap())
+        .stdout(predicate::str::is_match(r"Records\s+4").unwrap())
+        .stdout(
+            predicate::str::is_match(r"NASDAQ:AAPL\s+1d\s+3\s+2024-01-02\s+2024-01-04").unwrap(),
+        );
+}
+
+#[test]
+fn prefetch_stores_the_universe_from_the_sample_provider() {
+    let data_dir = TempDir::new().unwrap();
+
+    glowback(data_dir.path())
+        .args(["data", "prefetch", "--universe", "AAPL,BTC-USD:crypto"])
+        .args(["--start", "2024-01-01", "--end", "2024-01-31"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("NASDAQ:AAPL"))
+        .stdout(predicate::str::contains(":BTC-USD"));
+
+    glowback(data_dir.path())
+        .args(["data", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Symbols\s+2").unwrap());
+}
+
+#[test]
+fn backtest_run_writes_results_that_report_reads_back() {
+    let (data_dir, work) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    let manifest = write_manifest(work.path(), "sample", "ma_crossover");
+    let out = work.path().join("results");
+
+    glowback(data_dir.path())
+        .args(["backtest", "run", "--manifest"])
+        .arg(&manifest)
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Total return"));
+    for file in [
+        "summary.json",
+        "config.toml",
+        "equity_curve.arrow",
+        "trades.arrow",
+    ] {
+        assert!(out.join(file).is_file(), "missing {file}");
+    }
+
+    glowback(data_dir.path())
+        .args(["backtest", "report"])
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"Backtest\s+CLI smoke test").unwrap())
+        .stdout(predicate::str::is_match(r"Strategy\s+ma_crossover").unwrap())
+        .stdout(predicate::str::is_match(r"Sharpe ratio\s+\S+").unwrap());
+
+    // The saved manifest reruns the same backtest.
+    glowback(data_dir.path())
+        .args(["backtest", "run", "--manifest"])
+        .arg(out.join("config.toml"))
+        .arg("--out")
+        .arg(work.path().join("rerun"))
+        .assert()
+        .success();
+}
+
+#[test]
+fn config_errors_exit_with_code_2() {
+    let (data_dir, work) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+
+    glowback(data_dir.path())
+        .args(["backtest", "run", "--manifest"])
+        .arg(work.path().join("missing.toml"))
+        .assert()
+        .code(CONFIG_ERROR)
+        .stderr(predicate::str::contains("configuration error"));
+
+    let manifest = write_manifest(work.path(), "sample", "no_such_strategy");
+    glowback(data_dir.path())
+        .args(["backtest", "run", "--manifest"])
+        .arg(&manifest)
+        .assert()
+        .code(CONFIG_ERROR)
+        .stderr(predicate::str::contains("no_such_strategy"));
+
+    glowback(data_dir.path())
+        .args(["data", "ingest"])
+        .arg(work.path().join("no-such-dir"))
+        .assert()
+        .code(CONFIG_ERROR);
+
+    glowback(data_dir.path())
+        .args(["backtest", "report"])
+        .arg(work.path())
+        .assert()
+        .code(CONFIG_ERROR);
+
+    glowback(data_dir.path())
+        .args([
+            "data",
+            "prefetch",
+            "--universe",
+            "AAPL",
+            "--resolution",
+            "3d",
+        ])
+        .assert()
+        .code(CONFIG_ERROR);
+}
+
+#[test]
+fn runtime_failures_exit_with_code_1() {
+    let (data_dir, work) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    // Nothing is stored for AAPL and no provider can fetch it.
+    let manifest = write_manifest(work.path(), "local", "ma_crossover");
+
+    glowback(data_dir.path())
+        .args(["backtest", "run", "--manifest"])
+        .arg(&manifest)
+        .arg("--out")
+        .arg(work.path().join("results"))
+        .assert()
+        .code(RUNTIME_ERROR)
+        .stderr(predicate::str::contains("backtest failed"));
+
+    let csv_dir = TempDir::new().unwrap();
+    glowback(data_dir.path())
+        .args(["data", "prefetch", "--universe", "AAPL", "--source", "csv"])
+        .arg("--csv-dir")
+        .arg(csv_dir.path())
+        .assert()
+        .code(RUNTIME_ERROR)
+        .stdout(predicate::str::contains("NASDAQ:AAPL"));
+}
+
+#[test]
+fn optimize_run_sweeps_the_grid_and_exports_trials() {
+    let (data_dir, work) = (TempDir::new().unwrap(), TempDir::new().unwrap());
+    write_manifest(work.path(), "sample", "ma_crossover");
+    let config = work.path().join("opt.toml");
+    std::fs::write(
+        &config,
+        r#"
+name = "CLI sweep"
+manifest = "config.toml"
+strategy = "grid"
+max_trials = 4
+concurrency = 2
+objective_metric = "total_return"
+
+[[search_space.parameters]]
+name = "short_period"
+kind = { IntRange = { low = 3, high = 4 } }
+
+[[search_space.parameters]]
+name = "long_period"
+kind = { IntRange = { low = 15, high = 16 } }
+"#,
+    )
+    .unwrap();
+    let out = work.path().join("opt");
+
+    glowback(data_dir.path())
+        .args(["optimize", "run", "--config"])
+        .arg(&config)
+        .arg("--out")
+        .arg(&out)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Best total_return"));
+
+    let trials = std::fs::read_to_string(out.join("trials.csv")).unwrap();
+    assert_eq!(
+        trials.lines().count(),
+        5,
+        "header plus four trials:\n{trials}"
+    );
+
+    std::fs::write(&config, "strategy = \"grid\"\nmax_trials = \"many\"\n").unwrap();
+    glowback(data_dir.path())
+        .args(["optimize", "run", "--config"])
+        .arg(&config)
+        .assert()
+        .code(CONFIG_ERROR);
+}
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 use chrono::{DateTime, Utc};
-use gb_types::{Bar, Symbol, Resolution, GbResult};
+use gb_types::{Bar, BarColumns, Symbol, Resolution, GbResult};
 use dashmap::DashMap;
 use parking_lot::RwLock;
 
@@ -11,10 +11,10 @@ struct CacheKey {
     resolution: Resolution,
 }
 
-/// Cached data entry with metadata
+/// Cached data entry with metadata; bars are held column by column
 #[derive(Debug, Clone)]
 struct CacheEntry {
-    bars: Vec<Bar>,
+    bars: BarColumns,
     start_date: DateTime<Utc>,
     end_date: DateTime<Utc>,
     last_accessed: DateTime<Utc>,
@@ -22,15 +22,12 @@ struct CacheEntry {
 }
 
 impl CacheEntry {
-    fn new(bars: Vec<Bar>) -> Self {
+    fn new(bars: BarColumns) -> Self {
         let now = Utc::now();
-        let (start_date, end_date) = if bars.is_empty() {
-            (now, now)
-        } else {
-            (
-                bars.first().unwrap().timestamp,
-                bars.last().unwrap().timestamp,
-            )
+        let timestamps = bars.timestamps();
+        let (start_date, end_date) = match (timestamps.first(), timestamps.last()) {
+            (Some(first), Some(last)) => (*first, *last),
+            _ => (now, now),
         };
         
         Self {
@@ -52,11 +49,7 @@ impl CacheEntry {
     }
     
     fn get_bars_in_range(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<Bar> {
-        self.bars
-            .iter()
-            .filter(|bar| bar.timestamp >= start && bar.timestamp <= end)
-            .cloned()
-            .collect()
+        self.bars.bars_in_range(start, end)
     }
 }
 
@@ -141,7 +134,7 @@ impl CacheManager {
             resolution,
         };
         
-        let entry = CacheEntry::new(bars.to_vec());
+        let entry = CacheEntry::new(BarColumns::from_bars(symbol.clone(), resolution, bars)?);
         
         // Check if we need to evict entries
         if self.cache.len() >= self.max_entries {
@@ -201,6 +194,24 @@ impl CacheManager {
         }
     }
     
+    /// Drop every cached resolution of `symbol`, returning how many entries
+    /// were removed.
+    pub fn invalidate_symbol(&self, symbol: &Symbol) -> usize {
+        let before = self.cache.len();
+        let mut dropped_bars = 0u64;
+        self.cache.retain(|key, entry| {
+            if key.symbol != *symbol {
+                return true;
+            }
+            dropped_bars += entry.read().bars.len() as u64;
+            false
+        });
+        
+        let mut stats = self.stats.write();
+        stats.total_bars_cached = stats.total_bars_cached.saturating_sub(dropped_bars);
+        before - self.cache.len()
+    }
+    
     pub fn get_stats(&self) -> CacheStats {
         self.stats.read().clone()
     }
@@ -232,12 +243,11 @@ impl CacheManager {
     }
     
     fn estimate_memory_usage(&self) -> f64 {
-        // Rough estimation: each bar is approximately 100 bytes
-        let total_bars = self.cache.iter()
-            .map(|entry| entry.value().read().bars.len())
+        let total_bytes = self.cache.iter()
+            .map(|entry| entry.value().read().bars.memory_bytes())
             .sum::<usize>();
         
-        (total_bars * 100) as f64 / (1024.0 * 1024.0) // Convert to MB
+        total_bytes as f64 / (1024.0 * 1024.0) // Convert to MB
     }
 }
 
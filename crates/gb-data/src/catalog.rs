@@ -1,10 +1,10 @@
 use std::collections::{HashMap, HashSet};
 use std::path::Path;
 
-use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use gb_types::{
     AssetClass, DataError, DataValidationSummary, DatasetKind, GbResult, PriceAdjustmentMode,
-    Resolution, Symbol,
+    Resolution, Symbol, Universe,
 };
 use rusqlite::Connection;
 
@@ -35,6 +35,7 @@ impl DataCatalog {
                 dataset_kind TEXT NOT NULL DEFAULT 'external',
                 price_adjustment TEXT NOT NULL DEFAULT 'raw',
                 validation_summary TEXT,
+                searched_from TEXT,
                 created_at TEXT DEFAULT CURRENT_TIMESTAMP,
                 updated_at TEXT DEFAULT CURRENT_TIMESTAMP
             );
@@ -43,6 +44,24 @@ impl DataCatalog {
             CREATE INDEX IF NOT EXISTS idx_symbol_metadata_exchange ON symbol_metadata(exchange);
             CREATE INDEX IF NOT EXISTS idx_symbol_metadata_asset_class ON symbol_metadata(asset_class);
 
+            CREATE TABLE IF NOT EXISTS option_chain_metadata (
+                id TEXT PRIMARY KEY,
+                symbol TEXT NOT NULL,
+                exchange TEXT NOT NULL,
+                first_date TEXT NOT NULL,
+                last_date TEXT NOT NULL,
+                quote_days INTEGER DEFAULT 0,
+                expiry_count INTEGER DEFAULT 0,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS watchlists (
+                name TEXT PRIMARY KEY,
+                as_of TEXT NOT NULL,
+                symbols TEXT NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
             CREATE TABLE IF NOT EXISTS data_sources (
                 id TEXT PRIMARY KEY,
                 name TEXT NOT NULL,
@@ -68,6 +87,7 @@ impl DataCatalog {
             "TEXT NOT NULL DEFAULT 'raw'",
         )?;
         ensure_symbol_metadata_column(&connection, "validation_summary", "TEXT")?;
+        ensure_symbol_metadata_column(&connection, "searched_from", "TEXT")?;
 
         let symbols = Self::load_symbols(&connection)?;
 
@@ -77,6 +97,11 @@ impl DataCatalog {
         })
     }
 
+    /// Record that `record_count` bars of `symbol` at `resolution` are
+    /// stored from `start_date` through `end_date`, replacing the previous
+    /// registration but keeping how far back providers were searched.
+    /// `DataManager` registers the extent of the persisted bars rather than
+    /// of the requested range.
     #[allow(clippy::too_many_arguments)]
     pub async fn register_symbol_data(
         &mut self,
@@ -91,6 +116,7 @@ impl DataCatalog {
     ) -> GbResult<()> {
         let key = symbol_cache_key(symbol, resolution);
         let validation_summary_owned = validation_summary.cloned();
+        let searched_from = self.symbols.get(&key).and_then(|info| info.searched_from);
         let info = SymbolInfo {
             symbol: symbol.clone(),
             first_date: start_date,
@@ -100,6 +126,7 @@ impl DataCatalog {
             dataset_kind,
             price_adjustment,
             validation_summary: validation_summary_owned.clone(),
+            searched_from,
             last_updated: Utc::now(),
         };
 
@@ -116,8 +143,8 @@ impl DataCatalog {
         self.connection
             .execute(
                 "INSERT OR REPLACE INTO symbol_metadata
-             (id, symbol, exchange, asset_class, resolution, start_date, end_date, record_count, dataset_kind, price_adjustment, validation_summary, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, CURRENT_TIMESTAMP)",
+             (id, symbol, exchange, asset_class, resolution, start_date, end_date, record_count, dataset_kind, price_adjustment, validation_summary, searched_from, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, CURRENT_TIMESTAMP)",
                 rusqlite::params![
                     key,
                     symbol.symbol,
@@ -130,6 +157,7 @@ impl DataCatalog {
                     dataset_kind_as_str(dataset_kind),
                     price_adjustment_as_str(price_adjustment),
                     validation_summary_json,
+                    searched_from.map(|date| date.to_rfc3339()),
                 ],
             )
             .map_err(|e| DataError::DatabaseConnection {
@@ -145,6 +173,57 @@ impl DataCatalog {
         Ok(())
     }
 
+    /// Note that providers were asked for `symbol` at `resolution` from
+    /// `from` onwards, so the stretch up to its first stored bar is known to
+    /// be empty. Only registered symbols are tracked; the earliest search
+    /// wins.
+    pub async fn record_search(
+        &mut self,
+        symbol: &Symbol,
+        resolution: Resolution,
+        from: DateTime<Utc>,
+    ) -> GbResult<()> {
+        let key = symbol_cache_key(symbol, resolution);
+        let Some(info) = self.symbols.get_mut(&key) else {
+            return Ok(());
+        };
+        let searched_from = info.searched_from.map_or(from, |earlier| earlier.min(from));
+        info.searched_from = Some(searched_from);
+
+        self.connection
+            .execute(
+                "UPDATE symbol_metadata SET searched_from = ?2 WHERE id = ?1",
+                rusqlite::params![key, searched_from.to_rfc3339()],
+            )
+            .map_err(|e| DataError::QueryFailed {
+                query: "UPDATE symbol_metadata".to_string(),
+                error: e.to_string(),
+            })?;
+        Ok(())
+    }
+
+    /// Forget the registration of `symbol` at `resolution`, returning
+    /// whether there was one.
+    pub async fn remove_symbol_data(
+        &mut self,
+        symbol: &Symbol,
+        resolution: Resolution,
+    ) -> GbResult<bool> {
+        let key = symbol_cache_key(symbol, resolution);
+        let removed = self
+            .connection
+            .execute(
+                "DELETE FROM symbol_metadata WHERE id = ?1",
+                rusqlite::params![key],
+            )
+            .map_err(|e| DataError::QueryFailed {
+                query: "DELETE symbol_metadata".to_string(),
+                error: e.to_string(),
+            })?;
+        self.symbols.remove(&key);
+        Ok(removed > 0)
+    }
+
     pub async fn get_symbol_info_for_resolution(
         &self,
         symbol: &Symbol,
@@ -186,14 +265,59 @@ impl DataCatalog {
         Ok(symbols)
     }
 
+    /// Every registered symbol/resolution pair, ordered by symbol, exchange,
+    /// asset class and resolution.
+    pub async fn list_symbol_data(&self) -> GbResult<Vec<SymbolInfo>> {
+        let mut infos: Vec<SymbolInfo> = self.symbols.values().cloned().collect();
+        infos.sort_by(|a, b| {
+            (&a.symbol.symbol, &a.symbol.exchange)
+                .cmp(&(&b.symbol.symbol, &b.symbol.exchange))
+                .then_with(|| {
+                    format!("{:?}", a.symbol.asset_class)
+                        .cmp(&format!("{:?}", b.symbol.asset_class))
+                })
+                .then_with(|| a.resolution.to_seconds().cmp(&b.resolution.to_seconds()))
+        });
+        Ok(infos)
+    }
+
+    /// Forget every resolution registered for `symbol`, returning how many
+    /// entries were removed.
+    pub async fn remove_symbol(&mut self, symbol: &Symbol) -> GbResult<usize> {
+        let asset_class = format!("{:?}", symbol.asset_class);
+        let removed = self
+            .connection
+            .execute(
+                "DELETE FROM symbol_metadata WHERE symbol = ?1 AND exchange = ?2 AND asset_class = ?3",
+                rusqlite::params![symbol.symbol, symbol.exchange, asset_class],
+            )
+            .map_err(|e| DataError::QueryFailed {
+                query: "DELETE symbol_metadata".to_string(),
+                error: e.to_string(),
+            })?;
+        self.symbols.retain(|_, info| info.symbol != *symbol);
+
+        tracing::debug!("Removed {} catalog entries for {}", removed, symbol);
+        Ok(removed)
+    }
+
     pub async fn get_catalog_stats(&self) -> GbResult<CatalogStats> {
         let mut asset_classes = HashSet::new();
         let mut exchanges = HashSet::new();
         let mut total_records = 0u64;
         let mut earliest_date = None;
         let mut latest_date = None;
-
-        for info in self.symbols.values() {
+        let mut breakdown = Vec::new();
+
+        for info in self.list_symbol_data().await? {
+            breakdown.push(SymbolCoverage {
+                symbol: info.symbol.clone(),
+                resolution: info.resolution,
+                records: info.record_count,
+                first: info.first_date,
+                last: info.last_date,
+                bytes: 0,
+            });
             asset_classes.insert(format!("{:?}", info.symbol.asset_class));
             exchanges.insert(info.symbol.exchange.clone());
             total_records += info.record_count;
@@ -213,13 +337,177 @@ impl DataCatalog {
             total_records,
             earliest_date,
             latest_date,
+            breakdown,
         })
     }
 
+    /// Record the stored extent of an underlying's option chain history,
+    /// replacing any earlier record for it.
+    pub async fn register_option_chain_data(
+        &mut self,
+        underlying: &Symbol,
+        first_date: NaiveDate,
+        last_date: NaiveDate,
+        quote_days: u64,
+        expiry_count: u64,
+    ) -> GbResult<()> {
+        self.connection
+            .execute(
+                "INSERT OR REPLACE INTO option_chain_metadata
+             (id, symbol, exchange, first_date, last_date, quote_days, expiry_count, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CURRENT_TIMESTAMP)",
+                rusqlite::params![
+                    option_chain_key(underlying),
+                    underlying.symbol,
+                    underlying.exchange,
+                    first_date.to_string(),
+                    last_date.to_string(),
+                    quote_days as i64,
+                    expiry_count as i64,
+                ],
+            )
+            .map_err(|e| DataError::DatabaseConnection {
+                message: e.to_string(),
+            })?;
+
+        tracing::debug!(
+            "Registered option chain data: {} from {} to {}",
+            underlying,
+            first_date,
+            last_date
+        );
+        Ok(())
+    }
+
+    pub async fn get_option_chain_info(
+        &self,
+        underlying: &Symbol,
+    ) -> GbResult<Option<OptionChainInfo>> {
+        let query = "SELECT first_date, last_date, quote_days, expiry_count, updated_at
+                 FROM option_chain_metadata WHERE id = ?1";
+        let row = self
+            .connection
+            .query_row(query, [option_chain_key(underlying)], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            });
+
+        let (first_date, last_date, quote_days, expiry_count, updated_at) = match row {
+            Ok(row) => row,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => {
+                return Err(DataError::QueryFailed {
+                    query: "SELECT option_chain_metadata".to_string(),
+                    error: e.to_string(),
+                }
+                .into())
+            }
+        };
+
+        Ok(Some(OptionChainInfo {
+            underlying: underlying.clone(),
+            first_date: parse_catalog_date(&first_date)?,
+            last_date: parse_catalog_date(&last_date)?,
+            quote_days: quote_days as u64,
+            expiry_count: expiry_count as u64,
+            last_updated: parse_catalog_datetime(&updated_at)?,
+        }))
+    }
+
+    /// Save `universe` as a watchlist under its name, replacing any earlier
+    /// watchlist of that name.
+    pub async fn save_watchlist(&mut self, universe: &Universe) -> GbResult<()> {
+        let symbols =
+            serde_json::to_string(&universe.symbols).map_err(|error| DataError::ParseError {
+                message: format!("failed to serialize watchlist symbols: {error}"),
+            })?;
+        self.connection
+            .execute(
+                "INSERT OR REPLACE INTO watchlists (name, as_of, symbols, updated_at)
+             VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)",
+                rusqlite::params![universe.name, universe.as_of.to_rfc3339(), symbols],
+            )
+            .map_err(|e| DataError::DatabaseConnection {
+                message: e.to_string(),
+            })?;
+
+        tracing::debug!(
+            "Saved watchlist {} with {} symbols as of {}",
+            universe.name,
+            universe.len(),
+            universe.as_of
+        );
+        Ok(())
+    }
+
+    pub async fn load_watchlist(&self, name: &str) -> GbResult<Option<Universe>> {
+        let row = self.connection.query_row(
+            "SELECT as_of, symbols FROM watchlists WHERE name = ?1",
+            [name],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        );
+
+        let (as_of, symbols) = match row {
+            Ok(row) => row,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => {
+                return Err(DataError::QueryFailed {
+                    query: "SELECT watchlists".to_string(),
+                    error: e.to_string(),
+                }
+                .into())
+            }
+        };
+        let symbols = serde_json::from_str(&symbols).map_err(|error| DataError::ParseError {
+            message: format!("failed to parse symbols of watchlist {name}: {error}"),
+        })?;
+
+        Ok(Some(Universe::new(
+            name,
+            parse_catalog_datetime(&as_of)?,
+            symbols,
+        )))
+    }
+
+    /// Names of every saved watchlist, in order.
+    pub async fn list_watchlists(&self) -> GbResult<Vec<String>> {
+        let query_failed = |e: rusqlite::Error| DataError::QueryFailed {
+            query: "SELECT watchlists".to_string(),
+            error: e.to_string(),
+        };
+        let mut stmt = self
+            .connection
+            .prepare("SELECT name FROM watchlists ORDER BY name")
+            .map_err(query_failed)?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(0))
+            .map_err(query_failed)?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(query_failed)?;
+        Ok(names)
+    }
+
+    /// Delete the watchlist called `name`, returning whether it existed.
+    pub async fn delete_watchlist(&mut self, name: &str) -> GbResult<bool> {
+        let removed = self
+            .connection
+            .execute("DELETE FROM watchlists WHERE name = ?1", [name])
+            .map_err(|e| DataError::QueryFailed {
+                query: "DELETE watchlists".to_string(),
+                error: e.to_string(),
+            })?;
+        Ok(removed > 0)
+    }
+
     fn load_symbols(connection: &Connection) -> GbResult<HashMap<String, SymbolInfo>> {
         let mut stmt = connection
             .prepare(
-                "SELECT id, symbol, exchange, asset_class, resolution, start_date, end_date, record_count, dataset_kind, price_adjustment, validation_summary, updated_at
+                "SELECT id, symbol, exchange, asset_class, resolution, start_date, end_date, record_count, dataset_kind, price_adjustment, validation_summary, updated_at, searched_from
                  FROM symbol_metadata",
             )
             .map_err(|e| DataError::DatabaseConnection {
@@ -241,6 +529,7 @@ impl DataCatalog {
                     row.get::<_, String>(9)?,
                     row.get::<_, Option<String>>(10)?,
                     row.get::<_, String>(11)?,
+                    row.get::<_, Option<String>>(12)?,
                 ))
             })
             .map_err(|e| DataError::QueryFailed {
@@ -263,6 +552,7 @@ impl DataCatalog {
                 price_adjustment,
                 validation_summary,
                 updated_at,
+                searched_from,
             ) = row.map_err(|e| DataError::QueryFailed {
                 query: "SELECT symbol_metadata".to_string(),
                 error: e.to_string(),
@@ -273,6 +563,10 @@ impl DataCatalog {
             let first_date = parse_catalog_datetime(&start_date)?;
             let last_date = parse_catalog_datetime(&end_date)?;
             let last_updated = parse_catalog_datetime(&updated_at)?;
+            let searched_from = searched_from
+                .as_deref()
+                .map(parse_catalog_datetime)
+                .transpose()?;
             let dataset_kind = parse_dataset_kind(&dataset_kind)?;
             let price_adjustment = parse_price_adjustment(&price_adjustment)?;
             let validation_summary = validation_summary
@@ -300,6 +594,7 @@ impl DataCatalog {
                     dataset_kind,
                     price_adjustment,
                     validation_summary,
+                    searched_from,
                     last_updated,
                 },
             );
@@ -359,6 +654,10 @@ fn symbol_cache_key(symbol: &Symbol, resolution: Resolution) -> String {
     )
 }
 
+fn option_chain_key(underlying: &Symbol) -> String {
+    format!("{}:{}", underlying.symbol, underlying.exchange)
+}
+
 fn parse_asset_class(value: &str) -> GbResult<AssetClass> {
     match value {
         "Equity" => Ok(AssetClass::Equity),
@@ -366,6 +665,7 @@ fn parse_asset_class(value: &str) -> GbResult<AssetClass> {
         "Forex" => Ok(AssetClass::Forex),
         "Commodity" => Ok(AssetClass::Commodity),
         "Bond" => Ok(AssetClass::Bond),
+        "Option" => Ok(AssetClass::Option),
         _ => Err(DataError::ParseError {
             message: format!("unknown asset class in catalog metadata: {value}"),
         }
@@ -436,6 +736,15 @@ fn price_adjustment_as_str(value: PriceAdjustmentMode) -> &'static str {
     }
 }
 
+fn parse_catalog_date(value: &str) -> GbResult<NaiveDate> {
+    value.parse().map_err(|_| {
+        DataError::ParseError {
+            message: format!("unrecognized date in catalog metadata: {value}"),
+        }
+        .into()
+    })
+}
+
 fn parse_catalog_datetime(value: &str) -> GbResult<DateTime<Utc>> {
     if let Ok(parsed) = DateTime::parse_from_rfc3339(value) {
         return Ok(parsed.with_timezone(&Utc));
@@ -461,6 +770,22 @@ pub struct SymbolInfo {
     pub dataset_kind: DatasetKind,
     pub price_adjustment: PriceAdjustmentMode,
     pub validation_summary: Option<DataValidationSummary>,
+    /// Earliest start providers have been asked for. When it precedes
+    /// `first_date`, the symbol has no bars between the two.
+    pub searched_from: Option<DateTime<Utc>>,
+    pub last_updated: DateTime<Utc>,
+}
+
+/// Stored extent of an underlying's option chain history.
+#[derive(Debug, Clone)]
+pub struct OptionChainInfo {
+    pub underlying: Symbol,
+    pub first_date: NaiveDate,
+    pub last_date: NaiveDate,
+    /// Number of days with at least one stored quote.
+    pub quote_days: u64,
+    /// Number of distinct expiries ever quoted.
+    pub expiry_count: u64,
     pub last_updated: DateTime<Utc>,
 }
 
@@ -472,6 +797,44 @@ pub struct CatalogStats {
     pub total_records: u64,
     pub earliest_date: Option<DateTime<Utc>>,
     pub latest_date: Option<DateTime<Utc>>,
+    /// One entry per registered symbol/resolution pair, ordered as
+    /// `list_symbol_data` orders them.
+    pub breakdown: Vec<SymbolCoverage>,
+}
+
+/// Records held for one symbol at one resolution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolCoverage {
+    pub symbol: Symbol,
+    pub resolution: Resolution,
+    pub records: u64,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+    /// Size of the stored file; the catalog alone reports zero, and
+    /// `DataManager::catalog_stats` fills it in from storage.
+    pub bytes: u64,
+}
+
+/// What `DataManager::reconcile_catalog` changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CatalogReconciliation {
+    /// Registrations audited against storage.
+    pub checked: usize,
+    /// Registrations whose range or count disagreed with the stored bars and
+    /// were rewritten from them.
+    pub corrected: usize,
+    /// Registrations with no stored bars behind them, now removed.
+    pub removed: usize,
+}
+
+/// How `DataManager::catalog_stats` derives record counts and ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverageScan {
+    /// Trust the counts and ranges registered in the catalog.
+    #[default]
+    Registered,
+    /// Read every stored file to count its bars and find their true range.
+    Exact,
 }
 
 #[cfg(test)]
@@ -507,6 +870,7 @@ mod tests {
             warnings: vec![
                 "Detected 1 missing expected daily intervals for NASDAQ:AAPL.".to_string(),
             ],
+            ..DataValidationSummary::default()
         };
 
         {
@@ -0,0 +1,124 @@
+//! Write-ahead intents for bar writes.
+//!
+//! Storing bars changes two things that cannot be updated together: the
+//! symbol's Parquet file and its catalog registration. Before either
+//! changes, a [`WriteIntent`] naming the write is made durable in the data
+//! directory, and it is cleared only once both have. An intent still on
+//! disk when a [`DataManager`](crate::DataManager) opens the directory marks
+//! a write a crash interrupted, which
+//! [`DataManager::recover_writes`](crate::DataManager::recover_writes)
+//! finishes or undoes.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use gb_types::{DataError, DatasetKind, GbResult, PriceAdjustmentMode, Resolution, Symbol};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::storage::{sync_file, sync_parent};
+
+/// One bar write in flight: bars of `symbol` between `start` and `end`
+/// merged with what is stored, staged at `staged_path` and then renamed
+/// over `target_path`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WriteIntent {
+    pub id: Uuid,
+    pub symbol: Symbol,
+    pub resolution: Resolution,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub staged_path: PathBuf,
+    pub target_path: PathBuf,
+    pub dataset_kind: DatasetKind,
+    pub price_adjustment: PriceAdjustmentMode,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Directory of pending [`WriteIntent`]s, one JSON file each.
+#[derive(Debug)]
+pub struct IntentLog {
+    pub dir: PathBuf,
+}
+
+impl IntentLog {
+    pub fn new<P: AsRef<Path>>(dir: P) -> GbResult<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, id: Uuid) -> PathBuf {
+        self.dir.join(format!("{}.json", id))
+    }
+
+    /// Durably record `intent`. It is written aside and renamed into place,
+    /// so a crash leaves either the whole intent or none of it.
+    pub fn record(&self, intent: &WriteIntent) -> GbResult<()> {
+        let path = self.path(intent.id);
+        let partial = path.with_extension("json.tmp");
+        let json = serde_json::to_vec(intent).map_err(|e| DataError::ParseError {
+            message: format!("failed to serialize write intent: {}", e),
+        })?;
+        fs::write(&partial, json).map_err(|e| DataError::from_io(&partial, e))?;
+        sync_file(&partial)?;
+        fs::rename(&partial, &path)?;
+        sync_parent(&path)?;
+        Ok(())
+    }
+
+    /// Forget the intent `id` once its write has fully landed.
+    pub fn clear(&self, id: Uuid) -> GbResult<()> {
+        let path = self.path(id);
+        match fs::remove_file(&path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(DataError::from_io(&path, e)),
+        }
+        sync_parent(&path)?;
+        Ok(())
+    }
+
+    /// Intents recorded and never cleared, oldest first. Intents a crash
+    /// cut short while being recorded are discarded: their writes never
+    /// started.
+    pub fn pending(&self) -> GbResult<Vec<WriteIntent>> {
+        let mut intents = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if name.ends_with(".json.tmp") {
+                fs::remove_file(&path)?;
+            } else if name.ends_with(".json") {
+                let json = fs::read(&path).map_err(|e| DataError::from_io(&path, e))?;
+                let intent: WriteIntent =
+                    serde_json::from_slice(&json).map_err(|e| DataError::corrupt_file(&path, e))?;
+                intents.push(intent);
+            }
+        }
+        intents.sort_by_key(|intent| intent.recorded_at);
+        Ok(intents)
+    }
+}
+
+/// What [`DataManager::recover_writes`](crate::DataManager::recover_writes)
+/// did with the writes a crash interrupted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct WriteRecovery {
+    /// Writes whose staged file was complete and has now been renamed into
+    /// place and registered.
+    pub replayed: usize,
+    /// Writes whose staged file was incomplete and was discarded, leaving
+    /// the stored bars as they were.
+    pub rolled_back: usize,
+    /// Writes with no staged file left, either renamed into place or never
+    /// begun, whose catalog registration was rewritten from the stored bars.
+    pub registered: usize,
+}
+
+impl WriteRecovery {
+    pub fn total(&self) -> usize {
+        self.replayed + self.rolled_back + self.registered
+    }
+}
@@ -1,23 +1,59 @@
 pub mod cache;
 pub mod catalog;
+pub mod intents;
 pub mod loaders;
+pub mod options;
 pub mod providers;
+pub mod resample;
+pub mod screen;
 pub mod sources;
 pub mod storage;
+pub mod symbols;
 pub mod validation;
 
 pub use cache::*;
 pub use catalog::*;
+pub use intents::*;
 pub use loaders::*;
+pub use options::*;
 pub use providers::*;
+pub use resample::*;
+pub use screen::*;
 pub use sources::*;
 pub use storage::*;
+pub use symbols::*;
 pub use validation::*;
 
 use gb_types::{DataValidationSummary, DatasetKind, GbResult, PriceAdjustmentMode};
 use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
+/// The steps of a bar write, in order. [`DataManager::recover_writes`]
+/// settles a crash between any two of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteStage {
+    /// Durably record the [`WriteIntent`].
+    RecordIntent,
+    /// Write the merged bars beside the stored file and flush them.
+    StageBars,
+    /// Rename the staged file over the stored one.
+    Install,
+    /// Register the stored extent in the catalog.
+    Register,
+    /// Clear the intent.
+    ClearIntent,
+}
+
+impl WriteStage {
+    const ALL: [WriteStage; 5] = [
+        WriteStage::RecordIntent,
+        WriteStage::StageBars,
+        WriteStage::Install,
+        WriteStage::Register,
+        WriteStage::ClearIntent,
+    ];
+}
+
 /// Data manager coordinates all data operations
 #[derive(Debug)]
 pub struct DataManager {
@@ -25,6 +61,12 @@ pub struct DataManager {
     pub storage: storage::StorageManager,
     pub cache: cache::CacheManager,
     pub providers: Vec<Box<dyn providers::DataProvider>>,
+    /// Bar writes in flight, settled by [`recover_writes`](Self::recover_writes)
+    /// after a crash.
+    pub intents: intents::IntentLog,
+    /// Serve a resolution no provider offers natively by resampling finer
+    /// bars from one that does.
+    pub allow_resampling: bool,
 }
 
 impl DataManager {
@@ -42,13 +84,28 @@ impl DataManager {
         let catalog = catalog::DataCatalog::new(&data_dir.join("catalog.db")).await?;
         let storage = storage::StorageManager::new(&data_dir)?;
         let cache = cache::CacheManager::new()?;
+        let intents = intents::IntentLog::new(data_dir.join("intents"))?;
 
-        Ok(Self {
+        let mut manager = Self {
             catalog,
             storage,
             cache,
             providers: Vec::new(),
-        })
+            intents,
+            allow_resampling: true,
+        };
+        let recovery = manager.recover_writes().await?;
+        if recovery.total() > 0 {
+            tracing::warn!(
+                "Settled {} interrupted writes in {}: {} replayed, {} rolled back, {} re-registered",
+                recovery.total(),
+                data_dir.display(),
+                recovery.replayed,
+                recovery.rolled_back,
+                recovery.registered
+            );
+        }
+        Ok(manager)
     }
 
     pub async fn new_ephemeral(prefix: &str) -> GbResult<Self> {
@@ -72,6 +129,11 @@ impl DataManager {
             .and_then(|info| info.validation_summary))
     }
 
+    #[tracing::instrument(
+        level = "debug",
+        skip(self, start_date, end_date),
+        fields(symbol = %symbol, resolution = %resolution)
+    )]
     pub async fn load_data(
         &mut self,
         symbol: &gb_types::Symbol,
@@ -85,6 +147,7 @@ impl DataManager {
             .get_bars(symbol, start_date, end_date, resolution)
             .await?
         {
+            tracing::debug!("Loaded {} bars from the cache", data.len());
             return Ok(data);
         }
 
@@ -95,100 +158,612 @@ impl DataManager {
             .await
         {
             if !data.is_empty() {
-                let existing_info = self
+                // Bars stored without going through the catalog get registered
+                // with their full stored extent.
+                if self
                     .catalog
                     .get_symbol_info_for_resolution(symbol, resolution)
-                    .await?;
-                let dataset_kind = existing_info
-                    .as_ref()
-                    .map(|info| info.dataset_kind)
-                    .unwrap_or(DatasetKind::External);
-                let price_adjustment = existing_info
-                    .as_ref()
-                    .map(|info| info.price_adjustment)
-                    .unwrap_or(PriceAdjustmentMode::Raw);
-                let validation_summary = existing_info
-                    .and_then(|info| info.validation_summary)
-                    .unwrap_or_else(|| {
-                        summarize_bars(&data, symbol, resolution, dataset_kind, price_adjustment)
-                    });
-                let actual_start = data.first().map(|bar| bar.timestamp).unwrap_or(start_date);
-                let actual_end = data.last().map(|bar| bar.timestamp).unwrap_or(end_date);
-
-                self.catalog
-                    .register_symbol_data(
+                    .await?
+                    .is_none()
+                {
+                    let (dataset_kind, price_adjustment) =
+                        (DatasetKind::External, PriceAdjustmentMode::Raw);
+                    let validation_summary =
+                        summarize_bars(&data, symbol, resolution, dataset_kind, price_adjustment);
+                    self.register_stored_bars(
                         symbol,
-                        actual_start,
-                        actual_end,
                         resolution,
-                        data.len() as u64,
                         dataset_kind,
                         price_adjustment,
                         Some(&validation_summary),
                     )
                     .await?;
+                }
 
                 // Cache for future use
                 self.cache.store_bars(symbol, &data, resolution).await?;
+                tracing::debug!("Loaded {} bars from storage", data.len());
                 return Ok(data);
             }
         }
 
-        // Fetch from providers
-        for provider in &mut self.providers {
-            if provider.supports_symbol(symbol) {
-                if let Ok(data) = provider
-                    .fetch_bars(symbol, start_date, end_date, resolution)
-                    .await
-                {
-                    let dataset_kind = provider.dataset_kind();
-                    let price_adjustment = provider.price_adjustment_mode();
-                    let validation_summary =
-                        summarize_bars(&data, symbol, resolution, dataset_kind, price_adjustment);
+        let no_data = || -> gb_types::GbError {
+            gb_types::DataError::NoDataInRange {
+                symbol: symbol.to_string(),
+                start: start_date.to_rfc3339(),
+                end: end_date.to_rfc3339(),
+            }
+            .into()
+        };
+
+        // Fetch from providers what the catalog says is missing, best suited first
+        let ranges = self
+            .fetch_ranges(symbol, resolution, start_date, end_date, false)
+            .await?;
+        let (Some(&(fetch_start, _)), Some(&(_, fetch_end))) = (ranges.first(), ranges.last())
+        else {
+            tracing::debug!("Providers were already searched for {} in range", symbol);
+            return Err(no_data());
+        };
+        for (index, fetch_resolution) in self.plan_fetches(symbol, fetch_start, resolution) {
+            let provider = &mut self.providers[index];
+            let mut data = match provider
+                .fetch_bars(symbol, fetch_start, fetch_end, fetch_resolution)
+                .await
+            {
+                Ok(data) => data,
+                Err(error) => {
+                    tracing::debug!(
+                        "{} could not serve {} at {}: {}",
+                        provider.name(),
+                        symbol,
+                        fetch_resolution,
+                        error
+                    );
+                    continue;
+                }
+            };
+            for bar in &mut data {
+                bar.provenance
+                    .provider
+                    .get_or_insert_with(|| provider.name().to_string());
+            }
+            if fetch_resolution != resolution {
+                tracing::info!(
+                    "Resampling {} bars for {} from {} to {} ({})",
+                    data.len(),
+                    symbol,
+                    fetch_resolution,
+                    resolution,
+                    provider.name()
+                );
+                data = resample_bars(&data, fetch_resolution, resolution);
+            }
+            let dataset_kind = provider.dataset_kind();
+            let price_adjustment = provider.price_adjustment_mode();
+            let validation_summary =
+                summarize_bars(&data, symbol, resolution, dataset_kind, price_adjustment);
+
+            // Store and then reload the merged/deduped view for downstream consumers.
+            let provider_name = provider.name().to_string();
+            self.persist_bars(
+                symbol,
+                &data,
+                resolution,
+                dataset_kind,
+                price_adjustment,
+                Some(&validation_summary),
+            )
+            .await?;
+            let stored_data = self
+                .storage
+                .load_bars(symbol, start_date, end_date, resolution)
+                .await?;
+            self.cache
+                .store_bars(symbol, &stored_data, resolution)
+                .await?;
+            self.catalog
+                .record_search(symbol, resolution, fetch_start)
+                .await?;
+
+            tracing::debug!("Loaded {} bars from {}", stored_data.len(), provider_name);
+            return Ok(stored_data);
+        }
+
+        Err(no_data())
+    }
+
+    /// Stretches of `start..=end` a provider fetch for `symbol` should cover,
+    /// judged by the catalog: everything after the last stored bar, and
+    /// everything before the first one that providers have not already been
+    /// searched for. `force` searches the known-empty stretch again. An
+    /// unregistered symbol is missing the whole range.
+    pub async fn fetch_ranges(
+        &self,
+        symbol: &gb_types::Symbol,
+        resolution: gb_types::Resolution,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+        force: bool,
+    ) -> GbResult<Vec<(chrono::DateTime<chrono::Utc>, chrono::DateTime<chrono::Utc>)>> {
+        let Some(info) = self
+            .catalog
+            .get_symbol_info_for_resolution(symbol, resolution)
+            .await?
+        else {
+            return Ok(vec![(start, end)]);
+        };
+        let known_from = match info.searched_from {
+            Some(searched_from) if !force => searched_from.min(info.first_date),
+            _ => info.first_date,
+        };
+
+        let mut ranges = Vec::new();
+        if start < known_from {
+            ranges.push((start, end.min(known_from)));
+        }
+        if end > info.last_date {
+            ranges.push((start.max(info.last_date), end));
+        }
+        Ok(ranges)
+    }
+
+    /// Every bar stored for `symbol` at `resolution`; empty when nothing is.
+    async fn stored_bars(
+        &self,
+        symbol: &gb_types::Symbol,
+        resolution: gb_types::Resolution,
+    ) -> GbResult<Vec<gb_types::Bar>> {
+        match self
+            .storage
+            .load_bars(
+                symbol,
+                chrono::DateTime::<chrono::Utc>::MIN_UTC,
+                chrono::DateTime::<chrono::Utc>::MAX_UTC,
+                resolution,
+            )
+            .await
+        {
+            Ok(bars) => Ok(bars),
+            Err(gb_types::GbError::Data(gb_types::DataError::SymbolNotFound { .. })) => {
+                Ok(Vec::new())
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Register the range and count of the bars persisted for `symbol` at
+    /// `resolution`, however much of the requested range they cover. Without
+    /// a `validation_summary`, one summarizing the stored bars is recorded.
+    async fn register_stored_bars(
+        &mut self,
+        symbol: &gb_types::Symbol,
+        resolution: gb_types::Resolution,
+        dataset_kind: DatasetKind,
+        price_adjustment: PriceAdjustmentMode,
+        validation_summary: Option<&DataValidationSummary>,
+    ) -> GbResult<()> {
+        let stored = self.stored_bars(symbol, resolution).await?;
+        let (Some(first), Some(last)) = (
+            stored.iter().map(|bar| bar.timestamp).min(),
+            stored.iter().map(|bar| bar.timestamp).max(),
+        ) else {
+            return Ok(());
+        };
+        let summary = match validation_summary {
+            Some(summary) => summary.clone(),
+            None => summarize_bars(&stored, symbol, resolution, dataset_kind, price_adjustment),
+        };
+        self.catalog
+            .register_symbol_data(
+                symbol,
+                first,
+                last,
+                resolution,
+                stored.len() as u64,
+                dataset_kind,
+                price_adjustment,
+                Some(&summary),
+            )
+            .await
+    }
 
-                    // Store and then reload the merged/deduped view for downstream consumers.
-                    self.storage.save_bars(symbol, &data, resolution).await?;
-                    let stored_data = self
-                        .storage
-                        .load_bars(symbol, start_date, end_date, resolution)
-                        .await?;
-                    self.cache
-                        .store_bars(symbol, &stored_data, resolution)
-                        .await?;
-
-                    let actual_start = stored_data
-                        .first()
-                        .map(|bar| bar.timestamp)
-                        .unwrap_or(start_date);
-                    let actual_end = stored_data
-                        .last()
-                        .map(|bar| bar.timestamp)
-                        .unwrap_or(end_date);
-
-                    self.catalog
-                        .register_symbol_data(
+    /// Merge `bars` into storage and register the stored extent, one
+    /// [`WriteStage`] at a time under a [`WriteIntent`], so that a crash at
+    /// any point is settled by [`recover_writes`](Self::recover_writes).
+    async fn persist_bars(
+        &mut self,
+        symbol: &gb_types::Symbol,
+        bars: &[gb_types::Bar],
+        resolution: gb_types::Resolution,
+        dataset_kind: DatasetKind,
+        price_adjustment: PriceAdjustmentMode,
+        validation_summary: Option<&DataValidationSummary>,
+    ) -> GbResult<()> {
+        let Some(intent) =
+            self.write_intent(symbol, bars, resolution, dataset_kind, price_adjustment)
+        else {
+            return self
+                .register_stored_bars(
+                    symbol,
+                    resolution,
+                    dataset_kind,
+                    price_adjustment,
+                    validation_summary,
+                )
+                .await;
+        };
+        for stage in WriteStage::ALL {
+            if let Err(error) = self
+                .run_write_stage(&intent, stage, bars, validation_summary)
+                .await
+            {
+                if stage != WriteStage::RecordIntent {
+                    let mut recovery = WriteRecovery::default();
+                    if let Err(settle_error) = self.settle_write(&intent, &mut recovery).await {
+                        tracing::warn!(
+                            "Could not settle the failed write of {}: {}",
                             symbol,
-                            actual_start,
-                            actual_end,
-                            resolution,
-                            stored_data.len() as u64,
-                            dataset_kind,
-                            price_adjustment,
-                            Some(&validation_summary),
-                        )
-                        .await?;
-
-                    return Ok(stored_data);
+                            settle_error
+                        );
+                    }
                 }
+                return Err(error);
             }
         }
+        Ok(())
+    }
+
+    /// The intent to write `bars`; `None` when there are none.
+    fn write_intent(
+        &self,
+        symbol: &gb_types::Symbol,
+        bars: &[gb_types::Bar],
+        resolution: gb_types::Resolution,
+        dataset_kind: DatasetKind,
+        price_adjustment: PriceAdjustmentMode,
+    ) -> Option<WriteIntent> {
+        let start = bars.iter().map(|bar| bar.timestamp).min()?;
+        let end = bars.iter().map(|bar| bar.timestamp).max()?;
+        let target_path = self.storage.get_storage_path(symbol, resolution);
+        Some(WriteIntent {
+            id: Uuid::new_v4(),
+            symbol: symbol.clone(),
+            resolution,
+            start,
+            end,
+            staged_path: StorageManager::staged_path(&target_path),
+            target_path,
+            dataset_kind,
+            price_adjustment,
+            recorded_at: chrono::Utc::now(),
+        })
+    }
 
-        Err(gb_types::DataError::NoDataInRange {
-            symbol: symbol.to_string(),
-            start: start_date.to_rfc3339(),
-            end: end_date.to_rfc3339(),
+    async fn run_write_stage(
+        &mut self,
+        intent: &WriteIntent,
+        stage: WriteStage,
+        bars: &[gb_types::Bar],
+        validation_summary: Option<&DataValidationSummary>,
+    ) -> GbResult<()> {
+        match stage {
+            WriteStage::RecordIntent => self.intents.record(intent),
+            WriteStage::StageBars => self
+                .storage
+                .stage_bars(&intent.symbol, bars, intent.resolution)
+                .await
+                .map(|_| ()),
+            WriteStage::Install => {
+                StorageManager::install_staged(&intent.staged_path, &intent.target_path)
+            }
+            WriteStage::Register => {
+                self.register_stored_bars(
+                    &intent.symbol,
+                    intent.resolution,
+                    intent.dataset_kind,
+                    intent.price_adjustment,
+                    validation_summary,
+                )
+                .await
+            }
+            WriteStage::ClearIntent => self.intents.clear(intent.id),
         }
-        .into())
+    }
+
+    /// Settle every bar write a crash interrupted so that storage and the
+    /// catalog agree again: a staged file that reads back whole is renamed
+    /// into place, an incomplete one is discarded, and registrations are
+    /// rewritten from the stored bars. Runs whenever a data directory is
+    /// opened.
+    pub async fn recover_writes(&mut self) -> GbResult<WriteRecovery> {
+        let mut recovery = WriteRecovery::default();
+        for intent in self.intents.pending()? {
+            self.settle_write(&intent, &mut recovery).await?;
+        }
+        Ok(recovery)
+    }
+
+    async fn settle_write(
+        &mut self,
+        intent: &WriteIntent,
+        recovery: &mut WriteRecovery,
+    ) -> GbResult<()> {
+        if intent.staged_path.exists() {
+            let whole = StorageManager::load_all_bars_from_path(
+                &intent.staged_path,
+                &intent.symbol,
+                intent.resolution,
+            )
+            .is_ok();
+            if !whole {
+                // Neither the stored file nor the catalog changed yet.
+                std::fs::remove_file(&intent.staged_path)?;
+                recovery.rolled_back += 1;
+                return self.intents.clear(intent.id);
+            }
+            StorageManager::install_staged(&intent.staged_path, &intent.target_path)?;
+            recovery.replayed += 1;
+        } else {
+            recovery.registered += 1;
+        }
+
+        if self
+            .stored_bars(&intent.symbol, intent.resolution)
+            .await?
+            .is_empty()
+        {
+            self.catalog
+                .remove_symbol_data(&intent.symbol, intent.resolution)
+                .await?;
+        } else {
+            self.register_stored_bars(
+                &intent.symbol,
+                intent.resolution,
+                intent.dataset_kind,
+                intent.price_adjustment,
+                None,
+            )
+            .await?;
+        }
+        self.cache.invalidate_symbol(&intent.symbol);
+        self.intents.clear(intent.id)
+    }
+
+    /// Audit every catalog registration against storage: rewrite ranges and
+    /// counts that disagree with the stored bars, and drop registrations
+    /// with nothing stored behind them, so [`fetch_ranges`](Self::fetch_ranges)
+    /// never trusts coverage that is not there.
+    pub async fn reconcile_catalog(&mut self) -> GbResult<CatalogReconciliation> {
+        let mut report = CatalogReconciliation::default();
+        for info in self.catalog.list_symbol_data().await? {
+            report.checked += 1;
+            let stored = self.stored_bars(&info.symbol, info.resolution).await?;
+            let (Some(first), Some(last)) = (
+                stored.iter().map(|bar| bar.timestamp).min(),
+                stored.iter().map(|bar| bar.timestamp).max(),
+            ) else {
+                self.catalog
+                    .remove_symbol_data(&info.symbol, info.resolution)
+                    .await?;
+                report.removed += 1;
+                continue;
+            };
+            let records = stored.len() as u64;
+            if (first, last, records) != (info.first_date, info.last_date, info.record_count) {
+                tracing::info!(
+                    "Catalog claimed {} bars of {} at {} from {} to {}; storage holds {} from {} to {}",
+                    info.record_count,
+                    info.symbol,
+                    info.resolution,
+                    info.first_date,
+                    info.last_date,
+                    records,
+                    first,
+                    last
+                );
+                self.catalog
+                    .register_symbol_data(
+                        &info.symbol,
+                        first,
+                        last,
+                        info.resolution,
+                        records,
+                        info.dataset_kind,
+                        info.price_adjustment,
+                        info.validation_summary.as_ref(),
+                    )
+                    .await?;
+                report.corrected += 1;
+            }
+        }
+        Ok(report)
+    }
+
+    /// Providers able to serve `symbol` at `resolution`, as pairs of
+    /// provider index and the resolution to fetch, best first: providers
+    /// whose history reaches `start`, then those serving the resolution
+    /// natively rather than by resampling finer bars, then those with looser
+    /// rate limits, then in the order they were added.
+    fn plan_fetches(
+        &self,
+        symbol: &gb_types::Symbol,
+        start: chrono::DateTime<chrono::Utc>,
+        resolution: gb_types::Resolution,
+    ) -> Vec<(usize, gb_types::Resolution)> {
+        let now = chrono::Utc::now();
+        let mut plans: Vec<_> = self
+            .providers
+            .iter()
+            .enumerate()
+            .filter(|(_, provider)| provider.supports_symbol(symbol))
+            .filter_map(|(index, provider)| {
+                let capabilities = provider.capabilities();
+                if !capabilities.supports_asset_class(symbol.asset_class) {
+                    return None;
+                }
+                let fetch_resolution = capabilities.fetch_resolution_for(resolution)?;
+                if fetch_resolution != resolution && !self.allow_resampling {
+                    return None;
+                }
+                let rank = (
+                    !capabilities.reaches_back_to(start, now),
+                    fetch_resolution != resolution,
+                    capabilities.rate_limit,
+                    index,
+                );
+                Some((rank, index, fetch_resolution))
+            })
+            .collect();
+        plans.sort_by_key(|(rank, _, _)| *rank);
+        plans
+            .into_iter()
+            .map(|(_, index, fetch_resolution)| (index, fetch_resolution))
+            .collect()
+    }
+
+    /// Persist `bars` for `symbol` as they are, merging with anything
+    /// already stored, and record the stored extent in the catalog. Returns
+    /// the number of bars stored for the symbol at `resolution` afterwards.
+    pub async fn ingest_bars(
+        &mut self,
+        symbol: &gb_types::Symbol,
+        bars: &[gb_types::Bar],
+        resolution: gb_types::Resolution,
+        dataset_kind: DatasetKind,
+        price_adjustment: PriceAdjustmentMode,
+    ) -> GbResult<u64> {
+        if bars.is_empty() {
+            return Ok(0);
+        }
+        self.persist_bars(
+            symbol,
+            bars,
+            resolution,
+            dataset_kind,
+            price_adjustment,
+            None,
+        )
+        .await?;
+        self.cache.invalidate_symbol(symbol);
+
+        Ok(self
+            .catalog
+            .get_symbol_info_for_resolution(symbol, resolution)
+            .await?
+            .map_or(0, |info| info.record_count))
+    }
+
+    /// Catalog statistics with a per-symbol, per-resolution breakdown whose
+    /// sizes come from storage. With `CoverageScan::Exact` the counts and
+    /// ranges are read from the stored files rather than the catalog; either
+    /// way the totals are summed from the breakdown, so they always agree
+    /// with it.
+    pub async fn catalog_stats(&self, scan: CoverageScan) -> GbResult<CatalogStats> {
+        let mut stats = self.catalog.get_catalog_stats().await?;
+        for coverage in &mut stats.breakdown {
+            coverage.bytes = self
+                .storage
+                .bar_file_size(&coverage.symbol, coverage.resolution)?
+                .unwrap_or(0);
+            if scan == CoverageScan::Exact {
+                let bars = self
+                    .stored_bars(&coverage.symbol, coverage.resolution)
+                    .await?;
+                coverage.records = bars.len() as u64;
+                if let (Some(first), Some(last)) = (
+                    bars.iter().map(|bar| bar.timestamp).min(),
+                    bars.iter().map(|bar| bar.timestamp).max(),
+                ) {
+                    coverage.first = first;
+                    coverage.last = last;
+                }
+            }
+        }
+
+        let held = stats
+            .breakdown
+            .iter()
+            .filter(|coverage| coverage.records > 0);
+        stats.total_records = stats
+            .breakdown
+            .iter()
+            .map(|coverage| coverage.records)
+            .sum();
+        stats.earliest_date = held.clone().map(|coverage| coverage.first).min();
+        stats.latest_date = held.map(|coverage| coverage.last).max();
+        Ok(stats)
+    }
+
+    /// Runs of expected `resolution` bars for `symbol` missing from storage
+    /// between `start` and `end`, without fetching from providers. A symbol
+    /// with nothing stored is one gap over the whole range.
+    pub async fn find_gaps(
+        &self,
+        symbol: &gb_types::Symbol,
+        resolution: gb_types::Resolution,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> GbResult<Vec<DataGap>> {
+        let bars = match self.storage.load_bars(symbol, start, end, resolution).await {
+            Ok(bars) => bars,
+            Err(gb_types::GbError::Data(gb_types::DataError::SymbolNotFound { .. })) => Vec::new(),
+            Err(error) => return Err(error),
+        };
+        Ok(find_gaps(&bars, symbol, resolution, start, end))
+    }
+
+    /// Remove `symbol`'s stored bars, catalog entries and cached data,
+    /// returning how many files were deleted.
+    pub async fn purge_symbol(&mut self, symbol: &gb_types::Symbol) -> GbResult<u64> {
+        let removed = self.storage.delete_symbol(symbol)?;
+        self.catalog.remove_symbol(symbol).await?;
+        self.cache.invalidate_symbol(symbol);
+        Ok(removed)
+    }
+
+    /// Persist option quotes for `underlying` and record the stored extent of
+    /// its chain history in the catalog.
+    pub async fn ingest_option_quotes(
+        &mut self,
+        underlying: &gb_types::Symbol,
+        quotes: &[OptionQuote],
+    ) -> GbResult<()> {
+        self.storage.save_option_quotes(underlying, quotes).await?;
+
+        let dates = self.storage.list_option_quote_dates(underlying)?;
+        let (Some(first_date), Some(last_date)) = (
+            dates.keys().next().copied(),
+            dates.keys().next_back().copied(),
+        ) else {
+            return Ok(());
+        };
+        let expiry_count = dates
+            .values()
+            .flatten()
+            .filter_map(|path| path.parent())
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+
+        self.catalog
+            .register_option_chain_data(
+                underlying,
+                first_date,
+                last_date,
+                dates.len() as u64,
+                expiry_count as u64,
+            )
+            .await
+    }
+
+    /// The option chain for `underlying` as it was last quoted at or before
+    /// `as_of`; quotes taken later are never visible.
+    pub async fn load_option_chain(
+        &self,
+        underlying: &gb_types::Symbol,
+        as_of: chrono::DateTime<chrono::Utc>,
+    ) -> GbResult<Option<OptionChainSnapshot>> {
+        self.storage.load_option_snapshot(underlying, as_of).await
     }
 }
 
@@ -217,4 +792,694 @@ mod tests {
         assert!(!bars.is_empty());
         assert_eq!(bars[0].symbol, symbol);
     }
+
+    #[tokio::test]
+    async fn ingest_bars_merges_with_stored_history_and_registers_it() {
+        let mut manager = DataManager::new_ephemeral("gb-data-ingest").await.unwrap();
+        let symbol = Symbol::equity("AAPL");
+        let at = |day| Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap();
+        let mut provider = SampleDataProvider::new();
+        let january = provider
+            .fetch_bars(&symbol, at(1), at(20), Resolution::Day)
+            .await
+            .unwrap();
+        let first = &january[..january.len() / 2];
+
+        let stored = manager
+            .ingest_bars(
+                &symbol,
+                first,
+                Resolution::Day,
+                DatasetKind::UserProvided,
+                PriceAdjustmentMode::Raw,
+            )
+            .await
+            .unwrap();
+        assert_eq!(stored, first.len() as u64);
+        // Overlapping batches are deduplicated by timestamp.
+        let stored = manager
+            .ingest_bars(
+                &symbol,
+                &january[1..],
+                Resolution::Day,
+                DatasetKind::UserProvided,
+                PriceAdjustmentMode::Raw,
+            )
+            .await
+            .unwrap();
+        assert_eq!(stored, january.len() as u64);
+
+        let info = manager
+            .catalog
+            .get_symbol_info_for_resolution(&symbol, Resolution::Day)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.record_count, january.len() as u64);
+        assert_eq!(info.first_date, january[0].timestamp);
+        assert_eq!(info.last_date, january[january.len() - 1].timestamp);
+        assert_eq!(info.dataset_kind, DatasetKind::UserProvided);
+        let loaded = manager
+            .load_data(&symbol, at(1), at(20), Resolution::Day)
+            .await
+            .unwrap();
+        assert_eq!(loaded, january);
+    }
+
+    #[tokio::test]
+    async fn writes_interrupted_at_any_stage_are_settled_on_reopening() {
+        let symbol = Symbol::equity("AAPL");
+        let at = |day| Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap();
+        let january = SampleDataProvider::new()
+            .fetch_bars(&symbol, at(1), at(31), Resolution::Day)
+            .await
+            .unwrap();
+        let (first, rest) = january.split_at(january.len() / 2);
+
+        // Crash after each prefix of the stages, and once mid-way through
+        // writing the staged file.
+        for (completed, truncate_staged) in (0..WriteStage::ALL.len())
+            .map(|completed| (completed, false))
+            .chain([(2, true)])
+        {
+            let dir = tempfile::tempdir().unwrap();
+            let mut manager = DataManager::new_with_data_dir(dir.path()).await.unwrap();
+            manager
+                .ingest_bars(
+                    &symbol,
+                    first,
+                    Resolution::Day,
+                    DatasetKind::UserProvided,
+                    PriceAdjustmentMode::Raw,
+                )
+                .await
+                .unwrap();
+            let intent = manager
+                .write_intent(
+                    &symbol,
+                    rest,
+                    Resolution::Day,
+                    DatasetKind::UserProvided,
+                    PriceAdjustmentMode::Raw,
+                )
+                .unwrap();
+            for stage in &WriteStage::ALL[..completed] {
+                manager
+                    .run_write_stage(&intent, *stage, rest, None)
+                    .await
+                    .unwrap();
+            }
+            if truncate_staged {
+                let staged = std::fs::read(&intent.staged_path).unwrap();
+                std::fs::write(&intent.staged_path, &staged[..staged.len() / 2]).unwrap();
+            }
+            drop(manager);
+
+            let reopened = DataManager::new_with_data_dir(dir.path()).await.unwrap();
+            let case = format!("{} stages, truncated: {}", completed, truncate_staged);
+            assert!(reopened.intents.pending().unwrap().is_empty(), "{}", case);
+            assert!(!intent.staged_path.exists(), "{}", case);
+            let stored = reopened
+                .stored_bars(&symbol, Resolution::Day)
+                .await
+                .unwrap();
+            let landed = completed >= 2 && !truncate_staged;
+            let expected = if landed { &january[..] } else { first };
+            assert_eq!(stored, expected, "{}", case);
+            let info = reopened
+                .catalog
+                .get_symbol_info_for_resolution(&symbol, Resolution::Day)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(info.record_count, stored.len() as u64, "{}", case);
+            assert_eq!(info.first_date, stored[0].timestamp, "{}", case);
+            assert_eq!(info.last_date, stored.last().unwrap().timestamp, "{}", case);
+        }
+    }
+
+    #[tokio::test]
+    async fn catalog_stats_break_down_each_symbol_and_resolution() {
+        let mut manager = DataManager::new_ephemeral("gb-data-catalog-breakdown")
+            .await
+            .unwrap();
+        let mut provider = SampleDataProvider::new();
+        let at = |day, hour| Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap();
+        let (aapl, msft) = (Symbol::equity("AAPL"), Symbol::equity("MSFT"));
+        for (symbol, last_day) in [(&aapl, 10), (&msft, 5)] {
+            for resolution in [Resolution::Hour, Resolution::Day] {
+                let bars = provider
+                    .fetch_bars(symbol, at(1, 0), at(last_day, 0), resolution)
+                    .await
+                    .unwrap();
+                manager
+                    .ingest_bars(
+                        symbol,
+                        &bars,
+                        resolution,
+                        DatasetKind::Sample,
+                        PriceAdjustmentMode::Synthetic,
+                    )
+                    .await
+                    .unwrap();
+            }
+        }
+        // A registration that overstates what is stored
+        manager
+            .catalog
+            .register_symbol_data(
+                &msft,
+                at(1, 0),
+                at(31, 0),
+                Resolution::Day,
+                31,
+                DatasetKind::Sample,
+                PriceAdjustmentMode::Synthetic,
+                None,
+            )
+            .await
+            .unwrap();
+
+        let registered = manager
+            .catalog_stats(CoverageScan::Registered)
+            .await
+            .unwrap();
+        let exact = manager.catalog_stats(CoverageScan::Exact).await.unwrap();
+
+        let summary = |stats: &CatalogStats| {
+            stats
+                .breakdown
+                .iter()
+                .map(|coverage| {
+                    (
+                        coverage.symbol.symbol.clone(),
+                        coverage.resolution,
+                        coverage.records,
+                        coverage.first,
+                        coverage.last,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        let expected = |msft_days: u32, msft_last: u32| {
+            vec![
+                (
+                    "AAPL".to_string(),
+                    Resolution::Hour,
+                    217,
+                    at(1, 0),
+                    at(10, 0),
+                ),
+                ("AAPL".to_string(), Resolution::Day, 10, at(1, 0), at(10, 0)),
+                ("MSFT".to_string(), Resolution::Hour, 97, at(1, 0), at(5, 0)),
+                (
+                    "MSFT".to_string(),
+                    Resolution::Day,
+                    msft_days as u64,
+                    at(1, 0),
+                    at(msft_last, 0),
+                ),
+            ]
+        };
+        assert_eq!(summary(&registered), expected(31, 31));
+        assert_eq!(summary(&exact), expected(5, 5));
+
+        assert_eq!(exact.total_records, 217 + 10 + 97 + 5);
+        assert_eq!(registered.total_records, 217 + 10 + 97 + 31);
+        assert_eq!(exact.earliest_date, Some(at(1, 0)));
+        assert_eq!(exact.latest_date, Some(at(10, 0)));
+        assert_eq!(registered.latest_date, Some(at(31, 0)));
+        let storage = manager.storage.get_stats().unwrap();
+        assert_eq!(
+            exact
+                .breakdown
+                .iter()
+                .map(|coverage| coverage.bytes)
+                .sum::<u64>(),
+            storage.total_size_bytes
+        );
+        assert!(exact.breakdown.iter().all(|coverage| coverage.bytes > 0));
+    }
+
+    #[tokio::test]
+    async fn purge_symbol_removes_storage_catalog_and_cache_entries() {
+        let mut manager = DataManager::new_ephemeral("gb-data-purge").await.unwrap();
+        manager.add_provider(Box::new(SampleDataProvider::new()));
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2024, 1, 31, 0, 0, 0).unwrap();
+        let (aapl, msft) = (Symbol::equity("AAPL"), Symbol::equity("MSFT"));
+        for symbol in [&aapl, &msft] {
+            manager
+                .load_data(symbol, start, end, Resolution::Day)
+                .await
+                .unwrap();
+        }
+        assert!(manager
+            .find_gaps(&aapl, Resolution::Day, start, end)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(manager.purge_symbol(&aapl).await.unwrap(), 1);
+        let listed: Vec<Symbol> = manager
+            .catalog
+            .list_symbol_data()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|info| info.symbol)
+            .collect();
+        assert_eq!(listed, vec![msft.clone()]);
+        let stats = manager.storage.get_stats().unwrap();
+        assert_eq!(stats.total_files, 1);
+        assert_eq!(stats.symbols.len(), 1);
+        assert_eq!(stats.symbols[0].symbol, msft);
+        assert_eq!(stats.symbols[0].size_bytes, stats.total_size_bytes);
+        assert!(manager
+            .cache
+            .get_bars(&aapl, start, end, Resolution::Day)
+            .await
+            .unwrap()
+            .is_none());
+
+        // With nothing stored the whole range is one gap.
+        let gaps = manager
+            .find_gaps(&aapl, Resolution::Day, start, end)
+            .await
+            .unwrap();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].missing_bars, 23);
+    }
+
+    fn option_quote(
+        symbol: &Symbol,
+        day: u32,
+        hour: u32,
+        kind: gb_options::OptionKind,
+        bid: i64,
+    ) -> OptionQuote {
+        let expiration = chrono::NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        let strike = rust_decimal::Decimal::from(150);
+        OptionQuote {
+            contract_id: occ_contract_id(&symbol.symbol, expiration, kind, strike),
+            underlying: symbol.clone(),
+            expiration,
+            strike,
+            kind,
+            timestamp: Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap(),
+            bid: rust_decimal::Decimal::from(bid),
+            ask: rust_decimal::Decimal::from(bid + 1),
+            last: None,
+            volume: 10,
+            open_interest: 100,
+            implied_volatility: Some(0.25),
+        }
+    }
+
+    #[tokio::test]
+    async fn load_option_chain_returns_latest_snapshot_without_lookahead() {
+        use gb_options::OptionKind::{Call, Put};
+
+        let mut manager = DataManager::new_ephemeral("gb-data-option-chain")
+            .await
+            .unwrap();
+        let symbol = Symbol::equity("AAPL");
+        let first_day = vec![
+            option_quote(&symbol, 2, 21, Call, 5),
+            option_quote(&symbol, 2, 21, Put, 4),
+        ];
+        let second_day = vec![
+            option_quote(&symbol, 3, 21, Call, 7),
+            option_quote(&symbol, 3, 21, Put, 3),
+        ];
+        manager
+            .ingest_option_quotes(&symbol, &first_day)
+            .await
+            .unwrap();
+        manager
+            .ingest_option_quotes(&symbol, &second_day)
+            .await
+            .unwrap();
+
+        let at = |day, hour| Utc.with_ymd_and_hms(2026, 3, day, hour, 0, 0).unwrap();
+        assert!(manager
+            .load_option_chain(&symbol, at(2, 20))
+            .await
+            .unwrap()
+            .is_none());
+
+        let expiration = first_day[0].expiration;
+        let strike = first_day[0].strike;
+        for (as_of, expected) in [
+            (at(2, 21), &first_day),
+            // Same day as the second snapshot but before it was taken.
+            (at(3, 12), &first_day),
+            (at(3, 21), &second_day),
+            (at(10, 0), &second_day),
+        ] {
+            let snapshot = manager
+                .load_option_chain(&symbol, as_of)
+                .await
+                .unwrap()
+                .unwrap();
+            assert_eq!(snapshot.timestamp, expected[0].timestamp, "as of {as_of}");
+            assert_eq!(snapshot.len(), 2);
+            assert_eq!(snapshot.get(expiration, strike, Call), Some(&expected[0]));
+            assert_eq!(snapshot.get(expiration, strike, Put), Some(&expected[1]));
+        }
+
+        let info = manager
+            .catalog
+            .get_option_chain_info(&symbol)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.first_date, at(2, 0).date_naive());
+        assert_eq!(info.last_date, at(3, 0).date_naive());
+        assert_eq!(info.quote_days, 2);
+        assert_eq!(info.expiry_count, 1);
+
+        // Option partitions are not mistaken for bar symbols.
+        assert!(manager.storage.list_symbols().unwrap().is_empty());
+    }
+
+    type FetchRange = (chrono::DateTime<Utc>, chrono::DateTime<Utc>);
+
+    /// Serves flat bars at the resolutions its capabilities list, from
+    /// `listed_from` on, and records the ranges it is asked for.
+    #[derive(Debug)]
+    struct MockProvider {
+        name: &'static str,
+        capabilities: ProviderCapabilities,
+        listed_from: chrono::DateTime<Utc>,
+        requests: std::sync::Arc<std::sync::Mutex<Vec<FetchRange>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl DataProvider for MockProvider {
+        fn supports_symbol(&self, _symbol: &Symbol) -> bool {
+            true
+        }
+
+        async fn fetch_bars(
+            &mut self,
+            symbol: &Symbol,
+            start_date: chrono::DateTime<Utc>,
+            end_date: chrono::DateTime<Utc>,
+            resolution: Resolution,
+        ) -> GbResult<Vec<gb_types::Bar>> {
+            if !self.capabilities.resolutions.contains(&resolution) {
+                return Err(gb_types::DataError::UnsupportedResolution {
+                    provider: self.name.to_string(),
+                    resolution: resolution.to_string(),
+                }
+                .into());
+            }
+            self.requests.lock().unwrap().push((start_date, end_date));
+            let step = chrono::Duration::seconds(resolution.to_seconds().unwrap() as i64);
+            let mut bars = Vec::new();
+            let mut timestamp = start_date.max(self.listed_from);
+            while timestamp <= end_date {
+                let price = rust_decimal::Decimal::from(100 + bars.len() as i64);
+                bars.push(gb_types::Bar::new(
+                    symbol.clone(),
+                    timestamp,
+                    price,
+                    price,
+                    price,
+                    price,
+                    rust_decimal::Decimal::ONE,
+                    resolution,
+                ));
+                timestamp += step;
+            }
+            Ok(bars)
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn config(&self) -> serde_json::Value {
+            serde_json::json!({ "type": "mock" })
+        }
+
+        fn capabilities(&self) -> ProviderCapabilities {
+            self.capabilities.clone()
+        }
+    }
+
+    fn mock(
+        name: &'static str,
+        resolutions: Vec<Resolution>,
+        rate_limit: RateLimitClass,
+    ) -> MockProvider {
+        MockProvider {
+            name,
+            capabilities: ProviderCapabilities {
+                resolutions,
+                rate_limit,
+                ..Default::default()
+            },
+            listed_from: chrono::DateTime::<Utc>::MIN_UTC,
+            requests: Default::default(),
+        }
+    }
+
+    async fn manager_with(prefix: &str, providers: Vec<MockProvider>) -> DataManager {
+        let mut manager = DataManager::new_ephemeral(prefix).await.unwrap();
+        for provider in providers {
+            manager.add_provider(Box::new(provider));
+        }
+        manager
+    }
+
+    fn january(day: u32) -> chrono::DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn providers_serving_a_resolution_natively_are_preferred() {
+        let mut manager = manager_with(
+            "gb-data-native-provider",
+            vec![
+                mock(
+                    "intraday",
+                    vec![Resolution::Hour],
+                    RateLimitClass::Unlimited,
+                ),
+                mock("daily", vec![Resolution::Day], RateLimitClass::Strict),
+            ],
+        )
+        .await;
+
+        let bars = manager
+            .load_data(
+                &Symbol::equity("AAPL"),
+                january(2),
+                january(5),
+                Resolution::Day,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(bars.len(), 4);
+        assert!(bars.iter().all(|bar| {
+            bar.provenance.provider.as_deref() == Some("daily")
+                && bar.provenance.resampled_from.is_none()
+        }));
+    }
+
+    #[tokio::test]
+    async fn finer_bars_are_resampled_when_no_provider_serves_the_resolution() {
+        let mut manager = manager_with(
+            "gb-data-resampled-provider",
+            vec![
+                mock("daily", vec![Resolution::Day], RateLimitClass::Strict),
+                mock(
+                    "intraday",
+                    vec![Resolution::Minute, Resolution::Hour],
+                    RateLimitClass::Moderate,
+                ),
+            ],
+        )
+        .await;
+        let symbol = Symbol::equity("AAPL");
+
+        // Daily bars cannot be split into hours, so only the intraday feed
+        // is asked, and for its coarsest resolution that fits.
+        let bars = manager
+            .load_data(&symbol, january(2), january(3), Resolution::FourHour)
+            .await
+            .unwrap();
+
+        assert_eq!(bars.len(), 7);
+        assert_eq!(bars[0].timestamp, january(2));
+        assert_eq!(bars[0].open, rust_decimal::Decimal::from(100));
+        assert_eq!(bars[0].close, rust_decimal::Decimal::from(103));
+        assert_eq!(bars[0].volume, rust_decimal::Decimal::from(4));
+        assert!(bars.iter().all(|bar| {
+            bar.resolution == Resolution::FourHour
+                && bar.provenance.provider.as_deref() == Some("intraday")
+                && bar.provenance.resampled_from == Some(Resolution::Hour)
+        }));
+
+        manager.allow_resampling = false;
+        assert!(manager
+            .load_data(&symbol, january(2), january(3), Resolution::Week)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn providers_whose_history_falls_short_are_tried_last() {
+        let mut shallow = mock("shallow", vec![Resolution::Day], RateLimitClass::Unlimited);
+        shallow.capabilities.max_history_days = Some(30);
+        let mut manager = manager_with(
+            "gb-data-history-depth",
+            vec![
+                shallow,
+                mock("deep", vec![Resolution::Day], RateLimitClass::Strict),
+            ],
+        )
+        .await;
+
+        let bars = manager
+            .load_data(
+                &Symbol::equity("AAPL"),
+                january(2),
+                january(5),
+                Resolution::Day,
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(bars[0].provenance.provider.as_deref(), Some("deep"));
+    }
+
+    #[tokio::test]
+    async fn catalog_claims_only_the_bars_a_late_listing_returned() {
+        let year = |year| Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0).unwrap();
+        let symbol = Symbol::equity("NEWCO");
+        let provider = MockProvider {
+            listed_from: year(2019),
+            ..mock("daily", vec![Resolution::Day], RateLimitClass::Unlimited)
+        };
+        let requests = provider.requests.clone();
+        let mut manager = manager_with("gb-data-late-listing", vec![provider]).await;
+
+        let bars = manager
+            .load_data(&symbol, year(2015), year(2024), Resolution::Day)
+            .await
+            .unwrap();
+        assert_eq!(bars[0].timestamp, year(2019));
+
+        let info = manager
+            .catalog
+            .get_symbol_info_for_resolution(&symbol, Resolution::Day)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.first_date, year(2019));
+        assert_eq!(info.last_date, year(2024));
+        assert_eq!(info.record_count, bars.len() as u64);
+        assert_eq!(info.searched_from, Some(year(2015)));
+
+        // Nothing before 2019 is worth asking for again, unless forced.
+        for (start, force, expected) in [
+            (year(2015), false, vec![]),
+            (year(2015), true, vec![(year(2015), year(2019))]),
+            (year(2010), false, vec![(year(2010), year(2015))]),
+        ] {
+            let ranges = manager
+                .fetch_ranges(&symbol, Resolution::Day, start, year(2024), force)
+                .await
+                .unwrap();
+            assert_eq!(ranges, expected, "from {start}, forced: {force}");
+        }
+        assert!(manager
+            .load_data(&symbol, year(2016), year(2018), Resolution::Day)
+            .await
+            .is_err());
+        assert_eq!(
+            requests.lock().unwrap().as_slice(),
+            [(year(2015), year(2024))]
+        );
+    }
+
+    #[tokio::test]
+    async fn reconcile_catalog_trims_registrations_to_stored_bars() {
+        let mut manager = DataManager::new_ephemeral("gb-data-reconcile")
+            .await
+            .unwrap();
+        let (aapl, msft) = (Symbol::equity("AAPL"), Symbol::equity("MSFT"));
+        let bars = SampleDataProvider::new()
+            .fetch_bars(&aapl, january(1), january(10), Resolution::Day)
+            .await
+            .unwrap();
+        manager
+            .ingest_bars(
+                &aapl,
+                &bars,
+                Resolution::Day,
+                DatasetKind::Sample,
+                PriceAdjustmentMode::Synthetic,
+            )
+            .await
+            .unwrap();
+        // An over-claim, and a registration with nothing stored at all
+        for (symbol, records) in [(&aapl, 31), (&msft, 5)] {
+            manager
+                .catalog
+                .register_symbol_data(
+                    symbol,
+                    january(1),
+                    january(31),
+                    Resolution::Day,
+                    records,
+                    DatasetKind::Sample,
+                    PriceAdjustmentMode::Synthetic,
+                    None,
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            manager
+                .fetch_ranges(&aapl, Resolution::Day, january(1), january(31), false)
+                .await
+                .unwrap(),
+            vec![]
+        );
+
+        let report = manager.reconcile_catalog().await.unwrap();
+        assert_eq!(
+            report,
+            CatalogReconciliation {
+                checked: 2,
+                corrected: 1,
+                removed: 1,
+            }
+        );
+        let listed = manager.catalog.list_symbol_data().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].symbol, aapl);
+        assert_eq!(listed[0].first_date, january(1));
+        assert_eq!(listed[0].last_date, january(10));
+        assert_eq!(listed[0].record_count, bars.len() as u64);
+        assert_eq!(
+            manager
+                .fetch_ranges(&aapl, Resolution::Day, january(1), january(31), false)
+                .await
+                .unwrap(),
+            vec![(january(10), january(31))]
+        );
+        assert_eq!(
+            manager.reconcile_catalog().await.unwrap(),
+            CatalogReconciliation {
+                checked: 1,
+                ..Default::default()
+            }
+        );
+    }
 }
@@ -35,33 +35,20 @@ impl BatchLoader {
         let path = file_path.as_ref();
         tracing::info!("Loading Parquet data from: {}", path.display());
 
-        if !path.exists() {
-            return Err(DataError::SymbolNotFound { 
-                symbol: symbol.to_string() 
-            }.into());
-        }
-
-        let file = fs::File::open(path)?;
+        let file = fs::File::open(path).map_err(|e| DataError::from_io(path, e))?;
         let reader = ParquetRecordBatchReaderBuilder::try_new(file)
-            .map_err(|e| DataError::LoadingFailed { 
-                message: format!("Failed to create Parquet reader for {}: {}", path.display(), e) 
-            })?
-            .build()
-            .map_err(|e| DataError::LoadingFailed { 
-                message: format!("Failed to build Parquet reader: {}", e) 
-            })?;
+            .and_then(|builder| builder.build())
+            .map_err(|e| DataError::corrupt_file(path, e))?;
 
         let mut all_bars = Vec::new();
 
         for batch_result in reader {
-            let batch = batch_result
-                .map_err(|e| DataError::LoadingFailed { 
-                    message: format!("Failed to read Parquet batch: {}", e) 
-                })?;
+            let batch = batch_result.map_err(|e| DataError::corrupt_file(path, e))?;
 
             let batch_bars = Self::record_batch_to_bars(&batch, symbol, resolution)?;
             all_bars.extend(batch_bars);
         }
+        all_bars.sort_by_key(|bar| bar.timestamp);
 
         tracing::info!("Loaded {} bars from Parquet file: {}", all_bars.len(), path.display());
         Ok(all_bars)
@@ -73,6 +60,12 @@ impl BatchLoader {
         symbol: &Symbol,
         resolution: Resolution,
     ) -> GbResult<Vec<Bar>> {
+        if batch.num_columns() < 7 {
+            return Err(DataError::Corruption {
+                message: format!("Parquet file has {} columns, expected at least 7", batch.num_columns()),
+            }.into());
+        }
+
         let timestamps = batch.column(1)
             .as_any()
             .downcast_ref::<TimestampNanosecondArray>()
@@ -134,6 +127,10 @@ impl BatchLoader {
             let low = Decimal::from_i128_with_scale(lows.value(i), 4);
             let close = Decimal::from_i128_with_scale(closes.value(i), 4);
             let volume = Decimal::from(volumes.value(i));
+            if let Err(e) = Self::check_ohlc(open, high, low, close) {
+                tracing::warn!("Skipping invalid Parquet row {}: {}", i, e);
+                continue;
+            }
             
             let bar = Bar::new(
                 symbol.clone(),
@@ -160,26 +157,49 @@ impl BatchLoader {
         resolution: Resolution,
         has_headers: bool,
     ) -> GbResult<Vec<Bar>> {
-        use csv::ReaderBuilder;
         use std::str::FromStr;
         
         let path = file_path.as_ref();
         tracing::info!("Loading CSV data from: {}", path.display());
         
+        let file = fs::File::open(path).map_err(|e| DataError::from_io(path, e))?;
+        let rows = self
+            .parse_csv_rows(file, symbol, resolution, has_headers)
+            .map_err(|e| DataError::corrupt_file(path, e))?;
+
         let mut bars = Vec::new();
-        let mut rdr = ReaderBuilder::new()
+        for (line_num, row) in rows.into_iter().enumerate() {
+            match row {
+                Ok(bar) => bars.push(bar),
+                Err(e) => {
+                    tracing::warn!("Skipping invalid record at line {}: {}", line_num + if has_headers { 2 } else { 1 }, e);
+                    continue;
+                }
+            }
+        }
+        bars.sort_by_key(|bar| bar.timestamp);
+
+        tracing::info!("Loaded {} bars from CSV file", bars.len());
+        Ok(bars)
+    }
+
+    /// Parse CSV rows from `reader`, one result per data row: the bar, or
+    /// why the row is invalid. Rows may have any number of fields; the
+    /// outer error covers input that is not CSV at all.
+    pub fn parse_csv_rows<R: std::io::Read>(
+        &self,
+        reader: R,
+        symbol: &Symbol,
+        resolution: Resolution,
+        has_headers: bool,
+    ) -> Result<Vec<GbResult<Bar>>, csv::Error> {
+        let mut rdr = csv::ReaderBuilder::new()
             .has_headers(has_headers)
-            .from_path(path)
-            .map_err(|e| DataError::LoadingFailed {
-                message: format!("Failed to open CSV file {}: {}", path.display(), e),
-            })?;
+            .flexible(true)
+            .from_reader(reader);
 
         let headers = if has_headers {
-            Some(rdr.headers()
-                .map_err(|e| DataError::LoadingFailed {
-                    message: format!("Failed to read CSV headers: {}", e),
-                })?
-                .clone())
+            Some(rdr.headers()?.clone())
         } else {
             None
         };
@@ -188,22 +208,12 @@ impl BatchLoader {
             tracing::debug!("CSV headers: {:?}", h);
         }
 
-        for (line_num, result) in rdr.records().enumerate() {
-            let record = result.map_err(|e| DataError::LoadingFailed {
-                message: format!("Failed to read CSV record at line {}: {}", line_num + if has_headers { 2 } else { 1 }, e),
-            })?;
-
-            match self.parse_csv_record(&record, symbol, resolution, &headers) {
-                Ok(bar) => bars.push(bar),
-                Err(e) => {
-                    tracing::warn!("Skipping invalid record at line {}: {}", line_num + if has_headers { 2 } else { 1 }, e);
-                    continue;
-                }
-            }
+        let mut rows = Vec::new();
+        for result in rdr.records() {
+            let record = result?;
+            rows.push(self.parse_csv_record(&record, symbol, resolution, &headers));
         }
-
-        tracing::info!("Loaded {} bars from CSV file", bars.len());
-        Ok(bars)
+        Ok(rows)
     }
 
     /// Parse a CSV record into a Bar struct
@@ -242,7 +252,22 @@ impl BatchLoader {
         let close = self.parse_decimal(record.get(close_idx).unwrap_or(""), "close")?;
         let volume = self.parse_decimal(record.get(volume_idx).unwrap_or(""), "volume")?;
 
-        // Validate OHLC relationships
+        Self::check_ohlc(open, high, low, close)?;
+
+        Ok(Bar::new(
+            symbol.clone(),
+            timestamp,
+            open,
+            high,
+            low,
+            close,
+            volume,
+            resolution,
+        ))
+    }
+
+    /// Validate OHLC relationships
+    fn check_ohlc(open: Decimal, high: Decimal, low: Decimal, close: Decimal) -> GbResult<()> {
         if high < low {
             return Err(DataError::ParseError {
                 message: format!("Invalid OHLC: high ({}) < low ({})", high, low),
@@ -258,17 +283,7 @@ impl BatchLoader {
                 message: format!("Invalid OHLC: low ({}) > open ({}) or close ({})", low, open, close),
             }.into());
         }
-
-        Ok(Bar::new(
-            symbol.clone(),
-            timestamp,
-            open,
-            high,
-            low,
-            close,
-            volume,
-            resolution,
-        ))
+        Ok(())
     }
 
     /// Detect CSV column positions from headers
@@ -820,10 +835,25 @@ mod tests {
         
         assert!(result.is_err());
         match result.unwrap_err() {
-            gb_types::GbError::Data(DataError::SymbolNotFound { .. }) => {
+            gb_types::GbError::Data(DataError::FileNotFound { .. }) => {
                 // Expected error type
             }
-            other => panic!("Expected SymbolNotFound error, got: {:?}", other),
+            other => panic!("Expected FileNotFound error, got: {:?}", other),
         }
     }
+
+    #[tokio::test]
+    async fn test_parquet_loading_corrupt_file() {
+        let loader = BatchLoader::new();
+        let symbol = Symbol::equity("AAPL");
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("AAPL.parquet");
+        std::fs::write(&path, b"not a parquet file").unwrap();
+
+        let error = loader.load_parquet_file(&path, &symbol, Resolution::Day).await.unwrap_err();
+
+        assert!(matches!(error, gb_types::GbError::Data(DataError::CorruptFile { .. })), "{error:?}");
+        assert_eq!(error.code(), "data.corrupt_file");
+        assert!(!error.is_retryable());
+    }
 } 
\ No newline at end of file
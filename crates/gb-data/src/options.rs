@@ -0,0 +1,391 @@
+//! Historical option chain records: the quote type, CSV ingestion and the
+//! snapshot returned by as-of lookups.
+//!
+//! Two CSV layouts are understood. The GlowBack layout has one quote per row
+//! with the columns
+//!
+//! ```text
+//! contract_id,expiration,strike,kind,timestamp,bid,ask,last,volume,open_interest,implied_volatility
+//! ```
+//!
+//! where `expiration` is `YYYY-MM-DD`, `kind` is `call`/`put` (or `C`/`P`),
+//! `timestamp` is RFC 3339 or `YYYY-MM-DD HH:MM:SS` in UTC, and `last` and
+//! `implied_volatility` may be empty. CBOE end-of-day option summaries
+//! (`underlying_symbol,quote_date,root,expiration,strike,option_type,...`)
+//! are read from their `bid_eod`, `ask_eod`, `close`, `trade_volume`,
+//! `open_interest` and, when present, `implied_volatility_1545` columns; their
+//! quotes are stamped at 21:00 UTC on the quote date, after the close in
+//! either daylight-saving regime.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use gb_options::OptionKind;
+use gb_types::{DataError, GbResult, Symbol};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// One quote for one option contract at one point in time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionQuote {
+    /// OCC-style contract identifier, e.g. `AAPL  260320C00150000`.
+    pub contract_id: String,
+    pub underlying: Symbol,
+    pub expiration: NaiveDate,
+    pub strike: Decimal,
+    pub kind: OptionKind,
+    pub timestamp: DateTime<Utc>,
+    pub bid: Decimal,
+    pub ask: Decimal,
+    pub last: Option<Decimal>,
+    pub volume: u64,
+    pub open_interest: u64,
+    pub implied_volatility: Option<f64>,
+}
+
+impl OptionQuote {
+    /// Midpoint of a two-sided quote, falling back to the last trade.
+    pub fn mid(&self) -> Option<Decimal> {
+        if self.bid > Decimal::ZERO && self.ask >= self.bid {
+            Some((self.bid + self.ask) / Decimal::TWO)
+        } else {
+            self.last
+        }
+    }
+}
+
+/// Every quote for one underlying taken at the same timestamp.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionChainSnapshot {
+    pub underlying: Symbol,
+    pub timestamp: DateTime<Utc>,
+    /// Sorted by expiration, strike, then calls before puts.
+    pub quotes: Vec<OptionQuote>,
+}
+
+impl OptionChainSnapshot {
+    pub fn new(underlying: Symbol, timestamp: DateTime<Utc>, mut quotes: Vec<OptionQuote>) -> Self {
+        quotes.sort_by(|a, b| {
+            (a.expiration, a.strike, a.kind == OptionKind::Put).cmp(&(
+                b.expiration,
+                b.strike,
+                b.kind == OptionKind::Put,
+            ))
+        });
+        Self {
+            underlying,
+            timestamp,
+            quotes,
+        }
+    }
+
+    /// Distinct expirations in the snapshot, ascending.
+    pub fn expirations(&self) -> Vec<NaiveDate> {
+        let mut expirations: Vec<_> = self.quotes.iter().map(|q| q.expiration).collect();
+        expirations.dedup();
+        expirations
+    }
+
+    /// Quote for one contract.
+    pub fn get(
+        &self,
+        expiration: NaiveDate,
+        strike: Decimal,
+        kind: OptionKind,
+    ) -> Option<&OptionQuote> {
+        self.quotes
+            .iter()
+            .find(|q| q.expiration == expiration && q.strike == strike && q.kind == kind)
+    }
+
+    pub fn len(&self) -> usize {
+        self.quotes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.quotes.is_empty()
+    }
+}
+
+/// OCC option symbol: root padded to six characters, `YYMMDD`, `C`/`P` and
+/// the strike in thousandths padded to eight digits.
+pub fn occ_contract_id(
+    root: &str,
+    expiration: NaiveDate,
+    kind: OptionKind,
+    strike: Decimal,
+) -> String {
+    let right = match kind {
+        OptionKind::Call => 'C',
+        OptionKind::Put => 'P',
+    };
+    let strike_thousandths = (strike * Decimal::from(1000)).trunc().to_u64().unwrap_or(0);
+    format!(
+        "{root:<6}{}{right}{strike_thousandths:08}",
+        expiration.format("%y%m%d")
+    )
+}
+
+/// CSV layouts [`load_option_quotes_csv`] can read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OptionCsvLayout {
+    GlowBack,
+    Cboe,
+}
+
+impl OptionCsvLayout {
+    /// Recognise the layout from a header row.
+    pub fn detect(headers: &csv::StringRecord) -> Option<Self> {
+        let has = |name: &str| headers.iter().any(|h| h.trim() == name);
+        if has("quote_date") && has("option_type") {
+            Some(Self::Cboe)
+        } else if has("contract_id") && has("timestamp") {
+            Some(Self::GlowBack)
+        } else {
+            None
+        }
+    }
+}
+
+/// Load option quotes for `underlying` from a CSV file in either supported
+/// layout. Rows of a CBOE file for other underlyings are skipped.
+pub fn load_option_quotes_csv<P: AsRef<Path>>(
+    file_path: P,
+    underlying: &Symbol,
+) -> GbResult<Vec<OptionQuote>> {
+    let path = file_path.as_ref();
+    let file = std::fs::File::open(path).map_err(|e| DataError::from_io(path, e))?;
+    let mut reader = csv::Reader::from_reader(file);
+    let headers = reader
+        .headers()
+        .map_err(|e| DataError::ParseError {
+            message: format!("Failed to read option CSV headers: {e}"),
+        })?
+        .clone();
+    let layout = OptionCsvLayout::detect(&headers).ok_or_else(|| DataError::InvalidFormat {
+        message: format!("Unrecognised option CSV layout in {}", path.display()),
+    })?;
+    let columns: HashMap<&str, usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (h.trim(), i))
+        .collect();
+
+    let mut quotes = Vec::new();
+    for (row, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| DataError::ParseError {
+            message: format!("Failed to read option CSV row {}: {e}", row + 1),
+        })?;
+        let fields = Row {
+            record: &record,
+            columns: &columns,
+        };
+        let quote = match layout {
+            OptionCsvLayout::GlowBack => parse_glowback_row(&fields, underlying),
+            OptionCsvLayout::Cboe => parse_cboe_row(&fields, underlying),
+        }
+        .map_err(|message| DataError::ParseError {
+            message: format!("Option CSV row {}: {message}", row + 1),
+        })?;
+        quotes.extend(quote);
+    }
+
+    tracing::info!(
+        "Loaded {} option quotes for {} from {}",
+        quotes.len(),
+        underlying,
+        path.display()
+    );
+    Ok(quotes)
+}
+
+/// Field access by column name for one CSV record.
+struct Row<'a> {
+    record: &'a csv::StringRecord,
+    columns: &'a HashMap<&'a str, usize>,
+}
+
+impl Row<'_> {
+    /// Trimmed value of `name`; `None` when the column is missing or empty.
+    fn optional(&self, name: &str) -> Option<&str> {
+        let value = self.record.get(*self.columns.get(name)?)?.trim();
+        (!value.is_empty()).then_some(value)
+    }
+
+    fn required(&self, name: &str) -> Result<&str, String> {
+        self.optional(name)
+            .ok_or_else(|| format!("missing value for {name}"))
+    }
+
+    fn parse<T: std::str::FromStr>(&self, name: &str) -> Result<T, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        let value = self.required(name)?;
+        value
+            .parse()
+            .map_err(|e| format!("could not parse {name} value '{value}': {e}"))
+    }
+
+    fn parse_optional<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        self.optional(name).map(|_| self.parse(name)).transpose()
+    }
+
+    fn date(&self, name: &str) -> Result<NaiveDate, String> {
+        let value = self.required(name)?;
+        NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|e| format!("could not parse {name} value '{value}': {e}"))
+    }
+
+    fn kind(&self, name: &str) -> Result<OptionKind, String> {
+        match self.required(name)?.to_ascii_lowercase().as_str() {
+            "c" | "call" => Ok(OptionKind::Call),
+            "p" | "put" => Ok(OptionKind::Put),
+            other => Err(format!("unknown option kind '{other}'")),
+        }
+    }
+}
+
+fn parse_glowback_row(row: &Row<'_>, underlying: &Symbol) -> Result<Option<OptionQuote>, String> {
+    let timestamp = row.required("timestamp")?;
+    let timestamp = DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+                .map(|t| Utc.from_utc_datetime(&t))
+        })
+        .map_err(|e| format!("could not parse timestamp '{timestamp}': {e}"))?;
+
+    Ok(Some(OptionQuote {
+        contract_id: row.required("contract_id")?.to_string(),
+        underlying: underlying.clone(),
+        expiration: row.date("expiration")?,
+        strike: row.parse("strike")?,
+        kind: row.kind("kind")?,
+        timestamp,
+        bid: row.parse("bid")?,
+        ask: row.parse("ask")?,
+        last: row.parse_optional("last")?,
+        volume: row.parse_optional("volume")?.unwrap_or(0),
+        open_interest: row.parse_optional("open_interest")?.unwrap_or(0),
+        implied_volatility: row.parse_optional("implied_volatility")?,
+    }))
+}
+
+fn parse_cboe_row(row: &Row<'_>, underlying: &Symbol) -> Result<Option<OptionQuote>, String> {
+    let row_underlying = row.required("underlying_symbol")?.trim_start_matches('^');
+    if !row_underlying.eq_ignore_ascii_case(&underlying.symbol) {
+        return Ok(None);
+    }
+
+    let expiration = row.date("expiration")?;
+    let strike: Decimal = row.parse("strike")?;
+    let kind = row.kind("option_type")?;
+    let root = row.optional("root").unwrap_or(row_underlying);
+    let timestamp = Utc.from_utc_datetime(
+        &row.date("quote_date")?
+            .and_hms_opt(21, 0, 0)
+            .expect("21:00:00 is a valid time"),
+    );
+    let volume = row.parse_optional("trade_volume")?.unwrap_or(0);
+    // CBOE reports a zero close for contracts that did not trade.
+    let last = row
+        .parse_optional::<Decimal>("close")?
+        .filter(|_| volume > 0);
+
+    Ok(Some(OptionQuote {
+        contract_id: occ_contract_id(root, expiration, kind, strike),
+        underlying: underlying.clone(),
+        expiration,
+        strike,
+        kind,
+        timestamp,
+        bid: row.parse("bid_eod")?,
+        ask: row.parse("ask_eod")?,
+        last,
+        volume,
+        open_interest: row.parse_optional("open_interest")?.unwrap_or(0),
+        implied_volatility: row
+            .parse_optional::<f64>("implied_volatility_1545")?
+            .filter(|iv| *iv > 0.0),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::prelude::FromPrimitive;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn write_csv(contents: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn occ_contract_id_pads_root_and_strike() {
+        let expiration = NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        assert_eq!(
+            occ_contract_id(
+                "AAPL",
+                expiration,
+                OptionKind::Call,
+                Decimal::from_f64(152.5).unwrap()
+            ),
+            "AAPL  260320C00152500"
+        );
+    }
+
+    #[test]
+    fn loads_glowback_layout() {
+        let file = write_csv(
+            "contract_id,expiration,strike,kind,timestamp,bid,ask,last,volume,open_interest,implied_volatility\n\
+             AAPL  260320C00150000,2026-03-20,150,call,2026-03-02T21:00:00Z,5.10,5.30,5.20,120,1500,0.25\n\
+             AAPL  260320P00150000,2026-03-20,150,P,2026-03-02 21:00:00,4.00,4.20,,0,900,\n",
+        );
+        let symbol = Symbol::equity("AAPL");
+        let quotes = load_option_quotes_csv(file.path(), &symbol).unwrap();
+
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes[0].kind, OptionKind::Call);
+        assert_eq!(quotes[0].last, Some(Decimal::new(520, 2)));
+        assert_eq!(quotes[0].implied_volatility, Some(0.25));
+        assert_eq!(quotes[1].kind, OptionKind::Put);
+        assert_eq!(quotes[1].last, None);
+        assert_eq!(quotes[1].timestamp, quotes[0].timestamp);
+    }
+
+    #[test]
+    fn loads_cboe_layout_for_the_requested_underlying() {
+        let file = write_csv(
+            "underlying_symbol,quote_date,root,expiration,strike,option_type,open,high,low,close,trade_volume,bid_eod,ask_eod,open_interest,implied_volatility_1545\n\
+             AAPL,2026-03-02,AAPL,2026-03-20,150.000,C,5.0,5.5,4.9,5.2,120,5.10,5.30,1500,0.2500\n\
+             AAPL,2026-03-02,AAPL,2026-03-20,150.000,P,0,0,0,0,0,4.00,4.20,900,0\n\
+             MSFT,2026-03-02,MSFT,2026-03-20,400.000,C,1,1,1,1,1,1.00,1.10,10,0.3\n",
+        );
+        let symbol = Symbol::equity("AAPL");
+        let quotes = load_option_quotes_csv(file.path(), &symbol).unwrap();
+
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes[0].contract_id, "AAPL  260320C00150000");
+        assert_eq!(
+            quotes[0].timestamp,
+            Utc.with_ymd_and_hms(2026, 3, 2, 21, 0, 0).unwrap()
+        );
+        assert_eq!(quotes[1].last, None);
+        assert_eq!(quotes[1].implied_volatility, None);
+    }
+
+    #[test]
+    fn rejects_unknown_layouts() {
+        let file = write_csv("a,b,c\n1,2,3\n");
+        assert!(load_option_quotes_csv(file.path(), &Symbol::equity("AAPL")).is_err());
+    }
+}
@@ -1,11 +1,16 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Utc, Weekday};
 use csv::ReaderBuilder;
-use gb_types::{Bar, DataError, DatasetKind, GbResult, PriceAdjustmentMode, Resolution, Symbol};
+use gb_types::{
+    AssetClass, Bar, DataError, DatasetKind, GbResult, PriceAdjustmentMode, Resolution, Symbol,
+};
 use rust_decimal::Decimal;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::Path;
 
+use crate::symbols::{unmapped, ProviderSymbols, SymbolMapper};
+
 fn parse_csv_timestamp(raw: &str) -> GbResult<DateTime<Utc>> {
     if let Ok(timestamp) = chrono::DateTime::parse_from_rfc3339(raw) {
         return Ok(timestamp.with_timezone(&Utc));
@@ -33,6 +38,73 @@ fn parse_csv_timestamp(raw: &str) -> GbResult<DateTime<Utc>> {
     .into())
 }
 
+/// How tightly a provider limits request rates; the data manager prefers
+/// providers with looser limits when several can serve a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitClass {
+    /// Local or generated data, free to query at will.
+    #[default]
+    Unlimited,
+    /// A paid or generous API.
+    Moderate,
+    /// A free tier allowing a handful of requests a minute.
+    Strict,
+}
+
+/// What a provider can serve, used by the data manager to route requests.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProviderCapabilities {
+    /// Resolutions served natively; empty means any.
+    pub resolutions: Vec<Resolution>,
+    /// Asset classes served; empty means any.
+    pub asset_classes: Vec<AssetClass>,
+    /// How far back, in days, history reaches; `None` means no limit.
+    pub max_history_days: Option<u32>,
+    pub rate_limit: RateLimitClass,
+}
+
+impl Default for ProviderCapabilities {
+    fn default() -> Self {
+        Self {
+            resolutions: Vec::new(),
+            asset_classes: Vec::new(),
+            max_history_days: None,
+            rate_limit: RateLimitClass::Unlimited,
+        }
+    }
+}
+
+impl ProviderCapabilities {
+    pub fn supports_resolution(&self, resolution: Resolution) -> bool {
+        self.resolutions.is_empty() || self.resolutions.contains(&resolution)
+    }
+
+    pub fn supports_asset_class(&self, asset_class: AssetClass) -> bool {
+        self.asset_classes.is_empty() || self.asset_classes.contains(&asset_class)
+    }
+
+    /// True when history starting at `start` is within reach as of `now`.
+    pub fn reaches_back_to(&self, start: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.max_history_days
+            .is_none_or(|days| now - chrono::Duration::days(days as i64) <= start)
+    }
+
+    /// The resolution to fetch so that `resolution` can be served: the
+    /// resolution itself when supported natively, otherwise the coarsest
+    /// supported resolution that resamples into it.
+    pub fn fetch_resolution_for(&self, resolution: Resolution) -> Option<Resolution> {
+        if self.supports_resolution(resolution) {
+            return Some(resolution);
+        }
+        self.resolutions
+            .iter()
+            .copied()
+            .filter(|&finer| crate::resample::can_resample(finer, resolution))
+            .max_by_key(|finer| finer.to_seconds())
+    }
+}
+
 /// Trait for data providers (CSV, APIs, databases, etc.)
 #[async_trait]
 pub trait DataProvider: Send + Sync + std::fmt::Debug {
@@ -63,6 +135,13 @@ pub trait DataProvider: Send + Sync + std::fmt::Debug {
     fn price_adjustment_mode(&self) -> PriceAdjustmentMode {
         PriceAdjustmentMode::Raw
     }
+
+    /// Describe what this provider can serve. The default claims every
+    /// resolution and asset class, leaving `supports_symbol` and
+    /// `fetch_bars` to turn requests away.
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities::default()
+    }
 }
 
 /// CSV data provider for loading local CSV files
@@ -71,6 +150,8 @@ pub struct CsvDataProvider {
     pub name: String,
     pub data_directory: std::path::PathBuf,
     pub file_pattern: String,
+    /// How tickers are spelled in file names.
+    pub symbols: ProviderSymbols,
 }
 
 #[derive(Debug, Deserialize)]
@@ -95,6 +176,7 @@ impl CsvDataProvider {
             name: "CSV Provider".to_string(),
             data_directory: data_directory.as_ref().to_path_buf(),
             file_pattern: "{symbol}_{resolution}.csv".to_string(),
+            symbols: ProviderSymbols::default(),
         }
     }
 
@@ -103,22 +185,37 @@ impl CsvDataProvider {
         self
     }
 
-    fn get_file_path(&self, symbol: &Symbol, resolution: Resolution) -> std::path::PathBuf {
+    /// Spell tickers in file names by `symbols`, e.g. `BRK-B_1d.csv` for
+    /// `BRK.B` with [`ProviderSymbols::dashes`].
+    pub fn with_symbols(mut self, symbols: ProviderSymbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    fn get_file_path(
+        &self,
+        symbol: &Symbol,
+        resolution: Resolution,
+    ) -> GbResult<std::path::PathBuf> {
+        let ticker = self
+            .symbols
+            .ticker(symbol)
+            .ok_or_else(|| unmapped(&self.name, symbol))?;
         let filename = self
             .file_pattern
-            .replace("{symbol}", &symbol.symbol)
+            .replace("{symbol}", &ticker)
             .replace("{resolution}", &resolution.to_string())
             .replace("{exchange}", &symbol.exchange);
 
-        self.data_directory.join(filename)
+        Ok(self.data_directory.join(filename))
     }
 }
 
 #[async_trait]
 impl DataProvider for CsvDataProvider {
     fn supports_symbol(&self, symbol: &Symbol) -> bool {
-        let path = self.get_file_path(symbol, Resolution::Day);
-        path.exists()
+        self.get_file_path(symbol, Resolution::Day)
+            .is_ok_and(|path| path.exists())
     }
 
     async fn fetch_bars(
@@ -128,13 +225,10 @@ impl DataProvider for CsvDataProvider {
         end_date: DateTime<Utc>,
         resolution: Resolution,
     ) -> GbResult<Vec<Bar>> {
-        let file_path = self.get_file_path(symbol, resolution);
-
-        if !file_path.exists() {
-            return Err(DataError::SourceNotFound(file_path.to_string_lossy().to_string()).into());
-        }
+        let file_path = self.get_file_path(symbol, resolution)?;
 
-        let file = std::fs::File::open(&file_path)?;
+        let file =
+            std::fs::File::open(&file_path).map_err(|e| DataError::from_io(&file_path, e))?;
         let mut reader = ReaderBuilder::new().has_headers(true).from_reader(file);
 
         let mut bars = Vec::new();
@@ -173,7 +267,8 @@ impl DataProvider for CsvDataProvider {
         serde_json::json!({
             "type": "csv",
             "directory": self.data_directory,
-            "pattern": self.file_pattern
+            "pattern": self.file_pattern,
+            "symbols": self.symbols
         })
     }
 
@@ -186,18 +281,178 @@ impl DataProvider for CsvDataProvider {
     }
 }
 
+/// Seed of the sample generator's price stream when none is configured.
+pub const DEFAULT_SAMPLE_SEED: u64 = 12345;
+
+/// Shape of the synthetic data produced by [`SampleDataProvider`].
+///
+/// The default reproduces the original demo walk: every symbol starts from
+/// its fixed demo price and moves up to ±2% per bar, with no regimes, gaps,
+/// or missing bars, and with bars on every calendar day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SampleDataConfig {
+    /// Seeds every random draw, so equal configs produce equal bars.
+    pub seed: u64,
+    /// Statistics for symbols without an entry in `symbols`; `None` keeps
+    /// the ±2% demo walk.
+    pub statistics: Option<SampleStatistics>,
+    /// Per-ticker statistics, keyed by `Symbol::symbol`.
+    pub symbols: HashMap<String, SampleStatistics>,
+    /// Switches volatility between a calm and a turbulent regime.
+    pub regimes: Option<VolatilityRegimes>,
+    /// Largest overnight gap, as a fraction of the previous close, applied to
+    /// the first bar of each day.
+    pub overnight_gap: f64,
+    /// Chance that any one bar is left out of the output.
+    pub missing_bar_probability: f64,
+    pub weekends: WeekendHandling,
+}
+
+impl Default for SampleDataConfig {
+    fn default() -> Self {
+        Self {
+            seed: DEFAULT_SAMPLE_SEED,
+            statistics: None,
+            symbols: HashMap::new(),
+            regimes: None,
+            overnight_gap: 0.0,
+            missing_bar_probability: 0.0,
+            weekends: WeekendHandling::default(),
+        }
+    }
+}
+
+/// Annualized return statistics of one synthetic price series.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SampleStatistics {
+    pub annual_drift: f64,
+    pub annual_volatility: f64,
+    /// Opening price; `None` uses the symbol's demo price.
+    #[serde(default)]
+    pub start_price: Option<Decimal>,
+}
+
+/// Two-state volatility regime model: each bar the regime flips with
+/// `switch_probability`, and turbulent bars scale volatility by
+/// `high_volatility_multiplier`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VolatilityRegimes {
+    pub switch_probability: f64,
+    pub high_volatility_multiplier: f64,
+}
+
+/// Whether the generator produces bars on Saturdays and Sundays.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WeekendHandling {
+    /// Bars on every calendar day, whatever the asset class.
+    #[default]
+    Generate,
+    /// Skip weekends for asset classes that do not trade around the clock.
+    SkipOutsideContinuousMarkets,
+}
+
+/// Linear congruential generator behind the demo walk, kept so that the
+/// default config reproduces its bars exactly.
+#[derive(Debug, Clone)]
+struct SampleRng(u64);
+
+impl SampleRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_mul(1103515245).wrapping_add(12345);
+        self.0
+    }
+
+    /// Integer step drawn uniformly from `-half_width..=half_width`.
+    fn step(&mut self, half_width: u64) -> i64 {
+        ((self.next_u64() >> 16) % (2 * half_width + 1)) as i64 - half_width as i64
+    }
+
+    /// Uniform draw from `[0, 1)`, using the high bits of a splitmix step.
+    fn unit(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Half-width, in basis points, of the demo walk's per-bar step.
+const DEMO_STEP_BPS: u64 = 200;
+
+/// Widest per-bar step the generator takes, keeping prices positive.
+const MAX_STEP_BPS: u64 = 5000;
+
 /// Sample data provider for testing and demo purposes
 #[derive(Debug)]
 pub struct SampleDataProvider {
     pub name: String,
+    sample_config: SampleDataConfig,
 }
 
 impl SampleDataProvider {
     pub fn new() -> Self {
+        Self::with_config(SampleDataConfig::default())
+    }
+
+    /// A provider generating data shaped by `config`.
+    pub fn with_config(config: SampleDataConfig) -> Self {
         Self {
             name: "Sample Data Provider".to_string(),
+            sample_config: config,
         }
     }
+
+    pub fn sample_config(&self) -> &SampleDataConfig {
+        &self.sample_config
+    }
+
+    fn demo_price(symbol: &Symbol) -> Decimal {
+        match symbol.symbol.as_str() {
+            "AAPL" => Decimal::from(150),
+            "GOOGL" => Decimal::from(2500),
+            "MSFT" => Decimal::from(300),
+            "TSLA" => Decimal::from(800),
+            "SPY" => Decimal::from(400),
+            "BTC-USD" | "BTCUSDT" => Decimal::from(45000),
+            "ETH-USD" | "ETHUSDT" => Decimal::from(3000),
+            "SOL-USD" | "SOLUSDT" => Decimal::from(120),
+            "DOGE-USD" | "DOGEUSDT" => Decimal::new(8, 2), // $0.08
+            "ADA-USD" | "ADAUSDT" => Decimal::new(45, 2),  // $0.45
+            _ => Decimal::from(100),
+        }
+    }
+
+    fn demo_volume(symbol: &Symbol) -> Decimal {
+        match symbol.symbol.as_str() {
+            "AAPL" => Decimal::from(80000000),
+            "SPY" => Decimal::from(50000000),
+            "BTC-USD" | "BTCUSDT" => Decimal::from(25000),
+            "ETH-USD" | "ETHUSDT" => Decimal::from(150000),
+            "SOL-USD" | "SOLUSDT" => Decimal::from(500000),
+            "DOGE-USD" | "DOGEUSDT" => Decimal::from(2000000000u64),
+            "ADA-USD" | "ADAUSDT" => Decimal::from(800000000u64),
+            _ => Decimal::from(10000000),
+        }
+    }
+
+    fn skips_weekends(&self, symbol: &Symbol) -> bool {
+        self.sample_config.weekends == WeekendHandling::SkipOutsideContinuousMarkets
+            && !symbol.asset_class.is_24_7()
+    }
+
+    /// Bars per year at `increment`, used to scale annualized statistics.
+    pub fn bars_per_year(&self, symbol: &Symbol, increment: chrono::Duration) -> f64 {
+        let days_per_year = if self.skips_weekends(symbol) {
+            261.0
+        } else {
+            365.25
+        };
+        days_per_year * 86_400.0 / increment.num_seconds().max(1) as f64
+    }
 }
 
 impl Default for SampleDataProvider {
@@ -244,23 +499,6 @@ impl DataProvider for SampleDataProvider {
             .into());
         }
 
-        // Generate synthetic data for demo
-        let mut bars = Vec::new();
-        let mut current_date = start_date;
-        let mut price = match symbol.symbol.as_str() {
-            "AAPL" => Decimal::from(150),
-            "GOOGL" => Decimal::from(2500),
-            "MSFT" => Decimal::from(300),
-            "TSLA" => Decimal::from(800),
-            "SPY" => Decimal::from(400),
-            "BTC-USD" | "BTCUSDT" => Decimal::from(45000),
-            "ETH-USD" | "ETHUSDT" => Decimal::from(3000),
-            "SOL-USD" | "SOLUSDT" => Decimal::from(120),
-            "DOGE-USD" | "DOGEUSDT" => Decimal::new(8, 2), // $0.08
-            "ADA-USD" | "ADAUSDT" => Decimal::new(45, 2),  // $0.45
-            _ => Decimal::from(100),
-        };
-
         let increment = match resolution {
             Resolution::Minute => chrono::Duration::minutes(1),
             Resolution::FiveMinute => chrono::Duration::minutes(5),
@@ -273,45 +511,93 @@ impl DataProvider for SampleDataProvider {
             _ => chrono::Duration::days(1),
         };
 
-        let mut rng_state = 12345u64; // Simple PRNG
+        let config = &self.sample_config;
+        let statistics = config
+            .symbols
+            .get(&symbol.symbol)
+            .or(config.statistics.as_ref());
+        let mut price = statistics
+            .and_then(|stats| stats.start_price)
+            .unwrap_or_else(|| Self::demo_price(symbol));
+
+        // A uniform step of half-width h has standard deviation h / √3.
+        let bars_per_year = self.bars_per_year(symbol, increment);
+        let (base_step_bps, drift) = match statistics {
+            Some(stats) => {
+                let bar_volatility = stats.annual_volatility / bars_per_year.sqrt();
+                (
+                    bar_volatility * 3f64.sqrt() * 10_000.0,
+                    Decimal::from_f64_retain(stats.annual_drift / bars_per_year)
+                        .unwrap_or_default()
+                        .round_dp(8),
+                )
+            }
+            None => (DEMO_STEP_BPS as f64, Decimal::ZERO),
+        };
+        let volume = Self::demo_volume(symbol);
+        let skip_weekends = self.skips_weekends(symbol);
+
+        // Prices follow their own stream so that turning a feature on or off
+        // leaves the walk itself untouched.
+        let mut price_rng = SampleRng(config.seed);
+        let mut event_rng = SampleRng(config.seed ^ 0x5DEE_CE66_D1CE_4E5B);
+        let mut turbulent = false;
+        let mut last_day = None;
 
+        let mut bars = Vec::new();
+        let mut current_date = start_date;
         while current_date <= end_date {
-            // Simple random walk
-            rng_state = rng_state.wrapping_mul(1103515245).wrapping_add(12345);
-            let change_bps = ((rng_state >> 16) % 401) as i64 - 200; // ±2.00%
-
-            let change_pct = Decimal::new(change_bps, 4);
-            let new_price = (price * (Decimal::ONE + change_pct)).round_dp(4);
+            let timestamp = current_date;
+            current_date += increment;
+            if skip_weekends && matches!(timestamp.weekday(), Weekday::Sat | Weekday::Sun) {
+                continue;
+            }
 
-            let volatility = Decimal::from_f64_retain(0.01).unwrap_or_default(); // 1% intraday volatility
-            let high = (new_price * (Decimal::ONE + volatility)).round_dp(4);
-            let low = (new_price * (Decimal::ONE - volatility)).round_dp(4);
-
-            let volume = match symbol.symbol.as_str() {
-                "AAPL" => Decimal::from(80000000),
-                "SPY" => Decimal::from(50000000),
-                "BTC-USD" | "BTCUSDT" => Decimal::from(25000),
-                "ETH-USD" | "ETHUSDT" => Decimal::from(150000),
-                "SOL-USD" | "SOLUSDT" => Decimal::from(500000),
-                "DOGE-USD" | "DOGEUSDT" => Decimal::from(2000000000u64),
-                "ADA-USD" | "ADAUSDT" => Decimal::from(800000000u64),
-                _ => Decimal::from(10000000),
+            if let Some(regimes) = &config.regimes {
+                if event_rng.unit() < regimes.switch_probability {
+                    turbulent = !turbulent;
+                }
+            }
+            let regime_scale = match &config.regimes {
+                Some(regimes) if turbulent => regimes.high_volatility_multiplier,
+                _ => 1.0,
             };
+            let step_bps = ((base_step_bps * regime_scale).round() as u64).min(MAX_STEP_BPS);
+
+            let mut open = price;
+            let day = timestamp.date_naive();
+            if config.overnight_gap > 0.0 && last_day.is_some_and(|last| last != day) {
+                let gap = (event_rng.unit() * 2.0 - 1.0) * config.overnight_gap.min(0.5);
+                let gap = Decimal::from_f64_retain(gap)
+                    .unwrap_or_default()
+                    .round_dp(6);
+                open = (open * (Decimal::ONE + gap)).round_dp(4);
+            }
+            last_day = Some(day);
 
-            let bar = Bar::new(
+            let change_pct = Decimal::new(price_rng.step(step_bps), 4) + drift;
+            let close = (open * (Decimal::ONE + change_pct)).round_dp(4);
+
+            let volatility = Decimal::from_f64_retain(0.01).unwrap_or_default(); // 1% intraday volatility
+            let high = (close * (Decimal::ONE + volatility)).round_dp(4);
+            let low = (close * (Decimal::ONE - volatility)).round_dp(4);
+            price = close;
+
+            if config.missing_bar_probability > 0.0
+                && event_rng.unit() < config.missing_bar_probability
+            {
+                continue;
+            }
+            bars.push(Bar::new(
                 symbol.clone(),
-                current_date,
-                price,
-                high,
-                low,
-                new_price,
+                timestamp,
+                open,
+                high.max(open),
+                low.min(open),
+                close,
                 volume,
                 resolution,
-            );
-
-            bars.push(bar);
-            price = new_price;
-            current_date += increment;
+            ));
         }
 
         Ok(bars)
@@ -328,7 +614,8 @@ impl DataProvider for SampleDataProvider {
                 "AAPL", "GOOGL", "MSFT", "TSLA", "SPY",
                 "BTC-USD", "ETH-USD", "SOL-USD", "DOGE-USD", "ADA-USD",
                 "BTCUSDT", "ETHUSDT", "SOLUSDT", "DOGEUSDT", "ADAUSDT"
-            ]
+            ],
+            "generator": self.sample_config,
         })
     }
 
@@ -339,6 +626,23 @@ impl DataProvider for SampleDataProvider {
     fn price_adjustment_mode(&self) -> PriceAdjustmentMode {
         PriceAdjustmentMode::Synthetic
     }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            resolutions: vec![
+                Resolution::Minute,
+                Resolution::FiveMinute,
+                Resolution::FifteenMinute,
+                Resolution::Hour,
+                Resolution::FourHour,
+                Resolution::Day,
+                Resolution::Week,
+                Resolution::Month,
+            ],
+            asset_classes: vec![AssetClass::Equity, AssetClass::Crypto],
+            ..Default::default()
+        }
+    }
 }
 
 /// Alpha Vantage API provider (placeholder for future implementation)
@@ -347,6 +651,9 @@ pub struct AlphaVantageProvider {
     pub name: String,
     pub api_key: String,
     pub client: reqwest::Client,
+    /// How tickers are spelled in requests; share classes take a dash
+    /// (`BRK-B`) by default.
+    pub symbols: ProviderSymbols,
 }
 
 impl AlphaVantageProvider {
@@ -355,9 +662,15 @@ impl AlphaVantageProvider {
             name: "Alpha Vantage".to_string(),
             api_key,
             client: reqwest::Client::new(),
+            symbols: SymbolMapper::builtin().provider("alpha_vantage"),
         }
     }
 
+    pub fn with_symbols(mut self, symbols: ProviderSymbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
     /// Parse Alpha Vantage daily response
     fn parse_daily_response(
         &self,
@@ -465,13 +778,53 @@ impl AlphaVantageProvider {
             .into()
         })
     }
+
+    /// The error for an unsuccessful HTTP `status`; 429 is a rate limit.
+    fn check_status(&self, status: u16) -> GbResult<()> {
+        match status {
+            200..=299 => Ok(()),
+            429 => Err(DataError::RateLimited {
+                provider: self.name.clone(),
+                message: "HTTP 429 Too Many Requests".to_string(),
+                retry_after_seconds: None,
+            }
+            .into()),
+            status => Err(DataError::HttpStatus {
+                provider: self.name.clone(),
+                status,
+            }
+            .into()),
+        }
+    }
+
+    /// Alpha Vantage reports errors in a 200 response: `"Error Message"`
+    /// for bad requests and `"Note"` or `"Information"` when the call
+    /// allowance is used up.
+    fn check_response(&self, json: &serde_json::Value) -> GbResult<()> {
+        if let Some(error) = json.get("Error Message") {
+            return Err(DataError::ProviderError {
+                provider: self.name.clone(),
+                message: error.as_str().unwrap_or_default().to_string(),
+            }
+            .into());
+        }
+        if let Some(note) = json.get("Note").or_else(|| json.get("Information")) {
+            return Err(DataError::RateLimited {
+                provider: self.name.clone(),
+                message: note.as_str().unwrap_or_default().to_string(),
+                retry_after_seconds: Some(60),
+            }
+            .into());
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
 impl DataProvider for AlphaVantageProvider {
     fn supports_symbol(&self, symbol: &Symbol) -> bool {
         // Alpha Vantage supports most US equities
-        matches!(symbol.asset_class, gb_types::AssetClass::Equity)
+        matches!(symbol.asset_class, AssetClass::Equity) && self.symbols.ticker(symbol).is_some()
     }
 
     async fn fetch_bars(
@@ -493,19 +846,21 @@ impl DataProvider for AlphaVantageProvider {
         let function = match resolution {
             Resolution::Day => "TIME_SERIES_DAILY",
             _ => {
-                return Err(DataError::LoadingFailed {
-                    message: format!(
-                        "Resolution {:?} not supported by Alpha Vantage free tier",
-                        resolution
-                    ),
+                return Err(DataError::UnsupportedResolution {
+                    provider: self.name.clone(),
+                    resolution: resolution.to_string(),
                 }
                 .into());
             }
         };
 
+        let ticker = self
+            .symbols
+            .ticker(symbol)
+            .ok_or_else(|| unmapped(&self.name, symbol))?;
         let url = format!(
             "https://www.alphavantage.co/query?function={}&symbol={}&outputsize=full",
-            function, symbol.symbol
+            function, ticker
         );
 
         let response = self
@@ -514,39 +869,23 @@ impl DataProvider for AlphaVantageProvider {
             .query(&[("apikey", &self.api_key)])
             .send()
             .await
-            .map_err(|e| DataError::LoadingFailed {
-                message: format!("HTTP request failed: {}", e),
+            .map_err(|e| DataError::RequestFailed {
+                provider: self.name.clone(),
+                source: Box::new(e),
             })?;
 
-        if !response.status().is_success() {
-            return Err(DataError::LoadingFailed {
-                message: format!("HTTP error: {}", response.status()),
-            }
-            .into());
-        }
+        self.check_status(response.status().as_u16())?;
 
         let json: serde_json::Value =
             response
                 .json()
                 .await
-                .map_err(|e| DataError::LoadingFailed {
-                    message: format!("Failed to parse JSON response: {}", e),
+                .map_err(|e| DataError::RequestFailed {
+                    provider: self.name.clone(),
+                    source: Box::new(e),
                 })?;
 
-        // Check for API errors
-        if let Some(error) = json.get("Error Message") {
-            return Err(DataError::LoadingFailed {
-                message: format!("API error: {}", error),
-            }
-            .into());
-        }
-
-        if let Some(note) = json.get("Note") {
-            return Err(DataError::LoadingFailed {
-                message: format!("API limit exceeded: {}", note),
-            }
-            .into());
-        }
+        self.check_response(&json)?;
 
         let mut bars = self.parse_daily_response(json, symbol)?;
         let total_bars = bars.len();
@@ -562,11 +901,10 @@ impl DataProvider for AlphaVantageProvider {
         );
 
         if bars.is_empty() {
-            return Err(DataError::LoadingFailed {
-                message: format!(
-                    "No data found for {} in date range {} to {}",
-                    symbol, start_date, end_date
-                ),
+            return Err(DataError::NoDataInRange {
+                symbol: symbol.to_string(),
+                start: start_date.to_rfc3339(),
+                end: end_date.to_rfc3339(),
             }
             .into());
         }
@@ -592,11 +930,51 @@ impl DataProvider for AlphaVantageProvider {
     fn price_adjustment_mode(&self) -> PriceAdjustmentMode {
         PriceAdjustmentMode::Raw
     }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            resolutions: vec![Resolution::Day],
+            asset_classes: vec![AssetClass::Equity],
+            // The full daily series covers roughly the last twenty years.
+            max_history_days: Some(20 * 365),
+            rate_limit: RateLimitClass::Strict,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::parse_csv_timestamp;
+    use super::*;
+    use chrono::TimeZone;
+    use gb_types::GbError;
+    use rust_decimal::prelude::ToPrimitive;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    async fn daily_bars(config: SampleDataConfig, symbol: &Symbol, years: i32) -> Vec<Bar> {
+        SampleDataProvider::with_config(config)
+            .fetch_bars(
+                symbol,
+                at(2000, 1, 1),
+                at(2000 + years, 1, 1),
+                Resolution::Day,
+            )
+            .await
+            .unwrap()
+    }
+
+    fn annualized_volatility(bars: &[Bar], bars_per_year: f64) -> f64 {
+        let returns: Vec<f64> = bars
+            .windows(2)
+            .map(|pair| (pair[1].close / pair[0].close).to_f64().unwrap().ln())
+            .collect();
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64;
+        (variance * bars_per_year).sqrt()
+    }
 
     #[test]
     fn parse_csv_timestamp_accepts_date_only_rows() {
@@ -611,4 +989,163 @@ mod tests {
             parse_csv_timestamp("2025-01-02T15:30:00Z").expect("rfc3339 timestamp should parse");
         assert_eq!(timestamp.to_rfc3339(), "2025-01-02T15:30:00+00:00");
     }
+
+    #[tokio::test]
+    async fn default_sample_config_keeps_the_demo_walk() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = daily_bars(SampleDataConfig::default(), &symbol, 1).await;
+
+        assert_eq!(bars.len(), 367);
+        assert_eq!(bars[0].open, Decimal::from(150));
+        for pair in bars.windows(2) {
+            assert_eq!(pair[1].open, pair[0].close);
+            let change = (pair[1].close / pair[1].open - Decimal::ONE).abs();
+            assert!(change <= Decimal::new(201, 4), "step of {change}");
+        }
+    }
+
+    #[tokio::test]
+    async fn sample_bars_are_reproducible_from_the_seed() {
+        let symbol = Symbol::equity("MSFT");
+        let config = SampleDataConfig {
+            seed: 7,
+            statistics: Some(SampleStatistics {
+                annual_drift: 0.05,
+                annual_volatility: 0.4,
+                start_price: None,
+            }),
+            regimes: Some(VolatilityRegimes {
+                switch_probability: 0.05,
+                high_volatility_multiplier: 3.0,
+            }),
+            overnight_gap: 0.01,
+            missing_bar_probability: 0.02,
+            weekends: WeekendHandling::SkipOutsideContinuousMarkets,
+            ..Default::default()
+        };
+
+        let first = daily_bars(config.clone(), &symbol, 2).await;
+        let second = daily_bars(config.clone(), &symbol, 2).await;
+        let reseeded = daily_bars(SampleDataConfig { seed: 8, ..config }, &symbol, 2).await;
+
+        assert_eq!(first, second);
+        assert_ne!(first, reseeded);
+    }
+
+    #[tokio::test]
+    async fn requested_volatility_is_realized_over_long_samples() {
+        let symbol = Symbol::equity("SPY");
+        let mut config = SampleDataConfig::default();
+        config.symbols.insert(
+            "SPY".to_string(),
+            SampleStatistics {
+                annual_drift: 0.0,
+                annual_volatility: 0.3,
+                start_price: Some(Decimal::from(400)),
+            },
+        );
+
+        let bars = daily_bars(config.clone(), &symbol, 20).await;
+        let bars_per_year = SampleDataProvider::with_config(config)
+            .bars_per_year(&symbol, chrono::Duration::days(1));
+        let realized = annualized_volatility(&bars, bars_per_year);
+
+        assert_eq!(bars[0].open, Decimal::from(400));
+        assert!((realized - 0.3).abs() < 0.02, "realized {realized}");
+    }
+
+    #[tokio::test]
+    async fn weekends_are_skipped_only_for_session_markets() {
+        let config = SampleDataConfig {
+            weekends: WeekendHandling::SkipOutsideContinuousMarkets,
+            ..Default::default()
+        };
+        let equity = daily_bars(config.clone(), &Symbol::equity("AAPL"), 1).await;
+        let crypto = daily_bars(config, &Symbol::crypto("BTC-USD"), 1).await;
+
+        assert!(equity
+            .iter()
+            .all(|bar| !matches!(bar.timestamp.weekday(), Weekday::Sat | Weekday::Sun)));
+        assert_eq!(equity.len(), 261);
+        assert_eq!(crypto.len(), 367);
+    }
+
+    #[tokio::test]
+    async fn gaps_and_missing_bars_break_up_the_series() {
+        let symbol = Symbol::equity("TSLA");
+        let config = SampleDataConfig {
+            overnight_gap: 0.03,
+            missing_bar_probability: 0.1,
+            ..Default::default()
+        };
+        let bars = daily_bars(config, &symbol, 4).await;
+
+        let expected = 1462;
+        assert!(bars.len() < expected * 95 / 100 && bars.len() > expected * 85 / 100);
+        assert!(bars.windows(2).any(|pair| pair[1].open != pair[0].close));
+        assert!(bars
+            .iter()
+            .all(|bar| bar.low <= bar.open.min(bar.close) && bar.high >= bar.open.max(bar.close)));
+    }
+
+    #[tokio::test]
+    async fn alpha_vantage_errors_keep_their_kind() {
+        let mut provider = AlphaVantageProvider::new("demo".to_string());
+
+        let error = provider
+            .fetch_bars(
+                &Symbol::equity("AAPL"),
+                at(2024, 1, 1),
+                at(2024, 2, 1),
+                Resolution::Minute,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            GbError::Data(DataError::UnsupportedResolution { .. })
+        ));
+        assert!(!error.is_retryable());
+
+        let allowance = serde_json::json!({
+            "Note": "Thank you for using Alpha Vantage! Our standard API call frequency is 5 calls per minute."
+        });
+        let error = provider.check_response(&allowance).unwrap_err();
+        assert!(matches!(
+            error,
+            GbError::Data(DataError::RateLimited {
+                retry_after_seconds: Some(60),
+                ..
+            })
+        ));
+        assert!(error.is_retryable());
+        assert_eq!(
+            provider.check_status(429).unwrap_err().code(),
+            "data.rate_limited"
+        );
+        assert!(provider.check_status(503).unwrap_err().is_retryable());
+        assert!(!provider.check_status(403).unwrap_err().is_retryable());
+    }
+
+    #[tokio::test]
+    async fn missing_csv_files_are_reported_as_missing() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let mut provider = CsvDataProvider::new(temp_dir.path());
+
+        let error = provider
+            .fetch_bars(
+                &Symbol::equity("AAPL"),
+                at(2024, 1, 1),
+                at(2024, 2, 1),
+                Resolution::Day,
+            )
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            GbError::Data(DataError::FileNotFound { .. })
+        ));
+        assert_eq!(error.code(), "data.file_not_found");
+    }
 }
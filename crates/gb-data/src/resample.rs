@@ -0,0 +1,147 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use gb_types::{Bar, Resolution};
+
+/// True when bars at `from` can be aggregated into bars at `to`: `to` must
+/// be coarser, and every `to` period must hold a whole number of `from`
+/// periods.
+pub fn can_resample(from: Resolution, to: Resolution) -> bool {
+    let (Some(from_seconds), Some(to_seconds)) = (from.to_seconds(), to.to_seconds()) else {
+        return false;
+    };
+    if from_seconds >= to_seconds {
+        return false;
+    }
+    match to {
+        // Weeks and months start on calendar boundaries, which only
+        // intraday and daily bars line up with.
+        Resolution::Week | Resolution::Month => 86_400 % from_seconds == 0,
+        _ => to_seconds % from_seconds == 0,
+    }
+}
+
+/// Start of the `resolution` period holding `timestamp`. Weeks start on
+/// Monday and months on the first, both at midnight UTC.
+pub fn period_start(timestamp: DateTime<Utc>, resolution: Resolution) -> DateTime<Utc> {
+    let midnight = |date: chrono::NaiveDate| {
+        Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).expect("midnight is valid"))
+    };
+    match resolution {
+        Resolution::Week => {
+            let date = timestamp.date_naive();
+            midnight(date - Duration::days(date.weekday().num_days_from_monday() as i64))
+        }
+        Resolution::Month => midnight(timestamp.date_naive().with_day(1).expect("day 1 exists")),
+        _ => {
+            let Some(seconds) = resolution.to_seconds() else {
+                return timestamp;
+            };
+            let seconds = seconds as i64;
+            let start = timestamp.timestamp().div_euclid(seconds) * seconds;
+            Utc.timestamp_opt(start, 0).single().unwrap_or(timestamp)
+        }
+    }
+}
+
+/// Aggregate time-ordered `bars` at `from` into bars at `to`, one per
+/// period that holds data, stamped with the period start. Each bar records
+/// `from` as the resolution it was resampled from.
+pub fn resample_bars(bars: &[Bar], from: Resolution, to: Resolution) -> Vec<Bar> {
+    let mut resampled: Vec<Bar> = Vec::new();
+    for bar in bars {
+        let start = period_start(bar.timestamp, to);
+        match resampled.last_mut() {
+            Some(current) if current.timestamp == start => {
+                current.high = current.high.max(bar.high);
+                current.low = current.low.min(bar.low);
+                current.close = bar.close;
+                current.volume += bar.volume;
+                current.provenance.synthetic &= bar.provenance.synthetic;
+            }
+            _ => {
+                let mut provenance = bar.provenance.clone();
+                provenance.resampled_from = Some(from);
+                resampled.push(
+                    Bar::new(
+                        bar.symbol.clone(),
+                        start,
+                        bar.open,
+                        bar.high,
+                        bar.low,
+                        bar.close,
+                        bar.volume,
+                        to,
+                    )
+                    .with_provenance(provenance),
+                );
+            }
+        }
+    }
+    resampled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gb_types::Symbol;
+    use rust_decimal::Decimal;
+
+    fn hourly(day: u32, hour: u32, close: i64) -> Bar {
+        Bar::new(
+            Symbol::equity("AAPL"),
+            Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap(),
+            Decimal::from(close - 1),
+            Decimal::from(close + 2),
+            Decimal::from(close - 2),
+            Decimal::from(close),
+            Decimal::from(100),
+            Resolution::Hour,
+        )
+    }
+
+    #[test]
+    fn resampling_needs_a_coarser_aligned_target() {
+        assert!(can_resample(Resolution::Minute, Resolution::Hour));
+        assert!(can_resample(Resolution::FourHour, Resolution::Day));
+        assert!(can_resample(Resolution::Day, Resolution::Month));
+        assert!(!can_resample(Resolution::Day, Resolution::Minute));
+        assert!(!can_resample(Resolution::Week, Resolution::Month));
+        assert!(!can_resample(Resolution::Tick, Resolution::Minute));
+    }
+
+    #[test]
+    fn hourly_bars_aggregate_into_daily_bars() {
+        let bars = vec![
+            hourly(2, 14, 100),
+            hourly(2, 15, 110),
+            hourly(2, 16, 90),
+            hourly(3, 14, 95),
+        ];
+
+        let daily = resample_bars(&bars, Resolution::Hour, Resolution::Day);
+
+        assert_eq!(daily.len(), 2);
+        let first = &daily[0];
+        assert_eq!(
+            first.timestamp,
+            Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap()
+        );
+        assert_eq!(first.resolution, Resolution::Day);
+        assert_eq!(first.open, Decimal::from(99));
+        assert_eq!(first.high, Decimal::from(112));
+        assert_eq!(first.low, Decimal::from(88));
+        assert_eq!(first.close, Decimal::from(90));
+        assert_eq!(first.volume, Decimal::from(300));
+        assert_eq!(first.provenance.resampled_from, Some(Resolution::Hour));
+        assert_eq!(daily[1].close, Decimal::from(95));
+    }
+
+    #[test]
+    fn weekly_periods_start_on_monday() {
+        // Wednesday 3 January 2024 belongs to the week of Monday the 1st.
+        let start = period_start(
+            Utc.with_ymd_and_hms(2024, 1, 3, 15, 30, 0).unwrap(),
+            Resolution::Week,
+        );
+        assert_eq!(start, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+    }
+}
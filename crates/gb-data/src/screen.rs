@@ -0,0 +1,580 @@
+//! Universe screening: narrow a set of candidate symbols to those passing
+//! price, liquidity, history and fundamentals filters as of a date.
+//!
+//! Every filter only looks at data stamped at or before the screen date, so
+//! a universe built as of the start of a backtest cannot see the prices,
+//! volumes or fundamentals its strategy will trade on. Filters are applied
+//! cheapest first: symbol attributes, then one pass over the catalog, then
+//! one batched fundamentals lookup, and finally one storage read per
+//! surviving symbol shared by the price and liquidity filters.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use gb_types::{AssetClass, DataError, GbError, GbResult, Resolution, Symbol, Universe};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::DataManager;
+
+/// Point-in-time fundamentals of one symbol.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Fundamentals {
+    pub sector: Option<String>,
+    pub market_cap: Option<Decimal>,
+}
+
+/// Supplies fundamentals as they were known at a date.
+#[async_trait]
+pub trait FundamentalsSource: Send + Sync {
+    /// The latest fundamentals published at or before `as_of` for each of
+    /// `symbols`; symbols with nothing published yet are left out.
+    async fn fundamentals_as_of(
+        &self,
+        symbols: &[Symbol],
+        as_of: DateTime<Utc>,
+    ) -> GbResult<HashMap<Symbol, Fundamentals>>;
+}
+
+/// In-memory fundamentals history, for tests and for data loaded elsewhere.
+#[derive(Debug, Clone, Default)]
+pub struct FundamentalsTable {
+    records: HashMap<Symbol, Vec<(DateTime<Utc>, Fundamentals)>>,
+}
+
+impl FundamentalsTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record `fundamentals` for `symbol` as published at `published`,
+    /// superseding earlier records from then on.
+    pub fn insert(
+        &mut self,
+        symbol: Symbol,
+        published: DateTime<Utc>,
+        fundamentals: Fundamentals,
+    ) -> &mut Self {
+        let records = self.records.entry(symbol).or_default();
+        let index = records.partition_point(|(at, _)| *at <= published);
+        records.insert(index, (published, fundamentals));
+        self
+    }
+
+    pub fn get_as_of(&self, symbol: &Symbol, as_of: DateTime<Utc>) -> Option<&Fundamentals> {
+        let records = self.records.get(symbol)?;
+        let known = records.partition_point(|(at, _)| *at <= as_of);
+        known.checked_sub(1).map(|index| &records[index].1)
+    }
+}
+
+#[async_trait]
+impl FundamentalsSource for FundamentalsTable {
+    async fn fundamentals_as_of(
+        &self,
+        symbols: &[Symbol],
+        as_of: DateTime<Utc>,
+    ) -> GbResult<HashMap<Symbol, Fundamentals>> {
+        Ok(symbols
+            .iter()
+            .filter_map(|symbol| {
+                self.get_as_of(symbol, as_of)
+                    .map(|fundamentals| (symbol.clone(), fundamentals.clone()))
+            })
+            .collect())
+    }
+}
+
+/// A named set of filters producing a [`Universe`]. A symbol joins the
+/// universe only when it passes every filter; a screen without filters
+/// keeps every candidate.
+#[derive(Clone)]
+pub struct Screen {
+    name: String,
+    resolution: Resolution,
+    asset_classes: Option<HashSet<AssetClass>>,
+    exchanges: Option<HashSet<String>>,
+    min_coverage: Option<Duration>,
+    sectors: Option<HashSet<String>>,
+    market_cap: Option<(Option<Decimal>, Option<Decimal>)>,
+    price: Option<(Option<Decimal>, Option<Decimal>)>,
+    /// Minimum average dollar volume and the number of bars averaged.
+    dollar_volume: Option<(Decimal, usize)>,
+    fundamentals: Option<Arc<dyn FundamentalsSource>>,
+}
+
+impl std::fmt::Debug for Screen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Screen")
+            .field("name", &self.name)
+            .field("resolution", &self.resolution)
+            .field("asset_classes", &self.asset_classes)
+            .field("exchanges", &self.exchanges)
+            .field("min_coverage", &self.min_coverage)
+            .field("sectors", &self.sectors)
+            .field("market_cap", &self.market_cap)
+            .field("price", &self.price)
+            .field("dollar_volume", &self.dollar_volume)
+            .field("fundamentals", &self.fundamentals.is_some())
+            .finish()
+    }
+}
+
+impl Screen {
+    /// A screen over daily bars whose universes are called `name`.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            resolution: Resolution::Day,
+            asset_classes: None,
+            exchanges: None,
+            min_coverage: None,
+            sectors: None,
+            market_cap: None,
+            price: None,
+            dollar_volume: None,
+            fundamentals: None,
+        }
+    }
+
+    /// Read prices, volumes and coverage from bars at `resolution`.
+    pub fn with_resolution(mut self, resolution: Resolution) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    /// Where the sector and market-cap filters look fundamentals up.
+    pub fn with_fundamentals(mut self, source: Arc<dyn FundamentalsSource>) -> Self {
+        self.fundamentals = Some(source);
+        self
+    }
+
+    pub fn asset_classes(mut self, classes: impl IntoIterator<Item = AssetClass>) -> Self {
+        self.asset_classes = Some(classes.into_iter().collect());
+        self
+    }
+
+    pub fn exchanges<S: Into<String>>(mut self, exchanges: impl IntoIterator<Item = S>) -> Self {
+        self.exchanges = Some(exchanges.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Keep symbols whose last close at or before the screen date lies in
+    /// `min..=max`; either bound may be open.
+    pub fn price_between(mut self, min: Option<Decimal>, max: Option<Decimal>) -> Self {
+        self.price = Some((min, max));
+        self
+    }
+
+    /// Keep symbols whose close times volume, averaged over the last
+    /// `lookback` bars up to the screen date, is at least `min`. Symbols
+    /// with a shorter history are averaged over what they have.
+    pub fn min_average_dollar_volume(mut self, min: Decimal, lookback: usize) -> Self {
+        self.dollar_volume = Some((min, lookback.max(1)));
+        self
+    }
+
+    /// Keep symbols whose catalogued history at the screen's resolution
+    /// starts at least `coverage` before the screen date.
+    pub fn min_coverage(mut self, coverage: Duration) -> Self {
+        self.min_coverage = Some(coverage);
+        self
+    }
+
+    /// Keep symbols whose sector, as known at the screen date, is one of
+    /// `sectors`. Needs a fundamentals source.
+    pub fn sectors<S: Into<String>>(mut self, sectors: impl IntoIterator<Item = S>) -> Self {
+        self.sectors = Some(sectors.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Keep symbols whose market cap, as known at the screen date, lies in
+    /// `min..=max`. Needs a fundamentals source.
+    pub fn market_cap_between(mut self, min: Option<Decimal>, max: Option<Decimal>) -> Self {
+        self.market_cap = Some((min, max));
+        self
+    }
+
+    /// Screen every symbol in `data`'s catalog as of `as_of`.
+    pub async fn evaluate(&self, data: &DataManager, as_of: DateTime<Utc>) -> GbResult<Universe> {
+        let candidates = data.catalog.list_available_symbols().await?;
+        self.evaluate_candidates(data, &candidates, as_of).await
+    }
+
+    /// Screen `candidates` as of `as_of`, keeping their order in the
+    /// resulting universe.
+    pub async fn evaluate_candidates(
+        &self,
+        data: &DataManager,
+        candidates: &[Symbol],
+        as_of: DateTime<Utc>,
+    ) -> GbResult<Universe> {
+        let mut symbols: Vec<Symbol> = candidates
+            .iter()
+            .filter(|symbol| {
+                self.asset_classes
+                    .as_ref()
+                    .is_none_or(|classes| classes.contains(&symbol.asset_class))
+                    && self
+                        .exchanges
+                        .as_ref()
+                        .is_none_or(|exchanges| exchanges.contains(&symbol.exchange))
+            })
+            .cloned()
+            .collect();
+
+        if let Some(coverage) = self.min_coverage {
+            let first_dates: HashMap<Symbol, DateTime<Utc>> = data
+                .catalog
+                .list_symbol_data()
+                .await?
+                .into_iter()
+                .filter(|info| info.resolution == self.resolution)
+                .map(|info| (info.symbol, info.first_date))
+                .collect();
+            symbols.retain(|symbol| {
+                first_dates
+                    .get(symbol)
+                    .is_some_and(|first| as_of - *first >= coverage)
+            });
+        }
+
+        if self.sectors.is_some() || self.market_cap.is_some() {
+            let source = self.fundamentals.as_ref().ok_or_else(|| {
+                GbError::Config(format!(
+                    "screen {} filters on fundamentals but has no fundamentals source",
+                    self.name
+                ))
+            })?;
+            let fundamentals = source.fundamentals_as_of(&symbols, as_of).await?;
+            symbols.retain(|symbol| {
+                fundamentals
+                    .get(symbol)
+                    .is_some_and(|fundamentals| self.passes_fundamentals(fundamentals))
+            });
+        }
+
+        if self.price.is_some() || self.dollar_volume.is_some() {
+            let lookback = self.dollar_volume.map_or(1, |(_, lookback)| lookback);
+            let mut kept = Vec::with_capacity(symbols.len());
+            for symbol in symbols {
+                let bars = match data
+                    .storage
+                    .load_bars(&symbol, DateTime::<Utc>::MIN_UTC, as_of, self.resolution)
+                    .await
+                {
+                    Ok(bars) => bars,
+                    Err(GbError::Data(DataError::SymbolNotFound { .. })) => Vec::new(),
+                    Err(error) => return Err(error),
+                };
+                let recent = &bars[bars.len().saturating_sub(lookback)..];
+                if self.passes_bars(recent) {
+                    kept.push(symbol);
+                }
+            }
+            symbols = kept;
+        }
+
+        tracing::debug!(
+            "Screen {} kept {} of {} candidates as of {}",
+            self.name,
+            symbols.len(),
+            candidates.len(),
+            as_of
+        );
+        Ok(Universe::new(self.name.clone(), as_of, symbols))
+    }
+
+    fn passes_fundamentals(&self, fundamentals: &Fundamentals) -> bool {
+        let sector = self.sectors.as_ref().is_none_or(|sectors| {
+            fundamentals
+                .sector
+                .as_ref()
+                .is_some_and(|sector| sectors.contains(sector))
+        });
+        let market_cap = self.market_cap.is_none_or(|(min, max)| {
+            fundamentals
+                .market_cap
+                .is_some_and(|cap| within(cap, min, max))
+        });
+        sector && market_cap
+    }
+
+    /// `recent` holds the last bars up to the screen date, oldest first.
+    fn passes_bars(&self, recent: &[gb_types::Bar]) -> bool {
+        let Some(last) = recent.last() else {
+            return false;
+        };
+        let price = self
+            .price
+            .is_none_or(|(min, max)| within(last.close, min, max));
+        let dollar_volume = self.dollar_volume.is_none_or(|(min, _)| {
+            let total: Decimal = recent.iter().map(|bar| bar.close * bar.volume).sum();
+            total / Decimal::from(recent.len()) >= min
+        });
+        price && dollar_volume
+    }
+}
+
+fn within(value: Decimal, min: Option<Decimal>, max: Option<Decimal>) -> bool {
+    min.is_none_or(|min| value >= min) && max.is_none_or(|max| value <= max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{DataProvider, SampleDataProvider};
+    use chrono::TimeZone;
+    use gb_types::{DatasetKind, PriceAdjustmentMode};
+
+    fn day(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, month, day, 0, 0, 0).unwrap()
+    }
+
+    /// Sample-provider daily history for each symbol from its start date to
+    /// the end of 2024, ingested into a fresh data manager.
+    async fn manager(histories: &[(Symbol, DateTime<Utc>)]) -> DataManager {
+        let mut data = DataManager::new_ephemeral("gb-screen").await.unwrap();
+        let mut provider = SampleDataProvider::new();
+        for (symbol, start) in histories {
+            let bars = provider
+                .fetch_bars(symbol, *start, day(2024, 12, 31), Resolution::Day)
+                .await
+                .unwrap();
+            data.ingest_bars(
+                symbol,
+                &bars,
+                Resolution::Day,
+                DatasetKind::Sample,
+                PriceAdjustmentMode::Raw,
+            )
+            .await
+            .unwrap();
+        }
+        data
+    }
+
+    fn tickers(universe: &Universe) -> Vec<&str> {
+        universe
+            .symbols
+            .iter()
+            .map(|symbol| symbol.symbol.as_str())
+            .collect()
+    }
+
+    fn fundamentals(sector: &str, market_cap: i64) -> Fundamentals {
+        Fundamentals {
+            sector: Some(sector.to_string()),
+            market_cap: Some(Decimal::from(market_cap)),
+        }
+    }
+
+    #[tokio::test]
+    async fn filters_on_price_liquidity_coverage_and_asset_class() {
+        let data = manager(&[
+            (Symbol::equity("AAPL"), day(2020, 1, 1)),
+            (Symbol::equity("GOOGL"), day(2020, 1, 1)),
+            (Symbol::equity("MSFT"), day(2023, 6, 1)),
+            (Symbol::crypto("BTC-USD"), day(2020, 1, 1)),
+        ])
+        .await;
+        let as_of = day(2024, 6, 1);
+
+        let all = Screen::new("all").evaluate(&data, as_of).await.unwrap();
+        assert_eq!(tickers(&all), ["AAPL", "BTC-USD", "GOOGL", "MSFT"]);
+
+        let equities = Screen::new("equities")
+            .asset_classes([AssetClass::Equity])
+            .evaluate(&data, as_of)
+            .await
+            .unwrap();
+        assert_eq!(tickers(&equities), ["AAPL", "GOOGL", "MSFT"]);
+
+        // GOOGL trades near 2,500 and the others well below 1,000.
+        let cheap = Screen::new("cheap")
+            .price_between(Some(Decimal::from(5)), Some(Decimal::from(1_000)))
+            .evaluate(&data, as_of)
+            .await
+            .unwrap();
+        assert!(!cheap.contains(&Symbol::equity("GOOGL")));
+        assert!(cheap.contains(&Symbol::equity("AAPL")));
+
+        // BTC-USD trades only 25,000 units a day, around $1B at demo prices;
+        // AAPL trades 80M shares at around $150, about $12B.
+        let liquid = Screen::new("liquid")
+            .min_average_dollar_volume(Decimal::from(5_000_000_000u64), 20)
+            .evaluate(&data, as_of)
+            .await
+            .unwrap();
+        assert!(liquid.contains(&Symbol::equity("AAPL")));
+        assert!(!liquid.contains(&Symbol::crypto("BTC-USD")));
+
+        let seasoned = Screen::new("seasoned")
+            .min_coverage(Duration::days(3 * 365))
+            .evaluate(&data, as_of)
+            .await
+            .unwrap();
+        assert_eq!(tickers(&seasoned), ["AAPL", "BTC-USD", "GOOGL"]);
+
+        let combined = Screen::new("us large caps")
+            .asset_classes([AssetClass::Equity])
+            .price_between(Some(Decimal::from(5)), None)
+            .min_average_dollar_volume(Decimal::from(10_000_000), 20)
+            .min_coverage(Duration::days(3 * 365))
+            .evaluate(&data, as_of)
+            .await
+            .unwrap();
+        assert_eq!(combined.name, "us large caps");
+        assert_eq!(combined.as_of, as_of);
+        assert_eq!(tickers(&combined), ["AAPL", "GOOGL"]);
+    }
+
+    #[tokio::test]
+    async fn filters_on_fundamentals_known_at_the_screen_date() {
+        let data = manager(&[
+            (Symbol::equity("AAPL"), day(2020, 1, 1)),
+            (Symbol::equity("MSFT"), day(2020, 1, 1)),
+            (Symbol::equity("TSLA"), day(2020, 1, 1)),
+        ])
+        .await;
+        let mut table = FundamentalsTable::new();
+        table
+            .insert(
+                Symbol::equity("AAPL"),
+                day(2020, 1, 1),
+                fundamentals("Technology", 2_000),
+            )
+            .insert(
+                Symbol::equity("MSFT"),
+                day(2020, 1, 1),
+                fundamentals("Technology", 1_500),
+            )
+            .insert(
+                Symbol::equity("TSLA"),
+                day(2020, 1, 1),
+                fundamentals("Consumer Discretionary", 100),
+            )
+            // Reclassified, and re-rated, only after the first screen date.
+            .insert(
+                Symbol::equity("TSLA"),
+                day(2024, 3, 1),
+                fundamentals("Technology", 800),
+            );
+        let screen = Screen::new("large tech")
+            .with_fundamentals(Arc::new(table))
+            .sectors(["Technology"])
+            .market_cap_between(Some(Decimal::from(500)), None);
+
+        let before = screen
+            .evaluate_candidates(
+                &data,
+                &[
+                    Symbol::equity("TSLA"),
+                    Symbol::equity("AAPL"),
+                    Symbol::equity("MSFT"),
+                ],
+                day(2024, 1, 1),
+            )
+            .await
+            .unwrap();
+        assert_eq!(tickers(&before), ["AAPL", "MSFT"]);
+
+        let after = screen.evaluate(&data, day(2024, 6, 1)).await.unwrap();
+        assert_eq!(tickers(&after), ["AAPL", "MSFT", "TSLA"]);
+
+        let error = Screen::new("no source")
+            .sectors(["Technology"])
+            .evaluate(&data, day(2024, 6, 1))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, GbError::Config(_)), "{error}");
+    }
+
+    #[tokio::test]
+    async fn bar_filters_ignore_bars_after_the_screen_date() {
+        let data = manager(&[(Symbol::equity("AAPL"), day(2024, 1, 1))]).await;
+        let bars = data
+            .storage
+            .load_bars(
+                &Symbol::equity("AAPL"),
+                day(2024, 1, 1),
+                day(2024, 12, 31),
+                Resolution::Day,
+            )
+            .await
+            .unwrap();
+        let as_of = day(2024, 3, 1);
+        let close_at = bars
+            .iter()
+            .rev()
+            .find(|bar| bar.timestamp <= as_of)
+            .unwrap()
+            .close;
+        let last_close = bars.last().unwrap().close;
+        assert_ne!(close_at, last_close);
+
+        let exact = |price: Decimal| Screen::new("exact").price_between(Some(price), Some(price));
+        let screened = exact(close_at).evaluate(&data, as_of).await.unwrap();
+        assert_eq!(tickers(&screened), ["AAPL"]);
+        let screened = exact(last_close).evaluate(&data, as_of).await.unwrap();
+        assert!(screened.is_empty());
+
+        // Nothing is stored before the first bar, so nothing can pass.
+        let screened = exact(close_at)
+            .evaluate(&data, day(2023, 12, 1))
+            .await
+            .unwrap();
+        assert!(screened.is_empty());
+
+        // Coverage is measured back from the screen date, not from today.
+        let coverage = Screen::new("a month").min_coverage(Duration::days(30));
+        let screened = coverage.evaluate(&data, day(2024, 1, 15)).await.unwrap();
+        assert!(screened.is_empty());
+        let screened = coverage.evaluate(&data, day(2024, 3, 1)).await.unwrap();
+        assert_eq!(tickers(&screened), ["AAPL"]);
+    }
+
+    #[tokio::test]
+    async fn universes_round_trip_through_watchlists_into_a_backtest_config() {
+        let mut data = manager(&[
+            (Symbol::equity("AAPL"), day(2020, 1, 1)),
+            (Symbol::equity("SPY"), day(2020, 1, 1)),
+        ])
+        .await;
+        let universe = Screen::new("liquid")
+            .min_average_dollar_volume(Decimal::from(10_000_000), 20)
+            .evaluate(&data, day(2024, 6, 1))
+            .await
+            .unwrap();
+        assert_eq!(universe.len(), 2);
+
+        data.catalog.save_watchlist(&universe).await.unwrap();
+        assert_eq!(data.catalog.list_watchlists().await.unwrap(), ["liquid"]);
+        let saved = data
+            .catalog
+            .load_watchlist("liquid")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved, universe);
+        assert!(data
+            .catalog
+            .load_watchlist("other")
+            .await
+            .unwrap()
+            .is_none());
+
+        let config = gb_types::BacktestConfig::new(
+            "screened".into(),
+            gb_types::StrategyConfig::new("buy_and_hold".into(), "Buy and hold".into()),
+        )
+        .with_universe(&saved);
+        assert_eq!(config.symbols, universe.symbols);
+        assert_eq!(config.universe.as_deref(), Some("liquid"));
+
+        assert!(data.catalog.delete_watchlist("liquid").await.unwrap());
+        assert!(data.catalog.list_watchlists().await.unwrap().is_empty());
+    }
+}
@@ -1,11 +1,13 @@
 // TODO: Re-enable when Arrow compatibility issues are resolved - RESOLVED!
 use arrow::array::{
-    Array, ArrayRef, Decimal128Array, Int64Array, StringArray, TimestampNanosecondArray,
+    Array, ArrayRef, Date32Array, Decimal128Array, Float64Array, Int64Array, StringArray,
+    TimestampNanosecondArray,
 };
 use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
 use arrow::record_batch::RecordBatch;
-use chrono::{DateTime, Utc};
-use gb_types::{Bar, DataError, GbResult, Resolution, Symbol};
+use chrono::{DateTime, NaiveDate, Utc};
+use gb_options::OptionKind;
+use gb_types::{Bar, BarProvenance, DataError, GbResult, Resolution, Symbol};
 use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter};
 use parquet::file::properties::WriterProperties;
 use rust_decimal::prelude::ToPrimitive;
@@ -15,6 +17,28 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use crate::options::{OptionChainSnapshot, OptionQuote};
+
+/// Directory under the data root holding option chains, apart from bar data.
+const OPTIONS_DIR: &str = "options";
+
+/// Flush the file at `path` to disk.
+pub(crate) fn sync_file(path: &Path) -> std::io::Result<()> {
+    fs::OpenOptions::new().write(true).open(path)?.sync_all()
+}
+
+/// Flush the directory holding `path`, so that renaming or removing it
+/// survives a crash. Windows cannot open directories to flush them.
+pub(crate) fn sync_parent(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        fs::File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
 /// Storage manager for Parquet files
 #[derive(Debug)]
 pub struct StorageManager {
@@ -30,7 +54,7 @@ impl StorageManager {
     }
 
     /// Generate the storage path for a symbol and resolution
-    fn get_storage_path(&self, symbol: &Symbol, resolution: Resolution) -> PathBuf {
+    pub fn get_storage_path(&self, symbol: &Symbol, resolution: Resolution) -> PathBuf {
         self.data_root
             .join(&symbol.exchange)
             .join(format!("{:?}", symbol.asset_class))
@@ -38,6 +62,16 @@ impl StorageManager {
             .join(format!("{}.parquet", resolution))
     }
 
+    /// Size in bytes of the file storing `symbol` at `resolution`, or
+    /// `None` when nothing is stored
+    pub fn bar_file_size(&self, symbol: &Symbol, resolution: Resolution) -> GbResult<Option<u64>> {
+        let storage_path = self.get_storage_path(symbol, resolution);
+        if !storage_path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(fs::metadata(storage_path)?.len()))
+    }
+
     /// Save bars to Parquet file
     pub async fn save_bars(
         &self,
@@ -45,6 +79,25 @@ impl StorageManager {
         bars: &[Bar],
         resolution: Resolution,
     ) -> GbResult<()> {
+        let staged_path = self.stage_bars(symbol, bars, resolution).await?;
+        Self::install_staged(&staged_path, &self.get_storage_path(symbol, resolution))
+    }
+
+    /// Where bars bound for `storage_path` are written before being renamed
+    /// over it.
+    pub fn staged_path(storage_path: &Path) -> PathBuf {
+        storage_path.with_extension("parquet.tmp")
+    }
+
+    /// First half of [`save_bars`](Self::save_bars): merge `bars` with the
+    /// stored ones and write the result beside the stored file, flushed to
+    /// disk, without touching it. Returns the staged file's path.
+    pub async fn stage_bars(
+        &self,
+        symbol: &Symbol,
+        bars: &[Bar],
+        resolution: Resolution,
+    ) -> GbResult<PathBuf> {
         let storage_path = self.get_storage_path(symbol, resolution);
 
         // Ensure parent directory exists
@@ -59,14 +112,28 @@ impl StorageManager {
         };
 
         let merged_bars = Self::merge_bars(existing_bars, bars);
-        Self::write_bars_atomically(&storage_path, &merged_bars)?;
+        let staged_path = Self::staged_path(&storage_path);
+        let result = Self::write_bars_to_path(&staged_path, &merged_bars)
+            .and_then(|()| sync_file(&staged_path).map_err(Into::into));
+        if let Err(err) = result {
+            let _ = fs::remove_file(&staged_path);
+            return Err(err);
+        }
 
         tracing::info!(
-            "Saved {} merged bars ({} new) to {}",
+            "Staged {} merged bars ({} new) for {}",
             merged_bars.len(),
             bars.len(),
             storage_path.display()
         );
+        Ok(staged_path)
+    }
+
+    /// Second half of [`save_bars`](Self::save_bars): atomically replace
+    /// `storage_path` with the file staged for it.
+    pub fn install_staged(staged_path: &Path, storage_path: &Path) -> GbResult<()> {
+        fs::rename(staged_path, storage_path)?;
+        sync_parent(storage_path)?;
         Ok(())
     }
 
@@ -94,27 +161,20 @@ impl StorageManager {
         Ok(bars)
     }
 
-    fn load_all_bars_from_path(
+    pub(crate) fn load_all_bars_from_path(
         storage_path: &Path,
         symbol: &Symbol,
         resolution: Resolution,
     ) -> GbResult<Vec<Bar>> {
-        let file = fs::File::open(storage_path)?;
+        let file = fs::File::open(storage_path).map_err(|e| DataError::from_io(storage_path, e))?;
         let reader = ParquetRecordBatchReaderBuilder::try_new(file)
-            .map_err(|e| DataError::LoadingFailed {
-                message: e.to_string(),
-            })?
-            .build()
-            .map_err(|e| DataError::LoadingFailed {
-                message: e.to_string(),
-            })?;
+            .and_then(|builder| builder.build())
+            .map_err(|e| DataError::corrupt_file(storage_path, e))?;
 
         let mut bars = Vec::new();
 
         for batch_result in reader {
-            let batch = batch_result.map_err(|e| DataError::LoadingFailed {
-                message: e.to_string(),
-            })?;
+            let batch = batch_result.map_err(|e| DataError::corrupt_file(storage_path, e))?;
             bars.extend(Self::record_batch_to_bars(&batch, symbol, resolution)?);
         }
 
@@ -135,19 +195,6 @@ impl StorageManager {
         merged.into_values().collect()
     }
 
-    fn write_bars_atomically(storage_path: &Path, bars: &[Bar]) -> GbResult<()> {
-        let temp_path = storage_path.with_extension("parquet.tmp");
-        let result = Self::write_bars_to_path(&temp_path, bars);
-
-        if let Err(err) = result {
-            let _ = fs::remove_file(&temp_path);
-            return Err(err);
-        }
-
-        fs::rename(&temp_path, storage_path)?;
-        Ok(())
-    }
-
     fn write_bars_to_path(storage_path: &Path, bars: &[Bar]) -> GbResult<()> {
         let schema = Self::get_schema();
         let writer = ArrowWriter::try_new(
@@ -155,20 +202,16 @@ impl StorageManager {
             schema,
             Some(WriterProperties::builder().build()),
         )
-        .map_err(|e| DataError::LoadingFailed {
-            message: e.to_string(),
-        })?;
+        .map_err(|e| DataError::write_failed(storage_path, e))?;
 
         let record_batch = Self::bars_to_record_batch(bars)?;
         let mut writer = writer;
         writer
             .write(&record_batch)
-            .map_err(|e| DataError::LoadingFailed {
-                message: e.to_string(),
-            })?;
-        writer.close().map_err(|e| DataError::LoadingFailed {
-            message: e.to_string(),
-        })?;
+            .map_err(|e| DataError::write_failed(storage_path, e))?;
+        writer
+            .close()
+            .map_err(|e| DataError::write_failed(storage_path, e))?;
 
         Ok(())
     }
@@ -202,6 +245,19 @@ impl StorageManager {
             .iter()
             .map(|b| b.volume.to_i64().unwrap_or(0))
             .collect();
+        let provenances = bars
+            .iter()
+            .map(|b| {
+                if b.provenance.is_unknown() {
+                    return Ok(None);
+                }
+                serde_json::to_string(&b.provenance).map(Some).map_err(|e| {
+                    DataError::InvalidFormat {
+                        message: e.to_string(),
+                    }
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
 
         let arrays: Vec<ArrayRef> = vec![
             Arc::new(StringArray::from(symbols)),
@@ -235,6 +291,7 @@ impl StorageManager {
                     })?,
             ),
             Arc::new(Int64Array::from(volumes)),
+            Arc::new(StringArray::from(provenances)),
         ];
 
         let batch = RecordBatch::try_new(schema, arrays).map_err(|e| DataError::InvalidFormat {
@@ -297,6 +354,16 @@ impl StorageManager {
                 message: "Invalid volume column".to_string(),
             })?;
 
+        // Files written before provenance was tracked lack the column
+        let provenances = match batch.column_by_name("provenance") {
+            Some(column) => Some(column.as_any().downcast_ref::<StringArray>().ok_or_else(
+                || DataError::Corruption {
+                    message: "Invalid provenance column".to_string(),
+                },
+            )?),
+            None => None,
+        };
+
         let mut bars = Vec::new();
 
         for i in 0..batch.num_rows() {
@@ -322,6 +389,16 @@ impl StorageManager {
             let low = Decimal::from_i128_with_scale(lows.value(i), 4);
             let close = Decimal::from_i128_with_scale(closes.value(i), 4);
             let volume = Decimal::from(volumes.value(i));
+            let provenance = match provenances {
+                Some(provenances) if !provenances.is_null(i) => {
+                    serde_json::from_str(provenances.value(i)).map_err(|e| {
+                        DataError::Corruption {
+                            message: format!("Invalid provenance: {e}"),
+                        }
+                    })?
+                }
+                _ => BarProvenance::default(),
+            };
 
             let bar = Bar::new(
                 symbol.clone(),
@@ -332,7 +409,8 @@ impl StorageManager {
                 close,
                 volume,
                 resolution,
-            );
+            )
+            .with_provenance(provenance);
 
             bars.push(bar);
         }
@@ -354,6 +432,323 @@ impl StorageManager {
             Field::new("low", DataType::Decimal128(18, 4), false),
             Field::new("close", DataType::Decimal128(18, 4), false),
             Field::new("volume", DataType::Int64, false),
+            Field::new("provenance", DataType::Utf8, true),
+        ]))
+    }
+
+    /// Directory holding every option chain partition for an underlying.
+    fn get_option_chain_root(&self, underlying: &Symbol) -> PathBuf {
+        self.data_root
+            .join(OPTIONS_DIR)
+            .join(&underlying.exchange)
+            .join(&underlying.symbol)
+    }
+
+    /// Partition for the quotes of one expiry taken on one day.
+    fn get_option_partition_path(
+        &self,
+        underlying: &Symbol,
+        expiration: NaiveDate,
+        quote_date: NaiveDate,
+    ) -> PathBuf {
+        self.get_option_chain_root(underlying)
+            .join(expiration.to_string())
+            .join(format!("{quote_date}.parquet"))
+    }
+
+    /// Save option quotes, partitioned by underlying, expiry and quote date.
+    /// Quotes already stored for the same contract and timestamp are replaced.
+    pub async fn save_option_quotes(
+        &self,
+        underlying: &Symbol,
+        quotes: &[OptionQuote],
+    ) -> GbResult<()> {
+        let mut partitions: BTreeMap<(NaiveDate, NaiveDate), Vec<&OptionQuote>> = BTreeMap::new();
+        for quote in quotes {
+            partitions
+                .entry((quote.expiration, quote.timestamp.date_naive()))
+                .or_default()
+                .push(quote);
+        }
+
+        for ((expiration, quote_date), new_quotes) in partitions {
+            let path = self.get_option_partition_path(underlying, expiration, quote_date);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            let mut merged = BTreeMap::new();
+            if path.exists() {
+                for quote in Self::load_option_quotes_from_path(&path, underlying)? {
+                    merged.insert((quote.contract_id.clone(), quote.timestamp), quote);
+                }
+            }
+            for quote in new_quotes {
+                merged.insert((quote.contract_id.clone(), quote.timestamp), quote.clone());
+            }
+            let merged: Vec<_> = merged.into_values().collect();
+
+            let temp_path = path.with_extension("parquet.tmp");
+            if let Err(err) = Self::write_option_quotes_to_path(&temp_path, &merged) {
+                let _ = fs::remove_file(&temp_path);
+                return Err(err);
+            }
+            fs::rename(&temp_path, &path)?;
+        }
+
+        tracing::info!("Saved {} option quotes for {}", quotes.len(), underlying);
+        Ok(())
+    }
+
+    /// Every quote date stored for an underlying, with the partitions (one per
+    /// expiry) holding that date's quotes.
+    pub fn list_option_quote_dates(
+        &self,
+        underlying: &Symbol,
+    ) -> GbResult<BTreeMap<NaiveDate, Vec<PathBuf>>> {
+        let mut dates: BTreeMap<NaiveDate, Vec<PathBuf>> = BTreeMap::new();
+        let root = self.get_option_chain_root(underlying);
+        if !root.exists() {
+            return Ok(dates);
+        }
+
+        for expiry_entry in fs::read_dir(&root)? {
+            let expiry_path = expiry_entry?.path();
+            if !expiry_path.is_dir() {
+                continue;
+            }
+            for partition_entry in fs::read_dir(&expiry_path)? {
+                let partition_path = partition_entry?.path();
+                if partition_path.extension().and_then(|e| e.to_str()) != Some("parquet") {
+                    continue;
+                }
+                let Some(date) = partition_path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .and_then(|stem| stem.parse::<NaiveDate>().ok())
+                else {
+                    continue;
+                };
+                dates.entry(date).or_default().push(partition_path);
+            }
+        }
+
+        Ok(dates)
+    }
+
+    /// The latest option chain snapshot taken at or before `as_of`, or `None`
+    /// when nothing was quoted by then. Quotes stamped after `as_of` are never
+    /// returned, even when they fall on the same day.
+    pub async fn load_option_snapshot(
+        &self,
+        underlying: &Symbol,
+        as_of: DateTime<Utc>,
+    ) -> GbResult<Option<OptionChainSnapshot>> {
+        let dates = self.list_option_quote_dates(underlying)?;
+
+        for paths in dates
+            .range(..=as_of.date_naive())
+            .rev()
+            .map(|(_, paths)| paths)
+        {
+            let mut quotes = Vec::new();
+            for path in paths {
+                quotes.extend(Self::load_option_quotes_from_path(path, underlying)?);
+            }
+            quotes.retain(|quote| quote.timestamp <= as_of);
+
+            let Some(timestamp) = quotes.iter().map(|quote| quote.timestamp).max() else {
+                continue;
+            };
+            quotes.retain(|quote| quote.timestamp == timestamp);
+            return Ok(Some(OptionChainSnapshot::new(
+                underlying.clone(),
+                timestamp,
+                quotes,
+            )));
+        }
+
+        Ok(None)
+    }
+
+    fn load_option_quotes_from_path(
+        storage_path: &Path,
+        underlying: &Symbol,
+    ) -> GbResult<Vec<OptionQuote>> {
+        let file = fs::File::open(storage_path).map_err(|e| DataError::from_io(storage_path, e))?;
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+            .and_then(|builder| builder.build())
+            .map_err(|e| DataError::corrupt_file(storage_path, e))?;
+
+        let mut quotes = Vec::new();
+        for batch_result in reader {
+            let batch = batch_result.map_err(|e| DataError::corrupt_file(storage_path, e))?;
+            quotes.extend(Self::record_batch_to_option_quotes(&batch, underlying)?);
+        }
+        Ok(quotes)
+    }
+
+    fn write_option_quotes_to_path(storage_path: &Path, quotes: &[OptionQuote]) -> GbResult<()> {
+        let mut writer = ArrowWriter::try_new(
+            fs::File::create(storage_path)?,
+            Self::get_option_schema(),
+            Some(WriterProperties::builder().build()),
+        )
+        .map_err(|e| DataError::write_failed(storage_path, e))?;
+
+        let record_batch = Self::option_quotes_to_record_batch(quotes)?;
+        writer
+            .write(&record_batch)
+            .map_err(|e| DataError::write_failed(storage_path, e))?;
+        writer
+            .close()
+            .map_err(|e| DataError::write_failed(storage_path, e))?;
+        Ok(())
+    }
+
+    fn option_quotes_to_record_batch(quotes: &[OptionQuote]) -> GbResult<RecordBatch> {
+        let scaled = |value: Decimal| (value * Decimal::from(10000)).to_i128().unwrap_or(0);
+        let decimal_array = |values: Vec<Option<i128>>| -> GbResult<ArrayRef> {
+            Ok(Arc::new(
+                Decimal128Array::from(values)
+                    .with_precision_and_scale(18, 4)
+                    .map_err(|e| DataError::InvalidFormat {
+                        message: e.to_string(),
+                    })?,
+            ))
+        };
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch");
+
+        let arrays: Vec<ArrayRef> = vec![
+            Arc::new(StringArray::from_iter_values(
+                quotes.iter().map(|q| q.contract_id.as_str()),
+            )),
+            Arc::new(Date32Array::from_iter_values(
+                quotes
+                    .iter()
+                    .map(|q| (q.expiration - epoch).num_days() as i32),
+            )),
+            decimal_array(quotes.iter().map(|q| Some(scaled(q.strike))).collect())?,
+            Arc::new(StringArray::from_iter_values(quotes.iter().map(
+                |q| match q.kind {
+                    OptionKind::Call => "C",
+                    OptionKind::Put => "P",
+                },
+            ))),
+            Arc::new(
+                TimestampNanosecondArray::from_iter_values(
+                    quotes
+                        .iter()
+                        .map(|q| q.timestamp.timestamp_nanos_opt().unwrap_or(0)),
+                )
+                .with_timezone("UTC"),
+            ),
+            decimal_array(quotes.iter().map(|q| Some(scaled(q.bid))).collect())?,
+            decimal_array(quotes.iter().map(|q| Some(scaled(q.ask))).collect())?,
+            decimal_array(quotes.iter().map(|q| q.last.map(scaled)).collect())?,
+            Arc::new(Int64Array::from_iter_values(
+                quotes.iter().map(|q| q.volume as i64),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                quotes.iter().map(|q| q.open_interest as i64),
+            )),
+            Arc::new(Float64Array::from_iter(
+                quotes.iter().map(|q| q.implied_volatility),
+            )),
+        ];
+
+        RecordBatch::try_new(Self::get_option_schema(), arrays).map_err(|e| {
+            DataError::InvalidFormat {
+                message: e.to_string(),
+            }
+            .into()
+        })
+    }
+
+    fn record_batch_to_option_quotes(
+        batch: &RecordBatch,
+        underlying: &Symbol,
+    ) -> GbResult<Vec<OptionQuote>> {
+        fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> GbResult<&'a T> {
+            batch
+                .column_by_name(name)
+                .and_then(|column| column.as_any().downcast_ref::<T>())
+                .ok_or_else(|| {
+                    DataError::Corruption {
+                        message: format!("Invalid {name} column"),
+                    }
+                    .into()
+                })
+        }
+
+        let contract_ids = column::<StringArray>(batch, "contract_id")?;
+        let expirations = column::<Date32Array>(batch, "expiration")?;
+        let strikes = column::<Decimal128Array>(batch, "strike")?;
+        let kinds = column::<StringArray>(batch, "kind")?;
+        let timestamps = column::<TimestampNanosecondArray>(batch, "timestamp")?;
+        let bids = column::<Decimal128Array>(batch, "bid")?;
+        let asks = column::<Decimal128Array>(batch, "ask")?;
+        let lasts = column::<Decimal128Array>(batch, "last")?;
+        let volumes = column::<Int64Array>(batch, "volume")?;
+        let open_interests = column::<Int64Array>(batch, "open_interest")?;
+        let implied_vols = column::<Float64Array>(batch, "implied_volatility")?;
+
+        let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid epoch");
+        let mut quotes = Vec::with_capacity(batch.num_rows());
+        for i in 0..batch.num_rows() {
+            let timestamp_nanos = timestamps.value(i);
+            let kind = match kinds.value(i) {
+                "C" => OptionKind::Call,
+                "P" => OptionKind::Put,
+                other => {
+                    return Err(DataError::Corruption {
+                        message: format!("Invalid option kind {other}"),
+                    }
+                    .into())
+                }
+            };
+
+            quotes.push(OptionQuote {
+                contract_id: contract_ids.value(i).to_string(),
+                underlying: underlying.clone(),
+                expiration: epoch + chrono::Duration::days(expirations.value(i) as i64),
+                strike: Decimal::from_i128_with_scale(strikes.value(i), 4),
+                kind,
+                timestamp: DateTime::from_timestamp(
+                    timestamp_nanos.div_euclid(1_000_000_000),
+                    timestamp_nanos.rem_euclid(1_000_000_000) as u32,
+                )
+                .unwrap_or_default(),
+                bid: Decimal::from_i128_with_scale(bids.value(i), 4),
+                ask: Decimal::from_i128_with_scale(asks.value(i), 4),
+                last: (!lasts.is_null(i)).then(|| Decimal::from_i128_with_scale(lasts.value(i), 4)),
+                volume: volumes.value(i).max(0) as u64,
+                open_interest: open_interests.value(i).max(0) as u64,
+                implied_volatility: (!implied_vols.is_null(i)).then(|| implied_vols.value(i)),
+            });
+        }
+
+        Ok(quotes)
+    }
+
+    /// Get the Arrow schema for option quote data
+    fn get_option_schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("contract_id", DataType::Utf8, false),
+            Field::new("expiration", DataType::Date32, false),
+            Field::new("strike", DataType::Decimal128(18, 4), false),
+            Field::new("kind", DataType::Utf8, false),
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())),
+                false,
+            ),
+            Field::new("bid", DataType::Decimal128(18, 4), false),
+            Field::new("ask", DataType::Decimal128(18, 4), false),
+            Field::new("last", DataType::Decimal128(18, 4), true),
+            Field::new("volume", DataType::Int64, false),
+            Field::new("open_interest", DataType::Int64, false),
+            Field::new("implied_volatility", DataType::Float64, true),
         ]))
     }
 
@@ -376,6 +771,9 @@ impl StorageManager {
                 .and_then(|n| n.to_str())
                 .unwrap_or("unknown")
                 .to_string();
+            if exchange == OPTIONS_DIR {
+                continue;
+            }
 
             for asset_class_entry in std::fs::read_dir(&exchange_path)? {
                 let asset_class_path = asset_class_entry?.path();
@@ -388,13 +786,7 @@ impl StorageManager {
                     .and_then(|n| n.to_str())
                     .unwrap_or("Equity");
 
-                let asset_class = match asset_class_str {
-                    "Crypto" => gb_types::AssetClass::Crypto,
-                    "Forex" => gb_types::AssetClass::Forex,
-                    "Commodity" => gb_types::AssetClass::Commodity,
-                    "Bond" => gb_types::AssetClass::Bond,
-                    _ => gb_types::AssetClass::Equity,
-                };
+                let asset_class = asset_class_from_dir(asset_class_str);
 
                 for symbol_entry in std::fs::read_dir(&asset_class_path)? {
                     let symbol_path = symbol_entry?.path();
@@ -422,34 +814,99 @@ impl StorageManager {
         let mut total_files = 0;
         let mut total_size = 0u64;
 
-        fn scan_directory(path: &Path, stats: &mut (u64, u64)) -> std::io::Result<()> {
+        fn scan_directory(path: &Path, files: &mut Vec<(PathBuf, u64)>) -> std::io::Result<()> {
             for entry in std::fs::read_dir(path)? {
                 let entry = entry?;
                 let path = entry.path();
 
                 if path.is_dir() {
-                    scan_directory(&path, stats)?;
+                    scan_directory(&path, files)?;
                 } else if path.extension().and_then(|s| s.to_str()) == Some("parquet") {
-                    stats.0 += 1;
-                    stats.1 += entry.metadata()?.len();
+                    let size = entry.metadata()?.len();
+                    files.push((path, size));
                 }
             }
             Ok(())
         }
 
+        // Bar files sit at `<exchange>/<asset class>/<symbol>/<resolution>.parquet`.
+        let mut symbols: BTreeMap<(String, String, String), SymbolStorageStats> = BTreeMap::new();
         if self.data_root.exists() {
-            let mut stats = (0u64, 0u64);
-            scan_directory(&self.data_root, &mut stats)?;
-            total_files = stats.0;
-            total_size = stats.1;
+            let mut files = Vec::new();
+            scan_directory(&self.data_root, &mut files)?;
+            for (path, size) in files {
+                total_files += 1;
+                total_size += size;
+
+                let Ok(relative) = path.strip_prefix(&self.data_root) else {
+                    continue;
+                };
+                let parts: Vec<&str> = relative.iter().filter_map(|part| part.to_str()).collect();
+                let [exchange, asset_class, symbol, _file] = parts.as_slice() else {
+                    continue;
+                };
+                if *exchange == OPTIONS_DIR {
+                    continue;
+                }
+                let stats = symbols
+                    .entry((
+                        symbol.to_string(),
+                        exchange.to_string(),
+                        asset_class.to_string(),
+                    ))
+                    .or_insert_with(|| SymbolStorageStats {
+                        symbol: Symbol::new(symbol, exchange, asset_class_from_dir(asset_class)),
+                        files: 0,
+                        size_bytes: 0,
+                    });
+                stats.files += 1;
+                stats.size_bytes += size;
+            }
         }
 
         Ok(StorageStats {
             total_files,
             total_size_bytes: total_size,
             data_root: self.data_root.clone(),
+            symbols: symbols.into_values().collect(),
         })
     }
+
+    /// Delete every stored resolution of `symbol`'s bars, returning how many
+    /// files were removed. Option chains are kept.
+    pub fn delete_symbol(&self, symbol: &Symbol) -> GbResult<u64> {
+        let symbol_dir = self
+            .data_root
+            .join(&symbol.exchange)
+            .join(format!("{:?}", symbol.asset_class))
+            .join(&symbol.symbol);
+        if symbol.exchange == OPTIONS_DIR || !symbol_dir.is_dir() {
+            return Ok(0);
+        }
+
+        let mut removed = 0;
+        for entry in fs::read_dir(&symbol_dir)? {
+            let path = entry?.path();
+            if path.extension().and_then(|s| s.to_str()) == Some("parquet") {
+                removed += 1;
+            }
+        }
+        fs::remove_dir_all(&symbol_dir)?;
+
+        tracing::info!("Deleted {} files from {}", removed, symbol_dir.display());
+        Ok(removed)
+    }
+}
+
+fn asset_class_from_dir(name: &str) -> gb_types::AssetClass {
+    match name {
+        "Crypto" => gb_types::AssetClass::Crypto,
+        "Forex" => gb_types::AssetClass::Forex,
+        "Commodity" => gb_types::AssetClass::Commodity,
+        "Bond" => gb_types::AssetClass::Bond,
+        "Option" => gb_types::AssetClass::Option,
+        _ => gb_types::AssetClass::Equity,
+    }
 }
 
 /// Storage statistics
@@ -458,6 +915,16 @@ pub struct StorageStats {
     pub total_files: u64,
     pub total_size_bytes: u64,
     pub data_root: PathBuf,
+    /// Bar files per symbol, ordered by symbol, exchange and asset class.
+    pub symbols: Vec<SymbolStorageStats>,
+}
+
+/// Stored bar files for one symbol, across resolutions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolStorageStats {
+    pub symbol: Symbol,
+    pub files: u64,
+    pub size_bytes: u64,
 }
 
 impl StorageStats {
@@ -514,6 +981,66 @@ mod tests {
         assert_eq!(loaded_bars, bars);
     }
 
+    #[tokio::test]
+    async fn test_storage_roundtrips_bar_provenance() {
+        let temp_dir = tempdir().unwrap();
+        let storage = StorageManager::new(temp_dir.path()).unwrap();
+
+        let symbol = Symbol::new("AAPL", "NASDAQ", AssetClass::Equity);
+        let bars = vec![sample_bar(&symbol, 10, 102).with_provenance(BarProvenance {
+            provider: Some("alpaca".to_string()),
+            split_adjusted: true,
+            resampled_from: Some(Resolution::Minute),
+            ..BarProvenance::default()
+        })];
+
+        storage
+            .save_bars(&symbol, &bars, Resolution::Day)
+            .await
+            .unwrap();
+
+        let start = Utc.with_ymd_and_hms(2026, 3, 9, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2026, 3, 11, 0, 0, 0).unwrap();
+        let loaded_bars = storage
+            .load_bars(&symbol, start, end, Resolution::Day)
+            .await
+            .unwrap();
+
+        assert_eq!(loaded_bars, bars);
+        assert_eq!(
+            loaded_bars[0].provenance.resampled_from,
+            Some(Resolution::Minute)
+        );
+        assert!(loaded_bars[0].provenance.split_adjusted);
+    }
+
+    #[test]
+    fn test_legacy_files_without_provenance_load_with_default() {
+        let temp_dir = tempdir().unwrap();
+        let path = temp_dir.path().join("legacy.parquet");
+        let symbol = Symbol::new("AAPL", "NASDAQ", AssetClass::Equity);
+        let bars = vec![sample_bar(&symbol, 10, 102)];
+
+        let legacy_batch = StorageManager::bars_to_record_batch(&bars)
+            .unwrap()
+            .project(&[0, 1, 2, 3, 4, 5, 6])
+            .unwrap();
+        let mut writer = ArrowWriter::try_new(
+            fs::File::create(&path).unwrap(),
+            legacy_batch.schema(),
+            None,
+        )
+        .unwrap();
+        writer.write(&legacy_batch).unwrap();
+        writer.close().unwrap();
+
+        let loaded_bars =
+            StorageManager::load_all_bars_from_path(&path, &symbol, Resolution::Day).unwrap();
+
+        assert_eq!(loaded_bars, bars);
+        assert!(loaded_bars[0].provenance.is_unknown());
+    }
+
     #[tokio::test]
     async fn test_save_bars_merges_non_overlapping_history() {
         let temp_dir = tempdir().unwrap();
@@ -0,0 +1,273 @@
+//! Per-provider ticker mapping.
+//!
+//! The same instrument is `BRK.B` at one vendor, `BRK-B` at another and
+//! `BRK/B` at a broker, and crypto pairs are spelled differently
+//! everywhere. GlowBack keeps one canonical [`Symbol`] — share classes with
+//! a dot, crypto pairs as `BASE-QUOTE` — and each provider or broker
+//! translates it through its [`ProviderSymbols`] table at the edge.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use gb_types::{AssetClass, DataError, GbError, GbResult, Symbol};
+use serde::{Deserialize, Serialize};
+
+/// How one provider or broker spells tickers. The default passes canonical
+/// tickers through unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProviderSymbols {
+    /// Replaces the `.` of share-class tickers such as `BRK.B`.
+    pub share_class_separator: Option<String>,
+    /// Replaces the `-` of crypto pairs such as `BTC-USD`; empty joins the
+    /// two assets.
+    pub pair_separator: Option<String>,
+    /// Provider tickers by canonical ticker, taking precedence over the
+    /// separators.
+    pub aliases: BTreeMap<String, String>,
+    /// Only aliased tickers are known; any other symbol is an error rather
+    /// than a guess.
+    pub aliases_only: bool,
+}
+
+impl ProviderSymbols {
+    /// Share classes and pairs spelled with a dash: `BRK-B`, `BTC-USD`.
+    pub fn dashes() -> Self {
+        Self {
+            share_class_separator: Some("-".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Share classes and pairs spelled with a slash: `BRK/B`, `BTC/USD`.
+    pub fn slashes() -> Self {
+        Self {
+            share_class_separator: Some("/".to_string()),
+            pair_separator: Some("/".to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Pairs joined without a separator, `BTCUSD`; equities unchanged.
+    pub fn joined_pairs() -> Self {
+        Self {
+            pair_separator: Some(String::new()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_alias(mut self, canonical: impl Into<String>, ticker: impl Into<String>) -> Self {
+        self.aliases.insert(canonical.into(), ticker.into());
+        self
+    }
+
+    /// The explicit alias of `symbol`, if any.
+    pub fn alias(&self, symbol: &Symbol) -> Option<&str> {
+        self.aliases.get(&symbol.symbol).map(String::as_str)
+    }
+
+    /// The provider's ticker for `symbol`, or `None` under
+    /// [`aliases_only`](Self::aliases_only) when it has no alias.
+    pub fn ticker(&self, symbol: &Symbol) -> Option<String> {
+        if let Some(alias) = self.alias(symbol) {
+            return Some(alias.to_string());
+        }
+        if self.aliases_only {
+            return None;
+        }
+        let (canonical, separator) = match symbol.asset_class {
+            AssetClass::Crypto => ('-', &self.pair_separator),
+            _ => ('.', &self.share_class_separator),
+        };
+        Some(match separator {
+            Some(separator) => symbol.symbol.replace(canonical, separator),
+            None => symbol.symbol.clone(),
+        })
+    }
+
+    /// The canonical ticker behind a provider `ticker` of `asset_class`.
+    /// Pairs joined without a separator cannot be split and come back as
+    /// they are, unless aliased.
+    pub fn canonical_ticker(&self, ticker: &str, asset_class: AssetClass) -> String {
+        if let Some((canonical, _)) = self.aliases.iter().find(|(_, alias)| *alias == ticker) {
+            return canonical.clone();
+        }
+        let (canonical, separator) = match asset_class {
+            AssetClass::Crypto => ("-", &self.pair_separator),
+            _ => (".", &self.share_class_separator),
+        };
+        match separator {
+            Some(separator) if !separator.is_empty() => {
+                ticker.replace(separator.as_str(), canonical)
+            }
+            _ => ticker.to_string(),
+        }
+    }
+}
+
+/// Ticker tables keyed by provider or broker: `"csv"`, `"alpha_vantage"`,
+/// `"alpaca"`, `"binance"`, or any name a custom provider uses.
+///
+/// Loaded from TOML, one table per provider:
+///
+/// ```toml
+/// [providers.csv]
+/// share_class_separator = "-"
+///
+/// [providers.alpaca]
+/// share_class_separator = "/"
+/// pair_separator = "/"
+/// aliases = { "FB" = "META" }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SymbolMapper {
+    pub providers: BTreeMap<String, ProviderSymbols>,
+}
+
+impl SymbolMapper {
+    /// The conventions of the built-in providers and brokers: Alpha Vantage
+    /// spells share classes with a dash, Alpaca crypto pairs with a slash,
+    /// and Binance joins pairs.
+    pub fn builtin() -> Self {
+        Self::default()
+            .with_provider("alpha_vantage", ProviderSymbols::dashes())
+            .with_provider(
+                "alpaca",
+                ProviderSymbols {
+                    pair_separator: Some("/".to_string()),
+                    ..Default::default()
+                },
+            )
+            .with_provider("binance", ProviderSymbols::joined_pairs())
+    }
+
+    /// The built-in tables with those of a TOML document laid over them;
+    /// a provider's table replaces its built-in one entirely.
+    pub fn from_toml_str(document: &str) -> GbResult<Self> {
+        let loaded: Self = toml::from_str(document).map_err(|e| DataError::ParseError {
+            message: format!("invalid symbol map: {}", e),
+        })?;
+        let mut mapper = Self::builtin();
+        mapper.providers.extend(loaded.providers);
+        Ok(mapper)
+    }
+
+    /// [`from_toml_str`](Self::from_toml_str) on the file at `path`.
+    pub fn from_toml_file(path: impl AsRef<Path>) -> GbResult<Self> {
+        let path = path.as_ref();
+        let document = std::fs::read_to_string(path).map_err(|e| DataError::from_io(path, e))?;
+        Self::from_toml_str(&document)
+    }
+
+    pub fn with_provider(mut self, provider: impl Into<String>, table: ProviderSymbols) -> Self {
+        self.providers.insert(provider.into(), table);
+        self
+    }
+
+    /// The table for `provider`; providers without one use canonical
+    /// tickers.
+    pub fn provider(&self, provider: &str) -> ProviderSymbols {
+        self.providers.get(provider).cloned().unwrap_or_default()
+    }
+
+    /// `provider`'s ticker for `symbol`.
+    pub fn ticker(&self, provider: &str, symbol: &Symbol) -> GbResult<String> {
+        self.provider(provider)
+            .ticker(symbol)
+            .ok_or_else(|| unmapped(provider, symbol))
+    }
+}
+
+/// The error for `provider` having no ticker for `symbol`.
+pub(crate) fn unmapped(provider: &str, symbol: &Symbol) -> GbError {
+    DataError::UnmappedSymbol {
+        provider: provider.to_string(),
+        symbol: symbol.to_string(),
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::{CsvDataProvider, DataProvider};
+    use chrono::{TimeZone, Utc};
+    use gb_types::Resolution;
+
+    const SYMBOL_MAP: &str = r#"
+        [providers.csv]
+        share_class_separator = "-"
+
+        [providers.alpaca]
+        share_class_separator = "/"
+        pair_separator = "/"
+    "#;
+
+    #[tokio::test]
+    async fn a_share_class_is_spelled_for_each_provider() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("BRK-B_1d.csv"),
+            "timestamp,open,high,low,close,volume\n2024-01-02,360,365,359,362,1000\n",
+        )
+        .unwrap();
+        let mapper = SymbolMapper::from_toml_str(SYMBOL_MAP).unwrap();
+        let brk = Symbol::equity("BRK.B");
+        let mut csv = CsvDataProvider::new(dir.path()).with_symbols(mapper.provider("csv"));
+
+        assert!(csv.supports_symbol(&brk));
+        let bars = csv
+            .fetch_bars(
+                &brk,
+                Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 1, 31, 0, 0, 0).unwrap(),
+                Resolution::Day,
+            )
+            .await
+            .unwrap();
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].symbol, brk);
+
+        assert_eq!(mapper.ticker("alpaca", &brk).unwrap(), "BRK/B");
+        assert_eq!(mapper.ticker("alpha_vantage", &brk).unwrap(), "BRK-B");
+        assert_eq!(mapper.ticker("unknown", &brk).unwrap(), "BRK.B");
+        let alpaca = mapper.provider("alpaca");
+        assert_eq!(
+            alpaca.canonical_ticker("BRK/B", AssetClass::Equity),
+            "BRK.B"
+        );
+        let btc = Symbol::crypto("BTC-USD");
+        assert_eq!(mapper.ticker("binance", &btc).unwrap(), "BTCUSD");
+        assert_eq!(alpaca.ticker(&btc).unwrap(), "BTC/USD");
+    }
+
+    #[tokio::test]
+    async fn unknown_mappings_fail_instead_of_fetching_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = ProviderSymbols {
+            aliases_only: true,
+            ..Default::default()
+        }
+        .with_alias("FB", "META");
+        let mut csv = CsvDataProvider::new(dir.path()).with_symbols(table.clone());
+        let brk = Symbol::equity("BRK.B");
+
+        assert!(!csv.supports_symbol(&brk));
+        let error = csv
+            .fetch_bars(
+                &brk,
+                Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 1, 31, 0, 0, 0).unwrap(),
+                Resolution::Day,
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.code(), "data.unmapped_symbol");
+        assert!(error.to_string().contains("BRK.B"));
+
+        assert_eq!(table.ticker(&Symbol::equity("FB")).unwrap(), "META");
+        assert_eq!(table.canonical_ticker("META", AssetClass::Equity), "FB");
+        assert!(SymbolMapper::from_toml_str("providers = 3").is_err());
+    }
+}
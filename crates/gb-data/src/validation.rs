@@ -1,6 +1,6 @@
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet};
 
-use chrono::{DateTime, Datelike, Utc, Weekday};
+use chrono::{DateTime, Datelike, NaiveTime, Utc, Weekday};
 use gb_types::{Bar, DataValidationSummary, DatasetKind, PriceAdjustmentMode, Resolution, Symbol};
 use rust_decimal::Decimal;
 
@@ -17,8 +17,19 @@ pub fn summarize_bars(
     let mut negative_volume_rows = 0u64;
     let mut seen_timestamps = HashSet::new();
     let mut ordered_unique_timestamps = Vec::new();
+    let mut providers = BTreeSet::new();
+    let mut resampled_bars = 0u64;
+    let mut adjusted_bars = 0u64;
+    let mut synthetic_bars = 0u64;
 
     for bar in bars {
+        if let Some(provider) = &bar.provenance.provider {
+            providers.insert(provider.clone());
+        }
+        resampled_bars += u64::from(bar.provenance.resampled_from.is_some());
+        adjusted_bars += u64::from(bar.provenance.is_adjusted());
+        synthetic_bars += u64::from(bar.provenance.synthetic);
+
         if !seen_timestamps.insert(bar.timestamp) {
             duplicate_timestamps += 1;
         } else {
@@ -53,7 +64,8 @@ pub fn summarize_bars(
     let sample_data = dataset_kind == DatasetKind::Sample;
     let critical_issue_count =
         duplicate_timestamps + invalid_ohlcv_rows + negative_price_rows + negative_volume_rows;
-    let warning_issue_count = missing_intervals + u64::from(sample_data);
+    let warning_issue_count =
+        missing_intervals + u64::from(sample_data) + u64::from(synthetic_bars > 0);
 
     let mut critical_issues = Vec::new();
     let mut warnings = Vec::new();
@@ -90,6 +102,12 @@ pub fn summarize_bars(
             symbol
         ));
     }
+    if synthetic_bars > 0 {
+        warnings.push(format!(
+            "Detected {} synthetic gap-fill bars for {}.",
+            synthetic_bars, symbol
+        ));
+    }
     if sample_data {
         warnings.push(
             "Synthetic sample/demo data loaded — use these results for product smoke tests, not research conclusions."
@@ -115,6 +133,10 @@ pub fn summarize_bars(
         sample_data,
         critical_issues,
         warnings,
+        providers: providers.into_iter().collect(),
+        resampled_bars,
+        adjusted_bars,
+        synthetic_bars,
     }
 }
 
@@ -141,6 +163,95 @@ fn count_missing_intervals(
     }
 }
 
+/// A run of consecutive expected bars with no data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataGap {
+    /// Expected timestamp of the first missing bar (midnight UTC for daily
+    /// data).
+    pub start: DateTime<Utc>,
+    /// Expected timestamp of the last missing bar.
+    pub end: DateTime<Utc>,
+    pub missing_bars: u64,
+}
+
+/// Runs of expected bars between `start` and `end` (inclusive) that `bars`
+/// lacks, on the calendar the missing-interval count uses: weekdays for daily
+/// data outside 24/7 markets and fixed steps anchored on the first bar for
+/// 24/7 assets. Other series have no expected calendar and report no gaps.
+pub fn find_gaps(
+    bars: &[Bar],
+    symbol: &Symbol,
+    resolution: Resolution,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Vec<DataGap> {
+    if start > end {
+        return Vec::new();
+    }
+    let midnight =
+        |timestamp: DateTime<Utc>| timestamp.date_naive().and_time(NaiveTime::MIN).and_utc();
+
+    let (slots, present): (Vec<DateTime<Utc>>, HashSet<DateTime<Utc>>) = match resolution {
+        Resolution::Day if !symbol.asset_class.is_24_7() => {
+            let slots = start
+                .date_naive()
+                .iter_days()
+                .take_while(|day| *day <= end.date_naive())
+                .filter(|day| !matches!(day.weekday(), Weekday::Sat | Weekday::Sun))
+                .map(|day| day.and_time(NaiveTime::MIN).and_utc())
+                .collect();
+            let present = bars.iter().map(|bar| midnight(bar.timestamp)).collect();
+            (slots, present)
+        }
+        _ if symbol.asset_class.is_24_7() => {
+            let Some(step) = resolution
+                .to_seconds()
+                .map(|seconds| chrono::Duration::seconds(seconds as i64))
+            else {
+                return Vec::new();
+            };
+            let anchor = bars
+                .iter()
+                .map(|bar| bar.timestamp)
+                .filter(|timestamp| *timestamp >= start && *timestamp <= end)
+                .min()
+                .unwrap_or(start);
+            let steps_back = (anchor - start).num_seconds() / step.num_seconds();
+            let mut slot = anchor - step * steps_back as i32;
+            let mut slots = Vec::new();
+            while slot <= end {
+                slots.push(slot);
+                slot += step;
+            }
+            let present = bars.iter().map(|bar| bar.timestamp).collect();
+            (slots, present)
+        }
+        _ => return Vec::new(),
+    };
+
+    let mut gaps: Vec<DataGap> = Vec::new();
+    let mut in_gap = false;
+    for slot in slots {
+        if present.contains(&slot) {
+            in_gap = false;
+            continue;
+        }
+        match gaps.last_mut() {
+            Some(gap) if in_gap => {
+                gap.end = slot;
+                gap.missing_bars += 1;
+            }
+            _ => gaps.push(DataGap {
+                start: slot,
+                end: slot,
+                missing_bars: 1,
+            }),
+        }
+        in_gap = true;
+    }
+    gaps
+}
+
 fn count_missing_weekdays(timestamps: &[DateTime<Utc>]) -> usize {
     timestamps
         .windows(2)
@@ -197,7 +308,7 @@ fn resolution_label(resolution: Resolution) -> &'static str {
 mod tests {
     use super::*;
     use chrono::TimeZone;
-    use gb_types::{AssetClass, PriceAdjustmentMode};
+    use gb_types::{AssetClass, BarProvenance, PriceAdjustmentMode};
 
     fn equity_symbol() -> Symbol {
         Symbol::new("AAPL", "NASDAQ", AssetClass::Equity)
@@ -233,6 +344,114 @@ mod tests {
         assert_eq!(summary.warning_issue_count, 1);
     }
 
+    #[test]
+    fn summary_reports_bar_provenance() {
+        let resampled = BarProvenance {
+            provider: Some("alpaca".to_string()),
+            resampled_from: Some(Resolution::Minute),
+            dividend_adjusted: true,
+            ..BarProvenance::default()
+        };
+        let gap_fill = BarProvenance {
+            provider: Some("csv".to_string()),
+            synthetic: true,
+            ..BarProvenance::default()
+        };
+        let bars = vec![
+            sample_bar(6).with_provenance(resampled),
+            sample_bar(7).with_provenance(gap_fill),
+            sample_bar(8),
+        ];
+        let summary = summarize_bars(
+            &bars,
+            &equity_symbol(),
+            Resolution::Day,
+            DatasetKind::UserProvided,
+            PriceAdjustmentMode::Raw,
+        );
+
+        assert_eq!(summary.providers, vec!["alpaca", "csv"]);
+        assert_eq!(summary.resampled_bars, 1);
+        assert_eq!(summary.adjusted_bars, 1);
+        assert_eq!(summary.synthetic_bars, 1);
+        assert_eq!(summary.warning_issue_count, 1);
+        assert!(summary.warnings[0].contains("1 synthetic gap-fill bars"));
+    }
+
+    #[test]
+    fn find_gaps_groups_missing_weekdays_including_range_edges() {
+        // Tue 7, Wed 8 and Mon 13 present; Mon 6, Thu 9, Fri 10 and Tue 14
+        // missing, with the weekend of the 11th not expected.
+        let bars = vec![sample_bar(7), sample_bar(8), sample_bar(13)];
+        let day = |day: u32| Utc.with_ymd_and_hms(2026, 4, day, 0, 0, 0).unwrap();
+        let gaps = find_gaps(&bars, &equity_symbol(), Resolution::Day, day(6), day(14));
+
+        assert_eq!(
+            gaps,
+            vec![
+                DataGap {
+                    start: day(6),
+                    end: day(6),
+                    missing_bars: 1,
+                },
+                DataGap {
+                    start: day(9),
+                    end: day(10),
+                    missing_bars: 2,
+                },
+                DataGap {
+                    start: day(14),
+                    end: day(14),
+                    missing_bars: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn find_gaps_steps_24_7_series_from_the_first_bar() {
+        let symbol = Symbol::new("BTC-USD", "COINBASE", AssetClass::Crypto);
+        let hour = |hour: u32| Utc.with_ymd_and_hms(2026, 4, 6, hour, 30, 0).unwrap();
+        let bars: Vec<Bar> = [1, 2, 5]
+            .into_iter()
+            .map(|h| {
+                let mut bar = sample_bar(6);
+                bar.symbol = symbol.clone();
+                bar.timestamp = hour(h);
+                bar
+            })
+            .collect();
+        let gaps = find_gaps(
+            &bars,
+            &symbol,
+            Resolution::Hour,
+            Utc.with_ymd_and_hms(2026, 4, 6, 0, 0, 0).unwrap(),
+            hour(6),
+        );
+
+        assert_eq!(
+            gaps,
+            vec![
+                DataGap {
+                    start: hour(0),
+                    end: hour(0),
+                    missing_bars: 1,
+                },
+                DataGap {
+                    start: hour(3),
+                    end: hour(4),
+                    missing_bars: 2,
+                },
+                DataGap {
+                    start: hour(6),
+                    end: hour(6),
+                    missing_bars: 1,
+                },
+            ]
+        );
+        assert!(find_gaps(&bars, &equity_symbol(), Resolution::Hour, hour(0), hour(6)).is_empty());
+    }
+
     #[test]
     fn summary_flags_duplicate_timestamps_as_critical() {
         let bars = vec![sample_bar(6), sample_bar(6)];
@@ -0,0 +1,401 @@
+//! Property tests for the CSV and Parquet loaders: arbitrary valid and
+//! malformed input never panics, every CSV row becomes a bar or a typed
+//! error, and loaded bars come back in time order with consistent OHLC.
+
+use std::sync::Arc;
+
+use arrow::array::{ArrayRef, Decimal128Array, Int64Array, StringArray, TimestampNanosecondArray};
+use arrow::datatypes::{DataType, Field, Schema, TimeUnit};
+use arrow::record_batch::RecordBatch;
+use gb_data::loaders::BatchLoader;
+use gb_types::{Bar, GbError, GbResult, Resolution, Symbol};
+use parquet::arrow::ArrowWriter;
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+fn runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap()
+}
+
+/// Prices as (open, high, low, close) with low <= open, close <= high.
+fn consistent_prices() -> impl Strategy<Value = [Decimal; 4]> {
+    (0..=10_000_000i64, 0..=500_000i64)
+        .prop_flat_map(|(low, width)| {
+            let high = low + width;
+            (Just(low), Just(high), low..=high, low..=high)
+        })
+        .prop_map(|(low, high, open, close)| [open, high, low, close].map(|p| Decimal::new(p, 4)))
+}
+
+fn check_bars(bars: &[Bar]) -> Result<(), String> {
+    if let Some(pair) = bars
+        .windows(2)
+        .find(|pair| pair[0].timestamp > pair[1].timestamp)
+    {
+        return Err(format!(
+            "bars out of order: {} before {}",
+            pair[0].timestamp, pair[1].timestamp
+        ));
+    }
+    check_ohlc(bars)
+}
+
+fn check_ohlc(bars: &[Bar]) -> Result<(), String> {
+    match bars.iter().find(|bar| {
+        bar.low > bar.high
+            || bar.open < bar.low
+            || bar.open > bar.high
+            || bar.close < bar.low
+            || bar.close > bar.high
+    }) {
+        Some(bar) => Err(format!("inconsistent OHLC: {bar:?}")),
+        None => Ok(()),
+    }
+}
+
+/// One CSV data row: the fields and whether it is a valid bar.
+#[derive(Debug, Clone)]
+struct CsvRow {
+    fields: Vec<String>,
+    valid: bool,
+}
+
+/// How a generated CSV row is broken, if at all.
+#[derive(Debug, Clone)]
+enum Mutation {
+    Garbage { column: usize, value: &'static str },
+    Truncate { column: usize },
+    HighBelowLow,
+    ExtraField,
+}
+
+const GARBAGE: &[&str] = &["", "abc", "NaN", "1e400", "-", "１２", "2023-13-45", "\"\""];
+
+fn mutation() -> impl Strategy<Value = Mutation> {
+    prop_oneof![
+        (0..6usize, prop::sample::select(GARBAGE))
+            .prop_map(|(column, value)| Mutation::Garbage { column, value }),
+        (0..6usize).prop_map(|column| Mutation::Truncate { column }),
+        Just(Mutation::HighBelowLow),
+        Just(Mutation::ExtraField),
+    ]
+}
+
+fn csv_row() -> impl Strategy<Value = CsvRow> {
+    (
+        (1..=12u32, 1..=28u32, 0..=23u32, 0..=59u32),
+        consistent_prices(),
+        0..=10_000_000i64,
+        prop::option::weighted(0.4, mutation()),
+    )
+        .prop_map(|((month, day, hour, minute), prices, volume, mutation)| {
+            let [open, high, low, close] = prices;
+            let mut fields = vec![
+                format!("2023-{month:02}-{day:02}T{hour:02}:{minute:02}:00Z"),
+                open.to_string(),
+                high.to_string(),
+                low.to_string(),
+                close.to_string(),
+                volume.to_string(),
+            ];
+            match mutation {
+                None => {
+                    return CsvRow {
+                        fields,
+                        valid: true,
+                    }
+                }
+                Some(Mutation::Garbage { column, value }) => fields[column] = value.to_string(),
+                Some(Mutation::Truncate { column }) => fields.truncate(column),
+                Some(Mutation::HighBelowLow) => {
+                    if high == low {
+                        fields[2] = (low - Decimal::ONE).to_string();
+                    } else {
+                        fields.swap(2, 3);
+                    }
+                }
+                Some(Mutation::ExtraField) => fields.push("extra".to_string()),
+            }
+            // Extra trailing fields and a single garbage field in the volume
+            // column can still leave a valid bar; the other mutations cannot.
+            let valid = CsvRow::parses(&fields);
+            CsvRow { fields, valid }
+        })
+}
+
+impl CsvRow {
+    /// Whether the first six fields still describe a bar.
+    fn parses(fields: &[String]) -> bool {
+        if fields.len() < 6 {
+            return false;
+        }
+        let numbers: Option<Vec<Decimal>> = fields[1..6].iter().map(|f| f.parse().ok()).collect();
+        let Some(numbers) = numbers else {
+            return false;
+        };
+        let (open, high, low, close) = (numbers[0], numbers[1], numbers[2], numbers[3]);
+        chrono::DateTime::parse_from_rfc3339(&fields[0]).is_ok()
+            && low <= high
+            && low <= open.min(close)
+            && high >= open.max(close)
+    }
+}
+
+fn csv_text(rows: &[CsvRow]) -> Vec<u8> {
+    let mut writer = csv::WriterBuilder::new()
+        .flexible(true)
+        .from_writer(Vec::new());
+    writer
+        .write_record(["timestamp", "open", "high", "low", "close", "volume"])
+        .unwrap();
+    for row in rows {
+        writer.write_record(&row.fields).unwrap();
+    }
+    writer.into_inner().unwrap()
+}
+
+/// Fail the case with `message`.
+fn fail(message: String) -> Result<(), TestCaseError> {
+    Err(TestCaseError::fail(message))
+}
+
+fn csv_rows_parse(rows: &[CsvRow]) -> Result<(), String> {
+    let loader = BatchLoader::new();
+    let symbol = Symbol::equity("AAPL");
+    let text = csv_text(rows);
+    let parsed = loader
+        .parse_csv_rows(text.as_slice(), &symbol, Resolution::Minute, true)
+        .map_err(|e| format!("CSV reader failed: {e}"))?;
+    if parsed.len() != rows.len() {
+        return Err(format!("{} results for {} rows", parsed.len(), rows.len()));
+    }
+    for (row, result) in rows.iter().zip(&parsed) {
+        match (row.valid, result) {
+            (true, Ok(_)) | (false, Err(GbError::Data(_))) => {}
+            (_, result) => return Err(format!("{row:?} parsed as {result:?}")),
+        }
+    }
+    let parsed_bars: Vec<Bar> = parsed.into_iter().filter_map(GbResult::ok).collect();
+    check_ohlc(&parsed_bars)?;
+
+    let file = tempfile::NamedTempFile::new().unwrap();
+    std::fs::write(file.path(), &text).unwrap();
+    let bars = runtime()
+        .block_on(loader.load_csv_file(file.path(), &symbol, Resolution::Minute, true))
+        .map_err(|e| format!("load failed: {e}"))?;
+    if bars.len() != parsed_bars.len() {
+        return Err(format!(
+            "loaded {} bars from {} valid rows",
+            bars.len(),
+            parsed_bars.len()
+        ));
+    }
+    check_bars(&bars)
+}
+
+fn csv_bytes_parse(input: &[u8]) -> Result<(), String> {
+    let loader = BatchLoader::new();
+    let symbol = Symbol::equity("AAPL");
+    for has_headers in [true, false] {
+        if let Ok(rows) = loader.parse_csv_rows(input, &symbol, Resolution::Day, has_headers) {
+            let bars: Vec<Bar> = rows.into_iter().filter_map(GbResult::ok).collect();
+            check_ohlc(&bars)?;
+        }
+    }
+    Ok(())
+}
+
+/// One Parquet row in the loader's column layout; `None` prices are nulls.
+#[derive(Debug, Clone)]
+struct ParquetRow {
+    timestamp_nanos: i64,
+    prices: [Option<Decimal>; 4],
+    volume: i64,
+}
+
+#[derive(Debug, Clone)]
+struct ParquetCase {
+    rows: Vec<ParquetRow>,
+    /// Columns kept from the front of the layout.
+    columns: usize,
+    /// Bytes cut from the end of the written file.
+    truncate: usize,
+}
+
+fn parquet_row() -> impl Strategy<Value = ParquetRow> {
+    (
+        // 2000-01-01 to 2030-01-01.
+        946_684_800..=1_893_456_000i64,
+        consistent_prices(),
+        // Swap high and low.
+        prop::bool::weighted(0.1),
+        prop::option::weighted(0.1, 0..4usize),
+        0..=10_000_000i64,
+    )
+        .prop_map(|(seconds, prices, swap, null, volume)| {
+            let mut prices = prices.map(Some);
+            if swap {
+                prices.swap(1, 2);
+            }
+            if let Some(column) = null {
+                prices[column] = None;
+            }
+            ParquetRow {
+                timestamp_nanos: seconds * 1_000_000_000,
+                prices,
+                volume,
+            }
+        })
+}
+
+fn parquet_case() -> impl Strategy<Value = ParquetCase> {
+    (
+        prop::collection::vec(parquet_row(), 0..50),
+        prop::option::weighted(0.1, 0..7usize),
+        prop::option::weighted(0.1, 1..=64usize),
+    )
+        .prop_map(|(rows, columns, truncate)| ParquetCase {
+            rows,
+            columns: columns.unwrap_or(7),
+            truncate: truncate.unwrap_or(0),
+        })
+}
+
+fn write_parquet(case: &ParquetCase, path: &std::path::Path) {
+    let price = |index: usize| -> ArrayRef {
+        let values: Vec<Option<i128>> = case
+            .rows
+            .iter()
+            .map(|row| row.prices[index].map(|p| p.mantissa() * 10_i128.pow(4 - p.scale())))
+            .collect();
+        Arc::new(
+            Decimal128Array::from(values)
+                .with_precision_and_scale(18, 4)
+                .unwrap(),
+        )
+    };
+    let columns: Vec<(Field, ArrayRef)> = vec![
+        (
+            Field::new("symbol", DataType::Utf8, false),
+            Arc::new(StringArray::from(vec!["AAPL"; case.rows.len()])),
+        ),
+        (
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into())),
+                false,
+            ),
+            Arc::new(
+                TimestampNanosecondArray::from(
+                    case.rows
+                        .iter()
+                        .map(|row| row.timestamp_nanos)
+                        .collect::<Vec<_>>(),
+                )
+                .with_timezone("UTC"),
+            ),
+        ),
+        (
+            Field::new("open", DataType::Decimal128(18, 4), true),
+            price(0),
+        ),
+        (
+            Field::new("high", DataType::Decimal128(18, 4), true),
+            price(1),
+        ),
+        (
+            Field::new("low", DataType::Decimal128(18, 4), true),
+            price(2),
+        ),
+        (
+            Field::new("close", DataType::Decimal128(18, 4), true),
+            price(3),
+        ),
+        (
+            Field::new("volume", DataType::Int64, false),
+            Arc::new(Int64Array::from(
+                case.rows.iter().map(|row| row.volume).collect::<Vec<_>>(),
+            )),
+        ),
+    ];
+    let (fields, arrays): (Vec<Field>, Vec<ArrayRef>) =
+        columns.into_iter().take(case.columns.max(1)).unzip();
+    let batch = RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays).unwrap();
+
+    let mut writer =
+        ArrowWriter::try_new(std::fs::File::create(path).unwrap(), batch.schema(), None).unwrap();
+    writer.write(&batch).unwrap();
+    writer.close().unwrap();
+
+    if case.truncate > 0 {
+        let bytes = std::fs::read(path).unwrap();
+        let kept = bytes.len().saturating_sub(case.truncate);
+        std::fs::write(path, &bytes[..kept]).unwrap();
+    }
+}
+
+fn parquet_file_loads(case: &ParquetCase) -> Result<(), String> {
+    let loader = BatchLoader::new();
+    let symbol = Symbol::equity("AAPL");
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("AAPL.parquet");
+    write_parquet(case, &path);
+
+    let loaded = runtime().block_on(loader.load_parquet_file(&path, &symbol, Resolution::Day));
+    // Missing columns only show once there is a batch to read.
+    let malformed = (case.columns < 7 && !case.rows.is_empty()) || case.truncate > 0;
+    let bars = match loaded {
+        Ok(bars) if !malformed => bars,
+        Err(GbError::Data(_)) if malformed => return Ok(()),
+        Ok(bars) => return Err(format!("malformed file loaded {} bars", bars.len())),
+        Err(e) => return Err(format!("well-formed file failed to load: {e:?}")),
+    };
+    if case.columns < 7 {
+        return Ok(());
+    }
+    let expected = case
+        .rows
+        .iter()
+        .filter(|row| match row.prices {
+            [Some(open), Some(high), Some(low), Some(close)] => {
+                low <= high && low <= open.min(close) && high >= open.max(close)
+            }
+            _ => false,
+        })
+        .count();
+    if bars.len() != expected {
+        return Err(format!(
+            "loaded {} bars from {expected} valid rows",
+            bars.len()
+        ));
+    }
+    check_bars(&bars)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn every_csv_row_is_a_bar_or_a_typed_error(
+        rows in prop::collection::vec(csv_row(), 0..40),
+    ) {
+        csv_rows_parse(&rows).or_else(fail)?;
+    }
+
+    #[test]
+    fn arbitrary_bytes_never_panic_the_csv_loader(
+        input in prop::collection::vec(
+            prop::sample::select(&b"0123456789-:.,\"\n\r TZe\xff\xfe"[..]),
+            0..200,
+        ),
+    ) {
+        csv_bytes_parse(&input).or_else(fail)?;
+    }
+
+    #[test]
+    fn parquet_files_load_sorted_consistent_bars_or_a_typed_error(case in parquet_case()) {
+        parquet_file_loads(&case).or_else(fail)?;
+    }
+}
@@ -0,0 +1,90 @@
+//! Slicing of [`ExecutionAlgo`] parent orders across bars.
+//!
+//! An algo order's window starts at the first bar it could execute on. On
+//! each bar of the window [`slice_quantity`] decides how much of the
+//! remaining quantity to work; the engine fills that slice at the bar's
+//! close under the usual participation limit. Whatever is left when the
+//! window ends is worked on the following bars until the order fills.
+
+use gb_types::{Bar, ExecutionAlgo};
+use rust_decimal::Decimal;
+
+/// Quantity of `remaining` that `algo` works on the last bar of `window`,
+/// the bars of its window so far.
+///
+/// TWAP splits the remainder evenly over the bars left. VWAP weights the
+/// current bar by its volume against a forecast for the bars left, taken as
+/// the average volume seen in the window so far, and caps the slice at
+/// `participation_cap` of the bar's volume.
+pub fn slice_quantity(algo: &ExecutionAlgo, remaining: Decimal, window: &[Bar]) -> Decimal {
+    let Some(bar) = window.last() else {
+        return Decimal::ZERO;
+    };
+    let bars = match algo {
+        ExecutionAlgo::Twap { bars } | ExecutionAlgo::Vwap { bars, .. } => *bars as usize,
+    };
+    let bars_left = bars.saturating_sub(window.len() - 1).max(1);
+    match algo {
+        ExecutionAlgo::Twap { .. } => remaining / Decimal::from(bars_left),
+        ExecutionAlgo::Vwap {
+            participation_cap, ..
+        } => {
+            let volume = bar.volume.max(Decimal::ZERO);
+            let seen: Decimal = window.iter().map(|bar| bar.volume.max(Decimal::ZERO)).sum();
+            let average = seen / Decimal::from(window.len());
+            let forecast = volume + average * Decimal::from(bars_left - 1);
+            let weighted = if forecast.is_zero() {
+                remaining / Decimal::from(bars_left)
+            } else {
+                remaining * volume / forecast
+            };
+            weighted.min(volume * *participation_cap)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use gb_types::{Resolution, Symbol};
+    use rust_decimal_macros::dec;
+
+    fn bar(volume: Decimal) -> Bar {
+        Bar::new(
+            Symbol::equity("AAPL"),
+            Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap(),
+            dec!(100),
+            dec!(100),
+            dec!(100),
+            dec!(100),
+            volume,
+            Resolution::Day,
+        )
+    }
+
+    #[test]
+    fn vwap_leans_into_heavy_bars_under_its_cap() {
+        let vwap = ExecutionAlgo::Vwap {
+            bars: 3,
+            participation_cap: dec!(0.1),
+        };
+        // Nothing seen yet: the first bar is its own forecast, a third.
+        let first = [bar(dec!(6_000))];
+        assert_eq!(slice_quantity(&vwap, dec!(900), &first), dec!(300));
+
+        // A bar above the average so far takes more than an even split:
+        // 18k of a forecast 18k + 12k.
+        let second = [bar(dec!(6_000)), bar(dec!(18_000))];
+        assert_eq!(slice_quantity(&vwap, dec!(500), &second), dec!(300));
+
+        // Capped at 10% of a thin bar, and everything on the last bar.
+        let thin = [bar(dec!(6_000)), bar(dec!(1_000))];
+        assert_eq!(slice_quantity(&vwap, dec!(600), &thin), dec!(100));
+        let last = [bar(dec!(6_000)), bar(dec!(18_000)), bar(dec!(9_000))];
+        assert_eq!(slice_quantity(&vwap, dec!(200), &last), dec!(200));
+
+        let twap = ExecutionAlgo::Twap { bars: 3 };
+        assert_eq!(slice_quantity(&twap, dec!(600), &second), dec!(300));
+    }
+}
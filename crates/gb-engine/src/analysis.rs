@@ -0,0 +1,909 @@
+//! Side-by-side comparison of two backtest results, to answer "what changed"
+//! after tweaking a strategy.
+//!
+//! [`compare`] diffs a baseline against a candidate: headline metric deltas,
+//! the gap between their equity curves day by day, trades only one of them
+//! made, and the config fields that differ. The [`ComparisonReport`]
+//! serializes to JSON and renders to Markdown with
+//! [`ComparisonReport::to_markdown`].
+//!
+//! [`calendar_profile`] answers "when does it make money": returns and hit
+//! rates by month, weekday and, for intraday runs, hour of the exchange's
+//! day, with closed trades counted in the same buckets.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, Timelike, Utc};
+use gb_types::{
+    BacktestId, BacktestResult, EquityCurvePoint, ExchangeTimezone, PerformanceMetrics, Resolution,
+    TradeRecord,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How far apart two trades' entry times may be and still count as the same
+/// trade, used by [`compare`].
+pub const DEFAULT_TRADE_MATCH_TOLERANCE: Duration = Duration::minutes(1);
+
+/// Config fields left out of the diff: they identify a run rather than
+/// describe it.
+const IGNORED_CONFIG_FIELDS: [&str; 2] = ["id", "created_at"];
+
+/// Differences between a baseline result and a candidate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComparisonReport {
+    pub baseline_id: BacktestId,
+    pub candidate_id: BacktestId,
+    pub metrics: Vec<MetricDelta>,
+    /// Cumulative returns on the dates both equity curves cover.
+    pub tracking: Vec<TrackingPoint>,
+    pub trades: TradeDiff,
+    pub config: Vec<ConfigDifference>,
+}
+
+/// One headline metric in both runs; `delta` is candidate minus baseline,
+/// when both have a value.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricDelta {
+    pub metric: String,
+    pub baseline: Option<Decimal>,
+    pub candidate: Option<Decimal>,
+    pub delta: Option<Decimal>,
+}
+
+/// Cumulative returns at the close of one date; `difference` is candidate
+/// minus baseline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrackingPoint {
+    pub date: NaiveDate,
+    pub baseline_return: Decimal,
+    pub candidate_return: Decimal,
+    pub difference: Decimal,
+}
+
+/// Trades matched by symbol, side and entry time, and those left over.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeDiff {
+    pub matched: usize,
+    pub only_in_baseline: Vec<TradeRecord>,
+    pub only_in_candidate: Vec<TradeRecord>,
+}
+
+/// A config field, as a dotted path, whose value differs between the runs.
+/// A field missing from one side is `null` there.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfigDifference {
+    pub field: String,
+    pub baseline: Value,
+    pub candidate: Value,
+}
+
+impl ComparisonReport {
+    /// True when the runs have the same metrics, equity curves, trades and
+    /// config.
+    pub fn is_identical(&self) -> bool {
+        self.metrics
+            .iter()
+            .all(|metric| metric.baseline == metric.candidate)
+            && self.tracking.iter().all(|point| point.difference.is_zero())
+            && self.trades.only_in_baseline.is_empty()
+            && self.trades.only_in_candidate.is_empty()
+            && self.config.is_empty()
+    }
+
+    /// The largest absolute gap between the cumulative returns.
+    pub fn max_tracking_difference(&self) -> Decimal {
+        self.tracking
+            .iter()
+            .map(|point| point.difference.abs())
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Render the report as Markdown tables.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# Backtest comparison\n");
+        let _ = writeln!(
+            out,
+            "Baseline `{}`, candidate `{}`.\n",
+            self.baseline_id, self.candidate_id
+        );
+
+        let _ = writeln!(out, "## Metrics\n");
+        let _ = writeln!(out, "| Metric | Baseline | Candidate | Delta |");
+        let _ = writeln!(out, "| --- | ---: | ---: | ---: |");
+        for metric in &self.metrics {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                metric.metric,
+                format_value(metric.baseline),
+                format_value(metric.candidate),
+                format_value(metric.delta)
+            );
+        }
+
+        let _ = writeln!(out, "\n## Equity tracking\n");
+        match (self.tracking.first(), self.tracking.last()) {
+            (Some(first), Some(last)) => {
+                let _ = writeln!(
+                    out,
+                    "{} common dates from {} to {}; final difference {}, largest {}.",
+                    self.tracking.len(),
+                    first.date,
+                    last.date,
+                    last.difference.round_dp(6).normalize(),
+                    self.max_tracking_difference().round_dp(6).normalize()
+                );
+            }
+            _ => {
+                let _ = writeln!(out, "The equity curves share no dates.");
+            }
+        }
+
+        let _ = writeln!(out, "\n## Trades\n");
+        let _ = writeln!(
+            out,
+            "{} matched, {} only in baseline, {} only in candidate.",
+            self.trades.matched,
+            self.trades.only_in_baseline.len(),
+            self.trades.only_in_candidate.len()
+        );
+        let unmatched = self
+            .trades
+            .only_in_baseline
+            .iter()
+            .map(|trade| ("baseline", trade))
+            .chain(
+                self.trades
+                    .only_in_candidate
+                    .iter()
+                    .map(|trade| ("candidate", trade)),
+            )
+            .collect::<Vec<_>>();
+        if !unmatched.is_empty() {
+            let _ = writeln!(out, "\n| Run | Symbol | Side | Entry | Quantity | Price |");
+            let _ = writeln!(out, "| --- | --- | --- | --- | ---: | ---: |");
+            for (run, trade) in unmatched {
+                let _ = writeln!(
+                    out,
+                    "| {run} | {} | {:?} | {} | {} | {} |",
+                    trade.symbol,
+                    trade.side,
+                    trade.entry_time.to_rfc3339(),
+                    trade.quantity.normalize(),
+                    trade.entry_price.normalize()
+                );
+            }
+        }
+
+        let _ = writeln!(out, "\n## Config\n");
+        if self.config.is_empty() {
+            let _ = writeln!(out, "The configs are the same.");
+        } else {
+            let _ = writeln!(out, "| Field | Baseline | Candidate |");
+            let _ = writeln!(out, "| --- | --- | --- |");
+            for difference in &self.config {
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {} | {} |",
+                    difference.field, difference.baseline, difference.candidate
+                );
+            }
+        }
+        out
+    }
+}
+
+/// Compare `candidate` against `baseline`, matching trades whose entry times
+/// are within [`DEFAULT_TRADE_MATCH_TOLERANCE`].
+pub fn compare(baseline: &BacktestResult, candidate: &BacktestResult) -> ComparisonReport {
+    compare_with_tolerance(baseline, candidate, DEFAULT_TRADE_MATCH_TOLERANCE)
+}
+
+/// [`compare`], matching trades whose entry times are within `tolerance`.
+pub fn compare_with_tolerance(
+    baseline: &BacktestResult,
+    candidate: &BacktestResult,
+    tolerance: Duration,
+) -> ComparisonReport {
+    ComparisonReport {
+        baseline_id: baseline.id,
+        candidate_id: candidate.id,
+        metrics: metric_deltas(baseline, candidate),
+        tracking: tracking(&baseline.equity_curve, &candidate.equity_curve),
+        trades: diff_trades(&baseline.trade_log, &candidate.trade_log, tolerance),
+        config: diff_configs(baseline, candidate),
+    }
+}
+
+fn metric_deltas(baseline: &BacktestResult, candidate: &BacktestResult) -> Vec<MetricDelta> {
+    type Metric = fn(&PerformanceMetrics) -> Option<Decimal>;
+    let performance: [(&str, Metric); 9] = [
+        ("total_return", |m| Some(m.total_return)),
+        ("annualized_return", |m| Some(m.annualized_return)),
+        ("volatility", |m| Some(m.volatility)),
+        ("sharpe_ratio", |m| m.sharpe_ratio),
+        ("sortino_ratio", |m| m.sortino_ratio),
+        ("max_drawdown", |m| Some(m.max_drawdown)),
+        ("win_rate", |m| Some(m.win_rate)),
+        ("total_trades", |m| Some(Decimal::from(m.total_trades))),
+        ("total_commissions", |m| Some(m.total_commissions)),
+    ];
+    let mut deltas: Vec<MetricDelta> = performance
+        .into_iter()
+        .map(|(name, metric)| {
+            delta(
+                name,
+                baseline.performance_metrics.as_ref().and_then(metric),
+                candidate.performance_metrics.as_ref().and_then(metric),
+            )
+        })
+        .collect();
+    deltas.push(delta(
+        "logged_trades",
+        Some(Decimal::from(baseline.trade_log.len())),
+        Some(Decimal::from(candidate.trade_log.len())),
+    ));
+    deltas
+}
+
+fn delta(metric: &str, baseline: Option<Decimal>, candidate: Option<Decimal>) -> MetricDelta {
+    MetricDelta {
+        metric: metric.to_string(),
+        baseline,
+        candidate,
+        delta: baseline.zip(candidate).map(|(b, c)| c - b),
+    }
+}
+
+/// Cumulative return at the last point of each date.
+fn closing_returns(curve: &[EquityCurvePoint]) -> BTreeMap<NaiveDate, Decimal> {
+    curve
+        .iter()
+        .map(|point| (point.timestamp.date_naive(), point.cumulative_return))
+        .collect()
+}
+
+fn tracking(baseline: &[EquityCurvePoint], candidate: &[EquityCurvePoint]) -> Vec<TrackingPoint> {
+    let candidate = closing_returns(candidate);
+    closing_returns(baseline)
+        .into_iter()
+        .filter_map(|(date, baseline_return)| {
+            let candidate_return = *candidate.get(&date)?;
+            Some(TrackingPoint {
+                date,
+                baseline_return,
+                candidate_return,
+                difference: candidate_return - baseline_return,
+            })
+        })
+        .collect()
+}
+
+/// Pair each candidate trade with the nearest unpaired baseline trade in the
+/// same symbol and side, within `tolerance`.
+fn diff_trades(
+    baseline: &[TradeRecord],
+    candidate: &[TradeRecord],
+    tolerance: Duration,
+) -> TradeDiff {
+    let mut paired = vec![false; baseline.len()];
+    let mut only_in_candidate = Vec::new();
+    for trade in candidate {
+        let nearest = baseline
+            .iter()
+            .enumerate()
+            .filter(|(index, other)| {
+                !paired[*index]
+                    && other.symbol == trade.symbol
+                    && other.side == trade.side
+                    && (other.entry_time - trade.entry_time).abs() <= tolerance
+            })
+            .min_by_key(|(_, other)| (other.entry_time - trade.entry_time).abs())
+            .map(|(index, _)| index);
+        match nearest {
+            Some(index) => paired[index] = true,
+            None => only_in_candidate.push(trade.clone()),
+        }
+    }
+    TradeDiff {
+        matched: paired.iter().filter(|paired| **paired).count(),
+        only_in_baseline: baseline
+            .iter()
+            .zip(&paired)
+            .filter(|(_, paired)| !**paired)
+            .map(|(trade, _)| trade.clone())
+            .collect(),
+        only_in_candidate,
+    }
+}
+
+fn diff_configs(baseline: &BacktestResult, candidate: &BacktestResult) -> Vec<ConfigDifference> {
+    let mut flat_baseline = BTreeMap::new();
+    let mut flat_candidate = BTreeMap::new();
+    flatten(
+        "",
+        &serde_json::to_value(&baseline.config).unwrap_or_default(),
+        &mut flat_baseline,
+    );
+    flatten(
+        "",
+        &serde_json::to_value(&candidate.config).unwrap_or_default(),
+        &mut flat_candidate,
+    );
+    for field in IGNORED_CONFIG_FIELDS {
+        flat_baseline.remove(field);
+        flat_candidate.remove(field);
+    }
+
+    let mut fields: Vec<&String> = flat_baseline.keys().chain(flat_candidate.keys()).collect();
+    fields.sort();
+    fields.dedup();
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let baseline = flat_baseline.get(field).cloned().unwrap_or_default();
+            let candidate = flat_candidate.get(field).cloned().unwrap_or_default();
+            (baseline != candidate).then(|| ConfigDifference {
+                field: field.clone(),
+                baseline,
+                candidate,
+            })
+        })
+        .collect()
+}
+
+/// Leaves of `value` keyed by dotted path, with array elements as `[i]`.
+fn flatten(path: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(fields) if !fields.is_empty() => {
+            for (key, value) in fields {
+                let path = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                flatten(&path, value, out);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            for (index, item) in items.iter().enumerate() {
+                flatten(&format!("{path}[{index}]"), item, out);
+            }
+        }
+        // An empty container reads the same as a missing field.
+        Value::Object(_) | Value::Array(_) => {}
+        leaf => {
+            out.insert(path.to_string(), leaf.clone());
+        }
+    }
+}
+
+fn format_value(value: Option<Decimal>) -> String {
+    value.map_or_else(
+        || "-".to_string(),
+        |value| value.round_dp(6).normalize().to_string(),
+    )
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Returns and closed trades of one run bucketed by calendar position.
+///
+/// Daily, weekly and monthly bars are stamped with the date they cover, so
+/// those runs are bucketed by the UTC date of each point. Intraday runs are
+/// bucketed on the wall clock of `timezone`, the exchange of the run's
+/// first symbol; trades use their own symbol's exchange.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarProfile {
+    pub timezone: ExchangeTimezone,
+    /// Monthly returns grouped by calendar month, January first.
+    pub by_month: Vec<CalendarBucket>,
+    /// Daily returns grouped by weekday, Monday first.
+    pub by_weekday: Vec<CalendarBucket>,
+    /// Bar returns grouped by the hour they end in, midnight first; empty
+    /// for daily and coarser runs.
+    pub by_hour: Vec<CalendarBucket>,
+    pub day_of_month: DayOfMonthHeatmap,
+}
+
+/// Returns falling in one calendar bucket. Every bucket is present, with
+/// `None` statistics when no period fell in it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CalendarBucket {
+    /// Month 1–12, weekday 0–6 from Monday, or hour 0–23.
+    pub key: u32,
+    pub label: String,
+    pub periods: usize,
+    pub mean_return: Option<Decimal>,
+    pub median_return: Option<Decimal>,
+    /// Share of periods with a positive return.
+    pub hit_rate: Option<Decimal>,
+    /// Trades closed in this bucket, and how many made money.
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub trade_pnl: Decimal,
+}
+
+/// Daily returns laid out by month and day of month, for a heatmap.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct DayOfMonthHeatmap {
+    /// One row per month the run covers, oldest first.
+    pub rows: Vec<HeatmapRow>,
+    /// Sum of each day of month's returns over all rows; index 0 is the 1st.
+    pub totals: Vec<Decimal>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HeatmapRow {
+    pub year: i32,
+    pub month: u32,
+    /// The return of each day of the month; index 0 is the 1st, and days
+    /// without a return are `None`.
+    pub days: Vec<Option<Decimal>>,
+}
+
+/// Calendar profile of `result`, on the clock of its first symbol's
+/// exchange.
+pub fn calendar_profile(result: &BacktestResult) -> CalendarProfile {
+    let timezone = result
+        .config
+        .symbols
+        .first()
+        .map(|symbol| symbol.timezone())
+        .unwrap_or_default();
+    calendar_profile_in(result, timezone)
+}
+
+/// Calendar profile of `result` with its equity curve read on `timezone`'s
+/// clock.
+pub fn calendar_profile_in(result: &BacktestResult, timezone: ExchangeTimezone) -> CalendarProfile {
+    let intraday = !matches!(
+        result.config.resolution,
+        Resolution::Day | Resolution::Week | Resolution::Month
+    );
+    let clock = |at: DateTime<Utc>, timezone: ExchangeTimezone| -> NaiveDateTime {
+        if intraday {
+            timezone.to_local(at).naive_local()
+        } else {
+            at.naive_utc()
+        }
+    };
+
+    // Step returns, compounded into days and months.
+    let steps: Vec<(NaiveDateTime, Decimal)> = result
+        .equity_curve
+        .iter()
+        .filter_map(|point| Some((clock(point.timestamp, timezone), point.daily_return?)))
+        .collect();
+    let mut days: BTreeMap<NaiveDate, Decimal> = BTreeMap::new();
+    for (at, step) in &steps {
+        let growth = days.entry(at.date()).or_insert(Decimal::ONE);
+        *growth *= Decimal::ONE + step;
+    }
+    let days: BTreeMap<NaiveDate, Decimal> = days
+        .into_iter()
+        .map(|(day, growth)| (day, growth - Decimal::ONE))
+        .collect();
+    let mut months: BTreeMap<(i32, u32), Decimal> = BTreeMap::new();
+    for (day, daily) in &days {
+        let growth = months
+            .entry((day.year(), day.month()))
+            .or_insert(Decimal::ONE);
+        *growth *= Decimal::ONE + daily;
+    }
+
+    let trades: Vec<(NaiveDateTime, Decimal)> = result
+        .trade_log
+        .iter()
+        .filter_map(|trade| {
+            let closed = clock(trade.exit_time?, trade.symbol.timezone());
+            Some((closed, trade.pnl?))
+        })
+        .collect();
+
+    let by_month = buckets(
+        &MONTHS,
+        1,
+        months
+            .iter()
+            .map(|(&(_, month), growth)| (month, growth - Decimal::ONE)),
+        trades.iter().map(|(at, pnl)| (at.month(), *pnl)),
+    );
+    let by_weekday = buckets(
+        &WEEKDAYS,
+        0,
+        days.iter()
+            .map(|(day, daily)| (day.weekday().num_days_from_monday(), *daily)),
+        trades
+            .iter()
+            .map(|(at, pnl)| (at.weekday().num_days_from_monday(), *pnl)),
+    );
+    let by_hour = if intraday {
+        let labels: Vec<String> = (0..24).map(|hour| format!("{hour:02}:00")).collect();
+        let labels: Vec<&str> = labels.iter().map(String::as_str).collect();
+        buckets(
+            &labels,
+            0,
+            steps.iter().map(|(at, step)| (at.hour(), *step)),
+            trades.iter().map(|(at, pnl)| (at.hour(), *pnl)),
+        )
+    } else {
+        Vec::new()
+    };
+
+    CalendarProfile {
+        timezone,
+        by_month,
+        by_weekday,
+        by_hour,
+        day_of_month: heatmap(&days),
+    }
+}
+
+/// One bucket per label, keyed from `first_key`, holding the `returns` and
+/// trade P&Ls whose keys match.
+fn buckets(
+    labels: &[&str],
+    first_key: u32,
+    returns: impl Iterator<Item = (u32, Decimal)>,
+    trades: impl Iterator<Item = (u32, Decimal)>,
+) -> Vec<CalendarBucket> {
+    let mut grouped: Vec<Vec<Decimal>> = vec![Vec::new(); labels.len()];
+    for (key, value) in returns {
+        grouped[(key - first_key) as usize].push(value);
+    }
+    let mut buckets: Vec<CalendarBucket> = labels
+        .iter()
+        .zip(grouped)
+        .enumerate()
+        .map(|(index, (label, mut values))| {
+            values.sort();
+            let count = Decimal::from(values.len());
+            let (mean_return, median_return, hit_rate) = if values.is_empty() {
+                (None, None, None)
+            } else {
+                let middle = values.len() / 2;
+                let median = if values.len().is_multiple_of(2) {
+                    (values[middle - 1] + values[middle]) / Decimal::TWO
+                } else {
+                    values[middle]
+                };
+                let hits = values
+                    .iter()
+                    .filter(|value| **value > Decimal::ZERO)
+                    .count();
+                (
+                    Some(values.iter().sum::<Decimal>() / count),
+                    Some(median),
+                    Some(Decimal::from(hits) / count),
+                )
+            };
+            CalendarBucket {
+                key: first_key + index as u32,
+                label: label.to_string(),
+                periods: values.len(),
+                mean_return,
+                median_return,
+                hit_rate,
+                trades: 0,
+                winning_trades: 0,
+                trade_pnl: Decimal::ZERO,
+            }
+        })
+        .collect();
+    for (key, pnl) in trades {
+        let bucket = &mut buckets[(key - first_key) as usize];
+        bucket.trades += 1;
+        bucket.winning_trades += usize::from(pnl > Decimal::ZERO);
+        bucket.trade_pnl += pnl;
+    }
+    buckets
+}
+
+fn heatmap(days: &BTreeMap<NaiveDate, Decimal>) -> DayOfMonthHeatmap {
+    let mut rows: Vec<HeatmapRow> = Vec::new();
+    let mut totals = vec![Decimal::ZERO; 31];
+    for (day, daily) in days {
+        let (year, month) = (day.year(), day.month());
+        if rows
+            .last()
+            .is_none_or(|row| (row.year, row.month) != (year, month))
+        {
+            rows.push(HeatmapRow {
+                year,
+                month,
+                days: vec![None; 31],
+            });
+        }
+        let index = day.day0() as usize;
+        rows.last_mut().expect("pushed above").days[index] = Some(*daily);
+        totals[index] += daily;
+    }
+    DayOfMonthHeatmap { rows, totals }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use gb_types::{Portfolio, Side, StrategyConfig, StrategyMetrics, Symbol};
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn trade(symbol: &str, day: u32, price: Decimal) -> TradeRecord {
+        TradeRecord {
+            id: Uuid::new_v4(),
+            symbol: Symbol::equity(symbol),
+            entry_time: Utc.with_ymd_and_hms(2024, 1, day, 14, 30, 0).unwrap(),
+            exit_time: None,
+            entry_price: price,
+            exit_price: None,
+            quantity: dec!(10),
+            side: Side::Buy,
+            pnl: None,
+            commission: dec!(1),
+            duration_hours: None,
+            strategy_id: "ma_crossover".into(),
+            tags: Vec::new(),
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    fn sample_result() -> BacktestResult {
+        let strategy = StrategyConfig::new("ma_crossover".into(), "MA crossover".into());
+        let mut result =
+            BacktestResult::new(gb_types::BacktestConfig::new("baseline".into(), strategy));
+        result.mark_completed(
+            Portfolio::new("compare".into(), dec!(100000)),
+            StrategyMetrics::new("ma_crossover".into()),
+        );
+        result.equity_curve = (1..=5)
+            .map(|day| EquityCurvePoint {
+                timestamp: Utc.with_ymd_and_hms(2024, 1, day, 21, 0, 0).unwrap(),
+                portfolio_value: dec!(100000) + Decimal::from(day) * dec!(100),
+                cash: dec!(90000),
+                positions_value: dec!(10000) + Decimal::from(day) * dec!(100),
+                total_pnl: Decimal::from(day) * dec!(100),
+                daily_return: None,
+                cumulative_return: Decimal::from(day) * dec!(0.001),
+                drawdown: Decimal::ZERO,
+                stale_symbols: Vec::new(),
+                cash_flow: Decimal::ZERO,
+            })
+            .collect();
+        result.trade_log = vec![trade("AAPL", 2, dec!(180)), trade("MSFT", 3, dec!(400))];
+        result
+    }
+
+    #[test]
+    fn a_run_compared_with_itself_has_no_differences() {
+        let result = sample_result();
+        let report = compare(&result, &result);
+
+        assert!(report.is_identical());
+        assert!(report
+            .metrics
+            .iter()
+            .all(|metric| metric.delta.is_none_or(|delta| delta.is_zero())));
+        assert_eq!(report.tracking.len(), 5);
+        assert_eq!(report.max_tracking_difference(), Decimal::ZERO);
+        assert_eq!(report.trades.matched, 2);
+        assert!(report.config.is_empty());
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ComparisonReport>(&json).unwrap(),
+            report
+        );
+        assert!(report.to_markdown().contains("The configs are the same."));
+    }
+
+    #[test]
+    fn a_perturbed_copy_shows_the_extra_trade_and_changes() {
+        let baseline = sample_result();
+        let mut candidate = baseline.clone();
+        candidate.id = Uuid::new_v4();
+        candidate.config.id = Uuid::new_v4();
+        candidate
+            .config
+            .strategy_config
+            .parameters
+            .insert("short_period".into(), serde_json::json!(7));
+        candidate.config.initial_capital = dec!(150000);
+        // Same trade as the baseline's, filled 30 seconds later.
+        candidate.trade_log[0].entry_time += Duration::seconds(30);
+        candidate.trade_log.push(trade("NVDA", 4, dec!(500)));
+        candidate.equity_curve[4].cumulative_return += dec!(0.002);
+        candidate.performance_metrics.as_mut().unwrap().total_trades += 1;
+
+        let report = compare(&baseline, &candidate);
+
+        assert!(!report.is_identical());
+        assert_eq!(report.trades.matched, 2);
+        assert!(report.trades.only_in_baseline.is_empty());
+        assert_eq!(report.trades.only_in_candidate.len(), 1);
+        assert_eq!(
+            report.trades.only_in_candidate[0].symbol,
+            Symbol::equity("NVDA")
+        );
+
+        let metric = |name: &str| {
+            report
+                .metrics
+                .iter()
+                .find(|metric| metric.metric == name)
+                .unwrap()
+                .delta
+        };
+        assert_eq!(metric("total_trades"), Some(dec!(1)));
+        assert_eq!(metric("logged_trades"), Some(dec!(1)));
+        assert_eq!(metric("total_return"), Some(Decimal::ZERO));
+
+        assert_eq!(report.tracking[4].difference, dec!(0.002));
+        assert!(report.tracking[..4]
+            .iter()
+            .all(|point| point.difference.is_zero()));
+        assert_eq!(report.max_tracking_difference(), dec!(0.002));
+
+        let fields: Vec<_> = report.config.iter().map(|d| d.field.as_str()).collect();
+        assert_eq!(
+            fields,
+            ["initial_capital", "strategy_config.parameters.short_period"]
+        );
+        assert_eq!(report.config[1].baseline, Value::Null);
+        assert_eq!(report.config[1].candidate, serde_json::json!(7));
+
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| total_trades |"), "{markdown}");
+        assert!(markdown.contains("2 matched, 0 only in baseline, 1 only in candidate."));
+        assert!(
+            markdown.contains("| candidate | NASDAQ:NVDA | Buy |"),
+            "{markdown}"
+        );
+        assert!(markdown.contains("| `strategy_config.parameters.short_period` | null | 7 |"));
+
+        // Outside the tolerance the shifted trade no longer matches.
+        let strict = compare_with_tolerance(&baseline, &candidate, Duration::seconds(10));
+        assert_eq!(strict.trades.matched, 1);
+        assert_eq!(strict.trades.only_in_baseline.len(), 1);
+        assert_eq!(strict.trades.only_in_candidate.len(), 2);
+    }
+
+    /// Points every weekday from Monday 1 January 2024 for `weeks` weeks at
+    /// `hours` UTC, returning `gain(point time)` since the previous point.
+    fn curve(
+        weeks: i64,
+        hours: &[u32],
+        gain: impl Fn(DateTime<Utc>) -> Decimal,
+    ) -> Vec<EquityCurvePoint> {
+        let mut value = dec!(100000);
+        let mut points = Vec::new();
+        for day in 0..weeks * 7 {
+            let date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(day);
+            if date.weekday().num_days_from_monday() >= 5 {
+                continue;
+            }
+            for hour in hours {
+                let timestamp = date + Duration::hours(i64::from(*hour));
+                let step = gain(timestamp);
+                let daily_return = (!points.is_empty()).then_some(step);
+                value *= Decimal::ONE + daily_return.unwrap_or_default();
+                points.push(EquityCurvePoint {
+                    timestamp,
+                    portfolio_value: value,
+                    cash: value,
+                    positions_value: Decimal::ZERO,
+                    total_pnl: value - dec!(100000),
+                    daily_return,
+                    cumulative_return: value / dec!(100000) - Decimal::ONE,
+                    drawdown: Decimal::ZERO,
+                    stale_symbols: Vec::new(),
+                    cash_flow: Decimal::ZERO,
+                });
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn a_strategy_that_only_gains_on_mondays_shows_up_in_the_weekday_profile() {
+        let mut result = sample_result();
+        result.config.symbols = vec![Symbol::equity("AAPL")];
+        result.equity_curve = curve(8, &[0], |at| {
+            if at.weekday() == chrono::Weekday::Mon {
+                dec!(0.01)
+            } else {
+                Decimal::ZERO
+            }
+        });
+        let mut closed = trade("AAPL", 8, dec!(180));
+        closed.exit_time = Some(Utc.with_ymd_and_hms(2024, 1, 15, 0, 0, 0).unwrap());
+        closed.pnl = Some(dec!(250));
+        result.trade_log = vec![closed, trade("MSFT", 9, dec!(400))];
+
+        let profile = calendar_profile(&result);
+        assert_eq!(profile.timezone, ExchangeTimezone::NewYork);
+        assert!(profile.by_hour.is_empty());
+
+        let labels: Vec<_> = profile
+            .by_weekday
+            .iter()
+            .map(|b| b.label.as_str())
+            .collect();
+        assert_eq!(labels, WEEKDAYS);
+        let monday = &profile.by_weekday[0];
+        // The first Monday opens the curve and has no return.
+        assert_eq!(monday.periods, 7);
+        assert_eq!(monday.mean_return, Some(dec!(0.01)));
+        assert_eq!(monday.median_return, Some(dec!(0.01)));
+        assert_eq!(monday.hit_rate, Some(Decimal::ONE));
+        assert_eq!((monday.trades, monday.winning_trades), (1, 1));
+        assert_eq!(monday.trade_pnl, dec!(250));
+        for other in &profile.by_weekday[1..5] {
+            assert_eq!(other.periods, 8, "{}", other.label);
+            assert_eq!(other.mean_return, Some(Decimal::ZERO));
+            assert_eq!(other.hit_rate, Some(Decimal::ZERO));
+            assert_eq!(other.trades, 0);
+        }
+        for weekend in &profile.by_weekday[5..] {
+            assert_eq!(weekend.periods, 0);
+            assert_eq!(weekend.mean_return, None);
+        }
+
+        // Eight weeks span January and February: four and three Mondays.
+        let (january, february) = (&profile.by_month[0], &profile.by_month[1]);
+        assert_eq!((january.key, january.label.as_str()), (1, "Jan"));
+        assert_eq!(january.periods, 1);
+        assert_eq!(january.mean_return, Some(dec!(0.04060401)));
+        assert_eq!(february.mean_return, Some(dec!(0.030301)));
+        assert!(profile.by_month[2..].iter().all(|b| b.periods == 0));
+
+        let heatmap = &profile.day_of_month;
+        assert_eq!(heatmap.rows.len(), 2);
+        assert_eq!(heatmap.rows[0].days.len(), 31);
+        assert_eq!(heatmap.rows[0].days[7], Some(dec!(0.01)));
+        assert_eq!(heatmap.rows[0].days[8], Some(Decimal::ZERO));
+        assert_eq!(heatmap.rows[0].days[5], None);
+        assert_eq!(heatmap.totals[7], dec!(0.01));
+
+        let json = serde_json::to_string(&profile).unwrap();
+        assert_eq!(
+            serde_json::from_str::<CalendarProfile>(&json).unwrap(),
+            profile
+        );
+    }
+
+    #[test]
+    fn intraday_hours_follow_the_exchange_clock() {
+        let mut result = sample_result();
+        result.config.resolution = gb_types::Resolution::Hour;
+        result.config.symbols = vec![Symbol::equity("AAPL")];
+        // 15:00 UTC is 10:00 in New York in winter; only that bar gains.
+        result.equity_curve = curve(2, &[15, 16], |at| {
+            if at.hour() == 15 {
+                dec!(0.002)
+            } else {
+                dec!(-0.001)
+            }
+        });
+
+        let profile = calendar_profile(&result);
+        assert_eq!(profile.by_hour.len(), 24);
+        assert_eq!(profile.by_hour[10].label, "10:00");
+        assert_eq!(profile.by_hour[10].hit_rate, Some(Decimal::ONE));
+        assert_eq!(profile.by_hour[11].hit_rate, Some(Decimal::ZERO));
+        assert!(profile.by_hour[15].periods == 0 && profile.by_hour[16].periods == 0);
+
+        let utc = calendar_profile_in(&result, ExchangeTimezone::Utc);
+        assert_eq!(utc.by_hour[15].hit_rate, Some(Decimal::ONE));
+        assert_eq!(utc.by_hour[10].periods, 0);
+    }
+}
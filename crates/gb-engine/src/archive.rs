@@ -0,0 +1,650 @@
+//! On-disk archive of backtest results, one directory ("bundle") per result,
+//! so the API layer, the CLI and notebooks share one persistence format.
+//!
+//! A bundle holds:
+//!
+//! - `summary.json`: the [`BacktestSummary`], which is all [`list_results`]
+//!   reads;
+//! - `manifest.toml`: the config as a backtest manifest, runnable again with
+//!   [`BacktestEngine::from_manifest`](crate::BacktestEngine::from_manifest);
+//! - `metrics.json`: the [`ARCHIVE_SCHEMA_VERSION`], status and timings,
+//!   performance and strategy metrics, the final portfolio, the run manifest,
+//!   metadata and any benchmark curve and excess returns;
+//! - `equity.parquet` / `trades.parquet`: the equity curve and trade log, with
+//!   the columns of [`crate::ipc`] but amounts as `Decimal128(38, 18)`, so they
+//!   load back exactly to 18 decimal places;
+//! - `events.jsonl`: the order events, one JSON object per line;
+//! - `positions.jsonl`: the position history, one snapshot per line;
+//! - `logs.jsonl`: the run's log events, when [`crate::telemetry`] writes run
+//!   logs under the archive root and the bundle is saved in the directory
+//!   named after the result's config id.
+//!
+//! The schema version is bumped whenever the bundle layout changes;
+//! [`load_result`] rejects bundles written by a newer version and is where
+//! older versions get migrated. Version 1 bundles have no summary file;
+//! [`list_results`] regenerates it from the detail files the first time it
+//! lists them.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use arrow::array::RecordBatch;
+use arrow::datatypes::Schema;
+use chrono::{DateTime, Utc};
+use gb_types::{
+    BacktestConfig, BacktestId, BacktestResult, BacktestStatus, BacktestSummary, CashFlow,
+    DailyReturn, EquityCurvePoint, ExecutionReport, GbError, GbResult, GreeksExposure,
+    PerformanceMetrics, Portfolio, Position, RunManifest, StrategyMetrics, Symbol, TradeRecord,
+};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use rust_decimal::Decimal;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::ipc::{
+    check_table, equity_curve_from_batches, equity_curve_record_batch_with,
+    equity_curve_schema_with, trade_log_from_batches, trade_log_record_batch_with,
+    trade_log_schema_with, Amounts, EQUITY_CURVE_TABLE, IPC_BATCH_ROWS, TRADE_LOG_TABLE,
+};
+
+/// Version of the bundle layout described in the module docs.
+pub const ARCHIVE_SCHEMA_VERSION: u32 = 2;
+
+const SUMMARY_FILE: &str = "summary.json";
+const MANIFEST_FILE: &str = "manifest.toml";
+const METRICS_FILE: &str = "metrics.json";
+const EQUITY_FILE: &str = "equity.parquet";
+const TRADES_FILE: &str = "trades.parquet";
+const EVENTS_FILE: &str = "events.jsonl";
+const POSITIONS_FILE: &str = "positions.jsonl";
+
+/// One archived result, as listed by [`list_results`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultSummary {
+    /// Bundle directory, for [`load_result`].
+    pub path: PathBuf,
+    pub summary: BacktestSummary,
+}
+
+/// `summary.json`.
+#[derive(Debug, Serialize, Deserialize)]
+struct SummaryFile {
+    schema_version: u32,
+    summary: BacktestSummary,
+}
+
+/// `metrics.json`: everything but the config and the row data.
+#[derive(Debug, Serialize, Deserialize)]
+struct MetricsFile {
+    schema_version: u32,
+    id: BacktestId,
+    status: BacktestStatus,
+    created_at: DateTime<Utc>,
+    finished_at: Option<DateTime<Utc>>,
+    duration_seconds: Option<u64>,
+    performance_metrics: Option<PerformanceMetrics>,
+    strategy_metrics: Option<StrategyMetrics>,
+    final_portfolio: Option<PortfolioRecord>,
+    error_message: Option<String>,
+    #[serde(default)]
+    metadata: HashMap<String, serde_json::Value>,
+    run_manifest: Option<RunManifest>,
+    #[serde(default)]
+    benchmark_curve: Vec<EquityCurvePoint>,
+    #[serde(default)]
+    excess_returns: Vec<(DateTime<Utc>, Decimal)>,
+    #[serde(default)]
+    execution_report: ExecutionReport,
+}
+
+/// [`Portfolio`] with its symbol-keyed maps as lists, which JSON can hold.
+#[derive(Debug, Serialize, Deserialize)]
+struct PortfolioRecord {
+    account_id: String,
+    initial_capital: Decimal,
+    cash: Decimal,
+    positions: Vec<Position>,
+    total_equity: Decimal,
+    total_pnl: Decimal,
+    total_realized_pnl: Decimal,
+    total_unrealized_pnl: Decimal,
+    total_commissions: Decimal,
+    last_updated: DateTime<Utc>,
+    daily_returns: Vec<DailyReturn>,
+    #[serde(default)]
+    contract_multipliers: Vec<(Symbol, Decimal)>,
+    #[serde(default)]
+    option_greeks: Vec<(Symbol, GreeksExposure)>,
+    #[serde(default)]
+    external_adjustments: Decimal,
+    #[serde(default)]
+    cash_flows: Vec<CashFlow>,
+}
+
+impl From<&Portfolio> for PortfolioRecord {
+    fn from(portfolio: &Portfolio) -> Self {
+        let mut positions: Vec<Position> = portfolio.positions.values().cloned().collect();
+        positions.sort_by_key(|position| position.symbol.to_string());
+        Self {
+            account_id: portfolio.account_id.clone(),
+            initial_capital: portfolio.initial_capital,
+            cash: portfolio.cash,
+            positions,
+            total_equity: portfolio.total_equity,
+            total_pnl: portfolio.total_pnl,
+            total_realized_pnl: portfolio.total_realized_pnl,
+            total_unrealized_pnl: portfolio.total_unrealized_pnl,
+            total_commissions: portfolio.total_commissions,
+            last_updated: portfolio.last_updated,
+            daily_returns: portfolio.daily_returns.clone(),
+            contract_multipliers: sorted_entries(&portfolio.contract_multipliers),
+            option_greeks: sorted_entries(&portfolio.option_greeks),
+            external_adjustments: portfolio.external_adjustments,
+            cash_flows: portfolio.cash_flows.clone(),
+        }
+    }
+}
+
+/// Map entries ordered by symbol, so saved bundles are deterministic.
+fn sorted_entries<T: Copy>(map: &HashMap<Symbol, T>) -> Vec<(Symbol, T)> {
+    let mut entries: Vec<_> = map
+        .iter()
+        .map(|(symbol, value)| (symbol.clone(), *value))
+        .collect();
+    entries.sort_by_key(|(symbol, _)| symbol.to_string());
+    entries
+}
+
+impl From<PortfolioRecord> for Portfolio {
+    fn from(record: PortfolioRecord) -> Self {
+        Self {
+            account_id: record.account_id,
+            initial_capital: record.initial_capital,
+            cash: record.cash,
+            positions: record
+                .positions
+                .into_iter()
+                .map(|position| (position.symbol.clone(), position))
+                .collect(),
+            total_equity: record.total_equity,
+            total_pnl: record.total_pnl,
+            total_realized_pnl: record.total_realized_pnl,
+            total_unrealized_pnl: record.total_unrealized_pnl,
+            total_commissions: record.total_commissions,
+            last_updated: record.last_updated,
+            daily_returns: record.daily_returns,
+            contract_multipliers: record.contract_multipliers.into_iter().collect(),
+            option_greeks: record.option_greeks.into_iter().collect(),
+            external_adjustments: record.external_adjustments,
+            cash_flows: record.cash_flows,
+        }
+    }
+}
+
+/// Write `result` as a bundle in `dir`, creating it if needed and replacing
+/// any bundle already there.
+pub fn save_result(result: &BacktestResult, dir: impl AsRef<Path>) -> GbResult<()> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+
+    write_summary(dir, &result.summary())?;
+    std::fs::write(dir.join(MANIFEST_FILE), result.config.to_toml()?)?;
+
+    let metrics = MetricsFile {
+        schema_version: ARCHIVE_SCHEMA_VERSION,
+        id: result.id,
+        status: result.status,
+        created_at: result.start_time,
+        finished_at: result.end_time,
+        duration_seconds: result.duration_seconds,
+        performance_metrics: result.performance_metrics.clone(),
+        strategy_metrics: result.strategy_metrics.clone(),
+        final_portfolio: result.final_portfolio.as_ref().map(PortfolioRecord::from),
+        error_message: result.error_message.clone(),
+        metadata: result.metadata.clone(),
+        run_manifest: result.manifest.clone(),
+        benchmark_curve: result.benchmark_curve.clone(),
+        excess_returns: result.excess_returns.clone(),
+        execution_report: result.execution_report.clone(),
+    };
+    std::fs::write(dir.join(METRICS_FILE), serde_json::to_vec_pretty(&metrics)?)?;
+
+    write_parquet(
+        &dir.join(EQUITY_FILE),
+        equity_curve_schema_with(Amounts::Decimal),
+        result.equity_curve.chunks(IPC_BATCH_ROWS),
+        |points| equity_curve_record_batch_with(points, Amounts::Decimal),
+    )?;
+    write_parquet(
+        &dir.join(TRADES_FILE),
+        trade_log_schema_with(Amounts::Decimal),
+        result.trade_log.chunks(IPC_BATCH_ROWS),
+        |trades| trade_log_record_batch_with(trades, Amounts::Decimal),
+    )?;
+
+    write_jsonl(&dir.join(EVENTS_FILE), &result.order_events)?;
+    write_jsonl(&dir.join(POSITIONS_FILE), &result.positions_history)
+}
+
+/// Read the bundle in `dir` back into a [`BacktestResult`].
+pub fn load_result(dir: impl AsRef<Path>) -> GbResult<BacktestResult> {
+    let dir = dir.as_ref();
+    let metrics = read_metrics(dir)?;
+    let manifest = std::fs::read_to_string(dir.join(MANIFEST_FILE))?;
+    let config = BacktestConfig::from_toml(&manifest)?;
+
+    let mut result = BacktestResult::new(config);
+    result.id = metrics.id;
+    result.status = metrics.status;
+    result.start_time = metrics.created_at;
+    result.end_time = metrics.finished_at;
+    result.duration_seconds = metrics.duration_seconds;
+    result.final_portfolio = metrics.final_portfolio.map(Portfolio::from);
+    result.strategy_metrics = metrics.strategy_metrics;
+    result.performance_metrics = metrics.performance_metrics;
+    result.error_message = metrics.error_message;
+    result.metadata = metrics.metadata;
+    result.manifest = metrics.run_manifest;
+    result.benchmark_curve = metrics.benchmark_curve;
+    result.excess_returns = metrics.excess_returns;
+    result.execution_report = metrics.execution_report;
+    result.config_manifest = Some(manifest);
+    result.equity_curve = read_equity_curve(&dir.join(EQUITY_FILE))?;
+    result.trade_log = read_trade_log(&dir.join(TRADES_FILE))?;
+    result.order_events = read_jsonl(&dir.join(EVENTS_FILE))?;
+    // Bundles written before position history was archived lack the file.
+    let positions = dir.join(POSITIONS_FILE);
+    if positions.is_file() {
+        result.positions_history = read_jsonl(&positions)?;
+    }
+    Ok(result)
+}
+
+/// Read the summary of the bundle in `dir` without touching its detail
+/// files, regenerating it first for bundles written before summaries were
+/// archived.
+pub fn load_summary(dir: impl AsRef<Path>) -> GbResult<BacktestSummary> {
+    let dir = dir.as_ref();
+    if !dir.join(SUMMARY_FILE).is_file() {
+        return regenerate_summary(dir);
+    }
+    let file: SummaryFile =
+        serde_json::from_reader(BufReader::new(File::open(dir.join(SUMMARY_FILE))?))?;
+    check_schema_version(file.schema_version)?;
+    Ok(file.summary)
+}
+
+/// Rebuild the summary of the bundle in `dir` from its detail files and
+/// write it back, replacing any summary already there.
+pub fn regenerate_summary(dir: impl AsRef<Path>) -> GbResult<BacktestSummary> {
+    let dir = dir.as_ref();
+    let summary = load_result(dir)?.summary();
+    write_summary(dir, &summary)?;
+    Ok(summary)
+}
+
+/// Summaries of the bundles directly under `root`, oldest first, read from
+/// each bundle's summary file alone. Directories that are not bundles are
+/// skipped, as are unreadable bundles, with a warning.
+pub fn list_results(root: impl AsRef<Path>) -> GbResult<Vec<ResultSummary>> {
+    let mut summaries = Vec::new();
+    for entry in std::fs::read_dir(root)? {
+        let path = entry?.path();
+        if !path.join(SUMMARY_FILE).is_file() && !path.join(METRICS_FILE).is_file() {
+            continue;
+        }
+        match load_summary(&path) {
+            Ok(summary) => summaries.push(ResultSummary { path, summary }),
+            Err(e) => warn!("Skipping unreadable result bundle {}: {e}", path.display()),
+        }
+    }
+    summaries.sort_by(|a, b| {
+        a.summary
+            .created_at
+            .cmp(&b.summary.created_at)
+            .then(a.summary.id.cmp(&b.summary.id))
+    });
+    Ok(summaries)
+}
+
+fn write_summary(dir: &Path, summary: &BacktestSummary) -> GbResult<()> {
+    let file = SummaryFile {
+        schema_version: ARCHIVE_SCHEMA_VERSION,
+        summary: summary.clone(),
+    };
+    std::fs::write(dir.join(SUMMARY_FILE), serde_json::to_vec_pretty(&file)?)?;
+    Ok(())
+}
+
+fn check_schema_version(version: u32) -> GbResult<()> {
+    if version == 0 || version > ARCHIVE_SCHEMA_VERSION {
+        return Err(GbError::Validation(format!(
+            "unsupported result archive schema version {version} (this build reads up to {ARCHIVE_SCHEMA_VERSION})"
+        )));
+    }
+    Ok(())
+}
+
+fn read_metrics(dir: &Path) -> GbResult<MetricsFile> {
+    let metrics: MetricsFile =
+        serde_json::from_reader(BufReader::new(File::open(dir.join(METRICS_FILE))?))?;
+    check_schema_version(metrics.schema_version)?;
+    Ok(metrics)
+}
+
+fn write_parquet<'a, T: 'a>(
+    path: &Path,
+    schema: Schema,
+    chunks: impl Iterator<Item = &'a [T]>,
+    batch: impl Fn(&'a [T]) -> GbResult<RecordBatch>,
+) -> GbResult<()> {
+    let parquet_error = |e: parquet::errors::ParquetError| GbError::Parquet(e.to_string());
+    let mut writer =
+        ArrowWriter::try_new(File::create(path)?, Arc::new(schema), None).map_err(parquet_error)?;
+    for chunk in chunks {
+        writer.write(&batch(chunk)?).map_err(parquet_error)?;
+    }
+    writer.close().map_err(parquet_error)?;
+    Ok(())
+}
+
+fn read_parquet(path: &Path, table: &str) -> GbResult<Vec<RecordBatch>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)
+        .map_err(|e| GbError::Parquet(e.to_string()))?;
+    check_table(reader.schema(), table)?;
+    reader
+        .build()
+        .map_err(|e| GbError::Parquet(e.to_string()))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| GbError::Arrow(e.to_string()))
+}
+
+fn read_equity_curve(path: &Path) -> GbResult<Vec<EquityCurvePoint>> {
+    equity_curve_from_batches(read_parquet(path, EQUITY_CURVE_TABLE)?)
+}
+
+fn read_trade_log(path: &Path) -> GbResult<Vec<TradeRecord>> {
+    trade_log_from_batches(read_parquet(path, TRADE_LOG_TABLE)?)
+}
+
+fn write_jsonl<T: Serialize>(path: &Path, items: &[T]) -> GbResult<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    for item in items {
+        serde_json::to_writer(&mut writer, item)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_jsonl<T: DeserializeOwned>(path: &Path) -> GbResult<Vec<T>> {
+    let mut items = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            items.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use gb_types::{
+        AssetClass, Fill, FillExecution, GreeksExposure, Order, OrderEvent, PositionHolding,
+        PositionsSnapshot, Side, StrategyConfig, StrategyMetrics,
+    };
+    use rust_decimal_macros::dec;
+    use uuid::Uuid;
+
+    fn sample_result(name: &str, created_at: DateTime<Utc>) -> BacktestResult {
+        let aapl = Symbol::equity("AAPL");
+        let option = Symbol::new("AAPL240621C00200000", "OPRA", AssetClass::Option);
+        let mut strategy = StrategyConfig::new("ma_crossover".into(), "MA crossover".into());
+        strategy.symbols = vec![aapl.clone()];
+        let config = BacktestConfig::new(name.to_string(), strategy)
+            .with_symbols(vec![aapl.clone()])
+            .with_date_range(
+                Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 3, 29, 0, 0, 0).unwrap(),
+            );
+        let mut result = BacktestResult::new(config);
+        result.start_time = created_at;
+
+        let order = Order::market_order(aapl.clone(), Side::Buy, dec!(40), "ma".into());
+        let fill = Fill::new(
+            order.id,
+            aapl.clone(),
+            Side::Buy,
+            dec!(40),
+            dec!(178.25),
+            dec!(1.2),
+            "ma".into(),
+        );
+        let mut portfolio = Portfolio::new("archive".into(), dec!(100000));
+        portfolio.apply_fill(&fill);
+        portfolio
+            .contract_multipliers
+            .insert(option.clone(), dec!(100));
+        portfolio.option_greeks.insert(
+            option,
+            GreeksExposure {
+                delta: dec!(55.5),
+                ..Default::default()
+            },
+        );
+        portfolio.add_daily_return(created_at, dec!(0.0125));
+        result.mark_completed(portfolio, StrategyMetrics::new("ma_crossover".into()));
+        result.start_time = created_at;
+        result.end_time = Some(created_at + Duration::seconds(3));
+        result.order_events = vec![
+            OrderEvent::OrderSubmitted(order.clone()),
+            OrderEvent::OrderFilled {
+                order_id: order.id,
+                fill,
+            },
+            OrderEvent::OrderCanceled {
+                order_id: Uuid::new_v4(),
+                reason: "end of run".into(),
+            },
+        ];
+        result.equity_curve = (0..5)
+            .map(|day| EquityCurvePoint {
+                timestamp: created_at + Duration::days(day),
+                portfolio_value: dec!(100000) + Decimal::from(day) * dec!(12.345678901234567891),
+                cash: dec!(92870),
+                positions_value: dec!(7130) + Decimal::from(day) * dec!(12.345678901234567891),
+                total_pnl: Decimal::from(day) * dec!(12.345678901234567891),
+                daily_return: (day > 0).then(|| dec!(0.000123456789)),
+                cumulative_return: Decimal::from(day) * dec!(0.000123456789),
+                drawdown: dec!(0),
+                stale_symbols: Vec::new(),
+                cash_flow: Decimal::ZERO,
+            })
+            .collect();
+        result.trade_log = vec![TradeRecord {
+            id: Uuid::new_v4(),
+            symbol: Symbol::equity("AAPL"),
+            entry_time: created_at,
+            exit_time: None,
+            entry_price: dec!(178.25),
+            exit_price: None,
+            quantity: dec!(40),
+            side: Side::Buy,
+            pnl: None,
+            commission: dec!(1.2),
+            duration_hours: None,
+            strategy_id: "ma_crossover".into(),
+            tags: vec!["entry".into()],
+            metadata: serde_json::Value::Null,
+        }];
+        result
+            .metadata
+            .insert("source".into(), serde_json::json!({"runner": "test"}));
+        result.positions_history = vec![PositionsSnapshot {
+            timestamp: created_at,
+            positions: vec![PositionHolding {
+                symbol: aapl.clone(),
+                quantity: dec!(40),
+                market_value: dec!(7130),
+            }],
+        }];
+        result.execution_report.record(&FillExecution {
+            symbol: aapl.clone(),
+            side: Side::Buy,
+            quantity: dec!(40),
+            decision_price: dec!(178),
+            arrival_price: dec!(178.1),
+            impact_price: dec!(178.1),
+            fill_price: dec!(178.25),
+            commission: dec!(1),
+        });
+        result.attach_benchmark(&[
+            (created_at, dec!(400)),
+            (created_at + Duration::days(2), dec!(404.5)),
+            (created_at + Duration::days(4), dec!(398)),
+        ]);
+        result
+    }
+
+    #[test]
+    fn saved_results_load_back_field_by_field() {
+        let dir = tempfile::tempdir().unwrap();
+        let created_at = Utc.with_ymd_and_hms(2024, 4, 2, 9, 30, 0).unwrap();
+        let result = sample_result("archived run", created_at);
+
+        save_result(&result, dir.path()).unwrap();
+        for file in [
+            SUMMARY_FILE,
+            MANIFEST_FILE,
+            METRICS_FILE,
+            EQUITY_FILE,
+            TRADES_FILE,
+            EVENTS_FILE,
+            POSITIONS_FILE,
+        ] {
+            assert!(dir.path().join(file).is_file(), "missing {file}");
+        }
+        let loaded = load_result(dir.path()).unwrap();
+
+        assert_eq!(loaded.id, result.id);
+        assert_eq!(loaded.config, result.config);
+        assert_eq!(loaded.status, result.status);
+        assert_eq!(loaded.start_time, result.start_time);
+        assert_eq!(loaded.end_time, result.end_time);
+        assert_eq!(loaded.duration_seconds, result.duration_seconds);
+        assert_eq!(loaded.final_portfolio, result.final_portfolio);
+        assert_eq!(loaded.strategy_metrics, result.strategy_metrics);
+        assert_eq!(loaded.performance_metrics, result.performance_metrics);
+        assert_eq!(loaded.equity_curve, result.equity_curve);
+        assert_eq!(loaded.trade_log, result.trade_log);
+        assert_eq!(loaded.order_events, result.order_events);
+        assert_eq!(loaded.error_message, result.error_message);
+        assert_eq!(loaded.metadata, result.metadata);
+        assert_eq!(loaded.manifest, result.manifest);
+        assert_eq!(loaded.config_manifest, result.config_manifest);
+        assert_eq!(loaded.benchmark_curve, result.benchmark_curve);
+        assert_eq!(loaded.excess_returns, result.excess_returns);
+        assert_eq!(loaded.positions_history, result.positions_history);
+        assert_eq!(loaded.execution_report, result.execution_report);
+        assert_eq!(loaded.benchmark_curve.len(), 5);
+    }
+
+    #[test]
+    fn bundles_list_by_creation_time_and_newer_versions_are_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let at = |day| Utc.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap();
+        for (dir, name, created_at) in [
+            ("b", "second", at(2)),
+            ("c", "third", at(3)),
+            ("a", "first", at(1)),
+        ] {
+            save_result(&sample_result(name, created_at), root.path().join(dir)).unwrap();
+        }
+        std::fs::create_dir(root.path().join("not-a-bundle")).unwrap();
+
+        let summaries = list_results(root.path()).unwrap();
+        let names: Vec<_> = summaries.iter().map(|s| s.summary.name.as_str()).collect();
+        assert_eq!(names, ["first", "second", "third"]);
+        assert_eq!(summaries[0].summary.created_at, at(1));
+        assert_eq!(summaries[0].path, root.path().join("a"));
+        assert_eq!(summaries[0].summary.status, BacktestStatus::Completed);
+        assert!(summaries[0].summary.total_return.is_some());
+        assert_eq!(
+            load_result(&summaries[2].path).unwrap().config.name,
+            "third"
+        );
+
+        for file in [METRICS_FILE, SUMMARY_FILE] {
+            let path = root.path().join("c").join(file);
+            let mut json: serde_json::Value =
+                serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+            json["schema_version"] = serde_json::json!(ARCHIVE_SCHEMA_VERSION + 1);
+            std::fs::write(&path, json.to_string()).unwrap();
+        }
+        let error = load_result(root.path().join("c")).unwrap_err().to_string();
+        assert!(error.contains("schema version"), "{error}");
+        assert_eq!(list_results(root.path()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn listing_reads_only_summary_files() {
+        let root = tempfile::tempdir().unwrap();
+        let start = Utc.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap();
+        for index in 0..100 {
+            let dir = root.path().join(format!("run-{index:03}"));
+            let result = sample_result(&format!("run {index}"), start + Duration::hours(index));
+            save_result(&result, &dir).unwrap();
+            // Any read of a detail file would now fail the bundle.
+            for file in [
+                MANIFEST_FILE,
+                METRICS_FILE,
+                EQUITY_FILE,
+                TRADES_FILE,
+                EVENTS_FILE,
+                POSITIONS_FILE,
+            ] {
+                std::fs::write(dir.join(file), "not a detail file").unwrap();
+            }
+        }
+
+        let summaries = list_results(root.path()).unwrap();
+        assert_eq!(summaries.len(), 100);
+        assert_eq!(summaries[0].summary.name, "run 0");
+        assert_eq!(summaries[99].summary.name, "run 99");
+        assert_eq!(summaries[99].summary.trade_count, 1);
+        assert_eq!(summaries[99].summary.order_event_count, 3);
+        assert_eq!(summaries[99].summary.equity_points, 5);
+    }
+
+    #[test]
+    fn regenerated_summaries_match_the_stored_ones() {
+        let root = tempfile::tempdir().unwrap();
+        let created_at = Utc.with_ymd_and_hms(2024, 4, 2, 9, 30, 0).unwrap();
+        let dir = root.path().join("bundle");
+        save_result(&sample_result("archived run", created_at), &dir).unwrap();
+        let stored = load_summary(&dir).unwrap();
+        assert_eq!(regenerate_summary(&dir).unwrap(), stored);
+
+        // A version 1 bundle has no summary until it is first listed.
+        std::fs::remove_file(dir.join(SUMMARY_FILE)).unwrap();
+        let metrics_path = dir.join(METRICS_FILE);
+        let mut metrics: serde_json::Value =
+            serde_json::from_slice(&std::fs::read(&metrics_path).unwrap()).unwrap();
+        metrics["schema_version"] = serde_json::json!(1);
+        std::fs::write(&metrics_path, metrics.to_string()).unwrap();
+
+        let listed = list_results(root.path()).unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].summary, stored);
+        assert!(dir.join(SUMMARY_FILE).is_file());
+        assert_eq!(load_summary(&dir).unwrap(), stored);
+    }
+}
@@ -0,0 +1,318 @@
+//! Trading calendar used by the backtest simulator, the live engine and the
+//! live risk checks to locate session opens and closes, so a backtest and a
+//! live run end each trading day at the same instant.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::RangeInclusive;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use gb_options::calendar::nyse_holidays;
+use gb_types::market::AssetClass;
+use serde::{Deserialize, Serialize};
+
+/// Years whose NYSE holidays the default calendar closes on.
+const NYSE_HOLIDAY_YEARS: RangeInclusive<i32> = 1990..=2100;
+
+/// Daily session schedule: open and close times in the exchange's time zone,
+/// optional weekend trading, full-day holidays and shortened sessions. Opens
+/// and closes are converted to UTC per date, so sessions follow daylight
+/// saving time.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradingCalendar {
+    /// Time zone of the open and close times; UTC when not configured.
+    #[serde(default = "default_timezone")]
+    pub timezone: Tz,
+    /// Session open in exchange-local time. An open at or after the close
+    /// time belongs to the previous day (sessions spanning midnight, or
+    /// 24-hour sessions).
+    #[serde(default = "default_open_time")]
+    pub open_time: NaiveTime,
+    /// Session close in exchange-local time. A session contains every
+    /// instant up to and including its close.
+    pub close_time: NaiveTime,
+    /// Whether Saturdays and Sundays are trading days.
+    pub weekend_trading: bool,
+    /// Exchange-local dates with no session.
+    #[serde(default)]
+    pub holidays: BTreeSet<NaiveDate>,
+    /// Exchange-local close time of shortened sessions, such as the day
+    /// after Thanksgiving.
+    #[serde(default)]
+    pub early_closes: BTreeMap<NaiveDate, NaiveTime>,
+}
+
+fn default_timezone() -> Tz {
+    Tz::UTC
+}
+
+fn default_open_time() -> NaiveTime {
+    NaiveTime::from_hms_opt(14, 30, 0).expect("valid time")
+}
+
+impl Default for TradingCalendar {
+    /// US equities: weekdays, 9:30 AM–4:00 PM New York time, closed on NYSE
+    /// holidays.
+    fn default() -> Self {
+        Self {
+            timezone: chrono_tz::America::New_York,
+            open_time: NaiveTime::from_hms_opt(9, 30, 0).expect("valid time"),
+            close_time: NaiveTime::from_hms_opt(16, 0, 0).expect("valid time"),
+            weekend_trading: false,
+            holidays: NYSE_HOLIDAY_YEARS.flat_map(nyse_holidays).collect(),
+            early_closes: BTreeMap::new(),
+        }
+    }
+}
+
+impl TradingCalendar {
+    /// Calendar appropriate for the given asset class. Crypto rolls its
+    /// session at midnight UTC every day; forex trades round the clock from
+    /// Sunday to Friday, rolling over at 5:00 PM New York time; everything
+    /// else uses the US equity default.
+    pub fn for_asset_class(asset_class: AssetClass) -> Self {
+        match asset_class {
+            AssetClass::Crypto => Self::continuous(NaiveTime::MIN),
+            AssetClass::Forex => {
+                let rollover = NaiveTime::from_hms_opt(17, 0, 0).expect("valid time");
+                Self {
+                    timezone: chrono_tz::America::New_York,
+                    weekend_trading: false,
+                    ..Self::continuous(rollover)
+                }
+            }
+            _ => Self::default(),
+        }
+    }
+
+    /// Round-the-clock trading every day, with sessions rolling over at
+    /// `rollover` UTC (e.g. a venue's daily settlement time).
+    pub fn continuous(rollover: NaiveTime) -> Self {
+        Self {
+            timezone: Tz::UTC,
+            open_time: rollover,
+            close_time: rollover,
+            weekend_trading: true,
+            holidays: BTreeSet::new(),
+            early_closes: BTreeMap::new(),
+        }
+    }
+
+    /// Close the session on `date` early, at `close_time` exchange-local time
+    pub fn with_early_close(mut self, date: NaiveDate, close_time: NaiveTime) -> Self {
+        self.early_closes.insert(date, close_time);
+        self
+    }
+
+    /// Whether `date` has a session.
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        let weekend = matches!(date.weekday(), Weekday::Sat | Weekday::Sun);
+        (self.weekend_trading || !weekend) && !self.holidays.contains(&date)
+    }
+
+    /// Close of the session on `date`, or `None` when `date` has no session.
+    pub fn close_on(&self, date: NaiveDate) -> Option<DateTime<Utc>> {
+        self.is_trading_day(date)
+            .then(|| self.local_to_utc(date, self.close_time_on(date)))
+    }
+
+    /// Close of the session containing `at`: the first trading-day close at
+    /// or after `at`.
+    pub fn session_close(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let mut date = at.with_timezone(&self.timezone).date_naive();
+        // A year of consecutive holidays is a misconfiguration; stop there
+        // rather than loop forever.
+        for _ in 0..366 {
+            if let Some(close) = self.close_on(date).filter(|close| *close >= at) {
+                return close;
+            }
+            date = date.succ_opt().unwrap_or(date);
+        }
+        self.local_to_utc(date, self.close_time_on(date))
+    }
+
+    /// Whether `at` falls inside a session.
+    pub fn is_open(&self, at: DateTime<Utc>) -> bool {
+        self.session_open(at) <= at
+    }
+
+    /// Open of the session containing `at` (which may still be in the future
+    /// when `at` falls between sessions).
+    pub fn session_open(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let close = self.session_close(at);
+        let date = close.with_timezone(&self.timezone).date_naive();
+        let open = self.local_to_utc(date, self.open_time);
+        if open >= close {
+            self.local_to_utc(date.pred_opt().unwrap_or(date), self.open_time)
+        } else {
+            open
+        }
+    }
+
+    fn close_time_on(&self, date: NaiveDate) -> NaiveTime {
+        self.early_closes
+            .get(&date)
+            .copied()
+            .unwrap_or(self.close_time)
+    }
+
+    /// The instant `time` on `date` in the calendar's time zone. A time
+    /// repeated when clocks fall back resolves to its first occurrence; a
+    /// time skipped when they spring forward to the hour after.
+    fn local_to_utc(&self, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+        let local = date.and_time(time);
+        self.timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .or_else(|| {
+                self.timezone
+                    .from_local_datetime(&(local + Duration::hours(1)))
+                    .earliest()
+            })
+            .map(|at| at.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(y: i32, m: u32, d: u32, h: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_session_close_skips_weekends_and_holidays() {
+        let mut calendar = TradingCalendar::default();
+        // Tuesday morning closes the same day.
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 2, 15)),
+            utc(2024, 1, 2, 21)
+        );
+        // The close itself still belongs to that session.
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 2, 21)),
+            utc(2024, 1, 2, 21)
+        );
+        // Friday after the close rolls to Monday.
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 5, 22)),
+            utc(2024, 1, 8, 21)
+        );
+
+        calendar
+            .holidays
+            .insert(NaiveDate::from_ymd_opt(2024, 1, 8).unwrap());
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 5, 22)),
+            utc(2024, 1, 9, 21)
+        );
+    }
+
+    #[test]
+    fn test_default_sessions_follow_new_york_daylight_time() {
+        let calendar = TradingCalendar::default();
+        // Monday and Tuesday in June close at 4 PM EDT, 20:00 UTC.
+        assert_eq!(
+            calendar.session_close(utc(2024, 6, 17, 15)),
+            utc(2024, 6, 17, 20)
+        );
+        assert_eq!(
+            calendar.session_close(utc(2024, 6, 17, 21)),
+            utc(2024, 6, 18, 20)
+        );
+        assert_eq!(
+            calendar.session_open(utc(2024, 6, 18, 15)),
+            Utc.with_ymd_and_hms(2024, 6, 18, 13, 30, 0).unwrap()
+        );
+        assert!(calendar.is_open(utc(2024, 6, 18, 14)));
+        assert!(!calendar.is_open(utc(2024, 6, 18, 20) + Duration::minutes(30)));
+    }
+
+    #[test]
+    fn test_default_calendar_closes_on_nyse_holidays() {
+        let calendar = TradingCalendar::default();
+        assert!(!calendar.is_trading_day(NaiveDate::from_ymd_opt(2024, 6, 19).unwrap()));
+        // Tuesday after the close skips Juneteenth to Thursday.
+        assert_eq!(
+            calendar.session_close(utc(2024, 6, 18, 21)),
+            utc(2024, 6, 20, 20)
+        );
+    }
+
+    #[test]
+    fn test_crypto_calendar_closes_every_midnight() {
+        let calendar = TradingCalendar::for_asset_class(AssetClass::Crypto);
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 6, 15)),
+            utc(2024, 1, 7, 0)
+        );
+        assert_eq!(
+            calendar.session_open(utc(2024, 1, 6, 15)),
+            utc(2024, 1, 6, 0)
+        );
+    }
+
+    #[test]
+    fn test_forex_calendar_trades_sunday_evening_to_friday_evening() {
+        let calendar = TradingCalendar::for_asset_class(AssetClass::Forex);
+        // 5:00 PM EST on Friday ends the week's last session.
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 5, 15)),
+            utc(2024, 1, 5, 22)
+        );
+        assert!(!calendar.is_open(utc(2024, 1, 6, 12)));
+        assert!(!calendar.is_open(utc(2024, 1, 7, 21)));
+        // Sunday evening opens Monday's session.
+        assert!(calendar.is_open(utc(2024, 1, 7, 23)));
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 7, 23)),
+            utc(2024, 1, 8, 22)
+        );
+    }
+
+    #[test]
+    fn test_early_close_shortens_the_session() {
+        let friday = NaiveDate::from_ymd_opt(2024, 11, 29).unwrap();
+        let calendar = TradingCalendar::default()
+            .with_early_close(friday, NaiveTime::from_hms_opt(13, 0, 0).unwrap());
+        assert_eq!(calendar.close_on(friday), Some(utc(2024, 11, 29, 18)));
+        assert!(!calendar.is_open(utc(2024, 11, 29, 19)));
+        // Thanksgiving itself has no session.
+        assert_eq!(
+            calendar.close_on(NaiveDate::from_ymd_opt(2024, 11, 28).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_continuous_calendar_rolls_over_at_configured_time() {
+        let calendar = TradingCalendar::continuous(NaiveTime::from_hms_opt(22, 0, 0).unwrap());
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 6, 15)),
+            utc(2024, 1, 6, 22)
+        );
+        assert_eq!(
+            calendar.session_close(utc(2024, 1, 6, 23)),
+            utc(2024, 1, 7, 22)
+        );
+        assert_eq!(
+            calendar.session_open(utc(2024, 1, 6, 23)),
+            utc(2024, 1, 6, 22)
+        );
+        assert!(calendar.is_open(utc(2024, 1, 7, 3)));
+    }
+
+    #[test]
+    fn test_session_open_precedes_close() {
+        let calendar = TradingCalendar::default();
+        let open = Utc.with_ymd_and_hms(2024, 1, 2, 14, 30, 0).unwrap();
+        assert_eq!(calendar.session_open(utc(2024, 1, 2, 15)), open);
+        // Before the open, the upcoming session is reported.
+        assert_eq!(calendar.session_open(utc(2024, 1, 2, 9)), open);
+        assert!(!calendar.is_open(utc(2024, 1, 2, 9)));
+        assert!(calendar.is_open(utc(2024, 1, 2, 15)));
+        assert!(!calendar.is_open(utc(2024, 1, 6, 15)));
+    }
+}
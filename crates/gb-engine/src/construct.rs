@@ -0,0 +1,622 @@
+//! Portfolio construction: turn a return history into fully invested target
+//! [`Weights`] for [`quick_backtest`](crate::vectorized::quick_backtest) or
+//! a strategy's rebalance.
+//!
+//! Every solver returns weights summing to one within the bounds set by
+//! [`WeightConstraints`]. Equal, inverse-volatility and risk-parity weights
+//! are capped by clipping them at the maximum and handing the excess to the
+//! uncapped assets in proportion, which keeps their ordering but not exact
+//! equal risk once a cap binds. Minimum variance solves the constrained
+//! problem exactly.
+
+use gb_types::{GbError, GbResult, Symbol};
+
+use crate::vectorized::{BarMatrix, Weights};
+
+/// Tolerance on weights and risk contributions for the iterative solvers.
+const TOLERANCE: f64 = 1e-12;
+const MAX_ITERATIONS: usize = 100_000;
+
+/// Periodic returns of several symbols over the same dates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnHistory {
+    symbols: Vec<Symbol>,
+    /// One row of `symbols.len()` returns per period, oldest first.
+    rows: Vec<Vec<f64>>,
+}
+
+impl ReturnHistory {
+    /// Returns given as rows of one return per symbol. Fails for rows of the
+    /// wrong width or non-finite returns.
+    pub fn new(symbols: Vec<Symbol>, rows: Vec<Vec<f64>>) -> GbResult<Self> {
+        for (index, row) in rows.iter().enumerate() {
+            if row.len() != symbols.len() {
+                return Err(GbError::Validation(format!(
+                    "return row {index} has {} values for {} symbols",
+                    row.len(),
+                    symbols.len()
+                )));
+            }
+            if row.iter().any(|value| !value.is_finite()) {
+                return Err(GbError::Validation(format!(
+                    "return row {index} is not all finite numbers"
+                )));
+            }
+        }
+        Ok(Self { symbols, rows })
+    }
+
+    /// Close-to-close returns of every symbol in `prices`, over the rows
+    /// where all of them have a close on both sides.
+    pub fn from_matrix(prices: &BarMatrix) -> Self {
+        let symbols = prices.symbols().to_vec();
+        let closes = |row: usize| -> Option<Vec<f64>> {
+            symbols
+                .iter()
+                .map(|symbol| prices.close(row, symbol))
+                .collect()
+        };
+        let rows = (1..prices.len())
+            .filter_map(|row| {
+                let (previous, current) = (closes(row - 1)?, closes(row)?);
+                let returns: Vec<f64> = current
+                    .iter()
+                    .zip(&previous)
+                    .map(|(close, previous)| close / previous - 1.0)
+                    .collect();
+                returns
+                    .iter()
+                    .all(|value| value.is_finite())
+                    .then_some(returns)
+            })
+            .collect();
+        Self { symbols, rows }
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    /// Number of periods.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Sample covariance matrix, row-major. Needs at least two periods.
+    pub fn covariance(&self) -> GbResult<Vec<Vec<f64>>> {
+        let centered = self.centered()?;
+        let n = self.symbols.len();
+        let periods = (centered.len() - 1) as f64;
+        Ok((0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| centered.iter().map(|row| row[i] * row[j]).sum::<f64>() / periods)
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// Sample covariance shrunk toward a multiple of the identity with the
+    /// given intensity, which keeps it invertible.
+    pub fn shrunk_covariance(&self, shrinkage: Shrinkage) -> GbResult<Vec<Vec<f64>>> {
+        let mut covariance = self.covariance()?;
+        let n = covariance.len();
+        let mean_variance = (0..n).map(|i| covariance[i][i]).sum::<f64>() / n as f64;
+        let intensity = match shrinkage {
+            Shrinkage::Fixed(intensity) if (0.0..=1.0).contains(&intensity) => intensity,
+            Shrinkage::Fixed(intensity) => {
+                return Err(GbError::Validation(format!(
+                    "shrinkage intensity must lie in [0, 1], got {intensity}"
+                )))
+            }
+            Shrinkage::LedoitWolf => self.ledoit_wolf_intensity(mean_variance)?,
+        };
+        for (i, row) in covariance.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                let target = if i == j { mean_variance } else { 0.0 };
+                *value = (1.0 - intensity) * *value + intensity * target;
+            }
+        }
+        Ok(covariance)
+    }
+
+    /// Ledoit and Wolf's (2004) estimate of the intensity minimizing the
+    /// expected squared error of shrinking toward `mean_variance` × I.
+    fn ledoit_wolf_intensity(&self, mean_variance: f64) -> GbResult<f64> {
+        let centered = self.centered()?;
+        let n = self.symbols.len();
+        let periods = centered.len() as f64;
+        // The biased sample covariance the estimator is derived for.
+        let mut sample = vec![vec![0.0; n]; n];
+        for row in &centered {
+            for i in 0..n {
+                for j in 0..n {
+                    sample[i][j] += row[i] * row[j] / periods;
+                }
+            }
+        }
+        let mut dispersion = 0.0;
+        for (i, row) in sample.iter().enumerate() {
+            for (j, value) in row.iter().enumerate() {
+                let target = if i == j { mean_variance } else { 0.0 };
+                dispersion += (value - target).powi(2);
+            }
+        }
+        if dispersion <= 0.0 {
+            return Ok(0.0);
+        }
+        let mut noise = 0.0;
+        for row in &centered {
+            for i in 0..n {
+                for j in 0..n {
+                    noise += (row[i] * row[j] - sample[i][j]).powi(2);
+                }
+            }
+        }
+        noise /= periods * periods;
+        Ok((noise / dispersion).min(1.0))
+    }
+
+    fn centered(&self) -> GbResult<Vec<Vec<f64>>> {
+        if self.symbols.is_empty() {
+            return Err(GbError::Validation("no symbols to weight".to_string()));
+        }
+        if self.rows.len() < 2 {
+            return Err(GbError::Validation(format!(
+                "a covariance needs at least two periods of returns, got {}",
+                self.rows.len()
+            )));
+        }
+        let n = self.symbols.len();
+        let periods = self.rows.len() as f64;
+        let means: Vec<f64> = (0..n)
+            .map(|i| self.rows.iter().map(|row| row[i]).sum::<f64>() / periods)
+            .collect();
+        Ok(self
+            .rows
+            .iter()
+            .map(|row| row.iter().zip(&means).map(|(r, mean)| r - mean).collect())
+            .collect())
+    }
+}
+
+/// How far [`min_variance`] pulls the sample covariance toward a multiple
+/// of the identity.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Shrinkage {
+    /// Estimate the intensity from the returns.
+    #[default]
+    LedoitWolf,
+    /// A fixed intensity in `[0, 1]`; zero keeps the sample covariance.
+    Fixed(f64),
+}
+
+/// Bounds on each weight of a fully invested portfolio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightConstraints {
+    /// Largest weight of any one symbol, and with shorts allowed the
+    /// largest short weight too.
+    pub max_weight: f64,
+    pub long_only: bool,
+}
+
+impl Default for WeightConstraints {
+    fn default() -> Self {
+        Self {
+            max_weight: 1.0,
+            long_only: true,
+        }
+    }
+}
+
+impl WeightConstraints {
+    pub fn with_max_weight(mut self, max_weight: f64) -> Self {
+        self.max_weight = max_weight;
+        self
+    }
+
+    pub fn allow_shorts(mut self) -> Self {
+        self.long_only = false;
+        self
+    }
+
+    fn lower_bound(&self) -> f64 {
+        if self.long_only {
+            0.0
+        } else {
+            -self.max_weight
+        }
+    }
+
+    /// Fails unless `assets` weights within the bounds can sum to one.
+    fn check(&self, assets: usize) -> GbResult<()> {
+        if assets == 0 {
+            return Err(GbError::Validation("no symbols to weight".to_string()));
+        }
+        if !self.max_weight.is_finite() || self.max_weight <= 0.0 {
+            return Err(GbError::Validation(format!(
+                "max weight must be a positive number, got {}",
+                self.max_weight
+            )));
+        }
+        if self.max_weight * (assets as f64) < 1.0 - TOLERANCE {
+            return Err(GbError::Validation(format!(
+                "{assets} symbols capped at {} cannot be fully invested",
+                self.max_weight
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// One over the number of symbols each.
+pub fn equal_weight(history: &ReturnHistory, constraints: WeightConstraints) -> GbResult<Weights> {
+    let n = history.symbols.len();
+    constraints.check(n)?;
+    Ok(weights(history, cap(vec![1.0; n], constraints.max_weight)))
+}
+
+/// Weights proportional to one over each symbol's return volatility.
+pub fn inverse_volatility(
+    history: &ReturnHistory,
+    constraints: WeightConstraints,
+) -> GbResult<Weights> {
+    constraints.check(history.symbols.len())?;
+    let covariance = history.covariance()?;
+    let volatilities = volatilities(history, &covariance)?;
+    let raw = volatilities.iter().map(|vol| 1.0 / vol).collect();
+    Ok(weights(history, cap(raw, constraints.max_weight)))
+}
+
+/// Equal risk contribution weights: each symbol's weight times its
+/// covariance with the portfolio is the same. Solved by cyclical coordinate
+/// descent, which also converges for a singular covariance as long as no
+/// symbol is riskless.
+pub fn risk_parity(history: &ReturnHistory, constraints: WeightConstraints) -> GbResult<Weights> {
+    let n = history.symbols.len();
+    constraints.check(n)?;
+    let covariance = history.covariance()?;
+    let volatilities = volatilities(history, &covariance)?;
+
+    let budget = 1.0 / n as f64;
+    let mut raw: Vec<f64> = volatilities.iter().map(|vol| 1.0 / vol).collect();
+    for _ in 0..MAX_ITERATIONS {
+        let mut change: f64 = 0.0;
+        for i in 0..n {
+            let others: f64 = (0..n)
+                .filter(|&j| j != i)
+                .map(|j| covariance[i][j] * raw[j])
+                .sum();
+            let variance = covariance[i][i];
+            let next =
+                (-others + (others * others + 4.0 * variance * budget).sqrt()) / (2.0 * variance);
+            change = change.max((next - raw[i]).abs() / next);
+            raw[i] = next;
+        }
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    Ok(weights(history, cap(raw, constraints.max_weight)))
+}
+
+/// Weights with the lowest variance under the `shrinkage`-adjusted
+/// covariance, solved exactly within the constraints by projected gradient
+/// descent. Symbols interchangeable under a singular covariance share their
+/// weight equally.
+pub fn min_variance(
+    history: &ReturnHistory,
+    constraints: WeightConstraints,
+    shrinkage: Shrinkage,
+) -> GbResult<Weights> {
+    let n = history.symbols.len();
+    constraints.check(n)?;
+    let covariance = history.shrunk_covariance(shrinkage)?;
+    let (lower, upper) = (constraints.lower_bound(), constraints.max_weight);
+
+    // Gershgorin bound on the largest eigenvalue, the gradient's Lipschitz
+    // constant.
+    let lipschitz = covariance
+        .iter()
+        .map(|row| row.iter().map(|value| value.abs()).sum::<f64>())
+        .fold(0.0, f64::max);
+    let mut current = project(&vec![1.0 / n as f64; n], lower, upper);
+    if lipschitz <= 0.0 {
+        return Ok(weights(history, current));
+    }
+    let step = 1.0 / lipschitz;
+    for _ in 0..MAX_ITERATIONS {
+        let descended: Vec<f64> = (0..n)
+            .map(|i| {
+                let gradient: f64 = (0..n).map(|j| covariance[i][j] * current[j]).sum();
+                current[i] - step * gradient
+            })
+            .collect();
+        let next = project(&descended, lower, upper);
+        let change = next
+            .iter()
+            .zip(&current)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        current = next;
+        if change < TOLERANCE {
+            break;
+        }
+    }
+    Ok(weights(history, current))
+}
+
+fn volatilities(history: &ReturnHistory, covariance: &[Vec<f64>]) -> GbResult<Vec<f64>> {
+    covariance
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let volatility = row[i].sqrt();
+            if volatility > 0.0 {
+                Ok(volatility)
+            } else {
+                Err(GbError::Validation(format!(
+                    "{} has no return variance to scale by",
+                    history.symbols[i]
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Scale positive `raw` weights to sum to one, clipping any above `max`
+/// and handing the excess to the rest in proportion.
+fn cap(raw: Vec<f64>, max: f64) -> Vec<f64> {
+    let mut capped = vec![false; raw.len()];
+    let mut scaled = raw.clone();
+    loop {
+        let free: f64 = raw
+            .iter()
+            .zip(&capped)
+            .filter(|(_, capped)| !**capped)
+            .map(|(weight, _)| weight)
+            .sum();
+        let left = 1.0 - max * capped.iter().filter(|capped| **capped).count() as f64;
+        let mut clipped = false;
+        for i in 0..raw.len() {
+            if capped[i] {
+                scaled[i] = max;
+            } else {
+                scaled[i] = raw[i] / free * left;
+                if scaled[i] > max + TOLERANCE {
+                    capped[i] = true;
+                    clipped = true;
+                }
+            }
+        }
+        if !clipped || free <= 0.0 {
+            return scaled;
+        }
+    }
+}
+
+/// Euclidean projection of `point` onto the weights within
+/// `lower..=upper` that sum to one: shift every coordinate by the same
+/// amount, found by bisection, and clamp.
+fn project(point: &[f64], lower: f64, upper: f64) -> Vec<f64> {
+    let total = |shift: f64| -> f64 {
+        point
+            .iter()
+            .map(|value| (value - shift).clamp(lower, upper))
+            .sum()
+    };
+    let max = point.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min = point.iter().copied().fold(f64::INFINITY, f64::min);
+    let (mut low, mut high) = (min - upper, max - lower);
+    for _ in 0..200 {
+        let middle = 0.5 * (low + high);
+        if total(middle) > 1.0 {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    let shift = 0.5 * (low + high);
+    point
+        .iter()
+        .map(|value| (value - shift).clamp(lower, upper))
+        .collect()
+}
+
+fn weights(history: &ReturnHistory, values: Vec<f64>) -> Weights {
+    history.symbols.iter().cloned().zip(values).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Orthogonal, zero-mean ±1 columns: their sample covariance is exactly
+    /// diagonal.
+    const HADAMARD: [[f64; 3]; 4] = [
+        [1.0, 1.0, 1.0],
+        [-1.0, 1.0, -1.0],
+        [1.0, -1.0, -1.0],
+        [-1.0, -1.0, 1.0],
+    ];
+
+    fn symbols(n: usize) -> Vec<Symbol> {
+        ["A", "B", "C"][..n]
+            .iter()
+            .map(|ticker| Symbol::equity(ticker))
+            .collect()
+    }
+
+    /// Returns whose columns are the given combinations of the Hadamard
+    /// columns, scaled to daily-sized moves.
+    fn history(loadings: &[[f64; 3]]) -> ReturnHistory {
+        let rows = HADAMARD
+            .iter()
+            .map(|factors| {
+                loadings
+                    .iter()
+                    .map(|loading| {
+                        0.01 * loading.iter().zip(factors).map(|(l, f)| l * f).sum::<f64>()
+                    })
+                    .collect()
+            })
+            .collect();
+        ReturnHistory::new(symbols(loadings.len()), rows).unwrap()
+    }
+
+    fn assert_weights(weights: &Weights, expected: &[f64]) {
+        assert_eq!(weights.len(), expected.len());
+        for (symbol, expected) in symbols(expected.len()).iter().zip(expected) {
+            let got = weights[symbol];
+            assert!(
+                (got - expected).abs() < 1e-6,
+                "{symbol}: {got} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn uncorrelated_assets_match_the_closed_forms() {
+        // Volatilities 1 and 2, uncorrelated.
+        let history = history(&[[1.0, 0.0, 0.0], [0.0, 2.0, 0.0]]);
+        let constraints = WeightConstraints::default();
+
+        assert_weights(&equal_weight(&history, constraints).unwrap(), &[0.5, 0.5]);
+        // w ∝ 1/σ: 2/3 and 1/3.
+        let inverse = [2.0 / 3.0, 1.0 / 3.0];
+        assert_weights(
+            &inverse_volatility(&history, constraints).unwrap(),
+            &inverse,
+        );
+        // Without correlation, equal risk is inverse volatility.
+        assert_weights(&risk_parity(&history, constraints).unwrap(), &inverse);
+        // w ∝ 1/σ²: 4/5 and 1/5.
+        let min = min_variance(&history, constraints, Shrinkage::Fixed(0.0)).unwrap();
+        assert_weights(&min, &[0.8, 0.2]);
+
+        // Full shrinkage makes every asset look alike.
+        let shrunk = min_variance(&history, constraints, Shrinkage::Fixed(1.0)).unwrap();
+        assert_weights(&shrunk, &[0.5, 0.5]);
+        let estimated = min_variance(&history, constraints, Shrinkage::LedoitWolf).unwrap();
+        assert!(estimated[&Symbol::equity("A")] < 0.8);
+        assert!(estimated[&Symbol::equity("A")] >= 0.5);
+    }
+
+    #[test]
+    fn risk_parity_and_min_variance_handle_a_singular_covariance() {
+        // A and B are the same asset; C is independent with the same
+        // volatility. Equal risk needs 2w² = v² for A, B at w and C at v.
+        let history = history(&[[1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+        let constraints = WeightConstraints::default();
+
+        let pair = 1.0 / (2.0 + 2f64.sqrt());
+        let parity = risk_parity(&history, constraints).unwrap();
+        assert_weights(&parity, &[pair, pair, 2f64.sqrt() * pair]);
+
+        // Only A + B matters; the variance is lowest with half in C.
+        let min = min_variance(&history, constraints, Shrinkage::Fixed(0.0)).unwrap();
+        assert_weights(&min, &[0.25, 0.25, 0.5]);
+    }
+
+    #[test]
+    fn a_single_asset_takes_everything() {
+        let history = history(&[[1.0, 0.0, 0.0]]);
+        let constraints = WeightConstraints::default();
+        for weights in [
+            equal_weight(&history, constraints).unwrap(),
+            inverse_volatility(&history, constraints).unwrap(),
+            risk_parity(&history, constraints).unwrap(),
+            min_variance(&history, constraints, Shrinkage::LedoitWolf).unwrap(),
+        ] {
+            assert_weights(&weights, &[1.0]);
+        }
+    }
+
+    #[test]
+    fn constraints_bind_on_long_only_and_max_weight() {
+        // σ = 1 and 2 with correlation 0.9: the unconstrained minimum
+        // variance portfolio shorts B, w_A = (4 - 1.8) / (5 - 3.6).
+        let rho: f64 = 0.9;
+        let correlated = history(&[
+            [1.0, 0.0, 0.0],
+            [2.0 * rho, 2.0 * (1.0 - rho * rho).sqrt(), 0.0],
+        ]);
+        let unconstrained = WeightConstraints::default()
+            .with_max_weight(2.0)
+            .allow_shorts();
+        let a = 2.2 / 1.4;
+        let min = min_variance(&correlated, unconstrained, Shrinkage::Fixed(0.0)).unwrap();
+        assert_weights(&min, &[a, 1.0 - a]);
+
+        let long_only = WeightConstraints::default();
+        let min = min_variance(&correlated, long_only, Shrinkage::Fixed(0.0)).unwrap();
+        assert_weights(&min, &[1.0, 0.0]);
+
+        let capped = long_only.with_max_weight(0.8);
+        let min = min_variance(&correlated, capped, Shrinkage::Fixed(0.0)).unwrap();
+        assert_weights(&min, &[0.8, 0.2]);
+
+        // Inverse volatility gives A 2/3 of three assets with σ = 1, 4, 4;
+        // capped at a half, its excess is split between B and C.
+        let three = history(&[[1.0, 0.0, 0.0], [0.0, 4.0, 0.0], [0.0, 0.0, 4.0]]);
+        let capped = WeightConstraints::default().with_max_weight(0.5);
+        let inverse = inverse_volatility(&three, capped).unwrap();
+        assert_weights(&inverse, &[0.5, 0.25, 0.25]);
+
+        let error =
+            equal_weight(&three, WeightConstraints::default().with_max_weight(0.3)).unwrap_err();
+        assert!(matches!(error, GbError::Validation(_)), "{error}");
+    }
+
+    #[test]
+    fn returns_from_a_matrix_start_once_every_symbol_has_a_close() {
+        use chrono::{TimeZone, Utc};
+        use gb_types::{Bar, Resolution};
+        use rust_decimal::Decimal;
+
+        let bar = |ticker: &str, day: u32, close: i64| {
+            let close = Decimal::from(close);
+            Bar::new(
+                Symbol::equity(ticker),
+                Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+                close,
+                close,
+                close,
+                close,
+                Decimal::from(1_000),
+                Resolution::Day,
+            )
+        };
+        let prices = BarMatrix::from_bars(&[
+            bar("A", 2, 100),
+            bar("A", 3, 110),
+            bar("B", 3, 50),
+            bar("A", 4, 99),
+            bar("B", 4, 55),
+        ]);
+        let history = ReturnHistory::from_matrix(&prices);
+        assert_eq!(history.symbols(), &symbols(2)[..]);
+        assert_eq!(history.len(), 1);
+        assert!((history.rows[0][0] + 0.1).abs() < 1e-12);
+        assert!((history.rows[0][1] - 0.1).abs() < 1e-12);
+    }
+
+    #[test]
+    fn riskless_assets_and_short_histories_are_rejected() {
+        let flat = ReturnHistory::new(symbols(2), vec![vec![0.01, 0.0], vec![-0.01, 0.0]]).unwrap();
+        let constraints = WeightConstraints::default();
+        assert!(inverse_volatility(&flat, constraints).is_err());
+        assert!(risk_parity(&flat, constraints).is_err());
+        // Minimum variance is happy to hold the riskless asset.
+        let min = min_variance(&flat, constraints, Shrinkage::Fixed(0.0)).unwrap();
+        assert_weights(&min, &[0.0, 1.0]);
+
+        let short = ReturnHistory::new(symbols(2), vec![vec![0.01, 0.02]]).unwrap();
+        assert!(min_variance(&short, constraints, Shrinkage::LedoitWolf).is_err());
+        assert!(ReturnHistory::new(symbols(2), vec![vec![0.01]]).is_err());
+    }
+}
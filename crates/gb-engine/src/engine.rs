@@ -1,24 +1,87 @@
 // Core backtesting engine - enhanced implementation
 // Provides event-driven backtesting with realistic execution
 
-use chrono::{DateTime, Duration, Utc};
-use gb_data::DataManager;
-use gb_options::{black_scholes_price, simulate_open, OptionContract, OptionKind, PricingInput};
+use chrono::{DateTime, Datelike, Duration, Utc};
+use gb_data::{occ_contract_id, DataManager, OptionChainSnapshot, OptionQuote};
+use gb_options::{
+    black_scholes_price, historical_vol, simulate_open, OptionContract, OptionKind,
+    OptionsFillModel, PricingInput, PricingResult, VolEstimator,
+};
 use gb_types::{
-    BacktestConfig, BacktestError, BacktestResult, Bar, CoveredCallOrder, DataQualityMode,
-    DataValidationSummary, EquityCurvePoint, Fill, GbResult, LatencyModel, MarketDataBuffer,
-    MarketEvent, Order, OrderEvent, OrderStatus, OrderType, Portfolio, ReplayRequestManifest,
-    RunDatasetManifest, RunEngineManifest, RunExecutionManifest, RunManifest, RunMetricSnapshot,
-    RunStrategyManifest, Side, SlippageModel, Strategy, StrategyContext, StrategyMetrics, Symbol,
-    TimeInForce, TradeRecord,
+    AccountExposure, BacktestConfig, BacktestError, BacktestEvent, BacktestResult, Bar,
+    BarDelivery, BuyingPowerAccount, CashFlowEvent, CashFlowKind, CoveredCallOrder,
+    DailyReturnRecorder, DataQualityMode, DataValidationSummary, EquityCurvePoint, EventOrdering,
+    ExecutionReport, Fill, FillExecution, GbResult, GreeksExposure, LatencyModel, MarketDataBuffer,
+    MarketEvent, OptionOrder, OptionSettlement, Order, OrderEvent, OrderId, OrderStatus, OrderType,
+    Portfolio, PositionHolding, PositionsSnapshot, ProfilePhase, Profiler, ReplayRequestManifest,
+    Resolution, RunDatasetManifest, RunEngineManifest, RunExecutionManifest, RunManifest,
+    RunMetricSnapshot, RunStrategyManifest, Side, SlippageModel, SnapshotCadence, StalenessPolicy,
+    Strategy, StrategyContext, StrategyMetrics, Symbol, TimeInForce, TradeLedger, TradeRecord,
 };
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::Serialize;
-use std::collections::HashMap;
-use tracing::{debug, info, warn};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn, Instrument, Span};
+
+use crate::telemetry;
+
+use crate::algo::slice_quantity;
+use crate::lookahead::LookaheadGuard;
+use crate::simulator::{SessionCalendar, SessionResolver};
 
 const STRATEGY_MARKET_DATA_WINDOW: usize = 100;
+/// Events a lagging [`Engine::subscribe`] receiver can fall behind by before
+/// it starts skipping.
+pub const EVENT_CHANNEL_CAPACITY: usize = 1024;
+/// Bars of underlying history behind the volatility estimate used for option
+/// orders that give no implied volatility.
+const HISTORICAL_VOL_WINDOW: usize = 20;
+
+/// Cloneable flag that stops a running backtest.
+///
+/// Cancellation is cooperative: the engine checks it before each simulated
+/// day and ends the run with [`BacktestError::Canceled`].
+#[derive(Debug, Clone, Default)]
+pub struct CancellationHandle(Arc<AtomicBool>);
+
+impl CancellationHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Clear a cancellation so the handle can stop a later run.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Whether a snapshot is due at `now`, given that the engine steps one day
+/// at a time until `end`: weekly and monthly snapshots fall on the last step
+/// of the week or month.
+fn snapshot_due(cadence: SnapshotCadence, now: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+    let next = now + Duration::days(1);
+    let last_step = next > end;
+    match cadence {
+        SnapshotCadence::Off => false,
+        SnapshotCadence::Daily => true,
+        SnapshotCadence::Weekly => last_step || next.iso_week() != now.iso_week(),
+        SnapshotCadence::Monthly => last_step || next.month() != now.month(),
+    }
+}
 
 fn decimal_to_f64(value: Decimal) -> f64 {
     value.to_f64().unwrap_or(0.0)
@@ -28,6 +91,8 @@ enum ExecutionDecision {
     Pending,
     Fill {
         fill_quantity: Decimal,
+        /// Price the fill was based on, before slippage.
+        arrival_price: Decimal,
         execution_price: Decimal,
         keep_open: bool,
         remainder_event: Option<OrderEvent>,
@@ -105,6 +170,18 @@ struct OpenCoveredCallPosition {
     strategy_id: String,
 }
 
+/// An option position opened through `StrategyAction::TradeOption`; its
+/// quantity lives in the portfolio under `symbol`.
+#[derive(Debug, Clone)]
+struct OpenOptionPosition {
+    symbol: Symbol,
+    contract: OptionContract,
+    volatility: f64,
+    risk_free_rate: f64,
+    dividend_yield: f64,
+    strategy_id: String,
+}
+
 fn contract_symbol(contract: &OptionContract) -> String {
     format!(
         "{}-{}-{}C",
@@ -140,6 +217,21 @@ fn execution_latency_ms(model: &LatencyModel) -> Option<u64> {
     }
 }
 
+/// Index the in-session events of `market_data`, bars of `resolution`, for
+/// [`StrategyContext::session`].
+fn session_calendar(
+    sessions: &SessionResolver,
+    market_data: &HashMap<Symbol, Vec<Bar>>,
+    resolution: Resolution,
+) -> SessionCalendar {
+    SessionCalendar::build(
+        sessions,
+        market_data
+            .iter()
+            .map(|(symbol, bars)| (symbol, resolution, bars.iter().map(|bar| bar.timestamp))),
+    )
+}
+
 /// Enhanced backtesting engine with event-driven simulation
 pub struct Engine {
     config: BacktestConfig,
@@ -148,18 +240,54 @@ pub struct Engine {
     current_time: DateTime<Utc>,
     market_data: HashMap<Symbol, Vec<Bar>>,
     next_bar_indices: HashMap<Symbol, usize>,
+    /// Consecutive sessions each symbol has gone without a bar since its
+    /// first one.
+    missed_bars: HashMap<Symbol, u32>,
     current_market_bars: Vec<(Symbol, Bar)>,
+    /// Locates each bar in its trading session for the strategy context.
+    session_calendar: SessionCalendar,
     pending_orders: Vec<Order>,
     strategy_context: StrategyContext,
     strategy_metrics: StrategyMetrics,
     equity_curve: Vec<EquityCurvePoint>,
     trade_log: Vec<TradeRecord>,
     order_events: Vec<OrderEvent>,
+    positions_history: Vec<PositionsSnapshot>,
+    /// Close of the order's symbol when each pending order was placed.
+    decision_prices: HashMap<OrderId, Decimal>,
+    /// Index of the first bar each pending algo order could execute on.
+    algo_windows: HashMap<OrderId, usize>,
+    execution_report: ExecutionReport,
     option_trades: Vec<CoveredCallTradeRecord>,
     option_events: Vec<OptionLifecycleEvent>,
     open_covered_calls: Vec<OpenCoveredCallPosition>,
+    open_options: Vec<OpenOptionPosition>,
+    /// Stored chain snapshots per underlying, oldest first, used to mark
+    /// option positions when a quote for the contract is available.
+    option_chains: HashMap<Symbol, Vec<OptionChainSnapshot>>,
+    /// Fills `TradeOption` orders against bid/ask markets when set; otherwise
+    /// they fill at the mark.
+    option_fill_model: Option<OptionsFillModel>,
+    /// Benchmark bars whose closes become the result's benchmark curve.
+    benchmark: Vec<Bar>,
     equity_peak: Decimal,
+    /// Deposits and withdrawals still to apply, in date order.
+    pending_cash_flows: VecDeque<CashFlowEvent>,
+    /// Net cash flow applied in the current step.
+    step_cash_flow: Decimal,
+    /// Open lots behind the trade log's realized PnL and entry tags.
+    trade_ledger: TradeLedger,
+    /// Settlement and margin state under
+    /// [`ExecutionSettings::buying_power`](gb_types::ExecutionSettings::buying_power).
+    buying_power: Option<BuyingPowerAccount>,
     data_validation_summaries: HashMap<String, DataValidationSummary>,
+    cancellation: CancellationHandle,
+    events: broadcast::Sender<BacktestEvent>,
+    /// Times each phase under [`DataSettings::profile`](gb_types::DataSettings::profile).
+    profiler: Profiler,
+    /// Span carrying the run's backtest and strategy ids, entered while
+    /// loading data and running.
+    span: Span,
 }
 
 impl Engine {
@@ -168,12 +296,25 @@ impl Engine {
         config: BacktestConfig,
         data_manager: &mut DataManager,
         strategy: Box<dyn Strategy>,
+    ) -> GbResult<Self> {
+        let span = telemetry::backtest_span(config.id, &strategy.get_config().strategy_id);
+        Self::load(config, data_manager, strategy, span.clone())
+            .instrument(span)
+            .await
+    }
+
+    async fn load(
+        config: BacktestConfig,
+        data_manager: &mut DataManager,
+        strategy: Box<dyn Strategy>,
+        span: Span,
     ) -> GbResult<Self> {
         info!("Creating enhanced backtesting engine");
 
         let portfolio = Portfolio::new("backtest_portfolio".to_string(), config.initial_capital);
 
         let strategy_metrics = StrategyMetrics::new(strategy.get_config().strategy_id.clone());
+        let mut profiler = Profiler::new(config.data_settings.profile);
 
         // Load market data for all symbols
         let mut market_data = HashMap::new();
@@ -181,15 +322,17 @@ impl Engine {
         let mut load_failures = Vec::new();
         let mut data_quality_failures = Vec::new();
         for symbol in &config.symbols {
-            match data_manager
+            let mark = profiler.start();
+            let loaded = data_manager
                 .load_data(
                     symbol,
                     config.start_date,
                     config.end_date,
                     config.resolution,
                 )
-                .await
-            {
+                .await;
+            profiler.record_symbol(ProfilePhase::DataLoading, symbol, mark);
+            match loaded {
                 Ok(bars) if !bars.is_empty() => {
                     info!("Loaded {} bars for {}", bars.len(), symbol);
 
@@ -263,12 +406,40 @@ impl Engine {
             .into());
         }
 
+        let mut option_chains = HashMap::new();
+        for symbol in market_data.keys() {
+            let mark = profiler.start();
+            let quote_dates = data_manager.storage.list_option_quote_dates(symbol)?;
+            let mut snapshots: Vec<OptionChainSnapshot> = Vec::new();
+            for date in quote_dates.keys() {
+                if *date < config.start_date.date_naive() || *date > config.end_date.date_naive() {
+                    continue;
+                }
+                let end_of_day = date.and_hms_opt(23, 59, 59).unwrap_or_default().and_utc();
+                if let Some(snapshot) = data_manager.load_option_chain(symbol, end_of_day).await? {
+                    if snapshots.last().map(|last| last.timestamp) != Some(snapshot.timestamp) {
+                        snapshots.push(snapshot);
+                    }
+                }
+            }
+            profiler.record_symbol(ProfilePhase::DataLoading, symbol, mark);
+            if !snapshots.is_empty() {
+                info!(
+                    "Loaded {} option chain snapshots for {}",
+                    snapshots.len(),
+                    symbol
+                );
+                option_chains.insert(symbol.clone(), snapshots);
+            }
+        }
+
         let mut strategy_context = StrategyContext::new(
             strategy.get_config().strategy_id.clone(),
             config.initial_capital,
         );
         strategy_context.current_time = config.start_date;
         strategy_context.portfolio = portfolio.clone();
+        strategy_context.quantity_policy = config.execution_settings.quantity_policy.clone();
         for symbol in market_data.keys() {
             strategy_context.market_data.insert(
                 symbol.clone(),
@@ -276,6 +447,9 @@ impl Engine {
             );
         }
 
+        let session_calendar =
+            session_calendar(&SessionResolver::default(), &market_data, config.resolution);
+
         Ok(Self {
             current_time: config.start_date,
             equity_peak: config.initial_capital,
@@ -284,7 +458,13 @@ impl Engine {
                 .cloned()
                 .map(|symbol| (symbol, 0))
                 .collect(),
+            missed_bars: HashMap::new(),
             current_market_bars: Vec::new(),
+            session_calendar,
+            pending_cash_flows: VecDeque::new(),
+            step_cash_flow: Decimal::ZERO,
+            trade_ledger: TradeLedger::new(),
+            buying_power: None,
             config,
             portfolio,
             strategy,
@@ -295,15 +475,110 @@ impl Engine {
             equity_curve: Vec::new(),
             trade_log: Vec::new(),
             order_events: Vec::new(),
+            positions_history: Vec::new(),
+            decision_prices: HashMap::new(),
+            algo_windows: HashMap::new(),
+            execution_report: ExecutionReport::default(),
             option_trades: Vec::new(),
             option_events: Vec::new(),
             open_covered_calls: Vec::new(),
+            open_options: Vec::new(),
+            option_chains,
+            option_fill_model: None,
+            benchmark: Vec::new(),
             data_validation_summaries,
+            cancellation: CancellationHandle::new(),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            profiler,
+            span,
         })
     }
 
-    /// Run the complete backtesting simulation
+    /// Stop the run when `handle` is cancelled.
+    pub fn with_cancellation(mut self, handle: CancellationHandle) -> Self {
+        self.cancellation = handle;
+        self
+    }
+
+    /// Fill `TradeOption` orders against the stored chain's bid/ask, or a
+    /// quote synthesized by `model` when there is none, instead of at the
+    /// mark.
+    pub fn with_option_fill_model(mut self, model: OptionsFillModel) -> Self {
+        self.option_fill_model = Some(model);
+        self
+    }
+
+    /// Compare the run against `bars` of a benchmark, e.g. loaded with
+    /// [`DataManager::load_data`]: the result gets the benchmark's equity
+    /// curve and the daily excess returns, aligned with its own curve as
+    /// described in [`gb_types::benchmark`].
+    pub fn with_benchmark(mut self, bars: Vec<Bar>) -> Self {
+        self.benchmark = bars;
+        self
+    }
+
+    /// Locate bars in their trading sessions for [`StrategyContext::session`]
+    /// under `sessions` rather than each asset class's default calendar.
+    pub fn with_sessions(mut self, sessions: SessionResolver) -> Self {
+        self.session_calendar =
+            session_calendar(&sessions, &self.market_data, self.config.resolution);
+        self
+    }
+
+    pub fn cancellation_handle(&self) -> CancellationHandle {
+        self.cancellation.clone()
+    }
+
+    /// Subscribe to the [`BacktestEvent`]s of runs started from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<BacktestEvent> {
+        self.events.subscribe()
+    }
+
+    /// Publish events on `sender` instead of the engine's own channel.
+    pub fn with_event_sender(mut self, sender: broadcast::Sender<BacktestEvent>) -> Self {
+        self.events = sender;
+        self
+    }
+
+    /// Send an event if anyone is listening; `event` is only built then.
+    fn emit(&self, event: impl FnOnce() -> BacktestEvent) {
+        if self.events.receiver_count() > 0 {
+            let _ = self.events.send(event());
+        }
+    }
+
+    fn record_trade(&mut self, trade: TradeRecord) {
+        self.emit(|| BacktestEvent::TradeExecuted {
+            backtest_id: self.config.id,
+            trade: trade.clone(),
+        });
+        self.trade_log.push(trade);
+    }
+
+    /// Run the complete backtesting simulation, publishing `Started`, then
+    /// `Progress`, `EquityUpdate`, `TradeExecuted` and `PositionsSnapshot` as
+    /// it goes, and finally `Completed` or `Failed`.
     pub async fn run(&mut self) -> GbResult<BacktestResult> {
+        self.emit(|| BacktestEvent::Started {
+            backtest_id: self.config.id,
+            config: self.config.clone(),
+        });
+        let span = self.span.clone();
+        let outcome = self.simulate().instrument(span).await;
+        match &outcome {
+            Ok(result) => self.emit(|| BacktestEvent::Completed {
+                backtest_id: self.config.id,
+                result: result.clone(),
+            }),
+            Err(error) => self.emit(|| BacktestEvent::Failed {
+                backtest_id: self.config.id,
+                error: error.to_string(),
+            }),
+        }
+        outcome
+    }
+
+    async fn simulate(&mut self) -> GbResult<BacktestResult> {
         info!("Starting enhanced backtesting simulation");
 
         let mut result = BacktestResult::new(self.config.clone());
@@ -331,15 +606,46 @@ impl Engine {
 
         // Main simulation loop
         self.current_time = self.config.start_date;
+        self.pending_cash_flows = self
+            .config
+            .cash_flows
+            .events_between(self.config.start_date, self.config.end_date)
+            .into();
+        self.buying_power = self
+            .config
+            .execution_settings
+            .buying_power
+            .clone()
+            .map(BuyingPowerAccount::new);
 
         while self.current_time <= self.config.end_date {
+            if self.cancellation.is_cancelled() {
+                info!("Backtest {} cancelled", self.config.id);
+                return Err(BacktestError::Canceled {
+                    backtest_id: self.config.id.to_string(),
+                }
+                .into());
+            }
             debug!("Processing time: {}", self.current_time);
+            let step = self.profiler.start();
+
+            // Deposits and withdrawals land before the strategy trades
+            self.apply_cash_flows();
 
             // 1. Process market data for current time
             self.process_market_data().await?;
 
+            // On open, the strategy trades yesterday's bar before today's
+            // orders execute, so market orders fill at today's open
+            let on_open = self.config.execution_settings.bar_delivery == BarDelivery::OnOpen;
+            if on_open {
+                self.generate_strategy_signals().await?;
+            }
+
             // 2. Execute pending orders
+            let fills = self.profiler.start();
             self.execute_pending_orders().await?;
+            self.profiler.record(ProfilePhase::Fills, fills);
 
             // 3. Update portfolio with current market prices
             self.update_portfolio_values().await?;
@@ -348,7 +654,9 @@ impl Engine {
             self.process_option_lifecycle().await?;
 
             // 5. Generate strategy signals
-            self.generate_strategy_signals().await?;
+            if !on_open {
+                self.generate_strategy_signals().await?;
+            }
 
             // 6. Call strategy's on_day_end for end-of-day processing
             self.call_strategy_day_end().await?;
@@ -356,6 +664,16 @@ impl Engine {
             // 7. Update daily returns
             self.update_daily_returns().await?;
 
+            // 8. Snapshot positions now that every fill for the day is in
+            self.record_positions_snapshot();
+
+            self.emit(|| BacktestEvent::Progress {
+                backtest_id: self.config.id,
+                progress_pct: self.progress_pct(),
+                current_date: self.current_time,
+            });
+            self.profiler.record(ProfilePhase::Stepping, step);
+
             // Advance time
             self.current_time += Duration::days(1);
         }
@@ -370,12 +688,28 @@ impl Engine {
         Ok(result)
     }
 
-    /// Process market data for the current time
+    /// Share of the backtest window simulated so far, in percent.
+    fn progress_pct(&self) -> f64 {
+        let total = (self.config.end_date - self.config.start_date).num_seconds();
+        if total <= 0 {
+            return 100.0;
+        }
+        let done = (self.current_time - self.config.start_date).num_seconds();
+        (done as f64 / total as f64 * 100.0).clamp(0.0, 100.0)
+    }
+
+    /// Process market data for the current time, delivering the bars that
+    /// have closed by the step's clock under the configured [`BarDelivery`].
+    ///
+    /// [`BarDelivery`]: gb_types::BarDelivery
     async fn process_market_data(&mut self) -> GbResult<()> {
         self.strategy_context.current_time = self.current_time;
+        self.strategy_context.session = None;
         self.current_market_bars.clear();
 
-        let current_date = self.current_time.date_naive();
+        let guard = self.lookahead_guard();
+        let clock = guard.clock(self.current_time.date_naive());
+        let current_date = guard.delivered_date(self.current_time.date_naive());
         for symbol in self.config.symbols.clone() {
             let Some(bars) = self.market_data.get(&symbol) else {
                 continue;
@@ -392,6 +726,7 @@ impl Engine {
                     break;
                 }
 
+                guard.check_bar(&symbol, bar, clock)?;
                 self.current_market_bars.push((symbol.clone(), bar.clone()));
                 *next_index += 1;
             }
@@ -423,6 +758,11 @@ impl Engine {
         let pending_orders = std::mem::take(&mut self.pending_orders);
 
         for mut order in pending_orders {
+            if order.execution_algo.is_some() {
+                if let Some(index) = self.execution_index(&order.symbol) {
+                    self.algo_windows.entry(order.id).or_insert(index);
+                }
+            }
             match self.try_execute_order(&order, &mut remaining_liquidity)? {
                 ExecutionDecision::Pending => {
                     next_pending_orders.push(order);
@@ -438,6 +778,7 @@ impl Engine {
                 }
                 ExecutionDecision::Fill {
                     fill_quantity,
+                    arrival_price,
                     execution_price,
                     keep_open,
                     remainder_event,
@@ -451,24 +792,64 @@ impl Engine {
                         execution_price,
                         commission,
                         order.strategy_id.clone(),
-                    );
+                    )
+                    .with_order_annotations(&order);
                     fill.executed_at = self.current_time;
+                    self.lookahead_guard().check_fill(&order, &fill)?;
 
                     order.fill(fill_quantity, execution_price);
 
+                    if let Some(account) = &mut self.buying_power {
+                        let multiplier = self.portfolio.contract_multiplier(&fill.symbol);
+                        account.record_fill(
+                            fill.side,
+                            fill.quantity * fill.price * multiplier - fill.commission,
+                            fill.executed_at.date_naive(),
+                        );
+                    }
                     self.portfolio.apply_fill(&fill);
+
+                    self.debug_check_portfolio();
+                    let execution = FillExecution {
+                        symbol: fill.symbol.clone(),
+                        side: fill.side,
+                        quantity: fill.quantity,
+                        decision_price: self
+                            .decision_prices
+                            .get(&order.id)
+                            .copied()
+                            .unwrap_or(arrival_price),
+                        arrival_price,
+                        // The backtest does not model market impact yet.
+                        impact_price: arrival_price,
+                        fill_price: fill.price,
+                        commission: fill.commission,
+                    };
+                    self.execution_report.record(&execution);
+                    if let Some(algo) = &order.execution_algo {
+                        self.execution_report
+                            .record_algo_fill(order.id, algo, &execution);
+                    }
                     self.strategy_metrics.total_trades += 1;
-                    self.trade_log
-                        .push(self.trade_record_from_fill(&order, &fill));
+                    let trade_record = self.trade_record_from_fill(&order, &fill);
+                    self.record_trade(trade_record);
 
                     info!(
                         "Executed order: {:?} {} {} at {} (commission {})",
                         order.side, fill_quantity, order.symbol, fill.price, fill.commission
                     );
 
-                    order_events_to_process.push(OrderEvent::OrderFilled {
-                        order_id: order.id,
-                        fill,
+                    order_events_to_process.push(if order.remaining_quantity.is_zero() {
+                        OrderEvent::OrderFilled {
+                            order_id: order.id,
+                            fill,
+                        }
+                    } else {
+                        OrderEvent::OrderPartiallyFilled {
+                            order_id: order.id,
+                            fill,
+                            remaining_quantity: order.remaining_quantity,
+                        }
                     });
 
                     if let Some(event) = remainder_event {
@@ -490,9 +871,28 @@ impl Engine {
         }
 
         self.pending_orders = next_pending_orders;
+        let pending_orders = &self.pending_orders;
+        self.decision_prices
+            .retain(|order_id, _| pending_orders.iter().any(|order| order.id == *order_id));
+        self.algo_windows
+            .retain(|order_id, _| pending_orders.iter().any(|order| order.id == *order_id));
         self.record_order_events(order_events_to_process)
     }
 
+    /// Index of the bar of `symbol` an order placed now executes on.
+    fn execution_index(&self, symbol: &Symbol) -> Option<usize> {
+        let bars = self.market_data.get(symbol)?;
+        let current_index = bars
+            .iter()
+            .position(|bar| bar.timestamp.date_naive() == self.current_time.date_naive())?;
+        let execution_index = current_index.saturating_add(self.latency_bar_offset());
+        (execution_index < bars.len()).then_some(execution_index)
+    }
+
+    fn lookahead_guard(&self) -> LookaheadGuard {
+        LookaheadGuard::new(self.config.execution_settings.bar_delivery)
+    }
+
     fn latency_bar_offset(&self) -> usize {
         let latency_ms = match &self.config.execution_settings.latency_model {
             LatencyModel::None => return 0,
@@ -510,7 +910,12 @@ impl Engine {
             return 0;
         }
 
-        latency_ms.div_ceil(bar_ms) as usize
+        // Orders placed at the open reach the market within the same bar
+        // unless the latency outlasts it.
+        match self.config.execution_settings.bar_delivery {
+            BarDelivery::OnClose => latency_ms.div_ceil(bar_ms) as usize,
+            BarDelivery::OnOpen => (latency_ms / bar_ms) as usize,
+        }
     }
 
     fn apply_slippage(&self, base_price: Decimal, side: Side) -> Decimal {
@@ -551,7 +956,22 @@ impl Engine {
         }
     }
 
-    fn trade_record_from_fill(&self, order: &Order, fill: &Fill) -> TradeRecord {
+    /// One trade log record per fill. A fill that closes earlier entries
+    /// carries their realized PnL and the tags they were entered with, so
+    /// per-tag attribution credits the exit to the entry's tags.
+    fn trade_record_from_fill(&mut self, order: &Order, fill: &Fill) -> TradeRecord {
+        let multiplier = self.portfolio.contract_multiplier(&fill.symbol);
+        let close = self.trade_ledger.record(fill, multiplier);
+        let mut tags = vec!["fill".to_string()];
+        for tag in order
+            .tags
+            .iter()
+            .chain(close.iter().flat_map(|close| &close.entry_tags))
+        {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
         TradeRecord {
             id: order.id,
             symbol: fill.symbol.clone(),
@@ -561,11 +981,12 @@ impl Engine {
             exit_price: Some(fill.price),
             quantity: fill.quantity,
             side: fill.side,
-            pnl: None,
+            pnl: close.map(|close| close.realized_pnl),
             commission: fill.commission,
             duration_hours: Some(0.0),
             strategy_id: fill.strategy_id.clone(),
-            tags: vec!["fill".to_string()],
+            tags,
+            metadata: order.metadata.clone(),
         }
     }
 
@@ -602,6 +1023,18 @@ impl Engine {
     }
 
     fn base_execution_price(&self, order: &Order, bar: &Bar) -> Option<Decimal> {
+        // Algo slices trade at the bar's close.
+        if order.execution_algo.is_some() {
+            return match order.order_type {
+                OrderType::Market => Some(bar.close),
+                OrderType::Limit { price } => match order.side {
+                    Side::Buy if bar.close <= price => Some(bar.close),
+                    Side::Sell if bar.close >= price => Some(bar.close),
+                    _ => None,
+                },
+                _ => None,
+            };
+        }
         match order.order_type {
             OrderType::Market => Some(bar.open),
             OrderType::Limit { price } => match order.side {
@@ -631,6 +1064,8 @@ impl Engine {
                     _ => None,
                 }
             }
+            // Rejected up front in `try_execute_order`.
+            OrderType::TrailingStop { .. } => None,
         }
     }
 
@@ -643,10 +1078,12 @@ impl Engine {
         self.sync_strategy_context_account_state();
 
         for order_event in order_events {
-            let actions = match self
+            let mark = self.profiler.start();
+            let actions = self
                 .strategy
-                .on_order_event(&order_event, &self.strategy_context)
-            {
+                .on_order_event(&order_event, &self.strategy_context);
+            self.profiler.record(ProfilePhase::OnOrderEvent, mark);
+            let actions = match actions {
                 Ok(actions) => actions,
                 Err(e) => {
                     warn!("Strategy on_order_event error: {}", e);
@@ -667,6 +1104,13 @@ impl Engine {
         order: &Order,
         remaining_liquidity: &mut HashMap<(Symbol, usize), Decimal>,
     ) -> GbResult<ExecutionDecision> {
+        if matches!(order.order_type, OrderType::TrailingStop { .. }) {
+            return Ok(ExecutionDecision::Terminal(OrderEvent::OrderRejected {
+                order_id: order.id,
+                reason: "trailing stop orders are not supported by the backtest engine".to_string(),
+            }));
+        }
+
         let Some(bars) = self.market_data.get(&order.symbol) else {
             return Ok(ExecutionDecision::Terminal(OrderEvent::OrderRejected {
                 order_id: order.id,
@@ -708,12 +1152,23 @@ impl Engine {
             };
         };
 
+        let slice = match (&order.execution_algo, self.algo_windows.get(&order.id)) {
+            (Some(algo), Some(&start)) => slice_quantity(
+                algo,
+                order.remaining_quantity,
+                &bars[start.min(execution_index)..=execution_index],
+            ),
+            _ => order.remaining_quantity,
+        };
         let available_liquidity = remaining_liquidity
             .entry((order.symbol.clone(), execution_index))
             .or_insert_with(|| self.execution_liquidity_cap(bar));
-        let fill_quantity = order
-            .remaining_quantity
-            .min((*available_liquidity).max(Decimal::ZERO));
+        let fill_quantity = self
+            .config
+            .execution_settings
+            .quantity_policy
+            .rule_for(&order.symbol)
+            .round_down(slice.min((*available_liquidity).max(Decimal::ZERO)));
 
         if matches!(order.time_in_force, TimeInForce::FOK)
             && fill_quantity < order.remaining_quantity
@@ -763,12 +1218,44 @@ impl Engine {
 
         Ok(ExecutionDecision::Fill {
             fill_quantity,
+            arrival_price: base_price,
             execution_price,
             keep_open: remainder_quantity > Decimal::ZERO && remainder_event.is_none(),
             remainder_event,
         })
     }
 
+    /// Check `order` against the configured buying-power model at the
+    /// current price. Orders are let through when no price is known yet.
+    fn check_buying_power(&self, order: &Order) -> Result<(), String> {
+        let Some(account) = &self.buying_power else {
+            return Ok(());
+        };
+        let Some(price) = self.current_price_for_symbol(&order.symbol) else {
+            return Ok(());
+        };
+        let exposure = AccountExposure {
+            cash: self.portfolio.cash,
+            equity: self.portfolio.total_equity,
+            gross_exposure: self.gross_exposure(),
+            position_value: self
+                .portfolio
+                .positions
+                .get(&order.symbol)
+                .map_or(Decimal::ZERO, |position| position.market_value),
+        };
+        let notional =
+            order.remaining_quantity * price * self.portfolio.contract_multiplier(&order.symbol);
+        account
+            .check_order(
+                &exposure,
+                order.side,
+                notional,
+                self.current_time.date_naive(),
+            )
+            .map_err(|e| format!("insufficient buying power: {e}"))
+    }
+
     fn current_price_for_symbol(&self, symbol: &Symbol) -> Option<Decimal> {
         self.current_market_bars
             .iter()
@@ -829,10 +1316,12 @@ impl Engine {
         })?;
         trade.executed_at = self.current_time;
 
+        // Commission is booked on its own, so realized P&L takes the gross
+        // premium.
         let net_premium = trade.cash_flow();
         self.portfolio.apply_cash_adjustment(
             net_premium,
-            net_premium,
+            net_premium + trade.commission,
             trade.commission,
             self.current_time,
         );
@@ -892,32 +1381,553 @@ impl Engine {
         Ok(())
     }
 
-    async fn process_option_lifecycle(&mut self) -> GbResult<()> {
-        if self.open_covered_calls.is_empty() {
+    fn trade_option(&mut self, order: OptionOrder) -> GbResult<()> {
+        let strategy_id = self.strategy.get_config().strategy_id.clone();
+        let mut contract = OptionContract::equity(
+            order.underlying.clone(),
+            order.kind,
+            order.strike,
+            order.expiration,
+        );
+        contract.multiplier = order.multiplier;
+        let symbol = Symbol::option(&occ_contract_id(
+            &order.underlying.symbol,
+            order.expiration.date_naive(),
+            order.kind,
+            order.strike,
+        ));
+        let option_order = Order::market_order(
+            symbol.clone(),
+            order.side,
+            order.contracts,
+            strategy_id.clone(),
+        );
+
+        let rejection = if order.contracts <= Decimal::ZERO {
+            Some("option contracts must be positive".to_string())
+        } else if order.multiplier <= Decimal::ZERO {
+            Some("option multiplier must be positive".to_string())
+        } else if contract.is_expired(self.current_time) {
+            Some(format!("{} has already expired", symbol))
+        } else if !self.market_data.contains_key(&order.underlying) {
+            Some(format!(
+                "no market data configured for {}",
+                order.underlying
+            ))
+        } else {
+            None
+        };
+        if let Some(reason) = rejection {
+            return self.record_order_events(vec![OrderEvent::OrderRejected {
+                order_id: option_order.id,
+                reason,
+            }]);
+        }
+
+        let Some(spot) = self.current_price_for_symbol(&order.underlying) else {
+            warn!(
+                "Skipping option trade on {} because no current spot price is available",
+                order.underlying
+            );
             return Ok(());
+        };
+        let Some(volatility) = self.option_volatility(&contract, order.implied_volatility) else {
+            return self.record_order_events(vec![OrderEvent::OrderRejected {
+                order_id: option_order.id,
+                reason: format!(
+                    "no implied volatility for {} and too little {} history to estimate one",
+                    symbol, order.underlying
+                ),
+            }]);
+        };
+
+        let position = OpenOptionPosition {
+            symbol: symbol.clone(),
+            contract,
+            volatility,
+            risk_free_rate: decimal_to_f64(order.risk_free_rate),
+            dividend_yield: decimal_to_f64(order.dividend_yield),
+            strategy_id: strategy_id.clone(),
+        };
+        let (mark, pricing) = self.option_mark(&position, spot);
+        let fill_model = self.option_fill_model.clone().unwrap_or_default();
+        let (bid, ask) = match &self.option_fill_model {
+            Some(model) => self
+                .chain_quote(&position.contract)
+                .filter(|quote| quote.bid > Decimal::ZERO && quote.ask >= quote.bid)
+                .map(|quote| (quote.bid, quote.ask))
+                .unwrap_or_else(|| {
+                    model.synthetic_quote(
+                        &position.contract,
+                        decimal_to_f64(pricing.price),
+                        decimal_to_f64(spot),
+                        self.current_time,
+                    )
+                }),
+            None => (mark, mark),
+        };
+        let draw = {
+            let mut hasher = DefaultHasher::new();
+            (&symbol.symbol, order.side == Side::Buy, self.current_time).hash(&mut hasher);
+            (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+        };
+        let Some(premium) = fill_model.fill_price(order.side, order.limit_price, bid, ask, draw)
+        else {
+            return self.record_order_events(vec![OrderEvent::OrderCanceled {
+                order_id: option_order.id,
+                reason: format!(
+                    "limit {} not filled against {} bid / {} ask",
+                    order.limit_price.unwrap_or_default(),
+                    bid,
+                    ask
+                ),
+            }]);
+        };
+
+        let signed_contracts = match order.side {
+            Side::Buy => order.contracts,
+            Side::Sell => -order.contracts,
+        };
+        let (requirement, buying_power) =
+            self.option_margin_after(&position, signed_contracts, premium)?;
+        if requirement > buying_power {
+            return self.record_order_events(vec![OrderEvent::OrderRejected {
+                order_id: option_order.id,
+                reason: format!(
+                    "insufficient buying power: option margin {} exceeds {}",
+                    requirement.round_dp(2),
+                    buying_power.round_dp(2)
+                ),
+            }]);
+        }
+
+        match self
+            .open_options
+            .iter_mut()
+            .find(|open| open.symbol == symbol)
+        {
+            Some(open) => *open = position,
+            None => self.open_options.push(position),
+        }
+
+        let commission = order.commission_per_contract * order.contracts;
+        self.portfolio
+            .set_contract_multiplier(symbol.clone(), order.multiplier);
+        let mut fill = Fill::new(
+            option_order.id,
+            symbol.clone(),
+            order.side,
+            order.contracts,
+            premium,
+            commission,
+            strategy_id,
+        );
+        fill.executed_at = self.current_time;
+        self.portfolio.apply_fill(&fill);
+        self.debug_check_portfolio();
+        self.strategy_metrics.total_trades += 1;
+        if self.portfolio.get_position(&symbol).is_none() {
+            self.open_options.retain(|open| open.symbol != symbol);
+        }
+
+        let mut trade_record = self.trade_record_from_fill(&option_order, &fill);
+        trade_record.tags.push("option".to_string());
+        self.record_trade(trade_record);
+
+        let cash_flow = match order.side {
+            Side::Buy => -(premium * order.contracts * order.multiplier + commission),
+            Side::Sell => premium * order.contracts * order.multiplier - commission,
+        };
+        self.option_events.push(OptionLifecycleEvent {
+            timestamp: self.current_time.to_rfc3339(),
+            event: match order.side {
+                Side::Buy => "option_bought",
+                Side::Sell => "option_sold",
+            }
+            .to_string(),
+            contract_symbol: symbol.symbol.clone(),
+            underlying: order.underlying.symbol.clone(),
+            contracts: decimal_to_f64(order.contracts),
+            strike: decimal_to_f64(order.strike),
+            spot: decimal_to_f64(spot),
+            shares_delivered: 0.0,
+            cash_flow: decimal_to_f64(cash_flow),
+            note: format!(
+                "{} {} at {} per share",
+                match order.side {
+                    Side::Buy => "bought",
+                    Side::Sell => "sold",
+                },
+                order.kind,
+                premium
+            ),
+        });
+
+        self.update_option_marks();
+        self.record_order_events(vec![OrderEvent::OrderFilled {
+            order_id: option_order.id,
+            fill,
+        }])
+    }
+
+    /// Option margin requirement once `signed_contracts` of `traded` are
+    /// added at `premium`, and the buying power it must fit in: cash plus the
+    /// marked value of option positions, which a premium trade leaves
+    /// unchanged.
+    fn option_margin_after(
+        &self,
+        traded: &OpenOptionPosition,
+        signed_contracts: Decimal,
+        premium: Decimal,
+    ) -> GbResult<(Decimal, Decimal)> {
+        let mut positions = Vec::new();
+        let mut spots = HashMap::new();
+        let mut buying_power = self.portfolio.cash;
+        let mut traded_existing = false;
+
+        for open in &self.open_options {
+            let Some(held) = self.portfolio.get_position(&open.symbol) else {
+                continue;
+            };
+            let Some(spot) = self.current_price_for_symbol(&open.contract.underlying) else {
+                continue;
+            };
+            spots.insert(open.contract.underlying.clone(), spot);
+            let (mark, _) = self.option_mark(open, spot);
+            buying_power += held.quantity * mark * open.contract.multiplier;
+
+            let mut contracts = held.quantity;
+            if open.symbol == traded.symbol {
+                contracts += signed_contracts;
+                traded_existing = true;
+            }
+            positions.push(gb_options::MarginPosition::new(
+                open.contract.clone(),
+                contracts,
+                mark,
+            ));
+        }
+        if !traded_existing {
+            positions.push(gb_options::MarginPosition::new(
+                traded.contract.clone(),
+                signed_contracts,
+                premium,
+            ));
+        }
+        if let Some(spot) = self.current_price_for_symbol(&traded.contract.underlying) {
+            spots.insert(traded.contract.underlying.clone(), spot);
+        }
+
+        let requirement =
+            gb_options::portfolio_margin_requirement(&positions, &spots).map_err(|error| {
+                BacktestError::ExecutionFailed {
+                    message: format!("failed to compute option margin: {}", error),
+                }
+            })?;
+        Ok((requirement, buying_power))
+    }
+
+    fn option_pricing_input(&self, position: &OpenOptionPosition, spot: Decimal) -> PricingInput {
+        PricingInput {
+            spot: decimal_to_f64(spot),
+            risk_free_rate: position.risk_free_rate,
+            volatility: position.volatility,
+            dividend_yield: position.dividend_yield,
+            time_to_expiry: position.contract.time_to_expiry(self.current_time),
+        }
+    }
+
+    /// Per-share mark of an option position: the mid of the latest stored
+    /// chain quote for the contract when there is one, otherwise the model
+    /// price. Greeks always come from the model.
+    fn option_mark(
+        &self,
+        position: &OpenOptionPosition,
+        spot: Decimal,
+    ) -> (Decimal, PricingResult) {
+        let pricing = gb_options::price(
+            &position.contract,
+            &self.option_pricing_input(position, spot),
+        );
+        let chain_mid = self
+            .chain_quote(&position.contract)
+            .and_then(|quote| quote.mid());
+
+        let mark = chain_mid.unwrap_or_else(|| pricing.price.round_dp(4));
+        (mark, pricing)
+    }
+
+    /// Volatility to price `contract` at: `implied` when positive, otherwise
+    /// the implied volatility of its stored chain quote, otherwise the
+    /// close-to-close volatility of the underlying's bars seen so far.
+    fn option_volatility(&self, contract: &OptionContract, implied: Decimal) -> Option<f64> {
+        if implied > Decimal::ZERO {
+            return Some(decimal_to_f64(implied));
+        }
+        if let Some(market) = self
+            .chain_quote(contract)
+            .and_then(|quote| quote.implied_volatility)
+            .filter(|volatility| *volatility > 0.0)
+        {
+            return Some(market);
+        }
+        let bars = self.market_data.get(&contract.underlying)?;
+        let seen = self
+            .next_bar_indices
+            .get(&contract.underlying)
+            .copied()
+            .unwrap_or(0)
+            .min(bars.len());
+        historical_vol(
+            &bars[..seen],
+            HISTORICAL_VOL_WINDOW,
+            VolEstimator::CloseToClose,
+        )
+        .map(|estimate| estimate.volatility)
+        .filter(|volatility| *volatility > 0.0)
+    }
+
+    /// The contract's quote in the latest stored chain snapshot taken on or
+    /// before the current day.
+    fn chain_quote(&self, contract: &OptionContract) -> Option<&OptionQuote> {
+        self.option_chains
+            .get(&contract.underlying)
+            .and_then(|snapshots| {
+                snapshots.iter().rev().find(|snapshot| {
+                    snapshot.timestamp.date_naive() <= self.current_time.date_naive()
+                })
+            })
+            .and_then(|snapshot| {
+                snapshot.get(
+                    contract.expiration.date_naive(),
+                    contract.strike,
+                    contract.kind,
+                )
+            })
+    }
+
+    /// Mark every open option position and refresh its greek exposure.
+    fn update_option_marks(&mut self) {
+        let mut marks = HashMap::new();
+        for position in &self.open_options {
+            let Some(spot) = self.current_price_for_symbol(&position.contract.underlying) else {
+                continue;
+            };
+            let Some(quantity) = self
+                .portfolio
+                .get_position(&position.symbol)
+                .map(|held| held.quantity)
+            else {
+                continue;
+            };
+            let (mark, pricing) = self.option_mark(position, spot);
+            let scale = quantity * position.contract.multiplier;
+            self.portfolio.option_greeks.insert(
+                position.symbol.clone(),
+                GreeksExposure {
+                    delta: pricing.greeks.delta * scale,
+                    gamma: pricing.greeks.gamma * scale,
+                    theta: pricing.greeks.theta * scale,
+                    vega: pricing.greeks.vega * scale,
+                    rho: pricing.greeks.rho * scale,
+                },
+            );
+            marks.insert(position.symbol.clone(), mark);
         }
+        self.portfolio.update_market_prices(&marks);
+    }
 
+    /// Settle option positions that expired on or before the current bar.
+    /// In-the-money options are exercised (long) or assigned (short): with
+    /// physical settlement the underlying changes hands at the strike and the
+    /// contract closes at zero; with cash settlement the contract closes at
+    /// its intrinsic value. Out-of-the-money options expire worthless.
+    fn settle_expired_options(&mut self) -> Vec<OrderEvent> {
+        let mut settlement_events = Vec::new();
         let mut remaining_positions = Vec::new();
-        let mut assignment_events = Vec::new();
 
-        for position in std::mem::take(&mut self.open_covered_calls) {
-            if self.current_time < position.contract.expiration {
+        for position in std::mem::take(&mut self.open_options) {
+            if !position.contract.is_expired(self.current_time) {
                 remaining_positions.push(position);
                 continue;
             }
-
             let Some(spot) = self.current_price_for_symbol(&position.contract.underlying) else {
                 remaining_positions.push(position);
                 continue;
             };
+            let Some(quantity) = self
+                .portfolio
+                .get_position(&position.symbol)
+                .map(|held| held.quantity)
+            else {
+                continue;
+            };
 
-            let contract_label = contract_symbol(&position.contract);
+            let long = quantity > Decimal::ZERO;
+            let contracts = quantity.abs();
             let intrinsic_value = position.contract.intrinsic_value(spot);
-            let shares_delivered = position.contract.multiplier * position.contracts;
-            let settlement = if intrinsic_value > Decimal::ZERO {
-                let assignment_order = Order::market_order(
+            let in_the_money = intrinsic_value > Decimal::ZERO;
+            let settlement = self.config.execution_settings.option_settlement;
+            let (event, note) = match (in_the_money, settlement, long) {
+                (false, _, _) => ("option_expired", "expired out of the money"),
+                (true, OptionSettlement::Cash, true) => (
+                    "option_exercised",
+                    "exercised for its intrinsic value in cash",
+                ),
+                (true, OptionSettlement::Cash, false) => (
+                    "option_assigned",
+                    "assigned for its intrinsic value in cash",
+                ),
+                (true, OptionSettlement::Physical, true) => (
+                    "option_exercised",
+                    "exercised into the underlying at the strike",
+                ),
+                (true, OptionSettlement::Physical, false) => (
+                    "option_assigned",
+                    "assigned into the underlying at the strike",
+                ),
+            };
+
+            let close_price = if in_the_money && settlement == OptionSettlement::Cash {
+                intrinsic_value
+            } else {
+                Decimal::ZERO
+            };
+            let close_order = Order::market_order(
+                position.symbol.clone(),
+                if long { Side::Sell } else { Side::Buy },
+                contracts,
+                position.strategy_id.clone(),
+            );
+            let mut close_fill = Fill::new(
+                close_order.id,
+                position.symbol.clone(),
+                close_order.side,
+                contracts,
+                close_price,
+                Decimal::ZERO,
+                position.strategy_id.clone(),
+            );
+            close_fill.executed_at = self.current_time;
+            self.portfolio.apply_fill(&close_fill);
+            self.debug_check_portfolio();
+            self.strategy_metrics.total_trades += 1;
+
+            let mut trade_record = self.trade_record_from_fill(&close_order, &close_fill);
+            trade_record.tags.push("option".to_string());
+            trade_record.tags.push(event.to_string());
+            self.record_trade(trade_record);
+            settlement_events.push(OrderEvent::OrderFilled {
+                order_id: close_order.id,
+                fill: close_fill,
+            });
+
+            let mut cash_flow = if long { close_price } else { -close_price }
+                * contracts
+                * position.contract.multiplier;
+            let mut shares_delivered = Decimal::ZERO;
+            if in_the_money && settlement == OptionSettlement::Physical {
+                shares_delivered = contracts * position.contract.multiplier;
+                // Long calls and short puts end up buying the underlying.
+                let buys_underlying = (position.contract.kind == OptionKind::Call) == long;
+                let delivery_order = Order::market_order(
                     position.contract.underlying.clone(),
-                    Side::Sell,
+                    if buys_underlying {
+                        Side::Buy
+                    } else {
+                        Side::Sell
+                    },
+                    shares_delivered,
+                    position.strategy_id.clone(),
+                );
+                let mut delivery_fill = Fill::new(
+                    delivery_order.id,
+                    position.contract.underlying.clone(),
+                    delivery_order.side,
+                    shares_delivered,
+                    position.contract.strike,
+                    Decimal::ZERO,
+                    position.strategy_id.clone(),
+                );
+                delivery_fill.executed_at = self.current_time;
+                self.portfolio.apply_fill(&delivery_fill);
+                self.debug_check_portfolio();
+                self.strategy_metrics.total_trades += 1;
+                cash_flow = delivery_fill.net_amount();
+
+                let mut trade_record = self.trade_record_from_fill(&delivery_order, &delivery_fill);
+                trade_record.tags.push(
+                    if long {
+                        "option_exercise"
+                    } else {
+                        "option_assignment"
+                    }
+                    .to_string(),
+                );
+                trade_record.tags.push(position.symbol.symbol.clone());
+                self.record_trade(trade_record);
+                settlement_events.push(OrderEvent::OrderFilled {
+                    order_id: delivery_order.id,
+                    fill: delivery_fill,
+                });
+            }
+
+            self.option_events.push(OptionLifecycleEvent {
+                timestamp: self.current_time.to_rfc3339(),
+                event: event.to_string(),
+                contract_symbol: position.symbol.symbol.clone(),
+                underlying: position.contract.underlying.symbol.clone(),
+                contracts: decimal_to_f64(contracts),
+                strike: decimal_to_f64(position.contract.strike),
+                spot: decimal_to_f64(spot),
+                shares_delivered: decimal_to_f64(shares_delivered),
+                cash_flow: decimal_to_f64(cash_flow),
+                note: format!(
+                    "{} {} {}",
+                    if long { "long" } else { "short" },
+                    position.contract.kind,
+                    note
+                ),
+            });
+        }
+
+        self.open_options = remaining_positions;
+        settlement_events
+    }
+
+    async fn process_option_lifecycle(&mut self) -> GbResult<()> {
+        let option_settlements = self.settle_expired_options();
+        if !option_settlements.is_empty() {
+            // Delivered shares are marked on the bar they were delivered
+            self.update_portfolio_values().await?;
+            self.record_order_events(option_settlements)?;
+        }
+
+        if self.open_covered_calls.is_empty() {
+            return Ok(());
+        }
+
+        let mut remaining_positions = Vec::new();
+        let mut assignment_events = Vec::new();
+
+        for position in std::mem::take(&mut self.open_covered_calls) {
+            if self.current_time < position.contract.expiration {
+                remaining_positions.push(position);
+                continue;
+            }
+
+            let Some(spot) = self.current_price_for_symbol(&position.contract.underlying) else {
+                remaining_positions.push(position);
+                continue;
+            };
+
+            let contract_label = contract_symbol(&position.contract);
+            let intrinsic_value = position.contract.intrinsic_value(spot);
+            let shares_delivered = position.contract.multiplier * position.contracts;
+            let settlement = if intrinsic_value > Decimal::ZERO {
+                let assignment_order = Order::market_order(
+                    position.contract.underlying.clone(),
+                    Side::Sell,
                     shares_delivered,
                     position.strategy_id.clone(),
                 );
@@ -932,13 +1942,14 @@ impl Engine {
                 );
                 assignment_fill.executed_at = self.current_time;
                 self.portfolio.apply_fill(&assignment_fill);
+                self.debug_check_portfolio();
                 self.strategy_metrics.total_trades += 1;
 
                 let mut trade_record_fill =
                     self.trade_record_from_fill(&assignment_order, &assignment_fill);
                 trade_record_fill.tags.push("option_assignment".to_string());
                 trade_record_fill.tags.push(contract_label.clone());
-                self.trade_log.push(trade_record_fill);
+                self.record_trade(trade_record_fill);
                 assignment_events.push(OrderEvent::OrderFilled {
                     order_id: assignment_order.id,
                     fill: assignment_fill,
@@ -1011,31 +2022,164 @@ impl Engine {
         Ok(())
     }
 
+    fn gross_exposure(&self) -> Decimal {
+        self.portfolio
+            .positions
+            .values()
+            .map(|position| position.market_value.abs())
+            .sum()
+    }
+
+    /// Warn with a [`BacktestEvent::MarginCall`] when equity is below the
+    /// maintenance requirement of a margin account.
+    fn check_maintenance_margin(&self) {
+        let Some(account) = &self.buying_power else {
+            return;
+        };
+        if let Some(call) = account.margin_call(self.portfolio.total_equity, self.gross_exposure())
+        {
+            warn!(
+                "Margin call on {}: equity {} is below the maintenance requirement of {}",
+                self.current_time.date_naive(),
+                call.equity.round_dp(2),
+                call.maintenance_requirement.round_dp(2)
+            );
+            self.emit(|| BacktestEvent::MarginCall {
+                backtest_id: self.config.id,
+                timestamp: self.current_time,
+                call,
+            });
+        }
+    }
+
     /// Update portfolio values with current market prices
+    ///
+    /// Positions are marked at the close of the current day's bars, which
+    /// under [`BarDelivery::OnOpen`] the strategy has not been handed yet.
     async fn update_portfolio_values(&mut self) -> GbResult<()> {
+        let current_date = self.current_time.date_naive();
         let current_prices = self
-            .current_market_bars
+            .market_data
             .iter()
-            .map(|(symbol, bar)| (symbol.clone(), bar.close))
+            .filter_map(|(symbol, bars)| {
+                let start = bars.partition_point(|bar| bar.timestamp.date_naive() < current_date);
+                bars[start..]
+                    .iter()
+                    .take_while(|bar| bar.timestamp.date_naive() == current_date)
+                    .last()
+                    .map(|bar| (symbol.clone(), bar.close))
+            })
             .collect();
 
         self.portfolio.update_market_prices(&current_prices);
+        self.update_option_marks();
         self.strategy_context.portfolio = self.portfolio.clone();
 
+        self.check_maintenance_margin();
+
+        self.track_staleness()
+    }
+
+    /// Count the sessions each symbol has missed and apply the
+    /// [`StalenessPolicy`] to symbols crossing `max_stale_bars`. A symbol is
+    /// only tracked once its first bar has arrived.
+    fn track_staleness(&mut self) -> GbResult<()> {
+        if !self.is_session_day() {
+            return Ok(());
+        }
+        let current_date = self.current_time.date_naive();
+        let max_stale_bars = self.config.data_settings.max_stale_bars;
+        let mut newly_stale = Vec::new();
+        for (symbol, bars) in &self.market_data {
+            let seen = bars.partition_point(|bar| bar.timestamp.date_naive() <= current_date);
+            let Some(last_bar) = seen.checked_sub(1).map(|index| &bars[index]) else {
+                continue;
+            };
+            let missed = self.missed_bars.entry(symbol.clone()).or_insert(0);
+            if last_bar.timestamp.date_naive() == current_date {
+                *missed = 0;
+                continue;
+            }
+            *missed += 1;
+            if *missed == max_stale_bars + 1 {
+                newly_stale.push((symbol.clone(), last_bar.timestamp, *missed));
+            }
+        }
+        newly_stale.sort_by_key(|(symbol, ..)| symbol.to_string());
+
+        for (symbol, last_bar_at, missing_bars) in newly_stale {
+            self.emit(|| BacktestEvent::DataStale {
+                backtest_id: self.config.id,
+                symbol: symbol.clone(),
+                last_bar_at,
+                missing_bars,
+            });
+            match self.config.data_settings.staleness_policy {
+                StalenessPolicy::MarkStale => {}
+                StalenessPolicy::FreezeAndWarn => warn!(
+                    "{} has no bar since {}; freezing it at its last close",
+                    symbol, last_bar_at
+                ),
+                StalenessPolicy::FailRun => {
+                    return Err(BacktestError::StaleData {
+                        symbol: symbol.to_string(),
+                        last_bar_at: last_bar_at.to_string(),
+                        missing_bars,
+                    }
+                    .into())
+                }
+            }
+        }
         Ok(())
     }
 
-    /// Generate strategy signals by calling the strategy's on_market_event method
+    fn is_stale(&self, symbol: &Symbol) -> bool {
+        self.missed_bars
+            .get(symbol)
+            .is_some_and(|&missed| missed > self.config.data_settings.max_stale_bars)
+    }
+
+    fn stale_symbols(&self) -> Vec<Symbol> {
+        let mut stale: Vec<Symbol> = self
+            .missed_bars
+            .keys()
+            .filter(|symbol| self.is_stale(symbol))
+            .cloned()
+            .collect();
+        stale.sort_by_key(|symbol| symbol.to_string());
+        stale
+    }
+
+    /// Generate strategy signals by calling the strategy's on_market_event method.
+    ///
+    /// Under [`EventOrdering::Snapshot`] bars are taken in time order and the
+    /// actions of every callback on one timestamp are held back until all of
+    /// them have run.
     async fn generate_strategy_signals(&mut self) -> GbResult<()> {
-        let current_bars_to_process = self.current_market_bars.clone();
+        let mut current_bars_to_process = self.current_market_bars.clone();
+        let snapshot = self.config.execution_settings.event_ordering == EventOrdering::Snapshot;
+        if snapshot {
+            current_bars_to_process.sort_by_key(|(_, bar)| bar.timestamp);
+        }
+        let mut deferred = Vec::new();
+        let mut deferred_at = None;
 
         for (symbol, bar) in current_bars_to_process {
+            if deferred_at.replace(bar.timestamp) != Some(bar.timestamp) {
+                for action in std::mem::take(&mut deferred) {
+                    self.process_strategy_action(action)?;
+                }
+            }
+            self.strategy_context.session = self.session_calendar.position(bar.timestamp);
             let market_event = MarketEvent::Bar(bar);
 
-            let actions = match self
+            let mark = self.profiler.start();
+            let actions = self
                 .strategy
-                .on_market_event(&market_event, &self.strategy_context)
-            {
+                .on_market_event(&market_event, &self.strategy_context);
+            self.profiler
+                .record_symbol(ProfilePhase::OnMarketEvent, &symbol, mark);
+            let actions = match actions {
                 Ok(actions) => actions,
                 Err(e) => {
                     warn!("Strategy error processing {}: {}", symbol, e);
@@ -1043,14 +2187,34 @@ impl Engine {
                 }
             };
 
+            if snapshot {
+                deferred.extend(actions);
+                continue;
+            }
             for action in actions {
                 self.process_strategy_action(action)?;
             }
         }
+        for action in deferred {
+            self.process_strategy_action(action)?;
+        }
 
         Ok(())
     }
 
+    /// Debug builds check the portfolio's bookkeeping after every fill.
+    fn debug_check_portfolio(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        if let Err(error) = self.portfolio.check_invariants() {
+            panic!(
+                "portfolio invariant broken at {}: {error}",
+                self.current_time
+            );
+        }
+    }
+
     fn sync_strategy_context_account_state(&mut self) {
         self.strategy_context.current_time = self.current_time;
         self.strategy_context.portfolio = self.portfolio.clone();
@@ -1082,8 +2246,63 @@ impl Engine {
                     }]);
                 }
 
+                if self.config.data_settings.staleness_policy == StalenessPolicy::FreezeAndWarn
+                    && self.is_stale(&order.symbol)
+                {
+                    return self.record_order_events(vec![OrderEvent::OrderRejected {
+                        order_id: order.id,
+                        reason: format!("market data for {} is stale", order.symbol),
+                    }]);
+                }
+
+                let quantity_rule = self
+                    .config
+                    .execution_settings
+                    .quantity_policy
+                    .rule_for(&order.symbol);
+                let Some(quantity) = quantity_rule.apply(order.quantity) else {
+                    return self.record_order_events(vec![OrderEvent::OrderRejected {
+                        order_id: order.id,
+                        reason: format!(
+                            "order quantity {} is below the minimum of {} for {}",
+                            order.quantity, quantity_rule.min_quantity, order.symbol
+                        ),
+                    }]);
+                };
+                order.quantity = quantity;
+                order.remaining_quantity = quantity - order.filled_quantity;
+
+                if let Err(reason) = self.check_buying_power(&order) {
+                    return self.record_order_events(vec![OrderEvent::OrderRejected {
+                        order_id: order.id,
+                        reason,
+                    }]);
+                }
+
+                if order.execution_algo.is_some() {
+                    let reason = if !matches!(
+                        order.order_type,
+                        OrderType::Market | OrderType::Limit { .. }
+                    ) {
+                        Some("execution algos work market and limit orders only")
+                    } else if order.time_in_force != TimeInForce::GTC {
+                        Some("execution algo orders must be good till canceled")
+                    } else {
+                        None
+                    };
+                    if let Some(reason) = reason {
+                        return self.record_order_events(vec![OrderEvent::OrderRejected {
+                            order_id: order.id,
+                            reason: reason.to_string(),
+                        }]);
+                    }
+                }
+
                 order.status = OrderStatus::Submitted;
                 order.submitted_at = self.current_time;
+                if let Some(price) = self.current_price_for_symbol(&order.symbol) {
+                    self.decision_prices.insert(order.id, price);
+                }
                 self.pending_orders.push(order.clone());
                 self.sync_strategy_context_account_state();
                 self.record_order_events(vec![OrderEvent::OrderSubmitted(order)])?;
@@ -1112,6 +2331,18 @@ impl Engine {
                 );
                 self.open_covered_call(order)?;
             }
+            StrategyAction::TradeOption(order) => {
+                debug!(
+                    "Strategy traded option: {:?} {} {} {} strike {} exp {}",
+                    order.side,
+                    order.contracts,
+                    order.underlying,
+                    order.kind,
+                    order.strike,
+                    order.expiration
+                );
+                self.trade_option(order)?;
+            }
             StrategyAction::Log { level, message } => match level {
                 gb_types::LogLevel::Debug => debug!("[Strategy] {}", message),
                 gb_types::LogLevel::Info => info!("[Strategy] {}", message),
@@ -1127,24 +2358,57 @@ impl Engine {
         Ok(())
     }
 
-    /// Update daily returns
+    /// Apply the deposits and withdrawals dated up to the step's clock.
+    /// The drawdown peak moves with each flow, so money coming in or going
+    /// out is neither a new high nor a drawdown.
+    fn apply_cash_flows(&mut self) {
+        self.step_cash_flow = Decimal::ZERO;
+        while self
+            .pending_cash_flows
+            .front()
+            .is_some_and(|event| event.date <= self.current_time)
+        {
+            let Some(event) = self.pending_cash_flows.pop_front() else {
+                break;
+            };
+            let equity_before = self.portfolio.total_equity;
+            match event.kind {
+                CashFlowKind::Deposit => self.portfolio.deposit(event.amount, self.current_time),
+                CashFlowKind::Withdrawal => {
+                    self.portfolio.withdraw(event.amount, self.current_time)
+                }
+            }
+            if equity_before > Decimal::ZERO {
+                self.equity_peak *= (equity_before + event.signed_amount()) / equity_before;
+            }
+            self.step_cash_flow += event.signed_amount();
+            debug!(
+                "Applied {:?} of {} at {}",
+                event.kind, event.amount, self.current_time
+            );
+        }
+        if !self.step_cash_flow.is_zero() {
+            self.sync_strategy_context_account_state();
+        }
+    }
+
+    /// Record the day's return if it was a trading session, and add the
+    /// day's point to the equity curve.
     async fn update_daily_returns(&mut self) -> GbResult<()> {
         let total_value = self.portfolio.total_equity;
-        let (daily_return, daily_return_opt) =
-            if let Some(previous_value) = self.portfolio.daily_returns.last() {
-                if previous_value.portfolio_value > Decimal::ZERO {
-                    let dr = (total_value - previous_value.portfolio_value)
-                        / previous_value.portfolio_value;
-                    (dr, Some(dr))
-                } else {
-                    (Decimal::ZERO, Some(Decimal::ZERO))
-                }
+        // The step's deposits and withdrawals are not part of its return
+        let daily_return_opt = self.equity_curve.last().map(|previous| {
+            let base = previous.portfolio_value + self.step_cash_flow;
+            if base > Decimal::ZERO {
+                (total_value - base) / base
             } else {
-                (Decimal::ZERO, None)
-            };
+                Decimal::ZERO
+            }
+        });
 
-        self.portfolio
-            .add_daily_return(self.current_time, daily_return);
+        if self.is_session_day() {
+            DailyReturnRecorder.record(&mut self.portfolio, self.current_time, total_value);
+        }
 
         let positions_value: Decimal = self
             .portfolio
@@ -1171,13 +2435,62 @@ impl Engine {
             daily_return: daily_return_opt,
             cumulative_return: self.portfolio.get_total_return(),
             drawdown,
+            stale_symbols: self.stale_symbols(),
+            cash_flow: self.step_cash_flow,
         };
 
+        self.emit(|| BacktestEvent::EquityUpdate {
+            backtest_id: self.config.id,
+            point: point.clone(),
+        });
         self.equity_curve.push(point);
 
         Ok(())
     }
 
+    /// Whether any symbol has a bar dated today; days without one (weekends,
+    /// holidays) are not sessions and get no daily return.
+    fn is_session_day(&self) -> bool {
+        let current_date = self.current_time.date_naive();
+        self.market_data.values().any(|bars| {
+            let start = bars.partition_point(|bar| bar.timestamp.date_naive() < current_date);
+            bars.get(start)
+                .is_some_and(|bar| bar.timestamp.date_naive() == current_date)
+        })
+    }
+
+    /// Record the open positions when the snapshot cadence is due.
+    fn record_positions_snapshot(&mut self) {
+        if !snapshot_due(
+            self.config.data_settings.position_snapshots,
+            self.current_time,
+            self.config.end_date,
+        ) {
+            return;
+        }
+        let mut positions: Vec<PositionHolding> = self
+            .portfolio
+            .positions
+            .values()
+            .filter(|position| !position.quantity.is_zero())
+            .map(|position| PositionHolding {
+                symbol: position.symbol.clone(),
+                quantity: position.quantity,
+                market_value: position.market_value,
+            })
+            .collect();
+        positions.sort_by_key(|holding| holding.symbol.to_string());
+        let snapshot = PositionsSnapshot {
+            timestamp: self.current_time,
+            positions,
+        };
+        self.emit(|| BacktestEvent::PositionsSnapshot {
+            backtest_id: self.config.id,
+            snapshot: snapshot.clone(),
+        });
+        self.positions_history.push(snapshot);
+    }
+
     fn build_run_manifest(&self, result: &BacktestResult) -> RunManifest {
         let strategy_config = self.strategy.get_config();
         let symbols = self
@@ -1333,20 +2646,11 @@ impl Engine {
         }
 
         // Calculate max drawdown from daily returns
-        let mut peak = self.config.initial_capital;
-        let mut max_dd = Decimal::ZERO;
-        for dr in &self.portfolio.daily_returns {
-            if dr.portfolio_value > peak {
-                peak = dr.portfolio_value;
-            }
-            if peak > Decimal::ZERO {
-                let drawdown = (peak - dr.portfolio_value) / peak;
-                if drawdown > max_dd {
-                    max_dd = drawdown;
-                }
-            }
-        }
-        self.strategy_metrics.max_drawdown = max_dd;
+        self.strategy_metrics.max_drawdown = if self.config.initial_capital > Decimal::ZERO {
+            self.portfolio.get_max_drawdown()
+        } else {
+            Decimal::ZERO
+        };
 
         // Set end time
         self.strategy_metrics.end_time = Some(self.current_time);
@@ -1356,10 +2660,20 @@ impl Engine {
         result.equity_curve = self.equity_curve.clone();
         result.trade_log = self.trade_log.clone();
         result.order_events = self.order_events.clone();
-        result.performance_metrics = Some(gb_types::PerformanceMetrics::calculate_with_trades(
-            &self.portfolio,
-            &self.trade_log,
-        ));
+        result.positions_history = self.positions_history.clone();
+        result.execution_report = self.execution_report.clone();
+        if !self.benchmark.is_empty() {
+            let closes: Vec<_> = self
+                .benchmark
+                .iter()
+                .map(|bar| (bar.timestamp, bar.close))
+                .collect();
+            result.attach_benchmark(&closes);
+        }
+        result.performance_metrics = Some(
+            gb_types::PerformanceMetrics::calculate_with_trades(&self.portfolio, &self.trade_log)
+                .with_money_weighted_return(&self.portfolio, self.config.start_date),
+        );
         result.metadata.insert(
             "data_validation_summaries".to_string(),
             serde_json::to_value(&self.data_validation_summaries)?,
@@ -1368,6 +2682,14 @@ impl Engine {
             "data_quality_mode".to_string(),
             serde_json::to_value(&self.config.data_settings.data_quality_mode)?,
         );
+        result.metadata.insert(
+            "bar_delivery".to_string(),
+            serde_json::to_value(self.config.execution_settings.bar_delivery)?,
+        );
+        result.metadata.insert(
+            "event_ordering".to_string(),
+            serde_json::to_value(self.config.execution_settings.event_ordering)?,
+        );
         result.metadata.insert(
             "sample_data".to_string(),
             serde_json::json!(self
@@ -1385,12 +2707,28 @@ impl Engine {
         );
         result.metadata.insert(
             "option_support_level".to_string(),
-            serde_json::json!(if self.option_trades.is_empty() {
+            serde_json::json!(if self.option_events.is_empty() {
                 "none"
             } else {
                 "experimental"
             }),
         );
+        let finish = self.strategy.on_finish(&self.strategy_context);
+        if !finish.is_empty() {
+            result
+                .metadata
+                .insert("strategy".to_string(), serde_json::to_value(finish)?);
+        }
+        if self.profiler.is_enabled() {
+            let profile = self.profiler.report();
+            result
+                .metadata
+                .insert("profile".to_string(), serde_json::to_value(&profile)?);
+            self.emit(|| BacktestEvent::Profile {
+                backtest_id: self.config.id,
+                profile,
+            });
+        }
         result.manifest = Some(self.build_run_manifest(result));
 
         info!("Final portfolio value: {}", self.portfolio.total_equity);
@@ -1399,7 +2737,10 @@ impl Engine {
             "Annualized volatility: {:.2}%",
             self.strategy_metrics.volatility * Decimal::from(100)
         );
-        info!("Max drawdown: {:.2}%", max_dd * Decimal::from(100));
+        info!(
+            "Max drawdown: {:.2}%",
+            self.strategy_metrics.max_drawdown * Decimal::from(100)
+        );
         info!("Total trades: {}", self.strategy_metrics.total_trades);
         if self.strategy_metrics.total_trades > 0 {
             info!(
@@ -1418,7 +2759,10 @@ impl Engine {
     async fn call_strategy_day_end(&mut self) -> GbResult<()> {
         self.sync_strategy_context_account_state();
 
-        let actions = match self.strategy.on_day_end(&self.strategy_context) {
+        let mark = self.profiler.start();
+        let actions = self.strategy.on_day_end(&self.strategy_context);
+        self.profiler.record(ProfilePhase::OnDayEnd, mark);
+        let actions = match actions {
             Ok(actions) => actions,
             Err(e) => {
                 warn!("Strategy on_day_end error: {}", e);
@@ -1460,9 +2804,14 @@ mod tests {
     use super::*;
     use chrono::TimeZone;
     use gb_types::{
-        DataQualityMode, DataValidationSummary, DatasetKind, LatencyModel, OrderEvent, OrderStatus,
-        PriceAdjustmentMode, Resolution, Side, StrategyAction, StrategyConfig, TimeInForce,
+        AssetClass, BacktestEvent, BacktestResult, BuyingPowerModel, CashFlowSettings,
+        DataQualityMode, DataValidationSummary, DatasetKind, ExecutionAlgo, GbError, LatencyModel,
+        OrderEvent, OrderStatus, PriceAdjustmentMode, Resolution, RunProfile, SessionPosition,
+        Side, SlippageModel, StrategyAction, StrategyConfig, TimeInForce,
     };
+    use rust_decimal_macros::dec;
+
+    use crate::calendar::TradingCalendar;
 
     #[derive(Debug, Clone)]
     struct NoopStrategy {
@@ -1568,18 +2917,36 @@ mod tests {
             current_time: ts(1),
             market_data: HashMap::from([(symbol.clone(), bars)]),
             next_bar_indices: HashMap::from([(symbol.clone(), 0)]),
+            missed_bars: HashMap::new(),
             current_market_bars: Vec::new(),
+            session_calendar: SessionCalendar::default(),
             pending_orders: Vec::new(),
             strategy_context,
             strategy_metrics: StrategyMetrics::new("noop".to_string()),
             equity_curve: Vec::new(),
             trade_log: Vec::new(),
             order_events: Vec::new(),
+            positions_history: Vec::new(),
+            decision_prices: HashMap::new(),
+            algo_windows: HashMap::new(),
+            execution_report: ExecutionReport::default(),
             option_trades: Vec::new(),
             option_events: Vec::new(),
             open_covered_calls: Vec::new(),
+            open_options: Vec::new(),
+            option_chains: HashMap::new(),
+            option_fill_model: None,
+            benchmark: Vec::new(),
             equity_peak: Decimal::from(100_000),
+            pending_cash_flows: VecDeque::new(),
+            step_cash_flow: Decimal::ZERO,
+            trade_ledger: TradeLedger::new(),
+            buying_power: None,
             data_validation_summaries: HashMap::new(),
+            cancellation: CancellationHandle::new(),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+            profiler: Profiler::default(),
+            span: Span::none(),
         }
     }
 
@@ -1627,77 +2994,925 @@ mod tests {
         assert_eq!(buffer.get_current_price(), Some(Decimal::from(104)));
     }
 
-    #[test]
-    fn process_strategy_action_keeps_pending_order_snapshots_in_sync() {
+    #[tokio::test]
+    async fn on_open_delivery_hands_each_bar_over_a_day_later() {
         let symbol = Symbol::equity("AAPL");
-        let mut engine = test_engine(symbol.clone(), vec![test_bar(&symbol, 1, 100)]);
-        let order = Order::market_order(symbol, Side::Buy, Decimal::from(5), "noop".to_string());
-        let order_id = order.id;
+        let bars: Vec<Bar> = (1..=3)
+            .map(|day| test_bar(&symbol, day, 100 + day as i64))
+            .collect();
+        let mut engine = test_engine(symbol.clone(), bars);
+        engine.config.execution_settings.bar_delivery = BarDelivery::OnOpen;
 
-        engine
-            .process_strategy_action(StrategyAction::PlaceOrder(order.clone()))
-            .unwrap();
-        assert_eq!(engine.pending_orders.len(), 1);
-        assert_eq!(engine.strategy_context.pending_orders.len(), 1);
-        assert_eq!(engine.strategy_context.pending_orders[0].id, order_id);
-        assert!(matches!(
-            engine.order_events.last(),
-            Some(OrderEvent::OrderSubmitted(submitted)) if submitted.id == order_id
-        ));
+        engine.process_market_data().await.unwrap();
+        assert!(engine.current_market_bars.is_empty());
 
-        engine
-            .process_strategy_action(StrategyAction::CancelOrder { order_id })
-            .unwrap();
-        assert!(engine.pending_orders.is_empty());
-        assert!(engine.strategy_context.pending_orders.is_empty());
-        assert!(matches!(
-            engine.order_events.last(),
-            Some(OrderEvent::OrderCanceled { order_id: canceled_id, .. }) if *canceled_id == order_id
-        ));
+        for day in 2..=3 {
+            engine.current_time = ts(day);
+            engine.process_market_data().await.unwrap();
+            assert_eq!(engine.current_market_bars.len(), 1);
+            assert_eq!(engine.current_market_bars[0].1.timestamp, ts(day - 1));
+        }
     }
 
-    #[test]
-    fn process_strategy_action_rejects_non_positive_quantity() {
+    #[tokio::test]
+    async fn a_bar_delivered_before_its_close_fails_the_run() {
         let symbol = Symbol::equity("AAPL");
-        let mut engine = test_engine(symbol.clone(), vec![test_bar(&symbol, 1, 100)]);
-        let mut order = Order::market_order(symbol, Side::Buy, Decimal::ZERO, "noop".to_string());
-        order.quantity = Decimal::ZERO;
-        order.remaining_quantity = Decimal::ZERO;
+        let mut bars: Vec<Bar> = (1..=5).map(|day| test_bar(&symbol, day, 100)).collect();
+        // A weekly bar resampled onto the 3rd carries the rest of the week.
+        bars[2].resolution = Resolution::Week;
+        let mut engine = test_engine(symbol.clone(), bars);
+        let mut events = engine.subscribe();
+
+        let error = engine.run().await.unwrap_err();
+
+        let GbError::Backtest(BacktestError::LookaheadViolation {
+            symbol: violating,
+            message,
+        }) = &error
+        else {
+            panic!("expected a lookahead violation, got {error}");
+        };
+        assert_eq!(violating, "NASDAQ:AAPL");
+        assert!(
+            message.contains("stamped 2024-01-03 00:00:00 UTC")
+                && message.contains("closes at 2024-01-10 00:00:00 UTC")
+                && message.contains("simulator time 2024-01-04 00:00:00 UTC"),
+            "{message}"
+        );
+        let buffer = &engine.strategy_context.market_data[&symbol];
+        assert_eq!(buffer.data.len(), 2, "the strategy never saw the bar");
 
-        engine
-            .process_strategy_action(StrategyAction::PlaceOrder(order.clone()))
-            .unwrap();
+        let mut failed = None;
+        while let Ok(event) = events.try_recv() {
+            if let BacktestEvent::Failed { error, .. } = event {
+                failed = Some(error);
+            }
+        }
+        assert_eq!(failed, Some(error.to_string()));
+    }
 
-        assert!(engine.pending_orders.is_empty());
-        assert!(matches!(
-            engine.order_events.last(),
-            Some(OrderEvent::OrderRejected { order_id, reason }) if *order_id == order.id && reason.contains("positive")
-        ));
+    #[tokio::test]
+    async fn buy_and_hold_entry_price_under_each_bar_delivery() {
+        let symbol = Symbol::equity("AAPL");
+        // Opens at 100 + 2 * day and closes a dollar higher.
+        let bars: Vec<Bar> = (1..=5)
+            .map(|day| {
+                let open = Decimal::from(100 + 2 * day as i64);
+                Bar::new(
+                    symbol.clone(),
+                    ts(day),
+                    open,
+                    open + Decimal::ONE,
+                    open,
+                    open + Decimal::ONE,
+                    Decimal::from(10_000_000),
+                    Resolution::Day,
+                )
+            })
+            .collect();
+        let entry = |delivery| {
+            let mut engine = test_engine(symbol.clone(), bars.clone());
+            engine.strategy = Box::new(gb_types::BuyAndHoldStrategy::new());
+            let settings = &mut engine.config.execution_settings;
+            settings.bar_delivery = delivery;
+            settings.latency_model = LatencyModel::Fixed { milliseconds: 100 };
+            settings.slippage_model = SlippageModel::Fixed { basis_points: 10 };
+            async move {
+                let result = engine.run().await.unwrap();
+                assert_eq!(
+                    result.metadata["bar_delivery"],
+                    serde_json::to_value(delivery).unwrap()
+                );
+                let fills: Vec<_> = result
+                    .order_events
+                    .iter()
+                    .filter_map(|event| match event {
+                        OrderEvent::OrderFilled { fill, .. } => Some(fill.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                assert_eq!(fills.len(), 1);
+                fills[0].clone()
+            }
+        };
+        // Both decide on the 1st's close of 103 and size 95% of the cash
+        // from it, in whole shares.
+        let quantity = (dec!(95000) / dec!(103)).floor();
+
+        // On close the order waits for the 2nd, and 100ms of latency rounds
+        // up to a whole bar: it fills at the 3rd's open of 106.
+        let on_close = entry(BarDelivery::OnClose).await;
+        assert_eq!(on_close.price, dec!(106.106));
+        assert_eq!(on_close.quantity, quantity);
+
+        // On open the strategy sees the 1st's bar at the 2nd's open and
+        // fills there, at 104.
+        let on_open = entry(BarDelivery::OnOpen).await;
+        assert_eq!(on_open.price, dec!(104.104));
+        assert_eq!(on_open.quantity, quantity);
+        assert_eq!(on_open.executed_at, ts(2));
     }
 
     #[tokio::test]
-    async fn execute_pending_orders_partially_fills_gtc_orders_with_participation_limits() {
+    async fn daily_returns_skip_days_without_bars() {
         let symbol = Symbol::equity("AAPL");
         let mut engine = test_engine(
             symbol.clone(),
             vec![
-                test_bar_with_volume(&symbol, 1, 100, 1_000),
-                test_bar_with_volume(&symbol, 2, 101, 1_000),
-                test_bar_with_volume(&symbol, 3, 102, 1_000),
+                test_bar(&symbol, 1, 100),
+                test_bar(&symbol, 2, 102),
+                test_bar(&symbol, 4, 104),
             ],
         );
-        engine.config.execution_settings.latency_model = LatencyModel::None;
-        engine.config.execution_settings.max_volume_participation = Decimal::new(1, 1); // 10%
-
-        let order = Order::market_order(
-            symbol.clone(),
-            Side::Buy,
-            Decimal::from(150),
-            "noop".to_string(),
-        );
-        engine
-            .process_strategy_action(StrategyAction::PlaceOrder(order))
-            .unwrap();
+        engine.config.end_date = ts(4);
+        let result = engine.run().await.unwrap();
+
+        let portfolio = result.final_portfolio.unwrap();
+        let dates: Vec<_> = portfolio.daily_returns.iter().map(|dr| dr.date).collect();
+        assert_eq!(dates, [ts(1), ts(2), ts(4)]);
+        // The curve still has a point for every simulated day.
+        assert_eq!(result.equity_curve.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn monthly_contributions_grow_equity_without_a_return() {
+        let symbol = Symbol::equity("AAPL");
+        let day = |offset: i64| ts(1) + Duration::days(offset);
+        let flat = Decimal::from(100);
+        let bars: Vec<Bar> = (0..91)
+            .map(|offset| {
+                Bar::new(
+                    symbol.clone(),
+                    day(offset),
+                    flat,
+                    flat,
+                    flat,
+                    flat,
+                    Decimal::from(1_000),
+                    Resolution::Day,
+                )
+            })
+            .collect();
+        let mut engine = test_engine(symbol, bars);
+        engine.config.end_date = day(90);
+        engine.config.cash_flows =
+            CashFlowSettings::default().with_schedule("monthly $1,000".parse().unwrap());
+
+        let result = engine.run().await.unwrap();
+
+        let flows: Vec<_> = result
+            .equity_curve
+            .iter()
+            .filter(|point| !point.cash_flow.is_zero())
+            .map(|point| (point.timestamp, point.cash_flow))
+            .collect();
+        assert_eq!(
+            flows,
+            [
+                (day(31), Decimal::from(1_000)),
+                (day(60), Decimal::from(1_000))
+            ]
+        );
+        let last = result.equity_curve.last().unwrap();
+        assert_eq!(last.portfolio_value, Decimal::from(102_000));
+        assert!(result.equity_curve.iter().all(|point| point
+            .daily_return
+            .unwrap_or_default()
+            .is_zero()
+            && point.cumulative_return.is_zero()
+            && point.drawdown.is_zero()));
+
+        let metrics = result.performance_metrics.unwrap();
+        assert_eq!(metrics.total_return, Decimal::ZERO);
+        assert_eq!(metrics.max_drawdown, Decimal::ZERO);
+        assert!(metrics.money_weighted_return.unwrap().abs() < Decimal::new(1, 9));
+        assert_eq!(
+            result.final_portfolio.unwrap().net_deposits(),
+            Decimal::from(2_000)
+        );
+    }
+
+    /// Places scripted orders, optionally tagged, at the end of given days.
+    #[derive(Debug, Clone)]
+    struct ScriptedStrategy {
+        config: StrategyConfig,
+        orders: Vec<(DateTime<Utc>, Side, i64, Option<&'static str>)>,
+    }
+
+    impl Strategy for ScriptedStrategy {
+        fn initialize(&mut self, _config: &StrategyConfig) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn on_market_event(
+            &mut self,
+            _event: &MarketEvent,
+            _context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            Ok(vec![])
+        }
+
+        fn on_order_event(
+            &mut self,
+            _event: &OrderEvent,
+            _context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            Ok(vec![])
+        }
+
+        fn on_day_end(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+            let symbol = Symbol::equity("AAPL");
+            Ok(self
+                .orders
+                .iter()
+                .filter(|(day, _, _, _)| *day == context.current_time)
+                .map(|(_, side, quantity, tag)| {
+                    let order = Order::market_order(
+                        symbol.clone(),
+                        *side,
+                        Decimal::from(*quantity),
+                        "scripted".to_string(),
+                    );
+                    StrategyAction::PlaceOrder(match tag {
+                        Some(tag) => order.with_tag(*tag),
+                        None => order,
+                    })
+                })
+                .collect())
+        }
+
+        fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+            Ok(vec![])
+        }
+
+        fn on_finish(&self, _context: &StrategyContext) -> HashMap<String, serde_json::Value> {
+            HashMap::from([(
+                "signals_fired".to_string(),
+                serde_json::json!(self.orders.len()),
+            )])
+        }
+
+        fn get_config(&self) -> &StrategyConfig {
+            &self.config
+        }
+
+        fn get_metrics(&self) -> StrategyMetrics {
+            StrategyMetrics::new("scripted".to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn position_snapshots_show_end_of_day_quantities() {
+        let symbol = Symbol::equity("AAPL");
+        let bars: Vec<Bar> = (1..=5).map(|day| test_bar(&symbol, day, 100)).collect();
+        let mut engine = test_engine(symbol.clone(), bars.clone());
+        engine.config.execution_settings.latency_model = LatencyModel::None;
+        // Orders placed at the end of a day fill on the next day's bar: 5
+        // shares on the 2nd, then a 10 share round trip on the 3rd.
+        engine.strategy = Box::new(ScriptedStrategy {
+            config: StrategyConfig::new("scripted".to_string(), "Scripted".to_string()),
+            orders: vec![
+                (ts(1), Side::Buy, 5, None),
+                (ts(2), Side::Buy, 10, None),
+                (ts(2), Side::Sell, 10, None),
+            ],
+        });
+        let mut events = engine.subscribe();
+
+        let result = engine.run().await.unwrap();
+
+        let trades_on_3rd = result
+            .trade_log
+            .iter()
+            .filter(|trade| trade.entry_time == ts(3))
+            .count();
+        assert_eq!(trades_on_3rd, 2, "the round trip fills on the 3rd");
+        let history = &result.positions_history;
+        let dates: Vec<_> = history.iter().map(|snapshot| snapshot.timestamp).collect();
+        assert_eq!(dates, [ts(1), ts(2), ts(3), ts(4), ts(5)]);
+        assert!(history[0].positions.is_empty());
+        for snapshot in &history[1..] {
+            assert_eq!(snapshot.positions.len(), 1);
+            assert_eq!(snapshot.positions[0].symbol, symbol);
+            assert_eq!(snapshot.positions[0].quantity, Decimal::from(5));
+            assert_eq!(snapshot.positions[0].market_value, Decimal::from(500));
+        }
+
+        let mut streamed = 0;
+        while let Ok(event) = events.try_recv() {
+            if let BacktestEvent::PositionsSnapshot { snapshot, .. } = event {
+                assert_eq!(snapshot, history[streamed]);
+                streamed += 1;
+            }
+        }
+        assert_eq!(streamed, history.len());
+
+        let mut engine = test_engine(symbol.clone(), bars);
+        engine.config.data_settings.position_snapshots = SnapshotCadence::Off;
+        assert!(engine.run().await.unwrap().positions_history.is_empty());
+    }
+
+    #[tokio::test]
+    async fn execution_report_attributes_the_gap_from_close_price_fills() {
+        let symbol = Symbol::equity("AAPL");
+        let bars: Vec<Bar> = [100, 102, 105, 103, 101]
+            .into_iter()
+            .zip(1..)
+            .map(|(price, day)| test_bar_with_volume(&symbol, day, price, 1_000_000))
+            .collect();
+        let mut engine = test_engine(symbol.clone(), bars);
+        let settings = &mut engine.config.execution_settings;
+        settings.latency_model = LatencyModel::Fixed {
+            milliseconds: 86_400_000,
+        };
+        settings.slippage_model = SlippageModel::Fixed { basis_points: 10 };
+        settings.commission_per_share = dec!(0.01);
+        settings.commission_percentage = Decimal::ZERO;
+        settings.minimum_commission = Decimal::ZERO;
+        // Decided at the close of the 1st (100) and 3rd (105); one bar of
+        // latency fills them on the bars of the 3rd (105) and 5th (101).
+        engine.strategy = Box::new(ScriptedStrategy {
+            config: StrategyConfig::new("scripted".to_string(), "Scripted".to_string()),
+            orders: vec![(ts(1), Side::Buy, 10, None), (ts(3), Side::Sell, 10, None)],
+        });
+
+        let result = engine.run().await.unwrap();
+
+        let fills: Vec<_> = result
+            .order_events
+            .iter()
+            .filter_map(|event| match event {
+                OrderEvent::OrderFilled { fill, .. } => Some(fill.clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(fills.len(), 2);
+        let buy_price = (dec!(105) * dec!(1.001)).round_dp(6);
+        let sell_price = (dec!(101) / dec!(1.001)).round_dp(6);
+        assert_eq!(fills[0].price, buy_price);
+        assert_eq!(fills[1].price, sell_price);
+
+        let ideal_shortfall =
+            (buy_price - dec!(100)) * dec!(10) + (dec!(105) - sell_price) * dec!(10);
+        let report = &result.execution_report;
+        let totals = &report.totals;
+        assert_eq!(totals.fills, 2);
+        assert_eq!(totals.commission, dec!(0.2));
+        assert_eq!(totals.total(), ideal_shortfall + dec!(0.2));
+        assert_eq!(totals.latency_drift, dec!(50) + dec!(40));
+        assert_eq!(totals.market_impact, Decimal::ZERO);
+        assert_eq!(
+            totals.slippage,
+            (buy_price - dec!(105)) * dec!(10) + (dec!(101) - sell_price) * dec!(10)
+        );
+        assert_eq!(
+            totals.total_bps(),
+            totals.total() / ((buy_price + sell_price) * dec!(10)) * dec!(10000)
+        );
+        assert_eq!(report.symbol(&symbol), Some(totals));
+    }
+
+    fn frictionless(engine: &mut Engine) {
+        let settings = &mut engine.config.execution_settings;
+        settings.latency_model = LatencyModel::None;
+        settings.slippage_model = SlippageModel::None;
+        settings.commission_per_share = Decimal::ZERO;
+        settings.commission_percentage = Decimal::ZERO;
+        settings.minimum_commission = Decimal::ZERO;
+    }
+
+    fn rejections(result: &BacktestResult) -> Vec<&str> {
+        result
+            .order_events
+            .iter()
+            .filter_map(|event| match event {
+                OrderEvent::OrderRejected { reason, .. } => Some(reason.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn cash_account_rebuys_only_with_settled_proceeds() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = (1..=6)
+            .map(|day| test_bar_with_volume(&symbol, day, 100, 1_000_000))
+            .collect();
+        let mut engine = test_engine(symbol.clone(), bars);
+        engine.config.end_date = ts(6);
+        frictionless(&mut engine);
+        engine.config.execution_settings.buying_power = Some(BuyingPowerModel::cash_account());
+        // The sale fills on Wednesday the 3rd and settles on the 4th, so the
+        // rebuy placed on the 3rd is refused and the one on the 4th is not.
+        engine.strategy = Box::new(ScriptedStrategy {
+            config: StrategyConfig::new("scripted".to_string(), "Scripted".to_string()),
+            orders: vec![
+                (ts(1), Side::Buy, 1_000, None),
+                (ts(2), Side::Sell, 1_000, None),
+                (ts(3), Side::Buy, 500, None),
+                (ts(4), Side::Buy, 500, None),
+            ],
+        });
+
+        let result = engine.run().await.unwrap();
+
+        let rejected = rejections(&result);
+        assert_eq!(rejected.len(), 1);
+        assert!(rejected[0].contains("settled cash is 0"));
+        let portfolio = result.final_portfolio.unwrap();
+        assert_eq!(portfolio.positions[&symbol].quantity, Decimal::from(500));
+    }
+
+    #[tokio::test]
+    async fn margin_account_allows_twice_equity_and_warns_on_maintenance() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = [100, 100, 100, 60, 60]
+            .into_iter()
+            .zip(1..)
+            .map(|(price, day)| test_bar_with_volume(&symbol, day, price, 1_000_000))
+            .collect();
+        let mut engine = test_engine(symbol.clone(), bars);
+        frictionless(&mut engine);
+        engine.config.execution_settings.buying_power = Some(BuyingPowerModel::reg_t());
+        engine.strategy = Box::new(ScriptedStrategy {
+            config: StrategyConfig::new("scripted".to_string(), "Scripted".to_string()),
+            orders: vec![
+                (ts(1), Side::Buy, 2_000, None),
+                (ts(2), Side::Buy, 10, None),
+            ],
+        });
+        let mut events = engine.subscribe();
+
+        let result = engine.run().await.unwrap();
+
+        // $200k of stock on $100k of equity, and not a share more.
+        let portfolio = result.final_portfolio.as_ref().unwrap();
+        assert_eq!(portfolio.positions[&symbol].quantity, Decimal::from(2_000));
+        let rejected = rejections(&result);
+        assert_eq!(rejected.len(), 1);
+        assert!(rejected[0].contains("initial margin"));
+
+        // At $60 equity is $20k against a $30k maintenance requirement.
+        let mut calls = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let BacktestEvent::MarginCall {
+                timestamp, call, ..
+            } = event
+            {
+                calls.push((timestamp, call));
+            }
+        }
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0, ts(4));
+        assert_eq!(calls[0].1.equity, Decimal::from(20_000));
+        assert_eq!(calls[0].1.maintenance_requirement, Decimal::from(30_000));
+    }
+
+    #[tokio::test]
+    async fn twap_fills_one_slice_per_bar_at_the_closes() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = [100, 102, 104, 106, 108]
+            .into_iter()
+            .zip(1..)
+            .map(|(price, day)| test_bar_with_volume(&symbol, day, price, 1_000_000))
+            .collect();
+        let mut engine = test_engine(symbol.clone(), bars);
+        frictionless(&mut engine);
+        let order =
+            Order::market_order(symbol.clone(), Side::Buy, Decimal::from(100), "twap".into())
+                .with_execution_algo(ExecutionAlgo::Twap { bars: 4 });
+        let order_id = order.id;
+        engine.current_time = ts(1);
+        engine.process_market_data().await.unwrap();
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(order))
+            .unwrap();
+        for day in 2..=5 {
+            engine.current_time = ts(day);
+            engine.process_market_data().await.unwrap();
+            engine.execute_pending_orders().await.unwrap();
+        }
+
+        let fills: Vec<&Fill> = engine
+            .order_events
+            .iter()
+            .filter_map(OrderEvent::fill)
+            .collect();
+        assert_eq!(fills.len(), 4);
+        assert!(fills.iter().all(|fill| fill.order_id == order_id));
+        assert!(fills.iter().all(|fill| fill.quantity == Decimal::from(25)));
+        assert!(engine.pending_orders.is_empty());
+
+        // The mean of the four closes, against 100 on arrival.
+        let parent = &engine.execution_report.algo_orders[0];
+        assert_eq!(parent.child_fills, 4);
+        assert_eq!(parent.average_price, Decimal::from(105));
+        assert_eq!(parent.arrival_price, Decimal::from(100));
+        assert_eq!(parent.implementation_shortfall(), Decimal::from(500));
+        assert_eq!(parent.shortfall_bps(), Decimal::from(500));
+    }
+
+    #[tokio::test]
+    async fn strategy_finish_metrics_are_kept_in_result_metadata() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = (1..=5).map(|day| test_bar(&symbol, day, 100)).collect();
+        let mut engine = test_engine(symbol, bars);
+        engine.strategy = Box::new(ScriptedStrategy {
+            config: StrategyConfig::new("scripted".to_string(), "Scripted".to_string()),
+            orders: vec![(ts(1), Side::Buy, 10, None), (ts(2), Side::Sell, 10, None)],
+        });
+
+        let result = engine.run().await.unwrap();
+
+        assert_eq!(
+            result.metadata["strategy"],
+            serde_json::json!({ "signals_fired": 2 })
+        );
+    }
+
+    /// Spends 10ms of every `on_day_end` asleep.
+    struct SleepyStrategy(NoopStrategy);
+
+    impl Strategy for SleepyStrategy {
+        fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+            self.0.initialize(config)
+        }
+
+        fn on_market_event(
+            &mut self,
+            event: &MarketEvent,
+            context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            self.0.on_market_event(event, context)
+        }
+
+        fn on_order_event(
+            &mut self,
+            event: &OrderEvent,
+            context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            self.0.on_order_event(event, context)
+        }
+
+        fn on_day_end(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            self.0.on_day_end(context)
+        }
+
+        fn on_stop(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+            self.0.on_stop(context)
+        }
+
+        fn get_config(&self) -> &StrategyConfig {
+            self.0.get_config()
+        }
+
+        fn get_metrics(&self) -> StrategyMetrics {
+            self.0.get_metrics()
+        }
+    }
+
+    #[tokio::test]
+    async fn profiler_attributes_a_slow_day_end_to_its_callback() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = (1..=5).map(|day| test_bar(&symbol, day, 100)).collect();
+        let mut engine = test_engine(symbol.clone(), bars);
+        engine.strategy = Box::new(SleepyStrategy(NoopStrategy::new()));
+        engine.profiler = Profiler::new(true);
+        let mut events = engine.events.subscribe();
+
+        let result = engine.run().await.unwrap();
+
+        let profile: RunProfile =
+            serde_json::from_value(result.metadata["profile"].clone()).unwrap();
+        let day_end = profile.phase(ProfilePhase::OnDayEnd).unwrap();
+        assert_eq!(day_end.calls, 5);
+        assert!(day_end.total_seconds >= 0.05);
+        assert!(day_end.p95_seconds >= 0.01);
+        assert_eq!(profile.slowest().unwrap().phase, ProfilePhase::OnDayEnd);
+        let on_bar = profile.phase(ProfilePhase::OnMarketEvent).unwrap();
+        assert_eq!(on_bar.calls, 5);
+        assert_eq!(on_bar.by_symbol[0].symbol, symbol);
+        assert!(profile.phase(ProfilePhase::Stepping).unwrap().total_seconds < 0.05);
+
+        let mut streamed = None;
+        while let Ok(event) = events.try_recv() {
+            if let BacktestEvent::Profile { profile, .. } = event {
+                streamed = Some(profile);
+            }
+        }
+        assert_eq!(streamed, Some(profile));
+    }
+
+    #[tokio::test]
+    async fn unprofiled_runs_carry_no_profile() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = (1..=5).map(|day| test_bar(&symbol, day, 100)).collect();
+        let mut engine = test_engine(symbol, bars);
+
+        let result = engine.run().await.unwrap();
+
+        assert!(!result.metadata.contains_key("profile"));
+    }
+
+    #[tokio::test]
+    async fn exits_are_attributed_to_the_tags_of_their_entries() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = [100, 100, 110, 100, 90, 90, 120]
+            .into_iter()
+            .zip(1..)
+            .map(|(price, day)| test_bar(&symbol, day, price))
+            .collect();
+        let mut engine = test_engine(symbol, bars);
+        engine.config.end_date = ts(7);
+        let settings = &mut engine.config.execution_settings;
+        settings.latency_model = LatencyModel::None;
+        settings.slippage_model = SlippageModel::None;
+        settings.commission_per_share = Decimal::ZERO;
+        settings.commission_percentage = Decimal::ZERO;
+        settings.minimum_commission = Decimal::ZERO;
+        // Each order fills on the next day's bar: a breakout entry at 100
+        // exits at 110, a hedge at 100 exits at 90, and a second breakout
+        // at 90 exits at 120.
+        engine.strategy = Box::new(ScriptedStrategy {
+            config: StrategyConfig::new("scripted".to_string(), "Scripted".to_string()),
+            orders: vec![
+                (ts(1), Side::Buy, 10, Some("breakout")),
+                (ts(2), Side::Sell, 10, None),
+                (ts(3), Side::Buy, 10, Some("hedge")),
+                (ts(4), Side::Sell, 10, None),
+                (ts(5), Side::Buy, 10, Some("breakout")),
+                (ts(6), Side::Sell, 10, None),
+            ],
+        });
+
+        let result = engine.run().await.unwrap();
+
+        assert_eq!(result.trade_log.len(), 6);
+        let exit = &result.trade_log[1];
+        assert_eq!(exit.pnl, Some(Decimal::from(100)));
+        assert_eq!(exit.tags, ["fill", "breakout"]);
+        let attribution = result.trade_attribution();
+        let breakout = attribution.tag("breakout").unwrap();
+        assert_eq!((breakout.trades, breakout.pnl), (4, Decimal::from(400)));
+        let hedge = attribution.tag("hedge").unwrap();
+        assert_eq!((hedge.trades, hedge.pnl), (2, Decimal::from(-100)));
+        assert_eq!(attribution.tag("fill").unwrap().pnl, Decimal::from(300));
+    }
+
+    #[test]
+    fn weekly_and_monthly_snapshots_fall_on_the_last_step_of_the_period() {
+        let at = |month, day| Utc.with_ymd_and_hms(2024, month, day, 0, 0, 0).unwrap();
+        let end = at(3, 6);
+        // 2024-01-07 is a Sunday and 2024-01-31 the end of the month.
+        assert!(snapshot_due(SnapshotCadence::Weekly, at(1, 7), end));
+        assert!(!snapshot_due(SnapshotCadence::Weekly, at(1, 6), end));
+        assert!(snapshot_due(SnapshotCadence::Monthly, at(1, 31), end));
+        assert!(!snapshot_due(SnapshotCadence::Monthly, at(1, 30), end));
+        assert!(snapshot_due(SnapshotCadence::Monthly, end, end));
+        assert!(!snapshot_due(SnapshotCadence::Off, end, end));
+    }
+
+    #[tokio::test]
+    async fn benchmark_curve_forward_fills_a_benchmark_holiday() {
+        let symbol = Symbol::equity("AAPL");
+        let index = Symbol::equity("SPY");
+        let bars = (1..=5).map(|day| test_bar(&symbol, day, 100)).collect();
+        // The benchmark did not trade on the 3rd.
+        let benchmark = vec![
+            test_bar(&index, 1, 400),
+            test_bar(&index, 2, 410),
+            test_bar(&index, 4, 420),
+            test_bar(&index, 5, 380),
+        ];
+        let mut engine = test_engine(symbol, bars).with_benchmark(benchmark);
+
+        let result = engine.run().await.unwrap();
+
+        assert_eq!(result.equity_curve.len(), 5);
+        let values: Vec<_> = result
+            .benchmark_curve
+            .iter()
+            .map(|point| point.portfolio_value)
+            .collect();
+        assert_eq!(
+            values,
+            [100_000, 102_500, 102_500, 105_000, 95_000].map(Decimal::from)
+        );
+        let dates: Vec<_> = result
+            .benchmark_curve
+            .iter()
+            .map(|point| point.timestamp)
+            .collect();
+        assert_eq!(dates, [ts(1), ts(2), ts(3), ts(4), ts(5)]);
+
+        // The flat portfolio's excess return is minus the benchmark's.
+        let excess: Vec<_> = result.excess_returns.iter().map(|(_, r)| *r).collect();
+        assert_eq!(excess.len(), 4);
+        assert_eq!(excess[0], Decimal::new(-25, 3));
+        assert_eq!(excess[1], Decimal::ZERO);
+        assert_eq!(
+            excess[3],
+            -(Decimal::from(380) / Decimal::from(420) - Decimal::ONE)
+        );
+    }
+
+    #[test]
+    fn process_strategy_action_keeps_pending_order_snapshots_in_sync() {
+        let symbol = Symbol::equity("AAPL");
+        let mut engine = test_engine(symbol.clone(), vec![test_bar(&symbol, 1, 100)]);
+        let order = Order::market_order(symbol, Side::Buy, Decimal::from(5), "noop".to_string());
+        let order_id = order.id;
+
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(order.clone()))
+            .unwrap();
+        assert_eq!(engine.pending_orders.len(), 1);
+        assert_eq!(engine.strategy_context.pending_orders.len(), 1);
+        assert_eq!(engine.strategy_context.pending_orders[0].id, order_id);
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderSubmitted(submitted)) if submitted.id == order_id
+        ));
+
+        engine
+            .process_strategy_action(StrategyAction::CancelOrder { order_id })
+            .unwrap();
+        assert!(engine.pending_orders.is_empty());
+        assert!(engine.strategy_context.pending_orders.is_empty());
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderCanceled { order_id: canceled_id, .. }) if *canceled_id == order_id
+        ));
+    }
+
+    #[test]
+    fn process_strategy_action_rejects_non_positive_quantity() {
+        let symbol = Symbol::equity("AAPL");
+        let mut engine = test_engine(symbol.clone(), vec![test_bar(&symbol, 1, 100)]);
+        let mut order = Order::market_order(symbol, Side::Buy, Decimal::ZERO, "noop".to_string());
+        order.quantity = Decimal::ZERO;
+        order.remaining_quantity = Decimal::ZERO;
+
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(order.clone()))
+            .unwrap();
+
+        assert!(engine.pending_orders.is_empty());
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderRejected { order_id, reason }) if *order_id == order.id && reason.contains("positive")
+        ));
+    }
+
+    #[tokio::test]
+    async fn trade_option_rejects_naked_puts_beyond_buying_power() {
+        let symbol = Symbol::equity("AAPL");
+        let mut engine = test_engine(symbol.clone(), vec![test_bar(&symbol, 1, 100)]);
+        engine.process_market_data().await.unwrap();
+        let naked_puts = |contracts: i64| {
+            StrategyAction::TradeOption(OptionOrder::new(
+                symbol.clone(),
+                OptionKind::Put,
+                Side::Sell,
+                Decimal::from(contracts),
+                Decimal::from(95),
+                ts(30),
+                Decimal::new(25, 2),
+            ))
+        };
+
+        // 100 puts need roughly 100 × 100 × (20 − 5 + premium) = $150k+.
+        engine.process_strategy_action(naked_puts(100)).unwrap();
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderRejected { reason, .. }) if reason.contains("insufficient buying power")
+        ));
+        assert!(engine.portfolio.positions.is_empty());
+        assert!(engine.open_options.is_empty());
+
+        engine.process_strategy_action(naked_puts(5)).unwrap();
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderFilled { fill, .. }) if fill.quantity == Decimal::from(5)
+        ));
+        assert_eq!(engine.open_options.len(), 1);
+        assert!(engine.portfolio.cash > Decimal::from(100_000));
+    }
+
+    #[tokio::test]
+    async fn option_fill_model_charges_wider_spreads_out_of_the_money() {
+        let symbol = Symbol::equity("AAPL");
+        let mut engine = test_engine(symbol.clone(), vec![test_bar(&symbol, 1, 100)])
+            .with_option_fill_model(OptionsFillModel::default());
+        engine.process_market_data().await.unwrap();
+        let buy_call = |strike: i64| {
+            OptionOrder::new(
+                symbol.clone(),
+                OptionKind::Call,
+                Side::Buy,
+                Decimal::ONE,
+                Decimal::from(strike),
+                ts(30),
+                Decimal::new(25, 2),
+            )
+        };
+
+        // Premium paid over the mark, as a fraction of the mark.
+        let mut slippage = |order: OptionOrder| {
+            engine
+                .process_strategy_action(StrategyAction::TradeOption(order))
+                .unwrap();
+            let Some(OrderEvent::OrderFilled { fill, .. }) = engine.order_events.last() else {
+                panic!("expected a fill, got {:?}", engine.order_events.last());
+            };
+            let held = engine.portfolio.get_position(&fill.symbol).unwrap();
+            let mark = held.market_value / (held.quantity * held.multiplier);
+            decimal_to_f64((fill.price - mark) / mark)
+        };
+        let atm = slippage(buy_call(100));
+        let otm = slippage(buy_call(115));
+        assert!(atm > 0.0, "atm slippage = {atm}");
+        assert!(otm > 2.0 * atm, "otm {otm} vs atm {atm}");
+
+        // A bid far below the market is canceled rather than filled.
+        engine
+            .process_strategy_action(StrategyAction::TradeOption(
+                buy_call(105).with_limit_price(Decimal::new(1, 2)),
+            ))
+            .unwrap();
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderCanceled { reason, .. }) if reason.contains("not filled")
+        ));
+    }
+
+    #[tokio::test]
+    async fn trade_option_without_implied_volatility_uses_historical_volatility() {
+        let symbol = Symbol::equity("AAPL");
+        let bars = (1..=25)
+            .map(|day| test_bar(&symbol, day, if day % 2 == 0 { 102 } else { 100 }))
+            .collect();
+        let mut engine = test_engine(symbol.clone(), bars);
+        let buy_call = || {
+            StrategyAction::TradeOption(OptionOrder::new(
+                symbol.clone(),
+                OptionKind::Call,
+                Side::Buy,
+                Decimal::ONE,
+                Decimal::from(100),
+                ts(31),
+                Decimal::ZERO,
+            ))
+        };
+
+        engine.process_market_data().await.unwrap();
+        engine.process_strategy_action(buy_call()).unwrap();
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderRejected { reason, .. }) if reason.contains("too little")
+        ));
+
+        for day in 2..=25 {
+            engine.current_time = ts(day);
+            engine.process_market_data().await.unwrap();
+        }
+        engine.process_strategy_action(buy_call()).unwrap();
+        let expected = historical_vol(
+            &engine.market_data[&symbol],
+            HISTORICAL_VOL_WINDOW,
+            VolEstimator::CloseToClose,
+        )
+        .unwrap()
+        .volatility;
+        assert_eq!(engine.open_options.len(), 1);
+        assert_eq!(engine.open_options[0].volatility, expected);
+        assert!(expected > 0.2, "volatility = {expected}");
+    }
+
+    #[tokio::test]
+    async fn execute_pending_orders_partially_fills_gtc_orders_with_participation_limits() {
+        let symbol = Symbol::equity("AAPL");
+        let mut engine = test_engine(
+            symbol.clone(),
+            vec![
+                test_bar_with_volume(&symbol, 1, 100, 1_000),
+                test_bar_with_volume(&symbol, 2, 101, 1_000),
+                test_bar_with_volume(&symbol, 3, 102, 1_000),
+            ],
+        );
+        engine.config.execution_settings.latency_model = LatencyModel::None;
+        engine.config.execution_settings.max_volume_participation = Decimal::new(1, 1); // 10%
+
+        let order = Order::market_order(
+            symbol.clone(),
+            Side::Buy,
+            Decimal::from(150),
+            "noop".to_string(),
+        );
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(order))
+            .unwrap();
 
         engine.current_time = ts(2);
         engine.execute_pending_orders().await.unwrap();
@@ -1756,6 +3971,52 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn orders_are_sized_to_the_quantity_policy() {
+        let symbol = Symbol::equity("AAPL");
+        let mut engine = test_engine(
+            symbol.clone(),
+            vec![test_bar(&symbol, 1, 100), test_bar(&symbol, 2, 100)],
+        );
+        engine.config.execution_settings.latency_model = LatencyModel::None;
+        engine.config.execution_settings.slippage_model = SlippageModel::None;
+
+        let too_small = Order::market_order(
+            symbol.clone(),
+            Side::Buy,
+            Decimal::new(5, 1),
+            "noop".to_string(),
+        );
+        let too_small_id = too_small.id;
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(too_small))
+            .unwrap();
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderRejected { order_id, reason })
+                if *order_id == too_small_id && reason.contains("below the minimum")
+        ));
+
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(Order::market_order(
+                symbol.clone(),
+                Side::Buy,
+                Decimal::new(125, 1),
+                "noop".to_string(),
+            )))
+            .unwrap();
+        engine.current_time = ts(2);
+        engine.execute_pending_orders().await.unwrap();
+
+        let position = engine.portfolio.get_position(&symbol).unwrap();
+        assert_eq!(position.quantity, Decimal::from(12));
+        let commission = engine.calculate_commission(Decimal::from(12), Decimal::from(100));
+        assert_eq!(
+            engine.portfolio.cash,
+            Decimal::from(100_000 - 1_200) - commission
+        );
+    }
+
     #[tokio::test]
     async fn execute_pending_orders_expires_day_orders_when_not_marketable() {
         let symbol = Symbol::equity("AAPL");
@@ -1830,6 +4091,7 @@ mod tests {
                 "Detected 1 duplicate timestamp rows for NASDAQ:AAPL.".to_string()
             ],
             warnings: Vec::new(),
+            ..DataValidationSummary::default()
         };
         data_manager
             .catalog
@@ -1852,4 +4114,393 @@ mod tests {
         assert!(error.contains("data quality mode 'fail' rejected the dataset"));
         assert!(error.contains("duplicate timestamp"));
     }
+
+    /// On the first day, buys each symbol with half the cash not yet
+    /// committed to pending buys.
+    struct HalfOfUncommittedCash(NoopStrategy);
+
+    impl Strategy for HalfOfUncommittedCash {
+        fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+            self.0.initialize(config)
+        }
+
+        fn on_market_event(
+            &mut self,
+            event: &MarketEvent,
+            context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            if context.current_time != ts(1) {
+                return Ok(vec![]);
+            }
+            let committed: Decimal = context
+                .pending_orders
+                .iter()
+                .map(|order| {
+                    order.quantity * context.get_current_price(&order.symbol).unwrap_or_default()
+                })
+                .sum();
+            let price = context.get_current_price(event.symbol()).unwrap();
+            let quantity = ((context.portfolio.cash - committed) / dec!(2) / price).floor();
+            Ok(vec![StrategyAction::PlaceOrder(Order::market_order(
+                event.symbol().clone(),
+                Side::Buy,
+                quantity,
+                "noop".to_string(),
+            ))])
+        }
+
+        fn on_order_event(
+            &mut self,
+            event: &OrderEvent,
+            context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            self.0.on_order_event(event, context)
+        }
+
+        fn on_day_end(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+            self.0.on_day_end(context)
+        }
+
+        fn on_stop(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+            self.0.on_stop(context)
+        }
+
+        fn get_config(&self) -> &StrategyConfig {
+            self.0.get_config()
+        }
+
+        fn get_metrics(&self) -> StrategyMetrics {
+            self.0.get_metrics()
+        }
+    }
+
+    /// Runs [`HalfOfUncommittedCash`] on a $100 and a $50 stock named
+    /// `names`, configured in name order as a screen would list them, and
+    /// returns the final equity and the shares held of each stock.
+    async fn run_named_pair(names: [&str; 2], ordering: EventOrdering) -> (Decimal, [Decimal; 2]) {
+        let symbols = names.map(Symbol::equity);
+        let mut engine = test_engine(
+            symbols[0].clone(),
+            (1..=5)
+                .map(|day| test_bar_with_volume(&symbols[0], day, 100, 1_000_000))
+                .collect(),
+        );
+        engine.strategy = Box::new(HalfOfUncommittedCash(NoopStrategy::new()));
+        engine.config.execution_settings.event_ordering = ordering;
+        engine.market_data.insert(
+            symbols[1].clone(),
+            (1..=5)
+                .map(|day| test_bar_with_volume(&symbols[1], day, 50, 1_000_000))
+                .collect(),
+        );
+        engine.next_bar_indices.insert(symbols[1].clone(), 0);
+        engine.strategy_context.market_data.insert(
+            symbols[1].clone(),
+            MarketDataBuffer::new(symbols[1].clone(), STRATEGY_MARKET_DATA_WINDOW),
+        );
+        let mut configured = symbols.to_vec();
+        configured.sort_by_key(|symbol| symbol.symbol.clone());
+        engine.config.symbols = configured;
+
+        let result = engine.run().await.unwrap();
+
+        assert_eq!(
+            result.metadata["event_ordering"],
+            serde_json::to_value(ordering).unwrap()
+        );
+        let held = |symbol: &Symbol| {
+            engine
+                .portfolio
+                .positions
+                .get(symbol)
+                .map_or(Decimal::ZERO, |position| position.quantity)
+        };
+        (
+            engine.portfolio.total_equity,
+            [held(&symbols[0]), held(&symbols[1])],
+        )
+    }
+
+    #[tokio::test]
+    async fn snapshot_ordering_makes_results_independent_of_symbol_names() {
+        // Renaming flips which stock comes first in the configured order.
+        let original = run_named_pair(["AAA", "ZZZ"], EventOrdering::Snapshot).await;
+        let renamed = run_named_pair(["YYY", "BBB"], EventOrdering::Snapshot).await;
+        assert_eq!(original, renamed);
+        // Both decisions saw the full $100,000.
+        assert_eq!(original.1, [dec!(500), dec!(1000)]);
+
+        // Sequentially, whichever stock comes first gets the larger share.
+        let original = run_named_pair(["AAA", "ZZZ"], EventOrdering::Sequential).await;
+        let renamed = run_named_pair(["YYY", "BBB"], EventOrdering::Sequential).await;
+        assert_eq!(original.1, [dec!(500), dec!(500)]);
+        assert_eq!(renamed.1, [dec!(250), dec!(1000)]);
+    }
+
+    /// AAPL trades every day through the 14th; MSFT is missing the 6th
+    /// through the 12th.
+    fn engine_with_missing_week(policy: StalenessPolicy) -> (Engine, Symbol) {
+        let aapl = Symbol::equity("AAPL");
+        let msft = Symbol::equity("MSFT");
+        let mut engine = test_engine(
+            aapl.clone(),
+            (1..=14).map(|day| test_bar(&aapl, day, 100)).collect(),
+        );
+        let msft_bars = (1..=14)
+            .filter(|day| !(6..=12).contains(day))
+            .map(|day| test_bar(&msft, day, 200 + day as i64))
+            .collect();
+        engine.config.end_date = ts(14);
+        engine.config.symbols.push(msft.clone());
+        engine.config.data_settings.staleness_policy = policy;
+        engine.market_data.insert(msft.clone(), msft_bars);
+        engine.next_bar_indices.insert(msft.clone(), 0);
+        engine.strategy_context.market_data.insert(
+            msft.clone(),
+            MarketDataBuffer::new(msft.clone(), STRATEGY_MARKET_DATA_WINDOW),
+        );
+        (engine, msft)
+    }
+
+    fn stale_events(
+        events: &mut broadcast::Receiver<BacktestEvent>,
+    ) -> Vec<(Symbol, DateTime<Utc>, u32)> {
+        let mut stale = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            if let BacktestEvent::DataStale {
+                symbol,
+                last_bar_at,
+                missing_bars,
+                ..
+            } = event
+            {
+                stale.push((symbol, last_bar_at, missing_bars));
+            }
+        }
+        stale
+    }
+
+    #[tokio::test]
+    async fn mark_stale_flags_a_symbol_missing_a_week_of_bars() {
+        let (mut engine, msft) = engine_with_missing_week(StalenessPolicy::MarkStale);
+        let mut events = engine.subscribe();
+
+        let result = engine.run().await.unwrap();
+
+        let stale_days: Vec<u32> = result
+            .equity_curve
+            .iter()
+            .filter(|point| point.stale_symbols == vec![msft.clone()])
+            .map(|point| point.timestamp.day())
+            .collect();
+        assert_eq!(stale_days, vec![9, 10, 11, 12]);
+        assert!(result.equity_curve.iter().all(
+            |point| point.stale_symbols.is_empty() || point.stale_symbols == vec![msft.clone()]
+        ));
+        assert_eq!(stale_events(&mut events), vec![(msft, ts(5), 4)]);
+    }
+
+    #[tokio::test]
+    async fn freeze_and_warn_rejects_orders_for_a_stale_symbol() {
+        let (mut engine, msft) = engine_with_missing_week(StalenessPolicy::FreezeAndWarn);
+        let aapl = Symbol::equity("AAPL");
+        let mut events = engine.subscribe();
+        for day in 1..=9 {
+            engine.current_time = ts(day);
+            engine.process_market_data().await.unwrap();
+            engine.update_portfolio_values().await.unwrap();
+        }
+        assert_eq!(stale_events(&mut events), vec![(msft.clone(), ts(5), 4)]);
+
+        let order = Order::market_order(msft.clone(), Side::Buy, Decimal::ONE, "noop".to_string());
+        let order_id = order.id;
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(order))
+            .unwrap();
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderRejected { order_id: rejected, reason })
+                if *rejected == order_id && reason == "market data for NASDAQ:MSFT is stale"
+        ));
+
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(Order::market_order(
+                aapl,
+                Side::Buy,
+                Decimal::ONE,
+                "noop".to_string(),
+            )))
+            .unwrap();
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderSubmitted(_))
+        ));
+
+        for day in 10..=13 {
+            engine.current_time = ts(day);
+            engine.process_market_data().await.unwrap();
+            engine.update_portfolio_values().await.unwrap();
+        }
+        assert!(engine.stale_symbols().is_empty());
+        engine
+            .process_strategy_action(StrategyAction::PlaceOrder(Order::market_order(
+                msft,
+                Side::Buy,
+                Decimal::ONE,
+                "noop".to_string(),
+            )))
+            .unwrap();
+        assert!(matches!(
+            engine.order_events.last(),
+            Some(OrderEvent::OrderSubmitted(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn fail_run_stops_when_a_symbol_goes_stale() {
+        let (mut engine, _msft) = engine_with_missing_week(StalenessPolicy::FailRun);
+
+        let error = engine.run().await.unwrap_err();
+
+        assert!(matches!(
+            &error,
+            GbError::Backtest(BacktestError::StaleData { symbol, missing_bars: 4, .. })
+                if symbol == "NASDAQ:MSFT"
+        ));
+        assert_eq!(engine.equity_curve.len(), 8, "the run stopped on the 9th");
+    }
+
+    type SeenSessions =
+        Arc<std::sync::Mutex<Vec<(String, DateTime<Utc>, Option<SessionPosition>)>>>;
+
+    /// Records the session position the context carries with each bar.
+    struct SessionRecorder {
+        config: StrategyConfig,
+        seen: SeenSessions,
+    }
+
+    impl Strategy for SessionRecorder {
+        fn initialize(&mut self, _config: &StrategyConfig) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn on_market_event(
+            &mut self,
+            event: &MarketEvent,
+            context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            if let MarketEvent::Bar(bar) = event {
+                self.seen.lock().unwrap().push((
+                    bar.symbol.symbol.clone(),
+                    bar.timestamp,
+                    context.session,
+                ));
+            }
+            Ok(vec![])
+        }
+
+        fn on_order_event(
+            &mut self,
+            _event: &OrderEvent,
+            _context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            Ok(vec![])
+        }
+
+        fn on_day_end(
+            &mut self,
+            _context: &StrategyContext,
+        ) -> Result<Vec<StrategyAction>, String> {
+            Ok(vec![])
+        }
+
+        fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+            Ok(vec![])
+        }
+
+        fn get_config(&self) -> &StrategyConfig {
+            &self.config
+        }
+
+        fn get_metrics(&self) -> StrategyMetrics {
+            StrategyMetrics::new(self.config.strategy_id.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn strategy_context_locates_bars_in_their_sessions_across_days_and_a_half_day() {
+        let wednesday = chrono::NaiveDate::from_ymd_opt(2025, 11, 26).unwrap();
+        let friday = chrono::NaiveDate::from_ymd_opt(2025, 11, 28).unwrap();
+        let at = |date: chrono::NaiveDate, hour| date.and_hms_opt(hour, 0, 0).unwrap().and_utc();
+        let hourly_bars = |symbol: &Symbol| -> Vec<Bar> {
+            [(wednesday, 15), (wednesday, 17), (wednesday, 20)]
+                .into_iter()
+                .chain([(friday, 15), (friday, 17), (friday, 19)])
+                .map(|(date, hour)| {
+                    let price = Decimal::from(100);
+                    Bar::new(
+                        symbol.clone(),
+                        at(date, hour),
+                        price,
+                        price,
+                        price,
+                        price,
+                        Decimal::from(1_000),
+                        Resolution::Hour,
+                    )
+                })
+                .collect()
+        };
+        let aapl = Symbol::equity("AAPL");
+        let msft = Symbol::equity("MSFT");
+        let mut engine = test_engine(aapl.clone(), hourly_bars(&aapl));
+        engine.config.start_date = at(wednesday, 0);
+        engine.config.end_date = at(friday, 0);
+        engine.config.resolution = Resolution::Hour;
+        engine.config.symbols.push(msft.clone());
+        engine.market_data.insert(msft.clone(), hourly_bars(&msft));
+        engine.next_bar_indices.insert(msft, 0);
+        let seen = SeenSessions::default();
+        engine.strategy = Box::new(SessionRecorder {
+            config: StrategyConfig::new("sessions".to_string(), "Sessions".to_string()),
+            seen: seen.clone(),
+        });
+        let mut engine = engine.with_sessions(
+            SessionResolver::new().with_asset_class_calendar(
+                AssetClass::Equity,
+                TradingCalendar::default()
+                    .with_early_close(friday, chrono::NaiveTime::from_hms_opt(13, 0, 0).unwrap()),
+            ),
+        );
+
+        engine.run().await.unwrap();
+
+        let position = |seen, remaining, open, close| {
+            Some(SessionPosition {
+                events_seen: seen,
+                events_remaining: remaining,
+                is_session_open: open,
+                is_session_close: close,
+            })
+        };
+        // Friday's 19:00 bars come after the 13:00 EST (18:00 UTC) early close
+        // and are outside every session
+        let mut expected: Vec<_> = [
+            (at(wednesday, 15), position(2, 4, true, false)),
+            (at(wednesday, 17), position(4, 2, false, false)),
+            (at(wednesday, 20), position(6, 0, false, true)),
+            (at(friday, 15), position(2, 2, true, false)),
+            (at(friday, 17), position(4, 0, false, true)),
+            (at(friday, 19), None),
+        ]
+        .into_iter()
+        .flat_map(|(time, session)| {
+            ["AAPL", "MSFT"].map(|ticker| (ticker.to_string(), time, session))
+        })
+        .collect();
+        let mut seen = seen.lock().unwrap().clone();
+        seen.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+        expected.sort_by(|a, b| (a.1, &a.0).cmp(&(b.1, &b.0)));
+        assert_eq!(seen, expected);
+    }
 }
@@ -245,6 +245,11 @@ impl ExecutionEngine {
                     return Ok(Decimal::ZERO); // Not triggered
                 }
             }
+            gb_types::OrderType::TrailingStop { .. } => {
+                // Trailing stops need a running high/low across bars, which
+                // this single-bar simulator does not track.
+                return Ok(Decimal::ZERO);
+            }
         };
 
         Ok(price)
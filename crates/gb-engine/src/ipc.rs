@@ -0,0 +1,659 @@
+//! Arrow IPC encoding of a backtest's equity curve and trade log, for the API
+//! and UI layers to stream instead of JSON. Metrics stay in the JSON result.
+//!
+//! Buffers are Arrow IPC files (Feather v2), readable with
+//! `pyarrow.feather.read_table` or `tableFromIPC` from `apache-arrow`; the
+//! readers here also accept the IPC stream format. Rows are written in
+//! batches of [`IPC_BATCH_ROWS`]. The schema metadata names the table
+//! (`glowback.table`) and its [`IPC_SCHEMA_VERSION`]
+//! (`glowback.schema_version`). Timestamps are `Timestamp(Nanosecond, "UTC")`
+//! and amounts `Float64`, so decimals with more than 15 significant digits
+//! come back rounded.
+//!
+//! Equity curve columns: `timestamp`, `portfolio_value`, `cash`,
+//! `positions_value`, `total_pnl`, `daily_return` (nullable),
+//! `cumulative_return`, `drawdown`. Stale-symbol flags stay in the JSON result.
+//!
+//! Trade log columns: `id`, `symbol`, `exchange`, `asset_class`,
+//! `entry_time`, `exit_time` (nullable), `entry_price`, `exit_price`
+//! (nullable), `quantity`, `side`, `pnl` (nullable), `commission`,
+//! `duration_hours` (nullable), `strategy_id`, `tags` (list of strings).
+
+use std::collections::HashMap;
+use std::io::Cursor;
+use std::sync::Arc;
+
+use arrow::array::{
+    Array, ArrayRef, AsArray, Decimal128Array, Float64Array, ListBuilder, RecordBatch, StringArray,
+    StringBuilder, TimestampNanosecondArray,
+};
+use arrow::datatypes::{
+    DataType, Decimal128Type, Field, Float64Type, Schema, TimeUnit, TimestampNanosecondType,
+};
+use arrow::error::ArrowError;
+use arrow::ipc::reader::{FileReader, StreamReader};
+use arrow::ipc::writer::FileWriter;
+use chrono::{DateTime, Utc};
+use gb_types::{AssetClass, EquityCurvePoint, GbError, GbResult, Side, Symbol, TradeRecord};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use uuid::Uuid;
+
+/// Version of the table layouts described in the module docs.
+pub const IPC_SCHEMA_VERSION: u32 = 1;
+
+/// Rows per record batch in written buffers.
+pub const IPC_BATCH_ROWS: usize = 65_536;
+
+const TABLE_KEY: &str = "glowback.table";
+const VERSION_KEY: &str = "glowback.schema_version";
+pub(crate) const EQUITY_CURVE_TABLE: &str = "equity_curve";
+pub(crate) const TRADE_LOG_TABLE: &str = "trade_log";
+const ARROW_FILE_MAGIC: &[u8] = b"ARROW1";
+
+/// Precision and scale of [`Amounts::Decimal`] columns.
+const DECIMAL_PRECISION: u8 = 38;
+const DECIMAL_SCALE: i8 = 18;
+
+/// How amount columns are typed. The readers accept either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Amounts {
+    /// `Float64`, for the IPC buffers.
+    Float64,
+    /// `Decimal128(38, 18)`, exact to 18 decimal places.
+    Decimal,
+}
+
+impl Amounts {
+    fn data_type(self) -> DataType {
+        match self {
+            Self::Float64 => DataType::Float64,
+            Self::Decimal => DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE),
+        }
+    }
+
+    fn array(self, values: impl Iterator<Item = Option<Decimal>>) -> GbResult<ArrayRef> {
+        match self {
+            Self::Float64 => Ok(Arc::new(Float64Array::from_iter(
+                values.map(|value| value.map(to_f64)),
+            ))),
+            Self::Decimal => {
+                let mantissas = values
+                    .map(|value| value.map(to_mantissa).transpose())
+                    .collect::<GbResult<Vec<_>>>()?;
+                Ok(Arc::new(
+                    Decimal128Array::from(mantissas)
+                        .with_precision_and_scale(DECIMAL_PRECISION, DECIMAL_SCALE)
+                        .map_err(arrow_error)?,
+                ))
+            }
+        }
+    }
+}
+
+/// An amount column of either [`Amounts`] type.
+enum AmountColumn<'a> {
+    Float64(&'a Float64Array),
+    Decimal(&'a Decimal128Array, u32),
+}
+
+impl AmountColumn<'_> {
+    fn at(&self, row: usize) -> GbResult<Option<Decimal>> {
+        match self {
+            Self::Float64(values) => decimal_at(values, row),
+            Self::Decimal(values, scale) => Ok((!values.is_null(row))
+                .then(|| Decimal::from_i128_with_scale(values.value(row), *scale).normalize())),
+        }
+    }
+}
+
+fn arrow_error(error: ArrowError) -> GbError {
+    GbError::Arrow(error.to_string())
+}
+
+fn timestamp_type() -> DataType {
+    DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".into()))
+}
+
+fn table_schema(table: &str, fields: Vec<Field>) -> Schema {
+    Schema::new(fields).with_metadata(HashMap::from([
+        (TABLE_KEY.to_string(), table.to_string()),
+        (VERSION_KEY.to_string(), IPC_SCHEMA_VERSION.to_string()),
+    ]))
+}
+
+/// Schema of [`write_equity_curve_ipc`] buffers.
+pub fn equity_curve_schema() -> Schema {
+    equity_curve_schema_with(Amounts::Float64)
+}
+
+pub(crate) fn equity_curve_schema_with(amounts: Amounts) -> Schema {
+    let amount = |name: &str, nullable: bool| Field::new(name, amounts.data_type(), nullable);
+    table_schema(
+        EQUITY_CURVE_TABLE,
+        vec![
+            Field::new("timestamp", timestamp_type(), false),
+            amount("portfolio_value", false),
+            amount("cash", false),
+            amount("positions_value", false),
+            amount("total_pnl", false),
+            amount("daily_return", true),
+            amount("cumulative_return", false),
+            amount("drawdown", false),
+        ],
+    )
+}
+
+/// Schema of [`write_trade_log_ipc`] buffers.
+pub fn trade_log_schema() -> Schema {
+    trade_log_schema_with(Amounts::Float64)
+}
+
+pub(crate) fn trade_log_schema_with(amounts: Amounts) -> Schema {
+    let amount = |name: &str, nullable: bool| Field::new(name, amounts.data_type(), nullable);
+    let text = |name: &str| Field::new(name, DataType::Utf8, false);
+    table_schema(
+        TRADE_LOG_TABLE,
+        vec![
+            text("id"),
+            text("symbol"),
+            text("exchange"),
+            text("asset_class"),
+            Field::new("entry_time", timestamp_type(), false),
+            Field::new("exit_time", timestamp_type(), true),
+            amount("entry_price", false),
+            amount("exit_price", true),
+            amount("quantity", false),
+            text("side"),
+            amount("pnl", true),
+            amount("commission", false),
+            Field::new("duration_hours", DataType::Float64, true),
+            text("strategy_id"),
+            Field::new(
+                "tags",
+                DataType::List(Arc::new(Field::new_list_field(DataType::Utf8, true))),
+                false,
+            ),
+        ],
+    )
+}
+
+/// `points` as one record batch in the equity curve schema.
+pub fn equity_curve_record_batch(points: &[EquityCurvePoint]) -> GbResult<RecordBatch> {
+    equity_curve_record_batch_with(points, Amounts::Float64)
+}
+
+pub(crate) fn equity_curve_record_batch_with(
+    points: &[EquityCurvePoint],
+    amounts: Amounts,
+) -> GbResult<RecordBatch> {
+    let column =
+        |value: fn(&EquityCurvePoint) -> Option<Decimal>| amounts.array(points.iter().map(value));
+    let columns: Vec<ArrayRef> = vec![
+        timestamps(points.iter().map(|point| Some(point.timestamp)))?,
+        column(|point| Some(point.portfolio_value))?,
+        column(|point| Some(point.cash))?,
+        column(|point| Some(point.positions_value))?,
+        column(|point| Some(point.total_pnl))?,
+        column(|point| point.daily_return)?,
+        column(|point| Some(point.cumulative_return))?,
+        column(|point| Some(point.drawdown))?,
+    ];
+    RecordBatch::try_new(Arc::new(equity_curve_schema_with(amounts)), columns).map_err(arrow_error)
+}
+
+/// `trades` as one record batch in the trade log schema.
+pub fn trade_log_record_batch(trades: &[TradeRecord]) -> GbResult<RecordBatch> {
+    trade_log_record_batch_with(trades, Amounts::Float64)
+}
+
+pub(crate) fn trade_log_record_batch_with(
+    trades: &[TradeRecord],
+    amounts: Amounts,
+) -> GbResult<RecordBatch> {
+    let text = |value: fn(&TradeRecord) -> String| -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(trades.iter().map(value)))
+    };
+    let column =
+        |value: fn(&TradeRecord) -> Option<Decimal>| amounts.array(trades.iter().map(value));
+    let mut tags = ListBuilder::new(StringBuilder::new());
+    for trade in trades {
+        tags.append_value(trade.tags.iter().map(Some));
+    }
+    let columns: Vec<ArrayRef> = vec![
+        text(|trade| trade.id.to_string()),
+        text(|trade| trade.symbol.symbol.clone()),
+        text(|trade| trade.symbol.exchange.clone()),
+        text(|trade| format!("{:?}", trade.symbol.asset_class)),
+        timestamps(trades.iter().map(|trade| Some(trade.entry_time)))?,
+        timestamps(trades.iter().map(|trade| trade.exit_time))?,
+        column(|trade| Some(trade.entry_price))?,
+        column(|trade| trade.exit_price)?,
+        column(|trade| Some(trade.quantity))?,
+        text(|trade| format!("{:?}", trade.side)),
+        column(|trade| trade.pnl)?,
+        column(|trade| Some(trade.commission))?,
+        Arc::new(Float64Array::from_iter(
+            trades.iter().map(|trade| trade.duration_hours),
+        )),
+        text(|trade| trade.strategy_id.clone()),
+        Arc::new(tags.finish()),
+    ];
+    RecordBatch::try_new(Arc::new(trade_log_schema_with(amounts)), columns).map_err(arrow_error)
+}
+
+/// Encode an equity curve as an Arrow IPC file buffer.
+pub fn write_equity_curve_ipc(points: &[EquityCurvePoint]) -> GbResult<Vec<u8>> {
+    write_ipc(
+        equity_curve_schema(),
+        points.chunks(IPC_BATCH_ROWS),
+        equity_curve_record_batch,
+    )
+}
+
+/// Encode a trade log as an Arrow IPC file buffer.
+pub fn write_trade_log_ipc(trades: &[TradeRecord]) -> GbResult<Vec<u8>> {
+    write_ipc(
+        trade_log_schema(),
+        trades.chunks(IPC_BATCH_ROWS),
+        trade_log_record_batch,
+    )
+}
+
+/// Decode an equity curve from an Arrow IPC file or stream buffer.
+pub fn read_equity_curve_ipc(bytes: &[u8]) -> GbResult<Vec<EquityCurvePoint>> {
+    equity_curve_from_batches(read_ipc(bytes, EQUITY_CURVE_TABLE)?)
+}
+
+/// Decode equity curve batches whose schema passed [`check_table`].
+pub(crate) fn equity_curve_from_batches(
+    batches: impl IntoIterator<Item = RecordBatch>,
+) -> GbResult<Vec<EquityCurvePoint>> {
+    let mut points = Vec::new();
+    for batch in batches {
+        let timestamp = timestamp_column(&batch, "timestamp")?;
+        let amount = |name: &str| amount_column(&batch, name);
+        let (portfolio_value, cash, positions_value, total_pnl) = (
+            amount("portfolio_value")?,
+            amount("cash")?,
+            amount("positions_value")?,
+            amount("total_pnl")?,
+        );
+        let (daily_return, cumulative_return, drawdown) = (
+            amount("daily_return")?,
+            amount("cumulative_return")?,
+            amount("drawdown")?,
+        );
+        for row in 0..batch.num_rows() {
+            points.push(EquityCurvePoint {
+                timestamp: required(timestamp_at(timestamp, row), "timestamp", row)?,
+                portfolio_value: required(portfolio_value.at(row), "portfolio_value", row)?,
+                cash: required(cash.at(row), "cash", row)?,
+                positions_value: required(positions_value.at(row), "positions_value", row)?,
+                total_pnl: required(total_pnl.at(row), "total_pnl", row)?,
+                daily_return: daily_return.at(row)?,
+                cumulative_return: required(cumulative_return.at(row), "cumulative_return", row)?,
+                drawdown: required(drawdown.at(row), "drawdown", row)?,
+                stale_symbols: Vec::new(),
+                cash_flow: Decimal::ZERO,
+            });
+        }
+    }
+    Ok(points)
+}
+
+/// Decode a trade log from an Arrow IPC file or stream buffer.
+pub fn read_trade_log_ipc(bytes: &[u8]) -> GbResult<Vec<TradeRecord>> {
+    trade_log_from_batches(read_ipc(bytes, TRADE_LOG_TABLE)?)
+}
+
+/// Decode trade log batches whose schema passed [`check_table`].
+pub(crate) fn trade_log_from_batches(
+    batches: impl IntoIterator<Item = RecordBatch>,
+) -> GbResult<Vec<TradeRecord>> {
+    let mut trades = Vec::new();
+    for batch in batches {
+        let text = |name: &str| string_column(&batch, name);
+        let amount = |name: &str| amount_column(&batch, name);
+        let (id, symbol, exchange, asset_class, side, strategy_id) = (
+            text("id")?,
+            text("symbol")?,
+            text("exchange")?,
+            text("asset_class")?,
+            text("side")?,
+            text("strategy_id")?,
+        );
+        let (entry_time, exit_time) = (
+            timestamp_column(&batch, "entry_time")?,
+            timestamp_column(&batch, "exit_time")?,
+        );
+        let (entry_price, exit_price, quantity, pnl, commission) = (
+            amount("entry_price")?,
+            amount("exit_price")?,
+            amount("quantity")?,
+            amount("pnl")?,
+            amount("commission")?,
+        );
+        let duration_hours = float_column(&batch, "duration_hours")?;
+        let tags = column(&batch, "tags")?
+            .as_list_opt::<i32>()
+            .ok_or_else(|| column_type_error("tags"))?;
+
+        for row in 0..batch.num_rows() {
+            let tag_values = tags.value(row);
+            let tag_values = tag_values
+                .as_string_opt::<i32>()
+                .ok_or_else(|| column_type_error("tags"))?;
+            trades.push(TradeRecord {
+                id: Uuid::parse_str(id.value(row))
+                    .map_err(|e| GbError::Arrow(format!("trade log row {row} id: {e}")))?,
+                symbol: Symbol::new(
+                    symbol.value(row),
+                    exchange.value(row),
+                    parse_asset_class(asset_class.value(row), row)?,
+                ),
+                entry_time: required(timestamp_at(entry_time, row), "entry_time", row)?,
+                exit_time: timestamp_at(exit_time, row)?,
+                entry_price: required(entry_price.at(row), "entry_price", row)?,
+                exit_price: exit_price.at(row)?,
+                quantity: required(quantity.at(row), "quantity", row)?,
+                side: parse_side(side.value(row), row)?,
+                pnl: pnl.at(row)?,
+                commission: required(commission.at(row), "commission", row)?,
+                duration_hours: (!duration_hours.is_null(row)).then(|| duration_hours.value(row)),
+                strategy_id: strategy_id.value(row).to_string(),
+                tags: tag_values.iter().flatten().map(str::to_string).collect(),
+                metadata: serde_json::Value::Null,
+            });
+        }
+    }
+    Ok(trades)
+}
+
+fn write_ipc<'a, T: 'a>(
+    schema: Schema,
+    chunks: impl Iterator<Item = &'a [T]>,
+    batch: impl Fn(&'a [T]) -> GbResult<RecordBatch>,
+) -> GbResult<Vec<u8>> {
+    let mut writer = FileWriter::try_new(Vec::new(), &schema).map_err(arrow_error)?;
+    for chunk in chunks {
+        writer.write(&batch(chunk)?).map_err(arrow_error)?;
+    }
+    writer.into_inner().map_err(arrow_error)
+}
+
+fn read_ipc(bytes: &[u8], table: &str) -> GbResult<Vec<RecordBatch>> {
+    let (schema, batches) = if bytes.starts_with(ARROW_FILE_MAGIC) {
+        let reader = FileReader::try_new(Cursor::new(bytes), None).map_err(arrow_error)?;
+        let schema = reader.schema();
+        (schema, reader.collect::<Result<Vec<_>, _>>())
+    } else {
+        let reader = StreamReader::try_new(Cursor::new(bytes), None).map_err(arrow_error)?;
+        let schema = reader.schema();
+        (schema, reader.collect::<Result<Vec<_>, _>>())
+    };
+
+    check_table(&schema, table)?;
+    batches.map_err(arrow_error)
+}
+
+/// Check that `schema` is tagged as a GlowBack `table` of a version this
+/// build reads.
+pub(crate) fn check_table(schema: &Schema, table: &str) -> GbResult<()> {
+    let metadata = schema.metadata();
+    if metadata.get(TABLE_KEY).map(String::as_str) != Some(table) {
+        return Err(GbError::Arrow(format!(
+            "expected a GlowBack {table} table, found {}",
+            metadata
+                .get(TABLE_KEY)
+                .map_or("an untagged table", String::as_str)
+        )));
+    }
+    let version = metadata
+        .get(VERSION_KEY)
+        .and_then(|version| version.parse::<u32>().ok())
+        .unwrap_or(0);
+    if version == 0 || version > IPC_SCHEMA_VERSION {
+        return Err(GbError::Arrow(format!(
+            "unsupported {table} schema version {version} (this build reads up to {IPC_SCHEMA_VERSION})"
+        )));
+    }
+    Ok(())
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+/// `value` as a [`DECIMAL_SCALE`] mantissa, rounded beyond 18 places.
+fn to_mantissa(value: Decimal) -> GbResult<i128> {
+    let rounded = value.round_dp(DECIMAL_SCALE as u32);
+    10i128
+        .checked_pow(DECIMAL_SCALE as u32 - rounded.scale())
+        .and_then(|factor| rounded.mantissa().checked_mul(factor))
+        .filter(|mantissa| mantissa.unsigned_abs() < 10u128.pow(DECIMAL_PRECISION as u32))
+        .ok_or_else(|| {
+            GbError::Arrow(format!(
+                "{value} does not fit Decimal128({DECIMAL_PRECISION}, {DECIMAL_SCALE})"
+            ))
+        })
+}
+
+fn timestamps(values: impl Iterator<Item = Option<DateTime<Utc>>>) -> GbResult<ArrayRef> {
+    let nanos = values
+        .map(|value| {
+            value
+                .map(|timestamp| {
+                    timestamp.timestamp_nanos_opt().ok_or_else(|| {
+                        GbError::Arrow(format!(
+                            "timestamp {timestamp} is outside the nanosecond range"
+                        ))
+                    })
+                })
+                .transpose()
+        })
+        .collect::<GbResult<Vec<_>>>()?;
+    Ok(Arc::new(
+        TimestampNanosecondArray::from(nanos).with_timezone("UTC"),
+    ))
+}
+
+fn column<'a>(batch: &'a RecordBatch, name: &str) -> GbResult<&'a ArrayRef> {
+    batch
+        .column_by_name(name)
+        .ok_or_else(|| GbError::Arrow(format!("missing column '{name}'")))
+}
+
+fn column_type_error(name: &str) -> GbError {
+    GbError::Arrow(format!("column '{name}' has an unexpected type"))
+}
+
+fn amount_column<'a>(batch: &'a RecordBatch, name: &str) -> GbResult<AmountColumn<'a>> {
+    let values = column(batch, name)?;
+    if let Some(values) = values.as_primitive_opt::<Float64Type>() {
+        return Ok(AmountColumn::Float64(values));
+    }
+    match (
+        values.as_primitive_opt::<Decimal128Type>(),
+        values.data_type(),
+    ) {
+        (Some(values), DataType::Decimal128(_, scale)) if *scale >= 0 => {
+            Ok(AmountColumn::Decimal(values, *scale as u32))
+        }
+        _ => Err(column_type_error(name)),
+    }
+}
+
+fn float_column<'a>(batch: &'a RecordBatch, name: &str) -> GbResult<&'a Float64Array> {
+    column(batch, name)?
+        .as_primitive_opt::<Float64Type>()
+        .ok_or_else(|| column_type_error(name))
+}
+
+fn string_column<'a>(batch: &'a RecordBatch, name: &str) -> GbResult<&'a StringArray> {
+    column(batch, name)?
+        .as_string_opt::<i32>()
+        .ok_or_else(|| column_type_error(name))
+}
+
+fn timestamp_column<'a>(
+    batch: &'a RecordBatch,
+    name: &str,
+) -> GbResult<&'a TimestampNanosecondArray> {
+    column(batch, name)?
+        .as_primitive_opt::<TimestampNanosecondType>()
+        .ok_or_else(|| column_type_error(name))
+}
+
+fn decimal_at(values: &Float64Array, row: usize) -> GbResult<Option<Decimal>> {
+    if values.is_null(row) {
+        return Ok(None);
+    }
+    let value = values.value(row);
+    Decimal::from_f64(value)
+        .map(Some)
+        .ok_or_else(|| GbError::Arrow(format!("row {row}: {value} is not a decimal")))
+}
+
+fn timestamp_at(values: &TimestampNanosecondArray, row: usize) -> GbResult<Option<DateTime<Utc>>> {
+    Ok((!values.is_null(row)).then(|| DateTime::from_timestamp_nanos(values.value(row))))
+}
+
+fn required<T>(value: GbResult<Option<T>>, name: &str, row: usize) -> GbResult<T> {
+    value?.ok_or_else(|| GbError::Arrow(format!("row {row}: '{name}' is null")))
+}
+
+fn parse_side(value: &str, row: usize) -> GbResult<Side> {
+    match value {
+        "Buy" => Ok(Side::Buy),
+        "Sell" => Ok(Side::Sell),
+        other => Err(GbError::Arrow(format!("row {row}: unknown side '{other}'"))),
+    }
+}
+
+fn parse_asset_class(value: &str, row: usize) -> GbResult<AssetClass> {
+    match value {
+        "Equity" => Ok(AssetClass::Equity),
+        "Crypto" => Ok(AssetClass::Crypto),
+        "Forex" => Ok(AssetClass::Forex),
+        "Commodity" => Ok(AssetClass::Commodity),
+        "Bond" => Ok(AssetClass::Bond),
+        "Option" => Ok(AssetClass::Option),
+        other => Err(GbError::Arrow(format!(
+            "row {row}: unknown asset class '{other}'"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal_macros::dec;
+
+    fn synthetic_curve(points: usize) -> Vec<EquityCurvePoint> {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let mut peak = Decimal::ZERO;
+        (0..points)
+            .map(|i| {
+                let step = Decimal::from(i as i64);
+                let value = dec!(100000) + step * dec!(0.37) - Decimal::from((i % 97) as i64);
+                peak = peak.max(value);
+                EquityCurvePoint {
+                    timestamp: start + Duration::minutes(i as i64),
+                    portfolio_value: value,
+                    cash: dec!(25000.5),
+                    positions_value: value - dec!(25000.5),
+                    total_pnl: value - dec!(100000),
+                    daily_return: (i > 0).then(|| Decimal::new((i % 200) as i64 - 100, 5)),
+                    cumulative_return: (value - dec!(100000)) / dec!(100000),
+                    drawdown: ((peak - value) / peak).round_dp(8),
+                    stale_symbols: Vec::new(),
+                    cash_flow: Decimal::ZERO,
+                }
+            })
+            .collect()
+    }
+
+    fn sample_trades() -> Vec<TradeRecord> {
+        let entry = Utc.with_ymd_and_hms(2024, 3, 1, 14, 30, 0).unwrap();
+        vec![
+            TradeRecord {
+                id: Uuid::new_v4(),
+                symbol: Symbol::equity("AAPL"),
+                entry_time: entry,
+                exit_time: Some(entry + Duration::hours(26)),
+                entry_price: dec!(178.25),
+                exit_price: Some(dec!(181.1)),
+                quantity: dec!(40),
+                side: Side::Buy,
+                pnl: Some(dec!(114)),
+                commission: dec!(1.2),
+                duration_hours: Some(26.0),
+                strategy_id: "ma_crossover".into(),
+                tags: vec!["breakout".into(), "earnings".into()],
+                metadata: serde_json::Value::Null,
+            },
+            TradeRecord {
+                id: Uuid::new_v4(),
+                symbol: Symbol::crypto("BTC-USD"),
+                entry_time: entry,
+                exit_time: None,
+                entry_price: dec!(61234.5),
+                exit_price: None,
+                quantity: dec!(0.125),
+                side: Side::Sell,
+                pnl: None,
+                commission: dec!(0),
+                duration_hours: None,
+                strategy_id: "ma_crossover".into(),
+                tags: Vec::new(),
+                metadata: serde_json::Value::Null,
+            },
+        ]
+    }
+
+    #[test]
+    fn equity_curve_round_trips_and_beats_json() {
+        let curve = synthetic_curve(100_000);
+
+        let bytes = write_equity_curve_ipc(&curve).unwrap();
+        assert!(bytes.starts_with(ARROW_FILE_MAGIC));
+        assert_eq!(read_equity_curve_ipc(&bytes).unwrap(), curve);
+
+        let json = serde_json::to_vec(&curve).unwrap();
+        assert!(
+            bytes.len() * 3 < json.len(),
+            "IPC {} bytes vs JSON {} bytes",
+            bytes.len(),
+            json.len()
+        );
+    }
+
+    #[test]
+    fn trade_log_round_trips_with_nulls_and_tags() {
+        let trades = sample_trades();
+        let bytes = write_trade_log_ipc(&trades).unwrap();
+        assert_eq!(read_trade_log_ipc(&bytes).unwrap(), trades);
+        assert!(read_trade_log_ipc(&write_trade_log_ipc(&[]).unwrap())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn readers_accept_streams_and_check_the_table() {
+        let curve = synthetic_curve(10);
+        let batch = equity_curve_record_batch(&curve).unwrap();
+        let mut stream =
+            arrow::ipc::writer::StreamWriter::try_new(Vec::new(), &batch.schema()).unwrap();
+        stream.write(&batch).unwrap();
+        let stream = stream.into_inner().unwrap();
+        assert_eq!(read_equity_curve_ipc(&stream).unwrap(), curve);
+
+        let error = read_trade_log_ipc(&stream).unwrap_err().to_string();
+        assert!(
+            error.contains("expected a GlowBack trade_log table"),
+            "{error}"
+        );
+    }
+}
@@ -1,25 +1,45 @@
 // GlowBack backtesting engine
 // Simple working implementation for Phase 1
 
+pub mod algo;
+pub mod analysis;
+pub mod archive;
+pub mod calendar;
+pub mod construct;
 pub mod engine;
 pub mod execution;
+pub mod ipc;
+pub mod lookahead;
+pub mod parity;
 pub mod simulator;
+pub mod stream;
+pub mod telemetry;
+pub mod vectorized;
 
-use gb_data::{CsvDataProvider, DataManager, SampleDataProvider};
-use gb_types::{BacktestConfig, BacktestResult, DataError, GbResult, Strategy, Symbol};
-use tracing::info;
+use gb_data::{CsvDataProvider, DataManager, SampleDataConfig, SampleDataProvider};
+use gb_types::{
+    BacktestConfig, BacktestEvent, BacktestResult, Bar, DataError, GbResult, Strategy, Symbol,
+};
+use tokio::sync::broadcast;
+use tracing::{info, Instrument};
 
 // Re-export the Engine for direct use
-pub use engine::Engine;
+pub use engine::{CancellationHandle, Engine, EVENT_CHANNEL_CAPACITY};
+use vectorized::BarMatrix;
 
 /// Simple backtesting engine that works with existing types
 #[derive(Debug)]
 pub struct BacktestEngine {
     config: BacktestConfig,
     data_manager: DataManager,
+    cancellation: CancellationHandle,
+    events: broadcast::Sender<BacktestEvent>,
 }
 
-fn uses_explicit_sample_data_source(config: &BacktestConfig) -> bool {
+/// Whether `config` names the built-in sample data source (`sample`,
+/// `demo`, ...), which [`BacktestEngine::new`] serves from an isolated
+/// ephemeral store.
+pub fn uses_explicit_sample_data_source(config: &BacktestConfig) -> bool {
     matches!(
         config
             .data_settings
@@ -56,15 +76,65 @@ impl BacktestEngine {
         Ok(Self {
             config,
             data_manager,
+            cancellation: CancellationHandle::new(),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
         })
     }
 
+    /// Create an engine that loads data through `data_manager` and its
+    /// providers, e.g. one rooted at a specific data directory. Unlike
+    /// [`new`](Self::new), no provider is added for a sample data source.
+    pub fn with_data_manager(config: BacktestConfig, data_manager: DataManager) -> Self {
+        Self {
+            config,
+            data_manager,
+            cancellation: CancellationHandle::new(),
+            events: broadcast::channel(EVENT_CHANNEL_CAPACITY).0,
+        }
+    }
+
+    /// Create an engine from a TOML or YAML backtest manifest file.
+    pub async fn from_manifest(path: impl AsRef<std::path::Path>) -> GbResult<Self> {
+        Self::new(BacktestConfig::from_manifest_file(path)?).await
+    }
+
+    /// Stop [`run_with_strategy`](Self::run_with_strategy) when `handle` is
+    /// cancelled.
+    pub fn with_cancellation(mut self, handle: CancellationHandle) -> Self {
+        self.cancellation = handle;
+        self
+    }
+
+    /// Profile the run: its result gets a [`RunProfile`](gb_types::RunProfile)
+    /// under `"profile"` in its metadata, also sent as a
+    /// [`BacktestEvent::Profile`] event.
+    pub fn with_profiling(mut self) -> Self {
+        self.config.data_settings.profile = true;
+        self
+    }
+
+    pub fn cancellation_handle(&self) -> CancellationHandle {
+        self.cancellation.clone()
+    }
+
+    /// Subscribe to the [`BacktestEvent`]s of
+    /// [`run_with_strategy`](Self::run_with_strategy) calls made from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<BacktestEvent> {
+        self.events.subscribe()
+    }
+
     /// Add the built-in sample/demo data provider explicitly.
     pub fn add_sample_provider(&mut self) {
         self.data_manager
             .add_provider(Box::new(SampleDataProvider::new()));
     }
 
+    /// Add the sample data provider, generating data shaped by `config`.
+    pub fn add_sample_provider_with_config(&mut self, config: SampleDataConfig) {
+        self.data_manager
+            .add_provider(Box::new(SampleDataProvider::with_config(config)));
+    }
+
     /// Add a CSV data provider rooted at the supplied directory.
     pub fn add_csv_provider(&mut self, base_path: &str) {
         self.data_manager
@@ -73,32 +143,53 @@ impl BacktestEngine {
 
     /// Load market data for backtesting
     pub async fn load_market_data(&mut self, symbols: Vec<Symbol>) -> GbResult<()> {
-        info!("Loading market data for {} symbols", symbols.len());
-
-        for symbol in symbols {
-            let bars = self
-                .data_manager
-                .load_data(
-                    &symbol,
-                    self.config.start_date,
-                    self.config.end_date,
-                    self.config.resolution,
-                )
-                .await?;
-
-            if bars.is_empty() {
-                return Err(DataError::NoDataInRange {
-                    symbol: symbol.to_string(),
-                    start: self.config.start_date.to_rfc3339(),
-                    end: self.config.end_date.to_rfc3339(),
+        self.load_bars(&symbols).await.map(|_| ())
+    }
+
+    /// Daily closes of the configured symbols over the configured range,
+    /// for [`vectorized::quick_backtest`].
+    pub async fn bar_matrix(&mut self) -> GbResult<BarMatrix> {
+        let symbols = self.config.symbols.clone();
+        Ok(BarMatrix::from_bars(&self.load_bars(&symbols).await?))
+    }
+
+    /// Bars of every symbol over the configured range; fails if a symbol has
+    /// none.
+    async fn load_bars(&mut self, symbols: &[Symbol]) -> GbResult<Vec<Bar>> {
+        let span =
+            telemetry::backtest_span(self.config.id, &self.config.strategy_config.strategy_id);
+        async {
+            info!("Loading market data for {} symbols", symbols.len());
+
+            let mut loaded = Vec::new();
+            for symbol in symbols {
+                let bars = self
+                    .data_manager
+                    .load_data(
+                        symbol,
+                        self.config.start_date,
+                        self.config.end_date,
+                        self.config.resolution,
+                    )
+                    .await?;
+
+                if bars.is_empty() {
+                    return Err(DataError::NoDataInRange {
+                        symbol: symbol.to_string(),
+                        start: self.config.start_date.to_rfc3339(),
+                        end: self.config.end_date.to_rfc3339(),
+                    }
+                    .into());
                 }
-                .into());
+
+                info!("Loaded {} bars for {}", bars.len(), symbol);
+                loaded.extend(bars);
             }
 
-            info!("Loaded {} bars for {}", bars.len(), symbol);
+            Ok(loaded)
         }
-
-        Ok(())
+        .instrument(span)
+        .await
     }
 
     /// Run a backtest with a provided strategy
@@ -115,7 +206,10 @@ impl BacktestEngine {
         );
 
         // Create the full Engine with strategy support using our existing data manager
-        let mut engine = Engine::new(self.config.clone(), &mut self.data_manager, strategy).await?;
+        let mut engine = Engine::new(self.config.clone(), &mut self.data_manager, strategy)
+            .await?
+            .with_cancellation(self.cancellation.clone())
+            .with_event_sender(self.events.clone());
 
         // Run the backtest using the full engine
         engine.run().await
@@ -177,6 +271,8 @@ impl BacktestEngine {
                 daily_return: previous_value.map(|_| daily_return),
                 cumulative_return: portfolio.get_total_return(),
                 drawdown,
+                stale_symbols: Vec::new(),
+                cash_flow: rust_decimal::Decimal::ZERO,
             };
 
             equity_curve.push(point);
@@ -201,7 +297,9 @@ impl BacktestEngine {
 mod tests {
     use super::*;
     use chrono::{Duration, Utc};
-    use gb_types::{BuyAndHoldStrategy, Resolution, RunManifest, StrategyConfig, Symbol};
+    use gb_types::{
+        BacktestError, BuyAndHoldStrategy, Resolution, RunManifest, StrategyConfig, Symbol,
+    };
     use rust_decimal::prelude::FromPrimitive;
     use rust_decimal::Decimal;
 
@@ -373,6 +471,41 @@ mod tests {
         assert_eq!(engine.get_config().initial_capital, Decimal::from(100000));
     }
 
+    #[tokio::test]
+    async fn cancelled_backtest_stops_with_canceled_error() {
+        let config = create_test_config();
+        let mut engine = BacktestEngine::new(config.clone()).await.unwrap();
+        engine.cancellation_handle().cancel();
+
+        let error = engine
+            .run_with_strategy(Box::new(BuyAndHoldStrategy::new()))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            gb_types::GbError::Backtest(BacktestError::Canceled { backtest_id })
+                if backtest_id == config.id.to_string()
+        ));
+    }
+
+    #[tokio::test]
+    async fn manifest_file_drives_the_run_and_rides_along_in_the_result() {
+        let config = create_test_config();
+        let path = std::env::temp_dir().join(format!("gb-engine-{}.toml", config.id));
+        config.write_manifest_file(&path).unwrap();
+
+        let mut engine = BacktestEngine::from_manifest(&path).await.unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(engine.get_config(), &config);
+
+        let result = engine
+            .run_with_strategy(Box::new(BuyAndHoldStrategy::new()))
+            .await
+            .unwrap();
+        let manifest = result.config_manifest.expect("result carries its manifest");
+        assert_eq!(BacktestConfig::from_toml(&manifest).unwrap(), config);
+    }
+
     #[tokio::test]
     async fn test_data_loading() {
         let config = create_test_config();
@@ -0,0 +1,201 @@
+//! Lookahead-bias checks for the backtest loop.
+//!
+//! The engine steps one simulated day at a time. [`LookaheadGuard`] decides
+//! when in that day its bars are delivered (the step's clock) and fails the
+//! run when a bar would reach the strategy before it closed, or a fill is
+//! stamped before the order that triggered it.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use gb_types::{BacktestError, Bar, BarDelivery, Fill, GbResult, Order, Resolution, Symbol};
+
+/// Enforces [`BarDelivery`] and fill ordering for one run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LookaheadGuard {
+    delivery: BarDelivery,
+}
+
+impl LookaheadGuard {
+    pub fn new(delivery: BarDelivery) -> Self {
+        Self { delivery }
+    }
+
+    /// The date whose bars are delivered on the step for `day`: the same day
+    /// on close, the previous day on open.
+    pub fn delivered_date(&self, day: NaiveDate) -> NaiveDate {
+        match self.delivery {
+            BarDelivery::OnClose => day,
+            BarDelivery::OnOpen => day - Duration::days(1),
+        }
+    }
+
+    /// The simulator clock when the step for `day` delivers its bars: the
+    /// end of the day on close, its start on open.
+    pub fn clock(&self, day: NaiveDate) -> DateTime<Utc> {
+        let start = day.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc();
+        match self.delivery {
+            BarDelivery::OnClose => start + Duration::days(1),
+            BarDelivery::OnOpen => start,
+        }
+    }
+
+    /// When `bar` closes. Intraday bars are stamped at their open and close
+    /// one period later; daily bars close at the end of the UTC day they are
+    /// stamped with, whatever the time of day. Weekly and monthly bars close
+    /// a period after their stamp, so a resampled bar stamped at the start of
+    /// its week is not delivered until the week is over.
+    pub fn bar_close(bar: &Bar) -> DateTime<Utc> {
+        match bar.resolution {
+            Resolution::Tick => bar.timestamp,
+            Resolution::Day => {
+                let day = bar.timestamp.date_naive() + Duration::days(1);
+                day.and_hms_opt(0, 0, 0).expect("midnight exists").and_utc()
+            }
+            resolution => {
+                let seconds = resolution.to_seconds().unwrap_or(0);
+                bar.timestamp + Duration::seconds(seconds as i64)
+            }
+        }
+    }
+
+    /// Fail if `bar` has not both started and closed by `clock`.
+    pub fn check_bar(&self, symbol: &Symbol, bar: &Bar, clock: DateTime<Utc>) -> GbResult<()> {
+        if bar.timestamp > clock {
+            return Err(violation(
+                symbol,
+                format!(
+                    "bar stamped {} was delivered at simulator time {}",
+                    bar.timestamp, clock
+                ),
+            ));
+        }
+        let close = Self::bar_close(bar);
+        if close > clock {
+            return Err(violation(
+                symbol,
+                format!(
+                    "{:?} bar stamped {} closes at {} but was delivered at simulator time {}",
+                    bar.resolution, bar.timestamp, close, clock
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Fail if `fill` happened before `order` was submitted.
+    pub fn check_fill(&self, order: &Order, fill: &Fill) -> GbResult<()> {
+        if fill.executed_at < order.submitted_at {
+            return Err(violation(
+                &fill.symbol,
+                format!(
+                    "order {} submitted at {} filled at {}",
+                    order.id, order.submitted_at, fill.executed_at
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn violation(symbol: &Symbol, message: String) -> gb_types::GbError {
+    BacktestError::LookaheadViolation {
+        symbol: symbol.to_string(),
+        message,
+    }
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use gb_types::{GbError, Side};
+    use rust_decimal::Decimal;
+
+    fn bar(timestamp: DateTime<Utc>, resolution: Resolution) -> Bar {
+        let price = Decimal::from(100);
+        Bar::new(
+            Symbol::equity("AAPL"),
+            timestamp,
+            price,
+            price,
+            price,
+            price,
+            Decimal::from(1_000),
+            resolution,
+        )
+    }
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    #[test]
+    fn bars_are_delivered_only_after_they_close() {
+        let symbol = Symbol::equity("AAPL");
+        let on_close = LookaheadGuard::new(BarDelivery::OnClose);
+        let at = |hour| Utc.with_ymd_and_hms(2024, 1, 2, hour, 0, 0).unwrap();
+
+        // A daily bar stamped at the session close still closes with the day.
+        let daily = bar(at(16), Resolution::Day);
+        assert!(on_close
+            .check_bar(&symbol, &daily, on_close.clock(day(2)))
+            .is_ok());
+        let hourly = bar(at(23), Resolution::Hour);
+        assert!(on_close
+            .check_bar(&symbol, &hourly, on_close.clock(day(2)))
+            .is_ok());
+
+        let weekly = bar(at(0), Resolution::Week);
+        let error = on_close
+            .check_bar(&symbol, &weekly, on_close.clock(day(2)))
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            GbError::Backtest(BacktestError::LookaheadViolation { ref symbol, .. })
+                if symbol == "NASDAQ:AAPL"
+        ));
+        assert!(
+            error.to_string().contains("2024-01-09 00:00:00 UTC"),
+            "{error}"
+        );
+
+        let on_open = LookaheadGuard::new(BarDelivery::OnOpen);
+        assert_eq!(on_open.delivered_date(day(3)), day(2));
+        assert!(on_open
+            .check_bar(&symbol, &daily, on_open.clock(day(3)))
+            .is_ok());
+        assert!(on_open
+            .check_bar(&symbol, &daily, on_open.clock(day(2)))
+            .is_err());
+    }
+
+    #[test]
+    fn fills_before_their_order_are_rejected() {
+        let guard = LookaheadGuard::new(BarDelivery::OnClose);
+        let mut order = Order::market_order(
+            Symbol::equity("AAPL"),
+            Side::Buy,
+            Decimal::from(10),
+            "test".to_string(),
+        );
+        order.submitted_at = Utc.with_ymd_and_hms(2024, 1, 3, 0, 0, 0).unwrap();
+        let mut fill = Fill::new(
+            order.id,
+            order.symbol.clone(),
+            Side::Buy,
+            Decimal::from(10),
+            Decimal::from(100),
+            Decimal::ZERO,
+            "test".to_string(),
+        );
+
+        fill.executed_at = order.submitted_at;
+        assert!(guard.check_fill(&order, &fill).is_ok());
+        fill.executed_at = order.submitted_at - Duration::days(1);
+        let error = guard.check_fill(&order, &fill).unwrap_err();
+        assert!(
+            error.to_string().contains("filled at 2024-01-02"),
+            "{error}"
+        );
+    }
+}
@@ -0,0 +1,518 @@
+//! Backtest-to-live parity: what a strategy did in one run, reduced to its
+//! fills and daily positions and equity, compared with another run of the
+//! same strategy on the same bars.
+//!
+//! This module runs the backtest half and does the comparison. The live
+//! half, a `LiveEngine` on a paper broker, is driven by
+//! `gb_live::parity::run_parity`: gb-live builds on this crate, so the
+//! engine cannot reach the live engine from here.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+use chrono::NaiveDate;
+use gb_data::DataManager;
+use gb_types::{
+    BacktestConfig, BacktestResult, Bar, ExecutionSettings, Fill, GbResult, LatencyModel,
+    MarketImpactModel, Side, SlippageModel, SnapshotCadence, Strategy, StrategyConfig, Symbol,
+};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::Engine;
+
+/// Execution settings shared by both halves of a parity run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParitySettings {
+    pub initial_capital: Decimal,
+    pub commission_per_share: Decimal,
+    /// Slippage of market orders, in basis points of the fill price.
+    pub slippage_bps: u32,
+    /// Share of a bar's volume one order may fill on it.
+    pub max_volume_participation: Decimal,
+    pub normalization: ParityNormalization,
+}
+
+impl Default for ParitySettings {
+    fn default() -> Self {
+        Self {
+            initial_capital: Decimal::from(100_000),
+            commission_per_share: Decimal::new(1, 2),
+            slippage_bps: 5,
+            max_volume_participation: Decimal::ONE,
+            normalization: ParityNormalization::default(),
+        }
+    }
+}
+
+impl ParitySettings {
+    /// Backtest execution matching a paper broker: per-share commission
+    /// only, linear slippage, no latency and no market impact.
+    pub fn execution_settings(&self) -> ExecutionSettings {
+        ExecutionSettings {
+            commission_per_share: self.commission_per_share,
+            commission_percentage: Decimal::ZERO,
+            minimum_commission: Decimal::ZERO,
+            slippage_model: SlippageModel::Linear {
+                basis_points: self.slippage_bps,
+            },
+            latency_model: LatencyModel::None,
+            market_impact_model: MarketImpactModel::None,
+            max_volume_participation: self.max_volume_participation,
+            ..Default::default()
+        }
+    }
+}
+
+/// Differences between the two runs that do not count as divergences.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParityNormalization {
+    /// Compare each day's fills sorted by symbol, side, quantity and price
+    /// rather than in the order they were reported, since the two paths
+    /// order fills sharing a timestamp differently.
+    pub sort_same_day_fills: bool,
+    /// Largest difference in fill price or commission still treated as equal.
+    pub price_tolerance: Decimal,
+    /// Largest difference in daily equity still treated as equal.
+    pub equity_tolerance: Decimal,
+}
+
+impl Default for ParityNormalization {
+    fn default() -> Self {
+        Self {
+            sort_same_day_fills: true,
+            price_tolerance: Decimal::ZERO,
+            equity_tolerance: Decimal::ZERO,
+        }
+    }
+}
+
+/// A fill reduced to what both paths report for it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParityFill {
+    /// Trading day of the bar the fill executed on.
+    pub date: NaiveDate,
+    pub symbol: Symbol,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub price: Decimal,
+    pub commission: Decimal,
+}
+
+impl ParityFill {
+    pub fn new(date: NaiveDate, fill: &Fill) -> Self {
+        Self {
+            date,
+            symbol: fill.symbol.clone(),
+            side: fill.side,
+            quantity: fill.quantity,
+            price: fill.price,
+            commission: fill.commission,
+        }
+    }
+}
+
+impl fmt::Display for ParityFill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} {} {} @ {} (commission {})",
+            self.date, self.side, self.quantity, self.symbol, self.price, self.commission
+        )
+    }
+}
+
+/// Equity and open positions at the end of a trading day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParityPoint {
+    pub date: NaiveDate,
+    pub equity: Decimal,
+    /// Signed quantity by symbol, without flat positions.
+    pub positions: BTreeMap<String, Decimal>,
+}
+
+impl fmt::Display for ParityPoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} equity {} positions {:?}",
+            self.date, self.equity, self.positions
+        )
+    }
+}
+
+/// What one run of a strategy did: its fills in order and its end-of-day
+/// equity and positions on each day with bars.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParityTrace {
+    pub fills: Vec<ParityFill>,
+    pub path: Vec<ParityPoint>,
+}
+
+impl ParityTrace {
+    /// The trace of a finished backtest over `bars`. The engine steps every
+    /// calendar day; only days with bars are kept.
+    pub fn from_backtest(result: &BacktestResult, bars: &[Bar]) -> Self {
+        let days: BTreeSet<NaiveDate> = bars.iter().map(|bar| bar.timestamp.date_naive()).collect();
+        let fills = result
+            .order_events
+            .iter()
+            .filter_map(|event| event.fill())
+            .map(|fill| ParityFill::new(fill.executed_at.date_naive(), fill))
+            .collect();
+        let positions: HashMap<NaiveDate, BTreeMap<String, Decimal>> = result
+            .positions_history
+            .iter()
+            .map(|snapshot| {
+                let held = snapshot
+                    .positions
+                    .iter()
+                    .map(|holding| (holding.symbol.to_string(), holding.quantity))
+                    .collect();
+                (snapshot.timestamp.date_naive(), held)
+            })
+            .collect();
+        let path = result
+            .equity_curve
+            .iter()
+            .filter(|point| days.contains(&point.timestamp.date_naive()))
+            .map(|point| {
+                let date = point.timestamp.date_naive();
+                ParityPoint {
+                    date,
+                    equity: point.portfolio_value,
+                    positions: positions.get(&date).cloned().unwrap_or_default(),
+                }
+            })
+            .collect();
+        Self { fills, path }
+    }
+
+    /// Fills in the order [`compare`] checks them.
+    fn normalized_fills(&self, normalization: &ParityNormalization) -> Vec<ParityFill> {
+        let mut fills = self.fills.clone();
+        if normalization.sort_same_day_fills {
+            fills.sort_by_key(|fill| {
+                (
+                    fill.date,
+                    fill.symbol.to_string(),
+                    side_rank(fill.side),
+                    fill.quantity,
+                    fill.price,
+                )
+            });
+        }
+        fills
+    }
+}
+
+fn side_rank(side: Side) -> u8 {
+    match side {
+        Side::Buy => 0,
+        Side::Sell => 1,
+    }
+}
+
+/// The first place two traces disagree, with both sides of it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ParityDivergence {
+    /// The `index`-th fill differs, or only one run has it.
+    Fill {
+        index: usize,
+        backtest: Option<ParityFill>,
+        live: Option<ParityFill>,
+        /// The fills both runs agreed on just before this one.
+        preceding: Vec<ParityFill>,
+    },
+    /// The runs hold different positions at the end of a day.
+    Positions {
+        index: usize,
+        backtest: Option<ParityPoint>,
+        live: Option<ParityPoint>,
+    },
+    /// The runs end a day with different equity.
+    Equity {
+        index: usize,
+        backtest: ParityPoint,
+        live: ParityPoint,
+    },
+}
+
+/// Agreed fills listed as context for a fill divergence.
+const PRECEDING_FILLS: usize = 3;
+
+impl ParityDivergence {
+    /// Trading day the divergence shows up on.
+    pub fn date(&self) -> Option<NaiveDate> {
+        match self {
+            Self::Fill { backtest, live, .. } => {
+                backtest.as_ref().or(live.as_ref()).map(|fill| fill.date)
+            }
+            Self::Positions { backtest, live, .. } => {
+                backtest.as_ref().or(live.as_ref()).map(|point| point.date)
+            }
+            Self::Equity { backtest, .. } => Some(backtest.date),
+        }
+    }
+}
+
+fn or_none<T: fmt::Display>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map_or_else(|| "nothing".to_string(), ToString::to_string)
+}
+
+impl fmt::Display for ParityDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fill {
+                index,
+                backtest,
+                live,
+                preceding,
+            } => {
+                write!(
+                    f,
+                    "fill #{index} diverges: backtest {}, live {}",
+                    or_none(backtest),
+                    or_none(live)
+                )?;
+                for fill in preceding {
+                    write!(f, "\n  after {fill}")?;
+                }
+                Ok(())
+            }
+            Self::Positions {
+                index,
+                backtest,
+                live,
+            } => write!(
+                f,
+                "positions diverge on day #{index}: backtest {}, live {}",
+                or_none(backtest),
+                or_none(live)
+            ),
+            Self::Equity {
+                index,
+                backtest,
+                live,
+            } => write!(
+                f,
+                "equity diverges on day #{index} by {}: backtest {backtest}, live {live}",
+                live.equity - backtest.equity
+            ),
+        }
+    }
+}
+
+/// Both traces of a parity run and where they first disagree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParityReport {
+    pub backtest: ParityTrace,
+    pub live: ParityTrace,
+    pub divergence: Option<ParityDivergence>,
+}
+
+impl ParityReport {
+    pub fn new(
+        backtest: ParityTrace,
+        live: ParityTrace,
+        normalization: &ParityNormalization,
+    ) -> Self {
+        let divergence = compare(&backtest, &live, normalization);
+        Self {
+            backtest,
+            live,
+            divergence,
+        }
+    }
+
+    pub fn is_match(&self) -> bool {
+        self.divergence.is_none()
+    }
+}
+
+/// The first divergence between `backtest` and `live`: fills are checked
+/// first, then each day's positions and equity.
+pub fn compare(
+    backtest: &ParityTrace,
+    live: &ParityTrace,
+    normalization: &ParityNormalization,
+) -> Option<ParityDivergence> {
+    let backtest_fills = backtest.normalized_fills(normalization);
+    let live_fills = live.normalized_fills(normalization);
+    let tolerance = normalization.price_tolerance;
+    for index in 0..backtest_fills.len().max(live_fills.len()) {
+        let (expected, actual) = (backtest_fills.get(index), live_fills.get(index));
+        let same = match (expected, actual) {
+            (Some(a), Some(b)) => {
+                a.date == b.date
+                    && a.symbol == b.symbol
+                    && a.side == b.side
+                    && a.quantity == b.quantity
+                    && (a.price - b.price).abs() <= tolerance
+                    && (a.commission - b.commission).abs() <= tolerance
+            }
+            _ => false,
+        };
+        if !same {
+            return Some(ParityDivergence::Fill {
+                index,
+                backtest: expected.cloned(),
+                live: actual.cloned(),
+                preceding: backtest_fills[index.saturating_sub(PRECEDING_FILLS)..index].to_vec(),
+            });
+        }
+    }
+
+    for index in 0..backtest.path.len().max(live.path.len()) {
+        let (expected, actual) = (backtest.path.get(index), live.path.get(index));
+        match (expected, actual) {
+            (Some(a), Some(b)) if a.date == b.date && a.positions == b.positions => {
+                if (a.equity - b.equity).abs() > normalization.equity_tolerance {
+                    return Some(ParityDivergence::Equity {
+                        index,
+                        backtest: a.clone(),
+                        live: b.clone(),
+                    });
+                }
+            }
+            _ => {
+                return Some(ParityDivergence::Positions {
+                    index,
+                    backtest: expected.cloned(),
+                    live: actual.cloned(),
+                })
+            }
+        }
+    }
+    None
+}
+
+/// Symbols of `bars` in order of first appearance.
+pub fn bar_symbols(bars: &[Bar]) -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = Vec::new();
+    for bar in bars {
+        if !symbols.contains(&bar.symbol) {
+            symbols.push(bar.symbol.clone());
+        }
+    }
+    symbols
+}
+
+/// Backtest `strategy` over `bars` with `settings` and return its trace.
+/// The run covers the days of the bars, which must share one resolution.
+pub async fn backtest_trace(
+    strategy: Box<dyn Strategy>,
+    strategy_config: &StrategyConfig,
+    bars: &[Bar],
+    settings: &ParitySettings,
+) -> GbResult<ParityTrace> {
+    let (Some(first), Some(last)) = (
+        bars.iter().map(|bar| bar.timestamp).min(),
+        bars.iter().map(|bar| bar.timestamp).max(),
+    ) else {
+        return Ok(ParityTrace::default());
+    };
+    let resolution = bars[0].resolution;
+    let symbols = bar_symbols(bars);
+
+    let mut data_manager = DataManager::new_ephemeral("gb-engine-parity").await?;
+    for symbol in &symbols {
+        let mut series: Vec<Bar> = bars
+            .iter()
+            .filter(|bar| bar.symbol == *symbol)
+            .cloned()
+            .collect();
+        series.sort_by_key(|bar| bar.timestamp);
+        data_manager
+            .storage
+            .save_bars(symbol, &series, resolution)
+            .await?;
+    }
+
+    let start = first
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap_or_default()
+        .and_utc();
+    let mut config = BacktestConfig::new("parity".to_string(), strategy_config.clone())
+        .with_symbols(symbols)
+        .with_date_range(start, last)
+        .with_resolution(resolution);
+    config.initial_capital = settings.initial_capital;
+    config.execution_settings = settings.execution_settings();
+    config.data_settings.position_snapshots = SnapshotCadence::Daily;
+
+    let mut engine = Engine::new(config, &mut data_manager, strategy).await?;
+    let result = engine.run().await?;
+    Ok(ParityTrace::from_backtest(&result, bars))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, d).unwrap()
+    }
+
+    fn fill(d: u32, ticker: &str, price: Decimal) -> ParityFill {
+        ParityFill {
+            date: day(d),
+            symbol: Symbol::equity(ticker),
+            side: Side::Buy,
+            quantity: dec!(10),
+            price,
+            commission: dec!(0.1),
+        }
+    }
+
+    #[test]
+    fn same_day_fills_compare_in_normalized_order() {
+        let backtest = ParityTrace {
+            fills: vec![fill(2, "AAPL", dec!(100)), fill(2, "MSFT", dec!(50))],
+            path: Vec::new(),
+        };
+        let live = ParityTrace {
+            fills: vec![fill(2, "MSFT", dec!(50)), fill(2, "AAPL", dec!(100))],
+            path: Vec::new(),
+        };
+        assert_eq!(
+            compare(&backtest, &live, &ParityNormalization::default()),
+            None
+        );
+
+        let strict = ParityNormalization {
+            sort_same_day_fills: false,
+            ..Default::default()
+        };
+        let Some(ParityDivergence::Fill { index, .. }) = compare(&backtest, &live, &strict) else {
+            panic!("expected a fill divergence");
+        };
+        assert_eq!(index, 0);
+
+        // A later fill that is off by more than the tolerance names the
+        // fills agreed before it.
+        let mut late = backtest.clone();
+        late.fills.push(fill(3, "AAPL", dec!(101)));
+        let mut live = backtest.clone();
+        live.fills.push(fill(3, "AAPL", dec!(101.02)));
+        let tolerant = ParityNormalization {
+            price_tolerance: dec!(0.01),
+            ..Default::default()
+        };
+        let divergence = compare(&late, &live, &tolerant).unwrap();
+        assert_eq!(divergence.date(), Some(day(3)));
+        let ParityDivergence::Fill {
+            index, preceding, ..
+        } = &divergence
+        else {
+            panic!("expected a fill divergence");
+        };
+        assert_eq!((*index, preceding.len()), (2, 2));
+        assert!(divergence.to_string().contains("101.02"));
+    }
+}
@@ -1,20 +1,30 @@
 // Market simulator - comprehensive implementation for realistic backtesting
-use chrono::{DateTime, Datelike, Timelike, Utc};
-use gb_types::{Bar, DataError, GbResult, MarketEvent, Resolution, Symbol};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use gb_types::{
+    AssetClass, BacktestError, Bar, BarColumns, DataError, GbResult, MarketEvent, Resolution,
+    SessionPosition, Symbol,
+};
 use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::OnceLock;
 use tracing::{debug, info};
 
+use crate::calendar::TradingCalendar;
+
 /// Market data event with timestamp for chronological ordering
 #[derive(Debug, Clone)]
 pub struct TimestampedEvent {
     pub timestamp: DateTime<Utc>,
     pub symbol: Symbol,
+    /// Rank of `symbol` in the simulator, the order its feed was first
+    /// added; orders events sharing a timestamp, so renaming a symbol never
+    /// reorders them.
+    pub priority: usize,
     pub event: MarketEvent,
 }
 
 impl PartialEq for TimestampedEvent {
     fn eq(&self, other: &Self) -> bool {
-        self.timestamp == other.timestamp && self.symbol == other.symbol
+        self.timestamp == other.timestamp && self.priority == other.priority
     }
 }
 
@@ -30,15 +40,24 @@ impl Ord for TimestampedEvent {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.timestamp
             .cmp(&other.timestamp)
-            .then_with(|| self.symbol.symbol.cmp(&other.symbol.symbol))
+            .then_with(|| self.priority.cmp(&other.priority))
     }
 }
 
+/// Position of a bar within the simulator's data feeds
+#[derive(Debug, Clone, Copy)]
+struct FeedRow {
+    feed: usize,
+    row: usize,
+}
+
 /// Comprehensive market simulator for realistic backtesting
 #[derive(Debug)]
 pub struct MarketSimulator {
-    /// All market events ordered by timestamp
-    events: BTreeMap<DateTime<Utc>, Vec<TimestampedEvent>>,
+    /// Bars of each data feed, stored column by column
+    feeds: Vec<BarColumns>,
+    /// All market events ordered by timestamp, as positions in `feeds`
+    events: BTreeMap<DateTime<Utc>, Vec<FeedRow>>,
     /// Current market data state for each symbol
     current_data: HashMap<Symbol, Bar>,
     /// Event queue for the current simulation time
@@ -49,60 +68,134 @@ pub struct MarketSimulator {
     start_time: Option<DateTime<Utc>>,
     /// Simulation end time  
     end_time: Option<DateTime<Utc>>,
-    /// Symbols being simulated
+    /// Symbols being simulated, in priority order
     symbols: Vec<Symbol>,
     /// Resolution for time advancement
     resolution: Resolution,
-    /// Market hours configuration
-    market_hours: MarketHours,
+    /// Trading sessions of each symbol
+    sessions: SessionResolver,
+    /// In-session event counts of the feeds, built on initialization
+    session_calendar: SessionCalendar,
 }
 
-/// Market hours configuration for realistic simulation
-#[derive(Debug, Clone)]
-pub struct MarketHours {
-    /// Market open time (UTC hour)
-    pub open_hour: u32,
-    /// Market close time (UTC hour)
-    pub close_hour: u32,
-    /// Weekend trading enabled
-    pub weekend_trading: bool,
-    /// True when the asset trades 24 hours on active days
-    pub round_the_clock: bool,
+/// Resolves the trading calendar of each symbol: a calendar set for the
+/// symbol itself, then one set for its asset class, then the asset-class
+/// defaults of [`TradingCalendar::for_asset_class`].
+#[derive(Debug, Clone, Default)]
+pub struct SessionResolver {
+    symbol_calendars: HashMap<Symbol, TradingCalendar>,
+    asset_class_calendars: HashMap<AssetClass, TradingCalendar>,
 }
 
-impl Default for MarketHours {
-    fn default() -> Self {
-        Self {
-            open_hour: 14,  // 9:30 AM EST = 14:30 UTC
-            close_hour: 21, // 4:00 PM EST = 21:00 UTC
-            weekend_trading: false,
-            round_the_clock: false,
-        }
+impl SessionResolver {
+    pub fn new() -> Self {
+        Self::default()
     }
+
+    /// Trade `symbol` on `calendar`, whatever its asset class
+    pub fn with_symbol_calendar(mut self, symbol: Symbol, calendar: TradingCalendar) -> Self {
+        self.symbol_calendars.insert(symbol, calendar);
+        self
+    }
+
+    /// Trade every symbol of `asset_class` without a calendar of its own on
+    /// `calendar`
+    pub fn with_asset_class_calendar(
+        mut self,
+        asset_class: AssetClass,
+        calendar: TradingCalendar,
+    ) -> Self {
+        self.asset_class_calendars.insert(asset_class, calendar);
+        self
+    }
+
+    /// Trading calendar of `symbol`
+    pub fn calendar_for(&self, symbol: &Symbol) -> &TradingCalendar {
+        self.symbol_calendars
+            .get(symbol)
+            .or_else(|| self.asset_class_calendars.get(&symbol.asset_class))
+            .unwrap_or_else(|| default_calendar(symbol.asset_class))
+    }
+}
+
+/// [`TradingCalendar::for_asset_class`], built once per calendar rather than
+/// on every lookup
+fn default_calendar(asset_class: AssetClass) -> &'static TradingCalendar {
+    static EQUITY: OnceLock<TradingCalendar> = OnceLock::new();
+    static CRYPTO: OnceLock<TradingCalendar> = OnceLock::new();
+    static FOREX: OnceLock<TradingCalendar> = OnceLock::new();
+    let cell = match asset_class {
+        AssetClass::Crypto => &CRYPTO,
+        AssetClass::Forex => &FOREX,
+        _ => &EQUITY,
+    };
+    cell.get_or_init(|| TradingCalendar::for_asset_class(asset_class))
+}
+
+/// In-session event counts of a set of feeds, per timestamp and per day, so
+/// locating an event in its session is a lookup rather than a scan of the
+/// day. An event counts towards the session of its UTC date when its
+/// symbol's market is open at its timestamp; daily and coarser bars count on
+/// every trading day of their calendar, shortened sessions included.
+#[derive(Debug, Clone, Default)]
+pub struct SessionCalendar {
+    /// In-session events at each timestamp
+    slots: BTreeMap<DateTime<Utc>, SessionSlot>,
+    /// In-session events of each day
+    day_totals: HashMap<NaiveDate, usize>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct SessionSlot {
+    /// Events earlier the same day
+    before: usize,
+    count: usize,
 }
 
-impl MarketHours {
-    /// Return `MarketHours` appropriate for the given asset class.
-    ///
-    /// * **Crypto** – 24/7, no market close, weekends active.
-    /// * **Forex** – 24h Sun evening – Fri evening; weekends off.
-    /// * Everything else – US equity defaults (14–21 UTC, weekdays).
-    pub fn for_asset_class(asset_class: gb_types::AssetClass) -> Self {
-        match asset_class {
-            gb_types::AssetClass::Crypto => Self {
-                open_hour: 0,
-                close_hour: 24,
-                weekend_trading: true,
-                round_the_clock: true,
-            },
-            gb_types::AssetClass::Forex => Self {
-                open_hour: 0,
-                close_hour: 24,
-                weekend_trading: false,
-                round_the_clock: true,
-            },
-            _ => Self::default(),
+impl SessionCalendar {
+    /// Count the in-session events of each `(symbol, resolution, timestamps)`
+    /// feed, with the symbol's calendar taken from `sessions`
+    pub fn build<'a, T>(
+        sessions: &SessionResolver,
+        feeds: impl IntoIterator<Item = (&'a Symbol, Resolution, T)>,
+    ) -> Self
+    where
+        T: IntoIterator<Item = DateTime<Utc>>,
+    {
+        let mut slots: BTreeMap<DateTime<Utc>, SessionSlot> = BTreeMap::new();
+        for (symbol, resolution, timestamps) in feeds {
+            let calendar = sessions.calendar_for(symbol);
+            let whole_days = resolution
+                .to_seconds()
+                .is_some_and(|seconds| seconds >= 86_400);
+            for time in timestamps {
+                let in_session = if whole_days {
+                    calendar.is_trading_day(time.date_naive())
+                } else {
+                    calendar.is_open(time)
+                };
+                if in_session {
+                    slots.entry(time).or_default().count += 1;
+                }
+            }
         }
+
+        let mut day_totals: HashMap<NaiveDate, usize> = HashMap::new();
+        for (time, slot) in slots.iter_mut() {
+            let total = day_totals.entry(time.date_naive()).or_default();
+            slot.before = *total;
+            *total += slot.count;
+        }
+        Self { slots, day_totals }
+    }
+
+    /// Where the events at `at` fall in their day's session, counting every
+    /// feed's events rather than their prices. `None` when no feed has an
+    /// in-session event at `at`.
+    pub fn position(&self, at: DateTime<Utc>) -> Option<SessionPosition> {
+        let slot = self.slots.get(&at)?;
+        let total = self.day_totals.get(&at.date_naive())?;
+        Some(SessionPosition::new(slot.before, slot.count, *total))
     }
 }
 
@@ -110,6 +203,7 @@ impl MarketSimulator {
     /// Create a new market simulator
     pub fn new() -> Self {
         Self {
+            feeds: Vec::new(),
             events: BTreeMap::new(),
             current_data: HashMap::new(),
             current_events: VecDeque::new(),
@@ -118,13 +212,14 @@ impl MarketSimulator {
             end_time: None,
             symbols: Vec::new(),
             resolution: Resolution::Day,
-            market_hours: MarketHours::default(),
+            sessions: SessionResolver::default(),
+            session_calendar: SessionCalendar::default(),
         }
     }
 
-    /// Configure market hours
-    pub fn with_market_hours(mut self, market_hours: MarketHours) -> Self {
-        self.market_hours = market_hours;
+    /// Configure the trading session of each symbol
+    pub fn with_sessions(mut self, sessions: SessionResolver) -> Self {
+        self.sessions = sessions;
         self
     }
 
@@ -134,7 +229,8 @@ impl MarketSimulator {
         self
     }
 
-    /// Add market data feed for a symbol
+    /// Add market data feed for a symbol; every bar must be of `symbol` and
+    /// share one resolution
     pub fn add_data_feed(&mut self, symbol: Symbol, bars: Vec<Bar>) -> GbResult<()> {
         if bars.is_empty() {
             return Err(DataError::InsufficientData {
@@ -145,32 +241,30 @@ impl MarketSimulator {
 
         info!("Adding data feed for {} with {} bars", symbol, bars.len());
 
+        // Store the bars and add their positions to the timeline
+        let columns = BarColumns::from_bars(symbol.clone(), bars[0].resolution, &bars)?;
+
         // Add symbol to simulation
         if !self.symbols.contains(&symbol) {
-            self.symbols.push(symbol.clone());
+            self.symbols.push(symbol);
         }
 
-        // Convert bars to market events and add to timeline
-        for bar in bars {
-            let event = TimestampedEvent {
-                timestamp: bar.timestamp,
-                symbol: symbol.clone(),
-                event: MarketEvent::Bar(bar.clone()),
-            };
-
+        let feed = self.feeds.len();
+        for (row, &timestamp) in columns.timestamps().iter().enumerate() {
             self.events
-                .entry(bar.timestamp)
-                .or_insert_with(Vec::new)
-                .push(event);
+                .entry(timestamp)
+                .or_default()
+                .push(FeedRow { feed, row });
 
             // Update simulation time bounds
-            if self.start_time.is_none() || bar.timestamp < self.start_time.unwrap() {
-                self.start_time = Some(bar.timestamp);
+            if self.start_time.is_none() || timestamp < self.start_time.unwrap() {
+                self.start_time = Some(timestamp);
             }
-            if self.end_time.is_none() || bar.timestamp > self.end_time.unwrap() {
-                self.end_time = Some(bar.timestamp);
+            if self.end_time.is_none() || timestamp > self.end_time.unwrap() {
+                self.end_time = Some(timestamp);
             }
         }
+        self.feeds.push(columns);
 
         debug!(
             "Data feed added: {} events between {:?} and {:?}",
@@ -191,6 +285,17 @@ impl MarketSimulator {
             .into());
         }
 
+        self.session_calendar = SessionCalendar::build(
+            &self.sessions,
+            self.feeds.iter().map(|feed| {
+                (
+                    feed.symbol(),
+                    feed.resolution(),
+                    feed.timestamps().iter().copied(),
+                )
+            }),
+        );
+
         // Set current time to just before start time so we can capture the first events
         self.current_time = self
             .start_time
@@ -223,21 +328,26 @@ impl MarketSimulator {
         }
 
         // Find next time with events
-        let current_time = self.current_time.ok_or_else(|| DataError::LoadingFailed {
-            message: "Simulation not initialized".to_string(),
-        })?;
-
-        // Find next timestamp with events (use Excluded to find events after current time)
-        let next_time = self
-            .events
-            .range((
-                std::ops::Bound::Excluded(current_time),
-                std::ops::Bound::Unbounded,
-            ))
-            .next()
-            .map(|(time, _)| *time);
-
-        if let Some(next_time) = next_time {
+        let current_time = self
+            .current_time
+            .ok_or_else(|| BacktestError::SimulationError {
+                message: "Simulation not initialized".to_string(),
+            })?;
+
+        // Find the next timestamp holding events for a symbol whose market is
+        // open, skipping over those when every market is closed
+        let mut after = current_time;
+        loop {
+            let Some((next_time, events)) = self
+                .events
+                .range((std::ops::Bound::Excluded(after), std::ops::Bound::Unbounded))
+                .next()
+            else {
+                debug!("No more market events available");
+                return Ok(Vec::new());
+            };
+            let next_time = *next_time;
+
             // Check if we've reached the end
             if let Some(end_time) = self.end_time {
                 if next_time > end_time {
@@ -246,33 +356,44 @@ impl MarketSimulator {
                 }
             }
 
+            let mut events: Vec<_> = events
+                .iter()
+                .filter_map(|position| {
+                    let feed = &self.feeds[position.feed];
+                    if !self.is_market_open(feed.symbol(), next_time) {
+                        return None;
+                    }
+                    Some(TimestampedEvent {
+                        timestamp: next_time,
+                        symbol: feed.symbol().clone(),
+                        priority: self.symbols.iter().position(|s| s == feed.symbol())?,
+                        event: MarketEvent::Bar(feed.get(position.row)?),
+                    })
+                })
+                .collect();
+            events.sort();
+
             // Advance to next time
             self.current_time = Some(next_time);
+            if events.is_empty() {
+                debug!("Every market closed at {:?}, skipping", next_time);
+                after = next_time;
+                continue;
+            }
 
-            // Get events for this time
-            if let Some(events) = self.events.get(&next_time) {
-                let events = events.clone();
-
-                // Update current market data state
-                for event in &events {
-                    if let MarketEvent::Bar(bar) = &event.event {
-                        self.current_data.insert(event.symbol.clone(), bar.clone());
-                    }
+            // Update current market data state
+            for event in &events {
+                if let MarketEvent::Bar(bar) = &event.event {
+                    self.current_data.insert(event.symbol.clone(), bar.clone());
                 }
-
-                debug!(
-                    "Advanced to {:?}, returning {} events",
-                    next_time,
-                    events.len()
-                );
-                Ok(events)
-            } else {
-                Ok(Vec::new())
             }
-        } else {
-            // No more events
-            debug!("No more market events available");
-            Ok(Vec::new())
+
+            debug!(
+                "Advanced to {:?}, returning {} events",
+                next_time,
+                events.len()
+            );
+            return Ok(events);
         }
     }
 
@@ -392,24 +513,49 @@ impl MarketSimulator {
         Ok(())
     }
 
-    /// Check if market is open at given time (simplified)
-    pub fn is_market_open(&self, time: DateTime<Utc>) -> bool {
-        // Weekend check
-        if !self.market_hours.weekend_trading {
-            let weekday = time.weekday();
-            if weekday == chrono::Weekday::Sat || weekday == chrono::Weekday::Sun {
-                return false;
-            }
-        }
+    /// Check if the market `symbol` trades on is open at the given time
+    pub fn is_market_open(&self, symbol: &Symbol, time: DateTime<Utc>) -> bool {
+        self.sessions.calendar_for(symbol).is_open(time)
+    }
 
-        // 24-hour assets skip intraday hour gating
-        if self.market_hours.round_the_clock {
-            return true;
-        }
+    /// Session closes of the simulated symbols in `(after, until]`, in time
+    /// order. Each symbol's trading day ends at its own close while the
+    /// simulation advances on a single clock, so end-of-day work for a
+    /// step should run for the closes since the previous step.
+    pub fn session_closes_between(
+        &self,
+        after: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Vec<(Symbol, DateTime<Utc>)> {
+        let mut closes: Vec<_> = self
+            .symbols
+            .iter()
+            .flat_map(|symbol| {
+                let calendar = self.sessions.calendar_for(symbol);
+                // An exchange-local date may start or end on a different UTC
+                // date
+                let first_date = after.date_naive() - Duration::days(1);
+                let last_date = until.date_naive() + Duration::days(1);
+                first_date
+                    .iter_days()
+                    .take_while(|date| *date <= last_date)
+                    .filter_map(|date| calendar.close_on(date))
+                    .filter(|close| *close > after && *close <= until)
+                    .map(|close| (symbol.clone(), close))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        closes.sort_by(|(a, a_close), (b, b_close)| {
+            a_close.cmp(b_close).then_with(|| a.symbol.cmp(&b.symbol))
+        });
+        closes
+    }
 
-        // Market hours check
-        let hour = time.hour();
-        hour >= self.market_hours.open_hour && hour < self.market_hours.close_hour
+    /// Where the events at the current time fall in the day's session, as
+    /// located by [`SessionCalendar::position`]. `None` before the first
+    /// step, or when no market has events at the current time.
+    pub fn session_position(&self) -> Option<SessionPosition> {
+        self.session_calendar.position(self.current_time?)
     }
 }
 
@@ -432,9 +578,26 @@ impl Default for MarketSimulator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{NaiveTime, TimeZone};
     use gb_types::AssetClass;
+    use gb_types::GbError;
     use rust_decimal::Decimal;
 
+    #[test]
+    fn empty_feeds_are_insufficient_data() {
+        let mut simulator = MarketSimulator::new();
+
+        let error = simulator
+            .add_data_feed(Symbol::equity("AAPL"), Vec::new())
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            GbError::Data(DataError::InsufficientData { .. })
+        ));
+        let error = simulator.initialize().unwrap_err();
+        assert_eq!(error.code(), "data.insufficient_data");
+    }
+
     #[tokio::test]
     async fn test_market_simulator_basic() {
         let mut simulator = MarketSimulator::new();
@@ -442,7 +605,11 @@ mod tests {
         let symbol = Symbol::new("AAPL", "NASDAQ", AssetClass::Equity);
         let bars = vec![Bar::new(
             symbol.clone(),
-            Utc::now(),
+            chrono::NaiveDate::from_ymd_opt(2026, 2, 20)
+                .unwrap()
+                .and_hms_opt(15, 0, 0)
+                .unwrap()
+                .and_utc(),
             Decimal::from(100),
             Decimal::from(105),
             Decimal::from(99),
@@ -459,6 +626,27 @@ mod tests {
         assert_eq!(events[0].symbol, symbol);
     }
 
+    #[test]
+    fn test_data_feed_rejects_bars_of_another_symbol() {
+        let mut simulator = MarketSimulator::new();
+        let bar = Bar::new(
+            Symbol::equity("MSFT"),
+            Utc.with_ymd_and_hms(2026, 2, 20, 15, 0, 0).unwrap(),
+            Decimal::from(100),
+            Decimal::from(105),
+            Decimal::from(99),
+            Decimal::from(102),
+            Decimal::from(1000),
+            Resolution::Day,
+        );
+
+        assert!(simulator
+            .add_data_feed(Symbol::equity("AAPL"), vec![bar])
+            .is_err());
+        assert_eq!(simulator.get_stats().total_symbols, 0);
+        assert!(simulator.initialize().is_err());
+    }
+
     #[tokio::test]
     async fn test_market_simulator_multi_symbol() {
         let mut simulator = MarketSimulator::new();
@@ -466,7 +654,11 @@ mod tests {
         let symbol1 = Symbol::new("AAPL", "NASDAQ", AssetClass::Equity);
         let symbol2 = Symbol::new("GOOGL", "NASDAQ", AssetClass::Equity);
 
-        let time = Utc::now();
+        let time = chrono::NaiveDate::from_ymd_opt(2026, 2, 20)
+            .unwrap()
+            .and_hms_opt(15, 0, 0)
+            .unwrap()
+            .and_utc();
         let bars1 = vec![Bar::new(
             symbol1.clone(),
             time,
@@ -501,35 +693,41 @@ mod tests {
     }
 
     #[test]
-    fn test_market_hours_for_crypto() {
-        let hours = MarketHours::for_asset_class(AssetClass::Crypto);
-        assert!(hours.weekend_trading, "Crypto should trade on weekends");
-        assert!(hours.round_the_clock, "Crypto should be 24h");
-    }
+    fn same_timestamp_events_follow_the_order_feeds_were_added() {
+        let time = Utc.with_ymd_and_hms(2026, 2, 20, 15, 0, 0).unwrap();
+        let bar = |symbol: &Symbol| {
+            let price = Decimal::from(100);
+            Bar::new(
+                symbol.clone(),
+                time,
+                price,
+                price,
+                price,
+                price,
+                Decimal::from(1000),
+                Resolution::Day,
+            )
+        };
+        let mut simulator = MarketSimulator::new();
+        for ticker in ["ZZZ", "AAA", "MMM"] {
+            let symbol = Symbol::equity(ticker);
+            simulator
+                .add_data_feed(symbol.clone(), vec![bar(&symbol)])
+                .unwrap();
+        }
+        simulator.initialize().unwrap();
 
-    #[test]
-    fn test_market_hours_for_equity() {
-        let hours = MarketHours::for_asset_class(AssetClass::Equity);
-        assert!(
-            !hours.weekend_trading,
-            "Equities should not trade on weekends"
-        );
-        assert!(!hours.round_the_clock, "Equities are not 24h");
-        assert_eq!(hours.open_hour, 14);
-        assert_eq!(hours.close_hour, 21);
-    }
+        let events = simulator.next_events().unwrap();
 
-    #[test]
-    fn test_market_hours_for_forex() {
-        let hours = MarketHours::for_asset_class(AssetClass::Forex);
-        assert!(!hours.weekend_trading, "Forex should not trade on weekends");
-        assert!(hours.round_the_clock, "Forex should be 24h on weekdays");
+        let tickers: Vec<_> = events.iter().map(|e| e.symbol.symbol.as_str()).collect();
+        assert_eq!(tickers, ["ZZZ", "AAA", "MMM"]);
+        assert!(events.windows(2).all(|pair| pair[0] < pair[1]));
     }
 
     #[test]
     fn test_crypto_market_open_on_weekend() {
-        let simulator = MarketSimulator::new()
-            .with_market_hours(MarketHours::for_asset_class(AssetClass::Crypto));
+        let simulator = MarketSimulator::new();
+        let btc = Symbol::crypto("BTC-USD");
 
         // Saturday 3 AM UTC
         let saturday = chrono::NaiveDate::from_ymd_opt(2026, 2, 21)
@@ -538,7 +736,7 @@ mod tests {
             .unwrap()
             .and_utc();
         assert!(
-            simulator.is_market_open(saturday),
+            simulator.is_market_open(&btc, saturday),
             "Crypto should be open Saturday"
         );
 
@@ -549,15 +747,15 @@ mod tests {
             .unwrap()
             .and_utc();
         assert!(
-            simulator.is_market_open(sunday),
+            simulator.is_market_open(&btc, sunday),
             "Crypto should be open Sunday"
         );
     }
 
     #[test]
     fn test_equity_market_closed_on_weekend() {
-        let simulator = MarketSimulator::new()
-            .with_market_hours(MarketHours::for_asset_class(AssetClass::Equity));
+        let simulator = MarketSimulator::new();
+        let aapl = Symbol::equity("AAPL");
 
         let saturday = chrono::NaiveDate::from_ymd_opt(2026, 2, 21)
             .unwrap()
@@ -565,8 +763,158 @@ mod tests {
             .unwrap()
             .and_utc();
         assert!(
-            !simulator.is_market_open(saturday),
+            !simulator.is_market_open(&aapl, saturday),
             "Equities should be closed Saturday"
         );
     }
+
+    #[test]
+    fn test_equity_session_closes_follow_new_york_time() {
+        let mut simulator = MarketSimulator::new();
+        let aapl = Symbol::equity("AAPL");
+        simulator.symbols.push(aapl.clone());
+        let at = |month: u32, day: u32, hour: u32| {
+            chrono::Utc
+                .with_ymd_and_hms(2024, month, day, hour, 0, 0)
+                .unwrap()
+        };
+
+        // 4:00 PM EST in January, 4:00 PM EDT in June
+        assert_eq!(
+            simulator.session_closes_between(at(1, 2, 12), at(1, 2, 23)),
+            vec![(aapl.clone(), at(1, 2, 21))]
+        );
+        assert_eq!(
+            simulator.session_closes_between(at(6, 17, 12), at(6, 17, 23)),
+            vec![(aapl.clone(), at(6, 17, 20))]
+        );
+        // No close on Juneteenth
+        assert!(simulator
+            .session_closes_between(at(6, 19, 0), at(6, 19, 23))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_session_position_across_days_and_half_day() {
+        let wednesday = chrono::NaiveDate::from_ymd_opt(2025, 11, 26).unwrap();
+        let friday = chrono::NaiveDate::from_ymd_opt(2025, 11, 28).unwrap();
+        let mut simulator = MarketSimulator::new().with_sessions(
+            SessionResolver::new().with_asset_class_calendar(
+                AssetClass::Equity,
+                TradingCalendar::default()
+                    .with_early_close(friday, NaiveTime::from_hms_opt(13, 0, 0).unwrap()),
+            ),
+        );
+
+        // Friday's 19:00 bar falls after the 13:00 EST (18:00 UTC) early close
+        let times: Vec<DateTime<Utc>> = [(wednesday, 15), (wednesday, 17), (wednesday, 20)]
+            .into_iter()
+            .chain([(friday, 15), (friday, 17), (friday, 19)])
+            .map(|(date, hour)| date.and_hms_opt(hour, 0, 0).unwrap().and_utc())
+            .collect();
+        for ticker in ["AAPL", "MSFT"] {
+            let symbol = Symbol::new(ticker, "NASDAQ", AssetClass::Equity);
+            let bars = times
+                .iter()
+                .map(|time| {
+                    Bar::new(
+                        symbol.clone(),
+                        *time,
+                        Decimal::from(100),
+                        Decimal::from(101),
+                        Decimal::from(99),
+                        Decimal::from(100),
+                        Decimal::from(1000),
+                        Resolution::Hour,
+                    )
+                })
+                .collect();
+            simulator.add_data_feed(symbol, bars).unwrap();
+        }
+        simulator.initialize().unwrap();
+        assert_eq!(simulator.session_position(), None);
+
+        let position = |seen, remaining, open, close| {
+            Some(SessionPosition {
+                events_seen: seen,
+                events_remaining: remaining,
+                is_session_open: open,
+                is_session_close: close,
+            })
+        };
+        let expected = [
+            position(2, 4, true, false),
+            position(4, 2, false, false),
+            position(6, 0, false, true),
+            position(2, 2, true, false),
+            position(4, 0, false, true),
+        ];
+        for expected in expected {
+            assert_eq!(simulator.next_events().unwrap().len(), 2);
+            assert_eq!(simulator.session_position(), expected);
+        }
+        assert!(simulator.next_events().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mixed_universe_follows_each_symbols_session() {
+        let aapl = Symbol::equity("AAPL");
+        let btc = Symbol::crypto("BTC-USD");
+        let at = |day: u32, hour: u32| {
+            chrono::NaiveDate::from_ymd_opt(2026, 2, day)
+                .unwrap()
+                .and_hms_opt(hour, 0, 0)
+                .unwrap()
+                .and_utc()
+        };
+        // Friday, Saturday and Monday afternoons
+        let times = [at(20, 15), at(21, 15), at(23, 15)];
+
+        let mut simulator = MarketSimulator::new();
+        for symbol in [&aapl, &btc] {
+            let bars = times
+                .iter()
+                .map(|time| {
+                    Bar::new(
+                        symbol.clone(),
+                        *time,
+                        Decimal::from(100),
+                        Decimal::from(101),
+                        Decimal::from(99),
+                        Decimal::from(100),
+                        Decimal::from(1000),
+                        Resolution::Hour,
+                    )
+                })
+                .collect();
+            simulator.add_data_feed(symbol.clone(), bars).unwrap();
+        }
+        simulator.initialize().unwrap();
+
+        let delivered = |events: Vec<TimestampedEvent>| -> Vec<Symbol> {
+            events.into_iter().map(|event| event.symbol).collect()
+        };
+        assert_eq!(
+            delivered(simulator.next_events().unwrap()),
+            vec![aapl.clone(), btc.clone()]
+        );
+        assert_eq!(
+            delivered(simulator.next_events().unwrap()),
+            vec![btc.clone()]
+        );
+        assert_eq!(simulator.current_time(), Some(times[1]));
+        assert_eq!(
+            simulator.session_closes_between(times[0], times[1]),
+            vec![(aapl.clone(), at(20, 21)), (btc.clone(), at(21, 0))]
+        );
+
+        assert_eq!(
+            delivered(simulator.next_events().unwrap()),
+            vec![aapl.clone(), btc.clone()]
+        );
+        assert_eq!(
+            simulator.session_closes_between(times[1], times[2]),
+            vec![(btc.clone(), at(22, 0)), (btc.clone(), at(23, 0))]
+        );
+    }
 }
@@ -0,0 +1,584 @@
+//! Transport bridge for engine events.
+//!
+//! [`EventBridge`] reads a backtest or live engine's broadcast channel,
+//! numbers each event, encodes it once as a JSON frame and fans the frames
+//! out to clients. Each client gets its own [`EventFilter`] and hands frames
+//! to any async [`Sink`], so a web layer can mount the bridge as a WebSocket
+//! or SSE endpoint without this crate knowing about either.
+//!
+//! A frame's JSON is
+//! `{"sequence": 7, "kind": "OrderFilled", "severity": "info", "timestamp": "...", "event": {...}}`,
+//! where `event` is the engine event in its serde form. Sequences start at 1
+//! and count every bridged event, so a filtered client sees gaps by design.
+//! The last `replay_capacity` frames are kept; a reconnecting client passes
+//! the last sequence it saw and receives everything after it before joining
+//! the live stream.
+
+use std::collections::{BTreeSet, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use chrono::{DateTime, Utc};
+use futures_util::{Sink, SinkExt};
+use gb_types::BacktestEvent;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+/// How much attention an event deserves; clients can drop the lower ones.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EventSeverity {
+    /// High-volume telemetry such as progress ticks and market data.
+    #[default]
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+/// An engine event the bridge can carry.
+pub trait StreamEvent: Clone + Send + Sync + 'static {
+    /// The variant name, e.g. `"OrderFilled"`; what [`EventFilter::kinds`]
+    /// matches against.
+    fn kind(&self) -> &'static str;
+
+    fn severity(&self) -> EventSeverity;
+
+    /// The frame's `event` field.
+    fn payload(&self) -> serde_json::Result<serde_json::Value>;
+}
+
+impl StreamEvent for BacktestEvent {
+    fn kind(&self) -> &'static str {
+        match self {
+            BacktestEvent::Started { .. } => "Started",
+            BacktestEvent::Progress { .. } => "Progress",
+            BacktestEvent::EquityUpdate { .. } => "EquityUpdate",
+            BacktestEvent::TradeExecuted { .. } => "TradeExecuted",
+            BacktestEvent::PositionsSnapshot { .. } => "PositionsSnapshot",
+            BacktestEvent::DataStale { .. } => "DataStale",
+            BacktestEvent::MarginCall { .. } => "MarginCall",
+            BacktestEvent::Profile { .. } => "Profile",
+            BacktestEvent::Completed { .. } => "Completed",
+            BacktestEvent::Failed { .. } => "Failed",
+        }
+    }
+
+    fn severity(&self) -> EventSeverity {
+        match self {
+            BacktestEvent::Progress { .. }
+            | BacktestEvent::EquityUpdate { .. }
+            | BacktestEvent::PositionsSnapshot { .. }
+            | BacktestEvent::Profile { .. } => EventSeverity::Debug,
+            BacktestEvent::Started { .. }
+            | BacktestEvent::TradeExecuted { .. }
+            | BacktestEvent::Completed { .. } => EventSeverity::Info,
+            BacktestEvent::DataStale { .. } | BacktestEvent::MarginCall { .. } => {
+                EventSeverity::Warning
+            }
+            BacktestEvent::Failed { .. } => EventSeverity::Error,
+        }
+    }
+
+    /// `Completed` carries a summary of the result — status, metrics and
+    /// sizes — rather than the full result, whose curve and trades already
+    /// went out as `EquityUpdate` and `TradeExecuted` frames.
+    fn payload(&self) -> serde_json::Result<serde_json::Value> {
+        match self {
+            BacktestEvent::Completed {
+                backtest_id,
+                result,
+            } => Ok(serde_json::json!({
+                "Completed": {
+                    "backtest_id": backtest_id,
+                    "status": result.status,
+                    "duration_seconds": result.duration_seconds,
+                    "performance_metrics": serde_json::to_value(&result.performance_metrics)?,
+                    "equity_points": result.equity_curve.len(),
+                    "trades": result.trade_log.len(),
+                    "error_message": result.error_message,
+                }
+            })),
+            event => serde_json::to_value(event),
+        }
+    }
+}
+
+/// One encoded event, ready to send.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EventFrame {
+    pub sequence: u64,
+    pub kind: &'static str,
+    pub severity: EventSeverity,
+    /// The full JSON frame described in the module docs.
+    pub json: String,
+}
+
+#[derive(Serialize)]
+struct FrameBody<'a> {
+    sequence: u64,
+    kind: &'a str,
+    severity: EventSeverity,
+    timestamp: DateTime<Utc>,
+    event: serde_json::Value,
+}
+
+/// Which frames a client receives.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventFilter {
+    /// Event kinds to deliver; empty delivers every kind.
+    #[serde(default)]
+    pub kinds: BTreeSet<String>,
+    #[serde(default)]
+    pub min_severity: EventSeverity,
+}
+
+impl EventFilter {
+    /// Delivers every frame.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    pub fn with_kinds<I, S>(mut self, kinds: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.kinds = kinds.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_min_severity(mut self, severity: EventSeverity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    pub fn matches(&self, frame: &EventFrame) -> bool {
+        frame.severity >= self.min_severity
+            && (self.kinds.is_empty() || self.kinds.contains(frame.kind))
+    }
+}
+
+/// Why a client's stream ended early.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgeError {
+    /// The frames after `requested` have left the replay buffer; the oldest
+    /// one still held is `oldest`.
+    ReplayUnavailable { requested: u64, oldest: u64 },
+    /// The client fell more than the buffer behind the live stream. It can
+    /// reconnect with `resume_after`.
+    Lagged { resume_after: u64 },
+    /// The client's sink failed.
+    Sink(String),
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeError::ReplayUnavailable { requested, oldest } => write!(
+                f,
+                "cannot replay after sequence {requested}; the oldest buffered frame is {oldest}"
+            ),
+            BridgeError::Lagged { resume_after } => write!(
+                f,
+                "client fell behind the event stream; resume after sequence {resume_after}"
+            ),
+            BridgeError::Sink(message) => write!(f, "event sink failed: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BridgeError {}
+
+#[derive(Debug)]
+struct BridgeState {
+    replay: VecDeque<Arc<EventFrame>>,
+    replay_capacity: usize,
+    next_sequence: u64,
+    /// `None` once the source channel has closed.
+    live: Option<broadcast::Sender<Arc<EventFrame>>>,
+}
+
+/// Numbers, encodes and fans out one engine's events. Clones share the same
+/// stream.
+#[derive(Debug, Clone)]
+pub struct EventBridge {
+    state: Arc<Mutex<BridgeState>>,
+}
+
+impl EventBridge {
+    /// Bridge `source`, keeping the last `replay_capacity` frames for
+    /// reconnecting clients. Must be called inside a Tokio runtime; the
+    /// forwarding task ends when every sender of `source` is dropped.
+    pub fn spawn<E: StreamEvent>(
+        mut source: broadcast::Receiver<E>,
+        replay_capacity: usize,
+    ) -> Self {
+        let replay_capacity = replay_capacity.max(1);
+        let (live, _) = broadcast::channel(replay_capacity);
+        let bridge = Self {
+            state: Arc::new(Mutex::new(BridgeState {
+                replay: VecDeque::with_capacity(replay_capacity),
+                replay_capacity,
+                next_sequence: 1,
+                live: Some(live),
+            })),
+        };
+
+        let forwarder = bridge.clone();
+        tokio::spawn(async move {
+            loop {
+                match source.recv().await {
+                    Ok(event) => forwarder.publish(&event),
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        warn!("Event bridge fell behind its source and missed {missed} events");
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+            forwarder.lock().live = None;
+        });
+        bridge
+    }
+
+    /// Sequence of the newest frame, or 0 before the first.
+    pub fn latest_sequence(&self) -> u64 {
+        self.lock().next_sequence - 1
+    }
+
+    /// Whether the source has closed; clients then end after the replay.
+    pub fn is_closed(&self) -> bool {
+        self.lock().live.is_none()
+    }
+
+    /// Send frames matching `filter` to `sink` until the source closes.
+    ///
+    /// With `resume_after` set, every buffered frame after that sequence is
+    /// sent first (`Some(0)` replays from the start); with `None` the client
+    /// only gets frames published from now on. The sink is closed when the
+    /// stream ends.
+    pub async fn serve<S>(
+        &self,
+        filter: &EventFilter,
+        resume_after: Option<u64>,
+        sink: S,
+    ) -> Result<(), BridgeError>
+    where
+        S: Sink<EventFrame>,
+        S::Error: fmt::Display,
+    {
+        // Snapshot the buffer and subscribe under one lock so no frame
+        // falls between the replay and the live stream.
+        let (backlog, live) = {
+            let state = self.lock();
+            let backlog = match resume_after {
+                None => Vec::new(),
+                Some(after) => {
+                    let oldest = state
+                        .replay
+                        .front()
+                        .map_or(state.next_sequence, |frame| frame.sequence);
+                    if after + 1 < oldest {
+                        return Err(BridgeError::ReplayUnavailable {
+                            requested: after,
+                            oldest,
+                        });
+                    }
+                    state
+                        .replay
+                        .iter()
+                        .filter(|frame| frame.sequence > after)
+                        .cloned()
+                        .collect()
+                }
+            };
+            let live = state.live.as_ref().map(broadcast::Sender::subscribe);
+            (backlog, live)
+        };
+
+        let mut sink = std::pin::pin!(sink);
+        let mut last_seen = resume_after.unwrap_or(0);
+        for frame in backlog {
+            last_seen = frame.sequence;
+            deliver(&mut sink, filter, &frame).await?;
+        }
+        if let Some(mut live) = live {
+            loop {
+                match live.recv().await {
+                    Ok(frame) => {
+                        last_seen = frame.sequence;
+                        deliver(&mut sink, filter, &frame).await?;
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => {
+                        return Err(BridgeError::Lagged {
+                            resume_after: last_seen,
+                        });
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+        sink.close()
+            .await
+            .map_err(|e| BridgeError::Sink(e.to_string()))
+    }
+
+    fn publish<E: StreamEvent>(&self, event: &E) {
+        let payload = match event.payload() {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!(
+                    "Dropping {} event that failed to encode: {}",
+                    event.kind(),
+                    e
+                );
+                return;
+            }
+        };
+
+        let mut state = self.lock();
+        let sequence = state.next_sequence;
+        let body = FrameBody {
+            sequence,
+            kind: event.kind(),
+            severity: event.severity(),
+            timestamp: Utc::now(),
+            event: payload,
+        };
+        let json = match serde_json::to_string(&body) {
+            Ok(json) => json,
+            Err(e) => {
+                warn!(
+                    "Dropping {} event that failed to encode: {}",
+                    event.kind(),
+                    e
+                );
+                return;
+            }
+        };
+        let frame = Arc::new(EventFrame {
+            sequence,
+            kind: event.kind(),
+            severity: event.severity(),
+            json,
+        });
+
+        state.next_sequence += 1;
+        if state.replay.len() == state.replay_capacity {
+            state.replay.pop_front();
+        }
+        state.replay.push_back(frame.clone());
+        if let Some(live) = &state.live {
+            // No subscribers is fine; the frame stays in the replay buffer.
+            let _ = live.send(frame);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BridgeState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+async fn deliver<S>(
+    sink: &mut std::pin::Pin<&mut S>,
+    filter: &EventFilter,
+    frame: &EventFrame,
+) -> Result<(), BridgeError>
+where
+    S: Sink<EventFrame>,
+    S::Error: fmt::Display,
+{
+    if !filter.matches(frame) {
+        return Ok(());
+    }
+    sink.send(frame.clone())
+        .await
+        .map_err(|e| BridgeError::Sink(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BacktestEngine;
+    use chrono::Duration;
+    use gb_types::{BacktestConfig, BuyAndHoldStrategy, StrategyConfig, Symbol};
+    use tokio::sync::mpsc;
+    use uuid::Uuid;
+
+    fn channel_sink() -> (
+        impl Sink<EventFrame, Error = String>,
+        mpsc::UnboundedReceiver<EventFrame>,
+    ) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let sink = futures_util::sink::unfold(tx, |tx, frame: EventFrame| async move {
+            tx.send(frame).map_err(|e| e.to_string())?;
+            Ok::<_, String>(tx)
+        });
+        (sink, rx)
+    }
+
+    fn collect(mut frames: mpsc::UnboundedReceiver<EventFrame>) -> Vec<EventFrame> {
+        let mut collected = Vec::new();
+        while let Ok(frame) = frames.try_recv() {
+            collected.push(frame);
+        }
+        collected
+    }
+
+    fn progress(backtest_id: Uuid, day: i64) -> BacktestEvent {
+        BacktestEvent::Progress {
+            backtest_id,
+            progress_pct: day as f64,
+            current_date: Utc::now() + Duration::days(day),
+        }
+    }
+
+    async fn wait_for(bridge: &EventBridge, sequence: u64) {
+        while bridge.latest_sequence() < sequence {
+            tokio::task::yield_now().await;
+        }
+    }
+
+    fn sequences(frames: &[EventFrame]) -> Vec<u64> {
+        frames.iter().map(|frame| frame.sequence).collect()
+    }
+
+    #[tokio::test]
+    async fn client_joining_mid_stream_with_a_cursor_sees_no_gaps() {
+        let (source, receiver) = broadcast::channel(64);
+        let bridge = EventBridge::spawn(receiver, 64);
+        let id = Uuid::new_v4();
+
+        for day in 1..=5 {
+            source.send(progress(id, day)).unwrap();
+        }
+        wait_for(&bridge, 5).await;
+
+        let (sink, frames) = channel_sink();
+        let client = {
+            let bridge = bridge.clone();
+            tokio::spawn(async move { bridge.serve(&EventFilter::all(), Some(2), sink).await })
+        };
+        for day in 6..=10 {
+            source.send(progress(id, day)).unwrap();
+        }
+        drop(source);
+        client.await.unwrap().unwrap();
+
+        let frames = collect(frames);
+        assert_eq!(sequences(&frames), (3..=10).collect::<Vec<_>>());
+        let body: serde_json::Value = serde_json::from_str(&frames[0].json).unwrap();
+        assert_eq!(body["sequence"], 3);
+        assert_eq!(body["kind"], "Progress");
+        assert_eq!(body["severity"], "debug");
+        assert_eq!(body["event"]["Progress"]["progress_pct"], 3.0);
+        assert!(bridge.is_closed());
+    }
+
+    #[tokio::test]
+    async fn scripted_backtest_replays_to_a_late_subscriber_without_gaps() {
+        let mut strategy = StrategyConfig::new("buy_and_hold".into(), "Buy and hold".into());
+        strategy.symbols = vec![Symbol::equity("AAPL")];
+        let mut config = BacktestConfig::new("Bridge".into(), strategy)
+            .with_symbols(vec![Symbol::equity("AAPL")])
+            .with_date_range(Utc::now() - Duration::days(20), Utc::now());
+        config.data_settings.data_source = "sample".to_string();
+
+        let mut engine = BacktestEngine::new(config).await.unwrap();
+        let bridge = EventBridge::spawn(engine.subscribe(), 1024);
+
+        let (sink, early_frames) = channel_sink();
+        let early = {
+            let bridge = bridge.clone();
+            tokio::spawn(async move { bridge.serve(&EventFilter::all(), Some(0), sink).await })
+        };
+        let (sink, trade_frames) = channel_sink();
+        let trades_only = {
+            let bridge = bridge.clone();
+            let filter = EventFilter::all().with_kinds(["TradeExecuted"]);
+            tokio::spawn(async move { bridge.serve(&filter, Some(0), sink).await })
+        };
+
+        let result = engine
+            .run_with_strategy(Box::new(BuyAndHoldStrategy::new()))
+            .await
+            .unwrap();
+        drop(engine);
+        early.await.unwrap().unwrap();
+        trades_only.await.unwrap().unwrap();
+
+        let early_frames = collect(early_frames);
+        let total = early_frames.len() as u64;
+        assert_eq!(sequences(&early_frames), (1..=total).collect::<Vec<_>>());
+        assert_eq!(early_frames.first().unwrap().kind, "Started");
+        assert_eq!(early_frames.last().unwrap().kind, "Completed");
+        let equity_updates = early_frames
+            .iter()
+            .filter(|frame| frame.kind == "EquityUpdate")
+            .count();
+        assert_eq!(equity_updates, result.equity_curve.len());
+
+        // A client reconnecting after the run resumes from its cursor.
+        let cursor = total / 2;
+        let (sink, late_frames) = channel_sink();
+        bridge
+            .serve(&EventFilter::all(), Some(cursor), sink)
+            .await
+            .unwrap();
+        let late_frames = collect(late_frames);
+        assert_eq!(late_frames, early_frames[cursor as usize..].to_vec());
+
+        let trade_frames = collect(trade_frames);
+        assert_eq!(trade_frames.len(), result.trade_log.len());
+        assert!(!trade_frames.is_empty());
+        let body: serde_json::Value = serde_json::from_str(&trade_frames[0].json).unwrap();
+        let event: BacktestEvent = serde_json::from_value(body["event"].clone()).unwrap();
+        assert!(matches!(
+            event,
+            BacktestEvent::TradeExecuted { trade, .. } if trade == result.trade_log[0]
+        ));
+    }
+
+    #[tokio::test]
+    async fn severity_filter_and_evicted_cursors() {
+        let (source, receiver) = broadcast::channel(64);
+        let bridge = EventBridge::spawn(receiver, 4);
+        let id = Uuid::new_v4();
+        source.send(progress(id, 1)).unwrap();
+        source
+            .send(BacktestEvent::Failed {
+                backtest_id: id,
+                error: "boom".into(),
+            })
+            .unwrap();
+        for day in 2..=4 {
+            source.send(progress(id, day)).unwrap();
+        }
+        wait_for(&bridge, 5).await;
+
+        let (sink, _) = channel_sink();
+        assert_eq!(
+            bridge.serve(&EventFilter::all(), Some(0), sink).await,
+            Err(BridgeError::ReplayUnavailable {
+                requested: 0,
+                oldest: 2
+            })
+        );
+
+        drop(source);
+        while !bridge.is_closed() {
+            tokio::task::yield_now().await;
+        }
+        let (sink, frames) = channel_sink();
+        let warnings = EventFilter::all().with_min_severity(EventSeverity::Warning);
+        bridge.serve(&warnings, Some(1), sink).await.unwrap();
+        let frames = collect(frames);
+        assert_eq!(sequences(&frames), vec![2]);
+        assert_eq!(frames[0].kind, "Failed");
+    }
+}
@@ -0,0 +1,391 @@
+//! Tracing spans that tie log events to the run they came from, and a
+//! subscriber that writes each run's events to its own JSON-lines file.
+//!
+//! Backtests, optimizations and their trials, and live engines run inside
+//! spans carrying `backtest_id`, `optimization_id`, `trial_id` and
+//! `strategy_id` fields, so every event logged during a run, down to the
+//! data layer, carries the ids as span fields. [`init`] installs a global
+//! subscriber that logs to stderr and, once a run log directory is set,
+//! appends every event of a run to `<dir>/<run id>/logs.jsonl`. Pointed at
+//! the result archive root, that is the run's bundle directory when it is
+//! saved under its id (see [`crate::archive`]).
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+use chrono::Utc;
+use gb_types::{BacktestId, GbError, GbResult};
+use serde_json::{json, Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Span, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+/// Name of the file a run's events are appended to, in the run's directory.
+pub const RUN_LOG_FILE: &str = "logs.jsonl";
+
+/// Span fields that name a run, in the order a run log is chosen by: the
+/// trials of an optimization log to their backtests' files, and anything
+/// else logged by the optimization to its own.
+const RUN_ID_FIELDS: [&str; 2] = ["backtest_id", "optimization_id"];
+
+/// Run log files kept open at once. All are closed when the limit is hit;
+/// runs still logging reopen theirs.
+const OPEN_RUN_LOGS: usize = 64;
+
+// The run spans are error-level so that they are enabled under any level
+// filter and their ids reach every event that is.
+
+/// Span of one backtest, from loading its data to its result.
+pub fn backtest_span(backtest_id: BacktestId, strategy_id: &str) -> Span {
+    tracing::error_span!("backtest", %backtest_id, strategy_id)
+}
+
+/// Span of an optimization run and everything its trials do.
+pub fn optimization_span(optimization_id: impl fmt::Display) -> Span {
+    tracing::error_span!("optimization", optimization_id = %optimization_id)
+}
+
+/// Span of one optimization trial, inside its optimization's span.
+pub fn trial_span(trial_id: impl fmt::Display, trial_number: usize) -> Span {
+    tracing::error_span!("trial", trial_id = %trial_id, trial_number)
+}
+
+/// Span of one strategy hosted by a live engine, inside the engine's span.
+pub fn strategy_span(parent: &Span, strategy_id: &str) -> Span {
+    tracing::error_span!(parent: parent, "strategy", strategy_id)
+}
+
+/// Settings for [`init`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TelemetryConfig {
+    /// Level filter in `EnvFilter` syntax, e.g. `info` or
+    /// `warn,gb_engine=debug`.
+    pub level: String,
+    /// Also log human-readable lines to stderr.
+    pub stderr: bool,
+    /// Directory each run's events are written under, usually the result
+    /// archive root. `None` writes no run logs.
+    pub run_log_dir: Option<PathBuf>,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            stderr: true,
+            run_log_dir: None,
+        }
+    }
+}
+
+/// Handle to the subscriber installed by [`init`], for changing its level
+/// and run log directory afterwards.
+#[derive(Clone)]
+pub struct Telemetry {
+    filter: reload::Handle<EnvFilter, Registry>,
+    run_logs: RunLogLayer,
+}
+
+impl fmt::Debug for Telemetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Telemetry")
+            .field("run_log_dir", &self.run_logs.dir())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Telemetry {
+    /// Replace the level filter.
+    pub fn set_level(&self, level: &str) -> GbResult<()> {
+        self.filter
+            .reload(parse_filter(level)?)
+            .map_err(|e| GbError::Internal(format!("could not change the log level: {e}")))
+    }
+
+    /// Write run logs under `dir`, or stop writing them with `None`.
+    pub fn set_run_log_dir(&self, dir: Option<PathBuf>) {
+        self.run_logs.set_dir(dir);
+    }
+
+    pub fn run_log_dir(&self) -> Option<PathBuf> {
+        self.run_logs.dir()
+    }
+}
+
+/// Install the global tracing subscriber described by `config`. Fails when
+/// the level does not parse or another global subscriber is installed.
+pub fn init(config: &TelemetryConfig) -> GbResult<Telemetry> {
+    let (filter, handle) = reload::Layer::new(parse_filter(&config.level)?);
+    let run_logs = RunLogLayer::new(config.run_log_dir.clone());
+    let stderr = config
+        .stderr
+        .then(|| tracing_subscriber::fmt::layer().with_writer(std::io::stderr));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(stderr)
+        .with(run_logs.clone())
+        .try_init()
+        .map_err(|e| GbError::Config(format!("could not install the tracing subscriber: {e}")))?;
+    Ok(Telemetry {
+        filter: handle,
+        run_logs,
+    })
+}
+
+fn parse_filter(level: &str) -> GbResult<EnvFilter> {
+    EnvFilter::try_new(level)
+        .map_err(|e| GbError::Config(format!("invalid log level '{level}': {e}")))
+}
+
+/// Appends every event logged inside a run's span, as one JSON object per
+/// line, to `<dir>/<run id>/logs.jsonl`. Each line holds the event's
+/// `timestamp`, `level`, `target`, `message` and other `fields`, the fields
+/// of its enclosing spans merged in `span` (innermost wins) and the span
+/// names from the outermost in `spans`. Events outside any run are skipped.
+///
+/// Clones share their directory and open files.
+#[derive(Clone, Default)]
+pub struct RunLogLayer {
+    dir: Arc<RwLock<Option<PathBuf>>>,
+    files: Arc<Mutex<HashMap<PathBuf, File>>>,
+}
+
+impl RunLogLayer {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self {
+            dir: Arc::new(RwLock::new(dir)),
+            files: Arc::default(),
+        }
+    }
+
+    pub fn dir(&self) -> Option<PathBuf> {
+        self.dir.read().map_or(None, |dir| dir.clone())
+    }
+
+    pub fn set_dir(&self, dir: Option<PathBuf>) {
+        if let Ok(mut current) = self.dir.write() {
+            *current = dir;
+        }
+        if let Ok(mut files) = self.files.lock() {
+            files.clear();
+        }
+    }
+
+    fn append(&self, path: &Path, line: &Value) -> std::io::Result<()> {
+        let mut files = self
+            .files
+            .lock()
+            .map_err(|_| std::io::Error::other("run log files poisoned"))?;
+        if !files.contains_key(path) {
+            if files.len() >= OPEN_RUN_LOGS {
+                files.clear();
+            }
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = OpenOptions::new().create(true).append(true).open(path)?;
+            files.insert(path.to_path_buf(), file);
+        }
+        let file = files.get_mut(path).expect("opened above");
+        let mut bytes = serde_json::to_vec(line)?;
+        bytes.push(b'\n');
+        file.write_all(&bytes)
+    }
+}
+
+impl fmt::Debug for RunLogLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RunLogLayer")
+            .field("dir", &self.dir())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Recorded fields of a span, kept in its extensions.
+struct SpanFields(Map<String, Value>);
+
+/// Records fields into a JSON object.
+struct JsonFields<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonFields<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{value:?}")));
+    }
+}
+
+impl<S> Layer<S> for RunLogLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut fields = Map::new();
+        attrs.record(&mut JsonFields(&mut fields));
+        span.extensions_mut().insert(SpanFields(fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+        let mut extensions = span.extensions_mut();
+        if let Some(fields) = extensions.get_mut::<SpanFields>() {
+            values.record(&mut JsonFields(&mut fields.0));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let Some(dir) = self.dir() else {
+            return;
+        };
+        let mut span_fields = Map::new();
+        let mut names = Vec::new();
+        for span in ctx
+            .event_scope(event)
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+        {
+            names.push(json!(span.name()));
+            if let Some(fields) = span.extensions().get::<SpanFields>() {
+                span_fields.extend(fields.0.clone());
+            }
+        }
+        let Some(run_id) = RUN_ID_FIELDS
+            .iter()
+            .find_map(|field| span_fields.get(*field).and_then(Value::as_str))
+        else {
+            return;
+        };
+        let path = dir.join(run_id).join(RUN_LOG_FILE);
+
+        let mut fields = Map::new();
+        event.record(&mut JsonFields(&mut fields));
+        let metadata = event.metadata();
+        let line = json!({
+            "timestamp": Utc::now().to_rfc3339(),
+            "level": metadata.level().as_str(),
+            "target": metadata.target(),
+            "message": fields.remove("message").unwrap_or(Value::Null),
+            "fields": fields,
+            "span": span_fields,
+            "spans": names,
+        });
+        // Logging the failure from inside the subscriber would recurse.
+        let _ = self.append(&path, &line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Engine;
+    use chrono::TimeZone;
+    use gb_data::DataManager;
+    use gb_types::{BacktestConfig, Bar, BuyAndHoldStrategy, Resolution, StrategyConfig, Symbol};
+    use rust_decimal::Decimal;
+
+    #[test]
+    fn run_logs_carry_the_run_ids_into_the_data_layer() {
+        let root = tempfile::tempdir().unwrap();
+        let subscriber =
+            tracing_subscriber::registry().with(RunLogLayer::new(Some(root.path().into())));
+        let symbol = Symbol::equity("AAPL");
+        let day = |d| Utc.with_ymd_and_hms(2024, 1, d, 0, 0, 0).unwrap();
+        let bars: Vec<Bar> = (2..=5)
+            .map(|d| {
+                let price = Decimal::from(100 + d);
+                Bar::new(
+                    symbol.clone(),
+                    day(d),
+                    price,
+                    price,
+                    price,
+                    price,
+                    Decimal::from(1_000_000),
+                    Resolution::Day,
+                )
+            })
+            .collect();
+        let strategy_config =
+            StrategyConfig::new("buy_and_hold".to_string(), "Buy and Hold".to_string());
+        let config = BacktestConfig::new("telemetry".to_string(), strategy_config)
+            .with_symbols(vec![symbol.clone()])
+            .with_date_range(day(2), day(5))
+            .with_resolution(Resolution::Day);
+        let backtest_id = config.id;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        tracing::subscriber::with_default(subscriber, || {
+            runtime.block_on(async {
+                let mut data_manager = DataManager::new_ephemeral("gb-engine-telemetry")
+                    .await
+                    .unwrap();
+                data_manager
+                    .storage
+                    .save_bars(&symbol, &bars, Resolution::Day)
+                    .await
+                    .unwrap();
+                let strategy = Box::new(BuyAndHoldStrategy::new());
+                let mut engine = Engine::new(config, &mut data_manager, strategy)
+                    .await
+                    .unwrap();
+                engine.run().await.unwrap();
+            });
+            tracing::info!("outside any run");
+        });
+
+        let log =
+            std::fs::read_to_string(root.path().join(backtest_id.to_string()).join(RUN_LOG_FILE))
+                .unwrap();
+        let lines: Vec<Value> = log
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let data_event = lines
+            .iter()
+            .find(|line| line["target"].as_str().unwrap().starts_with("gb_data"))
+            .expect("the data layer logged during the run");
+        assert_eq!(data_event["span"]["backtest_id"], backtest_id.to_string());
+        assert_eq!(data_event["span"]["strategy_id"], "buy_and_hold");
+        assert_eq!(data_event["span"]["symbol"], "NASDAQ:AAPL");
+        assert_eq!(data_event["spans"], json!(["backtest", "load_data"]));
+        assert!(lines
+            .iter()
+            .all(|line| line["message"] != "outside any run"));
+        assert_eq!(std::fs::read_dir(root.path()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,196 @@
+use chrono::{DateTime, TimeZone, Utc};
+use gb_data::DataManager;
+use gb_engine::Engine;
+use gb_types::{
+    BacktestConfig, Bar, MarketEvent, OptionKind, OptionOrder, OptionSettlement, OrderEvent,
+    Resolution, Side, Strategy, StrategyAction, StrategyConfig, StrategyContext, StrategyMetrics,
+    Symbol,
+};
+use rust_decimal::Decimal;
+
+/// Buys one call on the first bar it sees and then holds.
+struct BuyOneCall {
+    config: StrategyConfig,
+    order: OptionOrder,
+    traded: bool,
+}
+
+impl Strategy for BuyOneCall {
+    fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+        self.config = config.clone();
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        _event: &MarketEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        if self.traded {
+            return Ok(vec![]);
+        }
+        self.traded = true;
+        Ok(vec![StrategyAction::TradeOption(self.order.clone())])
+    }
+
+    fn on_order_event(
+        &mut self,
+        _event: &OrderEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_day_end(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+fn day(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()
+}
+
+fn bar(symbol: &Symbol, day_of_month: u32, close: i64) -> Bar {
+    let close = Decimal::from(close);
+    Bar::new(
+        symbol.clone(),
+        day(day_of_month),
+        close,
+        close,
+        close,
+        close,
+        Decimal::from(1_000_000),
+        Resolution::Day,
+    )
+}
+
+/// Buys one 100-strike call expiring on the 4th while the underlying rallies
+/// from 100 to 110, and returns the finished run.
+async fn run_long_call(
+    name: &str,
+    settlement: OptionSettlement,
+) -> (gb_types::BacktestResult, Symbol) {
+    let symbol = Symbol::equity("AAPL");
+    let mut data_manager = DataManager::new_ephemeral(name).await.unwrap();
+    let bars: Vec<Bar> = [100, 102, 105, 110, 110, 110]
+        .into_iter()
+        .enumerate()
+        .map(|(i, close)| bar(&symbol, i as u32 + 1, close))
+        .collect();
+    data_manager
+        .storage
+        .save_bars(&symbol, &bars, Resolution::Day)
+        .await
+        .unwrap();
+
+    let strategy_config = StrategyConfig::new("long_call".to_string(), "Long Call".to_string());
+    let mut config = BacktestConfig::new(name.to_string(), strategy_config.clone())
+        .with_symbols(vec![symbol.clone()])
+        .with_date_range(day(1), day(6))
+        .with_resolution(Resolution::Day);
+    config.initial_capital = Decimal::from(100_000);
+    config.execution_settings.option_settlement = settlement;
+
+    let mut order = OptionOrder::new(
+        symbol.clone(),
+        OptionKind::Call,
+        Side::Buy,
+        Decimal::ONE,
+        Decimal::from(100),
+        day(4),
+        Decimal::new(30, 2),
+    );
+    order.commission_per_contract = Decimal::new(65, 2);
+    let strategy = BuyOneCall {
+        config: strategy_config,
+        order,
+        traded: false,
+    };
+
+    let mut engine = Engine::new(config, &mut data_manager, Box::new(strategy))
+        .await
+        .unwrap();
+    (engine.run().await.unwrap(), symbol)
+}
+
+fn premium_paid(result: &gb_types::BacktestResult) -> Decimal {
+    let purchase = result
+        .trade_log
+        .iter()
+        .find(|trade| trade.tags.iter().any(|tag| tag == "option") && trade.side == Side::Buy)
+        .expect("the call purchase is logged");
+    assert_eq!(purchase.commission, Decimal::new(65, 2));
+    assert!(purchase.entry_price > Decimal::ZERO);
+    purchase.entry_price
+}
+
+#[tokio::test]
+async fn long_call_expiring_in_the_money_is_exercised_into_shares() {
+    let (result, symbol) =
+        run_long_call("gb-engine-option-exercise", OptionSettlement::Physical).await;
+    let premium = premium_paid(&result);
+    let portfolio = result.final_portfolio.as_ref().unwrap();
+
+    let shares = portfolio
+        .get_position(&symbol)
+        .expect("exercise delivers shares");
+    assert_eq!(shares.quantity, Decimal::from(100));
+    assert_eq!(shares.average_price, Decimal::from(100));
+    assert_eq!(portfolio.positions.len(), 1);
+    assert!(portfolio.option_greeks.is_empty());
+
+    let commission = Decimal::new(65, 2);
+    let expected_pnl = Decimal::from(1_000) - premium * Decimal::from(100) - commission;
+    assert_eq!(
+        portfolio.cash,
+        Decimal::from(100_000) - premium * Decimal::from(100) - commission - Decimal::from(10_000)
+    );
+    assert_eq!(
+        portfolio.total_equity - portfolio.initial_capital,
+        expected_pnl
+    );
+
+    let exercise = result
+        .trade_log
+        .iter()
+        .find(|trade| trade.tags.iter().any(|tag| tag == "option_exercise"))
+        .expect("the share delivery is logged");
+    assert_eq!(exercise.symbol, symbol);
+    assert_eq!(exercise.side, Side::Buy);
+    assert_eq!(exercise.entry_time, day(4));
+
+    let events = result.metadata["option_events"].as_array().unwrap();
+    assert!(events
+        .iter()
+        .any(|event| event["event"] == "option_exercised" && event["shares_delivered"] == 100.0));
+}
+
+#[tokio::test]
+async fn cash_settled_call_pays_its_intrinsic_value() {
+    let (result, symbol) =
+        run_long_call("gb-engine-option-cash-settlement", OptionSettlement::Cash).await;
+    let premium = premium_paid(&result);
+    let portfolio = result.final_portfolio.as_ref().unwrap();
+
+    assert!(portfolio.get_position(&symbol).is_none());
+    assert!(portfolio.positions.is_empty());
+    let expected_pnl = Decimal::from(1_000) - premium * Decimal::from(100) - Decimal::new(65, 2);
+    assert_eq!(portfolio.cash - portfolio.initial_capital, expected_pnl);
+    assert_eq!(
+        portfolio.total_realized_pnl,
+        expected_pnl + Decimal::new(65, 2)
+    );
+    portfolio.check_invariants().unwrap();
+}
@@ -0,0 +1,183 @@
+//! Property tests for backtest order flow: random daily market orders against
+//! random prices and volumes fill each order at most once over, report
+//! partial fills with the quantity still open, and keep equity balanced.
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, TimeZone, Utc};
+use gb_data::DataManager;
+use gb_engine::Engine;
+use gb_types::{
+    BacktestConfig, Bar, MarketEvent, Order, OrderEvent, OrderId, Resolution, Side, Strategy,
+    StrategyAction, StrategyConfig, StrategyContext, StrategyMetrics, Symbol,
+};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+/// Places the scripted market orders at the end of their day.
+struct ScriptedStrategy {
+    config: StrategyConfig,
+    symbol: Symbol,
+    orders: Vec<(u32, Side, i64)>,
+}
+
+impl Strategy for ScriptedStrategy {
+    fn initialize(&mut self, _config: &StrategyConfig) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        _event: &MarketEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_order_event(
+        &mut self,
+        _event: &OrderEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_day_end(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(self
+            .orders
+            .iter()
+            .filter(|(day_of_month, _, _)| day(*day_of_month) == context.current_time)
+            .map(|(_, side, quantity)| {
+                StrategyAction::PlaceOrder(Order::market_order(
+                    self.symbol.clone(),
+                    *side,
+                    Decimal::from(*quantity),
+                    "scripted".to_string(),
+                ))
+            })
+            .collect())
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+fn day(day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap()
+}
+
+fn bar(symbol: &Symbol, day_of_month: u32, price: i64, volume: i64) -> Bar {
+    let price = Decimal::from(price);
+    Bar::new(
+        symbol.clone(),
+        day(day_of_month),
+        price,
+        price,
+        price,
+        price,
+        Decimal::from(volume),
+        Resolution::Day,
+    )
+}
+
+/// Ten daily (price, volume) bars and the (day, side, quantity) orders
+/// placed on them.
+type OrderFlow = (Vec<(i64, i64)>, Vec<(u32, Side, i64)>);
+
+fn order_flow() -> impl proptest::strategy::Strategy<Value = OrderFlow> {
+    let side = prop_oneof![Just(Side::Buy), Just(Side::Sell)];
+    (
+        prop::collection::vec((1..=300i64, 0..=20_000i64), 10),
+        prop::collection::vec((1..=10u32, side, 1..=5_000i64), 0..20),
+    )
+}
+
+async fn run_order_flow(bars: &[(i64, i64)], orders: &[(u32, Side, i64)]) -> Result<(), String> {
+    let symbol = Symbol::equity("AAPL");
+    let data_dir = tempfile::tempdir().map_err(|e| e.to_string())?;
+    let mut data_manager = DataManager::new_with_data_dir(data_dir.path())
+        .await
+        .map_err(|e| e.to_string())?;
+    let bars: Vec<Bar> = bars
+        .iter()
+        .zip(1..)
+        .map(|((price, volume), day_of_month)| bar(&symbol, day_of_month, *price, *volume))
+        .collect();
+    data_manager
+        .storage
+        .save_bars(&symbol, &bars, Resolution::Day)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let strategy_config = StrategyConfig::new("scripted".to_string(), "Scripted".to_string());
+    let mut config = BacktestConfig::new("order-flow".to_string(), strategy_config.clone())
+        .with_symbols(vec![symbol.clone()])
+        .with_date_range(day(1), day(10))
+        .with_resolution(Resolution::Day);
+    config.initial_capital = Decimal::from(100_000);
+    let strategy = ScriptedStrategy {
+        config: strategy_config,
+        symbol,
+        orders: orders.to_vec(),
+    };
+    let mut engine = Engine::new(config, &mut data_manager, Box::new(strategy))
+        .await
+        .map_err(|e| e.to_string())?;
+    let result = engine.run().await.map_err(|e| e.to_string())?;
+
+    let mut filled: HashMap<OrderId, (Decimal, Decimal)> = HashMap::new();
+    for event in &result.order_events {
+        if let OrderEvent::OrderSubmitted(order) = event {
+            filled.insert(order.id, (order.quantity, Decimal::ZERO));
+        }
+        let Some(fill) = event.fill() else {
+            continue;
+        };
+        let (quantity, total) = filled
+            .get_mut(&event.order_id())
+            .ok_or_else(|| format!("fill for an order never submitted: {event:?}"))?;
+        *total += fill.quantity;
+        let consistent = match event {
+            OrderEvent::OrderPartiallyFilled {
+                remaining_quantity, ..
+            } => *total + *remaining_quantity == *quantity && *total < *quantity,
+            _ => *total == *quantity,
+        };
+        if !consistent {
+            return Err(format!("{event:?} after {total} of {quantity} filled"));
+        }
+    }
+
+    let portfolio = result.final_portfolio.ok_or("no final portfolio")?;
+    let held: Decimal = portfolio.positions.values().map(|p| p.market_value).sum();
+    if portfolio.total_equity != portfolio.cash + held {
+        return Err(format!("equity does not balance: {portfolio:?}"));
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn random_order_flow_fills_each_order_at_most_once_over(
+        (bars, orders) in order_flow(),
+    ) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        if let Err(message) = runtime.block_on(run_order_flow(&bars, &orders)) {
+            return Err(TestCaseError::fail(message));
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Compares the vectorized quick backtest with the event engine on a
+//! monthly-rebalanced two-asset portfolio.
+
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc, Weekday};
+use gb_engine::parity::{backtest_trace, ParitySettings};
+use gb_engine::vectorized::{quick_backtest, BarMatrix, Weights};
+use gb_types::{
+    Bar, MarketEvent, Order, OrderEvent, Resolution, Side, Strategy, StrategyAction,
+    StrategyConfig, StrategyContext, StrategyMetrics, Symbol,
+};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+const WEIGHT: f64 = 0.45;
+
+fn symbols() -> [Symbol; 2] {
+    [Symbol::equity("AAA"), Symbol::equity("BBB")]
+}
+
+/// Holds `WEIGHT` of equity in each symbol, rebalanced at the first close
+/// of every month.
+struct MonthlyRebalance {
+    config: StrategyConfig,
+    month: Option<u32>,
+}
+
+impl Strategy for MonthlyRebalance {
+    fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+        self.config = config.clone();
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        _event: &MarketEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_order_event(
+        &mut self,
+        _event: &OrderEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_day_end(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        let month = context.current_time.month();
+        if self.month == Some(month) {
+            return Ok(vec![]);
+        }
+        self.month = Some(month);
+
+        let equity = context.get_portfolio_value();
+        let mut actions = Vec::new();
+        for symbol in symbols() {
+            let price = context.get_current_price(&symbol).ok_or("no price")?;
+            let target = (equity * Decimal::from_f64(WEIGHT).unwrap() / price).floor();
+            let held = context
+                .get_position(&symbol)
+                .map_or(Decimal::ZERO, |position| position.quantity);
+            let change = target - held;
+            if change.is_zero() {
+                continue;
+            }
+            let side = if change > Decimal::ZERO {
+                Side::Buy
+            } else {
+                Side::Sell
+            };
+            actions.push(StrategyAction::PlaceOrder(Order::market_order(
+                symbol,
+                side,
+                change.abs(),
+                self.config.strategy_id.clone(),
+            )));
+        }
+        Ok(actions)
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+/// Six months of weekday bars for both symbols: deterministic swings of up
+/// to 2% a day, opening within 0.2% of the previous close.
+fn bars() -> Vec<Bar> {
+    let mut bars = Vec::new();
+    for (index, symbol) in symbols().into_iter().enumerate() {
+        let mut close = 100.0 + 20.0 * index as f64;
+        let mut state = 7u64 + index as u64;
+        let mut day = Utc.with_ymd_and_hms(2024, 1, 2, 0, 0, 0).unwrap();
+        while day < Utc.with_ymd_and_hms(2024, 7, 1, 0, 0, 0).unwrap() {
+            if !matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                let mut draw = || {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    (state >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+                };
+                let open = close * (1.0 + 0.002 * draw());
+                close = open * (1.0 + 0.02 * draw());
+                let price = |value: f64| Decimal::from_f64(value).unwrap().round_dp(4);
+                bars.push(Bar::new(
+                    symbol.clone(),
+                    day,
+                    price(open),
+                    price(open.max(close) * 1.01),
+                    price(open.min(close) * 0.99),
+                    price(close),
+                    Decimal::from(1_000_000),
+                    Resolution::Day,
+                ));
+            }
+            day += Duration::days(1);
+        }
+    }
+    bars
+}
+
+#[tokio::test]
+async fn quick_backtest_tracks_the_event_engine_within_fill_timing_and_rounding() {
+    let bars = bars();
+    let settings = ParitySettings {
+        commission_per_share: Decimal::ZERO,
+        slippage_bps: 5,
+        ..Default::default()
+    };
+    let strategy = MonthlyRebalance {
+        config: StrategyConfig::new("monthly".into(), "Monthly rebalance".into()),
+        month: None,
+    };
+    let mut strategy_config = strategy.config.clone();
+    strategy_config.symbols = symbols().to_vec();
+    let event = backtest_trace(Box::new(strategy), &strategy_config, &bars, &settings)
+        .await
+        .unwrap();
+
+    let prices = BarMatrix::from_bars(&bars);
+    let mut month = None;
+    let quick = quick_backtest(
+        &prices,
+        |timestamp: DateTime<Utc>| {
+            (month.replace(timestamp.month()) != Some(timestamp.month())).then(|| {
+                symbols()
+                    .into_iter()
+                    .map(|symbol| (symbol, WEIGHT))
+                    .collect::<Weights>()
+            })
+        },
+        5.0,
+    )
+    .unwrap();
+
+    // Six rebalances: the first buys 90% of the portfolio.
+    let rebalances = quick
+        .equity_curve
+        .iter()
+        .filter(|point| point.turnover > 0.0);
+    assert_eq!(rebalances.count(), 6);
+    assert!(quick.turnover > 0.9);
+    assert!(quick.cost_drag > 0.0);
+
+    // The event engine fills at the next open, up to 0.2% away, and in whole
+    // shares of about $100; both cost well under 0.5% of equity.
+    assert!(event.fills.len() >= 6);
+    let capital = settings.initial_capital.to_f64().unwrap();
+    assert_eq!(event.path.len(), quick.equity_curve.len());
+    for (event, quick) in event.path.iter().zip(&quick.equity_curve) {
+        assert_eq!(event.date, quick.timestamp.date_naive());
+        let event = event.equity.to_f64().unwrap() / capital;
+        assert!(
+            (event - quick.equity).abs() < 0.005,
+            "{}: event {event} vs quick {}",
+            quick.timestamp,
+            quick.equity
+        );
+    }
+    let total_return = quick.metrics.total_return.to_f64().unwrap();
+    assert!((total_return - (quick.equity_curve.last().unwrap().equity - 1.0)).abs() < 1e-9);
+}
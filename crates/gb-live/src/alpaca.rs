@@ -0,0 +1,2542 @@
+//! Alpaca brokerage adapter.
+//!
+//! [`AlpacaBroker`] implements [`Broker`] against Alpaca's trading REST API
+//! (orders, positions, account) and its websocket streams: `trade_updates`
+//! frames become order-status changes and [`Fill`]s, and the market-data feed
+//! becomes [`MarketEvent`]s.  Both are delivered through the registered
+//! [`BrokerCallback`].  Paper and live accounts expose the same API and only
+//! differ in their endpoints (see [`AlpacaEnvironment`]).
+//!
+//! HTTP calls go through the [`AlpacaTransport`] trait so that request and
+//! response mapping can be exercised against recorded fixtures without
+//! network access.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures_util::{SinkExt, StreamExt};
+use gb_data::symbols::{ProviderSymbols, SymbolMapper};
+use gb_types::errors::{DataError, GbError};
+use gb_types::market::{AssetClass, Bar, MarketEvent, Resolution, Symbol, Tick, TickType};
+use gb_types::orders::{Fill, Order, OrderId, OrderStatus, OrderType, Side, TimeInForce};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::broker::{
+    backoff_delay, read_lock, write_lock, AccountBalance, Broker, BrokerCallback, BrokerError,
+    BrokerPosition, BrokerResult, ClientOrderIds, ConnectionStatus, HttpMethod,
+};
+
+const PAPER_TRADING_URL: &str = "https://paper-api.alpaca.markets";
+const LIVE_TRADING_URL: &str = "https://api.alpaca.markets";
+const PAPER_TRADING_STREAM_URL: &str = "wss://paper-api.alpaca.markets/stream";
+const LIVE_TRADING_STREAM_URL: &str = "wss://api.alpaca.markets/stream";
+const DATA_STREAM_URL: &str = "wss://stream.data.alpaca.markets";
+
+/// Back-off used for HTTP 429 responses that carry no rate-limit headers.
+const DEFAULT_RATE_LIMIT_BACKOFF_MS: u64 = 1_000;
+
+/// Which Alpaca account the broker trades against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlpacaEnvironment {
+    Paper,
+    Live,
+}
+
+/// Endpoints used by [`AlpacaBroker`].
+///
+/// Normally derived from the [`AlpacaEnvironment`]; overriding them is useful
+/// for proxies and for tests that run against local fixture servers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlpacaEndpoints {
+    /// Trading REST API base url (no trailing slash).
+    pub trading_rest: String,
+    /// Websocket url of the `trade_updates` stream.
+    pub trading_stream: String,
+    /// Websocket url of the equity market-data stream.
+    pub stock_data_stream: String,
+    /// Websocket url of the crypto market-data stream.
+    pub crypto_data_stream: String,
+}
+
+impl AlpacaEndpoints {
+    /// Default endpoints for an environment and equity data feed (`iex` or
+    /// `sip`).
+    pub fn for_environment(environment: AlpacaEnvironment, data_feed: &str) -> Self {
+        let (trading_rest, trading_stream) = match environment {
+            AlpacaEnvironment::Paper => (PAPER_TRADING_URL, PAPER_TRADING_STREAM_URL),
+            AlpacaEnvironment::Live => (LIVE_TRADING_URL, LIVE_TRADING_STREAM_URL),
+        };
+        Self {
+            trading_rest: trading_rest.to_string(),
+            trading_stream: trading_stream.to_string(),
+            stock_data_stream: format!("{}/v2/{}", DATA_STREAM_URL, data_feed),
+            crypto_data_stream: format!("{}/v1beta3/crypto/us", DATA_STREAM_URL),
+        }
+    }
+}
+
+/// Configuration for [`AlpacaBroker`].
+#[derive(Clone, PartialEq)]
+pub struct AlpacaConfig {
+    /// API key id (`APCA-API-KEY-ID`).
+    pub api_key_id: String,
+    /// API secret key (`APCA-API-SECRET-KEY`).
+    pub api_secret_key: String,
+    /// Paper or live account.
+    pub environment: AlpacaEnvironment,
+    /// REST and websocket endpoints.
+    pub endpoints: AlpacaEndpoints,
+    /// Whether `connect` opens the `trade_updates` stream for fills.
+    pub stream_trade_updates: bool,
+    /// How many times a request answered with HTTP 429 is retried before
+    /// surfacing [`BrokerError::RateLimited`].
+    pub max_rate_limit_retries: u32,
+    /// Per-request HTTP timeout in milliseconds.
+    pub request_timeout_ms: u64,
+    /// First websocket reconnect delay; doubles on each failed attempt.
+    pub reconnect_initial_delay_ms: u64,
+    /// Upper bound on the websocket reconnect delay.
+    pub reconnect_max_delay_ms: u64,
+    /// How canonical symbols are spelled as Alpaca tickers.
+    pub symbols: ProviderSymbols,
+}
+
+impl AlpacaConfig {
+    pub fn new(
+        api_key_id: impl Into<String>,
+        api_secret_key: impl Into<String>,
+        environment: AlpacaEnvironment,
+    ) -> Self {
+        Self {
+            api_key_id: api_key_id.into(),
+            api_secret_key: api_secret_key.into(),
+            environment,
+            endpoints: AlpacaEndpoints::for_environment(environment, "iex"),
+            stream_trade_updates: true,
+            max_rate_limit_retries: 3,
+            request_timeout_ms: 10_000,
+            reconnect_initial_delay_ms: 500,
+            reconnect_max_delay_ms: 30_000,
+            symbols: SymbolMapper::builtin().provider("alpaca"),
+        }
+    }
+
+    /// Build a config from Alpaca's standard environment variables:
+    /// `APCA_API_KEY_ID`, `APCA_API_SECRET_KEY`, plus the optional
+    /// `APCA_ENVIRONMENT` (`paper` (default) or `live`) and `APCA_DATA_FEED`.
+    pub fn from_env() -> BrokerResult<Self> {
+        let key = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| BrokerError::AuthenticationFailed {
+                    message: format!("{} is not set", name),
+                })
+        };
+        let api_key_id = key("APCA_API_KEY_ID")?;
+        let api_secret_key = key("APCA_API_SECRET_KEY")?;
+
+        let environment = match std::env::var("APCA_ENVIRONMENT")
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "paper" => AlpacaEnvironment::Paper,
+            "live" => AlpacaEnvironment::Live,
+            other => {
+                return Err(BrokerError::Internal {
+                    message: format!("unknown APCA_ENVIRONMENT '{}'", other),
+                })
+            }
+        };
+
+        let mut config = Self::new(api_key_id, api_secret_key, environment);
+        if let Ok(feed) = std::env::var("APCA_DATA_FEED") {
+            if !feed.trim().is_empty() {
+                config = config.with_data_feed(feed.trim());
+            }
+        }
+        Ok(config)
+    }
+
+    /// Use a different equity data feed (`iex` or `sip`).
+    pub fn with_data_feed(mut self, data_feed: &str) -> Self {
+        self.endpoints.stock_data_stream =
+            AlpacaEndpoints::for_environment(self.environment, data_feed).stock_data_stream;
+        self
+    }
+
+    /// Translate symbols through `symbols`, e.g. one spelling share classes
+    /// `BRK/B`.
+    pub fn with_symbols(mut self, symbols: ProviderSymbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Delay before the given (zero-based) reconnect attempt.
+    fn reconnect_delay(&self, attempt: u32) -> Duration {
+        backoff_delay(
+            self.reconnect_initial_delay_ms,
+            self.reconnect_max_delay_ms,
+            attempt,
+        )
+    }
+}
+
+impl fmt::Debug for AlpacaConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AlpacaConfig")
+            .field("api_key_id", &self.api_key_id)
+            .field("api_secret_key", &"<redacted>")
+            .field("environment", &self.environment)
+            .field("endpoints", &self.endpoints)
+            .field("stream_trade_updates", &self.stream_trade_updates)
+            .field("max_rate_limit_retries", &self.max_rate_limit_retries)
+            .field("request_timeout_ms", &self.request_timeout_ms)
+            .field(
+                "reconnect_initial_delay_ms",
+                &self.reconnect_initial_delay_ms,
+            )
+            .field("reconnect_max_delay_ms", &self.reconnect_max_delay_ms)
+            .field("symbols", &self.symbols)
+            .finish()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HTTP transport
+// ---------------------------------------------------------------------------
+
+/// A request against the trading REST API. `path` is relative to
+/// [`AlpacaEndpoints::trading_rest`] and includes any query string.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlpacaRequest {
+    pub method: HttpMethod,
+    pub path: String,
+    pub body: Option<Value>,
+}
+
+/// Raw response from the trading REST API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlpacaResponse {
+    pub status: u16,
+    pub body: String,
+    /// Suggested wait before retrying, derived from `Retry-After` or
+    /// `X-RateLimit-Reset`.
+    pub retry_after_ms: Option<u64>,
+}
+
+impl AlpacaResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    fn json(&self) -> BrokerResult<Value> {
+        serde_json::from_str(&self.body)
+            .map_err(|e| payload_error(format!("invalid JSON response: {}", e)))
+    }
+
+    /// The `message` field of an Alpaca error body, or the raw body.
+    fn error_message(&self) -> String {
+        serde_json::from_str::<Value>(&self.body)
+            .ok()
+            .and_then(|value| {
+                value
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .map(str::to_string)
+            })
+            .unwrap_or_else(|| self.body.trim().to_string())
+    }
+}
+
+/// Sends requests to the trading REST API.
+#[async_trait]
+pub trait AlpacaTransport: Send + Sync {
+    async fn send(&self, request: &AlpacaRequest) -> BrokerResult<AlpacaResponse>;
+}
+
+/// [`AlpacaTransport`] backed by `reqwest`.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: reqwest::Client,
+    base_url: String,
+    api_key_id: String,
+    api_secret_key: String,
+}
+
+impl HttpTransport {
+    pub fn new(config: &AlpacaConfig) -> BrokerResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()
+            .map_err(|e| BrokerError::Internal {
+                message: format!("failed to build HTTP client: {}", e),
+            })?;
+        Ok(Self {
+            client,
+            base_url: config
+                .endpoints
+                .trading_rest
+                .trim_end_matches('/')
+                .to_string(),
+            api_key_id: config.api_key_id.clone(),
+            api_secret_key: config.api_secret_key.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl AlpacaTransport for HttpTransport {
+    async fn send(&self, request: &AlpacaRequest) -> BrokerResult<AlpacaResponse> {
+        let method = match request.method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+        };
+        let mut builder = self
+            .client
+            .request(method, format!("{}{}", self.base_url, request.path))
+            .header("APCA-API-KEY-ID", &self.api_key_id)
+            .header("APCA-API-SECRET-KEY", &self.api_secret_key);
+        if let Some(body) = &request.body {
+            builder = builder.json(body);
+        }
+
+        let response = builder.send().await.map_err(|e| BrokerError::Internal {
+            message: format!("Alpaca request failed: {}", e),
+        })?;
+        let status = response.status().as_u16();
+        let retry_after_ms = retry_after_from_headers(response.headers());
+        let body = response.text().await.map_err(|e| BrokerError::Internal {
+            message: format!("failed to read Alpaca response: {}", e),
+        })?;
+
+        Ok(AlpacaResponse {
+            status,
+            body,
+            retry_after_ms,
+        })
+    }
+}
+
+fn retry_after_from_headers(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<i64>().ok())
+    };
+    if let Some(seconds) = header("retry-after") {
+        return Some(seconds.max(0) as u64 * 1_000);
+    }
+    header("x-ratelimit-reset").map(|reset| (reset - Utc::now().timestamp()).max(0) as u64 * 1_000)
+}
+
+/// Map a non-2xx trading API response into a typed [`BrokerError`].
+///
+/// `order_id` is set for order-scoped calls so that 403/404 responses can be
+/// reported as rejections / unknown orders rather than auth or internal
+/// failures (Alpaca answers 403 for insufficient buying power).
+pub fn map_error_response(response: &AlpacaResponse, order_id: Option<OrderId>) -> BrokerError {
+    let message = response.error_message();
+    match (response.status, order_id) {
+        (401, _) => BrokerError::AuthenticationFailed { message },
+        (403, Some(_)) | (422, _) => BrokerError::OrderRejected { reason: message },
+        (403, None) => BrokerError::AuthenticationFailed { message },
+        (404, Some(order_id)) => BrokerError::OrderNotFound {
+            order_id: order_id.to_string(),
+        },
+        (429, _) => BrokerError::RateLimited {
+            retry_after_ms: response
+                .retry_after_ms
+                .unwrap_or(DEFAULT_RATE_LIMIT_BACKOFF_MS),
+        },
+        (status, _) => BrokerError::Internal {
+            message: format!("Alpaca HTTP {}: {}", status, message),
+        },
+    }
+}
+
+/// Whether Alpaca refused an order because its `client_order_id` is already
+/// in use, i.e. an earlier submission of the same order was accepted.
+fn is_duplicate_client_id(error: &BrokerError) -> bool {
+    matches!(
+        error,
+        BrokerError::OrderRejected { reason } if reason.contains("client_order_id must be unique")
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Payload mapping
+// ---------------------------------------------------------------------------
+
+fn payload_error(message: impl Into<String>) -> BrokerError {
+    BrokerError::Internal {
+        message: format!("malformed Alpaca payload: {}", message.into()),
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> BrokerResult<&'a str> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| payload_error(format!("missing string field '{}'", key)))
+}
+
+/// Alpaca encodes REST decimals as strings and stream decimals as numbers.
+fn json_decimal(value: &Value) -> Option<Decimal> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        _ => return None,
+    };
+    text.parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(&text))
+        .ok()
+}
+
+fn optional_decimal_field(value: &Value, key: &str) -> BrokerResult<Option<Decimal>> {
+    match value.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(raw) => json_decimal(raw)
+            .map(Some)
+            .ok_or_else(|| payload_error(format!("field '{}' is not a decimal", key))),
+    }
+}
+
+fn decimal_field(value: &Value, key: &str) -> BrokerResult<Decimal> {
+    optional_decimal_field(value, key)?
+        .ok_or_else(|| payload_error(format!("missing decimal field '{}'", key)))
+}
+
+fn timestamp_field(value: &Value, key: &str) -> BrokerResult<Option<DateTime<Utc>>> {
+    match value.get(key).and_then(Value::as_str) {
+        None => Ok(None),
+        Some(raw) => DateTime::parse_from_rfc3339(raw)
+            .map(|ts| Some(ts.with_timezone(&Utc)))
+            .map_err(|e| payload_error(format!("field '{}' is not RFC 3339: {}", key, e))),
+    }
+}
+
+fn side_code(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "buy",
+        Side::Sell => "sell",
+    }
+}
+
+fn parse_side(code: &str) -> BrokerResult<Side> {
+    match code {
+        "buy" => Ok(Side::Buy),
+        "sell" => Ok(Side::Sell),
+        other => Err(payload_error(format!("unknown side '{}'", other))),
+    }
+}
+
+fn time_in_force_code(time_in_force: TimeInForce) -> &'static str {
+    match time_in_force {
+        TimeInForce::Day => "day",
+        TimeInForce::GTC => "gtc",
+        TimeInForce::IOC => "ioc",
+        TimeInForce::FOK => "fok",
+    }
+}
+
+fn parse_time_in_force(code: &str) -> BrokerResult<TimeInForce> {
+    match code {
+        // Opening / closing auction orders live for a single session.
+        "day" | "opg" | "cls" => Ok(TimeInForce::Day),
+        "gtc" => Ok(TimeInForce::GTC),
+        "ioc" => Ok(TimeInForce::IOC),
+        "fok" => Ok(TimeInForce::FOK),
+        other => Err(payload_error(format!("unknown time_in_force '{}'", other))),
+    }
+}
+
+fn parse_order_status(code: &str) -> BrokerResult<OrderStatus> {
+    match code {
+        "new"
+        | "accepted"
+        | "pending_new"
+        | "accepted_for_bidding"
+        | "pending_cancel"
+        | "pending_replace"
+        | "stopped"
+        | "calculated"
+        | "suspended"
+        | "held" => Ok(OrderStatus::Submitted),
+        "partially_filled" => Ok(OrderStatus::PartiallyFilled),
+        "filled" => Ok(OrderStatus::Filled),
+        "canceled" | "replaced" => Ok(OrderStatus::Canceled),
+        "expired" | "done_for_day" => Ok(OrderStatus::Expired),
+        "rejected" => Ok(OrderStatus::Rejected),
+        other => Err(payload_error(format!("unknown order status '{}'", other))),
+    }
+}
+
+/// Alpaca ticker for a symbol under `symbols`. The built-in table keeps
+/// equities' bare ticker and spells crypto pairs `BASE/QUOTE` (so `BTC-USD`
+/// becomes `BTC/USD`).
+fn to_alpaca_symbol(symbols: &ProviderSymbols, symbol: &Symbol) -> BrokerResult<String> {
+    symbols.ticker(symbol).ok_or_else(|| {
+        GbError::from(DataError::UnmappedSymbol {
+            provider: "alpaca".to_string(),
+            symbol: symbol.to_string(),
+        })
+        .into()
+    })
+}
+
+/// Inverse of [`to_alpaca_symbol`] for the built-in table; other spellings
+/// are mapped back in `SharedState::resolve_symbol`. Crypto positions are
+/// reported without a separator (`BTCUSD`), so a known quote currency suffix
+/// is split off.
+fn from_alpaca_symbol(raw: &str, asset_class: AssetClass) -> Symbol {
+    match asset_class {
+        AssetClass::Crypto => {
+            let pair = if raw.contains('/') {
+                raw.replace('/', "-")
+            } else {
+                ["USDT", "USDC", "USD", "BTC"]
+                    .iter()
+                    .find(|quote| raw.len() > quote.len() && raw.ends_with(*quote))
+                    .map(|quote| format!("{}-{}", &raw[..raw.len() - quote.len()], quote))
+                    .unwrap_or_else(|| raw.to_string())
+            };
+            Symbol::crypto(&pair)
+        }
+        _ => Symbol::equity(raw),
+    }
+}
+
+fn asset_class_from_code(code: Option<&str>) -> AssetClass {
+    match code {
+        Some("crypto") => AssetClass::Crypto,
+        _ => AssetClass::Equity,
+    }
+}
+
+/// JSON body for `POST /v2/orders`. The GlowBack order id is sent as the
+/// `client_order_id` so stream updates can be matched without a lookup.
+fn order_request_body(order: &Order, symbols: &ProviderSymbols) -> BrokerResult<Value> {
+    let mut body = json!({
+        "symbol": to_alpaca_symbol(symbols, &order.symbol)?,
+        "qty": order.quantity.normalize().to_string(),
+        "side": side_code(order.side),
+        "time_in_force": time_in_force_code(order.time_in_force),
+        "client_order_id": ClientOrderIds::for_order(order).to_string(),
+    });
+    let (kind, limit_price, stop_price, trail_price) = match &order.order_type {
+        OrderType::Market => ("market", None, None, None),
+        OrderType::Limit { price } => ("limit", Some(*price), None, None),
+        OrderType::Stop { stop_price } => ("stop", None, Some(*stop_price), None),
+        OrderType::StopLimit {
+            stop_price,
+            limit_price,
+        } => ("stop_limit", Some(*limit_price), Some(*stop_price), None),
+        OrderType::TrailingStop { trail_amount } => {
+            ("trailing_stop", None, None, Some(*trail_amount))
+        }
+    };
+    body["type"] = json!(kind);
+    if let Some(price) = limit_price {
+        body["limit_price"] = json!(price.normalize().to_string());
+    }
+    if let Some(price) = stop_price {
+        body["stop_price"] = json!(price.normalize().to_string());
+    }
+    if let Some(price) = trail_price {
+        body["trail_price"] = json!(price.normalize().to_string());
+    }
+    Ok(body)
+}
+
+/// Convert an Alpaca order object into an [`Order`].
+///
+/// Orders that did not originate from GlowBack carry a non-UUID
+/// `client_order_id` and receive a fresh id; the Alpaca id is always kept in
+/// `metadata.alpaca_order_id`.
+fn order_from_alpaca(value: &Value) -> BrokerResult<Order> {
+    let alpaca_id = str_field(value, "id")?;
+    let id = value
+        .get("client_order_id")
+        .and_then(Value::as_str)
+        .and_then(|raw| Uuid::parse_str(raw).ok())
+        .unwrap_or_else(Uuid::new_v4);
+    let asset_class = asset_class_from_code(value.get("asset_class").and_then(Value::as_str));
+    let symbol = from_alpaca_symbol(str_field(value, "symbol")?, asset_class);
+    let side = parse_side(str_field(value, "side")?)?;
+
+    let kind = value
+        .get("type")
+        .or_else(|| value.get("order_type"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| payload_error("missing string field 'type'"))?;
+    let order_type = match kind {
+        "market" => OrderType::Market,
+        "limit" => OrderType::Limit {
+            price: decimal_field(value, "limit_price")?,
+        },
+        "stop" => OrderType::Stop {
+            stop_price: decimal_field(value, "stop_price")?,
+        },
+        "stop_limit" => OrderType::StopLimit {
+            stop_price: decimal_field(value, "stop_price")?,
+            limit_price: decimal_field(value, "limit_price")?,
+        },
+        "trailing_stop" => OrderType::TrailingStop {
+            trail_amount: decimal_field(value, "trail_price")?,
+        },
+        other => return Err(payload_error(format!("unsupported order type '{}'", other))),
+    };
+
+    let quantity = optional_decimal_field(value, "qty")?.unwrap_or(Decimal::ZERO);
+    let filled = optional_decimal_field(value, "filled_qty")?.unwrap_or(Decimal::ZERO);
+
+    let mut order = Order::new(symbol, side, quantity, order_type, String::new());
+    order.id = id;
+    order.time_in_force = parse_time_in_force(str_field(value, "time_in_force")?)?;
+    order.status = parse_order_status(str_field(value, "status")?)?;
+    if let Some(submitted_at) = match timestamp_field(value, "submitted_at")? {
+        Some(ts) => Some(ts),
+        None => timestamp_field(value, "created_at")?,
+    } {
+        order.submitted_at = submitted_at;
+    }
+    order.filled_quantity = filled;
+    order.remaining_quantity = (quantity - filled).max(Decimal::ZERO);
+    order.average_fill_price = optional_decimal_field(value, "filled_avg_price")?;
+    order.metadata = json!({ "alpaca_order_id": alpaca_id });
+    Ok(order)
+}
+
+fn account_from_alpaca(value: &Value) -> BrokerResult<AccountBalance> {
+    Ok(AccountBalance {
+        cash: decimal_field(value, "cash")?,
+        buying_power: decimal_field(value, "buying_power")?,
+        equity: decimal_field(value, "equity")?,
+        timestamp: Utc::now(),
+    })
+}
+
+fn position_from_alpaca(value: &Value) -> BrokerResult<BrokerPosition> {
+    let asset_class = asset_class_from_code(value.get("asset_class").and_then(Value::as_str));
+    let mut quantity = decimal_field(value, "qty")?;
+    if value.get("side").and_then(Value::as_str) == Some("short") && quantity > Decimal::ZERO {
+        quantity = -quantity;
+    }
+    Ok(BrokerPosition {
+        symbol: from_alpaca_symbol(str_field(value, "symbol")?, asset_class),
+        quantity,
+        market_value: decimal_field(value, "market_value")?,
+        average_cost: decimal_field(value, "avg_entry_price")?,
+        unrealized_pnl: decimal_field(value, "unrealized_pl")?,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Stream decoding
+// ---------------------------------------------------------------------------
+
+/// A `trade_updates` event for one order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlpacaTradeUpdate {
+    /// Alpaca event name (`new`, `fill`, `partial_fill`, `canceled`, ...).
+    pub event: String,
+    pub order_id: OrderId,
+    pub alpaca_order_id: String,
+    pub symbol: Symbol,
+    pub side: Side,
+    pub status: OrderStatus,
+    /// Quantity of this execution (fill events only).
+    pub fill_quantity: Option<Decimal>,
+    /// Price of this execution (fill events only).
+    pub fill_price: Option<Decimal>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Event decoded from an Alpaca websocket frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlpacaStreamEvent {
+    /// Authentication succeeded.
+    Authorized,
+    /// Authentication (or the subscription entitlement) was refused;
+    /// reconnecting will not help.
+    Unauthorized {
+        message: String,
+    },
+    TradeUpdate(AlpacaTradeUpdate),
+    Market(MarketEvent),
+    /// Recoverable stream error reported by Alpaca.
+    Error {
+        code: Option<i64>,
+        message: String,
+    },
+}
+
+/// Decode a frame from the trading (`trade_updates`) stream.
+pub fn parse_trading_stream_message(text: &str) -> BrokerResult<Vec<AlpacaStreamEvent>> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| payload_error(format!("invalid stream frame: {}", e)))?;
+    let data = value.get("data").unwrap_or(&Value::Null);
+
+    match value.get("stream").and_then(Value::as_str) {
+        Some("authorization") => match data.get("status").and_then(Value::as_str) {
+            Some("authorized") => Ok(vec![AlpacaStreamEvent::Authorized]),
+            status => Ok(vec![AlpacaStreamEvent::Unauthorized {
+                message: format!("trade stream authorization {}", status.unwrap_or("failed")),
+            }]),
+        },
+        Some("trade_updates") => {
+            let event = str_field(data, "event")?;
+            let raw_order = data
+                .get("order")
+                .ok_or_else(|| payload_error("trade update without order"))?;
+            let order = order_from_alpaca(raw_order)?;
+            let (fill_quantity, fill_price) = if matches!(event, "fill" | "partial_fill") {
+                (
+                    optional_decimal_field(data, "qty")?,
+                    optional_decimal_field(data, "price")?,
+                )
+            } else {
+                (None, None)
+            };
+            Ok(vec![AlpacaStreamEvent::TradeUpdate(AlpacaTradeUpdate {
+                event: event.to_string(),
+                order_id: order.id,
+                alpaca_order_id: str_field(raw_order, "id")?.to_string(),
+                symbol: order.symbol,
+                side: order.side,
+                status: order.status,
+                fill_quantity,
+                fill_price,
+                timestamp: timestamp_field(data, "timestamp")?.unwrap_or_else(Utc::now),
+            })])
+        }
+        _ => Ok(Vec::new()),
+    }
+}
+
+/// Decode a frame from a market-data stream. Frames are arrays of messages
+/// tagged by `T` (`q` quote, `t` trade, `b`/`u` minute bar, `d` daily bar).
+pub fn parse_data_stream_message(
+    text: &str,
+    asset_class: AssetClass,
+) -> BrokerResult<Vec<AlpacaStreamEvent>> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| payload_error(format!("invalid stream frame: {}", e)))?;
+    let messages = match value {
+        Value::Array(items) => items,
+        other => vec![other],
+    };
+
+    let mut events = Vec::new();
+    for message in &messages {
+        let symbol =
+            || Ok::<_, BrokerError>(from_alpaca_symbol(str_field(message, "S")?, asset_class));
+        let timestamp =
+            || timestamp_field(message, "t")?.ok_or_else(|| payload_error("missing timestamp 't'"));
+        match str_field(message, "T")? {
+            "success" if message.get("msg").and_then(Value::as_str) == Some("authenticated") => {
+                events.push(AlpacaStreamEvent::Authorized);
+            }
+            "error" => {
+                let code = message.get("code").and_then(Value::as_i64);
+                let text = message
+                    .get("msg")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+                    .to_string();
+                // 402 auth failed, 406 connection limit, 409 insufficient subscription.
+                if matches!(code, Some(402) | Some(406) | Some(409)) {
+                    events.push(AlpacaStreamEvent::Unauthorized { message: text });
+                } else {
+                    events.push(AlpacaStreamEvent::Error {
+                        code,
+                        message: text,
+                    });
+                }
+            }
+            "q" => events.push(AlpacaStreamEvent::Market(MarketEvent::Quote {
+                symbol: symbol()?,
+                timestamp: timestamp()?,
+                bid: decimal_field(message, "bp")?,
+                ask: decimal_field(message, "ap")?,
+                bid_size: decimal_field(message, "bs")?,
+                ask_size: decimal_field(message, "as")?,
+            })),
+            "t" => events.push(AlpacaStreamEvent::Market(MarketEvent::Tick(Tick {
+                symbol: symbol()?,
+                timestamp: timestamp()?,
+                price: decimal_field(message, "p")?,
+                size: decimal_field(message, "s")?,
+                tick_type: TickType::Trade,
+            }))),
+            kind @ ("b" | "u" | "d") => {
+                let resolution = if kind == "d" {
+                    Resolution::Day
+                } else {
+                    Resolution::Minute
+                };
+                events.push(AlpacaStreamEvent::Market(MarketEvent::Bar(Bar::new(
+                    symbol()?,
+                    timestamp()?,
+                    decimal_field(message, "o")?,
+                    decimal_field(message, "h")?,
+                    decimal_field(message, "l")?,
+                    decimal_field(message, "c")?,
+                    decimal_field(message, "v")?,
+                    resolution,
+                ))));
+            }
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+// ---------------------------------------------------------------------------
+// Shared state between the broker and its stream tasks
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct TrackedOrder {
+    alpaca_id: String,
+    symbol: Symbol,
+    strategy_id: String,
+}
+
+#[derive(Debug)]
+struct SharedState {
+    symbols: ProviderSymbols,
+    status: RwLock<ConnectionStatus>,
+    prices: RwLock<HashMap<Symbol, Decimal>>,
+    orders: RwLock<HashMap<OrderId, TrackedOrder>>,
+    client_ids: ClientOrderIds,
+    subscriptions: RwLock<Vec<Symbol>>,
+}
+
+impl SharedState {
+    fn new(symbols: ProviderSymbols) -> Self {
+        Self {
+            symbols,
+            status: RwLock::new(ConnectionStatus::Disconnected),
+            prices: RwLock::new(HashMap::new()),
+            orders: RwLock::new(HashMap::new()),
+            client_ids: ClientOrderIds::default(),
+            subscriptions: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Map a symbol decoded from Alpaca onto the caller's own [`Symbol`]
+    /// (which may carry a different exchange) when the ticker is known from
+    /// a subscription or a submitted order.
+    fn resolve_symbol(&self, mut symbol: Symbol) -> Symbol {
+        symbol.symbol = self
+            .symbols
+            .canonical_ticker(&symbol.symbol, symbol.asset_class);
+        let matches = |known: &Symbol| {
+            known.asset_class == symbol.asset_class && known.symbol == symbol.symbol
+        };
+        if let Some(known) = read_lock(&self.subscriptions).iter().find(|s| matches(s)) {
+            return known.clone();
+        }
+        read_lock(&self.orders)
+            .values()
+            .find(|tracked| matches(&tracked.symbol))
+            .map(|tracked| tracked.symbol.clone())
+            .unwrap_or(symbol)
+    }
+
+    /// Resolve the GlowBack order id and strategy for a trade update,
+    /// preferring the Alpaca order id (which also covers orders whose
+    /// `client_order_id` was not a GlowBack UUID).
+    fn order_context(&self, update: &AlpacaTradeUpdate) -> (OrderId, Symbol, String) {
+        let order_id = self.client_ids.order_id(update.order_id);
+        let orders = read_lock(&self.orders);
+        let tracked = orders
+            .iter()
+            .find(|(_, tracked)| tracked.alpaca_id == update.alpaca_order_id)
+            .or_else(|| orders.get_key_value(&order_id));
+        match tracked {
+            Some((order_id, tracked)) => (
+                *order_id,
+                tracked.symbol.clone(),
+                tracked.strategy_id.clone(),
+            ),
+            None => {
+                drop(orders);
+                (
+                    order_id,
+                    self.resolve_symbol(update.symbol.clone()),
+                    String::new(),
+                )
+            }
+        }
+    }
+
+    fn record_price(&self, event: &MarketEvent) {
+        let price = match event {
+            MarketEvent::Bar(bar) => bar.close,
+            MarketEvent::Tick(tick) => tick.price,
+            MarketEvent::Quote { bid, ask, .. } => (*bid + *ask) / Decimal::from(2),
+        };
+        write_lock(&self.prices).insert(event.symbol().clone(), price);
+    }
+
+    fn subscribed_tickers(&self, kind: StreamKind) -> Vec<String> {
+        read_lock(&self.subscriptions)
+            .iter()
+            .filter(|symbol| StreamKind::for_asset_class(symbol.asset_class) == kind)
+            .filter_map(|symbol| self.symbols.ticker(symbol))
+            .collect()
+    }
+}
+
+fn with_symbol(event: MarketEvent, symbol: Symbol) -> MarketEvent {
+    match event {
+        MarketEvent::Bar(mut bar) => {
+            bar.symbol = symbol;
+            MarketEvent::Bar(bar)
+        }
+        MarketEvent::Tick(mut tick) => {
+            tick.symbol = symbol;
+            MarketEvent::Tick(tick)
+        }
+        MarketEvent::Quote {
+            timestamp,
+            bid,
+            ask,
+            bid_size,
+            ask_size,
+            ..
+        } => MarketEvent::Quote {
+            symbol,
+            timestamp,
+            bid,
+            ask,
+            bid_size,
+            ask_size,
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Websocket stream tasks
+// ---------------------------------------------------------------------------
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum StreamKind {
+    Trading,
+    StockData,
+    CryptoData,
+}
+
+impl StreamKind {
+    fn for_asset_class(asset_class: AssetClass) -> Self {
+        match asset_class {
+            AssetClass::Crypto => StreamKind::CryptoData,
+            _ => StreamKind::StockData,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StreamCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+}
+
+impl StreamCommand {
+    fn to_message(&self) -> String {
+        let (action, tickers) = match self {
+            StreamCommand::Subscribe(tickers) => ("subscribe", tickers),
+            StreamCommand::Unsubscribe(tickers) => ("unsubscribe", tickers),
+        };
+        json!({ "action": action, "quotes": tickers, "bars": tickers }).to_string()
+    }
+}
+
+struct StreamHandle {
+    commands: mpsc::UnboundedSender<StreamCommand>,
+    task: JoinHandle<()>,
+}
+
+enum SessionEnd {
+    Shutdown,
+    Dropped,
+    Unauthorized(String),
+}
+
+struct StreamContext {
+    kind: StreamKind,
+    url: String,
+    config: AlpacaConfig,
+    shared: Arc<SharedState>,
+    callback: Option<Arc<dyn BrokerCallback>>,
+}
+
+impl StreamContext {
+    /// Connect, authenticate, and pump frames until shutdown, reconnecting
+    /// with exponential back-off whenever the socket drops.
+    async fn run(
+        self,
+        mut commands: mpsc::UnboundedReceiver<StreamCommand>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut attempt: u32 = 0;
+        loop {
+            let connection = tokio::select! {
+                result = tokio_tungstenite::connect_async(self.url.as_str()) => result,
+                _ = shutdown.changed() => return,
+            };
+            match connection {
+                Ok((mut socket, _)) => {
+                    match self
+                        .session(&mut socket, &mut commands, &mut shutdown, &mut attempt)
+                        .await
+                    {
+                        SessionEnd::Shutdown => {
+                            let _ = socket.close(None).await;
+                            return;
+                        }
+                        SessionEnd::Unauthorized(message) => {
+                            warn!("Alpaca {:?} stream refused: {}", self.kind, message);
+                            if self.kind == StreamKind::Trading {
+                                self.set_status(ConnectionStatus::Disconnected).await;
+                            }
+                            return;
+                        }
+                        SessionEnd::Dropped => {}
+                    }
+                }
+                Err(err) => warn!("Alpaca {:?} stream connect failed: {}", self.kind, err),
+            }
+
+            if *shutdown.borrow() {
+                return;
+            }
+            self.set_status(ConnectionStatus::Reconnecting).await;
+            let delay = self.config.reconnect_delay(attempt);
+            attempt = attempt.saturating_add(1);
+            info!(
+                "reconnecting Alpaca {:?} stream in {} ms (attempt {})",
+                self.kind,
+                delay.as_millis(),
+                attempt
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = shutdown.changed() => return,
+            }
+        }
+    }
+
+    async fn session(
+        &self,
+        socket: &mut Socket,
+        commands: &mut mpsc::UnboundedReceiver<StreamCommand>,
+        shutdown: &mut watch::Receiver<bool>,
+        attempt: &mut u32,
+    ) -> SessionEnd {
+        if socket
+            .send(Message::Text(self.auth_message().into()))
+            .await
+            .is_err()
+        {
+            return SessionEnd::Dropped;
+        }
+
+        let mut authorized = false;
+        loop {
+            tokio::select! {
+                frame = socket.next() => {
+                    let text = match frame {
+                        Some(Ok(Message::Text(text))) => text.as_str().to_owned(),
+                        Some(Ok(Message::Binary(bytes))) => String::from_utf8_lossy(&bytes).into_owned(),
+                        Some(Ok(Message::Close(_))) | None => return SessionEnd::Dropped,
+                        Some(Ok(_)) => continue,
+                        Some(Err(err)) => {
+                            warn!("Alpaca {:?} stream error: {}", self.kind, err);
+                            return SessionEnd::Dropped;
+                        }
+                    };
+                    let events = match self.parse(&text) {
+                        Ok(events) => events,
+                        Err(err) => {
+                            warn!("ignoring Alpaca {:?} frame: {}", self.kind, err);
+                            continue;
+                        }
+                    };
+                    for event in events {
+                        match event {
+                            AlpacaStreamEvent::Authorized => {
+                                authorized = true;
+                                *attempt = 0;
+                                if let Some(message) = self.post_auth_message() {
+                                    if socket.send(Message::Text(message.into())).await.is_err() {
+                                        return SessionEnd::Dropped;
+                                    }
+                                }
+                                self.set_status(ConnectionStatus::Connected).await;
+                            }
+                            AlpacaStreamEvent::Unauthorized { message } => {
+                                return SessionEnd::Unauthorized(message);
+                            }
+                            AlpacaStreamEvent::Error { code, message } => {
+                                warn!("Alpaca {:?} stream error {:?}: {}", self.kind, code, message);
+                            }
+                            other => self.dispatch(other).await,
+                        }
+                    }
+                }
+                command = commands.recv() => {
+                    let Some(command) = command else {
+                        return SessionEnd::Shutdown;
+                    };
+                    // Before authorization the full subscription set is sent
+                    // by `post_auth_message`, so early commands can be dropped.
+                    if authorized
+                        && socket.send(Message::Text(command.to_message().into())).await.is_err()
+                    {
+                        return SessionEnd::Dropped;
+                    }
+                }
+                _ = shutdown.changed() => return SessionEnd::Shutdown,
+            }
+        }
+    }
+
+    fn parse(&self, text: &str) -> BrokerResult<Vec<AlpacaStreamEvent>> {
+        match self.kind {
+            StreamKind::Trading => parse_trading_stream_message(text),
+            StreamKind::StockData => parse_data_stream_message(text, AssetClass::Equity),
+            StreamKind::CryptoData => parse_data_stream_message(text, AssetClass::Crypto),
+        }
+    }
+
+    fn auth_message(&self) -> String {
+        json!({
+            "action": "auth",
+            "key": self.config.api_key_id,
+            "secret": self.config.api_secret_key,
+        })
+        .to_string()
+    }
+
+    fn post_auth_message(&self) -> Option<String> {
+        match self.kind {
+            StreamKind::Trading => Some(
+                json!({ "action": "listen", "data": { "streams": ["trade_updates"] } }).to_string(),
+            ),
+            kind => {
+                let tickers = self.shared.subscribed_tickers(kind);
+                (!tickers.is_empty()).then(|| StreamCommand::Subscribe(tickers).to_message())
+            }
+        }
+    }
+
+    async fn set_status(&self, status: ConnectionStatus) {
+        let changed = {
+            let mut current = write_lock(&self.shared.status);
+            let changed = *current != status;
+            *current = status;
+            changed
+        };
+        if changed {
+            if let Some(callback) = &self.callback {
+                callback.on_connection_status(status).await;
+            }
+        }
+    }
+
+    async fn dispatch(&self, event: AlpacaStreamEvent) {
+        match event {
+            AlpacaStreamEvent::TradeUpdate(update) => {
+                let (order_id, symbol, strategy_id) = self.shared.order_context(&update);
+                if let Some(callback) = &self.callback {
+                    callback.on_order_status(order_id, update.status).await;
+                }
+                if let (Some(quantity), Some(price)) = (update.fill_quantity, update.fill_price) {
+                    let mut fill = Fill::new(
+                        order_id,
+                        symbol,
+                        update.side,
+                        quantity,
+                        price,
+                        Decimal::ZERO, // Alpaca equities and crypto are commission-free
+                        strategy_id,
+                    );
+                    fill.executed_at = update.timestamp;
+                    if let Some(callback) = &self.callback {
+                        callback.on_fill(fill).await;
+                    }
+                }
+                if matches!(
+                    update.status,
+                    OrderStatus::Filled
+                        | OrderStatus::Canceled
+                        | OrderStatus::Rejected
+                        | OrderStatus::Expired
+                ) {
+                    write_lock(&self.shared.orders).remove(&order_id);
+                }
+            }
+            AlpacaStreamEvent::Market(event) => {
+                let symbol = self.shared.resolve_symbol(event.symbol().clone());
+                let event = with_symbol(event, symbol);
+                self.shared.record_price(&event);
+                if let Some(callback) = &self.callback {
+                    callback.on_market_data(event).await;
+                }
+            }
+            AlpacaStreamEvent::Authorized
+            | AlpacaStreamEvent::Unauthorized { .. }
+            | AlpacaStreamEvent::Error { .. } => {}
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Broker
+// ---------------------------------------------------------------------------
+
+/// Broker adapter for Alpaca's trading API.
+pub struct AlpacaBroker {
+    config: AlpacaConfig,
+    transport: Arc<dyn AlpacaTransport>,
+    callback: Option<Arc<dyn BrokerCallback>>,
+    shared: Arc<SharedState>,
+    connected: bool,
+    shutdown: Option<watch::Sender<bool>>,
+    streams: HashMap<StreamKind, StreamHandle>,
+}
+
+impl AlpacaBroker {
+    /// Create a broker that talks to Alpaca over HTTPS.
+    pub fn new(config: AlpacaConfig) -> BrokerResult<Self> {
+        let transport = Arc::new(HttpTransport::new(&config)?);
+        Ok(Self::with_transport(config, transport))
+    }
+
+    /// Create a broker with a custom REST transport (e.g. recorded fixtures).
+    pub fn with_transport(config: AlpacaConfig, transport: Arc<dyn AlpacaTransport>) -> Self {
+        Self {
+            shared: Arc::new(SharedState::new(config.symbols.clone())),
+            config,
+            transport,
+            callback: None,
+            connected: false,
+            shutdown: None,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Register the receiver for fills, order-status changes, market data,
+    /// and connection-status changes coming off the websocket streams.
+    pub fn with_callback(mut self, callback: Arc<dyn BrokerCallback>) -> Self {
+        self.callback = Some(callback);
+        self
+    }
+
+    pub fn config(&self) -> &AlpacaConfig {
+        &self.config
+    }
+
+    fn ensure_connected(&self) -> BrokerResult<()> {
+        if self.connected {
+            Ok(())
+        } else {
+            Err(BrokerError::NotConnected)
+        }
+    }
+
+    /// Send a request, retrying HTTP 429 responses after the advertised
+    /// back-off up to `max_rate_limit_retries` times.
+    async fn send_with_retry(&self, request: AlpacaRequest) -> BrokerResult<AlpacaResponse> {
+        let mut retries = 0;
+        loop {
+            let response = self.transport.send(&request).await?;
+            if response.status != 429 || retries >= self.config.max_rate_limit_retries {
+                return Ok(response);
+            }
+            let wait_ms = response
+                .retry_after_ms
+                .unwrap_or(DEFAULT_RATE_LIMIT_BACKOFF_MS);
+            retries += 1;
+            warn!(
+                "Alpaca rate limit hit on {}; retry {}/{} in {} ms",
+                request.path, retries, self.config.max_rate_limit_retries, wait_ms
+            );
+            tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+        }
+    }
+
+    async fn request(
+        &self,
+        method: HttpMethod,
+        path: impl Into<String>,
+        body: Option<Value>,
+        order_id: Option<OrderId>,
+    ) -> BrokerResult<Value> {
+        let response = self
+            .send_with_retry(AlpacaRequest {
+                method,
+                path: path.into(),
+                body,
+            })
+            .await?;
+        if !response.is_success() {
+            return Err(map_error_response(&response, order_id));
+        }
+        if response.body.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+        response.json()
+    }
+
+    async fn fetch_order(&self, order_id: OrderId) -> BrokerResult<Value> {
+        self.request(
+            HttpMethod::Get,
+            format!(
+                "/v2/orders:by_client_order_id?client_order_id={}",
+                self.shared.client_ids.client_id(order_id)
+            ),
+            None,
+            Some(order_id),
+        )
+        .await
+    }
+
+    /// The Alpaca id of an order already placed under `order_id`'s client
+    /// id, if Alpaca holds one.
+    async fn submitted_alpaca_id(&self, order_id: OrderId) -> BrokerResult<Option<String>> {
+        match self.fetch_order(order_id).await {
+            Ok(order) => Ok(Some(str_field(&order, "id")?.to_string())),
+            Err(BrokerError::OrderNotFound { .. }) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn track_order(&self, order_id: OrderId, alpaca_id: String, order: Order) {
+        write_lock(&self.shared.orders).insert(
+            order_id,
+            TrackedOrder {
+                alpaca_id,
+                symbol: order.symbol,
+                strategy_id: order.strategy_id,
+            },
+        );
+    }
+
+    async fn alpaca_order_id(&self, order_id: OrderId) -> BrokerResult<String> {
+        if let Some(tracked) = read_lock(&self.shared.orders).get(&order_id) {
+            return Ok(tracked.alpaca_id.clone());
+        }
+        let order = self.fetch_order(order_id).await?;
+        Ok(str_field(&order, "id")?.to_string())
+    }
+
+    /// Fill in the order id, strategy id and caller symbol for orders we
+    /// submitted.
+    fn adopt_order(&self, mut order: Order) -> Order {
+        order.id = self.shared.client_ids.order_id(order.id);
+        let tracked = read_lock(&self.shared.orders).get(&order.id).cloned();
+        match tracked {
+            Some(tracked) => {
+                order.symbol = tracked.symbol;
+                order.strategy_id = tracked.strategy_id;
+            }
+            None => order.symbol = self.shared.resolve_symbol(order.symbol),
+        }
+        order
+    }
+
+    fn spawn_stream(&mut self, kind: StreamKind) {
+        let Some(shutdown) = &self.shutdown else {
+            return;
+        };
+        if self.streams.contains_key(&kind) {
+            return;
+        }
+        let url = match kind {
+            StreamKind::Trading => self.config.endpoints.trading_stream.clone(),
+            StreamKind::StockData => self.config.endpoints.stock_data_stream.clone(),
+            StreamKind::CryptoData => self.config.endpoints.crypto_data_stream.clone(),
+        };
+        let context = StreamContext {
+            kind,
+            url,
+            config: self.config.clone(),
+            shared: Arc::clone(&self.shared),
+            callback: self.callback.clone(),
+        };
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(context.run(receiver, shutdown.subscribe()));
+        self.streams.insert(kind, StreamHandle { commands, task });
+    }
+
+    /// Forward a subscription change to the relevant data streams, starting
+    /// a stream the first time one of its asset classes is subscribed.
+    fn route_subscription(&mut self, symbols: &[Symbol], subscribe: bool) {
+        if !self.connected {
+            return;
+        }
+        let mut by_kind: HashMap<StreamKind, Vec<String>> = HashMap::new();
+        for symbol in symbols {
+            let Some(ticker) = self.shared.symbols.ticker(symbol) else {
+                continue;
+            };
+            by_kind
+                .entry(StreamKind::for_asset_class(symbol.asset_class))
+                .or_default()
+                .push(ticker);
+        }
+        for (kind, tickers) in by_kind {
+            match self.streams.get(&kind) {
+                Some(handle) => {
+                    let command = if subscribe {
+                        StreamCommand::Subscribe(tickers)
+                    } else {
+                        StreamCommand::Unsubscribe(tickers)
+                    };
+                    let _ = handle.commands.send(command);
+                }
+                // A new stream subscribes to the full set once authorized.
+                None if subscribe => self.spawn_stream(kind),
+                None => {}
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Broker for AlpacaBroker {
+    async fn connect(&mut self) -> BrokerResult<()> {
+        if self.connected {
+            return Ok(());
+        }
+        // Validates the credentials before any stream is opened.
+        let account = self
+            .request(HttpMethod::Get, "/v2/account", None, None)
+            .await?;
+        if account.get("trading_blocked").and_then(Value::as_bool) == Some(true) {
+            warn!("Alpaca account is trading-blocked; orders will be rejected");
+        }
+
+        self.connected = true;
+        *write_lock(&self.shared.status) = ConnectionStatus::Connected;
+        let (shutdown, _) = watch::channel(false);
+        self.shutdown = Some(shutdown);
+
+        if self.config.stream_trade_updates {
+            self.spawn_stream(StreamKind::Trading);
+        }
+        let subscriptions = read_lock(&self.shared.subscriptions).clone();
+        self.route_subscription(&subscriptions, true);
+
+        if let Some(callback) = &self.callback {
+            callback
+                .on_connection_status(ConnectionStatus::Connected)
+                .await;
+        }
+        info!(
+            "Alpaca broker connected ({:?} environment)",
+            self.config.environment
+        );
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> BrokerResult<()> {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(true);
+        }
+        for (_, handle) in self.streams.drain() {
+            let abort = handle.task.abort_handle();
+            if tokio::time::timeout(Duration::from_secs(2), handle.task)
+                .await
+                .is_err()
+            {
+                abort.abort();
+            }
+        }
+        let was_connected = self.connected;
+        self.connected = false;
+        *write_lock(&self.shared.status) = ConnectionStatus::Disconnected;
+        if was_connected {
+            if let Some(callback) = &self.callback {
+                callback
+                    .on_connection_status(ConnectionStatus::Disconnected)
+                    .await;
+            }
+        }
+        info!("Alpaca broker disconnected");
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        if self.connected {
+            *read_lock(&self.shared.status)
+        } else {
+            ConnectionStatus::Disconnected
+        }
+    }
+
+    async fn submit_order(&mut self, order: Order) -> BrokerResult<OrderId> {
+        self.ensure_connected()?;
+        let (client_id, resent) = self.shared.client_ids.assign(&order);
+        let order_id = self.shared.client_ids.order_id(client_id);
+        if resent {
+            if read_lock(&self.shared.orders).contains_key(&order_id) {
+                return Ok(order_id);
+            }
+            // An earlier attempt may have been accepted before it failed.
+            if let Some(alpaca_id) = self.submitted_alpaca_id(order_id).await? {
+                self.track_order(order_id, alpaca_id, order);
+                return Ok(order_id);
+            }
+        }
+        let body = order_request_body(&order, &self.shared.symbols)?;
+        let response = match self
+            .request(HttpMethod::Post, "/v2/orders", Some(body), Some(order_id))
+            .await
+        {
+            Ok(response) => response,
+            Err(e) if is_duplicate_client_id(&e) => {
+                let Some(alpaca_id) = self.submitted_alpaca_id(order_id).await? else {
+                    return Err(e);
+                };
+                self.track_order(order_id, alpaca_id, order);
+                return Ok(order_id);
+            }
+            Err(e) => return Err(e),
+        };
+        let alpaca_id = str_field(&response, "id")?.to_string();
+        info!(
+            "Alpaca order submitted: {} {:?} {} {} (alpaca id {})",
+            order_id, order.side, order.quantity, order.symbol, alpaca_id
+        );
+        self.track_order(order_id, alpaca_id, order);
+        Ok(order_id)
+    }
+
+    async fn cancel_order(&mut self, order_id: OrderId) -> BrokerResult<()> {
+        self.ensure_connected()?;
+        let alpaca_id = self.alpaca_order_id(order_id).await?;
+        self.request(
+            HttpMethod::Delete,
+            format!("/v2/orders/{}", alpaca_id),
+            None,
+            Some(order_id),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get_order_status(&self, order_id: OrderId) -> BrokerResult<OrderStatus> {
+        self.ensure_connected()?;
+        let order = self.fetch_order(order_id).await?;
+        parse_order_status(str_field(&order, "status")?)
+    }
+
+    async fn get_order(&self, order_id: OrderId) -> BrokerResult<Order> {
+        self.ensure_connected()?;
+        let order = self.fetch_order(order_id).await?;
+        order_from_alpaca(&order).map(|order| self.adopt_order(order))
+    }
+
+    async fn get_open_orders(&self) -> BrokerResult<Vec<Order>> {
+        self.ensure_connected()?;
+        let response = self
+            .request(
+                HttpMethod::Get,
+                "/v2/orders?status=open&limit=500",
+                None,
+                None,
+            )
+            .await?;
+        response
+            .as_array()
+            .ok_or_else(|| payload_error("expected an array of orders"))?
+            .iter()
+            .map(|raw| order_from_alpaca(raw).map(|order| self.adopt_order(order)))
+            .collect()
+    }
+
+    async fn get_account_balance(&self) -> BrokerResult<AccountBalance> {
+        self.ensure_connected()?;
+        let account = self
+            .request(HttpMethod::Get, "/v2/account", None, None)
+            .await?;
+        account_from_alpaca(&account)
+    }
+
+    async fn get_positions(&self) -> BrokerResult<Vec<BrokerPosition>> {
+        self.ensure_connected()?;
+        let response = self
+            .request(HttpMethod::Get, "/v2/positions", None, None)
+            .await?;
+        response
+            .as_array()
+            .ok_or_else(|| payload_error("expected an array of positions"))?
+            .iter()
+            .map(|raw| {
+                position_from_alpaca(raw).map(|mut position| {
+                    position.symbol = self.shared.resolve_symbol(position.symbol);
+                    position
+                })
+            })
+            .collect()
+    }
+
+    async fn get_position(&self, symbol: &Symbol) -> BrokerResult<Option<BrokerPosition>> {
+        self.ensure_connected()?;
+        let response = self
+            .send_with_retry(AlpacaRequest {
+                method: HttpMethod::Get,
+                path: format!(
+                    "/v2/positions/{}",
+                    to_alpaca_symbol(&self.shared.symbols, symbol)?.replace('/', "")
+                ),
+                body: None,
+            })
+            .await?;
+        if response.status == 404 {
+            return Ok(None);
+        }
+        if !response.is_success() {
+            return Err(map_error_response(&response, None));
+        }
+        let mut position = position_from_alpaca(&response.json()?)?;
+        position.symbol = symbol.clone();
+        Ok(Some(position))
+    }
+
+    async fn subscribe_market_data(&mut self, symbols: &[Symbol]) -> BrokerResult<()> {
+        for symbol in symbols {
+            to_alpaca_symbol(&self.shared.symbols, symbol)?;
+        }
+        let added: Vec<Symbol> = {
+            let mut subscriptions = write_lock(&self.shared.subscriptions);
+            let added: Vec<Symbol> = symbols
+                .iter()
+                .filter(|symbol| !subscriptions.contains(symbol))
+                .cloned()
+                .collect();
+            subscriptions.extend(added.iter().cloned());
+            added
+        };
+        self.route_subscription(&added, true);
+        Ok(())
+    }
+
+    async fn unsubscribe_market_data(&mut self, symbols: &[Symbol]) -> BrokerResult<()> {
+        write_lock(&self.shared.subscriptions).retain(|symbol| !symbols.contains(symbol));
+        self.route_subscription(symbols, false);
+        Ok(())
+    }
+
+    fn get_latest_price(&self, symbol: &Symbol) -> Option<Decimal> {
+        read_lock(&self.shared.prices).get(symbol).copied()
+    }
+
+    fn get_all_prices(&self) -> HashMap<Symbol, Decimal> {
+        read_lock(&self.shared.prices).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    // Recorded (trimmed) Alpaca paper API payloads.
+    const ACCOUNT_FIXTURE: &str = r#"{
+        "id": "904837e3-3b76-47ec-b432-046db621571b",
+        "account_number": "PA3CQ8Y7ZZ7X",
+        "status": "ACTIVE",
+        "currency": "USD",
+        "cash": "98123.45",
+        "buying_power": "196246.90",
+        "equity": "100512.30",
+        "trading_blocked": false
+    }"#;
+
+    const POSITIONS_FIXTURE: &str = r#"[
+        {
+            "asset_id": "b0b6dd9d-8b9b-48a9-ba46-b9d54906e415",
+            "symbol": "AAPL",
+            "exchange": "NASDAQ",
+            "asset_class": "us_equity",
+            "avg_entry_price": "178.25",
+            "qty": "10",
+            "side": "long",
+            "market_value": "1801.20",
+            "unrealized_pl": "18.70"
+        },
+        {
+            "asset_id": "276e2673-764b-4ab6-a611-caf665ca6340",
+            "symbol": "BTCUSD",
+            "exchange": "CRYPTO",
+            "asset_class": "crypto",
+            "avg_entry_price": "64000",
+            "qty": "0.5",
+            "side": "long",
+            "market_value": "32500",
+            "unrealized_pl": "500"
+        }
+    ]"#;
+
+    fn order_fixture(client_order_id: &str, status: &str, filled_qty: &str) -> String {
+        json!({
+            "id": "61e69015-8549-4bfd-b9c3-01e75843f47d",
+            "client_order_id": client_order_id,
+            "created_at": "2024-03-01T14:30:00.123456Z",
+            "submitted_at": "2024-03-01T14:30:00.234567Z",
+            "asset_class": "us_equity",
+            "symbol": "AAPL",
+            "qty": "10",
+            "filled_qty": filled_qty,
+            "filled_avg_price": if filled_qty == "0" { Value::Null } else { json!("179.08") },
+            "order_type": "limit",
+            "type": "limit",
+            "side": "buy",
+            "time_in_force": "day",
+            "limit_price": "180.5",
+            "stop_price": null,
+            "status": status
+        })
+        .to_string()
+    }
+
+    fn trade_update_fixture(client_order_id: &str, event: &str, status: &str, qty: &str) -> String {
+        let order: Value =
+            serde_json::from_str(&order_fixture(client_order_id, status, qty)).unwrap();
+        json!({
+            "stream": "trade_updates",
+            "data": {
+                "event": event,
+                "execution_id": "1a5b3c8e-7c53-4f5b-9d0a-3d4c8f2c2f11",
+                "timestamp": "2024-03-01T14:31:02.5Z",
+                "price": "179.08",
+                "qty": qty,
+                "position_qty": qty,
+                "order": order
+            }
+        })
+        .to_string()
+    }
+
+    const AUTHORIZED_FIXTURE: &str =
+        r#"{"stream":"authorization","data":{"action":"authenticate","status":"authorized"}}"#;
+
+    fn response(status: u16, body: &str) -> AlpacaResponse {
+        AlpacaResponse {
+            status,
+            body: body.to_string(),
+            retry_after_ms: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct FixtureTransport {
+        responses: Mutex<VecDeque<AlpacaResponse>>,
+        requests: Mutex<Vec<AlpacaRequest>>,
+    }
+
+    impl FixtureTransport {
+        fn new(responses: Vec<AlpacaResponse>) -> Arc<Self> {
+            Arc::new(Self {
+                responses: Mutex::new(responses.into()),
+                requests: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn requests(&self) -> Vec<AlpacaRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl AlpacaTransport for FixtureTransport {
+        async fn send(&self, request: &AlpacaRequest) -> BrokerResult<AlpacaResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            self.responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| BrokerError::Internal {
+                    message: format!("no fixture for {}", request.path),
+                })
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingCallback {
+        fills: Mutex<Vec<Fill>>,
+        statuses: Mutex<Vec<(OrderId, OrderStatus)>>,
+        market: Mutex<Vec<MarketEvent>>,
+        connection: Mutex<Vec<ConnectionStatus>>,
+    }
+
+    #[async_trait]
+    impl BrokerCallback for RecordingCallback {
+        async fn on_fill(&self, fill: Fill) {
+            self.fills.lock().unwrap().push(fill);
+        }
+        async fn on_order_status(&self, order_id: OrderId, status: OrderStatus) {
+            self.statuses.lock().unwrap().push((order_id, status));
+        }
+        async fn on_market_data(&self, event: MarketEvent) {
+            self.market.lock().unwrap().push(event);
+        }
+        async fn on_connection_status(&self, status: ConnectionStatus) {
+            self.connection.lock().unwrap().push(status);
+        }
+    }
+
+    fn fixture_config() -> AlpacaConfig {
+        let mut config = AlpacaConfig::new("key", "secret", AlpacaEnvironment::Paper);
+        config.stream_trade_updates = false;
+        config
+    }
+
+    async fn connected_broker(
+        responses: Vec<AlpacaResponse>,
+    ) -> (AlpacaBroker, Arc<FixtureTransport>) {
+        let mut all = vec![response(200, ACCOUNT_FIXTURE)];
+        all.extend(responses);
+        let transport = FixtureTransport::new(all);
+        let mut broker = AlpacaBroker::with_transport(fixture_config(), transport.clone());
+        broker.connect().await.unwrap();
+        (broker, transport)
+    }
+
+    #[test]
+    fn test_environment_selects_endpoints() {
+        let paper = AlpacaConfig::new("k", "s", AlpacaEnvironment::Paper);
+        assert_eq!(
+            paper.endpoints.trading_rest,
+            "https://paper-api.alpaca.markets"
+        );
+        assert_eq!(
+            paper.endpoints.trading_stream,
+            "wss://paper-api.alpaca.markets/stream"
+        );
+        assert_eq!(
+            paper.endpoints.stock_data_stream,
+            "wss://stream.data.alpaca.markets/v2/iex"
+        );
+
+        let live = AlpacaConfig::new("k", "s", AlpacaEnvironment::Live).with_data_feed("sip");
+        assert_eq!(live.endpoints.trading_rest, "https://api.alpaca.markets");
+        assert_eq!(
+            live.endpoints.stock_data_stream,
+            "wss://stream.data.alpaca.markets/v2/sip"
+        );
+        assert!(!format!("{:?}", live).contains("\"s\""));
+    }
+
+    #[test]
+    fn test_reconnect_delay_doubles_and_caps() {
+        let mut config = fixture_config();
+        config.reconnect_initial_delay_ms = 100;
+        config.reconnect_max_delay_ms = 1_000;
+        assert_eq!(config.reconnect_delay(0), Duration::from_millis(100));
+        assert_eq!(config.reconnect_delay(2), Duration::from_millis(400));
+        assert_eq!(config.reconnect_delay(10), Duration::from_millis(1_000));
+        assert_eq!(
+            config.reconnect_delay(u32::MAX),
+            Duration::from_millis(1_000)
+        );
+    }
+
+    #[test]
+    fn test_order_request_body_maps_types_and_time_in_force() {
+        let alpaca = SymbolMapper::builtin().provider("alpaca");
+        let symbol = Symbol::equity("AAPL");
+        let mut market = Order::market_order(symbol.clone(), Side::Buy, dec!(10), "s".into());
+        market.time_in_force = TimeInForce::Day;
+        let body = order_request_body(&market, &alpaca).unwrap();
+        assert_eq!(body["type"], "market");
+        assert_eq!(body["side"], "buy");
+        assert_eq!(body["qty"], "10");
+        assert_eq!(body["time_in_force"], "day");
+        assert_eq!(body["client_order_id"], market.id.to_string());
+        assert!(body.get("limit_price").is_none());
+
+        let limit = Order::limit_order(
+            symbol.clone(),
+            Side::Sell,
+            dec!(5),
+            dec!(180.50),
+            "s".into(),
+        );
+        let body = order_request_body(&limit, &alpaca).unwrap();
+        assert_eq!(body["type"], "limit");
+        assert_eq!(body["limit_price"], "180.5");
+        assert_eq!(body["time_in_force"], "gtc");
+
+        let mut stop =
+            Order::stop_order(symbol.clone(), Side::Sell, dec!(5), dec!(170), "s".into());
+        stop.time_in_force = TimeInForce::IOC;
+        let body = order_request_body(&stop, &alpaca).unwrap();
+        assert_eq!(body["type"], "stop");
+        assert_eq!(body["stop_price"], "170");
+        assert_eq!(body["time_in_force"], "ioc");
+
+        let mut stop_limit = Order::new(
+            Symbol::crypto("BTC-USD"),
+            Side::Buy,
+            dec!(0.25),
+            OrderType::StopLimit {
+                stop_price: dec!(65000),
+                limit_price: dec!(65100),
+            },
+            "s".into(),
+        );
+        stop_limit.time_in_force = TimeInForce::FOK;
+        let body = order_request_body(&stop_limit, &alpaca).unwrap();
+        assert_eq!(body["symbol"], "BTC/USD");
+        assert_eq!(body["type"], "stop_limit");
+        assert_eq!(body["stop_price"], "65000");
+        assert_eq!(body["limit_price"], "65100");
+        assert_eq!(body["time_in_force"], "fok");
+
+        let trailing =
+            Order::trailing_stop_order(symbol.clone(), Side::Sell, dec!(5), dec!(2.5), "s".into());
+        let body = order_request_body(&trailing, &alpaca).unwrap();
+        assert_eq!(body["type"], "trailing_stop");
+        assert_eq!(body["trail_price"], "2.5");
+        assert!(body.get("stop_price").is_none());
+    }
+
+    #[test]
+    fn test_share_classes_use_the_configured_ticker() {
+        let config = fixture_config().with_symbols(ProviderSymbols::slashes());
+        let brk = Symbol::equity("BRK.B");
+        let order = Order::market_order(brk.clone(), Side::Buy, dec!(1), "s".into());
+        let body = order_request_body(&order, &config.symbols).unwrap();
+        assert_eq!(body["symbol"], "BRK/B");
+
+        let shared = SharedState::new(config.symbols);
+        assert_eq!(shared.resolve_symbol(Symbol::equity("BRK/B")), brk);
+
+        let aliases_only = ProviderSymbols {
+            aliases_only: true,
+            ..Default::default()
+        };
+        let error = order_request_body(&order, &aliases_only).unwrap_err();
+        assert_eq!(error.code(), "data.unmapped_symbol");
+    }
+
+    #[test]
+    fn test_order_from_alpaca_fixture() {
+        let id = Uuid::new_v4();
+        let raw: Value =
+            serde_json::from_str(&order_fixture(&id.to_string(), "partially_filled", "4")).unwrap();
+        let order = order_from_alpaca(&raw).unwrap();
+        assert_eq!(order.id, id);
+        assert_eq!(order.symbol, Symbol::equity("AAPL"));
+        assert_eq!(order.side, Side::Buy);
+        assert_eq!(order.order_type, OrderType::Limit { price: dec!(180.5) });
+        assert_eq!(order.time_in_force, TimeInForce::Day);
+        assert_eq!(order.status, OrderStatus::PartiallyFilled);
+        assert_eq!(order.filled_quantity, dec!(4));
+        assert_eq!(order.remaining_quantity, dec!(6));
+        assert_eq!(order.average_fill_price, Some(dec!(179.08)));
+        assert_eq!(
+            order.metadata["alpaca_order_id"],
+            "61e69015-8549-4bfd-b9c3-01e75843f47d"
+        );
+
+        // Orders placed outside GlowBack still parse, with a fresh id.
+        let raw: Value = serde_json::from_str(&order_fixture("manual-1", "new", "0")).unwrap();
+        let order = order_from_alpaca(&raw).unwrap();
+        assert_eq!(order.status, OrderStatus::Submitted);
+        assert_eq!(order.average_fill_price, None);
+    }
+
+    #[test]
+    fn test_status_and_symbol_mapping() {
+        assert_eq!(
+            parse_order_status("done_for_day").unwrap(),
+            OrderStatus::Expired
+        );
+        assert_eq!(
+            parse_order_status("replaced").unwrap(),
+            OrderStatus::Canceled
+        );
+        assert_eq!(
+            parse_order_status("pending_cancel").unwrap(),
+            OrderStatus::Submitted
+        );
+        assert!(parse_order_status("mystery").is_err());
+        assert_eq!(parse_time_in_force("opg").unwrap(), TimeInForce::Day);
+
+        assert_eq!(
+            from_alpaca_symbol("BTC/USD", AssetClass::Crypto),
+            Symbol::crypto("BTC-USD")
+        );
+        assert_eq!(
+            from_alpaca_symbol("ETHUSDT", AssetClass::Crypto),
+            Symbol::crypto("ETH-USDT")
+        );
+        let alpaca = SymbolMapper::builtin().provider("alpaca");
+        assert_eq!(
+            to_alpaca_symbol(&alpaca, &Symbol::crypto("ETH-USD")).unwrap(),
+            "ETH/USD"
+        );
+        assert_eq!(
+            to_alpaca_symbol(&alpaca, &Symbol::equity("MSFT")).unwrap(),
+            "MSFT"
+        );
+    }
+
+    #[test]
+    fn test_error_responses_map_to_broker_errors() {
+        let order_id = Uuid::new_v4();
+        let body = r#"{"code":40310000,"message":"insufficient buying power"}"#;
+
+        assert!(matches!(
+            map_error_response(&response(401, r#"{"message":"unauthorized."}"#), None),
+            BrokerError::AuthenticationFailed { message } if message == "unauthorized."
+        ));
+        assert!(matches!(
+            map_error_response(&response(403, body), Some(order_id)),
+            BrokerError::OrderRejected { reason } if reason == "insufficient buying power"
+        ));
+        assert!(matches!(
+            map_error_response(&response(403, "forbidden"), None),
+            BrokerError::AuthenticationFailed { .. }
+        ));
+        assert!(matches!(
+            map_error_response(&response(404, "{}"), Some(order_id)),
+            BrokerError::OrderNotFound { order_id: id } if id == order_id.to_string()
+        ));
+        assert!(matches!(
+            map_error_response(&response(422, r#"{"message":"qty must be > 0"}"#), None),
+            BrokerError::OrderRejected { .. }
+        ));
+        let mut limited = response(429, "rate limit exceeded");
+        limited.retry_after_ms = Some(3_000);
+        assert!(matches!(
+            map_error_response(&limited, None),
+            BrokerError::RateLimited {
+                retry_after_ms: 3_000
+            }
+        ));
+        assert!(matches!(
+            map_error_response(&response(503, "unavailable"), None),
+            BrokerError::Internal { message } if message.contains("503")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_requires_connection() {
+        let transport = FixtureTransport::new(vec![]);
+        let mut broker = AlpacaBroker::with_transport(fixture_config(), transport);
+        let order = Order::market_order(Symbol::equity("AAPL"), Side::Buy, dec!(1), "s".into());
+        assert!(matches!(
+            broker.submit_order(order).await,
+            Err(BrokerError::NotConnected)
+        ));
+        assert_eq!(broker.connection_status(), ConnectionStatus::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_bad_credentials() {
+        let transport = FixtureTransport::new(vec![response(
+            401,
+            r#"{"code":40110000,"message":"request is not authorized"}"#,
+        )]);
+        let mut broker = AlpacaBroker::with_transport(fixture_config(), transport);
+        assert!(matches!(
+            broker.connect().await,
+            Err(BrokerError::AuthenticationFailed { .. })
+        ));
+        assert_eq!(broker.connection_status(), ConnectionStatus::Disconnected);
+    }
+
+    #[tokio::test]
+    async fn test_submit_and_cancel_order_round_trip() {
+        let order = Order::limit_order(
+            Symbol::equity("AAPL"),
+            Side::Buy,
+            dec!(10),
+            dec!(180.5),
+            "alpha".into(),
+        );
+        let (mut broker, transport) = connected_broker(vec![
+            response(200, &order_fixture(&order.id.to_string(), "accepted", "0")),
+            response(204, ""),
+        ])
+        .await;
+
+        let order_id = broker.submit_order(order.clone()).await.unwrap();
+        assert_eq!(order_id, order.id);
+        broker.cancel_order(order_id).await.unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests[0].path, "/v2/account");
+        assert_eq!(requests[1].method, HttpMethod::Post);
+        assert_eq!(requests[1].path, "/v2/orders");
+        assert_eq!(
+            requests[1].body.as_ref().unwrap()["client_order_id"],
+            order.id.to_string()
+        );
+        // Cancel uses the Alpaca id remembered from submission, no lookup.
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[2].method, HttpMethod::Delete);
+        assert_eq!(
+            requests[2].path,
+            "/v2/orders/61e69015-8549-4bfd-b9c3-01e75843f47d"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rejected_order_and_unknown_cancel_are_typed() {
+        let unknown = Uuid::new_v4();
+        let (mut broker, _) = connected_broker(vec![
+            response(
+                403,
+                r#"{"code":40310000,"message":"insufficient buying power"}"#,
+            ),
+            response(404, r#"{"code":40410000,"message":"order not found"}"#),
+        ])
+        .await;
+
+        let order = Order::market_order(Symbol::equity("AAPL"), Side::Buy, dec!(1000), "s".into());
+        assert!(matches!(
+            broker.submit_order(order).await,
+            Err(BrokerError::OrderRejected { .. })
+        ));
+        assert!(matches!(
+            broker.cancel_order(unknown).await,
+            Err(BrokerError::OrderNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_resubmitted_orders_resolve_to_the_accepted_order() {
+        let client_id = Uuid::new_v4();
+        let order = Order::limit_order(
+            Symbol::equity("AAPL"),
+            Side::Buy,
+            dec!(10),
+            dec!(180.5),
+            "alpha".into(),
+        )
+        .with_client_order_id(client_id);
+        let accepted = order_fixture(&client_id.to_string(), "accepted", "0");
+        let lookup = format!(
+            "/v2/orders:by_client_order_id?client_order_id={}",
+            client_id
+        );
+
+        // The first attempt times out after Alpaca accepted it; the retry
+        // finds the order instead of placing it again.
+        let (mut broker, transport) = connected_broker(vec![
+            response(504, r#"{"message":"gateway timeout"}"#),
+            response(200, &accepted),
+            response(204, ""),
+        ])
+        .await;
+        assert!(matches!(
+            broker.submit_order(order.clone()).await,
+            Err(BrokerError::Internal { .. })
+        ));
+        assert_eq!(broker.submit_order(order.clone()).await.unwrap(), order.id);
+        broker.cancel_order(order.id).await.unwrap();
+        let requests = transport.requests();
+        assert_eq!(
+            requests[1].body.as_ref().unwrap()["client_order_id"],
+            client_id.to_string()
+        );
+        assert_eq!(
+            (requests[2].method, requests[2].path.as_str()),
+            (HttpMethod::Get, lookup.as_str())
+        );
+        assert_eq!(
+            requests[3].path,
+            "/v2/orders/61e69015-8549-4bfd-b9c3-01e75843f47d"
+        );
+        assert_eq!(requests.len(), 4);
+
+        // After a restart nothing is remembered, so Alpaca's duplicate-id
+        // rejection leads to the lookup.
+        let (mut broker, transport) = connected_broker(vec![
+            response(
+                422,
+                r#"{"code":40010001,"message":"client_order_id must be unique"}"#,
+            ),
+            response(200, &accepted),
+        ])
+        .await;
+        assert_eq!(broker.submit_order(order.clone()).await.unwrap(), order.id);
+        let requests = transport.requests();
+        assert_eq!(requests[1].method, HttpMethod::Post);
+        assert_eq!(requests[2].path, lookup);
+
+        // A rejection for any other reason is not looked up.
+        let (mut broker, transport) = connected_broker(vec![response(
+            422,
+            r#"{"code":40010001,"message":"qty must be > 0"}"#,
+        )])
+        .await;
+        assert!(matches!(
+            broker.submit_order(order).await,
+            Err(BrokerError::OrderRejected { .. })
+        ));
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_requests_are_retried_then_surfaced() {
+        let mut limited = response(429, r#"{"message":"too many requests"}"#);
+        limited.retry_after_ms = Some(1);
+        let (broker, transport) = connected_broker(vec![
+            limited.clone(),
+            response(200, ACCOUNT_FIXTURE),
+            limited.clone(),
+            limited.clone(),
+            limited.clone(),
+            limited.clone(),
+        ])
+        .await;
+
+        let balance = broker.get_account_balance().await.unwrap();
+        assert_eq!(balance.cash, dec!(98123.45));
+        assert_eq!(transport.requests().len(), 3);
+
+        assert!(matches!(
+            broker.get_account_balance().await,
+            Err(BrokerError::RateLimited { retry_after_ms: 1 })
+        ));
+        // One initial attempt plus `max_rate_limit_retries` retries.
+        assert_eq!(transport.requests().len(), 3 + 4);
+    }
+
+    #[tokio::test]
+    async fn test_account_and_positions_fixtures() {
+        let (mut broker, transport) = connected_broker(vec![
+            response(200, ACCOUNT_FIXTURE),
+            response(200, POSITIONS_FIXTURE),
+            response(
+                404,
+                r#"{"code":40410000,"message":"position does not exist"}"#,
+            ),
+        ])
+        .await;
+        let aapl = Symbol::new("AAPL", "NYSE", AssetClass::Equity);
+        broker
+            .subscribe_market_data(std::slice::from_ref(&aapl))
+            .await
+            .unwrap();
+
+        let balance = broker.get_account_balance().await.unwrap();
+        assert_eq!(balance.buying_power, dec!(196246.90));
+        assert_eq!(balance.equity, dec!(100512.30));
+
+        let positions = broker.get_positions().await.unwrap();
+        assert_eq!(positions.len(), 2);
+        // Symbols resolve to the caller's own instance when known.
+        assert_eq!(positions[0].symbol, aapl);
+        assert_eq!(positions[0].quantity, dec!(10));
+        assert_eq!(positions[0].average_cost, dec!(178.25));
+        assert_eq!(positions[1].symbol, Symbol::crypto("BTC-USD"));
+        assert_eq!(positions[1].unrealized_pnl, dec!(500));
+
+        assert_eq!(
+            broker
+                .get_position(&Symbol::crypto("ETH-USD"))
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            transport.requests().last().unwrap().path,
+            "/v2/positions/ETHUSD"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_open_orders_keep_strategy_ids() {
+        let order = Order::limit_order(
+            Symbol::equity("AAPL"),
+            Side::Buy,
+            dec!(10),
+            dec!(180.5),
+            "alpha".into(),
+        );
+        let open = format!("[{}]", order_fixture(&order.id.to_string(), "new", "0"));
+        let (mut broker, _) = connected_broker(vec![
+            response(200, &order_fixture(&order.id.to_string(), "accepted", "0")),
+            response(200, &open),
+        ])
+        .await;
+        broker.submit_order(order.clone()).await.unwrap();
+
+        let open_orders = broker.get_open_orders().await.unwrap();
+        assert_eq!(open_orders.len(), 1);
+        assert_eq!(open_orders[0].id, order.id);
+        assert_eq!(open_orders[0].strategy_id, "alpha");
+        assert_eq!(open_orders[0].status, OrderStatus::Submitted);
+    }
+
+    #[test]
+    fn test_parse_trading_stream_fixtures() {
+        assert_eq!(
+            parse_trading_stream_message(AUTHORIZED_FIXTURE).unwrap(),
+            vec![AlpacaStreamEvent::Authorized]
+        );
+        assert!(matches!(
+            parse_trading_stream_message(
+                r#"{"stream":"authorization","data":{"action":"authenticate","status":"unauthorized"}}"#
+            )
+            .unwrap()[0],
+            AlpacaStreamEvent::Unauthorized { .. }
+        ));
+        assert!(parse_trading_stream_message(
+            r#"{"stream":"listening","data":{"streams":["trade_updates"]}}"#
+        )
+        .unwrap()
+        .is_empty());
+
+        let id = Uuid::new_v4();
+        let events = parse_trading_stream_message(&trade_update_fixture(
+            &id.to_string(),
+            "partial_fill",
+            "partially_filled",
+            "4",
+        ))
+        .unwrap();
+        let AlpacaStreamEvent::TradeUpdate(update) = &events[0] else {
+            panic!("expected trade update");
+        };
+        assert_eq!(update.order_id, id);
+        assert_eq!(update.status, OrderStatus::PartiallyFilled);
+        assert_eq!(update.fill_quantity, Some(dec!(4)));
+        assert_eq!(update.fill_price, Some(dec!(179.08)));
+
+        let events = parse_trading_stream_message(&trade_update_fixture(
+            &id.to_string(),
+            "canceled",
+            "canceled",
+            "4",
+        ))
+        .unwrap();
+        let AlpacaStreamEvent::TradeUpdate(update) = &events[0] else {
+            panic!("expected trade update");
+        };
+        assert_eq!(update.fill_quantity, None);
+    }
+
+    #[test]
+    fn test_parse_data_stream_fixtures() {
+        let frame = r#"[
+            {"T":"success","msg":"authenticated"},
+            {"T":"q","S":"AAPL","bx":"V","bp":179.02,"bs":3,"ax":"V","ap":179.1,"as":2,"t":"2024-03-01T14:31:00.1Z","c":["R"],"z":"C"},
+            {"T":"t","S":"AAPL","i":52983525029461,"x":"V","p":179.05,"s":100,"t":"2024-03-01T14:31:00.2Z","c":["@"],"z":"C"},
+            {"T":"b","S":"AAPL","o":178.9,"h":179.2,"l":178.8,"c":179.05,"v":12045,"t":"2024-03-01T14:31:00Z","n":88,"vw":179.01}
+        ]"#;
+        let events = parse_data_stream_message(frame, AssetClass::Equity).unwrap();
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], AlpacaStreamEvent::Authorized);
+        let AlpacaStreamEvent::Market(MarketEvent::Quote {
+            bid, ask, bid_size, ..
+        }) = &events[1]
+        else {
+            panic!("expected quote");
+        };
+        assert_eq!(
+            (*bid, *ask, *bid_size),
+            (dec!(179.02), dec!(179.1), dec!(3))
+        );
+        assert!(matches!(
+            &events[2],
+            AlpacaStreamEvent::Market(MarketEvent::Tick(tick)) if tick.price == dec!(179.05)
+        ));
+        let AlpacaStreamEvent::Market(MarketEvent::Bar(bar)) = &events[3] else {
+            panic!("expected bar");
+        };
+        assert_eq!(bar.resolution, Resolution::Minute);
+        assert_eq!(bar.volume, dec!(12045));
+
+        let crypto = r#"[{"T":"q","S":"BTC/USD","bp":64990.5,"bs":0.25,"ap":65010.5,"as":0.4,"t":"2024-03-01T14:31:00Z"}]"#;
+        let events = parse_data_stream_message(crypto, AssetClass::Crypto).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            match &events[0] {
+                AlpacaStreamEvent::Market(event) => event.symbol().clone(),
+                _ => panic!("expected market event"),
+            },
+            Symbol::crypto("BTC-USD")
+        );
+
+        let refused = parse_data_stream_message(
+            r#"[{"T":"error","code":402,"msg":"auth failed"}]"#,
+            AssetClass::Equity,
+        )
+        .unwrap();
+        assert!(matches!(refused[0], AlpacaStreamEvent::Unauthorized { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_stream_dispatch_routes_fills_and_prices() {
+        let shared = Arc::new(SharedState::new(ProviderSymbols::default()));
+        let order_id = Uuid::new_v4();
+        let aapl = Symbol::new("AAPL", "NYSE", AssetClass::Equity);
+        write_lock(&shared.orders).insert(
+            order_id,
+            TrackedOrder {
+                alpaca_id: "61e69015-8549-4bfd-b9c3-01e75843f47d".into(),
+                symbol: aapl.clone(),
+                strategy_id: "alpha".into(),
+            },
+        );
+        let callback = Arc::new(RecordingCallback::default());
+        let context = StreamContext {
+            kind: StreamKind::Trading,
+            url: String::new(),
+            config: fixture_config(),
+            shared: Arc::clone(&shared),
+            callback: Some(callback.clone()),
+        };
+
+        for frame in [
+            trade_update_fixture(
+                &order_id.to_string(),
+                "partial_fill",
+                "partially_filled",
+                "4",
+            ),
+            trade_update_fixture(&order_id.to_string(), "fill", "filled", "6"),
+        ] {
+            for event in context.parse(&frame).unwrap() {
+                context.dispatch(event).await;
+            }
+        }
+
+        let fills = callback.fills.lock().unwrap().clone();
+        assert_eq!(fills.len(), 2);
+        assert!(fills.iter().all(|fill| fill.order_id == order_id
+            && fill.symbol == aapl
+            && fill.strategy_id == "alpha"
+            && fill.commission == Decimal::ZERO));
+        assert_eq!(fills[0].quantity + fills[1].quantity, dec!(10));
+        assert_eq!(
+            callback.statuses.lock().unwrap().last(),
+            Some(&(order_id, OrderStatus::Filled))
+        );
+        // Terminal orders are no longer tracked.
+        assert!(read_lock(&shared.orders).is_empty());
+
+        write_lock(&shared.subscriptions).push(aapl.clone());
+        let data = StreamContext {
+            kind: StreamKind::StockData,
+            ..context
+        };
+        let frame =
+            r#"[{"T":"q","S":"AAPL","bp":100,"bs":1,"ap":102,"as":1,"t":"2024-03-01T14:31:00Z"}]"#;
+        for event in data.parse(frame).unwrap() {
+            data.dispatch(event).await;
+        }
+        assert_eq!(read_lock(&shared.prices).get(&aapl), Some(&dec!(101)));
+        assert_eq!(callback.market.lock().unwrap()[0].symbol(), &aapl);
+    }
+
+    #[tokio::test]
+    async fn test_trade_stream_reconnects_and_keeps_routing_fills() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let order_id = Uuid::new_v4();
+
+        // Each connection authorizes, acknowledges `listen`, sends one fill,
+        // and then drops the socket to force a reconnect.
+        let server = tokio::spawn(async move {
+            let frames = [
+                trade_update_fixture(
+                    &order_id.to_string(),
+                    "partial_fill",
+                    "partially_filled",
+                    "4",
+                ),
+                trade_update_fixture(&order_id.to_string(), "fill", "filled", "6"),
+            ];
+            let mut received = Vec::new();
+            for frame in frames {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let auth = socket.next().await.unwrap().unwrap();
+                received.push(auth.into_text().unwrap().to_string());
+                socket
+                    .send(Message::Text(AUTHORIZED_FIXTURE.into()))
+                    .await
+                    .unwrap();
+                let listen = socket.next().await.unwrap().unwrap();
+                received.push(listen.into_text().unwrap().to_string());
+                socket.send(Message::Text(frame.into())).await.unwrap();
+            }
+            received
+        });
+
+        let mut config = fixture_config();
+        config.stream_trade_updates = true;
+        config.endpoints.trading_stream = format!("ws://{}", address);
+        config.reconnect_initial_delay_ms = 10;
+        config.reconnect_max_delay_ms = 50;
+        let callback = Arc::new(RecordingCallback::default());
+        let transport = FixtureTransport::new(vec![response(200, ACCOUNT_FIXTURE)]);
+        let mut broker =
+            AlpacaBroker::with_transport(config, transport).with_callback(callback.clone());
+        broker.connect().await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(5), server)
+            .await
+            .expect("server timed out")
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while callback.fills.lock().unwrap().len() < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("fills not routed");
+        broker.disconnect().await.unwrap();
+
+        let auth: Value = serde_json::from_str(&received[0]).unwrap();
+        assert_eq!(auth["action"], "auth");
+        assert_eq!(auth["key"], "key");
+        let listen: Value = serde_json::from_str(&received[1]).unwrap();
+        assert_eq!(listen["data"]["streams"][0], "trade_updates");
+
+        let fills = callback.fills.lock().unwrap().clone();
+        assert_eq!(fills[0].quantity, dec!(4));
+        assert_eq!(fills[1].quantity, dec!(6));
+        let connection = callback.connection.lock().unwrap().clone();
+        assert!(connection.contains(&ConnectionStatus::Reconnecting));
+        assert_eq!(connection.last(), Some(&ConnectionStatus::Disconnected));
+        assert_eq!(broker.connection_status(), ConnectionStatus::Disconnected);
+    }
+}
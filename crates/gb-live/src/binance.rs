@@ -0,0 +1,2491 @@
+//! Binance spot brokerage adapter.
+//!
+//! [`BinanceBroker`] implements [`Broker`] over Binance's signed REST API and
+//! its websocket streams: the user-data stream turns `executionReport`
+//! events into order-status changes and [`Fill`]s and keeps free/locked
+//! balances current, while the public market stream delivers book tickers
+//! and closed klines as [`MarketEvent`]s through the registered
+//! [`BrokerCallback`].
+//!
+//! Signed requests are HMAC-SHA256 signed with a timestamp corrected by the
+//! measured server-time offset, and order quantities / prices are rounded to
+//! the market's `LOT_SIZE` and `PRICE_FILTER` before submission.  GlowBack
+//! crypto symbols use the `BASE-QUOTE` form shared with the data providers,
+//! so `BTC-USD` trades as `BTCUSDT` (see [`BinanceConfig::usd_quote_asset`]).
+
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use futures_util::{SinkExt, StreamExt};
+use gb_data::symbols::ProviderSymbols;
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::{Fill, Order, OrderId, OrderStatus, OrderType, Side, TimeInForce};
+use hmac::{Hmac, Mac};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::broker::{
+    backoff_delay, read_lock, write_lock, AccountBalance, Broker, BrokerCallback, BrokerError,
+    BrokerPosition, BrokerResult, ClientOrderIds, ConnectionStatus, HttpMethod,
+};
+
+const MAINNET_REST_URL: &str = "https://api.binance.com";
+const TESTNET_REST_URL: &str = "https://testnet.binance.vision";
+const MAINNET_STREAM_URL: &str = "wss://stream.binance.com:9443/ws";
+const TESTNET_STREAM_URL: &str = "wss://stream.testnet.binance.vision/ws";
+
+/// Back-off used for HTTP 418/429 responses without a `Retry-After` header.
+const DEFAULT_RATE_LIMIT_BACKOFF_MS: u64 = 1_000;
+
+/// Listen keys expire after 60 minutes without a keep-alive.
+const LISTEN_KEY_KEEPALIVE: Duration = Duration::from_secs(30 * 60);
+
+/// Quote assets recognised when splitting an exchange ticker such as
+/// `ETHBTC`; longer codes first so `FDUSD` wins over `USD`-like suffixes.
+const KNOWN_QUOTE_ASSETS: [&str; 9] = [
+    "FDUSD", "USDT", "USDC", "TUSD", "BTC", "ETH", "BNB", "EUR", "TRY",
+];
+
+/// Which Binance deployment the broker trades against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BinanceEnvironment {
+    Mainnet,
+    Testnet,
+}
+
+/// Endpoints used by [`BinanceBroker`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinanceEndpoints {
+    /// Spot REST API base url (no trailing slash).
+    pub rest: String,
+    /// Raw websocket stream base url; user-data streams are opened at
+    /// `{stream}/{listenKey}`.
+    pub stream: String,
+}
+
+impl BinanceEndpoints {
+    pub fn for_environment(environment: BinanceEnvironment) -> Self {
+        let (rest, stream) = match environment {
+            BinanceEnvironment::Mainnet => (MAINNET_REST_URL, MAINNET_STREAM_URL),
+            BinanceEnvironment::Testnet => (TESTNET_REST_URL, TESTNET_STREAM_URL),
+        };
+        Self {
+            rest: rest.to_string(),
+            stream: stream.to_string(),
+        }
+    }
+}
+
+/// Configuration for [`BinanceBroker`].
+#[derive(Clone, PartialEq)]
+pub struct BinanceConfig {
+    pub api_key: String,
+    pub api_secret: String,
+    pub environment: BinanceEnvironment,
+    pub endpoints: BinanceEndpoints,
+    /// Exchange asset that GlowBack's `-USD` pairs trade against (`USDT` by
+    /// default, matching the data providers' `BTC-USD` ↔ `BTCUSDT` mapping).
+    /// Its balance is reported as cash.
+    pub usd_quote_asset: String,
+    /// `recvWindow` sent with signed requests, in milliseconds.
+    pub recv_window_ms: u64,
+    /// Whether `connect` opens the user-data stream for fills and balances.
+    pub stream_user_data: bool,
+    /// How many times a request answered with HTTP 418/429 is retried before
+    /// surfacing [`BrokerError::RateLimited`].
+    pub max_rate_limit_retries: u32,
+    /// Per-request HTTP timeout in milliseconds.
+    pub request_timeout_ms: u64,
+    /// First websocket reconnect delay; doubles on each failed attempt.
+    pub reconnect_initial_delay_ms: u64,
+    /// Upper bound on the websocket reconnect delay.
+    pub reconnect_max_delay_ms: u64,
+    /// Exchange tickers for symbols the `usd_quote_asset` convention does
+    /// not spell; only its aliases are consulted.
+    pub symbols: ProviderSymbols,
+}
+
+impl BinanceConfig {
+    pub fn new(
+        api_key: impl Into<String>,
+        api_secret: impl Into<String>,
+        environment: BinanceEnvironment,
+    ) -> Self {
+        Self {
+            api_key: api_key.into(),
+            api_secret: api_secret.into(),
+            environment,
+            endpoints: BinanceEndpoints::for_environment(environment),
+            usd_quote_asset: "USDT".to_string(),
+            recv_window_ms: 5_000,
+            stream_user_data: true,
+            max_rate_limit_retries: 3,
+            request_timeout_ms: 10_000,
+            reconnect_initial_delay_ms: 500,
+            reconnect_max_delay_ms: 30_000,
+            symbols: ProviderSymbols::default(),
+        }
+    }
+
+    /// Build a config from `BINANCE_API_KEY`, `BINANCE_API_SECRET`, and the
+    /// optional `BINANCE_ENVIRONMENT` (`testnet` (default) or `mainnet`).
+    pub fn from_env() -> BrokerResult<Self> {
+        let key = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+                .ok_or_else(|| BrokerError::AuthenticationFailed {
+                    message: format!("{} is not set", name),
+                })
+        };
+        let api_key = key("BINANCE_API_KEY")?;
+        let api_secret = key("BINANCE_API_SECRET")?;
+        let environment = match std::env::var("BINANCE_ENVIRONMENT")
+            .unwrap_or_default()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "" | "testnet" => BinanceEnvironment::Testnet,
+            "mainnet" => BinanceEnvironment::Mainnet,
+            other => {
+                return Err(BrokerError::Internal {
+                    message: format!("unknown BINANCE_ENVIRONMENT '{}'", other),
+                })
+            }
+        };
+        Ok(Self::new(api_key, api_secret, environment))
+    }
+
+    fn reconnect_delay(&self, attempt: u32) -> Duration {
+        backoff_delay(
+            self.reconnect_initial_delay_ms,
+            self.reconnect_max_delay_ms,
+            attempt,
+        )
+    }
+}
+
+impl fmt::Debug for BinanceConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BinanceConfig")
+            .field("api_key", &self.api_key)
+            .field("api_secret", &"<redacted>")
+            .field("environment", &self.environment)
+            .field("endpoints", &self.endpoints)
+            .field("usd_quote_asset", &self.usd_quote_asset)
+            .field("recv_window_ms", &self.recv_window_ms)
+            .field("stream_user_data", &self.stream_user_data)
+            .field("max_rate_limit_retries", &self.max_rate_limit_retries)
+            .field("request_timeout_ms", &self.request_timeout_ms)
+            .field(
+                "reconnect_initial_delay_ms",
+                &self.reconnect_initial_delay_ms,
+            )
+            .field("reconnect_max_delay_ms", &self.reconnect_max_delay_ms)
+            .field("symbols", &self.symbols)
+            .finish()
+    }
+}
+
+/// HMAC-SHA256 signature of a query string, hex encoded, as required for
+/// Binance `SIGNED` endpoints.
+pub fn sign_query(secret: &str, query: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(query.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+// ---------------------------------------------------------------------------
+// HTTP transport
+// ---------------------------------------------------------------------------
+
+/// A request against the spot REST API. `query` is the full (already
+/// signed, if needed) query string without the leading `?`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinanceRequest {
+    pub method: HttpMethod,
+    pub path: String,
+    pub query: String,
+}
+
+/// Raw response from the spot REST API.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinanceResponse {
+    pub status: u16,
+    pub body: String,
+    /// Suggested wait from the `Retry-After` header.
+    pub retry_after_ms: Option<u64>,
+}
+
+impl BinanceResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    fn json(&self) -> BrokerResult<Value> {
+        serde_json::from_str(&self.body)
+            .map_err(|e| payload_error(format!("invalid JSON response: {}", e)))
+    }
+
+    /// Binance error `code` and `msg`, falling back to the raw body.
+    fn error(&self) -> (Option<i64>, String) {
+        let value = serde_json::from_str::<Value>(&self.body).ok();
+        let code = value
+            .as_ref()
+            .and_then(|v| v.get("code"))
+            .and_then(Value::as_i64);
+        let message = value
+            .as_ref()
+            .and_then(|v| v.get("msg"))
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| self.body.trim().to_string());
+        (code, message)
+    }
+}
+
+/// Sends requests to the spot REST API. Implementations attach the
+/// `X-MBX-APIKEY` header.
+#[async_trait]
+pub trait BinanceTransport: Send + Sync {
+    async fn send(&self, request: &BinanceRequest) -> BrokerResult<BinanceResponse>;
+}
+
+/// [`BinanceTransport`] backed by `reqwest`.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: String,
+}
+
+impl HttpTransport {
+    pub fn new(config: &BinanceConfig) -> BrokerResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_millis(config.request_timeout_ms))
+            .build()
+            .map_err(|e| BrokerError::Internal {
+                message: format!("failed to build HTTP client: {}", e),
+            })?;
+        Ok(Self {
+            client,
+            base_url: config.endpoints.rest.trim_end_matches('/').to_string(),
+            api_key: config.api_key.clone(),
+        })
+    }
+}
+
+#[async_trait]
+impl BinanceTransport for HttpTransport {
+    async fn send(&self, request: &BinanceRequest) -> BrokerResult<BinanceResponse> {
+        let method = match request.method {
+            HttpMethod::Get => reqwest::Method::GET,
+            HttpMethod::Post => reqwest::Method::POST,
+            HttpMethod::Put => reqwest::Method::PUT,
+            HttpMethod::Delete => reqwest::Method::DELETE,
+        };
+        let mut url = format!("{}{}", self.base_url, request.path);
+        if !request.query.is_empty() {
+            url = format!("{}?{}", url, request.query);
+        }
+        let response = self
+            .client
+            .request(method, url)
+            .header("X-MBX-APIKEY", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| BrokerError::Internal {
+                message: format!("Binance request failed: {}", e),
+            })?;
+        let status = response.status().as_u16();
+        let retry_after_ms = response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(|seconds| seconds * 1_000);
+        let body = response.text().await.map_err(|e| BrokerError::Internal {
+            message: format!("failed to read Binance response: {}", e),
+        })?;
+        Ok(BinanceResponse {
+            status,
+            body,
+            retry_after_ms,
+        })
+    }
+}
+
+/// Map a non-2xx spot API response into a typed [`BrokerError`].
+///
+/// `order_id` is set for order-scoped calls so that filter failures and
+/// unknown-order codes are reported against the order.
+pub fn map_error_response(response: &BinanceResponse, order_id: Option<OrderId>) -> BrokerError {
+    let (code, message) = response.error();
+    let not_found = || BrokerError::OrderNotFound {
+        order_id: order_id.map(|id| id.to_string()).unwrap_or_default(),
+    };
+    match (response.status, code) {
+        (418 | 429, _) => BrokerError::RateLimited {
+            retry_after_ms: response
+                .retry_after_ms
+                .unwrap_or(DEFAULT_RATE_LIMIT_BACKOFF_MS),
+        },
+        (401, _) | (_, Some(-2014 | -2015 | -1022)) => {
+            BrokerError::AuthenticationFailed { message }
+        }
+        (_, Some(-2013)) => not_found(),
+        // CANCEL_REJECTED is also used for "Unknown order sent."
+        (_, Some(-2011)) if message.to_ascii_lowercase().contains("unknown order") => not_found(),
+        (_, Some(-2010 | -2011 | -1013 | -1111 | -1100)) => {
+            BrokerError::OrderRejected { reason: message }
+        }
+        (400, _) if order_id.is_some() => BrokerError::OrderRejected { reason: message },
+        (status, code) => BrokerError::Internal {
+            message: format!(
+                "Binance HTTP {} (code {}): {}",
+                status,
+                code.map(|c| c.to_string()).unwrap_or_else(|| "-".into()),
+                message
+            ),
+        },
+    }
+}
+
+/// Whether Binance refused an order as a duplicate of an open order with the
+/// same client id, i.e. an earlier submission of it was accepted.
+fn is_duplicate_order(error: &BrokerError) -> bool {
+    matches!(
+        error,
+        BrokerError::OrderRejected { reason }
+            if reason.to_ascii_lowercase().contains("duplicate order")
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Payload mapping
+// ---------------------------------------------------------------------------
+
+fn payload_error(message: impl Into<String>) -> BrokerError {
+    BrokerError::Internal {
+        message: format!("malformed Binance payload: {}", message.into()),
+    }
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> BrokerResult<&'a str> {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| payload_error(format!("missing string field '{}'", key)))
+}
+
+fn decimal_field(value: &Value, key: &str) -> BrokerResult<Decimal> {
+    let raw = value
+        .get(key)
+        .ok_or_else(|| payload_error(format!("missing decimal field '{}'", key)))?;
+    let text = match raw {
+        Value::String(text) => text.clone(),
+        Value::Number(number) => number.to_string(),
+        _ => return Err(payload_error(format!("field '{}' is not a decimal", key))),
+    };
+    text.parse::<Decimal>()
+        .or_else(|_| Decimal::from_scientific(&text))
+        .map_err(|_| payload_error(format!("field '{}' is not a decimal", key)))
+}
+
+fn millis_field(value: &Value, key: &str) -> Option<DateTime<Utc>> {
+    value
+        .get(key)
+        .and_then(Value::as_i64)
+        .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+}
+
+fn parse_side(code: &str) -> BrokerResult<Side> {
+    match code {
+        "BUY" => Ok(Side::Buy),
+        "SELL" => Ok(Side::Sell),
+        other => Err(payload_error(format!("unknown side '{}'", other))),
+    }
+}
+
+fn side_code(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "BUY",
+        Side::Sell => "SELL",
+    }
+}
+
+fn parse_order_status(code: &str) -> BrokerResult<OrderStatus> {
+    match code {
+        "NEW" | "PENDING_NEW" | "PENDING_CANCEL" => Ok(OrderStatus::Submitted),
+        "PARTIALLY_FILLED" => Ok(OrderStatus::PartiallyFilled),
+        "FILLED" => Ok(OrderStatus::Filled),
+        "CANCELED" => Ok(OrderStatus::Canceled),
+        "REJECTED" => Ok(OrderStatus::Rejected),
+        "EXPIRED" | "EXPIRED_IN_MATCH" => Ok(OrderStatus::Expired),
+        other => Err(payload_error(format!("unknown order status '{}'", other))),
+    }
+}
+
+/// Exchange ticker for a GlowBack symbol: `BTC-USD` → `BTCUSDT` (using
+/// `usd_quote_asset`), `ETH-BTC` → `ETHBTC`, and bare tickers unchanged.
+pub fn to_binance_symbol(symbol: &Symbol, usd_quote_asset: &str) -> String {
+    let raw = symbol.symbol.to_ascii_uppercase();
+    match raw.split_once(['-', '/']) {
+        Some((base, "USD")) => format!("{}{}", base, usd_quote_asset),
+        Some((base, quote)) => format!("{}{}", base, quote),
+        None => raw,
+    }
+}
+
+/// Inverse of [`to_binance_symbol`] for tickers the caller has not
+/// registered: `BTCUSDT` → `BTC-USD`, `ETHBTC` → `ETH-BTC`.
+pub fn from_binance_symbol(raw: &str, usd_quote_asset: &str) -> Symbol {
+    let split = |quote: &str| {
+        raw.strip_suffix(quote)
+            .filter(|base| !base.is_empty())
+            .map(str::to_string)
+    };
+    if let Some(base) = split(usd_quote_asset) {
+        return Symbol::crypto(&format!("{}-USD", base));
+    }
+    KNOWN_QUOTE_ASSETS
+        .iter()
+        .find_map(|quote| split(quote).map(|base| Symbol::crypto(&format!("{}-{}", base, quote))))
+        .unwrap_or_else(|| Symbol::crypto(raw))
+}
+
+/// Trading rules for one market, taken from `exchangeInfo` filters. A zero
+/// bound means the filter does not constrain that side.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolFilters {
+    pub base_asset: String,
+    pub quote_asset: String,
+    pub tick_size: Decimal,
+    pub min_price: Decimal,
+    pub max_price: Decimal,
+    pub step_size: Decimal,
+    pub min_qty: Decimal,
+    pub max_qty: Decimal,
+    pub min_notional: Decimal,
+}
+
+fn round_to_step(value: Decimal, step: Decimal, round_up: bool) -> Decimal {
+    if step <= Decimal::ZERO {
+        return value;
+    }
+    let units = value / step;
+    let units = if round_up {
+        units.ceil()
+    } else {
+        units.floor()
+    };
+    (units * step).normalize()
+}
+
+impl SymbolFilters {
+    /// Parse a `symbols[]` entry of `GET /api/v3/exchangeInfo`.
+    pub fn from_exchange_info(value: &Value) -> BrokerResult<Self> {
+        let mut filters = Self {
+            base_asset: str_field(value, "baseAsset")?.to_string(),
+            quote_asset: str_field(value, "quoteAsset")?.to_string(),
+            tick_size: Decimal::ZERO,
+            min_price: Decimal::ZERO,
+            max_price: Decimal::ZERO,
+            step_size: Decimal::ZERO,
+            min_qty: Decimal::ZERO,
+            max_qty: Decimal::ZERO,
+            min_notional: Decimal::ZERO,
+        };
+        for filter in value
+            .get("filters")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+        {
+            match filter.get("filterType").and_then(Value::as_str) {
+                Some("PRICE_FILTER") => {
+                    filters.tick_size = decimal_field(filter, "tickSize")?;
+                    filters.min_price = decimal_field(filter, "minPrice")?;
+                    filters.max_price = decimal_field(filter, "maxPrice")?;
+                }
+                Some("LOT_SIZE") => {
+                    filters.step_size = decimal_field(filter, "stepSize")?;
+                    filters.min_qty = decimal_field(filter, "minQty")?;
+                    filters.max_qty = decimal_field(filter, "maxQty")?;
+                }
+                Some("NOTIONAL") | Some("MIN_NOTIONAL") => {
+                    filters.min_notional = decimal_field(filter, "minNotional")?;
+                }
+                _ => {}
+            }
+        }
+        Ok(filters)
+    }
+
+    /// Quantity rounded down to `stepSize` (never buys or sells more than
+    /// requested).
+    pub fn round_quantity(&self, quantity: Decimal) -> Decimal {
+        round_to_step(quantity, self.step_size, false)
+    }
+
+    /// Price rounded to `tickSize` in the direction that never makes the
+    /// order more aggressive: buys round down, sells round up.
+    pub fn round_price(&self, price: Decimal, side: Side) -> Decimal {
+        round_to_step(price, self.tick_size, side == Side::Sell)
+    }
+
+    /// Round an order's quantity and limit price and check them against the
+    /// filters. `reference_price` (the latest mark) is used for the notional
+    /// check of market orders.
+    pub fn apply(
+        &self,
+        order: &Order,
+        reference_price: Option<Decimal>,
+    ) -> BrokerResult<(Decimal, Option<Decimal>)> {
+        let reject = |reason: String| Err(BrokerError::OrderRejected { reason });
+        let quantity = self.round_quantity(order.quantity);
+        if quantity <= Decimal::ZERO || quantity < self.min_qty {
+            return reject(format!(
+                "quantity {} rounds to {} (step {}), below LOT_SIZE minimum {}",
+                order.quantity, quantity, self.step_size, self.min_qty
+            ));
+        }
+        if self.max_qty > Decimal::ZERO && quantity > self.max_qty {
+            return reject(format!(
+                "quantity {} exceeds LOT_SIZE maximum {}",
+                quantity, self.max_qty
+            ));
+        }
+
+        let price = match &order.order_type {
+            OrderType::Limit { price } => Some(self.round_price(*price, order.side)),
+            _ => None,
+        };
+        if let Some(price) = price {
+            if price <= Decimal::ZERO || price < self.min_price {
+                return reject(format!(
+                    "price {} is below PRICE_FILTER minimum {}",
+                    price, self.min_price
+                ));
+            }
+            if self.max_price > Decimal::ZERO && price > self.max_price {
+                return reject(format!(
+                    "price {} exceeds PRICE_FILTER maximum {}",
+                    price, self.max_price
+                ));
+            }
+        }
+
+        if let Some(notional_price) = price.or(reference_price) {
+            let notional = quantity * notional_price;
+            if notional < self.min_notional {
+                return reject(format!(
+                    "notional {} is below minimum {}",
+                    notional, self.min_notional
+                ));
+            }
+        }
+        Ok((quantity, price))
+    }
+}
+
+/// Free and locked amounts of one asset.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct AssetBalance {
+    pub free: Decimal,
+    pub locked: Decimal,
+}
+
+impl AssetBalance {
+    pub fn total(&self) -> Decimal {
+        self.free + self.locked
+    }
+}
+
+fn balances_from_account(value: &Value) -> BrokerResult<HashMap<String, AssetBalance>> {
+    value
+        .get("balances")
+        .and_then(Value::as_array)
+        .ok_or_else(|| payload_error("account without balances"))?
+        .iter()
+        .map(|balance| {
+            Ok((
+                str_field(balance, "asset")?.to_string(),
+                AssetBalance {
+                    free: decimal_field(balance, "free")?,
+                    locked: decimal_field(balance, "locked")?,
+                },
+            ))
+        })
+        .collect()
+}
+
+/// Convert a REST order object (`GET /api/v3/order`, `openOrders`).
+///
+/// Orders not placed through GlowBack carry a non-UUID `clientOrderId` and
+/// receive a fresh id; the exchange order id is kept in
+/// `metadata.binance_order_id`.
+fn order_from_binance(value: &Value, symbol: Symbol) -> BrokerResult<Order> {
+    let side = parse_side(str_field(value, "side")?)?;
+    let order_type = match str_field(value, "type")? {
+        "MARKET" => OrderType::Market,
+        "LIMIT" | "LIMIT_MAKER" => OrderType::Limit {
+            price: decimal_field(value, "price")?,
+        },
+        other => return Err(payload_error(format!("unsupported order type '{}'", other))),
+    };
+    let quantity = decimal_field(value, "origQty")?;
+    let filled = decimal_field(value, "executedQty")?;
+
+    let mut order = Order::new(symbol, side, quantity, order_type, String::new());
+    order.id = value
+        .get("clientOrderId")
+        .and_then(Value::as_str)
+        .and_then(|raw| Uuid::parse_str(raw).ok())
+        .unwrap_or_else(Uuid::new_v4);
+    order.time_in_force = match value.get("timeInForce").and_then(Value::as_str) {
+        Some("IOC") => TimeInForce::IOC,
+        Some("FOK") => TimeInForce::FOK,
+        _ => TimeInForce::GTC,
+    };
+    order.status = parse_order_status(str_field(value, "status")?)?;
+    if let Some(submitted_at) = millis_field(value, "time").or(millis_field(value, "transactTime"))
+    {
+        order.submitted_at = submitted_at;
+    }
+    order.filled_quantity = filled;
+    order.remaining_quantity = (quantity - filled).max(Decimal::ZERO);
+    if filled > Decimal::ZERO {
+        order.average_fill_price = Some(decimal_field(value, "cummulativeQuoteQty")? / filled);
+    }
+    order.metadata =
+        json!({ "binance_order_id": value.get("orderId").cloned().unwrap_or(Value::Null) });
+    Ok(order)
+}
+
+// ---------------------------------------------------------------------------
+// Stream decoding
+// ---------------------------------------------------------------------------
+
+/// An `executionReport` from the user-data stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinanceExecutionReport {
+    pub order_id: OrderId,
+    /// Exchange ticker (`BTCUSDT`).
+    pub symbol: String,
+    pub side: Side,
+    pub status: OrderStatus,
+    /// Binance execution type (`NEW`, `TRADE`, `CANCELED`, ...).
+    pub execution_type: String,
+    pub last_quantity: Decimal,
+    pub last_price: Decimal,
+    pub commission: Decimal,
+    pub commission_asset: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Event decoded from a Binance websocket frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinanceStreamEvent {
+    Execution(BinanceExecutionReport),
+    /// `outboundAccountPosition`: new free/locked amounts per asset.
+    Balances(HashMap<String, AssetBalance>),
+    Market(MarketEvent),
+    /// The listen key expired; the user-data stream must reconnect.
+    ListenKeyExpired,
+}
+
+/// Decode a frame from the user-data or market stream. Frames from combined
+/// streams (`{"stream": ..., "data": ...}`) are unwrapped first.
+pub fn parse_stream_message(
+    text: &str,
+    usd_quote_asset: &str,
+) -> BrokerResult<Vec<BinanceStreamEvent>> {
+    let value: Value = serde_json::from_str(text)
+        .map_err(|e| payload_error(format!("invalid stream frame: {}", e)))?;
+    let data = value.get("data").unwrap_or(&value);
+
+    let event = match data.get("e").and_then(Value::as_str) {
+        Some("executionReport") => {
+            // Cancels carry the original id in `C`; `c` is the cancel request id.
+            let client_id = data
+                .get("C")
+                .and_then(Value::as_str)
+                .filter(|id| !id.is_empty())
+                .map_or_else(|| str_field(data, "c"), Ok)?;
+            let Ok(order_id) = Uuid::parse_str(client_id) else {
+                // Not a GlowBack order (e.g. placed manually on the exchange).
+                return Ok(Vec::new());
+            };
+            BinanceStreamEvent::Execution(BinanceExecutionReport {
+                order_id,
+                symbol: str_field(data, "s")?.to_string(),
+                side: parse_side(str_field(data, "S")?)?,
+                status: parse_order_status(str_field(data, "X")?)?,
+                execution_type: str_field(data, "x")?.to_string(),
+                last_quantity: decimal_field(data, "l")?,
+                last_price: decimal_field(data, "L")?,
+                commission: decimal_field(data, "n")?,
+                commission_asset: data.get("N").and_then(Value::as_str).map(str::to_string),
+                timestamp: millis_field(data, "T")
+                    .or(millis_field(data, "E"))
+                    .unwrap_or_else(Utc::now),
+            })
+        }
+        Some("outboundAccountPosition") => {
+            let balances = data
+                .get("B")
+                .and_then(Value::as_array)
+                .ok_or_else(|| payload_error("account update without balances"))?
+                .iter()
+                .map(|balance| {
+                    Ok((
+                        str_field(balance, "a")?.to_string(),
+                        AssetBalance {
+                            free: decimal_field(balance, "f")?,
+                            locked: decimal_field(balance, "l")?,
+                        },
+                    ))
+                })
+                .collect::<BrokerResult<_>>()?;
+            BinanceStreamEvent::Balances(balances)
+        }
+        Some("listenKeyExpired") => BinanceStreamEvent::ListenKeyExpired,
+        Some("kline") => {
+            let kline = data
+                .get("k")
+                .ok_or_else(|| payload_error("kline without 'k'"))?;
+            // Only closed klines are complete bars.
+            if kline.get("x").and_then(Value::as_bool) != Some(true) {
+                return Ok(Vec::new());
+            }
+            let resolution = match kline.get("i").and_then(Value::as_str) {
+                Some("1s") => Resolution::Second,
+                Some("5m") => Resolution::FiveMinute,
+                Some("15m") => Resolution::FifteenMinute,
+                Some("1h") => Resolution::Hour,
+                Some("4h") => Resolution::FourHour,
+                Some("1d") => Resolution::Day,
+                _ => Resolution::Minute,
+            };
+            BinanceStreamEvent::Market(MarketEvent::Bar(Bar::new(
+                from_binance_symbol(str_field(kline, "s")?, usd_quote_asset),
+                millis_field(kline, "t").ok_or_else(|| payload_error("kline without open time"))?,
+                decimal_field(kline, "o")?,
+                decimal_field(kline, "h")?,
+                decimal_field(kline, "l")?,
+                decimal_field(kline, "c")?,
+                decimal_field(kline, "v")?,
+                resolution,
+            )))
+        }
+        // Book ticker frames have no event type but carry best bid/ask.
+        None if data.get("b").is_some() && data.get("a").is_some() && data.get("s").is_some() => {
+            BinanceStreamEvent::Market(MarketEvent::Quote {
+                symbol: from_binance_symbol(str_field(data, "s")?, usd_quote_asset),
+                timestamp: millis_field(data, "E").unwrap_or_else(Utc::now),
+                bid: decimal_field(data, "b")?,
+                ask: decimal_field(data, "a")?,
+                bid_size: decimal_field(data, "B")?,
+                ask_size: decimal_field(data, "A")?,
+            })
+        }
+        _ => return Ok(Vec::new()),
+    };
+    Ok(vec![event])
+}
+
+// ---------------------------------------------------------------------------
+// Shared state between the broker and its stream tasks
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+struct TrackedOrder {
+    symbol: Symbol,
+    strategy_id: String,
+}
+
+#[derive(Debug)]
+struct SharedState {
+    usd_quote_asset: String,
+    symbols: ProviderSymbols,
+    status: RwLock<ConnectionStatus>,
+    /// Server time minus local time, in milliseconds.
+    time_offset_ms: AtomicI64,
+    prices: RwLock<HashMap<Symbol, Decimal>>,
+    orders: RwLock<HashMap<OrderId, TrackedOrder>>,
+    client_ids: ClientOrderIds,
+    subscriptions: RwLock<Vec<Symbol>>,
+    balances: RwLock<HashMap<String, AssetBalance>>,
+}
+
+impl SharedState {
+    fn new(usd_quote_asset: &str, symbols: ProviderSymbols) -> Self {
+        Self {
+            usd_quote_asset: usd_quote_asset.to_string(),
+            symbols,
+            status: RwLock::new(ConnectionStatus::Disconnected),
+            time_offset_ms: AtomicI64::new(0),
+            prices: RwLock::new(HashMap::new()),
+            orders: RwLock::new(HashMap::new()),
+            client_ids: ClientOrderIds::default(),
+            subscriptions: RwLock::new(Vec::new()),
+            balances: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn ticker(&self, symbol: &Symbol) -> String {
+        match self.symbols.alias(symbol) {
+            Some(alias) => alias.to_string(),
+            None => to_binance_symbol(symbol, &self.usd_quote_asset),
+        }
+    }
+
+    /// Map an exchange ticker onto the caller's own [`Symbol`] when it is
+    /// known from a subscription or a submitted order.
+    fn resolve_ticker(&self, ticker: &str) -> Symbol {
+        if let Some(known) = read_lock(&self.subscriptions)
+            .iter()
+            .find(|symbol| self.ticker(symbol) == ticker)
+        {
+            return known.clone();
+        }
+        read_lock(&self.orders)
+            .values()
+            .find(|tracked| self.ticker(&tracked.symbol) == ticker)
+            .map(|tracked| tracked.symbol.clone())
+            .unwrap_or_else(|| from_binance_symbol(ticker, &self.usd_quote_asset))
+    }
+
+    fn timestamp_ms(&self) -> i64 {
+        Utc::now().timestamp_millis() + self.time_offset_ms.load(Ordering::Relaxed)
+    }
+
+    fn record_price(&self, event: &MarketEvent) {
+        let price = match event {
+            MarketEvent::Bar(bar) => bar.close,
+            MarketEvent::Tick(tick) => tick.price,
+            MarketEvent::Quote { bid, ask, .. } => (*bid + *ask) / Decimal::from(2),
+        };
+        write_lock(&self.prices).insert(event.symbol().clone(), price);
+    }
+
+    /// Latest USD(-quote) price of an asset, if a market for it is marked.
+    fn asset_price(&self, asset: &str) -> Option<Decimal> {
+        if asset == self.usd_quote_asset {
+            return Some(Decimal::ONE);
+        }
+        let ticker = format!("{}{}", asset, self.usd_quote_asset);
+        read_lock(&self.prices)
+            .iter()
+            .find(|(symbol, _)| self.ticker(symbol) == ticker)
+            .map(|(_, price)| *price)
+    }
+
+    /// Commission of an execution expressed in the market's quote asset.
+    fn commission_in_quote(&self, report: &BinanceExecutionReport, symbol: &Symbol) -> Decimal {
+        let Some(asset) = report.commission_asset.as_deref() else {
+            return report.commission;
+        };
+        let ticker = self.ticker(symbol);
+        if report.commission.is_zero() || ticker.ends_with(asset) {
+            return report.commission;
+        }
+        if ticker.starts_with(asset) {
+            return report.commission * report.last_price;
+        }
+        match self.asset_price(asset) {
+            Some(price) => report.commission * price,
+            None => {
+                warn!(
+                    "no {} price to value commission of {} on {}; recording zero",
+                    asset, report.commission, ticker
+                );
+                Decimal::ZERO
+            }
+        }
+    }
+
+    fn account_balance(&self) -> AccountBalance {
+        let balances = read_lock(&self.balances);
+        let quote = balances
+            .get(&self.usd_quote_asset)
+            .copied()
+            .unwrap_or_default();
+        let holdings: Decimal = balances
+            .iter()
+            .filter(|(asset, _)| **asset != self.usd_quote_asset)
+            .filter_map(|(asset, balance)| Some(balance.total() * self.asset_price(asset)?))
+            .sum();
+        AccountBalance {
+            cash: quote.total(),
+            buying_power: quote.free,
+            equity: quote.total() + holdings,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Non-quote balances reported as positions. Spot balances carry no cost
+    /// basis, so `average_cost` and `unrealized_pnl` are zero.
+    fn positions(&self) -> Vec<BrokerPosition> {
+        let mut positions: Vec<BrokerPosition> = read_lock(&self.balances)
+            .iter()
+            .filter(|(asset, balance)| {
+                **asset != self.usd_quote_asset && !balance.total().is_zero()
+            })
+            .map(|(asset, balance)| {
+                let symbol = self.resolve_ticker(&format!("{}{}", asset, self.usd_quote_asset));
+                let price = self.asset_price(asset).unwrap_or(Decimal::ZERO);
+                BrokerPosition {
+                    symbol,
+                    quantity: balance.total(),
+                    market_value: balance.total() * price,
+                    average_cost: Decimal::ZERO,
+                    unrealized_pnl: Decimal::ZERO,
+                }
+            })
+            .collect();
+        positions.sort_by(|a, b| a.symbol.symbol.cmp(&b.symbol.symbol));
+        positions
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Websocket stream tasks
+// ---------------------------------------------------------------------------
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum StreamKind {
+    UserData,
+    Market,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StreamCommand {
+    Subscribe(Vec<String>),
+    Unsubscribe(Vec<String>),
+}
+
+/// Stream names for a ticker: best bid/ask plus one-minute klines.
+fn market_streams(tickers: &[String]) -> Vec<String> {
+    tickers
+        .iter()
+        .flat_map(|ticker| {
+            let ticker = ticker.to_ascii_lowercase();
+            [
+                format!("{}@bookTicker", ticker),
+                format!("{}@kline_1m", ticker),
+            ]
+        })
+        .collect()
+}
+
+struct StreamHandle {
+    commands: mpsc::UnboundedSender<StreamCommand>,
+    task: JoinHandle<()>,
+}
+
+enum SessionEnd {
+    Shutdown,
+    Dropped,
+}
+
+struct StreamContext {
+    kind: StreamKind,
+    config: BinanceConfig,
+    transport: Arc<dyn BinanceTransport>,
+    shared: Arc<SharedState>,
+    callback: Option<Arc<dyn BrokerCallback>>,
+    request_ids: AtomicU64,
+}
+
+impl StreamContext {
+    async fn run(
+        self,
+        mut commands: mpsc::UnboundedReceiver<StreamCommand>,
+        mut shutdown: watch::Receiver<bool>,
+    ) {
+        let mut attempt: u32 = 0;
+        loop {
+            match self.stream_url().await {
+                Ok(url) => {
+                    let connection = tokio::select! {
+                        result = tokio_tungstenite::connect_async(url.as_str()) => result,
+                        _ = shutdown.changed() => return,
+                    };
+                    match connection {
+                        Ok((mut socket, _)) => {
+                            attempt = 0;
+                            let end = self
+                                .session(&mut socket, url, &mut commands, &mut shutdown)
+                                .await;
+                            if let SessionEnd::Shutdown = end {
+                                let _ = socket.close(None).await;
+                                return;
+                            }
+                        }
+                        Err(err) => warn!("Binance {:?} stream connect failed: {}", self.kind, err),
+                    }
+                }
+                Err(BrokerError::AuthenticationFailed { message }) => {
+                    warn!("Binance user-data stream refused: {}", message);
+                    self.set_status(ConnectionStatus::Disconnected).await;
+                    return;
+                }
+                Err(err) => warn!("Binance listen key request failed: {}", err),
+            }
+
+            if *shutdown.borrow() {
+                return;
+            }
+            self.set_status(ConnectionStatus::Reconnecting).await;
+            let delay = self.config.reconnect_delay(attempt);
+            attempt = attempt.saturating_add(1);
+            info!(
+                "reconnecting Binance {:?} stream in {} ms (attempt {})",
+                self.kind,
+                delay.as_millis(),
+                attempt
+            );
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = shutdown.changed() => return,
+            }
+        }
+    }
+
+    /// The user-data stream needs a fresh listen key per connection.
+    async fn stream_url(&self) -> BrokerResult<String> {
+        match self.kind {
+            StreamKind::Market => Ok(self.config.endpoints.stream.clone()),
+            StreamKind::UserData => {
+                let response = self
+                    .listen_key_request(HttpMethod::Post, String::new())
+                    .await?;
+                let listen_key = str_field(&response, "listenKey")?;
+                Ok(format!("{}/{}", self.config.endpoints.stream, listen_key))
+            }
+        }
+    }
+
+    async fn listen_key_request(&self, method: HttpMethod, query: String) -> BrokerResult<Value> {
+        let response = self
+            .transport
+            .send(&BinanceRequest {
+                method,
+                path: "/api/v3/userDataStream".to_string(),
+                query,
+            })
+            .await?;
+        if !response.is_success() {
+            return Err(map_error_response(&response, None));
+        }
+        response.json()
+    }
+
+    async fn session(
+        &self,
+        socket: &mut Socket,
+        url: String,
+        commands: &mut mpsc::UnboundedReceiver<StreamCommand>,
+        shutdown: &mut watch::Receiver<bool>,
+    ) -> SessionEnd {
+        if self.kind == StreamKind::Market {
+            let tickers: Vec<String> = read_lock(&self.shared.subscriptions)
+                .iter()
+                .map(|symbol| self.shared.ticker(symbol))
+                .collect();
+            if !tickers.is_empty()
+                && socket
+                    .send(Message::Text(
+                        self.command_message(&StreamCommand::Subscribe(tickers))
+                            .into(),
+                    ))
+                    .await
+                    .is_err()
+            {
+                return SessionEnd::Dropped;
+            }
+        }
+        self.set_status(ConnectionStatus::Connected).await;
+
+        let listen_key = url.rsplit('/').next().unwrap_or_default().to_string();
+        let mut keepalive = tokio::time::interval(LISTEN_KEY_KEEPALIVE);
+        keepalive.tick().await;
+
+        loop {
+            tokio::select! {
+                frame = socket.next() => {
+                    let text = match frame {
+                        Some(Ok(Message::Text(text))) => text.as_str().to_owned(),
+                        Some(Ok(Message::Binary(bytes))) => String::from_utf8_lossy(&bytes).into_owned(),
+                        Some(Ok(Message::Close(_))) | None => return SessionEnd::Dropped,
+                        Some(Ok(_)) => continue,
+                        Some(Err(err)) => {
+                            warn!("Binance {:?} stream error: {}", self.kind, err);
+                            return SessionEnd::Dropped;
+                        }
+                    };
+                    let events = match parse_stream_message(&text, &self.config.usd_quote_asset) {
+                        Ok(events) => events,
+                        Err(err) => {
+                            warn!("ignoring Binance {:?} frame: {}", self.kind, err);
+                            continue;
+                        }
+                    };
+                    for event in events {
+                        if event == BinanceStreamEvent::ListenKeyExpired {
+                            return SessionEnd::Dropped;
+                        }
+                        self.dispatch(event).await;
+                    }
+                }
+                _ = keepalive.tick(), if self.kind == StreamKind::UserData => {
+                    if let Err(err) = self
+                        .listen_key_request(HttpMethod::Put, format!("listenKey={}", listen_key))
+                        .await
+                    {
+                        warn!("Binance listen key keep-alive failed: {}", err);
+                    }
+                }
+                command = commands.recv() => {
+                    let Some(command) = command else {
+                        return SessionEnd::Shutdown;
+                    };
+                    if socket.send(Message::Text(self.command_message(&command).into())).await.is_err() {
+                        return SessionEnd::Dropped;
+                    }
+                }
+                _ = shutdown.changed() => return SessionEnd::Shutdown,
+            }
+        }
+    }
+
+    fn command_message(&self, command: &StreamCommand) -> String {
+        let (method, tickers) = match command {
+            StreamCommand::Subscribe(tickers) => ("SUBSCRIBE", tickers),
+            StreamCommand::Unsubscribe(tickers) => ("UNSUBSCRIBE", tickers),
+        };
+        json!({
+            "method": method,
+            "params": market_streams(tickers),
+            "id": self.request_ids.fetch_add(1, Ordering::Relaxed) + 1,
+        })
+        .to_string()
+    }
+
+    async fn set_status(&self, status: ConnectionStatus) {
+        // Only the user-data stream (fills) drives the broker's status.
+        if self.kind != StreamKind::UserData {
+            return;
+        }
+        let changed = {
+            let mut current = write_lock(&self.shared.status);
+            let changed = *current != status;
+            *current = status;
+            changed
+        };
+        if changed {
+            if let Some(callback) = &self.callback {
+                callback.on_connection_status(status).await;
+            }
+        }
+    }
+
+    async fn dispatch(&self, event: BinanceStreamEvent) {
+        match event {
+            BinanceStreamEvent::Execution(report) => {
+                let order_id = self.shared.client_ids.order_id(report.order_id);
+                let tracked = read_lock(&self.shared.orders).get(&order_id).cloned();
+                let (symbol, strategy_id) = match tracked {
+                    Some(tracked) => (tracked.symbol, tracked.strategy_id),
+                    None => (self.shared.resolve_ticker(&report.symbol), String::new()),
+                };
+                if let Some(callback) = &self.callback {
+                    callback.on_order_status(order_id, report.status).await;
+                }
+                if report.execution_type == "TRADE" && report.last_quantity > Decimal::ZERO {
+                    let mut fill = Fill::new(
+                        order_id,
+                        symbol.clone(),
+                        report.side,
+                        report.last_quantity,
+                        report.last_price,
+                        self.shared.commission_in_quote(&report, &symbol),
+                        strategy_id,
+                    );
+                    fill.executed_at = report.timestamp;
+                    if let Some(callback) = &self.callback {
+                        callback.on_fill(fill).await;
+                    }
+                }
+                if matches!(
+                    report.status,
+                    OrderStatus::Filled
+                        | OrderStatus::Canceled
+                        | OrderStatus::Rejected
+                        | OrderStatus::Expired
+                ) {
+                    write_lock(&self.shared.orders).remove(&order_id);
+                }
+            }
+            BinanceStreamEvent::Balances(balances) => {
+                write_lock(&self.shared.balances).extend(balances);
+            }
+            BinanceStreamEvent::Market(event) => {
+                let symbol = self
+                    .shared
+                    .resolve_ticker(&self.shared.ticker(event.symbol()));
+                let event = match event {
+                    MarketEvent::Bar(mut bar) => {
+                        bar.symbol = symbol;
+                        MarketEvent::Bar(bar)
+                    }
+                    MarketEvent::Quote {
+                        timestamp,
+                        bid,
+                        ask,
+                        bid_size,
+                        ask_size,
+                        ..
+                    } => MarketEvent::Quote {
+                        symbol,
+                        timestamp,
+                        bid,
+                        ask,
+                        bid_size,
+                        ask_size,
+                    },
+                    other => other,
+                };
+                self.shared.record_price(&event);
+                if let Some(callback) = &self.callback {
+                    callback.on_market_data(event).await;
+                }
+            }
+            BinanceStreamEvent::ListenKeyExpired => {}
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Broker
+// ---------------------------------------------------------------------------
+
+/// Broker adapter for Binance spot trading. Supports market and limit
+/// orders (GTC/IOC/FOK); orders are identified on the exchange by their
+/// GlowBack id sent as `newClientOrderId`.
+pub struct BinanceBroker {
+    config: BinanceConfig,
+    transport: Arc<dyn BinanceTransport>,
+    callback: Option<Arc<dyn BrokerCallback>>,
+    shared: Arc<SharedState>,
+    filters: HashMap<String, SymbolFilters>,
+    connected: bool,
+    shutdown: Option<watch::Sender<bool>>,
+    streams: HashMap<StreamKind, StreamHandle>,
+}
+
+impl BinanceBroker {
+    /// Create a broker that talks to Binance over HTTPS.
+    pub fn new(config: BinanceConfig) -> BrokerResult<Self> {
+        let transport = Arc::new(HttpTransport::new(&config)?);
+        Ok(Self::with_transport(config, transport))
+    }
+
+    /// Create a broker with a custom REST transport (e.g. recorded fixtures).
+    pub fn with_transport(config: BinanceConfig, transport: Arc<dyn BinanceTransport>) -> Self {
+        let shared = Arc::new(SharedState::new(
+            &config.usd_quote_asset,
+            config.symbols.clone(),
+        ));
+        Self {
+            config,
+            transport,
+            callback: None,
+            shared,
+            filters: HashMap::new(),
+            connected: false,
+            shutdown: None,
+            streams: HashMap::new(),
+        }
+    }
+
+    /// Register the receiver for fills, order-status changes, market data,
+    /// and connection-status changes coming off the websocket streams.
+    pub fn with_callback(mut self, callback: Arc<dyn BrokerCallback>) -> Self {
+        self.callback = Some(callback);
+        self
+    }
+
+    pub fn config(&self) -> &BinanceConfig {
+        &self.config
+    }
+
+    /// Free/locked amounts per asset, as of the last account fetch or
+    /// user-data stream update.
+    pub fn balances(&self) -> HashMap<String, AssetBalance> {
+        read_lock(&self.shared.balances).clone()
+    }
+
+    /// Measured server-time offset (server minus local) in milliseconds.
+    pub fn time_offset_ms(&self) -> i64 {
+        self.shared.time_offset_ms.load(Ordering::Relaxed)
+    }
+
+    fn ensure_connected(&self) -> BrokerResult<()> {
+        if self.connected {
+            Ok(())
+        } else {
+            Err(BrokerError::NotConnected)
+        }
+    }
+
+    /// Query string with `recvWindow`, `timestamp`, and `signature` appended.
+    fn signed_query(&self, params: &[(&str, String)], timestamp_ms: i64) -> String {
+        let mut query: Vec<String> = params
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        query.push(format!("recvWindow={}", self.config.recv_window_ms));
+        query.push(format!("timestamp={}", timestamp_ms));
+        let query = query.join("&");
+        let signature = sign_query(&self.config.api_secret, &query);
+        format!("{}&signature={}", query, signature)
+    }
+
+    async fn send_with_retry(&self, request: BinanceRequest) -> BrokerResult<BinanceResponse> {
+        let mut retries = 0;
+        loop {
+            let response = self.transport.send(&request).await?;
+            if !matches!(response.status, 418 | 429)
+                || retries >= self.config.max_rate_limit_retries
+            {
+                return Ok(response);
+            }
+            let wait_ms = response
+                .retry_after_ms
+                .unwrap_or(DEFAULT_RATE_LIMIT_BACKOFF_MS);
+            retries += 1;
+            warn!(
+                "Binance rate limit hit on {}; retry {}/{} in {} ms",
+                request.path, retries, self.config.max_rate_limit_retries, wait_ms
+            );
+            tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+        }
+    }
+
+    async fn public(&self, path: &str, query: String) -> BrokerResult<Value> {
+        let response = self
+            .send_with_retry(BinanceRequest {
+                method: HttpMethod::Get,
+                path: path.to_string(),
+                query,
+            })
+            .await?;
+        if !response.is_success() {
+            return Err(map_error_response(&response, None));
+        }
+        response.json()
+    }
+
+    /// Send a `SIGNED` request. A `-1021` (timestamp outside `recvWindow`)
+    /// answer triggers one server-time resync and retry.
+    async fn signed(
+        &self,
+        method: HttpMethod,
+        path: &str,
+        params: &[(&str, String)],
+        order_id: Option<OrderId>,
+    ) -> BrokerResult<Value> {
+        let mut resynced = false;
+        loop {
+            let response = self
+                .send_with_retry(BinanceRequest {
+                    method,
+                    path: path.to_string(),
+                    query: self.signed_query(params, self.shared.timestamp_ms()),
+                })
+                .await?;
+            if response.is_success() {
+                return response.json();
+            }
+            if response.error().0 == Some(-1021) && !resynced {
+                warn!("Binance rejected request timestamp; resyncing server time");
+                self.sync_time().await?;
+                resynced = true;
+                continue;
+            }
+            return Err(map_error_response(&response, order_id));
+        }
+    }
+
+    /// Measure the offset between Binance server time and the local clock,
+    /// assuming symmetric latency.
+    async fn sync_time(&self) -> BrokerResult<()> {
+        let before = Utc::now().timestamp_millis();
+        let response = self.public("/api/v3/time", String::new()).await?;
+        let after = Utc::now().timestamp_millis();
+        let server_time = response
+            .get("serverTime")
+            .and_then(Value::as_i64)
+            .ok_or_else(|| payload_error("missing serverTime"))?;
+        let offset = server_time - (before + after) / 2;
+        self.shared.time_offset_ms.store(offset, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn symbol_filters(&mut self, ticker: &str) -> BrokerResult<SymbolFilters> {
+        if let Some(filters) = self.filters.get(ticker) {
+            return Ok(filters.clone());
+        }
+        let info = self
+            .public("/api/v3/exchangeInfo", format!("symbol={}", ticker))
+            .await?;
+        let entry = info
+            .get("symbols")
+            .and_then(Value::as_array)
+            .and_then(|symbols| symbols.first())
+            .ok_or_else(|| BrokerError::OrderRejected {
+                reason: format!("unknown Binance market {}", ticker),
+            })?;
+        let filters = SymbolFilters::from_exchange_info(entry)?;
+        self.filters.insert(ticker.to_string(), filters.clone());
+        Ok(filters)
+    }
+
+    async fn refresh_balances(&self) -> BrokerResult<()> {
+        let account = self
+            .signed(
+                HttpMethod::Get,
+                "/api/v3/account",
+                &[("omitZeroBalances", "true".to_string())],
+                None,
+            )
+            .await?;
+        *write_lock(&self.shared.balances) = balances_from_account(&account)?;
+        Ok(())
+    }
+
+    /// Whether Binance holds an order placed under `order_id`'s client id.
+    async fn order_submitted(&self, ticker: &str, order_id: OrderId) -> BrokerResult<bool> {
+        let query = [
+            ("symbol", ticker.to_string()),
+            (
+                "origClientOrderId",
+                self.shared.client_ids.client_id(order_id).to_string(),
+            ),
+        ];
+        match self
+            .signed(HttpMethod::Get, "/api/v3/order", &query, Some(order_id))
+            .await
+        {
+            Ok(_) => Ok(true),
+            Err(BrokerError::OrderNotFound { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn track_order(&self, order_id: OrderId, order: Order) {
+        write_lock(&self.shared.orders).insert(
+            order_id,
+            TrackedOrder {
+                symbol: order.symbol,
+                strategy_id: order.strategy_id,
+            },
+        );
+    }
+
+    fn tracked_symbol(&self, order_id: OrderId) -> BrokerResult<Symbol> {
+        read_lock(&self.shared.orders)
+            .get(&order_id)
+            .map(|tracked| tracked.symbol.clone())
+            .ok_or_else(|| BrokerError::OrderNotFound {
+                order_id: order_id.to_string(),
+            })
+    }
+
+    fn spawn_stream(&mut self, kind: StreamKind) {
+        let Some(shutdown) = &self.shutdown else {
+            return;
+        };
+        if self.streams.contains_key(&kind) {
+            return;
+        }
+        let context = StreamContext {
+            kind,
+            config: self.config.clone(),
+            transport: Arc::clone(&self.transport),
+            shared: Arc::clone(&self.shared),
+            callback: self.callback.clone(),
+            request_ids: AtomicU64::new(0),
+        };
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let task = tokio::spawn(context.run(receiver, shutdown.subscribe()));
+        self.streams.insert(kind, StreamHandle { commands, task });
+    }
+
+    fn route_subscription(&mut self, symbols: &[Symbol], subscribe: bool) {
+        if !self.connected || symbols.is_empty() {
+            return;
+        }
+        let tickers: Vec<String> = symbols
+            .iter()
+            .map(|symbol| self.shared.ticker(symbol))
+            .collect();
+        match self.streams.get(&StreamKind::Market) {
+            Some(handle) => {
+                let command = if subscribe {
+                    StreamCommand::Subscribe(tickers)
+                } else {
+                    StreamCommand::Unsubscribe(tickers)
+                };
+                let _ = handle.commands.send(command);
+            }
+            // A new stream subscribes to the full set once connected.
+            None if subscribe => self.spawn_stream(StreamKind::Market),
+            None => {}
+        }
+    }
+}
+
+#[async_trait]
+impl Broker for BinanceBroker {
+    async fn connect(&mut self) -> BrokerResult<()> {
+        if self.connected {
+            return Ok(());
+        }
+        self.sync_time().await?;
+        // Validates the API key and loads balances before streams open.
+        self.refresh_balances().await?;
+
+        self.connected = true;
+        *write_lock(&self.shared.status) = ConnectionStatus::Connected;
+        let (shutdown, _) = watch::channel(false);
+        self.shutdown = Some(shutdown);
+        if self.config.stream_user_data {
+            self.spawn_stream(StreamKind::UserData);
+        }
+        let subscriptions = read_lock(&self.shared.subscriptions).clone();
+        self.route_subscription(&subscriptions, true);
+
+        if let Some(callback) = &self.callback {
+            callback
+                .on_connection_status(ConnectionStatus::Connected)
+                .await;
+        }
+        info!(
+            "Binance broker connected ({:?}, server time offset {} ms)",
+            self.config.environment,
+            self.time_offset_ms()
+        );
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> BrokerResult<()> {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(true);
+        }
+        for (_, handle) in self.streams.drain() {
+            let abort = handle.task.abort_handle();
+            if tokio::time::timeout(Duration::from_secs(2), handle.task)
+                .await
+                .is_err()
+            {
+                abort.abort();
+            }
+        }
+        let was_connected = self.connected;
+        self.connected = false;
+        *write_lock(&self.shared.status) = ConnectionStatus::Disconnected;
+        if was_connected {
+            if let Some(callback) = &self.callback {
+                callback
+                    .on_connection_status(ConnectionStatus::Disconnected)
+                    .await;
+            }
+        }
+        info!("Binance broker disconnected");
+        Ok(())
+    }
+
+    fn connection_status(&self) -> ConnectionStatus {
+        if self.connected {
+            *read_lock(&self.shared.status)
+        } else {
+            ConnectionStatus::Disconnected
+        }
+    }
+
+    async fn submit_order(&mut self, order: Order) -> BrokerResult<OrderId> {
+        self.ensure_connected()?;
+        let (kind, time_in_force) = match (&order.order_type, order.time_in_force) {
+            (OrderType::Market, _) => ("MARKET", None),
+            (OrderType::Limit { .. }, TimeInForce::Day) => {
+                return Err(BrokerError::OrderRejected {
+                    reason: "Binance spot has no DAY time in force; use GTC, IOC, or FOK"
+                        .to_string(),
+                })
+            }
+            (OrderType::Limit { .. }, tif) => (
+                "LIMIT",
+                Some(match tif {
+                    TimeInForce::IOC => "IOC",
+                    TimeInForce::FOK => "FOK",
+                    _ => "GTC",
+                }),
+            ),
+            (other, _) => {
+                return Err(BrokerError::OrderRejected {
+                    reason: format!("order type {:?} is not supported on Binance spot", other),
+                })
+            }
+        };
+
+        let ticker = self.shared.ticker(&order.symbol);
+        let (client_id, resent) = self.shared.client_ids.assign(&order);
+        let order_id = self.shared.client_ids.order_id(client_id);
+        if resent {
+            if read_lock(&self.shared.orders).contains_key(&order_id) {
+                return Ok(order_id);
+            }
+            // An earlier attempt may have been accepted before it failed.
+            if self.order_submitted(&ticker, order_id).await? {
+                self.track_order(order_id, order);
+                return Ok(order_id);
+            }
+        }
+        let filters = self.symbol_filters(&ticker).await?;
+        let (quantity, price) = filters.apply(&order, self.get_latest_price(&order.symbol))?;
+
+        let mut params = vec![
+            ("symbol", ticker.clone()),
+            ("side", side_code(order.side).to_string()),
+            ("type", kind.to_string()),
+            ("quantity", quantity.to_string()),
+            ("newClientOrderId", client_id.to_string()),
+        ];
+        if let Some(tif) = time_in_force {
+            params.push(("timeInForce", tif.to_string()));
+        }
+        if let Some(price) = price {
+            params.push(("price", price.to_string()));
+        }
+
+        match self
+            .signed(HttpMethod::Post, "/api/v3/order", &params, Some(order_id))
+            .await
+        {
+            Ok(_) => {}
+            Err(e) if is_duplicate_order(&e) => {
+                if !self.order_submitted(&ticker, order_id).await? {
+                    return Err(e);
+                }
+            }
+            Err(e) => return Err(e),
+        }
+        info!(
+            "Binance order submitted: {} {:?} {} {} (requested {})",
+            order_id, order.side, quantity, ticker, order.quantity
+        );
+        self.track_order(order_id, order);
+        Ok(order_id)
+    }
+
+    async fn cancel_order(&mut self, order_id: OrderId) -> BrokerResult<()> {
+        self.ensure_connected()?;
+        let symbol = self.tracked_symbol(order_id)?;
+        self.signed(
+            HttpMethod::Delete,
+            "/api/v3/order",
+            &[
+                ("symbol", self.shared.ticker(&symbol)),
+                (
+                    "origClientOrderId",
+                    self.shared.client_ids.client_id(order_id).to_string(),
+                ),
+            ],
+            Some(order_id),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get_order_status(&self, order_id: OrderId) -> BrokerResult<OrderStatus> {
+        self.ensure_connected()?;
+        let symbol = self.tracked_symbol(order_id)?;
+        let order = self
+            .signed(
+                HttpMethod::Get,
+                "/api/v3/order",
+                &[
+                    ("symbol", self.shared.ticker(&symbol)),
+                    (
+                        "origClientOrderId",
+                        self.shared.client_ids.client_id(order_id).to_string(),
+                    ),
+                ],
+                Some(order_id),
+            )
+            .await?;
+        parse_order_status(str_field(&order, "status")?)
+    }
+
+    async fn get_order(&self, order_id: OrderId) -> BrokerResult<Order> {
+        self.ensure_connected()?;
+        let symbol = self.tracked_symbol(order_id)?;
+        let raw = self
+            .signed(
+                HttpMethod::Get,
+                "/api/v3/order",
+                &[
+                    ("symbol", self.shared.ticker(&symbol)),
+                    (
+                        "origClientOrderId",
+                        self.shared.client_ids.client_id(order_id).to_string(),
+                    ),
+                ],
+                Some(order_id),
+            )
+            .await?;
+        let mut order = order_from_binance(&raw, symbol)?;
+        order.id = self.shared.client_ids.order_id(order.id);
+        if let Some(tracked) = read_lock(&self.shared.orders).get(&order.id) {
+            order.strategy_id = tracked.strategy_id.clone();
+        }
+        Ok(order)
+    }
+
+    async fn get_open_orders(&self) -> BrokerResult<Vec<Order>> {
+        self.ensure_connected()?;
+        let response = self
+            .signed(HttpMethod::Get, "/api/v3/openOrders", &[], None)
+            .await?;
+        response
+            .as_array()
+            .ok_or_else(|| payload_error("expected an array of orders"))?
+            .iter()
+            .map(|raw| {
+                let ticker = str_field(raw, "symbol")?;
+                let mut order = order_from_binance(raw, self.shared.resolve_ticker(ticker))?;
+                order.id = self.shared.client_ids.order_id(order.id);
+                if let Some(tracked) = read_lock(&self.shared.orders).get(&order.id) {
+                    order.symbol = tracked.symbol.clone();
+                    order.strategy_id = tracked.strategy_id.clone();
+                }
+                Ok(order)
+            })
+            .collect()
+    }
+
+    async fn get_account_balance(&self) -> BrokerResult<AccountBalance> {
+        self.ensure_connected()?;
+        self.refresh_balances().await?;
+        Ok(self.shared.account_balance())
+    }
+
+    async fn get_positions(&self) -> BrokerResult<Vec<BrokerPosition>> {
+        self.ensure_connected()?;
+        self.refresh_balances().await?;
+        Ok(self.shared.positions())
+    }
+
+    async fn get_position(&self, symbol: &Symbol) -> BrokerResult<Option<BrokerPosition>> {
+        let ticker = self.shared.ticker(symbol);
+        Ok(self
+            .get_positions()
+            .await?
+            .into_iter()
+            .find(|position| self.shared.ticker(&position.symbol) == ticker))
+    }
+
+    async fn subscribe_market_data(&mut self, symbols: &[Symbol]) -> BrokerResult<()> {
+        let added: Vec<Symbol> = {
+            let mut subscriptions = write_lock(&self.shared.subscriptions);
+            let added: Vec<Symbol> = symbols
+                .iter()
+                .filter(|symbol| !subscriptions.contains(symbol))
+                .cloned()
+                .collect();
+            subscriptions.extend(added.iter().cloned());
+            added
+        };
+        self.route_subscription(&added, true);
+        Ok(())
+    }
+
+    async fn unsubscribe_market_data(&mut self, symbols: &[Symbol]) -> BrokerResult<()> {
+        write_lock(&self.shared.subscriptions).retain(|symbol| !symbols.contains(symbol));
+        self.route_subscription(symbols, false);
+        Ok(())
+    }
+
+    fn get_latest_price(&self, symbol: &Symbol) -> Option<Decimal> {
+        read_lock(&self.shared.prices).get(symbol).copied()
+    }
+
+    fn get_all_prices(&self) -> HashMap<Symbol, Decimal> {
+        read_lock(&self.shared.prices).clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use tokio::net::TcpListener;
+
+    const SERVER_TIME_FIXTURE: &str = r#"{"serverTime":1709303460000}"#;
+
+    const ACCOUNT_FIXTURE: &str = r#"{
+        "makerCommission": 10, "takerCommission": 10,
+        "canTrade": true, "canWithdraw": true, "canDeposit": true,
+        "accountType": "SPOT",
+        "balances": [
+            {"asset": "BTC", "free": "0.50000000", "locked": "0.10000000"},
+            {"asset": "USDT", "free": "9000.00000000", "locked": "1000.00000000"},
+            {"asset": "BNB", "free": "0.00000000", "locked": "0.00000000"}
+        ],
+        "permissions": ["SPOT"]
+    }"#;
+
+    const EXCHANGE_INFO_FIXTURE: &str = r#"{
+        "timezone": "UTC",
+        "symbols": [{
+            "symbol": "BTCUSDT",
+            "status": "TRADING",
+            "baseAsset": "BTC",
+            "quoteAsset": "USDT",
+            "filters": [
+                {"filterType": "PRICE_FILTER", "minPrice": "0.01000000", "maxPrice": "1000000.00000000", "tickSize": "0.01000000"},
+                {"filterType": "LOT_SIZE", "minQty": "0.00001000", "maxQty": "9000.00000000", "stepSize": "0.00001000"},
+                {"filterType": "NOTIONAL", "minNotional": "5.00000000", "applyMinToMarket": true, "maxNotional": "9000000.00000000"}
+            ]
+        }]
+    }"#;
+
+    fn order_ack_fixture(client_order_id: &str) -> String {
+        json!({
+            "symbol": "BTCUSDT",
+            "orderId": 28,
+            "clientOrderId": client_order_id,
+            "transactTime": 1709303460123i64,
+            "price": "64000.00",
+            "origQty": "0.12345",
+            "executedQty": "0.00000",
+            "cummulativeQuoteQty": "0.00000",
+            "status": "NEW",
+            "timeInForce": "GTC",
+            "type": "LIMIT",
+            "side": "BUY"
+        })
+        .to_string()
+    }
+
+    fn execution_report_fixture(
+        client_order_id: &str,
+        execution: &str,
+        status: &str,
+        last_qty: &str,
+        commission_asset: &str,
+    ) -> String {
+        json!({
+            "e": "executionReport",
+            "E": 1709303461000i64,
+            "s": "BTCUSDT",
+            "c": client_order_id,
+            "S": "BUY",
+            "o": "LIMIT",
+            "f": "GTC",
+            "q": "0.12345",
+            "p": "64000.00",
+            "x": execution,
+            "X": status,
+            "l": last_qty,
+            "L": "64000.00",
+            "n": "0.0001",
+            "N": commission_asset,
+            "T": 1709303461001i64,
+            "C": ""
+        })
+        .to_string()
+    }
+
+    fn response(status: u16, body: &str) -> BinanceResponse {
+        BinanceResponse {
+            status,
+            body: body.to_string(),
+            retry_after_ms: None,
+        }
+    }
+
+    #[derive(Default)]
+    struct FixtureTransport {
+        responses: Mutex<VecDeque<BinanceResponse>>,
+        requests: Mutex<Vec<BinanceRequest>>,
+    }
+
+    impl FixtureTransport {
+        fn new(responses: Vec<BinanceResponse>) -> Arc<Self> {
+            Arc::new(Self {
+                responses: Mutex::new(responses.into()),
+                requests: Mutex::new(Vec::new()),
+            })
+        }
+
+        fn requests(&self) -> Vec<BinanceRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl BinanceTransport for FixtureTransport {
+        async fn send(&self, request: &BinanceRequest) -> BrokerResult<BinanceResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            self.responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| BrokerError::Internal {
+                    message: format!("no fixture for {}", request.path),
+                })
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingCallback {
+        fills: Mutex<Vec<Fill>>,
+        statuses: Mutex<Vec<(OrderId, OrderStatus)>>,
+    }
+
+    #[async_trait]
+    impl BrokerCallback for RecordingCallback {
+        async fn on_fill(&self, fill: Fill) {
+            self.fills.lock().unwrap().push(fill);
+        }
+        async fn on_order_status(&self, order_id: OrderId, status: OrderStatus) {
+            self.statuses.lock().unwrap().push((order_id, status));
+        }
+        async fn on_market_data(&self, _event: MarketEvent) {}
+        async fn on_connection_status(&self, _status: ConnectionStatus) {}
+    }
+
+    fn fixture_config() -> BinanceConfig {
+        let mut config = BinanceConfig::new("api-key", "secret", BinanceEnvironment::Testnet);
+        config.stream_user_data = false;
+        config
+    }
+
+    async fn connected_broker(
+        responses: Vec<BinanceResponse>,
+    ) -> (BinanceBroker, Arc<FixtureTransport>) {
+        let mut all = vec![
+            response(200, SERVER_TIME_FIXTURE),
+            response(200, ACCOUNT_FIXTURE),
+        ];
+        all.extend(responses);
+        let transport = FixtureTransport::new(all);
+        let mut broker = BinanceBroker::with_transport(fixture_config(), transport.clone());
+        broker.connect().await.unwrap();
+        (broker, transport)
+    }
+
+    fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+        query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix(key)?.strip_prefix('='))
+    }
+
+    #[test]
+    fn test_sign_query_matches_binance_reference_vector() {
+        // Example from Binance's "SIGNED endpoint security" documentation.
+        let secret = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        assert_eq!(
+            sign_query(secret, query),
+            "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71"
+        );
+    }
+
+    #[test]
+    fn test_signed_query_appends_window_timestamp_and_signature() {
+        let broker = BinanceBroker::with_transport(fixture_config(), FixtureTransport::new(vec![]));
+        let query = broker.signed_query(&[("symbol", "BTCUSDT".to_string())], 1_700_000_000_000);
+        let (unsigned, signature) = query.split_once("&signature=").unwrap();
+        assert_eq!(
+            unsigned,
+            "symbol=BTCUSDT&recvWindow=5000&timestamp=1700000000000"
+        );
+        assert_eq!(signature, sign_query("secret", unsigned));
+    }
+
+    #[test]
+    fn test_symbol_mapping_matches_data_provider_convention() {
+        assert_eq!(
+            to_binance_symbol(&Symbol::crypto("BTC-USD"), "USDT"),
+            "BTCUSDT"
+        );
+        assert_eq!(
+            to_binance_symbol(&Symbol::crypto("eth-btc"), "USDT"),
+            "ETHBTC"
+        );
+        assert_eq!(
+            to_binance_symbol(&Symbol::crypto("BTCUSDT"), "USDT"),
+            "BTCUSDT"
+        );
+        assert_eq!(
+            to_binance_symbol(&Symbol::crypto("BTC-USD"), "FDUSD"),
+            "BTCFDUSD"
+        );
+
+        assert_eq!(
+            from_binance_symbol("BTCUSDT", "USDT"),
+            Symbol::crypto("BTC-USD")
+        );
+        assert_eq!(
+            from_binance_symbol("ETHBTC", "USDT"),
+            Symbol::crypto("ETH-BTC")
+        );
+        assert_eq!(
+            from_binance_symbol("SOLFDUSD", "USDT"),
+            Symbol::crypto("SOL-FDUSD")
+        );
+
+        let shared = SharedState::new(
+            "USDT",
+            ProviderSymbols::default().with_alias("MATIC-USD", "POLUSDT"),
+        );
+        let matic = Symbol::crypto("MATIC-USD");
+        assert_eq!(shared.ticker(&matic), "POLUSDT");
+        write_lock(&shared.subscriptions).push(matic.clone());
+        assert_eq!(shared.resolve_ticker("POLUSDT"), matic);
+    }
+
+    #[test]
+    fn test_filters_round_and_validate_orders() {
+        let info: Value = serde_json::from_str(EXCHANGE_INFO_FIXTURE).unwrap();
+        let filters = SymbolFilters::from_exchange_info(&info["symbols"][0]).unwrap();
+        assert_eq!(filters.step_size, dec!(0.00001));
+        assert_eq!(filters.tick_size, dec!(0.01));
+        assert_eq!(filters.min_notional, dec!(5));
+
+        let symbol = Symbol::crypto("BTC-USD");
+        let buy = Order::limit_order(
+            symbol.clone(),
+            Side::Buy,
+            dec!(0.123456789),
+            dec!(64000.019),
+            "s".into(),
+        );
+        assert_eq!(
+            filters.apply(&buy, None).unwrap(),
+            (dec!(0.12345), Some(dec!(64000.01)))
+        );
+        let sell = Order::limit_order(
+            symbol.clone(),
+            Side::Sell,
+            dec!(0.5),
+            dec!(64000.011),
+            "s".into(),
+        );
+        assert_eq!(filters.apply(&sell, None).unwrap().1, Some(dec!(64000.02)));
+
+        let dust = Order::market_order(symbol.clone(), Side::Buy, dec!(0.000004), "s".into());
+        assert!(matches!(
+            filters.apply(&dust, Some(dec!(64000))),
+            Err(BrokerError::OrderRejected { .. })
+        ));
+        let tiny_notional = Order::market_order(symbol, Side::Buy, dec!(0.00005), "s".into());
+        assert!(matches!(
+            filters.apply(&tiny_notional, Some(dec!(64000))),
+            Err(BrokerError::OrderRejected { reason }) if reason.contains("notional")
+        ));
+    }
+
+    #[test]
+    fn test_error_responses_map_to_broker_errors() {
+        let order_id = Uuid::new_v4();
+        assert!(matches!(
+            map_error_response(&response(400, r#"{"code":-2010,"msg":"Account has insufficient balance for requested action."}"#), Some(order_id)),
+            BrokerError::OrderRejected { reason } if reason.contains("insufficient balance")
+        ));
+        assert!(matches!(
+            map_error_response(&response(400, r#"{"code":-2011,"msg":"Unknown order sent."}"#), Some(order_id)),
+            BrokerError::OrderNotFound { order_id: id } if id == order_id.to_string()
+        ));
+        assert!(matches!(
+            map_error_response(
+                &response(
+                    401,
+                    r#"{"code":-2015,"msg":"Invalid API-key, IP, or permissions for action."}"#
+                ),
+                None
+            ),
+            BrokerError::AuthenticationFailed { .. }
+        ));
+        let mut banned = response(418, r#"{"code":-1003,"msg":"Way too many requests"}"#);
+        banned.retry_after_ms = Some(120_000);
+        assert!(matches!(
+            map_error_response(&banned, None),
+            BrokerError::RateLimited {
+                retry_after_ms: 120_000
+            }
+        ));
+        assert!(matches!(
+            map_error_response(&response(503, "Service Unavailable"), None),
+            BrokerError::Internal { message } if message.contains("503")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_connect_syncs_server_time_and_loads_balances() {
+        let (mut broker, transport) = connected_broker(vec![response(200, ACCOUNT_FIXTURE)]).await;
+        let expected = 1_709_303_460_000 - Utc::now().timestamp_millis();
+        assert!((broker.time_offset_ms() - expected).abs() < 5_000);
+        assert_eq!(transport.requests()[0].path, "/api/v3/time");
+        assert_eq!(broker.balances()["BTC"].locked, dec!(0.1));
+
+        broker
+            .subscribe_market_data(&[Symbol::crypto("BTC-USD")])
+            .await
+            .unwrap();
+        write_lock(&broker.shared.prices).insert(Symbol::crypto("BTC-USD"), dec!(60000));
+        let balance = broker.get_account_balance().await.unwrap();
+        assert_eq!(balance.cash, dec!(10000));
+        assert_eq!(balance.buying_power, dec!(9000));
+        assert_eq!(balance.equity, dec!(10000) + dec!(0.6) * dec!(60000));
+
+        let positions = broker.shared.positions();
+        assert_eq!(positions.len(), 1);
+        assert_eq!(positions[0].symbol, Symbol::crypto("BTC-USD"));
+        assert_eq!(positions[0].quantity, dec!(0.6));
+        assert_eq!(positions[0].market_value, dec!(36000));
+        broker.disconnect().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_submit_rounds_and_signs_then_cancels_by_client_id() {
+        let order = Order::limit_order(
+            Symbol::crypto("BTC-USD"),
+            Side::Buy,
+            dec!(0.123456),
+            dec!(64000.009),
+            "grid".into(),
+        );
+        let (mut broker, transport) = connected_broker(vec![
+            response(200, EXCHANGE_INFO_FIXTURE),
+            response(200, &order_ack_fixture(&order.id.to_string())),
+            response(200, &order_ack_fixture(&order.id.to_string())),
+        ])
+        .await;
+
+        assert_eq!(broker.submit_order(order.clone()).await.unwrap(), order.id);
+        broker.cancel_order(order.id).await.unwrap();
+
+        let requests = transport.requests();
+        let submit = &requests[3];
+        assert_eq!(
+            (submit.method, submit.path.as_str()),
+            (HttpMethod::Post, "/api/v3/order")
+        );
+        assert_eq!(query_param(&submit.query, "symbol"), Some("BTCUSDT"));
+        assert_eq!(query_param(&submit.query, "quantity"), Some("0.12345"));
+        assert_eq!(query_param(&submit.query, "price"), Some("64000"));
+        assert_eq!(query_param(&submit.query, "timeInForce"), Some("GTC"));
+        let client_id = order.id.to_string();
+        assert_eq!(
+            query_param(&submit.query, "newClientOrderId"),
+            Some(client_id.as_str())
+        );
+        let (unsigned, signature) = submit.query.split_once("&signature=").unwrap();
+        assert_eq!(signature, sign_query("secret", unsigned));
+
+        let cancel = &requests[4];
+        assert_eq!(cancel.method, HttpMethod::Delete);
+        assert_eq!(
+            query_param(&cancel.query, "origClientOrderId"),
+            Some(client_id.as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resubmitted_orders_resolve_to_the_accepted_order() {
+        let client_id = Uuid::new_v4();
+        let order = Order::limit_order(
+            Symbol::crypto("BTC-USD"),
+            Side::Buy,
+            dec!(0.12345),
+            dec!(64000),
+            "grid".into(),
+        )
+        .with_client_order_id(client_id);
+        let client_id = client_id.to_string();
+        let accepted = order_ack_fixture(&client_id);
+
+        // The first attempt times out after Binance accepted it; the retry
+        // finds the order instead of placing it again.
+        let (mut broker, transport) = connected_broker(vec![
+            response(200, EXCHANGE_INFO_FIXTURE),
+            response(
+                504,
+                r#"{"code":-1007,"msg":"Timeout waiting for response."}"#,
+            ),
+            response(200, &accepted),
+            response(200, &accepted),
+        ])
+        .await;
+        assert!(matches!(
+            broker.submit_order(order.clone()).await,
+            Err(BrokerError::Internal { .. })
+        ));
+        assert_eq!(broker.submit_order(order.clone()).await.unwrap(), order.id);
+        broker.cancel_order(order.id).await.unwrap();
+        let requests = transport.requests();
+        assert_eq!(
+            query_param(&requests[3].query, "newClientOrderId"),
+            Some(client_id.as_str())
+        );
+        let lookup = &requests[4];
+        assert_eq!(
+            (lookup.method, lookup.path.as_str()),
+            (HttpMethod::Get, "/api/v3/order")
+        );
+        assert_eq!(
+            query_param(&lookup.query, "origClientOrderId"),
+            Some(client_id.as_str())
+        );
+        assert_eq!(requests[5].method, HttpMethod::Delete);
+        assert_eq!(
+            query_param(&requests[5].query, "origClientOrderId"),
+            Some(client_id.as_str())
+        );
+        assert_eq!(requests.len(), 6);
+
+        // After a restart nothing is remembered, so Binance's duplicate
+        // rejection leads to the lookup.
+        let (mut broker, transport) = connected_broker(vec![
+            response(200, EXCHANGE_INFO_FIXTURE),
+            response(400, r#"{"code":-2010,"msg":"Duplicate order sent."}"#),
+            response(200, &accepted),
+        ])
+        .await;
+        assert_eq!(broker.submit_order(order.clone()).await.unwrap(), order.id);
+        let requests = transport.requests();
+        assert_eq!(requests[3].method, HttpMethod::Post);
+        assert_eq!(
+            (requests[4].method, requests[4].path.as_str()),
+            (HttpMethod::Get, "/api/v3/order")
+        );
+
+        // A duplicate whose original is gone is still a rejection.
+        let (mut broker, _) = connected_broker(vec![
+            response(200, EXCHANGE_INFO_FIXTURE),
+            response(400, r#"{"code":-2010,"msg":"Duplicate order sent."}"#),
+            response(400, r#"{"code":-2013,"msg":"Order does not exist."}"#),
+        ])
+        .await;
+        assert!(matches!(
+            broker.submit_order(order).await,
+            Err(BrokerError::OrderRejected { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_orders_are_rejected_locally() {
+        let (mut broker, transport) = connected_broker(vec![]).await;
+        let symbol = Symbol::crypto("BTC-USD");
+
+        let stop = Order::stop_order(symbol.clone(), Side::Sell, dec!(1), dec!(60000), "s".into());
+        assert!(matches!(
+            broker.submit_order(stop).await,
+            Err(BrokerError::OrderRejected { .. })
+        ));
+        let mut day = Order::limit_order(symbol, Side::Buy, dec!(1), dec!(60000), "s".into());
+        day.time_in_force = TimeInForce::Day;
+        assert!(matches!(
+            broker.submit_order(day).await,
+            Err(BrokerError::OrderRejected { .. })
+        ));
+        assert!(matches!(
+            broker.cancel_order(Uuid::new_v4()).await,
+            Err(BrokerError::OrderNotFound { .. })
+        ));
+        assert_eq!(transport.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_timestamp_rejection_resyncs_and_retries_once() {
+        let (broker, transport) = connected_broker(vec![
+            response(400, r#"{"code":-1021,"msg":"Timestamp for this request is outside of the recvWindow."}"#),
+            response(200, SERVER_TIME_FIXTURE),
+            response(200, ACCOUNT_FIXTURE),
+        ])
+        .await;
+
+        broker.get_account_balance().await.unwrap();
+        let paths: Vec<String> = transport.requests().into_iter().map(|r| r.path).collect();
+        assert_eq!(
+            paths[2..],
+            ["/api/v3/account", "/api/v3/time", "/api/v3/account"]
+        );
+    }
+
+    #[test]
+    fn test_parse_stream_fixtures() {
+        let order_id = Uuid::new_v4();
+        let events = parse_stream_message(
+            &execution_report_fixture(
+                &order_id.to_string(),
+                "TRADE",
+                "PARTIALLY_FILLED",
+                "0.05",
+                "BNB",
+            ),
+            "USDT",
+        )
+        .unwrap();
+        let BinanceStreamEvent::Execution(report) = &events[0] else {
+            panic!("expected execution report");
+        };
+        assert_eq!(report.order_id, order_id);
+        assert_eq!(report.status, OrderStatus::PartiallyFilled);
+        assert_eq!(report.last_quantity, dec!(0.05));
+        assert_eq!(report.commission_asset.as_deref(), Some("BNB"));
+
+        let manual = execution_report_fixture("web_abc123", "NEW", "NEW", "0", "");
+        assert!(parse_stream_message(&manual, "USDT").unwrap().is_empty());
+
+        let account = r#"{"e":"outboundAccountPosition","E":1709303461002,"u":1709303461001,
+            "B":[{"a":"BTC","f":"0.55000000","l":"0.07345000"},{"a":"USDT","f":"9000.00","l":"4200.00"}]}"#;
+        let events = parse_stream_message(account, "USDT").unwrap();
+        let BinanceStreamEvent::Balances(balances) = &events[0] else {
+            panic!("expected balances");
+        };
+        assert_eq!(balances["BTC"].free, dec!(0.55));
+
+        let ticker =
+            r#"{"u":400900217,"s":"BTCUSDT","b":"63999.99","B":"1.2","a":"64000.01","A":"0.8"}"#;
+        let events = parse_stream_message(ticker, "USDT").unwrap();
+        assert!(matches!(
+            &events[0],
+            BinanceStreamEvent::Market(MarketEvent::Quote { symbol, ask, .. })
+                if *symbol == Symbol::crypto("BTC-USD") && *ask == dec!(64000.01)
+        ));
+
+        let open_kline = r#"{"e":"kline","E":1709303461000,"s":"BTCUSDT","k":{"t":1709303400000,"T":1709303459999,"s":"BTCUSDT","i":"1m","o":"63950","c":"64000","h":"64010","l":"63940","v":"12.5","x":false}}"#;
+        assert!(parse_stream_message(open_kline, "USDT").unwrap().is_empty());
+        let closed = open_kline.replace("\"x\":false", "\"x\":true");
+        let events = parse_stream_message(&closed, "USDT").unwrap();
+        assert!(matches!(
+            &events[0],
+            BinanceStreamEvent::Market(MarketEvent::Bar(bar))
+                if bar.close == dec!(64000) && bar.resolution == Resolution::Minute
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_user_data_stream_routes_fills_with_quote_commission() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let order = Order::limit_order(
+            Symbol::crypto("BTC-USD"),
+            Side::Buy,
+            dec!(0.12345),
+            dec!(64000),
+            "grid".into(),
+        );
+        let client_id = order.id.to_string();
+
+        let frames = vec![
+            execution_report_fixture(&client_id, "TRADE", "PARTIALLY_FILLED", "0.1", "USDT"),
+            execution_report_fixture(&client_id, "TRADE", "FILLED", "0.02345", "BTC"),
+        ];
+        let (release, released) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            // Hold the executions until the order has been acknowledged.
+            released.await.unwrap();
+            for frame in frames {
+                socket.send(Message::Text(frame.into())).await.unwrap();
+            }
+            // Keep the socket open until the client disconnects.
+            while socket.next().await.is_some() {}
+        });
+
+        let mut config = fixture_config();
+        config.stream_user_data = true;
+        config.endpoints.stream = format!("ws://{}/ws", address);
+        let transport = FixtureTransport::new(vec![
+            response(200, SERVER_TIME_FIXTURE),
+            response(200, ACCOUNT_FIXTURE),
+            response(
+                200,
+                r#"{"listenKey":"pqia91ma19a5s61cv6a81va65sdf19v8a65a1a5s61cv6a81va65sdf19v8a65a1"}"#,
+            ),
+            response(200, EXCHANGE_INFO_FIXTURE),
+            response(200, &order_ack_fixture(&client_id)),
+        ]);
+        let callback = Arc::new(RecordingCallback::default());
+        let mut broker = BinanceBroker::with_transport(config, transport.clone())
+            .with_callback(callback.clone());
+        broker.connect().await.unwrap();
+        // Let the stream task fetch its listen key before the order requests.
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while transport.requests().len() < 3 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("listen key not requested");
+        broker.submit_order(order.clone()).await.unwrap();
+        release.send(()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while callback.fills.lock().unwrap().len() < 2 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("fills not routed");
+        broker.disconnect().await.unwrap();
+        server.await.unwrap();
+        let listen_key = &transport.requests()[2];
+        assert_eq!(
+            (listen_key.method, listen_key.path.as_str()),
+            (HttpMethod::Post, "/api/v3/userDataStream")
+        );
+
+        let fills = callback.fills.lock().unwrap().clone();
+        assert!(fills.iter().all(|fill| fill.order_id == order.id
+            && fill.strategy_id == "grid"
+            && fill.symbol == Symbol::crypto("BTC-USD")));
+        assert_eq!(fills[0].quantity + fills[1].quantity, dec!(0.12345));
+        // Quote-asset commission as-is; base-asset commission valued at the fill price.
+        assert_eq!(fills[0].commission, dec!(0.0001));
+        assert_eq!(fills[1].commission, dec!(6.4));
+        assert_eq!(
+            callback.statuses.lock().unwrap().last(),
+            Some(&(order.id, OrderStatus::Filled))
+        );
+    }
+}
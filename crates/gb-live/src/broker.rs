@@ -3,10 +3,14 @@
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use gb_types::market::{MarketEvent, Symbol};
-use gb_types::orders::{Fill, Order, OrderId, OrderStatus};
+use gb_types::orders::{Fill, Order, OrderEvent, OrderId, OrderStatus};
+use gb_types::{DataError, GbError, OrderError};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+use uuid::Uuid;
 
 /// Snapshot of an account balance returned by a broker.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -50,11 +54,225 @@ pub enum BrokerError {
     RateLimited { retry_after_ms: u64 },
     #[error("broker internal error: {message}")]
     Internal { message: String },
+    /// A GlowBack error that repeating the call will not fix, with its
+    /// [`GbError::code`].
+    #[error("{message}")]
+    Failed { code: &'static str, message: String },
+}
+
+impl BrokerError {
+    /// Whether the call may succeed if repeated unchanged: rate limits and
+    /// transport or server failures, which include timeouts. A retried
+    /// submission may already have reached the broker, so only orders with
+    /// a `client_order_id` should be resubmitted.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            BrokerError::RateLimited { .. } | BrokerError::Internal { .. }
+        )
+    }
+
+    /// Stable identifier of the variant, e.g. `"broker.rate_limited"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BrokerError::NotConnected => "broker.not_connected",
+            BrokerError::OrderRejected { .. } => "broker.order_rejected",
+            BrokerError::OrderNotFound { .. } => "broker.order_not_found",
+            BrokerError::AuthenticationFailed { .. } => "broker.authentication_failed",
+            BrokerError::RateLimited { .. } => "broker.rate_limited",
+            BrokerError::Internal { .. } => "broker.internal",
+            BrokerError::Failed { code, .. } => code,
+        }
+    }
+}
+
+impl From<GbError> for BrokerError {
+    /// Order errors keep their meaning, a rate-limited data provider stays
+    /// rate limited, and anything else retryable becomes `Internal` so retry
+    /// policies treat it the same way.
+    fn from(error: GbError) -> Self {
+        match error {
+            GbError::Order(OrderError::NotFound { order_id }) => {
+                BrokerError::OrderNotFound { order_id }
+            }
+            GbError::Order(error) => BrokerError::OrderRejected {
+                reason: error.to_string(),
+            },
+            GbError::Data(DataError::RateLimited {
+                retry_after_seconds,
+                ..
+            }) => BrokerError::RateLimited {
+                retry_after_ms: retry_after_seconds.unwrap_or(1).saturating_mul(1_000),
+            },
+            error if error.is_retryable() => BrokerError::Internal {
+                message: error.to_string(),
+            },
+            error => BrokerError::Failed {
+                code: error.code(),
+                message: error.to_string(),
+            },
+        }
+    }
 }
 
 /// Result alias for broker operations.
 pub type BrokerResult<T> = Result<T, BrokerError>;
 
+/// Why a broker refused an order it had already accepted.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+pub enum RejectionReason {
+    #[error("insufficient funds: order costs {required} but cash is {available}")]
+    InsufficientFunds {
+        required: Decimal,
+        available: Decimal,
+    },
+    #[error("no market price for {symbol}")]
+    NoMarketPrice { symbol: String },
+    #[error("insufficient margin: requires {required} but only {available} is available")]
+    MarginExceeded {
+        required: Decimal,
+        available: Decimal,
+    },
+    #[error("short sales are not supported; sell quantity exceeds current inventory")]
+    ShortSaleNotAllowed,
+    #[error("order quantity {quantity} is below the minimum of {minimum} for {symbol}")]
+    BelowMinimumQuantity {
+        quantity: Decimal,
+        minimum: Decimal,
+        symbol: String,
+    },
+    #[error("{message}")]
+    Other { message: String },
+}
+
+/// An order state change a broker reports outside the call that caused it,
+/// drained with [`Broker::poll_order_updates`]. Fills keep arriving through
+/// the fill stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BrokerOrderUpdate {
+    /// The broker is working the order for `quantity`, which may differ
+    /// from the quantity submitted once lot rules are applied.
+    Accepted {
+        order_id: OrderId,
+        quantity: Decimal,
+    },
+    Canceled {
+        order_id: OrderId,
+        reason: String,
+    },
+    /// The order's time in force ran out with quantity unfilled.
+    Expired {
+        order_id: OrderId,
+        reason: String,
+    },
+    Rejected {
+        order_id: OrderId,
+        reason: RejectionReason,
+    },
+}
+
+impl BrokerOrderUpdate {
+    pub fn order_id(&self) -> OrderId {
+        match self {
+            BrokerOrderUpdate::Accepted { order_id, .. }
+            | BrokerOrderUpdate::Canceled { order_id, .. }
+            | BrokerOrderUpdate::Expired { order_id, .. }
+            | BrokerOrderUpdate::Rejected { order_id, .. } => *order_id,
+        }
+    }
+
+    /// The update as the [`OrderEvent`] a strategy receives.
+    pub fn to_order_event(&self) -> OrderEvent {
+        let order_id = self.order_id();
+        match self {
+            BrokerOrderUpdate::Accepted { .. } => OrderEvent::OrderAccepted { order_id },
+            BrokerOrderUpdate::Canceled { reason, .. } => OrderEvent::OrderCanceled {
+                order_id,
+                reason: reason.clone(),
+            },
+            BrokerOrderUpdate::Expired { reason, .. } => OrderEvent::OrderExpired {
+                order_id,
+                reason: reason.clone(),
+            },
+            BrokerOrderUpdate::Rejected { reason, .. } => OrderEvent::OrderRejected {
+                order_id,
+                reason: reason.to_string(),
+            },
+        }
+    }
+}
+
+/// HTTP verbs used by REST broker adapters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Delete,
+}
+
+/// Exponential back-off: `initial_ms` doubled per attempt, capped at `max_ms`.
+pub(crate) fn backoff_delay(initial_ms: u64, max_ms: u64, attempt: u32) -> Duration {
+    let factor = 1u64.checked_shl(attempt.min(20)).unwrap_or(u64::MAX);
+    Duration::from_millis(initial_ms.saturating_mul(factor).min(max_ms))
+}
+
+/// Read a lock shared with adapter stream tasks, ignoring poisoning (the
+/// guarded data is plain bookkeeping that stays consistent on panic).
+pub(crate) fn read_lock<T>(lock: &RwLock<T>) -> RwLockReadGuard<'_, T> {
+    lock.read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Write counterpart of [`read_lock`].
+pub(crate) fn write_lock<T>(lock: &RwLock<T>) -> RwLockWriteGuard<'_, T> {
+    lock.write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Client order ids a REST adapter has sent to its venue, and the order each
+/// stands for. An order goes out under its [`Order::client_order_id`] when it
+/// has one, so that the venue itself recognises a resubmission, and under its
+/// own id otherwise.
+#[derive(Debug, Default)]
+pub(crate) struct ClientOrderIds {
+    sent: RwLock<HashMap<Uuid, OrderId>>,
+}
+
+impl ClientOrderIds {
+    /// The client id `order` is sent under.
+    pub(crate) fn for_order(order: &Order) -> Uuid {
+        order.client_order_id.unwrap_or(order.id)
+    }
+
+    /// Record that `order` is being sent. Returns the client id to send it
+    /// under and whether that id was sent before, in which case the venue may
+    /// already hold the order.
+    pub(crate) fn assign(&self, order: &Order) -> (Uuid, bool) {
+        let client_id = Self::for_order(order);
+        let mut sent = write_lock(&self.sent);
+        let resent = sent.contains_key(&client_id);
+        sent.entry(client_id).or_insert(order.id);
+        (client_id, resent)
+    }
+
+    /// The order sent under `client_id`. Ids this adapter did not send are
+    /// taken to be order ids themselves.
+    pub(crate) fn order_id(&self, client_id: Uuid) -> OrderId {
+        read_lock(&self.sent)
+            .get(&client_id)
+            .copied()
+            .unwrap_or(client_id)
+    }
+
+    /// The client id `order_id` was sent under.
+    pub(crate) fn client_id(&self, order_id: OrderId) -> Uuid {
+        read_lock(&self.sent)
+            .iter()
+            .find(|(_, sent)| **sent == order_id)
+            .map_or(order_id, |(client_id, _)| *client_id)
+    }
+}
+
 /// Callback receiver for asynchronous broker events (fills, status changes, etc.).
 #[async_trait]
 pub trait BrokerCallback: Send + Sync {
@@ -86,6 +304,10 @@ pub trait Broker: Send + Sync {
     // -- Order management ---------------------------------------------------
 
     /// Submit a new order. Returns the broker-assigned order id.
+    ///
+    /// Submission must be idempotent on [`Order::client_order_id`]: an order
+    /// whose client id the broker has already accepted is not placed again,
+    /// and the original order's id is returned.
     async fn submit_order(&mut self, order: Order) -> BrokerResult<OrderId>;
 
     /// Cancel an open order.
@@ -94,6 +316,10 @@ pub trait Broker: Send + Sync {
     /// Query the current status of an order.
     async fn get_order_status(&self, order_id: OrderId) -> BrokerResult<OrderStatus>;
 
+    /// Fetch the broker's full view of an order, including its cumulative
+    /// filled quantity and average fill price. Works for closed orders too.
+    async fn get_order(&self, order_id: OrderId) -> BrokerResult<Order>;
+
     /// List all open (active) orders.
     async fn get_open_orders(&self) -> BrokerResult<Vec<Order>>;
 
@@ -131,4 +357,49 @@ pub trait Broker: Send + Sync {
     async fn on_market_event(&mut self, _event: &MarketEvent) -> BrokerResult<()> {
         Ok(())
     }
+
+    /// Take the order updates produced since the last poll, oldest first:
+    /// acceptances, cancels, expirations, and orders rejected after
+    /// `submit_order` returned or when a market event tried to fill them.
+    /// Brokers that report everything through [`BrokerCallback`] can leave
+    /// the default.
+    fn poll_order_updates(&mut self) -> Vec<BrokerOrderUpdate> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glowback_errors_map_onto_broker_errors() {
+        let missing: BrokerError = GbError::Order(OrderError::NotFound {
+            order_id: "abc".into(),
+        })
+        .into();
+        assert!(matches!(missing, BrokerError::OrderNotFound { order_id } if order_id == "abc"));
+
+        let throttled: BrokerError = GbError::Data(DataError::RateLimited {
+            provider: "alpha_vantage".into(),
+            message: "slow down".into(),
+            retry_after_seconds: Some(60),
+        })
+        .into();
+        assert!(matches!(
+            throttled,
+            BrokerError::RateLimited {
+                retry_after_ms: 60_000
+            }
+        ));
+        assert!(throttled.is_retryable());
+
+        let unsupported: BrokerError = GbError::Data(DataError::UnsupportedResolution {
+            provider: "alpha_vantage".into(),
+            resolution: "1m".into(),
+        })
+        .into();
+        assert_eq!(unsupported.code(), "data.unsupported_resolution");
+        assert!(!unsupported.is_retryable());
+    }
 }
@@ -0,0 +1,5 @@
+//! Trading calendar used by the live engine and risk checks to locate session
+//! opens and closes. The type lives in `gb-engine`, so live day-ends fall on
+//! the same instants as backtest ones.
+
+pub use gb_engine::calendar::TradingCalendar;
@@ -1,16 +1,45 @@
 //! Live trading engine that ties a [`Strategy`], [`Broker`], and [`RiskManager`]
 //! together in an event-driven loop.
 
-use gb_types::market::MarketEvent;
-use gb_types::orders::{Fill, Order, OrderEvent, OrderId};
-use gb_types::strategy::{Strategy, StrategyAction, StrategyConfig, StrategyContext};
+use chrono::{DateTime, Utc};
+use futures_util::{FutureExt, Stream, StreamExt};
+use gb_engine::stream::{EventSeverity, StreamEvent};
+use gb_engine::telemetry;
+use gb_types::backtest::EventOrdering;
+use gb_types::execution::{ExecutionReport, FillExecution};
+use gb_types::market::{MarketEvent, Symbol};
+use gb_types::orders::{Fill, Order, OrderEvent, OrderId, OrderType, Side};
+use gb_types::portfolio::{DailyReturn, DailyReturnRecorder, Portfolio, Position};
+use gb_types::strategy::{
+    MarketDataBuffer, Strategy, StrategyAction, StrategyConfig, StrategyContext,
+};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tracing::{error, info, warn};
-
-use crate::broker::Broker;
-use crate::risk::{RiskCheckResult, RiskConfig, RiskManager};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn, Instrument, Span};
+
+use crate::broker::{
+    backoff_delay, Broker, BrokerError, BrokerOrderUpdate, BrokerPosition, BrokerResult,
+    ConnectionStatus, RejectionReason,
+};
+use crate::calendar::TradingCalendar;
+use crate::journal::{JournalConfig, JournalRecord, OrderJournal};
+use crate::paper::{PaperBroker, PaperBrokerState};
+use crate::risk::{RiskCheckResult, RiskConfig, RiskManager, RiskRule, RiskSessionState};
+use crate::shadow::{ShadowConfig, ShadowReport};
+use crate::throttle::{OrderThrottle, OverflowPolicy, ThrottleConfig};
+
+/// Buffered events per subscriber before the slowest one starts lagging.
+const EVENT_CHANNEL_CAPACITY: usize = 1024;
+
+/// How often [`LiveEngine::run`] looks for stale market data.
+const STALE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Operating mode of the live engine.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -19,6 +48,10 @@ pub enum TradingMode {
     Live,
     /// Orders are executed on a paper broker — no real money at risk.
     Sandbox,
+    /// The real broker supplies market data and nothing else: orders pass
+    /// through the risk checks in dry-run and fill on an internal paper
+    /// book configured by [`LiveEngineConfig::shadow`].
+    Shadow,
 }
 
 /// Events emitted by the live engine for external consumption (logging, UI,
@@ -32,125 +65,1122 @@ pub enum LiveEngineEvent {
     Stopped {
         strategy_id: String,
         reason: String,
+        /// What the strategy's `on_finish` reported.
+        #[serde(default)]
+        metrics: HashMap<String, serde_json::Value>,
     },
     OrderSubmitted {
+        strategy_id: String,
         order_id: OrderId,
         symbol: String,
         side: String,
         quantity: Decimal,
     },
     OrderFilled {
+        strategy_id: String,
+        order_id: OrderId,
+        symbol: String,
+        price: Decimal,
+        quantity: Decimal,
+    },
+    /// A fill on the internal paper book in [`TradingMode::Shadow`], in
+    /// place of [`LiveEngineEvent::OrderFilled`].
+    ShadowOrderFilled {
+        strategy_id: String,
         order_id: OrderId,
+        symbol: String,
         price: Decimal,
         quantity: Decimal,
     },
     OrderRejectedByRisk {
+        strategy_id: String,
         order_id: OrderId,
+        symbol: String,
+        rule: RiskRule,
         reason: String,
     },
     OrderRejectedByBroker {
+        strategy_id: String,
         order_id: OrderId,
+        symbol: String,
         error: String,
+        /// Set when the broker rejected an order it had accepted, reported
+        /// through [`Broker::poll_order_updates`].
+        #[serde(default)]
+        reason: Option<RejectionReason>,
+    },
+    OrderExpired {
+        strategy_id: String,
+        order_id: OrderId,
+        reason: String,
+    },
+    /// A strategy canceled one of its working orders.
+    OrderCanceled {
+        strategy_id: String,
+        order_id: OrderId,
+    },
+    /// A queued order was dropped by the [`OrderThrottle`] before reaching
+    /// the broker: the queue overflowed, it was netted into another order,
+    /// or the engine stopped.
+    OrderDropped {
+        strategy_id: String,
+        order_id: OrderId,
+        symbol: String,
+        reason: String,
+    },
+    /// Market orders closing the strategy's positions were submitted ahead
+    /// of `session_close`.
+    PositionsFlattened {
+        strategy_id: String,
+        session_close: DateTime<Utc>,
+        orders: usize,
     },
+    /// One strategy's results for the session ending at `session_close`,
+    /// with positions marked at the latest prices.
+    DailySummary {
+        strategy_id: String,
+        session_close: DateTime<Utc>,
+        equity: Decimal,
+        realized_pnl: Decimal,
+        unrealized_pnl: Decimal,
+        fills: usize,
+        commissions: Decimal,
+        /// Largest peak-to-trough equity decline during the session, as a
+        /// fraction of the peak.
+        max_drawdown: Decimal,
+        /// The session's return as appended to the strategy's
+        /// `daily_returns`, for a hosted risk monitor to push
+        /// (`RiskMonitor::push_daily_return`); `None` when the session was
+        /// already recorded.
+        daily_return: Option<DailyReturn>,
+    },
+    OrderReplaced {
+        strategy_id: String,
+        order_id: OrderId,
+        replacement_id: OrderId,
+        limit_price: Decimal,
+    },
+    /// A circuit breaker tripped: the named strategy's own, or the
+    /// engine-wide one when `strategy_id` is `None`.
     CircuitBreakerTripped {
+        strategy_id: Option<String>,
         equity: Decimal,
     },
     MarketDataReceived {
         symbol: String,
     },
+    Reconnected {
+        attempts: u32,
+    },
+    Resynced {
+        summary: ResyncSummary,
+    },
+    ReconciliationMismatch {
+        positions: Vec<PositionMismatch>,
+        cash_delta: Decimal,
+        adopted: bool,
+    },
+    /// No market event arrived for `symbol` within the configured
+    /// staleness period while its session was open.
+    DataStale {
+        symbol: String,
+        seconds: u64,
+    },
+    /// Market data resumed for a symbol previously reported stale.
+    DataResumed {
+        symbol: String,
+    },
+    /// Execution costs of every fill in the session, emitted on stop.
+    ExecutionReport {
+        report: ExecutionReport,
+    },
     Error {
         message: String,
     },
 }
 
+impl StreamEvent for LiveEngineEvent {
+    fn kind(&self) -> &'static str {
+        match self {
+            LiveEngineEvent::Started { .. } => "Started",
+            LiveEngineEvent::Stopped { .. } => "Stopped",
+            LiveEngineEvent::OrderSubmitted { .. } => "OrderSubmitted",
+            LiveEngineEvent::OrderFilled { .. } => "OrderFilled",
+            LiveEngineEvent::ShadowOrderFilled { .. } => "ShadowOrderFilled",
+            LiveEngineEvent::OrderRejectedByRisk { .. } => "OrderRejectedByRisk",
+            LiveEngineEvent::OrderRejectedByBroker { .. } => "OrderRejectedByBroker",
+            LiveEngineEvent::OrderExpired { .. } => "OrderExpired",
+            LiveEngineEvent::OrderCanceled { .. } => "OrderCanceled",
+            LiveEngineEvent::OrderDropped { .. } => "OrderDropped",
+            LiveEngineEvent::PositionsFlattened { .. } => "PositionsFlattened",
+            LiveEngineEvent::DailySummary { .. } => "DailySummary",
+            LiveEngineEvent::OrderReplaced { .. } => "OrderReplaced",
+            LiveEngineEvent::CircuitBreakerTripped { .. } => "CircuitBreakerTripped",
+            LiveEngineEvent::MarketDataReceived { .. } => "MarketDataReceived",
+            LiveEngineEvent::Reconnected { .. } => "Reconnected",
+            LiveEngineEvent::Resynced { .. } => "Resynced",
+            LiveEngineEvent::ReconciliationMismatch { .. } => "ReconciliationMismatch",
+            LiveEngineEvent::DataStale { .. } => "DataStale",
+            LiveEngineEvent::DataResumed { .. } => "DataResumed",
+            LiveEngineEvent::ExecutionReport { .. } => "ExecutionReport",
+            LiveEngineEvent::Error { .. } => "Error",
+        }
+    }
+
+    fn severity(&self) -> EventSeverity {
+        match self {
+            LiveEngineEvent::MarketDataReceived { .. } => EventSeverity::Debug,
+            LiveEngineEvent::OrderRejectedByRisk { .. }
+            | LiveEngineEvent::OrderRejectedByBroker { .. }
+            | LiveEngineEvent::OrderDropped { .. }
+            | LiveEngineEvent::ReconciliationMismatch { .. }
+            | LiveEngineEvent::DataStale { .. } => EventSeverity::Warning,
+            LiveEngineEvent::CircuitBreakerTripped { .. } | LiveEngineEvent::Error { .. } => {
+                EventSeverity::Error
+            }
+            _ => EventSeverity::Info,
+        }
+    }
+
+    fn payload(&self) -> serde_json::Result<serde_json::Value> {
+        serde_json::to_value(self)
+    }
+}
+
+/// What [`LiveEngine::run`] does with an order that is still working after
+/// a timeout. Read from the order's `metadata["order_timeout"]`, falling back
+/// to the strategy parameter of the same name, e.g.
+/// `{"action": "replace", "after_secs": 60, "reprice_bps": 10}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum OrderTimeout {
+    /// Cancel the order after `after_secs` seconds.
+    Cancel { after_secs: u64 },
+    /// Cancel a limit order after `after_secs` seconds and resubmit the
+    /// remaining quantity with its limit moved `reprice_bps` toward the
+    /// market (never past the latest price). Other order types are only
+    /// cancelled.
+    Replace { after_secs: u64, reprice_bps: u32 },
+}
+
+impl OrderTimeout {
+    pub const PARAMETER: &'static str = "order_timeout";
+
+    /// The timeout that applies to `order`, if any.
+    pub fn for_order(order: &Order, strategy_config: &StrategyConfig) -> Option<Self> {
+        order
+            .metadata
+            .get(Self::PARAMETER)
+            .and_then(|value| serde_json::from_value(value.clone()).ok())
+            .or_else(|| strategy_config.get_parameter(Self::PARAMETER))
+    }
+
+    pub fn after(&self) -> Duration {
+        match self {
+            OrderTimeout::Cancel { after_secs } | OrderTimeout::Replace { after_secs, .. } => {
+                Duration::from_secs(*after_secs)
+            }
+        }
+    }
+}
+
+/// How the engine recovers from a dropped broker connection.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconnectPolicy {
+    /// Connection attempts before giving up; `0` disables reconnecting.
+    pub max_attempts: u32,
+    /// Delay before the second attempt, doubled for each one after.
+    pub initial_backoff_ms: u64,
+    /// Upper bound on the delay between attempts.
+    pub max_backoff_ms: u64,
+    /// How often [`LiveEngine::run`] polls the broker's connection status.
+    pub check_interval_ms: u64,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+            check_interval_ms: 1_000,
+        }
+    }
+}
+
+/// How the engine resubmits an order after a retryable broker error, such
+/// as a timeout. Every order carries a `client_order_id`, so a resubmission
+/// the broker already accepted is not placed twice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SubmitRetryPolicy {
+    /// Submission attempts per order, including the first; `1` disables
+    /// retrying.
+    pub max_attempts: u32,
+    /// Delay between attempts, unless the broker asked for a longer one.
+    pub backoff_ms: u64,
+}
+
+impl Default for SubmitRetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff_ms: 250,
+        }
+    }
+}
+
+/// A symbol whose broker quantity disagrees with the local portfolio.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionMismatch {
+    pub symbol: Symbol,
+    pub local_quantity: Decimal,
+    pub broker_quantity: Decimal,
+}
+
+impl PositionMismatch {
+    /// Broker quantity minus local quantity.
+    pub fn delta(&self) -> Decimal {
+        self.broker_quantity - self.local_quantity
+    }
+}
+
+/// Tolerances and behaviour for [`LiveEngine::reconcile`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationConfig {
+    /// Largest per-symbol quantity difference still treated as a match.
+    pub quantity_tolerance: Decimal,
+    /// Largest cash difference still treated as a match.
+    pub cash_tolerance: Decimal,
+    /// Treat the broker as authoritative and overwrite local state on a
+    /// mismatch.
+    pub adopt_broker_state: bool,
+    /// Seconds between reconciliations inside [`LiveEngine::run`]; `0`
+    /// reconciles only at day end.
+    pub interval_secs: u64,
+}
+
+impl Default for ReconciliationConfig {
+    fn default() -> Self {
+        Self {
+            quantity_tolerance: Decimal::new(1, 6),
+            cash_tolerance: Decimal::new(1, 2),
+            adopt_broker_state: false,
+            interval_secs: 300,
+        }
+    }
+}
+
+/// Differences found by [`LiveEngine::reconcile`] beyond the configured
+/// tolerances.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReconciliationReport {
+    pub position_mismatches: Vec<PositionMismatch>,
+    /// Broker cash minus local cash, or zero when within tolerance.
+    pub cash_delta: Decimal,
+    /// Whether the broker's view replaced the local one.
+    pub adopted: bool,
+}
+
+impl ReconciliationReport {
+    pub fn is_clean(&self) -> bool {
+        self.position_mismatches.is_empty() && self.cash_delta.is_zero()
+    }
+}
+
+/// What [`LiveEngine::resync`] found when comparing local state with the
+/// broker.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResyncSummary {
+    /// Fills the engine had missed, replayed through `on_fill`.
+    pub replayed_fills: usize,
+    /// Working orders at the broker that were not tracked locally.
+    pub adopted_orders: Vec<OrderId>,
+    /// Tracked orders the broker has since canceled, rejected, or expired.
+    pub closed_orders: Vec<OrderId>,
+    /// Position differences remaining after replaying fills.
+    pub position_mismatches: Vec<PositionMismatch>,
+    /// Broker cash minus local portfolio cash after replaying fills.
+    pub cash_delta: Decimal,
+}
+
+/// End-of-session behaviour for [`LiveEngine::run`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EndOfDayConfig {
+    /// Close every position with market orders shortly before each session
+    /// close.
+    pub flatten_eod: bool,
+    /// How long before the close positions are flattened.
+    pub flatten_minutes_before_close: u32,
+}
+
+impl Default for EndOfDayConfig {
+    fn default() -> Self {
+        Self {
+            flatten_eod: false,
+            flatten_minutes_before_close: 5,
+        }
+    }
+}
+
+/// Liveness monitoring for [`LiveEngine::run`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// Seconds without a market event for a configured symbol, during its
+    /// session, before [`LiveEngineEvent::DataStale`] is emitted; `0`
+    /// disables the watchdog.
+    pub stale_after_secs: u64,
+}
+
+/// Point-in-time health of a [`LiveEngine`], for heartbeats and status
+/// endpoints.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HealthStatus {
+    pub running: bool,
+    pub mode: TradingMode,
+    pub strategy_id: String,
+    pub connection: ConnectionStatus,
+    /// When the last market event for each symbol was received.
+    pub last_market_event_at: Vec<(Symbol, DateTime<Utc>)>,
+    /// When the last fill was received.
+    pub last_fill_at: Option<DateTime<Utc>>,
+    /// Market events received over the last minute.
+    pub events_per_minute: usize,
+    /// Symbols currently reported stale by the watchdog.
+    pub stale_symbols: Vec<Symbol>,
+    pub circuit_breaker_tripped: bool,
+    pub circuit_breaker_tripped_at: Option<DateTime<Utc>>,
+    /// Orders waiting in the [`OrderThrottle`] queue.
+    #[serde(default)]
+    pub queued_orders: usize,
+}
+
+impl HealthStatus {
+    /// Running, connected, receiving data, and free to trade.
+    pub fn is_healthy(&self) -> bool {
+        self.running
+            && self.connection == ConnectionStatus::Connected
+            && self.stale_symbols.is_empty()
+            && !self.circuit_breaker_tripped
+    }
+}
+
+/// Where and how often the engine persists its session for crash recovery.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionPersistence {
+    /// When set, [`LiveEngine::persist_session`] runs against this path
+    /// after fills, order submissions, day ends, and stops.
+    pub path: Option<PathBuf>,
+    /// Seconds between additional saves inside [`LiveEngine::run`]; `0`
+    /// saves only on those events.
+    #[serde(default)]
+    pub interval_secs: u64,
+}
+
+/// Format version written by [`LiveEngine::persist_session`].
+pub const LIVE_SESSION_STATE_VERSION: u32 = 1;
+
+/// Persisted live-session state: the engine config, combined and
+/// per-strategy contexts, tracked orders, risk sessions, and event
+/// high-water mark, wrapped with a format version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiveSessionState {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    pub config: LiveEngineConfig,
+    pub current_time: DateTime<Utc>,
+    /// The portfolio without its positions, which are stored separately in
+    /// `positions` (JSON object keys must be strings).
+    pub portfolio: Portfolio,
+    pub positions: Vec<Position>,
+    pub market_data: Vec<MarketDataBuffer>,
+    pub pending_orders: Vec<Order>,
+    /// The engine-wide risk session.
+    pub risk: RiskSessionState,
+    /// One entry per hosted strategy, in slot order.
+    #[serde(default)]
+    pub strategies: Vec<StrategySessionState>,
+    /// Number of events emitted over the life of the session.
+    pub events_emitted: u64,
+    /// The internal paper book in [`TradingMode::Shadow`].
+    #[serde(default)]
+    pub shadow_broker: Option<PaperBrokerState>,
+    #[serde(default)]
+    pub shadow_report: ShadowReport,
+    #[serde(default)]
+    pub execution_report: ExecutionReport,
+    /// Decision prices of `pending_orders`, by order ID.
+    #[serde(default)]
+    pub decision_prices: Vec<(OrderId, Decimal)>,
+}
+
+/// Persisted state of one [`StrategySlot`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategySessionState {
+    pub allocation: StrategyAllocation,
+    pub current_time: DateTime<Utc>,
+    /// The sub-portfolio without its positions, as in [`LiveSessionState`].
+    pub portfolio: Portfolio,
+    pub positions: Vec<Position>,
+    pub market_data: Vec<MarketDataBuffer>,
+    pub risk: RiskSessionState,
+}
+
+/// Split a context into its portfolio (without positions), sorted
+/// positions, and sorted market data for serialization.
+fn context_parts(context: &StrategyContext) -> (Portfolio, Vec<Position>, Vec<MarketDataBuffer>) {
+    let mut portfolio = context.portfolio.clone();
+    let mut positions: Vec<Position> = portfolio.positions.drain().map(|(_, p)| p).collect();
+    positions.sort_by_key(|position| position.symbol.to_string());
+    let mut market_data: Vec<MarketDataBuffer> = context.market_data.values().cloned().collect();
+    market_data.sort_by_key(|buffer| buffer.symbol.to_string());
+    (portfolio, positions, market_data)
+}
+
+/// Apply a persisted [`StrategySessionState`] to a slot built from the same
+/// allocation.
+fn restore_slot<S: Strategy>(slot: &mut StrategySlot<S>, state: StrategySessionState) {
+    restore_context(
+        &mut slot.context,
+        state.current_time,
+        state.portfolio,
+        state.positions,
+        state.market_data,
+    );
+    slot.risk_manager.restore_session_state(state.risk);
+    slot.day = DayStats::new(&slot.context.portfolio, slot.context.portfolio.total_equity);
+}
+
+/// Inverse of [`context_parts`].
+fn restore_context(
+    context: &mut StrategyContext,
+    current_time: DateTime<Utc>,
+    mut portfolio: Portfolio,
+    positions: Vec<Position>,
+    market_data: Vec<MarketDataBuffer>,
+) {
+    portfolio.positions = positions
+        .into_iter()
+        .map(|position| (position.symbol.clone(), position))
+        .collect();
+    context.portfolio = portfolio;
+    context.current_time = current_time;
+    context.market_data = market_data
+        .into_iter()
+        .map(|buffer| (buffer.symbol.clone(), buffer))
+        .collect();
+}
+
+/// Append `event` to the context's market data and advance its clock.
+fn record_in_context(context: &mut StrategyContext, event: &MarketEvent) {
+    let symbol = event.symbol();
+    context
+        .market_data
+        .entry(symbol.clone())
+        .or_insert_with(|| MarketDataBuffer::new(symbol.clone(), 500))
+        .add_event(event.clone());
+    context.current_time = event.timestamp();
+}
+
+fn session_error(action: &str, path: &Path, error: impl std::fmt::Display) -> String {
+    format!(
+        "failed to {} live session state at {}: {}",
+        action,
+        path.display(),
+        error
+    )
+}
+
+/// Capital and risk limits for one strategy hosted by a [`LiveEngine`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyAllocation {
+    pub strategy_config: StrategyConfig,
+    /// Capital backing the strategy's sub-portfolio.
+    pub capital: Decimal,
+    /// Limits applied to the strategy's own orders, on top of the
+    /// engine-wide [`LiveEngineConfig::risk_config`].
+    pub risk_config: RiskConfig,
+}
+
+/// Running totals for the current session, reported in
+/// [`LiveEngineEvent::DailySummary`].
+#[derive(Debug, Clone)]
+struct DayStats {
+    start_realized_pnl: Decimal,
+    start_commissions: Decimal,
+    fills: usize,
+    peak_equity: Decimal,
+    max_drawdown: Decimal,
+}
+
+impl DayStats {
+    /// Start a session for `portfolio` at (marked) `equity`.
+    fn new(portfolio: &Portfolio, equity: Decimal) -> Self {
+        Self {
+            start_realized_pnl: portfolio.total_realized_pnl,
+            start_commissions: portfolio.total_commissions,
+            fills: 0,
+            peak_equity: equity,
+            max_drawdown: Decimal::ZERO,
+        }
+    }
+
+    fn observe(&mut self, equity: Decimal) {
+        self.peak_equity = self.peak_equity.max(equity);
+        if self.peak_equity > Decimal::ZERO {
+            let drawdown = (self.peak_equity - equity) / self.peak_equity;
+            self.max_drawdown = self.max_drawdown.max(drawdown);
+        }
+    }
+}
+
+/// A strategy hosted by a [`LiveEngine`] together with its allocation,
+/// sub-portfolio, and risk manager.
+pub struct StrategySlot<S: Strategy> {
+    strategy: S,
+    allocation: StrategyAllocation,
+    context: StrategyContext,
+    risk_manager: RiskManager,
+    day: DayStats,
+    /// The strategy's latest decision time and the orders submitted at it,
+    /// from which client order ids are derived.
+    decision: (DateTime<Utc>, u32),
+    /// Carries the strategy id, inside the engine's span; entered around
+    /// the strategy's callbacks and the handling of its actions.
+    span: Span,
+}
+
+impl<S: Strategy> StrategySlot<S> {
+    fn new(strategy: S, allocation: StrategyAllocation, engine_span: &Span) -> Self {
+        let span = telemetry::strategy_span(engine_span, &allocation.strategy_config.strategy_id);
+        let context = StrategyContext::new(
+            allocation.strategy_config.strategy_id.clone(),
+            allocation.capital,
+        );
+        let risk_manager = RiskManager::new(allocation.risk_config.clone(), allocation.capital);
+        let day = DayStats::new(&context.portfolio, context.portfolio.total_equity);
+        let decision = (context.current_time, 0);
+        Self {
+            strategy,
+            allocation,
+            context,
+            risk_manager,
+            day,
+            decision,
+            span,
+        }
+    }
+
+    pub fn strategy_id(&self) -> &str {
+        &self.allocation.strategy_config.strategy_id
+    }
+
+    pub fn strategy(&self) -> &S {
+        &self.strategy
+    }
+
+    pub fn allocation(&self) -> &StrategyAllocation {
+        &self.allocation
+    }
+
+    /// The strategy's own view: its sub-portfolio and the market data for
+    /// its symbols.
+    pub fn context(&self) -> &StrategyContext {
+        &self.context
+    }
+
+    pub fn risk_manager(&self) -> &RiskManager {
+        &self.risk_manager
+    }
+
+    /// Strategies with no configured symbols see every market event.
+    fn wants(&self, symbol: &Symbol) -> bool {
+        let symbols = &self.allocation.strategy_config.symbols;
+        symbols.is_empty() || symbols.contains(symbol)
+    }
+}
+
 /// Configuration for the live trading engine.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LiveEngineConfig {
     pub mode: TradingMode,
+    /// The engine's first strategy; more can be added with
+    /// [`LiveEngine::add_strategy`].
     pub strategy_config: StrategyConfig,
+    /// Limits for the first strategy, and engine-wide limits checked against
+    /// the combined portfolio for every strategy's orders.
     pub risk_config: RiskConfig,
+    /// Capital allocated to the first strategy.
     pub initial_capital: Decimal,
+    /// Session schedule that drives `on_day_end` inside [`LiveEngine::run`].
+    #[serde(default)]
+    pub calendar: TradingCalendar,
+    #[serde(default)]
+    pub reconnect: ReconnectPolicy,
+    #[serde(default)]
+    pub submit_retry: SubmitRetryPolicy,
+    #[serde(default)]
+    pub throttle: ThrottleConfig,
+    #[serde(default)]
+    pub reconciliation: ReconciliationConfig,
+    #[serde(default)]
+    pub persistence: SessionPersistence,
+    #[serde(default)]
+    pub health: HealthConfig,
+    #[serde(default)]
+    pub journal: JournalConfig,
+    #[serde(default)]
+    pub end_of_day: EndOfDayConfig,
+    /// Used in [`TradingMode::Shadow`] only.
+    #[serde(default)]
+    pub shadow: ShadowConfig,
+    /// How [`LiveEngine::on_market_events`] applies the actions strategies
+    /// take on a burst of events sharing a timestamp.
+    #[serde(default)]
+    pub event_ordering: EventOrdering,
 }
 
 /// The live trading engine.  Generic over the broker and strategy
 /// implementations so callers can plug in a paper broker for sandbox mode or a
 /// real brokerage adapter for live trading.
+///
+/// One engine can host several strategies (use `Box<dyn Strategy>` to mix
+/// types) sharing a broker connection. Each [`StrategySlot`] has its own
+/// sub-portfolio and risk manager; the engine keeps the combined portfolio
+/// and an engine-wide risk manager on top.
 pub struct LiveEngine<B: Broker, S: Strategy> {
     broker: B,
-    strategy: S,
+    /// Where orders go in [`TradingMode::Shadow`] instead of `broker`.
+    shadow: Option<PaperBroker>,
+    shadow_report: ShadowReport,
+    /// Costs of the session's fills against their decision prices.
+    execution_report: ExecutionReport,
+    /// Latest price of each working order's symbol when it was submitted.
+    decision_prices: HashMap<OrderId, Decimal>,
+    slots: Vec<StrategySlot<S>>,
+    /// Engine-wide risk manager, checked against the combined portfolio.
     risk_manager: RiskManager,
     config: LiveEngineConfig,
+    /// Combined view across all strategies.
     context: StrategyContext,
-    events: Vec<LiveEngineEvent>,
+    /// Session totals for the combined portfolio.
+    day: DayStats,
+    events: broadcast::Sender<LiveEngineEvent>,
+    /// Events not yet taken by [`LiveEngine::drain_events`]. Unlike a
+    /// subscription this never drops events.
+    event_log: Vec<LiveEngineEvent>,
+    /// Events emitted so far, carried across restored sessions.
+    events_emitted: u64,
+    /// Opened by [`start`](Self::start) when a journal directory is set.
+    journal: Option<OrderJournal>,
     running: bool,
     /// Maps order IDs to the orders tracked locally.
     pending_orders: HashMap<OrderId, Order>,
+    /// Cancels and expirations the broker reported before delivering every
+    /// fill of the order, held with the broker's filled quantity until the
+    /// fill stream catches up.
+    closing_orders: HashMap<OrderId, (Decimal, BrokerOrderUpdate)>,
+    /// Order events for strategies raised while routing their own actions,
+    /// such as the confirmation of a cancel, delivered with the broker's
+    /// updates.
+    order_notices: VecDeque<(usize, OrderEvent)>,
+    /// When each timed order expires; entries for orders that are no longer
+    /// pending are dropped lazily.
+    order_deadlines: HashMap<OrderId, (Instant, OrderTimeout)>,
+    /// When the last market event for each symbol arrived, on the tokio
+    /// clock (for the staleness watchdog) and the wall clock (for health).
+    last_market_events: HashMap<Symbol, (Instant, DateTime<Utc>)>,
+    /// Arrival times of market events over the last minute.
+    recent_market_events: VecDeque<Instant>,
+    last_fill_at: Option<DateTime<Utc>>,
+    /// Symbols reported stale and not yet resumed.
+    stale_symbols: HashSet<Symbol>,
+    /// Staleness baseline for symbols that have not had an event yet.
+    data_watch_started: Instant,
+    /// Orders placed by strategies and not yet submitted.
+    throttle: OrderThrottle,
+    /// Wall-clock time at a tokio instant, from which [`Self::now`] runs.
+    clock_origin: (DateTime<Utc>, Instant),
+    /// Entered by the public entry points, so everything the engine and its
+    /// strategies log carries the trading mode and strategy ids.
+    span: Span,
 }
 
 impl<B: Broker, S: Strategy> LiveEngine<B, S> {
-    /// Create a new live engine.
-    pub fn new(broker: B, strategy: S, config: LiveEngineConfig) -> Self {
+    /// Create a new live engine hosting `strategy` with the configured
+    /// capital and risk limits.
+    pub fn new(broker: B, strategy: S, mut config: LiveEngineConfig) -> Self {
+        let shadow = (config.mode == TradingMode::Shadow).then(|| {
+            config.risk_config.dry_run = true;
+            PaperBroker::new(config.shadow.paper.clone())
+        });
         let context = StrategyContext::new(
             config.strategy_config.strategy_id.clone(),
             config.initial_capital,
         );
         let risk_manager = RiskManager::new(config.risk_config.clone(), config.initial_capital);
+        let span = tracing::error_span!("live_engine", mode = ?config.mode);
+        let slot = StrategySlot::new(
+            strategy,
+            StrategyAllocation {
+                strategy_config: config.strategy_config.clone(),
+                capital: config.initial_capital,
+                risk_config: config.risk_config.clone(),
+            },
+            &span,
+        );
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
 
+        let day = DayStats::new(&context.portfolio, context.portfolio.total_equity);
+        let throttle = OrderThrottle::new(config.throttle.clone());
         Self {
             broker,
-            strategy,
+            shadow,
+            shadow_report: ShadowReport::default(),
+            execution_report: ExecutionReport::default(),
+            decision_prices: HashMap::new(),
+            slots: vec![slot],
             risk_manager,
             config,
             context,
-            events: Vec::new(),
+            day,
+            events,
+            event_log: Vec::new(),
+            events_emitted: 0,
+            journal: None,
             running: false,
             pending_orders: HashMap::new(),
+            closing_orders: HashMap::new(),
+            order_notices: VecDeque::new(),
+            order_deadlines: HashMap::new(),
+            last_market_events: HashMap::new(),
+            recent_market_events: VecDeque::new(),
+            last_fill_at: None,
+            stale_symbols: HashSet::new(),
+            data_watch_started: Instant::now(),
+            throttle,
+            clock_origin: (Utc::now(), Instant::now()),
+            span,
+        }
+    }
+
+    /// Wall-clock time advanced on the tokio clock, so the risk rate window
+    /// and the order throttle's pacing measure time the same way.
+    fn now(&self) -> DateTime<Utc> {
+        let (wall, instant) = self.clock_origin;
+        wall + chrono::Duration::from_std(instant.elapsed()).unwrap_or_default()
+    }
+
+    /// Host another strategy with its own capital and risk limits. Its
+    /// capital is added to the combined portfolio. Only allowed before
+    /// [`start`](Self::start).
+    pub fn add_strategy(
+        &mut self,
+        strategy: S,
+        mut allocation: StrategyAllocation,
+    ) -> Result<(), String> {
+        if self.config.mode == TradingMode::Shadow {
+            allocation.risk_config.dry_run = true;
+        }
+        if self.running {
+            return Err("strategies must be added before the engine starts".into());
         }
+        let strategy_id = &allocation.strategy_config.strategy_id;
+        if self
+            .slots
+            .iter()
+            .any(|slot| slot.strategy_id() == strategy_id)
+        {
+            return Err(format!("strategy {strategy_id} is already hosted"));
+        }
+
+        let portfolio = &mut self.context.portfolio;
+        portfolio.initial_capital += allocation.capital;
+        portfolio.apply_cash_adjustment(
+            allocation.capital,
+            Decimal::ZERO,
+            Decimal::ZERO,
+            portfolio.last_updated,
+        );
+        let equity = portfolio.total_equity;
+        self.risk_manager.reset_daily(equity);
+        self.day = DayStats::new(portfolio, equity);
+        self.slots
+            .push(StrategySlot::new(strategy, allocation, &self.span));
+        Ok(())
     }
 
-    /// Start the engine: connect the broker, initialize the strategy, and
+    /// Start the engine: connect the broker, initialize the strategies, and
     /// subscribe to market data.
     pub async fn start(&mut self) -> Result<(), String> {
+        let span = self.span.clone();
+        self.start_session().instrument(span).await
+    }
+
+    async fn start_session(&mut self) -> Result<(), String> {
         self.broker
             .connect()
             .await
             .map_err(|e| format!("broker connect failed: {e}"))?;
+        if let Some(paper) = &mut self.shadow {
+            paper
+                .connect()
+                .await
+                .map_err(|e| format!("shadow paper book connect failed: {e}"))?;
+        }
+
+        if let (None, Some(dir)) = (&self.journal, &self.config.journal.dir) {
+            let journal = OrderJournal::open(dir, self.config.journal.fsync)
+                .map_err(|e| format!("could not open order journal {}: {e}", dir.display()))?;
+            self.journal = Some(journal);
+        }
+
+        // Adopt orders still working at the broker (e.g. restored from a
+        // persisted paper session) so their fills are tracked.
+        match self.venue().get_open_orders().await {
+            Ok(orders) => {
+                for order in orders {
+                    self.track_order(order);
+                }
+            }
+            Err(e) => warn!(error = %e, "could not load open orders from broker"),
+        }
 
-        self.strategy
-            .initialize(&self.config.strategy_config)
-            .map_err(|e| format!("strategy init failed: {e}"))?;
+        for slot in &mut self.slots {
+            slot.span
+                .in_scope(|| slot.strategy.initialize(&slot.allocation.strategy_config))
+                .map_err(|e| format!("strategy {} init failed: {e}", slot.strategy_id()))?;
+        }
 
         // Subscribe to market data for configured symbols.
         self.broker
-            .subscribe_market_data(&self.config.strategy_config.symbols)
+            .subscribe_market_data(&self.symbols())
             .await
             .map_err(|e| format!("market data subscription failed: {e}"))?;
 
         self.running = true;
+        self.data_watch_started = Instant::now();
+
+        for index in 0..self.slots.len() {
+            let strategy_id = self.slots[index].strategy_id().to_string();
+            info!(mode = ?self.config.mode, strategy = %strategy_id, "live engine started");
+            self.emit(LiveEngineEvent::Started {
+                mode: self.config.mode,
+                strategy_id,
+            });
+        }
 
-        let event = LiveEngineEvent::Started {
-            mode: self.config.mode,
-            strategy_id: self.config.strategy_config.strategy_id.clone(),
+        Ok(())
+    }
+
+    /// Every symbol some hosted strategy trades, in configuration order.
+    fn symbols(&self) -> Vec<Symbol> {
+        let mut symbols: Vec<Symbol> = Vec::new();
+        for slot in &self.slots {
+            for symbol in &slot.allocation.strategy_config.symbols {
+                if !symbols.contains(symbol) {
+                    symbols.push(symbol.clone());
+                }
+            }
+        }
+        symbols
+    }
+
+    /// Index of the slot hosting `strategy_id`, falling back to the first
+    /// strategy for orders placed outside the engine.
+    fn slot_index(&self, strategy_id: &str) -> usize {
+        self.slots
+            .iter()
+            .position(|slot| slot.strategy_id() == strategy_id)
+            .unwrap_or(0)
+    }
+
+    /// Snapshot the session for [`persist_session`](Self::persist_session).
+    pub fn session_state(&self) -> LiveSessionState {
+        let (portfolio, positions, market_data) = context_parts(&self.context);
+        let strategies = self
+            .slots
+            .iter()
+            .map(|slot| {
+                let (portfolio, positions, market_data) = context_parts(&slot.context);
+                StrategySessionState {
+                    allocation: slot.allocation.clone(),
+                    current_time: slot.context.current_time,
+                    portfolio,
+                    positions,
+                    market_data,
+                    risk: slot.risk_manager.session_state(),
+                }
+            })
+            .collect();
+        let mut pending_orders: Vec<Order> = self.pending_orders.values().cloned().collect();
+        pending_orders.sort_by_key(|order| (order.submitted_at, order.id));
+        let mut decision_prices: Vec<(OrderId, Decimal)> = self
+            .decision_prices
+            .iter()
+            .map(|(order_id, price)| (*order_id, *price))
+            .collect();
+        decision_prices.sort();
+
+        LiveSessionState {
+            version: LIVE_SESSION_STATE_VERSION,
+            saved_at: Utc::now(),
+            config: self.config.clone(),
+            current_time: self.context.current_time,
+            portfolio,
+            positions,
+            market_data,
+            pending_orders,
+            risk: self.risk_manager.session_state(),
+            strategies,
+            events_emitted: self.events_emitted,
+            shadow_broker: self.shadow.as_ref().map(PaperBroker::state),
+            shadow_report: self.shadow_report.clone(),
+            execution_report: self.execution_report.clone(),
+            decision_prices,
+        }
+    }
+
+    /// Write the session state to `path` as versioned JSON. The file is
+    /// replaced atomically so a crash mid-write leaves the previous save.
+    pub fn persist_session(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        let json = serde_json::to_vec_pretty(&self.session_state())
+            .map_err(|e| session_error("serialize", path, e))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json).map_err(|e| session_error("write", &tmp, e))?;
+        std::fs::rename(&tmp, path).map_err(|e| session_error("write", path, e))
+    }
+
+    /// Rebuild an engine from a session written by
+    /// [`persist_session`](Self::persist_session) and resume it.
+    ///
+    /// `strategies` must list one strategy per hosted slot, in the order
+    /// they were added. The restored contexts, pending orders, and risk
+    /// sessions (including tripped circuit breakers) are applied before
+    /// [`start`](Self::start); each strategy's [`Strategy::on_restore`] hook
+    /// then runs, and finally [`resync`](Self::resync) and
+    /// [`reconcile`](Self::reconcile) catch up with anything the broker did
+    /// while the engine was down.
+    pub async fn restore_session(
+        path: impl AsRef<Path>,
+        broker: B,
+        strategies: impl IntoIterator<Item = S>,
+    ) -> Result<Self, String> {
+        let path = path.as_ref();
+        let json = std::fs::read(path).map_err(|e| session_error("read", path, e))?;
+        let state: LiveSessionState =
+            serde_json::from_slice(&json).map_err(|e| session_error("parse", path, e))?;
+        if state.version != LIVE_SESSION_STATE_VERSION {
+            return Err(format!(
+                "unsupported live session state version {} (expected {})",
+                state.version, LIVE_SESSION_STATE_VERSION
+            ));
+        }
+
+        let mut strategies = strategies.into_iter();
+        let expected = state.strategies.len().max(1);
+        let Some(first) = strategies.next() else {
+            return Err(format!(
+                "session hosts {expected} strategies but none were given"
+            ));
         };
-        self.emit(event);
+        let mut engine = Self::new(broker, first, state.config);
+        let mut saved = state.strategies.into_iter();
+        if let Some(primary) = saved.next() {
+            restore_slot(&mut engine.slots[0], primary);
+        }
+        for slot_state in saved {
+            let Some(strategy) = strategies.next() else {
+                return Err(format!(
+                    "session hosts {expected} strategies but only {} were given",
+                    engine.slots.len()
+                ));
+            };
+            let mut slot = StrategySlot::new(strategy, slot_state.allocation.clone(), &engine.span);
+            restore_slot(&mut slot, slot_state);
+            engine.slots.push(slot);
+        }
+        if strategies.next().is_some() {
+            return Err(format!(
+                "session hosts {expected} strategies but more were given"
+            ));
+        }
+
+        restore_context(
+            &mut engine.context,
+            state.current_time,
+            state.portfolio,
+            state.positions,
+            state.market_data,
+        );
+        engine.pending_orders = state
+            .pending_orders
+            .into_iter()
+            .map(|order| (order.id, order))
+            .collect();
+        engine.sync_open_orders();
+        engine.risk_manager.restore_session_state(state.risk);
+        if let (Some(paper), Some(paper_state)) = (&mut engine.shadow, state.shadow_broker) {
+            paper
+                .restore_state(paper_state)
+                .map_err(|e| format!("could not restore shadow paper book: {e}"))?;
+        }
+        engine.shadow_report = state.shadow_report;
+        engine.execution_report = state.execution_report;
+        engine.decision_prices = state.decision_prices.into_iter().collect();
+        engine.day = DayStats::new(
+            &engine.context.portfolio,
+            engine.context.portfolio.total_equity,
+        );
+        engine.events_emitted = state.events_emitted;
+
+        engine.start().await?;
+        for slot in &mut engine.slots {
+            slot.span
+                .in_scope(|| slot.strategy.on_restore(&slot.context))
+                .map_err(|e| format!("strategy {} restore failed: {e}", slot.strategy_id()))?;
+        }
+        engine.resync().await?;
+        engine.reconcile().await?;
 
         info!(
-            mode = ?self.config.mode,
-            strategy = %self.config.strategy_config.strategy_id,
-            "live engine started"
+            path = %path.display(),
+            strategy = %engine.config.strategy_config.strategy_id,
+            "live session restored"
         );
+        Ok(engine)
+    }
 
-        Ok(())
+    fn autosave(&self) {
+        if let Some(path) = &self.config.persistence.path {
+            if let Err(e) = self.persist_session(path) {
+                warn!(error = %e, "live session autosave failed");
+            }
+        }
     }
 
-    /// Stop the engine gracefully.
+    /// Stop the engine gracefully. Each strategy's `on_finish` metrics are
+    /// reported on its [`LiveEngineEvent::Stopped`].
     pub async fn stop(&mut self, reason: &str) -> Result<(), String> {
+        let span = self.span.clone();
+        self.stop_session(reason).instrument(span).await
+    }
+
+    async fn stop_session(&mut self, reason: &str) -> Result<(), String> {
         self.running = false;
+        for order in self.throttle.clear() {
+            self.drop_queued_order(order, format!("engine stopped: {reason}"));
+        }
 
-        let _ = self.strategy.on_stop(&self.context);
+        let mut finished = Vec::with_capacity(self.slots.len());
+        for slot in &mut self.slots {
+            let _entered = slot.span.enter();
+            let _ = slot.strategy.on_stop(&slot.context);
+            finished.push(slot.strategy.on_finish(&slot.context));
+        }
 
         self.broker
-            .unsubscribe_market_data(&self.config.strategy_config.symbols)
+            .unsubscribe_market_data(&self.symbols())
             .await
             .map_err(|e| format!("unsubscribe failed: {e}"))?;
 
@@ -158,138 +1188,1309 @@ impl<B: Broker, S: Strategy> LiveEngine<B, S> {
             .disconnect()
             .await
             .map_err(|e| format!("broker disconnect failed: {e}"))?;
+        if let Some(paper) = &mut self.shadow {
+            paper
+                .disconnect()
+                .await
+                .map_err(|e| format!("shadow paper book disconnect failed: {e}"))?;
+        }
 
-        let event = LiveEngineEvent::Stopped {
-            strategy_id: self.config.strategy_config.strategy_id.clone(),
-            reason: reason.to_string(),
+        self.emit(LiveEngineEvent::ExecutionReport {
+            report: self.execution_report.clone(),
+        });
+        for (index, metrics) in finished.into_iter().enumerate() {
+            let strategy_id = self.slots[index].strategy_id().to_string();
+            info!(strategy = %strategy_id, reason = %reason, "live engine stopped");
+            self.emit(LiveEngineEvent::Stopped {
+                strategy_id,
+                reason: reason.to_string(),
+                metrics,
+            });
+        }
+        self.autosave();
+
+        Ok(())
+    }
+
+    /// Drive the engine until `shutdown` is cancelled or `data_stream` ends.
+    ///
+    /// Market events and broker fills are dispatched to
+    /// [`on_market_event`](Self::on_market_event) and
+    /// [`on_fill`](Self::on_fill). `on_day_end` fires whenever a session of
+    /// the configured [`TradingCalendar`] closes: either when the wall clock
+    /// reaches the close, or when a market event stamped in a later session
+    /// arrives (so replayed history gets one day-end per simulated day).
+    /// With [`EndOfDayConfig::flatten_eod`] set, positions are flattened once
+    /// per session, at the configured lead before the close by the same two
+    /// clocks. Errors from individual events are emitted as
+    /// [`LiveEngineEvent::Error`] rather than aborting the loop. On exit any
+    /// fills already delivered are applied before [`stop`](Self::stop).
+    pub async fn run<D, F>(
+        &mut self,
+        data_stream: D,
+        fill_stream: F,
+        shutdown: CancellationToken,
+    ) -> Result<(), String>
+    where
+        D: Stream<Item = MarketEvent> + Unpin,
+        F: Stream<Item = Fill> + Unpin,
+    {
+        let span = self.span.clone();
+        self.run_session(data_stream, fill_stream, shutdown)
+            .instrument(span)
+            .await
+    }
+
+    async fn run_session<D, F>(
+        &mut self,
+        data_stream: D,
+        fill_stream: F,
+        shutdown: CancellationToken,
+    ) -> Result<(), String>
+    where
+        D: Stream<Item = MarketEvent> + Unpin,
+        F: Stream<Item = Fill> + Unpin,
+    {
+        if !self.running {
+            self.start().await?;
+        }
+
+        let mut data_stream = data_stream;
+        let mut fill_stream = fill_stream.fuse();
+        let mut shadow_fills = self.shadow.as_mut().map(|paper| paper.fill_stream().fuse());
+        let mut connection_check = tokio::time::interval(Duration::from_millis(
+            self.config.reconnect.check_interval_ms.max(1),
+        ));
+        connection_check.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut reconcile_timer = match self.config.reconciliation.interval_secs {
+            0 => None,
+            secs => {
+                let period = Duration::from_secs(secs);
+                Some(tokio::time::interval_at(Instant::now() + period, period))
+            }
+        };
+        let mut persist_timer = match self.config.persistence.interval_secs {
+            0 => None,
+            secs => {
+                let period = Duration::from_secs(secs);
+                Some(tokio::time::interval_at(Instant::now() + period, period))
+            }
+        };
+        let mut stale_check = match self.config.health.stale_after_secs {
+            0 => None,
+            _ => Some(tokio::time::interval(STALE_CHECK_INTERVAL)),
         };
-        self.emit(event);
+        // Close of the session the last market event belonged to.
+        let mut session_close: Option<DateTime<Utc>> = None;
+        // Close of the last session whose day end has run.
+        let mut day_ended: Option<DateTime<Utc>> = None;
+        // Close of the last session whose positions were flattened.
+        let mut flattened: Option<DateTime<Utc>> = None;
+        let flatten_lead =
+            chrono::Duration::minutes(self.config.end_of_day.flatten_minutes_before_close.into());
+
+        let reason = loop {
+            let day_end_timer = self.day_end_timer(session_close, day_ended);
+            let flatten_timer = self.flatten_timer(session_close, day_ended, flattened);
+            let shadow_fill = async {
+                match shadow_fills.as_mut() {
+                    Some(fills) => fills.next().await,
+                    None => std::future::pending().await,
+                }
+            };
+            let order_timer = self.order_timer();
+            let throttle_timer = self.throttle_timer();
+            let reconcile_tick = async {
+                match reconcile_timer.as_mut() {
+                    Some(timer) => {
+                        timer.tick().await;
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+            let persist_tick = async {
+                match persist_timer.as_mut() {
+                    Some(timer) => {
+                        timer.tick().await;
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+            let stale_tick = async {
+                match stale_check.as_mut() {
+                    Some(timer) => {
+                        timer.tick().await;
+                    }
+                    None => std::future::pending().await,
+                }
+            };
 
-        info!(
-            strategy = %self.config.strategy_config.strategy_id,
-            reason = %reason,
-            "live engine stopped"
+            tokio::select! {
+                biased;
+
+                _ = shutdown.cancelled() => break "shutdown requested",
+                Some(fill) = fill_stream.next() => {
+                    if let Err(e) = self.on_fill(fill).await {
+                        self.report_error(e);
+                    }
+                }
+                Some(fill) = shadow_fill => {
+                    if let Err(e) = self.on_fill(fill).await {
+                        self.report_error(e);
+                    }
+                }
+                _ = throttle_timer => {
+                    if let Err(e) = self.submit_queued_orders().await {
+                        self.report_error(e);
+                    }
+                }
+                event = data_stream.next() => {
+                    let Some(event) = event else {
+                        break "market data stream ended";
+                    };
+                    let timestamp = event.timestamp();
+                    let close = self.config.calendar.session_close(timestamp);
+                    match session_close {
+                        Some(current) if close > current => {
+                            if day_ended != Some(current) {
+                                day_ended = Some(current);
+                                if let Err(e) = self.on_day_end().await {
+                                    self.report_error(e);
+                                }
+                            }
+                            session_close = Some(close);
+                        }
+                        None => session_close = Some(close),
+                        Some(_) => {}
+                    }
+                    if let Err(e) = self.on_market_event(event).await {
+                        self.report_error(e);
+                    }
+                    if self.config.end_of_day.flatten_eod
+                        && flattened != Some(close)
+                        && timestamp >= close - flatten_lead
+                    {
+                        flattened = Some(close);
+                        if let Err(e) = self.flatten_positions(close).await {
+                            self.report_error(e);
+                        }
+                    }
+                }
+                _ = connection_check.tick() => {
+                    if let Err(e) = self.ensure_connected().await {
+                        self.report_error(e);
+                        break "broker connection lost";
+                    }
+                }
+                _ = reconcile_tick => {
+                    if let Err(e) = self.reconcile().await {
+                        self.report_error(e);
+                    }
+                }
+                _ = persist_tick => self.autosave(),
+                _ = stale_tick => self.check_stale_data(),
+                _ = order_timer => {
+                    if let Err(e) = self.expire_orders().await {
+                        self.report_error(e);
+                    }
+                }
+                close = flatten_timer => {
+                    flattened = Some(close);
+                    if let Err(e) = self.flatten_positions(close).await {
+                        self.report_error(e);
+                    }
+                }
+                close = day_end_timer => {
+                    day_ended = Some(close);
+                    if let Err(e) = self.on_day_end().await {
+                        self.report_error(e);
+                    }
+                }
+            }
+        };
+
+        while let Some(Some(fill)) = fill_stream.next().now_or_never() {
+            if let Err(e) = self.on_fill(fill).await {
+                self.report_error(e);
+            }
+        }
+        if let Some(fills) = shadow_fills.as_mut() {
+            while let Some(Some(fill)) = fills.next().now_or_never() {
+                if let Err(e) = self.on_fill(fill).await {
+                    self.report_error(e);
+                }
+            }
+        }
+
+        self.stop(reason).await
+    }
+
+    /// The session close the end-of-day timers aim at: the current session's
+    /// until its day end has run, then the next one on the calendar. Never
+    /// `day_ended` itself. Sessions that closed in the past (replayed data)
+    /// are left to the event timestamps.
+    fn next_session_close(
+        &self,
+        now: DateTime<Utc>,
+        session_close: Option<DateTime<Utc>>,
+        day_ended: Option<DateTime<Utc>>,
+    ) -> Option<DateTime<Utc>> {
+        let close = match session_close {
+            Some(close) if day_ended == Some(close) => self.config.calendar.session_close(now),
+            Some(close) if close > now => close,
+            Some(_) => return None,
+            None => self.config.calendar.session_close(now),
+        };
+        (day_ended != Some(close)).then_some(close)
+    }
+
+    /// Sleep until the close of the current session on the engine clock,
+    /// yielding that close.
+    fn day_end_timer(
+        &self,
+        session_close: Option<DateTime<Utc>>,
+        day_ended: Option<DateTime<Utc>>,
+    ) -> impl std::future::Future<Output = DateTime<Utc>> {
+        let now = self.now();
+        let close = self.next_session_close(now, session_close, day_ended);
+        async move {
+            match close {
+                Some(close) => {
+                    tokio::time::sleep((close - now).to_std().unwrap_or_default()).await;
+                    close
+                }
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    /// Sleep until the flattening lead before the close of the current
+    /// session on the engine clock, yielding that close. Pending when
+    /// flattening is off or this session was already flattened.
+    fn flatten_timer(
+        &self,
+        session_close: Option<DateTime<Utc>>,
+        day_ended: Option<DateTime<Utc>>,
+        flattened: Option<DateTime<Utc>>,
+    ) -> impl std::future::Future<Output = DateTime<Utc>> {
+        let now = self.now();
+        let close = self
+            .next_session_close(now, session_close, day_ended)
+            .filter(|close| self.config.end_of_day.flatten_eod && flattened != Some(*close));
+        let lead =
+            chrono::Duration::minutes(self.config.end_of_day.flatten_minutes_before_close.into());
+        async move {
+            match close {
+                Some(close) => {
+                    let wait = (close - lead - now).to_std().unwrap_or_default();
+                    tokio::time::sleep(wait).await;
+                    close
+                }
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    /// Submit market orders closing every strategy's open positions, net of
+    /// working orders, ahead of `session_close`. Returns the number of
+    /// orders submitted.
+    async fn flatten_positions(&mut self, session_close: DateTime<Utc>) -> Result<usize, String> {
+        let mut total = 0;
+        for index in 0..self.slots.len() {
+            let strategy_id = self.slots[index].strategy_id().to_string();
+            // Net each position with the strategy's working orders, which
+            // may still fill before the close.
+            let mut exposure: HashMap<Symbol, Decimal> = self.slots[index]
+                .context
+                .portfolio
+                .positions
+                .values()
+                .map(|position| (position.symbol.clone(), position.quantity))
+                .collect();
+            for order in self.pending_orders.values() {
+                if order.strategy_id == strategy_id {
+                    let working = match order.side {
+                        Side::Buy => order.remaining_quantity,
+                        Side::Sell => -order.remaining_quantity,
+                    };
+                    *exposure.entry(order.symbol.clone()).or_default() += working;
+                }
+            }
+            let mut positions: Vec<(Symbol, Decimal)> = exposure
+                .into_iter()
+                .filter(|(_, quantity)| !quantity.is_zero())
+                .collect();
+            if positions.is_empty() {
+                continue;
+            }
+            positions.sort_by_key(|(symbol, _)| symbol.to_string());
+
+            let mut orders = 0;
+            for (symbol, quantity) in positions {
+                let side = if quantity > Decimal::ZERO {
+                    Side::Sell
+                } else {
+                    Side::Buy
+                };
+                let mut order =
+                    Order::market_order(symbol, side, quantity.abs(), strategy_id.clone());
+                order.metadata = serde_json::json!({ "flatten_eod": true });
+                if self.submit_order(order).await?.is_some() {
+                    orders += 1;
+                }
+            }
+            info!(strategy = %strategy_id, orders, "flattened positions before the close");
+            self.emit(LiveEngineEvent::PositionsFlattened {
+                strategy_id,
+                session_close,
+                orders,
+            });
+            total += orders;
+        }
+        Ok(total)
+    }
+
+    /// Equity and unrealized P&L of `portfolio` with positions marked at the
+    /// broker's latest prices, falling back to their last fill mark.
+    fn mark_to_market(&self, portfolio: &Portfolio) -> (Decimal, Decimal) {
+        let mut equity = portfolio.cash;
+        let mut unrealized = Decimal::ZERO;
+        for position in portfolio.positions.values() {
+            match self.broker.get_latest_price(&position.symbol) {
+                Some(price) => {
+                    equity += position.quantity * price;
+                    unrealized += (price - position.average_price) * position.quantity;
+                }
+                None => {
+                    equity += position.market_value;
+                    unrealized += position.unrealized_pnl;
+                }
+            }
+        }
+        (equity, unrealized)
+    }
+
+    /// Feed marked equity into the session drawdown of every strategy and
+    /// the combined portfolio.
+    fn observe_equity(&mut self) {
+        for index in 0..self.slots.len() {
+            let (equity, _) = self.mark_to_market(&self.slots[index].context.portfolio);
+            self.slots[index].day.observe(equity);
+        }
+        let (equity, _) = self.mark_to_market(&self.context.portfolio);
+        self.day.observe(equity);
+    }
+
+    /// The broker orders and account queries go to: the internal paper book
+    /// in [`TradingMode::Shadow`], otherwise the configured broker.
+    fn venue(&self) -> &dyn Broker {
+        match &self.shadow {
+            Some(paper) => paper,
+            None => &self.broker,
+        }
+    }
+
+    fn venue_mut(&mut self) -> &mut dyn Broker {
+        match &mut self.shadow {
+            Some(paper) => paper,
+            None => &mut self.broker,
+        }
+    }
+
+    /// Sleep until the earliest order deadline.
+    fn order_timer(&mut self) -> impl std::future::Future<Output = ()> {
+        self.order_deadlines
+            .retain(|order_id, _| self.pending_orders.contains_key(order_id));
+        let deadline = self
+            .order_deadlines
+            .values()
+            .map(|(deadline, _)| *deadline)
+            .min();
+        async move {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    /// Apply the [`OrderTimeout`] of every pending order whose deadline has
+    /// passed: cancel it, tell the strategy with
+    /// [`OrderEvent::OrderExpired`], and for `Replace` resubmit it re-pegged
+    /// toward the market.
+    pub async fn expire_orders(&mut self) -> Result<(), String> {
+        let now = Instant::now();
+        let mut due: Vec<(Instant, OrderId, OrderTimeout)> = self
+            .order_deadlines
+            .iter()
+            .filter(|(_, (deadline, _))| *deadline <= now)
+            .map(|(order_id, (deadline, timeout))| (*deadline, *order_id, *timeout))
+            .collect();
+        due.sort_by_key(|(deadline, _, _)| *deadline);
+
+        for (_, order_id, timeout) in due {
+            self.order_deadlines.remove(&order_id);
+            let Some(order) = self.pending_orders.get(&order_id).cloned() else {
+                continue;
+            };
+            if let Err(e) = self.venue_mut().cancel_order(order_id).await {
+                warn!(order_id = %order_id, error = %e, "could not cancel timed-out order");
+                continue;
+            }
+            self.forget_order(order_id);
+
+            let reason = format!("order timed out after {}s", timeout.after().as_secs());
+            info!(order_id = %order_id, reason = %reason, "order expired");
+            let index = self.slot_index(&order.strategy_id);
+            self.emit(LiveEngineEvent::OrderExpired {
+                strategy_id: self.slots[index].strategy_id().to_string(),
+                order_id,
+                reason: reason.clone(),
+            });
+            let expired = OrderEvent::OrderExpired { order_id, reason };
+            let slot = &mut self.slots[index];
+            let actions = slot
+                .span
+                .in_scope(|| slot.strategy.on_order_event(&expired, &slot.context))
+                .map_err(|e| format!("strategy error on expiry: {e}"))?;
+            for action in actions {
+                self.handle_action(index, action).await?;
+            }
+
+            if let OrderTimeout::Replace { reprice_bps, .. } = timeout {
+                self.replace_order(order, reprice_bps).await?;
+            }
+        }
+
+        self.process_order_updates().await
+    }
+
+    /// Resubmit the unfilled remainder of an expired limit order with its
+    /// price moved `reprice_bps` toward the latest market price.
+    async fn replace_order(&mut self, order: Order, reprice_bps: u32) -> Result<(), String> {
+        let OrderType::Limit { price } = order.order_type else {
+            return Ok(());
+        };
+        let step = price * Decimal::from(reprice_bps) / Decimal::from(10_000);
+        let market = self.broker.get_latest_price(&order.symbol);
+        let limit_price = match order.side {
+            Side::Buy => market.map_or(price + step, |m| (price + step).min(m.max(price))),
+            Side::Sell => market.map_or(price - step, |m| (price - step).max(m.min(price))),
+        };
+
+        let mut replacement = Order::new(
+            order.symbol.clone(),
+            order.side,
+            order.remaining_quantity,
+            OrderType::Limit { price: limit_price },
+            order.strategy_id.clone(),
         );
+        replacement.time_in_force = order.time_in_force;
+        replacement.metadata = order.metadata.clone();
 
+        let Some(replacement_id) = self.submit_order(replacement.clone()).await? else {
+            return Ok(());
+        };
+        let index = self.slot_index(&order.strategy_id);
+        self.emit(LiveEngineEvent::OrderReplaced {
+            strategy_id: self.slots[index].strategy_id().to_string(),
+            order_id: order.id,
+            replacement_id,
+            limit_price,
+        });
+        let submitted = OrderEvent::OrderSubmitted(replacement);
+        let slot = &mut self.slots[index];
+        let actions = slot
+            .span
+            .in_scope(|| slot.strategy.on_order_event(&submitted, &slot.context))
+            .map_err(|e| format!("strategy error on replacement: {e}"))?;
+        for action in actions {
+            self.handle_action(index, action).await?;
+        }
         Ok(())
     }
 
+    /// Emit [`LiveEngineEvent::DataStale`] once for each configured symbol
+    /// that has had no market event for `stale_after_secs` while the
+    /// calendar's session is open.
+    fn check_stale_data(&mut self) {
+        let threshold = Duration::from_secs(self.config.health.stale_after_secs);
+        if threshold.is_zero() || !self.running || !self.config.calendar.is_open(Utc::now()) {
+            return;
+        }
+        let now = Instant::now();
+        for symbol in self.symbols() {
+            if self.stale_symbols.contains(&symbol) {
+                continue;
+            }
+            let last = self
+                .last_market_events
+                .get(&symbol)
+                .map_or(self.data_watch_started, |(at, _)| *at);
+            let silent = now.duration_since(last);
+            if silent < threshold {
+                continue;
+            }
+            warn!(symbol = %symbol, seconds = silent.as_secs(), "market data is stale");
+            self.emit(LiveEngineEvent::DataStale {
+                symbol: symbol.to_string(),
+                seconds: silent.as_secs(),
+            });
+            self.stale_symbols.insert(symbol);
+        }
+    }
+
+    fn report_error(&mut self, message: String) {
+        error!(error = %message, "live engine error");
+        self.emit(LiveEngineEvent::Error { message });
+    }
+
+    /// Reconnect and resync if the broker no longer reports a live
+    /// connection. Returns whether a reconnect took place.
+    pub async fn ensure_connected(&mut self) -> Result<bool, String> {
+        if !self.running || self.broker.connection_status() == ConnectionStatus::Connected {
+            return Ok(false);
+        }
+        self.reconnect().await?;
+        Ok(true)
+    }
+
+    /// Reconnect following the configured [`ReconnectPolicy`], then
+    /// resubscribe to market data and [`resync`](Self::resync).
+    async fn reconnect(&mut self) -> Result<(), String> {
+        let policy = self.config.reconnect.clone();
+        warn!(
+            strategy = %self.config.strategy_config.strategy_id,
+            "broker connection lost; reconnecting"
+        );
+
+        let mut last_error = String::from("reconnects disabled");
+        for attempt in 0..policy.max_attempts {
+            if attempt > 0 {
+                tokio::time::sleep(backoff_delay(
+                    policy.initial_backoff_ms,
+                    policy.max_backoff_ms,
+                    attempt - 1,
+                ))
+                .await;
+            }
+            match self.broker.connect().await {
+                Ok(()) => {
+                    self.emit(LiveEngineEvent::Reconnected {
+                        attempts: attempt + 1,
+                    });
+                    let symbols = self.symbols();
+                    self.broker
+                        .subscribe_market_data(&symbols)
+                        .await
+                        .map_err(|e| format!("market data subscription failed: {e}"))?;
+                    self.resync().await?;
+                    return Ok(());
+                }
+                Err(e) => {
+                    warn!(attempt = attempt + 1, error = %e, "broker reconnect failed");
+                    last_error = e.to_string();
+                }
+            }
+        }
+
+        Err(format!(
+            "broker reconnect failed after {} attempts: {last_error}",
+            policy.max_attempts
+        ))
+    }
+
+    /// Pull open orders, positions, and balance from the broker and
+    /// reconcile them with the local portfolio and pending orders.
+    ///
+    /// Fills the engine missed are replayed through
+    /// [`on_fill`](Self::on_fill) at the incremental average price the broker
+    /// reports (without commission, which the order snapshot does not
+    /// carry). Remaining position and cash differences are reported in the
+    /// emitted [`LiveEngineEvent::Resynced`], not corrected.
+    pub async fn resync(&mut self) -> Result<ResyncSummary, String> {
+        let mut summary = ResyncSummary::default();
+        let open_orders = self
+            .venue()
+            .get_open_orders()
+            .await
+            .map_err(|e| format!("resync could not load open orders: {e}"))?;
+
+        let mut tracked: Vec<&Order> = self.pending_orders.values().collect();
+        tracked.sort_by_key(|order| order.submitted_at);
+        let tracked: Vec<OrderId> = tracked.into_iter().map(|order| order.id).collect();
+
+        for order_id in tracked {
+            let broker_order = match open_orders.iter().find(|order| order.id == order_id) {
+                Some(order) => order.clone(),
+                None => match self.venue().get_order(order_id).await {
+                    Ok(order) => order,
+                    Err(e) => {
+                        warn!(order_id = %order_id, error = %e, "resync could not load order");
+                        continue;
+                    }
+                },
+            };
+            let Some(local) = self.pending_orders.get(&order_id) else {
+                continue;
+            };
+
+            let missed = broker_order.filled_quantity - local.filled_quantity;
+            if missed > Decimal::ZERO {
+                let broker_notional = broker_order.average_fill_price.unwrap_or(Decimal::ZERO)
+                    * broker_order.filled_quantity;
+                let local_notional =
+                    local.average_fill_price.unwrap_or(Decimal::ZERO) * local.filled_quantity;
+                let fill = Fill::new(
+                    order_id,
+                    local.symbol.clone(),
+                    local.side,
+                    missed,
+                    (broker_notional - local_notional) / missed,
+                    Decimal::ZERO,
+                    local.strategy_id.clone(),
+                );
+                self.on_fill(fill).await?;
+                summary.replayed_fills += 1;
+            }
+
+            if !broker_order.is_active() && self.forget_order(order_id) {
+                summary.closed_orders.push(order_id);
+            }
+        }
+
+        for order in open_orders {
+            let order_id = order.id;
+            if self.track_order(order) {
+                summary.adopted_orders.push(order_id);
+            }
+        }
+
+        let positions = self
+            .venue()
+            .get_positions()
+            .await
+            .map_err(|e| format!("resync could not load positions: {e}"))?;
+        let balance = self
+            .venue()
+            .get_account_balance()
+            .await
+            .map_err(|e| format!("resync could not load account balance: {e}"))?;
+
+        summary.position_mismatches = self.position_mismatches(&positions, Decimal::ZERO);
+        summary.cash_delta = balance.cash - self.context.portfolio.cash;
+
+        info!(
+            replayed_fills = summary.replayed_fills,
+            adopted = summary.adopted_orders.len(),
+            closed = summary.closed_orders.len(),
+            mismatches = summary.position_mismatches.len(),
+            cash_delta = %summary.cash_delta,
+            "resynced with broker"
+        );
+        self.emit(LiveEngineEvent::Resynced {
+            summary: summary.clone(),
+        });
+
+        Ok(summary)
+    }
+
+    /// Compare the local portfolio with the broker's positions and cash.
+    ///
+    /// Differences beyond the configured [`ReconciliationConfig`] tolerances
+    /// are emitted as [`LiveEngineEvent::ReconciliationMismatch`] and, when
+    /// `adopt_broker_state` is set, the broker's quantities, costs, and cash
+    /// replace the local ones. Runs on the configured interval inside
+    /// [`run`](Self::run) and at every [`on_day_end`](Self::on_day_end).
+    pub async fn reconcile(&mut self) -> Result<ReconciliationReport, String> {
+        let positions = self
+            .venue()
+            .get_positions()
+            .await
+            .map_err(|e| format!("reconciliation could not load positions: {e}"))?;
+        let balance = self
+            .venue()
+            .get_account_balance()
+            .await
+            .map_err(|e| format!("reconciliation could not load account balance: {e}"))?;
+
+        let tolerances = self.config.reconciliation.clone();
+        let cash_delta = balance.cash - self.context.portfolio.cash;
+        let mut report = ReconciliationReport {
+            position_mismatches: self
+                .position_mismatches(&positions, tolerances.quantity_tolerance),
+            cash_delta: if cash_delta.abs() > tolerances.cash_tolerance {
+                cash_delta
+            } else {
+                Decimal::ZERO
+            },
+            adopted: false,
+        };
+        if report.is_clean() {
+            return Ok(report);
+        }
+
+        warn!(
+            mismatches = report.position_mismatches.len(),
+            cash_delta = %report.cash_delta,
+            "portfolio differs from broker"
+        );
+        if tolerances.adopt_broker_state {
+            self.adopt_broker_view(&report, &positions, balance.cash);
+            report.adopted = true;
+        }
+        self.emit(LiveEngineEvent::ReconciliationMismatch {
+            positions: report.position_mismatches.clone(),
+            cash_delta: report.cash_delta,
+            adopted: report.adopted,
+        });
+
+        Ok(report)
+    }
+
+    /// Per-symbol differences between the local portfolio and `positions`
+    /// larger than `tolerance`, sorted by symbol.
+    fn position_mismatches(
+        &self,
+        positions: &[BrokerPosition],
+        tolerance: Decimal,
+    ) -> Vec<PositionMismatch> {
+        let portfolio = &self.context.portfolio;
+        let mut symbols: HashSet<Symbol> = positions.iter().map(|p| p.symbol.clone()).collect();
+        symbols.extend(portfolio.positions.keys().cloned());
+        let mut symbols: Vec<Symbol> = symbols.into_iter().collect();
+        symbols.sort_by_key(|symbol| symbol.to_string());
+
+        symbols
+            .into_iter()
+            .filter_map(|symbol| {
+                let local_quantity = portfolio
+                    .get_position(&symbol)
+                    .map_or(Decimal::ZERO, |p| p.quantity);
+                let broker_quantity = positions
+                    .iter()
+                    .find(|p| p.symbol == symbol)
+                    .map_or(Decimal::ZERO, |p| p.quantity);
+                ((broker_quantity - local_quantity).abs() > tolerance).then_some(PositionMismatch {
+                    symbol,
+                    local_quantity,
+                    broker_quantity,
+                })
+            })
+            .collect()
+    }
+
+    /// Overwrite mismatched local positions and cash with the broker's view,
+    /// keeping the risk manager's position tracking in step.
+    fn adopt_broker_view(
+        &mut self,
+        report: &ReconciliationReport,
+        positions: &[BrokerPosition],
+        broker_cash: Decimal,
+    ) {
+        let now = Utc::now();
+        for mismatch in &report.position_mismatches {
+            let delta = mismatch.delta();
+            let side = if delta > Decimal::ZERO {
+                Side::Buy
+            } else {
+                Side::Sell
+            };
+            let broker = positions.iter().find(|p| p.symbol == mismatch.symbol);
+            let price = broker.map_or(Decimal::ZERO, |p| p.average_cost);
+            self.risk_manager
+                .update_position(&mismatch.symbol, side, delta.abs(), price);
+
+            let portfolio = &mut self.context.portfolio;
+            match broker {
+                Some(broker) if !broker.quantity.is_zero() => {
+                    let position = portfolio
+                        .positions
+                        .entry(mismatch.symbol.clone())
+                        .or_insert_with(|| Position::new(mismatch.symbol.clone()));
+                    position.quantity = broker.quantity;
+                    position.average_price = broker.average_cost;
+                    position.market_value = broker.market_value;
+                    position.unrealized_pnl = broker.unrealized_pnl;
+                    position.last_updated = now;
+                }
+                _ => {
+                    portfolio.positions.remove(&mismatch.symbol);
+                }
+            }
+        }
+
+        // Also refreshes the portfolio totals for the position edits above.
+        let cash_delta = broker_cash - self.context.portfolio.cash;
+        self.context
+            .portfolio
+            .apply_cash_adjustment(cash_delta, Decimal::ZERO, Decimal::ZERO, now);
+        self.context.portfolio.absorb_external_adjustment();
+    }
+
     /// Process an incoming market data event.  Feeds it to the strategy and
     /// routes any resulting actions through the risk manager and broker.
     pub async fn on_market_event(&mut self, event: MarketEvent) -> Result<(), String> {
+        let span = self.span.clone();
+        self.handle_market_event(event).instrument(span).await
+    }
+
+    async fn handle_market_event(&mut self, event: MarketEvent) -> Result<(), String> {
+        self.handle_market_events(vec![event]).await
+    }
+
+    /// Process a burst of market events sharing a timestamp, such as the
+    /// bars of several symbols closing together, in the order given. Every
+    /// event is recorded before any strategy sees one. Under
+    /// [`EventOrdering::Sequential`] each strategy's actions are routed as
+    /// soon as it returns them; under [`EventOrdering::Snapshot`] they are
+    /// routed once every strategy has seen every event, in the order they
+    /// were returned, so no decision sees another's orders.
+    pub async fn on_market_events(&mut self, events: Vec<MarketEvent>) -> Result<(), String> {
+        let span = self.span.clone();
+        self.handle_market_events(events).instrument(span).await
+    }
+
+    async fn handle_market_events(&mut self, events: Vec<MarketEvent>) -> Result<(), String> {
         if !self.running {
             return Err("engine not running".into());
         }
 
+        for event in &events {
+            self.observe_market_event(event).await?;
+        }
+
+        // Let each interested strategy react.
+        let snapshot = self.config.event_ordering == EventOrdering::Snapshot;
+        let mut deferred = Vec::new();
+        for event in &events {
+            let symbol = event.symbol();
+            for index in 0..self.slots.len() {
+                let slot = &mut self.slots[index];
+                if !slot.wants(symbol) {
+                    continue;
+                }
+                let actions = slot
+                    .span
+                    .in_scope(|| slot.strategy.on_market_event(event, &slot.context))
+                    .map_err(|e| format!("strategy {} error: {e}", slot.strategy_id()))?;
+
+                if snapshot {
+                    deferred.extend(actions.into_iter().map(|action| (index, action)));
+                    continue;
+                }
+                for action in actions {
+                    self.handle_action(index, action).await?;
+                }
+            }
+        }
+        for (index, action) in deferred {
+            self.handle_action(index, action).await?;
+        }
+
+        self.process_order_updates().await
+    }
+
+    /// Bring the brokers, risk managers and contexts up to date with one
+    /// market event.
+    async fn observe_market_event(&mut self, event: &MarketEvent) -> Result<(), String> {
         let symbol = event.symbol().clone();
+        self.record_market_event(&symbol);
 
         self.broker
-            .on_market_event(&event)
+            .on_market_event(event)
             .await
             .map_err(|e| format!("broker market data update failed: {e}"))?;
+        if let Some(paper) = &mut self.shadow {
+            paper
+                .on_market_event(event)
+                .await
+                .map_err(|e| format!("shadow paper book update failed: {e}"))?;
+        }
+        self.observe_equity();
 
-        let price = match &event {
+        let price = match event {
             MarketEvent::Bar(bar) => bar.close,
             MarketEvent::Tick(tick) => tick.price,
             MarketEvent::Quote { bid, ask, .. } => (*bid + *ask) / Decimal::from(2),
         };
+        if self.shadow.is_some() {
+            let horizon =
+                chrono::Duration::seconds(self.config.shadow.divergence_horizon_secs as i64);
+            self.shadow_report
+                .observe(&symbol, price, event.timestamp(), horizon);
+        }
         self.risk_manager.update_market_price(&symbol, price);
+        record_in_context(&mut self.context, event);
+        for slot in &mut self.slots {
+            slot.risk_manager.update_market_price(&symbol, price);
+            if slot.wants(&symbol) {
+                record_in_context(&mut slot.context, event);
+            }
+        }
+        Ok(())
+    }
 
-        // Update strategy context's market data buffer.
+    /// Note a market event's arrival for health reporting and clear any
+    /// staleness reported for its symbol.
+    fn record_market_event(&mut self, symbol: &Symbol) {
+        let now = Instant::now();
+        self.last_market_events
+            .insert(symbol.clone(), (now, Utc::now()));
+        self.recent_market_events.push_back(now);
+        while self
+            .recent_market_events
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
         {
-            let buffer = self
-                .context
-                .market_data
-                .entry(symbol.clone())
-                .or_insert_with(|| gb_types::strategy::MarketDataBuffer::new(symbol.clone(), 500));
-            buffer.add_event(event.clone());
+            self.recent_market_events.pop_front();
         }
+        if self.stale_symbols.remove(symbol) {
+            info!(symbol = %symbol, "market data resumed");
+            self.emit(LiveEngineEvent::DataResumed {
+                symbol: symbol.to_string(),
+            });
+        }
+    }
 
-        self.context.current_time = event.timestamp();
-
-        // Let the strategy react.
-        let actions = self
-            .strategy
-            .on_market_event(&event, &self.context)
-            .map_err(|e| format!("strategy error: {e}"))?;
+    /// Process an order fill received from the broker. The fill is applied
+    /// to the combined portfolio and to the sub-portfolio of the strategy
+    /// that placed the order.
+    pub async fn on_fill(&mut self, fill: Fill) -> Result<(), String> {
+        let span = self.span.clone();
+        self.handle_fill(fill).instrument(span).await
+    }
 
-        for action in actions {
-            self.handle_action(action).await?;
+    async fn handle_fill(&mut self, mut fill: Fill) -> Result<(), String> {
+        self.last_fill_at = Some(Utc::now());
+        // Brokers do not echo tags and metadata back; take them from the
+        // order the strategy placed.
+        if fill.tags.is_empty() && fill.metadata.is_null() {
+            if let Some(order) = self.pending_orders.get(&fill.order_id) {
+                fill = fill.with_order_annotations(order);
+            }
         }
+        self.write_journal(JournalRecord::Fill(fill.clone()));
+        let index = match self.pending_orders.get(&fill.order_id) {
+            Some(order) => self.slot_index(&order.strategy_id),
+            None => self.slot_index(&fill.strategy_id),
+        };
 
-        Ok(())
-    }
+        // Live fills cannot separate drift from slippage, so the whole move
+        // from the decision price is reported as slippage.
+        let decision_price = self
+            .decision_prices
+            .get(&fill.order_id)
+            .copied()
+            .unwrap_or(fill.price);
+        self.execution_report.record(&FillExecution {
+            symbol: fill.symbol.clone(),
+            side: fill.side,
+            quantity: fill.quantity,
+            decision_price,
+            arrival_price: decision_price,
+            impact_price: decision_price,
+            fill_price: fill.price,
+            commission: fill.commission,
+        });
 
-    /// Process an order fill received from the broker.
-    pub async fn on_fill(&mut self, fill: Fill) -> Result<(), String> {
-        // Update portfolio
+        // Update portfolios
         self.context.portfolio.apply_fill(&fill);
+        self.slots[index].context.portfolio.apply_fill(&fill);
+        self.debug_check_portfolios(index);
+        self.slots[index].day.fills += 1;
+        self.day.fills += 1;
+        self.observe_equity();
 
         // Update risk manager position tracking
         self.risk_manager
             .update_position(&fill.symbol, fill.side, fill.quantity, fill.price);
+        self.slots[index].risk_manager.update_position(
+            &fill.symbol,
+            fill.side,
+            fill.quantity,
+            fill.price,
+        );
 
-        // Remove from pending if fully filled
-        if let Some(order) = self.pending_orders.get(&fill.order_id) {
-            if order.remaining_quantity <= fill.quantity {
-                self.pending_orders.remove(&fill.order_id);
+        // Track partial fills on the local copy and drop it once complete.
+        let mut tracked = None;
+        if let Some(order) = self.pending_orders.get_mut(&fill.order_id) {
+            order.fill(fill.quantity, fill.price);
+            tracked = Some((order.remaining_quantity, order.filled_quantity));
+            if order.remaining_quantity <= Decimal::ZERO {
+                self.forget_order(fill.order_id);
+            } else {
+                self.sync_open_orders();
             }
         }
 
-        self.emit(LiveEngineEvent::OrderFilled {
-            order_id: fill.order_id,
-            price: fill.price,
-            quantity: fill.quantity,
-        });
+        let strategy_id = self.slots[index].strategy_id().to_string();
+        if self.shadow.is_some() {
+            self.shadow_report
+                .record_fill(&fill, &strategy_id, self.context.current_time);
+            self.emit(LiveEngineEvent::ShadowOrderFilled {
+                strategy_id,
+                order_id: fill.order_id,
+                symbol: fill.symbol.to_string(),
+                price: fill.price,
+                quantity: fill.quantity,
+            });
+        } else {
+            self.emit(LiveEngineEvent::OrderFilled {
+                strategy_id,
+                order_id: fill.order_id,
+                symbol: fill.symbol.to_string(),
+                price: fill.price,
+                quantity: fill.quantity,
+            });
+        }
 
         // Notify strategy
-        let order_event = OrderEvent::OrderFilled {
-            order_id: fill.order_id,
-            fill,
+        let order_id = fill.order_id;
+        let order_event = match tracked {
+            Some((remaining_quantity, _)) if remaining_quantity > Decimal::ZERO => {
+                OrderEvent::OrderPartiallyFilled {
+                    order_id,
+                    fill,
+                    remaining_quantity,
+                }
+            }
+            _ => OrderEvent::OrderFilled { order_id, fill },
         };
-        let actions = self
-            .strategy
-            .on_order_event(&order_event, &self.context)
+        let slot = &mut self.slots[index];
+        let actions = slot
+            .span
+            .in_scope(|| slot.strategy.on_order_event(&order_event, &slot.context))
             .map_err(|e| format!("strategy error on fill: {e}"))?;
 
         for action in actions {
-            self.handle_action(action).await?;
+            self.handle_action(index, action).await?;
+        }
+
+        // A cancel or expiry held back for this fill can now close the order.
+        if let Some((_, filled)) = tracked {
+            let caught_up = self
+                .closing_orders
+                .get(&order_id)
+                .is_some_and(|(broker_filled, _)| filled >= *broker_filled);
+            if let Some((_, update)) = caught_up
+                .then(|| self.closing_orders.remove(&order_id))
+                .flatten()
+            {
+                self.apply_order_update(update).await?;
+            }
+        }
+
+        self.autosave();
+        self.process_order_updates().await
+    }
+
+    /// Apply the order updates the venue reported since the last poll.
+    /// Updates for orders no longer tracked are ignored: the engine already
+    /// closed them itself. Orders the strategy places in response are polled
+    /// in turn.
+    pub async fn process_order_updates(&mut self) -> Result<(), String> {
+        loop {
+            let updates = self.venue_mut().poll_order_updates();
+            if updates.is_empty() && self.order_notices.is_empty() {
+                return Ok(());
+            }
+            for update in updates {
+                self.apply_order_update(update).await?;
+            }
+            while let Some((index, event)) = self.order_notices.pop_front() {
+                let slot = &mut self.slots[index];
+                let actions = slot
+                    .span
+                    .in_scope(|| slot.strategy.on_order_event(&event, &slot.context))
+                    .map_err(|e| format!("strategy error on order update: {e}"))?;
+                for action in actions {
+                    self.handle_action(index, action).await?;
+                }
+            }
+            self.autosave();
+        }
+    }
+
+    /// Apply one broker update to the tracked order and hand the strategy
+    /// the matching [`OrderEvent`]. A rejected, canceled, or expired order
+    /// stops being tracked; a cancel or expiry arriving ahead of fills the
+    /// broker already made waits in `closing_orders` for them.
+    async fn apply_order_update(&mut self, update: BrokerOrderUpdate) -> Result<(), String> {
+        let order_id = update.order_id();
+        let Some(order) = self.pending_orders.get(&order_id).cloned() else {
+            debug!(order_id = %order_id, "ignoring broker update for an untracked order");
+            return Ok(());
+        };
+        let index = self.slot_index(&order.strategy_id);
+        let strategy_id = self.slots[index].strategy_id().to_string();
+
+        match &update {
+            BrokerOrderUpdate::Accepted { quantity, .. } => {
+                if let Some(tracked) = self.pending_orders.get_mut(&order_id) {
+                    tracked.quantity = *quantity;
+                    tracked.remaining_quantity = *quantity - tracked.filled_quantity;
+                }
+                self.sync_open_orders();
+            }
+            BrokerOrderUpdate::Canceled { .. } | BrokerOrderUpdate::Expired { .. } => {
+                if let Ok(broker_order) = self.venue().get_order(order_id).await {
+                    if broker_order.filled_quantity > order.filled_quantity {
+                        self.closing_orders
+                            .insert(order_id, (broker_order.filled_quantity, update));
+                        return Ok(());
+                    }
+                }
+                self.forget_order(order_id);
+                self.order_deadlines.remove(&order_id);
+                match &update {
+                    BrokerOrderUpdate::Expired { reason, .. } => {
+                        info!(order_id = %order_id, reason = %reason, "order expired");
+                        self.emit(LiveEngineEvent::OrderExpired {
+                            strategy_id,
+                            order_id,
+                            reason: reason.clone(),
+                        });
+                    }
+                    _ => self.emit(LiveEngineEvent::OrderCanceled {
+                        strategy_id,
+                        order_id,
+                    }),
+                }
+            }
+            BrokerOrderUpdate::Rejected { reason, .. } => {
+                self.forget_order(order_id);
+                self.order_deadlines.remove(&order_id);
+                warn!(order_id = %order_id, reason = %reason, "broker rejected order");
+                self.emit(LiveEngineEvent::OrderRejectedByBroker {
+                    strategy_id,
+                    order_id,
+                    symbol: order.symbol.to_string(),
+                    error: reason.to_string(),
+                    reason: Some(reason.clone()),
+                });
+            }
         }
 
+        let event = update.to_order_event();
+        let slot = &mut self.slots[index];
+        let actions = slot
+            .span
+            .in_scope(|| slot.strategy.on_order_event(&event, &slot.context))
+            .map_err(|e| format!("strategy error on order update: {e}"))?;
+        for action in actions {
+            self.handle_action(index, action).await?;
+        }
         Ok(())
     }
 
     /// Signal end of trading day to the strategy.
     pub async fn on_day_end(&mut self) -> Result<(), String> {
+        let span = self.span.clone();
+        self.close_day().instrument(span).await
+    }
+
+    async fn close_day(&mut self) -> Result<(), String> {
         if !self.running {
             return Ok(());
         }
 
-        let actions = self
-            .strategy
-            .on_day_end(&self.context)
-            .map_err(|e| format!("strategy day-end error: {e}"))?;
+        for index in 0..self.slots.len() {
+            let slot = &mut self.slots[index];
+            let actions = slot
+                .span
+                .in_scope(|| slot.strategy.on_day_end(&slot.context))
+                .map_err(|e| format!("strategy {} day-end error: {e}", slot.strategy_id()))?;
 
-        for action in actions {
-            self.handle_action(action).await?;
+            for action in actions {
+                self.handle_action(index, action).await?;
+            }
+        }
+        self.process_order_updates().await?;
+
+        if let Err(e) = self.reconcile().await {
+            warn!(error = %e, "day-end reconciliation failed");
+        }
+
+        // Summarize the session and snapshot each portfolio's daily return.
+        let session_close = self
+            .config
+            .calendar
+            .session_close(self.context.current_time);
+        for index in 0..self.slots.len() {
+            let (equity, unrealized_pnl) =
+                self.mark_to_market(&self.slots[index].context.portfolio);
+            let slot = &mut self.slots[index];
+            slot.day.observe(equity);
+            let portfolio = &mut slot.context.portfolio;
+            let daily_return = DailyReturnRecorder.record(portfolio, session_close, equity);
+            let summary = LiveEngineEvent::DailySummary {
+                strategy_id: slot.allocation.strategy_config.strategy_id.clone(),
+                session_close,
+                equity,
+                realized_pnl: portfolio.total_realized_pnl - slot.day.start_realized_pnl,
+                unrealized_pnl,
+                fills: slot.day.fills,
+                commissions: portfolio.total_commissions - slot.day.start_commissions,
+                max_drawdown: slot.day.max_drawdown,
+                daily_return,
+            };
+            self.emit(summary);
         }
+        let (combined_equity, _) = self.mark_to_market(&self.context.portfolio);
+        DailyReturnRecorder.record(&mut self.context.portfolio, session_close, combined_equity);
 
         // Refresh risk manager daily state using the current equity.
+        for index in 0..self.slots.len() {
+            let (marked, _) = self.mark_to_market(&self.slots[index].context.portfolio);
+            let slot = &mut self.slots[index];
+            let equity = slot.context.portfolio.total_equity;
+            slot.risk_manager.reset_daily(equity);
+            slot.day = DayStats::new(&slot.context.portfolio, marked);
+        }
         let equity = self.context.portfolio.total_equity;
         self.risk_manager.reset_daily(equity);
+        self.day = DayStats::new(&self.context.portfolio, combined_equity);
+        self.autosave();
 
         Ok(())
     }
 
-    /// Route a single [`StrategyAction`] through risk checks and the broker.
-    async fn handle_action(&mut self, action: StrategyAction) -> Result<(), String> {
+    /// Route a single [`StrategyAction`] from the strategy in slot `index`
+    /// through risk checks and the broker.
+    async fn handle_action(&mut self, index: usize, action: StrategyAction) -> Result<(), String> {
+        let span = self.slots[index].span.clone();
+        self.route_action(index, action).instrument(span).await
+    }
+
+    async fn route_action(&mut self, index: usize, action: StrategyAction) -> Result<(), String> {
+        let strategy_id = self.slots[index].strategy_id().to_string();
         match action {
-            StrategyAction::PlaceOrder(order) => {
-                self.submit_order(order).await?;
+            StrategyAction::PlaceOrder(mut order) => {
+                order.strategy_id = strategy_id;
+                if self.throttle.config().enabled {
+                    self.queue_order(order).await?;
+                } else {
+                    self.submit_order(order).await?;
+                }
             }
             StrategyAction::CancelOrder { order_id } => {
-                if let Err(e) = self.broker.cancel_order(order_id).await {
-                    warn!(order_id = %order_id, error = %e, "cancel failed");
+                match self.venue_mut().cancel_order(order_id).await {
+                    Ok(()) => {
+                        self.forget_order(order_id);
+                        self.emit(LiveEngineEvent::OrderCanceled {
+                            strategy_id,
+                            order_id,
+                        });
+                        self.order_notices.push_back((
+                            index,
+                            OrderEvent::OrderCanceled {
+                                order_id,
+                                reason: "canceled by strategy".into(),
+                            },
+                        ));
+                    }
+                    Err(e) => warn!(order_id = %order_id, error = %e, "cancel failed"),
                 }
             }
             StrategyAction::WriteCoveredCall(order) => {
@@ -298,18 +2499,24 @@ impl<B: Broker, S: Strategy> LiveEngine<B, S> {
                     order.underlying
                 ));
             }
+            StrategyAction::TradeOption(order) => {
+                return Err(format!(
+                    "live engine does not support TradeOption for {} yet",
+                    order.underlying
+                ));
+            }
             StrategyAction::Log { level, message } => match level {
                 gb_types::strategy::LogLevel::Debug => {
-                    tracing::debug!(strategy = %self.config.strategy_config.strategy_id, "{message}")
+                    tracing::debug!(strategy = %strategy_id, "{message}")
                 }
                 gb_types::strategy::LogLevel::Info => {
-                    tracing::info!(strategy = %self.config.strategy_config.strategy_id, "{message}")
+                    tracing::info!(strategy = %strategy_id, "{message}")
                 }
                 gb_types::strategy::LogLevel::Warning => {
-                    tracing::warn!(strategy = %self.config.strategy_config.strategy_id, "{message}")
+                    tracing::warn!(strategy = %strategy_id, "{message}")
                 }
                 gb_types::strategy::LogLevel::Error => {
-                    tracing::error!(strategy = %self.config.strategy_config.strategy_id, "{message}")
+                    tracing::error!(strategy = %strategy_id, "{message}")
                 }
             },
             StrategyAction::SetParameter { .. } => {
@@ -319,54 +2526,273 @@ impl<B: Broker, S: Strategy> LiveEngine<B, S> {
         Ok(())
     }
 
-    /// Submit an order through the risk manager and, if approved, to the
-    /// broker.
-    async fn submit_order(&mut self, order: Order) -> Result<(), String> {
+    /// Submit an order through the owning strategy's risk manager, then the
+    /// engine-wide one, and, if both approve, to the broker. Returns the
+    /// broker's order id when it was accepted.
+    async fn submit_order(&mut self, mut order: Order) -> Result<Option<OrderId>, String> {
+        let index = self.slot_index(&order.strategy_id);
+        self.assign_client_order_id(index, &mut order);
+        let strategy_id = self.slots[index].strategy_id().to_string();
         let symbol = &order.symbol;
         let price = self
             .broker
             .get_latest_price(symbol)
             .unwrap_or(Decimal::ZERO);
-        let equity = self.context.portfolio.total_equity;
 
-        // Pre-trade risk check
-        let result = self.risk_manager.check_order(&order, price, equity);
+        // Pre-trade risk checks: the strategy's own limits, then the
+        // engine-wide limits against the combined portfolio.
+        let now = self.now();
+        let slot = &mut self.slots[index];
+        let slot_equity = slot.context.portfolio.total_equity;
+        let (result, breaker) =
+            match slot
+                .risk_manager
+                .check_order_at(&order, price, slot_equity, now)
+            {
+                RiskCheckResult::Approved => {
+                    let equity = self.context.portfolio.total_equity;
+                    let result = self.risk_manager.check_order_at(&order, price, equity, now);
+                    let tripped = self.risk_manager.is_circuit_breaker_tripped();
+                    (result, tripped.then_some((None, equity)))
+                }
+                rejected => {
+                    let tripped = slot.risk_manager.is_circuit_breaker_tripped();
+                    (
+                        rejected,
+                        tripped.then(|| (Some(strategy_id.clone()), slot_equity)),
+                    )
+                }
+            };
 
         match result {
-            RiskCheckResult::Approved => match self.broker.submit_order(order.clone()).await {
+            RiskCheckResult::Approved => match self.submit_with_retry(&order).await {
                 Ok(oid) => {
+                    self.write_journal(JournalRecord::Order(Order {
+                        id: oid,
+                        ..order.clone()
+                    }));
                     self.emit(LiveEngineEvent::OrderSubmitted {
+                        strategy_id,
                         order_id: oid,
                         symbol: order.symbol.to_string(),
                         side: format!("{:?}", order.side),
                         quantity: order.quantity,
                     });
-                    self.pending_orders.insert(oid, order);
+                    if let Some(timeout) = OrderTimeout::for_order(
+                        &order,
+                        &self.slots[index].allocation.strategy_config,
+                    ) {
+                        self.order_deadlines
+                            .insert(oid, (Instant::now() + timeout.after(), timeout));
+                    }
+                    if price > Decimal::ZERO {
+                        self.decision_prices.insert(oid, price);
+                    }
+                    self.track_order(Order { id: oid, ..order });
+                    self.autosave();
+                    return Ok(Some(oid));
                 }
                 Err(e) => {
                     self.emit(LiveEngineEvent::OrderRejectedByBroker {
+                        strategy_id,
                         order_id: order.id,
+                        symbol: order.symbol.to_string(),
                         error: e.to_string(),
+                        reason: None,
                     });
                     error!(order_id = %order.id, error = %e, "broker rejected order");
                 }
             },
-            RiskCheckResult::Rejected { reason, .. } => {
+            RiskCheckResult::Rejected { rule, reason } => {
                 self.emit(LiveEngineEvent::OrderRejectedByRisk {
+                    strategy_id,
                     order_id: order.id,
+                    symbol: order.symbol.to_string(),
+                    rule,
                     reason: reason.clone(),
                 });
                 warn!(order_id = %order.id, reason = %reason, "risk manager rejected order");
 
-                if self.risk_manager.is_circuit_breaker_tripped() {
-                    self.emit(LiveEngineEvent::CircuitBreakerTripped { equity });
+                if let Some((strategy_id, equity)) = breaker {
+                    self.emit(LiveEngineEvent::CircuitBreakerTripped {
+                        strategy_id,
+                        equity,
+                    });
+                    self.autosave();
                 }
             }
         }
 
+        Ok(None)
+    }
+
+    /// Give `order` a client order id derived from the decision of the
+    /// strategy in slot `index` that placed it, unless it has one.
+    fn assign_client_order_id(&mut self, index: usize, order: &mut Order) {
+        if order.client_order_id.is_some() {
+            return;
+        }
+        let slot = &mut self.slots[index];
+        let decided_at = slot.context.current_time;
+        if slot.decision.0 != decided_at {
+            slot.decision = (decided_at, 0);
+        }
+        order.client_order_id = Some(order.derive_client_order_id(decided_at, slot.decision.1));
+        slot.decision.1 += 1;
+    }
+
+    /// Queue `order` in the throttle, which [`run`](Self::run) drains. A full
+    /// queue under [`OverflowPolicy::Block`] first submits its oldest order
+    /// once the pacing interval allows.
+    async fn queue_order(&mut self, mut order: Order) -> Result<(), String> {
+        let index = self.slot_index(&order.strategy_id);
+        self.assign_client_order_id(index, &mut order);
+        if self.throttle.is_full() && self.throttle.config().overflow == OverflowPolicy::Block {
+            tokio::time::sleep_until(self.throttle.paced_until()).await;
+            if let Some(oldest) = self.throttle.pop(Instant::now()) {
+                self.submit_order(oldest).await?;
+            }
+        }
+        for dropped in self.throttle.enqueue(order) {
+            self.drop_queued_order(dropped.order, dropped.reason);
+        }
+        Ok(())
+    }
+
+    fn drop_queued_order(&mut self, order: Order, reason: String) {
+        warn!(order_id = %order.id, reason = %reason, "queued order dropped");
+        let index = self.slot_index(&order.strategy_id);
+        self.emit(LiveEngineEvent::OrderDropped {
+            strategy_id: self.slots[index].strategy_id().to_string(),
+            order_id: order.id,
+            symbol: order.symbol.to_string(),
+            reason,
+        });
+    }
+
+    /// Submit the queued orders the throttle's pacing and in-flight limit
+    /// allow now.
+    async fn submit_queued_orders(&mut self) -> Result<(), String> {
+        while let Some(order) = self
+            .throttle
+            .pop_ready(Instant::now(), self.pending_orders.len())
+        {
+            self.submit_order(order).await?;
+        }
         Ok(())
     }
 
+    /// Sleep until the throttle may submit its next queued order.
+    fn throttle_timer(&self) -> impl std::future::Future<Output = ()> {
+        let ready = self.throttle.next_ready(self.pending_orders.len());
+        async move {
+            match ready {
+                Some(ready) => tokio::time::sleep_until(ready).await,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    /// Send `order` to the venue, resubmitting it under the same client
+    /// order id after retryable errors.
+    async fn submit_with_retry(&mut self, order: &Order) -> BrokerResult<OrderId> {
+        let policy = self.config.submit_retry.clone();
+        let mut attempt = 1;
+        loop {
+            match self.venue_mut().submit_order(order.clone()).await {
+                Err(e) if e.is_retryable() && attempt < policy.max_attempts => {
+                    let wait_ms = match e {
+                        BrokerError::RateLimited { retry_after_ms } => {
+                            retry_after_ms.max(policy.backoff_ms)
+                        }
+                        _ => policy.backoff_ms,
+                    };
+                    warn!(
+                        order_id = %order.id,
+                        attempt,
+                        error = %e,
+                        "order submission failed; retrying"
+                    );
+                    tokio::time::sleep(Duration::from_millis(wait_ms)).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Start tracking a working order. Returns `false` if it was already
+    /// tracked.
+    fn track_order(&mut self, order: Order) -> bool {
+        match self.pending_orders.entry(order.id) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                let index = self
+                    .slots
+                    .iter()
+                    .position(|slot| slot.strategy_id() == order.strategy_id)
+                    .unwrap_or(0);
+                self.risk_manager.record_order_opened(&order.symbol);
+                self.slots[index]
+                    .risk_manager
+                    .record_order_opened(&order.symbol);
+                entry.insert(order);
+                self.sync_open_orders();
+                true
+            }
+        }
+    }
+
+    /// Mirror the working orders into the strategy contexts: each slot sees
+    /// its own, the combined context sees all of them, oldest first.
+    fn sync_open_orders(&mut self) {
+        let mut orders: Vec<Order> = self.pending_orders.values().cloned().collect();
+        orders.sort_by_key(|order| (order.submitted_at, order.id));
+        let mut by_slot = vec![Vec::new(); self.slots.len()];
+        for order in &orders {
+            by_slot[self.slot_index(&order.strategy_id)].push(order.clone());
+        }
+        for (slot, orders) in self.slots.iter_mut().zip(by_slot) {
+            slot.context.pending_orders = orders;
+        }
+        self.context.pending_orders = orders;
+    }
+
+    /// Debug builds check the engine's and the filled slot's bookkeeping
+    /// after every fill.
+    fn debug_check_portfolios(&self, slot: usize) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        for portfolio in [&self.context.portfolio, &self.slots[slot].context.portfolio] {
+            if let Err(error) = portfolio.check_invariants() {
+                panic!(
+                    "portfolio {} invariant broken: {error}",
+                    portfolio.account_id
+                );
+            }
+        }
+    }
+
+    /// Stop tracking an order that is no longer working. Returns `false` if
+    /// it was not tracked.
+    fn forget_order(&mut self, order_id: OrderId) -> bool {
+        self.decision_prices.remove(&order_id);
+        self.closing_orders.remove(&order_id);
+        match self.pending_orders.remove(&order_id) {
+            Some(order) => {
+                let index = self.slot_index(&order.strategy_id);
+                self.risk_manager.record_order_closed(&order.symbol);
+                self.slots[index]
+                    .risk_manager
+                    .record_order_closed(&order.symbol);
+                self.sync_open_orders();
+                true
+            }
+            None => false,
+        }
+    }
+
     // -- accessors ----------------------------------------------------------
 
     /// Whether the engine is currently running.
@@ -379,16 +2805,47 @@ impl<B: Broker, S: Strategy> LiveEngine<B, S> {
         self.config.mode
     }
 
-    /// Drain all emitted events (consuming them).
+    /// Subscribe to events emitted from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<LiveEngineEvent> {
+        self.events.subscribe()
+    }
+
+    /// Drain the events emitted since the last call.
     pub fn drain_events(&mut self) -> Vec<LiveEngineEvent> {
-        std::mem::take(&mut self.events)
+        std::mem::take(&mut self.event_log)
     }
 
-    /// Borrow the current strategy context (portfolio, market data, etc.).
+    /// Borrow the combined context: the portfolio across all strategies and
+    /// market data for every symbol.
     pub fn context(&self) -> &StrategyContext {
         &self.context
     }
 
+    /// The hosted strategies, in the order they were added.
+    pub fn strategies(&self) -> &[StrategySlot<S>] {
+        &self.slots
+    }
+
+    /// The slot hosting `strategy_id`, if any.
+    pub fn strategy(&self, strategy_id: &str) -> Option<&StrategySlot<S>> {
+        self.slots
+            .iter()
+            .find(|slot| slot.strategy_id() == strategy_id)
+    }
+
+    /// Equity of each strategy's sub-portfolio, in slot order.
+    pub fn strategy_equity(&self) -> Vec<(String, Decimal)> {
+        self.slots
+            .iter()
+            .map(|slot| {
+                (
+                    slot.strategy_id().to_string(),
+                    slot.context.portfolio.total_equity,
+                )
+            })
+            .collect()
+    }
+
     /// Mutable access to the broker for direct inspection or advanced manual
     /// control in tests/integrations.
     pub fn broker_mut(&mut self) -> &mut B {
@@ -400,13 +2857,85 @@ impl<B: Broker, S: Strategy> LiveEngine<B, S> {
         &self.broker
     }
 
-    /// Access to the risk manager.
+    /// The internal paper book, in [`TradingMode::Shadow`].
+    pub fn shadow_broker(&self) -> Option<&PaperBroker> {
+        self.shadow.as_ref()
+    }
+
+    /// Shadow fills compared with the later market, in
+    /// [`TradingMode::Shadow`].
+    pub fn shadow_report(&self) -> Option<&ShadowReport> {
+        self.shadow.as_ref().map(|_| &self.shadow_report)
+    }
+
+    /// Costs of the session's fills, each measured from the latest price of
+    /// its symbol when the order was submitted.
+    pub fn execution_report(&self) -> &ExecutionReport {
+        &self.execution_report
+    }
+
+    /// Access to the engine-wide risk manager.
     pub fn risk_manager(&self) -> &RiskManager {
         &self.risk_manager
     }
 
+    /// Current health: connection, data freshness, throughput, and circuit
+    /// breaker state.
+    pub fn health(&self) -> HealthStatus {
+        let now = Instant::now();
+        let mut last_market_event_at: Vec<(Symbol, DateTime<Utc>)> = self
+            .last_market_events
+            .iter()
+            .map(|(symbol, (_, at))| (symbol.clone(), *at))
+            .collect();
+        last_market_event_at.sort_by_key(|(symbol, _)| symbol.to_string());
+        let mut stale_symbols: Vec<Symbol> = self.stale_symbols.iter().cloned().collect();
+        stale_symbols.sort_by_key(|symbol| symbol.to_string());
+
+        HealthStatus {
+            running: self.running,
+            mode: self.config.mode,
+            strategy_id: self.config.strategy_config.strategy_id.clone(),
+            connection: self.broker.connection_status(),
+            last_market_event_at,
+            last_fill_at: self.last_fill_at,
+            events_per_minute: self
+                .recent_market_events
+                .iter()
+                .filter(|at| now.duration_since(**at) < Duration::from_secs(60))
+                .count(),
+            stale_symbols,
+            circuit_breaker_tripped: self.risk_manager.is_circuit_breaker_tripped(),
+            circuit_breaker_tripped_at: self.risk_manager.circuit_breaker_tripped_at(),
+            queued_orders: self.throttle.len(),
+        }
+    }
+
+    /// Events emitted over the life of the session, including those emitted
+    /// before it was restored.
+    pub fn events_emitted(&self) -> u64 {
+        self.events_emitted
+    }
+
+    /// The order journal, once [`start`](Self::start) has opened it.
+    pub fn journal(&self) -> Option<&OrderJournal> {
+        self.journal.as_ref()
+    }
+
+    fn write_journal(&mut self, record: JournalRecord) {
+        if let Some(journal) = &mut self.journal {
+            if let Err(e) = journal.append(record, Utc::now()) {
+                warn!(dir = %journal.dir().display(), error = %e, "order journal write failed");
+            }
+        }
+    }
+
     fn emit(&mut self, event: LiveEngineEvent) {
-        self.events.push(event);
+        self.events_emitted += 1;
+        self.write_journal(JournalRecord::Event(event.clone()));
+        // Sending only fails when nobody is subscribed.
+        let _ = self.events.send(event.clone());
+        self.event_log.push(event);
     }
 }
 
@@ -416,6 +2945,7 @@ mod tests {
     use crate::paper::{PaperBroker, PaperBrokerConfig};
     use chrono::Utc;
     use gb_types::market::{AssetClass, Bar, Resolution, Symbol};
+    use gb_types::orders::Side;
     use gb_types::strategy::{BuyAndHoldStrategy, StrategyConfig};
     use rust_decimal_macros::dec;
 
@@ -433,6 +2963,7 @@ mod tests {
             close,
             volume: dec!(1000),
             resolution: Resolution::Day,
+            provenance: Default::default(),
         })
     }
 
@@ -461,6 +2992,17 @@ mod tests {
                 ..Default::default()
             },
             initial_capital: dec!(100_000),
+            calendar: Default::default(),
+            reconnect: Default::default(),
+            submit_retry: Default::default(),
+            throttle: Default::default(),
+            reconciliation: Default::default(),
+            persistence: Default::default(),
+            health: Default::default(),
+            journal: Default::default(),
+            end_of_day: Default::default(),
+            shadow: Default::default(),
+            event_ordering: Default::default(),
         };
 
         LiveEngine::new(broker, strategy, config)
@@ -489,6 +3031,76 @@ mod tests {
             .any(|e| matches!(e, LiveEngineEvent::Stopped { .. })));
     }
 
+    #[tokio::test]
+    async fn drain_events_keeps_events_beyond_the_channel_capacity() {
+        let mut engine = default_engine();
+        let mut slow = engine.subscribe();
+        let count = EVENT_CHANNEL_CAPACITY + 10;
+        for index in 0..count {
+            engine.emit(LiveEngineEvent::Stopped {
+                strategy_id: "test".into(),
+                reason: index.to_string(),
+                metrics: HashMap::new(),
+            });
+        }
+
+        let events = engine.drain_events();
+        assert_eq!(events.len(), count);
+        assert!(matches!(
+            &events[0],
+            LiveEngineEvent::Stopped { reason, .. } if reason == "0"
+        ));
+        assert!(engine.drain_events().is_empty());
+        // Subscribers still only see the most recent events.
+        assert!(matches!(
+            slow.try_recv(),
+            Err(broadcast::error::TryRecvError::Lagged(10))
+        ));
+    }
+
+    #[tokio::test]
+    async fn event_bridge_streams_live_events() {
+        use futures_util::sink;
+        use gb_engine::stream::{EventBridge, EventFilter, EventFrame};
+
+        let mut engine = default_engine();
+        let bridge = EventBridge::spawn(engine.subscribe(), 256);
+        engine.start().await.unwrap();
+        engine.on_market_event(make_bar(dec!(150))).await.unwrap();
+        engine.stop("test").await.unwrap();
+        drop(engine);
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let frames = sink::unfold(tx, |tx, frame: EventFrame| async move {
+            tx.send(frame).map_err(|e| e.to_string())?;
+            Ok::<_, String>(tx)
+        });
+        let filter = EventFilter::all().with_min_severity(EventSeverity::Info);
+        bridge.serve(&filter, Some(0), frames).await.unwrap();
+
+        let mut received = Vec::new();
+        while let Ok(frame) = rx.try_recv() {
+            received.push(frame);
+        }
+        let kinds: Vec<_> = received.iter().map(|frame| frame.kind).collect();
+        assert_eq!(kinds.first(), Some(&"Started"));
+        assert_eq!(kinds.last(), Some(&"Stopped"));
+        assert!(kinds.contains(&"OrderSubmitted"), "{kinds:?}");
+        assert!(!kinds.contains(&"MarketDataReceived"), "{kinds:?}");
+        assert!(received
+            .windows(2)
+            .all(|pair| pair[0].sequence < pair[1].sequence));
+        let started: serde_json::Value = serde_json::from_str(&received[0].json).unwrap();
+        assert_eq!(started["event"]["Started"]["mode"], "Sandbox");
+        assert_eq!(
+            LiveEngineEvent::Error {
+                message: "down".into()
+            }
+            .severity(),
+            EventSeverity::Error
+        );
+    }
+
     #[tokio::test]
     async fn test_engine_rejects_event_when_stopped() {
         let mut engine = default_engine();
@@ -545,6 +3157,447 @@ mod tests {
         engine.on_day_end().await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_engine_keeps_order_pending_until_all_partial_fills_arrive() {
+        let mut engine = default_engine();
+        engine.start().await.unwrap();
+
+        let order = Order::limit_order(test_symbol(), Side::Buy, dec!(300), dec!(10), "s".into());
+        let order_id = order.id;
+        engine.pending_orders.insert(order_id, order);
+
+        for (quantity, price) in [(dec!(100), dec!(10)), (dec!(100), dec!(9))] {
+            let fill = Fill::new(
+                order_id,
+                test_symbol(),
+                Side::Buy,
+                quantity,
+                price,
+                Decimal::ZERO,
+                "test_live".into(),
+            );
+            engine.on_fill(fill).await.unwrap();
+        }
+        let pending = &engine.pending_orders[&order_id];
+        assert_eq!(pending.remaining_quantity, dec!(100));
+        assert_eq!(pending.average_fill_price, Some(dec!(9.5)));
+
+        let last = Fill::new(
+            order_id,
+            test_symbol(),
+            Side::Buy,
+            dec!(100),
+            dec!(8),
+            Decimal::ZERO,
+            "test_live".into(),
+        );
+        engine.on_fill(last).await.unwrap();
+        assert!(!engine.pending_orders.contains_key(&order_id));
+    }
+
+    #[tokio::test]
+    async fn test_engine_adopts_broker_open_orders_on_start() {
+        let mut engine = default_engine();
+        let working = Order::limit_order(test_symbol(), Side::Buy, dec!(5), dec!(1), "s".into());
+        let working_id = working.id;
+        let broker = engine.broker_mut();
+        broker.connect().await.unwrap();
+        broker.submit_order(working).await.unwrap();
+        let state = broker.state();
+
+        let mut restarted = default_engine();
+        restarted.broker_mut().restore_state(state).unwrap();
+        restarted.start().await.unwrap();
+        assert!(restarted.pending_orders.contains_key(&working_id));
+    }
+
+    #[tokio::test]
+    async fn test_engine_maintains_open_order_count_for_risk_and_strategies() {
+        let mut engine = default_engine();
+        engine.risk_manager = RiskManager::new(
+            RiskConfig {
+                max_open_orders_per_symbol: Some(1),
+                ..Default::default()
+            },
+            dec!(100_000),
+        );
+        engine.start().await.unwrap();
+        engine
+            .broker_mut()
+            .process_market_event(&make_bar(dec!(100)));
+
+        let first = Order::limit_order(test_symbol(), Side::Buy, dec!(1), dec!(95), "s".into());
+        let first_id = engine.submit_order(first).await.unwrap().unwrap();
+        assert_eq!(engine.risk_manager().open_order_count(&test_symbol()), 1);
+        let slot = engine.strategies()[0].context();
+        assert_eq!(slot.open_order_count(&test_symbol()), 1);
+        assert_eq!(slot.pending_orders[0].id, first_id);
+        assert_eq!(engine.context().open_order_count(&test_symbol()), 1);
+
+        let second = Order::limit_order(test_symbol(), Side::Buy, dec!(1), dec!(96), "s".into());
+        assert_eq!(engine.submit_order(second.clone()).await.unwrap(), None);
+
+        engine
+            .handle_action(0, StrategyAction::CancelOrder { order_id: first_id })
+            .await
+            .unwrap();
+        assert_eq!(engine.risk_manager().open_order_count(&test_symbol()), 0);
+        assert!(!engine.pending_orders.contains_key(&first_id));
+        assert_eq!(
+            engine.strategies()[0]
+                .context()
+                .open_order_count(&test_symbol()),
+            0
+        );
+        assert!(engine.context().pending_orders.is_empty());
+
+        let second_id = engine.submit_order(second).await.unwrap().unwrap();
+        engine
+            .broker_mut()
+            .process_market_event(&make_bar(dec!(90)));
+        let fill = engine.broker().get_order_fills(second_id)[0].clone();
+        engine.on_fill(fill).await.unwrap();
+        assert_eq!(engine.risk_manager().open_order_count(&test_symbol()), 0);
+    }
+
+    /// Buy 10 shares directly at the paper broker, bypassing the engine.
+    async fn drift_behind_engine(engine: &mut LiveEngine<PaperBroker, BuyAndHoldStrategy>) {
+        let broker = engine.broker_mut();
+        broker.process_market_event(&make_bar(dec!(100)));
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(10), "manual".into());
+        broker.submit_order(order).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_engine_reconcile_detects_and_heals_drift() {
+        let mut engine = default_engine();
+        engine.start().await.unwrap();
+        assert!(engine.reconcile().await.unwrap().is_clean());
+
+        drift_behind_engine(&mut engine).await;
+        let broker_cash = engine.broker().get_account_balance().await.unwrap().cash;
+        engine.drain_events();
+
+        let report = engine.reconcile().await.unwrap();
+        assert!(!report.adopted);
+        assert_eq!(report.position_mismatches.len(), 1);
+        assert_eq!(report.position_mismatches[0].delta(), dec!(10));
+        assert_eq!(report.cash_delta, broker_cash - dec!(100_000));
+        assert!(engine.context().portfolio.positions.is_empty());
+        assert!(engine.drain_events().iter().any(|e| matches!(
+            e,
+            LiveEngineEvent::ReconciliationMismatch { adopted: false, .. }
+        )));
+
+        // Within tolerance: not reported.
+        engine.config.reconciliation.quantity_tolerance = dec!(10);
+        engine.config.reconciliation.cash_tolerance = dec!(10_000);
+        assert!(engine.reconcile().await.unwrap().is_clean());
+
+        engine.config.reconciliation = ReconciliationConfig {
+            adopt_broker_state: true,
+            ..Default::default()
+        };
+        assert!(engine.reconcile().await.unwrap().adopted);
+        let position = engine
+            .context()
+            .portfolio
+            .get_position(&test_symbol())
+            .unwrap();
+        assert_eq!(position.quantity, dec!(10));
+        assert_eq!(engine.context().portfolio.cash, broker_cash);
+        assert!(engine.reconcile().await.unwrap().is_clean());
+    }
+
+    #[tokio::test]
+    async fn test_engine_day_end_reconciles_with_broker() {
+        let mut engine = default_engine();
+        engine.start().await.unwrap();
+        drift_behind_engine(&mut engine).await;
+        engine.drain_events();
+
+        engine.on_day_end().await.unwrap();
+        assert!(engine
+            .drain_events()
+            .iter()
+            .any(|e| matches!(e, LiveEngineEvent::ReconciliationMismatch { .. })));
+    }
+
+    /// Paper broker whose connection can be dropped from the test, refusing
+    /// the next `failed_connects` reconnect attempts. The next `lost_acks`
+    /// submissions are accepted but reported as timed out.
+    struct FlakyBroker {
+        inner: PaperBroker,
+        dropped: bool,
+        failed_connects: u32,
+        lost_acks: u32,
+    }
+
+    #[async_trait::async_trait]
+    impl Broker for FlakyBroker {
+        async fn connect(&mut self) -> crate::broker::BrokerResult<()> {
+            if self.failed_connects > 0 {
+                self.failed_connects -= 1;
+                return Err(crate::broker::BrokerError::NotConnected);
+            }
+            self.dropped = false;
+            self.inner.connect().await
+        }
+        async fn disconnect(&mut self) -> crate::broker::BrokerResult<()> {
+            self.inner.disconnect().await
+        }
+        fn connection_status(&self) -> ConnectionStatus {
+            if self.dropped {
+                ConnectionStatus::Disconnected
+            } else {
+                self.inner.connection_status()
+            }
+        }
+        async fn submit_order(&mut self, order: Order) -> crate::broker::BrokerResult<OrderId> {
+            let order_id = self.inner.submit_order(order).await?;
+            if self.lost_acks > 0 {
+                self.lost_acks -= 1;
+                return Err(crate::broker::BrokerError::Internal {
+                    message: "request timed out".into(),
+                });
+            }
+            Ok(order_id)
+        }
+        async fn cancel_order(&mut self, order_id: OrderId) -> crate::broker::BrokerResult<()> {
+            self.inner.cancel_order(order_id).await
+        }
+        async fn get_order_status(
+            &self,
+            order_id: OrderId,
+        ) -> crate::broker::BrokerResult<gb_types::orders::OrderStatus> {
+            self.inner.get_order_status(order_id).await
+        }
+        async fn get_order(&self, order_id: OrderId) -> crate::broker::BrokerResult<Order> {
+            Broker::get_order(&self.inner, order_id).await
+        }
+        async fn get_open_orders(&self) -> crate::broker::BrokerResult<Vec<Order>> {
+            self.inner.get_open_orders().await
+        }
+        async fn get_account_balance(
+            &self,
+        ) -> crate::broker::BrokerResult<crate::broker::AccountBalance> {
+            self.inner.get_account_balance().await
+        }
+        async fn get_positions(
+            &self,
+        ) -> crate::broker::BrokerResult<Vec<crate::broker::BrokerPosition>> {
+            self.inner.get_positions().await
+        }
+        async fn get_position(
+            &self,
+            symbol: &Symbol,
+        ) -> crate::broker::BrokerResult<Option<crate::broker::BrokerPosition>> {
+            self.inner.get_position(symbol).await
+        }
+        async fn subscribe_market_data(
+            &mut self,
+            symbols: &[Symbol],
+        ) -> crate::broker::BrokerResult<()> {
+            self.inner.subscribe_market_data(symbols).await
+        }
+        async fn unsubscribe_market_data(
+            &mut self,
+            symbols: &[Symbol],
+        ) -> crate::broker::BrokerResult<()> {
+            self.inner.unsubscribe_market_data(symbols).await
+        }
+        fn get_latest_price(&self, symbol: &Symbol) -> Option<Decimal> {
+            self.inner.get_latest_price(symbol)
+        }
+        fn get_all_prices(&self) -> HashMap<Symbol, Decimal> {
+            self.inner.get_all_prices()
+        }
+        async fn on_market_event(
+            &mut self,
+            event: &MarketEvent,
+        ) -> crate::broker::BrokerResult<()> {
+            self.inner.on_market_event(event).await
+        }
+    }
+
+    fn flaky_engine(max_attempts: u32) -> LiveEngine<FlakyBroker, BuyAndHoldStrategy> {
+        let broker = FlakyBroker {
+            inner: PaperBroker::new(PaperBrokerConfig {
+                initial_cash: dec!(100_000),
+                ..Default::default()
+            }),
+            dropped: false,
+            failed_connects: 0,
+            lost_acks: 0,
+        };
+        let mut strategy_config = StrategyConfig::new("flaky".into(), "Flaky".into());
+        strategy_config.add_symbol(test_symbol());
+        let config = LiveEngineConfig {
+            mode: TradingMode::Sandbox,
+            strategy_config,
+            risk_config: RiskConfig::default(),
+            initial_capital: dec!(100_000),
+            calendar: Default::default(),
+            reconnect: ReconnectPolicy {
+                max_attempts,
+                ..Default::default()
+            },
+            submit_retry: Default::default(),
+            throttle: Default::default(),
+            reconciliation: Default::default(),
+            persistence: Default::default(),
+            health: Default::default(),
+            journal: Default::default(),
+            end_of_day: Default::default(),
+            shadow: Default::default(),
+            event_ordering: Default::default(),
+        };
+        LiveEngine::new(broker, BuyAndHoldStrategy::new(), config)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_engine_reconnects_and_replays_fills_missed_while_down() {
+        let mut engine = flaky_engine(5);
+        engine.start().await.unwrap();
+        engine
+            .broker_mut()
+            .inner
+            .process_market_event(&make_bar(dec!(100)));
+
+        let order = Order::limit_order(test_symbol(), Side::Buy, dec!(10), dec!(95), "s".into());
+        let order_id = order.id;
+        engine.submit_order(order).await.unwrap();
+        assert!(engine.pending_orders.contains_key(&order_id));
+        engine.drain_events();
+
+        // The connection drops, and the order fills while the engine is blind.
+        let broker = engine.broker_mut();
+        broker.dropped = true;
+        broker.failed_connects = 2;
+        broker.inner.process_market_event(&make_bar(dec!(94)));
+
+        assert!(engine.ensure_connected().await.unwrap());
+        assert!(!engine.pending_orders.contains_key(&order_id));
+        let position = engine
+            .context()
+            .portfolio
+            .get_position(&test_symbol())
+            .unwrap();
+        assert_eq!(position.quantity, dec!(10));
+        assert_eq!(position.average_price, dec!(95));
+
+        let events = engine.drain_events();
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, LiveEngineEvent::Reconnected { attempts: 3 })));
+        let summary = events
+            .iter()
+            .find_map(|e| match e {
+                LiveEngineEvent::Resynced { summary } => Some(summary.clone()),
+                _ => None,
+            })
+            .expect("expected a Resynced event");
+        assert_eq!(summary.replayed_fills, 1);
+        assert!(summary.position_mismatches.is_empty());
+        assert!(summary.closed_orders.is_empty());
+
+        // Already connected: nothing to do.
+        assert!(!engine.ensure_connected().await.unwrap());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_day_end_timer_fires_once_per_close_on_the_engine_clock() {
+        use chrono::TimeZone;
+
+        let mut engine = default_engine();
+        let close = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+        engine.clock_origin = (close - chrono::Duration::minutes(5), Instant::now());
+        let fills = engine.broker_mut().fill_stream();
+        let shutdown = CancellationToken::new();
+        let cancel = shutdown.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(3600)).await;
+            cancel.cancel();
+        });
+        engine
+            .run(futures_util::stream::pending(), fills, shutdown)
+            .await
+            .unwrap();
+
+        let day_ends = engine
+            .drain_events()
+            .iter()
+            .filter(|event| matches!(event, LiveEngineEvent::DailySummary { .. }))
+            .count();
+        assert_eq!(day_ends, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_engine_retries_a_timed_out_submit_without_filling_twice() {
+        let mut engine = flaky_engine(5);
+        engine.start().await.unwrap();
+        let broker = engine.broker_mut();
+        broker.inner.process_market_event(&make_bar(dec!(100)));
+        // The broker fills the order, but the response never arrives.
+        broker.lost_acks = 1;
+
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(10), "s".into());
+        let order_id = engine.submit_order(order).await.unwrap().unwrap();
+
+        let inner = &engine.broker.inner;
+        assert_eq!(inner.get_fills().len(), 1);
+        let position = inner.get_position(&test_symbol()).await.unwrap().unwrap();
+        assert_eq!(position.quantity, dec!(10));
+        let tracked = &engine.pending_orders[&order_id];
+        assert_eq!(
+            tracked.client_order_id,
+            Broker::get_order(inner, order_id)
+                .await
+                .unwrap()
+                .client_order_id
+        );
+        assert!(tracked.client_order_id.is_some());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_engine_resync_reports_closed_and_untracked_orders() {
+        let mut engine = flaky_engine(5);
+        engine.start().await.unwrap();
+        engine
+            .broker_mut()
+            .inner
+            .process_market_event(&make_bar(dec!(100)));
+
+        let tracked = Order::limit_order(test_symbol(), Side::Buy, dec!(1), dec!(50), "s".into());
+        let tracked_id = tracked.id;
+        engine.submit_order(tracked).await.unwrap();
+
+        // Behind the engine's back: cancel the tracked order, place another.
+        let inner = &mut engine.broker_mut().inner;
+        inner.cancel_order(tracked_id).await.unwrap();
+        let manual = Order::limit_order(test_symbol(), Side::Buy, dec!(1), dec!(60), "s".into());
+        let manual_id = inner.submit_order(manual).await.unwrap();
+
+        let summary = engine.resync().await.unwrap();
+        assert_eq!(summary.closed_orders, vec![tracked_id]);
+        assert_eq!(summary.adopted_orders, vec![manual_id]);
+        assert!(engine.pending_orders.contains_key(&manual_id));
+        assert_eq!(summary.cash_delta, Decimal::ZERO);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_engine_gives_up_after_max_reconnect_attempts() {
+        let mut engine = flaky_engine(3);
+        engine.start().await.unwrap();
+        let broker = engine.broker_mut();
+        broker.dropped = true;
+        broker.failed_connects = 10;
+
+        let err = engine.ensure_connected().await.unwrap_err();
+        assert!(err.contains("after 3 attempts"), "{err}");
+        assert_eq!(engine.broker().failed_connects, 7);
+    }
+
     #[tokio::test]
     async fn test_engine_circuit_breaker_propagates() {
         let risk_config = RiskConfig {
@@ -563,6 +3616,17 @@ mod tests {
             strategy_config,
             risk_config,
             initial_capital: dec!(100_000),
+            calendar: Default::default(),
+            reconnect: Default::default(),
+            submit_retry: Default::default(),
+            throttle: Default::default(),
+            reconciliation: Default::default(),
+            persistence: Default::default(),
+            health: Default::default(),
+            journal: Default::default(),
+            end_of_day: Default::default(),
+            shadow: Default::default(),
+            event_ordering: Default::default(),
         };
 
         let mut engine = LiveEngine::new(broker, strategy, config);
@@ -594,4 +3658,148 @@ mod tests {
             "expected circuit breaker or risk rejection, got: {events:?}"
         );
     }
+
+    #[tokio::test]
+    async fn test_engine_restores_session_after_crash_mid_position() {
+        let dir = tempfile::tempdir().unwrap();
+        let broker_path = dir.path().join("broker.json");
+        let session_path = dir.path().join("session.json");
+        let broker_config = PaperBrokerConfig {
+            initial_cash: dec!(100_000),
+            autosave_path: Some(broker_path.clone()),
+            ..Default::default()
+        };
+
+        let mut engine = default_engine();
+        engine.broker = PaperBroker::new(broker_config.clone());
+        engine.config.persistence.path = Some(session_path.clone());
+        engine.start().await.unwrap();
+        engine.on_market_event(make_bar(dec!(150))).await.unwrap();
+        let fill = engine.broker().get_fills()[0].clone();
+        engine.on_fill(fill).await.unwrap();
+        let quantity = engine
+            .context()
+            .get_position(&test_symbol())
+            .unwrap()
+            .quantity;
+
+        // A 6% drawdown trips the default 5% breaker on the next order,
+        // which autosaves the session.
+        engine.context.portfolio.total_equity = dec!(94_000);
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(1), "test_live".into());
+        assert_eq!(engine.submit_order(order).await.unwrap(), None);
+        assert!(engine.risk_manager().is_circuit_breaker_tripped());
+        let events_emitted = engine.events_emitted();
+        // Crash: no stop, nothing flushed beyond the autosaves.
+        drop(engine);
+
+        let mut broker = PaperBroker::new(broker_config);
+        broker.load_state(&broker_path).unwrap();
+        let mut restored =
+            LiveEngine::restore_session(&session_path, broker, [BuyAndHoldStrategy::new()])
+                .await
+                .unwrap();
+
+        assert!(restored.is_running());
+        assert_eq!(restored.context().portfolio.total_equity, dec!(94_000));
+        assert_eq!(
+            restored
+                .context()
+                .get_position(&test_symbol())
+                .unwrap()
+                .quantity,
+            quantity
+        );
+        assert!(restored.risk_manager().is_circuit_breaker_tripped());
+        assert!(restored.events_emitted() > events_emitted);
+        let events = restored.drain_events();
+        assert!(events.iter().any(|e| matches!(
+            e,
+            LiveEngineEvent::Resynced { summary } if summary.position_mismatches.is_empty()
+        )));
+
+        // The strategy knows it already holds its position.
+        restored.on_market_event(make_bar(dec!(151))).await.unwrap();
+        assert!(!restored
+            .drain_events()
+            .iter()
+            .any(|e| matches!(e, LiveEngineEvent::OrderRejectedByRisk { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_engine_restore_rejects_unknown_session_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let engine = default_engine();
+        let mut state = engine.session_state();
+        state.version = LIVE_SESSION_STATE_VERSION + 1;
+        std::fs::write(&path, serde_json::to_vec(&state).unwrap()).unwrap();
+
+        let err = LiveEngine::restore_session(
+            &path,
+            PaperBroker::with_defaults(),
+            [BuyAndHoldStrategy::new()],
+        )
+        .await
+        .err()
+        .unwrap();
+        assert!(err.contains("unsupported live session state version"));
+    }
+
+    #[tokio::test]
+    async fn test_engine_restores_every_hosted_strategy() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let mut engine = default_engine();
+        let mut second = StrategyConfig::new("second".into(), "Second".into());
+        second.add_symbol(test_symbol());
+        engine
+            .add_strategy(
+                BuyAndHoldStrategy::new(),
+                StrategyAllocation {
+                    strategy_config: second,
+                    capital: dec!(50_000),
+                    risk_config: engine.config.risk_config.clone(),
+                },
+            )
+            .unwrap();
+        engine.start().await.unwrap();
+        assert!(engine
+            .add_strategy(
+                BuyAndHoldStrategy::new(),
+                engine.strategies()[1].allocation().clone(),
+            )
+            .is_err());
+        engine.on_market_event(make_bar(dec!(150))).await.unwrap();
+        for fill in engine.broker().get_fills().to_vec() {
+            engine.on_fill(fill).await.unwrap();
+        }
+        engine.persist_session(&path).unwrap();
+        let equity = engine.strategy_equity();
+        let broker_state = engine.broker().state();
+
+        let mut broker = PaperBroker::with_defaults();
+        broker.restore_state(broker_state.clone()).unwrap();
+        let err = LiveEngine::restore_session(&path, broker, [BuyAndHoldStrategy::new()])
+            .await
+            .err()
+            .unwrap();
+        assert!(err.contains("session hosts 2 strategies"), "{err}");
+
+        let mut broker = PaperBroker::with_defaults();
+        broker.restore_state(broker_state).unwrap();
+        let restored = LiveEngine::restore_session(
+            &path,
+            broker,
+            [BuyAndHoldStrategy::new(), BuyAndHoldStrategy::new()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(restored.strategy_equity(), equity);
+        assert_eq!(
+            restored.strategy("second").unwrap().allocation().capital,
+            dec!(50_000)
+        );
+        assert_eq!(restored.context().portfolio.initial_capital, dec!(150_000));
+    }
 }
@@ -0,0 +1,380 @@
+//! Append-only journal of a live session's order flow.
+//!
+//! [`OrderJournal`] writes every [`LiveEngineEvent`] together with the raw
+//! [`Order`]s and [`Fill`]s behind them as JSON lines, one file per UTC day,
+//! each entry stamped with a monotonic sequence number. [`OrderFlow`] reads a
+//! journal back and recomputes cash and realized P&L without the engine.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use gb_types::market::Symbol;
+use gb_types::orders::{Fill, Order, OrderId};
+use gb_types::portfolio::Position;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::engine::LiveEngineEvent;
+
+/// Where and how the engine journals its order flow.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JournalConfig {
+    /// Directory for the daily journal files; `None` disables journaling.
+    pub dir: Option<PathBuf>,
+    /// Flush every entry to disk before the engine continues.
+    #[serde(default)]
+    pub fsync: bool,
+}
+
+/// What a [`JournalEntry`] records.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "payload", rename_all = "snake_case")]
+pub enum JournalRecord {
+    Event(LiveEngineEvent),
+    /// An order as accepted by the broker, under the broker's id.
+    Order(Order),
+    Fill(Fill),
+}
+
+/// One line of the journal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Strictly increasing across the journal's files, starting at 1.
+    pub seq: u64,
+    pub recorded_at: DateTime<Utc>,
+    pub record: JournalRecord,
+}
+
+/// Writer for a directory of daily JSONL journal files.
+#[derive(Debug)]
+pub struct OrderJournal {
+    dir: PathBuf,
+    fsync: bool,
+    seq: u64,
+    /// The day and handle of the file currently appended to.
+    file: Option<(NaiveDate, File)>,
+}
+
+impl OrderJournal {
+    /// Open the journal in `dir`, creating the directory if needed. Sequence
+    /// numbers continue from the newest existing file, after cutting off a
+    /// last line that a crash left half-written.
+    pub fn open(dir: impl Into<PathBuf>, fsync: bool) -> io::Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)?;
+        let seq = match Self::days(&dir)?.last() {
+            Some(day) => {
+                let path = Self::day_path(&dir, *day);
+                truncate_torn_line(&path)?;
+                read_journal(path)?.last().map_or(0, |entry| entry.seq)
+            }
+            None => 0,
+        };
+        Ok(Self {
+            dir,
+            fsync,
+            seq,
+            file: None,
+        })
+    }
+
+    /// Path of the journal file for `day`.
+    pub fn day_path(dir: impl AsRef<Path>, day: NaiveDate) -> PathBuf {
+        dir.as_ref()
+            .join(format!("orders-{}.jsonl", day.format("%Y-%m-%d")))
+    }
+
+    /// Days with a journal file in `dir`, oldest first.
+    pub fn days(dir: impl AsRef<Path>) -> io::Result<Vec<NaiveDate>> {
+        let mut days: Vec<NaiveDate> = fs::read_dir(dir)?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                let day = name
+                    .to_str()?
+                    .strip_prefix("orders-")?
+                    .strip_suffix(".jsonl")?;
+                NaiveDate::parse_from_str(day, "%Y-%m-%d").ok()
+            })
+            .collect();
+        days.sort();
+        Ok(days)
+    }
+
+    /// Read every entry journaled on `day`.
+    pub fn read_day(dir: impl AsRef<Path>, day: NaiveDate) -> io::Result<Vec<JournalEntry>> {
+        read_journal(Self::day_path(dir, day))
+    }
+
+    /// Append `record` stamped `recorded_at`, rolling over to a new file
+    /// when the UTC day changes. Returns the entry's sequence number.
+    pub fn append(&mut self, record: JournalRecord, recorded_at: DateTime<Utc>) -> io::Result<u64> {
+        let day = recorded_at.date_naive();
+        let file = match &mut self.file {
+            Some((open_day, file)) if *open_day == day => file,
+            slot => {
+                let file = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(Self::day_path(&self.dir, day))?;
+                &mut slot.insert((day, file)).1
+            }
+        };
+
+        let entry = JournalEntry {
+            seq: self.seq + 1,
+            recorded_at,
+            record,
+        };
+        let mut line = serde_json::to_vec(&entry).map_err(io::Error::other)?;
+        line.push(b'\n');
+        file.write_all(&line)?;
+        if self.fsync {
+            file.sync_data()?;
+        }
+        self.seq = entry.seq;
+        Ok(entry.seq)
+    }
+
+    /// Sequence number of the last entry written.
+    pub fn last_seq(&self) -> u64 {
+        self.seq
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Read every entry of one journal file, in order. A last line without its
+/// newline was cut short mid-write and is skipped; any other unreadable line
+/// is an error.
+pub fn read_journal(path: impl AsRef<Path>) -> io::Result<Vec<JournalEntry>> {
+    let path = path.as_ref();
+    let contents = fs::read(path)?;
+    let (complete, torn) = split_torn_line(&contents);
+    if !torn.is_empty() {
+        warn!(
+            path = %path.display(),
+            bytes = torn.len(),
+            "skipping half-written last journal line"
+        );
+    }
+    let mut entries = Vec::new();
+    for (number, line) in complete.split(|byte| *byte == b'\n').enumerate() {
+        if line.trim_ascii().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_slice(line).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: {e}", path.display(), number + 1),
+            )
+        })?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Split a journal file into its newline-terminated lines and whatever
+/// follows the last newline. Every entry is written with its newline in one
+/// go, so anything after it is a torn write.
+fn split_torn_line(contents: &[u8]) -> (&[u8], &[u8]) {
+    let end = contents
+        .iter()
+        .rposition(|byte| *byte == b'\n')
+        .map_or(0, |newline| newline + 1);
+    contents.split_at(end)
+}
+
+/// Cut a torn last line off the journal file at `path`, so that new entries
+/// start on a line of their own.
+fn truncate_torn_line(path: &Path) -> io::Result<()> {
+    let contents = fs::read(path)?;
+    let (complete, torn) = split_torn_line(&contents);
+    if torn.is_empty() {
+        return Ok(());
+    }
+    warn!(
+        path = %path.display(),
+        bytes = torn.len(),
+        "truncating half-written last journal line"
+    );
+    let file = OpenOptions::new().write(true).open(path)?;
+    file.set_len(complete.len() as u64)?;
+    file.sync_data()
+}
+
+/// Order flow reconstructed from journal entries.
+#[derive(Debug, Clone, Default)]
+pub struct OrderFlow {
+    /// Orders accepted by the broker, in submission order.
+    pub orders: Vec<Order>,
+    pub fills: Vec<Fill>,
+    /// Orders rejected by risk checks or the broker.
+    pub rejected: Vec<OrderId>,
+    /// Orders canceled by their strategy or expired by the engine.
+    pub canceled: Vec<OrderId>,
+}
+
+impl OrderFlow {
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a JournalEntry>) -> Self {
+        let mut flow = Self::default();
+        for entry in entries {
+            match &entry.record {
+                JournalRecord::Order(order) => flow.orders.push(order.clone()),
+                JournalRecord::Fill(fill) => flow.fills.push(fill.clone()),
+                JournalRecord::Event(
+                    LiveEngineEvent::OrderRejectedByRisk { order_id, .. }
+                    | LiveEngineEvent::OrderRejectedByBroker { order_id, .. },
+                ) => flow.rejected.push(*order_id),
+                JournalRecord::Event(
+                    LiveEngineEvent::OrderCanceled { order_id, .. }
+                    | LiveEngineEvent::OrderExpired { order_id, .. },
+                ) => flow.canceled.push(*order_id),
+                JournalRecord::Event(_) => {}
+            }
+        }
+        flow
+    }
+
+    /// Rebuild the flow of every journal file in `dir`, oldest day first.
+    pub fn read_dir(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        let mut entries = Vec::new();
+        for day in OrderJournal::days(dir)? {
+            entries.extend(OrderJournal::read_day(dir, day)?);
+        }
+        Ok(Self::from_entries(&entries))
+    }
+
+    /// Net cash moved by the fills, commissions included.
+    pub fn cash_delta(&self) -> Decimal {
+        self.fills.iter().map(Fill::net_amount).sum()
+    }
+
+    /// Commissions paid across all fills.
+    pub fn commissions(&self) -> Decimal {
+        self.fills.iter().map(|fill| fill.commission).sum()
+    }
+
+    /// Positions implied by the fills, with realized P&L on average cost.
+    pub fn positions(&self) -> HashMap<Symbol, Position> {
+        let mut positions: HashMap<Symbol, Position> = HashMap::new();
+        for fill in &self.fills {
+            positions
+                .entry(fill.symbol.clone())
+                .or_insert_with(|| Position::new(fill.symbol.clone()))
+                .apply_fill(fill);
+        }
+        positions
+    }
+
+    /// Realized P&L before commissions, as tracked by the portfolio.
+    pub fn realized_pnl(&self) -> Decimal {
+        self.positions()
+            .values()
+            .map(|position| position.realized_pnl)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use gb_types::orders::Side;
+    use rust_decimal_macros::dec;
+
+    fn fill(side: Side, quantity: Decimal, price: Decimal) -> Fill {
+        Fill::new(
+            uuid::Uuid::new_v4(),
+            Symbol::equity("AAPL"),
+            side,
+            quantity,
+            price,
+            dec!(1),
+            "journal".into(),
+        )
+    }
+
+    #[test]
+    fn test_journal_rotates_by_day_and_resumes_sequence() {
+        let dir = tempfile::tempdir().unwrap();
+        let day_one = Utc.with_ymd_and_hms(2024, 3, 4, 20, 0, 0).unwrap();
+        let day_two = Utc.with_ymd_and_hms(2024, 3, 5, 14, 0, 0).unwrap();
+
+        let mut journal = OrderJournal::open(dir.path(), true).unwrap();
+        let buy = fill(Side::Buy, dec!(10), dec!(100));
+        assert_eq!(
+            journal.append(JournalRecord::Fill(buy), day_one).unwrap(),
+            1
+        );
+        drop(journal);
+
+        let mut journal = OrderJournal::open(dir.path(), false).unwrap();
+        assert_eq!(journal.last_seq(), 1);
+        let sell = fill(Side::Sell, dec!(4), dec!(110));
+        assert_eq!(
+            journal.append(JournalRecord::Fill(sell), day_two).unwrap(),
+            2
+        );
+
+        assert_eq!(
+            OrderJournal::days(dir.path()).unwrap(),
+            vec![day_one.date_naive(), day_two.date_naive()]
+        );
+        let second_day = OrderJournal::read_day(dir.path(), day_two.date_naive()).unwrap();
+        assert_eq!(second_day.len(), 1);
+        assert_eq!(second_day[0].seq, 2);
+
+        let flow = OrderFlow::read_dir(dir.path()).unwrap();
+        assert_eq!(flow.fills.len(), 2);
+        assert_eq!(flow.realized_pnl(), dec!(40));
+        assert_eq!(flow.commissions(), dec!(2));
+        assert_eq!(
+            flow.cash_delta(),
+            dec!(-1000) - dec!(1) + dec!(440) - dec!(1)
+        );
+        assert_eq!(flow.positions()[&Symbol::equity("AAPL")].quantity, dec!(6));
+    }
+
+    #[test]
+    fn test_torn_last_line_is_dropped_on_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = Utc.with_ymd_and_hms(2024, 3, 4, 20, 0, 0).unwrap();
+        let mut journal = OrderJournal::open(dir.path(), false).unwrap();
+        for quantity in [dec!(10), dec!(5)] {
+            let record = JournalRecord::Fill(fill(Side::Buy, quantity, dec!(100)));
+            journal.append(record, day).unwrap();
+        }
+        drop(journal);
+
+        // A crash cuts the second entry short.
+        let path = OrderJournal::day_path(dir.path(), day.date_naive());
+        let contents = fs::read(&path).unwrap();
+        fs::write(&path, &contents[..contents.len() - 20]).unwrap();
+        let entries = read_journal(&path).unwrap();
+        assert_eq!(entries.len(), 1);
+
+        let mut journal = OrderJournal::open(dir.path(), false).unwrap();
+        assert_eq!(journal.last_seq(), 1);
+        let record = JournalRecord::Fill(fill(Side::Sell, dec!(4), dec!(110)));
+        assert_eq!(journal.append(record, day).unwrap(), 2);
+        let entries = read_journal(&path).unwrap();
+        assert_eq!(
+            entries.iter().map(|entry| entry.seq).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        // Damage anywhere but the last line is still an error.
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::write(&path, contents.replacen("\"seq\":1", "\"seq\":", 1)).unwrap();
+        assert_eq!(
+            read_journal(&path).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -1,4 +1,13 @@
+pub mod alpaca;
+pub mod binance;
 pub mod broker;
+pub mod calendar;
 pub mod engine;
+pub mod journal;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 pub mod paper;
+pub mod parity;
 pub mod risk;
+pub mod shadow;
+pub mod throttle;
@@ -0,0 +1,450 @@
+//! Prometheus metrics for the live trading stack.
+//!
+//! [`LiveMetrics`] is fed from [`LiveEngineEvent`]s, [`LiveEngine`] state, and
+//! [`RiskMonitor`](gb_risk::monitor::RiskMonitor) snapshots. The hosting
+//! application serves [`LiveMetrics::encode`] from its scrape endpoint.
+//!
+//! Labels are limited to `strategy_id`, `symbol` (bounded by the strategies'
+//! universes), and `reason` (a risk rule or `broker`); the combined portfolio
+//! is reported under `strategy_id="all"`.
+
+use std::collections::HashMap;
+use std::time::Instant;
+
+use gb_risk::metrics::PortfolioRiskSnapshot;
+use gb_types::orders::OrderId;
+use gb_types::strategy::Strategy;
+use prometheus::{
+    exponential_buckets, GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+
+use crate::broker::Broker;
+use crate::engine::{LiveEngine, LiveEngineEvent};
+use crate::risk::RiskRule;
+
+/// `strategy_id` label used for engine-wide values.
+pub const COMBINED_STRATEGY_LABEL: &str = "all";
+
+/// Counters, gauges, and histograms describing a live trading session.
+pub struct LiveMetrics {
+    registry: Registry,
+    orders_submitted: IntCounterVec,
+    orders_filled: IntCounterVec,
+    orders_rejected: IntCounterVec,
+    orders_expired: IntCounterVec,
+    fill_latency: HistogramVec,
+    equity: GaugeVec,
+    gross_exposure: GaugeVec,
+    circuit_breaker: IntGaugeVec,
+    /// Submission times of orders awaiting their first fill.
+    submitted_at: HashMap<OrderId, Instant>,
+}
+
+impl LiveMetrics {
+    /// Create the metrics and register them in a fresh [`Registry`].
+    pub fn new() -> prometheus::Result<Self> {
+        Self::with_registry(Registry::new())
+    }
+
+    /// Create the metrics and register them in `registry`, e.g. one shared
+    /// with the hosting application's own metrics.
+    pub fn with_registry(registry: Registry) -> prometheus::Result<Self> {
+        let orders_submitted = IntCounterVec::new(
+            Opts::new(
+                "gb_live_orders_submitted_total",
+                "Orders accepted by the broker",
+            ),
+            &["strategy_id", "symbol"],
+        )?;
+        let orders_filled = IntCounterVec::new(
+            Opts::new("gb_live_fills_total", "Fills received from the broker"),
+            &["strategy_id", "symbol"],
+        )?;
+        let orders_rejected = IntCounterVec::new(
+            Opts::new(
+                "gb_live_orders_rejected_total",
+                "Orders rejected by a risk rule or by the broker",
+            ),
+            &["strategy_id", "symbol", "reason"],
+        )?;
+        let orders_expired = IntCounterVec::new(
+            Opts::new(
+                "gb_live_orders_expired_total",
+                "Working orders canceled after their timeout",
+            ),
+            &["strategy_id"],
+        )?;
+        let fill_latency = HistogramVec::new(
+            HistogramOpts::new(
+                "gb_live_fill_latency_seconds",
+                "Time from broker acceptance to an order's first fill",
+            )
+            .buckets(exponential_buckets(0.001, 2.0, 16)?),
+            &["strategy_id"],
+        )?;
+        let equity = GaugeVec::new(
+            Opts::new("gb_live_equity", "Current portfolio equity"),
+            &["strategy_id"],
+        )?;
+        let gross_exposure = GaugeVec::new(
+            Opts::new(
+                "gb_live_gross_exposure",
+                "Sum of absolute position weights from the latest risk snapshot",
+            ),
+            &["strategy_id"],
+        )?;
+        let circuit_breaker = IntGaugeVec::new(
+            Opts::new(
+                "gb_live_circuit_breaker_tripped",
+                "1 while the daily-loss circuit breaker is tripped",
+            ),
+            &["strategy_id"],
+        )?;
+
+        registry.register(Box::new(orders_submitted.clone()))?;
+        registry.register(Box::new(orders_filled.clone()))?;
+        registry.register(Box::new(orders_rejected.clone()))?;
+        registry.register(Box::new(orders_expired.clone()))?;
+        registry.register(Box::new(fill_latency.clone()))?;
+        registry.register(Box::new(equity.clone()))?;
+        registry.register(Box::new(gross_exposure.clone()))?;
+        registry.register(Box::new(circuit_breaker.clone()))?;
+
+        Ok(Self {
+            registry,
+            orders_submitted,
+            orders_filled,
+            orders_rejected,
+            orders_expired,
+            fill_latency,
+            equity,
+            gross_exposure,
+            circuit_breaker,
+            submitted_at: HashMap::new(),
+        })
+    }
+
+    /// Update counters from an engine event, e.g. one received from
+    /// [`LiveEngine::subscribe`].
+    pub fn record_event(&mut self, event: &LiveEngineEvent) {
+        match event {
+            LiveEngineEvent::OrderSubmitted {
+                strategy_id,
+                order_id,
+                symbol,
+                ..
+            } => {
+                self.orders_submitted
+                    .with_label_values(&[strategy_id.as_str(), symbol.as_str()])
+                    .inc();
+                self.submitted_at.insert(*order_id, Instant::now());
+            }
+            LiveEngineEvent::OrderFilled {
+                strategy_id,
+                order_id,
+                symbol,
+                ..
+            } => {
+                self.orders_filled
+                    .with_label_values(&[strategy_id.as_str(), symbol.as_str()])
+                    .inc();
+                if let Some(submitted) = self.submitted_at.remove(order_id) {
+                    self.fill_latency
+                        .with_label_values(&[strategy_id.as_str()])
+                        .observe(submitted.elapsed().as_secs_f64());
+                }
+            }
+            LiveEngineEvent::OrderRejectedByRisk {
+                strategy_id,
+                symbol,
+                rule,
+                ..
+            } => {
+                self.orders_rejected
+                    .with_label_values(&[strategy_id.as_str(), symbol.as_str(), rule_label(*rule)])
+                    .inc();
+            }
+            LiveEngineEvent::OrderRejectedByBroker {
+                strategy_id,
+                symbol,
+                ..
+            } => {
+                self.orders_rejected
+                    .with_label_values(&[strategy_id.as_str(), symbol.as_str(), "broker"])
+                    .inc();
+            }
+            LiveEngineEvent::OrderExpired {
+                strategy_id,
+                order_id,
+                ..
+            } => {
+                self.orders_expired
+                    .with_label_values(&[strategy_id.as_str()])
+                    .inc();
+                self.submitted_at.remove(order_id);
+            }
+            LiveEngineEvent::OrderCanceled { order_id, .. } => {
+                self.submitted_at.remove(order_id);
+            }
+            LiveEngineEvent::CircuitBreakerTripped { strategy_id, .. } => {
+                let label = strategy_id.as_deref().unwrap_or(COMBINED_STRATEGY_LABEL);
+                self.circuit_breaker.with_label_values(&[label]).set(1);
+            }
+            LiveEngineEvent::Stopped { .. } => self.submitted_at.clear(),
+            _ => {}
+        }
+    }
+
+    /// Refresh equity and circuit-breaker gauges for every hosted strategy
+    /// and the combined portfolio.
+    pub fn record_engine<B: Broker, S: Strategy>(&self, engine: &LiveEngine<B, S>) {
+        for slot in engine.strategies() {
+            self.set_strategy_state(
+                slot.strategy_id(),
+                slot.context().portfolio.total_equity,
+                slot.risk_manager().is_circuit_breaker_tripped(),
+            );
+        }
+        self.set_strategy_state(
+            COMBINED_STRATEGY_LABEL,
+            engine.context().portfolio.total_equity,
+            engine.risk_manager().is_circuit_breaker_tripped(),
+        );
+    }
+
+    /// Record a [`RiskMonitor`](gb_risk::monitor::RiskMonitor) snapshot for
+    /// `strategy_id` (or [`COMBINED_STRATEGY_LABEL`]).
+    pub fn record_risk_snapshot(&self, strategy_id: &str, snapshot: &PortfolioRiskSnapshot) {
+        self.gross_exposure
+            .with_label_values(&[strategy_id])
+            .set(to_f64(snapshot.gross_exposure));
+    }
+
+    /// Render every registered metric in the Prometheus text format.
+    pub fn encode(&self) -> prometheus::Result<String> {
+        TextEncoder::new().encode_to_string(&self.registry.gather())
+    }
+
+    /// The registry holding these metrics.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    fn set_strategy_state(&self, strategy_id: &str, equity: Decimal, tripped: bool) {
+        self.equity
+            .with_label_values(&[strategy_id])
+            .set(to_f64(equity));
+        self.circuit_breaker
+            .with_label_values(&[strategy_id])
+            .set(i64::from(tripped));
+    }
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
+/// `reason` label for a risk rejection.
+fn rule_label(rule: RiskRule) -> &'static str {
+    match rule {
+        RiskRule::CircuitBreaker => "circuit_breaker",
+        RiskRule::OrderRateLimit => "order_rate_limit",
+        RiskRule::MaxOrderNotional => "max_order_notional",
+        RiskRule::PositionConcentration => "position_concentration",
+        RiskRule::TotalExposure => "total_exposure",
+        RiskRule::RestrictedSymbol => "restricted_symbol",
+        RiskRule::TradingWindow => "trading_window",
+        RiskRule::PriceDeviation => "price_deviation",
+        RiskRule::AdvParticipation => "adv_participation",
+        RiskRule::MaxPositionQuantity => "max_position_quantity",
+        RiskRule::MaxOpenOrders => "max_open_orders",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{LiveEngineConfig, StrategyAllocation, TradingMode};
+    use crate::paper::{PaperBroker, PaperBrokerConfig};
+    use crate::risk::RiskConfig;
+    use chrono::Utc;
+    use gb_risk::metrics::RiskMetricsCalculator;
+    use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+    use gb_types::strategy::{BuyAndHoldStrategy, StrategyConfig};
+    use rust_decimal_macros::dec;
+
+    fn test_symbol() -> Symbol {
+        Symbol::equity("AAPL")
+    }
+
+    fn relaxed_risk() -> RiskConfig {
+        RiskConfig {
+            max_order_notional: Decimal::from(1_000_000),
+            max_total_exposure: Decimal::from(1_000_000),
+            limits: gb_types::portfolio::RiskLimits {
+                position_concentration_limit: dec!(1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Two buy-and-hold strategies on one paper broker; the second may not
+    /// trade the symbol.
+    fn session() -> LiveEngine<PaperBroker, BuyAndHoldStrategy> {
+        let broker = PaperBroker::new(PaperBrokerConfig {
+            initial_cash: dec!(200_000),
+            ..Default::default()
+        });
+        let mut strategy_config = StrategyConfig::new("trader".into(), "Trader".into());
+        strategy_config.add_symbol(test_symbol());
+        let config = LiveEngineConfig {
+            mode: TradingMode::Sandbox,
+            strategy_config,
+            risk_config: relaxed_risk(),
+            initial_capital: dec!(100_000),
+            calendar: Default::default(),
+            reconnect: Default::default(),
+            submit_retry: Default::default(),
+            throttle: Default::default(),
+            reconciliation: Default::default(),
+            persistence: Default::default(),
+            health: Default::default(),
+            journal: Default::default(),
+            end_of_day: Default::default(),
+            shadow: Default::default(),
+            event_ordering: Default::default(),
+        };
+        let mut engine = LiveEngine::new(broker, BuyAndHoldStrategy::new(), config);
+
+        let mut restricted_config = StrategyConfig::new("restricted".into(), "Restricted".into());
+        restricted_config.add_symbol(test_symbol());
+        engine
+            .add_strategy(
+                BuyAndHoldStrategy::new(),
+                StrategyAllocation {
+                    strategy_config: restricted_config,
+                    capital: dec!(50_000),
+                    risk_config: RiskConfig {
+                        restricted_symbols: vec![test_symbol()],
+                        ..relaxed_risk()
+                    },
+                },
+            )
+            .unwrap();
+        engine
+    }
+
+    #[tokio::test]
+    async fn test_metrics_follow_a_paper_session() {
+        let mut metrics = LiveMetrics::new().unwrap();
+        let mut engine = session();
+        let mut events = engine.subscribe();
+
+        engine.start().await.unwrap();
+        engine
+            .on_market_event(MarketEvent::Bar(Bar {
+                symbol: test_symbol(),
+                timestamp: Utc::now(),
+                open: dec!(150),
+                high: dec!(150),
+                low: dec!(150),
+                close: dec!(150),
+                volume: dec!(1_000_000),
+                resolution: Resolution::Day,
+                provenance: Default::default(),
+            }))
+            .await
+            .unwrap();
+        for fill in engine.broker().get_fills().to_vec() {
+            engine.on_fill(fill).await.unwrap();
+        }
+        while let Ok(event) = events.try_recv() {
+            metrics.record_event(&event);
+        }
+        metrics.record_engine(&engine);
+        let snapshot = RiskMetricsCalculator::compute(
+            &engine.context().portfolio,
+            &[],
+            engine.context().portfolio.total_equity,
+        );
+        metrics.record_risk_snapshot(COMBINED_STRATEGY_LABEL, &snapshot);
+
+        let symbol = test_symbol().to_string();
+        assert_eq!(
+            metrics
+                .orders_submitted
+                .with_label_values(&["trader", symbol.as_str()])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .orders_filled
+                .with_label_values(&["trader", symbol.as_str()])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .orders_rejected
+                .with_label_values(&["restricted", symbol.as_str(), "restricted_symbol"])
+                .get(),
+            1
+        );
+        assert_eq!(
+            metrics
+                .fill_latency
+                .with_label_values(&["trader"])
+                .get_sample_count(),
+            1
+        );
+        assert!(metrics.submitted_at.is_empty());
+        assert!(
+            metrics
+                .equity
+                .with_label_values(&[COMBINED_STRATEGY_LABEL])
+                .get()
+                > 149_000.0
+        );
+        assert_eq!(
+            metrics
+                .circuit_breaker
+                .with_label_values(&["restricted"])
+                .get(),
+            0
+        );
+        assert!(
+            metrics
+                .gross_exposure
+                .with_label_values(&[COMBINED_STRATEGY_LABEL])
+                .get()
+                > 0.5
+        );
+
+        let text = metrics.encode().unwrap();
+        assert!(text.contains(&format!(
+            "gb_live_orders_submitted_total{{strategy_id=\"trader\",symbol=\"{symbol}\"}} 1"
+        )));
+        assert!(text.contains("gb_live_fill_latency_seconds_count{strategy_id=\"trader\"} 1"));
+    }
+
+    #[test]
+    fn test_circuit_breaker_event_sets_gauge() {
+        let mut metrics = LiveMetrics::new().unwrap();
+        metrics.record_event(&LiveEngineEvent::CircuitBreakerTripped {
+            strategy_id: None,
+            equity: dec!(90_000),
+        });
+        assert_eq!(
+            metrics
+                .circuit_breaker
+                .with_label_values(&[COMBINED_STRATEGY_LABEL])
+                .get(),
+            1
+        );
+    }
+}
@@ -5,18 +5,55 @@
 //! live.
 
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use futures_util::Stream;
+use gb_options::{portfolio_margin_requirement, MarginPosition, OptionContract, OptionsExecError};
+use gb_types::backtest::QuantityPolicy;
+use gb_types::margin::{
+    AccountExposure, BuyingPowerAccount, BuyingPowerError, BuyingPowerModel, MarginCall,
+};
 use gb_types::market::{MarketEvent, Symbol};
-use gb_types::orders::{Fill, Order, OrderId, OrderStatus, OrderType, Side};
+use gb_types::orders::{Fill, Order, OrderId, OrderStatus, OrderType, Side, TimeInForce};
+use gb_types::portfolio::Position;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tracing::{info, warn};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
 
 use crate::broker::{
-    AccountBalance, Broker, BrokerError, BrokerPosition, BrokerResult, ConnectionStatus,
+    AccountBalance, Broker, BrokerError, BrokerOrderUpdate, BrokerPosition, BrokerResult,
+    ConnectionStatus, RejectionReason,
 };
 
+/// How the paper broker synthesizes a bid/ask around the last price when no
+/// quote has been observed for a symbol. Spreads are full widths (ask − bid)
+/// in basis points of price.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum SpreadModel {
+    /// No synthetic spread: bid and ask both equal the last price.
+    None,
+    /// The same spread for every symbol.
+    FixedBps { basis_points: u32 },
+    /// Spread per ticker (`Symbol::symbol`), falling back to `default_bps`.
+    PerSymbol {
+        spreads: HashMap<String, u32>,
+        default_bps: u32,
+    },
+    /// `multiplier` × the average `(high − low) / close` of the last
+    /// `lookback` bars, never narrower than `min_bps`.
+    VolatilityScaled {
+        multiplier: Decimal,
+        lookback: usize,
+        min_bps: u32,
+    },
+}
+
+fn bps_fraction(basis_points: u32) -> Decimal {
+    Decimal::from(basis_points) / Decimal::from(10_000)
+}
+
 /// Configuration for the paper broker.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PaperBrokerConfig {
@@ -29,6 +66,46 @@ pub struct PaperBrokerConfig {
     /// Whether to fill market orders immediately at the current price or wait
     /// for the next market event.
     pub fill_market_orders_immediately: bool,
+    /// Cap on the share of each market event's liquidity a single event may
+    /// fill: `rate × volume` for bars, `rate × size` for ticks and `rate ×`
+    /// the displayed size on the opposite side for quotes. Larger
+    /// orders fill across several events and stay `PartiallyFilled` in
+    /// between. `None` fills the whole remaining quantity at once.
+    #[serde(default)]
+    pub max_participation_rate: Option<Decimal>,
+    /// Whether sells beyond current inventory may open or extend short
+    /// positions. When `false` such sells are rejected.
+    #[serde(default)]
+    pub allow_short_selling: bool,
+    /// Equity required per unit of short market value (0.5 = 50% initial
+    /// margin). Checked before any fill that opens or extends a short.
+    #[serde(default = "default_short_margin_requirement")]
+    pub short_margin_requirement: Decimal,
+    /// Synthetic spread used when no quote is available. Market buys fill at
+    /// the ask and sells at the bid before slippage is applied; observed
+    /// `Quote` events always take precedence.
+    #[serde(default = "default_spread_model")]
+    pub spread_model: SpreadModel,
+    /// When set, [`PaperBroker::save_state`] runs against this path after
+    /// every order submission, fill, cancel, and rejection.
+    #[serde(default)]
+    pub autosave_path: Option<PathBuf>,
+    /// Lot size, minimum and precision of order quantities. Submitted
+    /// quantities are rounded down to it and rejected below its minimum.
+    #[serde(default)]
+    pub quantity_policy: QuantityPolicy,
+    /// Cash or margin account rules, checked when an order is accepted and
+    /// again when it fills. `None` only requires buys to fit in cash.
+    #[serde(default)]
+    pub buying_power: Option<BuyingPowerModel>,
+}
+
+fn default_spread_model() -> SpreadModel {
+    SpreadModel::None
+}
+
+fn default_short_margin_requirement() -> Decimal {
+    Decimal::new(5, 1)
 }
 
 impl Default for PaperBrokerConfig {
@@ -38,16 +115,83 @@ impl Default for PaperBrokerConfig {
             commission_per_share: Decimal::new(1, 2), // $0.01
             slippage_bps: Decimal::new(5, 4),         // 0.05%
             fill_market_orders_immediately: true,
+            max_participation_rate: None,
+            allow_short_selling: false,
+            short_margin_requirement: default_short_margin_requirement(),
+            spread_model: default_spread_model(),
+            autosave_path: None,
+            quantity_policy: QuantityPolicy::default(),
+            buying_power: None,
+        }
+    }
+}
+
+/// Quantity the latest market event can still absorb for each side when
+/// [`PaperBrokerConfig::max_participation_rate`] is set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EventLiquidity {
+    buy: Decimal,
+    sell: Decimal,
+}
+
+impl EventLiquidity {
+    fn side_mut(&mut self, side: Side) -> &mut Decimal {
+        match side {
+            Side::Buy => &mut self.buy,
+            Side::Sell => &mut self.sell,
         }
     }
 }
 
-/// Internal position tracking.
-#[derive(Debug, Clone)]
-struct PaperPosition {
-    symbol: Symbol,
-    quantity: Decimal,
-    average_cost: Decimal,
+/// Format version written by [`PaperBroker::save_state`].
+pub const PAPER_BROKER_STATE_VERSION: u32 = 1;
+
+/// Persisted paper-broker state: cash, positions, working orders, fills,
+/// and last prices, wrapped with a format version.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaperBrokerState {
+    pub version: u32,
+    pub saved_at: DateTime<Utc>,
+    pub cash: Decimal,
+    pub positions: Vec<Position>,
+    pub open_orders: Vec<Order>,
+    pub fills: Vec<Fill>,
+    pub latest_prices: Vec<(Symbol, Decimal)>,
+    #[serde(default)]
+    pub stop_states: Vec<(OrderId, StopTriggerState)>,
+    #[serde(default)]
+    pub option_contracts: Vec<(Symbol, OptionContract)>,
+    /// Unsettled sale proceeds of a cash account.
+    #[serde(default)]
+    pub buying_power: Option<BuyingPowerAccount>,
+}
+
+fn state_error(action: &str, path: &Path, error: impl std::fmt::Display) -> BrokerError {
+    BrokerError::Internal {
+        message: format!(
+            "failed to {} paper broker state at {}: {}",
+            action,
+            path.display(),
+            error
+        ),
+    }
+}
+
+/// Trigger progress of a stop-type order, kept across market events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct StopTriggerState {
+    pub triggered: bool,
+    /// Best price seen since submission for trailing stops: the running
+    /// high for sells, the running low for buys.
+    pub trail_anchor: Option<Decimal>,
+}
+
+/// Most recent observed quote for a symbol.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ObservedQuote {
+    bid: Decimal,
+    ask: Decimal,
+    timestamp: DateTime<Utc>,
 }
 
 /// Broker-level audit event categories recorded for paper-trading activity.
@@ -58,6 +202,8 @@ pub enum PaperBrokerAuditKind {
     OrderSubmitted,
     OrderFilled,
     OrderRejected,
+    /// Equity fell below the maintenance requirement of a margin account.
+    MarginCall,
 }
 
 /// Append-only paper-broker audit log entry.
@@ -80,17 +226,34 @@ pub struct PaperBroker {
     config: PaperBrokerConfig,
     connected: bool,
     cash: Decimal,
-    positions: HashMap<Symbol, PaperPosition>,
+    positions: HashMap<Symbol, Position>,
     orders: HashMap<OrderId, Order>,
     fills: Vec<Fill>,
     latest_prices: HashMap<Symbol, Decimal>,
+    liquidity: HashMap<Symbol, EventLiquidity>,
+    quotes: HashMap<Symbol, ObservedQuote>,
+    /// Recent `(high − low) / close` per symbol for volatility-scaled spreads.
+    bar_ranges: HashMap<Symbol, VecDeque<Decimal>>,
+    stop_states: HashMap<OrderId, StopTriggerState>,
+    /// Contracts behind option symbols; orders in them are margined and
+    /// their quantities scaled by the contract multiplier.
+    option_contracts: HashMap<Symbol, OptionContract>,
+    fill_subscribers: Vec<mpsc::UnboundedSender<Fill>>,
     subscribed_symbols: Vec<Symbol>,
     audit_log: Vec<PaperBrokerAuditEntry>,
+    /// Order updates not yet taken by [`Broker::poll_order_updates`].
+    order_updates: Vec<BrokerOrderUpdate>,
+    buying_power: Option<BuyingPowerAccount>,
+    /// The margin call the account is in, if any.
+    margin_call: Option<MarginCall>,
+    /// Timestamp of the latest market event; settlement dates count from it.
+    last_event_at: Option<DateTime<Utc>>,
 }
 
 impl PaperBroker {
     pub fn new(config: PaperBrokerConfig) -> Self {
         let cash = config.initial_cash;
+        let buying_power = config.buying_power.clone().map(BuyingPowerAccount::new);
         Self {
             config,
             connected: false,
@@ -99,8 +262,18 @@ impl PaperBroker {
             orders: HashMap::new(),
             fills: Vec::new(),
             latest_prices: HashMap::new(),
+            liquidity: HashMap::new(),
+            quotes: HashMap::new(),
+            bar_ranges: HashMap::new(),
+            stop_states: HashMap::new(),
+            option_contracts: HashMap::new(),
+            fill_subscribers: Vec::new(),
             subscribed_symbols: Vec::new(),
             audit_log: Vec::new(),
+            order_updates: Vec::new(),
+            buying_power,
+            margin_call: None,
+            last_event_at: None,
         }
     }
 
@@ -110,7 +283,9 @@ impl PaperBroker {
     }
 
     /// Feed a market event to update the latest price and attempt to fill
-    /// pending limit / stop orders.
+    /// pending limit / stop orders. Day orders submitted before the event's
+    /// date expire first; immediate-or-cancel and fill-or-kill orders are
+    /// canceled if this attempt leaves them unfilled.
     pub fn process_market_event(&mut self, event: &MarketEvent) {
         let symbol = event.symbol().clone();
         let price = match event {
@@ -119,18 +294,203 @@ impl PaperBroker {
             MarketEvent::Quote { bid, ask, .. } => (*bid + *ask) / Decimal::from(2),
         };
         self.latest_prices.insert(symbol.clone(), price);
+        self.last_event_at = Some(event.timestamp());
+        self.record_spread_inputs(event);
+
+        if let Some(rate) = self.config.max_participation_rate {
+            let liquidity = match event {
+                MarketEvent::Bar(bar) => {
+                    let volume = bar.volume * rate;
+                    EventLiquidity {
+                        buy: volume,
+                        sell: volume,
+                    }
+                }
+                MarketEvent::Tick(tick) => {
+                    let size = tick.size * rate;
+                    EventLiquidity {
+                        buy: size,
+                        sell: size,
+                    }
+                }
+                MarketEvent::Quote {
+                    bid_size, ask_size, ..
+                } => EventLiquidity {
+                    buy: *ask_size * rate,
+                    sell: *bid_size * rate,
+                },
+            };
+            self.liquidity.insert(symbol.clone(), liquidity);
+        }
+
+        self.expire_day_orders(&symbol, event.timestamp().date_naive());
 
-        // Try to fill pending orders for this symbol.
-        let pending: Vec<OrderId> = self
+        // Try to fill pending orders for this symbol, oldest first so that
+        // earlier orders get first claim on the event's liquidity.
+        let mut pending: Vec<(DateTime<Utc>, OrderId)> = self
             .orders
             .iter()
             .filter(|(_, o)| o.symbol == symbol && o.is_active())
-            .map(|(id, _)| *id)
+            .map(|(id, o)| (o.submitted_at, *id))
             .collect();
+        pending.sort();
 
-        for order_id in pending {
+        for (_, order_id) in pending {
             let _ = self.try_fill_order(order_id, price);
+            self.cancel_unfilled_immediate_order(order_id);
+        }
+        self.check_maintenance_margin();
+    }
+
+    /// The trading date: that of the latest market event, or today before
+    /// any event has been seen.
+    fn trading_date(&self) -> NaiveDate {
+        self.last_event_at.unwrap_or_else(Utc::now).date_naive()
+    }
+
+    /// Cash, equity and gross exposure at current marks, with the signed
+    /// market value held in `symbol` when given.
+    fn account_exposure(&self, symbol: Option<&Symbol>) -> AccountExposure {
+        let mut exposure = AccountExposure {
+            cash: self.cash,
+            equity: self.cash,
+            ..Default::default()
+        };
+        for position in self.positions.values() {
+            let value = position.quantity * self.mark_price(position) * position.multiplier;
+            exposure.equity += value;
+            exposure.gross_exposure += value.abs();
+            if Some(&position.symbol) == symbol {
+                exposure.position_value = value;
+            }
+        }
+        exposure
+    }
+
+    /// Check trading `quantity` of `symbol` at `price` against the
+    /// configured [`BuyingPowerModel`].
+    fn check_buying_power(
+        &self,
+        symbol: &Symbol,
+        side: Side,
+        quantity: Decimal,
+        price: Decimal,
+    ) -> Result<(), RejectionReason> {
+        let Some(account) = &self.buying_power else {
+            return Ok(());
+        };
+        let notional = quantity * price * self.multiplier(symbol);
+        account
+            .check_order(
+                &self.account_exposure(Some(symbol)),
+                side,
+                notional,
+                self.trading_date(),
+            )
+            .map_err(|error| match error {
+                BuyingPowerError::InsufficientSettledCash {
+                    required,
+                    available,
+                } => RejectionReason::InsufficientFunds {
+                    required,
+                    available,
+                },
+                BuyingPowerError::ShortSaleInCashAccount => RejectionReason::ShortSaleNotAllowed,
+                BuyingPowerError::InitialMarginExceeded { required, equity } => {
+                    RejectionReason::MarginExceeded {
+                        required: required.round_dp(2),
+                        available: equity.round_dp(2),
+                    }
+                }
+            })
+    }
+
+    /// Record a margin call in the audit log when equity first falls below
+    /// the maintenance requirement, and clear it once equity recovers.
+    fn check_maintenance_margin(&mut self) {
+        let Some(account) = &self.buying_power else {
+            return;
+        };
+        let exposure = self.account_exposure(None);
+        let call = account.margin_call(exposure.equity, exposure.gross_exposure);
+        if let (Some(call), None) = (call, self.margin_call) {
+            warn!(
+                equity = %call.equity,
+                maintenance_requirement = %call.maintenance_requirement,
+                gross_exposure = %call.gross_exposure,
+                "paper broker: margin call, positions may be liquidated"
+            );
+            self.record_audit_entry(
+                PaperBrokerAuditKind::MarginCall,
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(format!(
+                    "equity {} is below the maintenance requirement of {}",
+                    call.equity.round_dp(2),
+                    call.maintenance_requirement.round_dp(2)
+                )),
+            );
         }
+        self.margin_call = call;
+    }
+
+    /// The margin call the account is in after the latest market event.
+    pub fn margin_call(&self) -> Option<MarginCall> {
+        self.margin_call
+    }
+
+    /// Expire active day orders in `symbol` submitted before `date`.
+    fn expire_day_orders(&mut self, symbol: &Symbol, date: NaiveDate) {
+        let mut expired: Vec<(DateTime<Utc>, OrderId)> = self
+            .orders
+            .values()
+            .filter(|order| {
+                order.symbol == *symbol
+                    && order.is_active()
+                    && order.time_in_force == TimeInForce::Day
+                    && order.submitted_at.date_naive() < date
+            })
+            .map(|order| (order.submitted_at, order.id))
+            .collect();
+        expired.sort();
+        for (_, order_id) in expired {
+            if let Some(order) = self.orders.get_mut(&order_id) {
+                order.status = OrderStatus::Expired;
+            }
+            self.stop_states.remove(&order_id);
+            info!(order_id = %order_id, "paper broker: day order expired");
+            self.order_updates.push(BrokerOrderUpdate::Expired {
+                order_id,
+                reason: "day order expired at the end of its session".into(),
+            });
+        }
+    }
+
+    /// Cancel what an immediate-or-cancel or fill-or-kill order has left
+    /// after its chance to fill.
+    fn cancel_unfilled_immediate_order(&mut self, order_id: OrderId) {
+        let Some(order) = self
+            .orders
+            .get_mut(&order_id)
+            .filter(|order| order.is_active())
+        else {
+            return;
+        };
+        let reason = match order.time_in_force {
+            TimeInForce::IOC => "remaining quantity canceled after immediate-or-cancel attempt",
+            TimeInForce::FOK => "fill-or-kill order could not fill in full",
+            TimeInForce::Day | TimeInForce::GTC => return,
+        };
+        order.cancel();
+        self.stop_states.remove(&order_id);
+        info!(order_id = %order_id, reason, "paper broker: order canceled");
+        self.order_updates.push(BrokerOrderUpdate::Canceled {
+            order_id,
+            reason: reason.into(),
+        });
     }
 
     fn available_quantity(&self, symbol: &Symbol) -> Decimal {
@@ -140,6 +500,274 @@ impl PaperBroker {
             .unwrap_or(Decimal::ZERO)
     }
 
+    fn record_spread_inputs(&mut self, event: &MarketEvent) {
+        let symbol = event.symbol();
+        match event {
+            MarketEvent::Quote {
+                bid,
+                ask,
+                timestamp,
+                ..
+            } => {
+                self.quotes.insert(
+                    symbol.clone(),
+                    ObservedQuote {
+                        bid: *bid,
+                        ask: *ask,
+                        timestamp: *timestamp,
+                    },
+                );
+            }
+            MarketEvent::Bar(_) | MarketEvent::Tick(_) => {
+                // A newer trade print supersedes an older quote.
+                let timestamp = event.timestamp();
+                if self
+                    .quotes
+                    .get(symbol)
+                    .is_some_and(|quote| quote.timestamp < timestamp)
+                {
+                    self.quotes.remove(symbol);
+                }
+            }
+        }
+
+        if let (MarketEvent::Bar(bar), SpreadModel::VolatilityScaled { lookback, .. }) =
+            (event, &self.config.spread_model)
+        {
+            if bar.close > Decimal::ZERO {
+                let ranges = self.bar_ranges.entry(symbol.clone()).or_default();
+                ranges.push_back((bar.high - bar.low) / bar.close);
+                while ranges.len() > (*lookback).max(1) {
+                    ranges.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Synthetic spread for `symbol` as a fraction of price.
+    fn spread_fraction(&self, symbol: &Symbol) -> Decimal {
+        match &self.config.spread_model {
+            SpreadModel::None => Decimal::ZERO,
+            SpreadModel::FixedBps { basis_points } => bps_fraction(*basis_points),
+            SpreadModel::PerSymbol {
+                spreads,
+                default_bps,
+            } => bps_fraction(*spreads.get(&symbol.symbol).unwrap_or(default_bps)),
+            SpreadModel::VolatilityScaled {
+                multiplier,
+                min_bps,
+                ..
+            } => {
+                let floor = bps_fraction(*min_bps);
+                match self.bar_ranges.get(symbol) {
+                    Some(ranges) if !ranges.is_empty() => {
+                        let average =
+                            ranges.iter().copied().sum::<Decimal>() / Decimal::from(ranges.len());
+                        (average * *multiplier).max(floor)
+                    }
+                    _ => floor,
+                }
+            }
+        }
+    }
+
+    /// Bid and ask for `symbol`: the latest observed quote, or a spread
+    /// synthesized around `last_price`.
+    fn bid_ask(&self, symbol: &Symbol, last_price: Decimal) -> (Decimal, Decimal) {
+        if let Some(quote) = self.quotes.get(symbol) {
+            return (quote.bid, quote.ask);
+        }
+        let half_spread = last_price * self.spread_fraction(symbol) / Decimal::from(2);
+        (last_price - half_spread, last_price + half_spread)
+    }
+
+    /// Quantity of `order` that may fill now: the remaining quantity, capped
+    /// by the latest event's liquidity when a participation rate is set.
+    fn fillable_quantity(&self, order: &Order) -> Decimal {
+        if self.config.max_participation_rate.is_none() {
+            return order.remaining_quantity;
+        }
+        let available = self
+            .liquidity
+            .get(&order.symbol)
+            .map(|liquidity| match order.side {
+                Side::Buy => liquidity.buy,
+                Side::Sell => liquidity.sell,
+            })
+            .unwrap_or(Decimal::ZERO);
+        self.config
+            .quantity_policy
+            .rule_for(&order.symbol)
+            .round_down(order.remaining_quantity.min(available.max(Decimal::ZERO)))
+    }
+
+    fn position_quantity(&self, symbol: &Symbol) -> Decimal {
+        self.positions
+            .get(symbol)
+            .map(|position| position.quantity)
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// Make `symbol` tradable as the option `contract`.
+    pub fn register_option_contract(&mut self, symbol: Symbol, contract: OptionContract) {
+        self.option_contracts.insert(symbol, contract);
+    }
+
+    fn multiplier(&self, symbol: &Symbol) -> Decimal {
+        self.option_contracts
+            .get(symbol)
+            .map(|contract| contract.multiplier)
+            .unwrap_or(Decimal::ONE)
+    }
+
+    /// Margin on option positions, with `symbol` changed by `delta`
+    /// contracts at `price` when given, and the buying power available to
+    /// it: cash plus the marked value of option positions.
+    fn option_margin(
+        &self,
+        trade: Option<(&Symbol, Decimal, Decimal)>,
+    ) -> Result<(Decimal, Decimal), OptionsExecError> {
+        let mut positions = Vec::new();
+        let mut buying_power = self.cash;
+        for (symbol, contract) in &self.option_contracts {
+            let held = self.position_quantity(symbol);
+            let mut mark = self
+                .positions
+                .get(symbol)
+                .map(|position| self.mark_price(position))
+                .or_else(|| self.latest_prices.get(symbol).copied())
+                .unwrap_or(Decimal::ZERO);
+            buying_power += held * mark * contract.multiplier;
+
+            let mut contracts = held;
+            if let Some((traded, delta, price)) = trade {
+                if traded == symbol {
+                    contracts += delta;
+                    mark = price;
+                }
+            }
+            if !contracts.is_zero() {
+                positions.push(MarginPosition::new(contract.clone(), contracts, mark));
+            }
+        }
+
+        let spots = positions
+            .iter()
+            .filter_map(|position| {
+                let underlying = &position.contract.underlying;
+                Some((underlying.clone(), *self.latest_prices.get(underlying)?))
+            })
+            .collect();
+        let requirement = portfolio_margin_requirement(&positions, &spots)?;
+        Ok((requirement, buying_power))
+    }
+
+    /// Check whether trading `quantity` contracts of the option `symbol` at
+    /// `price` keeps the option margin within buying power. Trades that lower
+    /// the requirement are always allowed.
+    fn check_option_margin(
+        &self,
+        symbol: &Symbol,
+        side: Side,
+        quantity: Decimal,
+        price: Decimal,
+    ) -> Result<(), RejectionReason> {
+        let delta = match side {
+            Side::Buy => quantity,
+            Side::Sell => -quantity,
+        };
+        let margin = |trade| {
+            self.option_margin(trade).map_err(|error| match error {
+                OptionsExecError::MissingSpot(symbol) => RejectionReason::NoMarketPrice { symbol },
+                error => RejectionReason::Other {
+                    message: error.to_string(),
+                },
+            })
+        };
+        let (before, _) = margin(None)?;
+        let (after, buying_power) = margin(Some((symbol, delta, price)))?;
+        if after > before && after > buying_power {
+            return Err(RejectionReason::MarginExceeded {
+                required: after.round_dp(2),
+                available: buying_power.round_dp(2),
+            });
+        }
+        Ok(())
+    }
+
+    fn mark_price(&self, position: &Position) -> Decimal {
+        self.latest_prices
+            .get(&position.symbol)
+            .copied()
+            .unwrap_or(position.average_price)
+    }
+
+    /// Quantity of `symbol` still to be sold by working orders other than
+    /// `except`.
+    fn working_sell_quantity(&self, symbol: &Symbol, except: OrderId) -> Decimal {
+        self.orders
+            .values()
+            .filter(|order| {
+                order.id != except
+                    && order.symbol == *symbol
+                    && order.side == Side::Sell
+                    && order.is_active()
+            })
+            .map(|order| order.remaining_quantity)
+            .sum()
+    }
+
+    /// Check whether order `order_id` may sell `quantity` of `symbol` at
+    /// `price`: sells within the long inventory not already committed to
+    /// other working sells always are, while sells that open or extend a
+    /// short need short selling enabled and enough equity to cover the
+    /// margin on every short afterwards. Without a price only the inventory
+    /// rule is applied; margin is then checked at fill time.
+    fn check_sell(
+        &self,
+        order_id: OrderId,
+        symbol: &Symbol,
+        quantity: Decimal,
+        price: Option<Decimal>,
+    ) -> Result<(), RejectionReason> {
+        let resulting = self.position_quantity(symbol)
+            - self.working_sell_quantity(symbol, order_id)
+            - quantity;
+        if resulting >= Decimal::ZERO {
+            return Ok(());
+        }
+        if !self.config.allow_short_selling {
+            return Err(RejectionReason::ShortSaleNotAllowed);
+        }
+        let Some(price) = price else {
+            return Ok(());
+        };
+
+        let mut equity = self.cash;
+        let mut short_value = resulting.abs() * price;
+        for position in self.positions.values() {
+            if position.symbol == *symbol {
+                equity += position.quantity * price;
+                continue;
+            }
+            let mark = self.mark_price(position);
+            equity += position.quantity * mark * position.multiplier;
+            if position.quantity < Decimal::ZERO
+                && !self.option_contracts.contains_key(&position.symbol)
+            {
+                short_value += position.quantity.abs() * mark;
+            }
+        }
+        let required = short_value * self.config.short_margin_requirement;
+        if required > equity {
+            return Err(RejectionReason::MarginExceeded {
+                required,
+                available: equity,
+            });
+        }
+        Ok(())
+    }
+
     fn record_audit_entry(
         &mut self,
         kind: PaperBrokerAuditKind,
@@ -163,12 +791,13 @@ impl PaperBroker {
         });
     }
 
-    fn reject_order(&mut self, order_id: OrderId, reason: &str) {
+    fn reject_order(&mut self, order_id: OrderId, reason: RejectionReason) {
         let mut audit_order_id = None;
         let mut audit_symbol = None;
         let mut audit_side = None;
         let mut audit_quantity = None;
 
+        self.stop_states.remove(&order_id);
         if let Some(order) = self.orders.get_mut(&order_id) {
             order.status = OrderStatus::Rejected;
             audit_order_id = Some(order.id.to_string());
@@ -190,110 +819,183 @@ impl PaperBroker {
             Some(reason.to_string()),
         );
 
-        warn!(order_id = %order_id, reason, "paper broker: order rejected");
+        warn!(order_id = %order_id, reason = %reason, "paper broker: order rejected");
+        self.order_updates
+            .push(BrokerOrderUpdate::Rejected { order_id, reason });
+        self.autosave();
+    }
+
+    /// Update the trigger state of a stop-type order at `market_price` and
+    /// return the type it currently executes as, or `None` while it waits
+    /// for its trigger.
+    ///
+    /// Stops trigger when the price trades through the stop (buy: at or
+    /// above, sell: at or below) and stay triggered on later events, so a
+    /// partially filled stop keeps executing. Triggered stops and trailing
+    /// stops become market orders, so a gap through the stop (a sell stop at
+    /// 100 with the next print at 90) fills at the gapped price, not the
+    /// stop. Triggered stop-limits rest as limit orders and fill on the
+    /// first event where the limit is marketable, which may be after the
+    /// trigger event.
+    fn advance_trigger(&mut self, order: &Order, market_price: Decimal) -> Option<OrderType> {
+        let through = |stop: Decimal| match order.side {
+            Side::Buy => market_price >= stop,
+            Side::Sell => market_price <= stop,
+        };
+        let execute_as = match &order.order_type {
+            OrderType::Market | OrderType::Limit { .. } => return Some(order.order_type.clone()),
+            OrderType::Stop { .. } | OrderType::TrailingStop { .. } => OrderType::Market,
+            OrderType::StopLimit { limit_price, .. } => OrderType::Limit {
+                price: *limit_price,
+            },
+        };
+        let state = self.stop_states.entry(order.id).or_default();
+        if !state.triggered {
+            let stop = match order.order_type {
+                OrderType::Stop { stop_price } | OrderType::StopLimit { stop_price, .. } => {
+                    stop_price
+                }
+                OrderType::TrailingStop { trail_amount } => {
+                    let anchor = match (order.side, state.trail_anchor) {
+                        (_, None) => market_price,
+                        (Side::Sell, Some(high)) => high.max(market_price),
+                        (Side::Buy, Some(low)) => low.min(market_price),
+                    };
+                    state.trail_anchor = Some(anchor);
+                    match order.side {
+                        Side::Sell => anchor - trail_amount,
+                        Side::Buy => anchor + trail_amount,
+                    }
+                }
+                OrderType::Market | OrderType::Limit { .. } => return Some(execute_as),
+            };
+            state.triggered = through(stop);
+        }
+        state.triggered.then_some(execute_as)
+    }
+
+    /// Whether a stop, stop-limit, or trailing-stop order has triggered.
+    pub fn is_stop_triggered(&self, order_id: OrderId) -> bool {
+        self.stop_states
+            .get(&order_id)
+            .is_some_and(|state| state.triggered)
+    }
+
+    /// Current stop level of a trailing-stop order, once a price is known.
+    pub fn trailing_stop_price(&self, order_id: OrderId) -> Option<Decimal> {
+        let order = self.orders.get(&order_id)?;
+        let OrderType::TrailingStop { trail_amount } = order.order_type else {
+            return None;
+        };
+        let anchor = self.stop_states.get(&order_id)?.trail_anchor?;
+        Some(match order.side {
+            Side::Sell => anchor - trail_amount,
+            Side::Buy => anchor + trail_amount,
+        })
     }
 
-    /// Attempt to fill an order at `market_price`.  Returns `true` if filled.
+    /// Attempt to fill an order at `market_price`.  Returns `true` if any
+    /// quantity was filled.
     fn try_fill_order(&mut self, order_id: OrderId, market_price: Decimal) -> bool {
         let order = match self.orders.get(&order_id) {
             Some(o) if o.is_active() => o.clone(),
             _ => return false,
         };
 
-        let fill_price = match &order.order_type {
+        // Buys execute against the ask and sells against the bid.
+        let (bid, ask) = self.bid_ask(&order.symbol, market_price);
+        let executable = match order.side {
+            Side::Buy => ask,
+            Side::Sell => bid,
+        };
+
+        let Some(execution_type) = self.advance_trigger(&order, market_price) else {
+            return false;
+        };
+        let fill_price = match &execution_type {
             OrderType::Market => {
                 // Apply slippage
-                let slip = market_price * self.config.slippage_bps;
+                let slip = executable * self.config.slippage_bps;
                 match order.side {
-                    Side::Buy => market_price + slip,
-                    Side::Sell => market_price - slip,
+                    Side::Buy => executable + slip,
+                    Side::Sell => executable - slip,
                 }
             }
             OrderType::Limit { price } => {
                 match order.side {
-                    Side::Buy if market_price <= *price => *price,
-                    Side::Sell if market_price >= *price => *price,
+                    Side::Buy if executable <= *price => *price,
+                    Side::Sell if executable >= *price => *price,
                     _ => return false, // Not yet fillable
                 }
             }
-            OrderType::Stop { stop_price } => match order.side {
-                Side::Buy if market_price >= *stop_price => market_price,
-                Side::Sell if market_price <= *stop_price => market_price,
-                _ => return false,
-            },
-            OrderType::StopLimit {
-                stop_price,
-                limit_price,
-            } => match order.side {
-                Side::Buy if market_price >= *stop_price && market_price <= *limit_price => {
-                    *limit_price
-                }
-                Side::Sell if market_price <= *stop_price && market_price >= *limit_price => {
-                    *limit_price
-                }
-                _ => return false,
-            },
+            // `advance_trigger` converts triggered stops to market/limit.
+            OrderType::Stop { .. }
+            | OrderType::StopLimit { .. }
+            | OrderType::TrailingStop { .. } => return false,
         };
 
-        let quantity = order.remaining_quantity;
+        let quantity = self.fillable_quantity(&order);
+        if quantity <= Decimal::ZERO
+            || (order.time_in_force == TimeInForce::FOK && quantity < order.remaining_quantity)
+        {
+            return false;
+        }
         let commission = quantity * self.config.commission_per_share;
 
-        if order.side == Side::Sell {
-            let available_quantity = self.available_quantity(&order.symbol);
-            if quantity > available_quantity {
-                self.reject_order(
-                    order_id,
-                    "paper broker does not support short sales; sell quantity exceeds current inventory",
-                );
-                return false;
-            }
+        let multiplier = self.multiplier(&order.symbol);
+        let check = if self.option_contracts.contains_key(&order.symbol) {
+            self.check_option_margin(&order.symbol, order.side, quantity, fill_price)
+        } else if order.side == Side::Sell {
+            self.check_sell(order_id, &order.symbol, quantity, Some(fill_price))
+                .and_then(|()| {
+                    self.check_buying_power(&order.symbol, order.side, quantity, fill_price)
+                })
+        } else {
+            self.check_buying_power(&order.symbol, order.side, quantity, fill_price)
+        };
+        if let Err(reason) = check {
+            self.reject_order(order_id, reason);
+            return false;
         }
 
         // Update cash
+        let proceeds = match order.side {
+            Side::Buy => -(quantity * fill_price * multiplier + commission),
+            Side::Sell => quantity * fill_price * multiplier - commission,
+        };
+        let on_margin = matches!(
+            self.config.buying_power,
+            Some(BuyingPowerModel::RegTMargin { .. })
+        );
         match order.side {
             Side::Buy => {
-                let cost = quantity * fill_price + commission;
-                if cost > self.cash {
-                    // Insufficient funds — reject
-                    self.reject_order(order_id, "insufficient funds");
+                // Margin accounts may borrow against their equity.
+                let cost = -proceeds;
+                if !on_margin && cost > self.cash {
+                    self.reject_order(
+                        order_id,
+                        RejectionReason::InsufficientFunds {
+                            required: cost,
+                            available: self.cash,
+                        },
+                    );
                     return false;
                 }
                 self.cash -= cost;
             }
-            Side::Sell => {
-                self.cash += quantity * fill_price - commission;
-            }
+            Side::Sell => self.cash += proceeds,
+        }
+        let traded_on = self.trading_date();
+        if let Some(account) = &mut self.buying_power {
+            account.record_fill(order.side, proceeds, traded_on);
         }
 
-        // Update position
-        let pos = self
-            .positions
-            .entry(order.symbol.clone())
-            .or_insert_with(|| PaperPosition {
-                symbol: order.symbol.clone(),
-                quantity: Decimal::ZERO,
-                average_cost: Decimal::ZERO,
-            });
-
-        match order.side {
-            Side::Buy => {
-                let total_cost = pos.quantity * pos.average_cost + quantity * fill_price;
-                pos.quantity += quantity;
-                if pos.quantity > Decimal::ZERO {
-                    pos.average_cost = total_cost / pos.quantity;
-                }
-            }
-            Side::Sell => {
-                pos.quantity -= quantity;
-                if pos.quantity <= Decimal::ZERO {
-                    pos.quantity = Decimal::ZERO;
-                    pos.average_cost = Decimal::ZERO;
-                }
-            }
+        if let Some(liquidity) = self.liquidity.get_mut(&order.symbol) {
+            *liquidity.side_mut(order.side) -= quantity;
         }
 
-        // Record fill
-        let fill = Fill::new(
+        // Record fill and update the (possibly short) position
+        let mut fill = Fill::new(
             order_id,
             order.symbol.clone(),
             order.side,
@@ -301,7 +1003,15 @@ impl PaperBroker {
             fill_price,
             commission,
             order.strategy_id.clone(),
-        );
+        )
+        .with_order_annotations(&order);
+        fill.spread = Some(ask - bid);
+        self.positions
+            .entry(order.symbol.clone())
+            .or_insert_with(|| Position::with_multiplier(order.symbol.clone(), multiplier))
+            .apply_fill(&fill);
+        self.fill_subscribers
+            .retain(|subscriber| subscriber.send(fill.clone()).is_ok());
         self.fills.push(fill);
         self.record_audit_entry(
             PaperBrokerAuditKind::OrderFilled,
@@ -314,8 +1024,13 @@ impl PaperBroker {
         );
 
         // Update order status
+        let mut remaining = Decimal::ZERO;
         if let Some(o) = self.orders.get_mut(&order_id) {
             o.fill(quantity, fill_price);
+            remaining = o.remaining_quantity;
+        }
+        if remaining.is_zero() {
+            self.stop_states.remove(&order_id);
         }
 
         info!(
@@ -323,27 +1038,184 @@ impl PaperBroker {
             symbol = %order.symbol,
             side = ?order.side,
             quantity = %quantity,
+            remaining = %remaining,
             price = %fill_price,
             "paper broker: order filled"
         );
 
+        self.autosave();
         true
     }
 
+    /// Snapshot the persistent part of the broker state. Collections are
+    /// sorted so identical states serialize identically.
+    pub fn state(&self) -> PaperBrokerState {
+        let mut positions: Vec<Position> = self.positions.values().cloned().collect();
+        positions.sort_by_key(|position| position.symbol.to_string());
+        let mut open_orders: Vec<Order> = self
+            .orders
+            .values()
+            .filter(|order| order.is_active())
+            .cloned()
+            .collect();
+        open_orders.sort_by_key(|order| (order.submitted_at, order.id));
+        let mut latest_prices: Vec<(Symbol, Decimal)> = self
+            .latest_prices
+            .iter()
+            .map(|(symbol, price)| (symbol.clone(), *price))
+            .collect();
+        latest_prices.sort_by_key(|(symbol, _)| symbol.to_string());
+        let stop_states = open_orders
+            .iter()
+            .filter_map(|order| Some((order.id, *self.stop_states.get(&order.id)?)))
+            .collect();
+        let mut option_contracts: Vec<(Symbol, OptionContract)> = self
+            .option_contracts
+            .iter()
+            .map(|(symbol, contract)| (symbol.clone(), contract.clone()))
+            .collect();
+        option_contracts.sort_by_key(|(symbol, _)| symbol.to_string());
+
+        PaperBrokerState {
+            version: PAPER_BROKER_STATE_VERSION,
+            saved_at: Utc::now(),
+            cash: self.cash,
+            positions,
+            open_orders,
+            fills: self.fills.clone(),
+            latest_prices,
+            stop_states,
+            option_contracts,
+            buying_power: self.buying_power.clone(),
+        }
+    }
+
+    /// Replace cash, positions, working orders, fills, and prices with a
+    /// previously saved snapshot. Order ids are preserved so callers can
+    /// reconcile against [`Broker::get_open_orders`].
+    pub fn restore_state(&mut self, state: PaperBrokerState) -> BrokerResult<()> {
+        if state.version != PAPER_BROKER_STATE_VERSION {
+            return Err(BrokerError::Internal {
+                message: format!(
+                    "unsupported paper broker state version {} (expected {})",
+                    state.version, PAPER_BROKER_STATE_VERSION
+                ),
+            });
+        }
+        self.cash = state.cash;
+        self.positions = state
+            .positions
+            .into_iter()
+            .map(|position| (position.symbol.clone(), position))
+            .collect();
+        self.orders = state
+            .open_orders
+            .into_iter()
+            .map(|order| (order.id, order))
+            .collect();
+        self.fills = state.fills;
+        self.latest_prices = state.latest_prices.into_iter().collect();
+        self.stop_states = state.stop_states.into_iter().collect();
+        self.option_contracts = state.option_contracts.into_iter().collect();
+        if let Some(account) = state.buying_power {
+            self.buying_power = Some(account);
+        }
+        self.liquidity.clear();
+        self.quotes.clear();
+        self.bar_ranges.clear();
+        Ok(())
+    }
+
+    /// Write the broker state to `path` as versioned JSON. The file is
+    /// replaced atomically so a crash mid-write leaves the previous save.
+    pub fn save_state(&self, path: impl AsRef<Path>) -> BrokerResult<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_vec_pretty(&self.state())
+            .map_err(|e| state_error("serialize", path, e))?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json).map_err(|e| state_error("write", &tmp, e))?;
+        std::fs::rename(&tmp, path).map_err(|e| state_error("write", path, e))
+    }
+
+    /// Load state written by [`PaperBroker::save_state`], replacing the
+    /// current cash, positions, orders, fills, and prices.
+    pub fn load_state(&mut self, path: impl AsRef<Path>) -> BrokerResult<()> {
+        let path = path.as_ref();
+        let json = std::fs::read(path).map_err(|e| state_error("read", path, e))?;
+        let state: PaperBrokerState =
+            serde_json::from_slice(&json).map_err(|e| state_error("parse", path, e))?;
+        self.restore_state(state)?;
+        info!(path = %path.display(), "paper broker state loaded");
+        Ok(())
+    }
+
+    fn autosave(&self) {
+        if let Some(path) = &self.config.autosave_path {
+            if let Err(e) = self.save_state(path) {
+                warn!(error = %e, "paper broker autosave failed");
+            }
+        }
+    }
+
+    /// Stream of fills executed from now on, e.g. for
+    /// [`LiveEngine::run`](crate::engine::LiveEngine::run). The stream ends
+    /// when the broker is dropped.
+    pub fn fill_stream(&mut self) -> impl Stream<Item = Fill> + Send + Unpin + 'static {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        self.fill_subscribers.push(tx);
+        futures_util::stream::poll_fn(move |cx| rx.poll_recv(cx))
+    }
+
     /// Get all recorded fills.
     pub fn get_fills(&self) -> &[Fill] {
         &self.fills
     }
 
+    /// Fills recorded for one order, in execution order.
+    pub fn get_order_fills(&self, order_id: OrderId) -> Vec<&Fill> {
+        self.fills
+            .iter()
+            .filter(|fill| fill.order_id == order_id)
+            .collect()
+    }
+
     /// Borrow the append-only paper-broker audit log.
     pub fn audit_log(&self) -> &[PaperBrokerAuditEntry] {
         &self.audit_log
     }
 
+    /// Signed broker view of a position; shorts have negative quantity and
+    /// market value, and gain as the market falls below the average cost.
+    fn broker_position(&self, position: &Position) -> BrokerPosition {
+        let market_price = self.mark_price(position);
+        BrokerPosition {
+            symbol: position.symbol.clone(),
+            quantity: position.quantity,
+            market_value: position.quantity * market_price * position.multiplier,
+            average_cost: position.average_price,
+            unrealized_pnl: position.quantity
+                * (market_price - position.average_price)
+                * position.multiplier,
+        }
+    }
+
+    /// Profit realized by closing or reducing positions, across all symbols.
+    pub fn realized_pnl(&self) -> Decimal {
+        self.positions
+            .values()
+            .map(|position| position.realized_pnl)
+            .sum()
+    }
+
     /// Current cash balance.
     pub fn cash(&self) -> Decimal {
         self.cash
     }
+
+    /// Rules order quantities are sized to.
+    pub fn quantity_policy(&self) -> &QuantityPolicy {
+        &self.config.quantity_policy
+    }
 }
 
 #[async_trait]
@@ -390,48 +1262,123 @@ impl Broker for PaperBroker {
         if !self.connected {
             return Err(BrokerError::NotConnected);
         }
+        if let Some(client_order_id) = order.client_order_id {
+            let known = self
+                .orders
+                .values()
+                .find(|known| known.client_order_id == Some(client_order_id));
+            if let Some(known) = known {
+                debug!(
+                    "Paper order {} already submitted as {}",
+                    client_order_id, known.id
+                );
+                return Ok(known.id);
+            }
+        }
 
         let order_id = order.id;
         let order_symbol = order.symbol.clone();
         let order_side = order.side;
+        let quantity_rule = self.config.quantity_policy.rule_for(&order.symbol);
+        let sized_quantity = quantity_rule.apply(order.remaining_quantity);
+        if let Some(quantity) = sized_quantity {
+            order.quantity = order.filled_quantity + quantity;
+            order.remaining_quantity = quantity;
+        }
         let order_quantity = order.remaining_quantity;
         order.status = OrderStatus::Submitted;
 
-        if order.side == Side::Sell {
-            let available_quantity = self.available_quantity(&order.symbol);
-            if order.remaining_quantity > available_quantity {
-                order.status = OrderStatus::Rejected;
-                self.orders.insert(order_id, order);
-                self.record_audit_entry(
-                    PaperBrokerAuditKind::OrderRejected,
-                    Some(order_id.to_string()),
-                    Some(order_symbol.clone()),
-                    Some(order_side),
-                    Some(order_quantity),
-                    self.latest_prices.get(&order_symbol).copied(),
-                    Some(
-                        "paper broker does not support short sales; sell quantity exceeds current inventory"
-                            .to_string(),
-                    ),
-                );
-                warn!(
-                    order_id = %order_id,
-                    symbol = %self.orders[&order_id].symbol,
-                    requested_quantity = %self.orders[&order_id].remaining_quantity,
-                    available_quantity = %available_quantity,
-                    "paper broker rejected sell order that exceeds current inventory"
+        if let OrderType::TrailingStop { trail_amount } = order.order_type {
+            if trail_amount <= Decimal::ZERO {
+                return Err(BrokerError::OrderRejected {
+                    reason: "trailing stop trail_amount must be positive".into(),
+                });
+            }
+            // Trail from the price at submission when one is known.
+            if let Some(&price) = self.latest_prices.get(&order.symbol) {
+                self.stop_states.insert(
+                    order_id,
+                    StopTriggerState {
+                        triggered: false,
+                        trail_anchor: Some(price),
+                    },
                 );
-                return Ok(order_id);
             }
         }
 
-        // For market orders with immediate fill, try to fill now.
-        if self.config.fill_market_orders_immediately
-            && matches!(order.order_type, OrderType::Market)
-        {
-            if let Some(&price) = self.latest_prices.get(&order.symbol) {
-                self.orders.insert(order_id, order);
-                self.record_audit_entry(
+        let price = self.latest_prices.get(&order.symbol).copied();
+        let check = if sized_quantity.is_none() {
+            Err(RejectionReason::BelowMinimumQuantity {
+                quantity: order.remaining_quantity,
+                minimum: quantity_rule.min_quantity,
+                symbol: order.symbol.to_string(),
+            })
+        } else if self.option_contracts.contains_key(&order.symbol) {
+            match price {
+                Some(price) => self.check_option_margin(
+                    &order.symbol,
+                    order.side,
+                    order.remaining_quantity,
+                    price,
+                ),
+                None => Ok(()),
+            }
+        } else {
+            let sell_check = match order.side {
+                Side::Sell => {
+                    self.check_sell(order_id, &order.symbol, order.remaining_quantity, price)
+                }
+                Side::Buy => Ok(()),
+            };
+            sell_check.and_then(|()| match price {
+                Some(price) => self.check_buying_power(
+                    &order.symbol,
+                    order.side,
+                    order.remaining_quantity,
+                    price,
+                ),
+                None => Ok(()),
+            })
+        };
+        if let Err(reason) = check {
+            let available_quantity = self.available_quantity(&order.symbol);
+            order.status = OrderStatus::Rejected;
+            self.orders.insert(order_id, order);
+            self.record_audit_entry(
+                PaperBrokerAuditKind::OrderRejected,
+                Some(order_id.to_string()),
+                Some(order_symbol.clone()),
+                Some(order_side),
+                Some(order_quantity),
+                price,
+                Some(reason.to_string()),
+            );
+            warn!(
+                order_id = %order_id,
+                symbol = %self.orders[&order_id].symbol,
+                requested_quantity = %self.orders[&order_id].remaining_quantity,
+                available_quantity = %available_quantity,
+                reason = %reason,
+                "paper broker rejected order"
+            );
+            self.order_updates
+                .push(BrokerOrderUpdate::Rejected { order_id, reason });
+            self.autosave();
+            return Ok(order_id);
+        }
+
+        self.order_updates.push(BrokerOrderUpdate::Accepted {
+            order_id,
+            quantity: order.quantity,
+        });
+
+        // For market orders with immediate fill, try to fill now.
+        if self.config.fill_market_orders_immediately
+            && matches!(order.order_type, OrderType::Market)
+        {
+            if let Some(&price) = self.latest_prices.get(&order.symbol) {
+                self.orders.insert(order_id, order);
+                self.record_audit_entry(
                     PaperBrokerAuditKind::OrderSubmitted,
                     Some(order_id.to_string()),
                     Some(order_symbol),
@@ -441,6 +1388,8 @@ impl Broker for PaperBroker {
                     None,
                 );
                 self.try_fill_order(order_id, price);
+                self.cancel_unfilled_immediate_order(order_id);
+                self.autosave();
                 return Ok(order_id);
             }
         }
@@ -455,6 +1404,7 @@ impl Broker for PaperBroker {
             self.latest_prices.get(&order_symbol).copied(),
             None,
         );
+        self.autosave();
         Ok(order_id)
     }
 
@@ -466,6 +1416,12 @@ impl Broker for PaperBroker {
         match self.orders.get_mut(&order_id) {
             Some(order) if order.is_active() => {
                 order.cancel();
+                self.stop_states.remove(&order_id);
+                self.order_updates.push(BrokerOrderUpdate::Canceled {
+                    order_id,
+                    reason: "canceled on request".into(),
+                });
+                self.autosave();
                 Ok(())
             }
             Some(_) => Err(BrokerError::OrderRejected {
@@ -486,6 +1442,15 @@ impl Broker for PaperBroker {
             })
     }
 
+    async fn get_order(&self, order_id: OrderId) -> BrokerResult<Order> {
+        self.orders
+            .get(&order_id)
+            .cloned()
+            .ok_or(BrokerError::OrderNotFound {
+                order_id: order_id.to_string(),
+            })
+    }
+
     async fn get_open_orders(&self) -> BrokerResult<Vec<Order>> {
         Ok(self
             .orders
@@ -499,21 +1464,38 @@ impl Broker for PaperBroker {
         let position_value: Decimal = self
             .positions
             .values()
-            .map(|p| {
-                let price = self
-                    .latest_prices
-                    .get(&p.symbol)
-                    .copied()
-                    .unwrap_or(p.average_cost);
-                p.quantity * price
-            })
+            .map(|p| p.quantity * self.mark_price(p) * p.multiplier)
             .sum();
 
         let equity = self.cash + position_value;
+        let buying_power = if let Some(account) = &self.buying_power {
+            match account.model() {
+                BuyingPowerModel::CashAccount { .. } => {
+                    self.cash - account.unsettled_cash(self.trading_date())
+                }
+                BuyingPowerModel::RegTMargin { initial_margin, .. } => {
+                    let gross = self.account_exposure(None).gross_exposure;
+                    if initial_margin.is_zero() {
+                        equity
+                    } else {
+                        (equity / initial_margin - gross).max(Decimal::ZERO)
+                    }
+                }
+            }
+        } else if self.option_contracts.is_empty() {
+            self.cash
+        } else {
+            let (requirement, option_buying_power) =
+                self.option_margin(None)
+                    .map_err(|error| BrokerError::Internal {
+                        message: error.to_string(),
+                    })?;
+            option_buying_power - requirement
+        };
 
         Ok(AccountBalance {
             cash: self.cash,
-            buying_power: self.cash,
+            buying_power,
             equity,
             timestamp: Utc::now(),
         })
@@ -523,42 +1505,17 @@ impl Broker for PaperBroker {
         Ok(self
             .positions
             .values()
-            .filter(|p| p.quantity > Decimal::ZERO)
-            .map(|p| {
-                let market_price = self
-                    .latest_prices
-                    .get(&p.symbol)
-                    .copied()
-                    .unwrap_or(p.average_cost);
-                BrokerPosition {
-                    symbol: p.symbol.clone(),
-                    quantity: p.quantity,
-                    market_value: p.quantity * market_price,
-                    average_cost: p.average_cost,
-                    unrealized_pnl: p.quantity * (market_price - p.average_cost),
-                }
-            })
+            .filter(|p| !p.quantity.is_zero())
+            .map(|p| self.broker_position(p))
             .collect())
     }
 
     async fn get_position(&self, symbol: &Symbol) -> BrokerResult<Option<BrokerPosition>> {
-        Ok(self.positions.get(symbol).and_then(|p| {
-            if p.quantity <= Decimal::ZERO {
-                return None;
-            }
-            let market_price = self
-                .latest_prices
-                .get(&p.symbol)
-                .copied()
-                .unwrap_or(p.average_cost);
-            Some(BrokerPosition {
-                symbol: p.symbol.clone(),
-                quantity: p.quantity,
-                market_value: p.quantity * market_price,
-                average_cost: p.average_cost,
-                unrealized_pnl: p.quantity * (market_price - p.average_cost),
-            })
-        }))
+        Ok(self
+            .positions
+            .get(symbol)
+            .filter(|p| !p.quantity.is_zero())
+            .map(|p| self.broker_position(p)))
     }
 
     async fn subscribe_market_data(&mut self, symbols: &[Symbol]) -> BrokerResult<()> {
@@ -587,12 +1544,16 @@ impl Broker for PaperBroker {
         self.process_market_event(event);
         Ok(())
     }
+
+    fn poll_order_updates(&mut self) -> Vec<BrokerOrderUpdate> {
+        std::mem::take(&mut self.order_updates)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Duration;
+    use chrono::{Duration, TimeZone};
     use gb_engine::BacktestEngine;
     use gb_types::market::{AssetClass, Bar, Resolution};
     use gb_types::{
@@ -606,15 +1567,20 @@ mod tests {
     }
 
     fn make_bar(symbol: Symbol, close: Decimal) -> MarketEvent {
+        make_bar_at(symbol, close, Utc::now())
+    }
+
+    fn make_bar_at(symbol: Symbol, close: Decimal, timestamp: DateTime<Utc>) -> MarketEvent {
         MarketEvent::Bar(Bar {
             symbol,
-            timestamp: Utc::now(),
+            timestamp,
             open: close,
             high: close,
             low: close,
             close,
             volume: dec!(1000),
             resolution: Resolution::Day,
+            provenance: Default::default(),
         })
     }
 
@@ -638,6 +1604,11 @@ mod tests {
             latency_model: LatencyModel::None,
             market_impact_model: MarketImpactModel::None,
             max_volume_participation: Decimal::ONE,
+            option_settlement: Default::default(),
+            bar_delivery: Default::default(),
+            event_ordering: Default::default(),
+            quantity_policy: Default::default(),
+            buying_power: None,
         };
         config
     }
@@ -688,6 +1659,59 @@ mod tests {
         assert!(bal.cash < dec!(100_000));
     }
 
+    #[tokio::test]
+    async fn test_paper_broker_fills_keep_order_tags_and_metadata() {
+        let mut broker = PaperBroker::with_defaults();
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(150)));
+
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(10), "s".into())
+            .with_tag("breakout")
+            .with_metadata(serde_json::json!({ "signal": 0.8 }));
+        broker.submit_order(order).await.unwrap();
+
+        let fill = &broker.get_fills()[0];
+        assert_eq!(fill.tags, ["breakout"]);
+        assert_eq!(fill.metadata["signal"], 0.8);
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_sizes_orders_to_the_quantity_policy() {
+        let mut broker = PaperBroker::new(PaperBrokerConfig {
+            commission_per_share: Decimal::ZERO,
+            slippage_bps: Decimal::ZERO,
+            ..Default::default()
+        });
+        broker.connect().await.unwrap();
+        let btc = Symbol::crypto("BTC-USD");
+        broker.process_market_event(&make_bar(test_symbol(), dec!(100)));
+        broker.process_market_event(&make_bar(btc.clone(), dec!(40000)));
+
+        // A fractional equity order is rounded down; the residual stays cash
+        let aapl = Order::market_order(test_symbol(), Side::Buy, dec!(12.5), "s".into());
+        let aapl_id = broker.submit_order(aapl).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(aapl_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+        let position = broker.get_position(&test_symbol()).await.unwrap().unwrap();
+        assert_eq!(position.quantity, dec!(12));
+        assert_eq!(broker.cash(), dec!(98_800));
+
+        let below_minimum = Order::market_order(test_symbol(), Side::Buy, dec!(0.5), "s".into());
+        let below_minimum_id = broker.submit_order(below_minimum).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(below_minimum_id).await.unwrap(),
+            OrderStatus::Rejected
+        );
+
+        let crypto = Order::market_order(btc.clone(), Side::Buy, dec!(0.3751), "s".into());
+        broker.submit_order(crypto).await.unwrap();
+        let position = broker.get_position(&btc).await.unwrap().unwrap();
+        assert_eq!(position.quantity, dec!(0.3751));
+        assert_eq!(broker.cash(), dec!(83_796));
+    }
+
     #[tokio::test]
     async fn test_paper_broker_limit_order_pending_then_filled() {
         let mut broker = PaperBroker::with_defaults();
@@ -758,6 +1782,50 @@ mod tests {
         assert_eq!(status, OrderStatus::Rejected);
     }
 
+    #[tokio::test]
+    async fn test_paper_broker_reports_rejections_through_order_updates() {
+        let mut broker = PaperBroker::new(PaperBrokerConfig {
+            initial_cash: dec!(1_000),
+            commission_per_share: Decimal::ZERO,
+            slippage_bps: Decimal::ZERO,
+            ..Default::default()
+        });
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(150)));
+
+        // Resting until the price reaches 120, when 10 shares cost $1,200.
+        let order = Order::limit_order(test_symbol(), Side::Buy, dec!(10), dec!(120), "s".into());
+        let oid = broker.submit_order(order).await.unwrap();
+        assert_eq!(
+            broker.poll_order_updates(),
+            vec![BrokerOrderUpdate::Accepted {
+                order_id: oid,
+                quantity: dec!(10),
+            }]
+        );
+
+        broker.process_market_event(&make_bar(test_symbol(), dec!(120)));
+        let updates = broker.poll_order_updates();
+        assert_eq!(
+            updates,
+            vec![BrokerOrderUpdate::Rejected {
+                order_id: oid,
+                reason: RejectionReason::InsufficientFunds {
+                    required: dec!(1_200),
+                    available: dec!(1_000),
+                },
+            }]
+        );
+        assert_eq!(
+            updates[0].to_order_event(),
+            gb_types::orders::OrderEvent::OrderRejected {
+                order_id: oid,
+                reason: "insufficient funds: order costs 1200 but cash is 1000".into(),
+            }
+        );
+        assert!(broker.poll_order_updates().is_empty());
+    }
+
     #[tokio::test]
     async fn test_paper_broker_rejects_naked_sell_orders() {
         let mut broker = PaperBroker::with_defaults();
@@ -816,6 +1884,564 @@ mod tests {
             .contains("current inventory"));
     }
 
+    fn buying_power_broker(model: BuyingPowerModel) -> PaperBroker {
+        PaperBroker::new(PaperBrokerConfig {
+            initial_cash: dec!(10_000),
+            commission_per_share: Decimal::ZERO,
+            slippage_bps: Decimal::ZERO,
+            buying_power: Some(model),
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_cash_account_waits_for_settled_proceeds() {
+        let mut broker = buying_power_broker(BuyingPowerModel::cash_account());
+        broker.connect().await.unwrap();
+        let tuesday = Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap();
+        broker.process_market_event(&make_bar_at(test_symbol(), dec!(100), tuesday));
+
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(100), "s".into());
+        broker.submit_order(buy).await.unwrap();
+        let sell = Order::market_order(test_symbol(), Side::Sell, dec!(100), "s".into());
+        broker.submit_order(sell).await.unwrap();
+        // The sale settles on Wednesday.
+        assert_eq!(broker.cash(), dec!(10_000));
+        let balance = broker.get_account_balance().await.unwrap();
+        assert_eq!(balance.buying_power, dec!(0));
+
+        let rebuy = Order::market_order(test_symbol(), Side::Buy, dec!(50), "s".into());
+        let rebuy_id = broker.submit_order(rebuy).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(rebuy_id).await.unwrap(),
+            OrderStatus::Rejected
+        );
+        assert!(matches!(
+            broker.poll_order_updates().last(),
+            Some(BrokerOrderUpdate::Rejected {
+                reason: RejectionReason::InsufficientFunds { .. },
+                ..
+            })
+        ));
+
+        let wednesday = tuesday + Duration::days(1);
+        broker.process_market_event(&make_bar_at(test_symbol(), dec!(100), wednesday));
+        let rebuy = Order::market_order(test_symbol(), Side::Buy, dec!(50), "s".into());
+        let rebuy_id = broker.submit_order(rebuy).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(rebuy_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_margin_account_levers_and_calls() {
+        let mut broker = buying_power_broker(BuyingPowerModel::reg_t());
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(100)));
+
+        // Twice the account's equity, and not a share more.
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(200), "s".into());
+        let buy_id = broker.submit_order(buy).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(buy_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+        assert_eq!(broker.cash(), dec!(-10_000));
+        let more = Order::market_order(test_symbol(), Side::Buy, dec!(1), "s".into());
+        let more_id = broker.submit_order(more).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(more_id).await.unwrap(),
+            OrderStatus::Rejected
+        );
+        assert_eq!(broker.margin_call(), None);
+
+        // At $60 equity is $2,000 against a $3,000 maintenance requirement;
+        // the call is logged once while it lasts.
+        broker.process_market_event(&make_bar(test_symbol(), dec!(60)));
+        broker.process_market_event(&make_bar(test_symbol(), dec!(60)));
+        let call = broker.margin_call().unwrap();
+        assert_eq!(call.equity, dec!(2_000));
+        assert_eq!(call.maintenance_requirement, dec!(3_000));
+        let calls = broker
+            .audit_log()
+            .iter()
+            .filter(|entry| entry.kind == PaperBrokerAuditKind::MarginCall)
+            .count();
+        assert_eq!(calls, 1);
+
+        broker.process_market_event(&make_bar(test_symbol(), dec!(80)));
+        assert_eq!(broker.margin_call(), None);
+    }
+
+    fn short_enabled_broker(initial_cash: Decimal) -> PaperBroker {
+        PaperBroker::new(PaperBrokerConfig {
+            initial_cash,
+            commission_per_share: Decimal::ZERO,
+            slippage_bps: Decimal::ZERO,
+            allow_short_selling: true,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_short_then_cover_at_profit() {
+        let mut broker = short_enabled_broker(dec!(100_000));
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(100)));
+
+        let short = Order::market_order(test_symbol(), Side::Sell, dec!(50), "s".into());
+        let short_id = broker.submit_order(short).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(short_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+        assert_eq!(broker.cash(), dec!(105_000));
+
+        broker.process_market_event(&make_bar(test_symbol(), dec!(90)));
+        let position = broker.get_position(&test_symbol()).await.unwrap().unwrap();
+        assert_eq!(position.quantity, dec!(-50));
+        assert_eq!(position.average_cost, dec!(100));
+        assert_eq!(position.market_value, dec!(-4500));
+        assert_eq!(position.unrealized_pnl, dec!(500));
+        assert_eq!(broker.get_positions().await.unwrap().len(), 1);
+        let balance = broker.get_account_balance().await.unwrap();
+        assert_eq!(balance.equity, dec!(100_500));
+
+        let cover = Order::market_order(test_symbol(), Side::Buy, dec!(50), "s".into());
+        broker.submit_order(cover).await.unwrap();
+        assert!(broker.get_position(&test_symbol()).await.unwrap().is_none());
+        assert_eq!(broker.realized_pnl(), dec!(500));
+        assert_eq!(broker.cash(), dec!(100_500));
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_counts_resting_sells_against_inventory() {
+        let mut broker = PaperBroker::with_defaults();
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(100)));
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(100), "s".into());
+        broker.submit_order(buy).await.unwrap();
+
+        // Two resting sells commit all 100 shares between them.
+        for quantity in [dec!(60), dec!(40)] {
+            let sell =
+                Order::limit_order(test_symbol(), Side::Sell, quantity, dec!(110), "s".into());
+            let sell_id = broker.submit_order(sell).await.unwrap();
+            assert_eq!(
+                broker.get_order_status(sell_id).await.unwrap(),
+                OrderStatus::Submitted
+            );
+        }
+
+        // A third sell would go short once the first two fill.
+        let third = Order::limit_order(test_symbol(), Side::Sell, dec!(1), dec!(110), "s".into());
+        let third_id = broker.submit_order(third).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(third_id).await.unwrap(),
+            OrderStatus::Rejected
+        );
+
+        broker.process_market_event(&make_bar(test_symbol(), dec!(110)));
+        assert!(broker.get_position(&test_symbol()).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_rejects_short_when_margin_exhausted() {
+        let mut broker = short_enabled_broker(dec!(10_000));
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(100)));
+
+        // 150 shares at $100 needs $7,500 of margin at 50%.
+        let first = Order::market_order(test_symbol(), Side::Sell, dec!(150), "s".into());
+        let first_id = broker.submit_order(first).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(first_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+
+        // Extending to 250 shares would need $12,500 against $10,000 equity.
+        let extend = Order::market_order(test_symbol(), Side::Sell, dec!(100), "s".into());
+        let extend_id = broker.submit_order(extend).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(extend_id).await.unwrap(),
+            OrderStatus::Rejected
+        );
+        let position = broker.get_position(&test_symbol()).await.unwrap().unwrap();
+        assert_eq!(position.quantity, dec!(-150));
+
+        let rejection = broker
+            .audit_log()
+            .iter()
+            .rev()
+            .find(|entry| entry.kind == PaperBrokerAuditKind::OrderRejected)
+            .expect("margin rejection should be audited");
+        assert!(rejection
+            .reason
+            .as_deref()
+            .unwrap_or_default()
+            .contains("insufficient margin"));
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_margins_naked_option_sales() {
+        let mut broker = PaperBroker::new(PaperBrokerConfig {
+            initial_cash: dec!(20_000),
+            commission_per_share: Decimal::ZERO,
+            slippage_bps: Decimal::ZERO,
+            ..Default::default()
+        });
+        broker.connect().await.unwrap();
+        let put = Symbol::option("AAPL  260619P00095000");
+        broker.register_option_contract(
+            put.clone(),
+            OptionContract::equity(
+                test_symbol(),
+                gb_types::OptionKind::Put,
+                dec!(95),
+                Utc::now() + Duration::days(60),
+            ),
+        );
+        broker.process_market_event(&make_bar(test_symbol(), dec!(100)));
+        broker.process_market_event(&make_bar(put.clone(), dec!(1.5)));
+
+        // One naked put needs (20 − 5 + 1.5) × 100 = $1,650.
+        let first = Order::market_order(put.clone(), Side::Sell, dec!(1), "s".into());
+        let first_id = broker.submit_order(first).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(first_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+        assert_eq!(broker.cash(), dec!(20_150));
+        let balance = broker.get_account_balance().await.unwrap();
+        assert_eq!(balance.equity, dec!(20_000));
+        assert_eq!(balance.buying_power, dec!(18_350));
+
+        // Twenty more would need $34,650 against $20,000.
+        let extend = Order::market_order(put.clone(), Side::Sell, dec!(20), "s".into());
+        let extend_id = broker.submit_order(extend).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(extend_id).await.unwrap(),
+            OrderStatus::Rejected
+        );
+        let position = broker.get_position(&put).await.unwrap().unwrap();
+        assert_eq!(position.quantity, dec!(-1));
+        assert_eq!(position.market_value, dec!(-150));
+
+        // Buying back lowers the requirement and is always allowed.
+        let close = Order::market_order(put.clone(), Side::Buy, dec!(1), "s".into());
+        let close_id = broker.submit_order(close).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(close_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+        assert_eq!(broker.cash(), dec!(20_000));
+    }
+
+    fn spread_broker(spread_model: SpreadModel) -> PaperBroker {
+        PaperBroker::new(PaperBrokerConfig {
+            slippage_bps: Decimal::ZERO,
+            spread_model,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_fixed_spread_fills_at_synthetic_bid_ask() {
+        let mut broker = spread_broker(SpreadModel::FixedBps { basis_points: 20 });
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(100)));
+
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(10), "s".into());
+        broker.submit_order(buy).await.unwrap();
+        let sell = Order::market_order(test_symbol(), Side::Sell, dec!(10), "s".into());
+        broker.submit_order(sell).await.unwrap();
+
+        let fills = broker.get_fills();
+        assert_eq!(fills[0].price, dec!(100.10));
+        assert_eq!(fills[1].price, dec!(99.90));
+        assert!(fills.iter().all(|fill| fill.spread == Some(dec!(0.20))));
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_observed_quote_overrides_synthetic_spread() {
+        let mut broker = spread_broker(SpreadModel::FixedBps { basis_points: 20 });
+        broker.connect().await.unwrap();
+        let quote_time = Utc::now();
+        broker.process_market_event(&MarketEvent::Quote {
+            symbol: test_symbol(),
+            timestamp: quote_time,
+            bid: dec!(99.98),
+            ask: dec!(100.03),
+            bid_size: dec!(100),
+            ask_size: dec!(100),
+        });
+
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(10), "s".into());
+        broker.submit_order(buy).await.unwrap();
+        let sell = Order::market_order(test_symbol(), Side::Sell, dec!(10), "s".into());
+        broker.submit_order(sell).await.unwrap();
+        assert_eq!(broker.get_fills()[0].price, dec!(100.03));
+        assert_eq!(broker.get_fills()[1].price, dec!(99.98));
+        assert_eq!(broker.get_fills()[1].spread, Some(dec!(0.05)));
+
+        // A later trade print falls back to the synthetic spread.
+        let mut bar = make_bar(test_symbol(), dec!(101));
+        if let MarketEvent::Bar(bar) = &mut bar {
+            bar.timestamp = quote_time + Duration::seconds(1);
+        }
+        broker.process_market_event(&bar);
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(10), "s".into());
+        broker.submit_order(buy).await.unwrap();
+        assert_eq!(broker.get_fills()[2].price, dec!(101.101));
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_volatility_scaled_spread_tracks_bar_ranges() {
+        let mut broker = spread_broker(SpreadModel::VolatilityScaled {
+            multiplier: dec!(0.1),
+            lookback: 2,
+            min_bps: 1,
+        });
+        broker.connect().await.unwrap();
+        for (high, low) in [
+            (dec!(130), dec!(70)),
+            (dec!(102), dec!(98)),
+            (dec!(104), dec!(96)),
+        ] {
+            broker.process_market_event(&MarketEvent::Bar(Bar {
+                symbol: test_symbol(),
+                timestamp: Utc::now(),
+                open: dec!(100),
+                high,
+                low,
+                close: dec!(100),
+                volume: dec!(1000),
+                resolution: Resolution::Day,
+                provenance: Default::default(),
+            }));
+        }
+
+        // Only the last two ranges (4% and 8%) count: 0.1 × 6% = 60 bps.
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(1), "s".into());
+        broker.submit_order(buy).await.unwrap();
+        assert_eq!(broker.get_fills()[0].spread, Some(dec!(0.6)));
+        assert_eq!(broker.get_fills()[0].price, dec!(100.3));
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_state_round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paper.json");
+
+        let mut broker = PaperBroker::with_defaults();
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(150)));
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(10), "s".into());
+        broker.submit_order(buy).await.unwrap();
+        let working = Order::limit_order(test_symbol(), Side::Buy, dec!(5), dec!(140), "s".into());
+        let working_id = broker.submit_order(working).await.unwrap();
+        broker.save_state(&path).unwrap();
+
+        let mut restored = PaperBroker::with_defaults();
+        restored.load_state(&path).unwrap();
+        restored.connect().await.unwrap();
+
+        assert_eq!(
+            restored.get_account_balance().await.unwrap().cash,
+            broker.get_account_balance().await.unwrap().cash
+        );
+        assert_eq!(
+            restored.get_account_balance().await.unwrap().equity,
+            broker.get_account_balance().await.unwrap().equity
+        );
+        assert_eq!(
+            restored.get_positions().await.unwrap(),
+            broker.get_positions().await.unwrap()
+        );
+        assert_eq!(restored.get_fills(), broker.get_fills());
+        let open = restored.get_open_orders().await.unwrap();
+        assert_eq!(open, broker.get_open_orders().await.unwrap());
+        assert_eq!(open.len(), 1);
+        assert_eq!(open[0].id, working_id);
+
+        // The restored working order keeps executing.
+        restored.process_market_event(&make_bar(test_symbol(), dec!(139)));
+        assert_eq!(
+            restored.get_order_status(working_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_rejects_unknown_state_version_and_autosaves() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("paper.json");
+        let mut broker = PaperBroker::new(PaperBrokerConfig {
+            autosave_path: Some(path.clone()),
+            ..Default::default()
+        });
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(150)));
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(10), "s".into());
+        broker.submit_order(buy).await.unwrap();
+
+        let mut state: PaperBrokerState =
+            serde_json::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(state.cash, broker.cash());
+        assert_eq!(state.positions[0].quantity, dec!(10));
+
+        state.version = PAPER_BROKER_STATE_VERSION + 1;
+        let mut fresh = PaperBroker::with_defaults();
+        assert!(matches!(
+            fresh.restore_state(state),
+            Err(BrokerError::Internal { message }) if message.contains("version")
+        ));
+        assert_eq!(fresh.cash(), dec!(100_000));
+    }
+
+    async fn trigger_broker_with_inventory() -> PaperBroker {
+        let mut broker = PaperBroker::new(PaperBrokerConfig {
+            slippage_bps: Decimal::ZERO,
+            ..Default::default()
+        });
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(105)));
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(10), "s".into());
+        broker.submit_order(buy).await.unwrap();
+        broker
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_stops_fill_at_gapped_price() {
+        let mut broker = trigger_broker_with_inventory().await;
+        let sell_stop =
+            Order::stop_order(test_symbol(), Side::Sell, dec!(10), dec!(100), "s".into());
+        let sell_id = broker.submit_order(sell_stop).await.unwrap();
+        let buy_stop = Order::stop_order(test_symbol(), Side::Buy, dec!(1), dec!(120), "s".into());
+        let buy_id = broker.submit_order(buy_stop).await.unwrap();
+
+        broker.process_market_event(&make_bar(test_symbol(), dec!(101)));
+        assert!(!broker.is_stop_triggered(sell_id));
+
+        // Gap down through the stop: fills at the gapped price, not 100.
+        broker.process_market_event(&make_bar(test_symbol(), dec!(90)));
+        assert_eq!(broker.get_order_fills(sell_id)[0].price, dec!(90));
+
+        // Gap up through the buy stop.
+        broker.process_market_event(&make_bar(test_symbol(), dec!(130)));
+        assert_eq!(broker.get_order_fills(buy_id)[0].price, dec!(130));
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_stop_limit_triggers_then_fills_later() {
+        let mut broker = trigger_broker_with_inventory().await;
+        let sell = Order::new(
+            test_symbol(),
+            Side::Sell,
+            dec!(10),
+            OrderType::StopLimit {
+                stop_price: dec!(100),
+                limit_price: dec!(98),
+            },
+            "s".into(),
+        );
+        let sell_id = broker.submit_order(sell).await.unwrap();
+        let buy = Order::new(
+            test_symbol(),
+            Side::Buy,
+            dec!(1),
+            OrderType::StopLimit {
+                stop_price: dec!(110),
+                limit_price: dec!(112),
+            },
+            "s".into(),
+        );
+        let buy_id = broker.submit_order(buy).await.unwrap();
+
+        // Gaps through the sell stop and below its limit: triggered, resting.
+        broker.process_market_event(&make_bar(test_symbol(), dec!(97)));
+        assert!(broker.is_stop_triggered(sell_id));
+        assert_eq!(
+            broker.get_order_status(sell_id).await.unwrap(),
+            OrderStatus::Submitted
+        );
+        // Recovers above the limit (but still under the stop): now fills.
+        broker.process_market_event(&make_bar(test_symbol(), dec!(99)));
+        assert_eq!(
+            broker.get_order_status(sell_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+        assert_eq!(broker.get_order_fills(sell_id)[0].price, dec!(98));
+
+        // Gaps through the buy stop and above its limit, then pulls back.
+        broker.process_market_event(&make_bar(test_symbol(), dec!(115)));
+        assert!(broker.is_stop_triggered(buy_id));
+        assert!(broker.get_order_fills(buy_id).is_empty());
+        broker.process_market_event(&make_bar(test_symbol(), dec!(108)));
+        assert_eq!(broker.get_order_fills(buy_id)[0].price, dec!(112));
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_trailing_stops_follow_price() {
+        let mut broker = trigger_broker_with_inventory().await;
+        broker.process_market_event(&make_bar(test_symbol(), dec!(100)));
+        let sell =
+            Order::trailing_stop_order(test_symbol(), Side::Sell, dec!(10), dec!(5), "s".into());
+        let sell_id = broker.submit_order(sell).await.unwrap();
+        let buy =
+            Order::trailing_stop_order(test_symbol(), Side::Buy, dec!(1), dec!(5), "s".into());
+        let buy_id = broker.submit_order(buy).await.unwrap();
+        assert_eq!(broker.trailing_stop_price(sell_id), Some(dec!(95)));
+        assert_eq!(broker.trailing_stop_price(buy_id), Some(dec!(105)));
+
+        broker.process_market_event(&make_bar(test_symbol(), dec!(104)));
+        assert_eq!(broker.trailing_stop_price(sell_id), Some(dec!(99)));
+        assert_eq!(broker.trailing_stop_price(buy_id), Some(dec!(105)));
+        broker.process_market_event(&make_bar(test_symbol(), dec!(110)));
+        assert_eq!(broker.get_order_fills(buy_id)[0].price, dec!(110));
+        assert_eq!(broker.trailing_stop_price(sell_id), Some(dec!(105)));
+
+        broker.process_market_event(&make_bar(test_symbol(), dec!(107)));
+        assert!(broker.get_order_fills(sell_id).is_empty());
+        broker.process_market_event(&make_bar(test_symbol(), dec!(103)));
+        assert_eq!(broker.get_order_fills(sell_id)[0].price, dec!(103));
+
+        let invalid =
+            Order::trailing_stop_order(test_symbol(), Side::Sell, dec!(1), dec!(0), "s".into());
+        assert!(matches!(
+            broker.submit_order(invalid).await,
+            Err(BrokerError::OrderRejected { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_triggered_state_survives_restore() {
+        let mut broker = trigger_broker_with_inventory().await;
+        let sell = Order::new(
+            test_symbol(),
+            Side::Sell,
+            dec!(10),
+            OrderType::StopLimit {
+                stop_price: dec!(100),
+                limit_price: dec!(98),
+            },
+            "s".into(),
+        );
+        let sell_id = broker.submit_order(sell).await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(97)));
+        assert!(broker.is_stop_triggered(sell_id));
+
+        let mut restored = PaperBroker::new(broker.config.clone());
+        restored.restore_state(broker.state()).unwrap();
+        assert!(restored.is_stop_triggered(sell_id));
+        restored.process_market_event(&make_bar(test_symbol(), dec!(99)));
+        assert_eq!(
+            restored.get_order_status(sell_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+    }
+
     #[tokio::test]
     async fn test_paper_broker_fills_recorded() {
         let mut broker = PaperBroker::with_defaults();
@@ -831,6 +2457,90 @@ mod tests {
         assert_eq!(fill.side, Side::Buy);
     }
 
+    #[tokio::test]
+    async fn test_paper_broker_participation_rate_splits_large_orders() {
+        let mut broker = PaperBroker::new(PaperBrokerConfig {
+            initial_cash: dec!(2_000_000),
+            slippage_bps: Decimal::ZERO,
+            max_participation_rate: Some(dec!(0.5)),
+            ..Default::default()
+        });
+        broker.connect().await.unwrap();
+
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(10_000), "s".into());
+        let oid = broker.submit_order(order).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(oid).await.unwrap(),
+            OrderStatus::Submitted
+        );
+
+        // Each 1,000-share bar fills at most 500 shares.
+        for i in 0..20 {
+            broker.process_market_event(&make_bar(test_symbol(), dec!(100) + Decimal::from(i)));
+            let expected = if i < 19 {
+                OrderStatus::PartiallyFilled
+            } else {
+                OrderStatus::Filled
+            };
+            assert_eq!(broker.get_order_status(oid).await.unwrap(), expected);
+        }
+
+        let fills = broker.get_order_fills(oid);
+        assert_eq!(fills.len(), 20);
+        assert!(fills.iter().all(|fill| fill.quantity == dec!(500)));
+        let commission: Decimal = fills.iter().map(|fill| fill.commission).sum();
+        assert_eq!(commission, dec!(100));
+
+        let position = broker.get_position(&test_symbol()).await.unwrap().unwrap();
+        assert_eq!(position.quantity, dec!(10_000));
+        assert_eq!(position.average_cost, dec!(109.5));
+        assert_eq!(
+            broker.cash(),
+            dec!(2_000_000) - dec!(10_000) * dec!(109.5) - commission
+        );
+        assert!(broker.get_open_orders().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_paper_broker_participation_shares_event_liquidity() {
+        let mut broker = PaperBroker::new(PaperBrokerConfig {
+            slippage_bps: Decimal::ZERO,
+            max_participation_rate: Some(dec!(0.1)),
+            ..Default::default()
+        });
+        broker.connect().await.unwrap();
+        broker.process_market_event(&make_bar(test_symbol(), dec!(50)));
+
+        // Immediate market fills draw on what the last bar left over.
+        let first = Order::market_order(test_symbol(), Side::Buy, dec!(80), "s".into());
+        let first_id = broker.submit_order(first).await.unwrap();
+        let second = Order::market_order(test_symbol(), Side::Buy, dec!(80), "s".into());
+        let second_id = broker.submit_order(second).await.unwrap();
+        assert_eq!(
+            broker.get_order_status(first_id).await.unwrap(),
+            OrderStatus::Filled
+        );
+        assert_eq!(broker.get_order_fills(second_id)[0].quantity, dec!(20));
+
+        // Quotes cap fills at the same share of the displayed depth on the
+        // opposite side.
+        broker.process_market_event(&MarketEvent::Quote {
+            symbol: test_symbol(),
+            timestamp: Utc::now(),
+            bid: dec!(49.99),
+            ask: dec!(50.01),
+            bid_size: dec!(500),
+            ask_size: dec!(250),
+        });
+        let second_fills = broker.get_order_fills(second_id);
+        assert_eq!(second_fills.len(), 2);
+        assert_eq!(second_fills[1].quantity, dec!(25));
+        assert_eq!(
+            broker.get_order_status(second_id).await.unwrap(),
+            OrderStatus::PartiallyFilled
+        );
+    }
+
     #[tokio::test]
     async fn test_backtest_order_stream_replays_on_paper_broker() {
         let config = sample_backtest_config();
@@ -855,6 +2565,8 @@ mod tests {
             commission_per_share: Decimal::ZERO,
             slippage_bps: Decimal::ZERO,
             fill_market_orders_immediately: false,
+            max_participation_rate: None,
+            ..Default::default()
         });
         broker.connect().await.unwrap();
 
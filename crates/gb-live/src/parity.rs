@@ -0,0 +1,173 @@
+//! Backtest-to-live parity runs: one strategy over the same bars through the
+//! backtest engine and through a [`LiveEngine`] on a [`PaperBroker`], with
+//! the resulting fills, positions and equity compared day by day.
+//!
+//! The paper broker fills market orders on the next event at its price, so
+//! each bar is preceded by a trade tick at its open: orders placed on one
+//! bar fill at the next bar's open, as they do in the backtest. Partial
+//! fills are not matched, since the paper broker works the remainder at the
+//! bar's close; give the bars enough volume for the strategy's orders.
+
+use chrono::NaiveDate;
+use gb_engine::parity::{
+    backtest_trace, bar_symbols, ParityFill, ParityPoint, ParityReport, ParitySettings, ParityTrace,
+};
+use gb_types::market::{Bar, MarketEvent, Tick, TickType};
+use gb_types::strategy::{Strategy, StrategyConfig};
+use rust_decimal::Decimal;
+
+use crate::broker::Broker;
+use crate::engine::{LiveEngine, LiveEngineConfig, TradingMode};
+use crate::paper::{PaperBroker, PaperBrokerConfig};
+use crate::risk::RiskConfig;
+
+/// The paper broker `settings` describe: per-share commission, slippage as
+/// a fraction of price, no spread, and market orders held for the next
+/// market event.
+pub fn paper_broker_config(settings: &ParitySettings) -> PaperBrokerConfig {
+    PaperBrokerConfig {
+        initial_cash: settings.initial_capital,
+        commission_per_share: settings.commission_per_share,
+        slippage_bps: Decimal::from(settings.slippage_bps) / Decimal::from(10_000),
+        fill_market_orders_immediately: false,
+        max_participation_rate: Some(settings.max_volume_participation),
+        ..Default::default()
+    }
+}
+
+/// Run the strategy `strategy` builds through the backtest engine and the
+/// live engine on `bars` with matched `settings`, and report where the two
+/// first diverge. The strategy trades every symbol of the bars.
+pub async fn run_parity<S, F>(
+    strategy: F,
+    strategy_config: &StrategyConfig,
+    bars: &[Bar],
+    settings: &ParitySettings,
+) -> Result<ParityReport, String>
+where
+    S: Strategy + 'static,
+    F: Fn() -> S,
+{
+    let paper = paper_broker_config(settings);
+    run_parity_with(strategy, strategy_config, bars, settings, paper).await
+}
+
+/// [`run_parity`] with the live path's paper broker configured by `paper`
+/// instead of `settings`.
+pub async fn run_parity_with<S, F>(
+    strategy: F,
+    strategy_config: &StrategyConfig,
+    bars: &[Bar],
+    settings: &ParitySettings,
+    paper: PaperBrokerConfig,
+) -> Result<ParityReport, String>
+where
+    S: Strategy + 'static,
+    F: Fn() -> S,
+{
+    // The backtest hands the strategy its own symbols and capital.
+    let mut strategy_config = strategy_config.clone();
+    strategy_config.symbols = bar_symbols(bars);
+    strategy_config.initial_capital = settings.initial_capital;
+
+    let backtest = backtest_trace(Box::new(strategy()), &strategy_config, bars, settings)
+        .await
+        .map_err(|e| format!("parity backtest failed: {e}"))?;
+    let live = live_trace(strategy(), strategy_config, bars, settings, paper).await?;
+    Ok(ParityReport::new(backtest, live, &settings.normalization))
+}
+
+/// Replay `bars` through a live engine on a paper broker, a day at a time.
+async fn live_trace<S: Strategy>(
+    strategy: S,
+    strategy_config: StrategyConfig,
+    bars: &[Bar],
+    settings: &ParitySettings,
+    paper: PaperBrokerConfig,
+) -> Result<ParityTrace, String> {
+    let config = LiveEngineConfig {
+        mode: TradingMode::Sandbox,
+        strategy_config,
+        // The backtest has no pre-trade risk checks.
+        risk_config: RiskConfig {
+            dry_run: true,
+            ..Default::default()
+        },
+        initial_capital: settings.initial_capital,
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: Default::default(),
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: Default::default(),
+        end_of_day: Default::default(),
+        shadow: Default::default(),
+        event_ordering: Default::default(),
+    };
+    let mut engine = LiveEngine::new(PaperBroker::new(paper), strategy, config);
+    engine.start().await?;
+
+    let mut bars = bars.to_vec();
+    bars.sort_by_key(|bar| bar.timestamp);
+    let mut trace = ParityTrace::default();
+    for day in bars.chunk_by(|a, b| a.timestamp.date_naive() == b.timestamp.date_naive()) {
+        let date = day[0].timestamp.date_naive();
+        for bar in day {
+            let open = MarketEvent::Tick(Tick {
+                symbol: bar.symbol.clone(),
+                timestamp: bar.timestamp,
+                price: bar.open,
+                size: bar.volume,
+                tick_type: TickType::Trade,
+            });
+            engine.broker_mut().process_market_event(&open);
+        }
+        deliver_fills(&mut engine, &mut trace, date).await?;
+        for bar in day {
+            engine
+                .on_market_event(MarketEvent::Bar(bar.clone()))
+                .await?;
+            deliver_fills(&mut engine, &mut trace, date).await?;
+        }
+        engine.on_day_end().await?;
+        deliver_fills(&mut engine, &mut trace, date).await?;
+
+        let broker = engine.broker();
+        let equity = broker
+            .get_account_balance()
+            .await
+            .map_err(|e| e.to_string())?
+            .equity;
+        let positions = broker
+            .get_positions()
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|position| !position.quantity.is_zero())
+            .map(|position| (position.symbol.to_string(), position.quantity))
+            .collect();
+        trace.path.push(ParityPoint {
+            date,
+            equity,
+            positions,
+        });
+    }
+    engine.stop("parity run finished").await?;
+    Ok(trace)
+}
+
+/// Hand the broker's fills since the last call to the engine, recording
+/// them in `trace` on `date`.
+async fn deliver_fills<S: Strategy>(
+    engine: &mut LiveEngine<PaperBroker, S>,
+    trace: &mut ParityTrace,
+    date: NaiveDate,
+) -> Result<(), String> {
+    while let Some(fill) = engine.broker().get_fills().get(trace.fills.len()).cloned() {
+        trace.fills.push(ParityFill::new(date, &fill));
+        engine.on_fill(fill).await?;
+    }
+    Ok(())
+}
@@ -1,14 +1,16 @@
 //! Pre-trade risk controls and circuit breakers for live trading.
 
 use chrono::{DateTime, Duration, Utc};
-use gb_types::market::Symbol;
-use gb_types::orders::{Order, Side};
+use gb_types::market::{AssetClass, Symbol};
+use gb_types::orders::{Order, OrderType, Side};
 use gb_types::portfolio::RiskLimits;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::warn;
 
+use crate::calendar::TradingCalendar;
+
 /// Specific rule that approved or rejected an order.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RiskRule {
@@ -17,6 +19,12 @@ pub enum RiskRule {
     MaxOrderNotional,
     PositionConcentration,
     TotalExposure,
+    RestrictedSymbol,
+    TradingWindow,
+    PriceDeviation,
+    AdvParticipation,
+    MaxPositionQuantity,
+    MaxOpenOrders,
 }
 
 /// Audit outcome recorded for each risk decision.
@@ -57,6 +65,41 @@ impl RiskCheckResult {
     }
 }
 
+/// Session buffers during which orders that open or add to a position are
+/// rejected for one asset class. Orders that only reduce an existing
+/// position are always allowed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradingWindow {
+    pub asset_class: AssetClass,
+    /// Session schedule the buffers are measured from.
+    pub calendar: TradingCalendar,
+    /// Minutes after the session open before new positions are allowed.
+    #[serde(default)]
+    pub open_buffer_minutes: u32,
+    /// Minutes before the session close from which new positions are blocked.
+    #[serde(default)]
+    pub close_buffer_minutes: u32,
+}
+
+/// How the risk checks treat a symbol that has no known price: the order's
+/// own symbol, or a held symbol while computing total exposure.
+///
+/// Limit and stop-limit orders are valued at their limit price when there is
+/// no mark, so this policy only governs unpriced market and stop orders; the
+/// fat-finger deviation check handles a missing price through
+/// `price_check_strict` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MissingMarkPolicy {
+    /// Reject the order — its notional or exposure cannot be computed
+    /// reliably.
+    #[default]
+    Strict,
+    /// Log a warning, value an unpriced order at zero and leave an unpriced
+    /// position out of the exposure sum.
+    Lenient,
+}
+
 /// Configuration for the live risk manager.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RiskConfig {
@@ -72,11 +115,50 @@ pub struct RiskConfig {
     pub max_order_notional: Decimal,
     /// Maximum total notional exposure across all positions.
     pub max_total_exposure: Decimal,
+    /// What to do when a held symbol has no known mark while computing total
+    /// exposure.
+    #[serde(default)]
+    pub missing_mark_policy: MissingMarkPolicy,
 
     /// If the portfolio loses more than this fraction of starting equity in a
     /// single day, halt all trading (circuit breaker).
     pub daily_loss_circuit_breaker: Decimal,
 
+    /// Symbols that must not be traded.
+    #[serde(default)]
+    pub restricted_symbols: Vec<Symbol>,
+    /// Still allow orders that only reduce an existing position in a
+    /// restricted symbol.
+    #[serde(default)]
+    pub restricted_allow_closing: bool,
+    /// Per-asset-class windows outside which new positions are blocked.
+    #[serde(default)]
+    pub trading_windows: Vec<TradingWindow>,
+
+    /// Largest distance, in percent of the market price, between a limit or
+    /// stop-limit order's prices and the market (e.g. `10` = 10%).
+    #[serde(default)]
+    pub max_price_deviation_pct: Option<Decimal>,
+    /// Largest market order as a fraction of the symbol's average daily
+    /// volume (see [`RiskManager::set_average_daily_volume`]).
+    #[serde(default)]
+    pub max_adv_fraction: Option<Decimal>,
+    /// Reject orders the fat-finger checks cannot evaluate because no market
+    /// price (or average daily volume) is known, instead of letting them
+    /// through.
+    #[serde(default)]
+    pub price_check_strict: bool,
+
+    /// Hard cap on the absolute position quantity, keyed by ticker.
+    #[serde(default)]
+    pub max_position_quantity: HashMap<String, Decimal>,
+    /// Cap for tickers missing from `max_position_quantity`.
+    #[serde(default)]
+    pub default_max_position_quantity: Option<Decimal>,
+    /// Maximum working orders per symbol.
+    #[serde(default)]
+    pub max_open_orders_per_symbol: Option<u32>,
+
     /// When true, log rejections as warnings but still allow the order through.
     /// Useful during initial deployment to observe the risk engine.
     pub dry_run: bool,
@@ -90,7 +172,17 @@ impl Default for RiskConfig {
             order_window_seconds: 60,
             max_order_notional: Decimal::from(100_000),
             max_total_exposure: Decimal::from(500_000),
+            missing_mark_policy: MissingMarkPolicy::Strict,
             daily_loss_circuit_breaker: Decimal::new(5, 2), // 5%
+            restricted_symbols: Vec::new(),
+            restricted_allow_closing: false,
+            trading_windows: Vec::new(),
+            max_price_deviation_pct: None,
+            max_adv_fraction: None,
+            price_check_strict: false,
+            max_position_quantity: HashMap::new(),
+            default_max_position_quantity: None,
+            max_open_orders_per_symbol: None,
             dry_run: false,
         }
     }
@@ -105,6 +197,10 @@ struct SessionState {
     positions: HashMap<Symbol, Decimal>,
     /// Latest known marks keyed by symbol.
     marks: HashMap<Symbol, Decimal>,
+    /// Average daily volume (in units) keyed by symbol.
+    average_daily_volumes: HashMap<Symbol, Decimal>,
+    /// Working orders per symbol, maintained by the engine.
+    open_orders: HashMap<Symbol, u32>,
     /// Starting equity for the current trading day.
     start_of_day_equity: Decimal,
     /// Whether the circuit breaker has been tripped.
@@ -113,6 +209,30 @@ struct SessionState {
     circuit_breaker_tripped_at: Option<DateTime<Utc>>,
 }
 
+/// Serializable snapshot of a [`RiskManager`]'s session state, used to
+/// persist and restore a live session. Maps are stored as sorted pairs since
+/// JSON object keys must be strings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RiskSessionState {
+    pub recent_orders: Vec<DateTime<Utc>>,
+    pub positions: Vec<(Symbol, Decimal)>,
+    pub marks: Vec<(Symbol, Decimal)>,
+    pub average_daily_volumes: Vec<(Symbol, Decimal)>,
+    pub open_orders: Vec<(Symbol, u32)>,
+    pub start_of_day_equity: Decimal,
+    pub circuit_breaker_tripped: bool,
+    pub circuit_breaker_tripped_at: Option<DateTime<Utc>>,
+}
+
+fn sorted_pairs<V: Copy>(map: &HashMap<Symbol, V>) -> Vec<(Symbol, V)> {
+    let mut pairs: Vec<(Symbol, V)> = map
+        .iter()
+        .map(|(symbol, value)| (symbol.clone(), *value))
+        .collect();
+    pairs.sort_by_key(|(symbol, _)| symbol.to_string());
+    pairs
+}
+
 /// Live risk manager that validates every order before it reaches the broker.
 #[derive(Debug)]
 pub struct RiskManager {
@@ -131,6 +251,8 @@ impl RiskManager {
                 recent_orders: Vec::new(),
                 positions: HashMap::new(),
                 marks: HashMap::new(),
+                average_daily_volumes: HashMap::new(),
+                open_orders: HashMap::new(),
                 start_of_day_equity: starting_equity,
                 circuit_breaker_tripped: false,
                 circuit_breaker_tripped_at: None,
@@ -150,7 +272,19 @@ impl RiskManager {
         current_price: Decimal,
         current_equity: Decimal,
     ) -> RiskCheckResult {
-        let result = self.run_checks(order, current_price, current_equity);
+        self.check_order_at(order, current_price, current_equity, Utc::now())
+    }
+
+    /// [`check_order`](Self::check_order) evaluated as of `now` (rate limits
+    /// and trading windows are time dependent).
+    pub fn check_order_at(
+        &mut self,
+        order: &Order,
+        current_price: Decimal,
+        current_equity: Decimal,
+        now: DateTime<Utc>,
+    ) -> RiskCheckResult {
+        let result = self.run_checks(order, current_price, current_equity, now);
 
         if let RiskCheckResult::Rejected { reason, .. } = &result {
             if self.config.dry_run {
@@ -221,20 +355,65 @@ impl RiskManager {
         order: &Order,
         current_price: Decimal,
         current_equity: Decimal,
+        now: DateTime<Utc>,
     ) -> RiskCheckResult {
         // 1) Circuit breaker
         if let result @ RiskCheckResult::Rejected { .. } =
-            self.check_circuit_breaker(current_equity)
+            self.check_circuit_breaker(current_equity, now)
         {
             return result;
         }
 
-        // 2) Order rate limit
-        if let result @ RiskCheckResult::Rejected { .. } = self.check_order_rate() {
+        // 2) Compliance: restricted symbols and trading windows
+        if let result @ RiskCheckResult::Rejected { .. } = self.check_restricted_symbol(order) {
+            return result;
+        }
+        if let result @ RiskCheckResult::Rejected { .. } = self.check_trading_window(order, now) {
             return result;
         }
 
-        // 3) Single-order notional limit
+        // 3) Order rate limit
+        if let result @ RiskCheckResult::Rejected { .. } = self.check_order_rate(now) {
+            return result;
+        }
+
+        // Fall back to the last known mark when the caller has no live price,
+        // so the remaining checks never value the order at zero.
+        let mark = self.order_mark(&order.symbol, current_price);
+
+        // 4) Fat-finger price and size checks, which handle a missing price
+        // themselves
+        if let result @ RiskCheckResult::Rejected { .. } =
+            self.check_price_deviation(order, mark.unwrap_or(Decimal::ZERO))
+        {
+            return result;
+        }
+
+        // 5) Per-symbol hard caps
+        if let result @ RiskCheckResult::Rejected { .. } = self.check_symbol_caps(order) {
+            return result;
+        }
+
+        let current_price = match mark.or_else(|| Self::order_limit_price(order)) {
+            Some(price) => price,
+            None => match self.config.missing_mark_policy {
+                MissingMarkPolicy::Strict => {
+                    return RiskCheckResult::Rejected {
+                        rule: RiskRule::MaxOrderNotional,
+                        reason: format!("no price or mark for {} to value the order", order.symbol),
+                    };
+                }
+                MissingMarkPolicy::Lenient => {
+                    warn!(
+                        symbol = %order.symbol,
+                        "no price or mark for order symbol; valuing it at zero"
+                    );
+                    Decimal::ZERO
+                }
+            },
+        };
+
+        // 6) Single-order notional limit
         let notional = order.quantity * current_price;
         if notional > self.config.max_order_notional {
             return RiskCheckResult::Rejected {
@@ -246,14 +425,14 @@ impl RiskManager {
             };
         }
 
-        // 4) Position concentration
+        // 7) Position concentration
         if let result @ RiskCheckResult::Rejected { .. } =
             self.check_position_concentration(order, current_price, current_equity)
         {
             return result;
         }
 
-        // 5) Total exposure
+        // 8) Total exposure
         if let result @ RiskCheckResult::Rejected { .. } =
             self.check_total_exposure(order, current_price)
         {
@@ -261,14 +440,18 @@ impl RiskManager {
         }
 
         // All checks passed — record the order timestamp for rate limiting.
-        self.state.recent_orders.push(Utc::now());
+        self.state.recent_orders.push(now);
 
         RiskCheckResult::Approved
     }
 
     // -- individual checks --------------------------------------------------
 
-    fn check_circuit_breaker(&mut self, current_equity: Decimal) -> RiskCheckResult {
+    fn check_circuit_breaker(
+        &mut self,
+        current_equity: Decimal,
+        now: DateTime<Utc>,
+    ) -> RiskCheckResult {
         if self.state.circuit_breaker_tripped {
             return RiskCheckResult::Rejected {
                 rule: RiskRule::CircuitBreaker,
@@ -281,7 +464,7 @@ impl RiskManager {
                 (self.state.start_of_day_equity - current_equity) / self.state.start_of_day_equity;
             if loss_pct >= self.config.daily_loss_circuit_breaker {
                 self.state.circuit_breaker_tripped = true;
-                self.state.circuit_breaker_tripped_at = Some(Utc::now());
+                self.state.circuit_breaker_tripped_at = Some(now);
                 warn!(
                     loss_pct = %loss_pct,
                     threshold = %self.config.daily_loss_circuit_breaker,
@@ -300,9 +483,203 @@ impl RiskManager {
         RiskCheckResult::Approved
     }
 
-    fn check_order_rate(&mut self) -> RiskCheckResult {
+    fn check_restricted_symbol(&self, order: &Order) -> RiskCheckResult {
+        if !self.config.restricted_symbols.contains(&order.symbol) {
+            return RiskCheckResult::Approved;
+        }
+        if self.config.restricted_allow_closing && self.reduces_position(order) {
+            return RiskCheckResult::Approved;
+        }
+        RiskCheckResult::Rejected {
+            rule: RiskRule::RestrictedSymbol,
+            reason: if self.config.restricted_allow_closing {
+                format!("{} is restricted to closing orders", order.symbol)
+            } else {
+                format!("{} is on the restricted list", order.symbol)
+            },
+        }
+    }
+
+    fn check_trading_window(&self, order: &Order, now: DateTime<Utc>) -> RiskCheckResult {
+        let Some(window) = self
+            .config
+            .trading_windows
+            .iter()
+            .find(|window| window.asset_class == order.symbol.asset_class)
+        else {
+            return RiskCheckResult::Approved;
+        };
+        if self.reduces_position(order) {
+            return RiskCheckResult::Approved;
+        }
+
+        let opens_at = window.calendar.session_open(now)
+            + Duration::minutes(window.open_buffer_minutes as i64);
+        let closes_at = window.calendar.session_close(now)
+            - Duration::minutes(window.close_buffer_minutes as i64);
+        if now < opens_at {
+            return RiskCheckResult::Rejected {
+                rule: RiskRule::TradingWindow,
+                reason: format!(
+                    "new {} positions are not allowed before {opens_at}",
+                    order.symbol
+                ),
+            };
+        }
+        if now >= closes_at {
+            return RiskCheckResult::Rejected {
+                rule: RiskRule::TradingWindow,
+                reason: format!(
+                    "new {} positions are not allowed in the last {} minutes of the session",
+                    order.symbol, window.close_buffer_minutes
+                ),
+            };
+        }
+
+        RiskCheckResult::Approved
+    }
+
+    fn check_price_deviation(&self, order: &Order, current_price: Decimal) -> RiskCheckResult {
+        let prices: Vec<(&str, Decimal)> = match order.order_type {
+            OrderType::Limit { price } => vec![("limit", price)],
+            OrderType::StopLimit {
+                stop_price,
+                limit_price,
+            } => vec![("stop", stop_price), ("limit", limit_price)],
+            OrderType::Market => return self.check_adv_participation(order, current_price),
+            _ => return RiskCheckResult::Approved,
+        };
+        let Some(max_pct) = self.config.max_price_deviation_pct else {
+            return RiskCheckResult::Approved;
+        };
+        if current_price <= Decimal::ZERO {
+            return self.unpriced(order, RiskRule::PriceDeviation);
+        }
+
+        for (label, price) in prices {
+            let deviation_pct = (price - current_price).abs() / current_price * Decimal::from(100);
+            if deviation_pct > max_pct {
+                return RiskCheckResult::Rejected {
+                    rule: RiskRule::PriceDeviation,
+                    reason: format!(
+                        "{label} price {price} is {deviation_pct:.2}% from market price \
+                         {current_price} (limit {max_pct}%)"
+                    ),
+                };
+            }
+        }
+
+        RiskCheckResult::Approved
+    }
+
+    fn check_adv_participation(&self, order: &Order, current_price: Decimal) -> RiskCheckResult {
+        let Some(max_fraction) = self.config.max_adv_fraction else {
+            return RiskCheckResult::Approved;
+        };
+        let Some(adv) = self.state.average_daily_volumes.get(&order.symbol).copied() else {
+            return self.unpriced(order, RiskRule::AdvParticipation);
+        };
+        if current_price <= Decimal::ZERO {
+            return self.unpriced(order, RiskRule::AdvParticipation);
+        }
+
+        let notional = order.quantity * current_price;
+        let cap = adv * max_fraction * current_price;
+        if notional > cap {
+            return RiskCheckResult::Rejected {
+                rule: RiskRule::AdvParticipation,
+                reason: format!(
+                    "market order notional {notional} at {current_price} exceeds {max_fraction} \
+                     of average daily volume ({cap})"
+                ),
+            };
+        }
+
+        RiskCheckResult::Approved
+    }
+
+    fn check_symbol_caps(&self, order: &Order) -> RiskCheckResult {
+        if let Some(max_open) = self.config.max_open_orders_per_symbol {
+            let open = self.open_order_count(&order.symbol);
+            if open >= max_open {
+                return RiskCheckResult::Rejected {
+                    rule: RiskRule::MaxOpenOrders,
+                    reason: format!(
+                        "{} already has {open} working orders (limit {max_open})",
+                        order.symbol
+                    ),
+                };
+            }
+        }
+
+        let Some(limit) = self
+            .config
+            .max_position_quantity
+            .get(&order.symbol.symbol)
+            .copied()
+            .or(self.config.default_max_position_quantity)
+        else {
+            return RiskCheckResult::Approved;
+        };
+        let current = self
+            .state
+            .positions
+            .get(&order.symbol)
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+        let new_qty = match order.side {
+            Side::Buy => current + order.quantity,
+            Side::Sell => current - order.quantity,
+        };
+        // Shrinking a position on the same side always passes, even while it
+        // is still above the cap.
+        let reducing = new_qty.abs() < current.abs()
+            && (new_qty.is_zero() || new_qty.is_sign_positive() == current.is_sign_positive());
+        if new_qty.abs() > limit && !reducing {
+            return RiskCheckResult::Rejected {
+                rule: RiskRule::MaxPositionQuantity,
+                reason: format!(
+                    "{} position would be {new_qty}, beyond the {limit} quantity cap",
+                    order.symbol
+                ),
+            };
+        }
+
+        RiskCheckResult::Approved
+    }
+
+    /// Outcome of a fat-finger check that has no reference to compare with.
+    fn unpriced(&self, order: &Order, rule: RiskRule) -> RiskCheckResult {
+        if !self.config.price_check_strict {
+            return RiskCheckResult::Approved;
+        }
+        RiskCheckResult::Rejected {
+            rule,
+            reason: format!(
+                "no market reference for {} to validate the order",
+                order.symbol
+            ),
+        }
+    }
+
+    /// Whether `order` only shrinks the current position (without flipping
+    /// it).
+    fn reduces_position(&self, order: &Order) -> bool {
+        let current = self
+            .state
+            .positions
+            .get(&order.symbol)
+            .copied()
+            .unwrap_or(Decimal::ZERO);
+        match order.side {
+            Side::Buy => current < Decimal::ZERO && order.quantity <= -current,
+            Side::Sell => current > Decimal::ZERO && order.quantity <= current,
+        }
+    }
+
+    fn check_order_rate(&mut self, now: DateTime<Utc>) -> RiskCheckResult {
         let window = Duration::seconds(self.config.order_window_seconds as i64);
-        let cutoff = Utc::now() - window;
+        let cutoff = now - window;
 
         // Prune old entries
         self.state.recent_orders.retain(|t| *t >= cutoff);
@@ -385,12 +762,24 @@ impl RiskManager {
             } else if let Some(mark) = self.state.marks.get(&symbol).copied() {
                 mark
             } else {
-                return RiskCheckResult::Rejected {
-                    rule: RiskRule::TotalExposure,
-                    reason: format!(
-                        "missing mark for existing position {symbol} while computing total exposure"
-                    ),
-                };
+                match self.config.missing_mark_policy {
+                    MissingMarkPolicy::Strict => {
+                        return RiskCheckResult::Rejected {
+                            rule: RiskRule::TotalExposure,
+                            reason: format!(
+                                "missing mark for existing position {symbol} while computing total exposure"
+                            ),
+                        };
+                    }
+                    MissingMarkPolicy::Lenient => {
+                        warn!(
+                            symbol = %symbol,
+                            quantity = %quantity,
+                            "no mark for held position; excluding it from total exposure"
+                        );
+                        continue;
+                    }
+                }
             };
 
             new_exposure += quantity.abs() * mark;
@@ -409,6 +798,25 @@ impl RiskManager {
         RiskCheckResult::Approved
     }
 
+    /// Price used to value the order's own symbol: the caller-supplied price
+    /// when one is available, otherwise the last known mark.
+    fn order_mark(&self, symbol: &Symbol, current_price: Decimal) -> Option<Decimal> {
+        if current_price > Decimal::ZERO {
+            return Some(current_price);
+        }
+        self.state.marks.get(symbol).copied()
+    }
+
+    /// The price a limit or stop-limit order caps itself at, used to value it
+    /// when its symbol has no mark.
+    fn order_limit_price(order: &Order) -> Option<Decimal> {
+        match order.order_type {
+            OrderType::Limit { price } => Some(price),
+            OrderType::StopLimit { limit_price, .. } => Some(limit_price),
+            _ => None,
+        }
+    }
+
     // -- state updates called by the engine ---------------------------------
 
     /// Update the latest known mark for a symbol from market data.
@@ -416,6 +824,35 @@ impl RiskManager {
         self.state.marks.insert(symbol.clone(), price);
     }
 
+    /// Record a new working order for `symbol`.
+    pub fn record_order_opened(&mut self, symbol: &Symbol) {
+        *self.state.open_orders.entry(symbol.clone()).or_insert(0) += 1;
+    }
+
+    /// Record that a working order for `symbol` was filled, canceled, or
+    /// otherwise closed.
+    pub fn record_order_closed(&mut self, symbol: &Symbol) {
+        if let Some(count) = self.state.open_orders.get_mut(symbol) {
+            *count = count.saturating_sub(1);
+            if *count == 0 {
+                self.state.open_orders.remove(symbol);
+            }
+        }
+    }
+
+    /// Number of working orders tracked for `symbol`.
+    pub fn open_order_count(&self, symbol: &Symbol) -> u32 {
+        self.state.open_orders.get(symbol).copied().unwrap_or(0)
+    }
+
+    /// Set the average daily volume (in units, e.g. over 20 days) used to
+    /// cap market order size.
+    pub fn set_average_daily_volume(&mut self, symbol: &Symbol, volume: Decimal) {
+        self.state
+            .average_daily_volumes
+            .insert(symbol.clone(), volume);
+    }
+
     /// Update internal position tracking after a fill.
     pub fn update_position(
         &mut self,
@@ -445,6 +882,11 @@ impl RiskManager {
         }
     }
 
+    /// Latest known mark for a symbol, if any.
+    pub fn mark(&self, symbol: &Symbol) -> Option<Decimal> {
+        self.state.marks.get(symbol).copied()
+    }
+
     /// Reset the start-of-day equity (call at market open / start of session).
     pub fn reset_daily(&mut self, equity: Decimal) {
         self.state.start_of_day_equity = equity;
@@ -453,6 +895,37 @@ impl RiskManager {
         self.state.recent_orders.clear();
     }
 
+    /// Snapshot the session state (positions, marks, order-rate window,
+    /// start-of-day equity, and circuit breaker) for persistence.
+    pub fn session_state(&self) -> RiskSessionState {
+        RiskSessionState {
+            recent_orders: self.state.recent_orders.clone(),
+            positions: sorted_pairs(&self.state.positions),
+            marks: sorted_pairs(&self.state.marks),
+            average_daily_volumes: sorted_pairs(&self.state.average_daily_volumes),
+            open_orders: sorted_pairs(&self.state.open_orders),
+            start_of_day_equity: self.state.start_of_day_equity,
+            circuit_breaker_tripped: self.state.circuit_breaker_tripped,
+            circuit_breaker_tripped_at: self.state.circuit_breaker_tripped_at,
+        }
+    }
+
+    /// Replace the session state with one captured by
+    /// [`session_state`](Self::session_state). The config and audit log are
+    /// left untouched.
+    pub fn restore_session_state(&mut self, state: RiskSessionState) {
+        self.state = SessionState {
+            recent_orders: state.recent_orders,
+            positions: state.positions.into_iter().collect(),
+            marks: state.marks.into_iter().collect(),
+            average_daily_volumes: state.average_daily_volumes.into_iter().collect(),
+            open_orders: state.open_orders.into_iter().collect(),
+            start_of_day_equity: state.start_of_day_equity,
+            circuit_breaker_tripped: state.circuit_breaker_tripped,
+            circuit_breaker_tripped_at: state.circuit_breaker_tripped_at,
+        };
+    }
+
     /// Returns `true` if the circuit breaker is currently tripped.
     pub fn is_circuit_breaker_tripped(&self) -> bool {
         self.state.circuit_breaker_tripped
@@ -477,8 +950,9 @@ impl RiskManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
     use gb_types::market::{AssetClass, Symbol};
-    use gb_types::orders::{Order, Side};
+    use gb_types::orders::{Order, OrderType, Side};
     use rust_decimal_macros::dec;
 
     fn equity_symbol(symbol: &str) -> Symbol {
@@ -702,6 +1176,166 @@ mod tests {
         assert!(!result.is_approved(), "expected rejection, got {result:?}");
     }
 
+    #[test]
+    fn test_total_exposure_sums_positions_at_their_own_marks() {
+        let config = RiskConfig {
+            max_total_exposure: dec!(60_000),
+            max_order_notional: Decimal::from(1_000_000),
+            limits: RiskLimits {
+                position_concentration_limit: dec!(1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut rm = RiskManager::new(config, dec!(1_000_000));
+        let expensive = equity_symbol("BRK.A");
+        let cheap = equity_symbol("SNDL");
+        let incoming = equity_symbol("MSFT");
+
+        rm.update_position(&expensive, Side::Buy, dec!(1), dec!(50_000));
+        rm.update_position(&cheap, Side::Buy, dec!(1_000), dec!(2));
+        assert_eq!(rm.mark(&expensive), Some(dec!(50_000)));
+        assert_eq!(rm.mark(&cheap), Some(dec!(2)));
+
+        // $50k + $2k held + $7k new = $59k, under the $60k limit. Valuing all
+        // 1,101 shares at the incoming $70 price would falsely reject it at
+        // $77,070.
+        let order = Order::market_order(incoming.clone(), Side::Buy, dec!(100), "test".into());
+        assert!(rm
+            .check_order(&order, dec!(70), dec!(1_000_000))
+            .is_approved());
+
+        // $50k + $2k + $9k = $61k breaches the limit.
+        let order = Order::market_order(incoming, Side::Buy, dec!(100), "test".into());
+        let result = rm.check_order(&order, dec!(90), dec!(1_000_000));
+        assert!(
+            matches!(
+                result,
+                RiskCheckResult::Rejected {
+                    rule: RiskRule::TotalExposure,
+                    ..
+                }
+            ),
+            "expected exposure rejection, got {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_missing_mark_policy_strict_rejects_and_lenient_skips() {
+        let held = equity_symbol("MSFT");
+        let incoming = test_symbol();
+        let order = Order::market_order(incoming, Side::Buy, dec!(10), "test".into());
+
+        let mut strict = default_risk_manager();
+        strict.state.positions.insert(held.clone(), dec!(100));
+        let result = strict.check_order(&order, dec!(150), dec!(100_000));
+        assert!(
+            matches!(
+                result,
+                RiskCheckResult::Rejected {
+                    rule: RiskRule::TotalExposure,
+                    ..
+                }
+            ),
+            "expected strict rejection, got {result:?}"
+        );
+
+        let mut lenient = RiskManager::new(
+            RiskConfig {
+                missing_mark_policy: MissingMarkPolicy::Lenient,
+                ..Default::default()
+            },
+            dec!(100_000),
+        );
+        lenient.state.positions.insert(held, dec!(100));
+        let result = lenient.check_order(&order, dec!(150), dec!(100_000));
+        assert!(result.is_approved(), "expected approval, got {result:?}");
+    }
+
+    #[test]
+    fn test_unpriced_order_symbol_follows_missing_mark_policy() {
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(1_000_000), "test".into());
+
+        let mut strict = default_risk_manager();
+        let result = strict.check_order(&order, Decimal::ZERO, dec!(100_000));
+        assert!(
+            matches!(
+                result,
+                RiskCheckResult::Rejected {
+                    rule: RiskRule::MaxOrderNotional,
+                    ..
+                }
+            ),
+            "expected strict rejection, got {result:?}"
+        );
+
+        let mut lenient = RiskManager::new(
+            RiskConfig {
+                missing_mark_policy: MissingMarkPolicy::Lenient,
+                ..Default::default()
+            },
+            dec!(100_000),
+        );
+        let result = lenient.check_order(&order, Decimal::ZERO, dec!(100_000));
+        assert!(result.is_approved(), "expected approval, got {result:?}");
+    }
+
+    #[test]
+    fn test_unpriced_limit_order_is_valued_at_its_limit_under_strict_policy() {
+        let mut rm = RiskManager::new(
+            RiskConfig {
+                max_order_notional: dec!(1_000),
+                ..Default::default()
+            },
+            dec!(100_000),
+        );
+
+        let small = Order::limit_order(test_symbol(), Side::Buy, dec!(5), dec!(150), "test".into());
+        let result = rm.check_order(&small, Decimal::ZERO, dec!(100_000));
+        assert!(result.is_approved(), "expected approval, got {result:?}");
+
+        let large =
+            Order::limit_order(test_symbol(), Side::Buy, dec!(10), dec!(150), "test".into());
+        let result = rm.check_order(&large, Decimal::ZERO, dec!(100_000));
+        match result {
+            RiskCheckResult::Rejected {
+                rule: RiskRule::MaxOrderNotional,
+                reason,
+            } => assert!(reason.contains("1500"), "unexpected reason: {reason}"),
+            other => panic!("expected notional rejection, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_checks_fall_back_to_known_mark_without_live_price() {
+        let config = RiskConfig {
+            limits: RiskLimits {
+                position_concentration_limit: dec!(0.25),
+                ..Default::default()
+            },
+            max_order_notional: Decimal::from(1_000_000),
+            max_total_exposure: Decimal::from(1_000_000),
+            ..Default::default()
+        };
+        let mut rm = RiskManager::new(config, dec!(100_000));
+        rm.update_market_price(&test_symbol(), dec!(150));
+
+        // No live price from the broker, but the known $150 mark still values
+        // 200 shares at 30% of equity.
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(200), "test".into());
+        let result = rm.check_order(&order, Decimal::ZERO, dec!(100_000));
+        assert!(
+            matches!(
+                result,
+                RiskCheckResult::Rejected {
+                    rule: RiskRule::PositionConcentration,
+                    ..
+                }
+            ),
+            "expected concentration rejection, got {result:?}"
+        );
+    }
+
     #[test]
     fn test_audit_log_records_max_order_notional_rejection() {
         let mut rm = default_risk_manager();
@@ -824,4 +1458,307 @@ mod tests {
         assert!(!rm.state.positions.contains_key(&sym));
         assert!(!rm.state.marks.contains_key(&sym));
     }
+
+    #[test]
+    fn test_restricted_symbol_rejected_unless_closing_allowed() {
+        let config = RiskConfig {
+            restricted_symbols: vec![test_symbol()],
+            ..Default::default()
+        };
+        let mut rm = RiskManager::new(config, dec!(100_000));
+        rm.update_position(&test_symbol(), Side::Buy, dec!(10), dec!(150));
+
+        let sell = Order::market_order(test_symbol(), Side::Sell, dec!(10), "test".into());
+        let result = rm.check_order(&sell, dec!(150), dec!(100_000));
+        assert!(matches!(
+            result,
+            RiskCheckResult::Rejected { rule: RiskRule::RestrictedSymbol, ref reason }
+                if reason.contains("restricted list")
+        ));
+
+        rm.config.restricted_allow_closing = true;
+        assert!(rm
+            .check_order(&sell, dec!(150), dec!(100_000))
+            .is_approved());
+        // Adding to, or flipping, the position is still blocked.
+        for (side, quantity) in [(Side::Buy, dec!(1)), (Side::Sell, dec!(11))] {
+            let order = Order::market_order(test_symbol(), side, quantity, "test".into());
+            let result = rm.check_order(&order, dec!(150), dec!(100_000));
+            assert!(matches!(
+                result,
+                RiskCheckResult::Rejected { rule: RiskRule::RestrictedSymbol, ref reason }
+                    if reason.contains("closing orders")
+            ));
+        }
+
+        let other = Order::market_order(equity_symbol("MSFT"), Side::Buy, dec!(1), "test".into());
+        assert!(rm
+            .check_order(&other, dec!(150), dec!(100_000))
+            .is_approved());
+    }
+
+    #[test]
+    fn test_trading_window_blocks_new_positions_near_the_close() {
+        let config = RiskConfig {
+            trading_windows: vec![TradingWindow {
+                asset_class: AssetClass::Equity,
+                calendar: TradingCalendar::default(),
+                open_buffer_minutes: 5,
+                close_buffer_minutes: 10,
+            }],
+            ..Default::default()
+        };
+        let mut rm = RiskManager::new(config, dec!(100_000));
+        let at = |h, m| Utc.with_ymd_and_hms(2024, 1, 2, h, m, 0).unwrap();
+        let buy = Order::market_order(test_symbol(), Side::Buy, dec!(10), "test".into());
+
+        assert!(rm
+            .check_order_at(&buy, dec!(150), dec!(100_000), at(15, 0))
+            .is_approved());
+        for now in [at(20, 55), at(14, 32), at(22, 0)] {
+            let result = rm.check_order_at(&buy, dec!(150), dec!(100_000), now);
+            assert!(
+                matches!(
+                    result,
+                    RiskCheckResult::Rejected {
+                        rule: RiskRule::TradingWindow,
+                        ..
+                    }
+                ),
+                "expected rejection at {now}"
+            );
+        }
+
+        // Closing the position is allowed at any time.
+        rm.update_position(&test_symbol(), Side::Buy, dec!(10), dec!(150));
+        let sell = Order::market_order(test_symbol(), Side::Sell, dec!(10), "test".into());
+        assert!(rm
+            .check_order_at(&sell, dec!(150), dec!(100_000), at(20, 55))
+            .is_approved());
+
+        // Other asset classes are unaffected.
+        let btc = Order::market_order(Symbol::crypto("BTC-USD"), Side::Buy, dec!(1), "test".into());
+        assert!(rm
+            .check_order_at(&btc, dec!(150), dec!(100_000), at(20, 55))
+            .is_approved());
+    }
+
+    #[test]
+    fn test_dry_run_applies_to_compliance_checks() {
+        let config = RiskConfig {
+            restricted_symbols: vec![test_symbol()],
+            dry_run: true,
+            ..Default::default()
+        };
+        let mut rm = RiskManager::new(config, dec!(100_000));
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(1), "test".into());
+        assert!(rm
+            .check_order(&order, dec!(150), dec!(100_000))
+            .is_approved());
+        assert_eq!(rm.audit_log()[0].decision, RiskAuditDecision::WouldReject);
+        assert_eq!(rm.audit_log()[0].rule, Some(RiskRule::RestrictedSymbol));
+    }
+
+    fn fat_finger_manager(strict: bool) -> RiskManager {
+        let config = RiskConfig {
+            max_price_deviation_pct: Some(dec!(10)),
+            max_adv_fraction: Some(dec!(0.1)),
+            price_check_strict: strict,
+            ..Default::default()
+        };
+        RiskManager::new(config, dec!(100_000))
+    }
+
+    #[test]
+    fn test_price_deviation_rejects_limits_far_from_market() {
+        let mut rm = fat_finger_manager(false);
+
+        let above =
+            Order::limit_order(test_symbol(), Side::Buy, dec!(1), dec!(1500), "test".into());
+        match rm.check_order(&above, dec!(150), dec!(100_000)) {
+            RiskCheckResult::Rejected { rule, reason } => {
+                assert_eq!(rule, RiskRule::PriceDeviation);
+                assert!(
+                    reason.contains("1500") && reason.contains("150"),
+                    "{reason}"
+                );
+            }
+            other => panic!("expected rejection, got {other:?}"),
+        }
+
+        let below =
+            Order::limit_order(test_symbol(), Side::Sell, dec!(1), dec!(120), "test".into());
+        assert!(matches!(
+            rm.check_order(&below, dec!(150), dec!(100_000)),
+            RiskCheckResult::Rejected {
+                rule: RiskRule::PriceDeviation,
+                ..
+            }
+        ));
+
+        let near = Order::limit_order(test_symbol(), Side::Buy, dec!(1), dec!(160), "test".into());
+        assert!(rm
+            .check_order(&near, dec!(150), dec!(100_000))
+            .is_approved());
+
+        let stop_limit = Order::new(
+            test_symbol(),
+            Side::Sell,
+            dec!(1),
+            OrderType::StopLimit {
+                stop_price: dec!(145),
+                limit_price: dec!(14.5),
+            },
+            "test".into(),
+        );
+        assert!(!rm
+            .check_order(&stop_limit, dec!(150), dec!(100_000))
+            .is_approved());
+    }
+
+    #[test]
+    fn test_price_deviation_without_market_price_respects_strict_mode() {
+        let order =
+            Order::limit_order(test_symbol(), Side::Buy, dec!(1), dec!(1500), "test".into());
+
+        let mut lenient = fat_finger_manager(false);
+        assert!(lenient
+            .check_order(&order, Decimal::ZERO, dec!(100_000))
+            .is_approved());
+
+        let mut strict = fat_finger_manager(true);
+        assert!(matches!(
+            strict.check_order(&order, Decimal::ZERO, dec!(100_000)),
+            RiskCheckResult::Rejected {
+                rule: RiskRule::PriceDeviation,
+                ..
+            }
+        ));
+
+        // A known mark is used when the caller has no live price.
+        lenient.update_market_price(&test_symbol(), dec!(150));
+        assert!(!lenient
+            .check_order(&order, Decimal::ZERO, dec!(100_000))
+            .is_approved());
+    }
+
+    #[test]
+    fn test_market_orders_capped_by_average_daily_volume() {
+        let mut rm = fat_finger_manager(false);
+        let large = Order::market_order(test_symbol(), Side::Buy, dec!(150), "test".into());
+        // No ADV known: lenient mode lets it through.
+        assert!(rm
+            .check_order(&large, dec!(150), dec!(100_000))
+            .is_approved());
+
+        rm.set_average_daily_volume(&test_symbol(), dec!(1000));
+        assert!(matches!(
+            rm.check_order(&large, dec!(150), dec!(100_000)),
+            RiskCheckResult::Rejected {
+                rule: RiskRule::AdvParticipation,
+                ..
+            }
+        ));
+        let small = Order::market_order(test_symbol(), Side::Buy, dec!(50), "test".into());
+        assert!(rm
+            .check_order(&small, dec!(150), dec!(100_000))
+            .is_approved());
+
+        let mut strict = fat_finger_manager(true);
+        assert!(!strict
+            .check_order(&small, dec!(150), dec!(100_000))
+            .is_approved());
+    }
+
+    #[test]
+    fn test_position_quantity_cap_considers_direction() {
+        let config = RiskConfig {
+            max_position_quantity: HashMap::from([("AAPL".to_string(), dec!(500))]),
+            default_max_position_quantity: Some(dec!(50)),
+            ..Default::default()
+        };
+        let mut rm = RiskManager::new(config, dec!(10_000_000));
+        let check = |rm: &mut RiskManager, side, quantity| {
+            let order = Order::market_order(test_symbol(), side, quantity, "test".into());
+            rm.check_order(&order, dec!(1), dec!(10_000_000))
+        };
+
+        assert!(check(&mut rm, Side::Buy, dec!(500)).is_approved());
+        assert!(matches!(
+            check(&mut rm, Side::Buy, dec!(501)),
+            RiskCheckResult::Rejected {
+                rule: RiskRule::MaxPositionQuantity,
+                ..
+            }
+        ));
+
+        // Already over the cap (e.g. the limit was lowered): reducing passes,
+        // adding or flipping beyond the cap does not.
+        rm.update_position(&test_symbol(), Side::Buy, dec!(600), dec!(1));
+        assert!(check(&mut rm, Side::Sell, dec!(50)).is_approved());
+        assert!(!check(&mut rm, Side::Buy, dec!(1)).is_approved());
+        assert!(!check(&mut rm, Side::Sell, dec!(1150)).is_approved());
+        assert!(check(&mut rm, Side::Sell, dec!(1100)).is_approved());
+
+        // Unlisted tickers fall back to the default cap.
+        let msft = Order::market_order(equity_symbol("MSFT"), Side::Buy, dec!(51), "test".into());
+        assert!(!rm
+            .check_order(&msft, dec!(1), dec!(10_000_000))
+            .is_approved());
+    }
+
+    #[test]
+    fn test_open_order_counter_limits_working_orders() {
+        let config = RiskConfig {
+            max_open_orders_per_symbol: Some(2),
+            ..Default::default()
+        };
+        let mut rm = RiskManager::new(config, dec!(100_000));
+        let order = Order::limit_order(test_symbol(), Side::Buy, dec!(1), dec!(150), "test".into());
+
+        rm.record_order_opened(&test_symbol());
+        rm.record_order_opened(&test_symbol());
+        assert!(matches!(
+            rm.check_order(&order, dec!(150), dec!(100_000)),
+            RiskCheckResult::Rejected {
+                rule: RiskRule::MaxOpenOrders,
+                ..
+            }
+        ));
+
+        rm.record_order_closed(&test_symbol());
+        assert_eq!(rm.open_order_count(&test_symbol()), 1);
+        assert!(rm
+            .check_order(&order, dec!(150), dec!(100_000))
+            .is_approved());
+
+        rm.record_order_closed(&test_symbol());
+        rm.record_order_closed(&test_symbol());
+        assert_eq!(rm.open_order_count(&test_symbol()), 0);
+    }
+
+    #[test]
+    fn test_session_state_round_trips_through_json() {
+        let config = RiskConfig {
+            daily_loss_circuit_breaker: dec!(0.05),
+            ..Default::default()
+        };
+        let mut rm = RiskManager::new(config.clone(), dec!(100_000));
+        rm.update_position(&test_symbol(), Side::Buy, dec!(10), dec!(150));
+        rm.record_order_opened(&test_symbol());
+        let order = Order::market_order(test_symbol(), Side::Buy, dec!(1), "test".into());
+        let _ = rm.check_order(&order, dec!(150), dec!(94_000));
+        assert!(rm.is_circuit_breaker_tripped());
+
+        let json = serde_json::to_string(&rm.session_state()).unwrap();
+        let mut restored = RiskManager::new(config, dec!(100_000));
+        restored.restore_session_state(serde_json::from_str(&json).unwrap());
+
+        assert_eq!(restored.session_state(), rm.session_state());
+        assert!(restored.is_circuit_breaker_tripped());
+        assert_eq!(restored.open_order_count(&test_symbol()), 1);
+        assert!(!restored
+            .check_order(&order, dec!(150), dec!(99_000))
+            .is_approved());
+    }
 }
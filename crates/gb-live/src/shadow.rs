@@ -0,0 +1,167 @@
+//! Shadow trading: live market data with orders filled on an internal paper
+//! book, and a report comparing those fills with the market that followed.
+
+use chrono::{DateTime, Duration, Utc};
+use gb_types::market::Symbol;
+use gb_types::orders::{Fill, OrderId, Side};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::paper::PaperBrokerConfig;
+
+/// Settings for [`TradingMode::Shadow`](crate::engine::TradingMode::Shadow).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowConfig {
+    /// The paper book shadow orders are filled against.
+    #[serde(default)]
+    pub paper: PaperBrokerConfig,
+    /// Seconds of market time after a shadow fill at which its price is
+    /// compared with the market.
+    pub divergence_horizon_secs: u64,
+}
+
+impl Default for ShadowConfig {
+    fn default() -> Self {
+        Self {
+            paper: PaperBrokerConfig::default(),
+            divergence_horizon_secs: 60,
+        }
+    }
+}
+
+/// A shadow fill and the market price observed after it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShadowDivergence {
+    pub order_id: OrderId,
+    pub strategy_id: String,
+    pub symbol: Symbol,
+    pub side: Side,
+    pub quantity: Decimal,
+    pub fill_price: Decimal,
+    /// Market time of the fill.
+    pub filled_at: DateTime<Utc>,
+    /// First market price at least the horizon after the fill, once seen.
+    pub market_price: Option<Decimal>,
+    pub market_at: Option<DateTime<Utc>>,
+}
+
+impl ShadowDivergence {
+    /// How much better the shadow fill was than the later market, in basis
+    /// points of the market price: positive when a buy filled below it or a
+    /// sell above it.
+    pub fn divergence_bps(&self) -> Option<Decimal> {
+        let market = self.market_price.filter(|price| !price.is_zero())?;
+        let edge = match self.side {
+            Side::Buy => market - self.fill_price,
+            Side::Sell => self.fill_price - market,
+        };
+        Some(edge / market * Decimal::from(10_000))
+    }
+}
+
+/// Would-have-filled prices of a shadow session against the later market.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ShadowReport {
+    pub fills: Vec<ShadowDivergence>,
+}
+
+impl ShadowReport {
+    pub(crate) fn record_fill(&mut self, fill: &Fill, strategy_id: &str, at: DateTime<Utc>) {
+        self.fills.push(ShadowDivergence {
+            order_id: fill.order_id,
+            strategy_id: strategy_id.to_string(),
+            symbol: fill.symbol.clone(),
+            side: fill.side,
+            quantity: fill.quantity,
+            fill_price: fill.price,
+            filled_at: at,
+            market_price: None,
+            market_at: None,
+        });
+    }
+
+    /// Resolve fills of `symbol` at least `horizon` older than `at`.
+    pub(crate) fn observe(
+        &mut self,
+        symbol: &Symbol,
+        price: Decimal,
+        at: DateTime<Utc>,
+        horizon: Duration,
+    ) {
+        for fill in &mut self.fills {
+            if fill.market_price.is_none()
+                && &fill.symbol == symbol
+                && at >= fill.filled_at + horizon
+            {
+                fill.market_price = Some(price);
+                fill.market_at = Some(at);
+            }
+        }
+    }
+
+    /// Fills still waiting for a market price.
+    pub fn unresolved(&self) -> usize {
+        self.fills
+            .iter()
+            .filter(|fill| fill.market_price.is_none())
+            .count()
+    }
+
+    /// Quantity-weighted mean of [`ShadowDivergence::divergence_bps`] over
+    /// the resolved fills.
+    pub fn average_divergence_bps(&self) -> Option<Decimal> {
+        let (weighted, quantity) = self
+            .fills
+            .iter()
+            .filter_map(|fill| Some((fill.divergence_bps()?, fill.quantity)))
+            .fold(
+                (Decimal::ZERO, Decimal::ZERO),
+                |(sum, total), (bps, qty)| (sum + bps * qty, total + qty),
+            );
+        (!quantity.is_zero()).then(|| weighted / quantity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_report_resolves_fills_after_the_horizon() {
+        let symbol = Symbol::equity("AAPL");
+        let filled_at = Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap();
+        let mut report = ShadowReport::default();
+        let buy = Fill::new(
+            uuid::Uuid::new_v4(),
+            symbol.clone(),
+            Side::Buy,
+            dec!(10),
+            dec!(99),
+            dec!(0),
+            "shadow".into(),
+        );
+        report.record_fill(&buy, "shadow", filled_at);
+
+        let horizon = Duration::seconds(60);
+        report.observe(
+            &symbol,
+            dec!(120),
+            filled_at + Duration::seconds(30),
+            horizon,
+        );
+        assert_eq!(report.unresolved(), 1);
+        assert_eq!(report.average_divergence_bps(), None);
+
+        report.observe(
+            &symbol,
+            dec!(100),
+            filled_at + Duration::seconds(60),
+            horizon,
+        );
+        assert_eq!(report.unresolved(), 0);
+        assert_eq!(report.fills[0].divergence_bps(), Some(dec!(100)));
+        assert_eq!(report.average_divergence_bps(), Some(dec!(100)));
+    }
+}
@@ -0,0 +1,296 @@
+//! Queueing and pacing of order submissions, for strategies that place
+//! orders in bursts faster than the broker or the risk rate limit accept.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use gb_types::orders::{Order, OrderType, Side};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+
+/// What happens to an order placed while the queue is full.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+    /// Drop the new order.
+    #[default]
+    RejectNew,
+    /// Drop the oldest queued order to make room.
+    DropOldest,
+    /// Wait out the pacing interval and submit the oldest queued order to
+    /// make room, holding up the strategy instead of losing orders. The
+    /// in-flight limit does not hold these submissions back.
+    Block,
+}
+
+/// Settings for [`OrderThrottle`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThrottleConfig {
+    /// Queue the orders strategies place instead of submitting each one
+    /// straight away.
+    pub enabled: bool,
+    /// Working orders the engine may track before queued orders wait for
+    /// fills or cancels; `0` for no limit. Resting limit orders count.
+    pub max_in_flight: usize,
+    /// Least time between two submissions.
+    pub pacing_interval_ms: u64,
+    /// Net queued market orders of one strategy for the same symbol into a
+    /// single order.
+    pub net_by_symbol: bool,
+    /// Orders the queue holds before `overflow` applies.
+    pub max_queue: usize,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for ThrottleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_in_flight: 0,
+            pacing_interval_ms: 100,
+            net_by_symbol: false,
+            max_queue: 1_000,
+            overflow: OverflowPolicy::RejectNew,
+        }
+    }
+}
+
+/// An order that left the queue without being submitted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DroppedOrder {
+    pub order: Order,
+    pub reason: String,
+}
+
+/// Orders waiting to be submitted, in the order they were placed.
+#[derive(Debug)]
+pub struct OrderThrottle {
+    config: ThrottleConfig,
+    queue: VecDeque<Order>,
+    last_submission: Option<Instant>,
+}
+
+impl OrderThrottle {
+    pub fn new(config: ThrottleConfig) -> Self {
+        Self {
+            config,
+            queue: VecDeque::new(),
+            last_submission: None,
+        }
+    }
+
+    pub fn config(&self) -> &ThrottleConfig {
+        &self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.queue.len() >= self.config.max_queue
+    }
+
+    /// Queue `order`, netting it into a queued order when configured and
+    /// applying the overflow policy when the queue is full (except
+    /// [`OverflowPolicy::Block`], which the caller makes room for first).
+    /// Returns the orders that were dropped.
+    pub fn enqueue(&mut self, order: Order) -> Vec<DroppedOrder> {
+        if self.config.net_by_symbol {
+            if let Some(dropped) = self.net(order.clone()) {
+                return dropped;
+            }
+        }
+        if !self.is_full() {
+            self.queue.push_back(order);
+            return Vec::new();
+        }
+        match self.config.overflow {
+            OverflowPolicy::RejectNew => vec![DroppedOrder {
+                order,
+                reason: format!("order queue full ({} orders)", self.config.max_queue),
+            }],
+            OverflowPolicy::DropOldest | OverflowPolicy::Block => {
+                let oldest = self.queue.pop_front();
+                self.queue.push_back(order);
+                oldest
+                    .map(|order| DroppedOrder {
+                        order,
+                        reason: "dropped from the full order queue for a newer order".into(),
+                    })
+                    .into_iter()
+                    .collect()
+            }
+        }
+    }
+
+    /// Fold a market `order` into a queued market order of the same
+    /// strategy and symbol. Returns `None` when there is none to net with.
+    fn net(&mut self, order: Order) -> Option<Vec<DroppedOrder>> {
+        if order.order_type != OrderType::Market {
+            return None;
+        }
+        let index = self.queue.iter().position(|queued| {
+            queued.order_type == OrderType::Market
+                && queued.strategy_id == order.strategy_id
+                && queued.symbol == order.symbol
+        })?;
+        let signed = |side: Side, quantity: Decimal| match side {
+            Side::Buy => quantity,
+            Side::Sell => -quantity,
+        };
+        let queued = &mut self.queue[index];
+        let net = signed(queued.side, queued.remaining_quantity)
+            + signed(order.side, order.remaining_quantity);
+        let queued_id = queued.id;
+        if net.is_zero() {
+            let queued = self.queue.remove(index).expect("index is in the queue");
+            let reason = |other| format!("netted out against queued order {other}");
+            return Some(vec![
+                DroppedOrder {
+                    reason: reason(order.id),
+                    order: queued,
+                },
+                DroppedOrder {
+                    reason: reason(queued_id),
+                    order,
+                },
+            ]);
+        }
+
+        queued.side = if net > Decimal::ZERO {
+            Side::Buy
+        } else {
+            Side::Sell
+        };
+        queued.quantity = net.abs();
+        queued.remaining_quantity = net.abs();
+        for tag in &order.tags {
+            if !queued.tags.contains(tag) {
+                queued.tags.push(tag.clone());
+            }
+        }
+        Some(vec![DroppedOrder {
+            reason: format!("netted into queued order {queued_id}"),
+            order,
+        }])
+    }
+
+    fn pacing_interval(&self) -> Duration {
+        Duration::from_millis(self.config.pacing_interval_ms)
+    }
+
+    /// When the pacing interval next allows a submission.
+    pub fn paced_until(&self) -> Instant {
+        match self.last_submission {
+            Some(last) => last + self.pacing_interval(),
+            None => Instant::now(),
+        }
+    }
+
+    /// When the next queued order may be submitted with `in_flight` working
+    /// orders, or `None` while the queue is empty or the in-flight limit is
+    /// reached.
+    pub fn next_ready(&self, in_flight: usize) -> Option<Instant> {
+        let limited = self.config.max_in_flight > 0 && in_flight >= self.config.max_in_flight;
+        (!self.queue.is_empty() && !limited).then(|| self.paced_until())
+    }
+
+    /// Take the next order if it may be submitted at `now`.
+    pub fn pop_ready(&mut self, now: Instant, in_flight: usize) -> Option<Order> {
+        if self.next_ready(in_flight)? > now {
+            return None;
+        }
+        self.pop(now)
+    }
+
+    /// Take the next order regardless of the limits, recording a submission
+    /// at `now`.
+    pub fn pop(&mut self, now: Instant) -> Option<Order> {
+        let order = self.queue.pop_front()?;
+        self.last_submission = Some(now);
+        Some(order)
+    }
+
+    /// Empty the queue, returning the orders it held.
+    pub fn clear(&mut self) -> Vec<Order> {
+        self.queue.drain(..).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gb_types::market::Symbol;
+    use rust_decimal_macros::dec;
+
+    fn order(symbol: &str, side: Side, quantity: Decimal) -> Order {
+        Order::market_order(Symbol::equity(symbol), side, quantity, "s".into())
+    }
+
+    fn throttle(configure: impl FnOnce(&mut ThrottleConfig)) -> OrderThrottle {
+        let mut config = ThrottleConfig {
+            enabled: true,
+            max_queue: 2,
+            ..Default::default()
+        };
+        configure(&mut config);
+        OrderThrottle::new(config)
+    }
+
+    #[test]
+    fn full_queue_applies_the_overflow_policy() {
+        let mut reject = throttle(|_| {});
+        let mut drop_oldest = throttle(|config| config.overflow = OverflowPolicy::DropOldest);
+        let orders: Vec<_> = (1..=3)
+            .map(|quantity| order("AAPL", Side::Buy, Decimal::from(quantity)))
+            .collect();
+        for order in &orders[..2] {
+            assert!(reject.enqueue(order.clone()).is_empty());
+            assert!(drop_oldest.enqueue(order.clone()).is_empty());
+        }
+
+        assert_eq!(reject.enqueue(orders[2].clone())[0].order, orders[2]);
+        assert_eq!(drop_oldest.enqueue(orders[2].clone())[0].order, orders[0]);
+        let remaining: Vec<_> = drop_oldest.clear().into_iter().map(|o| o.id).collect();
+        assert_eq!(remaining, [orders[1].id, orders[2].id]);
+    }
+
+    #[test]
+    fn market_orders_for_a_symbol_net_into_the_first() {
+        let mut throttle = throttle(|config| config.net_by_symbol = true);
+        let first = order("AAPL", Side::Buy, dec!(10));
+        throttle.enqueue(first.clone());
+        throttle.enqueue(order("MSFT", Side::Buy, dec!(5)));
+
+        let dropped = throttle.enqueue(order("AAPL", Side::Sell, dec!(15)));
+        assert_eq!(dropped.len(), 1);
+        let netted = throttle.pop(Instant::now()).unwrap();
+        assert_eq!(netted.id, first.id);
+        assert_eq!((netted.side, netted.quantity), (Side::Sell, dec!(5)));
+
+        let dropped = throttle.enqueue(order("MSFT", Side::Sell, dec!(5)));
+        assert_eq!(dropped.len(), 2);
+        assert!(throttle.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn submissions_wait_for_the_pacing_interval_and_in_flight_limit() {
+        let mut throttle = throttle(|config| config.max_in_flight = 1);
+        throttle.enqueue(order("AAPL", Side::Buy, dec!(1)));
+        throttle.enqueue(order("AAPL", Side::Buy, dec!(2)));
+
+        let start = Instant::now();
+        assert!(throttle.pop_ready(start, 0).is_some());
+        assert!(throttle.pop_ready(start, 0).is_none());
+        let paced = start + Duration::from_millis(100);
+        assert_eq!(throttle.next_ready(0), Some(paced));
+        assert_eq!(throttle.next_ready(1), None);
+        assert!(throttle.pop_ready(paced, 0).is_some());
+    }
+}
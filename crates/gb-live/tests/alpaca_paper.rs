@@ -0,0 +1,62 @@
+//! Integration test against a real Alpaca paper account.
+//!
+//! Skipped unless `APCA_API_KEY_ID` and `APCA_API_SECRET_KEY` are set. The
+//! test only ever targets the paper environment and places a far-from-market
+//! limit order that is cancelled immediately.
+
+use gb_live::alpaca::{AlpacaBroker, AlpacaConfig, AlpacaEnvironment};
+use gb_live::broker::{Broker, ConnectionStatus};
+use gb_types::market::Symbol;
+use gb_types::orders::{Order, OrderStatus, Side, TimeInForce};
+use rust_decimal_macros::dec;
+
+#[tokio::test]
+async fn alpaca_paper_order_lifecycle() {
+    let config = match AlpacaConfig::from_env() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("skipping Alpaca paper integration test: {}", err);
+            return;
+        }
+    };
+    assert_eq!(
+        config.environment,
+        AlpacaEnvironment::Paper,
+        "integration test must not run against a live account"
+    );
+
+    let mut broker = AlpacaBroker::new(config).unwrap();
+    broker.connect().await.unwrap();
+    assert_eq!(broker.connection_status(), ConnectionStatus::Connected);
+
+    let balance = broker.get_account_balance().await.unwrap();
+    assert!(balance.equity >= rust_decimal::Decimal::ZERO);
+    broker.get_positions().await.unwrap();
+
+    let mut order = Order::limit_order(
+        Symbol::equity("SPY"),
+        Side::Buy,
+        dec!(1),
+        dec!(1.00),
+        "alpaca-integration".into(),
+    );
+    order.time_in_force = TimeInForce::Day;
+    let order_id = broker.submit_order(order).await.unwrap();
+
+    let open = broker.get_open_orders().await.unwrap();
+    assert!(open.iter().any(|o| o.id == order_id));
+
+    broker.cancel_order(order_id).await.unwrap();
+    let mut status = broker.get_order_status(order_id).await.unwrap();
+    for _ in 0..20 {
+        if status == OrderStatus::Canceled {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+        status = broker.get_order_status(order_id).await.unwrap();
+    }
+    assert_eq!(status, OrderStatus::Canceled);
+
+    broker.disconnect().await.unwrap();
+    assert_eq!(broker.connection_status(), ConnectionStatus::Disconnected);
+}
@@ -0,0 +1,56 @@
+//! Integration test against the Binance spot testnet.
+//!
+//! Skipped unless `BINANCE_API_KEY` and `BINANCE_API_SECRET` are set. The
+//! test only ever targets the testnet and places a far-from-market limit
+//! order that is cancelled immediately.
+
+use gb_live::binance::{BinanceBroker, BinanceConfig, BinanceEnvironment};
+use gb_live::broker::{Broker, ConnectionStatus};
+use gb_types::market::Symbol;
+use gb_types::orders::{Order, OrderStatus, Side};
+use rust_decimal_macros::dec;
+
+#[tokio::test]
+async fn binance_testnet_order_lifecycle() {
+    let config = match BinanceConfig::from_env() {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("skipping Binance testnet integration test: {}", err);
+            return;
+        }
+    };
+    assert_eq!(
+        config.environment,
+        BinanceEnvironment::Testnet,
+        "integration test must not run against mainnet"
+    );
+
+    let mut broker = BinanceBroker::new(config).unwrap();
+    broker.connect().await.unwrap();
+    assert_eq!(broker.connection_status(), ConnectionStatus::Connected);
+
+    let balance = broker.get_account_balance().await.unwrap();
+    assert!(balance.cash >= rust_decimal::Decimal::ZERO);
+    broker.get_positions().await.unwrap();
+
+    // 0.001 BTC at $10,000 clears the testnet's minimum notional while
+    // sitting far below the market.
+    let order = Order::limit_order(
+        Symbol::crypto("BTC-USD"),
+        Side::Buy,
+        dec!(0.001),
+        dec!(10000),
+        "binance-integration".into(),
+    );
+    let order_id = broker.submit_order(order).await.unwrap();
+
+    let open = broker.get_open_orders().await.unwrap();
+    assert!(open.iter().any(|o| o.id == order_id));
+
+    broker.cancel_order(order_id).await.unwrap();
+    let status = broker.get_order_status(order_id).await.unwrap();
+    assert_eq!(status, OrderStatus::Canceled);
+
+    broker.disconnect().await.unwrap();
+    assert_eq!(broker.connection_status(), ConnectionStatus::Disconnected);
+}
@@ -0,0 +1,77 @@
+//! Helpers shared by the `LiveEngine` integration tests.
+
+use gb_types::market::MarketEvent;
+use gb_types::orders::{Order, OrderEvent, Side};
+use gb_types::strategy::{
+    Strategy, StrategyAction, StrategyConfig, StrategyContext, StrategyMetrics,
+};
+use rust_decimal::Decimal;
+
+/// Places the scripted market order (if any) on each successive bar.
+pub struct ScriptedStrategy {
+    config: StrategyConfig,
+    script: Vec<Option<(Side, Decimal)>>,
+    bars_seen: usize,
+}
+
+impl Strategy for ScriptedStrategy {
+    fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+        self.config = config.clone();
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        event: &MarketEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        let step = self.script.get(self.bars_seen).copied().flatten();
+        self.bars_seen += 1;
+        Ok(step
+            .map(|(side, quantity)| {
+                StrategyAction::PlaceOrder(Order::market_order(
+                    event.symbol().clone(),
+                    side,
+                    quantity,
+                    self.config.strategy_id.clone(),
+                ))
+            })
+            .into_iter()
+            .collect())
+    }
+
+    fn on_order_event(
+        &mut self,
+        _event: &OrderEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_day_end(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+impl ScriptedStrategy {
+    /// Step `n` of `script` runs on the `n`th bar; `None` skips that bar.
+    pub fn new(config: StrategyConfig, script: Vec<Option<(Side, Decimal)>>) -> Self {
+        Self {
+            config,
+            script,
+            bars_seen: 0,
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Runs a two-day paper session with end-of-day flattening and checks the
+//! flattening orders and daily summaries it produces.
+
+use chrono::{DateTime, TimeZone, Utc};
+use gb_live::engine::{EndOfDayConfig, LiveEngine, LiveEngineConfig, LiveEngineEvent, TradingMode};
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_live::risk::RiskConfig;
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::Side;
+use gb_types::portfolio::RiskLimits;
+use gb_types::strategy::StrategyConfig;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
+
+mod common;
+
+use common::ScriptedStrategy;
+
+fn symbol() -> Symbol {
+    Symbol::equity("AAPL")
+}
+
+fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap()
+}
+
+fn bar(timestamp: DateTime<Utc>, close: Decimal) -> MarketEvent {
+    MarketEvent::Bar(Bar {
+        symbol: symbol(),
+        timestamp,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: dec!(1_000_000),
+        resolution: Resolution::Minute,
+        provenance: Default::default(),
+    })
+}
+
+#[tokio::test]
+async fn two_day_session_flattens_before_each_close_and_summarizes() {
+    let broker = PaperBroker::new(PaperBrokerConfig::default());
+    let mut strategy_config = StrategyConfig::new("intraday".into(), "Intraday".into());
+    strategy_config.add_symbol(symbol());
+    let strategy = ScriptedStrategy::new(
+        strategy_config.clone(),
+        vec![
+            Some((Side::Buy, dec!(10))),
+            Some((Side::Buy, dec!(5))),
+            // Inside the flattening window: still closed out before the close.
+            Some((Side::Buy, dec!(2))),
+            Some((Side::Buy, dec!(3))),
+            None,
+            None,
+        ],
+    );
+    let config = LiveEngineConfig {
+        mode: TradingMode::Sandbox,
+        strategy_config,
+        risk_config: RiskConfig {
+            limits: RiskLimits {
+                position_concentration_limit: dec!(1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        initial_capital: dec!(100_000),
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: Default::default(),
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: Default::default(),
+        end_of_day: EndOfDayConfig {
+            flatten_eod: true,
+            flatten_minutes_before_close: 10,
+        },
+        shadow: Default::default(),
+        event_ordering: Default::default(),
+    };
+    let mut engine = LiveEngine::new(broker, strategy, config);
+    let mut events = engine.subscribe();
+
+    // The default calendar closes at 21:00 UTC, so flattening starts at 20:50.
+    let bars = vec![
+        bar(at(2, 15, 0), dec!(100)),
+        bar(at(2, 20, 45), dec!(90)),
+        bar(at(2, 20, 55), dec!(110)),
+        bar(at(3, 15, 0), dec!(100)),
+        bar(at(3, 20, 52), dec!(105)),
+        // Opens the third session, ending the second.
+        bar(at(4, 15, 0), dec!(105)),
+    ];
+    let fill_stream = engine.broker_mut().fill_stream();
+    engine
+        .run(
+            futures_util::stream::iter(bars),
+            fill_stream,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    let received: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+    let flattened: Vec<_> = received
+        .iter()
+        .filter_map(|event| match event {
+            LiveEngineEvent::PositionsFlattened {
+                session_close,
+                orders,
+                ..
+            } => Some((*session_close, *orders)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(flattened, vec![(at(2, 21, 0), 1), (at(3, 21, 0), 1)]);
+
+    let sells: Vec<_> = received
+        .iter()
+        .filter_map(|event| match event {
+            LiveEngineEvent::OrderSubmitted { side, quantity, .. } if side == "Sell" => {
+                Some(*quantity)
+            }
+            _ => None,
+        })
+        .collect();
+    assert_eq!(sells, vec![dec!(17), dec!(3)]);
+
+    let summaries: Vec<_> = received
+        .iter()
+        .filter_map(|event| match event {
+            LiveEngineEvent::DailySummary {
+                session_close,
+                realized_pnl,
+                unrealized_pnl,
+                fills,
+                commissions,
+                max_drawdown,
+                ..
+            } => Some((
+                *session_close,
+                *realized_pnl,
+                *unrealized_pnl,
+                *fills,
+                *commissions,
+                *max_drawdown,
+            )),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(summaries.len(), 2);
+
+    let (close, realized, unrealized, fills, commissions, drawdown) = summaries[0];
+    assert_eq!(close, at(2, 21, 0));
+    assert!(realized > Decimal::ZERO);
+    assert_eq!(unrealized, Decimal::ZERO);
+    assert_eq!(fills, 4);
+    assert_eq!(commissions, dec!(0.34));
+    // Marked at 90 after buying 10 near 100, the session dipped below its
+    // starting equity.
+    assert!(drawdown > Decimal::ZERO);
+
+    let (close, realized, unrealized, fills, commissions, _) = summaries[1];
+    assert_eq!(close, at(3, 21, 0));
+    assert!(realized > Decimal::ZERO);
+    assert_eq!(unrealized, Decimal::ZERO);
+    assert_eq!(fills, 2);
+    assert_eq!(commissions, dec!(0.06));
+
+    assert!(engine.context().get_position(&symbol()).is_none());
+    assert_eq!(engine.context().portfolio.daily_returns.len(), 2);
+    let recorded = &engine.strategies()[0].context().portfolio.daily_returns;
+    assert_eq!(recorded.len(), 2);
+
+    // Each summary carries the record appended to the strategy's history.
+    let forwarded: Vec<_> = received
+        .iter()
+        .filter_map(|event| match event {
+            LiveEngineEvent::DailySummary { daily_return, .. } => daily_return.clone(),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(&forwarded, recorded);
+    assert_eq!(forwarded[1].date, at(3, 21, 0));
+    assert_eq!(
+        forwarded[1].cumulative_return,
+        (Decimal::ONE + forwarded[0].daily_return) * (Decimal::ONE + forwarded[1].daily_return)
+            - Decimal::ONE
+    );
+}
@@ -0,0 +1,127 @@
+//! Journals a scripted paper session and rebuilds its cash and realized P&L
+//! from the journal alone.
+
+use chrono::{Duration, TimeZone, Utc};
+use gb_live::engine::{LiveEngine, LiveEngineConfig, TradingMode};
+use gb_live::journal::{JournalConfig, JournalRecord, OrderFlow, OrderJournal};
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_live::risk::RiskConfig;
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::Side;
+use gb_types::portfolio::RiskLimits;
+use gb_types::strategy::StrategyConfig;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
+
+mod common;
+
+use common::ScriptedStrategy;
+
+fn symbol() -> Symbol {
+    Symbol::equity("AAPL")
+}
+
+#[tokio::test]
+async fn journal_replay_matches_paper_broker_cash() {
+    let dir = tempfile::tempdir().unwrap();
+    let initial_cash = dec!(100_000);
+    let broker = PaperBroker::new(PaperBrokerConfig {
+        initial_cash,
+        ..Default::default()
+    });
+    let mut strategy_config = StrategyConfig::new("journaled".into(), "Journaled".into());
+    strategy_config.add_symbol(symbol());
+    let strategy = ScriptedStrategy::new(
+        strategy_config.clone(),
+        vec![
+            Some((Side::Buy, dec!(10))),
+            Some((Side::Buy, dec!(5))),
+            Some((Side::Sell, dec!(8))),
+            // Far beyond the account: rejected by risk or the broker.
+            Some((Side::Buy, dec!(10_000))),
+        ],
+    );
+    let config = LiveEngineConfig {
+        mode: TradingMode::Sandbox,
+        strategy_config,
+        risk_config: RiskConfig {
+            limits: RiskLimits {
+                position_concentration_limit: dec!(1.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        initial_capital: initial_cash,
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: Default::default(),
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: JournalConfig {
+            dir: Some(dir.path().to_path_buf()),
+            fsync: true,
+        },
+        end_of_day: Default::default(),
+        shadow: Default::default(),
+        event_ordering: Default::default(),
+    };
+    let mut engine = LiveEngine::new(broker, strategy, config);
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap();
+    let bars: Vec<_> = [dec!(100), dec!(110), dec!(120), dec!(130)]
+        .into_iter()
+        .enumerate()
+        .map(|(minute, close)| {
+            MarketEvent::Bar(Bar {
+                symbol: symbol(),
+                timestamp: start + Duration::minutes(minute as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: dec!(1_000_000),
+                resolution: Resolution::Minute,
+                provenance: Default::default(),
+            })
+        })
+        .collect();
+    let fill_stream = engine.broker_mut().fill_stream();
+    engine
+        .run(
+            futures_util::stream::iter(bars),
+            fill_stream,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    let mut entries = Vec::new();
+    for day in OrderJournal::days(dir.path()).unwrap() {
+        entries.extend(OrderJournal::read_day(dir.path(), day).unwrap());
+    }
+    assert!(entries
+        .windows(2)
+        .all(|pair| pair[1].seq == pair[0].seq + 1));
+    assert_eq!(
+        entries.last().unwrap().seq,
+        engine.journal().unwrap().last_seq()
+    );
+    assert!(entries
+        .iter()
+        .any(|entry| matches!(entry.record, JournalRecord::Event(_))));
+
+    let flow = OrderFlow::read_dir(dir.path()).unwrap();
+    assert_eq!(flow.orders.len(), 3);
+    assert_eq!(flow.fills.len(), 3);
+    assert_eq!(flow.rejected.len(), 1);
+    assert_eq!(initial_cash + flow.cash_delta(), engine.broker().cash());
+    assert_eq!(
+        flow.realized_pnl(),
+        engine.context().portfolio.total_realized_pnl
+    );
+    assert!(flow.realized_pnl() > Decimal::ZERO);
+    assert_eq!(flow.positions()[&symbol()].quantity, dec!(7));
+}
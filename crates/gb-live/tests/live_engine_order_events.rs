@@ -0,0 +1,252 @@
+//! Checks the order lifecycle a strategy sees when a volume-limited
+//! `PaperBroker` fills its orders in pieces.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{TimeZone, Utc};
+use futures_util::{FutureExt, Stream, StreamExt};
+use gb_live::broker::Broker;
+use gb_live::engine::{LiveEngine, LiveEngineConfig, TradingMode};
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_live::risk::RiskConfig;
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::{Fill, Order, OrderEvent, OrderStatus, Side, TimeInForce};
+use gb_types::strategy::{
+    Strategy, StrategyAction, StrategyConfig, StrategyContext, StrategyMetrics,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Buys 25 shares on the first bar and records every order event it is
+/// handed, canceling the order after its first partial fill when
+/// `cancel_after_partial` is set.
+struct RecordingStrategy {
+    config: StrategyConfig,
+    time_in_force: TimeInForce,
+    cancel_after_partial: bool,
+    bought: bool,
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+impl Strategy for RecordingStrategy {
+    fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+        self.config = config.clone();
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        event: &MarketEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        if self.bought {
+            return Ok(vec![]);
+        }
+        self.bought = true;
+        let mut order = Order::market_order(
+            event.symbol().clone(),
+            Side::Buy,
+            dec!(25),
+            self.config.strategy_id.clone(),
+        );
+        order.time_in_force = self.time_in_force;
+        Ok(vec![StrategyAction::PlaceOrder(order)])
+    }
+
+    fn on_order_event(
+        &mut self,
+        event: &OrderEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        let entry = match event {
+            OrderEvent::OrderSubmitted(_) => "submitted".to_string(),
+            OrderEvent::OrderAccepted { .. } => "accepted".to_string(),
+            OrderEvent::OrderPartiallyFilled {
+                fill,
+                remaining_quantity,
+                ..
+            } => format!(
+                "partially_filled {} of {}",
+                fill.quantity, remaining_quantity
+            ),
+            OrderEvent::OrderFilled { fill, .. } => format!("filled {}", fill.quantity),
+            OrderEvent::OrderCanceled { .. } => "canceled".to_string(),
+            OrderEvent::OrderRejected { .. } => "rejected".to_string(),
+            OrderEvent::OrderExpired { .. } => "expired".to_string(),
+        };
+        self.events.lock().unwrap().push(entry);
+
+        if let OrderEvent::OrderPartiallyFilled { order_id, .. } = event {
+            if self.cancel_after_partial {
+                return Ok(vec![StrategyAction::CancelOrder {
+                    order_id: *order_id,
+                }]);
+            }
+        }
+        Ok(vec![])
+    }
+
+    fn on_day_end(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+fn symbol() -> Symbol {
+    Symbol::equity("AAPL")
+}
+
+/// A 100-share bar, of which the broker fills at most 10 per event.
+fn bar(hour: u32) -> MarketEvent {
+    MarketEvent::Bar(Bar {
+        symbol: symbol(),
+        timestamp: Utc.with_ymd_and_hms(2024, 1, 2, hour, 0, 0).unwrap(),
+        open: dec!(100),
+        high: dec!(100),
+        low: dec!(100),
+        close: dec!(100),
+        volume: dec!(100),
+        resolution: Resolution::Hour,
+        provenance: Default::default(),
+    })
+}
+
+fn engine(
+    time_in_force: TimeInForce,
+    cancel_after_partial: bool,
+) -> (
+    LiveEngine<PaperBroker, RecordingStrategy>,
+    Arc<Mutex<Vec<String>>>,
+) {
+    let broker = PaperBroker::new(PaperBrokerConfig {
+        initial_cash: dec!(100_000),
+        commission_per_share: Decimal::ZERO,
+        slippage_bps: Decimal::ZERO,
+        max_participation_rate: Some(dec!(0.1)),
+        ..Default::default()
+    });
+    let mut strategy_config = StrategyConfig::new("recorder".into(), "Recorder".into());
+    strategy_config.add_symbol(symbol());
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let strategy = RecordingStrategy {
+        config: strategy_config.clone(),
+        time_in_force,
+        cancel_after_partial,
+        bought: false,
+        events: events.clone(),
+    };
+    let config = LiveEngineConfig {
+        mode: TradingMode::Sandbox,
+        strategy_config,
+        risk_config: RiskConfig::default(),
+        initial_capital: dec!(100_000),
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: Default::default(),
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: Default::default(),
+        end_of_day: Default::default(),
+        shadow: Default::default(),
+        event_ordering: Default::default(),
+    };
+    (LiveEngine::new(broker, strategy, config), events)
+}
+
+fn delivered(fills: &mut (impl Stream<Item = Fill> + Unpin)) -> Vec<Fill> {
+    let mut delivered = Vec::new();
+    while let Some(Some(fill)) = fills.next().now_or_never() {
+        delivered.push(fill);
+    }
+    delivered
+}
+
+#[tokio::test]
+async fn a_partial_fill_then_strategy_cancel_reaches_the_strategy_in_order() {
+    let (mut engine, events) = engine(TimeInForce::GTC, true);
+    let mut fills = engine.broker_mut().fill_stream();
+    engine.start().await.unwrap();
+
+    engine.on_market_event(bar(15)).await.unwrap();
+    let first = delivered(&mut fills);
+    assert_eq!(first.len(), 1);
+    let order_id = first[0].order_id;
+    for fill in first {
+        engine.on_fill(fill).await.unwrap();
+    }
+    // Later bars find nothing left to fill.
+    engine.on_market_event(bar(16)).await.unwrap();
+    assert!(delivered(&mut fills).is_empty());
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["accepted", "partially_filled 10 of 15", "canceled"]
+    );
+    assert!(engine.session_state().pending_orders.is_empty());
+    assert_eq!(engine.risk_manager().open_order_count(&symbol()), 0);
+    assert_eq!(
+        engine.broker().get_order_status(order_id).await.unwrap(),
+        OrderStatus::Canceled
+    );
+}
+
+#[tokio::test]
+async fn an_ioc_cancel_waits_for_the_fill_it_overtook() {
+    let (mut engine, events) = engine(TimeInForce::IOC, false);
+    let mut fills = engine.broker_mut().fill_stream();
+    engine.start().await.unwrap();
+
+    // The broker fills 10 and cancels the rest before the fill is handed to
+    // the engine.
+    engine.on_market_event(bar(15)).await.unwrap();
+    assert_eq!(*events.lock().unwrap(), vec!["accepted"]);
+    assert_eq!(engine.session_state().pending_orders.len(), 1);
+
+    for fill in delivered(&mut fills) {
+        engine.on_fill(fill).await.unwrap();
+    }
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec!["accepted", "partially_filled 10 of 15", "canceled"]
+    );
+    assert!(engine.session_state().pending_orders.is_empty());
+}
+
+#[tokio::test]
+async fn multiple_partials_close_the_order_on_the_last_fill() {
+    let (mut engine, events) = engine(TimeInForce::GTC, false);
+    let mut fills = engine.broker_mut().fill_stream();
+    engine.start().await.unwrap();
+
+    for hour in 15..=17 {
+        engine.on_market_event(bar(hour)).await.unwrap();
+        for fill in delivered(&mut fills) {
+            engine.on_fill(fill).await.unwrap();
+        }
+    }
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            "accepted",
+            "partially_filled 10 of 15",
+            "partially_filled 10 of 5",
+            "filled 5"
+        ]
+    );
+    assert!(engine.session_state().pending_orders.is_empty());
+    assert_eq!(engine.risk_manager().open_order_count(&symbol()), 0);
+}
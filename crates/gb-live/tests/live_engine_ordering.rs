@@ -0,0 +1,129 @@
+//! Feeds a burst of same-timestamp bars to `LiveEngine::on_market_events`
+//! and checks what each decision sees under both event-ordering policies.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{TimeZone, Utc};
+use gb_live::engine::{LiveEngine, LiveEngineConfig, TradingMode};
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_types::backtest::EventOrdering;
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::{Order, OrderEvent, Side};
+use gb_types::strategy::{
+    Strategy, StrategyAction, StrategyConfig, StrategyContext, StrategyMetrics,
+};
+use rust_decimal_macros::dec;
+
+/// Rests a far-away limit buy on every bar, noting how many working orders
+/// it could see when deciding.
+struct OrderCounter {
+    config: StrategyConfig,
+    seen: Arc<Mutex<Vec<usize>>>,
+}
+
+impl Strategy for OrderCounter {
+    fn initialize(&mut self, _config: &StrategyConfig) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        event: &MarketEvent,
+        context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        self.seen.lock().unwrap().push(context.pending_orders.len());
+        Ok(vec![StrategyAction::PlaceOrder(Order::limit_order(
+            event.symbol().clone(),
+            Side::Buy,
+            dec!(1),
+            dec!(1),
+            self.config.strategy_id.clone(),
+        ))])
+    }
+
+    fn on_order_event(
+        &mut self,
+        _event: &OrderEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_day_end(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+/// Working orders the strategy saw on each bar of a two-symbol burst.
+async fn orders_seen(event_ordering: EventOrdering) -> Vec<usize> {
+    let symbols = [Symbol::equity("AAA"), Symbol::equity("BBB")];
+    let mut strategy_config = StrategyConfig::new("counter".into(), "Counter".into());
+    for symbol in &symbols {
+        strategy_config.add_symbol(symbol.clone());
+    }
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let strategy = OrderCounter {
+        config: strategy_config.clone(),
+        seen: seen.clone(),
+    };
+    let config = LiveEngineConfig {
+        mode: TradingMode::Sandbox,
+        strategy_config,
+        risk_config: Default::default(),
+        initial_capital: dec!(100_000),
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: Default::default(),
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: Default::default(),
+        end_of_day: Default::default(),
+        shadow: Default::default(),
+        event_ordering,
+    };
+    let broker = PaperBroker::new(PaperBrokerConfig::default());
+    let mut engine = LiveEngine::new(broker, strategy, config);
+    engine.start().await.unwrap();
+
+    let timestamp = Utc.with_ymd_and_hms(2024, 1, 2, 21, 0, 0).unwrap();
+    let burst = symbols
+        .iter()
+        .map(|symbol| {
+            MarketEvent::Bar(Bar::new(
+                symbol.clone(),
+                timestamp,
+                dec!(100),
+                dec!(100),
+                dec!(100),
+                dec!(100),
+                dec!(10_000),
+                Resolution::Day,
+            ))
+        })
+        .collect();
+    engine.on_market_events(burst).await.unwrap();
+
+    assert_eq!(engine.context().pending_orders.len(), 2);
+    let seen = seen.lock().unwrap().clone();
+    seen
+}
+
+#[tokio::test]
+async fn snapshot_bursts_hide_each_decisions_orders_from_the_others() {
+    assert_eq!(orders_seen(EventOrdering::Sequential).await, [0, 1]);
+    assert_eq!(orders_seen(EventOrdering::Snapshot).await, [0, 0]);
+}
@@ -0,0 +1,492 @@
+//! Drives `LiveEngine::run` with a `PaperBroker` and a synthetic bar stream
+//! spanning several trading days.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use chrono::{DateTime, TimeZone, Utc};
+use futures_util::StreamExt;
+use gb_live::broker::{Broker, RejectionReason};
+use gb_live::calendar::TradingCalendar;
+use gb_live::engine::{
+    HealthConfig, LiveEngine, LiveEngineConfig, LiveEngineEvent, OrderTimeout, TradingMode,
+};
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_live::risk::RiskConfig;
+use gb_types::market::{AssetClass, Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::{Order, OrderEvent, OrderType, Side};
+use gb_types::strategy::{
+    Strategy, StrategyAction, StrategyConfig, StrategyContext, StrategyMetrics,
+};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
+
+#[derive(Default, Clone)]
+struct Counters {
+    fills: Arc<AtomicUsize>,
+    day_ends: Arc<AtomicUsize>,
+    expiries: Arc<AtomicUsize>,
+    rejections: Arc<AtomicUsize>,
+}
+
+impl Counters {
+    fn get(counter: &AtomicUsize) -> usize {
+        counter.load(Ordering::SeqCst)
+    }
+}
+
+/// Buys once on the first bar (at market, or with a limit when
+/// `limit_price` is set) and counts fills, expiries, rejections, and
+/// day-ends.
+struct CountingStrategy {
+    config: StrategyConfig,
+    bought: bool,
+    limit_price: Option<Decimal>,
+    counters: Counters,
+}
+
+impl Strategy for CountingStrategy {
+    fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+        self.config = config.clone();
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        event: &MarketEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        if self.bought {
+            return Ok(vec![]);
+        }
+        self.bought = true;
+        let symbol = event.symbol().clone();
+        let strategy_id = self.config.strategy_id.clone();
+        let order = match self.limit_price {
+            Some(price) => Order::limit_order(symbol, Side::Buy, dec!(10), price, strategy_id),
+            None => Order::market_order(symbol, Side::Buy, dec!(10), strategy_id),
+        };
+        Ok(vec![StrategyAction::PlaceOrder(order)])
+    }
+
+    fn on_order_event(
+        &mut self,
+        event: &OrderEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        let counter = match event {
+            OrderEvent::OrderFilled { .. } => &self.counters.fills,
+            OrderEvent::OrderExpired { .. } => &self.counters.expiries,
+            OrderEvent::OrderRejected { .. } => &self.counters.rejections,
+            _ => return Ok(vec![]),
+        };
+        counter.fetch_add(1, Ordering::SeqCst);
+        Ok(vec![])
+    }
+
+    fn on_day_end(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        self.counters.day_ends.fetch_add(1, Ordering::SeqCst);
+        Ok(vec![])
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+fn symbol() -> Symbol {
+    Symbol::equity("AAPL")
+}
+
+fn bar(timestamp: DateTime<Utc>, close: Decimal) -> MarketEvent {
+    MarketEvent::Bar(Bar {
+        symbol: symbol(),
+        timestamp,
+        open: close,
+        high: close,
+        low: close,
+        close,
+        volume: dec!(10_000),
+        resolution: Resolution::Hour,
+        provenance: Default::default(),
+    })
+}
+
+fn engine(
+    counters: Counters,
+    limit_price: Option<Decimal>,
+    order_timeout: Option<OrderTimeout>,
+) -> LiveEngine<PaperBroker, CountingStrategy> {
+    engine_with(counters, limit_price, order_timeout, |_| {})
+}
+
+fn engine_with(
+    counters: Counters,
+    limit_price: Option<Decimal>,
+    order_timeout: Option<OrderTimeout>,
+    configure: impl FnOnce(&mut LiveEngineConfig),
+) -> LiveEngine<PaperBroker, CountingStrategy> {
+    let broker = PaperBroker::new(PaperBrokerConfig {
+        initial_cash: dec!(100_000),
+        ..Default::default()
+    });
+    engine_on(broker, counters, limit_price, order_timeout, configure)
+}
+
+fn engine_on(
+    broker: PaperBroker,
+    counters: Counters,
+    limit_price: Option<Decimal>,
+    order_timeout: Option<OrderTimeout>,
+    configure: impl FnOnce(&mut LiveEngineConfig),
+) -> LiveEngine<PaperBroker, CountingStrategy> {
+    let mut strategy_config = StrategyConfig::new("run_test".into(), "Run Test".into());
+    strategy_config.add_symbol(symbol());
+    if let Some(timeout) = order_timeout {
+        strategy_config.set_parameter(OrderTimeout::PARAMETER, timeout);
+    }
+    let strategy = CountingStrategy {
+        config: strategy_config.clone(),
+        bought: false,
+        limit_price,
+        counters,
+    };
+    let mut config = LiveEngineConfig {
+        mode: TradingMode::Sandbox,
+        strategy_config,
+        risk_config: RiskConfig::default(),
+        initial_capital: dec!(100_000),
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: Default::default(),
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: Default::default(),
+        end_of_day: Default::default(),
+        shadow: Default::default(),
+        event_ordering: Default::default(),
+    };
+    configure(&mut config);
+    LiveEngine::new(broker, strategy, config)
+}
+
+#[tokio::test]
+async fn run_replays_several_days_through_paper_broker() {
+    let counters = Counters::default();
+    let mut engine = engine(counters.clone(), None, None);
+    let mut events = engine.subscribe();
+
+    // Two bars a day on Tue 2 – Thu 4 January 2024; the default calendar
+    // closes each session at 21:00 UTC.
+    let bars: Vec<_> = (2..=4)
+        .flat_map(|day| {
+            [15, 20].map(|hour| {
+                let timestamp = Utc.with_ymd_and_hms(2024, 1, day, hour, 0, 0).unwrap();
+                bar(timestamp, Decimal::from(100 + day))
+            })
+        })
+        .collect();
+
+    let fill_stream = engine.broker_mut().fill_stream();
+    engine
+        .run(
+            futures_util::stream::iter(bars),
+            fill_stream,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert!(!engine.is_running());
+    // Sessions closing on the 2nd and 3rd were crossed by later bars.
+    assert_eq!(Counters::get(&counters.day_ends), 2);
+    assert_eq!(Counters::get(&counters.fills), 1);
+    assert_eq!(
+        engine
+            .context()
+            .portfolio
+            .get_position(&symbol())
+            .unwrap()
+            .quantity,
+        dec!(10)
+    );
+    assert_eq!(engine.broker().get_latest_price(&symbol()), Some(dec!(104)));
+
+    let mut received = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        received.push(event);
+    }
+    assert!(matches!(
+        received.first(),
+        Some(LiveEngineEvent::Started { .. })
+    ));
+    assert!(received
+        .iter()
+        .any(|e| matches!(e, LiveEngineEvent::OrderFilled { .. })));
+    assert!(matches!(
+        received.last(),
+        Some(LiveEngineEvent::Stopped { reason, .. }) if reason == "market data stream ended"
+    ));
+}
+
+#[tokio::test]
+async fn broker_rejections_reach_the_strategy_and_clear_the_order() {
+    let counters = Counters::default();
+    // The broker holds far less cash than the engine believes it has.
+    let broker = PaperBroker::new(PaperBrokerConfig {
+        initial_cash: dec!(500),
+        ..Default::default()
+    });
+    let mut engine = engine_on(broker, counters.clone(), None, None, |_| {});
+    let mut events = engine.subscribe();
+    engine.start().await.unwrap();
+
+    let timestamp = Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap();
+    engine
+        .on_market_event(bar(timestamp, dec!(100)))
+        .await
+        .unwrap();
+
+    assert_eq!(Counters::get(&counters.rejections), 1);
+    assert_eq!(Counters::get(&counters.fills), 0);
+    assert!(engine.session_state().pending_orders.is_empty());
+    assert_eq!(engine.risk_manager().open_order_count(&symbol()), 0);
+
+    let mut received = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        received.push(event);
+    }
+    let submitted = received
+        .iter()
+        .position(|e| matches!(e, LiveEngineEvent::OrderSubmitted { .. }))
+        .expect("the broker accepted the order");
+    let rejected = received
+        .iter()
+        .position(|e| {
+            matches!(
+                e,
+                LiveEngineEvent::OrderRejectedByBroker {
+                    reason: Some(RejectionReason::InsufficientFunds { available, .. }),
+                    ..
+                } if *available == dec!(500)
+            )
+        })
+        .expect("the rejection is reported");
+    assert!(submitted < rejected);
+}
+
+#[tokio::test]
+async fn execution_report_measures_fills_from_the_submit_time_price() {
+    let mut engine = engine(Counters::default(), None, None);
+    let mut events = engine.subscribe();
+    let bars: Vec<_> = [15, 20]
+        .map(|hour| {
+            let timestamp = Utc.with_ymd_and_hms(2024, 1, 2, hour, 0, 0).unwrap();
+            bar(timestamp, dec!(102) + Decimal::from(hour))
+        })
+        .to_vec();
+
+    let fill_stream = engine.broker_mut().fill_stream();
+    engine
+        .run(
+            futures_util::stream::iter(bars),
+            fill_stream,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    let mut received = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        received.push(event);
+    }
+    let fill_price = received
+        .iter()
+        .find_map(|event| match event {
+            LiveEngineEvent::OrderFilled { price, .. } => Some(*price),
+            _ => None,
+        })
+        .expect("the buy fills");
+    // Decided on the first bar, closing at 117; the paper broker's spread
+    // and slippage plus a cent a share of commission are the whole cost.
+    let totals = &engine.execution_report().totals;
+    assert_eq!(totals.fills, 1);
+    assert_eq!(totals.commission, dec!(0.1));
+    assert_eq!(totals.slippage, (fill_price - dec!(117)) * dec!(10));
+    assert_eq!(totals.latency_drift, Decimal::ZERO);
+    assert_eq!(
+        totals.total(),
+        (fill_price - dec!(117)) * dec!(10) + dec!(0.1)
+    );
+    assert!(totals.total_bps() > Decimal::ZERO);
+
+    let emitted = received.iter().find_map(|event| match event {
+        LiveEngineEvent::ExecutionReport { report } => Some(report),
+        _ => None,
+    });
+    assert_eq!(emitted, Some(engine.execution_report()));
+}
+
+#[tokio::test]
+async fn run_stops_when_shutdown_is_cancelled() {
+    let mut engine = engine(Counters::default(), None, None);
+    let shutdown = CancellationToken::new();
+    let trigger = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        trigger.cancel();
+    });
+
+    let fill_stream = engine.broker_mut().fill_stream();
+    engine
+        .run(futures_util::stream::pending(), fill_stream, shutdown)
+        .await
+        .unwrap();
+
+    assert!(!engine.is_running());
+    let events = engine.drain_events();
+    assert!(matches!(
+        events.last(),
+        Some(LiveEngineEvent::Stopped { reason, .. }) if reason == "shutdown requested"
+    ));
+}
+
+/// Feeds one bar at 100, then keeps the stream open until `shutdown_after`.
+async fn run_with_resting_limit(
+    engine: &mut LiveEngine<PaperBroker, CountingStrategy>,
+    shutdown_after: std::time::Duration,
+) {
+    let shutdown = CancellationToken::new();
+    let trigger = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(shutdown_after).await;
+        trigger.cancel();
+    });
+
+    let first_bar = bar(
+        Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap(),
+        dec!(100),
+    );
+    let data = futures_util::stream::iter([first_bar]).chain(futures_util::stream::pending());
+    let fill_stream = engine.broker_mut().fill_stream();
+    engine.run(data, fill_stream, shutdown).await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn run_cancels_orders_that_outlive_their_timeout() {
+    let counters = Counters::default();
+    let timeout = OrderTimeout::Cancel { after_secs: 5 };
+    let mut engine = engine(counters.clone(), Some(dec!(90)), Some(timeout));
+    let mut events = engine.subscribe();
+
+    run_with_resting_limit(&mut engine, std::time::Duration::from_secs(6)).await;
+
+    assert_eq!(Counters::get(&counters.expiries), 1);
+    assert_eq!(Counters::get(&counters.fills), 0);
+    assert!(engine.broker().get_open_orders().await.unwrap().is_empty());
+    let mut expired = 0;
+    while let Ok(event) = events.try_recv() {
+        if let LiveEngineEvent::OrderExpired { reason, .. } = event {
+            assert_eq!(reason, "order timed out after 5s");
+            expired += 1;
+        }
+    }
+    assert_eq!(expired, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn run_replaces_timed_out_limits_closer_to_the_market() {
+    let counters = Counters::default();
+    let timeout = OrderTimeout::Replace {
+        after_secs: 5,
+        reprice_bps: 50,
+    };
+    let mut engine = engine(counters.clone(), Some(dec!(90)), Some(timeout));
+    let mut events = engine.subscribe();
+
+    run_with_resting_limit(&mut engine, std::time::Duration::from_secs(7)).await;
+
+    assert_eq!(Counters::get(&counters.expiries), 1);
+    let replacement = std::iter::from_fn(|| events.try_recv().ok())
+        .find_map(|event| match event {
+            LiveEngineEvent::OrderReplaced {
+                replacement_id,
+                limit_price,
+                ..
+            } => Some((replacement_id, limit_price)),
+            _ => None,
+        })
+        .expect("expected an OrderReplaced event");
+    assert_eq!(replacement.1, dec!(90.45));
+
+    let open = engine.broker().get_open_orders().await.unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].id, replacement.0);
+    assert_eq!(open[0].order_type, OrderType::Limit { price: dec!(90.45) });
+}
+
+#[tokio::test(start_paused = true)]
+async fn run_reports_stale_data_until_events_resume() {
+    let mut engine = engine_with(Counters::default(), None, None, |config| {
+        // Always in session, so the watchdog is independent of the wall clock.
+        config.calendar = TradingCalendar::for_asset_class(AssetClass::Crypto);
+        config.health = HealthConfig {
+            stale_after_secs: 5,
+        };
+    });
+    let mut events = engine.subscribe();
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap();
+    let late_bar = async move {
+        tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+        bar(start + chrono::Duration::seconds(10), dec!(101))
+    };
+    let data = futures_util::stream::iter([bar(start, dec!(100))])
+        .chain(futures_util::stream::once(Box::pin(late_bar)))
+        .chain(futures_util::stream::pending());
+    let shutdown = CancellationToken::new();
+    let trigger = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(12)).await;
+        trigger.cancel();
+    });
+    let fill_stream = engine.broker_mut().fill_stream();
+    engine.run(data, fill_stream, shutdown).await.unwrap();
+
+    let health_events: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+        .filter(|event| {
+            matches!(
+                event,
+                LiveEngineEvent::DataStale { .. } | LiveEngineEvent::DataResumed { .. }
+            )
+        })
+        .collect();
+    assert_eq!(health_events.len(), 2, "{health_events:?}");
+    assert!(matches!(
+        &health_events[0],
+        LiveEngineEvent::DataStale { symbol: s, seconds } if *s == symbol().to_string() && *seconds >= 5
+    ));
+    assert!(matches!(
+        &health_events[1],
+        LiveEngineEvent::DataResumed { symbol: s } if *s == symbol().to_string()
+    ));
+
+    let health = engine.health();
+    assert!(health.stale_symbols.is_empty());
+    assert_eq!(health.events_per_minute, 2);
+    assert_eq!(health.last_market_event_at.len(), 1);
+    assert!(health.last_fill_at.is_some());
+    assert!(!health.running);
+    assert!(!health.is_healthy());
+}
@@ -0,0 +1,213 @@
+//! Runs a strategy in shadow mode against a data-only "real" broker and
+//! checks that every order lands on the internal paper book instead.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use chrono::{Duration, TimeZone, Utc};
+use gb_live::broker::{
+    AccountBalance, Broker, BrokerError, BrokerPosition, BrokerResult, ConnectionStatus,
+};
+use gb_live::engine::{LiveEngine, LiveEngineConfig, LiveEngineEvent, TradingMode};
+use gb_live::paper::PaperBrokerConfig;
+use gb_live::risk::RiskConfig;
+use gb_live::shadow::ShadowConfig;
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::{Order, OrderId, OrderStatus, Side};
+use gb_types::strategy::StrategyConfig;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
+
+mod common;
+
+use common::ScriptedStrategy;
+
+/// A market data feed standing in for a real brokerage: it tracks prices and
+/// counts order calls, which it refuses.
+#[derive(Default)]
+struct DataFeedBroker {
+    prices: HashMap<Symbol, Decimal>,
+    order_calls: Arc<AtomicUsize>,
+    connected: bool,
+}
+
+impl DataFeedBroker {
+    fn refuse<T>(&self) -> BrokerResult<T> {
+        self.order_calls.fetch_add(1, Ordering::SeqCst);
+        Err(BrokerError::OrderRejected {
+            reason: "orders must not reach the real broker in shadow mode".into(),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Broker for DataFeedBroker {
+    async fn connect(&mut self) -> BrokerResult<()> {
+        self.connected = true;
+        Ok(())
+    }
+    async fn disconnect(&mut self) -> BrokerResult<()> {
+        self.connected = false;
+        Ok(())
+    }
+    fn connection_status(&self) -> ConnectionStatus {
+        if self.connected {
+            ConnectionStatus::Connected
+        } else {
+            ConnectionStatus::Disconnected
+        }
+    }
+    async fn submit_order(&mut self, _order: Order) -> BrokerResult<OrderId> {
+        self.refuse()
+    }
+    async fn cancel_order(&mut self, _order_id: OrderId) -> BrokerResult<()> {
+        self.refuse()
+    }
+    async fn get_order_status(&self, _order_id: OrderId) -> BrokerResult<OrderStatus> {
+        self.refuse()
+    }
+    async fn get_order(&self, _order_id: OrderId) -> BrokerResult<Order> {
+        self.refuse()
+    }
+    async fn get_open_orders(&self) -> BrokerResult<Vec<Order>> {
+        self.refuse()
+    }
+    async fn get_account_balance(&self) -> BrokerResult<AccountBalance> {
+        self.refuse()
+    }
+    async fn get_positions(&self) -> BrokerResult<Vec<BrokerPosition>> {
+        self.refuse()
+    }
+    async fn get_position(&self, _symbol: &Symbol) -> BrokerResult<Option<BrokerPosition>> {
+        self.refuse()
+    }
+    async fn subscribe_market_data(&mut self, _symbols: &[Symbol]) -> BrokerResult<()> {
+        Ok(())
+    }
+    async fn unsubscribe_market_data(&mut self, _symbols: &[Symbol]) -> BrokerResult<()> {
+        Ok(())
+    }
+    fn get_latest_price(&self, symbol: &Symbol) -> Option<Decimal> {
+        self.prices.get(symbol).copied()
+    }
+    fn get_all_prices(&self) -> HashMap<Symbol, Decimal> {
+        self.prices.clone()
+    }
+    async fn on_market_event(&mut self, event: &MarketEvent) -> BrokerResult<()> {
+        if let MarketEvent::Bar(bar) = event {
+            self.prices.insert(bar.symbol.clone(), bar.close);
+        }
+        Ok(())
+    }
+}
+
+fn symbol() -> Symbol {
+    Symbol::equity("AAPL")
+}
+
+#[tokio::test]
+async fn shadow_orders_fill_on_the_paper_book_only() {
+    let order_calls = Arc::new(AtomicUsize::new(0));
+    let broker = DataFeedBroker {
+        order_calls: order_calls.clone(),
+        ..Default::default()
+    };
+    let mut strategy_config = StrategyConfig::new("candidate".into(), "Candidate".into());
+    strategy_config.add_symbol(symbol());
+    let strategy = ScriptedStrategy::new(
+        strategy_config.clone(),
+        vec![
+            Some((Side::Buy, dec!(10))),
+            None,
+            Some((Side::Sell, dec!(10))),
+            None,
+        ],
+    );
+    let config = LiveEngineConfig {
+        mode: TradingMode::Shadow,
+        strategy_config,
+        // A limit the orders break: shadow mode only logs the rejection.
+        risk_config: RiskConfig {
+            max_order_notional: dec!(100),
+            ..Default::default()
+        },
+        initial_capital: dec!(100_000),
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: Default::default(),
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: Default::default(),
+        end_of_day: Default::default(),
+        shadow: ShadowConfig {
+            paper: PaperBrokerConfig {
+                slippage_bps: Decimal::ZERO,
+                commission_per_share: Decimal::ZERO,
+                ..Default::default()
+            },
+            divergence_horizon_secs: 60,
+        },
+        event_ordering: Default::default(),
+    };
+    let mut engine = LiveEngine::new(broker, strategy, config);
+    let mut events = engine.subscribe();
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap();
+    let bars: Vec<_> = [dec!(100), dec!(104), dec!(110), dec!(105)]
+        .into_iter()
+        .enumerate()
+        .map(|(minute, close)| {
+            MarketEvent::Bar(Bar {
+                symbol: symbol(),
+                timestamp: start + Duration::minutes(minute as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: dec!(1_000_000),
+                resolution: Resolution::Minute,
+                provenance: Default::default(),
+            })
+        })
+        .collect();
+    engine
+        .run(
+            futures_util::stream::iter(bars),
+            futures_util::stream::empty(),
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(order_calls.load(Ordering::SeqCst), 0);
+
+    let paper = engine.shadow_broker().unwrap();
+    assert_eq!(paper.get_fills().len(), 2);
+    assert_eq!(paper.cash(), dec!(100_100));
+    assert!(engine.context().get_position(&symbol()).is_none());
+    assert_eq!(engine.context().portfolio.total_realized_pnl, dec!(100));
+
+    let received: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+    let shadow_fills = received
+        .iter()
+        .filter(|event| matches!(event, LiveEngineEvent::ShadowOrderFilled { .. }))
+        .count();
+    assert_eq!(shadow_fills, 2);
+    assert!(!received.iter().any(|event| matches!(
+        event,
+        LiveEngineEvent::OrderFilled { .. } | LiveEngineEvent::OrderRejectedByRisk { .. }
+    )));
+
+    // Bought at 100 with the market at 104 a minute later; sold at 110 with
+    // it at 105.
+    let report = engine.shadow_report().unwrap();
+    assert_eq!(report.fills.len(), 2);
+    assert_eq!(report.unresolved(), 0);
+    assert_eq!(report.fills[0].market_price, Some(dec!(104)));
+    assert_eq!(report.fills[1].market_price, Some(dec!(105)));
+    assert!(report.average_divergence_bps().unwrap() > Decimal::ZERO);
+}
@@ -0,0 +1,167 @@
+//! Hosts two strategies in one `LiveEngine` on a shared `PaperBroker` and
+//! checks that their capital and circuit breakers stay independent.
+
+use chrono::{Duration, TimeZone, Utc};
+use gb_live::engine::{
+    LiveEngine, LiveEngineConfig, LiveEngineEvent, StrategyAllocation, TradingMode,
+};
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_live::risk::RiskConfig;
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::Side;
+use gb_types::portfolio::RiskLimits;
+use gb_types::strategy::{Strategy, StrategyConfig};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
+
+mod common;
+
+use common::ScriptedStrategy;
+
+fn symbol() -> Symbol {
+    Symbol::equity("AAPL")
+}
+
+fn strategy(
+    strategy_id: &str,
+    script: Vec<Option<(Side, Decimal)>>,
+) -> (Box<dyn Strategy>, StrategyConfig) {
+    let mut config = StrategyConfig::new(strategy_id.into(), strategy_id.into());
+    config.add_symbol(symbol());
+    let strategy = ScriptedStrategy::new(config.clone(), script);
+    (Box::new(strategy), config)
+}
+
+/// Default limits apart from allowing a single position to use the whole
+/// allocation.
+fn risk_config() -> RiskConfig {
+    RiskConfig {
+        limits: RiskLimits {
+            position_concentration_limit: dec!(1.0),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn tripped_strategy_stops_while_the_other_keeps_trading() {
+    let broker = PaperBroker::new(PaperBrokerConfig {
+        initial_cash: dec!(200_000),
+        ..Default::default()
+    });
+    let (steady, steady_config) = strategy("steady", vec![Some((Side::Buy, dec!(1))); 4]);
+    // Buys at 100 and sells at 80: a 10% loss of its 2,000 allocation trips
+    // the default 5% breaker on its next order.
+    let (loser, loser_config) = strategy(
+        "loser",
+        vec![
+            Some((Side::Buy, dec!(10))),
+            Some((Side::Sell, dec!(10))),
+            Some((Side::Buy, dec!(1))),
+            Some((Side::Buy, dec!(1))),
+        ],
+    );
+
+    let config = LiveEngineConfig {
+        mode: TradingMode::Sandbox,
+        strategy_config: steady_config,
+        risk_config: risk_config(),
+        initial_capital: dec!(100_000),
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: Default::default(),
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: Default::default(),
+        end_of_day: Default::default(),
+        shadow: Default::default(),
+        event_ordering: Default::default(),
+    };
+    let mut engine = LiveEngine::new(broker, steady, config);
+    engine
+        .add_strategy(
+            loser,
+            StrategyAllocation {
+                strategy_config: loser_config,
+                capital: dec!(2_000),
+                risk_config: risk_config(),
+            },
+        )
+        .unwrap();
+    assert_eq!(engine.context().portfolio.total_equity, dec!(102_000));
+    let mut events = engine.subscribe();
+
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap();
+    let bars: Vec<_> = [dec!(100), dec!(80), dec!(80), dec!(80)]
+        .into_iter()
+        .enumerate()
+        .map(|(minute, close)| {
+            MarketEvent::Bar(Bar {
+                symbol: symbol(),
+                timestamp: start + Duration::minutes(minute as i64),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: dec!(10_000),
+                resolution: Resolution::Minute,
+                provenance: Default::default(),
+            })
+        })
+        .collect();
+    let fill_stream = engine.broker_mut().fill_stream();
+    engine
+        .run(
+            futures_util::stream::iter(bars),
+            fill_stream,
+            CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+    let loser = engine.strategy("loser").unwrap();
+    let steady = engine.strategy("steady").unwrap();
+    assert!(loser.risk_manager().is_circuit_breaker_tripped());
+    assert!(!steady.risk_manager().is_circuit_breaker_tripped());
+    assert!(!engine.risk_manager().is_circuit_breaker_tripped());
+
+    assert!(loser.context().get_position(&symbol()).is_none());
+    assert!(loser.context().portfolio.total_equity < dec!(1_900));
+    assert_eq!(
+        steady.context().get_position(&symbol()).unwrap().quantity,
+        dec!(4)
+    );
+    assert_eq!(
+        engine.context().get_position(&symbol()).unwrap().quantity,
+        dec!(4)
+    );
+    let equity = engine.strategy_equity();
+    assert_eq!(equity[0].0, "steady");
+    assert_eq!(equity[1].0, "loser");
+
+    let received: Vec<_> = std::iter::from_fn(|| events.try_recv().ok()).collect();
+    let tripped_at = received
+        .iter()
+        .position(|event| {
+            matches!(
+                event,
+                LiveEngineEvent::CircuitBreakerTripped { strategy_id: Some(id), .. } if id == "loser"
+            )
+        })
+        .expect("expected the loser's breaker to trip");
+    let submitted = |id: &str, events: &[LiveEngineEvent]| {
+        events
+            .iter()
+            .filter(|event| {
+                matches!(event, LiveEngineEvent::OrderSubmitted { strategy_id, .. } if strategy_id == id)
+            })
+            .count()
+    };
+    assert_eq!(submitted("steady", &received), 4);
+    assert_eq!(submitted("loser", &received), 2);
+    assert!(submitted("steady", &received[tripped_at..]) > 0);
+}
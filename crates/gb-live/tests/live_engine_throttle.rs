@@ -0,0 +1,174 @@
+//! Drives a burst of orders through `LiveEngine::run` with the order
+//! throttle pacing submissions under the risk rate limit.
+
+use std::time::Duration;
+
+use chrono::{TimeZone, Utc};
+use futures_util::StreamExt;
+use gb_live::engine::{LiveEngine, LiveEngineConfig, LiveEngineEvent, TradingMode};
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_live::risk::RiskConfig;
+use gb_live::throttle::ThrottleConfig;
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::{Order, OrderEvent, Side};
+use gb_types::strategy::{
+    Strategy, StrategyAction, StrategyConfig, StrategyContext, StrategyMetrics,
+};
+use rust_decimal_macros::dec;
+use tokio_util::sync::CancellationToken;
+
+const BURST: usize = 500;
+
+/// Places `BURST` one-share orders on the first bar, alternating buys and
+/// sells, each numbered in its metadata.
+struct BurstStrategy {
+    config: StrategyConfig,
+    fired: bool,
+}
+
+impl Strategy for BurstStrategy {
+    fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+        self.config = config.clone();
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        event: &MarketEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        if std::mem::replace(&mut self.fired, true) {
+            return Ok(vec![]);
+        }
+        Ok((0..BURST)
+            .map(|seq| {
+                let side = if seq % 2 == 0 { Side::Buy } else { Side::Sell };
+                let order = Order::market_order(
+                    event.symbol().clone(),
+                    side,
+                    dec!(1),
+                    self.config.strategy_id.clone(),
+                )
+                .with_metadata(serde_json::json!({ "seq": seq }));
+                StrategyAction::PlaceOrder(order)
+            })
+            .collect())
+    }
+
+    fn on_order_event(
+        &mut self,
+        _event: &OrderEvent,
+        _context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_day_end(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(vec![])
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+fn symbol() -> Symbol {
+    Symbol::equity("AAPL")
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_burst_drains_in_order_under_the_rate_limit() {
+    let mut strategy_config = StrategyConfig::new("burst".into(), "Burst".into());
+    strategy_config.add_symbol(symbol());
+    let config = LiveEngineConfig {
+        mode: TradingMode::Sandbox,
+        strategy_config: strategy_config.clone(),
+        // Unthrottled, all but 20 of the burst would be rejected.
+        risk_config: RiskConfig {
+            max_orders_per_window: 20,
+            order_window_seconds: 1,
+            ..Default::default()
+        },
+        initial_capital: dec!(100_000),
+        calendar: Default::default(),
+        reconnect: Default::default(),
+        submit_retry: Default::default(),
+        throttle: ThrottleConfig {
+            enabled: true,
+            max_in_flight: 5,
+            // Ten submissions a second.
+            pacing_interval_ms: 100,
+            ..Default::default()
+        },
+        reconciliation: Default::default(),
+        persistence: Default::default(),
+        health: Default::default(),
+        journal: Default::default(),
+        end_of_day: Default::default(),
+        shadow: Default::default(),
+        event_ordering: Default::default(),
+    };
+    let broker = PaperBroker::new(PaperBrokerConfig {
+        initial_cash: dec!(100_000),
+        ..Default::default()
+    });
+    let strategy = BurstStrategy {
+        config: strategy_config,
+        fired: false,
+    };
+    let mut engine = LiveEngine::new(broker, strategy, config);
+    let mut events = engine.subscribe();
+    let collector = tokio::spawn(async move {
+        let (mut risk_rejections, mut dropped) = (0, 0);
+        while let Ok(event) = events.recv().await {
+            match event {
+                LiveEngineEvent::OrderRejectedByRisk { .. } => risk_rejections += 1,
+                LiveEngineEvent::OrderDropped { .. } => dropped += 1,
+                LiveEngineEvent::Stopped { .. } => break,
+                _ => {}
+            }
+        }
+        (risk_rejections, dropped)
+    });
+
+    let bar = MarketEvent::Bar(Bar::new(
+        symbol(),
+        Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap(),
+        dec!(100),
+        dec!(100),
+        dec!(100),
+        dec!(100),
+        dec!(10_000),
+        Resolution::Minute,
+    ));
+    let data = futures_util::stream::iter([bar]).chain(futures_util::stream::pending());
+    let fills = engine.broker_mut().fill_stream();
+    let shutdown = CancellationToken::new();
+    let stop_later = shutdown.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        stop_later.cancel();
+    });
+
+    let started = tokio::time::Instant::now();
+    engine.run(data, fills, shutdown).await.unwrap();
+
+    assert!(started.elapsed() >= Duration::from_millis(100 * (BURST as u64 - 1)));
+    let sequence: Vec<u64> = engine
+        .broker()
+        .get_fills()
+        .iter()
+        .map(|fill| fill.metadata["seq"].as_u64().unwrap())
+        .collect();
+    assert_eq!(sequence, (0..BURST as u64).collect::<Vec<_>>());
+    assert_eq!(engine.health().queued_orders, 0);
+    assert_eq!(collector.await.unwrap(), (0, 0));
+}
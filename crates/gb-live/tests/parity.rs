@@ -0,0 +1,91 @@
+//! Runs buy-and-hold through the backtest engine and a paper-trading live
+//! engine on the same daily bars and compares the two.
+
+use chrono::{Datelike, TimeZone, Utc, Weekday};
+use gb_engine::parity::{ParityDivergence, ParitySettings};
+use gb_live::parity::{paper_broker_config, run_parity, run_parity_with};
+use gb_types::market::{Bar, Resolution, Symbol};
+use gb_types::strategy::{BuyAndHoldStrategy, StrategyConfig};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+
+/// Weekday bars for 2024-01-02 through 2024-01-12, drifting up with the
+/// open below the close.
+fn bars() -> Vec<Bar> {
+    (2..=12)
+        .map(|day| Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap())
+        .filter(|timestamp| !matches!(timestamp.weekday(), Weekday::Sat | Weekday::Sun))
+        .enumerate()
+        .map(|(i, timestamp)| {
+            let close = dec!(100) + Decimal::from(i as u32);
+            Bar::new(
+                Symbol::equity("AAPL"),
+                timestamp,
+                close - dec!(0.5),
+                close + dec!(1),
+                close - dec!(1),
+                close,
+                dec!(1_000_000),
+                Resolution::Day,
+            )
+        })
+        .collect()
+}
+
+fn strategy_config() -> StrategyConfig {
+    StrategyConfig::new("buy_and_hold".into(), "Buy and Hold".into())
+}
+
+#[tokio::test]
+async fn buy_and_hold_matches_exactly() {
+    let bars = bars();
+    let report = run_parity(
+        BuyAndHoldStrategy::new,
+        &strategy_config(),
+        &bars,
+        &ParitySettings::default(),
+    )
+    .await
+    .unwrap();
+
+    assert!(report.is_match(), "{}", report.divergence.unwrap());
+    // Bought on the first close, filled at the second open plus 5bps.
+    assert_eq!(report.live.fills.len(), 1);
+    assert_eq!(report.live.fills[0].date, bars[1].timestamp.date_naive());
+    assert_eq!(report.live.fills[0].quantity, dec!(950));
+    assert_eq!(report.live.fills[0].price, dec!(100.55025));
+    assert_eq!(report.live.path.len(), bars.len());
+    assert_eq!(report.backtest, report.live);
+}
+
+#[tokio::test]
+async fn different_slippage_diverges_at_the_first_fill() {
+    let settings = ParitySettings::default();
+    let mut paper = paper_broker_config(&settings);
+    paper.slippage_bps = dec!(0.002);
+    let report = run_parity_with(
+        BuyAndHoldStrategy::new,
+        &strategy_config(),
+        &bars(),
+        &settings,
+        paper,
+    )
+    .await
+    .unwrap();
+
+    let divergence = report.divergence.expect("slippage differs");
+    let ParityDivergence::Fill {
+        index,
+        backtest: Some(backtest),
+        live: Some(live),
+        preceding,
+    } = &divergence
+    else {
+        panic!("expected a fill divergence, got {divergence}");
+    };
+    assert_eq!(*index, 0);
+    assert!(preceding.is_empty());
+    assert_eq!(backtest.price, dec!(100.55025));
+    assert_eq!(live.price, dec!(100.701));
+    assert_eq!(backtest.quantity, live.quantity);
+}
@@ -0,0 +1,218 @@
+//! Property tests for paper-broker matching: random sequences of bars,
+//! orders and cancels keep every order's quantities adding up and, without
+//! short selling or margin, never leave the account with negative equity.
+
+use chrono::{Duration, TimeZone, Utc};
+use gb_live::broker::Broker;
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_types::market::{Bar, MarketEvent, Resolution, Symbol};
+use gb_types::orders::{Order, Side};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+#[derive(Debug, Clone)]
+enum Step {
+    Bar {
+        symbol: usize,
+        close: Decimal,
+        range: Decimal,
+        volume: Decimal,
+    },
+    Market {
+        symbol: usize,
+        side: Side,
+        quantity: Decimal,
+    },
+    Limit {
+        symbol: usize,
+        side: Side,
+        quantity: Decimal,
+        limit: Decimal,
+    },
+    /// Cancel the n-th submitted order, modulo the number submitted.
+    Cancel(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Scenario {
+    commission_per_share: Decimal,
+    slippage: Decimal,
+    max_participation_rate: Option<Decimal>,
+    steps: Vec<Step>,
+}
+
+fn symbols() -> [Symbol; 2] {
+    [Symbol::equity("AAPL"), Symbol::equity("MSFT")]
+}
+
+/// A decimal in `low..=high` steps of `10^-scale`.
+fn decimal(low: i64, high: i64, scale: u32) -> impl Strategy<Value = Decimal> {
+    (low..=high).prop_map(move |steps| Decimal::new(steps, scale))
+}
+
+fn side() -> impl Strategy<Value = Side> {
+    prop_oneof![Just(Side::Buy), Just(Side::Sell)]
+}
+
+fn step() -> impl Strategy<Value = Step> {
+    prop_oneof![
+        (
+            0..2usize,
+            decimal(100, 50_000, 2),
+            decimal(0, 2_000, 2),
+            decimal(0, 100_000, 0)
+        )
+            .prop_map(|(symbol, close, range, volume)| Step::Bar {
+                symbol,
+                close,
+                range,
+                volume,
+            }),
+        (0..2usize, side(), decimal(0, 2_000, 0)).prop_map(|(symbol, side, quantity)| {
+            Step::Market {
+                symbol,
+                side,
+                quantity,
+            }
+        }),
+        (
+            0..2usize,
+            side(),
+            decimal(0, 2_000, 0),
+            decimal(100, 50_000, 2)
+        )
+            .prop_map(|(symbol, side, quantity, limit)| Step::Limit {
+                symbol,
+                side,
+                quantity,
+                limit,
+            }),
+        (0..16usize).prop_map(Step::Cancel),
+    ]
+}
+
+fn scenario() -> impl Strategy<Value = Scenario> {
+    (
+        decimal(0, 50, 3),
+        decimal(0, 100, 4),
+        prop::option::weighted(0.3, decimal(1, 100, 2)),
+        prop::collection::vec(step(), 0..60),
+    )
+        .prop_map(
+            |(commission_per_share, slippage, max_participation_rate, steps)| Scenario {
+                commission_per_share,
+                slippage,
+                max_participation_rate,
+                steps,
+            },
+        )
+}
+
+async fn replay(scenario: &Scenario) -> Result<(), String> {
+    let symbols = symbols();
+    let mut broker = PaperBroker::new(PaperBrokerConfig {
+        initial_cash: Decimal::from(100_000),
+        commission_per_share: scenario.commission_per_share,
+        slippage_bps: scenario.slippage,
+        max_participation_rate: scenario.max_participation_rate,
+        ..Default::default()
+    });
+    broker.connect().await.map_err(|e| e.to_string())?;
+    let start = Utc.with_ymd_and_hms(2024, 1, 2, 15, 0, 0).unwrap();
+    let mut submitted = Vec::new();
+
+    for (index, step) in scenario.steps.iter().enumerate() {
+        match step {
+            Step::Bar {
+                symbol,
+                close,
+                range,
+                volume,
+            } => {
+                let low = (*close - *range).max(Decimal::new(1, 2));
+                let bar = Bar::new(
+                    symbols[*symbol].clone(),
+                    start + Duration::minutes(index as i64),
+                    *close,
+                    *close + *range,
+                    low,
+                    *close,
+                    *volume,
+                    Resolution::Minute,
+                );
+                broker.process_market_event(&MarketEvent::Bar(bar));
+            }
+            Step::Market {
+                symbol,
+                side,
+                quantity,
+            } => {
+                let order =
+                    Order::market_order(symbols[*symbol].clone(), *side, *quantity, "p".into());
+                if let Ok(id) = broker.submit_order(order).await {
+                    submitted.push(id);
+                }
+            }
+            Step::Limit {
+                symbol,
+                side,
+                quantity,
+                limit,
+            } => {
+                let order = Order::limit_order(
+                    symbols[*symbol].clone(),
+                    *side,
+                    *quantity,
+                    *limit,
+                    "p".into(),
+                );
+                if let Ok(id) = broker.submit_order(order).await {
+                    submitted.push(id);
+                }
+            }
+            Step::Cancel(n) => {
+                if !submitted.is_empty() {
+                    let _ = broker.cancel_order(submitted[n % submitted.len()]).await;
+                }
+            }
+        }
+
+        for id in &submitted {
+            let order = broker.get_order(*id).await.map_err(|e| e.to_string())?;
+            if order.filled_quantity + order.remaining_quantity != order.quantity
+                || order.filled_quantity < Decimal::ZERO
+            {
+                return Err(format!("after step {index}: {order:?} does not add up"));
+            }
+        }
+        let balance = broker
+            .get_account_balance()
+            .await
+            .map_err(|e| e.to_string())?;
+        if balance.equity < Decimal::ZERO {
+            return Err(format!("after step {index}: negative equity {balance:?}"));
+        }
+        for position in broker.get_positions().await.map_err(|e| e.to_string())? {
+            if position.quantity < Decimal::ZERO {
+                return Err(format!(
+                    "after step {index}: short without shorting {position:?}"
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(128))]
+
+    #[test]
+    fn random_order_flow_keeps_orders_and_equity_consistent(scenario in scenario()) {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        if let Err(message) = runtime.block_on(replay(&scenario)) {
+            return Err(TestCaseError::fail(message));
+        }
+    }
+}
@@ -0,0 +1,37 @@
+//! `glowback-trial-worker`: runs one optimization trial.
+//!
+//! Reads a `RayTaskDescriptor` as JSON from stdin, runs it with the
+//! built-in strategies and writes a `TaskReport` as JSON to stdout. A
+//! backtest failure is reported, not an error exit; a non-zero exit means
+//! the worker itself could not run the task.
+
+use gb_optimizer::{builtin_strategy, execute_task, RayTaskDescriptor};
+use std::io::Read;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut input = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut input) {
+        eprintln!("failed to read task descriptor: {e}");
+        return ExitCode::FAILURE;
+    }
+    let task: RayTaskDescriptor = match serde_json::from_str(&input) {
+        Ok(task) => task,
+        Err(e) => {
+            eprintln!("invalid task descriptor: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let report = execute_task(&task, &builtin_strategy);
+    match serde_json::to_string(&report) {
+        Ok(json) => {
+            println!("{json}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("failed to encode task report: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! How a trial's parameters are scored: on the whole backtest window, or
+//! as the mean over time-based folds of it.
+
+use chrono::{DateTime, Duration, Utc};
+use gb_types::BacktestConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// How each trial's objective is evaluated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum EvaluationMode {
+    /// One backtest over the full window.
+    #[default]
+    Single,
+    /// Split the window into `folds` contiguous, equally sized blocks of
+    /// days and average the objective over them.
+    KFoldTime { folds: usize },
+    /// Backtest windows of `window_days` days starting every `step_days`
+    /// days, as many as fit, and average the objective over them.
+    WalkForward {
+        window_days: usize,
+        step_days: usize,
+    },
+}
+
+/// Date range of one fold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fold {
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+}
+
+/// Objective and metrics of a trial on one fold.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FoldResult {
+    pub fold: Fold,
+    pub objective: f64,
+    pub metrics: HashMap<String, f64>,
+}
+
+impl EvaluationMode {
+    /// The folds `config` is evaluated on, in chronological order; `Single`
+    /// yields its whole date range. Folds are whole simulated days: each one
+    /// ends a second before the next day it doesn't cover, so consecutive
+    /// folds never share a day.
+    pub fn folds(&self, config: &BacktestConfig) -> Result<Vec<Fold>, String> {
+        let start = config.start_date;
+        let days = if config.end_date < start {
+            0
+        } else {
+            (config.end_date - start).num_days() as usize + 1
+        };
+        let fold = |first_day: usize, end_day: usize| Fold {
+            start_date: start + Duration::days(first_day as i64),
+            end_date: if end_day >= days {
+                config.end_date
+            } else {
+                start + Duration::days(end_day as i64) - Duration::seconds(1)
+            },
+        };
+
+        match *self {
+            Self::Single => Ok(vec![Fold {
+                start_date: config.start_date,
+                end_date: config.end_date,
+            }]),
+            Self::KFoldTime { folds } => {
+                if folds == 0 || folds > days {
+                    return Err(format!(
+                        "cannot split a {days}-day backtest into {folds} folds"
+                    ));
+                }
+                Ok((0..folds)
+                    .map(|i| fold(i * days / folds, (i + 1) * days / folds))
+                    .collect())
+            }
+            Self::WalkForward {
+                window_days,
+                step_days,
+            } => {
+                if window_days == 0 || step_days == 0 {
+                    return Err("walk-forward window and step must be at least one day".into());
+                }
+                if window_days > days {
+                    return Err(format!(
+                        "walk-forward window of {window_days} days exceeds the {days}-day backtest"
+                    ));
+                }
+                Ok((0..=days - window_days)
+                    .step_by(step_days)
+                    .map(|first_day| fold(first_day, first_day + window_days))
+                    .collect())
+            }
+        }
+    }
+}
+
+/// Mean of each metric reported by every fold.
+pub(crate) fn mean_metrics(folds: &[FoldResult]) -> HashMap<String, f64> {
+    let Some(first) = folds.first() else {
+        return HashMap::new();
+    };
+    first
+        .metrics
+        .keys()
+        .filter_map(|name| {
+            let total = folds
+                .iter()
+                .map(|fold| fold.metrics.get(name))
+                .sum::<Option<f64>>()?;
+            Some((name.clone(), total / folds.len() as f64))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gb_types::StrategyConfig;
+
+    fn config(days: i64) -> BacktestConfig {
+        let start = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        BacktestConfig::new(
+            "folds".to_string(),
+            StrategyConfig::new("buy_and_hold".to_string(), "Buy and Hold".to_string()),
+        )
+        .with_date_range(start, start + Duration::days(days - 1))
+    }
+
+    fn day(offset: i64) -> DateTime<Utc> {
+        config(1).start_date + Duration::days(offset)
+    }
+
+    #[test]
+    fn single_mode_covers_the_whole_window() {
+        let config = config(10);
+        assert_eq!(
+            EvaluationMode::Single.folds(&config).unwrap(),
+            vec![Fold {
+                start_date: config.start_date,
+                end_date: config.end_date,
+            }]
+        );
+    }
+
+    #[test]
+    fn kfold_splits_days_contiguously() {
+        let config = config(10);
+        let folds = EvaluationMode::KFoldTime { folds: 3 }
+            .folds(&config)
+            .unwrap();
+
+        let starts: Vec<_> = folds.iter().map(|fold| fold.start_date).collect();
+        assert_eq!(starts, vec![day(0), day(3), day(6)]);
+        assert_eq!(folds[0].end_date, day(3) - Duration::seconds(1));
+        assert_eq!(folds[1].end_date, day(6) - Duration::seconds(1));
+        assert_eq!(folds[2].end_date, config.end_date);
+        assert!(EvaluationMode::KFoldTime { folds: 11 }
+            .folds(&config)
+            .is_err());
+        assert!(EvaluationMode::KFoldTime { folds: 0 }
+            .folds(&config)
+            .is_err());
+    }
+
+    #[test]
+    fn walk_forward_rolls_fixed_windows() {
+        let config = config(10);
+        let folds = EvaluationMode::WalkForward {
+            window_days: 4,
+            step_days: 3,
+        }
+        .folds(&config)
+        .unwrap();
+
+        let bounds: Vec<_> = folds
+            .iter()
+            .map(|fold| (fold.start_date, fold.end_date))
+            .collect();
+        assert_eq!(
+            bounds,
+            vec![
+                (day(0), day(4) - Duration::seconds(1)),
+                (day(3), day(7) - Duration::seconds(1)),
+                (day(6), config.end_date),
+            ]
+        );
+        assert!(EvaluationMode::WalkForward {
+            window_days: 11,
+            step_days: 1,
+        }
+        .folds(&config)
+        .is_err());
+    }
+
+    #[test]
+    fn mean_metrics_keeps_metrics_every_fold_reports() {
+        let fold = Fold {
+            start_date: day(0),
+            end_date: day(1),
+        };
+        let folds = vec![
+            FoldResult {
+                fold,
+                objective: 1.0,
+                metrics: HashMap::from([("a".to_string(), 1.0), ("b".to_string(), 5.0)]),
+            },
+            FoldResult {
+                fold,
+                objective: 3.0,
+                metrics: HashMap::from([("a".to_string(), 3.0)]),
+            },
+        ];
+        assert_eq!(
+            mean_metrics(&folds),
+            HashMap::from([("a".to_string(), 2.0)])
+        );
+    }
+}
@@ -0,0 +1,538 @@
+//! Flat trial tables for analysis outside GlowBack, and importing them back
+//! as prior observations for a search.
+//!
+//! A table has one row per trial. The fixed columns come first —
+//! `trial_number`, `trial_id`, `status`, `budget`, `objective`,
+//! `duration_seconds`, `error` — then one `param_<name>` column per search
+//! parameter in search-space order, then one `metric_<name>` column per
+//! metric in name order. The prefixes keep a parameter and a metric of the
+//! same name apart. Choice parameters are stored as JSON text.
+
+use arrow::array::{Array, ArrayRef, Float64Array, Int64Array, RecordBatch, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter};
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::search::{ParameterKind, ParameterValue, SearchSpace};
+use crate::trial::{ObjectiveDirection, OptimizationStatus, Trial, TrialStatus};
+
+const PARAMETER_PREFIX: &str = "param_";
+const METRIC_PREFIX: &str = "metric_";
+
+/// Parameters and the objective a search should be told they scored.
+pub type Observation = (HashMap<String, ParameterValue>, f64);
+
+/// File format of a trial table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    /// Format named by `path`'s extension.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        match path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase)
+            .as_deref()
+        {
+            Some("csv") => Ok(Self::Csv),
+            Some("parquet") => Ok(Self::Parquet),
+            _ => Err(format!(
+                "cannot tell the table format of {}; use .csv or .parquet",
+                path.display()
+            )),
+        }
+    }
+}
+
+/// Write `trials` of the run described by `status` to `path`.
+pub fn export_trials(
+    status: &OptimizationStatus,
+    trials: &[Trial],
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+) -> Result<(), String> {
+    let columns = trial_columns(&status.config.search_space, trials);
+    match format {
+        ExportFormat::Csv => write_csv(&columns, path.as_ref()),
+        ExportFormat::Parquet => write_parquet(&columns, path.as_ref()),
+    }
+}
+
+/// `trials` of the run described by `status` as an in-memory Arrow table,
+/// with the same columns as [`export_trials`] writes.
+pub fn trials_record_batch(
+    status: &OptimizationStatus,
+    trials: &[Trial],
+) -> Result<RecordBatch, String> {
+    record_batch(&trial_columns(&status.config.search_space, trials))
+}
+
+/// Completed trials in the table at `path` as observations over `space`, ready to [`report`] to a search. Objectives of
+/// a minimized run are negated, since searches maximize what they are told.
+/// Rows missing a parameter of `space` are skipped.
+///
+/// [`report`]: crate::SearchStrategy::report
+pub fn import_observations(
+    path: impl AsRef<Path>,
+    format: ExportFormat,
+    space: &SearchSpace,
+    direction: ObjectiveDirection,
+) -> Result<Vec<Observation>, String> {
+    let rows = match format {
+        ExportFormat::Csv => read_csv(path.as_ref())?,
+        ExportFormat::Parquet => read_parquet(path.as_ref())?,
+    };
+    let completed = format!("{:?}", TrialStatus::Completed);
+    Ok(rows
+        .iter()
+        .filter(|row| matches!(row.get("status"), Some(Cell::Text(status)) if *status == completed))
+        .filter_map(|row| {
+            let objective = row.get("objective")?.as_f64()?;
+            let parameters = space
+                .parameters
+                .iter()
+                .map(|param| {
+                    let cell = row.get(&format!("{PARAMETER_PREFIX}{}", param.name))?;
+                    Some((param.name.clone(), cell.parameter_value(&param.kind)?))
+                })
+                .collect::<Option<HashMap<_, _>>>()?;
+            let objective = match direction {
+                ObjectiveDirection::Maximize => objective,
+                ObjectiveDirection::Minimize => -objective,
+            };
+            Some((parameters, objective))
+        })
+        .collect())
+}
+
+/// One typed column of a trial table.
+#[derive(Debug, Clone, PartialEq)]
+struct Column {
+    name: String,
+    values: Values,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Values {
+    Int(Vec<Option<i64>>),
+    Float(Vec<Option<f64>>),
+    Text(Vec<Option<String>>),
+}
+
+impl Values {
+    fn data_type(&self) -> DataType {
+        match self {
+            Self::Int(_) => DataType::Int64,
+            Self::Float(_) => DataType::Float64,
+            Self::Text(_) => DataType::Utf8,
+        }
+    }
+
+    fn array(&self) -> ArrayRef {
+        match self {
+            Self::Int(values) => Arc::new(Int64Array::from(values.clone())),
+            Self::Float(values) => Arc::new(Float64Array::from(values.clone())),
+            Self::Text(values) => Arc::new(StringArray::from(values.clone())),
+        }
+    }
+
+    fn text(&self, row: usize) -> String {
+        match self {
+            Self::Int(values) => values[row].map(|v| v.to_string()),
+            Self::Float(values) => values[row].map(|v| v.to_string()),
+            Self::Text(values) => values[row].clone(),
+        }
+        .unwrap_or_default()
+    }
+}
+
+/// A cell read back from a table.
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Int(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl Cell {
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Int(v) => Some(*v as f64),
+            Self::Float(v) => Some(*v),
+            Self::Text(text) => text.trim().parse().ok(),
+        }
+    }
+
+    fn parameter_value(&self, kind: &ParameterKind) -> Option<ParameterValue> {
+        match kind {
+            ParameterKind::IntRange { .. } => match self {
+                Self::Int(v) => Some(ParameterValue::Int(*v)),
+                Self::Text(text) => text.trim().parse().ok().map(ParameterValue::Int),
+                Self::Float(_) => None,
+            },
+            ParameterKind::FloatRange { .. } | ParameterKind::LogUniform { .. } => {
+                self.as_f64().map(ParameterValue::Float)
+            }
+            ParameterKind::Choice { .. } => match self {
+                Self::Text(text) => Some(ParameterValue::Json(
+                    serde_json::from_str(text)
+                        .unwrap_or_else(|_| serde_json::Value::String(text.clone())),
+                )),
+                Self::Int(v) => Some(ParameterValue::Json((*v).into())),
+                Self::Float(v) => Some(ParameterValue::Json((*v).into())),
+            },
+        }
+    }
+}
+
+fn trial_columns(space: &SearchSpace, trials: &[Trial]) -> Vec<Column> {
+    let column = |name: &str, values: Values| Column {
+        name: name.to_string(),
+        values,
+    };
+    let mut columns = vec![
+        column(
+            "trial_number",
+            Values::Int(trials.iter().map(|t| Some(t.trial_number as i64)).collect()),
+        ),
+        column(
+            "trial_id",
+            Values::Text(trials.iter().map(|t| Some(t.id.to_string())).collect()),
+        ),
+        column(
+            "status",
+            Values::Text(
+                trials
+                    .iter()
+                    .map(|t| Some(format!("{:?}", t.status)))
+                    .collect(),
+            ),
+        ),
+        column(
+            "budget",
+            Values::Float(trials.iter().map(|t| Some(t.budget)).collect()),
+        ),
+        column(
+            "objective",
+            Values::Float(
+                trials
+                    .iter()
+                    .map(|t| t.result.as_ref().map(|result| result.objective))
+                    .collect(),
+            ),
+        ),
+        column(
+            "duration_seconds",
+            Values::Int(
+                trials
+                    .iter()
+                    .map(|t| {
+                        t.result
+                            .as_ref()
+                            .and_then(|result| result.duration_seconds)
+                            .map(|seconds| seconds as i64)
+                    })
+                    .collect(),
+            ),
+        ),
+        column(
+            "error",
+            Values::Text(trials.iter().map(|t| t.error.clone()).collect()),
+        ),
+    ];
+
+    for param in &space.parameters {
+        let value = |trial: &Trial| trial.parameters.get(&param.name).cloned();
+        let values = match param.kind {
+            ParameterKind::IntRange { .. } => Values::Int(
+                trials
+                    .iter()
+                    .map(|t| match value(t) {
+                        Some(ParameterValue::Int(v)) => Some(v),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            ParameterKind::FloatRange { .. } | ParameterKind::LogUniform { .. } => Values::Float(
+                trials
+                    .iter()
+                    .map(|t| match value(t) {
+                        Some(ParameterValue::Float(v)) => Some(v),
+                        Some(ParameterValue::Int(v)) => Some(v as f64),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+            ParameterKind::Choice { .. } => Values::Text(
+                trials
+                    .iter()
+                    .map(|t| match value(t) {
+                        Some(ParameterValue::Json(v)) => Some(v.to_string()),
+                        _ => None,
+                    })
+                    .collect(),
+            ),
+        };
+        columns.push(column(&format!("{PARAMETER_PREFIX}{}", param.name), values));
+    }
+
+    let metric_names: BTreeSet<&String> = trials
+        .iter()
+        .filter_map(|t| t.result.as_ref())
+        .flat_map(|result| result.metrics.keys())
+        .collect();
+    for name in metric_names {
+        let values = trials
+            .iter()
+            .map(|t| t.result.as_ref().and_then(|r| r.metrics.get(name)).copied())
+            .collect();
+        columns.push(column(
+            &format!("{METRIC_PREFIX}{name}"),
+            Values::Float(values),
+        ));
+    }
+    columns
+}
+
+fn write_csv(columns: &[Column], path: &Path) -> Result<(), String> {
+    let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+    writer
+        .write_record(columns.iter().map(|column| column.name.as_str()))
+        .map_err(|e| e.to_string())?;
+    let rows = columns.first().map_or(0, |column| match &column.values {
+        Values::Int(values) => values.len(),
+        Values::Float(values) => values.len(),
+        Values::Text(values) => values.len(),
+    });
+    for row in 0..rows {
+        writer
+            .write_record(columns.iter().map(|column| column.values.text(row)))
+            .map_err(|e| e.to_string())?;
+    }
+    writer.flush().map_err(|e| e.to_string())
+}
+
+fn write_parquet(columns: &[Column], path: &Path) -> Result<(), String> {
+    let batch = record_batch(columns)?;
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let mut writer = ArrowWriter::try_new(file, batch.schema(), None).map_err(|e| e.to_string())?;
+    writer.write(&batch).map_err(|e| e.to_string())?;
+    writer.close().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn record_batch(columns: &[Column]) -> Result<RecordBatch, String> {
+    let schema = Arc::new(Schema::new(
+        columns
+            .iter()
+            .map(|column| Field::new(&column.name, column.values.data_type(), true))
+            .collect::<Vec<_>>(),
+    ));
+    RecordBatch::try_new(
+        schema,
+        columns.iter().map(|column| column.values.array()).collect(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+fn read_csv(path: &Path) -> Result<Vec<HashMap<String, Cell>>, String> {
+    let mut reader = csv::Reader::from_path(path).map_err(|e| e.to_string())?;
+    let headers = reader.headers().map_err(|e| e.to_string())?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| e.to_string())?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(name, value)| (name.to_string(), Cell::Text(value.to_string())))
+                .collect())
+        })
+        .collect()
+}
+
+fn read_parquet(path: &Path) -> Result<Vec<HashMap<String, Cell>>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = ParquetRecordBatchReaderBuilder::try_new(file)
+        .map_err(|e| e.to_string())?
+        .build()
+        .map_err(|e| e.to_string())?;
+    let mut rows = Vec::new();
+    for batch in reader {
+        let batch = batch.map_err(|e| e.to_string())?;
+        let schema = batch.schema();
+        let mut batch_rows = vec![HashMap::new(); batch.num_rows()];
+        for (field, array) in schema.fields().iter().zip(batch.columns()) {
+            for (row, cells) in batch_rows.iter_mut().enumerate() {
+                if array.is_null(row) {
+                    continue;
+                }
+                let cell = if let Some(values) = array.as_any().downcast_ref::<Int64Array>() {
+                    Cell::Int(values.value(row))
+                } else if let Some(values) = array.as_any().downcast_ref::<Float64Array>() {
+                    Cell::Float(values.value(row))
+                } else if let Some(values) = array.as_any().downcast_ref::<StringArray>() {
+                    Cell::Text(values.value(row).to_string())
+                } else {
+                    return Err(format!(
+                        "unsupported type {} for column {}",
+                        field.data_type(),
+                        field.name()
+                    ));
+                };
+                cells.insert(field.name().clone(), cell);
+            }
+        }
+        rows.extend(batch_rows);
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{BayesianSearch, SearchStrategy, SurrogateKind};
+    use crate::trial::{OptimizationConfig, TrialResult};
+
+    /// A finished run whose parameter `x` is also the name of a metric.
+    fn finished_run() -> (OptimizationStatus, Vec<Trial>) {
+        let space = SearchSpace::new()
+            .add_float("x", 0.0, 10.0)
+            .add_int("n", 1, 20)
+            .add_choice(
+                "mode",
+                vec![serde_json::json!("fast"), serde_json::json!({"k": 2})],
+            );
+        let status = OptimizationStatus::new(OptimizationConfig::new(
+            "exported".into(),
+            space,
+            "bayesian",
+        ));
+        let mut trials: Vec<Trial> = (0..6)
+            .map(|i| {
+                let parameters = HashMap::from([
+                    ("x".to_string(), ParameterValue::Float(1.25 * i as f64)),
+                    ("n".to_string(), ParameterValue::Int(3 * i + 1)),
+                    (
+                        "mode".to_string(),
+                        ParameterValue::Json(if i % 2 == 0 {
+                            serde_json::json!("fast")
+                        } else {
+                            serde_json::json!({"k": 2})
+                        }),
+                    ),
+                ]);
+                let mut trial = Trial::new(status.id, i as usize, parameters.clone());
+                let objective = -(1.25 * i as f64 - 5.0).powi(2);
+                trial.mark_completed(TrialResult {
+                    trial_id: trial.id,
+                    objective,
+                    metrics: HashMap::from([
+                        ("x".to_string(), 100.0 + i as f64),
+                        ("sharpe_ratio".to_string(), objective),
+                    ]),
+                    parameters,
+                    duration_seconds: Some(i as u64),
+                    folds: Vec::new(),
+                });
+                trial
+            })
+            .collect();
+        trials[5].status = TrialStatus::Failed;
+        trials[5].result = None;
+        trials[5].error = Some("no data".to_string());
+        (status, trials)
+    }
+
+    #[test]
+    fn columns_are_prefixed_and_ordered() {
+        let (status, trials) = finished_run();
+        let names: Vec<String> = trial_columns(&status.config.search_space, &trials)
+            .into_iter()
+            .map(|column| column.name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "trial_number",
+                "trial_id",
+                "status",
+                "budget",
+                "objective",
+                "duration_seconds",
+                "error",
+                "param_x",
+                "param_n",
+                "param_mode",
+                "metric_sharpe_ratio",
+                "metric_x",
+            ]
+        );
+        assert_eq!(
+            ExportFormat::from_path("runs/trials.Parquet").unwrap(),
+            ExportFormat::Parquet
+        );
+        assert!(ExportFormat::from_path("trials.xlsx").is_err());
+    }
+
+    #[test]
+    fn exported_runs_seed_a_bayesian_search() {
+        let (status, trials) = finished_run();
+        let space = &status.config.search_space;
+        let expected: Vec<_> = trials[..5]
+            .iter()
+            .map(|trial| {
+                (
+                    trial.parameters.clone(),
+                    trial.result.as_ref().unwrap().objective,
+                )
+            })
+            .collect();
+        let dir = tempfile::tempdir().unwrap();
+
+        for file in ["trials.csv", "trials.parquet"] {
+            let path = dir.path().join(file);
+            let format = ExportFormat::from_path(&path).unwrap();
+            export_trials(&status, &trials, &path, format).unwrap();
+
+            let imported =
+                import_observations(&path, format, space, ObjectiveDirection::Maximize).unwrap();
+            assert_eq!(imported, expected, "{file}");
+
+            // The imported best is x = 5, n = 13; exploiting stays near it.
+            let mut search =
+                BayesianSearch::new(space.clone(), 0.0, SurrogateKind::Perturbation).with_seed(3);
+            for (parameters, objective) in &imported {
+                search.report(parameters, *objective);
+            }
+            for suggestion in search.suggest(20) {
+                let ParameterValue::Float(x) = suggestion["x"] else {
+                    panic!("x should be a float");
+                };
+                let ParameterValue::Int(n) = suggestion["n"] else {
+                    panic!("n should be an int");
+                };
+                assert!((x - 5.0).abs() <= 1.0, "x = {x}");
+                assert!((n - 13).abs() <= 2, "n = {n}");
+            }
+        }
+
+        let minimized = import_observations(
+            dir.path().join("trials.csv"),
+            ExportFormat::Csv,
+            space,
+            ObjectiveDirection::Minimize,
+        )
+        .unwrap();
+        assert_eq!(minimized[0].1, -expected[0].1);
+    }
+}
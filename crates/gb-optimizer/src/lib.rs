@@ -3,19 +3,40 @@
 //! Parameter search and distributed optimization orchestration for GlowBack.
 //!
 //! Provides search space definitions, parameter sweep strategies (grid, random,
-//! Bayesian), trial tracking, and Ray-compatible task descriptors for distributed
-//! execution.
+//! Bayesian, Hyperband), trial tracking and persistence, CSV/Parquet export
+//! of trial tables and import of them as prior observations, resumable local
+//! execution of trials against the backtest engine with optional time-fold
+//! or walk-forward evaluation, and Ray-compatible task descriptors with a
+//! dispatcher that runs them over HTTP or in local worker processes.
 
+mod evaluation;
+mod export;
 mod ray;
+mod runner;
 mod search;
+mod store;
+mod surrogate;
 mod trial;
 
-pub use ray::{RayClusterConfig, RayTaskDescriptor, WorkerAllocation};
+pub use evaluation::{EvaluationMode, Fold, FoldResult};
+pub use export::{
+    export_trials, import_observations, trials_record_batch, ExportFormat, Observation,
+};
+pub use ray::{
+    execute_task, DataRequirements, HttpTransport, LocalProcessTransport, RayClusterConfig,
+    RayDispatcher, RayTaskDescriptor, TaskReport, Transport, WorkerAllocation,
+};
+pub use runner::{
+    apply_budget, builtin_strategy, metric_values, profile_metric_values, search_strategy,
+    strategy_metric_values, trial_backtest_config, OptimizationRunner, QuickEvaluator,
+    StrategyFactory, TrialObserver,
+};
 pub use search::{
-    BayesianSearch, GridSearch, ParameterDef, ParameterValue, RandomSearch, SearchSpace,
-    SearchStrategy,
+    same_parameters, BayesianSearch, GridSearch, HyperbandSearch, ParameterDef, ParameterKind,
+    ParameterValue, RandomSearch, SearchSpace, SearchStrategy,
 };
+pub use store::OptimizationStore;
 pub use trial::{
-    ObjectiveDirection, OptimizationConfig, OptimizationState, OptimizationStatus, Trial,
-    TrialResult, TrialStatus,
+    ObjectiveDirection, OptimizationConfig, OptimizationId, OptimizationState, OptimizationStatus,
+    StopReason, StoppingCriteria, Trial, TrialResult, TrialStatus,
 };
@@ -1,10 +1,26 @@
-//! Ray cluster configuration and task descriptors for distributed execution.
+//! Ray cluster configuration, task descriptors and the dispatcher that runs
+//! them on remote or local workers.
 
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use gb_engine::CancellationHandle;
+use gb_types::{Resolution, Symbol};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+use tokio::task::JoinSet;
 use uuid::Uuid;
 
+use crate::evaluation::EvaluationMode;
+use crate::runner::{
+    apply_budget, fold_backtests, run_backtest, score_folds, trial_backtest_config, StrategyFactory,
+};
 use crate::search::ParameterValue;
+use crate::trial::{OptimizationConfig, Trial, TrialResult, TrialStatus};
 
 /// Configuration for connecting to a Ray cluster.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -76,7 +92,10 @@ impl Default for WorkerResources {
 ///
 /// The Python Ray integration layer converts this descriptor into a
 /// `@ray.remote` function call that executes the backtest with the given
-/// parameter overrides.
+/// parameter overrides. Descriptors built by [`RayDispatcher::describe`]
+/// are self-contained: `base_config` is the trial's complete backtest
+/// configuration, parameters and budget already applied, so a worker needs
+/// nothing else to run it (see [`execute_task`]).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RayTaskDescriptor {
     /// Unique task id (matches the trial id).
@@ -99,6 +118,38 @@ pub struct RayTaskDescriptor {
 
     /// Resource requirements for this specific task.
     pub resources: WorkerResources,
+
+    /// How the objective is evaluated over `base_config`'s date range.
+    #[serde(default)]
+    pub evaluation: EvaluationMode,
+
+    /// Market data the task will load, so schedulers can place it near
+    /// the data.
+    #[serde(default)]
+    pub data_requirements: Option<DataRequirements>,
+}
+
+/// Market data a task's backtest loads.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DataRequirements {
+    pub symbols: Vec<Symbol>,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub resolution: Resolution,
+    pub data_source: String,
+}
+
+/// What a worker reports back for a task it ran.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum TaskReport {
+    Completed {
+        result: TrialResult,
+    },
+    /// The backtest itself failed; retrying won't help.
+    Failed {
+        error: String,
+    },
 }
 
 /// Allocation plan produced by the optimizer for the Ray dispatcher.
@@ -131,6 +182,283 @@ impl WorkerAllocation {
     }
 }
 
+/// Run `task` in this process, building each fold's strategy with
+/// `factory`. This is what a trial worker does with a descriptor.
+pub fn execute_task(task: &RayTaskDescriptor, factory: &StrategyFactory) -> TaskReport {
+    let started = Instant::now();
+    let scored = serde_json::from_value(task.base_config.clone())
+        .map_err(|e| format!("invalid backtest configuration: {e}"))
+        .and_then(|backtest| fold_backtests(&backtest, task.evaluation, factory))
+        .and_then(|folds| {
+            folds
+                .into_iter()
+                .map(|(fold, backtest, strategy)| {
+                    Ok((
+                        fold,
+                        run_backtest(backtest, strategy, CancellationHandle::new())?,
+                    ))
+                })
+                .collect::<Result<Vec<_>, String>>()
+        })
+        .and_then(|folds| score_folds(&task.objective_metric, task.evaluation, folds));
+    match scored {
+        Ok((objective, metrics, folds)) => TaskReport::Completed {
+            result: TrialResult {
+                trial_id: task.task_id,
+                objective,
+                metrics,
+                parameters: task.parameters.clone(),
+                duration_seconds: Some(started.elapsed().as_secs()),
+                folds,
+            },
+        },
+        Err(error) => TaskReport::Failed { error },
+    }
+}
+
+/// Delivers a task to a worker and waits for its report.
+///
+/// An `Err` means the worker itself failed (crashed, unreachable, garbled
+/// reply), and the dispatcher retries the task; a backtest that fails is a
+/// [`TaskReport::Failed`].
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn execute(&self, task: &RayTaskDescriptor) -> Result<TaskReport, String>;
+}
+
+/// [`Transport`] that POSTs descriptors to a Ray Serve endpoint, which
+/// replies with a [`TaskReport`].
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    client: reqwest::Client,
+    endpoint: String,
+}
+
+impl HttpTransport {
+    pub fn new(endpoint: impl Into<String>, timeout: Duration) -> Result<Self, String> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("failed to build HTTP client: {e}"))?;
+        Ok(Self {
+            client,
+            endpoint: endpoint.into(),
+        })
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn execute(&self, task: &RayTaskDescriptor) -> Result<TaskReport, String> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .json(task)
+            .send()
+            .await
+            .map_err(|e| format!("task request failed: {e}"))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| format!("failed to read task response: {e}"))?;
+        if !status.is_success() {
+            return Err(format!(
+                "worker endpoint returned {status}: {}",
+                body.trim()
+            ));
+        }
+        serde_json::from_str(&body).map_err(|e| format!("invalid task report: {e}"))
+    }
+}
+
+/// [`Transport`] that runs each task in a fresh `glowback-trial-worker`
+/// subprocess, writing the descriptor to its stdin and reading the report
+/// from its stdout. Needs no cluster.
+#[derive(Debug, Clone)]
+pub struct LocalProcessTransport {
+    program: PathBuf,
+}
+
+impl LocalProcessTransport {
+    pub fn new(program: impl Into<PathBuf>) -> Self {
+        Self {
+            program: program.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for LocalProcessTransport {
+    async fn execute(&self, task: &RayTaskDescriptor) -> Result<TaskReport, String> {
+        let input = serde_json::to_vec(task).map_err(|e| e.to_string())?;
+        let mut child = tokio::process::Command::new(&self.program)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("failed to start {}: {e}", self.program.display()))?;
+        let mut stdin = child.stdin.take().ok_or("worker stdin unavailable")?;
+        stdin
+            .write_all(&input)
+            .await
+            .map_err(|e| format!("failed to send task to worker: {e}"))?;
+        drop(stdin);
+
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| format!("worker did not finish: {e}"))?;
+        if !output.status.success() {
+            return Err(format!(
+                "worker exited with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let report = stdout
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .ok_or("worker reported nothing")?;
+        serde_json::from_str(report).map_err(|e| format!("invalid task report: {e}"))
+    }
+}
+
+/// Turns trials into [`RayTaskDescriptor`]s, runs them through a
+/// [`Transport`] with at most [`WorkerAllocation::num_workers`] in flight,
+/// and records each result on its trial as it arrives. A task whose worker
+/// fails is retried up to `max_retries` times before its trial is failed.
+pub struct RayDispatcher {
+    transport: Arc<dyn Transport>,
+    max_retries: usize,
+}
+
+impl RayDispatcher {
+    pub fn new(transport: impl Transport + 'static) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            max_retries: 2,
+        }
+    }
+
+    pub fn with_max_retries(mut self, max_retries: usize) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// A self-contained descriptor for each pending trial of `config`.
+    pub fn describe(
+        config: &OptimizationConfig,
+        cluster: &RayClusterConfig,
+        trials: &[Trial],
+    ) -> Result<Vec<RayTaskDescriptor>, String> {
+        trials
+            .iter()
+            .filter(|trial| trial.status == TrialStatus::Pending)
+            .map(|trial| {
+                let mut backtest = trial_backtest_config(&config.base_backtest, &trial.parameters)?;
+                apply_budget(&mut backtest, trial.budget);
+                Ok(RayTaskDescriptor {
+                    task_id: trial.id,
+                    optimization_id: trial.optimization_id,
+                    trial_number: trial.trial_number,
+                    parameters: trial.parameters.clone(),
+                    objective_metric: config.objective_metric.clone(),
+                    resources: cluster.worker_resources.clone(),
+                    evaluation: config.evaluation,
+                    data_requirements: Some(DataRequirements {
+                        symbols: backtest.symbols.clone(),
+                        start_date: backtest.start_date,
+                        end_date: backtest.end_date,
+                        resolution: backtest.resolution,
+                        data_source: backtest.data_settings.data_source.clone(),
+                    }),
+                    base_config: serde_json::to_value(&backtest).map_err(|e| e.to_string())?,
+                })
+            })
+            .collect()
+    }
+
+    /// Describe the pending `trials`, allocate workers on `cluster` and
+    /// [`dispatch`](Self::dispatch) them.
+    pub async fn run(
+        &self,
+        config: &OptimizationConfig,
+        cluster: RayClusterConfig,
+        trials: &mut [Trial],
+    ) -> Result<(), String> {
+        let tasks = Self::describe(config, &cluster, trials)?;
+        self.dispatch(&WorkerAllocation::new(cluster, tasks), trials)
+            .await;
+        Ok(())
+    }
+
+    /// Run every task in `allocation`, marking the trial with the task's id
+    /// running when it is submitted and completed or failed when it reports.
+    pub async fn dispatch(&self, allocation: &WorkerAllocation, trials: &mut [Trial]) {
+        let workers = allocation.num_workers.max(1);
+        let mut queue = allocation.tasks.iter();
+        let mut running = JoinSet::new();
+        loop {
+            while running.len() < workers {
+                let Some(task) = queue.next() else {
+                    break;
+                };
+                if let Some(trial) = trials.iter_mut().find(|trial| trial.id == task.task_id) {
+                    trial.mark_running(None);
+                }
+                let transport = self.transport.clone();
+                let task = task.clone();
+                let max_retries = self.max_retries;
+                running.spawn(async move {
+                    let report = execute_with_retries(&*transport, &task, max_retries).await;
+                    (task.task_id, report)
+                });
+            }
+
+            let Some(joined) = running.join_next().await else {
+                break;
+            };
+            let Ok((task_id, report)) = joined else {
+                continue;
+            };
+            let Some(trial) = trials.iter_mut().find(|trial| trial.id == task_id) else {
+                continue;
+            };
+            match report {
+                TaskReport::Completed { mut result } => {
+                    result.trial_id = trial.id;
+                    trial.mark_completed(result);
+                }
+                TaskReport::Failed { error } => trial.mark_failed(error),
+            }
+        }
+    }
+}
+
+async fn execute_with_retries(
+    transport: &dyn Transport,
+    task: &RayTaskDescriptor,
+    max_retries: usize,
+) -> TaskReport {
+    let mut last_error = String::new();
+    for _ in 0..=max_retries {
+        match transport.execute(task).await {
+            Ok(report) => return report,
+            Err(error) => last_error = error,
+        }
+    }
+    TaskReport::Failed {
+        error: format!(
+            "worker failed {} times, last with: {last_error}",
+            max_retries + 1
+        ),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -157,6 +485,8 @@ mod tests {
                 base_config: serde_json::Value::Null,
                 objective_metric: "sharpe_ratio".to_string(),
                 resources: WorkerResources::default(),
+                evaluation: EvaluationMode::Single,
+                data_requirements: None,
             })
             .collect();
 
@@ -178,6 +508,8 @@ mod tests {
             base_config: serde_json::json!({"strategy": "ma_crossover"}),
             objective_metric: "sharpe_ratio".to_string(),
             resources: WorkerResources::default(),
+            evaluation: EvaluationMode::KFoldTime { folds: 2 },
+            data_requirements: None,
         };
 
         let json = serde_json::to_string(&task).unwrap();
@@ -185,6 +517,153 @@ mod tests {
         assert_eq!(task, back);
     }
 
+    use crate::search::SearchSpace;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// Fails each task's first `failures` attempts, then completes it with
+    /// its trial number as the objective.
+    #[derive(Default)]
+    struct FlakyTransport {
+        failures: usize,
+        attempts: Mutex<HashMap<Uuid, usize>>,
+        in_flight: AtomicUsize,
+        peak_in_flight: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Transport for FlakyTransport {
+        async fn execute(&self, task: &RayTaskDescriptor) -> Result<TaskReport, String> {
+            let in_flight = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+            self.peak_in_flight.fetch_max(in_flight, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            self.in_flight.fetch_sub(1, Ordering::SeqCst);
+
+            let attempt = {
+                let mut attempts = self.attempts.lock().unwrap();
+                let attempt = attempts.entry(task.task_id).or_default();
+                *attempt += 1;
+                *attempt
+            };
+            if attempt <= self.failures {
+                return Err(format!("worker crashed on attempt {attempt}"));
+            }
+            Ok(TaskReport::Completed {
+                result: TrialResult {
+                    trial_id: task.task_id,
+                    objective: task.trial_number as f64,
+                    metrics: HashMap::new(),
+                    parameters: task.parameters.clone(),
+                    duration_seconds: Some(0),
+                    folds: Vec::new(),
+                },
+            })
+        }
+    }
+
+    fn sweep(trials: usize) -> (OptimizationConfig, Vec<Trial>) {
+        let config = OptimizationConfig::new(
+            "dispatch".into(),
+            SearchSpace::new().add_int("short_period", 1, 10),
+            "grid",
+        )
+        .with_base_backtest(serde_json::json!({
+            "strategy_config": {"strategy_id": "ma_crossover", "parameters": {"long_period": 30}},
+            "data_settings": {"data_source": "sample"}
+        }));
+        let trials = (0..trials)
+            .map(|i| {
+                let parameters =
+                    HashMap::from([("short_period".to_string(), ParameterValue::Int(i as i64))]);
+                Trial::new(config.id, i, parameters)
+            })
+            .collect();
+        (config, trials)
+    }
+
+    #[test]
+    fn descriptors_carry_the_full_trial_backtest() {
+        let (config, mut trials) = sweep(3);
+        trials[1].budget = 0.5;
+        trials[2].mark_running(None);
+
+        let tasks =
+            RayDispatcher::describe(&config, &RayClusterConfig::default(), &trials).unwrap();
+        assert_eq!(tasks.len(), 2);
+
+        let full: gb_types::BacktestConfig =
+            serde_json::from_value(tasks[0].base_config.clone()).unwrap();
+        assert_eq!(tasks[0].task_id, trials[0].id);
+        assert_eq!(full.data_settings.data_source, "sample");
+        assert_eq!(
+            full.strategy_config.get_parameter::<i64>("short_period"),
+            Some(0)
+        );
+        assert_eq!(
+            full.strategy_config.get_parameter::<i64>("long_period"),
+            Some(30)
+        );
+
+        let halved: gb_types::BacktestConfig =
+            serde_json::from_value(tasks[1].base_config.clone()).unwrap();
+        assert!(halved.end_date < full.end_date);
+        let data = tasks[1].data_requirements.as_ref().unwrap();
+        assert_eq!(data.end_date, halved.end_date);
+        assert_eq!(data.data_source, "sample");
+    }
+
+    #[tokio::test]
+    async fn dispatcher_retries_failed_workers_within_the_allocation() {
+        let (config, mut trials) = sweep(6);
+        let cluster = RayClusterConfig {
+            max_concurrent_tasks: 2,
+            ..Default::default()
+        };
+        let transport = Arc::new(FlakyTransport {
+            failures: 1,
+            ..Default::default()
+        });
+        let dispatcher = RayDispatcher {
+            transport: transport.clone(),
+            max_retries: 1,
+        };
+
+        dispatcher.run(&config, cluster, &mut trials).await.unwrap();
+
+        for trial in &trials {
+            assert_eq!(trial.status, TrialStatus::Completed);
+            let result = trial.result.as_ref().unwrap();
+            assert_eq!(result.trial_id, trial.id);
+            assert_eq!(result.objective, trial.trial_number as f64);
+        }
+        assert_eq!(transport.peak_in_flight.load(Ordering::SeqCst), 2);
+        assert!(transport.attempts.lock().unwrap().values().all(|n| *n == 2));
+    }
+
+    #[tokio::test]
+    async fn dispatcher_fails_trials_once_retries_run_out() {
+        let (config, mut trials) = sweep(2);
+        let dispatcher = RayDispatcher::new(FlakyTransport {
+            failures: usize::MAX,
+            ..Default::default()
+        })
+        .with_max_retries(2);
+
+        dispatcher
+            .run(&config, RayClusterConfig::default(), &mut trials)
+            .await
+            .unwrap();
+
+        for trial in &trials {
+            assert_eq!(trial.status, TrialStatus::Failed);
+            assert!(trial
+                .error
+                .as_deref()
+                .unwrap()
+                .contains("worker failed 3 times"));
+        }
+    }
+
     #[test]
     fn runtime_env_round_trip() {
         let env = RuntimeEnv {
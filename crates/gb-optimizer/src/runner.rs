@@ -0,0 +1,1459 @@
+//! Local execution of optimization runs against the backtest engine.
+
+use gb_engine::telemetry::{optimization_span, trial_span};
+use gb_engine::vectorized::{BarMatrix, QuickBacktestResult};
+use gb_engine::{BacktestEngine, CancellationHandle};
+use gb_types::{
+    BacktestConfig, BuyAndHoldStrategy, CoveredCallStrategy, MeanReversionStrategy,
+    MomentumStrategy, MovingAverageCrossoverStrategy, PerformanceMetrics, RsiStrategy, RunProfile,
+    Strategy, StrategyConfig,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::task::JoinSet;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::evaluation::{mean_metrics, EvaluationMode, Fold, FoldResult};
+use crate::search::{
+    BayesianSearch, GridSearch, HyperbandSearch, ParameterValue, RandomSearch, SearchStrategy,
+};
+use crate::store::OptimizationStore;
+use crate::trial::{
+    ObjectiveDirection, OptimizationConfig, OptimizationId, OptimizationStatus, StopReason,
+    StoppingCriteria, Trial, TrialResult, TrialStatus,
+};
+
+/// Replacement suggestions requested in a row before a search that keeps
+/// repeating itself is treated as exhausted.
+const DUPLICATE_RETRIES: usize = 32;
+
+/// Builds the strategy a trial runs from its merged strategy configuration.
+pub type StrategyFactory =
+    dyn Fn(&StrategyConfig) -> Result<Box<dyn Strategy>, String> + Send + Sync;
+
+/// Scores a trial's backtest from the daily closes of its symbols over its
+/// window, typically with [`quick_backtest`](gb_engine::vectorized::quick_backtest)
+/// and weights derived from the strategy's parameters.
+pub type QuickEvaluator =
+    dyn Fn(&BacktestConfig, &BarMatrix) -> Result<QuickBacktestResult, String> + Send + Sync;
+
+/// Called with each trial as it finishes, completed or failed, and the run's
+/// status after the trial is recorded.
+pub type TrialObserver = dyn Fn(&Trial, &OptimizationStatus) + Send + Sync;
+
+/// Runs optimization trials as backtests, a bounded number at a time.
+///
+/// Each suggested parameter map is merged into `base_backtest` under
+/// `strategy_config.parameters`; the strategy picks the values up when the
+/// engine initializes it. With a [store](Self::with_store) attached, the run
+/// and every finished trial are persisted so it can be
+/// [resumed](Self::resume) after an interruption.
+pub struct OptimizationRunner {
+    factory: Arc<StrategyFactory>,
+    store: Option<OptimizationStore>,
+    observer: Option<Arc<TrialObserver>>,
+    /// Largest budget scored by the quick evaluator, and the evaluator.
+    quick: Option<(f64, Arc<QuickEvaluator>)>,
+    shutdown: CancellationToken,
+    status: Option<OptimizationStatus>,
+}
+
+impl Default for OptimizationRunner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OptimizationRunner {
+    /// Runner for the built-in strategies, selected by `strategy_id`.
+    pub fn new() -> Self {
+        Self::with_strategy_factory(builtin_strategy)
+    }
+
+    pub fn with_strategy_factory(
+        factory: impl Fn(&StrategyConfig) -> Result<Box<dyn Strategy>, String> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            factory: Arc::new(factory),
+            store: None,
+            observer: None,
+            quick: None,
+            shutdown: CancellationToken::new(),
+            status: None,
+        }
+    }
+
+    /// Persist runs and trials to `store`.
+    pub fn with_store(mut self, store: OptimizationStore) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Report every finished trial to `observer`, e.g. to drive a progress
+    /// bar. It runs on the runner's task, so it should return quickly.
+    pub fn with_trial_observer(
+        mut self,
+        observer: impl Fn(&Trial, &OptimizationStatus) + Send + Sync + 'static,
+    ) -> Self {
+        self.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Score trials with a budget of at most `max_budget` with `evaluator`
+    /// instead of the event engine. Under Hyperband this turns the low
+    /// rungs into vectorized screening passes over the same shortened
+    /// windows; full-budget trials always run the event engine, so the best
+    /// trial is scored by it.
+    pub fn with_quick_evaluator(
+        mut self,
+        max_budget: f64,
+        evaluator: impl Fn(&BacktestConfig, &BarMatrix) -> Result<QuickBacktestResult, String>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.quick = Some((max_budget, Arc::new(evaluator)));
+        self
+    }
+
+    pub fn store(&self) -> Option<&OptimizationStore> {
+        self.store.as_ref()
+    }
+
+    /// Token that stops the runner: no new trials are started, trials in
+    /// flight finish and are recorded, and the run ends `Cancelled`.
+    pub fn shutdown_token(&self) -> CancellationToken {
+        self.shutdown.clone()
+    }
+
+    /// Status of the current or last run.
+    pub fn status(&self) -> Option<&OptimizationStatus> {
+        self.status.as_ref()
+    }
+
+    /// Run trials until `max_trials` is reached, the search is exhausted or
+    /// one of the config's [`StoppingCriteria`] triggers, returning every
+    /// trial in submission order. A trial whose backtest fails or times out
+    /// is marked failed and the run carries on; only an invalid search
+    /// strategy or a store write error fails the run itself. When a stopping
+    /// criterion triggers, backtests still in flight are cancelled and their
+    /// trials marked cancelled.
+    pub async fn run(&mut self, config: OptimizationConfig) -> Result<Vec<Trial>, String> {
+        let status = OptimizationStatus::new(config);
+        self.execute(status, Vec::new()).await
+    }
+
+    /// Continue a stored run with its remaining trial budget. Finished
+    /// trials are reloaded and replayed into the search strategy, and
+    /// parameter sets already evaluated are not suggested again; trials that
+    /// were still running when the run stopped are discarded and re-run.
+    pub async fn resume(&mut self, optimization_id: OptimizationId) -> Result<Vec<Trial>, String> {
+        let store = self
+            .store
+            .as_ref()
+            .ok_or_else(|| "resuming an optimization requires a store".to_string())?;
+        let mut status = store
+            .load_status(optimization_id)?
+            .ok_or_else(|| format!("optimization {optimization_id} not found"))?;
+        let trials: Vec<Trial> = store
+            .load_trials(optimization_id)?
+            .into_iter()
+            .filter(|trial| matches!(trial.status, TrialStatus::Completed | TrialStatus::Failed))
+            .collect();
+
+        status.trials_running = 0;
+        status.trials_completed = 0;
+        status.trials_failed = 0;
+        status.best_trial = None;
+        status.finished_at = None;
+        status.error = None;
+        status.stop_reason = None;
+        for trial in &trials {
+            match &trial.result {
+                Some(result) => {
+                    if trial.budget >= 1.0 {
+                        status.update_best(result);
+                    }
+                    status.trials_completed += 1;
+                }
+                None => status.trials_failed += 1,
+            }
+        }
+        self.execute(status, trials).await
+    }
+
+    /// Run inside the optimization's span, which each trial's span and, through
+    /// it, each backtest's nest in.
+    async fn execute(
+        &mut self,
+        status: OptimizationStatus,
+        trials: Vec<Trial>,
+    ) -> Result<Vec<Trial>, String> {
+        let span = optimization_span(status.id);
+        self.execute_trials(status, trials).instrument(span).await
+    }
+
+    async fn execute_trials(
+        &mut self,
+        status: OptimizationStatus,
+        mut trials: Vec<Trial>,
+    ) -> Result<Vec<Trial>, String> {
+        let config = status.config.clone();
+        let status = self.status.insert(status);
+        let seed = *status
+            .seed
+            .get_or_insert_with(|| config.seed.unwrap_or_else(rand::random));
+        // A resumed run must not replay the suggestions it already evaluated.
+        let search_seed = seed.wrapping_add(trials.len() as u64);
+        let mut search = match search_strategy(&config, search_seed) {
+            Ok(search) => search,
+            Err(e) => {
+                status.mark_failed(e.clone());
+                return Err(e);
+            }
+        };
+        let mut plateau = Plateau::default();
+        let mut cache =
+            (search.deduplicate() && !config.allow_duplicate_trials).then(SuggestionCache::default);
+        for trial in &trials {
+            plateau.record(&config, trial);
+            if let Some(cache) = cache.as_mut() {
+                cache.insert(&trial.parameters);
+            }
+            search.exclude(&trial.parameters);
+            match &trial.result {
+                Some(result) => search.report_with_budget(
+                    &trial.parameters,
+                    reported_objective(&config, result.objective),
+                    trial.budget,
+                ),
+                None => search.report_failure(&trial.parameters, trial.budget),
+            }
+        }
+        status.mark_running();
+        persist(self.store.as_ref(), status, None)?;
+
+        let concurrency = config.concurrency.max(1);
+        let mut next_number = trials
+            .iter()
+            .map(|trial| trial.trial_number + 1)
+            .max()
+            .unwrap_or_default();
+        let stopping = &config.stopping;
+        let deadline = stopping
+            .max_duration
+            .map(|budget| tokio::time::Instant::now() + budget);
+        let mut running = JoinSet::new();
+        // Cancellation handle and timeout deadline of each trial in flight,
+        // by index into `trials`.
+        let mut in_flight: HashMap<usize, (CancellationHandle, Option<tokio::time::Instant>)> =
+            HashMap::new();
+        let mut timed_out = HashSet::new();
+        let mut exhausted = false;
+
+        loop {
+            if status.stop_reason.is_none() {
+                let reason = if deadline.is_some_and(|at| tokio::time::Instant::now() >= at) {
+                    Some(StopReason::MaxDuration)
+                } else if plateau.exhausted(stopping) {
+                    Some(StopReason::NoImprovement)
+                } else {
+                    None
+                };
+                if let Some(reason) = reason {
+                    status.stop_reason = Some(reason);
+                    for (handle, _) in in_flight.values() {
+                        handle.cancel();
+                    }
+                }
+            }
+
+            while status.stop_reason.is_none()
+                && !exhausted
+                && !self.shutdown.is_cancelled()
+                && running.len() < concurrency
+                && trials.len() < config.max_trials
+            {
+                let Some((parameters, budget)) = next_suggestion(search.as_mut(), cache.as_mut())
+                else {
+                    // The search may be waiting on trials still in flight.
+                    exhausted = running.is_empty();
+                    break;
+                };
+                let index = trials.len();
+                let mut trial = Trial::new(status.id, next_number, parameters);
+                trial.budget = budget;
+                next_number += 1;
+                trial.mark_running(None);
+                let prepared = prepare_trial(&*self.factory, &config, &trial);
+                let quick = self
+                    .quick
+                    .as_ref()
+                    .filter(|(max_budget, _)| budget < 1.0 && budget <= *max_budget)
+                    .map(|(_, evaluator)| evaluator.clone());
+                let span = trial_span(trial.id, trial.trial_number);
+                trials.push(trial);
+                status.trials_running += 1;
+
+                let handle = CancellationHandle::new();
+                let timeout_at = stopping
+                    .per_trial_timeout
+                    .map(|timeout| tokio::time::Instant::now() + timeout);
+                in_flight.insert(index, (handle.clone(), timeout_at));
+                running.spawn_blocking(move || {
+                    let _span = span.enter();
+                    let started = Instant::now();
+                    let outcome = prepared.and_then(|folds| {
+                        folds
+                            .into_iter()
+                            .map(|(fold, backtest, strategy)| {
+                                let metrics = match &quick {
+                                    Some(evaluator) => run_quick_backtest(backtest, &**evaluator)?,
+                                    None => run_backtest(backtest, strategy, handle.clone())?,
+                                };
+                                Ok((fold, metrics))
+                            })
+                            .collect::<Result<Vec<_>, String>>()
+                    });
+                    (index, outcome, started.elapsed().as_secs())
+                });
+            }
+
+            // Wake for a trial finishing, a trial timing out or the run
+            // running out of time, whichever comes first.
+            let wake = in_flight
+                .iter()
+                .filter(|(index, _)| !timed_out.contains(*index))
+                .filter_map(|(_, (_, timeout_at))| *timeout_at)
+                .chain(deadline.filter(|_| status.stop_reason.is_none()))
+                .min();
+            let joined = match wake {
+                Some(wake) => tokio::select! {
+                    joined = running.join_next() => joined,
+                    () = tokio::time::sleep_until(wake) => {
+                        let now = tokio::time::Instant::now();
+                        for (index, (handle, timeout_at)) in &in_flight {
+                            if timeout_at.is_some_and(|at| at <= now) && timed_out.insert(*index) {
+                                handle.cancel();
+                            }
+                        }
+                        continue;
+                    }
+                },
+                None => running.join_next().await,
+            };
+            let Some(joined) = joined else {
+                break;
+            };
+            status.trials_running -= 1;
+            let (index, outcome, elapsed) = match joined {
+                Ok(done) => done,
+                Err(e) => {
+                    // A panicked task can't be traced back to its trial, so
+                    // fail the oldest one still running.
+                    let index = trials
+                        .iter()
+                        .position(|trial| trial.status == TrialStatus::Running)
+                        .unwrap_or_default();
+                    (index, Err(format!("trial task failed: {e}")), 0)
+                }
+            };
+
+            let trial = &mut trials[index];
+            let cancelled = in_flight
+                .remove(&index)
+                .is_some_and(|(handle, _)| handle.is_cancelled());
+            let outcome = match outcome {
+                Err(_) if timed_out.remove(&index) => Err(format!(
+                    "trial exceeded its timeout of {:?}",
+                    stopping.per_trial_timeout.unwrap_or_default()
+                )),
+                Err(_) if cancelled => {
+                    trial.mark_cancelled();
+                    persist(self.store.as_ref(), status, Some(trial))?;
+                    continue;
+                }
+                outcome => outcome,
+            };
+            let scored = outcome
+                .and_then(|folds| score_folds(&config.objective_metric, config.evaluation, folds));
+            match scored {
+                Ok((objective, metrics, folds)) => {
+                    let result = TrialResult {
+                        trial_id: trial.id,
+                        objective,
+                        metrics,
+                        parameters: trial.parameters.clone(),
+                        duration_seconds: Some(elapsed),
+                        folds,
+                    };
+                    search.report_with_budget(
+                        &trial.parameters,
+                        reported_objective(&config, objective),
+                        trial.budget,
+                    );
+                    // Only full-budget results are comparable across trials.
+                    if trial.budget >= 1.0 {
+                        status.update_best(&result);
+                    }
+                    status.trials_completed += 1;
+                    trial.mark_completed(result);
+                }
+                Err(e) => {
+                    search.report_failure(&trial.parameters, trial.budget);
+                    status.trials_failed += 1;
+                    trial.mark_failed(e);
+                }
+            }
+            plateau.record(&config, trial);
+            persist(self.store.as_ref(), status, Some(trial))?;
+            if let Some(observer) = &self.observer {
+                observer(trial, status);
+            }
+        }
+
+        if let Some(reason) = status.stop_reason {
+            status.mark_stopped(reason);
+        } else if self.shutdown.is_cancelled() && trials.len() < config.max_trials && !exhausted {
+            status.mark_cancelled();
+        } else {
+            status.mark_completed();
+        }
+        persist(self.store.as_ref(), status, None)?;
+        Ok(trials)
+    }
+}
+
+/// Canonical forms of the parameter sets a run has started, so a search
+/// that repeats itself can be asked for something new.
+#[derive(Debug, Default)]
+struct SuggestionCache {
+    seen: HashSet<String>,
+}
+
+impl SuggestionCache {
+    /// Record `params`, returning whether they are new.
+    fn insert(&mut self, params: &HashMap<String, ParameterValue>) -> bool {
+        self.seen.insert(Self::key(params))
+    }
+
+    /// Names in order, with floats rounded to nine significant digits so
+    /// values that differ only by storage rounding collide.
+    fn key(params: &HashMap<String, ParameterValue>) -> String {
+        let mut entries: Vec<String> = params
+            .iter()
+            .map(|(name, value)| match value {
+                ParameterValue::Int(v) => format!("{name}=i{v}"),
+                ParameterValue::Float(v) => format!("{name}=f{v:.8e}"),
+                ParameterValue::Json(v) => format!("{name}=j{v}"),
+            })
+            .collect();
+        entries.sort();
+        entries.join(";")
+    }
+}
+
+/// The search's next suggestion that `cache` hasn't seen, asking for up to
+/// [`DUPLICATE_RETRIES`] replacements; `None` if the search has nothing new.
+fn next_suggestion(
+    search: &mut dyn SearchStrategy,
+    cache: Option<&mut SuggestionCache>,
+) -> Option<(HashMap<String, ParameterValue>, f64)> {
+    let Some(cache) = cache else {
+        return search.suggest_with_budget(1).pop();
+    };
+    for _ in 0..=DUPLICATE_RETRIES {
+        let (parameters, budget) = search.suggest_with_budget(1).pop()?;
+        if cache.insert(&parameters) {
+            return Some((parameters, budget));
+        }
+    }
+    None
+}
+
+/// Finished trials since the best full-budget objective last improved by
+/// more than the run's `min_improvement`; failures count as no improvement.
+#[derive(Debug, Default)]
+struct Plateau {
+    best: Option<f64>,
+    since_improvement: usize,
+}
+
+impl Plateau {
+    fn record(&mut self, config: &OptimizationConfig, trial: &Trial) {
+        let improved = trial
+            .result
+            .as_ref()
+            .filter(|_| trial.budget >= 1.0)
+            .map(|result| reported_objective(config, result.objective))
+            .filter(|objective| {
+                self.best
+                    .is_none_or(|best| *objective > best + config.stopping.min_improvement)
+            });
+        match improved {
+            Some(objective) => {
+                self.best = Some(objective);
+                self.since_improvement = 0;
+            }
+            None => self.since_improvement += 1,
+        }
+    }
+
+    fn exhausted(&self, stopping: &StoppingCriteria) -> bool {
+        stopping
+            .patience_trials
+            .is_some_and(|patience| self.since_improvement >= patience)
+    }
+}
+
+/// Adaptive searches maximize what they are told.
+fn reported_objective(config: &OptimizationConfig, objective: f64) -> f64 {
+    match config.direction {
+        ObjectiveDirection::Maximize => objective,
+        ObjectiveDirection::Minimize => -objective,
+    }
+}
+
+/// Write `status` and, if given, `trial` to the store. A failed write fails
+/// the run: its progress would otherwise be lost on resume.
+fn persist(
+    store: Option<&OptimizationStore>,
+    status: &mut OptimizationStatus,
+    trial: Option<&Trial>,
+) -> Result<(), String> {
+    let Some(store) = store else {
+        return Ok(());
+    };
+    let written = trial
+        .map_or(Ok(()), |trial| store.save_trial(trial))
+        .and_then(|()| store.save_status(status));
+    if let Err(e) = &written {
+        status.mark_failed(format!("failed to persist optimization: {e}"));
+    }
+    written
+}
+
+/// A fold with the backtest and strategy that evaluate it.
+pub(crate) type FoldBacktest = (Fold, BacktestConfig, Box<dyn Strategy>);
+
+/// Objective, metrics and per-fold results of a finished trial.
+pub(crate) type TrialScore = (f64, HashMap<String, f64>, Vec<FoldResult>);
+
+/// The backtest and strategy for each fold `trial` is evaluated on. Folds
+/// are cut from the trial's backtest after its budget is applied.
+fn prepare_trial(
+    factory: &StrategyFactory,
+    config: &OptimizationConfig,
+    trial: &Trial,
+) -> Result<Vec<FoldBacktest>, String> {
+    let mut backtest = trial_backtest_config(&config.base_backtest, &trial.parameters)?;
+    apply_budget(&mut backtest, trial.budget);
+    fold_backtests(&backtest, config.evaluation, factory)
+}
+
+/// `backtest` cut into the folds of `evaluation`, each with its own strategy.
+pub(crate) fn fold_backtests(
+    backtest: &BacktestConfig,
+    evaluation: EvaluationMode,
+    factory: &StrategyFactory,
+) -> Result<Vec<FoldBacktest>, String> {
+    evaluation
+        .folds(backtest)?
+        .into_iter()
+        .map(|fold| {
+            let mut fold_backtest = backtest.clone();
+            fold_backtest.id = Uuid::new_v4();
+            fold_backtest.start_date = fold.start_date;
+            fold_backtest.end_date = fold.end_date;
+            let strategy = factory(&fold_backtest.strategy_config)?;
+            Ok((fold, fold_backtest, strategy))
+        })
+        .collect()
+}
+
+/// Score a trial from its fold backtests. Multi-fold evaluation reports the mean over folds; a single
+/// window reports its one backtest and no fold detail.
+pub(crate) fn score_folds(
+    objective_metric: &str,
+    evaluation: EvaluationMode,
+    folds: Vec<(Fold, HashMap<String, f64>)>,
+) -> Result<TrialScore, String> {
+    let folds = folds
+        .into_iter()
+        .map(|(fold, metrics)| {
+            let objective = *metrics
+                .get(objective_metric)
+                .ok_or_else(|| format!("backtest did not report '{objective_metric}'"))?;
+            Ok(FoldResult {
+                fold,
+                objective,
+                metrics,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if folds.is_empty() {
+        return Err("trial was evaluated on no folds".to_string());
+    }
+    if evaluation == EvaluationMode::Single {
+        let whole = folds.into_iter().next().expect("one fold");
+        return Ok((whole.objective, whole.metrics, Vec::new()));
+    }
+    let objective = folds.iter().map(|fold| fold.objective).sum::<f64>() / folds.len() as f64;
+    Ok((objective, mean_metrics(&folds), folds))
+}
+
+/// Build the search strategy named by `config.strategy`, sampling from
+/// `seed`.
+pub fn search_strategy(
+    config: &OptimizationConfig,
+    seed: u64,
+) -> Result<Box<dyn SearchStrategy>, String> {
+    let space = config.search_space.clone();
+    match config.strategy.trim().to_ascii_lowercase().as_str() {
+        "grid" => Ok(Box::new(GridSearch::new(space, config.grid_steps))),
+        "random" => Ok(Box::new(RandomSearch::new(space).with_seed(seed))),
+        "bayesian" => Ok(Box::new(
+            BayesianSearch::new(space, config.exploration_weight, config.surrogate)
+                .with_seed(seed),
+        )),
+        "hyperband" => Ok(Box::new(
+            HyperbandSearch::new(space, config.min_budget, config.reduction_factor)
+                .with_seed(seed),
+        )),
+        other => Err(format!(
+            "Unsupported search strategy: {other}. Supported strategies: grid, random, bayesian, hyperband"
+        )),
+    }
+}
+
+/// Shorten `config` to the first `budget` fraction of its date range, but
+/// never below one bar of its resolution. A budget of 1 or more leaves it
+/// unchanged.
+pub fn apply_budget(config: &mut BacktestConfig, budget: f64) {
+    if budget >= 1.0 {
+        return;
+    }
+    let span = (config.end_date - config.start_date).num_seconds().max(0);
+    let minimum = config.resolution.to_seconds().unwrap_or(1) as i64;
+    let kept = ((span as f64 * budget.max(0.0)).round() as i64).clamp(minimum.min(span), span);
+    config.end_date = config.start_date + chrono::Duration::seconds(kept);
+}
+
+/// Default [`StrategyFactory`]: the built-in strategy matching
+/// `strategy_id`, constructed with defaults.
+pub fn builtin_strategy(config: &StrategyConfig) -> Result<Box<dyn Strategy>, String> {
+    match config.strategy_id.trim().to_ascii_lowercase().as_str() {
+        "buy_and_hold" => Ok(Box::new(BuyAndHoldStrategy::new())),
+        "ma_crossover" | "moving_average_crossover" => {
+            Ok(Box::new(MovingAverageCrossoverStrategy::new(10, 20)))
+        }
+        "momentum" => Ok(Box::new(MomentumStrategy::new(10, 0.05))),
+        "mean_reversion" => Ok(Box::new(MeanReversionStrategy::new(20, 2.0, 1.0))),
+        "rsi" => Ok(Box::new(RsiStrategy::new(14, 30.0, 70.0))),
+        "covered_call" => Ok(Box::new(CoveredCallStrategy::new())),
+        other => Err(format!("Unsupported strategy: {other}")),
+    }
+}
+
+/// Merge `base` over a default backtest configuration and `parameters` into
+/// its strategy parameters. Each trial gets a fresh backtest id.
+pub fn trial_backtest_config(
+    base: &serde_json::Value,
+    parameters: &HashMap<String, ParameterValue>,
+) -> Result<BacktestConfig, String> {
+    let defaults = BacktestConfig::new(
+        "Optimization Trial".to_string(),
+        StrategyConfig::new("buy_and_hold".to_string(), "Buy and Hold".to_string()),
+    );
+    let mut merged = serde_json::to_value(&defaults).map_err(|e| e.to_string())?;
+    if !base.is_null() {
+        merge_json(&mut merged, base);
+    }
+
+    let strategy_parameters = &mut merged["strategy_config"]["parameters"];
+    if !strategy_parameters.is_object() {
+        *strategy_parameters = serde_json::Value::Object(Default::default());
+    }
+    for (name, value) in parameters {
+        strategy_parameters[name.as_str()] =
+            serde_json::to_value(value).map_err(|e| e.to_string())?;
+    }
+
+    let mut config: BacktestConfig = serde_json::from_value(merged)
+        .map_err(|e| format!("invalid base backtest configuration: {e}"))?;
+    config.id = Uuid::new_v4();
+    Ok(config)
+}
+
+fn merge_json(target: &mut serde_json::Value, overlay: &serde_json::Value) {
+    match (target, overlay) {
+        (serde_json::Value::Object(target), serde_json::Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                merge_json(
+                    target.entry(key.clone()).or_insert(serde_json::Value::Null),
+                    value,
+                );
+            }
+        }
+        (target, overlay) => *target = overlay.clone(),
+    }
+}
+
+/// Run one backtest to completion or until `cancellation` fires. The
+/// engine's data manager is not `Send`, so each trial drives it on its own
+/// single-threaded runtime.
+pub(crate) fn run_backtest(
+    config: BacktestConfig,
+    strategy: Box<dyn Strategy>,
+    cancellation: CancellationHandle,
+) -> Result<HashMap<String, f64>, String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let result = runtime
+        .block_on(async {
+            let mut engine = BacktestEngine::new(config)
+                .await?
+                .with_cancellation(cancellation);
+            engine.run_with_strategy(strategy).await
+        })
+        .map_err(|e| e.to_string())?;
+    if let Some(error) = result.error_message {
+        return Err(error);
+    }
+    let metrics = result
+        .performance_metrics
+        .ok_or_else(|| "backtest produced no performance metrics".to_string())?;
+    let mut values = metric_values(&metrics);
+    for (name, value) in strategy_metric_values(&result.metadata)
+        .into_iter()
+        .chain(profile_metric_values(&result.metadata))
+    {
+        values.entry(name).or_insert(value);
+    }
+    Ok(values)
+}
+
+/// Score `config` with `evaluator` over the daily closes of its symbols. The
+/// result's turnover and cost drag are reported next to its metrics.
+fn run_quick_backtest(
+    config: BacktestConfig,
+    evaluator: &QuickEvaluator,
+) -> Result<HashMap<String, f64>, String> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+    let prices = runtime
+        .block_on(async {
+            BacktestEngine::new(config.clone())
+                .await?
+                .bar_matrix()
+                .await
+        })
+        .map_err(|e| e.to_string())?;
+    let result = evaluator(&config, &prices)?;
+    let mut values = metric_values(&result.metrics);
+    values.insert("turnover".to_string(), result.turnover);
+    values.insert("cost_drag".to_string(), result.cost_drag);
+    Ok(values)
+}
+
+/// Numeric entries of the metrics a strategy reported from `on_finish`,
+/// stored under `"strategy"` in a result's metadata. They can be optimized
+/// like performance metrics but never shadow one of the same name.
+pub fn strategy_metric_values(
+    metadata: &HashMap<String, serde_json::Value>,
+) -> HashMap<String, f64> {
+    let Some(serde_json::Value::Object(fields)) = metadata.get("strategy") else {
+        return HashMap::new();
+    };
+    fields
+        .iter()
+        .filter_map(|(name, value)| Some((name.clone(), value.as_f64()?)))
+        .collect()
+}
+
+/// Seconds per phase of a profiled run, from the [`RunProfile`] stored under
+/// `"profile"` in a result's metadata: `profile_total_seconds` plus
+/// `profile_<phase>_seconds` for each phase timed.
+pub fn profile_metric_values(
+    metadata: &HashMap<String, serde_json::Value>,
+) -> HashMap<String, f64> {
+    let Some(profile) = metadata
+        .get("profile")
+        .and_then(|value| serde_json::from_value::<RunProfile>(value.clone()).ok())
+    else {
+        return HashMap::new();
+    };
+    profile
+        .phases
+        .iter()
+        .map(|phase| {
+            (
+                format!("profile_{}_seconds", phase.phase.as_str()),
+                phase.total_seconds,
+            )
+        })
+        .chain([("profile_total_seconds".to_string(), profile.total_seconds)])
+        .collect()
+}
+
+/// Numeric performance metrics by field name; unset optional metrics are
+/// left out.
+pub fn metric_values(metrics: &PerformanceMetrics) -> HashMap<String, f64> {
+    let serde_json::Value::Object(fields) =
+        serde_json::to_value(metrics).unwrap_or(serde_json::Value::Null)
+    else {
+        return HashMap::new();
+    };
+    fields
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let number = match &value {
+                serde_json::Value::Number(number) => number.as_f64(),
+                serde_json::Value::String(text) => text.parse().ok(),
+                _ => None,
+            }?;
+            Some((name, number))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{same_parameters, SearchSpace};
+    use crate::trial::OptimizationState;
+    use chrono::{Duration, Utc};
+    use gb_engine::vectorized::{quick_backtest, Weights};
+    use gb_types::{Resolution, Symbol};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::OnceLock;
+
+    fn ma_base_backtest() -> serde_json::Value {
+        let mut strategy_config = StrategyConfig::new(
+            "ma_crossover".to_string(),
+            "Moving Average Crossover".to_string(),
+        );
+        strategy_config.symbols = vec![Symbol::equity("AAPL")];
+        let mut config = BacktestConfig::new("MA sweep".to_string(), strategy_config)
+            .with_symbols(vec![Symbol::equity("AAPL")])
+            .with_date_range(Utc::now() - Duration::days(180), Utc::now())
+            .with_resolution(Resolution::Day);
+        config.data_settings.data_source = "sample".to_string();
+        serde_json::to_value(config).unwrap()
+    }
+
+    #[test]
+    fn trial_config_merges_parameters_into_partial_base() {
+        let base = serde_json::json!({
+            "name": "partial",
+            "strategy_config": {"strategy_id": "ma_crossover", "parameters": {"long_period": 30}},
+            "data_settings": {"data_source": "sample"}
+        });
+        let parameters = HashMap::from([("short_period".to_string(), ParameterValue::Int(7))]);
+
+        let config = trial_backtest_config(&base, &parameters).unwrap();
+        assert_eq!(config.name, "partial");
+        assert_eq!(config.data_settings.data_source, "sample");
+        assert!(config.data_settings.adjust_for_splits);
+        assert_eq!(config.strategy_config.strategy_id, "ma_crossover");
+        assert_eq!(
+            config
+                .strategy_config
+                .get_parameter::<usize>("short_period"),
+            Some(7)
+        );
+        assert_eq!(
+            config.strategy_config.get_parameter::<usize>("long_period"),
+            Some(30)
+        );
+    }
+
+    #[test]
+    fn budget_truncates_the_date_range_from_the_start() {
+        let base = ma_base_backtest();
+        let full = trial_backtest_config(&base, &HashMap::new()).unwrap();
+
+        let mut third = full.clone();
+        apply_budget(&mut third, 1.0 / 3.0);
+        assert_eq!(third.start_date, full.start_date);
+        assert_eq!(third.end_date, full.start_date + Duration::days(60));
+
+        let mut tiny = full.clone();
+        apply_budget(&mut tiny, 1e-6);
+        assert_eq!(tiny.end_date, full.start_date + Duration::days(1));
+
+        let mut unchanged = full.clone();
+        apply_budget(&mut unchanged, 1.0);
+        assert_eq!(unchanged, full);
+    }
+
+    #[tokio::test]
+    async fn seeded_runs_repeat_their_suggestions_and_record_the_seed() {
+        let space =
+            SearchSpace::new()
+                .add_int("short_period", 2, 40)
+                .add_float("position_size", 0.1, 1.0);
+        let config = |seed: Option<u64>| {
+            let config = OptimizationConfig::new("seeded".into(), space.clone(), "random")
+                .with_max_trials(6)
+                .with_concurrency(1);
+            match seed {
+                Some(seed) => config.with_seed(seed),
+                None => config,
+            }
+        };
+        // Fail every trial straight away; only the suggestions matter here.
+        let run = |config: OptimizationConfig| async move {
+            let mut runner =
+                OptimizationRunner::with_strategy_factory(|_| Err("not evaluated".to_string()));
+            let trials = runner.run(config).await.unwrap();
+            let parameters: Vec<_> = trials.into_iter().map(|trial| trial.parameters).collect();
+            (parameters, runner.status().unwrap().seed)
+        };
+
+        let (first, first_seed) = run(config(Some(42))).await;
+        let (second, _) = run(config(Some(42))).await;
+        let (other, _) = run(config(Some(43))).await;
+        assert_eq!(first_seed, Some(42));
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+
+        let (drawn, drawn_seed) = run(config(None)).await;
+        let (replayed, _) = run(config(drawn_seed)).await;
+        assert_eq!(drawn, replayed);
+    }
+
+    /// Buy-and-hold that sleeps at every day end, so a trial outlives
+    /// short time limits until it is cancelled.
+    struct SlowStrategy(BuyAndHoldStrategy);
+
+    impl Strategy for SlowStrategy {
+        fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+            self.0.initialize(config)
+        }
+
+        fn on_market_event(
+            &mut self,
+            event: &gb_types::MarketEvent,
+            context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            self.0.on_market_event(event, context)
+        }
+
+        fn on_order_event(
+            &mut self,
+            event: &gb_types::OrderEvent,
+            context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            self.0.on_order_event(event, context)
+        }
+
+        fn on_day_end(
+            &mut self,
+            context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            self.0.on_day_end(context)
+        }
+
+        fn on_stop(
+            &mut self,
+            context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            self.0.on_stop(context)
+        }
+
+        fn get_config(&self) -> &StrategyConfig {
+            self.0.get_config()
+        }
+
+        fn get_metrics(&self) -> gb_types::StrategyMetrics {
+            self.0.get_metrics()
+        }
+    }
+
+    /// Buy-and-hold that counts a signal on every `every`-th bar and
+    /// reports the count from `on_finish`.
+    struct SignalCounter {
+        inner: BuyAndHoldStrategy,
+        every: usize,
+        bars: usize,
+        signals_fired: usize,
+    }
+
+    impl Strategy for SignalCounter {
+        fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+            self.every = config.get_parameter("every").unwrap_or(1);
+            self.inner.initialize(config)
+        }
+
+        fn on_market_event(
+            &mut self,
+            event: &gb_types::MarketEvent,
+            context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            self.bars += 1;
+            if self.bars.is_multiple_of(self.every) {
+                self.signals_fired += 1;
+            }
+            self.inner.on_market_event(event, context)
+        }
+
+        fn on_order_event(
+            &mut self,
+            event: &gb_types::OrderEvent,
+            context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            self.inner.on_order_event(event, context)
+        }
+
+        fn on_day_end(
+            &mut self,
+            context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            self.inner.on_day_end(context)
+        }
+
+        fn on_stop(
+            &mut self,
+            context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            self.inner.on_stop(context)
+        }
+
+        fn on_finish(
+            &self,
+            _context: &gb_types::StrategyContext,
+        ) -> HashMap<String, serde_json::Value> {
+            HashMap::from([(
+                "signals_fired".to_string(),
+                serde_json::json!(self.signals_fired),
+            )])
+        }
+
+        fn get_config(&self) -> &StrategyConfig {
+            self.inner.get_config()
+        }
+
+        fn get_metrics(&self) -> gb_types::StrategyMetrics {
+            self.inner.get_metrics()
+        }
+    }
+
+    #[tokio::test]
+    async fn quick_evaluator_scores_hyperband_low_rungs() {
+        let space = SearchSpace::new()
+            .add_int("short_period", 3, 8)
+            .add_int("long_period", 15, 30);
+        let mut config = OptimizationConfig::new("screened".into(), space, "hyperband")
+            .with_max_trials(12)
+            .with_seed(7)
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(ma_base_backtest());
+        config.min_budget = 1.0 / 3.0;
+        let screened = Arc::new(AtomicUsize::new(0));
+        let mut runner = {
+            let screened = screened.clone();
+            OptimizationRunner::new().with_quick_evaluator(0.5, move |_, prices| {
+                screened.fetch_add(1, Ordering::SeqCst);
+                let first = prices.timestamps()[0];
+                let long = |timestamp| {
+                    (timestamp == first)
+                        .then(|| Weights::from([(prices.symbols()[0].clone(), 1.0)]))
+                };
+                quick_backtest(prices, long, 5.0).map_err(|e| e.to_string())
+            })
+        };
+
+        let trials = runner.run(config).await.unwrap();
+
+        let (low, full): (Vec<_>, Vec<_>) = trials.iter().partition(|trial| trial.budget < 1.0);
+        assert!(!low.is_empty() && !full.is_empty());
+        assert_eq!(screened.load(Ordering::SeqCst), low.len());
+        for trial in &trials {
+            let metrics = &trial.result.as_ref().expect("trial completed").metrics;
+            assert_eq!(metrics.contains_key("turnover"), trial.budget < 1.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn strategy_finish_metrics_can_be_the_objective() {
+        let space = SearchSpace::new().add_int("every", 1, 3);
+        let config = OptimizationConfig::new("signals".into(), space, "grid")
+            .with_objective("signals_fired", ObjectiveDirection::Maximize)
+            .with_base_backtest(ma_base_backtest());
+        let mut runner = OptimizationRunner::with_strategy_factory(|_| {
+            Ok(Box::new(SignalCounter {
+                inner: BuyAndHoldStrategy::new(),
+                every: 1,
+                bars: 0,
+                signals_fired: 0,
+            }))
+        });
+
+        let trials = runner.run(config).await.unwrap();
+
+        assert_eq!(trials.len(), 3);
+        let fired = |every: i64| {
+            trials
+                .iter()
+                .find(|trial| trial.parameters["every"] == ParameterValue::Int(every))
+                .and_then(|trial| trial.result.as_ref())
+                .map(|result| result.metrics["signals_fired"])
+                .unwrap()
+        };
+        assert!(fired(1) > fired(2) && fired(2) > fired(3));
+        let best = runner.status().unwrap().best_trial.as_ref().unwrap();
+        assert_eq!(best.parameters["every"], ParameterValue::Int(1));
+        assert_eq!(best.objective, fired(1));
+        assert!(best.metrics.contains_key("total_return"));
+    }
+
+    #[tokio::test]
+    async fn profiled_trials_report_seconds_per_phase() {
+        let space = SearchSpace::new().add_int("unused", 1, 2);
+        let mut base = ma_base_backtest();
+        base["strategy_config"]["strategy_id"] = serde_json::json!("buy_and_hold");
+        base["data_settings"]["profile"] = serde_json::json!(true);
+        let config = OptimizationConfig::new("profiled".into(), space, "grid")
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(base);
+        let mut runner = OptimizationRunner::new();
+
+        let trials = runner.run(config).await.unwrap();
+
+        assert_eq!(trials.len(), 2);
+        for trial in &trials {
+            let metrics = &trial.result.as_ref().unwrap().metrics;
+            let phases: f64 = ["data_loading", "stepping", "fills", "on_market_event"]
+                .iter()
+                .map(|phase| metrics[&format!("profile_{phase}_seconds")])
+                .sum();
+            assert!(metrics["profile_total_seconds"] >= phases - 1e-9);
+        }
+    }
+
+    fn slow_runner() -> OptimizationRunner {
+        OptimizationRunner::with_strategy_factory(|_| {
+            Ok(Box::new(SlowStrategy(BuyAndHoldStrategy::new())))
+        })
+    }
+
+    #[tokio::test]
+    async fn patience_stops_a_run_that_stops_improving() {
+        // The parameter is ignored by buy-and-hold, so every trial scores
+        // the same and only the first one is an improvement.
+        let space = SearchSpace::new().add_int("unused", 1, 20);
+        let mut base = ma_base_backtest();
+        base["strategy_config"]["strategy_id"] = serde_json::json!("buy_and_hold");
+        let config = OptimizationConfig::new("plateau".into(), space, "grid")
+            .with_max_trials(20)
+            .with_concurrency(1)
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(base)
+            .with_stopping(StoppingCriteria {
+                patience_trials: Some(3),
+                ..Default::default()
+            });
+
+        let mut runner = OptimizationRunner::new();
+        let trials = runner.run(config).await.unwrap();
+
+        assert_eq!(trials.len(), 4);
+        assert!(trials
+            .iter()
+            .all(|trial| trial.status == TrialStatus::Completed));
+        let status = runner.status().unwrap();
+        assert_eq!(status.state, OptimizationState::Completed);
+        assert_eq!(status.stop_reason, Some(StopReason::NoImprovement));
+    }
+
+    #[tokio::test]
+    async fn per_trial_timeout_fails_slow_trials_and_continues() {
+        let space = SearchSpace::new().add_int("unused", 1, 2);
+        let config = OptimizationConfig::new("timeouts".into(), space, "grid")
+            .with_concurrency(2)
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(ma_base_backtest())
+            .with_stopping(StoppingCriteria {
+                per_trial_timeout: Some(std::time::Duration::from_millis(100)),
+                ..Default::default()
+            });
+
+        let started = Instant::now();
+        let mut runner = slow_runner();
+        let trials = runner.run(config).await.unwrap();
+
+        // Uncancelled, each trial would sleep through 180 day ends.
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+        assert_eq!(trials.len(), 2);
+        for trial in &trials {
+            assert_eq!(trial.status, TrialStatus::Failed);
+            assert!(trial.error.as_deref().unwrap().contains("timeout"));
+        }
+        let status = runner.status().unwrap();
+        assert_eq!(status.state, OptimizationState::Completed);
+        assert_eq!(status.trials_failed, 2);
+        assert_eq!(status.stop_reason, None);
+    }
+
+    #[tokio::test]
+    async fn max_duration_cancels_trials_in_flight() {
+        let space = SearchSpace::new().add_int("unused", 1, 10);
+        let config = OptimizationConfig::new("time budget".into(), space, "grid")
+            .with_concurrency(2)
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(ma_base_backtest())
+            .with_stopping(StoppingCriteria {
+                max_duration: Some(std::time::Duration::from_millis(150)),
+                ..Default::default()
+            });
+
+        let started = Instant::now();
+        let mut runner = slow_runner();
+        let trials = runner.run(config).await.unwrap();
+
+        assert!(started.elapsed() < std::time::Duration::from_secs(3));
+        assert_eq!(trials.len(), 2);
+        assert!(trials
+            .iter()
+            .all(|trial| trial.status == TrialStatus::Cancelled));
+        let status = runner.status().unwrap();
+        assert_eq!(status.state, OptimizationState::Completed);
+        assert_eq!(status.stop_reason, Some(StopReason::MaxDuration));
+        assert_eq!(status.trials_running, 0);
+        assert_eq!(status.trials_completed + status.trials_failed, 0);
+    }
+
+    #[tokio::test]
+    async fn kfold_objective_is_the_mean_of_fold_objectives() {
+        let space = SearchSpace::new().add_int("short_period", 3, 4);
+        let config = OptimizationConfig::new("folds".into(), space, "grid")
+            .with_concurrency(2)
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(ma_base_backtest())
+            .with_evaluation(EvaluationMode::KFoldTime { folds: 3 });
+
+        let mut runner = OptimizationRunner::new();
+        let trials = runner.run(config.clone()).await.unwrap();
+
+        let expected_folds = config
+            .evaluation
+            .folds(&trial_backtest_config(&config.base_backtest, &HashMap::new()).unwrap())
+            .unwrap();
+        assert_eq!(trials.len(), 2);
+        for trial in &trials {
+            let result = trial.result.as_ref().unwrap();
+            let folds: Vec<Fold> = result.folds.iter().map(|fold| fold.fold).collect();
+            assert_eq!(folds, expected_folds);
+
+            let mean = result.folds.iter().map(|fold| fold.objective).sum::<f64>() / 3.0;
+            assert!((result.objective - mean).abs() < 1e-12);
+            assert!((result.metrics["total_return"] - mean).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn suggestion_cache_tolerates_float_rounding() {
+        let mut cache = SuggestionCache::default();
+        let params = |x: f64| {
+            HashMap::from([
+                ("n".to_string(), ParameterValue::Int(3)),
+                ("x".to_string(), ParameterValue::Float(x)),
+            ])
+        };
+        assert!(cache.insert(&params(0.1 + 0.2)));
+        assert!(!cache.insert(&params(0.3)));
+        assert!(cache.insert(&params(0.31)));
+    }
+
+    #[tokio::test]
+    async fn random_search_evaluates_each_combination_once() {
+        let space = SearchSpace::new()
+            .add_int("a", 1, 3)
+            .add_choice("b", vec![serde_json::json!("x"), serde_json::json!("y")]);
+        let config = |allow_duplicates: bool| {
+            let mut config = OptimizationConfig::new("dedup".into(), space.clone(), "random")
+                .with_max_trials(20)
+                .with_concurrency(2)
+                .with_seed(7);
+            config.allow_duplicate_trials = allow_duplicates;
+            config
+        };
+        let run = |config: OptimizationConfig| async move {
+            let mut runner =
+                OptimizationRunner::with_strategy_factory(|_| Err("not evaluated".to_string()));
+            runner.run(config).await.unwrap()
+        };
+
+        let trials = run(config(false)).await;
+        assert_eq!(trials.len(), 6);
+        for (i, a) in trials.iter().enumerate() {
+            for b in &trials[i + 1..] {
+                assert!(!same_parameters(&a.parameters, &b.parameters));
+            }
+        }
+
+        assert_eq!(run(config(true)).await.len(), 20);
+    }
+
+    #[tokio::test]
+    async fn unknown_search_strategy_fails_the_run() {
+        let config = OptimizationConfig::new("bad".into(), SearchSpace::new(), "annealing");
+        let mut runner = OptimizationRunner::new();
+
+        assert!(runner.run(config).await.is_err());
+        assert_eq!(runner.status().unwrap().state, OptimizationState::Failed);
+    }
+
+    #[tokio::test]
+    async fn grid_sweep_over_ma_crossover_finds_best_trial() {
+        let space = SearchSpace::new()
+            .add_int("short_period", 3, 5)
+            .add_int("long_period", 15, 20);
+        let config = OptimizationConfig::new("ma sweep".into(), space, "grid")
+            .with_max_trials(8)
+            .with_concurrency(3)
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(ma_base_backtest());
+
+        let mut runner = OptimizationRunner::new();
+        let trials = runner.run(config).await.unwrap();
+
+        assert_eq!(trials.len(), 8);
+        assert!(trials
+            .iter()
+            .all(|trial| trial.status == TrialStatus::Completed));
+        let status = runner.status().unwrap();
+        assert_eq!(status.state, OptimizationState::Completed);
+        assert_eq!(status.trials_completed, 8);
+        assert_eq!(status.trials_running, 0);
+
+        let best = status.best_trial.as_ref().unwrap();
+        let top = trials
+            .iter()
+            .filter_map(|trial| trial.result.as_ref())
+            .map(|result| result.objective)
+            .fold(f64::NEG_INFINITY, f64::max);
+        assert_eq!(best.objective, top);
+        assert!(best.metrics.contains_key("max_drawdown"));
+    }
+
+    #[tokio::test]
+    async fn failing_trials_do_not_stop_the_run() {
+        let space = SearchSpace::new().add_int("short_period", 3, 4);
+        let mut base = ma_base_backtest();
+        base["symbols"] = serde_json::to_value(vec![Symbol::equity("NOPE")]).unwrap();
+        let config = OptimizationConfig::new("missing data".into(), space, "grid")
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(base);
+
+        let observed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = observed.clone();
+        let mut runner = OptimizationRunner::with_strategy_factory(builtin_strategy)
+            .with_trial_observer(move |trial, status| {
+                seen.lock()
+                    .unwrap()
+                    .push((trial.trial_number, trial.status, status.trials_failed));
+            });
+        let trials = runner.run(config).await.unwrap();
+
+        assert_eq!(trials.len(), 2);
+        assert!(trials
+            .iter()
+            .all(|trial| trial.status == TrialStatus::Failed));
+        let status = runner.status().unwrap();
+        assert_eq!(status.state, OptimizationState::Completed);
+        assert_eq!(status.trials_failed, 2);
+        assert!(status.best_trial.is_none());
+
+        // The observer sees each trial once, after it is counted.
+        let mut observed = observed.lock().unwrap().clone();
+        observed.sort_by_key(|(number, _, _)| *number);
+        assert_eq!(
+            observed
+                .iter()
+                .map(|(number, status, _)| (*number, *status))
+                .collect::<Vec<_>>(),
+            vec![(0, TrialStatus::Failed), (1, TrialStatus::Failed)]
+        );
+        let mut counts: Vec<_> = observed.iter().map(|(_, _, failed)| *failed).collect();
+        counts.sort_unstable();
+        assert_eq!(counts, vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn interrupted_grid_run_resumes_without_repeating_trials() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = dir.path().join("optimizer.db");
+        let space = SearchSpace::new()
+            .add_int("short_period", 3, 5)
+            .add_int("long_period", 15, 18);
+        let config = OptimizationConfig::new("resumable".into(), space, "grid")
+            .with_max_trials(10)
+            .with_concurrency(2)
+            .with_objective("total_return", ObjectiveDirection::Maximize)
+            .with_base_backtest(ma_base_backtest());
+        let optimization_id = config.id;
+
+        // Stop the first runner once it has started four trials.
+        let started = Arc::new(AtomicUsize::new(0));
+        let stop = Arc::new(OnceLock::<CancellationToken>::new());
+        let mut runner = {
+            let started = started.clone();
+            let stop = stop.clone();
+            OptimizationRunner::with_strategy_factory(move |strategy_config| {
+                if started.fetch_add(1, Ordering::SeqCst) + 1 == 4 {
+                    stop.get().unwrap().cancel();
+                }
+                builtin_strategy(strategy_config)
+            })
+        }
+        .with_store(OptimizationStore::open(&db).unwrap());
+        stop.set(runner.shutdown_token()).unwrap();
+
+        let first = runner.run(config).await.unwrap();
+        assert_eq!(first.len(), 4);
+        assert_eq!(runner.status().unwrap().state, OptimizationState::Cancelled);
+        drop(runner);
+
+        let mut runner =
+            OptimizationRunner::new().with_store(OptimizationStore::open(&db).unwrap());
+        let trials = runner.resume(optimization_id).await.unwrap();
+
+        assert_eq!(trials.len(), 10);
+        for (i, a) in trials.iter().enumerate() {
+            for b in &trials[i + 1..] {
+                assert!(!same_parameters(&a.parameters, &b.parameters));
+            }
+        }
+        let status = runner.status().unwrap();
+        assert_eq!(status.state, OptimizationState::Completed);
+        assert_eq!(status.trials_completed + status.trials_failed, 10);
+        assert!(status.best_trial.is_some());
+
+        let stored = runner
+            .store()
+            .unwrap()
+            .load_trials(optimization_id)
+            .unwrap();
+        assert_eq!(stored.len(), 10);
+        assert_eq!(
+            runner
+                .store()
+                .unwrap()
+                .load_status(optimization_id)
+                .unwrap()
+                .unwrap()
+                .state,
+            OptimizationState::Completed
+        );
+    }
+}
@@ -1,8 +1,11 @@
 //! Search space definitions and parameter sweep strategies.
 
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+
+use crate::surrogate;
 
 /// A single parameter dimension in the search space.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -28,10 +31,11 @@ pub enum ParameterKind {
 
 /// A concrete parameter value produced by a search strategy.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+// `Int` must precede `Float` so integers round-trip through JSON unchanged.
 #[serde(untagged)]
 pub enum ParameterValue {
-    Float(f64),
     Int(i64),
+    Float(f64),
     Json(serde_json::Value),
 }
 
@@ -45,6 +49,28 @@ impl std::fmt::Display for ParameterValue {
     }
 }
 
+impl ParameterValue {
+    /// Equality that tolerates the rounding a float picks up when stored.
+    pub fn approx_eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Float(a), Self::Float(b)) => {
+                (a - b).abs() <= 1e-9 * a.abs().max(b.abs()).max(1.0)
+            }
+            _ => self == other,
+        }
+    }
+}
+
+/// Whether two parameter maps assign the same values to the same names.
+pub fn same_parameters(
+    a: &HashMap<String, ParameterValue>,
+    b: &HashMap<String, ParameterValue>,
+) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .all(|(name, value)| b.get(name).is_some_and(|other| value.approx_eq(other)))
+}
+
 /// The full search space: an ordered list of parameter definitions.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SearchSpace {
@@ -92,12 +118,14 @@ impl SearchSpace {
 
     /// Total number of grid points (returns `None` if any parameter is
     /// continuous without a natural grid).
+    /// Also `None` if the total overflows `usize`.
     pub fn grid_size(&self) -> Option<usize> {
         let mut total: usize = 1;
         for param in &self.parameters {
             let dim_size = match &param.kind {
-                ParameterKind::IntRange { low, high } => (high - low + 1) as usize,
-                ParameterKind::Choice { values } => values.len(),
+                ParameterKind::IntRange { .. } | ParameterKind::Choice { .. } => {
+                    axis_len(param, 0)?
+                }
                 // Continuous dimensions need explicit step count — not grid-able by default.
                 _ => return None,
             };
@@ -125,6 +153,42 @@ pub trait SearchStrategy: Send + Sync {
     /// Report completed trial results so adaptive strategies can learn.
     fn report(&mut self, _params: &HashMap<String, ParameterValue>, _objective: f64) {}
 
+    /// Mark `params` as already evaluated, e.g. by a resumed run, so that
+    /// exhaustive strategies don't suggest it again.
+    fn exclude(&mut self, _params: &HashMap<String, ParameterValue>) {}
+
+    /// Like [`suggest`](Self::suggest), paired with the fraction of the full
+    /// evaluation budget, in `(0, 1]`, each combination should get. An empty
+    /// batch while earlier suggestions are still being evaluated means the
+    /// strategy is waiting on their results, not that it is exhausted.
+    fn suggest_with_budget(&mut self, count: usize) -> Vec<(HashMap<String, ParameterValue>, f64)> {
+        self.suggest(count)
+            .into_iter()
+            .map(|params| (params, 1.0))
+            .collect()
+    }
+
+    /// Report a result evaluated at `budget`.
+    fn report_with_budget(
+        &mut self,
+        params: &HashMap<String, ParameterValue>,
+        objective: f64,
+        _budget: f64,
+    ) {
+        self.report(params, objective);
+    }
+
+    /// Report a suggestion whose evaluation failed.
+    fn report_failure(&mut self, _params: &HashMap<String, ParameterValue>, _budget: f64) {}
+
+    /// Whether a runner should ask for a replacement when this strategy
+    /// suggests a parameter set it has already evaluated. Strategies that
+    /// never repeat themselves, or that wait on every suggestion's result,
+    /// opt out.
+    fn deduplicate(&self) -> bool {
+        true
+    }
+
     /// Human-readable strategy name.
     fn name(&self) -> &str;
 }
@@ -132,95 +196,134 @@ pub trait SearchStrategy: Send + Sync {
 // ---- Grid search ----
 
 /// Exhaustive grid search over discrete parameter combinations.
+///
+/// Combinations are enumerated in a fixed order: the first parameter of the
+/// space varies slowest and the last fastest, integer ranges ascend, choices
+/// keep their listed order, and continuous ranges take `float_steps` evenly
+/// spaced values from `low` to `high` inclusive (log-spaced for log-uniform
+/// ranges).
+///
+/// Combinations are produced on demand from a mixed-radix counter, so the
+/// grid is never held in memory and the position within it is a single
+/// [`cursor`](Self::cursor).
 #[derive(Debug, Clone)]
 pub struct GridSearch {
-    #[allow(dead_code)]
     space: SearchSpace,
     /// Number of steps for continuous dimensions.
-    #[allow(dead_code)]
     float_steps: usize,
+    /// Number of combinations, `None` if it overflows `usize`.
+    total: Option<usize>,
     cursor: usize,
-    combos: Vec<HashMap<String, ParameterValue>>,
+    /// Combinations still ahead of the cursor that must be skipped.
+    excluded: Vec<HashMap<String, ParameterValue>>,
 }
 
 impl GridSearch {
     pub fn new(space: SearchSpace, float_steps: usize) -> Self {
-        let combos = Self::build_grid(&space, float_steps);
+        let total = space.parameters.iter().try_fold(1usize, |total, param| {
+            total.checked_mul(axis_len(param, float_steps)?)
+        });
         Self {
             space,
             float_steps,
+            total,
             cursor: 0,
-            combos,
+            excluded: Vec::new(),
         }
     }
 
-    fn build_grid(space: &SearchSpace, float_steps: usize) -> Vec<HashMap<String, ParameterValue>> {
-        let mut axes: Vec<Vec<(&str, ParameterValue)>> = Vec::new();
+    /// Number of combinations in the grid, `None` if it overflows `usize`.
+    pub fn total(&self) -> Option<usize> {
+        self.total
+    }
 
-        for param in &space.parameters {
-            let values: Vec<ParameterValue> = match &param.kind {
-                ParameterKind::FloatRange { low, high } => {
-                    let steps = float_steps.max(2);
-                    (0..steps)
-                        .map(|i| {
-                            let t = i as f64 / (steps - 1) as f64;
-                            ParameterValue::Float(low + t * (high - low))
-                        })
-                        .collect()
-                }
-                ParameterKind::IntRange { low, high } => {
-                    (*low..=*high).map(ParameterValue::Int).collect()
-                }
-                ParameterKind::LogUniform { low, high } => {
-                    let steps = float_steps.max(2);
-                    let log_low = low.ln();
-                    let log_high = high.ln();
-                    (0..steps)
-                        .map(|i| {
-                            let t = i as f64 / (steps - 1) as f64;
-                            ParameterValue::Float((log_low + t * (log_high - log_low)).exp())
-                        })
-                        .collect()
-                }
-                ParameterKind::Choice { values } => values
-                    .iter()
-                    .map(|v| ParameterValue::Json(v.clone()))
-                    .collect(),
-            };
-            axes.push(
-                values
-                    .into_iter()
-                    .map(|v| (param.name.as_str(), v))
-                    .collect(),
+    /// Index of the next combination to suggest.
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Continue enumerating from `cursor`.
+    pub fn with_cursor(mut self, cursor: usize) -> Self {
+        self.cursor = cursor;
+        self
+    }
+
+    /// The combination at `index`; the last parameter varies fastest.
+    fn combination(&self, mut index: usize) -> HashMap<String, ParameterValue> {
+        let mut combo = HashMap::with_capacity(self.space.parameters.len());
+        for param in self.space.parameters.iter().rev() {
+            let len = axis_len(param, self.float_steps).unwrap_or(usize::MAX);
+            combo.insert(
+                param.name.clone(),
+                axis_value(param, self.float_steps, index % len),
             );
+            index /= len;
         }
+        combo
+    }
+}
 
-        // Cartesian product
-        let mut result: Vec<HashMap<String, ParameterValue>> = vec![HashMap::new()];
-        for axis in &axes {
-            let mut next = Vec::with_capacity(result.len() * axis.len());
-            for existing in &result {
-                for (name, value) in axis {
-                    let mut combo = existing.clone();
-                    combo.insert(name.to_string(), value.clone());
-                    next.push(combo);
-                }
-            }
-            result = next;
+/// Number of grid values along `param`, `None` if it overflows `usize`.
+fn axis_len(param: &ParameterDef, float_steps: usize) -> Option<usize> {
+    match &param.kind {
+        ParameterKind::FloatRange { .. } | ParameterKind::LogUniform { .. } => {
+            Some(float_steps.max(2))
+        }
+        ParameterKind::IntRange { low, high } if high < low => Some(0),
+        ParameterKind::IntRange { low, high } => {
+            usize::try_from(high.abs_diff(*low)).ok()?.checked_add(1)
         }
+        ParameterKind::Choice { values } => Some(values.len()),
+    }
+}
 
-        result
+/// The `index`-th grid value along `param`.
+fn axis_value(param: &ParameterDef, float_steps: usize, index: usize) -> ParameterValue {
+    let steps = float_steps.max(2);
+    let t = index as f64 / (steps - 1) as f64;
+    match &param.kind {
+        ParameterKind::FloatRange { low, high } => ParameterValue::Float(low + t * (high - low)),
+        ParameterKind::IntRange { low, .. } => {
+            ParameterValue::Int(low.wrapping_add_unsigned(index as u64))
+        }
+        ParameterKind::LogUniform { low, high } => {
+            let log_low = low.ln();
+            let log_high = high.ln();
+            ParameterValue::Float((log_low + t * (log_high - log_low)).exp())
+        }
+        ParameterKind::Choice { values } => ParameterValue::Json(values[index].clone()),
     }
 }
 
 impl SearchStrategy for GridSearch {
     fn suggest(&mut self, count: usize) -> Vec<HashMap<String, ParameterValue>> {
-        let end = (self.cursor + count).min(self.combos.len());
-        let batch = self.combos[self.cursor..end].to_vec();
-        self.cursor = end;
+        let total = self.total.unwrap_or(usize::MAX);
+        let mut batch = Vec::with_capacity(count.min(total.saturating_sub(self.cursor)));
+        while batch.len() < count && self.cursor < total {
+            let combo = self.combination(self.cursor);
+            self.cursor += 1;
+            match self
+                .excluded
+                .iter()
+                .position(|excluded| same_parameters(excluded, &combo))
+            {
+                Some(skipped) => {
+                    self.excluded.swap_remove(skipped);
+                }
+                None => batch.push(combo),
+            }
+        }
         batch
     }
 
+    fn exclude(&mut self, params: &HashMap<String, ParameterValue>) {
+        self.excluded.push(params.clone());
+    }
+
+    fn deduplicate(&self) -> bool {
+        false
+    }
+
     fn name(&self) -> &str {
         "grid"
     }
@@ -232,40 +335,47 @@ impl SearchStrategy for GridSearch {
 #[derive(Debug, Clone)]
 pub struct RandomSearch {
     space: SearchSpace,
+    rng: StdRng,
 }
 
 impl RandomSearch {
     pub fn new(space: SearchSpace) -> Self {
-        Self { space }
+        Self {
+            space,
+            rng: StdRng::from_os_rng(),
+        }
     }
 
-    fn sample_one(&self) -> HashMap<String, ParameterValue> {
-        let mut rng = rand::thread_rng();
-        let mut params = HashMap::new();
+    /// Draw from a reproducible sequence.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
 
-        for param in &self.space.parameters {
-            let value = match &param.kind {
-                ParameterKind::FloatRange { low, high } => {
-                    ParameterValue::Float(rng.gen_range(*low..=*high))
-                }
-                ParameterKind::IntRange { low, high } => {
-                    ParameterValue::Int(rng.gen_range(*low..=*high))
-                }
-                ParameterKind::LogUniform { low, high } => {
-                    let log_low = low.ln();
-                    let log_high = high.ln();
-                    let log_val: f64 = rng.gen_range(log_low..=log_high);
-                    ParameterValue::Float(log_val.exp())
-                }
-                ParameterKind::Choice { values } => {
-                    let idx = rng.gen_range(0..values.len());
-                    ParameterValue::Json(values[idx].clone())
-                }
-            };
-            params.insert(param.name.clone(), value);
-        }
+    fn sample_one(&mut self) -> HashMap<String, ParameterValue> {
+        self.space
+            .parameters
+            .iter()
+            .map(|param| (param.name.clone(), sample_parameter(param, &mut self.rng)))
+            .collect()
+    }
+}
 
-        params
+fn sample_parameter(param: &ParameterDef, rng: &mut impl Rng) -> ParameterValue {
+    match &param.kind {
+        ParameterKind::FloatRange { low, high } => {
+            ParameterValue::Float(rng.random_range(*low..=*high))
+        }
+        ParameterKind::IntRange { low, high } => {
+            ParameterValue::Int(rng.random_range(*low..=*high))
+        }
+        ParameterKind::LogUniform { low, high } => {
+            let log_val: f64 = rng.random_range(low.ln()..=high.ln());
+            ParameterValue::Float(log_val.exp())
+        }
+        ParameterKind::Choice { values } => {
+            ParameterValue::Json(values[rng.random_range(0..values.len())].clone())
+        }
     }
 }
 
@@ -279,49 +389,86 @@ impl SearchStrategy for RandomSearch {
     }
 }
 
-// ---- Bayesian search (surrogate-model stub) ----
+// ---- Bayesian search ----
+
+/// Surrogate model behind [`BayesianSearch`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SurrogateKind {
+    /// Perturb the best observation seen so far.
+    Perturbation,
+    /// Gaussian process with an RBF kernel, maximizing expected improvement
+    /// over the numeric dimensions. Choices are picked by comparing how
+    /// often each appears among the best and the remaining observations.
+    #[default]
+    GaussianProcess,
+}
+
+/// Most observations the Gaussian process is fitted to; past this the best
+/// half and the most recent half are kept.
+const MAX_SURROGATE_OBSERVATIONS: usize = 200;
+
+/// Random candidates scored by expected improvement per suggestion.
+const GP_CANDIDATES: usize = 512;
+
+/// Candidates drawn near the best observation per suggestion.
+const GP_LOCAL_CANDIDATES: usize = 64;
 
-/// Bayesian optimization using a simple surrogate model.
+/// Bayesian optimization over a surrogate of the objective.
 ///
-/// This implementation tracks observed (params, objective) pairs and uses them
-/// to bias future sampling toward promising regions.  A full Gaussian-process
-/// backend can be plugged in via the `report` method; the default uses a
-/// weighted-random heuristic.
+/// Tracks observed (params, objective) pairs and, once there are enough of
+/// them, suggests the point the surrogate expects to improve on the best
+/// one most. With probability `exploration_weight` a suggestion is drawn
+/// uniformly at random instead.
 #[derive(Debug, Clone)]
 pub struct BayesianSearch {
     space: SearchSpace,
     observations: Vec<(HashMap<String, ParameterValue>, f64)>,
     exploration_weight: f64,
+    surrogate: SurrogateKind,
+    rng: StdRng,
 }
 
 impl BayesianSearch {
-    pub fn new(space: SearchSpace, exploration_weight: f64) -> Self {
+    pub fn new(space: SearchSpace, exploration_weight: f64, surrogate: SurrogateKind) -> Self {
         Self {
             space,
             observations: Vec::new(),
             exploration_weight,
+            surrogate,
+            rng: StdRng::from_os_rng(),
         }
     }
 
+    /// Draw from a reproducible sequence.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
     /// Pure exploration sample (same as random).
-    fn explore(&self) -> HashMap<String, ParameterValue> {
-        let random = RandomSearch::new(self.space.clone());
-        random.sample_one()
+    fn explore(&mut self) -> HashMap<String, ParameterValue> {
+        self.space
+            .parameters
+            .iter()
+            .map(|param| (param.name.clone(), sample_parameter(param, &mut self.rng)))
+            .collect()
     }
 
-    /// Exploitation: perturb the best-known point.
-    fn exploit(&self) -> HashMap<String, ParameterValue> {
-        let best = self
-            .observations
+    fn best(&self) -> Option<&(HashMap<String, ParameterValue>, f64)> {
+        self.observations
             .iter()
-            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+    }
 
-        let base = match best {
+    /// Exploitation: perturb the best-known point.
+    fn exploit(&mut self) -> HashMap<String, ParameterValue> {
+        let base = match self.best() {
             Some((params, _)) => params.clone(),
             None => return self.explore(),
         };
 
-        let mut rng = rand::thread_rng();
+        let rng = &mut self.rng;
         let mut perturbed = HashMap::new();
 
         for param in &self.space.parameters {
@@ -329,45 +476,230 @@ impl BayesianSearch {
             let value = match (&param.kind, base_val) {
                 (ParameterKind::FloatRange { low, high }, Some(ParameterValue::Float(v))) => {
                     let range = high - low;
-                    let noise = rng.gen_range(-0.1..0.1) * range;
+                    let noise = rng.random_range(-0.1..0.1) * range;
                     ParameterValue::Float((v + noise).clamp(*low, *high))
                 }
                 (ParameterKind::IntRange { low, high }, Some(ParameterValue::Int(v))) => {
-                    let delta: i64 = rng.gen_range(-2..=2);
+                    let delta: i64 = rng.random_range(-2..=2);
                     ParameterValue::Int((v + delta).clamp(*low, *high))
                 }
                 (ParameterKind::LogUniform { low, high }, Some(ParameterValue::Float(v))) => {
                     let log_v = v.ln();
                     let log_range = high.ln() - low.ln();
-                    let noise = rng.gen_range(-0.1..0.1) * log_range;
+                    let noise = rng.random_range(-0.1..0.1) * log_range;
                     ParameterValue::Float((log_v + noise).exp().clamp(*low, *high))
                 }
-                _ => {
-                    // Fall back to random for choices or missing base
-                    RandomSearch::new(SearchSpace {
-                        parameters: vec![param.clone()],
-                    })
-                    .sample_one()
-                    .remove(&param.name)
-                    .unwrap_or(ParameterValue::Int(0))
-                }
+                // Fall back to random for choices or missing base
+                _ => sample_parameter(param, rng),
             };
             perturbed.insert(param.name.clone(), value);
         }
 
         perturbed
     }
+
+    /// Numeric dimensions the Gaussian process models.
+    fn numeric_parameters(&self) -> Vec<&ParameterDef> {
+        self.space
+            .parameters
+            .iter()
+            .filter(|param| !matches!(param.kind, ParameterKind::Choice { .. }))
+            .collect()
+    }
+
+    /// Observations the surrogate is fitted to, capped at
+    /// [`MAX_SURROGATE_OBSERVATIONS`].
+    fn surrogate_observations(&self) -> Vec<&(HashMap<String, ParameterValue>, f64)> {
+        let n = self.observations.len();
+        if n <= MAX_SURROGATE_OBSERVATIONS {
+            return self.observations.iter().collect();
+        }
+        let half = MAX_SURROGATE_OBSERVATIONS / 2;
+        let mut by_objective: Vec<usize> = (0..n).collect();
+        by_objective.sort_by(|&a, &b| self.observations[b].1.total_cmp(&self.observations[a].1));
+        let mut keep: Vec<usize> = by_objective[..half].to_vec();
+        let recent: Vec<usize> = (0..n)
+            .rev()
+            .filter(|i| !keep.contains(i))
+            .take(half)
+            .collect();
+        keep.extend(recent);
+        keep.into_iter().map(|i| &self.observations[i]).collect()
+    }
+
+    /// Maximize expected improvement over the numeric dimensions.
+    fn gp_suggest(&mut self) -> HashMap<String, ParameterValue> {
+        let observations: Vec<(HashMap<String, ParameterValue>, f64)> =
+            self.surrogate_observations().into_iter().cloned().collect();
+        let numeric: Vec<ParameterDef> = self.numeric_parameters().into_iter().cloned().collect();
+        let encoded: Vec<(Vec<f64>, f64)> = observations
+            .iter()
+            .filter_map(|(params, objective)| {
+                let x = numeric
+                    .iter()
+                    .map(|param| encode(param, params.get(&param.name)?))
+                    .collect::<Option<Vec<f64>>>()?;
+                objective.is_finite().then_some((x, *objective))
+            })
+            .collect();
+        let choices = self.choose_categories(&observations);
+
+        let (xs, ys): (Vec<Vec<f64>>, Vec<f64>) = encoded.into_iter().unzip();
+        let Some(gp) = surrogate::GaussianProcess::fit(&xs, &ys) else {
+            let mut params = self.explore();
+            params.extend(choices);
+            return params;
+        };
+        let best_index = (0..ys.len())
+            .max_by(|&a, &b| ys[a].total_cmp(&ys[b]))
+            .unwrap_or_default();
+        let best = ys[best_index];
+        let xi = 0.01 * gp.predict(&xs[best_index]).1.max(f64::EPSILON);
+
+        let dims = numeric.len();
+        let rng = &mut self.rng;
+        let mut candidates: Vec<Vec<f64>> = (0..GP_CANDIDATES)
+            .map(|_| (0..dims).map(|_| rng.random_range(0.0..=1.0)).collect())
+            .collect();
+        candidates.extend((0..GP_LOCAL_CANDIDATES).map(|_| {
+            xs[best_index]
+                .iter()
+                .map(|u| (u + rng.random_range(-0.05..=0.05)).clamp(0.0, 1.0))
+                .collect::<Vec<f64>>()
+        }));
+        let chosen = candidates
+            .into_iter()
+            .map(|x| {
+                // Score integer dimensions where they will actually land.
+                let x: Vec<f64> = numeric
+                    .iter()
+                    .zip(&x)
+                    .map(|(param, u)| encode(param, &decode(param, *u)).unwrap_or(*u))
+                    .collect();
+                let score = gp.expected_improvement(&x, best, xi);
+                (score, x)
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, x)| x)
+            .unwrap_or_default();
+
+        let mut params: HashMap<String, ParameterValue> = numeric
+            .iter()
+            .zip(chosen)
+            .map(|(param, u)| (param.name.clone(), decode(param, u)))
+            .collect();
+        params.extend(choices);
+        params
+    }
+
+    /// Pick each categorical parameter by sampling choices in proportion to
+    /// how much more often they occur in the best quarter of observations
+    /// than in the rest (Laplace-smoothed).
+    fn choose_categories(
+        &mut self,
+        observations: &[(HashMap<String, ParameterValue>, f64)],
+    ) -> HashMap<String, ParameterValue> {
+        let mut ranked: Vec<&(HashMap<String, ParameterValue>, f64)> =
+            observations.iter().collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+        let good = ranked.len().div_ceil(4);
+        let (top, rest) = ranked.split_at(good);
+
+        let mut chosen = HashMap::new();
+        for param in &self.space.parameters {
+            let ParameterKind::Choice { values } = &param.kind else {
+                continue;
+            };
+            let frequency = |group: &[&(HashMap<String, ParameterValue>, f64)], value| {
+                let hits = group
+                    .iter()
+                    .filter(|(params, _)| {
+                        matches!(params.get(&param.name), Some(ParameterValue::Json(v)) if v == value)
+                    })
+                    .count();
+                (hits as f64 + 1.0) / (group.len() + values.len()) as f64
+            };
+            let weights: Vec<f64> = values
+                .iter()
+                .map(|value| frequency(top, value) / frequency(rest, value))
+                .collect();
+            let mut pick = self.rng.random_range(0.0..weights.iter().sum::<f64>());
+            let index = weights
+                .iter()
+                .position(|weight| {
+                    pick -= weight;
+                    pick < 0.0
+                })
+                .unwrap_or(values.len() - 1);
+            chosen.insert(
+                param.name.clone(),
+                ParameterValue::Json(values[index].clone()),
+            );
+        }
+        chosen
+    }
+
+    /// Observations needed before the surrogate is consulted.
+    fn warmup(&self) -> usize {
+        match self.surrogate {
+            SurrogateKind::Perturbation => 1,
+            SurrogateKind::GaussianProcess => self.numeric_parameters().len() + 2,
+        }
+    }
+}
+
+/// Position of `value` within `param`'s range, from 0 to 1.
+fn encode(param: &ParameterDef, value: &ParameterValue) -> Option<f64> {
+    let value = match value {
+        ParameterValue::Float(v) => *v,
+        ParameterValue::Int(v) => *v as f64,
+        ParameterValue::Json(_) => return None,
+    };
+    let (low, high, value) = match &param.kind {
+        ParameterKind::FloatRange { low, high } => (*low, *high, value),
+        ParameterKind::IntRange { low, high } => (*low as f64, *high as f64, value),
+        ParameterKind::LogUniform { low, high } => (low.ln(), high.ln(), value.ln()),
+        ParameterKind::Choice { .. } => return None,
+    };
+    Some(if high > low {
+        ((value - low) / (high - low)).clamp(0.0, 1.0)
+    } else {
+        0.5
+    })
+}
+
+/// Inverse of [`encode`] for numeric parameters.
+fn decode(param: &ParameterDef, u: f64) -> ParameterValue {
+    let u = u.clamp(0.0, 1.0);
+    match &param.kind {
+        ParameterKind::FloatRange { low, high } => ParameterValue::Float(low + u * (high - low)),
+        ParameterKind::IntRange { low, high } => {
+            let value = *low as f64 + u * (*high - *low) as f64;
+            ParameterValue::Int((value.round() as i64).clamp(*low, *high))
+        }
+        ParameterKind::LogUniform { low, high } => ParameterValue::Float(
+            (low.ln() + u * (high.ln() - low.ln()))
+                .exp()
+                .clamp(*low, *high),
+        ),
+        ParameterKind::Choice { values } => {
+            ParameterValue::Json(values.first().cloned().unwrap_or_default())
+        }
+    }
 }
 
 impl SearchStrategy for BayesianSearch {
     fn suggest(&mut self, count: usize) -> Vec<HashMap<String, ParameterValue>> {
-        let mut rng = rand::thread_rng();
         (0..count)
             .map(|_| {
-                if self.observations.is_empty() || rng.gen::<f64>() < self.exploration_weight {
-                    self.explore()
-                } else {
-                    self.exploit()
+                if self.observations.len() < self.warmup()
+                    || self.rng.random::<f64>() < self.exploration_weight
+                {
+                    return self.explore();
+                }
+                match self.surrogate {
+                    SurrogateKind::Perturbation => self.exploit(),
+                    SurrogateKind::GaussianProcess => self.gp_suggest(),
                 }
             })
             .collect()
@@ -382,6 +714,175 @@ impl SearchStrategy for BayesianSearch {
     }
 }
 
+// ---- Hyperband ----
+
+/// Hyperband: successive halving over brackets of randomly sampled
+/// combinations.
+///
+/// Each bracket evaluates many combinations at a small budget, promotes the
+/// best `1 / reduction_factor` of them to a budget `reduction_factor` times
+/// larger, and repeats until the survivors run at the full budget. Later
+/// brackets start fewer combinations at larger budgets, hedging against
+/// small budgets being poor predictors. A rung is promoted only once all of
+/// its results are reported.
+#[derive(Debug, Clone)]
+pub struct HyperbandSearch {
+    space: SearchSpace,
+    reduction_factor: usize,
+    /// Number of halvings in the most aggressive bracket.
+    max_halvings: usize,
+    /// Current bracket, counting down to 0 (full budget only).
+    bracket: usize,
+    /// Combinations started in the current bracket.
+    bracket_size: usize,
+    rung: usize,
+    queue: VecDeque<HashMap<String, ParameterValue>>,
+    outstanding: usize,
+    results: Vec<(HashMap<String, ParameterValue>, f64)>,
+    finished: bool,
+    rng: StdRng,
+}
+
+impl HyperbandSearch {
+    /// `min_budget` is the smallest fraction of the full budget a
+    /// combination is evaluated with; `reduction_factor` (at least 2) is how
+    /// aggressively each rung is pruned.
+    pub fn new(space: SearchSpace, min_budget: f64, reduction_factor: usize) -> Self {
+        let reduction_factor = reduction_factor.max(2);
+        let min_budget = min_budget.clamp(f64::MIN_POSITIVE, 1.0);
+        let max_halvings =
+            ((1.0 / min_budget).ln() / (reduction_factor as f64).ln() + 1e-9).floor() as usize;
+        let mut search = Self {
+            space,
+            reduction_factor,
+            max_halvings,
+            bracket: max_halvings,
+            bracket_size: 0,
+            rung: 0,
+            queue: VecDeque::new(),
+            outstanding: 0,
+            results: Vec::new(),
+            finished: false,
+            rng: StdRng::from_os_rng(),
+        };
+        search.start_bracket();
+        search
+    }
+
+    /// Draw from a reproducible sequence. Call before the first suggestion.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self.start_bracket();
+        self
+    }
+
+    /// Budget of the current rung.
+    fn budget(&self) -> f64 {
+        let halvings_left = (self.bracket - self.rung) as i32;
+        (self.reduction_factor as f64).powi(-halvings_left)
+    }
+
+    fn start_bracket(&mut self) {
+        let eta = self.reduction_factor as f64;
+        let size = ((self.max_halvings + 1) as f64 / (self.bracket + 1) as f64
+            * eta.powi(self.bracket as i32))
+        .ceil() as usize;
+        self.bracket_size = size;
+        self.rung = 0;
+        self.results.clear();
+        self.queue = (0..size)
+            .map(|_| {
+                self.space
+                    .parameters
+                    .iter()
+                    .map(|param| (param.name.clone(), sample_parameter(param, &mut self.rng)))
+                    .collect()
+            })
+            .collect();
+    }
+
+    /// Promote the finished rung, or move on to the next bracket.
+    fn advance(&mut self) {
+        if self.rung < self.bracket {
+            let eta = self.reduction_factor as f64;
+            let keep = ((self.bracket_size as f64 / eta.powi(self.rung as i32 + 1)).floor()
+                as usize)
+                .clamp(1, self.results.len().max(1));
+            let mut results = std::mem::take(&mut self.results);
+            results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+            self.queue = results
+                .into_iter()
+                .take(keep)
+                .map(|(params, _)| params)
+                .collect();
+            self.rung += 1;
+        } else if self.bracket == 0 {
+            self.finished = true;
+        } else {
+            self.bracket -= 1;
+            self.start_bracket();
+        }
+    }
+
+    fn record(&mut self, params: &HashMap<String, ParameterValue>, objective: f64) {
+        if self.outstanding > 0 {
+            self.outstanding -= 1;
+            self.results.push((params.clone(), objective));
+        }
+    }
+}
+
+impl SearchStrategy for HyperbandSearch {
+    fn suggest(&mut self, count: usize) -> Vec<HashMap<String, ParameterValue>> {
+        self.suggest_with_budget(count)
+            .into_iter()
+            .map(|(params, _)| params)
+            .collect()
+    }
+
+    fn suggest_with_budget(&mut self, count: usize) -> Vec<(HashMap<String, ParameterValue>, f64)> {
+        let mut batch = Vec::new();
+        while !self.finished && batch.len() < count {
+            if let Some(params) = self.queue.pop_front() {
+                self.outstanding += 1;
+                batch.push((params, self.budget()));
+            } else if self.outstanding > 0 {
+                break;
+            } else {
+                self.advance();
+            }
+        }
+        batch
+    }
+
+    fn report(&mut self, params: &HashMap<String, ParameterValue>, objective: f64) {
+        self.record(params, objective);
+    }
+
+    fn report_with_budget(
+        &mut self,
+        params: &HashMap<String, ParameterValue>,
+        objective: f64,
+        _budget: f64,
+    ) {
+        self.record(params, objective);
+    }
+
+    fn report_failure(&mut self, params: &HashMap<String, ParameterValue>, _budget: f64) {
+        self.record(params, f64::NEG_INFINITY);
+    }
+
+    /// Promotions re-run a configuration at a larger budget, and each rung
+    /// waits on all of its suggestions.
+    fn deduplicate(&self) -> bool {
+        false
+    }
+
+    fn name(&self) -> &str {
+        "hyperband"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,6 +916,188 @@ mod tests {
         assert_eq!(second.len(), 2); // only 2 remain
     }
 
+    /// The eager cartesian product `GridSearch` used to build up front.
+    fn eager_grid(space: &SearchSpace, float_steps: usize) -> Vec<HashMap<String, ParameterValue>> {
+        let mut result: Vec<HashMap<String, ParameterValue>> = vec![HashMap::new()];
+        for param in &space.parameters {
+            let len = axis_len(param, float_steps).unwrap();
+            let mut next = Vec::with_capacity(result.len() * len);
+            for existing in &result {
+                for i in 0..len {
+                    let mut combo = existing.clone();
+                    combo.insert(param.name.clone(), axis_value(param, float_steps, i));
+                    next.push(combo);
+                }
+            }
+            result = next;
+        }
+        result
+    }
+
+    #[test]
+    fn lazy_grid_matches_eager_enumeration() {
+        let space = SearchSpace::new()
+            .add_int("a", -1, 1)
+            .add_choice("b", vec![serde_json::json!("x"), serde_json::json!("y")])
+            .add_float("c", 0.0, 1.0)
+            .add_log_uniform("d", 0.01, 1.0);
+        let mut gs = GridSearch::new(space.clone(), 3);
+        let expected = eager_grid(&space, 3);
+        assert_eq!(gs.total(), Some(expected.len()));
+
+        let mut lazy = gs.suggest(5);
+        lazy.extend(gs.suggest(1000));
+        assert_eq!(lazy, expected);
+        assert!(gs.suggest(1).is_empty());
+        assert_eq!(gs.cursor(), expected.len());
+    }
+
+    #[test]
+    fn huge_grid_is_constructed_lazily() {
+        let space = (0..6).fold(SearchSpace::new(), |space, i| {
+            space.add_int(format!("p{i}"), 1, 20)
+        });
+        assert_eq!(space.grid_size(), Some(64_000_000));
+
+        let mut gs = GridSearch::new(space, 5);
+        let first = gs.suggest(10);
+        assert_eq!(first.len(), 10);
+        for (i, combo) in first.iter().enumerate() {
+            for dim in 0..5 {
+                assert_eq!(combo[&format!("p{dim}")], ParameterValue::Int(1));
+            }
+            assert_eq!(combo["p5"], ParameterValue::Int(1 + i as i64));
+        }
+
+        let mut resumed = GridSearch::new(
+            (0..6).fold(SearchSpace::new(), |space, i| {
+                space.add_int(format!("p{i}"), 1, 20)
+            }),
+            5,
+        )
+        .with_cursor(20);
+        assert_eq!(resumed.suggest(1)[0]["p4"], ParameterValue::Int(2));
+    }
+
+    #[test]
+    fn grid_size_overflow_is_none() {
+        let space = SearchSpace::new()
+            .add_int("a", i64::MIN, i64::MAX)
+            .add_int("b", 0, 1);
+        assert_eq!(space.grid_size(), None);
+        let mut gs = GridSearch::new(space, 2);
+        assert_eq!(gs.total(), None);
+        assert_eq!(gs.suggest(2).len(), 2);
+    }
+
+    #[test]
+    fn grid_search_skips_excluded_combos() {
+        let space = SearchSpace::new().add_int("x", 1, 5);
+        let mut gs = GridSearch::new(space, 5);
+        assert_eq!(gs.suggest(1).len(), 1);
+
+        let seen = HashMap::from([("x".to_string(), ParameterValue::Int(3))]);
+        gs.exclude(&seen);
+        let rest = gs.suggest(10);
+        assert_eq!(rest.len(), 3);
+        assert!(rest.iter().all(|params| !same_parameters(params, &seen)));
+    }
+
+    #[test]
+    fn parameter_values_round_trip_through_json() {
+        let params = HashMap::from([
+            ("n".to_string(), ParameterValue::Int(7)),
+            ("w".to_string(), ParameterValue::Float(0.5)),
+            ("one".to_string(), ParameterValue::Float(1.0)),
+        ]);
+        let json = serde_json::to_string(&params).unwrap();
+        let back: HashMap<String, ParameterValue> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, params);
+    }
+
+    /// Peaks at `x = 3`; smaller budgets keep the ordering but score lower.
+    fn synthetic_objective(params: &HashMap<String, ParameterValue>, budget: f64) -> f64 {
+        let Some(ParameterValue::Int(x)) = params.get("x") else {
+            panic!("missing x");
+        };
+        -((x - 3) as f64).powi(2) - (1.0 - budget)
+    }
+
+    /// Drive `search` to exhaustion, returning the best full-budget
+    /// combination and the number of full-budget evaluations.
+    fn drive(search: &mut dyn SearchStrategy, limit: usize) -> (Option<i64>, usize) {
+        let mut best: Option<(i64, f64)> = None;
+        let mut full_budget = 0;
+        let mut evaluated = 0;
+        loop {
+            let batch = search.suggest_with_budget(1);
+            if batch.is_empty() || evaluated >= limit {
+                break;
+            }
+            for (params, budget) in batch {
+                evaluated += 1;
+                let objective = synthetic_objective(&params, budget);
+                search.report_with_budget(&params, objective, budget);
+                if budget >= 1.0 {
+                    full_budget += 1;
+                    let Some(ParameterValue::Int(x)) = params.get("x") else {
+                        unreachable!()
+                    };
+                    if best.is_none_or(|(_, score)| objective > score) {
+                        best = Some((*x, objective));
+                    }
+                }
+            }
+        }
+        (best.map(|(x, _)| x), full_budget)
+    }
+
+    #[test]
+    fn hyperband_finds_optimum_with_fewer_full_budget_evaluations() {
+        let space = SearchSpace::new().add_int("x", 0, 4);
+
+        let mut hyperband = HyperbandSearch::new(space.clone(), 1.0 / 27.0, 3);
+        let (best, hyperband_full) = drive(&mut hyperband, usize::MAX);
+        // Brackets of 27, 12, 6 and 4 combinations.
+        let sampled = 27 + 12 + 6 + 4;
+        assert_eq!(best, Some(3));
+        assert_eq!(hyperband_full, 1 + 1 + 2 + 4);
+
+        let mut random = RandomSearch::new(space);
+        let (best, random_full) = drive(&mut random, sampled);
+        assert_eq!(best, Some(3));
+        assert_eq!(random_full, sampled);
+        assert!(hyperband_full < random_full);
+    }
+
+    #[test]
+    fn hyperband_waits_for_a_rung_before_promoting() {
+        let space = SearchSpace::new().add_int("x", 0, 4);
+        let mut hyperband = HyperbandSearch::new(space, 1.0 / 3.0, 3);
+
+        let first_rung = hyperband.suggest_with_budget(100);
+        assert_eq!(first_rung.len(), 3);
+        assert!(first_rung
+            .iter()
+            .all(|(_, budget)| (budget - 1.0 / 3.0).abs() < 1e-12));
+        assert!(hyperband.suggest_with_budget(1).is_empty());
+
+        for (params, budget) in &first_rung {
+            hyperband.report_with_budget(params, synthetic_objective(params, *budget), *budget);
+        }
+        let promoted = hyperband.suggest_with_budget(100);
+        assert_eq!(promoted.len(), 1);
+        assert_eq!(promoted[0].1, 1.0);
+    }
+
+    #[test]
+    fn budget_free_strategies_suggest_full_budget() {
+        let mut gs = GridSearch::new(SearchSpace::new().add_int("x", 1, 3), 5);
+        let batch = gs.suggest_with_budget(10);
+        assert_eq!(batch.len(), 3);
+        assert!(batch.iter().all(|(_, budget)| *budget == 1.0));
+    }
+
     #[test]
     fn random_search_respects_bounds() {
         let space = sample_space();
@@ -437,7 +1120,7 @@ mod tests {
     #[test]
     fn bayesian_search_starts_with_exploration() {
         let space = sample_space();
-        let mut bs = BayesianSearch::new(space, 0.3);
+        let mut bs = BayesianSearch::new(space, 0.3, SurrogateKind::GaussianProcess);
         // No observations yet → all suggestions are exploration
         let suggestions = bs.suggest(10);
         assert_eq!(suggestions.len(), 10);
@@ -446,7 +1129,7 @@ mod tests {
     #[test]
     fn bayesian_search_exploits_after_reports() {
         let space = SearchSpace::new().add_float("lr", 0.001, 1.0);
-        let mut bs = BayesianSearch::new(space, 0.0); // exploration_weight=0 → always exploit after report
+        let mut bs = BayesianSearch::new(space, 0.0, SurrogateKind::Perturbation); // exploration_weight=0 → always exploit after report
 
         let mut best_params = HashMap::new();
         best_params.insert("lr".to_string(), ParameterValue::Float(0.01));
@@ -465,6 +1148,163 @@ mod tests {
         }
     }
 
+    /// Best parameters found by `search` within `budget` trials.
+    fn optimize(
+        search: &mut dyn SearchStrategy,
+        objective: impl Fn(&HashMap<String, ParameterValue>) -> f64,
+        budget: usize,
+    ) -> HashMap<String, ParameterValue> {
+        let mut best: Option<(HashMap<String, ParameterValue>, f64)> = None;
+        for _ in 0..budget {
+            let params = search.suggest(1).pop().unwrap();
+            let value = objective(&params);
+            search.report(&params, value);
+            if best.as_ref().is_none_or(|(_, score)| value > *score) {
+                best = Some((params, value));
+            }
+        }
+        best.unwrap().0
+    }
+
+    fn float(params: &HashMap<String, ParameterValue>, name: &str) -> f64 {
+        match params.get(name) {
+            Some(ParameterValue::Float(v)) => *v,
+            other => panic!("unexpected {name} value: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn gaussian_process_beats_random_on_shifted_quadratic_1d() {
+        let space = SearchSpace::new().add_float("x", -5.0, 5.0);
+        let objective =
+            |params: &HashMap<String, ParameterValue>| -(float(params, "x") - 1.7).powi(2);
+
+        let mut gp =
+            BayesianSearch::new(space.clone(), 0.0, SurrogateKind::GaussianProcess).with_seed(7);
+        let mut random = RandomSearch::new(space).with_seed(7);
+        let gp_error = (float(&optimize(&mut gp, objective, 15), "x") - 1.7).abs();
+        let random_error = (float(&optimize(&mut random, objective, 15), "x") - 1.7).abs();
+
+        assert!(gp_error < 0.05, "gp error {gp_error}");
+        assert!(
+            gp_error < random_error,
+            "gp {gp_error} vs random {random_error}"
+        );
+    }
+
+    #[test]
+    fn gaussian_process_beats_random_on_shifted_quadratic_2d() {
+        let space = SearchSpace::new()
+            .add_float("x", -2.0, 2.0)
+            .add_float("y", -2.0, 2.0);
+        let objective = |params: &HashMap<String, ParameterValue>| {
+            -((float(params, "x") - 0.3).powi(2) + (float(params, "y") + 1.2).powi(2))
+        };
+        let distance = |params: &HashMap<String, ParameterValue>| (-objective(params)).sqrt();
+
+        let mut gp =
+            BayesianSearch::new(space.clone(), 0.0, SurrogateKind::GaussianProcess).with_seed(11);
+        let mut random = RandomSearch::new(space).with_seed(11);
+        let gp_distance = distance(&optimize(&mut gp, objective, 30));
+        let random_distance = distance(&optimize(&mut random, objective, 30));
+
+        assert!(gp_distance < 0.1, "gp distance {gp_distance}");
+        assert!(
+            gp_distance < random_distance,
+            "gp {gp_distance} vs random {random_distance}"
+        );
+    }
+
+    #[test]
+    fn gaussian_process_learns_categorical_choices() {
+        let space = SearchSpace::new().add_float("x", 0.0, 1.0).add_choice(
+            "mode",
+            vec![
+                serde_json::json!("a"),
+                serde_json::json!("b"),
+                serde_json::json!("c"),
+            ],
+        );
+        let objective = |params: &HashMap<String, ParameterValue>| {
+            let bonus = match params.get("mode") {
+                Some(ParameterValue::Json(v)) if v == "b" => 1.0,
+                _ => 0.0,
+            };
+            bonus - (float(params, "x") - 0.6).powi(2)
+        };
+
+        let mut gp = BayesianSearch::new(space, 0.0, SurrogateKind::GaussianProcess).with_seed(3);
+        optimize(&mut gp, objective, 20);
+        let later = gp.suggest(20);
+        let picked_b = later
+            .iter()
+            .filter(
+                |params| matches!(params.get("mode"), Some(ParameterValue::Json(v)) if v == "b"),
+            )
+            .count();
+        assert!(picked_b > 10, "picked b {picked_b} of 20");
+    }
+
+    #[test]
+    fn seeded_random_search_is_reproducible() {
+        let space = sample_space();
+        let a = RandomSearch::new(space.clone()).with_seed(5).suggest(5);
+        let b = RandomSearch::new(space.clone()).with_seed(5).suggest(5);
+        let c = RandomSearch::new(space).with_seed(6).suggest(5);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn seeded_bayesian_search_is_reproducible() {
+        let space = sample_space();
+        let run = |seed: u64| {
+            let mut search =
+                BayesianSearch::new(space.clone(), 0.2, SurrogateKind::GaussianProcess)
+                    .with_seed(seed);
+            let mut suggested = Vec::new();
+            for i in 0..8 {
+                let params = search.suggest(1).pop().unwrap();
+                search.report(&params, -(i as f64 - 4.0).powi(2));
+                suggested.push(params);
+            }
+            suggested
+        };
+        assert_eq!(run(9), run(9));
+        assert_ne!(run(9), run(10));
+    }
+
+    #[test]
+    fn seeded_hyperband_is_reproducible() {
+        let space = sample_space();
+        let a = HyperbandSearch::new(space.clone(), 1.0 / 9.0, 3)
+            .with_seed(1)
+            .suggest(9);
+        let b = HyperbandSearch::new(space, 1.0 / 9.0, 3)
+            .with_seed(1)
+            .suggest(9);
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn grid_enumerates_last_parameter_fastest() {
+        let space = SearchSpace::new()
+            .add_int("a", 1, 2)
+            .add_float("b", 0.0, 1.0);
+        let combos = GridSearch::new(space, 3).suggest(10);
+        let order: Vec<(i64, f64)> = combos
+            .iter()
+            .map(|params| match (&params["a"], &params["b"]) {
+                (ParameterValue::Int(a), ParameterValue::Float(b)) => (*a, *b),
+                other => panic!("unexpected combination {other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            order,
+            vec![(1, 0.0), (1, 0.5), (1, 1.0), (2, 0.0), (2, 0.5), (2, 1.0)]
+        );
+    }
+
     #[test]
     fn grid_size_none_for_float_only() {
         let space = SearchSpace::new().add_float("x", 0.0, 1.0);
@@ -0,0 +1,172 @@
+//! SQLite persistence for optimization runs and their trials.
+
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+
+use crate::trial::{OptimizationId, OptimizationStatus, Trial};
+
+/// Durable record of optimization runs, written as trials finish so an
+/// interrupted run can be resumed.
+#[derive(Debug)]
+pub struct OptimizationStore {
+    connection: Connection,
+}
+
+impl OptimizationStore {
+    /// Open or create the store at `path`.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, String> {
+        let connection = Connection::open(path).map_err(|e| e.to_string())?;
+        connection
+            .execute_batch(
+                "CREATE TABLE IF NOT EXISTS optimizations (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                state TEXT NOT NULL,
+                status TEXT NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS trials (
+                id TEXT PRIMARY KEY,
+                optimization_id TEXT NOT NULL,
+                trial_number INTEGER NOT NULL,
+                state TEXT NOT NULL,
+                objective REAL,
+                trial TEXT NOT NULL,
+                updated_at TEXT DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_trials_optimization ON trials(optimization_id);",
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(Self { connection })
+    }
+
+    /// `optimizer.db` next to the data catalog.
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("glowback")
+            .join("optimizer.db")
+    }
+
+    /// Open the store at [`default_path`](Self::default_path).
+    pub fn open_default() -> Result<Self, String> {
+        let path = Self::default_path();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+        Self::open(path)
+    }
+
+    /// Insert or update a run's status, configuration included.
+    pub fn save_status(&self, status: &OptimizationStatus) -> Result<(), String> {
+        let json = serde_json::to_string(status).map_err(|e| e.to_string())?;
+        self.connection
+            .execute(
+                "INSERT OR REPLACE INTO optimizations (id, name, state, status, updated_at)
+                 VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+                params![
+                    status.id.to_string(),
+                    status.config.name,
+                    format!("{:?}", status.state),
+                    json
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    pub fn load_status(&self, id: OptimizationId) -> Result<Option<OptimizationStatus>, String> {
+        let json: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT status FROM optimizations WHERE id = ?1",
+                params![id.to_string()],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        json.map(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+            .transpose()
+    }
+
+    /// Insert or update a trial, its result included.
+    pub fn save_trial(&self, trial: &Trial) -> Result<(), String> {
+        let json = serde_json::to_string(trial).map_err(|e| e.to_string())?;
+        self.connection
+            .execute(
+                "INSERT OR REPLACE INTO trials
+             (id, optimization_id, trial_number, state, objective, trial, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, CURRENT_TIMESTAMP)",
+                params![
+                    trial.id.to_string(),
+                    trial.optimization_id.to_string(),
+                    trial.trial_number as i64,
+                    format!("{:?}", trial.status),
+                    trial.result.as_ref().map(|result| result.objective),
+                    json
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Trials saved for run `id`, by trial number.
+    pub fn load_trials(&self, id: OptimizationId) -> Result<Vec<Trial>, String> {
+        let mut stmt = self
+            .connection
+            .prepare("SELECT trial FROM trials WHERE optimization_id = ?1 ORDER BY trial_number")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![id.to_string()], |row| row.get::<_, String>(0))
+            .map_err(|e| e.to_string())?;
+        rows.map(|json| {
+            let json = json.map_err(|e| e.to_string())?;
+            serde_json::from_str(&json).map_err(|e| e.to_string())
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::{ParameterValue, SearchSpace};
+    use crate::trial::{OptimizationConfig, TrialResult, TrialStatus};
+    use std::collections::HashMap;
+
+    #[test]
+    fn store_round_trips_status_and_trials() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = OptimizationStore::open(dir.path().join("optimizer.db")).unwrap();
+        let config = OptimizationConfig::new(
+            "persisted".into(),
+            SearchSpace::new().add_int("n", 1, 3),
+            "grid",
+        );
+        let mut status = OptimizationStatus::new(config);
+        status.mark_running();
+        store.save_status(&status).unwrap();
+
+        let params = HashMap::from([("n".to_string(), ParameterValue::Int(2))]);
+        let mut trial = Trial::new(status.id, 0, params.clone());
+        trial.mark_completed(TrialResult {
+            trial_id: trial.id,
+            objective: 1.25,
+            metrics: HashMap::from([("total_return".to_string(), 1.25)]),
+            parameters: params,
+            duration_seconds: Some(1),
+            folds: Vec::new(),
+        });
+        store.save_trial(&trial).unwrap();
+        drop(store);
+
+        let store = OptimizationStore::open(dir.path().join("optimizer.db")).unwrap();
+        assert_eq!(store.load_status(status.id).unwrap(), Some(status.clone()));
+        let trials = store.load_trials(status.id).unwrap();
+        assert_eq!(trials, vec![trial]);
+        assert_eq!(trials[0].status, TrialStatus::Completed);
+        assert!(store.load_status(uuid::Uuid::new_v4()).unwrap().is_none());
+    }
+}
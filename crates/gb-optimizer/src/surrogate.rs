@@ -0,0 +1,223 @@
+//! Gaussian-process surrogate behind [`BayesianSearch`](crate::BayesianSearch).
+//!
+//! Inputs are points in the unit cube; the kernel is a squared-exponential
+//! (RBF) whose length scale is picked from a small grid by marginal
+//! likelihood. Everything is dense linear algebra, so callers cap the number
+//! of observations they fit.
+
+/// Length scales tried when fitting, in unit-cube coordinates.
+const LENGTH_SCALES: [f64; 5] = [0.05, 0.1, 0.2, 0.4, 0.8];
+
+/// Diagonal jitter, raised tenfold until the kernel matrix factorizes.
+const INITIAL_JITTER: f64 = 1e-8;
+const MAX_JITTER: f64 = 1e-2;
+
+/// A fitted Gaussian process over normalized objectives.
+#[derive(Debug, Clone)]
+pub(crate) struct GaussianProcess {
+    xs: Vec<Vec<f64>>,
+    length_scale: f64,
+    /// Lower Cholesky factor of the jittered kernel matrix.
+    chol: Vec<Vec<f64>>,
+    /// `K⁻¹ y` for the normalized targets.
+    alpha: Vec<f64>,
+    y_mean: f64,
+    y_std: f64,
+}
+
+impl GaussianProcess {
+    /// Fit to `xs` and `ys`, or `None` with no observations or a kernel
+    /// matrix that won't factorize even with the largest jitter.
+    pub(crate) fn fit(xs: &[Vec<f64>], ys: &[f64]) -> Option<Self> {
+        if xs.is_empty() || xs.len() != ys.len() {
+            return None;
+        }
+        let n = ys.len() as f64;
+        let y_mean = ys.iter().sum::<f64>() / n;
+        let variance = ys.iter().map(|y| (y - y_mean).powi(2)).sum::<f64>() / n;
+        let y_std = if variance > 0.0 { variance.sqrt() } else { 1.0 };
+        let targets: Vec<f64> = ys.iter().map(|y| (y - y_mean) / y_std).collect();
+
+        LENGTH_SCALES
+            .iter()
+            .filter_map(|&length_scale| {
+                let (chol, alpha) = factorize(xs, &targets, length_scale)?;
+                let log_likelihood = -0.5 * dot(&targets, &alpha)
+                    - chol
+                        .iter()
+                        .enumerate()
+                        .map(|(i, row)| row[i].ln())
+                        .sum::<f64>();
+                Some((log_likelihood, length_scale, chol, alpha))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, length_scale, chol, alpha)| Self {
+                xs: xs.to_vec(),
+                length_scale,
+                chol,
+                alpha,
+                y_mean,
+                y_std,
+            })
+    }
+
+    /// Posterior mean and standard deviation at `x`, in objective units.
+    pub(crate) fn predict(&self, x: &[f64]) -> (f64, f64) {
+        let k: Vec<f64> = self
+            .xs
+            .iter()
+            .map(|xi| rbf(xi, x, self.length_scale))
+            .collect();
+        let mean = dot(&k, &self.alpha);
+        let v = solve_lower(&self.chol, &k);
+        let variance = (1.0 - dot(&v, &v)).max(0.0);
+        (
+            self.y_mean + mean * self.y_std,
+            variance.sqrt() * self.y_std,
+        )
+    }
+
+    /// Expected improvement of `x` over `best` (maximizing), with `xi`
+    /// trading exploitation for exploration.
+    pub(crate) fn expected_improvement(&self, x: &[f64], best: f64, xi: f64) -> f64 {
+        let (mean, std) = self.predict(x);
+        let improvement = mean - best - xi;
+        if std <= f64::EPSILON {
+            return improvement.max(0.0);
+        }
+        let z = improvement / std;
+        improvement * normal_cdf(z) + std * normal_pdf(z)
+    }
+}
+
+fn rbf(a: &[f64], b: &[f64], length_scale: f64) -> f64 {
+    let distance: f64 = a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum();
+    (-distance / (2.0 * length_scale * length_scale)).exp()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Cholesky factor of the kernel matrix and `K⁻¹ y`.
+fn factorize(
+    xs: &[Vec<f64>],
+    targets: &[f64],
+    length_scale: f64,
+) -> Option<(Vec<Vec<f64>>, Vec<f64>)> {
+    let kernel: Vec<Vec<f64>> = xs
+        .iter()
+        .map(|a| xs.iter().map(|b| rbf(a, b, length_scale)).collect())
+        .collect();
+    let mut jitter = INITIAL_JITTER;
+    loop {
+        if let Some(chol) = cholesky(&kernel, jitter) {
+            let z = solve_lower(&chol, targets);
+            let alpha = solve_upper_transposed(&chol, &z);
+            return Some((chol, alpha));
+        }
+        jitter *= 10.0;
+        if jitter > MAX_JITTER {
+            return None;
+        }
+    }
+}
+
+/// Lower-triangular `L` with `L Lᵀ = A + jitter I`.
+fn cholesky(matrix: &[Vec<f64>], jitter: f64) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut chol = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|k| chol[i][k] * chol[j][k]).sum();
+            if i == j {
+                let diagonal = matrix[i][i] + jitter - sum;
+                if diagonal <= 0.0 || !diagonal.is_finite() {
+                    return None;
+                }
+                chol[i][j] = diagonal.sqrt();
+            } else {
+                chol[i][j] = (matrix[i][j] - sum) / chol[j][j];
+            }
+        }
+    }
+    Some(chol)
+}
+
+/// Solve `L x = b`.
+fn solve_lower(chol: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let mut x = vec![0.0; b.len()];
+    for i in 0..b.len() {
+        let sum: f64 = (0..i).map(|k| chol[i][k] * x[k]).sum();
+        x[i] = (b[i] - sum) / chol[i][i];
+    }
+    x
+}
+
+/// Solve `Lᵀ x = b`.
+fn solve_upper_transposed(chol: &[Vec<f64>], b: &[f64]) -> Vec<f64> {
+    let n = b.len();
+    let mut x = vec![0.0; n];
+    for i in (0..n).rev() {
+        let sum: f64 = (i + 1..n).map(|k| chol[k][i] * x[k]).sum();
+        x[i] = (b[i] - sum) / chol[i][i];
+    }
+    x
+}
+
+fn normal_pdf(z: f64) -> f64 {
+    (-0.5 * z * z).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+fn normal_cdf(z: f64) -> f64 {
+    0.5 * (1.0 + erf(z / std::f64::consts::SQRT_2))
+}
+
+/// Abramowitz & Stegun 7.1.26; absolute error below 1.5e-7.
+fn erf(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.327_591_1 * x.abs());
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let value = 1.0 - poly * (-x * x).exp();
+    if x >= 0.0 {
+        value
+    } else {
+        -value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gp_interpolates_observations_and_is_uncertain_far_away() {
+        let xs: Vec<Vec<f64>> = [0.0, 0.25, 0.5, 0.75, 1.0]
+            .iter()
+            .map(|x| vec![*x])
+            .collect();
+        let ys: Vec<f64> = xs.iter().map(|x| (6.0 * x[0]).sin()).collect();
+        let gp = GaussianProcess::fit(&xs, &ys).unwrap();
+
+        for (x, y) in xs.iter().zip(&ys) {
+            let (mean, std) = gp.predict(x);
+            assert!((mean - y).abs() < 1e-3, "mean {mean} vs {y}");
+            assert!(std < 1e-2);
+        }
+        let (_, near) = gp.predict(&[0.5]);
+        let (_, between) = gp.predict(&[0.375]);
+        assert!(between > near);
+    }
+
+    #[test]
+    fn duplicate_points_factorize_with_jitter() {
+        let xs = vec![vec![0.3, 0.3], vec![0.3, 0.3], vec![0.7, 0.1]];
+        let ys = vec![1.0, 1.0, -1.0];
+        let gp = GaussianProcess::fit(&xs, &ys).unwrap();
+        assert!(gp.expected_improvement(&[0.9, 0.9], 1.0, 0.0) >= 0.0);
+        assert!((normal_cdf(0.0) - 0.5).abs() < 1e-7);
+        assert!((normal_cdf(1.96) - 0.975).abs() < 1e-3);
+    }
+}
@@ -3,9 +3,11 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 use uuid::Uuid;
 
-use crate::search::{ParameterValue, SearchSpace};
+use crate::evaluation::{EvaluationMode, FoldResult};
+use crate::search::{ParameterValue, SearchSpace, SurrogateKind};
 
 /// Unique optimization run identifier.
 pub type OptimizationId = Uuid;
@@ -56,12 +58,56 @@ pub struct OptimizationConfig {
     /// Exploration weight for Bayesian search (ignored for grid/random).
     pub exploration_weight: f64,
 
+    /// Surrogate model for Bayesian search (ignored by other strategies).
+    #[serde(default)]
+    pub surrogate: SurrogateKind,
+
+    /// Seed for the search strategy's sampling; `None` draws one per run.
+    #[serde(default)]
+    pub seed: Option<u64>,
+
     /// Number of steps per continuous dimension for grid search.
     pub grid_steps: usize,
 
+    /// Smallest fraction of the full backtest budget a Hyperband trial runs
+    /// with (ignored by other strategies).
+    #[serde(default = "default_min_budget")]
+    pub min_budget: f64,
+
+    /// Factor by which Hyperband prunes each rung (ignored by other
+    /// strategies).
+    #[serde(default = "default_reduction_factor")]
+    pub reduction_factor: usize,
+
+    /// Evaluate a parameter set again when the search repeats it, instead
+    /// of asking for a different one; for stochastic objectives.
+    #[serde(default)]
+    pub allow_duplicate_trials: bool,
+
+    /// Whether trials are scored on the full window or averaged over
+    /// time-based folds of it.
+    #[serde(default)]
+    pub evaluation: EvaluationMode,
+
+    /// Rules that end the run before `max_trials`.
+    #[serde(default)]
+    pub stopping: StoppingCriteria,
+
     pub created_at: DateTime<Utc>,
 }
 
+fn default_min_budget() -> f64 {
+    1.0 / 27.0
+}
+
+fn default_reduction_factor() -> usize {
+    3
+}
+
+fn default_budget() -> f64 {
+    1.0
+}
+
 impl OptimizationConfig {
     pub fn new(name: String, search_space: SearchSpace, strategy: &str) -> Self {
         Self {
@@ -76,7 +122,14 @@ impl OptimizationConfig {
             direction: ObjectiveDirection::Maximize,
             base_backtest: serde_json::Value::Null,
             exploration_weight: 0.3,
+            surrogate: SurrogateKind::default(),
+            seed: None,
             grid_steps: 5,
+            min_budget: default_min_budget(),
+            reduction_factor: default_reduction_factor(),
+            allow_duplicate_trials: false,
+            evaluation: EvaluationMode::default(),
+            stopping: StoppingCriteria::default(),
             created_at: Utc::now(),
         }
     }
@@ -101,6 +154,47 @@ impl OptimizationConfig {
         self.base_backtest = config;
         self
     }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn with_evaluation(mut self, evaluation: EvaluationMode) -> Self {
+        self.evaluation = evaluation;
+        self
+    }
+
+    pub fn with_stopping(mut self, stopping: StoppingCriteria) -> Self {
+        self.stopping = stopping;
+        self
+    }
+}
+
+/// Early-stopping rules for an optimization run, all off by default.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoppingCriteria {
+    /// Wall-clock budget for the run; resuming starts a fresh budget.
+    pub max_duration: Option<Duration>,
+
+    /// Stop once this many trials in a row finish without improving on the
+    /// best full-budget objective by more than `min_improvement`.
+    pub patience_trials: Option<usize>,
+
+    /// Smallest gain in the objective that counts as an improvement.
+    pub min_improvement: f64,
+
+    /// Cancel and fail a trial whose backtest runs longer than this.
+    pub per_trial_timeout: Option<Duration>,
+}
+
+/// Why a run stopped before exhausting its trials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason {
+    /// `max_duration` elapsed.
+    MaxDuration,
+    /// `patience_trials` finished without improvement.
+    NoImprovement,
 }
 
 /// Lifecycle state for an optimization run.
@@ -126,6 +220,12 @@ pub struct OptimizationStatus {
     pub started_at: Option<DateTime<Utc>>,
     pub finished_at: Option<DateTime<Utc>>,
     pub error: Option<String>,
+    /// Seed the search actually sampled from, so the run can be repeated.
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Stopping criterion that ended the run, if any.
+    #[serde(default)]
+    pub stop_reason: Option<StopReason>,
 }
 
 impl OptimizationStatus {
@@ -141,6 +241,8 @@ impl OptimizationStatus {
             started_at: None,
             finished_at: None,
             error: None,
+            seed: None,
+            stop_reason: None,
         }
     }
 
@@ -154,6 +256,17 @@ impl OptimizationStatus {
         self.finished_at = Some(Utc::now());
     }
 
+    pub fn mark_cancelled(&mut self) {
+        self.state = OptimizationState::Cancelled;
+        self.finished_at = Some(Utc::now());
+    }
+
+    /// Complete the run early because `reason` triggered.
+    pub fn mark_stopped(&mut self, reason: StopReason) {
+        self.stop_reason = Some(reason);
+        self.mark_completed();
+    }
+
     pub fn mark_failed(&mut self, error: String) {
         self.state = OptimizationState::Failed;
         self.finished_at = Some(Utc::now());
@@ -186,6 +299,9 @@ pub struct Trial {
     pub optimization_id: OptimizationId,
     pub trial_number: usize,
     pub parameters: HashMap<String, ParameterValue>,
+    /// Fraction of the full backtest this trial was evaluated on.
+    #[serde(default = "default_budget")]
+    pub budget: f64,
     pub status: TrialStatus,
     pub result: Option<TrialResult>,
     pub created_at: DateTime<Utc>,
@@ -206,6 +322,7 @@ impl Trial {
             optimization_id,
             trial_number,
             parameters,
+            budget: default_budget(),
             status: TrialStatus::Pending,
             result: None,
             created_at: Utc::now(),
@@ -233,6 +350,12 @@ impl Trial {
         self.finished_at = Some(Utc::now());
         self.error = Some(error);
     }
+
+    /// The run stopped before this trial finished; it is re-run on resume.
+    pub fn mark_cancelled(&mut self) {
+        self.status = TrialStatus::Cancelled;
+        self.finished_at = Some(Utc::now());
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -241,16 +364,22 @@ pub enum TrialStatus {
     Running,
     Completed,
     Failed,
+    Cancelled,
 }
 
 /// Result of a single trial.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TrialResult {
     pub trial_id: Uuid,
+    /// Objective, averaged over `folds` when there are any.
     pub objective: f64,
+    /// Metrics, averaged over `folds` when there are any.
     pub metrics: HashMap<String, f64>,
     pub parameters: HashMap<String, ParameterValue>,
     pub duration_seconds: Option<u64>,
+    /// Per-fold results; empty for single-window evaluation.
+    #[serde(default)]
+    pub folds: Vec<FoldResult>,
 }
 
 #[cfg(test)]
@@ -298,6 +427,7 @@ mod tests {
             metrics: HashMap::new(),
             parameters: HashMap::new(),
             duration_seconds: Some(10),
+            folds: Vec::new(),
         };
         status.update_best(&result_a);
         assert_eq!(status.best_trial.as_ref().unwrap().objective, 1.5);
@@ -308,6 +438,7 @@ mod tests {
             metrics: HashMap::new(),
             parameters: HashMap::new(),
             duration_seconds: Some(8),
+            folds: Vec::new(),
         };
         status.update_best(&result_b);
         assert_eq!(status.best_trial.as_ref().unwrap().objective, 2.0);
@@ -319,6 +450,7 @@ mod tests {
             metrics: HashMap::new(),
             parameters: HashMap::new(),
             duration_seconds: Some(12),
+            folds: Vec::new(),
         };
         status.update_best(&result_c);
         assert_eq!(status.best_trial.as_ref().unwrap().objective, 2.0);
@@ -337,6 +469,7 @@ mod tests {
             metrics: HashMap::new(),
             parameters: HashMap::new(),
             duration_seconds: None,
+            folds: Vec::new(),
         };
         status.update_best(&result_high);
         assert_eq!(status.best_trial.as_ref().unwrap().objective, 0.15);
@@ -347,6 +480,7 @@ mod tests {
             metrics: HashMap::new(),
             parameters: HashMap::new(),
             duration_seconds: None,
+            folds: Vec::new(),
         };
         status.update_best(&result_low);
         assert_eq!(status.best_trial.as_ref().unwrap().objective, 0.05);
@@ -371,6 +505,7 @@ mod tests {
             metrics: HashMap::new(),
             parameters: params,
             duration_seconds: Some(5),
+            folds: Vec::new(),
         };
         trial.mark_completed(result);
         assert_eq!(trial.status, TrialStatus::Completed);
@@ -0,0 +1,87 @@
+use chrono::{Duration, Utc};
+use gb_optimizer::{
+    EvaluationMode, GridSearch, LocalProcessTransport, ObjectiveDirection, OptimizationConfig,
+    RayClusterConfig, RayDispatcher, SearchSpace, SearchStrategy, Trial, TrialStatus,
+};
+use gb_types::{BacktestConfig, Resolution, StrategyConfig, Symbol};
+
+fn sweep_config() -> OptimizationConfig {
+    let mut strategy_config = StrategyConfig::new(
+        "ma_crossover".to_string(),
+        "Moving Average Crossover".to_string(),
+    );
+    strategy_config.symbols = vec![Symbol::equity("AAPL")];
+    let mut base = BacktestConfig::new("Ray sweep".to_string(), strategy_config)
+        .with_symbols(vec![Symbol::equity("AAPL")])
+        .with_date_range(Utc::now() - Duration::days(120), Utc::now())
+        .with_resolution(Resolution::Day);
+    base.data_settings.data_source = "sample".to_string();
+
+    let space = SearchSpace::new()
+        .add_int("short_period", 3, 4)
+        .add_int("long_period", 15, 16);
+    OptimizationConfig::new("local process sweep".into(), space, "grid")
+        .with_objective("total_return", ObjectiveDirection::Maximize)
+        .with_base_backtest(serde_json::to_value(base).unwrap())
+}
+
+fn worker() -> LocalProcessTransport {
+    LocalProcessTransport::new(env!("CARGO_BIN_EXE_glowback-trial-worker"))
+}
+
+#[tokio::test]
+async fn local_process_workers_run_a_grid_sweep() {
+    let config = sweep_config().with_evaluation(EvaluationMode::KFoldTime { folds: 2 });
+    let mut grid = GridSearch::new(config.search_space.clone(), config.grid_steps);
+    let mut trials: Vec<Trial> = grid
+        .suggest(10)
+        .into_iter()
+        .enumerate()
+        .map(|(i, parameters)| Trial::new(config.id, i, parameters))
+        .collect();
+    assert_eq!(trials.len(), 4);
+
+    let cluster = RayClusterConfig {
+        max_concurrent_tasks: 2,
+        ..Default::default()
+    };
+    RayDispatcher::new(worker())
+        .run(&config, cluster, &mut trials)
+        .await
+        .unwrap();
+
+    for trial in &trials {
+        assert_eq!(trial.status, TrialStatus::Completed, "{:?}", trial.error);
+        let result = trial.result.as_ref().unwrap();
+        assert_eq!(result.trial_id, trial.id);
+        assert_eq!(result.parameters, trial.parameters);
+        assert_eq!(result.folds.len(), 2);
+        assert_eq!(result.objective, result.metrics["total_return"]);
+    }
+}
+
+#[tokio::test]
+async fn backtest_failures_come_back_as_failed_trials() {
+    let mut config = sweep_config();
+    config.base_backtest["symbols"] = serde_json::json!([Symbol::equity("NOPE")]);
+    let mut trials = vec![Trial::new(
+        config.id,
+        0,
+        GridSearch::new(config.search_space.clone(), 2)
+            .suggest(1)
+            .remove(0),
+    )];
+
+    RayDispatcher::new(worker())
+        .with_max_retries(0)
+        .run(&config, RayClusterConfig::default(), &mut trials)
+        .await
+        .unwrap();
+
+    assert_eq!(trials[0].status, TrialStatus::Failed);
+    assert!(!trials[0]
+        .error
+        .as_deref()
+        .unwrap()
+        .contains("worker failed"));
+}
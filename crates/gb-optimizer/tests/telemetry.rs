@@ -0,0 +1,79 @@
+use std::collections::HashSet;
+
+use chrono::{Duration, Utc};
+use gb_engine::telemetry::{RunLogLayer, RUN_LOG_FILE};
+use gb_optimizer::{ObjectiveDirection, OptimizationConfig, OptimizationRunner, SearchSpace};
+use gb_types::{BacktestConfig, Resolution, StrategyConfig, Symbol};
+use tracing_subscriber::layer::SubscriberExt;
+
+fn sweep_config() -> OptimizationConfig {
+    let mut strategy_config = StrategyConfig::new(
+        "ma_crossover".to_string(),
+        "Moving Average Crossover".to_string(),
+    );
+    strategy_config.symbols = vec![Symbol::equity("AAPL")];
+    let mut base = BacktestConfig::new("Logged sweep".to_string(), strategy_config)
+        .with_symbols(vec![Symbol::equity("AAPL")])
+        .with_date_range(Utc::now() - Duration::days(120), Utc::now())
+        .with_resolution(Resolution::Day);
+    base.data_settings.data_source = "sample".to_string();
+
+    let space = SearchSpace::new()
+        .add_int("short_period", 3, 4)
+        .add_int("long_period", 15, 16);
+    OptimizationConfig::new("logged sweep".into(), space, "grid")
+        .with_max_trials(4)
+        .with_concurrency(2)
+        .with_objective("total_return", ObjectiveDirection::Maximize)
+        .with_base_backtest(serde_json::to_value(base).unwrap())
+}
+
+#[tokio::test]
+async fn trial_backtests_log_their_run_ids_from_the_data_layer() {
+    let root = tempfile::tempdir().unwrap();
+    // Trials run on blocking threads, so the subscriber has to be global.
+    tracing::subscriber::set_global_default(
+        tracing_subscriber::registry().with(RunLogLayer::new(Some(root.path().into()))),
+    )
+    .unwrap();
+
+    let config = sweep_config();
+    let optimization_id = config.id.to_string();
+    let trials = OptimizationRunner::new().run(config).await.unwrap();
+    assert_eq!(trials.len(), 4);
+
+    // Setting up each trial's engine logs to the optimization's own file.
+    let optimization_log =
+        std::fs::read_to_string(root.path().join(&optimization_id).join(RUN_LOG_FILE)).unwrap();
+    assert!(optimization_log.lines().all(|line| {
+        let line: serde_json::Value = serde_json::from_str(line).unwrap();
+        line["span"]["trial_id"].is_string() && line["span"]["backtest_id"].is_null()
+    }));
+
+    // Besides it, one log per trial backtest, each holding data-layer events
+    // tagged with the optimization, trial, backtest and strategy.
+    let mut logged_trials = HashSet::new();
+    for run in std::fs::read_dir(root.path()).unwrap() {
+        let run = run.unwrap();
+        let backtest_id = run.file_name().into_string().unwrap();
+        if backtest_id == optimization_id {
+            continue;
+        }
+        let log = std::fs::read_to_string(run.path().join(RUN_LOG_FILE)).unwrap();
+        let data_events: Vec<serde_json::Value> = log
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|line| line["target"].as_str().unwrap().starts_with("gb_data"))
+            .collect();
+        assert!(!data_events.is_empty(), "no data events for {backtest_id}");
+        for event in &data_events {
+            let span = &event["span"];
+            assert_eq!(span["optimization_id"], optimization_id.as_str());
+            assert_eq!(span["backtest_id"], backtest_id.as_str());
+            assert_eq!(span["strategy_id"], "ma_crossover");
+            logged_trials.insert(span["trial_id"].as_str().unwrap().to_string());
+        }
+    }
+    let trial_ids: HashSet<_> = trials.iter().map(|trial| trial.id.to_string()).collect();
+    assert_eq!(logged_trials, trial_ids);
+}
@@ -0,0 +1,211 @@
+//! Option expiry calendar — monthly (third Friday) and weekly (Friday)
+//! expirations on the NYSE holiday calendar, and the ACT/365 year fractions
+//! used for `PricingInput::time_to_expiry`.
+//!
+//! An expiration that falls on an exchange holiday moves to the previous
+//! trading day, usually the Thursday (e.g. Good Friday).
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc, Weekday};
+use std::ops::RangeInclusive;
+
+/// Seconds in an ACT/365 year.
+pub const SECONDS_PER_YEAR: f64 = 365.0 * 86_400.0;
+
+/// Year fraction from `now` to `expiry` (ACT/365), or 0 once expired.
+pub fn time_to_expiry(now: DateTime<Utc>, expiry: DateTime<Utc>) -> f64 {
+    (expiry - now).num_seconds().max(0) as f64 / SECONDS_PER_YEAR
+}
+
+/// Easter Sunday of `year` (anonymous Gregorian algorithm).
+fn easter_sunday(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("valid Easter date")
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).expect("valid weekday of month")
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+/// A fixed-date holiday moved to Friday when it falls on a Saturday and to
+/// Monday when it falls on a Sunday.
+fn observed(date: NaiveDate) -> NaiveDate {
+    match date.weekday() {
+        Weekday::Sat => date - Duration::days(1),
+        Weekday::Sun => date + Duration::days(1),
+        _ => date,
+    }
+}
+
+/// Full-day NYSE holidays observed in `year`, ascending.
+///
+/// Follows the exchange's standing rules (New Year's Day, Martin Luther King
+/// Jr. Day, Washington's Birthday, Good Friday, Memorial Day, Juneteenth from
+/// 2022, Independence Day, Labor Day, Thanksgiving, Christmas). One-off
+/// closures such as days of mourning are not included.
+pub fn nyse_holidays(year: i32) -> Vec<NaiveDate> {
+    let date = |month, day| NaiveDate::from_ymd_opt(year, month, day).expect("valid date");
+    let mut holidays = Vec::with_capacity(10);
+
+    // New Year's Day on a Saturday is not observed on the Friday before.
+    let new_year = date(1, 1);
+    if new_year.weekday() != Weekday::Sat {
+        holidays.push(observed(new_year));
+    }
+    holidays.push(nth_weekday(year, 1, Weekday::Mon, 3));
+    holidays.push(nth_weekday(year, 2, Weekday::Mon, 3));
+    holidays.push(easter_sunday(year) - Duration::days(2));
+    holidays.push(last_weekday(year, 5, Weekday::Mon));
+    if year >= 2022 {
+        holidays.push(observed(date(6, 19)));
+    }
+    holidays.push(observed(date(7, 4)));
+    holidays.push(nth_weekday(year, 9, Weekday::Mon, 1));
+    holidays.push(nth_weekday(year, 11, Weekday::Thu, 4));
+    holidays.push(observed(date(12, 25)));
+
+    holidays.sort();
+    holidays
+}
+
+/// True if the NYSE is closed all day on `date` for a holiday.
+pub fn is_nyse_holiday(date: NaiveDate) -> bool {
+    nyse_holidays(date.year()).contains(&date)
+}
+
+/// True if `date` is a weekday that is not an NYSE holiday.
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !is_nyse_holiday(date)
+}
+
+/// `date`, or the closest trading day before it.
+fn on_or_before_trading_day(mut date: NaiveDate) -> NaiveDate {
+    while !is_trading_day(date) {
+        date -= Duration::days(1);
+    }
+    date
+}
+
+/// Standard monthly expiration: the third Friday of `month`, moved to the
+/// previous trading day when that Friday is a holiday. `None` for an
+/// invalid month.
+pub fn monthly_expiry(year: i32, month: u32) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Fri, 3).map(on_or_before_trading_day)
+}
+
+/// Weekly expirations whose Friday falls within `range`: every Friday, moved
+/// to the previous trading day when it is a holiday. Monthly expirations are
+/// included, since they are also that week's expiration.
+pub fn weekly_expiries(range: RangeInclusive<NaiveDate>) -> Vec<NaiveDate> {
+    let (start, end) = (*range.start(), *range.end());
+    let mut friday = start
+        + Duration::days(
+            (Weekday::Fri.num_days_from_monday() as i64
+                - start.weekday().num_days_from_monday() as i64)
+                .rem_euclid(7),
+        );
+    let mut expiries = Vec::new();
+    while friday <= end {
+        expiries.push(on_or_before_trading_day(friday));
+        friday += Duration::days(7);
+    }
+    expiries
+}
+
+/// True if `date` is the (holiday-adjusted) expiration of its week.
+pub fn is_standard_expiry(date: NaiveDate) -> bool {
+    let friday = date
+        + Duration::days(
+            (Weekday::Fri.num_days_from_monday() as i64
+                - date.weekday().num_days_from_monday() as i64)
+                .rem_euclid(7),
+        );
+    on_or_before_trading_day(friday) == date
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn normal_month_expires_on_the_third_friday() {
+        assert_eq!(monthly_expiry(2026, 3), Some(day(2026, 3, 20)));
+        assert_eq!(monthly_expiry(2025, 1), Some(day(2025, 1, 17)));
+        assert!(is_standard_expiry(day(2026, 3, 20)));
+        assert_eq!(monthly_expiry(2026, 13), None);
+    }
+
+    #[test]
+    fn holiday_fridays_shift_to_thursday() {
+        // Good Friday 2025 was also April's third Friday.
+        assert!(is_nyse_holiday(day(2025, 4, 18)));
+        assert_eq!(monthly_expiry(2025, 4), Some(day(2025, 4, 17)));
+        // Juneteenth 2026 is June's third Friday.
+        assert_eq!(monthly_expiry(2026, 6), Some(day(2026, 6, 18)));
+        assert!(is_standard_expiry(day(2026, 6, 18)));
+        assert!(!is_standard_expiry(day(2026, 6, 19)));
+        assert!(!is_standard_expiry(day(2026, 6, 17)));
+    }
+
+    #[test]
+    fn weekly_expiries_cover_every_friday_in_range() {
+        // Good Friday 2026 is April 3.
+        assert_eq!(
+            weekly_expiries(day(2026, 3, 28)..=day(2026, 4, 17)),
+            vec![day(2026, 4, 2), day(2026, 4, 10), day(2026, 4, 17)]
+        );
+        assert!(weekly_expiries(day(2026, 4, 11)..=day(2026, 4, 16)).is_empty());
+    }
+
+    #[test]
+    fn holiday_rules_match_published_schedules() {
+        assert_eq!(
+            nyse_holidays(2026),
+            vec![
+                day(2026, 1, 1),
+                day(2026, 1, 19),
+                day(2026, 2, 16),
+                day(2026, 4, 3),
+                day(2026, 5, 25),
+                day(2026, 6, 19),
+                day(2026, 7, 3),
+                day(2026, 9, 7),
+                day(2026, 11, 26),
+                day(2026, 12, 25),
+            ]
+        );
+        // New Year's Day 2022 fell on a Saturday and was not observed.
+        assert!(!is_nyse_holiday(day(2021, 12, 31)));
+        assert!(!nyse_holidays(2021).contains(&day(2021, 6, 18)));
+    }
+
+    #[test]
+    fn time_to_expiry_is_act_365() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 2, 20, 0, 0).unwrap();
+        let expiry = Utc.with_ymd_and_hms(2027, 1, 2, 20, 0, 0).unwrap();
+        assert!((time_to_expiry(now, expiry) - 1.0).abs() < 1e-12);
+        assert_eq!(time_to_expiry(expiry, now), 0.0);
+    }
+}
@@ -1,14 +1,18 @@
-//! Options chain — a collection of contracts for a single underlying and expiration.
+//! Options chain — a collection of contracts for a single underlying and expiration,
+//! plus synthetic multi-expiry chains generated from an underlying bar.
 
-use chrono::{DateTime, Utc};
-use rust_decimal::prelude::ToPrimitive;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-use gb_types::market::Symbol;
+use gb_types::market::{Bar, Symbol};
 
+use crate::calendar::{monthly_expiry, weekly_expiries};
 use crate::contract::{ExerciseStyle, OptionContract, OptionKind};
-use crate::pricing::{black_scholes_price, PricingInput, PricingResult};
+use crate::pricing::{price, PricingInput, PricingResult};
+use crate::vol::{historical_vol, VolEstimator};
 
 /// A single row in an option chain (call + put at the same strike).
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -83,8 +87,8 @@ pub fn build_chain(
             time_to_expiry,
         };
 
-        let call_result = black_scholes_price(&call_contract, &input);
-        let put_result = black_scholes_price(&put_contract, &input);
+        let call_result = price(&call_contract, &input);
+        let put_result = price(&put_contract, &input);
 
         rows.push(ChainRow {
             strike,
@@ -131,6 +135,309 @@ impl OptionChain {
     }
 }
 
+// ---------- synthetic chain generation ----------
+
+/// Which strikes a generated chain lists: every multiple of `step` within
+/// `range_pct` of spot (e.g. every $5 within ±30 %).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StrikeRule {
+    pub step: f64,
+    pub range_pct: f64,
+}
+
+impl StrikeRule {
+    pub fn new(step: f64, range_pct: f64) -> Self {
+        Self { step, range_pct }
+    }
+
+    /// Listed strikes for `spot`, ascending.
+    pub fn strikes(&self, spot: f64) -> Vec<f64> {
+        if self.step <= 0.0 || spot <= 0.0 {
+            return Vec::new();
+        }
+        let low = (spot * (1.0 - self.range_pct)).max(0.0);
+        let high = spot * (1.0 + self.range_pct);
+        let first = (low / self.step).ceil() as i64;
+        let last = (high / self.step).floor() as i64;
+        (first.max(1)..=last)
+            .map(|i| i as f64 * self.step)
+            .collect()
+    }
+}
+
+/// Implied volatility assigned to each generated contract.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum VolModel {
+    /// The same volatility at every strike and expiry.
+    Flat { volatility: f64 },
+    /// `atm_vol + slope * m + curvature * m²` with log-moneyness
+    /// `m = ln(strike / spot)`; a negative slope gives the usual equity skew.
+    Skew {
+        atm_vol: f64,
+        slope: f64,
+        curvature: f64,
+    },
+}
+
+impl VolModel {
+    /// Floor on the volatility a skew can produce far from the money.
+    const MIN_VOL: f64 = 0.01;
+
+    pub fn volatility(&self, spot: f64, strike: f64) -> f64 {
+        match *self {
+            VolModel::Flat { volatility } => volatility,
+            VolModel::Skew {
+                atm_vol,
+                slope,
+                curvature,
+            } => {
+                let m = (strike / spot).ln();
+                (atm_vol + slope * m + curvature * m * m).max(Self::MIN_VOL)
+            }
+        }
+    }
+
+    /// Flat volatility estimated from the underlying's `bars` with
+    /// [`historical_vol`], for chains generated without market implied
+    /// volatility. `None` when the history is too short.
+    pub fn historical(bars: &[Bar], window: usize, method: VolEstimator) -> Option<Self> {
+        historical_vol(bars, window, method).map(|estimate| VolModel::Flat {
+            volatility: estimate.volatility.max(Self::MIN_VOL),
+        })
+    }
+}
+
+/// Quoted width around the theoretical price: the larger of `min_width`
+/// and `pct_of_price` of the price, split evenly either side.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SpreadModel {
+    pub min_width: f64,
+    pub pct_of_price: f64,
+}
+
+impl Default for SpreadModel {
+    fn default() -> Self {
+        Self {
+            min_width: 0.05,
+            pct_of_price: 0.02,
+        }
+    }
+}
+
+impl SpreadModel {
+    /// Bid and ask around `theoretical`, in cents; the bid never goes below zero.
+    pub fn quote(&self, theoretical: f64) -> (Decimal, Decimal) {
+        let half = (self.pct_of_price * theoretical).max(self.min_width) / 2.0;
+        let to_cents = |v: f64| {
+            Decimal::from_f64(v.max(0.0))
+                .unwrap_or_default()
+                .round_dp(2)
+        };
+        (to_cents(theoretical - half), to_cents(theoretical + half))
+    }
+}
+
+/// Market inputs and contract terms shared by every generated contract.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainParams {
+    pub risk_free_rate: f64,
+    pub dividend_yield: f64,
+    pub spread: SpreadModel,
+    pub exercise_style: ExerciseStyle,
+    pub multiplier: Decimal,
+}
+
+impl Default for ChainParams {
+    fn default() -> Self {
+        Self {
+            risk_free_rate: 0.05,
+            dividend_yield: 0.0,
+            spread: SpreadModel::default(),
+            exercise_style: ExerciseStyle::European,
+            multiplier: Decimal::from(100),
+        }
+    }
+}
+
+/// A generated contract with its theoretical value and quoted market.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionQuote {
+    pub contract: OptionContract,
+    pub volatility: f64,
+    pub theoretical: PricingResult,
+    pub bid: Decimal,
+    pub ask: Decimal,
+}
+
+impl OptionQuote {
+    pub fn mid(&self) -> Decimal {
+        (self.bid + self.ask) / Decimal::TWO
+    }
+}
+
+/// Call and put quotes at one strike.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuoteRow {
+    pub call: OptionQuote,
+    pub put: OptionQuote,
+}
+
+/// Quotes across several expiries, keyed by expiry and then strike.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainSnapshot {
+    pub underlying: Symbol,
+    pub spot: Decimal,
+    pub as_of: DateTime<Utc>,
+    pub expiries: BTreeMap<DateTime<Utc>, BTreeMap<Decimal, QuoteRow>>,
+}
+
+impl ChainSnapshot {
+    /// Quote for one contract.
+    pub fn quote(
+        &self,
+        expiration: DateTime<Utc>,
+        strike: Decimal,
+        kind: OptionKind,
+    ) -> Option<&OptionQuote> {
+        let row = self.expiries.get(&expiration)?.get(&strike)?;
+        Some(match kind {
+            OptionKind::Call => &row.call,
+            OptionKind::Put => &row.put,
+        })
+    }
+
+    /// Number of contracts quoted (calls and puts).
+    pub fn len(&self) -> usize {
+        self.expiries.values().map(|rows| rows.len() * 2).sum()
+    }
+
+    /// True if no contracts are quoted.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Expiries listed as of `as_of`: the next `weeklies` weekly expirations
+/// plus the monthly expiration of the next `monthlies` months, at 20:00 UTC
+/// (market close), ascending and without duplicates. Expirations follow
+/// [`calendar`](crate::calendar): Fridays, moved earlier for NYSE holidays.
+pub fn standard_expiries(
+    as_of: DateTime<Utc>,
+    weeklies: usize,
+    monthlies: usize,
+) -> Vec<DateTime<Utc>> {
+    let close = |date: NaiveDate| Utc.from_utc_datetime(&date.and_hms_opt(20, 0, 0).unwrap());
+
+    let mut expiries = Vec::with_capacity(weeklies + monthlies);
+    let mut week_start = as_of.date_naive();
+    while expiries.len() < weeklies {
+        let week_end = week_start + Duration::days(6);
+        expiries.extend(
+            weekly_expiries(week_start..=week_end)
+                .into_iter()
+                .map(close)
+                .filter(|expiry| *expiry > as_of),
+        );
+        week_start = week_end + Duration::days(1);
+    }
+
+    let today = as_of.date_naive();
+    let (mut year, mut month) = (today.year(), today.month());
+    let mut monthly = 0;
+    while monthly < monthlies {
+        let expiry = close(monthly_expiry(year, month).expect("valid month"));
+        if expiry > as_of {
+            expiries.push(expiry);
+            monthly += 1;
+        }
+        (year, month) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+    }
+
+    expiries.sort();
+    expiries.dedup();
+    expiries
+}
+
+/// Generate a synthetic chain as of `underlying_bar`'s close: calls and puts
+/// at every strike `strike_rule` lists for each expiry still open at the
+/// bar's timestamp, priced with [`price`] at the volatility `vol_model`
+/// assigns and quoted with `params.spread` around that price.
+pub fn generate_chain(
+    underlying_bar: &Bar,
+    expiries: &[DateTime<Utc>],
+    strike_rule: &StrikeRule,
+    vol_model: &VolModel,
+    params: &ChainParams,
+) -> ChainSnapshot {
+    let as_of = underlying_bar.timestamp;
+    let spot = underlying_bar.close.to_f64().unwrap_or(0.0);
+    let strikes = strike_rule.strikes(spot);
+
+    let quote = |kind: OptionKind, strike: Decimal, expiration: DateTime<Utc>, volatility: f64| {
+        let contract = OptionContract::new(
+            underlying_bar.symbol.clone(),
+            kind,
+            strike,
+            expiration,
+            params.exercise_style,
+            params.multiplier,
+        );
+        let input = PricingInput {
+            spot,
+            risk_free_rate: params.risk_free_rate,
+            volatility,
+            dividend_yield: params.dividend_yield,
+            time_to_expiry: contract.time_to_expiry(as_of),
+        };
+        let theoretical = price(&contract, &input);
+        let (bid, ask) = params
+            .spread
+            .quote(theoretical.price.to_f64().unwrap_or(0.0));
+        OptionQuote {
+            contract,
+            volatility,
+            theoretical,
+            bid,
+            ask,
+        }
+    };
+
+    let expiries = expiries
+        .iter()
+        .filter(|&&expiration| expiration > as_of)
+        .map(|&expiration| {
+            let rows = strikes
+                .iter()
+                .map(|&strike_f| {
+                    let strike = Decimal::from_f64(strike_f)
+                        .unwrap_or_default()
+                        .round_dp(4)
+                        .normalize();
+                    let volatility = vol_model.volatility(spot, strike_f);
+                    let row = QuoteRow {
+                        call: quote(OptionKind::Call, strike, expiration, volatility),
+                        put: quote(OptionKind::Put, strike, expiration, volatility),
+                    };
+                    (strike, row)
+                })
+                .collect();
+            (expiration, rows)
+        })
+        .collect();
+
+    ChainSnapshot {
+        underlying: underlying_bar.symbol.clone(),
+        spot: underlying_bar.close,
+        as_of,
+        expiries,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,4 +539,116 @@ mod tests {
             );
         }
     }
+
+    fn generated_snapshot(vol_model: VolModel) -> ChainSnapshot {
+        let as_of = Utc.with_ymd_and_hms(2026, 3, 2, 21, 0, 0).unwrap();
+        let bar = Bar::new(
+            Symbol::equity("AAPL"),
+            as_of,
+            dec!(148),
+            dec!(152),
+            dec!(147),
+            dec!(150),
+            dec!(1000000),
+            gb_types::market::Resolution::Day,
+        );
+        let params = ChainParams {
+            dividend_yield: 0.01,
+            ..ChainParams::default()
+        };
+        generate_chain(
+            &bar,
+            &standard_expiries(as_of, 2, 2),
+            &StrikeRule::new(5.0, 0.30),
+            &vol_model,
+            &params,
+        )
+    }
+
+    #[test]
+    fn test_standard_expiries_are_fridays_and_third_fridays() {
+        let as_of = Utc.with_ymd_and_hms(2026, 3, 2, 21, 0, 0).unwrap();
+        let expiries = standard_expiries(as_of, 3, 2);
+        let close = |d: u32, m: u32| Utc.with_ymd_and_hms(2026, m, d, 20, 0, 0).unwrap();
+        // Weeklies Mar 6/13/20; Mar 20 is also the March monthly.
+        assert_eq!(
+            expiries,
+            vec![close(6, 3), close(13, 3), close(20, 3), close(17, 4)]
+        );
+
+        // Good Friday (April 3) moves that week's expiry to Thursday.
+        let as_of = Utc.with_ymd_and_hms(2026, 3, 30, 21, 0, 0).unwrap();
+        assert_eq!(standard_expiries(as_of, 1, 0), vec![close(2, 4)]);
+    }
+
+    #[test]
+    fn test_strike_rule_spacing_and_range() {
+        let strikes = StrikeRule::new(5.0, 0.30).strikes(150.0);
+        assert_eq!(strikes.first(), Some(&105.0));
+        assert_eq!(strikes.last(), Some(&195.0));
+        assert_eq!(strikes.len(), 19);
+    }
+
+    #[test]
+    fn test_generated_chain_covers_every_expiry_and_strike() {
+        let chain = generated_snapshot(VolModel::Flat { volatility: 0.25 });
+        assert_eq!(chain.expiries.len(), 4);
+        assert_eq!(chain.len(), 4 * 19 * 2);
+        let expiry = *chain.expiries.keys().next().unwrap();
+        let put = chain.quote(expiry, dec!(150), OptionKind::Put).unwrap();
+        assert_eq!(put.contract.kind, OptionKind::Put);
+        assert!(put.bid < put.ask);
+        assert!(put.bid >= Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_generated_call_prices_fall_with_strike() {
+        for vol_model in [
+            VolModel::Flat { volatility: 0.25 },
+            VolModel::Skew {
+                atm_vol: 0.25,
+                slope: -0.2,
+                curvature: 0.1,
+            },
+        ] {
+            let chain = generated_snapshot(vol_model);
+            for rows in chain.expiries.values() {
+                let calls: Vec<_> = rows.values().map(|row| &row.call).collect();
+                for pair in calls.windows(2) {
+                    assert!(pair[1].theoretical.price <= pair[0].theoretical.price);
+                    assert!(pair[1].mid() <= pair[0].mid());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_generated_chain_put_call_parity() {
+        let chain = generated_snapshot(VolModel::Skew {
+            atm_vol: 0.25,
+            slope: -0.2,
+            curvature: 0.1,
+        });
+        let s = 150.0_f64;
+        for (expiry, rows) in &chain.expiries {
+            let t = rows
+                .values()
+                .next()
+                .unwrap()
+                .call
+                .contract
+                .time_to_expiry(chain.as_of);
+            for (strike, row) in rows {
+                let c = row.call.theoretical.price.to_f64().unwrap();
+                let p = row.put.theoretical.price.to_f64().unwrap();
+                let k = strike.to_f64().unwrap();
+                let rhs = s * (-0.01 * t).exp() - k * (-0.05 * t).exp();
+                assert!(
+                    (c - p - rhs).abs() < 1e-6,
+                    "parity violated at {expiry} {strike}: {} vs {rhs}",
+                    c - p
+                );
+            }
+        }
+    }
 }
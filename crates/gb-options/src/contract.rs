@@ -3,23 +3,11 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 
+pub use gb_types::market::OptionKind;
 use gb_types::market::Symbol;
 
-/// Option type — call or put.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
-pub enum OptionKind {
-    Call,
-    Put,
-}
-
-impl fmt::Display for OptionKind {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            OptionKind::Call => write!(f, "Call"),
-            OptionKind::Put => write!(f, "Put"),
-        }
-    }
-}
+use crate::calendar;
+use crate::execution::OptionsExecError;
 
 /// Exercise style.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -92,14 +80,20 @@ impl OptionContract {
         )
     }
 
-    /// Years remaining until expiration from `now`.
+    /// Years remaining until expiration from `now` (ACT/365).
     /// Returns 0 if already expired.
     pub fn time_to_expiry(&self, now: DateTime<Utc>) -> f64 {
-        let secs = (self.expiration - now).num_seconds();
-        if secs <= 0 {
-            0.0
+        calendar::time_to_expiry(now, self.expiration)
+    }
+
+    /// Check that the contract expires on a standard weekly or monthly
+    /// expiration date (see [`calendar::is_standard_expiry`]).
+    pub fn require_standard_expiry(self) -> Result<Self, OptionsExecError> {
+        let date = self.expiration.date_naive();
+        if calendar::is_standard_expiry(date) {
+            Ok(self)
         } else {
-            secs as f64 / (365.25 * 86400.0)
+            Err(OptionsExecError::NonStandardExpiry(date))
         }
     }
 
@@ -215,6 +209,21 @@ mod tests {
         assert!(c.is_expired(after));
     }
 
+    #[test]
+    fn test_require_standard_expiry() {
+        let c = sample_contract(OptionKind::Call, dec!(150));
+        // June 20, 2026 is a Saturday.
+        assert!(matches!(
+            c.clone().require_standard_expiry(),
+            Err(OptionsExecError::NonStandardExpiry(_))
+        ));
+        let monthly = OptionContract {
+            expiration: Utc.with_ymd_and_hms(2026, 6, 18, 20, 0, 0).unwrap(),
+            ..c
+        };
+        assert!(monthly.require_standard_expiry().is_ok());
+    }
+
     #[test]
     fn test_display() {
         let c = sample_contract(OptionKind::Call, dec!(150));
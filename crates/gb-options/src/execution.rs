@@ -1,6 +1,7 @@
 //! Options execution — fill simulation and exercise/assignment handling.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -8,8 +9,10 @@ use uuid::Uuid;
 
 use gb_types::orders::Side;
 
+use crate::chain::SpreadModel;
 use crate::contract::{OptionContract, OptionKind};
-use crate::pricing::{black_scholes_price, PricingInput};
+use crate::pricing::{price, PricingInput};
+use crate::strategies::MultiLegOrder;
 
 /// Errors specific to options execution.
 #[derive(Debug, Error)]
@@ -25,6 +28,14 @@ pub enum OptionsExecError {
     },
     #[error("invalid quantity: {0}")]
     InvalidQuantity(String),
+    #[error("invalid option strategy: {0}")]
+    InvalidStrategy(String),
+    #[error("net premium {net} is worse than the limit {limit}")]
+    LimitNotMet { net: Decimal, limit: Decimal },
+    #[error("no spot price for {0}")]
+    MissingSpot(String),
+    #[error("{0} is not a standard option expiration")]
+    NonStandardExpiry(NaiveDate),
 }
 
 /// An options trade (open or close).
@@ -80,7 +91,7 @@ pub fn simulate_open(
         return Err(OptionsExecError::Expired);
     }
 
-    let result = black_scholes_price(contract, input);
+    let result = price(contract, input);
     let premium = result.price;
     let commission = commission_per_contract * quantity;
 
@@ -96,6 +107,64 @@ pub fn simulate_open(
     })
 }
 
+/// Simulate opening every leg of a multi-leg order at model prices, all or
+/// nothing: no leg fills unless the order's net premium is within its limit
+/// and every leg can be opened. `input` supplies spot, volatility and rates;
+/// each leg's time to expiry is measured from `now`.
+pub fn simulate_multi_leg(
+    order: &MultiLegOrder,
+    input: &PricingInput,
+    now: DateTime<Utc>,
+    commission_per_contract: Decimal,
+    strategy_id: &str,
+) -> Result<Vec<OptionsTrade>, OptionsExecError> {
+    if order.legs.is_empty() {
+        return Err(OptionsExecError::InvalidStrategy(
+            "order has no legs".into(),
+        ));
+    }
+    if order.legs.iter().any(|leg| leg.ratio == 0) {
+        return Err(OptionsExecError::InvalidStrategy(
+            "leg ratios must be positive".into(),
+        ));
+    }
+
+    let trades = order
+        .legs
+        .iter()
+        .map(|leg| {
+            let leg_input = PricingInput {
+                time_to_expiry: leg.contract.time_to_expiry(now),
+                ..*input
+            };
+            let mut trade = simulate_open(
+                &leg.contract,
+                leg.side,
+                order.quantity * Decimal::from(leg.ratio),
+                &leg_input,
+                commission_per_contract,
+                strategy_id,
+            )?;
+            trade.executed_at = now;
+            Ok(trade)
+        })
+        .collect::<Result<Vec<_>, OptionsExecError>>()?;
+
+    if let Some(limit) = order.limit {
+        let net: Decimal = order
+            .legs
+            .iter()
+            .zip(&trades)
+            .map(|(leg, trade)| leg.signed_ratio() * trade.premium)
+            .sum();
+        if net > limit {
+            return Err(OptionsExecError::LimitNotMet { net, limit });
+        }
+    }
+
+    Ok(trades)
+}
+
 /// Simulate exercise at expiration (auto-exercise if ITM).
 pub fn simulate_exercise(
     contract: &OptionContract,
@@ -141,6 +210,101 @@ pub fn options_pnl(entry: &OptionsTrade, exit: &OptionsTrade) -> Decimal {
     entry.cash_flow() + exit.cash_flow()
 }
 
+/// Bid/ask-aware fill model for option orders.
+///
+/// Market orders take the touch: buys pay the ask, sells receive the bid.
+/// Marketable limit orders fill at the touch too. A limit resting strictly
+/// inside the spread fills at its limit with `inside_fill_probability`, and
+/// one at or beyond the far side of the spread never fills.
+///
+/// When no market quote is available, [`synthetic_quote`](Self::synthetic_quote)
+/// quotes `spread` around the model price, widened for strikes far from spot
+/// and for contracts close to expiry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionsFillModel {
+    /// Spread around the model price for an at-the-money contract far from
+    /// expiry.
+    pub spread: SpreadModel,
+    /// Added to the spread multiplier per unit of |ln(strike / spot)|.
+    pub moneyness_widening: f64,
+    /// Added to the spread multiplier divided by the days left to expiry
+    /// (at least one).
+    pub expiry_widening: f64,
+    /// Chance that a limit order resting inside the spread is filled.
+    pub inside_fill_probability: f64,
+}
+
+impl Default for OptionsFillModel {
+    fn default() -> Self {
+        Self {
+            spread: SpreadModel::default(),
+            moneyness_widening: 4.0,
+            expiry_widening: 2.0,
+            inside_fill_probability: 0.5,
+        }
+    }
+}
+
+impl OptionsFillModel {
+    /// Factor the base spread is scaled by for `contract` with the underlying
+    /// at `spot`, as of `now`. 1 for an at-the-money contract far from expiry.
+    pub fn spread_multiplier(
+        &self,
+        contract: &OptionContract,
+        spot: f64,
+        now: DateTime<Utc>,
+    ) -> f64 {
+        let strike = contract.strike.to_f64().unwrap_or(0.0);
+        let moneyness = if strike > 0.0 && spot > 0.0 {
+            (strike / spot).ln().abs()
+        } else {
+            0.0
+        };
+        let days_to_expiry = (contract.time_to_expiry(now) * 365.0).max(1.0);
+        (1.0 + self.moneyness_widening * moneyness) * (1.0 + self.expiry_widening / days_to_expiry)
+    }
+
+    /// Bid and ask around the model price `theoretical`, with the spread
+    /// widened by [`spread_multiplier`](Self::spread_multiplier).
+    pub fn synthetic_quote(
+        &self,
+        contract: &OptionContract,
+        theoretical: f64,
+        spot: f64,
+        now: DateTime<Utc>,
+    ) -> (Decimal, Decimal) {
+        let multiplier = self.spread_multiplier(contract, spot, now);
+        SpreadModel {
+            min_width: self.spread.min_width * multiplier,
+            pct_of_price: self.spread.pct_of_price * multiplier,
+        }
+        .quote(theoretical)
+    }
+
+    /// Per-share fill price of an order against a `bid`/`ask` market, or
+    /// `None` if it does not fill. `draw` is a uniform number in [0, 1)
+    /// compared with `inside_fill_probability` for limits inside the spread.
+    pub fn fill_price(
+        &self,
+        side: Side,
+        limit: Option<Decimal>,
+        bid: Decimal,
+        ask: Decimal,
+        draw: f64,
+    ) -> Option<Decimal> {
+        let inside_fills = draw < self.inside_fill_probability;
+        match (side, limit) {
+            (Side::Buy, None) => Some(ask),
+            (Side::Sell, None) => Some(bid),
+            (Side::Buy, Some(limit)) if limit >= ask => Some(ask),
+            (Side::Sell, Some(limit)) if limit <= bid => Some(bid),
+            (Side::Buy, Some(limit)) if limit > bid && inside_fills => Some(limit),
+            (Side::Sell, Some(limit)) if limit < ask && inside_fills => Some(limit),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,6 +396,43 @@ mod tests {
         assert!(matches!(err, Err(OptionsExecError::OutOfTheMoney)));
     }
 
+    #[test]
+    fn test_multi_leg_fills_all_legs_or_none() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 20, 20, 0, 0).unwrap();
+        let condor = crate::strategies::iron_condor(
+            Symbol::equity("AAPL"),
+            Utc.with_ymd_and_hms(2026, 6, 20, 20, 0, 0).unwrap(),
+            dec!(130),
+            dec!(140),
+            dec!(170),
+            dec!(180),
+        )
+        .unwrap()
+        .with_quantity(dec!(2));
+
+        let trades =
+            simulate_multi_leg(&condor, &default_input(), now, dec!(0.65), "test").unwrap();
+        assert_eq!(trades.len(), 4);
+        assert!(trades.iter().all(|trade| trade.quantity == dec!(2)));
+        let credit: Decimal = trades.iter().map(OptionsTrade::cash_flow).sum();
+        assert!(credit > Decimal::ZERO, "condor collects a credit: {credit}");
+
+        // Demanding more credit than the legs are worth fills nothing.
+        let greedy = condor.with_credit_limit(dec!(10));
+        let err = simulate_multi_leg(&greedy, &default_input(), now, dec!(0.65), "test");
+        assert!(matches!(err, Err(OptionsExecError::LimitNotMet { .. })));
+
+        // One expired leg blocks the whole order.
+        let mut stale = crate::strategies::straddle(
+            Symbol::equity("AAPL"),
+            Utc.with_ymd_and_hms(2026, 6, 20, 20, 0, 0).unwrap(),
+            dec!(150),
+        );
+        stale.legs[1].contract.expiration = now;
+        let err = simulate_multi_leg(&stale, &default_input(), now, dec!(0.65), "test");
+        assert!(matches!(err, Err(OptionsExecError::Expired)));
+    }
+
     #[test]
     fn test_pnl_round_trip() {
         let c = make_contract(OptionKind::Call);
@@ -249,4 +450,72 @@ mod tests {
         // Should be positive (underlying moved in our favour)
         assert!(pnl > Decimal::ZERO, "pnl = {pnl}");
     }
+
+    fn fill_model_contract(strike: Decimal, days: i64) -> OptionContract {
+        OptionContract::equity(
+            Symbol::equity("AAPL"),
+            OptionKind::Call,
+            strike,
+            fill_model_now() + chrono::Duration::days(days),
+        )
+    }
+
+    fn fill_model_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 20, 20, 0, 0).unwrap()
+    }
+
+    /// Premium paid over the model price, as a fraction of it, buying
+    /// `contract` at the synthetic ask.
+    fn buy_cost_over_model(model: &OptionsFillModel, contract: &OptionContract) -> f64 {
+        let input = PricingInput {
+            time_to_expiry: contract.time_to_expiry(fill_model_now()),
+            ..default_input()
+        };
+        let theoretical = price(contract, &input).price.to_f64().unwrap();
+        let (bid, ask) = model.synthetic_quote(contract, theoretical, input.spot, fill_model_now());
+        assert!(bid <= ask);
+        let paid = model
+            .fill_price(Side::Buy, None, bid, ask, 0.0)
+            .unwrap()
+            .to_f64()
+            .unwrap();
+        paid / theoretical - 1.0
+    }
+
+    #[test]
+    fn test_fill_model_otm_trade_pays_more_than_atm() {
+        let model = OptionsFillModel::default();
+        let atm = buy_cost_over_model(&model, &fill_model_contract(dec!(155), 90));
+        let otm = buy_cost_over_model(&model, &fill_model_contract(dec!(190), 90));
+        let otm_near_expiry = buy_cost_over_model(&model, &fill_model_contract(dec!(165), 2));
+        assert!(atm > 0.0 && atm < 0.02, "atm slippage = {atm}");
+        assert!(otm > 3.0 * atm, "otm {otm} vs atm {atm}");
+        assert!(
+            otm_near_expiry > 3.0 * atm,
+            "near expiry {otm_near_expiry} vs atm {atm}"
+        );
+    }
+
+    #[test]
+    fn test_fill_model_limits_fill_against_the_touch() {
+        let model = OptionsFillModel {
+            inside_fill_probability: 0.3,
+            ..OptionsFillModel::default()
+        };
+        let (bid, ask) = (dec!(1.00), dec!(1.20));
+        let fill = |side, limit, draw| model.fill_price(side, limit, bid, ask, draw);
+
+        assert_eq!(fill(Side::Buy, None, 0.9), Some(ask));
+        assert_eq!(fill(Side::Sell, None, 0.9), Some(bid));
+        // marketable limits take the touch
+        assert_eq!(fill(Side::Buy, Some(dec!(1.50)), 0.9), Some(ask));
+        assert_eq!(fill(Side::Sell, Some(dec!(0.90)), 0.9), Some(bid));
+        // inside the spread: fills at the limit only on a lucky draw
+        assert_eq!(fill(Side::Buy, Some(dec!(1.10)), 0.1), Some(dec!(1.10)));
+        assert_eq!(fill(Side::Buy, Some(dec!(1.10)), 0.5), None);
+        assert_eq!(fill(Side::Sell, Some(dec!(1.15)), 0.29), Some(dec!(1.15)));
+        // at or beyond the far side never fills
+        assert_eq!(fill(Side::Buy, Some(bid), 0.0), None);
+        assert_eq!(fill(Side::Sell, Some(dec!(1.25)), 0.0), None);
+    }
 }
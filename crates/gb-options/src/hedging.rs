@@ -0,0 +1,411 @@
+//! Delta-hedging simulation — replays an option position against a series
+//! of underlying bars, trading the underlying to stay delta neutral, and
+//! attributes the resulting PnL to realized versus implied volatility.
+//!
+//! The option is bought (or sold) at the model price on the first bar and
+//! valued at the model price (intrinsic value once expired) on the last bar
+//! at or before expiration. Cash earns the risk-free rate in between.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use gb_types::market::Bar;
+
+use crate::calendar::time_to_expiry;
+use crate::contract::OptionContract;
+use crate::execution::OptionsExecError;
+use crate::pricing::{price, PricingInput};
+
+/// When the hedge is brought back to delta neutral. The first bar is always
+/// hedged.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum RehedgeRule {
+    /// Every bar.
+    EveryBar,
+    /// Whenever the net delta of option plus hedge exceeds `shares` in
+    /// absolute value.
+    DeltaThreshold { shares: Decimal },
+    /// Every `bars` bars.
+    FixedInterval { bars: usize },
+}
+
+/// Cost of trading the underlying: a per-share fee plus a fraction of the
+/// traded notional.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct HedgeCostModel {
+    pub per_share: Decimal,
+    pub basis_points: Decimal,
+}
+
+impl HedgeCostModel {
+    pub fn cost(&self, shares: Decimal, spot: Decimal) -> Decimal {
+        shares.abs() * (self.per_share + spot * self.basis_points / Decimal::from(10_000))
+    }
+}
+
+/// The hedged position and the inputs used to price it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HedgeInputs {
+    /// Signed number of contracts held (negative = short).
+    pub contracts: Decimal,
+    /// Volatility the option is priced and hedged at.
+    pub implied_volatility: f64,
+    pub risk_free_rate: f64,
+    pub dividend_yield: f64,
+    pub costs: HedgeCostModel,
+}
+
+/// State after processing one bar.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HedgeLedgerEntry {
+    pub timestamp: DateTime<Utc>,
+    pub spot: Decimal,
+    /// Model value of the whole option position.
+    pub option_value: Decimal,
+    /// Model delta per share of one contract.
+    pub delta: Decimal,
+    /// Underlying shares traded on this bar (negative = sold).
+    pub traded_shares: Decimal,
+    /// Underlying shares held after trading.
+    pub hedge_shares: Decimal,
+    pub transaction_cost: Decimal,
+    pub cash: Decimal,
+    /// Option value + hedge value + cash: the cumulative PnL.
+    pub pnl: Decimal,
+}
+
+/// Ledger and PnL attribution of a delta-hedge simulation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HedgeResult {
+    pub ledger: Vec<HedgeLedgerEntry>,
+    pub total_pnl: Decimal,
+    pub transaction_costs: Decimal,
+    pub rehedges: usize,
+    /// Annualized volatility of the bar-to-bar log returns.
+    pub realized_volatility: f64,
+    pub implied_volatility: f64,
+    /// Sum over bars of ½ · Γ · S² · (r² − σ²ᵢ · Δt) for the position: the
+    /// PnL earned from realized variance exceeding implied variance.
+    pub volatility_pnl: Decimal,
+    /// What remains after volatility PnL and transaction costs, mostly
+    /// discrete-hedging error.
+    pub residual_pnl: Decimal,
+}
+
+fn decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default()
+}
+
+/// Simulate delta hedging `inputs.contracts` of `contract` along `bars` (in
+/// time order, closes used as spot) under `rule`.
+pub fn simulate_delta_hedge(
+    contract: &OptionContract,
+    bars: &[Bar],
+    rule: RehedgeRule,
+    inputs: &HedgeInputs,
+) -> Result<HedgeResult, OptionsExecError> {
+    let bars: Vec<&Bar> = bars
+        .iter()
+        .take_while(|bar| bar.timestamp <= contract.expiration)
+        .collect();
+    let Some(first) = bars.first() else {
+        return Err(OptionsExecError::Expired);
+    };
+    if contract.is_expired(first.timestamp) {
+        return Err(OptionsExecError::Expired);
+    }
+    if inputs.contracts.is_zero() {
+        return Err(OptionsExecError::InvalidQuantity(
+            "contracts must be non-zero".into(),
+        ));
+    }
+    if let RehedgeRule::FixedInterval { bars: 0 } = rule {
+        return Err(OptionsExecError::InvalidStrategy(
+            "rehedge interval must be at least one bar".into(),
+        ));
+    }
+
+    let position_shares = inputs.contracts * contract.multiplier;
+    let pricing_at = |bar: &Bar| {
+        price(
+            contract,
+            &PricingInput {
+                spot: bar.close.to_f64().unwrap_or(0.0),
+                risk_free_rate: inputs.risk_free_rate,
+                volatility: inputs.implied_volatility,
+                dividend_yield: inputs.dividend_yield,
+                time_to_expiry: contract.time_to_expiry(bar.timestamp),
+            },
+        )
+    };
+
+    let mut ledger = Vec::with_capacity(bars.len());
+    let mut cash = Decimal::ZERO;
+    let mut hedge_shares = Decimal::ZERO;
+    let mut transaction_costs = Decimal::ZERO;
+    let mut rehedges = 0;
+    let mut volatility_pnl = 0.0;
+    let mut log_returns = Vec::with_capacity(bars.len());
+    let mut previous: Option<(&Bar, f64)> = None;
+
+    for (index, bar) in bars.iter().enumerate() {
+        let pricing = pricing_at(bar);
+        let option_value = if contract.is_expired(bar.timestamp) {
+            contract.intrinsic_value(bar.close) * position_shares
+        } else {
+            pricing.price * position_shares
+        };
+
+        if let Some((last, last_gamma)) = previous {
+            let dt = time_to_expiry(last.timestamp, bar.timestamp);
+            cash += cash * decimal((inputs.risk_free_rate * dt).exp() - 1.0);
+
+            let last_spot = last.close.to_f64().unwrap_or(0.0);
+            let spot = bar.close.to_f64().unwrap_or(0.0);
+            if last_spot > 0.0 && spot > 0.0 {
+                let simple_return = spot / last_spot - 1.0;
+                log_returns.push((spot / last_spot).ln());
+                volatility_pnl += 0.5
+                    * last_gamma
+                    * last_spot
+                    * last_spot
+                    * (simple_return * simple_return
+                        - inputs.implied_volatility * inputs.implied_volatility * dt);
+            }
+        } else {
+            // Open the option position at the model price.
+            cash -= option_value;
+        }
+
+        let net_delta = pricing.greeks.delta * position_shares + hedge_shares;
+        let rehedge = !contract.is_expired(bar.timestamp)
+            && match rule {
+                RehedgeRule::EveryBar => true,
+                RehedgeRule::DeltaThreshold { shares } => index == 0 || net_delta.abs() > shares,
+                RehedgeRule::FixedInterval { bars } => index % bars == 0,
+            };
+        let traded_shares = if rehedge { -net_delta } else { Decimal::ZERO };
+        let transaction_cost = inputs.costs.cost(traded_shares, bar.close);
+        if rehedge {
+            rehedges += 1;
+            hedge_shares += traded_shares;
+            cash -= traded_shares * bar.close + transaction_cost;
+            transaction_costs += transaction_cost;
+        }
+
+        ledger.push(HedgeLedgerEntry {
+            timestamp: bar.timestamp,
+            spot: bar.close,
+            option_value,
+            delta: pricing.greeks.delta,
+            traded_shares,
+            hedge_shares,
+            transaction_cost,
+            cash,
+            pnl: option_value + hedge_shares * bar.close + cash,
+        });
+        previous = Some((
+            bar,
+            pricing.greeks.gamma.to_f64().unwrap_or(0.0) * position_shares.to_f64().unwrap_or(0.0),
+        ));
+    }
+
+    let total_pnl = ledger.last().map(|entry| entry.pnl).unwrap_or_default();
+    let realized_volatility = annualized_volatility(&bars, &log_returns);
+    let volatility_pnl = decimal(volatility_pnl);
+
+    Ok(HedgeResult {
+        ledger,
+        total_pnl,
+        transaction_costs,
+        rehedges,
+        realized_volatility,
+        implied_volatility: inputs.implied_volatility,
+        volatility_pnl,
+        residual_pnl: total_pnl - volatility_pnl + transaction_costs,
+    })
+}
+
+/// Annualized standard deviation of `log_returns` about zero, using the
+/// average bar spacing of `bars`.
+fn annualized_volatility(bars: &[&Bar], log_returns: &[f64]) -> f64 {
+    let (Some(first), Some(last)) = (bars.first(), bars.last()) else {
+        return 0.0;
+    };
+    if log_returns.is_empty() {
+        return 0.0;
+    }
+    let years = time_to_expiry(first.timestamp, last.timestamp);
+    if years <= 0.0 {
+        return 0.0;
+    }
+    let variance: f64 = log_returns.iter().map(|r| r * r).sum();
+    (variance / years).sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::calendar::SECONDS_PER_YEAR;
+    use crate::contract::OptionKind;
+    use chrono::{Duration, TimeZone};
+    use gb_types::market::{Resolution, Symbol};
+    use rust_decimal_macros::dec;
+
+    const STEPS: i64 = 2_000;
+
+    fn start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 2, 0, 0, 0).unwrap()
+    }
+
+    fn contract() -> OptionContract {
+        // Expires just after the last bar of a 0.25-year path.
+        OptionContract::equity(
+            Symbol::equity("SPY"),
+            OptionKind::Call,
+            dec!(100),
+            start() + Duration::seconds((0.25 * SECONDS_PER_YEAR) as i64),
+        )
+    }
+
+    /// A path whose log returns alternate ±σ√Δt, so its realized volatility
+    /// is exactly `volatility`, over 0.25 years in `STEPS` steps.
+    fn path(volatility: f64) -> Vec<Bar> {
+        let step_seconds = (0.25 * SECONDS_PER_YEAR) as i64 / STEPS;
+        let dt = step_seconds as f64 / SECONDS_PER_YEAR;
+        let move_size = volatility * dt.sqrt();
+        let mut spot = 100.0_f64;
+        (0..STEPS)
+            .map(|step| {
+                if step > 0 {
+                    spot *= if step % 2 == 1 { move_size } else { -move_size }.exp();
+                }
+                let close = decimal(spot);
+                Bar::new(
+                    Symbol::equity("SPY"),
+                    start() + Duration::seconds(step * step_seconds),
+                    close,
+                    close,
+                    close,
+                    close,
+                    dec!(1_000_000),
+                    Resolution::Hour,
+                )
+            })
+            .collect()
+    }
+
+    fn inputs(contracts: Decimal) -> HedgeInputs {
+        HedgeInputs {
+            contracts,
+            implied_volatility: 0.2,
+            risk_free_rate: 0.03,
+            dividend_yield: 0.0,
+            costs: HedgeCostModel::default(),
+        }
+    }
+
+    #[test]
+    fn hedging_at_implied_volatility_without_costs_breaks_even() {
+        let result = simulate_delta_hedge(
+            &contract(),
+            &path(0.2),
+            RehedgeRule::EveryBar,
+            &inputs(dec!(1)),
+        )
+        .unwrap();
+        let premium = result.ledger[0].option_value;
+
+        assert!((result.realized_volatility - 0.2).abs() < 1e-3);
+        assert_eq!(result.rehedges, STEPS as usize);
+        assert_eq!(result.transaction_costs, Decimal::ZERO);
+        assert!(
+            result.total_pnl.abs() < premium * dec!(0.02),
+            "pnl {} vs premium {}",
+            result.total_pnl,
+            premium
+        );
+        assert!(result.volatility_pnl.abs() < premium * dec!(0.02));
+    }
+
+    #[test]
+    fn realized_above_implied_pays_the_long_gamma_hedger() {
+        let long = simulate_delta_hedge(
+            &contract(),
+            &path(0.3),
+            RehedgeRule::EveryBar,
+            &inputs(dec!(1)),
+        )
+        .unwrap();
+        assert!((long.realized_volatility - 0.3).abs() < 1e-3);
+        assert!(long.total_pnl > Decimal::ZERO, "pnl {}", long.total_pnl);
+        assert!(long.volatility_pnl > Decimal::ZERO);
+        assert!(long.residual_pnl.abs() < long.total_pnl * dec!(0.2));
+
+        let short = simulate_delta_hedge(
+            &contract(),
+            &path(0.3),
+            RehedgeRule::EveryBar,
+            &inputs(dec!(-1)),
+        )
+        .unwrap();
+        assert!(short.total_pnl < Decimal::ZERO);
+    }
+
+    #[test]
+    fn sparser_rules_trade_less_and_costs_are_charged() {
+        let mut costly = inputs(dec!(10));
+        costly.costs = HedgeCostModel {
+            per_share: dec!(0.01),
+            basis_points: dec!(1),
+        };
+        let every_bar =
+            simulate_delta_hedge(&contract(), &path(0.2), RehedgeRule::EveryBar, &costly).unwrap();
+        let interval = simulate_delta_hedge(
+            &contract(),
+            &path(0.2),
+            RehedgeRule::FixedInterval { bars: 100 },
+            &costly,
+        )
+        .unwrap();
+        let threshold = simulate_delta_hedge(
+            &contract(),
+            &path(0.2),
+            RehedgeRule::DeltaThreshold { shares: dec!(25) },
+            &costly,
+        )
+        .unwrap();
+
+        assert_eq!(interval.rehedges, (STEPS as usize).div_ceil(100));
+        assert!(threshold.rehedges < every_bar.rehedges);
+        assert!(interval.transaction_costs < every_bar.transaction_costs);
+        assert!(every_bar.transaction_costs > Decimal::ZERO);
+        let costs: Decimal = every_bar.ledger.iter().map(|e| e.transaction_cost).sum();
+        assert_eq!(costs, every_bar.transaction_costs);
+        for entry in threshold
+            .ledger
+            .iter()
+            .filter(|e| !e.traded_shares.is_zero())
+        {
+            assert!((entry.delta * dec!(1000) + entry.hedge_shares).abs() < dec!(0.001));
+        }
+    }
+
+    #[test]
+    fn rejects_expired_contracts_and_empty_positions() {
+        let bars = path(0.2);
+        let mut expired = contract();
+        expired.expiration = start() - Duration::days(1);
+        assert!(matches!(
+            simulate_delta_hedge(&expired, &bars, RehedgeRule::EveryBar, &inputs(dec!(1))),
+            Err(OptionsExecError::Expired)
+        ));
+        assert!(matches!(
+            simulate_delta_hedge(&contract(), &bars, RehedgeRule::EveryBar, &inputs(dec!(0))),
+            Err(OptionsExecError::InvalidQuantity(_))
+        ));
+    }
+}
@@ -1,11 +1,21 @@
+pub mod calendar;
 pub mod chain;
 pub mod contract;
 pub mod execution;
 pub mod greeks;
+pub mod hedging;
+pub mod margin;
 pub mod pricing;
+pub mod strategies;
+pub mod vol;
 
+pub use calendar::*;
 pub use chain::*;
 pub use contract::*;
 pub use execution::*;
 pub use greeks::*;
+pub use hedging::*;
+pub use margin::*;
 pub use pricing::*;
+pub use strategies::*;
+pub use vol::*;
@@ -0,0 +1,274 @@
+//! Reg-T style margin requirements for option positions.
+//!
+//! Requirements are the equity a position ties up, in account currency:
+//!
+//! * long options — the premium (paid in full);
+//! * naked short calls — `max(20% × spot − OTM amount, 10% × spot) + premium`;
+//! * naked short puts — `max(20% × spot − OTM amount, 10% × strike) + premium`;
+//! * spreads — a short leg covered by a long leg of the same underlying and
+//!   kind expiring no earlier is margined at its maximum loss: the strike
+//!   width the long leg does not cover minus the net credit (or, for a debit
+//!   spread, the debit).
+//!
+//! Every figure is per share and scaled by contracts × multiplier.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use gb_types::market::Symbol;
+
+use crate::contract::{OptionContract, OptionKind};
+use crate::execution::OptionsExecError;
+
+/// An option holding to margin: signed contracts (negative = short) and the
+/// current per-share mark.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarginPosition {
+    pub contract: OptionContract,
+    pub contracts: Decimal,
+    pub mark: Decimal,
+}
+
+impl MarginPosition {
+    pub fn new(contract: OptionContract, contracts: Decimal, mark: Decimal) -> Self {
+        Self {
+            contract,
+            contracts,
+            mark,
+        }
+    }
+}
+
+/// Per-share requirement of one naked short contract.
+fn naked_short_per_share(contract: &OptionContract, mark: Decimal, spot: Decimal) -> Decimal {
+    let twenty_pct = Decimal::new(2, 1);
+    let ten_pct = Decimal::new(1, 1);
+    let (out_of_the_money, floor) = match contract.kind {
+        OptionKind::Call => ((contract.strike - spot).max(Decimal::ZERO), ten_pct * spot),
+        OptionKind::Put => (
+            (spot - contract.strike).max(Decimal::ZERO),
+            ten_pct * contract.strike,
+        ),
+    };
+    (twenty_pct * spot - out_of_the_money).max(floor) + mark
+}
+
+/// Per-share requirement of a short leg paired with a long leg: the strike
+/// width the long leg leaves uncovered plus the net debit (minus the net
+/// credit), never negative.
+fn spread_per_share(short: &MarginPosition, long: &MarginPosition) -> Decimal {
+    let uncovered_width = match short.contract.kind {
+        OptionKind::Call => long.contract.strike - short.contract.strike,
+        OptionKind::Put => short.contract.strike - long.contract.strike,
+    }
+    .max(Decimal::ZERO);
+    (uncovered_width + long.mark - short.mark).max(Decimal::ZERO)
+}
+
+/// Requirement of one position margined on its own: long positions at their
+/// premium, short positions as naked.
+pub fn margin_requirement(position: &MarginPosition, spot: Decimal) -> Decimal {
+    let per_share = if position.contracts >= Decimal::ZERO {
+        position.mark
+    } else {
+        naked_short_per_share(&position.contract, position.mark, spot)
+    };
+    per_share * position.contracts.abs() * position.contract.multiplier
+}
+
+/// Requirement of a set of positions, pairing each short leg with the long
+/// leg of the same underlying, kind and multiplier (expiring no earlier)
+/// that margins it most cheaply. Shorts left unpaired are margined as naked
+/// and unpaired longs at their premium. `spots` must hold a price for every
+/// underlying with a short position.
+///
+/// Pairs are formed leg by leg, so an iron condor is margined as its two
+/// credit spreads added together.
+pub fn portfolio_margin_requirement(
+    positions: &[MarginPosition],
+    spots: &HashMap<Symbol, Decimal>,
+) -> Result<Decimal, OptionsExecError> {
+    let mut long_remaining: Vec<Decimal> = positions
+        .iter()
+        .map(|position| position.contracts.max(Decimal::ZERO))
+        .collect();
+    let mut requirement = Decimal::ZERO;
+
+    for short in positions.iter().filter(|p| p.contracts < Decimal::ZERO) {
+        let mut short_remaining = short.contracts.abs();
+        while short_remaining > Decimal::ZERO {
+            let best_long = positions
+                .iter()
+                .enumerate()
+                .filter(|(index, long)| {
+                    long_remaining[*index] > Decimal::ZERO
+                        && long.contract.underlying == short.contract.underlying
+                        && long.contract.kind == short.contract.kind
+                        && long.contract.multiplier == short.contract.multiplier
+                        && long.contract.expiration >= short.contract.expiration
+                })
+                .min_by_key(|(_, long)| spread_per_share(short, long));
+            let Some((index, long)) = best_long else {
+                break;
+            };
+            let paired = short_remaining.min(long_remaining[index]);
+            requirement += spread_per_share(short, long) * paired * short.contract.multiplier;
+            short_remaining -= paired;
+            long_remaining[index] -= paired;
+        }
+
+        if short_remaining > Decimal::ZERO {
+            let spot = spots
+                .get(&short.contract.underlying)
+                .copied()
+                .ok_or_else(|| {
+                    OptionsExecError::MissingSpot(short.contract.underlying.to_string())
+                })?;
+            requirement += naked_short_per_share(&short.contract, short.mark, spot)
+                * short_remaining
+                * short.contract.multiplier;
+        }
+    }
+
+    for (position, remaining) in positions.iter().zip(long_remaining) {
+        requirement += position.mark * remaining * position.contract.multiplier;
+    }
+
+    Ok(requirement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use rust_decimal_macros::dec;
+
+    fn expiry() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 6, 19, 20, 0, 0).unwrap()
+    }
+
+    fn position(
+        kind: OptionKind,
+        strike: Decimal,
+        contracts: Decimal,
+        mark: Decimal,
+    ) -> MarginPosition {
+        MarginPosition::new(
+            OptionContract::equity(Symbol::equity("AAPL"), kind, strike, expiry()),
+            contracts,
+            mark,
+        )
+    }
+
+    fn spots() -> HashMap<Symbol, Decimal> {
+        HashMap::from([(Symbol::equity("AAPL"), dec!(100))])
+    }
+
+    #[test]
+    fn single_positions_match_hand_computed_requirements() {
+        use OptionKind::{Call, Put};
+        // (kind, strike, contracts, mark, requirement) with spot = 100
+        let table = [
+            // OTM call: 20 − 5 = 15 > 10, plus 2 premium
+            (Call, dec!(105), dec!(-1), dec!(2), dec!(1700)),
+            // ITM call: nothing out of the money
+            (Call, dec!(95), dec!(-1), dec!(7), dec!(2700)),
+            // deep OTM call falls to the 10% of spot floor
+            (Call, dec!(150), dec!(-1), dec!(0.05), dec!(1005)),
+            // OTM put: 20 − 5 = 15 > 9.5
+            (Put, dec!(95), dec!(-1), dec!(1.5), dec!(1650)),
+            // deep OTM put falls to 10% of the strike
+            (Put, dec!(50), dec!(-3), dec!(0.1), dec!(1530)),
+            // longs cost their premium
+            (Call, dec!(110), dec!(2), dec!(3), dec!(600)),
+            (Put, dec!(90), dec!(1), dec!(1.25), dec!(125)),
+        ];
+        for (kind, strike, contracts, mark, expected) in table {
+            let position = position(kind, strike, contracts, mark);
+            assert_eq!(
+                margin_requirement(&position, dec!(100)),
+                expected,
+                "{kind} {strike} x {contracts}"
+            );
+            assert_eq!(
+                portfolio_margin_requirement(&[position], &spots()).unwrap(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn spreads_are_margined_at_width_less_credit() {
+        use OptionKind::{Call, Put};
+        // bear call credit spread: width 5, credit 2
+        let bear_call = [
+            position(Call, dec!(100), dec!(-1), dec!(4)),
+            position(Call, dec!(105), dec!(1), dec!(2)),
+        ];
+        assert_eq!(
+            portfolio_margin_requirement(&bear_call, &spots()).unwrap(),
+            dec!(300)
+        );
+
+        // bull put credit spread on two lots: (5 − 1.8) × 2 × 100
+        let bull_put = [
+            position(Put, dec!(95), dec!(-2), dec!(3)),
+            position(Put, dec!(90), dec!(2), dec!(1.2)),
+        ];
+        assert_eq!(
+            portfolio_margin_requirement(&bull_put, &spots()).unwrap(),
+            dec!(640)
+        );
+
+        // debit call spread costs its debit
+        let bull_call = [
+            position(Call, dec!(100), dec!(1), dec!(4)),
+            position(Call, dec!(105), dec!(-1), dec!(2)),
+        ];
+        assert_eq!(
+            portfolio_margin_requirement(&bull_call, &spots()).unwrap(),
+            dec!(200)
+        );
+
+        // iron condor: both one-lot credit spreads
+        let condor = [
+            position(Put, dec!(90), dec!(1), dec!(1.2)),
+            position(Put, dec!(95), dec!(-1), dec!(3)),
+            position(Call, dec!(100), dec!(-1), dec!(4)),
+            position(Call, dec!(105), dec!(1), dec!(2)),
+        ];
+        assert_eq!(
+            portfolio_margin_requirement(&condor, &spots()).unwrap(),
+            dec!(300) + dec!(320)
+        );
+    }
+
+    #[test]
+    fn unmatched_shorts_are_naked_and_earlier_longs_do_not_cover() {
+        use OptionKind::Call;
+        // one of two shorts is covered by the spread, the other is naked
+        let partly_covered = [
+            position(Call, dec!(105), dec!(-2), dec!(2)),
+            position(Call, dec!(110), dec!(1), dec!(1)),
+        ];
+        assert_eq!(
+            portfolio_margin_requirement(&partly_covered, &spots()).unwrap(),
+            dec!(400) + dec!(1700)
+        );
+
+        // a long expiring before the short gives no cover
+        let mut early_long = position(Call, dec!(110), dec!(1), dec!(1));
+        early_long.contract.expiration = expiry() - Duration::days(7);
+        let uncovered = [position(Call, dec!(105), dec!(-1), dec!(2)), early_long];
+        assert_eq!(
+            portfolio_margin_requirement(&uncovered, &spots()).unwrap(),
+            dec!(1700) + dec!(100)
+        );
+
+        assert!(matches!(
+            portfolio_margin_requirement(&uncovered, &HashMap::new()),
+            Err(OptionsExecError::MissingSpot(_))
+        ));
+    }
+}
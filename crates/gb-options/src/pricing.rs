@@ -1,9 +1,10 @@
-//! Black-Scholes pricing and greeks for European options.
+//! Option pricing and greeks: Black-Scholes for European exercise and a
+//! Cox-Ross-Rubinstein binomial tree for American exercise.
 
 use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use rust_decimal::Decimal;
 
-use crate::contract::{OptionContract, OptionKind};
+use crate::contract::{ExerciseStyle, OptionContract, OptionKind};
 use crate::greeks::Greeks;
 
 /// Inputs shared by all pricing calls.
@@ -34,7 +35,8 @@ use serde::{Deserialize, Serialize};
 
 // ---------- normal distribution helpers (no external dep) ----------
 
-/// Standard normal cumulative distribution function (Abramowitz & Stegun 26.2.17).
+/// Standard normal cumulative distribution function, via the Abramowitz &
+/// Stegun 7.1.26 approximation of erf(x / sqrt(2)).
 fn norm_cdf(x: f64) -> f64 {
     if x >= 8.0 {
         return 1.0;
@@ -51,10 +53,9 @@ fn norm_cdf(x: f64) -> f64 {
     let p = 0.3275911_f64;
 
     let sign = if x < 0.0 { -1.0 } else { 1.0 };
-    let x_abs = x.abs();
-    let t = 1.0 / (1.0 + p * x_abs);
-    let y =
-        1.0 - (((((a5 * t + a4) * t) + a3) * t + a2) * t + a1) * t * (-x_abs * x_abs / 2.0).exp();
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + p * z);
+    let y = 1.0 - (((((a5 * t + a4) * t) + a3) * t + a2) * t + a1) * t * (-z * z).exp();
 
     0.5 * (1.0 + sign * y)
 }
@@ -145,6 +146,143 @@ pub fn black_scholes_price(contract: &OptionContract, input: &PricingInput) -> P
     }
 }
 
+/// Number of tree steps [`price`] uses for American contracts.
+pub const DEFAULT_BINOMIAL_STEPS: usize = 200;
+
+/// Price an option with the model matching its exercise style:
+/// Black-Scholes for European contracts, a binomial tree of
+/// [`DEFAULT_BINOMIAL_STEPS`] steps for American ones.
+pub fn price(contract: &OptionContract, input: &PricingInput) -> PricingResult {
+    match contract.exercise_style {
+        ExerciseStyle::European => black_scholes_price(contract, input),
+        ExerciseStyle::American => binomial_price(contract, input, DEFAULT_BINOMIAL_STEPS),
+    }
+}
+
+// ---------- Cox-Ross-Rubinstein binomial tree ----------
+
+/// Tree price plus the greeks read off its first two levels.
+struct TreeValuation {
+    price: f64,
+    delta: f64,
+    gamma: f64,
+    /// Annualised theta.
+    theta: f64,
+}
+
+fn payoff(kind: OptionKind, spot: f64, k: f64) -> f64 {
+    match kind {
+        OptionKind::Call => (spot - k).max(0.0),
+        OptionKind::Put => (k - spot).max(0.0),
+    }
+}
+
+/// Backward induction through a CRR tree of `steps` steps (at least two).
+/// With `american` set every node is worth at least its exercise value.
+#[allow(clippy::too_many_arguments)]
+fn crr_tree(
+    kind: OptionKind,
+    american: bool,
+    s: f64,
+    k: f64,
+    r: f64,
+    q: f64,
+    sigma: f64,
+    t: f64,
+    steps: usize,
+) -> TreeValuation {
+    let n = steps.max(2);
+    let dt = t / n as f64;
+    let u = (sigma * dt.sqrt()).exp();
+    let d = 1.0 / u;
+    let p = ((((r - q) * dt).exp() - d) / (u - d)).clamp(0.0, 1.0);
+    let disc = (-r * dt).exp();
+    // Node `i` of level `step` sits at s * u^i * d^(step - i) = s * u^(2i - step).
+    let spot_at = |step: usize, i: usize| s * u.powi(2 * i as i32 - step as i32);
+
+    let mut values: Vec<f64> = (0..=n).map(|i| payoff(kind, spot_at(n, i), k)).collect();
+    let mut level1 = [0.0; 2];
+    let mut level2 = [0.0; 3];
+    for step in (0..n).rev() {
+        for i in 0..=step {
+            let hold = disc * (p * values[i + 1] + (1.0 - p) * values[i]);
+            values[i] = if american {
+                hold.max(payoff(kind, spot_at(step, i), k))
+            } else {
+                hold
+            };
+        }
+        match step {
+            2 => level2.copy_from_slice(&values[..3]),
+            1 => level1.copy_from_slice(&values[..2]),
+            _ => {}
+        }
+    }
+
+    let (s_uu, s_dd) = (s * u * u, s * d * d);
+    let delta_up = (level2[2] - level2[1]) / (s_uu - s);
+    let delta_down = (level2[1] - level2[0]) / (s - s_dd);
+    TreeValuation {
+        price: values[0],
+        delta: (level1[1] - level1[0]) / (s * u - s * d),
+        gamma: (delta_up - delta_down) / (0.5 * (s_uu - s_dd)),
+        // The middle node two steps in has the same spot as the root.
+        theta: (level2[1] - values[0]) / (2.0 * dt),
+    }
+}
+
+/// Price an option on a Cox-Ross-Rubinstein binomial tree with `steps`
+/// steps, checking for early exercise at every node when the contract is
+/// American. Delta, gamma and theta come from the tree's first levels; vega
+/// and rho from central differences of repriced trees.
+pub fn binomial_price(
+    contract: &OptionContract,
+    input: &PricingInput,
+    steps: usize,
+) -> PricingResult {
+    let s = input.spot;
+    let k = contract.strike.to_f64().unwrap_or(0.0);
+    let r = input.risk_free_rate;
+    let q = input.dividend_yield;
+    let sigma = input.volatility;
+    let t = input.time_to_expiry;
+
+    // Degenerate: expired option
+    if t <= 0.0 {
+        let iv = contract.intrinsic_value(Decimal::from_f64(s).unwrap_or_default());
+        return PricingResult {
+            price: iv,
+            greeks: Greeks::zero(),
+        };
+    }
+
+    let american = contract.exercise_style == ExerciseStyle::American;
+    let tree = |r: f64, sigma: f64| crr_tree(contract.kind, american, s, k, r, q, sigma, t, steps);
+    let base = tree(r, sigma);
+
+    let vol_bump = (0.5 * sigma).min(0.01);
+    let vega =
+        (tree(r, sigma + vol_bump).price - tree(r, sigma - vol_bump).price) / (2.0 * vol_bump);
+    let rate_bump = 1e-4;
+    let rho =
+        (tree(r + rate_bump, sigma).price - tree(r - rate_bump, sigma).price) / (2.0 * rate_bump);
+
+    let to_dec = |v: f64| Decimal::from_f64(v).unwrap_or(Decimal::ZERO);
+
+    PricingResult {
+        price: to_dec(base.price),
+        greeks: Greeks {
+            delta: to_dec(base.delta),
+            gamma: to_dec(base.gamma),
+            // Per calendar day, per 1 % vol move and per 1 % rate move, as
+            // in `black_scholes_price`.
+            theta: to_dec(base.theta / 365.0),
+            vega: to_dec(vega / 100.0),
+            rho: to_dec(rho / 100.0),
+        },
+    }
+}
+
 /// Implied volatility via Newton-Raphson on Black-Scholes vega.
 /// Returns `None` if it fails to converge.
 pub fn implied_volatility(
@@ -365,10 +503,105 @@ mod tests {
         assert!((iv.unwrap() - true_vol).abs() < 0.001);
     }
 
+    fn american(contract: OptionContract) -> OptionContract {
+        OptionContract {
+            exercise_style: ExerciseStyle::American,
+            ..contract
+        }
+    }
+
+    fn f(v: Decimal) -> f64 {
+        v.to_f64().unwrap()
+    }
+
+    #[test]
+    fn test_binomial_converges_to_black_scholes() {
+        let input = PricingInput {
+            spot: 150.0,
+            risk_free_rate: 0.05,
+            volatility: 0.25,
+            dividend_yield: 0.02,
+            time_to_expiry: 0.5,
+        };
+        for kind in [OptionKind::Call, OptionKind::Put] {
+            let c = make_contract(kind, dec!(145));
+            let bs = black_scholes_price(&c, &input);
+            let coarse = f(binomial_price(&c, &input, 25).price) - f(bs.price);
+            let fine = binomial_price(&c, &input, 1000);
+            let fine_err = f(fine.price) - f(bs.price);
+            assert!(fine_err.abs() < 0.01, "{kind}: tree error {fine_err}");
+            assert!(fine_err.abs() < coarse.abs(), "{kind}: no convergence");
+
+            assert!((f(fine.greeks.delta) - f(bs.greeks.delta)).abs() < 1e-3);
+            assert!((f(fine.greeks.gamma) - f(bs.greeks.gamma)).abs() < 1e-3);
+            assert!((f(fine.greeks.theta) - f(bs.greeks.theta)).abs() < 1e-3);
+            assert!((f(fine.greeks.vega) - f(bs.greeks.vega)).abs() < 1e-2);
+            assert!((f(fine.greeks.rho) - f(bs.greeks.rho)).abs() < 1e-2);
+        }
+    }
+
+    #[test]
+    fn test_american_call_without_dividends_matches_european() {
+        let c = make_contract(OptionKind::Call, dec!(150));
+        let input = PricingInput {
+            spot: 160.0,
+            risk_free_rate: 0.05,
+            volatility: 0.30,
+            dividend_yield: 0.0,
+            time_to_expiry: 1.0,
+        };
+        let european = binomial_price(&c, &input, DEFAULT_BINOMIAL_STEPS).price;
+        let american = price(&american(c), &input).price;
+        assert!(
+            (f(american) - f(european)).abs() < 1e-6,
+            "american={american}, european={european}"
+        );
+    }
+
+    #[test]
+    fn test_american_put_carries_early_exercise_premium() {
+        let c = make_contract(OptionKind::Put, dec!(150));
+        let input = PricingInput {
+            spot: 130.0,
+            risk_free_rate: 0.05,
+            volatility: 0.25,
+            dividend_yield: 0.0,
+            time_to_expiry: 1.0,
+        };
+        let european = f(price(&c, &input).price);
+        let american = f(price(&american(c.clone()), &input).price);
+        assert!(
+            american > european + 0.1,
+            "american={american}, european={european}"
+        );
+        // Never worth less than exercising now.
+        assert!(american >= 20.0);
+    }
+
+    #[test]
+    fn test_dividends_make_early_call_exercise_valuable() {
+        let c = make_contract(OptionKind::Call, dec!(100));
+        let input = PricingInput {
+            spot: 130.0,
+            risk_free_rate: 0.02,
+            volatility: 0.20,
+            dividend_yield: 0.08,
+            time_to_expiry: 1.0,
+        };
+        let european = f(binomial_price(&c, &input, 300).price);
+        let american = f(binomial_price(&american(c), &input, 300).price);
+        assert!(
+            american > european,
+            "american={american}, european={european}"
+        );
+    }
+
     #[test]
     fn test_norm_cdf_boundaries() {
         assert!((norm_cdf(0.0) - 0.5).abs() < 1e-6);
         assert!(norm_cdf(8.0) == 1.0);
         assert!(norm_cdf(-8.0) == 0.0);
+        assert!((norm_cdf(1.0) - 0.841_344_746).abs() < 1e-6);
+        assert!((norm_cdf(-1.96) - 0.024_997_895).abs() < 1e-6);
     }
 }
@@ -0,0 +1,370 @@
+//! Multi-leg option strategies — verticals, straddles, strangles and iron
+//! condors built as a single [`MultiLegOrder`], with payoff-at-expiry and
+//! aggregate-greeks helpers.
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use gb_types::market::Symbol;
+use gb_types::orders::Side;
+
+use crate::contract::{OptionContract, OptionKind};
+use crate::execution::OptionsExecError;
+use crate::greeks::Greeks;
+use crate::pricing::{price, PricingInput};
+
+/// One leg of a multi-leg order: `ratio` contracts bought or sold per unit
+/// of the order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionLeg {
+    pub contract: OptionContract,
+    pub side: Side,
+    pub ratio: u32,
+}
+
+impl OptionLeg {
+    pub fn new(contract: OptionContract, side: Side, ratio: u32) -> Self {
+        Self {
+            contract,
+            side,
+            ratio,
+        }
+    }
+
+    /// +ratio for a long leg, -ratio for a short one.
+    pub fn signed_ratio(&self) -> Decimal {
+        match self.side {
+            Side::Buy => Decimal::from(self.ratio),
+            Side::Sell => -Decimal::from(self.ratio),
+        }
+    }
+
+    /// Pricing input for this leg: `input` with the time to expiry replaced by
+    /// the leg's own as of `now`.
+    fn input_at(&self, input: &PricingInput, now: DateTime<Utc>) -> PricingInput {
+        PricingInput {
+            time_to_expiry: self.contract.time_to_expiry(now),
+            ..*input
+        }
+    }
+}
+
+/// Several option legs traded together as one order.
+///
+/// Premiums, payoffs and greeks are per share of one unit of the order; a
+/// unit holds `ratio` contracts of each leg. A positive net premium is a
+/// debit (paid), a negative one a credit (received).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MultiLegOrder {
+    pub name: String,
+    pub legs: Vec<OptionLeg>,
+    /// Number of units to trade.
+    pub quantity: Decimal,
+    /// Worst acceptable net premium per share: the largest debit to pay, or
+    /// (when negative) the smallest credit to receive. `None` fills at any
+    /// price.
+    pub limit: Option<Decimal>,
+}
+
+impl MultiLegOrder {
+    pub fn new(name: impl Into<String>, legs: Vec<OptionLeg>) -> Self {
+        Self {
+            name: name.into(),
+            legs,
+            quantity: Decimal::ONE,
+            limit: None,
+        }
+    }
+
+    pub fn with_quantity(mut self, quantity: Decimal) -> Self {
+        self.quantity = quantity;
+        self
+    }
+
+    /// Fill only at a net debit of at most `limit`.
+    pub fn with_debit_limit(mut self, limit: Decimal) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Fill only at a net credit of at least `credit`.
+    pub fn with_credit_limit(mut self, credit: Decimal) -> Self {
+        self.limit = Some(-credit);
+        self
+    }
+
+    /// Value per share of one unit at expiry with the underlying at `spot`.
+    pub fn payoff_at_expiry(&self, spot: Decimal) -> Decimal {
+        self.legs
+            .iter()
+            .map(|leg| leg.signed_ratio() * leg.contract.intrinsic_value(spot))
+            .sum()
+    }
+
+    /// Model net premium per share of one unit (positive = debit).
+    pub fn net_premium(&self, input: &PricingInput, now: DateTime<Utc>) -> Decimal {
+        self.legs
+            .iter()
+            .map(|leg| leg.signed_ratio() * price(&leg.contract, &leg.input_at(input, now)).price)
+            .sum()
+    }
+
+    /// Greeks per share of one unit, summed over the legs.
+    pub fn aggregate_greeks(&self, input: &PricingInput, now: DateTime<Utc>) -> Greeks {
+        self.legs.iter().fold(Greeks::zero(), |mut total, leg| {
+            let greeks = price(&leg.contract, &leg.input_at(input, now)).greeks;
+            let ratio = leg.signed_ratio();
+            total.delta += greeks.delta * ratio;
+            total.gamma += greeks.gamma * ratio;
+            total.theta += greeks.theta * ratio;
+            total.vega += greeks.vega * ratio;
+            total.rho += greeks.rho * ratio;
+            total
+        })
+    }
+}
+
+fn leg(
+    underlying: &Symbol,
+    expiration: DateTime<Utc>,
+    kind: OptionKind,
+    strike: Decimal,
+    side: Side,
+) -> OptionLeg {
+    OptionLeg::new(
+        OptionContract::equity(underlying.clone(), kind, strike, expiration),
+        side,
+        1,
+    )
+}
+
+fn invalid(message: String) -> OptionsExecError {
+    OptionsExecError::InvalidStrategy(message)
+}
+
+/// Buy one `kind` at `long_strike` and sell one at `short_strike`. A long
+/// strike below the short strike makes a bull call or bull put spread,
+/// above it a bear spread.
+pub fn vertical_spread(
+    underlying: Symbol,
+    expiration: DateTime<Utc>,
+    kind: OptionKind,
+    long_strike: Decimal,
+    short_strike: Decimal,
+) -> Result<MultiLegOrder, OptionsExecError> {
+    if long_strike == short_strike {
+        return Err(invalid(format!(
+            "vertical spread needs two different strikes, got {long_strike} twice"
+        )));
+    }
+    Ok(MultiLegOrder::new(
+        format!("{kind} vertical {long_strike}/{short_strike}"),
+        vec![
+            leg(&underlying, expiration, kind, long_strike, Side::Buy),
+            leg(&underlying, expiration, kind, short_strike, Side::Sell),
+        ],
+    ))
+}
+
+/// Buy a call and a put at the same strike.
+pub fn straddle(underlying: Symbol, expiration: DateTime<Utc>, strike: Decimal) -> MultiLegOrder {
+    MultiLegOrder::new(
+        format!("straddle {strike}"),
+        vec![
+            leg(&underlying, expiration, OptionKind::Call, strike, Side::Buy),
+            leg(&underlying, expiration, OptionKind::Put, strike, Side::Buy),
+        ],
+    )
+}
+
+/// Buy a put at `put_strike` and a call at the higher `call_strike`.
+pub fn strangle(
+    underlying: Symbol,
+    expiration: DateTime<Utc>,
+    put_strike: Decimal,
+    call_strike: Decimal,
+) -> Result<MultiLegOrder, OptionsExecError> {
+    if put_strike >= call_strike {
+        return Err(invalid(format!(
+            "strangle put strike {put_strike} must be below call strike {call_strike}"
+        )));
+    }
+    Ok(MultiLegOrder::new(
+        format!("strangle {put_strike}/{call_strike}"),
+        vec![
+            leg(
+                &underlying,
+                expiration,
+                OptionKind::Put,
+                put_strike,
+                Side::Buy,
+            ),
+            leg(
+                &underlying,
+                expiration,
+                OptionKind::Call,
+                call_strike,
+                Side::Buy,
+            ),
+        ],
+    ))
+}
+
+/// Short iron condor: sell the `short_put`/`short_call` strangle and buy the
+/// `long_put`/`long_call` wings, for a net credit. Strikes must be strictly
+/// increasing: long put < short put < short call < long call.
+pub fn iron_condor(
+    underlying: Symbol,
+    expiration: DateTime<Utc>,
+    long_put: Decimal,
+    short_put: Decimal,
+    short_call: Decimal,
+    long_call: Decimal,
+) -> Result<MultiLegOrder, OptionsExecError> {
+    if !(long_put < short_put && short_put < short_call && short_call < long_call) {
+        return Err(invalid(format!(
+            "iron condor strikes must increase: {long_put} < {short_put} < {short_call} < {long_call}"
+        )));
+    }
+    Ok(MultiLegOrder::new(
+        format!("iron condor {long_put}/{short_put}/{short_call}/{long_call}"),
+        vec![
+            leg(
+                &underlying,
+                expiration,
+                OptionKind::Put,
+                long_put,
+                Side::Buy,
+            ),
+            leg(
+                &underlying,
+                expiration,
+                OptionKind::Put,
+                short_put,
+                Side::Sell,
+            ),
+            leg(
+                &underlying,
+                expiration,
+                OptionKind::Call,
+                short_call,
+                Side::Sell,
+            ),
+            leg(
+                &underlying,
+                expiration,
+                OptionKind::Call,
+                long_call,
+                Side::Buy,
+            ),
+        ],
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::prelude::ToPrimitive;
+    use rust_decimal_macros::dec;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 20, 20, 0, 0).unwrap()
+    }
+
+    fn input() -> PricingInput {
+        PricingInput {
+            spot: 100.0,
+            risk_free_rate: 0.0,
+            volatility: 0.2,
+            dividend_yield: 0.0,
+            time_to_expiry: 0.0,
+        }
+    }
+
+    fn condor() -> MultiLegOrder {
+        iron_condor(
+            Symbol::equity("SPY"),
+            now() + Duration::days(91),
+            dec!(80),
+            dec!(90),
+            dec!(110),
+            dec!(120),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn iron_condor_payoff_matches_piecewise_form() {
+        let condor = condor();
+        // Short 90/110 strangle with 10-wide wings.
+        let analytic = |s: Decimal| {
+            if s <= dec!(80) {
+                dec!(-10)
+            } else if s < dec!(90) {
+                s - dec!(90)
+            } else if s <= dec!(110) {
+                Decimal::ZERO
+            } else if s < dec!(120) {
+                dec!(110) - s
+            } else {
+                dec!(-10)
+            }
+        };
+        for spot in [50, 80, 85, 90, 95, 100, 110, 114, 120, 150] {
+            let spot = Decimal::from(spot);
+            assert_eq!(condor.payoff_at_expiry(spot), analytic(spot), "spot {spot}");
+        }
+    }
+
+    #[test]
+    fn symmetric_condor_is_nearly_delta_neutral_and_collects_credit() {
+        let condor = condor();
+        let greeks = condor.aggregate_greeks(&input(), now());
+        assert!(greeks.delta.abs() < dec!(0.05), "delta = {}", greeks.delta);
+        assert!(greeks.gamma < Decimal::ZERO);
+        assert!(greeks.theta > Decimal::ZERO);
+
+        let premium = condor.net_premium(&input(), now());
+        assert!(premium < Decimal::ZERO, "credit expected, got {premium}");
+        assert!(premium > dec!(-10));
+    }
+
+    #[test]
+    fn vertical_straddle_and_strangle_payoffs() {
+        let expiry = now() + Duration::days(30);
+        let bull_call = vertical_spread(
+            Symbol::equity("SPY"),
+            expiry,
+            OptionKind::Call,
+            dec!(95),
+            dec!(105),
+        )
+        .unwrap();
+        assert_eq!(bull_call.payoff_at_expiry(dec!(90)), Decimal::ZERO);
+        assert_eq!(bull_call.payoff_at_expiry(dec!(100)), dec!(5));
+        assert_eq!(bull_call.payoff_at_expiry(dec!(130)), dec!(10));
+
+        let straddle = straddle(Symbol::equity("SPY"), expiry, dec!(100));
+        assert_eq!(straddle.payoff_at_expiry(dec!(88)), dec!(12));
+        assert_eq!(straddle.payoff_at_expiry(dec!(107)), dec!(7));
+        let delta = straddle.aggregate_greeks(&input(), now()).delta;
+        assert!(delta.abs().to_f64().unwrap() < 0.1, "delta = {delta}");
+
+        let strangle = strangle(Symbol::equity("SPY"), expiry, dec!(95), dec!(105)).unwrap();
+        assert_eq!(strangle.payoff_at_expiry(dec!(100)), Decimal::ZERO);
+        assert_eq!(strangle.payoff_at_expiry(dec!(90)), dec!(5));
+    }
+
+    #[test]
+    fn constructors_reject_inconsistent_strikes() {
+        let expiry = now() + Duration::days(30);
+        let spy = Symbol::equity("SPY");
+        assert!(
+            vertical_spread(spy.clone(), expiry, OptionKind::Put, dec!(100), dec!(100)).is_err()
+        );
+        assert!(strangle(spy.clone(), expiry, dec!(105), dec!(95)).is_err());
+        assert!(iron_condor(spy, expiry, dec!(80), dec!(110), dec!(90), dec!(120)).is_err());
+    }
+}
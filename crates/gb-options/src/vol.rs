@@ -0,0 +1,255 @@
+//! Volatility estimators — historical close-to-close, Parkinson and
+//! Garman-Klass estimates and an EWMA forecast from underlying bars, for use
+//! as `PricingInput::volatility` when no market implied volatility is
+//! available.
+//!
+//! Every estimate is annualized from the bars' resolution: 252 trading days a
+//! year, 52 weeks or 12 months, and 6.5-hour sessions for intraday bars.
+
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use gb_types::market::{Bar, Resolution};
+
+/// Historical volatility estimator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VolEstimator {
+    /// Sample standard deviation of close-to-close log returns.
+    CloseToClose,
+    /// High-low range: mean of ln(H/L)² / (4 ln 2).
+    Parkinson,
+    /// Open-high-low-close: mean of ½ ln(H/L)² − (2 ln 2 − 1) ln(C/O)².
+    GarmanKlass,
+}
+
+/// An annualized volatility and the number of observations behind it
+/// (returns for close-to-close and EWMA, bars for the range estimators).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VolEstimate {
+    pub volatility: f64,
+    pub sample_size: usize,
+}
+
+/// Bars of `resolution` in a year, or `None` for ticks.
+pub fn periods_per_year(resolution: Resolution) -> Option<f64> {
+    const TRADING_DAYS: f64 = 252.0;
+    const SESSION_SECONDS: f64 = 6.5 * 3600.0;
+    match resolution {
+        Resolution::Tick => None,
+        Resolution::Day => Some(TRADING_DAYS),
+        Resolution::Week => Some(52.0),
+        Resolution::Month => Some(12.0),
+        intraday => intraday
+            .to_seconds()
+            .map(|seconds| TRADING_DAYS * SESSION_SECONDS / seconds as f64),
+    }
+}
+
+fn to_f64(bar: &Bar) -> Option<(f64, f64, f64, f64)> {
+    let values = (
+        bar.open.to_f64()?,
+        bar.high.to_f64()?,
+        bar.low.to_f64()?,
+        bar.close.to_f64()?,
+    );
+    let (open, high, low, close) = values;
+    (open > 0.0 && high > 0.0 && low > 0.0 && close > 0.0).then_some(values)
+}
+
+/// Log returns between the last `count + 1` closes, or `None` when there
+/// are fewer closes or any is not positive.
+fn log_returns(bars: &[Bar], count: usize) -> Option<Vec<f64>> {
+    let start = bars.len().checked_sub(count + 1)?;
+    let closes = bars[start..]
+        .iter()
+        .map(|bar| to_f64(bar).map(|(_, _, _, close)| close))
+        .collect::<Option<Vec<f64>>>()?;
+    Some(
+        closes
+            .windows(2)
+            .map(|pair| (pair[1] / pair[0]).ln())
+            .collect(),
+    )
+}
+
+fn annualize(variance: f64, resolution: Resolution, sample_size: usize) -> Option<VolEstimate> {
+    Some(VolEstimate {
+        volatility: (variance.max(0.0) * periods_per_year(resolution)?).sqrt(),
+        sample_size,
+    })
+}
+
+/// Annualized volatility of the last `window` observations of `bars` (in
+/// time order) under `method`. Close-to-close needs `window + 1` bars, the
+/// range estimators `window` bars; `None` when the history is shorter, the
+/// window is below 2, a price is not positive, or the bars are ticks. A
+/// series with no variation estimates zero.
+pub fn historical_vol(bars: &[Bar], window: usize, method: VolEstimator) -> Option<VolEstimate> {
+    if window < 2 {
+        return None;
+    }
+    let resolution = bars.last()?.resolution;
+
+    let variance = match method {
+        VolEstimator::CloseToClose => {
+            let returns = log_returns(bars, window)?;
+            let mean = returns.iter().sum::<f64>() / window as f64;
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (window - 1) as f64
+        }
+        VolEstimator::Parkinson | VolEstimator::GarmanKlass => {
+            let start = bars.len().checked_sub(window)?;
+            let mut total = 0.0;
+            for bar in &bars[start..] {
+                let (open, high, low, close) = to_f64(bar)?;
+                let range = (high / low).ln().powi(2);
+                total += match method {
+                    VolEstimator::Parkinson => range / (4.0 * std::f64::consts::LN_2),
+                    _ => {
+                        0.5 * range
+                            - (2.0 * std::f64::consts::LN_2 - 1.0) * (close / open).ln().powi(2)
+                    }
+                };
+            }
+            total / window as f64
+        }
+    };
+    annualize(variance, resolution, window)
+}
+
+/// RiskMetrics-style EWMA forecast of next-period volatility from the last
+/// `window` close-to-close log returns: the variance starts at the first
+/// squared return and is updated as `λ·σ² + (1 − λ)·r²` for each later one.
+/// `None` under the same conditions as [`historical_vol`], or when `lambda`
+/// is outside [0, 1).
+pub fn ewma_forecast(bars: &[Bar], window: usize, lambda: f64) -> Option<VolEstimate> {
+    if window < 2 || !(0.0..1.0).contains(&lambda) {
+        return None;
+    }
+    let resolution = bars.last()?.resolution;
+    let returns = log_returns(bars, window)?;
+    let variance = returns[1..]
+        .iter()
+        .fold(returns[0] * returns[0], |variance, r| {
+            lambda * variance + (1.0 - lambda) * r * r
+        });
+    annualize(variance, resolution, window)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+    use gb_types::market::Symbol;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn make_bars(ohlc: &[(Decimal, Decimal, Decimal, Decimal)]) -> Vec<Bar> {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 21, 0, 0).unwrap();
+        ohlc.iter()
+            .enumerate()
+            .map(|(day, &(open, high, low, close))| Bar {
+                symbol: Symbol::equity("AAPL"),
+                timestamp: start + Duration::days(day as i64),
+                open,
+                high,
+                low,
+                close,
+                volume: dec!(1000),
+                resolution: Resolution::Day,
+                provenance: Default::default(),
+            })
+            .collect()
+    }
+
+    fn sample() -> Vec<Bar> {
+        make_bars(&[
+            (dec!(100), dec!(102), dec!(99), dec!(101)),
+            (dec!(101), dec!(104), dec!(100), dec!(103)),
+            (dec!(103), dec!(103), dec!(98), dec!(99)),
+            (dec!(99), dec!(101), dec!(97), dec!(100)),
+        ])
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-12,
+            "actual {actual}, expected {expected}"
+        );
+    }
+
+    #[test]
+    fn close_to_close_matches_hand_computation() {
+        let returns = [
+            (103.0_f64 / 101.0).ln(),
+            (99.0_f64 / 103.0).ln(),
+            (100.0_f64 / 99.0).ln(),
+        ];
+        let mean = returns.iter().sum::<f64>() / 3.0;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / 2.0;
+
+        let estimate = historical_vol(&sample(), 3, VolEstimator::CloseToClose).unwrap();
+        assert_eq!(estimate.sample_size, 3);
+        assert_close(estimate.volatility, (variance * 252.0).sqrt());
+    }
+
+    #[test]
+    fn range_estimators_match_hand_computation() {
+        let bars = sample();
+        // Last two bars: (103, 103, 98, 99) and (99, 101, 97, 100).
+        let ranges = [(103.0_f64 / 98.0).ln(), (101.0_f64 / 97.0).ln()];
+        let drifts = [(99.0_f64 / 103.0).ln(), (100.0_f64 / 99.0).ln()];
+
+        let parkinson =
+            ranges.iter().map(|h| h * h).sum::<f64>() / (2.0 * 4.0 * std::f64::consts::LN_2);
+        let estimate = historical_vol(&bars, 2, VolEstimator::Parkinson).unwrap();
+        assert_eq!(estimate.sample_size, 2);
+        assert_close(estimate.volatility, (parkinson * 252.0).sqrt());
+
+        let garman_klass = ranges
+            .iter()
+            .zip(drifts)
+            .map(|(h, c)| 0.5 * h * h - (2.0 * std::f64::consts::LN_2 - 1.0) * c * c)
+            .sum::<f64>()
+            / 2.0;
+        let estimate = historical_vol(&bars, 2, VolEstimator::GarmanKlass).unwrap();
+        assert_close(estimate.volatility, (garman_klass * 252.0).sqrt());
+    }
+
+    #[test]
+    fn ewma_matches_hand_computation() {
+        let (r1, r2, r3) = (
+            (103.0_f64 / 101.0).ln(),
+            (99.0_f64 / 103.0).ln(),
+            (100.0_f64 / 99.0).ln(),
+        );
+        let lambda = 0.94;
+        let variance =
+            lambda * (lambda * r1 * r1 + (1.0 - lambda) * r2 * r2) + (1.0 - lambda) * r3 * r3;
+
+        let estimate = ewma_forecast(&sample(), 3, lambda).unwrap();
+        assert_eq!(estimate.sample_size, 3);
+        assert_close(estimate.volatility, (variance * 252.0).sqrt());
+        assert!(ewma_forecast(&sample(), 3, 1.0).is_none());
+    }
+
+    #[test]
+    fn short_histories_and_flat_series() {
+        let bars = sample();
+        assert!(historical_vol(&bars, 4, VolEstimator::CloseToClose).is_none());
+        assert!(historical_vol(&bars, 5, VolEstimator::Parkinson).is_none());
+        assert!(historical_vol(&bars, 1, VolEstimator::GarmanKlass).is_none());
+        assert!(ewma_forecast(&bars[..2], 2, 0.94).is_none());
+        assert!(historical_vol(&[], 2, VolEstimator::CloseToClose).is_none());
+
+        let flat = make_bars(&[(dec!(50), dec!(50), dec!(50), dec!(50)); 5]);
+        for method in [
+            VolEstimator::CloseToClose,
+            VolEstimator::Parkinson,
+            VolEstimator::GarmanKlass,
+        ] {
+            assert_eq!(historical_vol(&flat, 4, method).unwrap().volatility, 0.0);
+        }
+        assert_eq!(ewma_forecast(&flat, 4, 0.94).unwrap().volatility, 0.0);
+    }
+}
@@ -5,14 +5,136 @@ use pyo3::IntoPyObjectExt;
 use rust_decimal::prelude::FromPrimitive;
 use rust_decimal::Decimal;
 
-use gb_engine::BacktestEngine as RustBacktestEngine;
+use gb_engine::{BacktestEngine as RustBacktestEngine, CancellationHandle};
 use gb_types::{
     BacktestConfig, BacktestResult as RustBacktestResult, BuyAndHoldStrategy, CoveredCallStrategy,
-    DataQualityMode, LatencyModel, MeanReversionStrategy, MomentumStrategy,
-    MovingAverageCrossoverStrategy, Resolution, RsiStrategy, SlippageModel, Strategy,
-    StrategyConfig, Symbol,
+    DataQualityMode, GbError, LatencyModel, MeanReversionStrategy, MomentumStrategy,
+    MovingAverageCrossoverStrategy, Resolution, RsiStrategy, SlippageModel, SnapshotCadence,
+    Strategy, StrategyConfig, Symbol,
 };
 
+mod live;
+mod optimizer;
+mod options;
+mod risk;
+mod strategy;
+
+use live::{PyLiveEngine, PyPaperBroker};
+use optimizer::{PyOptimizationConfig, PyOptimizationResult, PyOptimizer, PySearchSpace};
+use options::PyOptionContract;
+use risk::{PyPortfolioRiskSnapshot, PyRiskMonitor};
+use strategy::{strategy_failure_error, PyOrderEvent, PyStrategy, PyStrategyContext};
+
+// Engine failures surface as subclasses of `GlowBackError`, itself a
+// `RuntimeError` so existing `except RuntimeError` handlers keep working.
+pyo3::create_exception!(
+    glowback,
+    GlowBackError,
+    pyo3::exceptions::PyRuntimeError,
+    "Base class for errors raised by the GlowBack engine."
+);
+pyo3::create_exception!(
+    glowback,
+    DataError,
+    GlowBackError,
+    "Market data could not be found, loaded or validated."
+);
+pyo3::create_exception!(
+    glowback,
+    StrategyError,
+    GlowBackError,
+    "A strategy failed to initialize or process data."
+);
+pyo3::create_exception!(
+    glowback,
+    ConfigError,
+    GlowBackError,
+    "The backtest configuration is invalid."
+);
+pyo3::create_exception!(
+    glowback,
+    BacktestError,
+    GlowBackError,
+    "The backtest could not be set up or failed while running."
+);
+pyo3::create_exception!(
+    glowback,
+    BrokerError,
+    GlowBackError,
+    "A broker rejected a request or a live trading session failed."
+);
+
+/// Attach the error's stable `code` and whether repeating the call may
+/// succeed (`retryable`) to a raised exception.
+pub(crate) fn with_error_details(error: PyErr, code: &str, retryable: bool) -> PyErr {
+    Python::attach(|py| {
+        let value = error.value(py);
+        // Plain exception instances always accept new attributes.
+        let _ = value.setattr("code", code);
+        let _ = value.setattr("retryable", retryable);
+    });
+    error
+}
+
+/// Map an engine error to the matching Python exception, prefixed with
+/// `context`.
+fn engine_error(context: &str, error: GbError) -> PyErr {
+    let message = format!("{}: {}", context, error);
+    let (code, retryable) = (error.code(), error.is_retryable());
+    let raised = match error {
+        GbError::Data(_) | GbError::Arrow(_) | GbError::Parquet(_) => DataError::new_err(message),
+        GbError::Strategy(_) => StrategyError::new_err(message),
+        GbError::Config(_)
+        | GbError::Validation(_)
+        | GbError::Backtest(
+            gb_types::BacktestError::InvalidConfig { .. }
+            | gb_types::BacktestError::InvalidDateRange { .. }
+            | gb_types::BacktestError::NoSymbols,
+        ) => ConfigError::new_err(message),
+        GbError::Backtest(gb_types::BacktestError::EngineInitFailed { message: reason })
+            if reason.contains("market data") || reason.contains("data quality") =>
+        {
+            DataError::new_err(message)
+        }
+        _ => BacktestError::new_err(message),
+    };
+    with_error_details(raised, code, retryable)
+}
+
+/// Tokio runtime owned by a Python object. Python may free the object from a
+/// callback that another runtime is blocked on, where dropping a runtime
+/// normally panics, so it is shut down in the background instead.
+struct OwnedRuntime(Option<tokio::runtime::Runtime>);
+
+impl OwnedRuntime {
+    fn new() -> PyResult<Self> {
+        tokio::runtime::Runtime::new()
+            .map(|runtime| Self(Some(runtime)))
+            .map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!(
+                    "Failed to create async runtime: {}",
+                    e
+                ))
+            })
+    }
+}
+
+impl std::ops::Deref for OwnedRuntime {
+    type Target = tokio::runtime::Runtime;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref().expect("runtime is only taken on drop")
+    }
+}
+
+impl Drop for OwnedRuntime {
+    fn drop(&mut self) {
+        if let Some(runtime) = self.0.take() {
+            runtime.shutdown_background();
+        }
+    }
+}
+
 const BUILTIN_STRATEGIES: [&str; 6] = [
     "buy_and_hold",
     "ma_crossover",
@@ -35,10 +157,36 @@ fn glowback(py: Python, m: &Bound<PyModule>) -> PyResult<()> {
     m.add_class::<PyDataManager>()?;
     m.add_class::<PyBar>()?;
     m.add_class::<PyCatalogStats>()?;
+    m.add_class::<PyBacktestConfig>()?;
     m.add_class::<PyBacktestEngine>()?;
     m.add_class::<PyBacktestResult>()?;
+    m.add_class::<PyStrategyContext>()?;
+    m.add_class::<PyOrderEvent>()?;
+    m.add_class::<PySearchSpace>()?;
+    m.add_class::<PyOptimizationConfig>()?;
+    m.add_class::<PyOptimizer>()?;
+    m.add_class::<PyOptimizationResult>()?;
+    m.add_class::<PyOptionContract>()?;
+    m.add_class::<PyPaperBroker>()?;
+    m.add_class::<PyLiveEngine>()?;
+    m.add_class::<PyRiskMonitor>()?;
+    m.add_class::<PyPortfolioRiskSnapshot>()?;
+    m.add("GlowBackError", py.get_type::<GlowBackError>())?;
+    m.add("DataError", py.get_type::<DataError>())?;
+    m.add("StrategyError", py.get_type::<StrategyError>())?;
+    m.add("ConfigError", py.get_type::<ConfigError>())?;
+    m.add("BacktestError", py.get_type::<BacktestError>())?;
+    m.add("BrokerError", py.get_type::<BrokerError>())?;
+    m.add_function(wrap_pyfunction!(set_strict_decimals, m)?)?;
+    m.add_function(wrap_pyfunction!(strict_decimals, m)?)?;
+    m.add_function(wrap_pyfunction!(set_log_level, m)?)?;
+    m.add_function(wrap_pyfunction!(enable_file_logging, m)?)?;
     m.add_function(wrap_pyfunction!(run_buy_and_hold, m)?)?;
     m.add_function(wrap_pyfunction!(run_builtin_strategy, m)?)?;
+    m.add_function(wrap_pyfunction!(options::black_scholes, m)?)?;
+    m.add_function(wrap_pyfunction!(options::implied_vol, m)?)?;
+    m.add_function(wrap_pyfunction!(options::black_scholes_array, m)?)?;
+    m.add_function(wrap_pyfunction!(options::implied_vol_array, m)?)?;
 
     // Backwards-compatible aliases (Py* names)
     m.add("PySymbol", m.getattr("Symbol")?)?;
@@ -58,10 +206,36 @@ fn glowback(py: Python, m: &Bound<PyModule>) -> PyResult<()> {
                 "DataManager",
                 "Bar",
                 "CatalogStats",
+                "BacktestConfig",
                 "BacktestEngine",
                 "BacktestResult",
+                "StrategyContext",
+                "OrderEvent",
+                "SearchSpace",
+                "OptimizationConfig",
+                "Optimizer",
+                "OptimizationResult",
+                "OptionContract",
+                "PaperBroker",
+                "LiveEngine",
+                "RiskMonitor",
+                "PortfolioRiskSnapshot",
+                "GlowBackError",
+                "DataError",
+                "StrategyError",
+                "ConfigError",
+                "BacktestError",
+                "BrokerError",
                 "run_buy_and_hold",
                 "run_builtin_strategy",
+                "set_strict_decimals",
+                "strict_decimals",
+                "set_log_level",
+                "enable_file_logging",
+                "black_scholes",
+                "implied_vol",
+                "black_scholes_array",
+                "implied_vol_array",
                 "PySymbol",
                 "PyDataManager",
                 "PyBar",
@@ -78,6 +252,108 @@ fn decimal_to_f64(value: Decimal) -> f64 {
     value.to_f64().unwrap_or(0.0)
 }
 
+fn to_decimal(name: &str, value: f64) -> PyResult<Decimal> {
+    Decimal::from_f64(value).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "{name} must be a finite number, got {value}"
+        ))
+    })
+}
+
+/// A price or quantity given as a float or, to keep every digit, a string.
+fn decimal_arg(name: &str, value: &Bound<'_, PyAny>) -> PyResult<Decimal> {
+    match value.extract::<String>() {
+        Ok(text) => Decimal::from_str_exact(text.trim()).map_err(|e| {
+            pyo3::exceptions::PyValueError::new_err(format!("invalid {name} '{text}': {e}"))
+        }),
+        Err(_) => to_decimal(name, value.extract()?),
+    }
+}
+
+/// When set, bar getters raise instead of returning a float that does not
+/// round-trip to the stored decimal.
+static STRICT_DECIMALS: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Make `Bar` price and volume getters raise `ValueError` when the float
+/// would lose precision; `Bar.as_decimal_strings()` always gives exact values.
+#[pyfunction]
+fn set_strict_decimals(enabled: bool) {
+    STRICT_DECIMALS.store(enabled, std::sync::atomic::Ordering::Relaxed);
+}
+
+/// Whether `set_strict_decimals` is on.
+#[pyfunction]
+fn strict_decimals() -> bool {
+    STRICT_DECIMALS.load(std::sync::atomic::Ordering::Relaxed)
+}
+
+/// Logging installed by the first `set_log_level` or `enable_file_logging`
+/// call.
+static TELEMETRY: std::sync::Mutex<Option<gb_engine::telemetry::Telemetry>> =
+    std::sync::Mutex::new(None);
+
+fn telemetry(level: &str) -> PyResult<gb_engine::telemetry::Telemetry> {
+    let mut installed = TELEMETRY
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(telemetry) = installed.as_ref() {
+        return Ok(telemetry.clone());
+    }
+    let telemetry = gb_engine::telemetry::init(&gb_engine::telemetry::TelemetryConfig {
+        level: level.to_string(),
+        ..Default::default()
+    })
+    .map_err(|e| engine_error("Could not set up logging", e))?;
+    *installed = Some(telemetry.clone());
+    Ok(telemetry)
+}
+
+/// Log engine events at `level` and above to stderr. `level` takes
+/// `RUST_LOG` syntax, e.g. `"debug"` or `"warn,gb_engine=debug"`.
+#[pyfunction]
+fn set_log_level(level: &str) -> PyResult<()> {
+    telemetry(level)?
+        .set_level(level)
+        .map_err(|e| engine_error("Could not set the log level", e))
+}
+
+/// Also append each backtest's and optimization's log events, as JSON lines,
+/// to `<directory>/<run id>/logs.jsonl`; `None` stops writing them.
+#[pyfunction]
+fn enable_file_logging(directory: Option<std::path::PathBuf>) -> PyResult<()> {
+    telemetry("info")?.set_run_log_dir(directory);
+    Ok(())
+}
+
+/// `value` as a float for a bar getter. Raises `ValueError` when there is no
+/// float for it or, with strict decimals on, when the float is inexact.
+fn bar_field_to_f64(name: &str, value: Decimal) -> PyResult<f64> {
+    checked_f64(name, value, value.to_f64(), strict_decimals())
+}
+
+fn checked_f64(name: &str, value: Decimal, converted: Option<f64>, strict: bool) -> PyResult<f64> {
+    let converted = converted
+        .filter(|converted| converted.is_finite())
+        .ok_or_else(|| {
+            pyo3::exceptions::PyValueError::new_err(format!("{name} {value} has no float value"))
+        })?;
+    if strict
+        && Decimal::from_f64(converted).map(|back| back.normalize()) != Some(value.normalize())
+    {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{name} {value} is not exactly representable as a float ({converted}); \
+             use Bar.as_decimal_strings()"
+        )));
+    }
+    Ok(converted)
+}
+
+fn parse_timestamp(name: &str, value: &str) -> PyResult<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(value)
+        .map(|timestamp| timestamp.with_timezone(&chrono::Utc))
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid {name} format: {e}")))
+}
+
 fn parse_resolution(resolution: &str) -> PyResult<Resolution> {
     match resolution.to_lowercase().as_str() {
         "minute" | "1m" => Ok(Resolution::Minute),
@@ -101,6 +377,19 @@ fn parse_data_quality_mode(mode: &str) -> PyResult<DataQualityMode> {
     }
 }
 
+fn parse_position_snapshots(cadence: &str) -> PyResult<SnapshotCadence> {
+    match cadence.trim().to_ascii_lowercase().as_str() {
+        "off" | "none" => Ok(SnapshotCadence::Off),
+        "daily" => Ok(SnapshotCadence::Daily),
+        "weekly" => Ok(SnapshotCadence::Weekly),
+        "monthly" => Ok(SnapshotCadence::Monthly),
+        other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "Invalid position_snapshots cadence: {}",
+            other
+        ))),
+    }
+}
+
 fn dict_get_usize(params: Option<&Bound<PyDict>>, key: &str, default: usize) -> PyResult<usize> {
     let Some(params) = params else {
         return Ok(default);
@@ -175,6 +464,7 @@ fn build_strategy(name: &str, params: Option<&Bound<PyDict>>) -> PyResult<Box<dy
 #[pyfunction]
 #[pyo3(signature = (symbols, start_date, end_date, resolution=None, initial_capital=None, name=None))]
 fn run_buy_and_hold(
+    py: Python<'_>,
     symbols: Vec<String>,
     start_date: &str,
     end_date: &str,
@@ -182,7 +472,7 @@ fn run_buy_and_hold(
     initial_capital: Option<f64>,
     name: Option<&str>,
 ) -> PyResult<PyBacktestResult> {
-    let mut engine = PyBacktestEngine::new(
+    let engine = PyBacktestEngine::new(
         symbols,
         start_date,
         end_date,
@@ -196,7 +486,7 @@ fn run_buy_and_hold(
         None,
         None,
     )?;
-    engine.run_buy_and_hold()
+    engine.run_buy_and_hold(py)
 }
 
 fn build_backtest_config(
@@ -287,6 +577,88 @@ fn build_backtest_config(
     Ok(config)
 }
 
+/// Build a sample-data config from `add_sample_provider` keyword options.
+fn sample_data_config(options: Option<&Bound<'_, PyDict>>) -> PyResult<gb_data::SampleDataConfig> {
+    let mut config = gb_data::SampleDataConfig::default();
+    let Some(options) = options else {
+        return Ok(config);
+    };
+
+    let statistics = |stats: &Bound<'_, PyAny>| -> PyResult<gb_data::SampleStatistics> {
+        let stats = stats.cast::<PyDict>()?;
+        let get = |key: &str| -> PyResult<Option<f64>> {
+            stats
+                .get_item(key)?
+                .map(|value| value.extract())
+                .transpose()
+        };
+        Ok(gb_data::SampleStatistics {
+            annual_drift: get("drift")?.unwrap_or(0.0),
+            annual_volatility: get("volatility")?.ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err("symbol statistics need a volatility")
+            })?,
+            start_price: get("start_price")?
+                .map(|price| to_decimal("start_price", price))
+                .transpose()?,
+        })
+    };
+
+    let (mut drift, mut volatility) = (None, None);
+    let mut regimes = gb_data::VolatilityRegimes {
+        switch_probability: 0.0,
+        high_volatility_multiplier: 1.0,
+    };
+    let mut with_regimes = false;
+    for (key, value) in options.iter() {
+        let key: String = key.extract()?;
+        match key.as_str() {
+            "seed" => config.seed = value.extract()?,
+            "drift" => drift = Some(value.extract::<f64>()?),
+            "volatility" => volatility = Some(value.extract::<f64>()?),
+            "symbols" => {
+                for (ticker, stats) in value.cast::<PyDict>()?.iter() {
+                    config
+                        .symbols
+                        .insert(ticker.extract()?, statistics(&stats)?);
+                }
+            }
+            "regime_switch_probability" => {
+                regimes.switch_probability = value.extract()?;
+                with_regimes = true;
+            }
+            "high_volatility_multiplier" => {
+                regimes.high_volatility_multiplier = value.extract()?;
+                with_regimes = true;
+            }
+            "overnight_gap" => config.overnight_gap = value.extract()?,
+            "missing_bar_probability" => config.missing_bar_probability = value.extract()?,
+            "skip_weekends" => {
+                if value.extract::<bool>()? {
+                    config.weekends = gb_data::WeekendHandling::SkipOutsideContinuousMarkets;
+                }
+            }
+            other => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "unknown sample provider option '{other}'"
+                )))
+            }
+        }
+    }
+    if drift.is_some() || volatility.is_some() {
+        config.statistics = Some(gb_data::SampleStatistics {
+            annual_drift: drift.unwrap_or(0.0),
+            annual_volatility: volatility.ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err("a drift needs a volatility")
+            })?,
+            start_price: None,
+        });
+    }
+    if with_regimes {
+        config.regimes = Some(regimes);
+    }
+    Ok(config)
+}
+
 fn apply_strategy_params(
     strategy_config: &mut StrategyConfig,
     strategy_params: Option<&Bound<'_, PyDict>>,
@@ -451,18 +823,11 @@ fn run_builtin_strategy(
 
     let mut engine = runtime
         .block_on(async { RustBacktestEngine::new(config).await })
-        .map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!(
-                "Failed to create backtest engine: {}",
-                e
-            ))
-        })?;
+        .map_err(|e| engine_error("Failed to create backtest engine", e))?;
 
     let result = runtime
         .block_on(async { engine.run_with_strategy(strategy).await })
-        .map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Backtest execution failed: {}", e))
-        })?;
+        .map_err(|e| engine_error("Backtest execution failed", e))?;
 
     Ok(PyBacktestResult::from_backtest_result(result))
 }
@@ -482,6 +847,7 @@ impl PySymbol {
             "forex" => gb_types::AssetClass::Forex,
             "commodity" => gb_types::AssetClass::Commodity,
             "bond" => gb_types::AssetClass::Bond,
+            "option" => gb_types::AssetClass::Option,
             _ => gb_types::AssetClass::Equity,
         };
 
@@ -512,28 +878,42 @@ impl PySymbol {
     }
 }
 
+/// Accept either a `Symbol` or a plain ticker, taken as a NASDAQ equity.
+fn symbol_arg(symbol: &Bound<'_, PyAny>) -> PyResult<Symbol> {
+    match symbol.cast::<PySymbol>() {
+        Ok(symbol) => Ok(symbol.borrow().inner.clone()),
+        Err(_) => Ok(Symbol::equity(&symbol.extract::<String>()?)),
+    }
+}
+
 /// Python wrapper for DataManager
+///
+/// Loads release the GIL. The manager sits behind an async mutex taken
+/// inside the runtime, so concurrent calls from Python threads queue instead
+/// of deadlocking; separate managers load in parallel.
 #[pyclass(name = "DataManager")]
 struct PyDataManager {
-    inner: std::sync::Mutex<gb_data::DataManager>,
-    runtime: tokio::runtime::Runtime,
+    inner: tokio::sync::Mutex<gb_data::DataManager>,
+    runtime: OwnedRuntime,
 }
 
 #[pymethods]
 impl PyDataManager {
+    /// Store data under `data_dir`, or the user data directory by default.
     #[new]
-    fn new() -> PyResult<Self> {
+    #[pyo3(signature = (data_dir=None))]
+    fn new(data_dir: Option<&str>) -> PyResult<Self> {
         // Create tokio runtime for async operations
-        let runtime = tokio::runtime::Runtime::new().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!(
-                "Failed to create async runtime: {}",
-                e
-            ))
-        })?;
+        let runtime = OwnedRuntime::new()?;
 
         // Create data manager
         let inner = runtime
-            .block_on(async { gb_data::DataManager::new().await })
+            .block_on(async {
+                match data_dir {
+                    Some(data_dir) => gb_data::DataManager::new_with_data_dir(data_dir).await,
+                    None => gb_data::DataManager::new().await,
+                }
+            })
             .map_err(|e| {
                 pyo3::exceptions::PyRuntimeError::new_err(format!(
                     "Failed to create data manager: {}",
@@ -542,14 +922,15 @@ impl PyDataManager {
             })?;
 
         Ok(Self {
-            inner: std::sync::Mutex::new(inner),
+            inner: tokio::sync::Mutex::new(inner),
             runtime,
         })
     }
 
     /// Load market data for a symbol
     fn load_data(
-        &mut self,
+        &self,
+        py: Python<'_>,
         symbol: &PySymbol,
         start_date: &str,
         end_date: &str,
@@ -581,82 +962,255 @@ impl PyDataManager {
             }
         };
 
-        // Load data asynchronously
-        let bars = self.runtime.block_on(async {
-            let mut inner = self.inner.lock().map_err(|e| {
-                pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-            })?;
-            inner
-                .load_data(&symbol.inner, start_date, end_date, resolution)
-                .await
-                .map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to load data: {}", e))
+        // Load data asynchronously without holding the GIL
+        let symbol = symbol.inner.clone();
+        let bars = py
+            .detach(|| {
+                self.runtime.block_on(async {
+                    let mut inner = self.inner.lock().await;
+                    inner
+                        .load_data(&symbol, start_date, end_date, resolution)
+                        .await
                 })
-        })?;
+            })
+            .map_err(|e| {
+                pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to load data: {}", e))
+            })?;
 
         // Convert to Python bars
         let py_bars = bars.into_iter().map(|bar| PyBar { inner: bar }).collect();
         Ok(py_bars)
     }
 
-    /// Add a sample data provider
-    fn add_sample_provider(&mut self) -> PyResult<()> {
-        let provider = Box::new(gb_data::SampleDataProvider::new());
-        let mut inner = self.inner.lock().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-        })?;
-        inner.add_provider(provider);
+    /// Add a sample data provider. Without options it generates the demo
+    /// random walk; keyword options reshape it: `seed`, annualized `drift`
+    /// and `volatility`, per-ticker `symbols={"AAPL": {"volatility": 0.3,
+    /// "drift": 0.05, "start_price": 150.0}}`, `regime_switch_probability`
+    /// and `high_volatility_multiplier`, `overnight_gap`,
+    /// `missing_bar_probability`, and `skip_weekends`.
+    #[pyo3(signature = (**options))]
+    fn add_sample_provider(
+        &self,
+        py: Python<'_>,
+        options: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let config = sample_data_config(options)?;
+        self.add_provider(
+            py,
+            Box::new(gb_data::SampleDataProvider::with_config(config)),
+        );
         Ok(())
     }
 
     /// Add a CSV data provider
-    fn add_csv_provider(&mut self, base_path: &str) -> PyResult<()> {
-        let provider = Box::new(gb_data::CsvDataProvider::new(base_path));
-        let mut inner = self.inner.lock().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-        })?;
-        inner.add_provider(provider);
-        Ok(())
+    fn add_csv_provider(&self, py: Python<'_>, base_path: &str) {
+        self.add_provider(py, Box::new(gb_data::CsvDataProvider::new(base_path)));
     }
 
     /// Add an Alpha Vantage provider
-    fn add_alpha_vantage_provider(&mut self, api_key: &str) -> PyResult<()> {
-        let provider = Box::new(gb_data::AlphaVantageProvider::new(api_key.to_string()));
-        let mut inner = self.inner.lock().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-        })?;
-        inner.add_provider(provider);
-        Ok(())
+    fn add_alpha_vantage_provider(&self, py: Python<'_>, api_key: &str) {
+        self.add_provider(
+            py,
+            Box::new(gb_data::AlphaVantageProvider::new(api_key.to_string())),
+        );
     }
 
-    /// Get catalog statistics
-    fn get_catalog_stats(&self) -> PyResult<PyCatalogStats> {
-        let stats = self.runtime.block_on(async {
-            let inner = self.inner.lock().map_err(|e| {
-                pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-            })?;
-            inner.catalog.get_catalog_stats().await.map_err(|e| {
+    /// Get catalog statistics. Counts and ranges are those registered in
+    /// the catalog; `exact=True` reads every stored file to count its bars
+    /// and find their true range instead.
+    #[pyo3(signature = (exact=false))]
+    fn get_catalog_stats(&self, py: Python<'_>, exact: bool) -> PyResult<PyCatalogStats> {
+        let scan = if exact {
+            gb_data::CoverageScan::Exact
+        } else {
+            gb_data::CoverageScan::Registered
+        };
+        let stats = py
+            .detach(|| {
+                self.runtime.block_on(async {
+                    let inner = self.inner.lock().await;
+                    inner.catalog_stats(scan).await
+                })
+            })
+            .map_err(|e| {
                 pyo3::exceptions::PyRuntimeError::new_err(format!(
                     "Failed to get catalog stats: {}",
                     e
                 ))
-            })
-        })?;
+            })?;
 
         Ok(PyCatalogStats {
             total_symbols: stats.total_symbols as usize,
             total_records: stats.total_records,
             date_range_start: stats.earliest_date.map(|d| d.to_rfc3339()),
             date_range_end: stats.latest_date.map(|d| d.to_rfc3339()),
+            breakdown: stats.breakdown,
         })
     }
 
     /// Get number of configured data providers
-    fn get_provider_count(&self) -> PyResult<usize> {
-        let inner = self.inner.lock().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-        })?;
-        Ok(inner.providers.len())
+    fn get_provider_count(&self, py: Python<'_>) -> usize {
+        py.detach(|| self.inner.blocking_lock().providers.len())
+    }
+
+    /// Catalogued symbols, one dict each: `symbol`, `exchange`,
+    /// `asset_class`, `resolutions`, the overall `start`/`end`/`records`, and
+    /// `coverage` mapping each resolution to its own `start`, `end` and
+    /// `records`.
+    fn list_symbols<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let infos = py
+            .detach(|| {
+                self.runtime.block_on(async {
+                    let inner = self.inner.lock().await;
+                    inner.catalog.list_symbol_data().await
+                })
+            })
+            .map_err(|e| engine_error("Failed to list symbols", e))?;
+
+        // The catalog lists each symbol's resolutions next to each other.
+        let mut grouped: Vec<Vec<gb_data::SymbolInfo>> = Vec::new();
+        for info in infos {
+            match grouped.last_mut() {
+                Some(group) if group[0].symbol == info.symbol => group.push(info),
+                _ => grouped.push(vec![info]),
+            }
+        }
+
+        let listed = PyList::empty(py);
+        for group in grouped {
+            let symbol = &group[0].symbol;
+            let coverage = PyDict::new(py);
+            for info in &group {
+                let range = PyDict::new(py);
+                range.set_item("start", info.first_date.to_rfc3339())?;
+                range.set_item("end", info.last_date.to_rfc3339())?;
+                range.set_item("records", info.record_count)?;
+                coverage.set_item(info.resolution.to_string(), range)?;
+            }
+            let start = group.iter().map(|info| info.first_date).min();
+            let end = group.iter().map(|info| info.last_date).max();
+
+            let entry = PyDict::new(py);
+            entry.set_item("symbol", &symbol.symbol)?;
+            entry.set_item("exchange", &symbol.exchange)?;
+            entry.set_item("asset_class", format!("{:?}", symbol.asset_class))?;
+            entry.set_item(
+                "resolutions",
+                group
+                    .iter()
+                    .map(|info| info.resolution.to_string())
+                    .collect::<Vec<_>>(),
+            )?;
+            entry.set_item("start", start.map(|start| start.to_rfc3339()))?;
+            entry.set_item("end", end.map(|end| end.to_rfc3339()))?;
+            entry.set_item(
+                "records",
+                group.iter().map(|info| info.record_count).sum::<u64>(),
+            )?;
+            entry.set_item("coverage", coverage)?;
+            listed.append(entry)?;
+        }
+        Ok(listed)
+    }
+
+    /// Parquet storage totals (`data_root`, `files`, `bytes`) and a
+    /// `symbols` list with each symbol's `files` and `bytes`.
+    fn storage_stats<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let stats = py
+            .detach(|| self.inner.blocking_lock().storage.get_stats())
+            .map_err(|e| engine_error("Failed to read storage stats", e))?;
+
+        let symbols = PyList::empty(py);
+        for entry in &stats.symbols {
+            let symbol = PyDict::new(py);
+            symbol.set_item("symbol", &entry.symbol.symbol)?;
+            symbol.set_item("exchange", &entry.symbol.exchange)?;
+            symbol.set_item("asset_class", format!("{:?}", entry.symbol.asset_class))?;
+            symbol.set_item("files", entry.files)?;
+            symbol.set_item("bytes", entry.size_bytes)?;
+            symbols.append(symbol)?;
+        }
+        let result = PyDict::new(py);
+        result.set_item("data_root", stats.data_root.display().to_string())?;
+        result.set_item("files", stats.total_files)?;
+        result.set_item("bytes", stats.total_size_bytes)?;
+        result.set_item("symbols", symbols)?;
+        Ok(result)
+    }
+
+    /// Runs of expected bars missing from storage between `start` and `end`,
+    /// as dicts with `start`, `end` and `missing_bars`. Providers are not
+    /// consulted.
+    fn find_gaps<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: &Bound<'_, PyAny>,
+        resolution: &str,
+        start: &str,
+        end: &str,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let symbol = symbol_arg(symbol)?;
+        let resolution = parse_resolution(resolution)?;
+        let start = parse_timestamp("start", start)?;
+        let end = parse_timestamp("end", end)?;
+        let gaps = py
+            .detach(|| {
+                self.runtime.block_on(async {
+                    let inner = self.inner.lock().await;
+                    inner.find_gaps(&symbol, resolution, start, end).await
+                })
+            })
+            .map_err(|e| engine_error("Failed to find gaps", e))?;
+
+        let listed = PyList::empty(py);
+        for gap in gaps {
+            let entry = PyDict::new(py);
+            entry.set_item("start", gap.start.to_rfc3339())?;
+            entry.set_item("end", gap.end.to_rfc3339())?;
+            entry.set_item("missing_bars", gap.missing_bars)?;
+            listed.append(entry)?;
+        }
+        Ok(listed)
+    }
+
+    /// Delete a symbol's stored bars, catalog entries and cached data;
+    /// returns how many files were removed.
+    fn purge_symbol(&self, py: Python<'_>, symbol: &Bound<'_, PyAny>) -> PyResult<u64> {
+        let symbol = symbol_arg(symbol)?;
+        py.detach(|| {
+            self.runtime.block_on(async {
+                let mut inner = self.inner.lock().await;
+                inner.purge_symbol(&symbol).await
+            })
+        })
+        .map_err(|e| engine_error("Failed to purge symbol", e))
+    }
+
+    /// Audit the catalog against storage, trimming registrations that claim
+    /// more than is stored; returns a dict of `checked`, `corrected` and
+    /// `removed` counts.
+    fn reconcile_catalog<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let report = py
+            .detach(|| {
+                self.runtime.block_on(async {
+                    let mut inner = self.inner.lock().await;
+                    inner.reconcile_catalog().await
+                })
+            })
+            .map_err(|e| engine_error("Failed to reconcile catalog", e))?;
+
+        let result = PyDict::new(py);
+        result.set_item("checked", report.checked)?;
+        result.set_item("corrected", report.corrected)?;
+        result.set_item("removed", report.removed)?;
+        Ok(result)
+    }
+}
+
+impl PyDataManager {
+    /// Register `provider`, waiting for any running load without the GIL.
+    fn add_provider(&self, py: Python<'_>, provider: Box<dyn gb_data::DataProvider>) {
+        py.detach(|| self.inner.blocking_lock().add_provider(provider));
     }
 }
 
@@ -668,6 +1222,40 @@ struct PyBar {
 
 #[pymethods]
 impl PyBar {
+    /// Build a bar, e.g. to feed a paper broker. `symbol` is a `Symbol` or a
+    /// ticker, taken as a NASDAQ equity.
+    /// Prices and volume may be floats or, to keep every digit, strings.
+    #[new]
+    #[pyo3(signature = (symbol, timestamp, open, high, low, close, volume=None, resolution="day"))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        symbol: &Bound<'_, PyAny>,
+        timestamp: &str,
+        open: &Bound<'_, PyAny>,
+        high: &Bound<'_, PyAny>,
+        low: &Bound<'_, PyAny>,
+        close: &Bound<'_, PyAny>,
+        volume: Option<&Bound<'_, PyAny>>,
+        resolution: &str,
+    ) -> PyResult<Self> {
+        let volume = match volume {
+            Some(volume) => decimal_arg("volume", volume)?,
+            None => Decimal::from(1000),
+        };
+        Ok(Self {
+            inner: gb_types::Bar::new(
+                symbol_arg(symbol)?,
+                parse_timestamp("timestamp", timestamp)?,
+                decimal_arg("open", open)?,
+                decimal_arg("high", high)?,
+                decimal_arg("low", low)?,
+                decimal_arg("close", close)?,
+                volume,
+                parse_resolution(resolution)?,
+            ),
+        })
+    }
+
     #[getter]
     fn symbol(&self) -> PySymbol {
         PySymbol {
@@ -681,28 +1269,28 @@ impl PyBar {
     }
 
     #[getter]
-    fn open(&self) -> f64 {
-        self.inner.open.to_f64().unwrap_or(0.0)
+    fn open(&self) -> PyResult<f64> {
+        bar_field_to_f64("open", self.inner.open)
     }
 
     #[getter]
-    fn high(&self) -> f64 {
-        self.inner.high.to_f64().unwrap_or(0.0)
+    fn high(&self) -> PyResult<f64> {
+        bar_field_to_f64("high", self.inner.high)
     }
 
     #[getter]
-    fn low(&self) -> f64 {
-        self.inner.low.to_f64().unwrap_or(0.0)
+    fn low(&self) -> PyResult<f64> {
+        bar_field_to_f64("low", self.inner.low)
     }
 
     #[getter]
-    fn close(&self) -> f64 {
-        self.inner.close.to_f64().unwrap_or(0.0)
+    fn close(&self) -> PyResult<f64> {
+        bar_field_to_f64("close", self.inner.close)
     }
 
     #[getter]
-    fn volume(&self) -> f64 {
-        self.inner.volume.to_f64().unwrap_or(0.0)
+    fn volume(&self) -> PyResult<f64> {
+        bar_field_to_f64("volume", self.inner.volume)
     }
 
     #[getter]
@@ -710,6 +1298,39 @@ impl PyBar {
         format!("{:?}", self.inner.resolution)
     }
 
+    /// Where the bar came from: `provider`, `split_adjusted`,
+    /// `dividend_adjusted`, `resampled_from` and `synthetic`.
+    #[getter]
+    fn provenance<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let provenance = &self.inner.provenance;
+        let dict = PyDict::new(py);
+        dict.set_item("provider", provenance.provider.as_deref())?;
+        dict.set_item("split_adjusted", provenance.split_adjusted)?;
+        dict.set_item("dividend_adjusted", provenance.dividend_adjusted)?;
+        dict.set_item(
+            "resampled_from",
+            provenance
+                .resampled_from
+                .map(|resolution| format!("{resolution:?}")),
+        )?;
+        dict.set_item("synthetic", provenance.synthetic)?;
+        Ok(dict)
+    }
+
+    /// Exact `open`, `high`, `low`, `close` and `volume` as decimal strings.
+    fn as_decimal_strings(&self) -> std::collections::HashMap<&'static str, String> {
+        [
+            ("open", self.inner.open),
+            ("high", self.inner.high),
+            ("low", self.inner.low),
+            ("close", self.inner.close),
+            ("volume", self.inner.volume),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name, value.to_string()))
+        .collect()
+    }
+
     fn __str__(&self) -> String {
         format!(
             "Bar({} {} O:{} H:{} L:{} C:{} V:{})",
@@ -746,10 +1367,31 @@ struct PyCatalogStats {
     total_records: u64,
     date_range_start: Option<String>,
     date_range_end: Option<String>,
+    breakdown: Vec<gb_data::SymbolCoverage>,
 }
 
 #[pymethods]
 impl PyCatalogStats {
+    /// One dict per symbol and resolution: `symbol`, `exchange`,
+    /// `asset_class`, `resolution`, `records`, `first`, `last` and `bytes`.
+    #[getter]
+    fn breakdown<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let breakdown = PyList::empty(py);
+        for coverage in &self.breakdown {
+            let entry = PyDict::new(py);
+            entry.set_item("symbol", &coverage.symbol.symbol)?;
+            entry.set_item("exchange", &coverage.symbol.exchange)?;
+            entry.set_item("asset_class", format!("{:?}", coverage.symbol.asset_class))?;
+            entry.set_item("resolution", coverage.resolution.to_string())?;
+            entry.set_item("records", coverage.records)?;
+            entry.set_item("first", coverage.first.to_rfc3339())?;
+            entry.set_item("last", coverage.last.to_rfc3339())?;
+            entry.set_item("bytes", coverage.bytes)?;
+            breakdown.append(entry)?;
+        }
+        Ok(breakdown)
+    }
+
     #[getter]
     fn date_range_start(&self) -> Option<String> {
         self.date_range_start.clone()
@@ -816,12 +1458,20 @@ struct ExposurePoint {
     net_exposure_pct: f64,
 }
 
+struct PositionPoint {
+    timestamp: String,
+    symbol: String,
+    quantity: f64,
+    market_value: f64,
+}
+
 #[pyclass(name = "BacktestResult")]
 struct PyBacktestResult {
     metrics_summary: std::collections::HashMap<String, f64>,
     equity_curve: Vec<EquityPoint>,
     trades: Vec<TradePoint>,
     exposures: Vec<ExposurePoint>,
+    positions_history: Vec<PositionPoint>,
     order_events: Vec<serde_json::Value>,
     option_trades: Vec<serde_json::Value>,
     option_events: Vec<serde_json::Value>,
@@ -868,6 +1518,12 @@ impl PyBacktestResult {
                 "annualized_return".to_string(),
                 decimal_to_f64(performance.annualized_return) * 100.0,
             );
+            if let Some(money_weighted_return) = performance.money_weighted_return {
+                metrics_summary.insert(
+                    "money_weighted_return".to_string(),
+                    decimal_to_f64(money_weighted_return) * 100.0,
+                );
+            }
             metrics_summary.insert(
                 "volatility".to_string(),
                 decimal_to_f64(performance.volatility) * 100.0,
@@ -1006,6 +1662,20 @@ impl PyBacktestResult {
             })
             .collect::<Vec<_>>();
 
+        let positions_history = result
+            .positions_history
+            .iter()
+            .flat_map(|snapshot| {
+                let timestamp = snapshot.timestamp.to_rfc3339();
+                snapshot.positions.iter().map(move |holding| PositionPoint {
+                    timestamp: timestamp.clone(),
+                    symbol: holding.symbol.symbol.clone(),
+                    quantity: decimal_to_f64(holding.quantity),
+                    market_value: decimal_to_f64(holding.market_value),
+                })
+            })
+            .collect::<Vec<_>>();
+
         let trades = result
             .trade_log
             .into_iter()
@@ -1130,6 +1800,7 @@ impl PyBacktestResult {
             equity_curve,
             trades,
             exposures,
+            positions_history,
             order_events,
             option_trades,
             option_events,
@@ -1173,6 +1844,54 @@ impl PyBacktestResult {
         Ok(list.unbind().into_any())
     }
 
+    /// The equity curve as one list per column (`timestamp`, `value`,
+    /// `cash`, `positions`, `total_pnl`, `returns`, `daily_return`,
+    /// `drawdown`), or NumPy arrays for the numeric columns when `numpy` is
+    /// true. Missing daily returns are NaN.
+    #[pyo3(signature = (numpy=false))]
+    fn equity_arrays(&self, py: Python, numpy: bool) -> PyResult<Py<PyAny>> {
+        let column = |value: fn(&EquityPoint) -> f64| -> Vec<f64> {
+            self.equity_curve.iter().map(value).collect()
+        };
+        let numeric = [
+            ("value", column(|point| point.value)),
+            ("cash", column(|point| point.cash)),
+            ("positions", column(|point| point.positions)),
+            ("total_pnl", column(|point| point.total_pnl)),
+            ("returns", column(|point| point.returns)),
+            (
+                "daily_return",
+                column(|point| point.daily_return.unwrap_or(f64::NAN)),
+            ),
+            ("drawdown", column(|point| point.drawdown)),
+        ];
+
+        let dict = PyDict::new(py);
+        dict.set_item(
+            "timestamp",
+            self.equity_curve
+                .iter()
+                .map(|point| point.timestamp.clone())
+                .collect::<Vec<_>>(),
+        )?;
+        let np = if numpy {
+            Some(py.import("numpy").map_err(|_| {
+                pyo3::exceptions::PyImportError::new_err(
+                    "numpy is required for equity_arrays(numpy=True)",
+                )
+            })?)
+        } else {
+            None
+        };
+        for (key, values) in numeric {
+            match &np {
+                Some(np) => dict.set_item(key, np.call_method1("asarray", (values,))?)?,
+                None => dict.set_item(key, values)?,
+            }
+        }
+        Ok(dict.unbind().into_any())
+    }
+
     #[getter]
     fn trades(&self, py: Python) -> PyResult<Py<PyAny>> {
         let list = PyList::empty(py);
@@ -1213,6 +1932,21 @@ impl PyBacktestResult {
         Ok(list.unbind().into_any())
     }
 
+    /// One row per held symbol per snapshot, in long format.
+    #[getter]
+    fn positions_history(&self, py: Python) -> PyResult<Py<PyAny>> {
+        let list = PyList::empty(py);
+        for point in &self.positions_history {
+            let dict = PyDict::new(py);
+            let _ = dict.set_item("timestamp", &point.timestamp);
+            let _ = dict.set_item("symbol", &point.symbol);
+            let _ = dict.set_item("quantity", point.quantity);
+            let _ = dict.set_item("market_value", point.market_value);
+            let _ = list.append(dict);
+        }
+        Ok(list.unbind().into_any())
+    }
+
     #[getter]
     fn order_events(&self, py: Python) -> PyResult<Py<PyAny>> {
         let json = py.import("json")?;
@@ -1317,6 +2051,29 @@ impl PyBacktestResult {
         Ok(df.unbind())
     }
 
+    /// Convert the position history to a long-format pandas DataFrame with
+    /// `timestamp`, `symbol`, `quantity` and `market_value` columns
+    fn positions_dataframe(&self, py: Python) -> PyResult<Py<PyAny>> {
+        let pandas = py.import("pandas").map_err(|_| {
+            pyo3::exceptions::PyImportError::new_err(
+                "pandas is required for positions_dataframe(). Install with `pip install pandas`.",
+            )
+        })?;
+
+        let data = self.positions_history(py)?;
+        let kwargs = PyDict::new(py);
+        kwargs.set_item(
+            "columns",
+            vec!["timestamp", "symbol", "quantity", "market_value"],
+        )?;
+        let df = pandas.getattr("DataFrame")?.call((data,), Some(&kwargs))?;
+        let to_datetime = pandas.getattr("to_datetime")?;
+        let ts = df.call_method1("__getitem__", ("timestamp",))?;
+        let ts_dt = to_datetime.call1((ts,))?;
+        df.call_method1("__setitem__", ("timestamp", ts_dt))?;
+        Ok(df.unbind())
+    }
+
     /// Convert metrics summary to a pandas DataFrame (Jupyter-friendly)
     fn metrics_dataframe(&self, py: Python) -> PyResult<Py<PyAny>> {
         let pandas = py.import("pandas").map_err(|_| {
@@ -1381,42 +2138,335 @@ impl PyBacktestResult {
     }
 }
 
-/// Python wrapper for running backtests
-#[pyclass(name = "BacktestEngine")]
-struct PyBacktestEngine {
-    inner: std::sync::Mutex<RustBacktestEngine>,
-    runtime: tokio::runtime::Runtime,
+/// Python-side backtest configuration: every setting of a `BacktestConfig`
+/// the bindings expose, validated as it is set. The `with_*` methods return
+/// an updated copy so calls can be chained.
+#[pyclass(name = "BacktestConfig", skip_from_py_object)]
+#[derive(Clone)]
+struct PyBacktestConfig {
+    name: String,
+    symbols: Vec<String>,
+    start_date: String,
+    end_date: String,
+    initial_capital: f64,
+    resolution: String,
+    strategy_name: String,
+    strategy_config: StrategyConfig,
+    data_source: Option<String>,
+    csv_data_path: Option<String>,
+    data_quality_mode: Option<String>,
+    position_snapshots: Option<String>,
+    commission_bps: Option<f64>,
+    slippage_bps: Option<f64>,
+    latency_ms: Option<u64>,
+}
+
+impl PyBacktestConfig {
+    /// The engine configuration these settings describe.
+    fn to_rust(&self) -> PyResult<BacktestConfig> {
+        let mut config = build_backtest_config(
+            self.symbols.clone(),
+            &self.start_date,
+            &self.end_date,
+            Some(&self.resolution),
+            Some(self.initial_capital),
+            Some(&self.name),
+            self.data_source.as_deref(),
+            self.data_quality_mode.as_deref(),
+            self.commission_bps,
+            self.slippage_bps,
+            self.latency_ms,
+            self.strategy_config.clone(),
+        )
+        .map_err(|error| ConfigError::new_err(error.to_string()))?;
+        if let Some(cadence) = self.position_snapshots.as_deref() {
+            config.data_settings.position_snapshots = parse_position_snapshots(cadence)?;
+        }
+        Ok(config)
+    }
+
+    fn validated(self) -> PyResult<Self> {
+        if !BUILTIN_STRATEGIES.contains(&self.strategy_name.as_str())
+            && self.strategy_name != "moving_average_crossover"
+        {
+            return Err(ConfigError::new_err(format!(
+                "Unsupported built-in strategy: {}. Supported strategies: {}",
+                self.strategy_name,
+                supported_builtin_strategies()
+            )));
+        }
+        if self.data_source.as_deref() == Some("csv") && self.csv_data_path.is_none() {
+            return Err(ConfigError::new_err(
+                "csv_data_path is required when data_source='csv'",
+            ));
+        }
+        let config = self.to_rust()?;
+        if config.start_date > config.end_date {
+            return Err(ConfigError::new_err(format!(
+                "start_date {} is after end_date {}",
+                self.start_date, self.end_date
+            )));
+        }
+        if config.initial_capital <= Decimal::ZERO {
+            return Err(ConfigError::new_err("initial_capital must be positive"));
+        }
+        Ok(self)
+    }
 }
 
 #[pymethods]
-impl PyBacktestEngine {
+impl PyBacktestConfig {
     #[new]
     #[pyo3(signature = (
         symbols,
         start_date,
         end_date,
-        resolution=None,
-        initial_capital=None,
         name=None,
-        commission_bps=None,
-        slippage_bps=None,
-        latency_ms=None,
-        data_source=None,
-        csv_data_path=None,
-        data_quality_mode=None,
+        initial_capital=None,
+        resolution=None,
+        strategy="buy_and_hold",
+        strategy_params=None,
     ))]
+    #[allow(clippy::too_many_arguments)]
     fn new(
         symbols: Vec<String>,
         start_date: &str,
         end_date: &str,
-        resolution: Option<&str>,
-        initial_capital: Option<f64>,
         name: Option<&str>,
-        commission_bps: Option<f64>,
-        slippage_bps: Option<f64>,
-        latency_ms: Option<u64>,
-        data_source: Option<&str>,
-        csv_data_path: Option<&str>,
+        initial_capital: Option<f64>,
+        resolution: Option<&str>,
+        strategy: &str,
+        strategy_params: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Self> {
+        let strategy_name = strategy.trim().to_lowercase();
+        let mut strategy_config = StrategyConfig::new(strategy_name.clone(), strategy_name.clone());
+        apply_strategy_params(&mut strategy_config, strategy_params)?;
+        Self {
+            name: name.unwrap_or("Python Backtest").to_string(),
+            symbols,
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            initial_capital: initial_capital.unwrap_or(100_000.0),
+            resolution: resolution.unwrap_or("day").to_string(),
+            strategy_name,
+            strategy_config,
+            data_source: Some("sample".to_string()),
+            csv_data_path: None,
+            data_quality_mode: None,
+            position_snapshots: None,
+            commission_bps: None,
+            slippage_bps: None,
+            latency_ms: None,
+        }
+        .validated()
+    }
+
+    fn with_name(&self, name: &str) -> PyResult<Self> {
+        Self {
+            name: name.to_string(),
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    fn with_symbols(&self, symbols: Vec<String>) -> PyResult<Self> {
+        Self {
+            symbols,
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    fn with_dates(&self, start_date: &str, end_date: &str) -> PyResult<Self> {
+        Self {
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    fn with_initial_capital(&self, initial_capital: f64) -> PyResult<Self> {
+        Self {
+            initial_capital,
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    fn with_resolution(&self, resolution: &str) -> PyResult<Self> {
+        Self {
+            resolution: resolution.to_string(),
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    /// Run the built-in strategy `strategy` with `params` (replacing any
+    /// earlier parameters).
+    #[pyo3(signature = (strategy, params=None))]
+    fn with_strategy(&self, strategy: &str, params: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let strategy_name = strategy.trim().to_lowercase();
+        let mut strategy_config = StrategyConfig::new(strategy_name.clone(), strategy_name.clone());
+        apply_strategy_params(&mut strategy_config, params)?;
+        Self {
+            strategy_name,
+            strategy_config,
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    /// Load market data from `source` ("sample", "csv", ...). CSV data needs
+    /// `csv_data_path`.
+    #[pyo3(signature = (source, csv_data_path=None))]
+    fn with_data_source(&self, source: &str, csv_data_path: Option<&str>) -> PyResult<Self> {
+        Self {
+            data_source: Some(source.trim().to_ascii_lowercase()),
+            csv_data_path: csv_data_path.map(str::to_string),
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    fn with_data_quality_mode(&self, mode: &str) -> PyResult<Self> {
+        parse_data_quality_mode(mode)?;
+        Self {
+            data_quality_mode: Some(mode.to_string()),
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    /// How often to record position snapshots: `"off"`, `"daily"` (the
+    /// default), `"weekly"` or `"monthly"`.
+    fn with_position_snapshots(&self, cadence: &str) -> PyResult<Self> {
+        parse_position_snapshots(cadence)?;
+        Self {
+            position_snapshots: Some(cadence.to_string()),
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    #[pyo3(signature = (commission_bps=None, slippage_bps=None, latency_ms=None))]
+    fn with_execution(
+        &self,
+        commission_bps: Option<f64>,
+        slippage_bps: Option<f64>,
+        latency_ms: Option<u64>,
+    ) -> PyResult<Self> {
+        Self {
+            commission_bps: commission_bps.or(self.commission_bps),
+            slippage_bps: slippage_bps.or(self.slippage_bps),
+            latency_ms: latency_ms.or(self.latency_ms),
+            ..self.clone()
+        }
+        .validated()
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    #[getter]
+    fn symbols(&self) -> Vec<String> {
+        self.symbols.clone()
+    }
+
+    #[getter]
+    fn start_date(&self) -> String {
+        self.start_date.clone()
+    }
+
+    #[getter]
+    fn end_date(&self) -> String {
+        self.end_date.clone()
+    }
+
+    #[getter]
+    fn initial_capital(&self) -> f64 {
+        self.initial_capital
+    }
+
+    #[getter]
+    fn resolution(&self) -> String {
+        self.resolution.clone()
+    }
+
+    #[getter]
+    fn strategy(&self) -> String {
+        self.strategy_name.clone()
+    }
+
+    #[getter]
+    fn strategy_params(&self, py: Python) -> PyResult<Py<PyAny>> {
+        let json = py.import("json")?;
+        let payload = serde_json::to_string(&self.strategy_config.parameters).map_err(|error| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!(
+                "Failed to serialize strategy parameters: {}",
+                error
+            ))
+        })?;
+        Ok(json.call_method1("loads", (payload,))?.unbind())
+    }
+
+    #[getter]
+    fn data_source(&self) -> Option<String> {
+        self.data_source.clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "BacktestConfig(name='{}', symbols={:?}, start_date='{}', end_date='{}', strategy='{}')",
+            self.name, self.symbols, self.start_date, self.end_date, self.strategy_name
+        )
+    }
+}
+
+/// Python wrapper for running backtests
+///
+/// Runs release the GIL; like `DataManager`, the engine is locked inside the
+/// runtime so runs from several Python threads queue.
+#[pyclass(name = "BacktestEngine")]
+struct PyBacktestEngine {
+    inner: tokio::sync::Mutex<RustBacktestEngine>,
+    runtime: OwnedRuntime,
+    /// Built-in strategy and its configuration run by `run()`, when the
+    /// engine was built from a `BacktestConfig`.
+    strategy: Option<(String, StrategyConfig)>,
+}
+
+#[pymethods]
+impl PyBacktestEngine {
+    #[new]
+    #[pyo3(signature = (
+        symbols,
+        start_date,
+        end_date,
+        resolution=None,
+        initial_capital=None,
+        name=None,
+        commission_bps=None,
+        slippage_bps=None,
+        latency_ms=None,
+        data_source=None,
+        csv_data_path=None,
+        data_quality_mode=None,
+    ))]
+    fn new(
+        symbols: Vec<String>,
+        start_date: &str,
+        end_date: &str,
+        resolution: Option<&str>,
+        initial_capital: Option<f64>,
+        name: Option<&str>,
+        commission_bps: Option<f64>,
+        slippage_bps: Option<f64>,
+        latency_ms: Option<u64>,
+        data_source: Option<&str>,
+        csv_data_path: Option<&str>,
         data_quality_mode: Option<&str>,
     ) -> PyResult<Self> {
         if symbols.is_empty() {
@@ -1486,21 +2536,11 @@ impl PyBacktestEngine {
             config.data_settings.data_quality_mode = parse_data_quality_mode(mode)?;
         }
 
-        let runtime = tokio::runtime::Runtime::new().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!(
-                "Failed to create async runtime: {}",
-                e
-            ))
-        })?;
+        let runtime = OwnedRuntime::new()?;
 
         let mut inner = runtime
             .block_on(async { RustBacktestEngine::new(config).await })
-            .map_err(|e| {
-                pyo3::exceptions::PyRuntimeError::new_err(format!(
-                    "Failed to create backtest engine: {}",
-                    e
-                ))
-            })?;
+            .map_err(|e| engine_error("Failed to create backtest engine", e))?;
 
         if normalized_data_source == "csv" {
             let base_path = csv_data_path.ok_or_else(|| {
@@ -1512,59 +2552,142 @@ impl PyBacktestEngine {
         }
 
         Ok(Self {
-            inner: std::sync::Mutex::new(inner),
+            inner: tokio::sync::Mutex::new(inner),
             runtime,
+            strategy: None,
         })
     }
 
-    fn add_sample_provider(&mut self) -> PyResult<()> {
-        let mut inner = self.inner.lock().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-        })?;
-        inner.add_sample_provider();
-        Ok(())
+    /// Build an engine for `config`; `run()` then executes its strategy.
+    #[staticmethod]
+    fn from_config(config: &PyBacktestConfig) -> PyResult<Self> {
+        let rust_config = config.to_rust()?;
+        let runtime = OwnedRuntime::new()?;
+        let mut inner = runtime
+            .block_on(async { RustBacktestEngine::new(rust_config).await })
+            .map_err(|e| engine_error("Failed to create backtest engine", e))?;
+        if let Some(base_path) = &config.csv_data_path {
+            inner.add_csv_provider(base_path);
+        }
+
+        Ok(Self {
+            inner: tokio::sync::Mutex::new(inner),
+            runtime,
+            strategy: Some((config.strategy_name.clone(), config.strategy_config.clone())),
+        })
     }
 
-    fn add_csv_provider(&mut self, base_path: &str) -> PyResult<()> {
-        let mut inner = self.inner.lock().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-        })?;
-        inner.add_csv_provider(base_path);
+    /// Run `strategy`, a Python object defining `on_market_event`,
+    /// `on_order_event` and/or `on_day_end`. Without one, run the strategy
+    /// from the engine's `BacktestConfig` (buy-and-hold for engines built
+    /// with the constructor).
+    #[pyo3(signature = (strategy=None))]
+    fn run(
+        &self,
+        py: Python<'_>,
+        strategy: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<PyBacktestResult> {
+        if let Some(strategy) = strategy {
+            return self.run_python_strategy(py, strategy);
+        }
+        let (strategy_name, strategy_config) = self.strategy.clone().unwrap_or_else(|| {
+            (
+                "buy_and_hold".to_string(),
+                StrategyConfig::new("buy_and_hold".to_string(), "Buy and Hold".to_string()),
+            )
+        });
+        let strategy = build_builtin_strategy(&strategy_name, &strategy_config)?;
+        self.execute(py, |_| strategy)
+    }
+
+    /// Add a sample data provider; takes the same options as
+    /// `DataManager.add_sample_provider`.
+    #[pyo3(signature = (**options))]
+    fn add_sample_provider(
+        &self,
+        py: Python<'_>,
+        options: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<()> {
+        let config = sample_data_config(options)?;
+        py.detach(|| {
+            self.inner
+                .blocking_lock()
+                .add_sample_provider_with_config(config)
+        });
         Ok(())
     }
 
+    fn add_csv_provider(&self, py: Python<'_>, base_path: &str) {
+        py.detach(|| self.inner.blocking_lock().add_csv_provider(base_path));
+    }
+
     /// Run a backtest using the built-in buy-and-hold strategy
-    fn run_buy_and_hold(&mut self) -> PyResult<PyBacktestResult> {
-        self.run_strategy("buy_and_hold", None)
+    fn run_buy_and_hold(&self, py: Python<'_>) -> PyResult<PyBacktestResult> {
+        self.run_strategy(py, "buy_and_hold", None)
     }
 
     fn run_strategy(
-        &mut self,
+        &self,
+        py: Python<'_>,
         strategy_name: &str,
         params: Option<&Bound<PyDict>>,
     ) -> PyResult<PyBacktestResult> {
         let strategy = build_strategy(strategy_name, params)?;
-        let mut inner = self.inner.lock().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to acquire lock: {}", e))
-        })?;
-        let result = self
-            .runtime
-            .block_on(inner.run_with_strategy(strategy))
-            .map_err(|e| {
-                pyo3::exceptions::PyRuntimeError::new_err(format!(
-                    "Backtest execution failed: {}",
-                    e
-                ))
-            })?;
+        self.execute(py, |_| strategy)
+    }
+}
+
+impl PyBacktestEngine {
+    /// Run the strategy built by `strategy` with the GIL released. The
+    /// builder receives the engine's cancellation handle, cleared for this
+    /// run.
+    fn execute(
+        &self,
+        py: Python<'_>,
+        strategy: impl FnOnce(CancellationHandle) -> Box<dyn Strategy> + Send,
+    ) -> PyResult<PyBacktestResult> {
+        let result = py
+            .detach(|| {
+                self.runtime.block_on(async {
+                    let mut engine = self.inner.lock().await;
+                    let cancellation = engine.cancellation_handle();
+                    cancellation.reset();
+                    let result = engine
+                        .run_with_strategy(strategy(cancellation.clone()))
+                        .await;
+                    cancellation.reset();
+                    result
+                })
+            })
+            .map_err(|e| engine_error("Backtest execution failed", e))?;
 
         Ok(PyBacktestResult::from_backtest_result(result))
     }
+
+    /// Run a Python strategy object. An exception it raises cancels the run
+    /// and is re-raised as `StrategyError` with the Python traceback.
+    fn run_python_strategy(
+        &self,
+        py: Python<'_>,
+        object: &Bound<'_, PyAny>,
+    ) -> PyResult<PyBacktestResult> {
+        let failure = strategy::StrategyFailure::default();
+        let strategy = PyStrategy::new(object, failure.clone())?;
+        let result = self.execute(py, |cancellation| {
+            Box::new(strategy.with_cancellation(cancellation))
+        });
+        match strategy_failure_error(py, &failure) {
+            Some(error) => Err(error),
+            None => result,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use pyo3::types::PyModule;
+    use std::collections::HashMap;
     use std::sync::Once;
 
     static PYTHON_INIT: Once = Once::new();
@@ -1752,6 +2875,38 @@ mod tests {
             assert!(exports.contains(&"BacktestResult".to_string()));
             assert!(exports.contains(&"run_builtin_strategy".to_string()));
             assert!(exports.contains(&"PyBacktestEngine".to_string()));
+            assert!(exports.contains(&"BacktestConfig".to_string()));
+            assert!(exports.contains(&"StrategyContext".to_string()));
+            assert!(exports.contains(&"OrderEvent".to_string()));
+            for name in [
+                "SearchSpace",
+                "OptimizationConfig",
+                "Optimizer",
+                "OptimizationResult",
+                "OptionContract",
+                "PaperBroker",
+                "LiveEngine",
+                "RiskMonitor",
+                "PortfolioRiskSnapshot",
+                "black_scholes",
+                "implied_vol",
+                "black_scholes_array",
+                "implied_vol_array",
+            ] {
+                assert!(exports.contains(&name.to_string()), "{name}");
+                assert!(module.getattr(name).is_ok(), "{name}");
+            }
+            for exception in [
+                "GlowBackError",
+                "DataError",
+                "StrategyError",
+                "ConfigError",
+                "BacktestError",
+                "BrokerError",
+            ] {
+                assert!(exports.contains(&exception.to_string()), "{exception}");
+                assert!(module.getattr(exception).is_ok(), "{exception}");
+            }
             assert_eq!(
                 builtin_strategies,
                 BUILTIN_STRATEGIES
@@ -1803,4 +2958,1305 @@ mod tests {
             rust_option_trades.len() as f64
         );
     }
+
+    fn sample_config(py: Python, strategy: &str, params: &[(&str, i64)]) -> PyBacktestConfig {
+        let strategy_params = PyDict::new(py);
+        for (key, value) in params {
+            strategy_params.set_item(key, *value).unwrap();
+        }
+        PyBacktestConfig::new(
+            vec![TEST_SYMBOL.to_string()],
+            TEST_START,
+            TEST_END,
+            Some("Python parity test"),
+            Some(TEST_CAPITAL),
+            Some("day"),
+            "buy_and_hold",
+            None,
+        )
+        .unwrap()
+        .with_strategy(strategy, Some(&strategy_params))
+        .unwrap()
+        .with_data_source("sample", None)
+        .unwrap()
+        .with_data_quality_mode("warn")
+        .unwrap()
+        .with_execution(
+            Some(TEST_COMMISSION_BPS),
+            Some(TEST_SLIPPAGE_BPS),
+            Some(TEST_LATENCY_MS),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn backtest_config_engine_runs_its_strategy_like_the_rust_engine() {
+        init_python();
+        let params = [("short_period", 5), ("long_period", 20)];
+        let python_result = Python::attach(|py| {
+            let config = sample_config(py, "ma_crossover", &params);
+            assert_eq!(config.strategy(), "ma_crossover");
+            let engine = PyBacktestEngine::from_config(&config).unwrap();
+            let result = engine.run(py, None).unwrap();
+
+            let arrays = result.equity_arrays(py, false).unwrap();
+            let arrays = arrays.bind(py).cast::<PyDict>().unwrap();
+            let values: Vec<f64> = arrays
+                .get_item("value")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(values.len(), result.equity_curve.len());
+            result
+        });
+        let rust_result = run_rust_builtin_strategy("ma_crossover", &params);
+        assert_python_result_matches_rust(&python_result, &rust_result);
+    }
+
+    #[test]
+    fn backtest_errors_map_to_glowback_exceptions() {
+        init_python();
+        Python::attach(|py| {
+            let config = sample_config(py, "buy_and_hold", &[]);
+
+            let error = config.with_dates(TEST_END, TEST_START).err().unwrap();
+            assert!(error.is_instance_of::<ConfigError>(py));
+            assert!(error.is_instance_of::<pyo3::exceptions::PyRuntimeError>(py));
+            let error = config.with_strategy("martingale", None).err().unwrap();
+            assert!(error.is_instance_of::<ConfigError>(py));
+            let error = config.with_data_source("csv", None).err().unwrap();
+            assert!(error.is_instance_of::<ConfigError>(py));
+
+            let missing_data = config
+                .with_data_source("csv", Some("/nonexistent/glowback-data"))
+                .unwrap()
+                .with_data_quality_mode("fail")
+                .unwrap();
+            let error = PyBacktestEngine::from_config(&missing_data)
+                .and_then(|engine| engine.run(py, None))
+                .err()
+                .unwrap();
+            assert!(error.is_instance_of::<DataError>(py), "{error}");
+        });
+    }
+
+    #[test]
+    fn position_snapshot_cadence_reaches_the_engine() {
+        init_python();
+        Python::attach(|py| {
+            let config = sample_config(py, "buy_and_hold", &[]);
+            let daily = PyBacktestEngine::from_config(&config)
+                .unwrap()
+                .run(py, None)
+                .unwrap();
+            assert!(!daily.positions_history.is_empty());
+            assert!(daily
+                .positions_history
+                .iter()
+                .all(|point| point.symbol == TEST_SYMBOL && point.quantity > 0.0));
+
+            let monthly = config.with_position_snapshots("monthly").unwrap();
+            assert_eq!(
+                monthly.to_rust().unwrap().data_settings.position_snapshots,
+                SnapshotCadence::Monthly
+            );
+            let off =
+                PyBacktestEngine::from_config(&config.with_position_snapshots("off").unwrap())
+                    .unwrap()
+                    .run(py, None)
+                    .unwrap();
+            assert!(off.positions_history.is_empty());
+
+            let error = config.with_position_snapshots("hourly").err().unwrap();
+            assert!(error.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+        });
+    }
+
+    const PYTHON_STRATEGIES: &std::ffi::CStr = cr#"
+class BuyOnce:
+    """Buys 10 shares on the first bar and holds."""
+
+    def __init__(self):
+        self.bars = 0
+        self.fills = []
+
+    def on_market_event(self, bar, context):
+        self.bars += 1
+        if context.position(bar.symbol.symbol) == 0 and not self.fills:
+            return {"action": "buy", "symbol": bar.symbol.symbol, "quantity": 10}
+
+    def on_order_event(self, event, context):
+        if event.kind == "filled":
+            self.fills.append((event.side, event.quantity))
+
+
+class FailsOnFifthBar:
+    def __init__(self):
+        self.bars = 0
+
+    def on_market_event(self, bar, context):
+        self.bars += 1
+        if self.bars == 5:
+            raise ValueError("bad signal on bar 5")
+        return []
+"#;
+
+    /// Rust twin of the Python `BuyOnce` strategy.
+    struct RustBuyOnce {
+        config: StrategyConfig,
+        bought: bool,
+    }
+
+    impl Strategy for RustBuyOnce {
+        fn initialize(&mut self, _config: &StrategyConfig) -> Result<(), String> {
+            Ok(())
+        }
+
+        fn on_market_event(
+            &mut self,
+            event: &gb_types::MarketEvent,
+            _context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            let gb_types::MarketEvent::Bar(bar) = event else {
+                return Ok(Vec::new());
+            };
+            if self.bought {
+                return Ok(Vec::new());
+            }
+            self.bought = true;
+            Ok(vec![gb_types::StrategyAction::PlaceOrder(
+                gb_types::Order::market_order(
+                    bar.symbol.clone(),
+                    gb_types::Side::Buy,
+                    Decimal::from(10),
+                    "BuyOnce".to_string(),
+                ),
+            )])
+        }
+
+        fn on_order_event(
+            &mut self,
+            _event: &gb_types::OrderEvent,
+            _context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            Ok(Vec::new())
+        }
+
+        fn on_day_end(
+            &mut self,
+            _context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            Ok(Vec::new())
+        }
+
+        fn on_stop(
+            &mut self,
+            _context: &gb_types::StrategyContext,
+        ) -> Result<Vec<gb_types::StrategyAction>, String> {
+            Ok(Vec::new())
+        }
+
+        fn get_config(&self) -> &StrategyConfig {
+            &self.config
+        }
+
+        fn get_metrics(&self) -> gb_types::StrategyMetrics {
+            gb_types::StrategyMetrics::new(self.config.strategy_id.clone())
+        }
+    }
+
+    fn python_strategy<'py>(py: Python<'py>, class: &str) -> Bound<'py, PyAny> {
+        PyModule::from_code(py, PYTHON_STRATEGIES, c"strategies.py", c"strategies")
+            .unwrap()
+            .getattr(class)
+            .unwrap()
+            .call0()
+            .unwrap()
+    }
+
+    #[test]
+    fn python_strategy_matches_rust_twin_and_reports_overhead() {
+        init_python();
+        Python::attach(|py| {
+            let config = sample_config(py, "buy_and_hold", &[]);
+            let engine = PyBacktestEngine::from_config(&config).unwrap();
+            // Warm up data loading so both timings measure the same work.
+            engine.run(py, None).unwrap();
+
+            let rust_started = std::time::Instant::now();
+            let rust_result = engine
+                .execute(py, |_| {
+                    Box::new(RustBuyOnce {
+                        config: StrategyConfig::new("BuyOnce".into(), "BuyOnce".into()),
+                        bought: false,
+                    })
+                })
+                .unwrap();
+            let rust_elapsed = rust_started.elapsed();
+
+            let strategy = python_strategy(py, "BuyOnce");
+            let python_started = std::time::Instant::now();
+            let python_result = engine.run(py, Some(&strategy)).unwrap();
+            let python_elapsed = python_started.elapsed();
+
+            let bars: usize = strategy.getattr("bars").unwrap().extract().unwrap();
+            assert_eq!(bars, python_result.equity_curve.len());
+            let fills: Vec<(String, f64)> = strategy.getattr("fills").unwrap().extract().unwrap();
+            assert_eq!(fills, vec![("buy".to_string(), 10.0)]);
+
+            assert_eq!(python_result.trades.len(), rust_result.trades.len());
+            approx_eq(
+                python_result.metrics_summary["final_value"],
+                rust_result.metrics_summary["final_value"],
+                1e-6,
+            );
+            assert_eq!(python_result.final_positions, rust_result.final_positions);
+
+            eprintln!(
+                "BuyOnce over {bars} bars: rust {rust_elapsed:?}, python {python_elapsed:?} \
+                 ({:.1} us/bar callback overhead)",
+                python_elapsed.saturating_sub(rust_elapsed).as_secs_f64() * 1e6 / bars as f64
+            );
+        });
+    }
+
+    #[test]
+    fn objects_owning_a_runtime_can_be_freed_inside_callbacks() {
+        init_python();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "glowback").unwrap();
+            glowback(py, &module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("glowback", &module).unwrap();
+            globals
+                .set_item("config", sample_config(py, "buy_and_hold", &[]))
+                .unwrap();
+            // Garbage collection can free an engine, data manager or
+            // optimizer while a callback runs inside another engine's
+            // runtime; releasing the last reference does so deterministically.
+            py.run(
+                cr#"
+class ReleasesObjects:
+    def __init__(self, *objects):
+        self.objects = list(objects)
+
+    def on_market_event(self, bar, context):
+        self.objects.clear()
+
+def spare_objects():
+    space = glowback.SearchSpace().add_int("short_period", 3, 4)
+    return (
+        glowback.BacktestEngine.from_config(config),
+        glowback.DataManager(),
+        glowback.Optimizer(glowback.OptimizationConfig(space, config)),
+    )
+
+strategy = ReleasesObjects(*spare_objects())
+result = glowback.BacktestEngine.from_config(config).run(strategy)
+
+spares = [spare_objects(), spare_objects()]
+glowback.Optimizer(
+    glowback.OptimizationConfig(glowback.SearchSpace().add_int("short_period", 3, 4), config)
+).run(progress=lambda trial: spares.pop())
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+            let strategy = globals.get_item("strategy").unwrap().unwrap();
+            let remaining = strategy.getattr("objects").unwrap();
+            assert_eq!(remaining.len().unwrap(), 0);
+            let spares = globals.get_item("spares").unwrap().unwrap();
+            assert_eq!(spares.len().unwrap(), 0);
+        });
+    }
+
+    #[test]
+    fn python_strategy_exception_aborts_with_traceback() {
+        init_python();
+        Python::attach(|py| {
+            let config = sample_config(py, "buy_and_hold", &[]);
+            let engine = PyBacktestEngine::from_config(&config).unwrap();
+            let strategy = python_strategy(py, "FailsOnFifthBar");
+
+            let error = engine.run(py, Some(&strategy)).err().unwrap();
+            assert!(error.is_instance_of::<StrategyError>(py));
+            let message = error.value(py).to_string();
+            assert!(message.contains("on_market_event"), "{message}");
+            assert!(message.contains("Traceback"), "{message}");
+            assert!(
+                message.contains("ValueError: bad signal on bar 5"),
+                "{message}"
+            );
+            let cause = error.cause(py).unwrap();
+            assert!(cause.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+
+            // The run stopped at the failure instead of calling the strategy
+            // again for the rest of the data.
+            let bars: usize = strategy.getattr("bars").unwrap().extract().unwrap();
+            assert_eq!(bars, 5);
+
+            // The engine is reusable once the failed run is cleaned up.
+            assert!(engine.run(py, None).is_ok());
+            let error = engine.run(py, Some(&PyDict::new(py))).err().unwrap();
+            assert!(error.is_instance_of::<pyo3::exceptions::PyTypeError>(py));
+        });
+    }
+    /// Sample data delivered after a fixed delay, standing in for a network
+    /// or disk bound provider.
+    #[derive(Debug)]
+    struct SlowSampleProvider(gb_data::SampleDataProvider);
+
+    const SLOW_PROVIDER_DELAY: std::time::Duration = std::time::Duration::from_millis(300);
+
+    #[async_trait::async_trait]
+    impl gb_data::DataProvider for SlowSampleProvider {
+        fn supports_symbol(&self, symbol: &Symbol) -> bool {
+            self.0.supports_symbol(symbol)
+        }
+
+        async fn fetch_bars(
+            &mut self,
+            symbol: &Symbol,
+            start_date: chrono::DateTime<chrono::Utc>,
+            end_date: chrono::DateTime<chrono::Utc>,
+            resolution: Resolution,
+        ) -> gb_types::GbResult<Vec<gb_types::Bar>> {
+            tokio::time::sleep(SLOW_PROVIDER_DELAY).await;
+            self.0
+                .fetch_bars(symbol, start_date, end_date, resolution)
+                .await
+        }
+
+        fn name(&self) -> &str {
+            "slow-sample"
+        }
+
+        fn config(&self) -> serde_json::Value {
+            self.0.config()
+        }
+    }
+
+    fn slow_data_manager(py: Python<'_>, data_dir: &std::path::Path) -> Py<PyDataManager> {
+        let runtime = OwnedRuntime::new().unwrap();
+        let mut inner = runtime
+            .block_on(gb_data::DataManager::new_with_data_dir(data_dir))
+            .unwrap();
+        inner.add_provider(Box::new(SlowSampleProvider(
+            gb_data::SampleDataProvider::new(),
+        )));
+        Py::new(
+            py,
+            PyDataManager {
+                inner: tokio::sync::Mutex::new(inner),
+                runtime,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn data_loads_from_python_threads_run_without_the_gil() {
+        init_python();
+        let data_dir = tempfile::tempdir().unwrap();
+        Python::attach(|py| {
+            let manager = |name: &str| slow_data_manager(py, &data_dir.path().join(name));
+            let symbol = |ticker: &str| {
+                Py::new(
+                    py,
+                    PySymbol {
+                        inner: Symbol::equity(ticker),
+                    },
+                )
+                .unwrap()
+            };
+
+            let globals = PyDict::new(py);
+            globals.set_item("START", TEST_START).unwrap();
+            globals.set_item("END", TEST_END).unwrap();
+            let shared = manager("shared");
+            for (name, jobs) in [
+                (
+                    "serial_jobs",
+                    [(manager("serial-a"), "AAPL"), (manager("serial-b"), "MSFT")],
+                ),
+                (
+                    "concurrent_jobs",
+                    [
+                        (manager("concurrent-a"), "AAPL"),
+                        (manager("concurrent-b"), "MSFT"),
+                    ],
+                ),
+                (
+                    "shared_jobs",
+                    [(shared.clone_ref(py), "AAPL"), (shared, "MSFT")],
+                ),
+            ] {
+                let jobs = jobs
+                    .into_iter()
+                    .map(|(manager, ticker)| (manager, symbol(ticker)))
+                    .collect::<Vec<_>>();
+                globals.set_item(name, jobs).unwrap();
+            }
+
+            py.run(
+                cr#"
+import threading
+import time
+
+loaded = []
+
+def load(manager, symbol):
+    loaded.append((symbol.symbol, len(manager.load_data(symbol, START, END, "day"))))
+
+def timed(jobs, concurrent):
+    started = time.perf_counter()
+    if concurrent:
+        threads = [threading.Thread(target=load, args=job) for job in jobs]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+    else:
+        for job in jobs:
+            load(*job)
+    return time.perf_counter() - started
+
+serial = timed(serial_jobs, concurrent=False)
+concurrent = timed(concurrent_jobs, concurrent=True)
+shared = timed(shared_jobs, concurrent=True)
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+
+            let elapsed =
+                |name: &str| -> f64 { globals.get_item(name).unwrap().unwrap().extract().unwrap() };
+            let (serial, concurrent, shared) =
+                (elapsed("serial"), elapsed("concurrent"), elapsed("shared"));
+            let loaded: Vec<(String, usize)> = globals
+                .get_item("loaded")
+                .unwrap()
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(loaded.len(), 6, "a load failed: {loaded:?}");
+            assert!(loaded.iter().all(|(_, bars)| *bars > 0), "{loaded:?}");
+
+            assert!(
+                serial >= 2.0 * SLOW_PROVIDER_DELAY.as_secs_f64(),
+                "serial loads took {serial:.3}s"
+            );
+            assert!(
+                concurrent < serial,
+                "concurrent loads took {concurrent:.3}s, serial {serial:.3}s"
+            );
+            // One manager serializes its own loads but must not deadlock.
+            assert!(shared >= 2.0 * SLOW_PROVIDER_DELAY.as_secs_f64());
+        });
+    }
+
+    #[test]
+    fn data_manager_lists_catalog_storage_and_gaps() {
+        init_python();
+        let data_dir = tempfile::tempdir().unwrap();
+        Python::attach(|py| {
+            let globals = glowback_globals(py);
+            globals
+                .set_item("data_dir", data_dir.path().to_str().unwrap())
+                .unwrap();
+            py.run(
+                cr#"
+manager = glowback.DataManager(data_dir)
+manager.add_sample_provider()
+for ticker in ["MSFT", "AAPL"]:
+    symbol = glowback.Symbol(ticker, "NASDAQ", "equity")
+    manager.load_data(symbol, "2024-01-01T00:00:00Z", "2024-01-31T00:00:00Z", "day")
+listed = manager.list_symbols()
+stats = manager.storage_stats()
+# January is stored, so only February's weekdays are missing.
+gaps = manager.find_gaps("AAPL", "1d", "2024-01-01T00:00:00Z", "2024-02-29T00:00:00Z")
+purged = manager.purge_symbol("AAPL")
+after_purge = [entry["symbol"] for entry in manager.list_symbols()]
+purged_gaps = manager.find_gaps("AAPL", "1d", "2024-01-01T00:00:00Z", "2024-01-31T00:00:00Z")
+stats_after = manager.storage_stats()
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+            let get = |name: &str| globals.get_item(name).unwrap().unwrap();
+
+            let listed = get("listed");
+            let names: Vec<String> = listed
+                .try_iter()
+                .unwrap()
+                .map(|entry| {
+                    entry
+                        .unwrap()
+                        .get_item("symbol")
+                        .unwrap()
+                        .extract()
+                        .unwrap()
+                })
+                .collect();
+            assert_eq!(names, ["AAPL", "MSFT"]);
+            let aapl = listed.get_item(0).unwrap();
+            let field = |name: &str| aapl.get_item(name).unwrap();
+            assert_eq!(field("exchange").extract::<String>().unwrap(), "NASDAQ");
+            assert_eq!(field("asset_class").extract::<String>().unwrap(), "Equity");
+            assert_eq!(
+                field("resolutions").extract::<Vec<String>>().unwrap(),
+                ["1d"]
+            );
+            let records: u64 = field("records").extract().unwrap();
+            assert!(records > 0);
+            let daily = field("coverage").get_item("1d").unwrap();
+            assert_eq!(
+                daily.get_item("records").unwrap().extract::<u64>().unwrap(),
+                records
+            );
+            assert_eq!(
+                daily
+                    .get_item("start")
+                    .unwrap()
+                    .extract::<String>()
+                    .unwrap(),
+                field("start").extract::<String>().unwrap()
+            );
+
+            let stats = get("stats");
+            assert_eq!(
+                stats.get_item("files").unwrap().extract::<u64>().unwrap(),
+                2
+            );
+            let per_symbol = stats.get_item("symbols").unwrap();
+            assert_eq!(per_symbol.len().unwrap(), 2);
+            let bytes: u64 = stats.get_item("bytes").unwrap().extract().unwrap();
+            let summed: u64 = per_symbol
+                .try_iter()
+                .unwrap()
+                .map(|entry| {
+                    entry
+                        .unwrap()
+                        .get_item("bytes")
+                        .unwrap()
+                        .extract::<u64>()
+                        .unwrap()
+                })
+                .sum();
+            assert_eq!(summed, bytes);
+
+            let gaps: Vec<HashMap<String, Py<PyAny>>> = get("gaps").extract().unwrap();
+            assert_eq!(gaps.len(), 1);
+            assert_eq!(
+                gaps[0]["start"].extract::<String>(py).unwrap(),
+                "2024-02-01T00:00:00+00:00"
+            );
+            assert_eq!(
+                gaps[0]["end"].extract::<String>(py).unwrap(),
+                "2024-02-29T00:00:00+00:00"
+            );
+            assert_eq!(gaps[0]["missing_bars"].extract::<u64>(py).unwrap(), 21);
+
+            assert_eq!(get("purged").extract::<u64>().unwrap(), 1);
+            assert_eq!(
+                get("after_purge").extract::<Vec<String>>().unwrap(),
+                ["MSFT"]
+            );
+            let purged_gaps: Vec<HashMap<String, Py<PyAny>>> =
+                get("purged_gaps").extract().unwrap();
+            assert_eq!(purged_gaps.len(), 1);
+            assert_eq!(
+                purged_gaps[0]["missing_bars"].extract::<u64>(py).unwrap(),
+                23
+            );
+            let stats_after = get("stats_after");
+            assert_eq!(
+                stats_after
+                    .get_item("files")
+                    .unwrap()
+                    .extract::<u64>()
+                    .unwrap(),
+                1
+            );
+        });
+    }
+
+    #[test]
+    fn optimizer_sweep_best_trial_matches_a_direct_rerun() {
+        init_python();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "glowback").unwrap();
+            glowback(py, &module).unwrap();
+            let backtest = sample_config(py, "ma_crossover", &[]);
+            let globals = PyDict::new(py);
+            globals.set_item("glowback", &module).unwrap();
+            globals.set_item("backtest", backtest).unwrap();
+
+            py.run(
+                cr#"
+space = glowback.SearchSpace().add_int("short_period", 3, 5).add_int("long_period", 18, 20)
+config = glowback.OptimizationConfig(space, backtest, max_trials=20, concurrency=3)
+seen = []
+result = glowback.Optimizer(config).run(progress=seen.append)
+trials = result.trials
+best = result.best_parameters
+rerun = glowback.BacktestEngine.from_config(backtest.with_strategy("ma_crossover", best)).run()
+rerun_objective = rerun.metrics_summary["sharpe_ratio"]
+
+def stop(trial):
+    raise ValueError("stop after one trial")
+
+try:
+    glowback.Optimizer(
+        glowback.OptimizationConfig(space, backtest, concurrency=1)
+    ).run(progress=stop)
+    stopped = None
+except ValueError as error:
+    stopped = str(error)
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+
+            let get = |name: &str| globals.get_item(name).unwrap().unwrap();
+            let trials = get("trials");
+            let trials = trials.cast::<PyList>().unwrap();
+            assert_eq!(trials.len(), 9);
+            let mut objectives = Vec::new();
+            for trial in trials.iter() {
+                let trial = trial.cast::<PyDict>().unwrap();
+                let status: String = trial
+                    .get_item("status")
+                    .unwrap()
+                    .unwrap()
+                    .extract()
+                    .unwrap();
+                assert_eq!(status, "Completed", "{trial}");
+                let objective: f64 = trial
+                    .get_item("objective")
+                    .unwrap()
+                    .unwrap()
+                    .extract()
+                    .unwrap();
+                objectives.push(objective);
+            }
+            let mut seen: Vec<usize> = get("seen")
+                .try_iter()
+                .unwrap()
+                .map(|trial| {
+                    trial
+                        .unwrap()
+                        .get_item("trial_number")
+                        .unwrap()
+                        .extract()
+                        .unwrap()
+                })
+                .collect();
+            seen.sort_unstable();
+            assert_eq!(seen, (0..9).collect::<Vec<_>>());
+
+            let result = get("result");
+            let best_objective: f64 = result.getattr("best_objective").unwrap().extract().unwrap();
+            assert_eq!(
+                best_objective,
+                objectives.iter().copied().fold(f64::NEG_INFINITY, f64::max)
+            );
+            let best: HashMap<String, i64> = get("best").extract().unwrap();
+            assert_eq!(best.len(), 2);
+            let rerun_objective: f64 = get("rerun_objective").extract().unwrap();
+            approx_eq(best_objective, rerun_objective, 1e-9);
+
+            let stopped: Option<String> = get("stopped").extract().unwrap();
+            assert_eq!(stopped.as_deref(), Some("stop after one trial"));
+        });
+    }
+
+    fn rust_black_scholes(
+        kind: gb_options::OptionKind,
+        spot: f64,
+        strike: f64,
+        rate: f64,
+        div_yield: f64,
+        vol: f64,
+        tte: f64,
+    ) -> gb_options::PricingResult {
+        let contract = gb_options::OptionContract::equity(
+            Symbol::equity(TEST_SYMBOL),
+            kind,
+            Decimal::from_f64(strike).unwrap(),
+            chrono::Utc::now(),
+        );
+        gb_options::black_scholes_price(
+            &contract,
+            &gb_options::PricingInput {
+                spot,
+                risk_free_rate: rate,
+                volatility: vol,
+                dividend_yield: div_yield,
+                time_to_expiry: tte,
+            },
+        )
+    }
+
+    #[test]
+    fn black_scholes_matches_the_rust_pricer() {
+        use gb_options::OptionKind;
+
+        init_python();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "glowback").unwrap();
+            glowback(py, &module).unwrap();
+            let price = |args: (&str, f64, f64, f64, f64, f64, f64)| -> HashMap<String, f64> {
+                module
+                    .getattr("black_scholes")
+                    .unwrap()
+                    .call1(args)
+                    .unwrap()
+                    .extract()
+                    .unwrap()
+            };
+
+            // The inputs of gb-options' call, put and dividend IV tests.
+            for (kind, name, spot, strike, rate, div_yield, vol, tte) in [
+                (
+                    OptionKind::Call,
+                    "call",
+                    155.0,
+                    150.0,
+                    0.05,
+                    0.0,
+                    0.25,
+                    0.25,
+                ),
+                (OptionKind::Put, "put", 145.0, 150.0, 0.05, 0.0, 0.25, 0.25),
+                (OptionKind::Put, "put", 148.0, 150.0, 0.04, 0.01, 0.30, 0.5),
+            ] {
+                let python = price((name, spot, strike, rate, div_yield, vol, tte));
+                let rust = rust_black_scholes(kind, spot, strike, rate, div_yield, vol, tte);
+                let greeks = &rust.greeks;
+                for (field, expected) in [
+                    ("price", rust.price),
+                    ("delta", greeks.delta),
+                    ("gamma", greeks.gamma),
+                    ("theta", greeks.theta),
+                    ("vega", greeks.vega),
+                    ("rho", greeks.rho),
+                ] {
+                    assert_eq!(python[field], expected.to_f64().unwrap(), "{name} {field}");
+                }
+            }
+
+            // Textbook at-the-money call: S = K = 100, r = 5%, vol = 20%, T = 1.
+            let call = price(("call", 100.0, 100.0, 0.05, 0.0, 0.2, 1.0));
+            approx_eq(call["price"], 10.4506, 1e-3);
+            approx_eq(call["delta"], 0.6368, 1e-3);
+            // Expired options are worth their intrinsic value.
+            let expired = price(("call", 160.0, 150.0, 0.05, 0.0, 0.25, 0.0));
+            assert_eq!(expired["price"], 10.0);
+
+            let implied_vol = module.getattr("implied_vol").unwrap();
+            let put = price(("put", 148.0, 150.0, 0.04, 0.01, 0.30, 0.5));
+            let vol: Option<f64> = implied_vol
+                .call1(("put", put["price"], 148.0, 150.0, 0.04, 0.01, 0.5))
+                .unwrap()
+                .extract()
+                .unwrap();
+            approx_eq(vol.unwrap(), 0.30, 1e-3);
+            let unsolvable: Option<f64> = implied_vol
+                .call1(("call", 0.0, 148.0, 150.0, 0.04, 0.01, 0.5))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(unsolvable, None);
+            let error = module
+                .getattr("black_scholes")
+                .unwrap()
+                .call1(("straddle", 100.0, 100.0, 0.05, 0.0, 0.2, 1.0))
+                .unwrap_err();
+            assert!(error.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+        });
+    }
+
+    #[test]
+    fn array_pricing_matches_scalar_pricing_elementwise() {
+        init_python();
+        Python::attach(|py| {
+            let module = PyModule::new(py, "glowback").unwrap();
+            glowback(py, &module).unwrap();
+            let globals = PyDict::new(py);
+            globals.set_item("glowback", &module).unwrap();
+            py.run(
+                cr#"
+kinds = ["call", "put", "call", "put", "call"]
+strikes = [90.0, 95.0, 100.0, 105.0, 110.0]
+vols = [0.15, 0.2, 0.25, 0.3, 0.35]
+ttes = [0.1, 0.25, 0.5, 1.0, 2.0]
+# Scalars broadcast against the arrays.
+table = glowback.black_scholes_array(kinds, 100.0, strikes, 0.03, 0.01, vols, ttes)
+rows = [
+    glowback.black_scholes(kind, 100.0, strike, 0.03, 0.01, vol, tte)
+    for kind, strike, vol, tte in zip(kinds, strikes, vols, ttes)
+]
+mismatches = [
+    (i, field)
+    for i, row in enumerate(rows)
+    for field, value in row.items()
+    if table[field][i] != value
+]
+recovered = glowback.implied_vol_array(kinds, table["price"], 100.0, strikes, 0.03, 0.01, ttes)
+scalar_vols = [
+    glowback.implied_vol(kind, price, 100.0, strike, 0.03, 0.01, tte)
+    for kind, price, strike, tte in zip(kinds, table["price"], strikes, ttes)
+]
+unsolved = glowback.implied_vol_array("call", [0.0, 5.0], 100.0, 100.0, 0.03, 0.0, 0.5)
+single = glowback.black_scholes_array("call", 100.0, 100.0, 0.05, 0.0, 0.2, 1.0)
+try:
+    glowback.black_scholes_array("call", [100.0, 101.0], strikes, 0.03, 0.0, 0.2, 1.0)
+    mismatched_lengths = None
+except ValueError as error:
+    mismatched_lengths = str(error)
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+
+            let get = |name: &str| globals.get_item(name).unwrap().unwrap();
+            let mismatches: Vec<(usize, String)> = get("mismatches").extract().unwrap();
+            assert!(mismatches.is_empty(), "{mismatches:?}");
+            let recovered: Vec<f64> = get("recovered").extract().unwrap();
+            let scalar_vols: Vec<Option<f64>> = get("scalar_vols").extract().unwrap();
+            assert_eq!(
+                recovered.iter().map(|vol| Some(*vol)).collect::<Vec<_>>(),
+                scalar_vols
+            );
+            for (vol, expected) in recovered.iter().zip([0.15, 0.2, 0.25, 0.3, 0.35]) {
+                approx_eq(*vol, expected, 1e-3);
+            }
+            let unsolved: Vec<f64> = get("unsolved").extract().unwrap();
+            assert!(unsolved[0].is_nan());
+            assert!(!unsolved[1].is_nan());
+            let single: HashMap<String, Vec<f64>> = get("single").extract().unwrap();
+            assert_eq!(single["price"].len(), 1);
+            let mismatched: Option<String> = get("mismatched_lengths").extract().unwrap();
+            assert!(mismatched.unwrap().contains("same length"));
+        });
+    }
+
+    #[test]
+    fn option_contract_mirrors_the_rust_contract() {
+        init_python();
+        Python::attach(|py| {
+            let contract = options::PyOptionContract::new(
+                TEST_SYMBOL,
+                "put",
+                150.0,
+                "2026-06-20T20:00:00Z",
+                "american",
+                100.0,
+            )
+            .unwrap();
+            let expected = gb_options::OptionContract::new(
+                Symbol::equity(TEST_SYMBOL),
+                gb_options::OptionKind::Put,
+                Decimal::from(150),
+                chrono::DateTime::parse_from_rfc3339("2026-06-20T20:00:00Z")
+                    .unwrap()
+                    .with_timezone(&chrono::Utc),
+                gb_options::ExerciseStyle::American,
+                Decimal::from(100),
+            );
+            assert_eq!(contract.inner, expected);
+
+            let contract = Bound::new(py, contract).unwrap();
+            let kind: String = contract.getattr("kind").unwrap().extract().unwrap();
+            assert_eq!(kind, "put");
+            let intrinsic: f64 = contract
+                .call_method1("intrinsic_value", (130.0,))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(intrinsic, 20.0);
+
+            let now = "2025-06-20T20:00:00Z";
+            let priced: HashMap<String, f64> = contract
+                .call_method1("price", (130.0, 0.05, 0.25, now))
+                .unwrap()
+                .extract()
+                .unwrap();
+            let input = gb_options::PricingInput {
+                spot: 130.0,
+                risk_free_rate: 0.05,
+                volatility: 0.25,
+                dividend_yield: 0.0,
+                time_to_expiry: expected.time_to_expiry(
+                    chrono::DateTime::parse_from_rfc3339(now)
+                        .unwrap()
+                        .with_timezone(&chrono::Utc),
+                ),
+            };
+            assert_eq!(
+                priced["price"],
+                gb_options::price(&expected, &input).price.to_f64().unwrap()
+            );
+        });
+    }
+
+    fn glowback_globals(py: Python<'_>) -> Bound<'_, PyDict> {
+        let module = PyModule::new(py, "glowback").unwrap();
+        glowback(py, &module).unwrap();
+        let globals = PyDict::new(py);
+        globals.set_item("glowback", &module).unwrap();
+        globals
+    }
+
+    #[test]
+    fn paper_broker_arithmetic_matches_the_rust_broker_tests() {
+        init_python();
+        Python::attach(|py| {
+            let globals = glowback_globals(py);
+            // The scripts of test_paper_broker_short_then_cover_at_profit and
+            // test_paper_broker_limit_order_pending_then_filled.
+            py.run(
+                cr#"
+def bar(close, day):
+    return glowback.Bar("AAPL", f"2024-01-{day:02d}T16:00:00Z", close, close, close, close)
+
+broker = glowback.PaperBroker(
+    initial_cash=100_000.0, commission_per_share=0.0, slippage_bps=0.0, allow_short_selling=True
+)
+broker.connect()
+broker.process_bar(bar(100.0, 2))
+broker.submit_market_order("AAPL", "sell", 50)
+cash_after_short = broker.cash
+broker.process_bar(bar(90.0, 3))
+short_position = broker.get_positions()[0]
+short_balance = broker.account_balance()
+broker.submit_market_order(glowback.Symbol("AAPL", "NASDAQ", "equity"), "buy", 50)
+covered_positions = broker.get_positions()
+
+limits = glowback.PaperBroker(initial_cash=100_000.0)
+limits.connect()
+limits.process_bar(bar(150.0, 2))
+limit_id = limits.submit_limit_order("AAPL", "buy", 10, 145.0)
+pending_status = limits.order_status(limit_id)
+limits.process_bar(bar(144.0, 3))
+filled_status = limits.order_status(limit_id)
+limit_fills = limits.get_fills()
+
+disconnected = glowback.PaperBroker()
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+            let get = |name: &str| globals.get_item(name).unwrap().unwrap();
+            let broker = get("broker");
+
+            assert_eq!(get("cash_after_short").extract::<f64>().unwrap(), 105_000.0);
+            let position: HashMap<String, Py<PyAny>> = get("short_position").extract().unwrap();
+            let field = |name: &str| position[name].extract::<f64>(py).unwrap();
+            assert_eq!(field("quantity"), -50.0);
+            assert_eq!(field("average_cost"), 100.0);
+            assert_eq!(field("market_value"), -4_500.0);
+            assert_eq!(field("unrealized_pnl"), 500.0);
+            let balance: HashMap<String, f64> = get("short_balance").extract().unwrap();
+            assert_eq!(balance["equity"], 100_500.0);
+
+            assert_eq!(get("covered_positions").len().unwrap(), 0);
+            let realized: f64 = broker.getattr("realized_pnl").unwrap().extract().unwrap();
+            assert_eq!(realized, 500.0);
+            let cash: f64 = broker.getattr("cash").unwrap().extract().unwrap();
+            assert_eq!(cash, 100_500.0);
+            let fills = broker.call_method0("get_fills").unwrap();
+            let sides: Vec<String> = fills
+                .try_iter()
+                .unwrap()
+                .map(|fill| fill.unwrap().get_item("side").unwrap().extract().unwrap())
+                .collect();
+            assert_eq!(sides, ["sell", "buy"]);
+
+            assert_eq!(
+                get("pending_status").extract::<String>().unwrap(),
+                "Submitted"
+            );
+            assert_eq!(get("filled_status").extract::<String>().unwrap(), "Filled");
+            let limit_fills = get("limit_fills");
+            assert_eq!(limit_fills.len().unwrap(), 1);
+            let price: f64 = limit_fills
+                .get_item(0)
+                .unwrap()
+                .get_item("price")
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert!(price <= 145.0, "limit fill at {price}");
+
+            // Selling more than the long-only broker holds is rejected like
+            // any other order; broker errors are raised.
+            let limits = get("limits");
+            let sell = limits
+                .call_method1("submit_market_order", ("AAPL", "sell", 1_000))
+                .unwrap();
+            let status: String = limits
+                .call_method1("order_status", (sell,))
+                .unwrap()
+                .extract()
+                .unwrap();
+            assert_eq!(status, "Rejected");
+            let error = get("disconnected")
+                .call_method1("submit_market_order", ("AAPL", "buy", 1))
+                .err()
+                .unwrap();
+            assert!(error.is_instance_of::<BrokerError>(py));
+        });
+    }
+
+    #[test]
+    fn live_engine_runs_a_python_strategy_in_sandbox() {
+        init_python();
+        Python::attach(|py| {
+            let globals = glowback_globals(py);
+            py.run(
+                cr#"
+class RoundTrip:
+    def __init__(self):
+        self.bars = 0
+        self.fills = []
+
+    def on_market_event(self, bar, context):
+        self.bars += 1
+        if self.bars == 1:
+            return {"action": "buy", "symbol": "AAPL", "quantity": 10}
+        if self.bars == 3:
+            return {"action": "sell", "symbol": "AAPL", "quantity": 10}
+
+    def on_order_event(self, event, context):
+        if event.kind == "filled":
+            self.fills.append((event.side, event.quantity, event.price))
+
+strategy = RoundTrip()
+engine = glowback.LiveEngine(
+    strategy, ["AAPL"], initial_capital=100_000.0, commission_per_share=0.0, slippage_bps=0.0
+)
+engine.start()
+for day, close in enumerate([100.0, 105.0, 110.0], start=2):
+    engine.process_bar(
+        glowback.Bar("AAPL", f"2024-01-{day:02d}T16:00:00Z", close, close, close, close)
+    )
+positions = engine.positions()
+events = engine.drain_events()
+engine.stop()
+
+class Broken:
+    def on_market_event(self, bar, context):
+        raise KeyError("missing signal")
+
+broken = glowback.LiveEngine(Broken(), ["AAPL"])
+broken.start()
+try:
+    broken.process_bar(glowback.Bar("AAPL", "2024-01-02T16:00:00Z", 1.0, 1.0, 1.0, 1.0))
+except glowback.StrategyError as error:
+    failure = error
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+            let get = |name: &str| globals.get_item(name).unwrap().unwrap();
+
+            let fills: Vec<(String, f64, f64)> =
+                get("strategy").getattr("fills").unwrap().extract().unwrap();
+            assert_eq!(
+                fills,
+                [
+                    ("buy".to_string(), 10.0, 100.0),
+                    ("sell".to_string(), 10.0, 110.0)
+                ]
+            );
+            let engine = get("engine");
+            assert_eq!(
+                engine.getattr("cash").unwrap().extract::<f64>().unwrap(),
+                100_100.0
+            );
+            assert!(!engine
+                .getattr("is_running")
+                .unwrap()
+                .extract::<bool>()
+                .unwrap());
+            assert_eq!(get("positions").len().unwrap(), 0);
+
+            let events: Vec<HashMap<String, Py<PyAny>>> = get("events").extract().unwrap();
+            let kinds: Vec<String> = events
+                .iter()
+                .map(|event| event["type"].extract(py).unwrap())
+                .collect();
+            assert_eq!(kinds[0], "Started");
+            let filled: Vec<f64> = events
+                .iter()
+                .filter(|event| event["type"].extract::<String>(py).unwrap() == "OrderFilled")
+                .map(|event| event["price"].extract(py).unwrap())
+                .collect();
+            assert_eq!(filled, [100.0, 110.0]);
+            assert_eq!(
+                kinds
+                    .iter()
+                    .filter(|kind| kind.as_str() == "OrderSubmitted")
+                    .count(),
+                2
+            );
+
+            let failure = get("failure");
+            let message = failure.to_string();
+            assert!(message.contains("Traceback"), "{message}");
+            assert!(message.contains("KeyError"), "{message}");
+            let cause = failure.getattr("__cause__").unwrap();
+            assert!(cause.is_instance_of::<pyo3::exceptions::PyKeyError>());
+        });
+    }
+
+    #[test]
+    fn risk_monitor_splits_a_mixed_portfolio_and_alerts_on_asset_class_limits() {
+        init_python();
+        Python::attach(|py| {
+            let globals = glowback_globals(py);
+            // The scripts of test_snapshot_splits_exposure_by_asset_class_and_currency
+            // and test_asset_class_limit_raises_an_alert.
+            py.run(
+                cr#"
+btc = glowback.Symbol("BTC-USDT", "BINANCE", "crypto")
+broker = glowback.PaperBroker(commission_per_share=0.0, slippage_bps=0.0, allow_short_selling=True)
+broker.connect()
+for symbol, close in [("AAPL", 100.0), ("MSFT", 50.0), (btc, 50_000.0)]:
+    broker.process_bar(glowback.Bar(symbol, "2024-01-02T16:00:00Z", close, close, close, close))
+broker.submit_market_order("AAPL", "buy", 200)
+broker.submit_market_order("MSFT", "sell", 200)
+broker.submit_market_order(btc, "buy", 0.5)
+
+monitor = glowback.RiskMonitor(max_gross_exposure_by_asset_class={"crypto": 0.2})
+snapshot = monitor.update(broker)
+alerts = [a for a in monitor.drain_alerts() if a["kind"] == "AssetClassExposureExceeded"]
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+            let get = |name: &str| globals.get_item(name).unwrap().unwrap();
+
+            let snapshot = get("snapshot");
+            let exposure = |attr: &str| -> HashMap<String, HashMap<String, f64>> {
+                snapshot.getattr(attr).unwrap().extract().unwrap()
+            };
+            let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+            let gross = snapshot
+                .getattr("gross_exposure")
+                .unwrap()
+                .extract::<f64>()
+                .unwrap();
+            let net = snapshot
+                .getattr("net_exposure")
+                .unwrap()
+                .extract::<f64>()
+                .unwrap();
+            assert!(close(gross, 0.55), "{gross}");
+            assert!(close(net, 0.35), "{net}");
+
+            let by_class = exposure("exposure_by_asset_class");
+            assert!(close(by_class["equity"]["gross"], 0.30));
+            assert!(close(by_class["equity"]["net"], 0.10));
+            assert!(close(by_class["crypto"]["net"], 0.25));
+            for split in [by_class, exposure("exposure_by_currency")] {
+                assert!(close(split.values().map(|e| e["gross"]).sum(), gross));
+                assert!(close(split.values().map(|e| e["net"]).sum(), net));
+            }
+
+            let alerts: Vec<HashMap<String, Py<PyAny>>> = get("alerts").extract().unwrap();
+            assert_eq!(alerts.len(), 1);
+            assert_eq!(
+                alerts[0]["asset_class"].extract::<String>(py).unwrap(),
+                "crypto"
+            );
+            assert_eq!(alerts[0]["limit"].extract::<f64>(py).unwrap(), 0.2);
+        });
+    }
+
+    #[test]
+    fn bar_decimal_strings_are_exact_and_lossy_floats_raise_when_strict() {
+        init_python();
+        Python::attach(|py| {
+            let globals = glowback_globals(py);
+            py.run(
+                cr#"
+sats = glowback.Bar(
+    glowback.Symbol("ETH", "BINANCE", "crypto"),
+    "2024-01-02T00:00:00Z",
+    "0.000012345678", "0.000012345679", "0.000012345677", "0.000012345678",
+    "1500.25",
+)
+exact = sats.as_decimal_strings()
+sats_repr = repr(sats)
+precise = glowback.Bar("AAPL", "2024-01-02T16:00:00Z", "67890.12345678901234567", 1.0, 1.0, 1.0)
+lenient_open = precise.open
+"#,
+                Some(&globals),
+                None,
+            )
+            .unwrap();
+            let get = |name: &str| globals.get_item(name).unwrap().unwrap();
+
+            let exact: HashMap<String, String> = get("exact").extract().unwrap();
+            assert_eq!(exact["open"], "0.000012345678");
+            assert_eq!(exact["high"], "0.000012345679");
+            assert_eq!(exact["volume"], "1500.25");
+            assert_eq!(
+                Decimal::from_str_exact(&exact["close"]).unwrap(),
+                Decimal::from_str_exact("0.000012345678").unwrap()
+            );
+            let sats_repr: String = get("sats_repr").extract().unwrap();
+            assert!(sats_repr.contains("0.000012345678"), "{sats_repr}");
+            assert!(
+                (get("lenient_open").extract::<f64>().unwrap() - 67_890.123_456_789).abs() < 1e-6
+            );
+
+            let precise = get("precise");
+            let bad_string = glowback_globals(py);
+            assert!(py
+                .run(
+                    cr#"glowback.Bar("AAPL", "2024-01-02T16:00:00Z", "1.2.3", 1.0, 1.0, 1.0)"#,
+                    Some(&bad_string),
+                    None,
+                )
+                .unwrap_err()
+                .is_instance_of::<pyo3::exceptions::PyValueError>(py));
+
+            set_strict_decimals(true);
+            let strict_open = precise.getattr("open");
+            let strict_close: f64 = precise.getattr("close").unwrap().extract().unwrap();
+            set_strict_decimals(false);
+            assert!(strict_open
+                .unwrap_err()
+                .is_instance_of::<pyo3::exceptions::PyValueError>(py));
+            assert_eq!(strict_close, 1.0);
+        });
+
+        let missing = checked_f64("close", Decimal::ONE, None, false).unwrap_err();
+        let infinite = checked_f64("close", Decimal::ONE, Some(f64::INFINITY), false).unwrap_err();
+        Python::attach(|py| {
+            assert!(missing.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+            assert!(infinite.is_instance_of::<pyo3::exceptions::PyValueError>(py));
+        });
+        let lossy = Decimal::from_str_exact("0.1000000000000000000001").unwrap();
+        assert!(checked_f64("open", lossy, lossy.to_f64(), true).is_err());
+        assert_eq!(
+            checked_f64("open", lossy, lossy.to_f64(), false).unwrap(),
+            0.1
+        );
+        let exact = Decimal::from_str_exact("0.000012345678").unwrap();
+        assert!(checked_f64("open", exact, exact.to_f64(), true).is_ok());
+    }
 }
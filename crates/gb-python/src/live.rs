@@ -0,0 +1,634 @@
+//! Paper trading from Python: a `PaperBroker` driven bar by bar, and a
+//! sandbox `LiveEngine` running a Python strategy against its own paper
+//! broker.
+//!
+//! Both hold their state behind an async mutex and run the broker's async
+//! methods on an embedded runtime with the GIL released. Prices and
+//! quantities cross the boundary as floats; positions, fills and engine
+//! events come back as dicts.
+
+use gb_live::broker::{AccountBalance, Broker, BrokerError as RustBrokerError, BrokerPosition};
+use gb_live::engine::{LiveEngine, LiveEngineConfig, LiveEngineEvent, TradingMode};
+use gb_live::paper::{PaperBroker, PaperBrokerConfig};
+use gb_live::risk::RiskConfig;
+use gb_types::{Fill, MarketEvent, Order, OrderId, Portfolio, Position, Side};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use rust_decimal::Decimal;
+
+use crate::strategy::{strategy_failure_error, PyStrategy, StrategyFailure};
+use crate::{
+    decimal_to_f64, symbol_arg, to_decimal, with_error_details, BrokerError, OwnedRuntime, PyBar,
+    StrategyError,
+};
+
+/// Paper broker settings shared by `PaperBroker` and `LiveEngine`.
+/// `slippage_bps` is in basis points, unlike the Rust config's fraction.
+fn paper_config(
+    initial_cash: f64,
+    commission_per_share: f64,
+    slippage_bps: f64,
+    allow_short_selling: bool,
+    fill_market_orders_immediately: bool,
+) -> PyResult<PaperBrokerConfig> {
+    Ok(PaperBrokerConfig {
+        initial_cash: to_decimal("initial_cash", initial_cash)?,
+        commission_per_share: to_decimal("commission_per_share", commission_per_share)?,
+        slippage_bps: to_decimal("slippage_bps", slippage_bps)? / Decimal::from(10_000),
+        fill_market_orders_immediately,
+        allow_short_selling,
+        ..Default::default()
+    })
+}
+
+fn broker_error(error: RustBrokerError) -> PyErr {
+    with_error_details(
+        BrokerError::new_err(error.to_string()),
+        error.code(),
+        error.is_retryable(),
+    )
+}
+
+fn parse_side(side: &str) -> PyResult<Side> {
+    match side.trim().to_ascii_lowercase().as_str() {
+        "buy" => Ok(Side::Buy),
+        "sell" => Ok(Side::Sell),
+        other => Err(PyValueError::new_err(format!(
+            "Unsupported side: {other}. Use 'buy' or 'sell'"
+        ))),
+    }
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "buy",
+        Side::Sell => "sell",
+    }
+}
+
+fn parse_order_id(order_id: &str) -> PyResult<OrderId> {
+    order_id
+        .parse()
+        .map_err(|e| PyValueError::new_err(format!("invalid order_id '{order_id}': {e}")))
+}
+
+fn positive_quantity(quantity: f64) -> PyResult<Decimal> {
+    let quantity = to_decimal("quantity", quantity)?;
+    if quantity <= Decimal::ZERO {
+        return Err(PyValueError::new_err("quantity must be positive"));
+    }
+    Ok(quantity)
+}
+
+fn position_dict<'py>(py: Python<'py>, position: &BrokerPosition) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("symbol", &position.symbol.symbol)?;
+    dict.set_item("quantity", decimal_to_f64(position.quantity))?;
+    dict.set_item("market_value", decimal_to_f64(position.market_value))?;
+    dict.set_item("average_cost", decimal_to_f64(position.average_cost))?;
+    dict.set_item("unrealized_pnl", decimal_to_f64(position.unrealized_pnl))?;
+    Ok(dict)
+}
+
+fn fill_dict<'py>(py: Python<'py>, fill: &Fill) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    dict.set_item("order_id", fill.order_id.to_string())?;
+    dict.set_item("symbol", &fill.symbol.symbol)?;
+    dict.set_item("side", side_name(fill.side))?;
+    dict.set_item("quantity", decimal_to_f64(fill.quantity))?;
+    dict.set_item("price", decimal_to_f64(fill.price))?;
+    dict.set_item("commission", decimal_to_f64(fill.commission))?;
+    dict.set_item("executed_at", fill.executed_at.to_rfc3339())?;
+    dict.set_item("strategy_id", &fill.strategy_id)?;
+    Ok(dict)
+}
+
+fn positions_list<'py>(
+    py: Python<'py>,
+    positions: &[BrokerPosition],
+) -> PyResult<Bound<'py, PyList>> {
+    let positions = positions
+        .iter()
+        .map(|position| position_dict(py, position))
+        .collect::<PyResult<Vec<_>>>()?;
+    PyList::new(py, positions)
+}
+
+fn fills_list<'py>(py: Python<'py>, fills: &[Fill]) -> PyResult<Bound<'py, PyList>> {
+    let fills = fills
+        .iter()
+        .map(|fill| fill_dict(py, fill))
+        .collect::<PyResult<Vec<_>>>()?;
+    PyList::new(py, fills)
+}
+
+/// The broker's account as a portfolio for risk metrics: its cash, equity
+/// and marked positions.
+fn broker_portfolio(
+    initial_cash: Decimal,
+    balance: &AccountBalance,
+    positions: &[BrokerPosition],
+) -> Portfolio {
+    let mut portfolio = Portfolio::new("paper".to_string(), initial_cash);
+    portfolio.cash = balance.cash;
+    portfolio.total_equity = balance.equity;
+    for broker in positions {
+        let mut position = Position::new(broker.symbol.clone());
+        position.quantity = broker.quantity;
+        position.average_price = broker.average_cost;
+        position.market_value = broker.market_value;
+        position.unrealized_pnl = broker.unrealized_pnl;
+        portfolio.positions.insert(broker.symbol.clone(), position);
+    }
+    portfolio
+}
+
+/// Event fields holding decimals, which serialize as strings.
+const DECIMAL_EVENT_FIELDS: [&str; 9] = [
+    "quantity",
+    "price",
+    "limit_price",
+    "equity",
+    "realized_pnl",
+    "unrealized_pnl",
+    "commissions",
+    "max_drawdown",
+    "cash_delta",
+];
+
+/// An engine event as `{"type": "OrderFilled", ...fields}` with decimal
+/// fields as floats.
+fn event_json(event: &LiveEngineEvent) -> serde_json::Value {
+    let (kind, fields) = match serde_json::to_value(event) {
+        Ok(serde_json::Value::Object(tagged)) => match tagged.into_iter().next() {
+            Some((kind, serde_json::Value::Object(fields))) => (kind, fields),
+            Some((kind, _)) => (kind, serde_json::Map::new()),
+            None => ("Unknown".to_string(), serde_json::Map::new()),
+        },
+        Ok(serde_json::Value::String(kind)) => (kind, serde_json::Map::new()),
+        _ => ("Unknown".to_string(), serde_json::Map::new()),
+    };
+    let mut dict = serde_json::Map::new();
+    dict.insert("type".to_string(), kind.into());
+    for (key, value) in fields {
+        let value = match (&value, DECIMAL_EVENT_FIELDS.contains(&key.as_str())) {
+            (serde_json::Value::String(text), true) => text
+                .parse::<f64>()
+                .map(serde_json::Value::from)
+                .unwrap_or(value),
+            _ => value,
+        };
+        dict.insert(key, value);
+    }
+    serde_json::Value::Object(dict)
+}
+
+/// Python wrapper for `gb_live::paper::PaperBroker`.
+///
+/// Orders fill against the latest bar fed through `process_bar`; limit and
+/// stop orders wait for a bar that reaches their price.
+#[pyclass(name = "PaperBroker")]
+pub(crate) struct PyPaperBroker {
+    inner: tokio::sync::Mutex<PaperBroker>,
+    initial_cash: Decimal,
+    runtime: OwnedRuntime,
+}
+
+#[pymethods]
+impl PyPaperBroker {
+    #[new]
+    #[pyo3(signature = (
+        initial_cash=100_000.0,
+        commission_per_share=0.01,
+        slippage_bps=5.0,
+        allow_short_selling=false,
+        fill_market_orders_immediately=true
+    ))]
+    fn new(
+        initial_cash: f64,
+        commission_per_share: f64,
+        slippage_bps: f64,
+        allow_short_selling: bool,
+        fill_market_orders_immediately: bool,
+    ) -> PyResult<Self> {
+        let config = paper_config(
+            initial_cash,
+            commission_per_share,
+            slippage_bps,
+            allow_short_selling,
+            fill_market_orders_immediately,
+        )?;
+        Ok(Self {
+            initial_cash: config.initial_cash,
+            inner: tokio::sync::Mutex::new(PaperBroker::new(config)),
+            runtime: OwnedRuntime::new()?,
+        })
+    }
+
+    fn connect(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_broker(py, |broker| Box::pin(broker.connect()))
+    }
+
+    fn disconnect(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_broker(py, |broker| Box::pin(broker.disconnect()))
+    }
+
+    #[getter]
+    fn is_connected(&self, py: Python<'_>) -> bool {
+        py.detach(|| self.inner.blocking_lock().connection_status())
+            == gb_live::broker::ConnectionStatus::Connected
+    }
+
+    /// Submit a market order and return its id.
+    fn submit_market_order(
+        &self,
+        py: Python<'_>,
+        symbol: &Bound<'_, PyAny>,
+        side: &str,
+        quantity: f64,
+    ) -> PyResult<String> {
+        let order = Order::market_order(
+            symbol_arg(symbol)?,
+            parse_side(side)?,
+            positive_quantity(quantity)?,
+            "python".to_string(),
+        );
+        self.submit(py, order)
+    }
+
+    /// Submit a limit order and return its id.
+    fn submit_limit_order(
+        &self,
+        py: Python<'_>,
+        symbol: &Bound<'_, PyAny>,
+        side: &str,
+        quantity: f64,
+        limit_price: f64,
+    ) -> PyResult<String> {
+        let order = Order::limit_order(
+            symbol_arg(symbol)?,
+            parse_side(side)?,
+            positive_quantity(quantity)?,
+            to_decimal("limit_price", limit_price)?,
+            "python".to_string(),
+        );
+        self.submit(py, order)
+    }
+
+    fn cancel_order(&self, py: Python<'_>, order_id: &str) -> PyResult<()> {
+        let order_id = parse_order_id(order_id)?;
+        self.with_broker(py, |broker| Box::pin(broker.cancel_order(order_id)))
+    }
+
+    /// Status of an order, e.g. `"Submitted"` or `"Filled"`.
+    fn order_status(&self, py: Python<'_>, order_id: &str) -> PyResult<String> {
+        let order_id = parse_order_id(order_id)?;
+        let status = self.with_broker(py, |broker| {
+            Box::pin(async move { broker.get_order_status(order_id).await })
+        })?;
+        Ok(format!("{:?}", status))
+    }
+
+    /// Update prices from `bar` and fill any working orders it reaches.
+    fn process_bar(&self, py: Python<'_>, bar: &PyBar) -> PyResult<()> {
+        let event = MarketEvent::Bar(bar.inner.clone());
+        self.with_broker(py, |broker| {
+            Box::pin(async move { broker.on_market_event(&event).await })
+        })
+    }
+
+    /// Open positions as dicts with `symbol`, `quantity`, `market_value`,
+    /// `average_cost` and `unrealized_pnl`.
+    fn get_positions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let positions = self.with_broker(py, |broker| Box::pin(broker.get_positions()))?;
+        positions_list(py, &positions)
+    }
+
+    /// Every fill so far, oldest first.
+    fn get_fills<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let fills = py.detach(|| self.inner.blocking_lock().get_fills().to_vec());
+        fills_list(py, &fills)
+    }
+
+    /// `cash`, `buying_power` and `equity`.
+    fn account_balance<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let balance = self.with_broker(py, |broker| Box::pin(broker.get_account_balance()))?;
+        let dict = PyDict::new(py);
+        dict.set_item("cash", decimal_to_f64(balance.cash))?;
+        dict.set_item("buying_power", decimal_to_f64(balance.buying_power))?;
+        dict.set_item("equity", decimal_to_f64(balance.equity))?;
+        Ok(dict)
+    }
+
+    #[getter]
+    fn cash(&self, py: Python<'_>) -> f64 {
+        decimal_to_f64(py.detach(|| self.inner.blocking_lock().cash()))
+    }
+
+    #[getter]
+    fn realized_pnl(&self, py: Python<'_>) -> f64 {
+        decimal_to_f64(py.detach(|| self.inner.blocking_lock().realized_pnl()))
+    }
+
+    /// How orders in `symbol` are sized: quantities are rounded down to
+    /// `quantity_precision` decimals and whole multiples of `lot_size`, and
+    /// rejected below `min_quantity`.
+    fn quantity_rule<'py>(
+        &self,
+        py: Python<'py>,
+        symbol: &Bound<'_, PyAny>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let symbol = symbol_arg(symbol)?;
+        let rule = py.detach(|| {
+            self.inner
+                .blocking_lock()
+                .quantity_policy()
+                .rule_for(&symbol)
+        });
+        let dict = PyDict::new(py);
+        dict.set_item("lot_size", decimal_to_f64(rule.lot_size))?;
+        dict.set_item("min_quantity", decimal_to_f64(rule.min_quantity))?;
+        dict.set_item("quantity_precision", rule.quantity_precision)?;
+        Ok(dict)
+    }
+}
+
+type BrokerFuture<'a, T> =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<T, RustBrokerError>> + Send + 'a>>;
+
+impl PyPaperBroker {
+    /// Run an async broker call on the runtime without the GIL.
+    fn with_broker<T: Send>(
+        &self,
+        py: Python<'_>,
+        call: impl for<'a> FnOnce(&'a mut PaperBroker) -> BrokerFuture<'a, T> + Send,
+    ) -> PyResult<T> {
+        py.detach(|| {
+            self.runtime.block_on(async {
+                let mut broker = self.inner.lock().await;
+                call(&mut broker).await
+            })
+        })
+        .map_err(broker_error)
+    }
+
+    /// The account and positions as a portfolio, for `RiskMonitor`.
+    pub(crate) fn portfolio(&self, py: Python<'_>) -> PyResult<Portfolio> {
+        let initial_cash = self.initial_cash;
+        self.with_broker(py, |broker| {
+            Box::pin(async move {
+                let balance = broker.get_account_balance().await?;
+                let positions = broker.get_positions().await?;
+                Ok(broker_portfolio(initial_cash, &balance, &positions))
+            })
+        })
+    }
+
+    fn submit(&self, py: Python<'_>, order: Order) -> PyResult<String> {
+        let order_id = self.with_broker(py, |broker| {
+            Box::pin(async move { broker.submit_order(order).await })
+        })?;
+        Ok(order_id.to_string())
+    }
+}
+
+/// A sandbox engine and how many of its broker's fills it has applied.
+struct SandboxSession {
+    engine: LiveEngine<PaperBroker, PyStrategy>,
+    delivered_fills: usize,
+}
+
+impl SandboxSession {
+    /// Apply the paper broker's new fills to the engine. Handling a fill
+    /// can place orders that fill at once, so repeat until none are left.
+    async fn deliver_fills(&mut self) -> Result<(), String> {
+        while let Some(fill) = self
+            .engine
+            .broker()
+            .get_fills()
+            .get(self.delivered_fills)
+            .cloned()
+        {
+            self.delivered_fills += 1;
+            self.engine.on_fill(fill).await?;
+        }
+        Ok(())
+    }
+}
+
+/// A `gb_live` engine in sandbox mode running a Python strategy against its
+/// own paper broker.
+///
+/// The strategy is the same kind of object `BacktestEngine.run` accepts.
+/// Feed it bars with `process_bar`; fills are applied to the engine and
+/// reported to the strategy's `on_order_event` before the call returns.
+#[pyclass(name = "LiveEngine")]
+pub(crate) struct PyLiveEngine {
+    session: tokio::sync::Mutex<SandboxSession>,
+    failure: StrategyFailure,
+    runtime: OwnedRuntime,
+}
+
+#[pymethods]
+impl PyLiveEngine {
+    #[new]
+    #[pyo3(signature = (
+        strategy,
+        symbols,
+        initial_capital=100_000.0,
+        commission_per_share=0.01,
+        slippage_bps=5.0,
+        allow_short_selling=false,
+        max_order_notional=100_000.0,
+        max_position_fraction=1.0
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        strategy: &Bound<'_, PyAny>,
+        symbols: Vec<Bound<'_, PyAny>>,
+        initial_capital: f64,
+        commission_per_share: f64,
+        slippage_bps: f64,
+        allow_short_selling: bool,
+        max_order_notional: f64,
+        max_position_fraction: f64,
+    ) -> PyResult<Self> {
+        let failure = StrategyFailure::default();
+        let strategy = PyStrategy::new(strategy, failure.clone())?;
+        let mut strategy_config = gb_types::Strategy::get_config(&strategy).clone();
+        for symbol in &symbols {
+            strategy_config.add_symbol(symbol_arg(symbol)?);
+        }
+
+        let broker = PaperBroker::new(paper_config(
+            initial_capital,
+            commission_per_share,
+            slippage_bps,
+            allow_short_selling,
+            true,
+        )?);
+        let config = LiveEngineConfig {
+            mode: TradingMode::Sandbox,
+            strategy_config,
+            risk_config: RiskConfig {
+                max_order_notional: to_decimal("max_order_notional", max_order_notional)?,
+                limits: gb_types::portfolio::RiskLimits {
+                    position_concentration_limit: to_decimal(
+                        "max_position_fraction",
+                        max_position_fraction,
+                    )?,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            initial_capital: to_decimal("initial_capital", initial_capital)?,
+            calendar: Default::default(),
+            reconnect: Default::default(),
+            submit_retry: Default::default(),
+            throttle: Default::default(),
+            reconciliation: Default::default(),
+            persistence: Default::default(),
+            health: Default::default(),
+            journal: Default::default(),
+            end_of_day: Default::default(),
+            shadow: Default::default(),
+            event_ordering: Default::default(),
+        };
+
+        Ok(Self {
+            session: tokio::sync::Mutex::new(SandboxSession {
+                engine: LiveEngine::new(broker, strategy, config),
+                delivered_fills: 0,
+            }),
+            failure,
+            runtime: OwnedRuntime::new()?,
+        })
+    }
+
+    /// Connect the paper broker and initialize the strategy.
+    fn start(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_session(py, |session| Box::pin(session.engine.start()))
+    }
+
+    #[pyo3(signature = (reason="stopped from Python"))]
+    fn stop(&self, py: Python<'_>, reason: &str) -> PyResult<()> {
+        let reason = reason.to_string();
+        self.with_session(py, |session| {
+            Box::pin(async move { session.engine.stop(&reason).await })
+        })
+    }
+
+    #[getter]
+    fn is_running(&self, py: Python<'_>) -> bool {
+        py.detach(|| self.session.blocking_lock().engine.is_running())
+    }
+
+    /// Feed `bar` to the broker and the strategy, then apply the fills it
+    /// produced.
+    fn process_bar(&self, py: Python<'_>, bar: &PyBar) -> PyResult<()> {
+        let event = MarketEvent::Bar(bar.inner.clone());
+        self.with_session(py, |session| {
+            Box::pin(async move {
+                session.engine.on_market_event(event).await?;
+                session.deliver_fills().await
+            })
+        })
+    }
+
+    /// Call the strategy's `on_day_end` and summarize the session.
+    fn day_end(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_session(py, |session| {
+            Box::pin(async move {
+                session.engine.on_day_end().await?;
+                session.deliver_fills().await
+            })
+        })
+    }
+
+    /// Events emitted since the last call, as dicts with a `type` key
+    /// (`"OrderSubmitted"`, `"OrderFilled"`, ...) and the event's fields.
+    fn drain_events<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let events = py.detach(|| {
+            let events = self.session.blocking_lock().engine.drain_events();
+            serde_json::Value::Array(events.iter().map(event_json).collect()).to_string()
+        });
+        py.import("json")?
+            .call_method1("loads", (events,))?
+            .cast_into::<PyList>()
+            .map_err(PyErr::from)
+    }
+
+    /// The paper broker's open positions.
+    fn positions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let positions = py
+            .detach(|| {
+                self.runtime.block_on(async {
+                    let session = self.session.lock().await;
+                    session.engine.broker().get_positions().await
+                })
+            })
+            .map_err(broker_error)?;
+        positions_list(py, &positions)
+    }
+
+    /// The paper broker's fills, oldest first.
+    fn fills<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let fills = py.detach(|| {
+            self.session
+                .blocking_lock()
+                .engine
+                .broker()
+                .get_fills()
+                .to_vec()
+        });
+        fills_list(py, &fills)
+    }
+
+    #[getter]
+    fn cash(&self, py: Python<'_>) -> f64 {
+        decimal_to_f64(py.detach(|| self.session.blocking_lock().engine.broker().cash()))
+    }
+}
+
+type SessionFuture<'a> =
+    std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), String>> + Send + 'a>>;
+
+impl PyLiveEngine {
+    /// The engine's portfolio, for `RiskMonitor`.
+    pub(crate) fn portfolio(&self, py: Python<'_>) -> Portfolio {
+        py.detach(|| {
+            self.session
+                .blocking_lock()
+                .engine
+                .context()
+                .portfolio
+                .clone()
+        })
+    }
+
+    /// Run an engine call on the runtime without the GIL. An exception
+    /// raised by the strategy is re-raised as `StrategyError`.
+    fn with_session(
+        &self,
+        py: Python<'_>,
+        call: impl for<'a> FnOnce(&'a mut SandboxSession) -> SessionFuture<'a> + Send,
+    ) -> PyResult<()> {
+        let outcome = py.detach(|| {
+            self.runtime.block_on(async {
+                let mut session = self.session.lock().await;
+                call(&mut session).await
+            })
+        });
+        if let Some(error) = strategy_failure_error(py, &self.failure) {
+            return Err(error);
+        }
+        outcome.map_err(|message| {
+            if message.contains("strategy") {
+                StrategyError::new_err(message)
+            } else {
+                BrokerError::new_err(message)
+            }
+        })
+    }
+}
@@ -0,0 +1,414 @@
+//! Parameter sweeps from Python: a search space, the optimization settings
+//! around a `BacktestConfig`, and the optimizer that runs the trials through
+//! the Rust `OptimizationRunner`.
+
+use std::sync::{Arc, Mutex};
+
+use arrow::ipc::writer::StreamWriter;
+use gb_optimizer::{
+    search_strategy, trials_record_batch, ObjectiveDirection, OptimizationConfig,
+    OptimizationRunner, OptimizationStatus, ParameterKind, SearchSpace, Trial,
+};
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyList};
+
+use crate::{BacktestError, ConfigError, OwnedRuntime, PyBacktestConfig};
+
+/// Parameters a sweep varies and the values each may take. `add_*` return an
+/// updated copy so calls can be chained.
+#[pyclass(name = "SearchSpace", skip_from_py_object)]
+#[derive(Clone, Default)]
+pub(crate) struct PySearchSpace {
+    inner: SearchSpace,
+}
+
+impl PySearchSpace {
+    fn with(&self, name: &str, add: impl FnOnce(SearchSpace) -> SearchSpace) -> PyResult<Self> {
+        if self.inner.parameters.iter().any(|param| param.name == name) {
+            return Err(ConfigError::new_err(format!(
+                "parameter '{name}' is already in the search space"
+            )));
+        }
+        Ok(Self {
+            inner: add(self.inner.clone()),
+        })
+    }
+}
+
+#[pymethods]
+impl PySearchSpace {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    /// Integers from `low` to `high`, inclusive.
+    fn add_int(&self, name: &str, low: i64, high: i64) -> PyResult<Self> {
+        if low > high {
+            return Err(ConfigError::new_err(format!(
+                "parameter '{name}': low {low} is above high {high}"
+            )));
+        }
+        self.with(name, |space| space.add_int(name, low, high))
+    }
+
+    /// Floats from `low` to `high`, sampled in log space when `log` is true.
+    #[pyo3(signature = (name, low, high, log=false))]
+    fn add_float(&self, name: &str, low: f64, high: f64, log: bool) -> PyResult<Self> {
+        if low.is_nan() || high.is_nan() || low > high || (log && low <= 0.0) {
+            return Err(ConfigError::new_err(format!(
+                "parameter '{name}': invalid range [{low}, {high}]{}",
+                if log {
+                    " for a log-uniform parameter"
+                } else {
+                    ""
+                }
+            )));
+        }
+        self.with(name, |space| {
+            if log {
+                space.add_log_uniform(name, low, high)
+            } else {
+                space.add_float(name, low, high)
+            }
+        })
+    }
+
+    /// One of `values`, which must be JSON-serializable.
+    fn add_choice(&self, py: Python<'_>, name: &str, values: &Bound<'_, PyList>) -> PyResult<Self> {
+        if values.is_empty() {
+            return Err(ConfigError::new_err(format!(
+                "parameter '{name}' needs at least one choice"
+            )));
+        }
+        let payload: String = py
+            .import("json")?
+            .call_method1("dumps", (values,))?
+            .extract()?;
+        let values: Vec<serde_json::Value> = serde_json::from_str(&payload)
+            .map_err(|error| ConfigError::new_err(format!("parameter '{name}': {error}")))?;
+        self.with(name, |space| space.add_choice(name, values))
+    }
+
+    #[getter]
+    fn names(&self) -> Vec<String> {
+        self.inner
+            .parameters
+            .iter()
+            .map(|param| param.name.clone())
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.inner.parameters.len()
+    }
+
+    fn __repr__(&self) -> String {
+        let params: Vec<String> = self
+            .inner
+            .parameters
+            .iter()
+            .map(|param| match &param.kind {
+                ParameterKind::IntRange { low, high } => {
+                    format!("{}=int[{low}, {high}]", param.name)
+                }
+                ParameterKind::FloatRange { low, high } => {
+                    format!("{}=float[{low}, {high}]", param.name)
+                }
+                ParameterKind::LogUniform { low, high } => {
+                    format!("{}=log[{low}, {high}]", param.name)
+                }
+                ParameterKind::Choice { values } => format!("{}={values:?}", param.name),
+            })
+            .collect();
+        format!("SearchSpace({})", params.join(", "))
+    }
+}
+
+/// A sweep: the search space, the backtest every trial starts from and how
+/// trials are chosen and scored.
+#[pyclass(name = "OptimizationConfig", skip_from_py_object)]
+#[derive(Clone)]
+pub(crate) struct PyOptimizationConfig {
+    inner: OptimizationConfig,
+}
+
+#[pymethods]
+impl PyOptimizationConfig {
+    #[new]
+    #[pyo3(signature = (
+        search_space,
+        backtest,
+        search="grid",
+        max_trials=None,
+        objective="sharpe_ratio",
+        direction="maximize",
+        concurrency=None,
+        grid_steps=None,
+        seed=None,
+        name=None,
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        search_space: &PySearchSpace,
+        backtest: &PyBacktestConfig,
+        search: &str,
+        max_trials: Option<usize>,
+        objective: &str,
+        direction: &str,
+        concurrency: Option<usize>,
+        grid_steps: Option<usize>,
+        seed: Option<u64>,
+        name: Option<&str>,
+    ) -> PyResult<Self> {
+        let space = search_space.inner.clone();
+        if space.parameters.is_empty() {
+            return Err(ConfigError::new_err("the search space has no parameters"));
+        }
+        // Trials build their engine from the serialized config, which has
+        // no way to carry a CSV provider.
+        if backtest.csv_data_path.is_some() {
+            return Err(ConfigError::new_err(
+                "optimization does not support data_source='csv'; use 'sample'",
+            ));
+        }
+        let direction = match direction.trim().to_ascii_lowercase().as_str() {
+            "maximize" | "max" => ObjectiveDirection::Maximize,
+            "minimize" | "min" => ObjectiveDirection::Minimize,
+            other => {
+                return Err(ConfigError::new_err(format!(
+                    "Unsupported direction: {other}. Use 'maximize' or 'minimize'"
+                )))
+            }
+        };
+        if max_trials == Some(0) || concurrency == Some(0) || grid_steps == Some(0) {
+            return Err(ConfigError::new_err(
+                "max_trials, concurrency and grid_steps must be positive",
+            ));
+        }
+        let base_backtest = serde_json::to_value(backtest.to_rust()?).map_err(|error| {
+            ConfigError::new_err(format!("Failed to serialize backtest config: {error}"))
+        })?;
+
+        let mut inner = OptimizationConfig::new(
+            name.unwrap_or(&backtest.name).to_string(),
+            space,
+            search.trim(),
+        )
+        .with_objective(objective, direction)
+        .with_base_backtest(base_backtest);
+        if let Some(max_trials) = max_trials {
+            inner = inner.with_max_trials(max_trials);
+        }
+        if let Some(concurrency) = concurrency {
+            inner = inner.with_concurrency(concurrency);
+        }
+        if let Some(seed) = seed {
+            inner = inner.with_seed(seed);
+        }
+        if let Some(grid_steps) = grid_steps {
+            inner.grid_steps = grid_steps;
+        }
+        search_strategy(&inner, 0).map_err(ConfigError::new_err)?;
+        Ok(Self { inner })
+    }
+
+    #[getter]
+    fn name(&self) -> String {
+        self.inner.name.clone()
+    }
+
+    #[getter]
+    fn search(&self) -> String {
+        self.inner.strategy.clone()
+    }
+
+    #[getter]
+    fn max_trials(&self) -> usize {
+        self.inner.max_trials
+    }
+
+    #[getter]
+    fn objective(&self) -> String {
+        self.inner.objective_metric.clone()
+    }
+
+    #[getter]
+    fn direction(&self) -> &'static str {
+        match self.inner.direction {
+            ObjectiveDirection::Maximize => "maximize",
+            ObjectiveDirection::Minimize => "minimize",
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OptimizationConfig(name='{}', search='{}', max_trials={}, objective='{}', direction='{}')",
+            self.inner.name,
+            self.inner.strategy,
+            self.inner.max_trials,
+            self.inner.objective_metric,
+            self.direction()
+        )
+    }
+}
+
+/// Runs a sweep's trials as backtests, several at a time, with the GIL
+/// released.
+#[pyclass(name = "Optimizer")]
+pub(crate) struct PyOptimizer {
+    config: OptimizationConfig,
+    runtime: OwnedRuntime,
+}
+
+#[pymethods]
+impl PyOptimizer {
+    #[new]
+    fn new(config: &PyOptimizationConfig) -> PyResult<Self> {
+        let runtime = OwnedRuntime::new()?;
+        Ok(Self {
+            config: config.inner.clone(),
+            runtime,
+        })
+    }
+
+    /// Run every trial and return the results. `progress`, if given, is
+    /// called with each finished trial's dict, e.g. to advance a tqdm bar;
+    /// an exception it raises stops the sweep and is re-raised.
+    #[pyo3(signature = (progress=None))]
+    fn run(&self, py: Python<'_>, progress: Option<Py<PyAny>>) -> PyResult<PyOptimizationResult> {
+        let failure: Arc<Mutex<Option<PyErr>>> = Arc::default();
+
+        let mut runner = OptimizationRunner::new();
+        if let Some(progress) = progress {
+            let shutdown = runner.shutdown_token();
+            let failure = failure.clone();
+            runner = runner.with_trial_observer(move |trial, _| {
+                let mut failure = failure.lock().unwrap_or_else(|e| e.into_inner());
+                if failure.is_some() {
+                    return;
+                }
+                let called =
+                    Python::attach(|py| progress.call1(py, (json_to_py(py, &trial_json(trial))?,)));
+                if let Err(error) = called {
+                    *failure = Some(error);
+                    shutdown.cancel();
+                }
+            });
+        }
+
+        let trials = py.detach(|| self.runtime.block_on(runner.run(self.config.clone())));
+        if let Some(error) = failure.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            return Err(error);
+        }
+        let trials = trials
+            .map_err(|error| BacktestError::new_err(format!("Optimization failed: {error}")))?;
+        let status = runner
+            .status()
+            .cloned()
+            .ok_or_else(|| BacktestError::new_err("Optimization produced no status"))?;
+        Ok(PyOptimizationResult { status, trials })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Optimizer(config='{}')", self.config.name)
+    }
+}
+
+/// Trials of a finished sweep and the best one found.
+#[pyclass(name = "OptimizationResult")]
+pub(crate) struct PyOptimizationResult {
+    status: OptimizationStatus,
+    trials: Vec<Trial>,
+}
+
+#[pymethods]
+impl PyOptimizationResult {
+    /// One dict per trial, in submission order.
+    #[getter]
+    fn trials<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let trials = self
+            .trials
+            .iter()
+            .map(|trial| json_to_py(py, &trial_json(trial)))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, trials)
+    }
+
+    /// Parameters of the best full-budget trial, or None if none completed.
+    #[getter]
+    fn best_parameters(&self, py: Python<'_>) -> PyResult<Option<Py<PyAny>>> {
+        self.status
+            .best_trial
+            .as_ref()
+            .map(|best| json_to_py(py, &serde_json::json!(best.parameters)))
+            .transpose()
+    }
+
+    #[getter]
+    fn best_objective(&self) -> Option<f64> {
+        self.status.best_trial.as_ref().map(|best| best.objective)
+    }
+
+    /// "Completed", "Cancelled", "Stopped" or "Failed".
+    #[getter]
+    fn state(&self) -> String {
+        format!("{:?}", self.status.state)
+    }
+
+    /// The trials as a `pyarrow.Table`, one row per trial with `param_*` and
+    /// `metric_*` columns.
+    fn to_arrow<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let batch = trials_record_batch(&self.status, &self.trials)
+            .map_err(pyo3::exceptions::PyRuntimeError::new_err)?;
+        let encode = |error: arrow::error::ArrowError| {
+            pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to encode trials: {error}"))
+        };
+        let mut payload = Vec::new();
+        let mut writer = StreamWriter::try_new(&mut payload, &batch.schema()).map_err(encode)?;
+        writer.write(&batch).map_err(encode)?;
+        writer.finish().map_err(encode)?;
+        drop(writer);
+        py.import("pyarrow.ipc")?
+            .call_method1("open_stream", (PyBytes::new(py, &payload),))?
+            .call_method0("read_all")
+    }
+
+    /// The trials as a pandas `DataFrame`, via `to_arrow()`.
+    fn to_dataframe<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.to_arrow(py)?.call_method0("to_pandas")
+    }
+
+    fn __len__(&self) -> usize {
+        self.trials.len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OptimizationResult(trials={}, state='{:?}', best_objective={:?})",
+            self.trials.len(),
+            self.status.state,
+            self.best_objective()
+        )
+    }
+}
+
+fn trial_json(trial: &Trial) -> serde_json::Value {
+    serde_json::json!({
+        "trial_number": trial.trial_number,
+        "status": format!("{:?}", trial.status),
+        "parameters": trial.parameters,
+        "budget": trial.budget,
+        "objective": trial.result.as_ref().map(|result| result.objective),
+        "metrics": trial.result.as_ref().map(|result| &result.metrics),
+        "duration_seconds": trial.result.as_ref().and_then(|result| result.duration_seconds),
+        "error": trial.error,
+    })
+}
+
+fn json_to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<Py<PyAny>> {
+    Ok(py
+        .import("json")?
+        .call_method1("loads", (value.to_string(),))?
+        .unbind())
+}
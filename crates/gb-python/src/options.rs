@@ -0,0 +1,461 @@
+//! Option pricing from Python: Black-Scholes prices and greeks, implied
+//! volatility, array variants that loop in Rust with the GIL released, and
+//! an `OptionContract` mirroring the Rust type.
+
+use chrono::{DateTime, Utc};
+use gb_options::{
+    black_scholes_price, implied_volatility, ExerciseStyle, OptionContract, OptionKind,
+    PricingInput, PricingResult,
+};
+use num_traits::cast::ToPrimitive;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use crate::{parse_timestamp, to_decimal};
+
+fn parse_kind(kind: &str) -> PyResult<OptionKind> {
+    match kind.trim().to_ascii_lowercase().as_str() {
+        "call" | "c" => Ok(OptionKind::Call),
+        "put" | "p" => Ok(OptionKind::Put),
+        other => Err(PyValueError::new_err(format!(
+            "Unsupported option kind: {other}. Use 'call' or 'put'"
+        ))),
+    }
+}
+
+/// A contract for pricing calls that only need its kind and strike.
+fn quote_contract(kind: OptionKind, strike: f64) -> PyResult<OptionContract> {
+    Ok(OptionContract::equity(
+        gb_types::Symbol::equity("UNDERLYING"),
+        kind,
+        to_decimal("strike", strike)?,
+        DateTime::<Utc>::UNIX_EPOCH,
+    ))
+}
+
+/// Price and greeks as plain floats, in the order `pricing_dict` and the
+/// array variants lay them out.
+const PRICING_FIELDS: [&str; 6] = ["price", "delta", "gamma", "theta", "vega", "rho"];
+
+fn pricing_values(result: &PricingResult) -> [f64; 6] {
+    let greeks = &result.greeks;
+    [
+        result.price,
+        greeks.delta,
+        greeks.gamma,
+        greeks.theta,
+        greeks.vega,
+        greeks.rho,
+    ]
+    .map(|value| value.to_f64().unwrap_or(f64::NAN))
+}
+
+fn pricing_dict<'py>(py: Python<'py>, result: &PricingResult) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (field, value) in PRICING_FIELDS.into_iter().zip(pricing_values(result)) {
+        dict.set_item(field, value)?;
+    }
+    Ok(dict)
+}
+
+/// Black-Scholes price and greeks of a European option. Theta is per
+/// calendar day; vega and rho are per 1% move.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn black_scholes<'py>(
+    py: Python<'py>,
+    kind: &str,
+    spot: f64,
+    strike: f64,
+    rate: f64,
+    div_yield: f64,
+    vol: f64,
+    tte: f64,
+) -> PyResult<Bound<'py, PyDict>> {
+    let contract = quote_contract(parse_kind(kind)?, strike)?;
+    let input = PricingInput {
+        spot,
+        risk_free_rate: rate,
+        volatility: vol,
+        dividend_yield: div_yield,
+        time_to_expiry: tte,
+    };
+    pricing_dict(py, &black_scholes_price(&contract, &input))
+}
+
+/// Black-Scholes implied volatility of a European option quoted at `price`,
+/// or None if the solver does not converge.
+#[pyfunction]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn implied_vol(
+    kind: &str,
+    price: f64,
+    spot: f64,
+    strike: f64,
+    rate: f64,
+    div_yield: f64,
+    tte: f64,
+) -> PyResult<Option<f64>> {
+    let contract = quote_contract(parse_kind(kind)?, strike)?;
+    Ok(implied_volatility(
+        &contract, price, spot, rate, div_yield, tte,
+    ))
+}
+
+/// An array argument, or a scalar repeated to the length of the others.
+enum Column<T> {
+    Scalar(T),
+    Values(Vec<T>),
+}
+
+impl<T: Copy> Column<T> {
+    fn len(&self) -> Option<usize> {
+        match self {
+            Self::Scalar(_) => None,
+            Self::Values(values) => Some(values.len()),
+        }
+    }
+
+    fn get(&self, index: usize) -> T {
+        match self {
+            Self::Scalar(value) => *value,
+            Self::Values(values) => values[index],
+        }
+    }
+}
+
+/// A scalar, a sequence or a numpy array (read through `tolist()`).
+fn column<T: Copy>(
+    value: &Bound<'_, PyAny>,
+    extract: impl Fn(&Bound<'_, PyAny>) -> PyResult<T>,
+) -> PyResult<Column<T>> {
+    if let Ok(scalar) = extract(value) {
+        return Ok(Column::Scalar(scalar));
+    }
+    let values = if value.hasattr("tolist")? {
+        value.call_method0("tolist")?
+    } else {
+        value.clone()
+    };
+    values
+        .try_iter()?
+        .map(|item| extract(&item?))
+        .collect::<PyResult<Vec<_>>>()
+        .map(Column::Values)
+}
+
+fn float_column(value: &Bound<'_, PyAny>) -> PyResult<Column<f64>> {
+    column(value, |item| item.extract::<f64>())
+}
+
+fn kind_column(value: &Bound<'_, PyAny>) -> PyResult<Column<OptionKind>> {
+    column(value, |item| parse_kind(&item.extract::<String>()?))
+}
+
+/// Common length of the array arguments; all scalars price a single row.
+fn broadcast_len(lengths: &[Option<usize>]) -> PyResult<usize> {
+    let mut lengths = lengths.iter().flatten();
+    let Some(&len) = lengths.next() else {
+        return Ok(1);
+    };
+    if lengths.any(|&other| other != len) {
+        return Err(PyValueError::new_err(
+            "array arguments must all have the same length",
+        ));
+    }
+    Ok(len)
+}
+
+/// Convert computed columns to lists, or numpy arrays when `numpy` is true.
+fn output_columns<'py>(
+    py: Python<'py>,
+    columns: Vec<(&str, Vec<f64>)>,
+    numpy: bool,
+    caller: &str,
+) -> PyResult<Vec<(String, Bound<'py, PyAny>)>> {
+    let np = if numpy {
+        Some(py.import("numpy").map_err(|_| {
+            pyo3::exceptions::PyImportError::new_err(format!(
+                "numpy is required for {caller}(numpy=True)"
+            ))
+        })?)
+    } else {
+        None
+    };
+    columns
+        .into_iter()
+        .map(|(name, values)| {
+            let values = match &np {
+                Some(np) => np.call_method1("asarray", (values,))?,
+                None => values.into_pyobject(py)?.into_any(),
+            };
+            Ok((name.to_string(), values))
+        })
+        .collect()
+}
+
+/// `black_scholes` over arrays: each argument is a scalar or a sequence /
+/// numpy array of a common length. Returns a dict of columns keyed like
+/// `black_scholes`. Pricing runs with the GIL released.
+#[pyfunction]
+#[pyo3(signature = (kind, spot, strike, rate, div_yield, vol, tte, numpy=false))]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn black_scholes_array<'py>(
+    py: Python<'py>,
+    kind: &Bound<'py, PyAny>,
+    spot: &Bound<'py, PyAny>,
+    strike: &Bound<'py, PyAny>,
+    rate: &Bound<'py, PyAny>,
+    div_yield: &Bound<'py, PyAny>,
+    vol: &Bound<'py, PyAny>,
+    tte: &Bound<'py, PyAny>,
+    numpy: bool,
+) -> PyResult<Bound<'py, PyDict>> {
+    let kind = kind_column(kind)?;
+    let [spot, strike, rate, div_yield, vol, tte] =
+        [spot, strike, rate, div_yield, vol, tte].map(float_column);
+    let (spot, strike, rate, div_yield, vol, tte) = (spot?, strike?, rate?, div_yield?, vol?, tte?);
+    let len = broadcast_len(&[
+        kind.len(),
+        spot.len(),
+        strike.len(),
+        rate.len(),
+        div_yield.len(),
+        vol.len(),
+        tte.len(),
+    ])?;
+
+    let rows = py.detach(|| {
+        (0..len)
+            .map(|i| {
+                let contract = quote_contract(kind.get(i), strike.get(i))?;
+                let input = PricingInput {
+                    spot: spot.get(i),
+                    risk_free_rate: rate.get(i),
+                    volatility: vol.get(i),
+                    dividend_yield: div_yield.get(i),
+                    time_to_expiry: tte.get(i),
+                };
+                Ok(pricing_values(&black_scholes_price(&contract, &input)))
+            })
+            .collect::<PyResult<Vec<_>>>()
+    })?;
+
+    let columns = PRICING_FIELDS
+        .iter()
+        .enumerate()
+        .map(|(field, name)| (*name, rows.iter().map(|row| row[field]).collect()))
+        .collect();
+    let dict = PyDict::new(py);
+    for (name, values) in output_columns(py, columns, numpy, "black_scholes_array")? {
+        dict.set_item(name, values)?;
+    }
+    Ok(dict)
+}
+
+/// `implied_vol` over arrays, broadcast like `black_scholes_array`. Rows
+/// that do not converge are NaN.
+#[pyfunction]
+#[pyo3(signature = (kind, price, spot, strike, rate, div_yield, tte, numpy=false))]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn implied_vol_array<'py>(
+    py: Python<'py>,
+    kind: &Bound<'py, PyAny>,
+    price: &Bound<'py, PyAny>,
+    spot: &Bound<'py, PyAny>,
+    strike: &Bound<'py, PyAny>,
+    rate: &Bound<'py, PyAny>,
+    div_yield: &Bound<'py, PyAny>,
+    tte: &Bound<'py, PyAny>,
+    numpy: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let kind = kind_column(kind)?;
+    let [price, spot, strike, rate, div_yield, tte] =
+        [price, spot, strike, rate, div_yield, tte].map(float_column);
+    let (price, spot, strike, rate, div_yield, tte) =
+        (price?, spot?, strike?, rate?, div_yield?, tte?);
+    let len = broadcast_len(&[
+        kind.len(),
+        price.len(),
+        spot.len(),
+        strike.len(),
+        rate.len(),
+        div_yield.len(),
+        tte.len(),
+    ])?;
+
+    let vols = py.detach(|| {
+        (0..len)
+            .map(|i| {
+                let contract = quote_contract(kind.get(i), strike.get(i))?;
+                Ok(implied_volatility(
+                    &contract,
+                    price.get(i),
+                    spot.get(i),
+                    rate.get(i),
+                    div_yield.get(i),
+                    tte.get(i),
+                )
+                .unwrap_or(f64::NAN))
+            })
+            .collect::<PyResult<Vec<_>>>()
+    })?;
+
+    let mut columns = output_columns(py, vec![("vol", vols)], numpy, "implied_vol_array")?;
+    Ok(columns.remove(0).1)
+}
+
+fn parse_exercise_style(style: &str) -> PyResult<ExerciseStyle> {
+    match style.trim().to_ascii_lowercase().as_str() {
+        "european" => Ok(ExerciseStyle::European),
+        "american" => Ok(ExerciseStyle::American),
+        other => Err(PyValueError::new_err(format!(
+            "Unsupported exercise style: {other}. Use 'european' or 'american'"
+        ))),
+    }
+}
+
+/// Python mirror of `gb_options::OptionContract`.
+#[pyclass(name = "OptionContract", frozen, skip_from_py_object)]
+#[derive(Clone)]
+pub(crate) struct PyOptionContract {
+    pub(crate) inner: OptionContract,
+}
+
+#[pymethods]
+impl PyOptionContract {
+    #[new]
+    #[pyo3(signature = (underlying, kind, strike, expiration, exercise_style="european", multiplier=100.0))]
+    pub(crate) fn new(
+        underlying: &str,
+        kind: &str,
+        strike: f64,
+        expiration: &str,
+        exercise_style: &str,
+        multiplier: f64,
+    ) -> PyResult<Self> {
+        Ok(Self {
+            inner: OptionContract::new(
+                gb_types::Symbol::equity(underlying),
+                parse_kind(kind)?,
+                to_decimal("strike", strike)?,
+                parse_timestamp("expiration", expiration)?,
+                parse_exercise_style(exercise_style)?,
+                to_decimal("multiplier", multiplier)?,
+            ),
+        })
+    }
+
+    #[getter]
+    fn underlying(&self) -> String {
+        self.inner.underlying.symbol.clone()
+    }
+
+    #[getter]
+    fn kind(&self) -> String {
+        self.inner.kind.to_string().to_ascii_lowercase()
+    }
+
+    #[getter]
+    fn strike(&self) -> f64 {
+        self.inner.strike.to_f64().unwrap_or(f64::NAN)
+    }
+
+    #[getter]
+    fn expiration(&self) -> String {
+        self.inner.expiration.to_rfc3339()
+    }
+
+    #[getter]
+    fn exercise_style(&self) -> String {
+        self.inner.exercise_style.to_string().to_ascii_lowercase()
+    }
+
+    #[getter]
+    fn multiplier(&self) -> f64 {
+        self.inner.multiplier.to_f64().unwrap_or(f64::NAN)
+    }
+
+    /// Years from `now` to expiration (ACT/365), 0 once expired.
+    fn time_to_expiry(&self, now: &str) -> PyResult<f64> {
+        Ok(self.inner.time_to_expiry(parse_timestamp("now", now)?))
+    }
+
+    fn is_expired(&self, now: &str) -> PyResult<bool> {
+        Ok(self.inner.is_expired(parse_timestamp("now", now)?))
+    }
+
+    fn intrinsic_value(&self, spot: f64) -> PyResult<f64> {
+        let value = self.inner.intrinsic_value(to_decimal("spot", spot)?);
+        Ok(value.to_f64().unwrap_or(f64::NAN))
+    }
+
+    fn is_itm(&self, spot: f64) -> PyResult<bool> {
+        Ok(self.inner.is_itm(to_decimal("spot", spot)?))
+    }
+
+    /// Price and greeks at `now`, with the model matching the exercise
+    /// style: Black-Scholes for European, a binomial tree for American.
+    #[pyo3(signature = (spot, rate, vol, now, div_yield=0.0))]
+    fn price<'py>(
+        &self,
+        py: Python<'py>,
+        spot: f64,
+        rate: f64,
+        vol: f64,
+        now: &str,
+        div_yield: f64,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let input = PricingInput {
+            spot,
+            risk_free_rate: rate,
+            volatility: vol,
+            dividend_yield: div_yield,
+            time_to_expiry: self.time_to_expiry(now)?,
+        };
+        pricing_dict(py, &gb_options::price(&self.inner, &input))
+    }
+
+    /// Black-Scholes implied volatility at `now`, or None if the solver does
+    /// not converge.
+    #[pyo3(signature = (price, spot, rate, now, div_yield=0.0))]
+    fn implied_vol(
+        &self,
+        price: f64,
+        spot: f64,
+        rate: f64,
+        now: &str,
+        div_yield: f64,
+    ) -> PyResult<Option<f64>> {
+        let tte = self.time_to_expiry(now)?;
+        Ok(implied_volatility(
+            &self.inner,
+            price,
+            spot,
+            rate,
+            div_yield,
+            tte,
+        ))
+    }
+
+    fn __eq__(&self, other: &Self) -> bool {
+        self.inner == other.inner
+    }
+
+    fn __str__(&self) -> String {
+        self.inner.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OptionContract(underlying='{}', kind='{}', strike={}, expiration='{}', exercise_style='{}', multiplier={})",
+            self.underlying(),
+            self.kind(),
+            self.inner.strike,
+            self.expiration(),
+            self.exercise_style(),
+            self.inner.multiplier
+        )
+    }
+}
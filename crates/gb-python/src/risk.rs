@@ -0,0 +1,288 @@
+//! Portfolio risk from Python: a `RiskMonitor` that snapshots the portfolio
+//! of a `PaperBroker` or sandbox `LiveEngine` and checks it against its
+//! limits.
+//!
+//! Exposures are fractions of equity. The per-asset-class and per-currency
+//! splits come back as `{"equity": {"gross": ..., "net": ...}, ...}` dicts,
+//! and alerts as dicts with a `kind` key and the alert's fields.
+
+use std::collections::HashMap;
+
+use crossbeam_channel::Receiver;
+use gb_risk::alerts::RiskAlert;
+use gb_risk::metrics::{Exposure, PortfolioRiskSnapshot};
+use gb_risk::monitor::{RiskMonitor, RiskMonitorConfig};
+use gb_types::AssetClass;
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+
+use crate::live::{PyLiveEngine, PyPaperBroker};
+use crate::{decimal_to_f64, to_decimal};
+
+/// Asset class names as `Symbol` takes them.
+const ASSET_CLASSES: [(&str, AssetClass); 6] = [
+    ("equity", AssetClass::Equity),
+    ("crypto", AssetClass::Crypto),
+    ("forex", AssetClass::Forex),
+    ("commodity", AssetClass::Commodity),
+    ("bond", AssetClass::Bond),
+    ("option", AssetClass::Option),
+];
+
+fn asset_class_name(asset_class: AssetClass) -> &'static str {
+    ASSET_CLASSES
+        .iter()
+        .find(|(_, class)| *class == asset_class)
+        .map_or("equity", |(name, _)| name)
+}
+
+fn parse_asset_class(name: &str) -> PyResult<AssetClass> {
+    let lowered = name.trim().to_ascii_lowercase();
+    ASSET_CLASSES
+        .iter()
+        .find(|(known, _)| *known == lowered)
+        .map(|(_, class)| *class)
+        .ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "Unsupported asset class: {name}. Use one of equity, crypto, forex, \
+                 commodity, bond or option"
+            ))
+        })
+}
+
+fn exposures_dict<'py, K>(
+    py: Python<'py>,
+    exposures: &HashMap<K, Exposure>,
+    key: impl Fn(&K) -> &str,
+) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    for (slice, exposure) in exposures {
+        let entry = PyDict::new(py);
+        entry.set_item("gross", decimal_to_f64(exposure.gross))?;
+        entry.set_item("net", decimal_to_f64(exposure.net))?;
+        dict.set_item(key(slice), entry)?;
+    }
+    Ok(dict)
+}
+
+/// Alert fields holding decimals, which serialize as strings.
+const DECIMAL_ALERT_FIELDS: [&str; 8] = [
+    "current_loss_pct",
+    "current_drawdown_pct",
+    "weight_pct",
+    "current_leverage",
+    "var_pct",
+    "gross_exposure",
+    "limit_pct",
+    "limit",
+];
+
+/// An alert as `{"kind": "VarExceeded", "severity": "Critical", "message":
+/// ..., ...fields}` with decimal fields as floats and asset classes by name.
+fn alert_dict<'py>(py: Python<'py>, alert: &RiskAlert) -> PyResult<Bound<'py, PyDict>> {
+    let dict = PyDict::new(py);
+    let (kind, fields) = match serde_json::to_value(&alert.kind) {
+        Ok(serde_json::Value::Object(tagged)) => match tagged.into_iter().next() {
+            Some((kind, serde_json::Value::Object(fields))) => (kind, fields),
+            Some((kind, _)) => (kind, serde_json::Map::new()),
+            None => ("Unknown".to_string(), serde_json::Map::new()),
+        },
+        _ => ("Unknown".to_string(), serde_json::Map::new()),
+    };
+    dict.set_item("kind", kind)?;
+    dict.set_item("severity", format!("{:?}", alert.severity))?;
+    dict.set_item("message", &alert.message)?;
+    dict.set_item("timestamp", alert.timestamp.to_rfc3339())?;
+    for (key, value) in fields {
+        match value {
+            serde_json::Value::String(text) if DECIMAL_ALERT_FIELDS.contains(&key.as_str()) => {
+                dict.set_item(key, text.parse::<f64>().unwrap_or(f64::NAN))?
+            }
+            serde_json::Value::String(text) if key == "asset_class" => {
+                dict.set_item(key, text.to_ascii_lowercase())?
+            }
+            serde_json::Value::String(text) => dict.set_item(key, text)?,
+            other => dict.set_item(key, other.to_string())?,
+        }
+    }
+    Ok(dict)
+}
+
+/// Python wrapper for `gb_risk::metrics::PortfolioRiskSnapshot`.
+#[pyclass(name = "PortfolioRiskSnapshot", frozen)]
+pub(crate) struct PyPortfolioRiskSnapshot {
+    inner: PortfolioRiskSnapshot,
+}
+
+#[pymethods]
+impl PyPortfolioRiskSnapshot {
+    #[getter]
+    fn timestamp(&self) -> String {
+        self.inner.timestamp.to_rfc3339()
+    }
+
+    /// Sum of absolute position weights.
+    #[getter]
+    fn gross_exposure(&self) -> f64 {
+        decimal_to_f64(self.inner.gross_exposure)
+    }
+
+    /// Sum of signed position weights (long – short).
+    #[getter]
+    fn net_exposure(&self) -> f64 {
+        decimal_to_f64(self.inner.net_exposure)
+    }
+
+    #[getter]
+    fn leverage(&self) -> f64 {
+        decimal_to_f64(self.inner.leverage)
+    }
+
+    #[getter]
+    fn num_positions(&self) -> usize {
+        self.inner.num_positions
+    }
+
+    /// `{asset_class: {"gross": ..., "net": ...}}` over the held positions.
+    #[getter]
+    fn exposure_by_asset_class<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        exposures_dict(py, &self.inner.exposure_by_asset_class, |asset_class| {
+            asset_class_name(*asset_class)
+        })
+    }
+
+    /// `{currency: {"gross": ..., "net": ...}}` by each position's quote
+    /// currency.
+    #[getter]
+    fn exposure_by_currency<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        exposures_dict(py, &self.inner.exposure_by_currency, String::as_str)
+    }
+
+    #[getter]
+    fn current_drawdown(&self) -> f64 {
+        decimal_to_f64(self.inner.current_drawdown)
+    }
+
+    #[getter]
+    fn max_drawdown(&self) -> f64 {
+        decimal_to_f64(self.inner.max_drawdown)
+    }
+
+    /// 1-day 95% VaR, or `None` without enough return history.
+    #[getter]
+    fn var_95(&self) -> Option<f64> {
+        self.inner.var_95.map(decimal_to_f64)
+    }
+
+    #[getter]
+    fn cvar_95(&self) -> Option<f64> {
+        self.inner.cvar_95.map(decimal_to_f64)
+    }
+
+    #[getter]
+    fn daily_pnl_pct(&self) -> f64 {
+        decimal_to_f64(self.inner.daily_pnl_pct)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "PortfolioRiskSnapshot(gross_exposure={}, net_exposure={}, num_positions={})",
+            self.inner.gross_exposure, self.inner.net_exposure, self.inner.num_positions
+        )
+    }
+}
+
+/// Python wrapper for `gb_risk::monitor::RiskMonitor`.
+///
+/// `max_gross_exposure_by_asset_class` caps the gross exposure of each
+/// listed asset class, e.g. `{"crypto": 0.25}`; classes not listed are
+/// unlimited. Alerts raised by `update` queue until `drain_alerts`.
+#[pyclass(name = "RiskMonitor")]
+pub(crate) struct PyRiskMonitor {
+    inner: RiskMonitor,
+    alerts: Receiver<RiskAlert>,
+}
+
+#[pymethods]
+impl PyRiskMonitor {
+    #[new]
+    #[pyo3(signature = (
+        max_gross_exposure=Some(3.0),
+        max_var_95=Some(0.05),
+        max_gross_exposure_by_asset_class=None,
+        warning_threshold_pct=0.8
+    ))]
+    fn new(
+        max_gross_exposure: Option<f64>,
+        max_var_95: Option<f64>,
+        max_gross_exposure_by_asset_class: Option<HashMap<String, f64>>,
+        warning_threshold_pct: f64,
+    ) -> PyResult<Self> {
+        let mut asset_class_limits = HashMap::new();
+        for (name, limit) in max_gross_exposure_by_asset_class.unwrap_or_default() {
+            asset_class_limits.insert(
+                parse_asset_class(&name)?,
+                to_decimal("max_gross_exposure_by_asset_class", limit)?,
+            );
+        }
+        let config = RiskMonitorConfig {
+            warning_threshold_pct: to_decimal("warning_threshold_pct", warning_threshold_pct)?,
+            max_gross_exposure: max_gross_exposure
+                .map(|limit| to_decimal("max_gross_exposure", limit))
+                .transpose()?,
+            max_var_95: max_var_95
+                .map(|limit| to_decimal("max_var_95", limit))
+                .transpose()?,
+            max_gross_exposure_by_asset_class: asset_class_limits,
+            ..Default::default()
+        };
+        let (sender, alerts) = crossbeam_channel::unbounded();
+        Ok(Self {
+            inner: RiskMonitor::new(config, sender),
+            alerts,
+        })
+    }
+
+    /// The configured gross exposure limit of each asset class.
+    #[getter]
+    fn max_gross_exposure_by_asset_class(&self) -> HashMap<&'static str, f64> {
+        self.inner
+            .config()
+            .max_gross_exposure_by_asset_class
+            .iter()
+            .map(|(asset_class, limit)| (asset_class_name(*asset_class), decimal_to_f64(*limit)))
+            .collect()
+    }
+
+    /// Snapshot the portfolio of `source`, a `PaperBroker` or `LiveEngine`,
+    /// and check it against the limits.
+    fn update(
+        &mut self,
+        py: Python<'_>,
+        source: &Bound<'_, PyAny>,
+    ) -> PyResult<PyPortfolioRiskSnapshot> {
+        let portfolio = if let Ok(broker) = source.cast::<PyPaperBroker>() {
+            broker.borrow().portfolio(py)?
+        } else if let Ok(engine) = source.cast::<PyLiveEngine>() {
+            engine.borrow().portfolio(py)
+        } else {
+            return Err(PyTypeError::new_err(
+                "update takes a PaperBroker or a LiveEngine",
+            ));
+        };
+        Ok(PyPortfolioRiskSnapshot {
+            inner: self.inner.update(&portfolio),
+        })
+    }
+
+    /// Alerts raised since the last call, oldest first.
+    fn drain_alerts<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        let alerts = self
+            .alerts
+            .try_iter()
+            .map(|alert| alert_dict(py, &alert))
+            .collect::<PyResult<Vec<_>>>()?;
+        PyList::new(py, alerts)
+    }
+}
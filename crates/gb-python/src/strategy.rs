@@ -0,0 +1,511 @@
+//! Python-defined strategies run by the Rust engine.
+//!
+//! [`PyStrategy`] wraps any Python object and implements the engine's
+//! [`Strategy`] trait by calling the object's optional `on_market_event(bar,
+//! context)`, `on_order_event(event, context)` and `on_day_end(context)`
+//! methods. Each returns `None`, one action dict or a list of them:
+//!
+//! * `{"action": "buy" | "sell", "symbol": ..., "quantity": ...}`, with an
+//!   optional `limit_price` or `stop_price`;
+//! * `{"action": "cancel", "order_id": ...}`;
+//! * `{"action": "log", "message": ..., "level": "info"}`.
+//!
+//! The engine runs with the GIL released; it is acquired only around each
+//! callback. The first exception a callback raises is recorded with its
+//! traceback and cancels the run.
+
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use rust_decimal::prelude::FromPrimitive;
+use rust_decimal::Decimal;
+
+use gb_engine::CancellationHandle;
+use gb_types::{
+    LogLevel, MarketEvent, Order, OrderEvent, OrderId, Side, Strategy, StrategyAction,
+    StrategyConfig, StrategyContext, StrategyMetrics, Symbol,
+};
+
+use crate::{decimal_to_f64, PyBar, StrategyError};
+
+/// The exception that stopped a Python strategy: the formatted traceback
+/// and the original error.
+pub(crate) type StrategyFailure = Arc<Mutex<Option<(String, PyErr)>>>;
+
+/// Read-only snapshot of the engine's `StrategyContext`.
+#[pyclass(name = "StrategyContext", frozen)]
+pub(crate) struct PyStrategyContext {
+    timestamp: DateTime<Utc>,
+    cash: f64,
+    total_equity: f64,
+    positions: Vec<(String, f64)>,
+}
+
+impl PyStrategyContext {
+    fn from_context(context: &StrategyContext) -> Self {
+        Self {
+            timestamp: context.current_time,
+            cash: decimal_to_f64(context.portfolio.cash),
+            total_equity: decimal_to_f64(context.portfolio.total_equity),
+            positions: context
+                .portfolio
+                .positions
+                .iter()
+                .filter(|(_, position)| !position.quantity.is_zero())
+                .map(|(symbol, position)| {
+                    (symbol.symbol.clone(), decimal_to_f64(position.quantity))
+                })
+                .collect(),
+        }
+    }
+}
+
+#[pymethods]
+impl PyStrategyContext {
+    #[getter]
+    fn timestamp(&self) -> String {
+        self.timestamp.to_rfc3339()
+    }
+
+    #[getter]
+    fn cash(&self) -> f64 {
+        self.cash
+    }
+
+    #[getter]
+    fn total_equity(&self) -> f64 {
+        self.total_equity
+    }
+
+    /// Open positions as `{symbol: quantity}`.
+    #[getter]
+    fn positions<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let positions = PyDict::new(py);
+        for (symbol, quantity) in &self.positions {
+            positions.set_item(symbol, quantity)?;
+        }
+        Ok(positions)
+    }
+
+    /// Quantity held in `symbol` (0 when flat).
+    fn position(&self, symbol: &str) -> f64 {
+        self.positions
+            .iter()
+            .find(|(held, _)| held == symbol)
+            .map_or(0.0, |(_, quantity)| *quantity)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "StrategyContext(timestamp='{}', cash={}, total_equity={}, positions={})",
+            self.timestamp.to_rfc3339(),
+            self.cash,
+            self.total_equity,
+            self.positions.len()
+        )
+    }
+}
+
+/// Python wrapper for an order lifecycle event.
+#[pyclass(name = "OrderEvent", frozen)]
+pub(crate) struct PyOrderEvent {
+    inner: OrderEvent,
+}
+
+#[pymethods]
+impl PyOrderEvent {
+    /// `submitted`, `accepted`, `partially_filled`, `filled`, `canceled`,
+    /// `rejected` or `expired`.
+    #[getter]
+    fn kind(&self) -> &'static str {
+        match self.inner {
+            OrderEvent::OrderSubmitted(_) => "submitted",
+            OrderEvent::OrderAccepted { .. } => "accepted",
+            OrderEvent::OrderPartiallyFilled { .. } => "partially_filled",
+            OrderEvent::OrderFilled { .. } => "filled",
+            OrderEvent::OrderCanceled { .. } => "canceled",
+            OrderEvent::OrderRejected { .. } => "rejected",
+            OrderEvent::OrderExpired { .. } => "expired",
+        }
+    }
+
+    #[getter]
+    fn order_id(&self) -> String {
+        self.inner.order_id().to_string()
+    }
+
+    #[getter]
+    fn symbol(&self) -> Option<String> {
+        match &self.inner {
+            OrderEvent::OrderSubmitted(order) => Some(order.symbol.symbol.clone()),
+            event => event.fill().map(|fill| fill.symbol.symbol.clone()),
+        }
+    }
+
+    /// `buy` or `sell`, for submissions and fills.
+    #[getter]
+    fn side(&self) -> Option<&'static str> {
+        let side = match &self.inner {
+            OrderEvent::OrderSubmitted(order) => order.side,
+            event => event.fill()?.side,
+        };
+        Some(match side {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        })
+    }
+
+    #[getter]
+    fn quantity(&self) -> Option<f64> {
+        match &self.inner {
+            OrderEvent::OrderSubmitted(order) => Some(decimal_to_f64(order.quantity)),
+            event => event.fill().map(|fill| decimal_to_f64(fill.quantity)),
+        }
+    }
+
+    /// Fill price, for fills.
+    #[getter]
+    fn price(&self) -> Option<f64> {
+        self.inner.fill().map(|fill| decimal_to_f64(fill.price))
+    }
+
+    #[getter]
+    fn commission(&self) -> Option<f64> {
+        self.inner
+            .fill()
+            .map(|fill| decimal_to_f64(fill.commission))
+    }
+
+    /// Why the order was canceled, rejected or expired.
+    #[getter]
+    fn reason(&self) -> Option<String> {
+        match &self.inner {
+            OrderEvent::OrderCanceled { reason, .. }
+            | OrderEvent::OrderRejected { reason, .. }
+            | OrderEvent::OrderExpired { reason, .. } => Some(reason.clone()),
+            _ => None,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "OrderEvent(kind='{}', order_id='{}')",
+            self.kind(),
+            self.inner.order_id()
+        )
+    }
+}
+
+/// Engine strategy backed by a Python object.
+pub(crate) struct PyStrategy {
+    on_market_event: Option<Py<PyAny>>,
+    on_order_event: Option<Py<PyAny>>,
+    on_day_end: Option<Py<PyAny>>,
+    config: StrategyConfig,
+    /// Context converted for the current timestamp, reused by every bar the
+    /// engine delivers at that time.
+    market_context: Option<(DateTime<Utc>, Py<PyStrategyContext>)>,
+    failure: StrategyFailure,
+    cancellation: CancellationHandle,
+}
+
+impl PyStrategy {
+    /// Wrap `object`, looking its callbacks up once. The first exception it
+    /// raises is stored in `failure`.
+    pub(crate) fn new(object: &Bound<'_, PyAny>, failure: StrategyFailure) -> PyResult<Self> {
+        let callback = |name: &str| -> PyResult<Option<Py<PyAny>>> {
+            if !object.hasattr(name)? {
+                return Ok(None);
+            }
+            let method = object.getattr(name)?;
+            if !method.is_callable() {
+                return Err(pyo3::exceptions::PyTypeError::new_err(format!(
+                    "strategy attribute '{}' is not callable",
+                    name
+                )));
+            }
+            Ok(Some(method.unbind()))
+        };
+        let on_market_event = callback("on_market_event")?;
+        let on_order_event = callback("on_order_event")?;
+        let on_day_end = callback("on_day_end")?;
+        if on_market_event.is_none() && on_order_event.is_none() && on_day_end.is_none() {
+            return Err(pyo3::exceptions::PyTypeError::new_err(
+                "strategy must define on_market_event, on_order_event or on_day_end",
+            ));
+        }
+
+        let name = object.get_type().name()?.to_string();
+        Ok(Self {
+            on_market_event,
+            on_order_event,
+            on_day_end,
+            config: StrategyConfig::new(name.clone(), name),
+            market_context: None,
+            failure,
+            cancellation: CancellationHandle::new(),
+        })
+    }
+
+    /// Cancel the run through `cancellation` when the strategy fails.
+    pub(crate) fn with_cancellation(mut self, cancellation: CancellationHandle) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    fn failed(&self) -> bool {
+        self.failure
+            .lock()
+            .map_or(true, |failure| failure.is_some())
+    }
+
+    /// Call `method` with the GIL held and convert what it returns. An
+    /// exception, or a result that is not a valid action list, is recorded
+    /// and cancels the run.
+    fn call(
+        &mut self,
+        callback: &str,
+        call: impl for<'py> FnOnce(Python<'py>, &mut Self) -> PyResult<Bound<'py, PyAny>>,
+    ) -> Result<Vec<StrategyAction>, String> {
+        if self.failed() {
+            return Ok(Vec::new());
+        }
+        Python::attach(|py| {
+            let outcome = call(py, self)
+                .and_then(|returned| actions_from_python(&returned, &self.config.strategy_id));
+            outcome.map_err(|error| {
+                let traceback = error
+                    .traceback(py)
+                    .and_then(|traceback| traceback.format().ok())
+                    .unwrap_or_default();
+                let message = format!(
+                    "Python strategy {} failed in {}:\n{}{}",
+                    self.config.name, callback, traceback, error
+                );
+                if let Ok(mut failure) = self.failure.lock() {
+                    failure.get_or_insert((message.clone(), error));
+                }
+                self.cancellation.cancel();
+                message
+            })
+        })
+    }
+
+    fn context<'py>(
+        &self,
+        py: Python<'py>,
+        context: &StrategyContext,
+    ) -> PyResult<Bound<'py, PyStrategyContext>> {
+        Bound::new(py, PyStrategyContext::from_context(context))
+    }
+}
+
+impl Strategy for PyStrategy {
+    fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+        let name = self.config.name.clone();
+        self.config = config.clone();
+        self.config.strategy_id = name.clone();
+        self.config.name = name;
+        Ok(())
+    }
+
+    fn on_market_event(
+        &mut self,
+        event: &MarketEvent,
+        context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        let MarketEvent::Bar(bar) = event else {
+            return Ok(Vec::new());
+        };
+        if self.on_market_event.is_none() {
+            return Ok(Vec::new());
+        }
+        self.call("on_market_event", |py, strategy| {
+            let py_context = match &strategy.market_context {
+                Some((time, cached)) if *time == context.current_time => cached.clone_ref(py),
+                _ => {
+                    let fresh = strategy.context(py, context)?.unbind();
+                    strategy.market_context = Some((context.current_time, fresh.clone_ref(py)));
+                    fresh
+                }
+            };
+            let bar = PyBar { inner: bar.clone() };
+            strategy
+                .on_market_event
+                .as_ref()
+                .expect("checked above")
+                .bind(py)
+                .call1((bar, py_context))
+        })
+    }
+
+    fn on_order_event(
+        &mut self,
+        event: &OrderEvent,
+        context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        // Fills change the portfolio, so later bars need a fresh context.
+        self.market_context = None;
+        if self.on_order_event.is_none() {
+            return Ok(Vec::new());
+        }
+        self.call("on_order_event", |py, strategy| {
+            let event = PyOrderEvent {
+                inner: event.clone(),
+            };
+            let py_context = strategy.context(py, context)?;
+            strategy
+                .on_order_event
+                .as_ref()
+                .expect("checked above")
+                .bind(py)
+                .call1((event, py_context))
+        })
+    }
+
+    fn on_day_end(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        self.market_context = None;
+        if self.on_day_end.is_none() {
+            return Ok(Vec::new());
+        }
+        self.call("on_day_end", |py, strategy| {
+            let py_context = strategy.context(py, context)?;
+            strategy
+                .on_day_end
+                .as_ref()
+                .expect("checked above")
+                .bind(py)
+                .call1((py_context,))
+        })
+    }
+
+    fn on_stop(&mut self, _context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        Ok(Vec::new())
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        &self.config
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        StrategyMetrics::new(self.config.strategy_id.clone())
+    }
+}
+
+/// Convert a callback's return value — `None`, an action dict or a list of
+/// them — into engine actions.
+fn actions_from_python(
+    returned: &Bound<'_, PyAny>,
+    strategy_id: &str,
+) -> PyResult<Vec<StrategyAction>> {
+    if returned.is_none() {
+        return Ok(Vec::new());
+    }
+    if let Ok(action) = returned.cast::<PyDict>() {
+        return Ok(vec![action_from_dict(action, strategy_id)?]);
+    }
+    if let Ok(actions) = returned.cast::<PyList>() {
+        let mut converted = Vec::with_capacity(actions.len());
+        for action in actions.iter() {
+            converted.push(action_from_dict(action.cast::<PyDict>()?, strategy_id)?);
+        }
+        return Ok(converted);
+    }
+    returned
+        .try_iter()?
+        .map(|action| action_from_dict(action?.cast::<PyDict>()?, strategy_id))
+        .collect()
+}
+
+fn required<'py>(action: &Bound<'py, PyDict>, key: &str) -> PyResult<Bound<'py, PyAny>> {
+    action.get_item(key)?.ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("strategy action is missing '{}'", key))
+    })
+}
+
+fn decimal_item(action: &Bound<'_, PyDict>, key: &str) -> PyResult<Option<Decimal>> {
+    let Some(value) = action.get_item(key)? else {
+        return Ok(None);
+    };
+    if value.is_none() {
+        return Ok(None);
+    }
+    let value: f64 = value.extract()?;
+    Decimal::from_f64(value).map(Some).ok_or_else(|| {
+        pyo3::exceptions::PyValueError::new_err(format!("invalid {}: {}", key, value))
+    })
+}
+
+fn action_from_dict(action: &Bound<'_, PyDict>, strategy_id: &str) -> PyResult<StrategyAction> {
+    let kind: String = required(action, "action")?.extract()?;
+    match kind.to_ascii_lowercase().as_str() {
+        side @ ("buy" | "sell") => {
+            let side = if side == "buy" { Side::Buy } else { Side::Sell };
+            let symbol = Symbol::equity(&required(action, "symbol")?.extract::<String>()?);
+            let quantity = decimal_item(action, "quantity")?
+                .filter(|quantity| *quantity > Decimal::ZERO)
+                .ok_or_else(|| {
+                    pyo3::exceptions::PyValueError::new_err(
+                        "strategy order needs a positive 'quantity'",
+                    )
+                })?;
+            let strategy_id = strategy_id.to_string();
+            let order = match (
+                decimal_item(action, "limit_price")?,
+                decimal_item(action, "stop_price")?,
+            ) {
+                (Some(limit), _) => Order::limit_order(symbol, side, quantity, limit, strategy_id),
+                (None, Some(stop)) => Order::stop_order(symbol, side, quantity, stop, strategy_id),
+                (None, None) => Order::market_order(symbol, side, quantity, strategy_id),
+            };
+            Ok(StrategyAction::PlaceOrder(order))
+        }
+        "cancel" => {
+            let order_id: String = required(action, "order_id")?.extract()?;
+            let order_id = order_id.parse::<OrderId>().map_err(|e| {
+                pyo3::exceptions::PyValueError::new_err(format!(
+                    "invalid order_id '{}': {}",
+                    order_id, e
+                ))
+            })?;
+            Ok(StrategyAction::CancelOrder { order_id })
+        }
+        "log" => {
+            let level = match action.get_item("level")? {
+                Some(level) => level.extract::<String>()?.to_ascii_lowercase(),
+                None => "info".to_string(),
+            };
+            let level = match level.as_str() {
+                "debug" => LogLevel::Debug,
+                "info" => LogLevel::Info,
+                "warning" | "warn" => LogLevel::Warning,
+                "error" => LogLevel::Error,
+                other => {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "unknown log level '{}'",
+                        other
+                    )))
+                }
+            };
+            Ok(StrategyAction::Log {
+                level,
+                message: required(action, "message")?.extract()?,
+            })
+        }
+        other => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "unknown strategy action '{}' (expected buy, sell, cancel or log)",
+            other
+        ))),
+    }
+}
+
+/// Raise the recorded failure as a `StrategyError` whose message carries
+/// the Python traceback and whose `__cause__` is the original exception.
+pub(crate) fn strategy_failure_error(py: Python<'_>, failure: &StrategyFailure) -> Option<PyErr> {
+    let (message, cause) = failure.lock().ok()?.take()?;
+    let error = StrategyError::new_err(message);
+    error.set_cause(py, Some(cause));
+    Some(error)
+}
@@ -0,0 +1,357 @@
+"""End-to-end tests for running backtests through the Python bindings.
+
+Build the extension first (``maturin develop -m crates/gb-python/Cargo.toml``),
+then run ``python -m pytest crates/gb-python/tests``.
+"""
+
+from __future__ import annotations
+
+import math
+
+import pytest
+
+glowback = pytest.importorskip("glowback")
+
+START_DATE = "2024-01-01T00:00:00Z"
+END_DATE = "2024-06-30T00:00:00Z"
+
+
+def sample_config(strategy: str = "buy_and_hold", params: dict | None = None):
+    return (
+        glowback.BacktestConfig(
+            symbols=["AAPL"],
+            start_date=START_DATE,
+            end_date=END_DATE,
+            name="pytest backtest",
+            initial_capital=100_000.0,
+        )
+        .with_strategy(strategy, params)
+        .with_data_source("sample")
+        .with_execution(commission_bps=1.0, slippage_bps=2.0)
+    )
+
+
+def test_sample_provider_backtest_runs_end_to_end():
+    config = sample_config("ma_crossover", {"short_period": 5, "long_period": 20})
+    assert config.strategy == "ma_crossover"
+    assert config.strategy_params == {"short_period": 5, "long_period": 20}
+
+    result = glowback.BacktestEngine.from_config(config).run()
+
+    metrics = result.metrics_summary
+    assert isinstance(metrics, dict)
+    assert metrics["initial_capital"] == 100_000.0
+    assert metrics["final_value"] > 0.0
+    assert metrics["total_trades"] == len(result.trades)
+
+    curve = result.equity_curve
+    assert curve, "equity curve should not be empty"
+    arrays = result.equity_arrays()
+    assert arrays["timestamp"] == [point["timestamp"] for point in curve]
+    assert arrays["value"] == [point["value"] for point in curve]
+    assert all(
+        math.isnan(column) if point["daily_return"] is None else column == point["daily_return"]
+        for column, point in zip(arrays["daily_return"], curve)
+    )
+
+    for trade in result.trades:
+        assert {"timestamp", "symbol", "action", "shares", "price"} <= trade.keys()
+    assert result.manifest["replay_request"]["strategy_name"] == "ma_crossover"
+
+
+def test_equity_arrays_as_numpy():
+    np = pytest.importorskip("numpy")
+    result = glowback.BacktestEngine.from_config(sample_config()).run()
+    arrays = result.equity_arrays(numpy=True)
+    assert isinstance(arrays["value"], np.ndarray)
+    assert arrays["value"].shape == (len(result.equity_curve),)
+
+
+def test_invalid_configuration_raises_config_error():
+    config = sample_config()
+    with pytest.raises(glowback.ConfigError):
+        config.with_dates(END_DATE, START_DATE)
+    with pytest.raises(glowback.ConfigError):
+        config.with_strategy("martingale")
+    with pytest.raises(glowback.ConfigError):
+        config.with_data_source("csv")
+
+
+def test_missing_data_raises_data_error():
+    config = (
+        sample_config()
+        .with_data_source("csv", "/nonexistent/glowback-data")
+        .with_data_quality_mode("fail")
+    )
+    with pytest.raises(glowback.DataError) as raised:
+        glowback.BacktestEngine.from_config(config).run()
+    assert isinstance(raised.value.code, str)
+    assert raised.value.retryable is False
+    # Every engine error is also a RuntimeError.
+    assert issubclass(glowback.DataError, glowback.GlowBackError)
+    assert issubclass(glowback.GlowBackError, RuntimeError)
+
+
+class BuyOnce:
+    def __init__(self):
+        self.fills = []
+
+    def on_market_event(self, bar, context):
+        if context.position(bar.symbol.symbol) == 0 and not self.fills:
+            return {"action": "buy", "symbol": bar.symbol.symbol, "quantity": 10}
+        return None
+
+    def on_order_event(self, event, context):
+        if event.kind == "filled":
+            self.fills.append((event.side, event.quantity, event.price))
+
+
+def test_python_strategy_runs_in_the_engine():
+    strategy = BuyOnce()
+    result = glowback.BacktestEngine.from_config(sample_config()).run(strategy)
+    assert [side for side, _, _ in strategy.fills] == ["buy"]
+    assert result.metrics_summary["total_trades"] == len(result.trades)
+
+
+def test_python_strategy_exception_keeps_traceback():
+    class Broken:
+        def on_market_event(self, bar, context):
+            raise KeyError("missing signal")
+
+    with pytest.raises(glowback.StrategyError) as raised:
+        glowback.BacktestEngine.from_config(sample_config()).run(Broken())
+    assert "Traceback" in str(raised.value)
+    assert "KeyError" in str(raised.value)
+    assert isinstance(raised.value.__cause__, KeyError)
+
+
+def test_optimizer_sweep_reruns_to_the_best_objective():
+    space = glowback.SearchSpace().add_int("short_period", 3, 5).add_int("long_period", 18, 20)
+    backtest = sample_config("ma_crossover")
+    seen = []
+    result = glowback.Optimizer(
+        glowback.OptimizationConfig(space, backtest, concurrency=3)
+    ).run(progress=seen.append)
+
+    assert len(result.trials) == len(seen) == 9
+    assert all(trial["status"] == "Completed" for trial in result.trials)
+    assert result.best_objective == max(trial["objective"] for trial in result.trials)
+
+    rerun = glowback.BacktestEngine.from_config(
+        backtest.with_strategy("ma_crossover", result.best_parameters)
+    ).run()
+    assert rerun.metrics_summary["sharpe_ratio"] == pytest.approx(result.best_objective)
+
+    pytest.importorskip("pyarrow")
+    pytest.importorskip("pandas")
+    frame = result.to_dataframe()
+    assert len(frame) == 9
+    assert {"param_short_period", "param_long_period", "objective"} <= set(frame.columns)
+
+
+def test_positions_history_is_a_long_format_frame():
+    config = sample_config()
+    result = glowback.BacktestEngine.from_config(config).run()
+    rows = result.positions_history
+    assert rows
+    assert {row["symbol"] for row in rows} == {"AAPL"}
+    assert all(row["quantity"] > 0 for row in rows)
+
+    pd = pytest.importorskip("pandas")
+    frame = result.positions_dataframe()
+    assert list(frame.columns) == ["timestamp", "symbol", "quantity", "market_value"]
+    assert len(frame) == len(rows)
+    assert pd.api.types.is_datetime64_any_dtype(frame["timestamp"])
+
+    quiet = glowback.BacktestEngine.from_config(config.with_position_snapshots("off")).run()
+    assert quiet.positions_history == []
+    assert list(quiet.positions_dataframe().columns) == list(frame.columns)
+
+
+def test_black_scholes_textbook_values():
+    call = glowback.black_scholes("call", 100.0, 100.0, 0.05, 0.0, 0.2, 1.0)
+    assert call["price"] == pytest.approx(10.4506, abs=1e-3)
+    assert call["delta"] == pytest.approx(0.6368, abs=1e-3)
+    put = glowback.black_scholes("put", 148.0, 150.0, 0.04, 0.01, 0.3, 0.5)
+    vol = glowback.implied_vol("put", put["price"], 148.0, 150.0, 0.04, 0.01, 0.5)
+    assert vol == pytest.approx(0.3, abs=1e-3)
+
+
+def test_array_pricing_accepts_numpy_and_matches_scalars():
+    np = pytest.importorskip("numpy")
+    strikes = np.linspace(80.0, 120.0, 9)
+    table = glowback.black_scholes_array("put", 100.0, strikes, 0.03, 0.0, 0.25, 0.5, numpy=True)
+    assert isinstance(table["price"], np.ndarray)
+    for i, strike in enumerate(strikes):
+        row = glowback.black_scholes("put", 100.0, float(strike), 0.03, 0.0, 0.25, 0.5)
+        assert {field: table[field][i] for field in row} == row
+    vols = glowback.implied_vol_array("put", table["price"], 100.0, strikes, 0.03, 0.0, 0.5)
+    assert vols == pytest.approx([0.25] * len(strikes), abs=1e-3)
+
+
+def test_option_contract_prices_by_exercise_style():
+    european = glowback.OptionContract("AAPL", "put", 150.0, "2026-06-20T20:00:00Z")
+    american = glowback.OptionContract(
+        "AAPL", "put", 150.0, "2026-06-20T20:00:00Z", exercise_style="american"
+    )
+    now = "2025-06-20T20:00:00Z"
+    assert european.intrinsic_value(130.0) == 20.0
+    assert european.time_to_expiry(now) == pytest.approx(1.0, abs=1e-2)
+    assert american.price(130.0, 0.05, 0.25, now)["price"] > european.price(130.0, 0.05, 0.25, now)["price"]
+
+
+def scripted_bar(close: float, day: int):
+    return glowback.Bar("AAPL", f"2024-01-{day:02d}T16:00:00Z", close, close, close, close)
+
+
+def test_paper_broker_quantity_rules_by_asset_class():
+    broker = glowback.PaperBroker()
+    assert broker.quantity_rule("AAPL") == {
+        "lot_size": 1.0,
+        "min_quantity": 1.0,
+        "quantity_precision": 0,
+    }
+    crypto = broker.quantity_rule(glowback.Symbol("BTC-USD", "COINBASE", "crypto"))
+    assert crypto["quantity_precision"] == 8
+    assert crypto["min_quantity"] == pytest.approx(1e-8)
+
+
+def test_paper_broker_short_then_cover_at_profit():
+    broker = glowback.PaperBroker(
+        commission_per_share=0.0, slippage_bps=0.0, allow_short_selling=True
+    )
+    broker.connect()
+    broker.process_bar(scripted_bar(100.0, 2))
+    broker.submit_market_order("AAPL", "sell", 50)
+    assert broker.cash == 105_000.0
+
+    broker.process_bar(scripted_bar(90.0, 3))
+    [position] = broker.get_positions()
+    assert position["quantity"] == -50.0
+    assert position["unrealized_pnl"] == 500.0
+    assert broker.account_balance()["equity"] == 100_500.0
+
+    broker.submit_market_order("AAPL", "buy", 50)
+    assert broker.get_positions() == []
+    assert broker.realized_pnl == 500.0
+    assert broker.cash == 100_500.0
+    assert [fill["side"] for fill in broker.get_fills()] == ["sell", "buy"]
+
+
+def test_live_engine_sandbox_session():
+    class RoundTrip:
+        def __init__(self):
+            self.bars = 0
+
+        def on_market_event(self, bar, context):
+            self.bars += 1
+            side = {1: "buy", 3: "sell"}.get(self.bars)
+            if side:
+                return {"action": side, "symbol": "AAPL", "quantity": 10}
+            return None
+
+    engine = glowback.LiveEngine(
+        RoundTrip(), ["AAPL"], commission_per_share=0.0, slippage_bps=0.0
+    )
+    engine.start()
+    for day, close in enumerate([100.0, 105.0, 110.0], start=2):
+        engine.process_bar(scripted_bar(close, day))
+    events = engine.drain_events()
+    engine.stop()
+
+    assert [event["price"] for event in events if event["type"] == "OrderFilled"] == [100.0, 110.0]
+    assert engine.positions() == []
+    assert engine.cash == 100_100.0
+
+
+def test_data_manager_introspection(tmp_path):
+    manager = glowback.DataManager(str(tmp_path))
+    manager.add_sample_provider()
+    for ticker in ["AAPL", "MSFT"]:
+        manager.load_data(
+            glowback.Symbol(ticker, "NASDAQ", "equity"),
+            "2024-01-01T00:00:00Z",
+            "2024-01-31T00:00:00Z",
+            "day",
+        )
+
+    listed = manager.list_symbols()
+    assert [entry["symbol"] for entry in listed] == ["AAPL", "MSFT"]
+    assert listed[0]["resolutions"] == ["1d"]
+    assert listed[0]["coverage"]["1d"]["records"] == listed[0]["records"]
+
+    stats = manager.storage_stats()
+    assert stats["files"] == 2
+    assert sum(entry["bytes"] for entry in stats["symbols"]) == stats["bytes"]
+
+    catalog = manager.get_catalog_stats(exact=True)
+    assert [(entry["symbol"], entry["resolution"]) for entry in catalog.breakdown] == [
+        ("AAPL", "1d"),
+        ("MSFT", "1d"),
+    ]
+    assert sum(entry["records"] for entry in catalog.breakdown) == catalog.total_records
+    assert catalog.breakdown[0]["records"] == listed[0]["records"]
+    assert sum(entry["bytes"] for entry in catalog.breakdown) == stats["bytes"]
+
+    [gap] = manager.find_gaps("AAPL", "1d", "2024-01-01T00:00:00Z", "2024-02-29T00:00:00Z")
+    assert gap["start"].startswith("2024-02-01")
+    assert gap["missing_bars"] == 21
+
+    assert manager.purge_symbol("AAPL") == 1
+    assert [entry["symbol"] for entry in manager.list_symbols()] == ["MSFT"]
+
+
+def test_sample_provider_options_shape_reproducible_data(tmp_path):
+    def load(directory, **options):
+        manager = glowback.DataManager(str(directory))
+        manager.add_sample_provider(**options)
+        bars = manager.load_data(
+            glowback.Symbol("AAPL", "NASDAQ", "equity"),
+            "2024-01-01T00:00:00Z",
+            "2024-03-31T00:00:00Z",
+            "day",
+        )
+        return [bar.close for bar in bars]
+
+    options = {"seed": 7, "symbols": {"AAPL": {"volatility": 0.4}}, "skip_weekends": True}
+    first = load(tmp_path / "first", **options)
+    assert first == load(tmp_path / "second", **options)
+    assert first != load(tmp_path / "reseeded", **{**options, "seed": 8})
+    assert len(first) < len(load(tmp_path / "demo"))
+
+    with pytest.raises(ValueError, match="unknown sample provider option"):
+        glowback.DataManager(str(tmp_path / "bad")).add_sample_provider(vol=0.2)
+
+
+def test_bar_provenance_defaults_to_unknown():
+    provenance = scripted_bar(100.0, 2).provenance
+    assert provenance == {
+        "provider": None,
+        "split_adjusted": False,
+        "dividend_adjusted": False,
+        "resampled_from": None,
+        "synthetic": False,
+    }
+
+
+def test_bar_decimal_strings_keep_precision():
+    bar = glowback.Bar(
+        glowback.Symbol("ETH", "BINANCE", "crypto"),
+        "2024-01-02T00:00:00Z",
+        "0.000012345678",
+        "0.000012345679",
+        "0.000012345677",
+        "0.000012345678",
+    )
+    assert bar.as_decimal_strings()["close"] == "0.000012345678"
+    assert "0.000012345678" in repr(bar)
+
+    precise = glowback.Bar("AAPL", "2024-01-02T16:00:00Z", "67890.12345678901234567", 1.0, 1.0, 1.0)
+    assert precise.open == pytest.approx(67890.123456789)
+    glowback.set_strict_decimals(True)
+    try:
+        assert glowback.strict_decimals()
+        with pytest.raises(ValueError):
+            precise.open
+    finally:
+        glowback.set_strict_decimals(False)
@@ -0,0 +1,74 @@
+"""Tests for the portfolio risk bindings.
+
+Build the extension first (``maturin develop -m crates/gb-python/Cargo.toml``),
+then run ``python -m pytest crates/gb-python/tests``.
+"""
+
+from __future__ import annotations
+
+import pytest
+
+glowback = pytest.importorskip("glowback")
+
+BTC = glowback.Symbol("BTC-USDT", "BINANCE", "crypto")
+
+
+def bar(symbol, close: float):
+    return glowback.Bar(symbol, "2024-01-02T16:00:00Z", close, close, close, close)
+
+
+def mixed_broker():
+    """20% of equity long AAPL and 25% long BTC-USDT, 10% short MSFT."""
+    broker = glowback.PaperBroker(
+        commission_per_share=0.0, slippage_bps=0.0, allow_short_selling=True
+    )
+    broker.connect()
+    broker.process_bar(bar("AAPL", 100.0))
+    broker.process_bar(bar("MSFT", 50.0))
+    broker.process_bar(bar(BTC, 50_000.0))
+    broker.submit_market_order("AAPL", "buy", 200)
+    broker.submit_market_order("MSFT", "sell", 200)
+    broker.submit_market_order(BTC, "buy", 0.5)
+    return broker
+
+
+def test_snapshot_splits_exposure_by_asset_class_and_currency():
+    snapshot = glowback.RiskMonitor().update(mixed_broker())
+
+    assert snapshot.num_positions == 3
+    assert snapshot.gross_exposure == pytest.approx(0.55)
+    assert snapshot.net_exposure == pytest.approx(0.35)
+
+    by_class = snapshot.exposure_by_asset_class
+    assert by_class["equity"] == pytest.approx({"gross": 0.30, "net": 0.10})
+    assert by_class["crypto"] == pytest.approx({"gross": 0.25, "net": 0.25})
+    by_currency = snapshot.exposure_by_currency
+    assert by_currency["USD"] == pytest.approx({"gross": 0.30, "net": 0.10})
+    assert by_currency["USDT"] == pytest.approx({"gross": 0.25, "net": 0.25})
+
+    for split in (by_class, by_currency):
+        assert sum(e["gross"] for e in split.values()) == pytest.approx(snapshot.gross_exposure)
+        assert sum(e["net"] for e in split.values()) == pytest.approx(snapshot.net_exposure)
+
+
+def test_asset_class_limit_raises_an_alert():
+    monitor = glowback.RiskMonitor(max_gross_exposure_by_asset_class={"crypto": 0.2})
+    assert monitor.max_gross_exposure_by_asset_class == {"crypto": 0.2}
+
+    monitor.update(mixed_broker())
+
+    [alert] = [
+        alert
+        for alert in monitor.drain_alerts()
+        if alert["kind"] == "AssetClassExposureExceeded"
+    ]
+    assert alert["asset_class"] == "crypto"
+    assert alert["severity"] == "Critical"
+    assert alert["gross_exposure"] == pytest.approx(0.25)
+    assert alert["limit"] == 0.2
+    assert monitor.drain_alerts() == []
+
+
+def test_unknown_asset_class_limit_is_rejected():
+    with pytest.raises(ValueError):
+        glowback.RiskMonitor(max_gross_exposure_by_asset_class={"stocks": 0.5})
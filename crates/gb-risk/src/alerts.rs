@@ -1,6 +1,7 @@
 //! Risk alert types and severity levels.
 
 use chrono::{DateTime, Utc};
+use gb_types::market::AssetClass;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
@@ -50,6 +51,12 @@ pub enum RiskAlertKind {
         gross_exposure: Decimal,
         limit: Decimal,
     },
+    /// Gross exposure of one asset class exceeds its limit.
+    AssetClassExposureExceeded {
+        asset_class: AssetClass,
+        gross_exposure: Decimal,
+        limit: Decimal,
+    },
     /// Custom/user-defined alert.
     Custom { name: String, message: String },
 }
@@ -5,11 +5,16 @@
 //! - Per-position risk metrics (concentration, Greeks placeholder)
 //! - Configurable risk limits with breach detection
 //! - Event-driven monitoring via channels
+//! - Historical scenario replay of today's positions
 
 pub mod alerts;
 pub mod metrics;
 pub mod monitor;
+pub mod scenario;
 
 pub use alerts::{RiskAlert, RiskAlertKind, RiskSeverity};
-pub use metrics::{PortfolioRiskSnapshot, PositionRisk, RiskMetricsCalculator};
+pub use metrics::{Exposure, PortfolioRiskSnapshot, PositionRisk, RiskMetricsCalculator};
 pub use monitor::{RiskMonitor, RiskMonitorConfig};
+pub use scenario::{
+    historical_replay, HistoricalScenario, ReturnSeries, ScenarioError, ScenarioReplay,
+};
@@ -3,13 +3,15 @@
 //! [`RiskMetricsCalculator`] takes a portfolio snapshot and historical returns to
 //! produce a [`PortfolioRiskSnapshot`] that captures the current risk posture.
 
+use std::collections::HashMap;
+
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use gb_types::market::Symbol;
-use gb_types::portfolio::{DailyReturn, Portfolio};
+use gb_types::market::{AssetClass, Symbol};
+use gb_types::portfolio::{DailyReturn, GreeksExposure, Portfolio};
 
 /// Per-position risk breakdown.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -25,6 +27,22 @@ pub struct PositionRisk {
     pub var_contribution: Decimal,
 }
 
+/// Exposure of a slice of the portfolio, as fractions of total equity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Exposure {
+    /// Sum of absolute position weights.
+    pub gross: Decimal,
+    /// Sum of signed position weights (long – short).
+    pub net: Decimal,
+}
+
+impl Exposure {
+    fn add(&mut self, weight: Decimal) {
+        self.gross += weight.abs();
+        self.net += weight;
+    }
+}
+
 /// A point-in-time snapshot of portfolio-level risk metrics.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PortfolioRiskSnapshot {
@@ -40,6 +58,13 @@ pub struct PortfolioRiskSnapshot {
     pub leverage: Decimal,
     /// Number of distinct positions.
     pub num_positions: usize,
+    /// Gross and net exposure split by the asset class of each position.
+    #[serde(default)]
+    pub exposure_by_asset_class: HashMap<AssetClass, Exposure>,
+    /// Gross and net exposure split by the currency each position is quoted
+    /// in (see [`Symbol::quote_currency`]).
+    #[serde(default)]
+    pub exposure_by_currency: HashMap<String, Exposure>,
 
     // --- drawdown ---
     /// Current drawdown from equity peak (0–1 fraction).
@@ -59,6 +84,11 @@ pub struct PortfolioRiskSnapshot {
 
     // --- per-position ---
     pub position_risks: Vec<PositionRisk>,
+
+    // --- options ---
+    /// Net greek exposure of option positions.
+    #[serde(default)]
+    pub greeks: GreeksExposure,
 }
 
 /// Stateless calculator for risk metrics.
@@ -83,6 +113,8 @@ impl RiskMetricsCalculator {
         let mut position_risks = Vec::new();
         let mut gross_exposure = Decimal::ZERO;
         let mut net_exposure = Decimal::ZERO;
+        let mut exposure_by_asset_class: HashMap<AssetClass, Exposure> = HashMap::new();
+        let mut exposure_by_currency: HashMap<String, Exposure> = HashMap::new();
 
         for (symbol, pos) in &portfolio.positions {
             let weight = if safe_equity > Decimal::ZERO {
@@ -93,6 +125,14 @@ impl RiskMetricsCalculator {
             let weight_abs = weight.abs();
             gross_exposure += weight_abs;
             net_exposure += weight;
+            exposure_by_asset_class
+                .entry(symbol.asset_class)
+                .or_default()
+                .add(weight);
+            exposure_by_currency
+                .entry(symbol.quote_currency().to_string())
+                .or_default()
+                .add(weight);
 
             position_risks.push(PositionRisk {
                 symbol: symbol.clone(),
@@ -136,12 +176,15 @@ impl RiskMetricsCalculator {
             net_exposure,
             leverage,
             num_positions: portfolio.positions.len(),
+            exposure_by_asset_class,
+            exposure_by_currency,
             current_drawdown,
             max_drawdown,
             var_95,
             cvar_95,
             daily_pnl_pct,
             position_risks,
+            greeks: portfolio.greeks_exposure(),
         }
     }
 
@@ -344,6 +387,73 @@ mod tests {
         assert_eq!(snap.gross_exposure, snap.position_risks[0].weight_abs);
     }
 
+    #[test]
+    fn option_greeks_are_summed_into_the_snapshot() {
+        let mut portfolio = Portfolio::new("test".into(), dec!(100_000));
+        let call = GreeksExposure {
+            delta: dec!(55),
+            gamma: dec!(2),
+            theta: dec!(-4),
+            vega: dec!(12),
+            rho: dec!(3),
+        };
+        let put = GreeksExposure {
+            delta: dec!(-40),
+            gamma: dec!(1),
+            theta: dec!(-3),
+            vega: dec!(10),
+            rho: dec!(-2),
+        };
+        portfolio.option_greeks.insert(sym("C1"), call);
+        portfolio.option_greeks.insert(sym("P1"), put);
+
+        let snap = RiskMetricsCalculator::compute(&portfolio, &[], dec!(100_000));
+        assert_eq!(snap.greeks.delta, dec!(15));
+        assert_eq!(snap.greeks.gamma, dec!(3));
+        assert_eq!(snap.greeks.theta, dec!(-7));
+        assert_eq!(snap.greeks.vega, dec!(22));
+        assert_eq!(snap.greeks.rho, dec!(1));
+    }
+
+    #[test]
+    fn exposure_splits_by_asset_class_and_currency() {
+        let portfolio = make_portfolio(vec![
+            (Symbol::equity("AAPL"), dec!(100), dec!(100), dec!(100)),
+            (Symbol::equity("MSFT"), dec!(-50), dec!(200), dec!(200)),
+            (
+                Symbol::crypto("BTC-USD"),
+                dec!(0.1),
+                dec!(40_000),
+                dec!(40_000),
+            ),
+            (
+                Symbol::crypto("ETHUSDT"),
+                dec!(-2),
+                dec!(2_000),
+                dec!(2_000),
+            ),
+        ]);
+        let snap = RiskMetricsCalculator::compute(&portfolio, &[], dec!(100_000));
+
+        // Longs and shorts offset, so equity stays at $100k.
+        let equities = snap.exposure_by_asset_class[&AssetClass::Equity];
+        assert_eq!((equities.gross, equities.net), (dec!(0.2), dec!(0)));
+        let crypto = snap.exposure_by_asset_class[&AssetClass::Crypto];
+        assert_eq!((crypto.gross, crypto.net), (dec!(0.08), dec!(0)));
+        let usd = snap.exposure_by_currency["USD"];
+        assert_eq!((usd.gross, usd.net), (dec!(0.24), dec!(0.04)));
+        assert_eq!(snap.exposure_by_currency["USDT"].net, dec!(-0.04));
+
+        for split in [
+            snap.exposure_by_asset_class.values().collect::<Vec<_>>(),
+            snap.exposure_by_currency.values().collect(),
+        ] {
+            let gross: Decimal = split.iter().map(|exposure| exposure.gross).sum();
+            let net: Decimal = split.iter().map(|exposure| exposure.net).sum();
+            assert_eq!((gross, net), (snap.gross_exposure, snap.net_exposure));
+        }
+    }
+
     #[test]
     fn snapshot_serialization_roundtrip() {
         let portfolio = Portfolio::new("test".into(), dec!(100_000));
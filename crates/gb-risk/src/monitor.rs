@@ -4,15 +4,20 @@
 //! risk metrics, checks configurable limits, and emits [`RiskAlert`]s via a
 //! channel.
 
+use std::collections::HashMap;
+
+use chrono::NaiveDate;
 use crossbeam_channel::Sender;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
 
+use gb_types::market::{AssetClass, Symbol};
 use gb_types::portfolio::{DailyReturn, Portfolio, RiskLimits};
 
 use crate::alerts::{RiskAlert, RiskAlertKind, RiskSeverity};
 use crate::metrics::{PortfolioRiskSnapshot, RiskMetricsCalculator};
+use crate::scenario::{historical_replay, HistoricalScenario, ReturnSeries, ScenarioReplay};
 
 /// Configuration for the risk monitor.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -24,6 +29,15 @@ pub struct RiskMonitorConfig {
     pub max_gross_exposure: Option<Decimal>,
     /// Maximum portfolio-level VaR (95%, 1-day) as fraction.
     pub max_var_95: Option<Decimal>,
+    /// Maximum gross exposure of each listed asset class (as fraction of
+    /// equity). Classes not listed are unlimited.
+    #[serde(default)]
+    pub max_gross_exposure_by_asset_class: HashMap<AssetClass, Decimal>,
+    /// Past windows replayed on the portfolio once a day, on the first
+    /// update of each date. History comes from
+    /// [`RiskMonitor::set_scenario_history`].
+    #[serde(default)]
+    pub historical_scenarios: Vec<HistoricalScenario>,
 }
 
 impl Default for RiskMonitorConfig {
@@ -33,6 +47,8 @@ impl Default for RiskMonitorConfig {
             warning_threshold_pct: Decimal::new(80, 2), // 80%
             max_gross_exposure: Some(Decimal::from(3)), // 300% gross
             max_var_95: Some(Decimal::new(5, 2)),       // 5%
+            max_gross_exposure_by_asset_class: HashMap::new(),
+            historical_scenarios: Vec::new(),
         }
     }
 }
@@ -47,6 +63,9 @@ pub struct RiskMonitor {
     equity_peak: Decimal,
     daily_returns: Vec<DailyReturn>,
     last_snapshot: Option<PortfolioRiskSnapshot>,
+    scenario_history: HashMap<Symbol, ReturnSeries>,
+    last_replay_date: Option<NaiveDate>,
+    last_replays: Vec<ScenarioReplay>,
 }
 
 impl RiskMonitor {
@@ -58,6 +77,9 @@ impl RiskMonitor {
             equity_peak: Decimal::ZERO,
             daily_returns: Vec::new(),
             last_snapshot: None,
+            scenario_history: HashMap::new(),
+            last_replay_date: None,
+            last_replays: Vec::new(),
         }
     }
 
@@ -77,6 +99,22 @@ impl RiskMonitor {
         self.equity_peak = peak;
     }
 
+    /// Replace the return history the configured historical scenarios are
+    /// replayed on.
+    pub fn set_scenario_history(&mut self, history: HashMap<Symbol, ReturnSeries>) {
+        self.scenario_history = history;
+    }
+
+    /// Results of the most recent daily scenario replays.
+    pub fn last_replays(&self) -> &[ScenarioReplay] {
+        &self.last_replays
+    }
+
+    /// The monitor's limits.
+    pub fn config(&self) -> &RiskMonitorConfig {
+        &self.config
+    }
+
     /// Get the most recently computed risk snapshot, if any.
     pub fn last_snapshot(&self) -> Option<&PortfolioRiskSnapshot> {
         self.last_snapshot.as_ref()
@@ -96,10 +134,37 @@ impl RiskMonitor {
 
         self.check_limits(&snapshot, portfolio);
 
+        let today = snapshot.timestamp.date_naive();
+        if self.last_replay_date != Some(today) {
+            self.last_replay_date = Some(today);
+            self.run_scenarios(portfolio);
+        }
+
         self.last_snapshot = Some(snapshot.clone());
         snapshot
     }
 
+    fn run_scenarios(&mut self, portfolio: &Portfolio) {
+        self.last_replays = self
+            .config
+            .historical_scenarios
+            .iter()
+            .filter_map(|scenario| {
+                historical_replay(portfolio, &self.scenario_history, scenario)
+                    .inspect(|replay| {
+                        info!(
+                            scenario = %replay.scenario,
+                            total_return = %replay.total_return,
+                            max_drawdown = %replay.max_drawdown,
+                            "historical scenario replayed"
+                        )
+                    })
+                    .inspect_err(|e| warn!(error = %e, "historical scenario skipped"))
+                    .ok()
+            })
+            .collect();
+    }
+
     // ---- internal limit checks ----
 
     fn check_limits(&self, snap: &PortfolioRiskSnapshot, portfolio: &Portfolio) {
@@ -120,6 +185,9 @@ impl RiskMonitor {
         // --- gross exposure ---
         self.check_gross_exposure(snap);
 
+        // --- exposure by asset class ---
+        self.check_asset_class_exposure(snap);
+
         // --- VaR ---
         self.check_var(snap);
     }
@@ -285,6 +353,42 @@ impl RiskMonitor {
         }
     }
 
+    fn check_asset_class_exposure(&self, snap: &PortfolioRiskSnapshot) {
+        for (&asset_class, &limit) in &self.config.max_gross_exposure_by_asset_class {
+            let ge = snap
+                .exposure_by_asset_class
+                .get(&asset_class)
+                .map_or(Decimal::ZERO, |exposure| exposure.gross);
+            if ge >= limit {
+                self.emit(RiskAlert::new(
+                    RiskSeverity::Critical,
+                    RiskAlertKind::AssetClassExposureExceeded {
+                        asset_class,
+                        gross_exposure: ge,
+                        limit,
+                    },
+                    format!(
+                        "{} gross exposure {:.2} exceeds {:.2} limit",
+                        asset_class, ge, limit
+                    ),
+                ));
+            } else if ge >= limit * self.config.warning_threshold_pct {
+                self.emit(RiskAlert::new(
+                    RiskSeverity::Warning,
+                    RiskAlertKind::AssetClassExposureExceeded {
+                        asset_class,
+                        gross_exposure: ge,
+                        limit,
+                    },
+                    format!(
+                        "{} gross exposure {:.2} approaching {:.2} limit",
+                        asset_class, ge, limit
+                    ),
+                ));
+            }
+        }
+    }
+
     fn check_var(&self, snap: &PortfolioRiskSnapshot) {
         if let (Some(limit), Some(var)) = (self.config.max_var_95, snap.var_95) {
             if var >= limit {
@@ -368,6 +472,66 @@ mod tests {
         assert!(rx.try_recv().is_err()); // No alerts
     }
 
+    #[test]
+    fn asset_class_exposure_alert_fires_only_for_limited_classes() {
+        let (tx, rx) = unbounded();
+        let mut config = RiskMonitorConfig::default();
+        config
+            .max_gross_exposure_by_asset_class
+            .insert(AssetClass::Crypto, dec!(0.10));
+        let mut monitor = RiskMonitor::new(config, tx);
+
+        // $20k of BTC on $100k cash ⇒ ~17% crypto, plus ~17% equities.
+        let portfolio = make_portfolio_with_positions(vec![
+            (
+                Symbol::crypto("BTC-USD"),
+                dec!(0.5),
+                dec!(40_000),
+                dec!(40_000),
+            ),
+            (sym("AAPL"), dec!(200), dec!(100), dec!(100)),
+        ]);
+        monitor.update(&portfolio);
+
+        let alerts: Vec<_> = rx.try_iter().collect();
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].severity, RiskSeverity::Critical);
+        assert!(matches!(
+            alerts[0].kind,
+            RiskAlertKind::AssetClassExposureExceeded {
+                asset_class: AssetClass::Crypto,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn configured_scenarios_replay_once_a_day() {
+        let (tx, _rx) = unbounded();
+        let start = Utc::now() - chrono::Duration::days(30);
+        let config = RiskMonitorConfig {
+            historical_scenarios: vec![HistoricalScenario::new(
+                "selloff",
+                start,
+                start + chrono::Duration::days(2),
+            )],
+            ..Default::default()
+        };
+        let mut monitor = RiskMonitor::new(config, tx);
+        let returns = |daily| ReturnSeries::from([(start, daily)]);
+        monitor.set_scenario_history(HashMap::from([(sym("AAPL"), returns(dec!(-0.5)))]));
+        let portfolio =
+            make_portfolio_with_positions(vec![(sym("AAPL"), dec!(100), dec!(100), dec!(100))]);
+
+        monitor.update(&portfolio);
+        monitor.set_scenario_history(HashMap::from([(sym("AAPL"), returns(dec!(-0.9)))]));
+        monitor.update(&portfolio);
+
+        let replays = monitor.last_replays();
+        assert_eq!(replays.len(), 1);
+        assert_eq!(replays[0].equity_path.last().unwrap().1, dec!(105_000));
+    }
+
     #[test]
     fn drawdown_alert_fires() {
         let (tx, rx) = unbounded();
@@ -0,0 +1,288 @@
+//! Historical scenario replay.
+//!
+//! [`historical_replay`] applies the realized returns of a past window (say
+//! 2020-02-19..2020-03-23) to the positions held today, compounding each
+//! position along its own return path, or a proxy's when the symbol has no
+//! history in the window, and reports the projected equity path.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use gb_types::market::{Bar, Symbol};
+use gb_types::portfolio::Portfolio;
+
+/// Daily returns of one symbol, keyed by the close they were realized at.
+pub type ReturnSeries = BTreeMap<DateTime<Utc>, Decimal>;
+
+/// A past window to replay, with stand-ins for symbols that have no history
+/// in it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoricalScenario {
+    pub name: String,
+    /// First close whose return is applied.
+    pub start: DateTime<Utc>,
+    /// Last close whose return is applied (inclusive).
+    pub end: DateTime<Utc>,
+    /// Proxy symbol by ticker, e.g. `"COIN"` replayed on `BTC-USD`.
+    #[serde(default)]
+    pub proxies: HashMap<String, Symbol>,
+}
+
+impl HistoricalScenario {
+    pub fn new(name: &str, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self {
+            name: name.to_string(),
+            start,
+            end,
+            proxies: HashMap::new(),
+        }
+    }
+
+    /// Replay `ticker` on the returns of `proxy`.
+    pub fn with_proxy(mut self, ticker: &str, proxy: Symbol) -> Self {
+        self.proxies.insert(ticker.to_string(), proxy);
+        self
+    }
+}
+
+/// Projected outcome of a [`HistoricalScenario`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioReplay {
+    pub scenario: String,
+    /// Equity today, then after each replayed close.
+    pub equity_path: Vec<(DateTime<Utc>, Decimal)>,
+    /// Final projected equity over today's, minus one.
+    pub total_return: Decimal,
+    /// Largest peak-to-trough fall along the path (0–1 fraction).
+    pub max_drawdown: Decimal,
+    /// Worst one-day equity return and the close it happened at.
+    pub worst_day: Option<(DateTime<Utc>, Decimal)>,
+    /// Positions replayed on a proxy's returns, as (position, proxy).
+    pub proxied: Vec<(Symbol, Symbol)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum ScenarioError {
+    #[error("no return history for {symbol} in scenario '{scenario}'")]
+    MissingHistory { scenario: String, symbol: Symbol },
+    #[error("scenario '{0}' ends before it starts")]
+    EmptyWindow(String),
+}
+
+/// Replay `scenario` on the positions of `portfolio`.
+///
+/// Each position's market value is compounded through the returns of its
+/// symbol (or proxy) on every close of the window; a close where a symbol
+/// has no return leaves it unchanged. Cash is held flat. Shorts carry their
+/// negative market value, so they gain when the path falls.
+pub fn historical_replay(
+    portfolio: &Portfolio,
+    symbol_return_series: &HashMap<Symbol, ReturnSeries>,
+    scenario: &HistoricalScenario,
+) -> Result<ScenarioReplay, ScenarioError> {
+    if scenario.end < scenario.start {
+        return Err(ScenarioError::EmptyWindow(scenario.name.clone()));
+    }
+    let window = scenario.start..=scenario.end;
+    let in_window = |symbol: &Symbol| {
+        symbol_return_series
+            .get(symbol)
+            .filter(|series| series.range(window.clone()).next().is_some())
+    };
+
+    let mut legs = Vec::with_capacity(portfolio.positions.len());
+    let mut proxied = Vec::new();
+    for (symbol, position) in &portfolio.positions {
+        let series = match in_window(symbol) {
+            Some(series) => series,
+            None => {
+                let proxy = scenario.proxies.get(&symbol.symbol);
+                let series =
+                    proxy
+                        .and_then(in_window)
+                        .ok_or_else(|| ScenarioError::MissingHistory {
+                            scenario: scenario.name.clone(),
+                            symbol: symbol.clone(),
+                        })?;
+                proxied.push((symbol.clone(), proxy.cloned().expect("proxy was found")));
+                series
+            }
+        };
+        legs.push((position.market_value, series));
+    }
+    proxied.sort_by(|a, b| a.0.symbol.cmp(&b.0.symbol));
+
+    let closes: BTreeSet<DateTime<Utc>> = legs
+        .iter()
+        .flat_map(|(_, series)| series.range(window.clone()).map(|(at, _)| *at))
+        .collect();
+
+    let equity = portfolio.cash + legs.iter().map(|(value, _)| *value).sum::<Decimal>();
+    let mut equity_path = vec![(scenario.start, equity)];
+    let (mut peak, mut max_drawdown) = (equity, Decimal::ZERO);
+    let mut worst_day: Option<(DateTime<Utc>, Decimal)> = None;
+    let mut previous = equity;
+    for close in closes {
+        for (value, series) in &mut legs {
+            if let Some(daily) = series.get(&close) {
+                *value *= Decimal::ONE + daily;
+            }
+        }
+        let equity = portfolio.cash + legs.iter().map(|(value, _)| *value).sum::<Decimal>();
+        if !previous.is_zero() {
+            let daily = equity / previous - Decimal::ONE;
+            if worst_day.is_none_or(|(_, worst)| daily < worst) {
+                worst_day = Some((close, daily));
+            }
+        }
+        peak = peak.max(equity);
+        if peak > Decimal::ZERO {
+            max_drawdown = max_drawdown.max((peak - equity) / peak);
+        }
+        equity_path.push((close, equity));
+        previous = equity;
+    }
+
+    let start = equity_path[0].1;
+    let end = equity_path.last().map_or(start, |(_, equity)| *equity);
+    let total_return = if start.is_zero() {
+        Decimal::ZERO
+    } else {
+        end / start - Decimal::ONE
+    };
+    Ok(ScenarioReplay {
+        scenario: scenario.name.clone(),
+        equity_path,
+        total_return,
+        max_drawdown,
+        worst_day,
+        proxied,
+    })
+}
+
+/// Close-to-close returns of `bars`, stamped at the later close. Bars must
+/// be in time order.
+pub fn returns_from_bars(bars: &[Bar]) -> ReturnSeries {
+    bars.windows(2)
+        .filter(|pair| !pair[0].close.is_zero())
+        .map(|pair| {
+            (
+                pair[1].timestamp,
+                pair[1].close / pair[0].close - Decimal::ONE,
+            )
+        })
+        .collect()
+}
+
+/// Load daily bars for `symbols` through `data` and turn them into the
+/// return series [`historical_replay`] needs for `scenario`. History starts
+/// a week before the window so its first close has a return. Symbols the
+/// data manager has nothing for are left out.
+#[cfg(feature = "history")]
+pub async fn load_return_series(
+    data: &mut gb_data::DataManager,
+    symbols: &[Symbol],
+    scenario: &HistoricalScenario,
+) -> HashMap<Symbol, ReturnSeries> {
+    let from = scenario.start - chrono::Duration::days(7);
+    let mut series = HashMap::new();
+    for symbol in symbols {
+        match data
+            .load_data(
+                symbol,
+                from,
+                scenario.end,
+                gb_types::market::Resolution::Day,
+            )
+            .await
+        {
+            Ok(bars) => {
+                series.insert(symbol.clone(), returns_from_bars(&bars));
+            }
+            Err(e) => {
+                tracing::warn!(symbol = %symbol, error = %e, "no history for scenario replay")
+            }
+        }
+    }
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use gb_types::portfolio::Position;
+    use rust_decimal_macros::dec;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2020, 3, d, 21, 0, 0).unwrap()
+    }
+
+    fn series(returns: &[(u32, Decimal)]) -> ReturnSeries {
+        returns.iter().map(|(d, r)| (day(*d), *r)).collect()
+    }
+
+    fn portfolio(cash: Decimal, positions: &[(Symbol, Decimal)]) -> Portfolio {
+        let mut portfolio = Portfolio::new("test".into(), cash);
+        for (symbol, value) in positions {
+            let mut position = Position::new(symbol.clone());
+            position.quantity = value / dec!(100);
+            position.average_price = dec!(100);
+            position.update_market_price(dec!(100));
+            portfolio.positions.insert(symbol.clone(), position);
+        }
+        portfolio
+    }
+
+    #[test]
+    fn compounds_each_position_along_its_own_path() {
+        let (spy, tlt) = (Symbol::equity("SPY"), Symbol::equity("TLT"));
+        let portfolio = portfolio(
+            dec!(10_000),
+            &[(spy.clone(), dec!(60_000)), (tlt.clone(), dec!(30_000))],
+        );
+        let history = HashMap::from([
+            (
+                spy,
+                series(&[(2, dec!(-0.10)), (3, dec!(-0.10)), (4, dec!(0.05))]),
+            ),
+            (tlt, series(&[(2, dec!(0.02)), (4, dec!(-0.01))])),
+        ]);
+        let scenario = HistoricalScenario::new("crash", day(2), day(4));
+
+        let replay = historical_replay(&portfolio, &history, &scenario).unwrap();
+
+        // SPY 60k → 54k → 48.6k → 51.03k; TLT 30k → 30.6k → 30.6k → 30.294k.
+        let path: Vec<Decimal> = replay.equity_path.iter().map(|(_, e)| *e).collect();
+        assert_eq!(
+            path,
+            [dec!(100_000), dec!(94_600), dec!(89_200), dec!(91_324)]
+        );
+        assert_eq!(replay.total_return, dec!(-0.08676));
+        assert_eq!(replay.max_drawdown, dec!(0.108));
+        assert_eq!(replay.worst_day.unwrap().0, day(3));
+        assert!(replay.proxied.is_empty());
+    }
+
+    #[test]
+    fn missing_symbols_replay_on_their_proxy_or_fail() {
+        let (coin, btc) = (Symbol::equity("COIN"), Symbol::crypto("BTC-USD"));
+        let short = portfolio(dec!(120_000), &[(coin.clone(), dec!(-20_000))]);
+        let history = HashMap::from([(btc.clone(), series(&[(2, dec!(-0.5))]))]);
+        let scenario = HistoricalScenario::new("crypto winter", day(1), day(5));
+
+        let error = historical_replay(&short, &history, &scenario).unwrap_err();
+        assert!(matches!(error, ScenarioError::MissingHistory { .. }));
+
+        let scenario = scenario.with_proxy("COIN", btc.clone());
+        let replay = historical_replay(&short, &history, &scenario).unwrap();
+        // The short halves in value against the proxy: 100k → 110k.
+        assert_eq!(replay.equity_path.last().unwrap().1, dec!(110_000));
+        assert_eq!(replay.max_drawdown, dec!(0));
+        assert_eq!(replay.proxied, [(coin, btc)]);
+    }
+}
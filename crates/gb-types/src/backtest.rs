@@ -1,13 +1,17 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Utc};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use uuid::Uuid;
 
-use crate::market::{Resolution, Symbol};
+use crate::errors::GbResult;
+use crate::execution::ExecutionReport;
+use crate::margin::{BuyingPowerModel, MarginCall};
+use crate::market::{AssetClass, Resolution, Symbol, Universe};
 use crate::orders::OrderEvent;
 use crate::portfolio::Portfolio;
+use crate::profile::RunProfile;
 use crate::strategy::{StrategyConfig, StrategyMetrics};
 
 /// Unique backtest identifier
@@ -23,10 +27,16 @@ pub struct BacktestConfig {
     pub end_date: DateTime<Utc>,
     pub initial_capital: Decimal,
     pub symbols: Vec<Symbol>,
+    /// Name of the saved universe `symbols` was taken from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub universe: Option<String>,
     pub resolution: Resolution,
     pub strategy_config: StrategyConfig,
     pub execution_settings: ExecutionSettings,
     pub data_settings: DataSettings,
+    /// Deposits and withdrawals applied over the run
+    #[serde(default)]
+    pub cash_flows: CashFlowSettings,
     pub created_at: DateTime<Utc>,
 }
 
@@ -40,10 +50,12 @@ impl BacktestConfig {
             end_date: Utc::now(),
             initial_capital: Decimal::from(100000),
             symbols: Vec::new(),
+            universe: None,
             resolution: Resolution::Day,
             strategy_config,
             execution_settings: ExecutionSettings::default(),
             data_settings: DataSettings::default(),
+            cash_flows: CashFlowSettings::default(),
             created_at: Utc::now(),
         }
     }
@@ -53,6 +65,14 @@ impl BacktestConfig {
         self
     }
 
+    /// Trade `universe`'s symbols, recording its name so the run can be
+    /// traced back to the screen that built it.
+    pub fn with_universe(mut self, universe: &Universe) -> Self {
+        self.symbols = universe.symbols.clone();
+        self.universe = Some(universe.name.clone());
+        self
+    }
+
     pub fn with_date_range(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
         self.start_date = start;
         self.end_date = end;
@@ -68,10 +88,16 @@ impl BacktestConfig {
         self.resolution = resolution;
         self
     }
+
+    pub fn with_cash_flows(mut self, cash_flows: CashFlowSettings) -> Self {
+        self.cash_flows = cash_flows;
+        self
+    }
 }
 
 /// Execution settings for realistic trading simulation
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ExecutionSettings {
     pub commission_per_share: Decimal,
     pub commission_percentage: Decimal,
@@ -81,6 +107,164 @@ pub struct ExecutionSettings {
     pub market_impact_model: MarketImpactModel,
     #[serde(default = "default_max_volume_participation")]
     pub max_volume_participation: Decimal,
+    #[serde(default)]
+    pub option_settlement: OptionSettlement,
+    /// When each simulated day's bars become visible to the strategy, and
+    /// so which bar its orders can fill on.
+    #[serde(default)]
+    pub bar_delivery: BarDelivery,
+    /// How the strategy's decisions on bars sharing a timestamp are
+    /// applied.
+    #[serde(default)]
+    pub event_ordering: EventOrdering,
+    /// Lot size, minimum and precision of order quantities per asset class
+    #[serde(default)]
+    pub quantity_policy: QuantityPolicy,
+    /// Cash or margin account rules orders are checked against when the
+    /// strategy places them. `None` leaves cash unchecked.
+    #[serde(default)]
+    pub buying_power: Option<BuyingPowerModel>,
+}
+
+/// Sizing constraints for one instrument's order quantities.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuantityRule {
+    /// Quantities are whole multiples of this
+    pub lot_size: Decimal,
+    /// Smallest quantity accepted
+    pub min_quantity: Decimal,
+    /// Decimal places a quantity may carry
+    pub quantity_precision: u32,
+}
+
+impl QuantityRule {
+    /// Whole units only, as for equities.
+    pub fn whole_units() -> Self {
+        Self {
+            lot_size: Decimal::ONE,
+            min_quantity: Decimal::ONE,
+            quantity_precision: 0,
+        }
+    }
+
+    /// Any quantity down to `precision` decimal places.
+    pub fn fractional(precision: u32) -> Self {
+        let step = Decimal::new(1, precision);
+        Self {
+            lot_size: step,
+            min_quantity: step,
+            quantity_precision: precision,
+        }
+    }
+
+    /// `quantity` rounded toward zero to the precision and lot size.
+    pub fn round_down(&self, quantity: Decimal) -> Decimal {
+        let quantity = quantity.round_dp_with_strategy(
+            self.quantity_precision,
+            rust_decimal::RoundingStrategy::ToZero,
+        );
+        if self.lot_size > Decimal::ZERO {
+            (quantity / self.lot_size).trunc() * self.lot_size
+        } else {
+            quantity
+        }
+    }
+
+    /// `quantity` rounded down, or `None` when that leaves less than the
+    /// minimum.
+    pub fn apply(&self, quantity: Decimal) -> Option<Decimal> {
+        let rounded = self.round_down(quantity);
+        (rounded > Decimal::ZERO && rounded >= self.min_quantity).then(|| rounded.normalize())
+    }
+}
+
+/// Quantity rules by asset class, overridable per ticker. Crypto trades in
+/// fractions down to 8 decimal places; everything else in whole units.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QuantityPolicy {
+    pub asset_classes: HashMap<AssetClass, QuantityRule>,
+    /// Overrides keyed by ticker, e.g. `"BTC-USD"`
+    pub symbols: HashMap<String, QuantityRule>,
+}
+
+impl Default for QuantityPolicy {
+    fn default() -> Self {
+        Self {
+            asset_classes: HashMap::from([(AssetClass::Crypto, QuantityRule::fractional(8))]),
+            symbols: HashMap::new(),
+        }
+    }
+}
+
+impl QuantityPolicy {
+    /// Override the rule for one ticker.
+    pub fn with_symbol_rule(mut self, ticker: impl Into<String>, rule: QuantityRule) -> Self {
+        self.symbols.insert(ticker.into(), rule);
+        self
+    }
+
+    /// Rule governing `symbol`'s order quantities.
+    pub fn rule_for(&self, symbol: &Symbol) -> QuantityRule {
+        self.symbols
+            .get(&symbol.symbol)
+            .or_else(|| self.asset_classes.get(&symbol.asset_class))
+            .copied()
+            .unwrap_or_else(QuantityRule::whole_units)
+    }
+
+    /// `quantity` of `symbol` rounded down to its rule, or `None` when below
+    /// the rule's minimum.
+    pub fn size(&self, symbol: &Symbol, quantity: Decimal) -> Option<Decimal> {
+        self.rule_for(symbol).apply(quantity)
+    }
+}
+
+/// When the engine hands a bar to the strategy. A bar is never delivered
+/// before its close; the two modes differ in how long after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BarDelivery {
+    /// At the bar's close: a day's bars arrive at the end of that day, and
+    /// orders placed on them fill on the next day's bar.
+    #[default]
+    OnClose,
+    /// At the next open: a day's bars arrive at the start of the following
+    /// simulated day, and market orders placed on them fill at that day's
+    /// open.
+    OnOpen,
+}
+
+/// How events that share a timestamp reach the strategy.
+///
+/// Under either policy, bars sharing a timestamp are delivered in the
+/// configured symbol order ([`BacktestConfig::symbols`], or the order feeds
+/// were added to a simulator), never by symbol name, and every bar is
+/// recorded in the context before the first callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventOrdering {
+    /// Each callback's actions are applied before the next callback, which
+    /// sees the orders placed so far. Results can depend on the configured
+    /// symbol order.
+    #[default]
+    Sequential,
+    /// Every callback sees the context as it was before any of them ran;
+    /// their actions are applied afterwards, in callback order. A strategy
+    /// that decides each symbol independently gets the same results
+    /// whatever the symbol order.
+    Snapshot,
+}
+
+/// How in-the-money options are settled at expiry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OptionSettlement {
+    /// Exercise and assignment deliver the underlying at the strike.
+    #[default]
+    Physical,
+    /// The intrinsic value is paid in cash and no shares change hands.
+    Cash,
 }
 
 fn default_max_volume_participation() -> Decimal {
@@ -99,6 +283,11 @@ impl Default for ExecutionSettings {
                 factor: Decimal::new(1, 4),
             },
             max_volume_participation: default_max_volume_participation(),
+            option_settlement: OptionSettlement::default(),
+            bar_delivery: BarDelivery::default(),
+            event_ordering: EventOrdering::default(),
+            quantity_policy: QuantityPolicy::default(),
+            buying_power: None,
         }
     }
 }
@@ -197,10 +386,23 @@ pub struct DataValidationSummary {
     pub sample_data: bool,
     pub critical_issues: Vec<String>,
     pub warnings: Vec<String>,
+    /// Providers named in the bars' provenance, sorted
+    #[serde(default)]
+    pub providers: Vec<String>,
+    /// Bars aggregated from a finer resolution
+    #[serde(default)]
+    pub resampled_bars: u64,
+    /// Bars with split- or dividend-adjusted prices
+    #[serde(default)]
+    pub adjusted_bars: u64,
+    /// Bars filling gaps rather than recording trading
+    #[serde(default)]
+    pub synthetic_bars: u64,
 }
 
 /// Data settings for backtest
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DataSettings {
     pub data_source: String,
     pub adjust_for_splits: bool,
@@ -210,6 +412,55 @@ pub struct DataSettings {
     pub max_bars_in_memory: usize,
     #[serde(default)]
     pub data_quality_mode: DataQualityMode,
+    /// How often the engine records the positions held into
+    /// [`BacktestResult::positions_history`].
+    #[serde(default)]
+    pub position_snapshots: SnapshotCadence,
+    /// What the engine does when a symbol stops receiving bars mid-run.
+    #[serde(default)]
+    pub staleness_policy: StalenessPolicy,
+    /// Consecutive session bars a symbol may miss before it counts as stale.
+    #[serde(default = "default_max_stale_bars")]
+    pub max_stale_bars: u32,
+    /// Time each phase of the run and store a [`RunProfile`](crate::RunProfile)
+    /// under `"profile"` in [`BacktestResult::metadata`].
+    #[serde(default)]
+    pub profile: bool,
+}
+
+fn default_max_stale_bars() -> u32 {
+    3
+}
+
+/// How the engine treats a symbol whose last bar is more than
+/// [`DataSettings::max_stale_bars`] sessions old.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StalenessPolicy {
+    /// Keep marking the position at its last close and list the symbol in
+    /// [`EquityCurvePoint::stale_symbols`].
+    #[default]
+    MarkStale,
+    /// Mark as above, log a warning, and reject new orders for the symbol
+    /// until its bars resume.
+    FreezeAndWarn,
+    /// Fail the run as soon as any symbol goes stale.
+    FailRun,
+}
+
+/// How often a run records a [`PositionsSnapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotCadence {
+    /// No position history, for memory-sensitive runs.
+    Off,
+    /// At the close of every simulated day.
+    #[default]
+    Daily,
+    /// At the last simulated day of each ISO week.
+    Weekly,
+    /// At the last simulated day of each month.
+    Monthly,
 }
 
 impl Default for DataSettings {
@@ -222,6 +473,218 @@ impl Default for DataSettings {
             survivor_bias_free: true,
             max_bars_in_memory: 10000,
             data_quality_mode: DataQualityMode::Warn,
+            position_snapshots: SnapshotCadence::default(),
+            staleness_policy: StalenessPolicy::default(),
+            max_stale_bars: default_max_stale_bars(),
+            profile: false,
+        }
+    }
+}
+
+/// Money moved into or out of the account during a backtest: one-off
+/// events plus recurring schedules such as "monthly $1,000". The engine
+/// applies each flow at the first step on or after its date.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CashFlowSettings {
+    pub events: Vec<CashFlowEvent>,
+    pub schedules: Vec<CashFlowSchedule>,
+}
+
+impl CashFlowSettings {
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty() && self.schedules.is_empty()
+    }
+
+    pub fn with_event(mut self, event: CashFlowEvent) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    pub fn with_schedule(mut self, schedule: CashFlowSchedule) -> Self {
+        self.schedules.push(schedule);
+        self
+    }
+
+    /// Events dated within `[start, end]`, with the schedules expanded, in
+    /// date order.
+    pub fn events_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<CashFlowEvent> {
+        let mut events: Vec<CashFlowEvent> = self
+            .events
+            .iter()
+            .filter(|event| event.date >= start && event.date <= end)
+            .cloned()
+            .chain(
+                self.schedules
+                    .iter()
+                    .flat_map(|schedule| schedule.events_between(start, end)),
+            )
+            .collect();
+        events.sort_by_key(|event| event.date);
+        events
+    }
+}
+
+/// One deposit or withdrawal of `amount`, which is always positive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CashFlowEvent {
+    pub date: DateTime<Utc>,
+    pub amount: Decimal,
+    #[serde(default)]
+    pub kind: CashFlowKind,
+}
+
+impl CashFlowEvent {
+    pub fn deposit(date: DateTime<Utc>, amount: Decimal) -> Self {
+        Self {
+            date,
+            amount,
+            kind: CashFlowKind::Deposit,
+        }
+    }
+
+    pub fn withdrawal(date: DateTime<Utc>, amount: Decimal) -> Self {
+        Self {
+            date,
+            amount,
+            kind: CashFlowKind::Withdrawal,
+        }
+    }
+
+    /// The change in cash: positive for deposits, negative for withdrawals.
+    pub fn signed_amount(&self) -> Decimal {
+        match self.kind {
+            CashFlowKind::Deposit => self.amount,
+            CashFlowKind::Withdrawal => -self.amount,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CashFlowKind {
+    #[default]
+    Deposit,
+    Withdrawal,
+}
+
+/// A deposit or withdrawal repeated every `frequency`. Parses from
+/// shorthand such as `"monthly $1,000"` or `"quarterly withdrawal 500"`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CashFlowSchedule {
+    pub frequency: CashFlowFrequency,
+    pub amount: Decimal,
+    #[serde(default)]
+    pub kind: CashFlowKind,
+    /// Date of the first flow; defaults to one period after the backtest
+    /// starts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<DateTime<Utc>>,
+    /// No flows after this date; defaults to the end of the backtest.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<DateTime<Utc>>,
+}
+
+impl CashFlowSchedule {
+    pub fn new(frequency: CashFlowFrequency, amount: Decimal, kind: CashFlowKind) -> Self {
+        Self {
+            frequency,
+            amount,
+            kind,
+            start: None,
+            end: None,
+        }
+    }
+
+    /// The schedule's flows within `[start, end]` of a backtest.
+    pub fn events_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<CashFlowEvent> {
+        let (anchor, first_period) = match self.start {
+            Some(first) => (first, 0),
+            None => (start, 1),
+        };
+        let last = self.end.map_or(end, |schedule_end| schedule_end.min(end));
+        // Each date is counted from the anchor rather than the previous one,
+        // so a month clamped to its last day does not pull later months back.
+        (first_period..)
+            .map_while(|periods| self.frequency.advance(anchor, periods))
+            .take_while(|date| *date <= last)
+            .filter(|date| *date >= start)
+            .map(|date| CashFlowEvent {
+                date,
+                amount: self.amount,
+                kind: self.kind,
+            })
+            .collect()
+    }
+}
+
+impl std::str::FromStr for CashFlowSchedule {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = value.split_whitespace().collect();
+        let (frequency, kind, amount) = match words.as_slice() {
+            [frequency, amount] => (frequency, CashFlowKind::Deposit, amount),
+            [frequency, kind, amount] => {
+                let kind = match kind.to_ascii_lowercase().as_str() {
+                    "deposit" => CashFlowKind::Deposit,
+                    "withdrawal" => CashFlowKind::Withdrawal,
+                    other => return Err(format!("unknown cash flow kind '{other}'")),
+                };
+                (frequency, kind, amount)
+            }
+            _ => {
+                return Err(format!(
+                    "expected '<frequency> [deposit|withdrawal] <amount>', got '{value}'"
+                ))
+            }
+        };
+        let frequency = match frequency.to_ascii_lowercase().as_str() {
+            "weekly" => CashFlowFrequency::Weekly,
+            "monthly" => CashFlowFrequency::Monthly,
+            "quarterly" => CashFlowFrequency::Quarterly,
+            "annually" | "yearly" => CashFlowFrequency::Annually,
+            other => return Err(format!("unknown cash flow frequency '{other}'")),
+        };
+        let digits: String = amount
+            .trim_start_matches('$')
+            .chars()
+            .filter(|c| *c != ',')
+            .collect();
+        let amount: Decimal = digits
+            .parse()
+            .map_err(|_| format!("invalid cash flow amount '{amount}'"))?;
+        if amount <= Decimal::ZERO {
+            return Err(format!("cash flow amount must be positive, got {amount}"));
+        }
+        Ok(Self::new(frequency, amount, kind))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CashFlowFrequency {
+    Weekly,
+    Monthly,
+    Quarterly,
+    Annually,
+}
+
+impl CashFlowFrequency {
+    /// `date` moved forward by `periods` periods. Months keep the day of the
+    /// month, clamped to the month's last day.
+    pub fn advance(self, date: DateTime<Utc>, periods: u32) -> Option<DateTime<Utc>> {
+        match self {
+            CashFlowFrequency::Weekly => {
+                date.checked_add_signed(chrono::Duration::weeks(periods as i64))
+            }
+            CashFlowFrequency::Monthly => date.checked_add_months(chrono::Months::new(periods)),
+            CashFlowFrequency::Quarterly => {
+                date.checked_add_months(chrono::Months::new(periods.checked_mul(3)?))
+            }
+            CashFlowFrequency::Annually => {
+                date.checked_add_months(chrono::Months::new(periods.checked_mul(12)?))
+            }
         }
     }
 }
@@ -340,12 +803,32 @@ pub struct BacktestResult {
     pub error_message: Option<String>,
     pub metadata: HashMap<String, serde_json::Value>,
     pub manifest: Option<RunManifest>,
+    /// `config` as a TOML manifest, so a saved result can be rerun from it.
+    #[serde(default)]
+    pub config_manifest: Option<String>,
+    /// The benchmark held over the run, scaled to the initial capital and
+    /// aligned with `equity_curve` as described in [`crate::benchmark`];
+    /// empty when the run had no benchmark.
+    #[serde(default)]
+    pub benchmark_curve: Vec<EquityCurvePoint>,
+    /// Daily portfolio return minus benchmark return on the aligned dates.
+    #[serde(default)]
+    pub excess_returns: Vec<(DateTime<Utc>, Decimal)>,
+    /// Positions held over the run, at the cadence set by
+    /// [`DataSettings::position_snapshots`]; empty when that is off.
+    #[serde(default)]
+    pub positions_history: Vec<PositionsSnapshot>,
+    /// Latency drift, market impact, slippage and commission attributed to
+    /// the run's fills.
+    #[serde(default)]
+    pub execution_report: ExecutionReport,
 }
 
 impl BacktestResult {
     pub fn new(config: BacktestConfig) -> Self {
         Self {
             id: config.id,
+            config_manifest: config.to_toml().ok(),
             config,
             status: BacktestStatus::Pending,
             start_time: Utc::now(),
@@ -360,6 +843,10 @@ impl BacktestResult {
             error_message: None,
             metadata: HashMap::new(),
             manifest: None,
+            benchmark_curve: Vec::new(),
+            excess_returns: Vec::new(),
+            positions_history: Vec::new(),
+            execution_report: ExecutionReport::default(),
         }
     }
 
@@ -375,7 +862,10 @@ impl BacktestResult {
         self.duration_seconds = Some((end_time - self.start_time).num_seconds() as u64);
 
         // Calculate performance metrics
-        self.performance_metrics = Some(PerformanceMetrics::calculate(&portfolio));
+        self.performance_metrics = Some(
+            PerformanceMetrics::calculate(&portfolio)
+                .with_money_weighted_return(&portfolio, self.config.start_date),
+        );
         self.final_portfolio = Some(portfolio);
         self.strategy_metrics = Some(metrics);
     }
@@ -385,6 +875,103 @@ impl BacktestResult {
         self.end_time = Some(Utc::now());
         self.error_message = Some(error);
     }
+
+    /// Writes the config that produced this result as a TOML or YAML
+    /// manifest, chosen by the file extension.
+    pub fn write_config_manifest(&self, path: impl AsRef<std::path::Path>) -> GbResult<()> {
+        self.config.write_manifest_file(path)
+    }
+
+    /// Fills `benchmark_curve` and `excess_returns` from benchmark closing
+    /// prices, aligned with the equity curve.
+    pub fn attach_benchmark(&mut self, prices: &[(DateTime<Utc>, Decimal)]) {
+        self.benchmark_curve = crate::benchmark::benchmark_equity_curve(
+            &self.equity_curve,
+            prices,
+            self.config.initial_capital,
+        );
+        self.excess_returns =
+            crate::benchmark::excess_returns(&self.equity_curve, &self.benchmark_curve);
+    }
+
+    /// Breaks the trade log down by symbol, side, month and hold time.
+    pub fn trade_attribution(&self) -> TradeAttribution {
+        TradeAttribution::from_trades(&self.trade_log)
+    }
+
+    /// The headline view of this result, for listings.
+    pub fn summary(&self) -> BacktestSummary {
+        let metrics = self.performance_metrics.as_ref();
+        BacktestSummary {
+            id: self.id,
+            name: self.config.name.clone(),
+            start_date: self.config.start_date,
+            end_date: self.config.end_date,
+            status: self.status,
+            created_at: self.start_time,
+            finished_at: self.end_time,
+            duration_seconds: self.duration_seconds,
+            initial_capital: self.config.initial_capital,
+            final_value: self
+                .final_portfolio
+                .as_ref()
+                .map(|portfolio| portfolio.total_equity),
+            total_return: metrics.map(|metrics| metrics.total_return),
+            annualized_return: metrics.map(|metrics| metrics.annualized_return),
+            sharpe_ratio: metrics.and_then(|metrics| metrics.sharpe_ratio),
+            max_drawdown: metrics.map(|metrics| metrics.max_drawdown),
+            trade_count: self.trade_log.len(),
+            order_event_count: self.order_events.len(),
+            equity_points: self.equity_curve.len(),
+            error_message: self.error_message.clone(),
+        }
+    }
+}
+
+/// Identity, status, headline metrics and row counts of a
+/// [`BacktestResult`], small enough to list hundreds of results without
+/// reading their equity curves and trade logs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestSummary {
+    pub id: BacktestId,
+    pub name: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub status: BacktestStatus,
+    /// When the backtest started running.
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_seconds: Option<u64>,
+    pub initial_capital: Decimal,
+    /// Final portfolio equity; `None` unless the run completed.
+    pub final_value: Option<Decimal>,
+    pub total_return: Option<Decimal>,
+    pub annualized_return: Option<Decimal>,
+    pub sharpe_ratio: Option<Decimal>,
+    pub max_drawdown: Option<Decimal>,
+    pub trade_count: usize,
+    pub order_event_count: usize,
+    pub equity_points: usize,
+    pub error_message: Option<String>,
+}
+
+/// A stored result at the level of detail the caller asked for. Serializes
+/// with a `detail` tag of `"summary"` or `"full"`, so API clients fetch
+/// either shape from one endpoint and tell them apart.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "detail", rename_all = "snake_case")]
+pub enum ResultView {
+    Summary(Box<BacktestSummary>),
+    Full(Box<BacktestResult>),
+}
+
+impl ResultView {
+    pub fn summary(&self) -> BacktestSummary {
+        match self {
+            Self::Summary(summary) => (**summary).clone(),
+            Self::Full(result) => result.summary(),
+        }
+    }
 }
 
 /// Performance metrics for backtest evaluation
@@ -413,6 +1000,13 @@ pub struct PerformanceMetrics {
     pub largest_loss: Decimal,
     pub total_trades: u64,
     pub total_commissions: Decimal,
+    /// Annualized internal rate of return on the initial capital and every
+    /// deposit and withdrawal, filled in by
+    /// [`with_money_weighted_return`](Self::with_money_weighted_return). The
+    /// other return metrics are time-weighted and ignore how much money was
+    /// invested when.
+    #[serde(default)]
+    pub money_weighted_return: Option<Decimal>,
 }
 
 impl PerformanceMetrics {
@@ -443,9 +1037,25 @@ impl PerformanceMetrics {
             largest_loss: Decimal::ZERO, // Requires trade data
             total_trades: 0,             // Requires trade data
             total_commissions: portfolio.total_commissions,
+            money_weighted_return: None,
         }
     }
 
+    /// Add the money-weighted return of `portfolio`, funded at `start` and
+    /// valued at its last daily record.
+    pub fn with_money_weighted_return(
+        mut self,
+        portfolio: &Portfolio,
+        start: DateTime<Utc>,
+    ) -> Self {
+        let end = portfolio
+            .daily_returns
+            .last()
+            .map_or(portfolio.last_updated, |last| last.date);
+        self.money_weighted_return = portfolio.money_weighted_return(start, end);
+        self
+    }
+
     /// Calculate performance metrics with trade data
     pub fn calculate_with_trades(portfolio: &Portfolio, trades: &[TradeRecord]) -> Self {
         let mut metrics = Self::calculate(portfolio);
@@ -565,8 +1175,9 @@ impl PerformanceMetrics {
             return None;
         }
 
+        // Annualizing a few days of large returns can leave no room to divide.
         let annual_return = Self::calculate_annualized_return(daily_returns);
-        Some(annual_return / max_drawdown.abs())
+        annual_return.checked_div(max_drawdown.abs())
     }
 
     /// Calculate maximum drawdown duration in days
@@ -821,6 +1432,30 @@ pub struct EquityCurvePoint {
     pub daily_return: Option<Decimal>,
     pub cumulative_return: Decimal,
     pub drawdown: Decimal,
+    /// Symbols valued at a close older than
+    /// [`DataSettings::max_stale_bars`] sessions.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stale_symbols: Vec<Symbol>,
+    /// Net deposits less withdrawals applied at this point; zero on points
+    /// without a flow.
+    #[serde(default, skip_serializing_if = "Decimal::is_zero")]
+    pub cash_flow: Decimal,
+}
+
+/// The open positions at a timestamp, taken after every fill at that
+/// timestamp was applied.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionsSnapshot {
+    pub timestamp: DateTime<Utc>,
+    /// Non-flat positions, ordered by symbol.
+    pub positions: Vec<PositionHolding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionHolding {
+    pub symbol: Symbol,
+    pub quantity: Decimal,
+    pub market_value: Decimal,
 }
 
 /// Trade record for analysis
@@ -839,6 +1474,216 @@ pub struct TradeRecord {
     pub duration_hours: Option<f64>,
     pub strategy_id: String,
     pub tags: Vec<String>,
+    /// Metadata the strategy attached to the order, see
+    /// [`crate::orders::Order::with_metadata`].
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub metadata: serde_json::Value,
+}
+
+/// PnL breakdown of a trade log by symbol, side, exit month, hold time and tag,
+/// to see which symbols or regimes drive the aggregate [`StrategyMetrics`].
+///
+/// Only closed trades (with an `exit_time`) are attributed; open trades are
+/// counted in `open_trades` and otherwise left out.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TradeAttribution {
+    pub closed_trades: u64,
+    pub open_trades: u64,
+    /// Per symbol, ordered by symbol.
+    pub by_symbol: Vec<SymbolAttribution>,
+    pub long: AttributionStats,
+    pub short: AttributionStats,
+    /// PnL by the calendar month trades closed in, one row per year.
+    pub monthly_pnl: Vec<MonthlyPnlRow>,
+    /// One entry per [`HoldTimeBucket`], in bucket order.
+    pub by_hold_time: Vec<HoldTimeAttribution>,
+    /// Per tag, ordered by tag. A trade with several tags counts towards
+    /// each of them.
+    #[serde(default)]
+    pub by_tag: Vec<TagAttribution>,
+}
+
+/// Totals over a group of closed trades. A trade wins when its PnL is
+/// positive, as in [`PerformanceMetrics::win_rate`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AttributionStats {
+    pub trades: u64,
+    pub winning_trades: u64,
+    pub pnl: Decimal,
+    pub commission: Decimal,
+    pub win_rate: Decimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolAttribution {
+    pub symbol: Symbol,
+    pub stats: AttributionStats,
+}
+
+/// A year of monthly PnL; `months[0]` is January.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonthlyPnlRow {
+    pub year: i32,
+    pub months: [Decimal; 12],
+    pub total: Decimal,
+}
+
+/// How long a trade was held, from entry to exit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HoldTimeBucket {
+    /// Under one day.
+    UnderOneDay,
+    /// One to five days, inclusive.
+    OneToFiveDays,
+    /// Over five days.
+    OverFiveDays,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagAttribution {
+    pub tag: String,
+    pub stats: AttributionStats,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HoldTimeAttribution {
+    pub bucket: HoldTimeBucket,
+    pub stats: AttributionStats,
+}
+
+impl AttributionStats {
+    fn add(&mut self, trade: &TradeRecord) {
+        let pnl = trade.pnl.unwrap_or(Decimal::ZERO);
+        self.trades += 1;
+        if pnl > Decimal::ZERO {
+            self.winning_trades += 1;
+        }
+        self.pnl += pnl;
+        self.commission += trade.commission;
+        self.win_rate = Decimal::from(self.winning_trades) / Decimal::from(self.trades);
+    }
+}
+
+impl HoldTimeBucket {
+    pub const ALL: [HoldTimeBucket; 3] = [
+        HoldTimeBucket::UnderOneDay,
+        HoldTimeBucket::OneToFiveDays,
+        HoldTimeBucket::OverFiveDays,
+    ];
+
+    pub fn for_duration(held: chrono::Duration) -> Self {
+        if held < chrono::Duration::days(1) {
+            HoldTimeBucket::UnderOneDay
+        } else if held <= chrono::Duration::days(5) {
+            HoldTimeBucket::OneToFiveDays
+        } else {
+            HoldTimeBucket::OverFiveDays
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            HoldTimeBucket::UnderOneDay => "<1d",
+            HoldTimeBucket::OneToFiveDays => "1-5d",
+            HoldTimeBucket::OverFiveDays => ">5d",
+        }
+    }
+}
+
+impl TradeAttribution {
+    pub fn from_trades(trades: &[TradeRecord]) -> Self {
+        let mut attribution = Self {
+            by_hold_time: HoldTimeBucket::ALL
+                .into_iter()
+                .map(|bucket| HoldTimeAttribution {
+                    bucket,
+                    stats: AttributionStats::default(),
+                })
+                .collect(),
+            ..Self::default()
+        };
+        let mut by_symbol: HashMap<Symbol, AttributionStats> = HashMap::new();
+        let mut by_year: HashMap<i32, [Decimal; 12]> = HashMap::new();
+        let mut by_tag: HashMap<&str, AttributionStats> = HashMap::new();
+
+        for trade in trades {
+            let Some(exit_time) = trade.exit_time else {
+                attribution.open_trades += 1;
+                continue;
+            };
+            attribution.closed_trades += 1;
+            by_symbol
+                .entry(trade.symbol.clone())
+                .or_default()
+                .add(trade);
+            match trade.side {
+                crate::orders::Side::Buy => attribution.long.add(trade),
+                crate::orders::Side::Sell => attribution.short.add(trade),
+            }
+            let months = by_year.entry(exit_time.year()).or_default();
+            months[exit_time.month0() as usize] += trade.pnl.unwrap_or(Decimal::ZERO);
+            let bucket = HoldTimeBucket::for_duration(exit_time - trade.entry_time);
+            attribution
+                .by_hold_time
+                .iter_mut()
+                .find(|entry| entry.bucket == bucket)
+                .expect("every bucket has an entry")
+                .stats
+                .add(trade);
+            for tag in &trade.tags {
+                by_tag.entry(tag.as_str()).or_default().add(trade);
+            }
+        }
+
+        attribution.by_symbol = by_symbol
+            .into_iter()
+            .map(|(symbol, stats)| SymbolAttribution { symbol, stats })
+            .collect();
+        attribution
+            .by_symbol
+            .sort_by_key(|entry| entry.symbol.to_string());
+        attribution.monthly_pnl = by_year
+            .into_iter()
+            .map(|(year, months)| MonthlyPnlRow {
+                year,
+                total: months.iter().sum(),
+                months,
+            })
+            .collect();
+        attribution.monthly_pnl.sort_by_key(|row| row.year);
+        attribution.by_tag = by_tag
+            .into_iter()
+            .map(|(tag, stats)| TagAttribution {
+                tag: tag.to_string(),
+                stats,
+            })
+            .collect();
+        attribution.by_tag.sort_by(|a, b| a.tag.cmp(&b.tag));
+        attribution
+    }
+
+    pub fn symbol(&self, symbol: &Symbol) -> Option<&AttributionStats> {
+        self.by_symbol
+            .iter()
+            .find(|entry| &entry.symbol == symbol)
+            .map(|entry| &entry.stats)
+    }
+
+    pub fn tag(&self, tag: &str) -> Option<&AttributionStats> {
+        self.by_tag
+            .iter()
+            .find(|entry| entry.tag == tag)
+            .map(|entry| &entry.stats)
+    }
+
+    pub fn hold_time(&self, bucket: HoldTimeBucket) -> &AttributionStats {
+        &self
+            .by_hold_time
+            .iter()
+            .find(|entry| entry.bucket == bucket)
+            .expect("every bucket has an entry")
+            .stats
+    }
 }
 
 /// Backtest event for real-time monitoring
@@ -861,6 +1706,30 @@ pub enum BacktestEvent {
         backtest_id: BacktestId,
         trade: TradeRecord,
     },
+    PositionsSnapshot {
+        backtest_id: BacktestId,
+        snapshot: PositionsSnapshot,
+    },
+    /// A symbol went stale: its last bar is `missing_bars` sessions old.
+    DataStale {
+        backtest_id: BacktestId,
+        symbol: Symbol,
+        last_bar_at: DateTime<Utc>,
+        missing_bars: u32,
+    },
+    /// Equity closed the day below the maintenance requirement of the
+    /// margin account; a broker would start liquidating.
+    MarginCall {
+        backtest_id: BacktestId,
+        timestamp: DateTime<Utc>,
+        call: MarginCall,
+    },
+    /// Where the run spent its time; sent just before `Completed` when
+    /// [`DataSettings::profile`] is on.
+    Profile {
+        backtest_id: BacktestId,
+        profile: RunProfile,
+    },
     Completed {
         backtest_id: BacktestId,
         result: BacktestResult,
@@ -880,6 +1749,97 @@ mod tests {
     use rust_decimal::Decimal;
     use serde_json::json;
 
+    #[test]
+    fn quantity_policy_rounds_equities_down_to_whole_shares() {
+        let policy = QuantityPolicy::default();
+        let aapl = Symbol::equity("AAPL");
+
+        assert_eq!(
+            policy.size(&aapl, Decimal::new(125, 1)),
+            Some(Decimal::from(12))
+        );
+        assert_eq!(policy.size(&aapl, Decimal::new(9, 1)), None);
+    }
+
+    #[test]
+    fn quantity_policy_passes_crypto_fractions_through() {
+        let policy = QuantityPolicy::default();
+        let btc = Symbol::crypto("BTC-USD");
+
+        assert_eq!(
+            policy.size(&btc, Decimal::new(3751, 4)),
+            Some(Decimal::new(3751, 4))
+        );
+        assert_eq!(
+            policy.size(&btc, Decimal::new(123_456_789_123, 12)),
+            Some(Decimal::new(12_345_678, 8))
+        );
+        assert_eq!(policy.size(&btc, Decimal::new(1, 9)), None);
+    }
+
+    #[test]
+    fn quantity_policy_applies_symbol_overrides_and_lot_sizes() {
+        let policy = QuantityPolicy::default().with_symbol_rule(
+            "ETH-USD",
+            QuantityRule {
+                lot_size: Decimal::new(5, 2),
+                min_quantity: Decimal::new(1, 1),
+                quantity_precision: 2,
+            },
+        );
+        let eth = Symbol::crypto("ETH-USD");
+
+        assert_eq!(
+            policy.size(&eth, Decimal::new(1237, 3)),
+            Some(Decimal::new(120, 2))
+        );
+        assert_eq!(policy.size(&eth, Decimal::new(5, 2)), None);
+        assert_eq!(
+            policy.rule_for(&Symbol::crypto("BTC-USD")),
+            QuantityRule::fractional(8)
+        );
+    }
+
+    #[test]
+    fn monthly_schedule_parses_and_keeps_the_day_of_month() {
+        let schedule: CashFlowSchedule = "monthly $1,000".parse().unwrap();
+        assert_eq!(
+            schedule,
+            CashFlowSchedule::new(
+                CashFlowFrequency::Monthly,
+                Decimal::from(1000),
+                CashFlowKind::Deposit
+            )
+        );
+        let withdrawal: CashFlowSchedule = "quarterly withdrawal 250.50".parse().unwrap();
+        assert_eq!(withdrawal.kind, CashFlowKind::Withdrawal);
+        assert_eq!(withdrawal.amount, Decimal::new(25050, 2));
+        assert!("fortnightly $10".parse::<CashFlowSchedule>().is_err());
+        assert!("monthly -$10".parse::<CashFlowSchedule>().is_err());
+
+        let day = |month, day| {
+            chrono::TimeZone::with_ymd_and_hms(&Utc, 2024, month, day, 0, 0, 0).unwrap()
+        };
+        let settings = CashFlowSettings::default()
+            .with_schedule(schedule)
+            .with_event(CashFlowEvent::withdrawal(day(3, 15), Decimal::from(500)));
+        let dates: Vec<_> = settings
+            .events_between(day(1, 31), day(5, 1))
+            .iter()
+            .map(|event| (event.date, event.signed_amount()))
+            .collect();
+        // February clamps to the 29th without pulling later months back.
+        assert_eq!(
+            dates,
+            [
+                (day(2, 29), Decimal::from(1000)),
+                (day(3, 15), Decimal::from(-500)),
+                (day(3, 31), Decimal::from(1000)),
+                (day(4, 30), Decimal::from(1000)),
+            ]
+        );
+    }
+
     fn make_daily_return_with_value(
         base: chrono::DateTime<Utc>,
         day_offset: i64,
@@ -973,6 +1933,33 @@ mod tests {
         assert_eq!(annualized, Decimal::ZERO);
     }
 
+    #[test]
+    fn result_views_are_tagged_with_their_detail_level() {
+        let strategy = StrategyConfig::new("bah".into(), "Buy and hold".into());
+        let mut result = BacktestResult::new(BacktestConfig::new("listed".into(), strategy));
+        result.mark_completed(
+            Portfolio::new("test".into(), Decimal::from(100_000)),
+            StrategyMetrics::new("bah".into()),
+        );
+        let summary = result.summary();
+        assert_eq!(summary.name, "listed");
+        assert_eq!(summary.status, BacktestStatus::Completed);
+        assert_eq!(summary.final_value, Some(Decimal::from(100_000)));
+        assert_eq!(summary.total_return, Some(Decimal::ZERO));
+
+        let view = ResultView::Summary(Box::new(summary.clone()));
+        let value = serde_json::to_value(&view).unwrap();
+        assert_eq!(value["detail"], "summary");
+        assert_eq!(value["name"], "listed");
+        assert_eq!(serde_json::from_value::<ResultView>(value).unwrap(), view);
+
+        let full = ResultView::Full(Box::new(result));
+        let value = serde_json::to_value(&full).unwrap();
+        assert_eq!(value["detail"], "full");
+        let full: ResultView = serde_json::from_value(value).unwrap();
+        assert_eq!(full.summary(), summary);
+    }
+
     #[test]
     fn run_manifest_round_trips_through_json() {
         let manifest = RunManifest {
@@ -1093,4 +2080,135 @@ mod tests {
             .to_string();
         assert!(error.contains("replay_request"));
     }
+
+    fn closed_trade(
+        symbol: &str,
+        side: crate::orders::Side,
+        entry: &str,
+        exit: Option<&str>,
+        pnl: Option<i64>,
+        commission: i64,
+    ) -> TradeRecord {
+        let at = |value: &str| {
+            chrono::DateTime::parse_from_rfc3339(value)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        TradeRecord {
+            id: Uuid::new_v4(),
+            symbol: Symbol::equity(symbol),
+            entry_time: at(entry),
+            exit_time: exit.map(at),
+            entry_price: Decimal::from(100),
+            exit_price: exit.map(|_| Decimal::from(101)),
+            quantity: Decimal::from(10),
+            side,
+            pnl: pnl.map(Decimal::from),
+            commission: Decimal::from(commission),
+            duration_hours: None,
+            strategy_id: "attribution".to_string(),
+            tags: Vec::new(),
+            metadata: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn trade_attribution_totals_each_bucket() {
+        use crate::orders::Side;
+        let trades = vec![
+            closed_trade(
+                "AAPL",
+                Side::Buy,
+                "2024-01-02T10:00:00Z",
+                Some("2024-01-02T15:00:00Z"),
+                Some(100),
+                1,
+            ),
+            closed_trade(
+                "AAPL",
+                Side::Sell,
+                "2024-01-10T15:00:00Z",
+                Some("2024-01-13T15:00:00Z"),
+                Some(-40),
+                1,
+            ),
+            closed_trade(
+                "MSFT",
+                Side::Buy,
+                "2024-01-25T15:00:00Z",
+                Some("2024-02-05T15:00:00Z"),
+                Some(250),
+                2,
+            ),
+            closed_trade(
+                "MSFT",
+                Side::Buy,
+                "2024-12-30T15:00:00Z",
+                Some("2025-01-02T15:00:00Z"),
+                Some(30),
+                1,
+            ),
+            closed_trade("NVDA", Side::Buy, "2024-12-31T15:00:00Z", None, None, 1),
+        ];
+        let mut result = BacktestResult::new(BacktestConfig::new(
+            "attribution".to_string(),
+            StrategyConfig::new("attribution".to_string(), "Attribution".to_string()),
+        ));
+        result.trade_log = trades;
+        let attribution = result.trade_attribution();
+
+        assert_eq!(attribution.closed_trades, 4);
+        assert_eq!(attribution.open_trades, 1);
+
+        assert_eq!(attribution.by_symbol.len(), 2);
+        assert!(attribution.symbol(&Symbol::equity("NVDA")).is_none());
+        let aapl = attribution.symbol(&Symbol::equity("AAPL")).unwrap();
+        assert_eq!((aapl.trades, aapl.winning_trades), (2, 1));
+        assert_eq!(aapl.pnl, Decimal::from(60));
+        assert_eq!(aapl.commission, Decimal::from(2));
+        assert_eq!(aapl.win_rate, Decimal::new(5, 1));
+        let msft = attribution.symbol(&Symbol::equity("MSFT")).unwrap();
+        assert_eq!((msft.trades, msft.winning_trades), (2, 2));
+        assert_eq!(msft.pnl, Decimal::from(280));
+        assert_eq!(msft.commission, Decimal::from(3));
+        assert_eq!(msft.win_rate, Decimal::ONE);
+
+        assert_eq!(attribution.long.trades, 3);
+        assert_eq!(attribution.long.pnl, Decimal::from(380));
+        assert_eq!(attribution.long.commission, Decimal::from(4));
+        assert_eq!(attribution.short.trades, 1);
+        assert_eq!(attribution.short.pnl, Decimal::from(-40));
+        assert_eq!(attribution.short.win_rate, Decimal::ZERO);
+
+        let years: Vec<_> = attribution.monthly_pnl.iter().map(|row| row.year).collect();
+        assert_eq!(years, [2024, 2025]);
+        let months_2024 = &attribution.monthly_pnl[0];
+        assert_eq!(months_2024.months[0], Decimal::from(60));
+        assert_eq!(months_2024.months[1], Decimal::from(250));
+        assert!(months_2024.months[2..].iter().all(|pnl| pnl.is_zero()));
+        assert_eq!(months_2024.total, Decimal::from(310));
+        assert_eq!(attribution.monthly_pnl[1].months[0], Decimal::from(30));
+        assert_eq!(attribution.monthly_pnl[1].total, Decimal::from(30));
+
+        let under_one_day = attribution.hold_time(HoldTimeBucket::UnderOneDay);
+        assert_eq!(
+            (under_one_day.trades, under_one_day.pnl),
+            (1, Decimal::from(100))
+        );
+        let one_to_five = attribution.hold_time(HoldTimeBucket::OneToFiveDays);
+        assert_eq!((one_to_five.trades, one_to_five.winning_trades), (2, 1));
+        assert_eq!(one_to_five.pnl, Decimal::from(-10));
+        let over_five = attribution.hold_time(HoldTimeBucket::OverFiveDays);
+        assert_eq!((over_five.trades, over_five.pnl), (1, Decimal::from(250)));
+        assert_eq!(
+            HoldTimeBucket::for_duration(Duration::days(5)),
+            HoldTimeBucket::OneToFiveDays
+        );
+
+        let json = serde_json::to_string(&attribution).unwrap();
+        assert_eq!(
+            serde_json::from_str::<TradeAttribution>(&json).unwrap(),
+            attribution
+        );
+    }
 }
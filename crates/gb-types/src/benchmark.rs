@@ -0,0 +1,275 @@
+//! Benchmark series aligned with a portfolio's equity curve.
+//!
+//! Every benchmark-relative series goes through [`align_series`], so charts
+//! and rolling statistics agree on which dates they cover. Its rules:
+//!
+//! - Each series is keyed by UTC calendar date, keeping the last value of a
+//!   date.
+//! - Dates before both series have started, or after either has ended, are
+//!   dropped.
+//! - On a date only one series has (a holiday the benchmark skipped but the
+//!   portfolio traded through, or the reverse), the other series carries its
+//!   last value forward.
+
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::backtest::EquityCurvePoint;
+
+/// Portfolio and benchmark values on one aligned date.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlignedValues {
+    /// The portfolio's timestamp on dates it has, else the benchmark's.
+    pub timestamp: DateTime<Utc>,
+    pub portfolio: Decimal,
+    pub benchmark: Decimal,
+}
+
+/// Align a portfolio series and a benchmark series by date, following the
+/// rules in the module docs. Neither input needs to be sorted.
+pub fn align_series(
+    portfolio: &[(DateTime<Utc>, Decimal)],
+    benchmark: &[(DateTime<Utc>, Decimal)],
+) -> Vec<AlignedValues> {
+    let portfolio = by_date(portfolio);
+    let benchmark = by_date(benchmark);
+    let (Some(start), Some(end)) = (
+        portfolio
+            .keys()
+            .next()
+            .max(benchmark.keys().next())
+            .copied(),
+        portfolio
+            .keys()
+            .next_back()
+            .min(benchmark.keys().next_back())
+            .copied(),
+    ) else {
+        return Vec::new();
+    };
+    if start > end {
+        return Vec::new();
+    }
+
+    let mut dates: Vec<NaiveDate> = portfolio
+        .range(start..=end)
+        .chain(benchmark.range(start..=end))
+        .map(|(date, _)| *date)
+        .collect();
+    dates.sort();
+    dates.dedup();
+
+    dates
+        .into_iter()
+        .filter_map(|date| {
+            let (portfolio_at, portfolio_value) = portfolio.range(..=date).next_back()?.1;
+            let (benchmark_at, benchmark_value) = benchmark.range(..=date).next_back()?.1;
+            let timestamp = if portfolio.contains_key(&date) {
+                *portfolio_at
+            } else {
+                *benchmark_at
+            };
+            Some(AlignedValues {
+                timestamp,
+                portfolio: *portfolio_value,
+                benchmark: *benchmark_value,
+            })
+        })
+        .collect()
+}
+
+/// The benchmark held from the first aligned date, as an equity curve
+/// starting at `initial_capital` on the aligned dates. Empty when
+/// the series do not overlap or the first benchmark price is not positive.
+pub fn benchmark_equity_curve(
+    portfolio_curve: &[EquityCurvePoint],
+    benchmark_prices: &[(DateTime<Utc>, Decimal)],
+    initial_capital: Decimal,
+) -> Vec<EquityCurvePoint> {
+    let aligned = align_series(&portfolio_values(portfolio_curve), benchmark_prices);
+    let Some(base) = aligned.first().map(|values| values.benchmark) else {
+        return Vec::new();
+    };
+    if base <= Decimal::ZERO || initial_capital <= Decimal::ZERO {
+        return Vec::new();
+    }
+
+    let mut peak = Decimal::ZERO;
+    let mut previous: Option<Decimal> = None;
+    aligned
+        .into_iter()
+        .map(|values| {
+            let value = initial_capital * values.benchmark / base;
+            peak = peak.max(value);
+            let point = EquityCurvePoint {
+                timestamp: values.timestamp,
+                portfolio_value: value,
+                cash: Decimal::ZERO,
+                positions_value: value,
+                total_pnl: value - initial_capital,
+                daily_return: previous.map(|previous| simple_return(previous, value)),
+                cumulative_return: value / initial_capital - Decimal::ONE,
+                drawdown: if peak > Decimal::ZERO {
+                    (peak - value) / peak
+                } else {
+                    Decimal::ZERO
+                },
+                stale_symbols: Vec::new(),
+                cash_flow: Decimal::ZERO,
+            };
+            previous = Some(value);
+            point
+        })
+        .collect()
+}
+
+/// Daily active return: the portfolio's return minus the benchmark's between
+/// consecutive aligned dates.
+pub fn excess_returns(
+    portfolio_curve: &[EquityCurvePoint],
+    benchmark_curve: &[EquityCurvePoint],
+) -> Vec<(DateTime<Utc>, Decimal)> {
+    let aligned = align_series(
+        &portfolio_values(portfolio_curve),
+        &portfolio_values(benchmark_curve),
+    );
+    aligned
+        .windows(2)
+        .map(|pair| {
+            let portfolio = simple_return(pair[0].portfolio, pair[1].portfolio);
+            let benchmark = simple_return(pair[0].benchmark, pair[1].benchmark);
+            (pair[1].timestamp, portfolio - benchmark)
+        })
+        .collect()
+}
+
+fn portfolio_values(curve: &[EquityCurvePoint]) -> Vec<(DateTime<Utc>, Decimal)> {
+    curve
+        .iter()
+        .map(|point| (point.timestamp, point.portfolio_value))
+        .collect()
+}
+
+fn by_date(series: &[(DateTime<Utc>, Decimal)]) -> BTreeMap<NaiveDate, (DateTime<Utc>, Decimal)> {
+    let mut dates: BTreeMap<NaiveDate, (DateTime<Utc>, Decimal)> = BTreeMap::new();
+    for (timestamp, value) in series {
+        let entry = dates
+            .entry(timestamp.date_naive())
+            .or_insert((*timestamp, *value));
+        if *timestamp >= entry.0 {
+            *entry = (*timestamp, *value);
+        }
+    }
+    dates
+}
+
+fn simple_return(previous: Decimal, current: Decimal) -> Decimal {
+    if previous.is_zero() {
+        Decimal::ZERO
+    } else {
+        current / previous - Decimal::ONE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    fn close(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 7, day, 20, 0, 0).unwrap()
+    }
+
+    fn curve(values: &[(u32, Decimal)]) -> Vec<EquityCurvePoint> {
+        values
+            .iter()
+            .map(|(day, value)| EquityCurvePoint {
+                timestamp: close(*day),
+                portfolio_value: *value,
+                cash: *value,
+                positions_value: Decimal::ZERO,
+                total_pnl: *value - dec!(1000),
+                daily_return: None,
+                cumulative_return: *value / dec!(1000) - Decimal::ONE,
+                drawdown: Decimal::ZERO,
+                stale_symbols: Vec::new(),
+                cash_flow: Decimal::ZERO,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn misaligned_calendars_drop_the_edges_and_forward_fill_gaps() {
+        // The portfolio trades through July 4th, which the benchmark skips,
+        // and the benchmark has a day either side of the portfolio's run.
+        let portfolio = curve(&[
+            (2, dec!(1000)),
+            (3, dec!(1010)),
+            (4, dec!(1020)),
+            (5, dec!(1030)),
+        ]);
+        let benchmark = vec![
+            (close(1), dec!(48)),
+            (close(2), dec!(50)),
+            (close(3), dec!(51)),
+            (close(5), dec!(49)),
+            (close(8), dec!(52)),
+        ];
+
+        let aligned = align_series(&portfolio_values(&portfolio), &benchmark);
+        let dates: Vec<_> = aligned.iter().map(|values| values.timestamp).collect();
+        assert_eq!(dates, [close(2), close(3), close(4), close(5)]);
+        let prices: Vec<_> = aligned.iter().map(|values| values.benchmark).collect();
+        assert_eq!(prices, [dec!(50), dec!(51), dec!(51), dec!(49)]);
+
+        let benchmark_curve = benchmark_equity_curve(&portfolio, &benchmark, dec!(1000));
+        let values: Vec<_> = benchmark_curve
+            .iter()
+            .map(|point| point.portfolio_value)
+            .collect();
+        assert_eq!(values, [dec!(1000), dec!(1020), dec!(1020), dec!(980)]);
+        assert_eq!(benchmark_curve[0].daily_return, None);
+        assert_eq!(benchmark_curve[1].daily_return, Some(dec!(0.02)));
+        assert_eq!(benchmark_curve[2].daily_return, Some(Decimal::ZERO));
+        assert_eq!(benchmark_curve[3].cumulative_return, dec!(-0.02));
+        assert_eq!(
+            benchmark_curve[3].drawdown,
+            (dec!(1020) - dec!(980)) / dec!(1020)
+        );
+
+        let excess = excess_returns(&portfolio, &benchmark_curve);
+        assert_eq!(excess.len(), 3);
+        assert_eq!(excess[0], (close(3), dec!(0.01) - dec!(0.02)));
+        // The forward-filled holiday has a flat benchmark.
+        assert_eq!(
+            excess[1],
+            (close(4), dec!(1020) / dec!(1010) - Decimal::ONE)
+        );
+        assert_eq!(
+            excess[2].1,
+            (dec!(1030) / dec!(1020) - Decimal::ONE) - (dec!(980) / dec!(1020) - Decimal::ONE)
+        );
+    }
+
+    #[test]
+    fn benchmark_only_dates_carry_the_portfolio_forward() {
+        let portfolio = vec![(close(1), dec!(100)), (close(3), dec!(110))];
+        let benchmark = vec![
+            (close(1), dec!(10)),
+            (close(2), dec!(11)),
+            (close(3), dec!(12)),
+        ];
+
+        let aligned = align_series(&portfolio, &benchmark);
+        assert_eq!(aligned.len(), 3);
+        assert_eq!(aligned[1].timestamp, close(2));
+        assert_eq!(aligned[1].portfolio, dec!(100));
+        assert_eq!(aligned[1].benchmark, dec!(11));
+
+        assert!(align_series(&portfolio, &[]).is_empty());
+        assert!(align_series(&portfolio, &[(close(9), dec!(1))]).is_empty());
+    }
+}
@@ -1,5 +1,17 @@
+//! Error types shared across GlowBack crates.
+//!
+//! Every error has a stable, machine-readable [`code`](GbError::code) such as
+//! `"data.rate_limited"` for callers that branch on the kind of failure (the
+//! API layer, the Python bindings), and
+//! [`is_retryable`](GbError::is_retryable) says whether repeating the call
+//! unchanged may succeed. Underlying errors are kept as the `source()` so the
+//! whole chain can be reported.
+
 use thiserror::Error;
 
+/// An underlying error kept as the `source()` of a GlowBack error.
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
 /// Main error type for the GlowBack system
 #[derive(Error, Debug)]
 pub enum GbError {
@@ -76,6 +88,117 @@ pub enum DataError {
     
     #[error("Query execution failed: {query}, error: {error}")]
     QueryFailed { query: String, error: String },
+    
+    #[error("File not found: {path}")]
+    FileNotFound { path: String },
+    
+    #[error("Unreadable data file {path}: {source}")]
+    CorruptFile {
+        path: String,
+        #[source]
+        source: BoxError,
+    },
+    
+    #[error("Failed to write data file {path}: {source}")]
+    WriteFailed {
+        path: String,
+        #[source]
+        source: BoxError,
+    },
+    
+    #[error("Rate limited by {provider}: {message}")]
+    RateLimited {
+        provider: String,
+        message: String,
+        retry_after_seconds: Option<u64>,
+    },
+    
+    #[error("{provider} does not serve {resolution} bars")]
+    UnsupportedResolution { provider: String, resolution: String },
+    
+    #[error("Request to {provider} failed: {source}")]
+    RequestFailed {
+        provider: String,
+        #[source]
+        source: BoxError,
+    },
+    
+    #[error("{provider} responded with HTTP {status}")]
+    HttpStatus { provider: String, status: u16 },
+    
+    #[error("{provider} error: {message}")]
+    ProviderError { provider: String, message: String },
+    
+    #[error("{provider} has no ticker for {symbol}; add an alias to its symbol map")]
+    UnmappedSymbol { provider: String, symbol: String },
+}
+
+impl DataError {
+    /// Stable machine-readable code, e.g. `"data.file_not_found"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            DataError::SourceNotFound(_) => "data.source_not_found",
+            DataError::SymbolNotFound { .. } => "data.symbol_not_found",
+            DataError::NoDataInRange { .. } => "data.no_data_in_range",
+            DataError::InvalidFormat { .. } => "data.invalid_format",
+            DataError::Corruption { .. } => "data.corruption",
+            DataError::InsufficientData { .. } => "data.insufficient_data",
+            DataError::LoadingFailed { .. } => "data.loading_failed",
+            DataError::ParseError { .. } => "data.parse_error",
+            DataError::DatabaseConnection { .. } => "data.database_connection",
+            DataError::QueryFailed { .. } => "data.query_failed",
+            DataError::FileNotFound { .. } => "data.file_not_found",
+            DataError::CorruptFile { .. } => "data.corrupt_file",
+            DataError::WriteFailed { .. } => "data.write_failed",
+            DataError::RateLimited { .. } => "data.rate_limited",
+            DataError::UnsupportedResolution { .. } => "data.unsupported_resolution",
+            DataError::RequestFailed { .. } => "data.request_failed",
+            DataError::HttpStatus { .. } => "data.http_status",
+            DataError::ProviderError { .. } => "data.provider_error",
+            DataError::UnmappedSymbol { .. } => "data.unmapped_symbol",
+        }
+    }
+    
+    /// Whether the same request may succeed later: rate limits, transport
+    /// failures, server errors and lost database connections.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            DataError::RateLimited { .. }
+            | DataError::RequestFailed { .. }
+            | DataError::DatabaseConnection { .. } => true,
+            DataError::HttpStatus { status, .. } => *status == 429 || *status >= 500,
+            _ => false,
+        }
+    }
+    
+    /// `path` could not be read as the data file it should be.
+    pub fn corrupt_file(path: &std::path::Path, source: impl Into<BoxError>) -> Self {
+        DataError::CorruptFile {
+            path: path.display().to_string(),
+            source: source.into(),
+        }
+    }
+    
+    /// Writing the data file at `path` failed.
+    pub fn write_failed(path: &std::path::Path, source: impl Into<BoxError>) -> Self {
+        DataError::WriteFailed {
+            path: path.display().to_string(),
+            source: source.into(),
+        }
+    }
+    
+    /// The error for opening `path` failing with `error`: a missing file
+    /// becomes [`DataError::FileNotFound`], anything else stays an I/O error.
+    pub fn from_io(path: &std::path::Path, error: std::io::Error) -> GbError {
+        if error.kind() == std::io::ErrorKind::NotFound {
+            DataError::FileNotFound {
+                path: path.display().to_string(),
+            }
+            .into()
+        } else {
+            GbError::Io(error)
+        }
+    }
 }
 
 /// Strategy-related errors
@@ -106,6 +229,22 @@ pub enum StrategyError {
     Timeout { timeout_seconds: u64 },
 }
 
+impl StrategyError {
+    /// Stable machine-readable code, e.g. `"strategy.timeout"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            StrategyError::NotFound { .. } => "strategy.not_found",
+            StrategyError::InitializationFailed { .. } => "strategy.initialization_failed",
+            StrategyError::ExecutionError { .. } => "strategy.execution_error",
+            StrategyError::InvalidConfig { .. } => "strategy.invalid_config",
+            StrategyError::ParameterError { .. } => "strategy.parameter_error",
+            StrategyError::StateError { .. } => "strategy.state_error",
+            StrategyError::CompilationError { .. } => "strategy.compilation_error",
+            StrategyError::Timeout { .. } => "strategy.timeout",
+        }
+    }
+}
+
 /// Order-related errors
 #[derive(Error, Debug)]
 pub enum OrderError {
@@ -143,6 +282,24 @@ pub enum OrderError {
     UnsupportedOrderType { order_type: String },
 }
 
+impl OrderError {
+    /// Stable machine-readable code, e.g. `"order.insufficient_funds"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            OrderError::NotFound { .. } => "order.not_found",
+            OrderError::Invalid { .. } => "order.invalid",
+            OrderError::Rejected { .. } => "order.rejected",
+            OrderError::InsufficientFunds { .. } => "order.insufficient_funds",
+            OrderError::PositionLimitExceeded { .. } => "order.position_limit_exceeded",
+            OrderError::RiskLimitViolation { .. } => "order.risk_limit_violation",
+            OrderError::AlreadyFilled { .. } => "order.already_filled",
+            OrderError::AlreadyCanceled { .. } => "order.already_canceled",
+            OrderError::MarketClosed { .. } => "order.market_closed",
+            OrderError::UnsupportedOrderType { .. } => "order.unsupported_order_type",
+        }
+    }
+}
+
 /// Portfolio-related errors
 #[derive(Error, Debug)]
 pub enum PortfolioError {
@@ -172,6 +329,20 @@ pub enum PortfolioError {
     CurrencyMismatch { expected: String, actual: String },
 }
 
+impl PortfolioError {
+    /// Stable machine-readable code, e.g. `"portfolio.currency_mismatch"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            PortfolioError::PositionNotFound { .. } => "portfolio.position_not_found",
+            PortfolioError::InsufficientPosition { .. } => "portfolio.insufficient_position",
+            PortfolioError::CalculationError { .. } => "portfolio.calculation_error",
+            PortfolioError::RiskLimitExceeded { .. } => "portfolio.risk_limit_exceeded",
+            PortfolioError::StateInconsistency { .. } => "portfolio.state_inconsistency",
+            PortfolioError::CurrencyMismatch { .. } => "portfolio.currency_mismatch",
+        }
+    }
+}
+
 /// Backtest-related errors
 #[derive(Error, Debug)]
 pub enum BacktestError {
@@ -204,6 +375,72 @@ pub enum BacktestError {
     
     #[error("Results processing error: {message}")]
     ResultsProcessingError { message: String },
+    
+    #[error("Lookahead bias detected for {symbol}: {message}")]
+    LookaheadViolation { symbol: String, message: String },
+    
+    #[error("Stale market data for {symbol}: last bar at {last_bar_at}, {missing_bars} sessions missed")]
+    StaleData { symbol: String, last_bar_at: String, missing_bars: u32 },
+}
+
+impl BacktestError {
+    /// Stable machine-readable code, e.g. `"backtest.lookahead_violation"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            BacktestError::NotFound { .. } => "backtest.not_found",
+            BacktestError::InvalidConfig { .. } => "backtest.invalid_config",
+            BacktestError::AlreadyRunning { .. } => "backtest.already_running",
+            BacktestError::ExecutionFailed { .. } => "backtest.execution_failed",
+            BacktestError::Canceled { .. } => "backtest.canceled",
+            BacktestError::InvalidDateRange { .. } => "backtest.invalid_date_range",
+            BacktestError::NoSymbols => "backtest.no_symbols",
+            BacktestError::EngineInitFailed { .. } => "backtest.engine_init_failed",
+            BacktestError::SimulationError { .. } => "backtest.simulation_error",
+            BacktestError::ResultsProcessingError { .. } => "backtest.results_processing_error",
+            BacktestError::LookaheadViolation { .. } => "backtest.lookahead_violation",
+            BacktestError::StaleData { .. } => "backtest.stale_data",
+        }
+    }
+}
+
+impl GbError {
+    /// Stable machine-readable code: the wrapped error's code, or `"config"`,
+    /// `"io"`, `"io.not_found"`, `"serialization"`, `"arrow"`, `"parquet"`,
+    /// `"internal"` or `"validation"`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            GbError::Data(error) => error.code(),
+            GbError::Strategy(error) => error.code(),
+            GbError::Order(error) => error.code(),
+            GbError::Portfolio(error) => error.code(),
+            GbError::Backtest(error) => error.code(),
+            GbError::Config(_) => "config",
+            GbError::Io(error) if error.kind() == std::io::ErrorKind::NotFound => "io.not_found",
+            GbError::Io(_) => "io",
+            GbError::Serialization(_) => "serialization",
+            GbError::Arrow(_) => "arrow",
+            GbError::Parquet(_) => "parquet",
+            GbError::Internal(_) => "internal",
+            GbError::Validation(_) => "validation",
+        }
+    }
+    
+    /// Whether repeating the operation unchanged may succeed: retryable data
+    /// errors and interrupted or timed-out I/O.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            GbError::Data(error) => error.is_retryable(),
+            GbError::Io(error) => matches!(
+                error.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+            ),
+            _ => false,
+        }
+    }
 }
 
 /// Result type alias for GlowBack operations
@@ -280,6 +517,41 @@ mod tests {
         }
     }
     
+    #[test]
+    fn codes_and_retryability_follow_the_variant() {
+        let rate_limited: GbError = DataError::RateLimited {
+            provider: "Alpha Vantage".to_string(),
+            message: "5 calls per minute".to_string(),
+            retry_after_seconds: Some(60),
+        }
+        .into();
+        assert_eq!(rate_limited.code(), "data.rate_limited");
+        assert!(rate_limited.is_retryable());
+        
+        let missing = DataError::from_io(
+            std::path::Path::new("/nonexistent/AAPL.csv"),
+            std::io::Error::from(std::io::ErrorKind::NotFound),
+        );
+        assert!(matches!(missing, GbError::Data(DataError::FileNotFound { .. })));
+        assert!(!missing.is_retryable());
+        
+        let corrupt: GbError = DataError::CorruptFile {
+            path: "AAPL.parquet".to_string(),
+            source: "invalid magic".into(),
+        }
+        .into();
+        assert_eq!(corrupt.code(), "data.corrupt_file");
+        let data_error = std::error::Error::source(&corrupt).unwrap();
+        assert_eq!(
+            std::error::Error::source(data_error).unwrap().to_string(),
+            "invalid magic"
+        );
+        
+        assert!(DataError::HttpStatus { provider: "x".to_string(), status: 503 }.is_retryable());
+        assert!(!DataError::HttpStatus { provider: "x".to_string(), status: 404 }.is_retryable());
+        assert_eq!(GbError::from(BacktestError::NoSymbols).code(), "backtest.no_symbols");
+    }
+    
     #[test]
     fn test_macros() {
         let _validation_err = validation_error!("Invalid value: {}", 42);
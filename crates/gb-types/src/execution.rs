@@ -0,0 +1,282 @@
+//! Execution cost attribution.
+//!
+//! Every fill is compared against the price the strategy saw when it placed
+//! the order (the decision price). The difference between the two, plus
+//! commission, is the fill's execution cost, split into the adjustment that
+//! produced each part of it:
+//!
+//! - `latency_drift`: the market moving from the decision price to the price
+//!   the fill was based on (`arrival_price`).
+//! - `market_impact`: the move from `arrival_price` caused by the order
+//!   itself (`impact_price`).
+//! - `slippage`: the remaining move from `impact_price` to the fill price.
+//! - `commission`.
+//!
+//! Costs are signed so a positive value always hurt the account: paying up
+//! on a buy and receiving less on a sell are both positive.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::market::Symbol;
+use crate::orders::{ExecutionAlgo, OrderId, Side};
+
+/// Prices along one fill's path from decision to execution.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FillExecution {
+    pub symbol: Symbol,
+    pub side: Side,
+    pub quantity: Decimal,
+    /// Price the strategy saw when it placed the order.
+    pub decision_price: Decimal,
+    /// Market price the fill was based on, before impact and slippage.
+    pub arrival_price: Decimal,
+    /// `arrival_price` moved by the modeled market impact.
+    pub impact_price: Decimal,
+    pub fill_price: Decimal,
+    pub commission: Decimal,
+}
+
+impl FillExecution {
+    /// The cost of moving from `from` to `to` on this fill's side and size.
+    fn cost(&self, from: Decimal, to: Decimal) -> Decimal {
+        let quantity = self.quantity.abs();
+        match self.side {
+            Side::Buy => (to - from) * quantity,
+            Side::Sell => (from - to) * quantity,
+        }
+    }
+}
+
+/// Execution costs summed over a set of fills, in account currency.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionCosts {
+    pub fills: usize,
+    /// Traded notional at fill prices.
+    pub notional: Decimal,
+    pub latency_drift: Decimal,
+    pub market_impact: Decimal,
+    pub slippage: Decimal,
+    pub commission: Decimal,
+}
+
+impl ExecutionCosts {
+    /// Sum of all cost components.
+    pub fn total(&self) -> Decimal {
+        self.latency_drift + self.market_impact + self.slippage + self.commission
+    }
+
+    /// [`total`](Self::total) in basis points of traded notional; zero when
+    /// nothing traded.
+    pub fn total_bps(&self) -> Decimal {
+        if self.notional.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.total() / self.notional * Decimal::from(10_000)
+        }
+    }
+
+    fn add(&mut self, fill: &FillExecution) {
+        self.fills += 1;
+        self.notional += (fill.quantity * fill.fill_price).abs();
+        self.latency_drift += fill.cost(fill.decision_price, fill.arrival_price);
+        self.market_impact += fill.cost(fill.arrival_price, fill.impact_price);
+        self.slippage += fill.cost(fill.impact_price, fill.fill_price);
+        self.commission += fill.commission;
+    }
+}
+
+/// One symbol's share of an [`ExecutionReport`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolExecutionCosts {
+    pub symbol: Symbol,
+    pub costs: ExecutionCosts,
+}
+
+/// How an [`ExecutionAlgo`] order fared against the price when it was
+/// placed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlgoOrderExecution {
+    pub order_id: OrderId,
+    pub symbol: Symbol,
+    pub side: Side,
+    pub algo: ExecutionAlgo,
+    /// Price when the parent order was placed.
+    pub arrival_price: Decimal,
+    pub child_fills: usize,
+    pub filled_quantity: Decimal,
+    /// Quantity-weighted average of the child fill prices.
+    pub average_price: Decimal,
+    pub commission: Decimal,
+}
+
+impl AlgoOrderExecution {
+    /// Cost of filling at `average_price` rather than `arrival_price`, plus
+    /// commission, signed like [`ExecutionCosts`].
+    pub fn implementation_shortfall(&self) -> Decimal {
+        let drift = match self.side {
+            Side::Buy => self.average_price - self.arrival_price,
+            Side::Sell => self.arrival_price - self.average_price,
+        };
+        drift * self.filled_quantity + self.commission
+    }
+
+    /// [`implementation_shortfall`](Self::implementation_shortfall) in basis
+    /// points of the filled quantity's notional at the arrival price.
+    pub fn shortfall_bps(&self) -> Decimal {
+        let notional = (self.arrival_price * self.filled_quantity).abs();
+        if notional.is_zero() {
+            Decimal::ZERO
+        } else {
+            self.implementation_shortfall() / notional * Decimal::from(10_000)
+        }
+    }
+}
+
+/// What execution cost a run: totals over every fill and a breakdown by
+/// symbol, sorted by symbol.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    pub totals: ExecutionCosts,
+    pub by_symbol: Vec<SymbolExecutionCosts>,
+    /// Parent orders worked by an [`ExecutionAlgo`], in the order they
+    /// first filled.
+    #[serde(default)]
+    pub algo_orders: Vec<AlgoOrderExecution>,
+}
+
+impl ExecutionReport {
+    /// Attribute one fill's costs.
+    pub fn record(&mut self, fill: &FillExecution) {
+        self.totals.add(fill);
+        let index = match self
+            .by_symbol
+            .binary_search_by(|entry| entry.symbol.to_string().cmp(&fill.symbol.to_string()))
+        {
+            Ok(index) => index,
+            Err(index) => {
+                self.by_symbol.insert(
+                    index,
+                    SymbolExecutionCosts {
+                        symbol: fill.symbol.clone(),
+                        costs: ExecutionCosts::default(),
+                    },
+                );
+                index
+            }
+        };
+        self.by_symbol[index].costs.add(fill);
+    }
+
+    /// Add a child fill of the algo order `order_id` to its parent's
+    /// summary. The fill's decision price is the parent's arrival price.
+    pub fn record_algo_fill(
+        &mut self,
+        order_id: OrderId,
+        algo: &ExecutionAlgo,
+        fill: &FillExecution,
+    ) {
+        let index = match self
+            .algo_orders
+            .iter()
+            .position(|parent| parent.order_id == order_id)
+        {
+            Some(index) => index,
+            None => {
+                self.algo_orders.push(AlgoOrderExecution {
+                    order_id,
+                    symbol: fill.symbol.clone(),
+                    side: fill.side,
+                    algo: algo.clone(),
+                    arrival_price: fill.decision_price,
+                    child_fills: 0,
+                    filled_quantity: Decimal::ZERO,
+                    average_price: Decimal::ZERO,
+                    commission: Decimal::ZERO,
+                });
+                self.algo_orders.len() - 1
+            }
+        };
+        let parent = &mut self.algo_orders[index];
+        let quantity = fill.quantity.abs();
+        let filled = parent.filled_quantity + quantity;
+        if !filled.is_zero() {
+            parent.average_price = (parent.average_price * parent.filled_quantity
+                + fill.fill_price * quantity)
+                / filled;
+        }
+        parent.filled_quantity = filled;
+        parent.child_fills += 1;
+        parent.commission += fill.commission;
+    }
+
+    /// The costs attributed to `symbol`, if it traded.
+    pub fn symbol(&self, symbol: &Symbol) -> Option<&ExecutionCosts> {
+        self.by_symbol
+            .iter()
+            .find(|entry| entry.symbol == *symbol)
+            .map(|entry| &entry.costs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fill(symbol: &str, side: Side, prices: [Decimal; 4], commission: Decimal) -> FillExecution {
+        FillExecution {
+            symbol: Symbol::equity(symbol),
+            side,
+            quantity: dec!(10),
+            decision_price: prices[0],
+            arrival_price: prices[1],
+            impact_price: prices[2],
+            fill_price: prices[3],
+            commission,
+        }
+    }
+
+    #[test]
+    fn components_add_up_to_the_decision_to_fill_shortfall() {
+        let mut report = ExecutionReport::default();
+        let buy = fill(
+            "MSFT",
+            Side::Buy,
+            [dec!(100), dec!(101), dec!(101.5), dec!(101.6)],
+            dec!(1),
+        );
+        let sell = fill(
+            "AAPL",
+            Side::Sell,
+            [dec!(50), dec!(49), dec!(48.8), dec!(48.7)],
+            dec!(0.5),
+        );
+        report.record(&buy);
+        report.record(&sell);
+
+        let msft = report.symbol(&Symbol::equity("MSFT")).unwrap();
+        assert_eq!(msft.latency_drift, dec!(10));
+        assert_eq!(msft.market_impact, dec!(5));
+        assert_eq!(msft.slippage, dec!(1));
+        assert_eq!(msft.total(), (dec!(101.6) - dec!(100)) * dec!(10) + dec!(1));
+
+        let aapl = report.symbol(&Symbol::equity("AAPL")).unwrap();
+        assert_eq!(aapl.total(), (dec!(50) - dec!(48.7)) * dec!(10) + dec!(0.5));
+
+        assert_eq!(report.totals.fills, 2);
+        assert_eq!(report.totals.total(), msft.total() + aapl.total());
+        assert_eq!(report.totals.notional, dec!(1016) + dec!(487));
+        assert_eq!(
+            report.totals.total_bps(),
+            report.totals.total() / dec!(1503) * dec!(10000)
+        );
+        let symbols: Vec<_> = report
+            .by_symbol
+            .iter()
+            .map(|entry| entry.symbol.symbol.as_str())
+            .collect();
+        assert_eq!(symbols, ["AAPL", "MSFT"]);
+        assert_eq!(ExecutionCosts::default().total_bps(), Decimal::ZERO);
+    }
+}
@@ -0,0 +1,167 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+
+use crate::market::Symbol;
+use crate::orders::{Fill, Side};
+
+/// Matches fills against the open lots of each symbol, first in first out,
+/// so the P&L of a closing fill can be credited to the entries it closed
+/// and to the tags they were opened with.
+#[derive(Debug, Clone, Default)]
+pub struct TradeLedger {
+    lots: HashMap<Symbol, VecDeque<OpenLot>>,
+}
+
+/// Part of a position still open, as entered by one fill.
+#[derive(Debug, Clone, PartialEq)]
+struct OpenLot {
+    side: Side,
+    quantity: Decimal,
+    price: Decimal,
+    opened_at: DateTime<Utc>,
+    tags: Vec<String>,
+}
+
+/// What a fill closed: the P&L realized on the closed quantity, gross of
+/// commission, and the tags of the entries it closed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LedgerClose {
+    pub quantity: Decimal,
+    pub realized_pnl: Decimal,
+    /// When the oldest closed entry was opened
+    pub opened_at: DateTime<Utc>,
+    /// Tags of the closed entries, each once, in the order first seen
+    pub entry_tags: Vec<String>,
+}
+
+impl TradeLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Book `fill`, whose symbol trades `multiplier` units of the
+    /// underlying per unit held. Returns what it closed, or `None` when it
+    /// only opened or added to a position. Quantity beyond the open lots
+    /// opens a lot on the other side.
+    pub fn record(&mut self, fill: &Fill, multiplier: Decimal) -> Option<LedgerClose> {
+        let lots = self.lots.entry(fill.symbol.clone()).or_default();
+        let mut remaining = fill.quantity;
+        let mut close: Option<LedgerClose> = None;
+
+        while remaining > Decimal::ZERO {
+            let Some(lot) = lots.front_mut().filter(|lot| lot.side != fill.side) else {
+                break;
+            };
+            let quantity = remaining.min(lot.quantity);
+            let per_unit = match lot.side {
+                Side::Buy => fill.price - lot.price,
+                Side::Sell => lot.price - fill.price,
+            };
+            let close = close.get_or_insert_with(|| LedgerClose {
+                quantity: Decimal::ZERO,
+                realized_pnl: Decimal::ZERO,
+                opened_at: lot.opened_at,
+                entry_tags: Vec::new(),
+            });
+            close.quantity += quantity;
+            close.realized_pnl += per_unit * quantity * multiplier;
+            for tag in &lot.tags {
+                if !close.entry_tags.contains(tag) {
+                    close.entry_tags.push(tag.clone());
+                }
+            }
+
+            lot.quantity -= quantity;
+            remaining -= quantity;
+            if lot.quantity.is_zero() {
+                lots.pop_front();
+            }
+        }
+
+        if remaining > Decimal::ZERO {
+            lots.push_back(OpenLot {
+                side: fill.side,
+                quantity: remaining,
+                price: fill.price,
+                opened_at: fill.executed_at,
+                tags: fill.tags.clone(),
+            });
+        }
+        close
+    }
+
+    /// Signed quantity of `symbol` still open: positive long, negative
+    /// short.
+    pub fn open_quantity(&self, symbol: &Symbol) -> Decimal {
+        self.lots.get(symbol).map_or(Decimal::ZERO, |lots| {
+            lots.iter()
+                .map(|lot| match lot.side {
+                    Side::Buy => lot.quantity,
+                    Side::Sell => -lot.quantity,
+                })
+                .sum()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fill(side: Side, quantity: Decimal, price: Decimal, tag: Option<&str>) -> Fill {
+        let mut fill = Fill::new(
+            uuid::Uuid::new_v4(),
+            Symbol::equity("AAPL"),
+            side,
+            quantity,
+            price,
+            Decimal::ZERO,
+            "ledger-test".to_string(),
+        );
+        fill.tags = tag.map(str::to_string).into_iter().collect();
+        fill
+    }
+
+    #[test]
+    fn closing_fills_realize_pnl_against_the_oldest_lots() {
+        let mut ledger = TradeLedger::new();
+        assert_eq!(
+            ledger.record(
+                &fill(Side::Buy, dec!(10), dec!(100), Some("breakout")),
+                Decimal::ONE
+            ),
+            None
+        );
+        assert_eq!(
+            ledger.record(
+                &fill(Side::Buy, dec!(10), dec!(110), Some("hedge")),
+                Decimal::ONE
+            ),
+            None
+        );
+
+        let first = ledger
+            .record(&fill(Side::Sell, dec!(5), dec!(120), None), Decimal::ONE)
+            .unwrap();
+        assert_eq!(first.realized_pnl, dec!(100));
+        assert_eq!(first.entry_tags, ["breakout"]);
+
+        // The rest of both lots, then 5 short.
+        let second = ledger
+            .record(&fill(Side::Sell, dec!(20), dec!(90), None), Decimal::ONE)
+            .unwrap();
+        assert_eq!(second.quantity, dec!(15));
+        assert_eq!(second.realized_pnl, dec!(-50) + dec!(-200));
+        assert_eq!(second.entry_tags, ["breakout", "hedge"]);
+        assert_eq!(ledger.open_quantity(&Symbol::equity("AAPL")), dec!(-5));
+
+        let cover = ledger
+            .record(&fill(Side::Buy, dec!(10), dec!(80), None), dec!(100))
+            .unwrap();
+        assert_eq!(cover.quantity, dec!(5));
+        assert_eq!(cover.realized_pnl, dec!(5000));
+        assert_eq!(ledger.open_quantity(&Symbol::equity("AAPL")), dec!(5));
+    }
+}
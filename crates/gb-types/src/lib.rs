@@ -1,13 +1,27 @@
 pub mod market;
+pub mod columns;
 pub mod orders;
 pub mod portfolio;
+pub mod ledger;
 pub mod strategy;
 pub mod backtest;
 pub mod errors;
+pub mod manifest;
+pub mod benchmark;
+pub mod execution;
+pub mod margin;
+pub mod profile;
 
 pub use market::*;
+pub use columns::*;
 pub use orders::*;
 pub use portfolio::*;
+pub use ledger::*;
 pub use strategy::*;
 pub use backtest::*;
-pub use errors::*; 
\ No newline at end of file
+pub use errors::*;
+pub use manifest::*;
+pub use benchmark::*;
+pub use execution::*;
+pub use margin::*;
+pub use profile::*; 
\ No newline at end of file
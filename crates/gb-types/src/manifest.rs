@@ -0,0 +1,524 @@
+//! Backtest manifests: `BacktestConfig` saved as TOML or YAML so a run can be
+//! kept in version control and reproduced from the file alone.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::backtest::{BacktestConfig, CashFlowSettings, DataSettings, ExecutionSettings};
+use crate::errors::{GbError, GbResult};
+use crate::market::{AssetClass, Resolution, Symbol};
+use crate::portfolio::RiskLimits;
+use crate::strategy::StrategyConfig;
+
+/// Manifest schema written by this build. Files with a newer version are
+/// rejected; fields this build does not know are skipped with a warning.
+pub const MANIFEST_SCHEMA_VERSION: u32 = 1;
+
+/// On-disk encoding of a backtest manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ManifestFormat {
+    Toml,
+    Yaml,
+}
+
+impl ManifestFormat {
+    /// Picks the format from a `.toml`, `.yaml` or `.yml` extension.
+    pub fn from_path(path: &Path) -> GbResult<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        match extension.as_deref() {
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            _ => Err(GbError::Config(format!(
+                "cannot tell the manifest format of {}; use a .toml, .yaml or .yml extension",
+                path.display()
+            ))),
+        }
+    }
+}
+
+impl BacktestConfig {
+    pub fn to_toml(&self) -> GbResult<String> {
+        self.to_manifest_string(ManifestFormat::Toml)
+    }
+
+    pub fn from_toml(text: &str) -> GbResult<Self> {
+        Self::from_manifest_str(text, ManifestFormat::Toml)
+    }
+
+    pub fn to_yaml(&self) -> GbResult<String> {
+        self.to_manifest_string(ManifestFormat::Yaml)
+    }
+
+    pub fn from_yaml(text: &str) -> GbResult<Self> {
+        Self::from_manifest_str(text, ManifestFormat::Yaml)
+    }
+
+    pub fn to_manifest_string(&self, format: ManifestFormat) -> GbResult<String> {
+        let manifest = BacktestManifest::from(self);
+        match format {
+            ManifestFormat::Toml => toml::to_string_pretty(&manifest)
+                .map_err(|e| GbError::Config(format!("failed to write TOML manifest: {e}"))),
+            ManifestFormat::Yaml => serde_norway::to_string(&manifest)
+                .map_err(|e| GbError::Config(format!("failed to write YAML manifest: {e}"))),
+        }
+    }
+
+    /// Parses a manifest, logging a warning for each field it does not know.
+    pub fn from_manifest_str(text: &str, format: ManifestFormat) -> GbResult<Self> {
+        let (config, unknown_fields) = parse_manifest(text, format)?;
+        for field in unknown_fields {
+            warn!("Ignoring unknown backtest manifest field '{}'", field);
+        }
+        Ok(config)
+    }
+
+    /// Reads a manifest file, choosing the format from its extension.
+    pub fn from_manifest_file(path: impl AsRef<Path>) -> GbResult<Self> {
+        let path = path.as_ref();
+        let format = ManifestFormat::from_path(path)?;
+        Self::from_manifest_str(&std::fs::read_to_string(path)?, format)
+    }
+
+    /// Writes the config as a manifest file, choosing the format from its
+    /// extension.
+    pub fn write_manifest_file(&self, path: impl AsRef<Path>) -> GbResult<()> {
+        let path = path.as_ref();
+        let format = ManifestFormat::from_path(path)?;
+        std::fs::write(path, self.to_manifest_string(format)?)?;
+        Ok(())
+    }
+}
+
+/// The config and the dotted paths of any fields the manifest schema lacks.
+fn parse_manifest(text: &str, format: ManifestFormat) -> GbResult<(BacktestConfig, Vec<String>)> {
+    let mut unknown_fields = Vec::new();
+    let record = |path: serde_ignored::Path<'_>| unknown_fields.push(path.to_string());
+    let manifest: BacktestManifest = match format {
+        ManifestFormat::Toml => toml::Deserializer::parse(text)
+            .and_then(|deserializer| serde_ignored::deserialize(deserializer, record))
+            .map_err(|e| GbError::Config(format!("invalid TOML manifest: {e}")))?,
+        ManifestFormat::Yaml => {
+            serde_ignored::deserialize(serde_norway::Deserializer::from_str(text), record)
+                .map_err(|e| GbError::Config(format!("invalid YAML manifest: {e}")))?
+        }
+    };
+    Ok((manifest.try_into()?, unknown_fields))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BacktestManifest {
+    schema_version: u32,
+    name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<Uuid>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    created_at: Option<DateTime<Utc>>,
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    initial_capital: Decimal,
+    #[serde(default = "default_resolution")]
+    resolution: Resolution,
+    symbols: Vec<ManifestSymbol>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    universe: Option<String>,
+    strategy: StrategyManifest,
+    #[serde(default)]
+    execution: ExecutionSettings,
+    #[serde(default)]
+    data: DataSettings,
+    #[serde(default, skip_serializing_if = "CashFlowSettings::is_empty")]
+    cash_flows: CashFlowSettings,
+}
+
+/// `StrategyConfig` with the symbols and capital left out when they match
+/// the backtest's.
+#[derive(Debug, Serialize, Deserialize)]
+struct StrategyManifest {
+    strategy_id: String,
+    #[serde(default)]
+    name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    description: String,
+    #[serde(default)]
+    parameters: BTreeMap<String, serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    symbols: Option<Vec<ManifestSymbol>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    initial_capital: Option<Decimal>,
+    #[serde(default)]
+    risk_limits: RiskLimits,
+    #[serde(default = "default_enabled")]
+    enabled: bool,
+}
+
+fn default_resolution() -> Resolution {
+    Resolution::Day
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl From<&BacktestConfig> for BacktestManifest {
+    fn from(config: &BacktestConfig) -> Self {
+        let strategy = &config.strategy_config;
+        Self {
+            schema_version: MANIFEST_SCHEMA_VERSION,
+            name: config.name.clone(),
+            description: config.description.clone(),
+            id: Some(config.id),
+            created_at: Some(config.created_at),
+            start_date: config.start_date,
+            end_date: config.end_date,
+            initial_capital: config.initial_capital,
+            resolution: config.resolution,
+            symbols: manifest_symbols(&config.symbols),
+            universe: config.universe.clone(),
+            strategy: StrategyManifest {
+                strategy_id: strategy.strategy_id.clone(),
+                name: strategy.name.clone(),
+                description: strategy.description.clone(),
+                parameters: strategy
+                    .parameters
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+                symbols: (strategy.symbols != config.symbols)
+                    .then(|| manifest_symbols(&strategy.symbols)),
+                initial_capital: (strategy.initial_capital != config.initial_capital)
+                    .then_some(strategy.initial_capital),
+                risk_limits: strategy.risk_limits.clone(),
+                enabled: strategy.enabled,
+            },
+            execution: config.execution_settings.clone(),
+            data: config.data_settings.clone(),
+            cash_flows: config.cash_flows.clone(),
+        }
+    }
+}
+
+impl TryFrom<BacktestManifest> for BacktestConfig {
+    type Error = GbError;
+
+    fn try_from(manifest: BacktestManifest) -> GbResult<Self> {
+        if manifest.schema_version == 0 || manifest.schema_version > MANIFEST_SCHEMA_VERSION {
+            return Err(GbError::Config(format!(
+                "unsupported manifest schema_version {} (this build reads up to {})",
+                manifest.schema_version, MANIFEST_SCHEMA_VERSION
+            )));
+        }
+        if manifest.start_date > manifest.end_date {
+            return Err(GbError::Config(format!(
+                "manifest start_date {} is after end_date {}",
+                manifest.start_date, manifest.end_date
+            )));
+        }
+
+        let symbols: Vec<Symbol> = manifest.symbols.into_iter().map(|s| s.0).collect();
+        let strategy = manifest.strategy;
+        let strategy_config = StrategyConfig {
+            name: if strategy.name.is_empty() {
+                strategy.strategy_id.clone()
+            } else {
+                strategy.name
+            },
+            strategy_id: strategy.strategy_id,
+            description: strategy.description,
+            parameters: strategy.parameters.into_iter().collect(),
+            symbols: strategy
+                .symbols
+                .map(|symbols| symbols.into_iter().map(|s| s.0).collect())
+                .unwrap_or_else(|| symbols.clone()),
+            initial_capital: strategy.initial_capital.unwrap_or(manifest.initial_capital),
+            risk_limits: strategy.risk_limits,
+            enabled: strategy.enabled,
+        };
+
+        Ok(Self {
+            id: manifest.id.unwrap_or_else(Uuid::new_v4),
+            name: manifest.name,
+            description: manifest.description,
+            start_date: manifest.start_date,
+            end_date: manifest.end_date,
+            initial_capital: manifest.initial_capital,
+            symbols,
+            universe: manifest.universe,
+            resolution: manifest.resolution,
+            strategy_config,
+            execution_settings: manifest.execution,
+            data_settings: manifest.data,
+            cash_flows: manifest.cash_flows,
+            created_at: manifest.created_at.unwrap_or_else(Utc::now),
+        })
+    }
+}
+
+fn manifest_symbols(symbols: &[Symbol]) -> Vec<ManifestSymbol> {
+    symbols.iter().cloned().map(ManifestSymbol).collect()
+}
+
+/// A symbol written as `TICKER[@EXCHANGE][:asset_class]`, e.g.
+/// `AAPL@NASDAQ:equity`. The asset class defaults to equity and the exchange
+/// to the asset class's default. A full `{symbol, exchange, asset_class}`
+/// table is accepted too.
+#[derive(Debug)]
+struct ManifestSymbol(Symbol);
+
+impl Serialize for ManifestSymbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let symbol = &self.0;
+        serializer.collect_str(&format_args!(
+            "{}@{}:{}",
+            symbol.symbol,
+            symbol.exchange,
+            asset_class_name(symbol.asset_class)
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for ManifestSymbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SymbolVisitor;
+
+        impl<'de> Visitor<'de> for SymbolVisitor {
+            type Value = ManifestSymbol;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a symbol like \"AAPL@NASDAQ:equity\" or a symbol table")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                parse_symbol_shorthand(value)
+                    .map(ManifestSymbol)
+                    .map_err(E::custom)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                Symbol::deserialize(de::value::MapAccessDeserializer::new(map)).map(ManifestSymbol)
+            }
+        }
+
+        deserializer.deserialize_any(SymbolVisitor)
+    }
+}
+
+/// Parse the manifest symbol shorthand `TICKER[@EXCHANGE][:asset_class]`;
+/// the asset class defaults to equity and the exchange to the asset class's
+/// default exchange.
+pub fn parse_symbol_shorthand(value: &str) -> Result<Symbol, String> {
+    let (rest, asset_class) = match value.rsplit_once(':') {
+        Some((rest, class)) => (rest, parse_asset_class(class)?),
+        None => (value, AssetClass::Equity),
+    };
+    let (ticker, exchange) = match rest.split_once('@') {
+        Some((ticker, exchange)) => (ticker.trim(), exchange.trim()),
+        None => (rest.trim(), asset_class.default_exchange()),
+    };
+    if ticker.is_empty() || exchange.is_empty() {
+        return Err(format!(
+            "invalid symbol '{value}'; expected TICKER[@EXCHANGE][:asset_class]"
+        ));
+    }
+    Ok(Symbol::new(ticker, exchange, asset_class))
+}
+
+/// Parse a lowercase asset class name such as `equity` or `crypto`.
+pub fn parse_asset_class(value: &str) -> Result<AssetClass, String> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "equity" => Ok(AssetClass::Equity),
+        "crypto" => Ok(AssetClass::Crypto),
+        "forex" => Ok(AssetClass::Forex),
+        "commodity" => Ok(AssetClass::Commodity),
+        "bond" => Ok(AssetClass::Bond),
+        "option" => Ok(AssetClass::Option),
+        other => Err(format!("unknown asset class '{other}'")),
+    }
+}
+
+fn asset_class_name(asset_class: AssetClass) -> &'static str {
+    match asset_class {
+        AssetClass::Equity => "equity",
+        AssetClass::Crypto => "crypto",
+        AssetClass::Forex => "forex",
+        AssetClass::Commodity => "commodity",
+        AssetClass::Bond => "bond",
+        AssetClass::Option => "option",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backtest::{CashFlowEvent, DataQualityMode, LatencyModel, SlippageModel};
+    use chrono::TimeZone;
+    use rust_decimal_macros::dec;
+
+    const FIXTURE: &str = include_str!("../fixtures/ma_crossover_manifest.toml");
+
+    fn sample_config() -> BacktestConfig {
+        let mut strategy = StrategyConfig::new("ma_crossover".into(), "MA crossover".into());
+        strategy.set_parameter("short_period", 10);
+        strategy.set_parameter("long_period", 30);
+        strategy.set_parameter("threshold", 0.5);
+        let mut config = BacktestConfig::new("MA sweep".into(), strategy)
+            .with_symbols(vec![
+                Symbol::equity("AAPL"),
+                Symbol::new("BTC-USD", "COINBASE", AssetClass::Crypto),
+            ])
+            .with_date_range(
+                Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+                Utc.with_ymd_and_hms(2024, 6, 30, 0, 0, 0).unwrap(),
+            )
+            .with_capital(dec!(250000.50))
+            .with_resolution(Resolution::Hour);
+        config.strategy_config.symbols = config.symbols.clone();
+        config.strategy_config.initial_capital = config.initial_capital;
+        config.universe = Some("large caps".into());
+        config.execution_settings.slippage_model = SlippageModel::VolumeWeighted {
+            min_bps: 2,
+            max_bps: 20,
+        };
+        config.execution_settings.latency_model = LatencyModel::None;
+        config.data_settings.data_quality_mode = DataQualityMode::Fail;
+        config.cash_flows = CashFlowSettings::default()
+            .with_schedule("monthly $1,000".parse().unwrap())
+            .with_event(CashFlowEvent::withdrawal(
+                Utc.with_ymd_and_hms(2024, 6, 3, 0, 0, 0).unwrap(),
+                dec!(5000),
+            ));
+        config
+    }
+
+    #[test]
+    fn toml_and_yaml_round_trip_the_config() {
+        let config = sample_config();
+
+        let toml = config.to_toml().unwrap();
+        assert!(toml.contains("schema_version = 1"), "{toml}");
+        assert!(toml.contains("\"BTC-USD@COINBASE:crypto\""), "{toml}");
+        assert!(toml.contains("universe = \"large caps\""), "{toml}");
+        assert_eq!(BacktestConfig::from_toml(&toml).unwrap(), config);
+
+        let yaml = config.to_yaml().unwrap();
+        assert_eq!(BacktestConfig::from_yaml(&yaml).unwrap(), config);
+    }
+
+    #[test]
+    fn strategy_symbols_and_capital_are_kept_when_they_differ() {
+        let mut config = sample_config();
+        config.strategy_config.symbols = vec![Symbol::equity("AAPL")];
+        config.strategy_config.initial_capital = dec!(1000);
+
+        let parsed = BacktestConfig::from_toml(&config.to_toml().unwrap()).unwrap();
+        assert_eq!(parsed.strategy_config, config.strategy_config);
+    }
+
+    #[test]
+    fn checked_in_fixture_keeps_parsing() {
+        let (config, unknown_fields) = parse_manifest(FIXTURE, ManifestFormat::Toml).unwrap();
+        assert!(unknown_fields.is_empty(), "{unknown_fields:?}");
+
+        assert_eq!(config.name, "MA crossover on large caps");
+        assert_eq!(
+            config.symbols,
+            vec![
+                Symbol::equity("AAPL"),
+                Symbol::new("MSFT", "NYSE", AssetClass::Equity),
+                Symbol::new("BTC-USD", "BINANCE", AssetClass::Crypto),
+            ]
+        );
+        assert_eq!(config.initial_capital, dec!(100000));
+        assert_eq!(config.resolution, Resolution::Day);
+        assert_eq!(config.strategy_config.strategy_id, "ma_crossover");
+        assert_eq!(config.strategy_config.symbols, config.symbols);
+        assert_eq!(
+            config.strategy_config.get_parameter::<u32>("short_period"),
+            Some(10)
+        );
+        assert_eq!(config.execution_settings.commission_per_share, dec!(0.005));
+        assert_eq!(
+            config.execution_settings.slippage_model,
+            SlippageModel::Fixed { basis_points: 3 }
+        );
+        // Settings the fixture leaves out keep their defaults.
+        assert_eq!(
+            config.execution_settings.minimum_commission,
+            ExecutionSettings::default().minimum_commission
+        );
+        assert_eq!(config.data_settings.data_source, "sample");
+        assert!(!config.data_settings.adjust_for_dividends);
+        assert!(config.data_settings.adjust_for_splits);
+    }
+
+    #[test]
+    fn unknown_fields_are_reported_not_fatal() {
+        let text = FIXTURE.replacen(
+            "schema_version = 1\n",
+            "schema_version = 1\nfuture_knob = 3\n",
+            1,
+        ) + "\n[data.cache]\nwarm = true\n";
+        let (config, mut unknown_fields) = parse_manifest(&text, ManifestFormat::Toml).unwrap();
+        unknown_fields.sort();
+        assert_eq!(config.name, "MA crossover on large caps");
+        assert_eq!(unknown_fields, vec!["data.cache", "future_knob"]);
+    }
+
+    #[test]
+    fn rejects_newer_schema_and_bad_symbols() {
+        let newer = FIXTURE.replace("schema_version = 1", "schema_version = 2");
+        let error = BacktestConfig::from_toml(&newer).unwrap_err().to_string();
+        assert!(error.contains("schema_version 2"), "{error}");
+
+        let bad_class = FIXTURE.replace("AAPL", "AAPL:stonks");
+        let error = BacktestConfig::from_toml(&bad_class)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("unknown asset class 'stonks'"), "{error}");
+    }
+
+    #[test]
+    fn symbol_shorthand_fills_defaults() {
+        assert_eq!(
+            parse_symbol_shorthand("AAPL").unwrap(),
+            Symbol::equity("AAPL")
+        );
+        assert_eq!(
+            parse_symbol_shorthand("ETH-USD:crypto").unwrap(),
+            Symbol::crypto("ETH-USD")
+        );
+        assert_eq!(
+            parse_symbol_shorthand("SPY@ARCA").unwrap(),
+            Symbol::new("SPY", "ARCA", AssetClass::Equity)
+        );
+        assert!(parse_symbol_shorthand("@NASDAQ:equity").is_err());
+    }
+
+    #[test]
+    fn manifest_files_pick_the_format_from_the_extension() {
+        let dir = std::env::temp_dir().join(format!("gb-manifest-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = sample_config();
+
+        for name in ["run.toml", "run.yaml", "run.yml"] {
+            let path = dir.join(name);
+            config.write_manifest_file(&path).unwrap();
+            assert_eq!(BacktestConfig::from_manifest_file(&path).unwrap(), config);
+        }
+        assert!(config.write_manifest_file(dir.join("run.json")).is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+//! Account buying power shared by the backtest engine and the paper broker.
+//!
+//! A [`BuyingPowerModel`] decides whether an account may take on a trade:
+//! cash accounts spend only settled cash, while Reg T margin accounts lever
+//! equity up to their initial requirement and are called when equity falls
+//! below the maintenance requirement. [`BuyingPowerAccount`] applies the
+//! model and tracks sale proceeds that have not settled yet.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::orders::Side;
+
+/// How much an account may buy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BuyingPowerModel {
+    /// Buys are paid from settled cash; sale proceeds settle
+    /// `settlement_days` business days after the trade. No short sales.
+    CashAccount { settlement_days: u32 },
+    /// Positions may be carried on margin. Every trade that adds gross
+    /// exposure needs equity of `initial_margin` times the gross exposure
+    /// afterwards (0.5 = 2:1, 0.25 = 4:1 day-trading leverage); equity below
+    /// `maintenance_margin` times the gross exposure is a margin call.
+    RegTMargin {
+        initial_margin: Decimal,
+        maintenance_margin: Decimal,
+    },
+}
+
+impl BuyingPowerModel {
+    /// A cash account settling sale proceeds T+1.
+    pub fn cash_account() -> Self {
+        Self::CashAccount { settlement_days: 1 }
+    }
+
+    /// A Reg T margin account: 50% initial and 25% maintenance margin.
+    pub fn reg_t() -> Self {
+        Self::RegTMargin {
+            initial_margin: Decimal::new(5, 1),
+            maintenance_margin: Decimal::new(25, 2),
+        }
+    }
+}
+
+/// The account figures a buying-power check needs, in account currency.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AccountExposure {
+    pub cash: Decimal,
+    pub equity: Decimal,
+    /// Sum of absolute position market values.
+    pub gross_exposure: Decimal,
+    /// Signed market value already held in the traded symbol.
+    pub position_value: Decimal,
+}
+
+/// Why a trade exceeds the account's buying power.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BuyingPowerError {
+    #[error("order costs {required} but settled cash is {available}")]
+    InsufficientSettledCash {
+        required: Decimal,
+        available: Decimal,
+    },
+    #[error("cash accounts cannot sell short")]
+    ShortSaleInCashAccount,
+    #[error("initial margin of {required} exceeds equity of {equity}")]
+    InitialMarginExceeded { required: Decimal, equity: Decimal },
+}
+
+/// Equity below the maintenance requirement of a margin account.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MarginCall {
+    pub equity: Decimal,
+    pub maintenance_requirement: Decimal,
+    pub gross_exposure: Decimal,
+}
+
+/// A [`BuyingPowerModel`] applied to one account, with the sale proceeds
+/// still waiting to settle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BuyingPowerAccount {
+    model: BuyingPowerModel,
+    /// Unsettled proceeds by the date they settle on.
+    #[serde(default)]
+    unsettled: BTreeMap<NaiveDate, Decimal>,
+}
+
+impl BuyingPowerAccount {
+    pub fn new(model: BuyingPowerModel) -> Self {
+        Self {
+            model,
+            unsettled: BTreeMap::new(),
+        }
+    }
+
+    pub fn model(&self) -> &BuyingPowerModel {
+        &self.model
+    }
+
+    /// Sale proceeds that have not settled by `today`.
+    pub fn unsettled_cash(&self, today: NaiveDate) -> Decimal {
+        self.unsettled
+            .range(today.succ_opt().unwrap_or(today)..)
+            .map(|(_, amount)| *amount)
+            .sum()
+    }
+
+    /// Record a fill traded on `traded_on` with net cash `proceeds`. Cash
+    /// accounts hold sale proceeds back until they settle.
+    pub fn record_fill(&mut self, side: Side, proceeds: Decimal, traded_on: NaiveDate) {
+        self.unsettled
+            .retain(|settles_on, _| *settles_on > traded_on);
+        if let (BuyingPowerModel::CashAccount { settlement_days }, Side::Sell) = (&self.model, side)
+        {
+            *self
+                .unsettled
+                .entry(add_business_days(traded_on, *settlement_days))
+                .or_default() += proceeds;
+        }
+    }
+
+    /// Check a trade of `notional` (quantity × price × multiplier) on
+    /// `side` against the account on `today`. Trades that reduce exposure
+    /// always pass.
+    pub fn check_order(
+        &self,
+        account: &AccountExposure,
+        side: Side,
+        notional: Decimal,
+        today: NaiveDate,
+    ) -> Result<(), BuyingPowerError> {
+        let signed = match side {
+            Side::Buy => notional,
+            Side::Sell => -notional,
+        };
+        let resulting = account.position_value + signed;
+        match &self.model {
+            BuyingPowerModel::CashAccount { .. } => {
+                if resulting < Decimal::ZERO {
+                    return Err(BuyingPowerError::ShortSaleInCashAccount);
+                }
+                let available = account.cash - self.unsettled_cash(today);
+                if side == Side::Buy && notional > available {
+                    return Err(BuyingPowerError::InsufficientSettledCash {
+                        required: notional,
+                        available: available.max(Decimal::ZERO),
+                    });
+                }
+            }
+            BuyingPowerModel::RegTMargin { initial_margin, .. } => {
+                let gross = account.gross_exposure - account.position_value.abs() + resulting.abs();
+                let required = gross * initial_margin;
+                if gross > account.gross_exposure && required > account.equity {
+                    return Err(BuyingPowerError::InitialMarginExceeded {
+                        required,
+                        equity: account.equity,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The margin call a margin account is in at `equity` and
+    /// `gross_exposure`, if any.
+    pub fn margin_call(&self, equity: Decimal, gross_exposure: Decimal) -> Option<MarginCall> {
+        let BuyingPowerModel::RegTMargin {
+            maintenance_margin, ..
+        } = &self.model
+        else {
+            return None;
+        };
+        let maintenance_requirement = gross_exposure * maintenance_margin;
+        (equity < maintenance_requirement).then_some(MarginCall {
+            equity,
+            maintenance_requirement,
+            gross_exposure,
+        })
+    }
+}
+
+/// `date` moved forward by `days` weekdays.
+fn add_business_days(date: NaiveDate, days: u32) -> NaiveDate {
+    let mut date = date;
+    let mut remaining = days;
+    while remaining > 0 {
+        date += Duration::days(1);
+        if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            remaining -= 1;
+        }
+    }
+    date
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn day(d: u32) -> NaiveDate {
+        // 2024-01-01 is a Monday.
+        NaiveDate::from_ymd_opt(2024, 1, d).unwrap()
+    }
+
+    #[test]
+    fn cash_account_spends_only_settled_cash() {
+        let mut account = BuyingPowerAccount::new(BuyingPowerModel::cash_account());
+        // Friday's sale settles on Monday.
+        account.record_fill(Side::Sell, dec!(10_000), day(5));
+        let flat = AccountExposure {
+            cash: dec!(10_000),
+            equity: dec!(10_000),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            account.check_order(&flat, Side::Buy, dec!(5_000), day(5)),
+            Err(BuyingPowerError::InsufficientSettledCash {
+                required: dec!(5_000),
+                available: dec!(0),
+            })
+        );
+        assert_eq!(account.unsettled_cash(day(7)), dec!(10_000));
+        assert!(account
+            .check_order(&flat, Side::Buy, dec!(10_000), day(8))
+            .is_ok());
+        assert_eq!(
+            account.check_order(&flat, Side::Sell, dec!(1), day(8)),
+            Err(BuyingPowerError::ShortSaleInCashAccount)
+        );
+    }
+
+    #[test]
+    fn margin_account_levers_to_the_initial_requirement() {
+        let account = BuyingPowerAccount::new(BuyingPowerModel::reg_t());
+        let flat = AccountExposure {
+            cash: dec!(10_000),
+            equity: dec!(10_000),
+            ..Default::default()
+        };
+        assert!(account
+            .check_order(&flat, Side::Buy, dec!(20_000), day(2))
+            .is_ok());
+
+        let held = AccountExposure {
+            cash: dec!(-10_000),
+            equity: dec!(10_000),
+            gross_exposure: dec!(20_000),
+            position_value: dec!(20_000),
+        };
+        assert!(matches!(
+            account.check_order(&held, Side::Buy, dec!(1), day(2)),
+            Err(BuyingPowerError::InitialMarginExceeded { .. })
+        ));
+        assert!(account
+            .check_order(&held, Side::Sell, dec!(5_000), day(2))
+            .is_ok());
+
+        assert_eq!(account.margin_call(dec!(5_000), dec!(20_000)), None);
+        let call = account.margin_call(dec!(4_000), dec!(20_000)).unwrap();
+        assert_eq!(call.maintenance_requirement, dec!(5_000));
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, TimeZone, Utc, Weekday};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -27,6 +27,42 @@ impl Symbol {
     pub fn crypto(symbol: &str) -> Self {
         Self::new(symbol, "BINANCE", AssetClass::Crypto)
     }
+
+    /// An exchange-listed option contract identified by `contract_id`
+    /// (typically its OCC symbol).
+    pub fn option(contract_id: &str) -> Self {
+        Self::new(contract_id, "OPRA", AssetClass::Option)
+    }
+
+    pub fn is_option(&self) -> bool {
+        self.asset_class == AssetClass::Option
+    }
+
+    /// Local time zone of the symbol's exchange.
+    pub fn timezone(&self) -> ExchangeTimezone {
+        ExchangeTimezone::for_exchange(&self.exchange)
+    }
+
+    /// Currency the symbol is priced in. Crypto and forex pairs quote in
+    /// the second leg (`BTC-USD`, `EUR/USD`, `BTCUSDT`, `EURUSD`); anything
+    /// else is taken to be priced in USD.
+    pub fn quote_currency(&self) -> &str {
+        const CRYPTO_QUOTES: [&str; 6] = ["USDT", "USDC", "BUSD", "USD", "EUR", "BTC"];
+        if !matches!(self.asset_class, AssetClass::Crypto | AssetClass::Forex) {
+            return "USD";
+        }
+        let ticker = self.symbol.as_str();
+        if let Some((_, quote)) = ticker.rsplit_once(['-', '/']) {
+            return quote;
+        }
+        if self.asset_class == AssetClass::Forex && ticker.len() == 6 {
+            return &ticker[3..];
+        }
+        CRYPTO_QUOTES
+            .into_iter()
+            .find(|quote| ticker.len() > quote.len() && ticker.ends_with(quote))
+            .map_or("USD", |quote| &ticker[ticker.len() - quote.len()..])
+    }
 }
 
 impl fmt::Display for Symbol {
@@ -35,6 +71,112 @@ impl fmt::Display for Symbol {
     }
 }
 
+/// Local time zone of an exchange, with the daylight-saving rules in force
+/// since 2007 in the US and since 1996 in the EU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExchangeTimezone {
+    /// Crypto and forex venues, and any exchange not listed below.
+    #[default]
+    Utc,
+    /// NYSE, NASDAQ and the other US equity and options venues.
+    NewYork,
+    /// CME and CBOT.
+    Chicago,
+    London,
+    Frankfurt,
+    Tokyo,
+}
+
+impl ExchangeTimezone {
+    pub fn for_exchange(exchange: &str) -> Self {
+        match exchange.to_ascii_uppercase().as_str() {
+            "NASDAQ" | "NYSE" | "AMEX" | "ARCA" | "NYSEARCA" | "BATS" | "CBOE" | "IEX" | "OPRA" => {
+                Self::NewYork
+            }
+            "CME" | "CBOT" => Self::Chicago,
+            "LSE" => Self::London,
+            "XETRA" | "FRA" | "EUREX" => Self::Frankfurt,
+            "TSE" | "JPX" => Self::Tokyo,
+            _ => Self::Utc,
+        }
+    }
+
+    /// Offset from UTC in effect at `at`.
+    pub fn offset_at(&self, at: DateTime<Utc>) -> FixedOffset {
+        let hours = |hours: i32| FixedOffset::east_opt(hours * 3600).expect("valid offset");
+        let year = at.year();
+        // The Sunday a switch falls on, at the given UTC hour.
+        let switch = |date: NaiveDate, hour: u32| {
+            Utc.from_utc_datetime(&date.and_hms_opt(hour, 0, 0).expect("valid time"))
+        };
+        let sunday = |month: u32, n: u8| {
+            NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, n)
+                .expect("every month has four Sundays")
+        };
+        let last_sunday = |month: u32| {
+            NaiveDate::from_weekday_of_month_opt(year, month, Weekday::Sun, 5)
+                .unwrap_or_else(|| sunday(month, 4))
+        };
+        // Clocks change at 2:00 local time in the US, at 1:00 UTC in the EU.
+        let us = |standard: i32| {
+            let start = switch(sunday(3, 2), (2 - standard) as u32);
+            let end = switch(sunday(11, 1), (1 - standard) as u32);
+            hours(standard + i32::from(at >= start && at < end))
+        };
+        let eu = |standard: i32| {
+            let summer = at >= switch(last_sunday(3), 1) && at < switch(last_sunday(10), 1);
+            hours(standard + i32::from(summer))
+        };
+        match self {
+            Self::Utc => hours(0),
+            Self::NewYork => us(-5),
+            Self::Chicago => us(-6),
+            Self::London => eu(0),
+            Self::Frankfurt => eu(1),
+            Self::Tokyo => hours(9),
+        }
+    }
+
+    /// `at` on the exchange's wall clock.
+    pub fn to_local(&self, at: DateTime<Utc>) -> DateTime<FixedOffset> {
+        at.with_timezone(&self.offset_at(at))
+    }
+}
+
+/// A named symbol list built as of a date, e.g. by screening candidates on
+/// price, liquidity and fundamentals. Saved universes are what
+/// `BacktestConfig::universe` refers to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Universe {
+    pub name: String,
+    /// Only data up to this time decided membership.
+    pub as_of: DateTime<Utc>,
+    pub symbols: Vec<Symbol>,
+}
+
+impl Universe {
+    pub fn new(name: impl Into<String>, as_of: DateTime<Utc>, symbols: Vec<Symbol>) -> Self {
+        Self {
+            name: name.into(),
+            as_of,
+            symbols,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    pub fn contains(&self, symbol: &Symbol) -> bool {
+        self.symbols.contains(symbol)
+    }
+}
+
 /// Asset classes supported by the platform
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AssetClass {
@@ -43,6 +185,8 @@ pub enum AssetClass {
     Forex,
     Commodity,
     Bond,
+    /// Listed options; positions are sized in contracts.
+    Option,
 }
 
 impl AssetClass {
@@ -64,6 +208,7 @@ impl AssetClass {
             AssetClass::Forex => "FOREX",
             AssetClass::Commodity => "CME",
             AssetClass::Bond => "NYSE",
+            AssetClass::Option => "OPRA",
         }
     }
 }
@@ -76,11 +221,28 @@ impl fmt::Display for AssetClass {
             AssetClass::Forex => "Forex",
             AssetClass::Commodity => "Commodity",
             AssetClass::Bond => "Bond",
+            AssetClass::Option => "Option",
         };
         write!(f, "{}", s)
     }
 }
 
+/// Option type — call or put.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OptionKind {
+    Call,
+    Put,
+}
+
+impl fmt::Display for OptionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionKind::Call => write!(f, "Call"),
+            OptionKind::Put => write!(f, "Put"),
+        }
+    }
+}
+
 /// OHLCV bar data with volume and timestamp
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Bar {
@@ -92,6 +254,9 @@ pub struct Bar {
     pub close: Decimal,
     pub volume: Decimal,
     pub resolution: Resolution,
+    /// Where the bar came from and how it has been transformed since
+    #[serde(default, skip_serializing_if = "BarProvenance::is_unknown")]
+    pub provenance: BarProvenance,
 }
 
 impl Bar {
@@ -114,9 +279,16 @@ impl Bar {
             close,
             volume,
             resolution,
+            provenance: BarProvenance::default(),
         }
     }
 
+    /// Attach the bar's provenance
+    pub fn with_provenance(mut self, provenance: BarProvenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
     /// Calculate typical price (HLC/3)
     pub fn typical_price(&self) -> Decimal {
         (self.high + self.low + self.close) / Decimal::from(3)
@@ -136,6 +308,35 @@ impl Bar {
     }
 }
 
+/// Origin of a bar and the transformations applied to it. Bars read from
+/// legacy data carry the default, which records nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BarProvenance {
+    /// Name of the provider the underlying data was fetched from
+    pub provider: Option<String>,
+    /// Prices are adjusted for splits
+    pub split_adjusted: bool,
+    /// Prices are adjusted for dividends
+    pub dividend_adjusted: bool,
+    /// Resolution of the bars this one was aggregated from
+    pub resampled_from: Option<Resolution>,
+    /// The bar fills a gap in the data rather than recording trading
+    pub synthetic: bool,
+}
+
+impl BarProvenance {
+    /// True when nothing is known about the bar's origin
+    pub fn is_unknown(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// True when prices differ from those the provider reported
+    pub fn is_adjusted(&self) -> bool {
+        self.split_adjusted || self.dividend_adjusted
+    }
+}
+
 /// Tick data for high-frequency analysis
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Tick {
@@ -274,4 +475,40 @@ mod tests {
         assert_eq!(s.exchange, "BINANCE");
         assert_eq!(s.asset_class, AssetClass::Crypto);
     }
+
+    #[test]
+    fn test_symbol_quote_currency() {
+        assert_eq!(Symbol::equity("AAPL").quote_currency(), "USD");
+        assert_eq!(Symbol::crypto("BTC-USD").quote_currency(), "USD");
+        assert_eq!(Symbol::crypto("ETHUSDT").quote_currency(), "USDT");
+        assert_eq!(Symbol::crypto("ETHBTC").quote_currency(), "BTC");
+        let forex = |ticker| Symbol::new(ticker, "FOREX", AssetClass::Forex);
+        assert_eq!(forex("EUR/GBP").quote_currency(), "GBP");
+        assert_eq!(forex("USDJPY").quote_currency(), "JPY");
+    }
+
+    #[test]
+    fn test_exchange_timezones_follow_daylight_saving() {
+        let at = |month, day, hour| Utc.with_ymd_and_hms(2024, month, day, hour, 0, 0).unwrap();
+        let offset_hours =
+            |timezone: ExchangeTimezone, at| timezone.offset_at(at).local_minus_utc() / 3600;
+        let new_york = Symbol::equity("AAPL").timezone();
+        assert_eq!(new_york, ExchangeTimezone::NewYork);
+        // 2024: US clocks go forward on 10 March at 07:00 UTC and back on
+        // 3 November at 06:00 UTC; EU clocks on 31 March and 27 October at
+        // 01:00 UTC.
+        assert_eq!(offset_hours(new_york, at(3, 10, 6)), -5);
+        assert_eq!(offset_hours(new_york, at(3, 10, 7)), -4);
+        assert_eq!(offset_hours(new_york, at(11, 3, 5)), -4);
+        assert_eq!(offset_hours(new_york, at(11, 3, 6)), -5);
+        assert_eq!(offset_hours(ExchangeTimezone::Chicago, at(7, 1, 0)), -5);
+        assert_eq!(offset_hours(ExchangeTimezone::London, at(3, 31, 0)), 0);
+        assert_eq!(offset_hours(ExchangeTimezone::London, at(3, 31, 1)), 1);
+        assert_eq!(offset_hours(ExchangeTimezone::Frankfurt, at(10, 27, 1)), 1);
+        assert_eq!(offset_hours(ExchangeTimezone::Tokyo, at(7, 1, 0)), 9);
+        assert_eq!(Symbol::crypto("BTC-USD").timezone(), ExchangeTimezone::Utc);
+
+        let open = new_york.to_local(at(7, 1, 13) + chrono::Duration::minutes(30));
+        assert_eq!(open.format("%H:%M").to_string(), "09:30");
+    }
 }
@@ -8,6 +8,9 @@ use crate::market::Symbol;
 /// Unique order identifier
 pub type OrderId = Uuid;
 
+/// Namespace of the name-based (v5) ids from [`Order::derive_client_order_id`].
+pub const CLIENT_ORDER_ID_NAMESPACE: Uuid = Uuid::from_u128(0x78952445_b455_4c9b_918f_057852cc64bc);
+
 /// Direction of an order (buy or sell)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Side {
@@ -45,6 +48,12 @@ pub enum OrderType {
         stop_price: Decimal,
         limit_price: Decimal,
     },
+    /// Stop that follows the best price since submission at a fixed
+    /// distance: a sell stop sits `trail_amount` below the running high, a
+    /// buy stop `trail_amount` above the running low.
+    TrailingStop {
+        trail_amount: Decimal,
+    },
 }
 
 /// Time in force specifications
@@ -56,6 +65,20 @@ pub enum TimeInForce {
     FOK, // Fill or Kill
 }
 
+/// How the backtest engine slices a parent order into child fills across
+/// the bars after it is placed. Slices fill at each bar's close.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ExecutionAlgo {
+    /// Equal slices over `bars` bars.
+    Twap { bars: u32 },
+    /// Slices over `bars` bars weighted by bar volume, never more than
+    /// `participation_cap` of a bar's volume.
+    Vwap {
+        bars: u32,
+        participation_cap: Decimal,
+    },
+}
+
 /// Order status during lifecycle
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderStatus {
@@ -83,7 +106,22 @@ pub struct Order {
     pub remaining_quantity: Decimal,
     pub average_fill_price: Option<Decimal>,
     pub strategy_id: String,
+    /// Free-form data the strategy attaches to the order
+    #[serde(default)]
     pub metadata: serde_json::Value,
+    /// Labels for the signal behind the order, such as `"breakout"` or
+    /// `"hedge"`, carried onto its fills and trade records
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Caller-chosen id that makes submission idempotent: a broker that
+    /// already accepted an order with this id returns that order's id
+    /// instead of placing it again. See [`Order::derive_client_order_id`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_order_id: Option<Uuid>,
+    /// Slice the order over several bars instead of filling it at once.
+    /// Only the backtest engine works algo orders.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_algo: Option<ExecutionAlgo>,
 }
 
 impl Order {
@@ -108,9 +146,52 @@ impl Order {
             average_fill_price: None,
             strategy_id,
             metadata: serde_json::Value::Null,
+            tags: Vec::new(),
+            client_order_id: None,
+            execution_algo: None,
         }
     }
 
+    /// Add `tag` to the order's tags.
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Attach strategy-defined `metadata` to the order.
+    pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    pub fn with_client_order_id(mut self, client_order_id: Uuid) -> Self {
+        self.client_order_id = Some(client_order_id);
+        self
+    }
+
+    /// Work the order with `algo`.
+    pub fn with_execution_algo(mut self, algo: ExecutionAlgo) -> Self {
+        self.execution_algo = Some(algo);
+        self
+    }
+
+    /// Client order id for this order as the `sequence`-th one its strategy
+    /// decided at `decided_at`. The id depends only on the strategy, the
+    /// symbol, side and quantity, and the decision, so the same decision
+    /// maps to the same id across retries and restarts.
+    pub fn derive_client_order_id(&self, decided_at: DateTime<Utc>, sequence: u32) -> Uuid {
+        let decision = format!(
+            "{}\0{}\0{:?}\0{}\0{}\0{}",
+            self.strategy_id,
+            self.symbol,
+            self.side,
+            self.quantity.normalize(),
+            decided_at.to_rfc3339(),
+            sequence
+        );
+        Uuid::new_v5(&CLIENT_ORDER_ID_NAMESPACE, decision.as_bytes())
+    }
+
     pub fn market_order(
         symbol: Symbol,
         side: Side,
@@ -152,6 +233,22 @@ impl Order {
         )
     }
 
+    pub fn trailing_stop_order(
+        symbol: Symbol,
+        side: Side,
+        quantity: Decimal,
+        trail_amount: Decimal,
+        strategy_id: String,
+    ) -> Self {
+        Self::new(
+            symbol,
+            side,
+            quantity,
+            OrderType::TrailingStop { trail_amount },
+            strategy_id,
+        )
+    }
+
     pub fn is_buy(&self) -> bool {
         matches!(self.side, Side::Buy)
     }
@@ -171,8 +268,13 @@ impl Order {
         )
     }
 
+    /// Record a fill of up to the remaining quantity. Fills of nothing,
+    /// or of a negative quantity, leave the order unchanged.
     pub fn fill(&mut self, quantity: Decimal, price: Decimal) {
         let fill_quantity = quantity.min(self.remaining_quantity);
+        if fill_quantity <= Decimal::ZERO {
+            return;
+        }
 
         // Update filled quantity and average price
         let total_filled = self.filled_quantity + fill_quantity;
@@ -213,6 +315,16 @@ pub struct Fill {
     pub commission: Decimal,
     pub executed_at: DateTime<Utc>,
     pub strategy_id: String,
+    /// Bid/ask spread (ask − bid) in effect at execution, when the venue or
+    /// simulator reports one.
+    #[serde(default)]
+    pub spread: Option<Decimal>,
+    /// Tags of the order that was filled
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Metadata of the order that was filled
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub metadata: serde_json::Value,
 }
 
 impl Fill {
@@ -235,9 +347,19 @@ impl Fill {
             commission,
             executed_at: Utc::now(),
             strategy_id,
+            spread: None,
+            tags: Vec::new(),
+            metadata: serde_json::Value::Null,
         }
     }
 
+    /// Carry `order`'s tags and metadata on the fill.
+    pub fn with_order_annotations(mut self, order: &Order) -> Self {
+        self.tags = order.tags.clone();
+        self.metadata = order.metadata.clone();
+        self
+    }
+
     pub fn gross_amount(&self) -> Decimal {
         self.quantity * self.price
     }
@@ -254,22 +376,55 @@ impl Fill {
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum OrderEvent {
     OrderSubmitted(Order),
-    OrderFilled { order_id: OrderId, fill: Fill },
-    OrderCanceled { order_id: OrderId, reason: String },
-    OrderRejected { order_id: OrderId, reason: String },
-    OrderExpired { order_id: OrderId, reason: String },
+    /// The venue acknowledged the order and is working it.
+    OrderAccepted {
+        order_id: OrderId,
+    },
+    /// A fill that left `remaining_quantity` of the order working.
+    OrderPartiallyFilled {
+        order_id: OrderId,
+        fill: Fill,
+        remaining_quantity: Decimal,
+    },
+    OrderFilled {
+        order_id: OrderId,
+        fill: Fill,
+    },
+    OrderCanceled {
+        order_id: OrderId,
+        reason: String,
+    },
+    OrderRejected {
+        order_id: OrderId,
+        reason: String,
+    },
+    OrderExpired {
+        order_id: OrderId,
+        reason: String,
+    },
 }
 
 impl OrderEvent {
     pub fn order_id(&self) -> OrderId {
         match self {
             OrderEvent::OrderSubmitted(order) => order.id,
+            OrderEvent::OrderAccepted { order_id } => *order_id,
+            OrderEvent::OrderPartiallyFilled { order_id, .. } => *order_id,
             OrderEvent::OrderFilled { order_id, .. } => *order_id,
             OrderEvent::OrderCanceled { order_id, .. } => *order_id,
             OrderEvent::OrderRejected { order_id, .. } => *order_id,
             OrderEvent::OrderExpired { order_id, .. } => *order_id,
         }
     }
+
+    /// The fill carried by a partial or final fill event.
+    pub fn fill(&self) -> Option<&Fill> {
+        match self {
+            OrderEvent::OrderPartiallyFilled { fill, .. }
+            | OrderEvent::OrderFilled { fill, .. } => Some(fill),
+            _ => None,
+        }
+    }
 }
 
 /// Order management system interface
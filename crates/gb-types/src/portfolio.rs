@@ -4,9 +4,14 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::errors::{GbResult, PortfolioError};
 use crate::market::Symbol;
 use crate::orders::{Fill, Side};
 
+/// Slack allowed by [`Portfolio::check_invariants`] for the rounding of
+/// average prices, in account currency.
+const INVARIANT_TOLERANCE: Decimal = Decimal::from_parts(1, 0, 0, false, 6);
+
 /// Portfolio position for a specific symbol
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Position {
@@ -17,10 +22,26 @@ pub struct Position {
     pub unrealized_pnl: Decimal,
     pub realized_pnl: Decimal,
     pub last_updated: DateTime<Utc>,
+    /// When the current position was opened from flat, or reopened on the
+    /// other side; `None` while flat or when adopted from a broker.
+    #[serde(default)]
+    pub opened_at: Option<DateTime<Utc>>,
+    /// Units of the underlying per unit of quantity (100 for a standard
+    /// equity option contract, 1 otherwise). Prices are per underlying unit.
+    #[serde(default = "default_multiplier")]
+    pub multiplier: Decimal,
+}
+
+fn default_multiplier() -> Decimal {
+    Decimal::ONE
 }
 
 impl Position {
     pub fn new(symbol: Symbol) -> Self {
+        Self::with_multiplier(symbol, Decimal::ONE)
+    }
+
+    pub fn with_multiplier(symbol: Symbol, multiplier: Decimal) -> Self {
         Self {
             symbol,
             quantity: Decimal::ZERO,
@@ -29,6 +50,8 @@ impl Position {
             unrealized_pnl: Decimal::ZERO,
             realized_pnl: Decimal::ZERO,
             last_updated: Utc::now(),
+            opened_at: None,
+            multiplier,
         }
     }
 
@@ -58,6 +81,7 @@ impl Position {
             // Opening new position
             self.quantity = new_quantity;
             self.average_price = fill.price;
+            self.opened_at = Some(fill.executed_at);
         } else if (current_quantity > Decimal::ZERO && fill_quantity > Decimal::ZERO)
             || (current_quantity < Decimal::ZERO && fill_quantity < Decimal::ZERO)
         {
@@ -76,13 +100,14 @@ impl Position {
                 (fill.price - self.average_price) * closed_quantity
             } else {
                 (self.average_price - fill.price) * closed_quantity
-            };
+            } * self.multiplier;
             self.realized_pnl += realized_pnl;
 
             if new_quantity == Decimal::ZERO {
                 // Position closed exactly
                 self.quantity = Decimal::ZERO;
                 self.average_price = Decimal::ZERO;
+                self.opened_at = None;
             } else if (current_quantity > Decimal::ZERO && new_quantity > Decimal::ZERO)
                 || (current_quantity < Decimal::ZERO && new_quantity < Decimal::ZERO)
             {
@@ -93,6 +118,7 @@ impl Position {
                 // The residual leg should carry the crossing fill's price as its new basis.
                 self.quantity = new_quantity;
                 self.average_price = fill.price;
+                self.opened_at = Some(fill.executed_at);
             }
         }
 
@@ -100,12 +126,12 @@ impl Position {
     }
 
     pub fn update_market_price(&mut self, market_price: Decimal) {
-        self.market_value = self.quantity * market_price;
+        self.market_value = self.quantity * market_price * self.multiplier;
         self.unrealized_pnl = match self.quantity {
             q if q > Decimal::ZERO => (market_price - self.average_price) * self.quantity,
             q if q < Decimal::ZERO => (self.average_price - market_price) * self.quantity.abs(),
             _ => Decimal::ZERO,
-        };
+        } * self.multiplier;
         self.last_updated = Utc::now();
     }
 
@@ -128,6 +154,61 @@ pub struct Portfolio {
     pub total_commissions: Decimal,
     pub last_updated: DateTime<Utc>,
     pub daily_returns: Vec<DailyReturn>,
+    /// Multipliers of symbols that are not traded one unit at a time
+    /// (option contracts); applied to positions opened in them.
+    #[serde(default)]
+    pub contract_multipliers: HashMap<Symbol, Decimal>,
+    /// Greek exposure of each open option position.
+    #[serde(default)]
+    pub option_greeks: HashMap<Symbol, GreeksExposure>,
+    /// Equity changes adopted from outside the fills (a broker
+    /// reconciliation overwriting cash or positions), kept so the P&L
+    /// identity in [`check_invariants`](Self::check_invariants) still holds.
+    #[serde(default)]
+    pub external_adjustments: Decimal,
+    /// Deposits and withdrawals, oldest first.
+    #[serde(default)]
+    pub cash_flows: Vec<CashFlow>,
+}
+
+/// Money moved into (positive) or out of (negative) the account from outside
+/// the strategy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CashFlow {
+    pub at: DateTime<Utc>,
+    pub amount: Decimal,
+    /// Total equity just before the flow, which splits the session into
+    /// sub-periods for the time-weighted return.
+    pub equity_before: Decimal,
+}
+
+/// Greek exposure of option positions in underlying units: per-share greeks
+/// times signed contracts times the contract multiplier, so `delta` is the
+/// share-equivalent position.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct GreeksExposure {
+    pub delta: Decimal,
+    pub gamma: Decimal,
+    /// Per calendar day.
+    pub theta: Decimal,
+    /// Per 1 % volatility move.
+    pub vega: Decimal,
+    /// Per 1 % rate move.
+    pub rho: Decimal,
+}
+
+impl std::ops::Add for GreeksExposure {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            delta: self.delta + other.delta,
+            gamma: self.gamma + other.gamma,
+            theta: self.theta + other.theta,
+            vega: self.vega + other.vega,
+            rho: self.rho + other.rho,
+        }
+    }
 }
 
 impl Portfolio {
@@ -144,19 +225,46 @@ impl Portfolio {
             total_commissions: Decimal::ZERO,
             last_updated: Utc::now(),
             daily_returns: Vec::new(),
+            contract_multipliers: HashMap::new(),
+            option_greeks: HashMap::new(),
+            external_adjustments: Decimal::ZERO,
+            cash_flows: Vec::new(),
         }
     }
 
+    /// Units of the underlying per unit of `symbol` held.
+    pub fn contract_multiplier(&self, symbol: &Symbol) -> Decimal {
+        self.contract_multipliers
+            .get(symbol)
+            .copied()
+            .unwrap_or(Decimal::ONE)
+    }
+
+    pub fn set_contract_multiplier(&mut self, symbol: Symbol, multiplier: Decimal) {
+        self.contract_multipliers.insert(symbol, multiplier);
+    }
+
+    /// Net greek exposure across all option positions.
+    pub fn greeks_exposure(&self) -> GreeksExposure {
+        self.option_greeks
+            .values()
+            .fold(GreeksExposure::default(), |total, greeks| total + *greeks)
+    }
+
     pub fn apply_fill(&mut self, fill: &Fill) {
-        // Update cash
-        self.cash += fill.net_amount();
+        // Update cash; fill prices are per underlying unit
+        let multiplier = self.contract_multiplier(&fill.symbol);
+        self.cash += match fill.side {
+            Side::Buy => -(fill.gross_amount() * multiplier + fill.commission),
+            Side::Sell => fill.gross_amount() * multiplier - fill.commission,
+        };
         self.total_commissions += fill.commission;
 
         // Update position
         let position = self
             .positions
             .entry(fill.symbol.clone())
-            .or_insert_with(|| Position::new(fill.symbol.clone()));
+            .or_insert_with(|| Position::with_multiplier(fill.symbol.clone(), multiplier));
         let realized_before_fill = position.realized_pnl;
         position.apply_fill(fill);
         let realized_after_fill = position.realized_pnl;
@@ -167,6 +275,7 @@ impl Portfolio {
         // Remove flat positions
         if position.is_flat() {
             self.positions.remove(&fill.symbol);
+            self.option_greeks.remove(&fill.symbol);
         }
 
         self.last_updated = fill.executed_at;
@@ -196,6 +305,90 @@ impl Portfolio {
         self.update_totals();
     }
 
+    /// Add `amount` of outside money to the account's cash.
+    pub fn deposit(&mut self, amount: Decimal, at: DateTime<Utc>) {
+        self.apply_cash_flow(amount, at);
+    }
+
+    /// Take `amount` of cash out of the account.
+    pub fn withdraw(&mut self, amount: Decimal, at: DateTime<Utc>) {
+        self.apply_cash_flow(-amount, at);
+    }
+
+    /// Net money deposited since the account opened, on top of
+    /// `initial_capital`.
+    pub fn net_deposits(&self) -> Decimal {
+        self.cash_flows.iter().map(|flow| flow.amount).sum()
+    }
+
+    fn apply_cash_flow(&mut self, amount: Decimal, at: DateTime<Utc>) {
+        self.update_totals();
+        self.cash_flows.push(CashFlow {
+            at,
+            amount,
+            equity_before: self.total_equity,
+        });
+        self.cash += amount;
+        self.last_updated = at;
+        self.update_totals();
+    }
+
+    /// Book the gap left by editing cash or positions directly, rather than
+    /// through fills, as an external adjustment.
+    pub fn absorb_external_adjustment(&mut self) {
+        self.update_totals();
+        self.external_adjustments += self.total_equity - self.equity_from_pnl();
+    }
+
+    /// Equity implied by the P&L booked so far.
+    fn equity_from_pnl(&self) -> Decimal {
+        self.initial_capital
+            + self.net_deposits()
+            + self.total_realized_pnl
+            + self.total_unrealized_pnl
+            - self.total_commissions
+            + self.external_adjustments
+    }
+
+    /// Check that the totals agree with the positions and that the P&L booked
+    /// from fills accounts for every change in equity:
+    ///
+    /// - `total_equity == cash + Σ market_value`
+    /// - `total_unrealized_pnl == Σ unrealized_pnl` and
+    ///   `total_pnl == total_realized_pnl + total_unrealized_pnl`
+    /// - `total_equity == initial_capital + total_pnl - total_commissions`
+    ///   (plus deposits and any external adjustments)
+    pub fn check_invariants(&self) -> GbResult<()> {
+        let mismatch = |name: &str, expected: Decimal, actual: Decimal| -> GbResult<()> {
+            if (expected - actual).abs() > INVARIANT_TOLERANCE {
+                return Err(PortfolioError::StateInconsistency {
+                    message: format!("{name} is {actual} but should be {expected}"),
+                }
+                .into());
+            }
+            Ok(())
+        };
+
+        let market_value: Decimal = self.positions.values().map(|p| p.market_value).sum();
+        mismatch("total equity", self.cash + market_value, self.total_equity)?;
+        let unrealized: Decimal = self.positions.values().map(|p| p.unrealized_pnl).sum();
+        mismatch(
+            "total unrealized P&L",
+            unrealized,
+            self.total_unrealized_pnl,
+        )?;
+        mismatch(
+            "total P&L",
+            self.total_realized_pnl + self.total_unrealized_pnl,
+            self.total_pnl,
+        )?;
+        mismatch(
+            "total equity implied by P&L",
+            self.total_equity,
+            self.equity_from_pnl(),
+        )
+    }
+
     fn update_totals(&mut self) {
         self.total_unrealized_pnl = self.positions.values().map(|p| p.unrealized_pnl).sum();
 
@@ -215,7 +408,13 @@ impl Portfolio {
         self.cash.max(Decimal::ZERO)
     }
 
+    /// Return since the account opened: simple on the initial capital, or
+    /// [time-weighted](Self::time_weighted_return) once money has been
+    /// deposited or withdrawn. Both agree when there are no flows.
     pub fn get_total_return(&self) -> Decimal {
+        if !self.cash_flows.is_empty() {
+            return self.time_weighted_return();
+        }
         if self.initial_capital > Decimal::ZERO {
             (self.total_equity - self.initial_capital) / self.initial_capital
         } else {
@@ -223,6 +422,53 @@ impl Portfolio {
         }
     }
 
+    /// Return since the account opened up to the current equity, with the
+    /// recorded daily returns compounded and deposits and withdrawals since
+    /// the last record chained out, so outside money never counts as
+    /// performance.
+    pub fn time_weighted_return(&self) -> Decimal {
+        let (growth, previous_cumulative) = self.growth_since_last_record(self.total_equity);
+        (Decimal::ONE + previous_cumulative) * growth - Decimal::ONE
+    }
+
+    /// Annualized internal rate of return from `start` to `end`: the initial
+    /// capital goes in at `start`, each deposit and withdrawal at its time,
+    /// and the current equity comes out at `end`. `None` when the rate is
+    /// undefined, e.g. over a zero-length period.
+    pub fn money_weighted_return(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Option<Decimal> {
+        let mut flows = vec![(start, -self.initial_capital)];
+        flows.extend(self.cash_flows.iter().map(|flow| (flow.at, -flow.amount)));
+        flows.push((end, self.total_equity));
+        annualized_irr(&flows)
+    }
+
+    /// Growth from the equity at the last daily record (or the initial
+    /// capital) to `equity`, chained through the flows since, and the
+    /// cumulative return at that record.
+    fn growth_since_last_record(&self, equity: Decimal) -> (Decimal, Decimal) {
+        let last = self.daily_returns.last();
+        let since = last.map(|last| last.date);
+        let (mut base, previous_cumulative) = last
+            .map_or((self.initial_capital, Decimal::ZERO), |last| {
+                (last.portfolio_value, last.cumulative_return)
+            });
+
+        let mut growth = Decimal::ONE;
+        for flow in self
+            .cash_flows
+            .iter()
+            .filter(|flow| since.is_none_or(|since| flow.at > since))
+        {
+            growth *= growth_ratio(flow.equity_before, base);
+            base = flow.equity_before + flow.amount;
+        }
+        (growth * growth_ratio(equity, base), previous_cumulative)
+    }
+
     pub fn add_daily_return(&mut self, date: DateTime<Utc>, daily_return: Decimal) {
         self.daily_returns.push(DailyReturn {
             date,
@@ -264,10 +510,25 @@ impl Portfolio {
         }
     }
 
+    /// Largest fall from a peak across the daily records. Once money has
+    /// been deposited or withdrawn the peaks are taken on the time-weighted
+    /// return, so a withdrawal is not a drawdown.
     pub fn get_max_drawdown(&self) -> Decimal {
         if self.daily_returns.is_empty() {
             return Decimal::ZERO;
         }
+        if !self.cash_flows.is_empty() {
+            let mut peak = Decimal::ONE;
+            let mut max_drawdown = Decimal::ZERO;
+            for daily_return in &self.daily_returns {
+                let wealth = Decimal::ONE + daily_return.cumulative_return;
+                peak = peak.max(wealth);
+                if peak > Decimal::ZERO {
+                    max_drawdown = max_drawdown.max((peak - wealth) / peak);
+                }
+            }
+            return max_drawdown;
+        }
 
         let mut max_value = self.initial_capital;
         let mut max_drawdown = Decimal::ZERO;
@@ -296,6 +557,107 @@ pub struct DailyReturn {
     pub cumulative_return: Decimal,
 }
 
+/// Appends a [`DailyReturn`] to a portfolio at each session boundary.
+///
+/// The return runs from the equity recorded at the previous boundary (or the
+/// initial capital) to the equity passed in. Deposits and withdrawals during
+/// the session are chained out as a time-weighted return: each flow closes a
+/// sub-period at the equity just before it, so outside money never counts as
+/// performance. The cumulative return compounds the daily ones.
+///
+/// The recorder keeps no state of its own, so it picks up where a restored
+/// portfolio's history left off. Callers decide what a session is: the
+/// backtest engine records days with bars, the live engine each calendar
+/// session close.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DailyReturnRecorder;
+
+impl DailyReturnRecorder {
+    /// Record the session ending at `session_close` with the portfolio worth
+    /// `equity`. Returns the new record, or `None` if that session (or a
+    /// later one) is already recorded.
+    pub fn record(
+        &self,
+        portfolio: &mut Portfolio,
+        session_close: DateTime<Utc>,
+        equity: Decimal,
+    ) -> Option<DailyReturn> {
+        let last = portfolio.daily_returns.last();
+        if last.is_some_and(|last| last.date.date_naive() >= session_close.date_naive()) {
+            return None;
+        }
+        let (growth, previous_cumulative) = portfolio.growth_since_last_record(equity);
+        let daily_return = growth - Decimal::ONE;
+
+        let record = DailyReturn {
+            date: session_close,
+            portfolio_value: equity,
+            daily_return,
+            cumulative_return: (Decimal::ONE + previous_cumulative) * (Decimal::ONE + daily_return)
+                - Decimal::ONE,
+        };
+        portfolio.daily_returns.push(record.clone());
+        Some(record)
+    }
+}
+
+/// `value / base`, or no growth when there is nothing to grow from.
+fn growth_ratio(value: Decimal, base: Decimal) -> Decimal {
+    if base.is_zero() {
+        Decimal::ONE
+    } else {
+        value / base
+    }
+}
+
+/// Annualized internal rate of return of dated cash flows, negative for
+/// money put in and positive for money taken out: the rate at which their
+/// present values sum to zero, with years of 365.25 days counted from the
+/// first flow. `None` when no rate between -100% and 1,000,000% does that.
+pub fn annualized_irr(flows: &[(DateTime<Utc>, Decimal)]) -> Option<Decimal> {
+    let first = flows.iter().map(|(at, _)| *at).min()?;
+    let flows: Vec<(f64, f64)> = flows
+        .iter()
+        .map(|(at, amount)| {
+            let years = (*at - first).num_seconds() as f64 / (365.25 * 86_400.0);
+            (years, amount.to_f64().unwrap_or(0.0))
+        })
+        .collect();
+    if flows.iter().all(|(years, _)| *years == 0.0) {
+        return None;
+    }
+    let present_value = |rate: f64| -> f64 {
+        flows
+            .iter()
+            .map(|(years, amount)| amount / (1.0 + rate).powf(*years))
+            .sum()
+    };
+
+    // Bisect between a rate just above -100% and one high enough that the
+    // present value changes sign.
+    let mut low = -0.999_999;
+    let mut high = 1.0;
+    let low_sign = present_value(low).signum();
+    while present_value(high).signum() == low_sign {
+        high *= 10.0;
+        if high > 10_000.0 {
+            return None;
+        }
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if present_value(mid).signum() == low_sign {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        if high - low < 1e-12 {
+            break;
+        }
+    }
+    Decimal::from_f64_retain((low + high) / 2.0)
+}
+
 /// Portfolio event for the event-driven engine
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum PortfolioEvent {
@@ -318,6 +680,7 @@ pub enum PortfolioEvent {
 
 /// Risk management parameters
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RiskLimits {
     pub max_position_size: Decimal,
     pub max_portfolio_leverage: Decimal,
@@ -340,10 +703,10 @@ impl Default for RiskLimits {
 
 #[cfg(test)]
 mod tests {
-    use super::{Portfolio, Position};
+    use super::{DailyReturnRecorder, Portfolio, Position};
     use crate::market::Symbol;
     use crate::orders::{Fill, Side};
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
     use rust_decimal::Decimal;
     use rust_decimal_macros::dec;
     use uuid::Uuid;
@@ -359,6 +722,9 @@ mod tests {
             commission: Decimal::ZERO,
             executed_at: Utc::now(),
             strategy_id: "test-strategy".to_string(),
+            spread: None,
+            tags: Vec::new(),
+            metadata: serde_json::Value::Null,
         }
     }
 
@@ -418,4 +784,222 @@ mod tests {
         assert_eq!(portfolio.cash, dec!(1650));
         assert_eq!(portfolio.total_equity, dec!(1100));
     }
+
+    #[test]
+    fn portfolio_scales_option_fills_by_contract_multiplier() {
+        let symbol = Symbol::option("AAPL  240119C00100000");
+        let mut portfolio = Portfolio::new("acct-1".to_string(), dec!(10000));
+        portfolio.set_contract_multiplier(symbol.clone(), dec!(100));
+
+        portfolio.apply_fill(&test_fill(&symbol, Side::Buy, dec!(2), dec!(3.5)));
+        assert_eq!(portfolio.cash, dec!(9300));
+
+        let mut prices = std::collections::HashMap::new();
+        prices.insert(symbol.clone(), dec!(4));
+        portfolio.update_market_prices(&prices);
+        let position = portfolio.get_position(&symbol).unwrap();
+        assert_eq!(position.market_value, dec!(800));
+        assert_eq!(position.unrealized_pnl, dec!(100));
+        assert_eq!(portfolio.total_equity, dec!(10100));
+
+        portfolio.apply_fill(&test_fill(&symbol, Side::Sell, dec!(2), dec!(5)));
+        assert!(portfolio.get_position(&symbol).is_none());
+        assert_eq!(portfolio.total_realized_pnl, dec!(300));
+        assert_eq!(portfolio.cash, dec!(10300));
+    }
+
+    /// Expected state after a fill: signed quantity, average price, realized
+    /// P&L so far, and cash.
+    type Expected = (Decimal, Decimal, Decimal, Decimal);
+
+    /// Apply `fills` (side, quantity, price) with a $1 commission each to a
+    /// $10,000 portfolio, checking the state and invariants after every one.
+    fn replay(fills: &[(Side, Decimal, Decimal)], expected: &[Expected]) -> Portfolio {
+        let symbol = Symbol::equity("AAPL");
+        let mut portfolio = Portfolio::new("acct-1".to_string(), dec!(10000));
+        for (step, ((side, quantity, price), want)) in fills.iter().zip(expected).enumerate() {
+            let mut fill = test_fill(&symbol, *side, *quantity, *price);
+            fill.commission = dec!(1);
+            portfolio.apply_fill(&fill);
+            portfolio.check_invariants().unwrap();
+
+            let (quantity, average_price) = portfolio
+                .get_position(&symbol)
+                .map_or((Decimal::ZERO, Decimal::ZERO), |p| {
+                    (p.quantity, p.average_price)
+                });
+            assert_eq!(
+                (
+                    quantity,
+                    average_price,
+                    portfolio.total_realized_pnl,
+                    portfolio.cash
+                ),
+                *want,
+                "after fill {step}"
+            );
+        }
+        assert_eq!(portfolio.total_commissions, Decimal::from(fills.len()));
+        portfolio
+    }
+
+    #[test]
+    fn long_positions_close_partially_then_to_flat() {
+        replay(
+            &[
+                (Side::Buy, dec!(100), dec!(10)),
+                (Side::Buy, dec!(100), dec!(12)),
+                (Side::Sell, dec!(50), dec!(15)),
+                (Side::Sell, dec!(150), dec!(9)),
+            ],
+            &[
+                (dec!(100), dec!(10), dec!(0), dec!(8999)),
+                (dec!(200), dec!(11), dec!(0), dec!(7798)),
+                (dec!(150), dec!(11), dec!(200), dec!(8547)),
+                (dec!(0), dec!(0), dec!(-100), dec!(9896)),
+            ],
+        );
+    }
+
+    #[test]
+    fn short_positions_close_partially_then_to_flat() {
+        replay(
+            &[
+                (Side::Sell, dec!(100), dec!(20)),
+                (Side::Buy, dec!(40), dec!(15)),
+                (Side::Buy, dec!(60), dec!(25)),
+            ],
+            &[
+                (dec!(-100), dec!(20), dec!(0), dec!(11999)),
+                (dec!(-60), dec!(20), dec!(200), dec!(11398)),
+                (dec!(0), dec!(0), dec!(-100), dec!(9897)),
+            ],
+        );
+    }
+
+    #[test]
+    fn crossing_flat_realizes_the_closed_lot_and_opens_at_the_fill_price() {
+        // Long 100 → short 50 → long 30.
+        let portfolio = replay(
+            &[
+                (Side::Buy, dec!(100), dec!(10)),
+                (Side::Sell, dec!(150), dec!(12)),
+                (Side::Buy, dec!(80), dec!(11)),
+            ],
+            &[
+                (dec!(100), dec!(10), dec!(0), dec!(8999)),
+                (dec!(-50), dec!(12), dec!(200), dec!(10798)),
+                (dec!(30), dec!(11), dec!(250), dec!(9917)),
+            ],
+        );
+        let position = portfolio.get_position(&Symbol::equity("AAPL")).unwrap();
+        assert_eq!(position.market_value, dec!(330));
+        assert_eq!(position.unrealized_pnl, Decimal::ZERO);
+        assert_eq!(portfolio.total_equity, dec!(10247));
+        assert_eq!(
+            portfolio.total_equity,
+            portfolio.initial_capital + portfolio.total_pnl - portfolio.total_commissions
+        );
+    }
+
+    #[test]
+    fn invariants_catch_unbooked_edits_until_absorbed() {
+        let symbol = Symbol::equity("AAPL");
+        let mut portfolio = Portfolio::new("acct-1".to_string(), dec!(10000));
+        portfolio.apply_fill(&test_fill(&symbol, Side::Buy, dec!(10), dec!(100)));
+        let mut prices = std::collections::HashMap::new();
+        prices.insert(symbol.clone(), dec!(105));
+        portfolio.update_market_prices(&prices);
+        portfolio.check_invariants().unwrap();
+
+        // A cash edit that no fill explains breaks the P&L identity.
+        portfolio.apply_cash_adjustment(dec!(-20), Decimal::ZERO, Decimal::ZERO, Utc::now());
+        let error = portfolio.check_invariants().unwrap_err();
+        assert!(
+            error.to_string().contains("total equity implied by P&L"),
+            "{error}"
+        );
+        portfolio.absorb_external_adjustment();
+        assert_eq!(portfolio.external_adjustments, dec!(-20));
+        portfolio.check_invariants().unwrap();
+
+        // So does a total that drifted from the positions.
+        portfolio.total_equity += dec!(1);
+        assert!(portfolio.check_invariants().is_err());
+    }
+
+    #[test]
+    fn recorder_chains_a_deposit_out_of_the_daily_returns() {
+        let symbol = Symbol::equity("AAPL");
+        let day = |day| Utc.with_ymd_and_hms(2024, 1, day, 21, 0, 0).unwrap();
+        let mut portfolio = Portfolio::new("acct-1".to_string(), dec!(10000));
+        let recorder = DailyReturnRecorder;
+        let close_day = |portfolio: &mut Portfolio, date, price| {
+            let mut prices = std::collections::HashMap::new();
+            prices.insert(symbol.clone(), price);
+            portfolio.update_market_prices(&prices);
+            let equity = portfolio.total_equity;
+            recorder.record(portfolio, day(date), equity).unwrap()
+        };
+
+        portfolio.apply_fill(&test_fill(&symbol, Side::Buy, dec!(50), dec!(100)));
+        assert_eq!(
+            close_day(&mut portfolio, 1, dec!(100)).daily_return,
+            dec!(0)
+        );
+        assert_eq!(
+            close_day(&mut portfolio, 2, dec!(110)).daily_return,
+            dec!(0.05)
+        );
+
+        // Depositing 10,500 on day 3 doubles equity before the price drops
+        // 19%: only the drop counts.
+        portfolio.deposit(dec!(10500), day(3) - chrono::Duration::hours(3));
+        let deposit_day = close_day(&mut portfolio, 3, dec!(89));
+        assert_eq!(deposit_day.portfolio_value, dec!(19950));
+        assert_eq!(deposit_day.daily_return, dec!(-0.05));
+        portfolio.check_invariants().unwrap();
+        assert_eq!(recorder.record(&mut portfolio, day(3), dec!(19950)), None);
+
+        assert_eq!(close_day(&mut portfolio, 4, dec!(89)).daily_return, dec!(0));
+        let last = close_day(&mut portfolio, 5, dec!(128.9));
+        assert_eq!(last.daily_return, dec!(0.1));
+        // 1.05 * 0.95 * 1.1, against a naive 119% on the initial capital.
+        assert_eq!(last.cumulative_return, dec!(0.09725));
+        assert_eq!(portfolio.net_deposits(), dec!(10500));
+        assert_eq!(portfolio.daily_returns.len(), 5);
+        assert!(portfolio.get_sharpe_ratio(Decimal::ZERO).is_some());
+    }
+
+    #[test]
+    fn irr_matches_the_closed_form_rate() {
+        let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let years = |years: i64| start + chrono::Duration::hours(years * 8766);
+        let close_to = |rate: Decimal, expected: Decimal| (rate - expected).abs() < dec!(0.000001);
+
+        // 1,000 growing to 1,210 over two years is 10% a year.
+        let single = super::annualized_irr(&[(start, dec!(-1000)), (years(2), dec!(1210))]);
+        assert!(close_to(single.unwrap(), dec!(0.1)));
+
+        // 100 now and 100 in a year are worth 100 * 1.1^2 + 100 * 1.1 = 231
+        // after two years at 10%.
+        let contributions = [
+            (start, dec!(-100)),
+            (years(1), dec!(-100)),
+            (years(2), dec!(231)),
+        ];
+        assert!(close_to(
+            super::annualized_irr(&contributions).unwrap(),
+            dec!(0.1)
+        ));
+
+        // The same flows through a portfolio.
+        let mut portfolio = Portfolio::new("acct-1".to_string(), dec!(100));
+        portfolio.deposit(dec!(100), years(1));
+        portfolio.total_equity = dec!(231);
+        let money_weighted = portfolio.money_weighted_return(start, years(2)).unwrap();
+        assert!(close_to(money_weighted, dec!(0.1)));
+
+        assert_eq!(super::annualized_irr(&[(start, dec!(-100))]), None);
+    }
 }
@@ -0,0 +1,252 @@
+//! Wall-clock profile of a backtest run.
+//!
+//! With [`DataSettings::profile`](crate::DataSettings::profile) on, the
+//! engine times every phase of a run, so a slow backtest shows whether the
+//! time goes to loading data, stepping the simulation, the strategy's
+//! callbacks or filling orders. Phases are timed exclusively: a strategy
+//! callback made while orders fill counts toward the callback, not the fills.
+//!
+//! A disabled [`Profiler`] never reads the clock.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::market::Symbol;
+
+/// Where a run's time is attributed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProfilePhase {
+    /// Loading bars and option chains before the first step.
+    DataLoading,
+    /// The engine's own work each simulated day: delivering bars, marking
+    /// the portfolio, option lifecycle, returns and snapshots.
+    Stepping,
+    /// Matching pending orders against the day's bars and booking fills.
+    Fills,
+    OnMarketEvent,
+    OnOrderEvent,
+    OnDayEnd,
+}
+
+impl ProfilePhase {
+    pub const ALL: [ProfilePhase; 6] = [
+        ProfilePhase::DataLoading,
+        ProfilePhase::Stepping,
+        ProfilePhase::Fills,
+        ProfilePhase::OnMarketEvent,
+        ProfilePhase::OnOrderEvent,
+        ProfilePhase::OnDayEnd,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProfilePhase::DataLoading => "data_loading",
+            ProfilePhase::Stepping => "stepping",
+            ProfilePhase::Fills => "fills",
+            ProfilePhase::OnMarketEvent => "on_market_event",
+            ProfilePhase::OnOrderEvent => "on_order_event",
+            ProfilePhase::OnDayEnd => "on_day_end",
+        }
+    }
+}
+
+/// Start of a timed section, from [`Profiler::start`].
+#[derive(Debug, Clone, Copy)]
+pub struct ProfileMark {
+    at: Instant,
+    /// [`Profiler`] time already attributed when the section started.
+    attributed: Duration,
+}
+
+/// Collects the timings behind a [`RunProfile`].
+#[derive(Debug, Clone, Default)]
+pub struct Profiler {
+    enabled: bool,
+    samples: HashMap<ProfilePhase, Vec<Duration>>,
+    by_symbol: HashMap<(ProfilePhase, Symbol), (usize, Duration)>,
+    /// Total time attributed to any phase so far.
+    attributed: Duration,
+}
+
+impl Profiler {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Start timing a section; `None` when disabled.
+    pub fn start(&self) -> Option<ProfileMark> {
+        self.enabled.then(|| ProfileMark {
+            at: Instant::now(),
+            attributed: self.attributed,
+        })
+    }
+
+    /// Attribute the time since `mark` to `phase`, less whatever nested
+    /// sections were attributed in the meantime.
+    pub fn record(&mut self, phase: ProfilePhase, mark: Option<ProfileMark>) {
+        self.record_exclusive(phase, mark);
+    }
+
+    /// [`record`](Self::record), also broken down by `symbol`.
+    pub fn record_symbol(
+        &mut self,
+        phase: ProfilePhase,
+        symbol: &Symbol,
+        mark: Option<ProfileMark>,
+    ) {
+        if let Some(elapsed) = self.record_exclusive(phase, mark) {
+            let entry = self.by_symbol.entry((phase, symbol.clone())).or_default();
+            entry.0 += 1;
+            entry.1 += elapsed;
+        }
+    }
+
+    fn record_exclusive(
+        &mut self,
+        phase: ProfilePhase,
+        mark: Option<ProfileMark>,
+    ) -> Option<Duration> {
+        let mark = mark?;
+        let nested = self.attributed - mark.attributed;
+        let elapsed = mark.at.elapsed().saturating_sub(nested);
+        self.samples.entry(phase).or_default().push(elapsed);
+        self.attributed += elapsed;
+        Some(elapsed)
+    }
+
+    /// Totals, percentiles and per-symbol breakdowns of every phase that
+    /// was timed, in [`ProfilePhase::ALL`] order.
+    pub fn report(&self) -> RunProfile {
+        let phases: Vec<PhaseProfile> = ProfilePhase::ALL
+            .into_iter()
+            .filter_map(|phase| {
+                let mut samples = self.samples.get(&phase)?.clone();
+                samples.sort();
+                let total: Duration = samples.iter().sum();
+                let percentile = |q: f64| {
+                    let rank = ((samples.len() as f64 * q).ceil() as usize).max(1);
+                    samples[rank.min(samples.len()) - 1].as_secs_f64()
+                };
+                let mut by_symbol: Vec<SymbolProfile> = self
+                    .by_symbol
+                    .iter()
+                    .filter(|((symbol_phase, _), _)| *symbol_phase == phase)
+                    .map(|((_, symbol), (calls, total))| SymbolProfile {
+                        symbol: symbol.clone(),
+                        calls: *calls,
+                        total_seconds: total.as_secs_f64(),
+                    })
+                    .collect();
+                by_symbol.sort_by(|a, b| {
+                    b.total_seconds
+                        .total_cmp(&a.total_seconds)
+                        .then_with(|| a.symbol.to_string().cmp(&b.symbol.to_string()))
+                });
+                Some(PhaseProfile {
+                    phase,
+                    calls: samples.len(),
+                    total_seconds: total.as_secs_f64(),
+                    mean_seconds: total.as_secs_f64() / samples.len() as f64,
+                    p95_seconds: percentile(0.95),
+                    max_seconds: percentile(1.0),
+                    by_symbol,
+                })
+            })
+            .collect();
+        RunProfile {
+            total_seconds: phases.iter().map(|phase| phase.total_seconds).sum(),
+            phases,
+        }
+    }
+}
+
+/// Where a run spent its time, stored under `"profile"` in
+/// [`BacktestResult::metadata`](crate::BacktestResult::metadata).
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RunProfile {
+    /// Sum of the phases' totals.
+    pub total_seconds: f64,
+    pub phases: Vec<PhaseProfile>,
+}
+
+impl RunProfile {
+    pub fn phase(&self, phase: ProfilePhase) -> Option<&PhaseProfile> {
+        self.phases.iter().find(|entry| entry.phase == phase)
+    }
+
+    /// The phase that took the longest.
+    pub fn slowest(&self) -> Option<&PhaseProfile> {
+        self.phases
+            .iter()
+            .max_by(|a, b| a.total_seconds.total_cmp(&b.total_seconds))
+    }
+}
+
+/// Timings of one [`ProfilePhase`]; a call is one timed section, such as
+/// one callback or one day's stepping.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseProfile {
+    pub phase: ProfilePhase,
+    pub calls: usize,
+    pub total_seconds: f64,
+    pub mean_seconds: f64,
+    pub p95_seconds: f64,
+    pub max_seconds: f64,
+    /// Per-symbol totals, slowest first, for phases timed per symbol:
+    /// data loading and `on_market_event`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub by_symbol: Vec<SymbolProfile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SymbolProfile {
+    pub symbol: Symbol,
+    pub calls: usize,
+    pub total_seconds: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_sections_are_attributed_once() {
+        let mut profiler = Profiler::new(true);
+        let symbol = Symbol::equity("AAPL");
+        let outer = profiler.start();
+        std::thread::sleep(Duration::from_millis(5));
+        let inner = profiler.start();
+        std::thread::sleep(Duration::from_millis(20));
+        profiler.record_symbol(ProfilePhase::OnMarketEvent, &symbol, inner);
+        profiler.record(ProfilePhase::Fills, outer);
+
+        let profile = profiler.report();
+        let callback = profile.phase(ProfilePhase::OnMarketEvent).unwrap();
+        let fills = profile.phase(ProfilePhase::Fills).unwrap();
+        assert!(callback.total_seconds >= 0.02);
+        assert!(fills.total_seconds >= 0.005 && fills.total_seconds < 0.02);
+        assert_eq!(callback.by_symbol[0].symbol, symbol);
+        assert_eq!(callback.by_symbol[0].calls, 1);
+        assert_eq!(
+            profile.slowest().unwrap().phase,
+            ProfilePhase::OnMarketEvent
+        );
+        assert!(profile.phase(ProfilePhase::OnDayEnd).is_none());
+
+        let mut disabled = Profiler::new(false);
+        let mark = disabled.start();
+        assert!(mark.is_none());
+        disabled.record(ProfilePhase::Fills, mark);
+        assert_eq!(disabled.report(), RunProfile::default());
+    }
+}
@@ -4,8 +4,9 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::market::{MarketEvent, Symbol};
-use crate::orders::{Order, OrderEvent};
+use crate::backtest::QuantityPolicy;
+use crate::market::{MarketEvent, OptionKind, Symbol};
+use crate::orders::{Order, OrderEvent, Side};
 use crate::portfolio::{Portfolio, Position};
 
 /// Strategy context provides access to market data, portfolio, and order management
@@ -14,8 +15,15 @@ pub struct StrategyContext {
     pub current_time: DateTime<Utc>,
     pub portfolio: Portfolio,
     pub market_data: HashMap<Symbol, MarketDataBuffer>,
+    /// The strategy's working orders, oldest first, kept current by the
+    /// backtest and live engines.
     pub pending_orders: Vec<Order>,
     pub strategy_id: String,
+    /// Where the event being handled falls in its trading session, when the
+    /// engine knows. Counts events, never their prices.
+    pub session: Option<SessionPosition>,
+    /// Lot size, minimum and precision order quantities must respect
+    pub quantity_policy: QuantityPolicy,
 }
 
 impl StrategyContext {
@@ -26,6 +34,8 @@ impl StrategyContext {
             market_data: HashMap::new(),
             pending_orders: Vec::new(),
             strategy_id,
+            session: None,
+            quantity_policy: QuantityPolicy::default(),
         }
     }
 
@@ -48,6 +58,88 @@ impl StrategyContext {
     pub fn get_portfolio_value(&self) -> Decimal {
         self.portfolio.total_equity
     }
+
+    /// Whether the portfolio holds a long or short position in `symbol`.
+    pub fn has_position(&self, symbol: &Symbol) -> bool {
+        self.get_position(symbol)
+            .is_some_and(|position| !position.is_flat())
+    }
+
+    /// Market value of the position in `symbol` as a fraction of portfolio
+    /// equity: negative when short, zero when flat or when equity is not
+    /// positive.
+    pub fn weight_of(&self, symbol: &Symbol) -> Decimal {
+        let equity = self.get_portfolio_value();
+        match self.get_position(symbol) {
+            Some(position) if equity > Decimal::ZERO => position.market_value / equity,
+            _ => Decimal::ZERO,
+        }
+    }
+
+    /// Unrealized P&L of the position in `symbol` at its last mark; zero
+    /// when flat.
+    pub fn unrealized_pnl(&self, symbol: &Symbol) -> Decimal {
+        self.get_position(symbol)
+            .map_or(Decimal::ZERO, |position| position.unrealized_pnl)
+    }
+
+    /// Working orders in `symbol`, including partially filled ones.
+    pub fn open_order_count(&self, symbol: &Symbol) -> usize {
+        self.open_orders(symbol).count()
+    }
+
+    /// Working orders in `symbol`, oldest first.
+    pub fn open_orders<'a>(&'a self, symbol: &'a Symbol) -> impl Iterator<Item = &'a Order> {
+        self.pending_orders
+            .iter()
+            .filter(move |order| order.symbol == *symbol && order.is_active())
+    }
+
+    /// How long the current position in `symbol` has been open, as of
+    /// `current_time`; `None` when flat or when the opening fill is unknown.
+    pub fn time_in_position(&self, symbol: &Symbol) -> Option<chrono::Duration> {
+        let position = self.get_position(symbol)?;
+        if position.is_flat() {
+            return None;
+        }
+        Some(self.current_time - position.opened_at?)
+    }
+
+    /// Round `quantity` of `symbol` down to a size the venue accepts, or
+    /// `None` when it is below the minimum order size.
+    pub fn size_order(&self, symbol: &Symbol, quantity: Decimal) -> Option<Decimal> {
+        self.quantity_policy.size(symbol, quantity)
+    }
+}
+
+/// Position of an event within its trading session: how many of the
+/// session's events have been delivered and how many are still to come.
+/// Events sharing a timestamp (one bar per symbol) are delivered together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionPosition {
+    /// Session events up to and including the current timestamp.
+    pub events_seen: usize,
+    /// Session events after the current timestamp.
+    pub events_remaining: usize,
+    /// The current timestamp is the session's first.
+    pub is_session_open: bool,
+    /// The current timestamp is the session's last.
+    pub is_session_close: bool,
+}
+
+impl SessionPosition {
+    /// Position of the `events_at` events sharing a timestamp, which follow
+    /// `events_before` events of a session holding `session_events` in all.
+    pub fn new(events_before: usize, events_at: usize, session_events: usize) -> Self {
+        let events_seen = events_before + events_at;
+        let events_remaining = session_events.saturating_sub(events_seen);
+        Self {
+            events_seen,
+            events_remaining,
+            is_session_open: events_before == 0 && events_at > 0,
+            is_session_close: events_remaining == 0 && events_at > 0,
+        }
+    }
 }
 
 /// Buffer for market data with rolling window
@@ -117,6 +209,66 @@ pub struct CoveredCallOrder {
     pub commission_per_contract: Decimal,
 }
 
+/// Buy or sell option contracts, filled immediately at the engine's mark for
+/// the contract (model price, or chain mid when chain data is loaded), or
+/// against a bid/ask market when the engine has an options fill model.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OptionOrder {
+    pub underlying: Symbol,
+    pub kind: OptionKind,
+    pub side: Side,
+    pub contracts: Decimal,
+    pub strike: Decimal,
+    pub expiration: DateTime<Utc>,
+    /// Shares per contract.
+    pub multiplier: Decimal,
+    /// Volatility used to price and mark the contract. Zero uses the chain
+    /// quote's implied volatility, or failing that the underlying's
+    /// historical volatility.
+    pub implied_volatility: Decimal,
+    pub risk_free_rate: Decimal,
+    pub dividend_yield: Decimal,
+    pub commission_per_contract: Decimal,
+    /// Worst per-share premium to pay (buys) or accept (sells). Orders that
+    /// cannot fill at their limit are canceled.
+    #[serde(default)]
+    pub limit_price: Option<Decimal>,
+}
+
+impl OptionOrder {
+    /// A standard 100-share equity option order with no rates, dividends or
+    /// commission.
+    pub fn new(
+        underlying: Symbol,
+        kind: OptionKind,
+        side: Side,
+        contracts: Decimal,
+        strike: Decimal,
+        expiration: DateTime<Utc>,
+        implied_volatility: Decimal,
+    ) -> Self {
+        Self {
+            underlying,
+            kind,
+            side,
+            contracts,
+            strike,
+            expiration,
+            multiplier: Decimal::from(100),
+            implied_volatility,
+            risk_free_rate: Decimal::ZERO,
+            dividend_yield: Decimal::ZERO,
+            commission_per_contract: Decimal::ZERO,
+            limit_price: None,
+        }
+    }
+
+    pub fn with_limit_price(mut self, limit_price: Decimal) -> Self {
+        self.limit_price = Some(limit_price);
+        self
+    }
+}
+
 /// Strategy action that can be taken
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StrategyAction {
@@ -125,6 +277,7 @@ pub enum StrategyAction {
         order_id: crate::orders::OrderId,
     },
     WriteCoveredCall(CoveredCallOrder),
+    TradeOption(OptionOrder),
     Log {
         level: LogLevel,
         message: String,
@@ -262,6 +415,21 @@ pub trait Strategy: Send + Sync {
     /// Called when strategy is stopped
     fn on_stop(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String>;
 
+    /// Called after a live session is restored from disk, once `initialize`
+    /// has run. Strategies with internal state (indicators, counters) can
+    /// rebuild it from `context.market_data` and the restored portfolio.
+    fn on_restore(&mut self, _context: &StrategyContext) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called once after `on_stop`, when the run is over. Returns custom
+    /// diagnostics (signal counts, average signal strength) to keep with the
+    /// results: backtests store them in `BacktestResult::metadata` under
+    /// `"strategy"`, where the optimizer can also target numeric entries.
+    fn on_finish(&self, _context: &StrategyContext) -> HashMap<String, serde_json::Value> {
+        HashMap::new()
+    }
+
     /// Get strategy configuration
     fn get_config(&self) -> &StrategyConfig;
 
@@ -269,6 +437,52 @@ pub trait Strategy: Send + Sync {
     fn get_metrics(&self) -> StrategyMetrics;
 }
 
+impl<T: Strategy + ?Sized> Strategy for Box<T> {
+    fn initialize(&mut self, config: &StrategyConfig) -> Result<(), String> {
+        (**self).initialize(config)
+    }
+
+    fn on_market_event(
+        &mut self,
+        event: &MarketEvent,
+        context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        (**self).on_market_event(event, context)
+    }
+
+    fn on_order_event(
+        &mut self,
+        event: &OrderEvent,
+        context: &StrategyContext,
+    ) -> Result<Vec<StrategyAction>, String> {
+        (**self).on_order_event(event, context)
+    }
+
+    fn on_day_end(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        (**self).on_day_end(context)
+    }
+
+    fn on_stop(&mut self, context: &StrategyContext) -> Result<Vec<StrategyAction>, String> {
+        (**self).on_stop(context)
+    }
+
+    fn on_restore(&mut self, context: &StrategyContext) -> Result<(), String> {
+        (**self).on_restore(context)
+    }
+
+    fn on_finish(&self, context: &StrategyContext) -> HashMap<String, serde_json::Value> {
+        (**self).on_finish(context)
+    }
+
+    fn get_config(&self) -> &StrategyConfig {
+        (**self).get_config()
+    }
+
+    fn get_metrics(&self) -> StrategyMetrics {
+        (**self).get_metrics()
+    }
+}
+
 /// Event emitted by strategies
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StrategyEvent {
@@ -330,6 +544,9 @@ impl Strategy for BuyAndHoldStrategy {
                 let available_cash = context.get_available_cash();
                 if let Some(price) = context.get_current_price(symbol) {
                     let quantity = available_cash * Decimal::new(95, 2) / price; // Use 95% of cash
+                    let Some(quantity) = context.size_order(symbol, quantity) else {
+                        return Ok(vec![]);
+                    };
 
                     let order = Order::market_order(
                         symbol.clone(),
@@ -363,6 +580,16 @@ impl Strategy for BuyAndHoldStrategy {
         Ok(vec![])
     }
 
+    fn on_restore(&mut self, context: &StrategyContext) -> Result<(), String> {
+        // Don't buy a second time after a restart.
+        self.position_opened = self
+            .config
+            .symbols
+            .first()
+            .is_some_and(|symbol| context.portfolio.get_position(symbol).is_some());
+        Ok(())
+    }
+
     fn get_config(&self) -> &StrategyConfig {
         &self.config
     }
@@ -542,6 +769,7 @@ impl Strategy for CoveredCallStrategy {
 
         match event {
             OrderEvent::OrderFilled { fill, .. }
+            | OrderEvent::OrderPartiallyFilled { fill, .. }
                 if fill.symbol == underlying
                     && fill.side == crate::orders::Side::Buy
                     && !self.call_written =>
@@ -1427,6 +1655,7 @@ mod strategy_tests {
             close: close_price,
             volume: dec!(1000),
             resolution: Resolution::Day,
+            provenance: Default::default(),
         }
     }
 
@@ -1444,6 +1673,121 @@ mod strategy_tests {
         context
     }
 
+    #[test]
+    fn test_position_queries_cover_longs_shorts_and_working_orders() {
+        use crate::orders::{Fill, OrderStatus};
+        use chrono::TimeZone;
+
+        let long = create_test_symbol();
+        let short = Symbol::new("MSFT", "NASDAQ", AssetClass::Equity);
+        let flat = Symbol::new("IBM", "NYSE", AssetClass::Equity);
+        let opened = Utc.with_ymd_and_hms(2024, 3, 1, 14, 30, 0).unwrap();
+        let mut context = StrategyContext::new("test_strategy".to_string(), dec!(100000));
+        context.current_time = opened + chrono::Duration::hours(5);
+
+        let fill = |symbol: &Symbol, side, quantity, price, at| {
+            let mut fill = Fill::new(
+                uuid::Uuid::new_v4(),
+                symbol.clone(),
+                side,
+                quantity,
+                price,
+                Decimal::ZERO,
+                "test_strategy".to_string(),
+            );
+            fill.executed_at = at;
+            fill
+        };
+        context
+            .portfolio
+            .apply_fill(&fill(&long, Side::Buy, dec!(100), dec!(100), opened));
+        // Adding to the long keeps its opening time
+        context.portfolio.apply_fill(&fill(
+            &long,
+            Side::Buy,
+            dec!(50),
+            dec!(100),
+            opened + chrono::Duration::hours(1),
+        ));
+        context.portfolio.apply_fill(&fill(
+            &short,
+            Side::Sell,
+            dec!(50),
+            dec!(200),
+            opened + chrono::Duration::hours(2),
+        ));
+        context.portfolio.update_market_prices(&HashMap::from([
+            (long.clone(), dec!(110)),
+            (short.clone(), dec!(190)),
+        ]));
+
+        let mut partial = Order::limit_order(
+            short.clone(),
+            Side::Buy,
+            dec!(50),
+            dec!(185),
+            "test_strategy".to_string(),
+        );
+        partial.status = OrderStatus::PartiallyFilled;
+        let mut filled = Order::market_order(
+            long.clone(),
+            Side::Buy,
+            dec!(50),
+            "test_strategy".to_string(),
+        );
+        filled.status = OrderStatus::Filled;
+        context.pending_orders = vec![
+            Order::limit_order(
+                long.clone(),
+                Side::Sell,
+                dec!(150),
+                dec!(120),
+                "test_strategy".to_string(),
+            ),
+            partial,
+            filled,
+        ];
+
+        // Equity is 95000 cash, 16500 long and -9500 short
+        assert_eq!(context.get_portfolio_value(), dec!(102000));
+        assert_eq!(context.weight_of(&long), dec!(16500) / dec!(102000));
+        assert_eq!(context.weight_of(&short), dec!(-9500) / dec!(102000));
+        assert_eq!(context.weight_of(&flat), Decimal::ZERO);
+
+        assert_eq!(context.unrealized_pnl(&long), dec!(1500));
+        assert_eq!(context.unrealized_pnl(&short), dec!(500));
+        assert_eq!(context.unrealized_pnl(&flat), Decimal::ZERO);
+
+        assert_eq!(context.open_order_count(&long), 1);
+        assert_eq!(context.open_order_count(&short), 1);
+        assert_eq!(context.open_order_count(&flat), 0);
+
+        assert!(context.has_position(&long));
+        assert!(context.has_position(&short));
+        assert!(!context.has_position(&flat));
+
+        assert_eq!(
+            context.time_in_position(&long),
+            Some(chrono::Duration::hours(5))
+        );
+        assert_eq!(
+            context.time_in_position(&short),
+            Some(chrono::Duration::hours(3))
+        );
+        assert_eq!(context.time_in_position(&flat), None);
+
+        // Covering past flat reopens the position on the long side
+        let reversed = opened + chrono::Duration::hours(4);
+        context
+            .portfolio
+            .apply_fill(&fill(&short, Side::Buy, dec!(60), dec!(190), reversed));
+        assert!(context.get_position(&short).unwrap().is_long());
+        assert_eq!(
+            context.time_in_position(&short),
+            Some(chrono::Duration::hours(1))
+        );
+    }
+
     #[test]
     fn test_buy_and_hold_strategy() {
         let mut strategy = BuyAndHoldStrategy::new();
@@ -1458,6 +1802,44 @@ mod strategy_tests {
         assert_eq!(metrics.strategy_id, "test_bah");
     }
 
+    #[test]
+    fn test_buy_and_hold_sizes_orders_to_the_quantity_policy() {
+        let order_quantity = |symbol: Symbol, price: Decimal| {
+            let mut strategy = BuyAndHoldStrategy::new();
+            let mut config = StrategyConfig::new("bah".to_string(), "Buy and Hold".to_string());
+            config.add_symbol(symbol.clone());
+            strategy.initialize(&config).unwrap();
+
+            let mut bar = create_test_bar(price, Utc::now());
+            bar.symbol = symbol.clone();
+            let mut context = StrategyContext::new("bah".to_string(), dec!(100000));
+            let mut buffer = MarketDataBuffer::new(symbol, 50);
+            buffer.add_event(MarketEvent::Bar(bar.clone()));
+            context.market_data.insert(bar.symbol.clone(), buffer);
+
+            match strategy
+                .on_market_event(&MarketEvent::Bar(bar), &context)
+                .unwrap()
+                .as_slice()
+            {
+                [StrategyAction::PlaceOrder(order)] => Some(order.quantity),
+                [] => None,
+                other => panic!("unexpected actions {other:?}"),
+            }
+        };
+
+        // 95% of the cash buys 12.5 shares; the half share stays in cash
+        assert_eq!(
+            order_quantity(create_test_symbol(), dec!(7600)),
+            Some(dec!(12))
+        );
+        assert_eq!(
+            order_quantity(Symbol::crypto("BTC-USD"), dec!(200000)),
+            Some(dec!(0.475))
+        );
+        assert_eq!(order_quantity(create_test_symbol(), dec!(100000)), None);
+    }
+
     #[test]
     fn test_covered_call_strategy_enters_shares_then_writes_option() {
         let mut strategy = CoveredCallStrategy::new();
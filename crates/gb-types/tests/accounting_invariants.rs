@@ -25,6 +25,9 @@ fn test_fill(
         commission,
         executed_at: Utc::now() + Duration::seconds(offset_seconds),
         strategy_id: "accounting-test".to_string(),
+        spread: None,
+        tags: Vec::new(),
+        metadata: serde_json::Value::Null,
     }
 }
 
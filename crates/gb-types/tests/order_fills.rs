@@ -0,0 +1,42 @@
+//! Property tests for `Order::fill`: any sequence of fills, including empty
+//! and negative ones, keeps the filled and remaining quantities adding up.
+
+use gb_types::{Order, Side, Symbol};
+use proptest::prelude::*;
+use rust_decimal::Decimal;
+
+/// A decimal in `low..=high` hundredths.
+fn cents(low: i64, high: i64) -> impl Strategy<Value = Decimal> {
+    (low..=high).prop_map(|cents| Decimal::new(cents, 2))
+}
+
+proptest! {
+    #[test]
+    fn filled_and_remaining_quantities_always_sum_to_the_order(
+        quantity in cents(0, 100_000),
+        fills in prop::collection::vec((cents(-1_000, 60_000), cents(100, 50_000)), 0..12),
+    ) {
+        let mut order =
+            Order::market_order(Symbol::equity("AAPL"), Side::Buy, quantity, "property".into());
+        for (fill_quantity, price) in fills {
+            order.fill(fill_quantity, price);
+            prop_assert_eq!(
+                order.filled_quantity + order.remaining_quantity,
+                order.quantity,
+                "{:?} no longer adds up",
+                order
+            );
+            prop_assert!(
+                order.filled_quantity >= Decimal::ZERO && order.remaining_quantity >= Decimal::ZERO,
+                "{:?} went negative",
+                order
+            );
+            prop_assert_eq!(
+                order.is_filled(),
+                order.remaining_quantity.is_zero() && !order.filled_quantity.is_zero(),
+                "{:?} has the wrong status",
+                order
+            );
+        }
+    }
+}